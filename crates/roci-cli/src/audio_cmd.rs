@@ -25,13 +25,17 @@ pub async fn handle_transcribe(args: TranscribeArgs) -> Result<(), Box<dyn std::
 }
 
 pub async fn handle_speak(args: SpeakArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let audio = synthesize_speech(&args).await?;
-    write_output_bytes(&args.output, &audio)?;
-
-    if !is_stdio_path(&args.output) {
-        println!("{}", args.output.display());
+    let provider = build_tts_provider(&args.model)?;
+    let request = speech_request(&args);
+    if is_stdio_path(&args.output) {
+        let audio = provider.generate_speech(&request).await?;
+        write_output_bytes(&args.output, &audio)?;
+        return Ok(());
     }
 
+    create_parent_dir(&args.output)?;
+    provider.save_to(&request, &args.output).await?;
+    println!("{}", args.output.display());
     Ok(())
 }
 
@@ -46,19 +50,19 @@ async fn transcribe_audio(
         .await?)
 }
 
-async fn synthesize_speech(args: &SpeakArgs) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let provider = build_tts_provider(&args.model)?;
-    let request = SpeechRequest {
+fn speech_request(args: &SpeakArgs) -> SpeechRequest {
+    SpeechRequest {
         text: args.text.clone(),
         voice: Voice {
             id: args.voice.clone(),
             name: None,
             provider: ProviderKey::OpenAi.as_str().to_string(),
+            description: None,
+            preview_url: None,
         },
         format: args.format.clone().into(),
         speed: args.speed,
-    };
-    Ok(provider.generate_speech(&request).await?)
+    }
 }
 
 fn build_transcription_provider(
@@ -108,15 +112,18 @@ fn write_output_bytes(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn std::erro
         return Ok(());
     }
 
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            fs::create_dir_all(parent)?;
-        }
-    }
+    create_parent_dir(path)?;
     fs::write(path, bytes)?;
     Ok(())
 }
 
+fn create_parent_dir(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
 fn resolve_mime_type(input: &Path, explicit: Option<&str>) -> Result<String, RociError> {
     if let Some(mime_type) = explicit {
         let trimmed = mime_type.trim();
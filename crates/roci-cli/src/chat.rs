@@ -1,36 +1,67 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use async_trait::async_trait;
 use roci::agent::{AgentConfig, AgentRuntime, HumanInteractionCoordinator, QueueDrainMode};
-use roci::agent_loop::{ApprovalPolicy, PreToolUseHookResult, RetryMode, RunStatus};
-use roci::attachments::{Attachment, PromptInput};
+use roci::agent_loop::{
+    ApprovalPolicy, DryRunResults, HookContext, PreToolUseHookResult, RetryMode, RunBudget,
+    RunPlugin, RunRequestDefaults, RunStatus,
+};
+use roci::attachments::{
+    assemble_file_context, Attachment, FileContextError, FileContextMode, FileContextOptions,
+    PromptInput,
+};
 use roci::config::RociConfig;
-use roci::context::ContextBudget;
-use roci::mcp::{merge_mcp_instructions, MCPInstructionMergePolicy};
+use roci::context::{ContextBudget, ContextReport};
+use roci::error::RociError;
+use roci::generation::PresetCatalog;
+use roci::models::PricingTable;
 use roci::resource::CompactionSettings;
 use roci::resource::SkillResourceOptions;
+use roci::roci_providers::remote_catalog::{self, RemoteCatalogCache};
 use roci::session::{
     CreateSessionOptions, LocalSessionStore, SessionConfig, SessionId, SessionModelPreferences,
     SessionResumeState,
 };
-use roci::skills::merge_system_prompt_with_skills;
 use roci::tools::ToolVisibilityPolicy;
+use roci::types::{AgentToolCall, AgentToolResult};
+use tokio_util::sync::CancellationToken;
 
-use crate::cli::{ChatApprovalArg, ChatArgs, ChatRetryModeArg};
+use crate::cli::{
+    ChatApprovalArg, ChatArgs, ChatFileModeArg, ChatRetryModeArg, ChatShowContextArg,
+};
 
+mod artifacts_view;
+mod changes_view;
+pub(crate) mod context_view;
+mod generated_images;
 mod mcp;
 mod resource_prompt;
+mod run_defaults;
 mod runtime_events;
 mod subagents;
+mod tool_progress;
 mod user_input;
+mod workspace;
 
+use artifacts_view::render_artifact_summary;
+use changes_view::render_change_summary;
+use context_view::{
+    context_preview_messages, render_context_report, render_message_lints, write_context_dump,
+};
+use generated_images::{images_from_last_turn, save_generated_images};
 use mcp::build_mcp_runtime_wiring;
 use resource_prompt::{
-    build_resource_system_prompt, expand_chat_prompt, print_resource_diagnostics,
+    expand_chat_prompt, print_resource_diagnostics, print_system_prompt_diagnostics,
+    ChatSystemPrompt,
 };
+use run_defaults::{ChatRunDefaults, ChatRunFlags};
 use runtime_events::RuntimeEventRenderer;
 use subagents::{load_cli_subagent_profiles, print_agent_profiles, select_session_agent_profile};
+use tool_progress::ToolOutputMode;
+use workspace::ChatWorkspace;
 
 pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error>> {
     let ChatArgs {
@@ -40,6 +71,8 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         max_retry_attempts,
         system,
         temperature,
+        preset,
+        workspace,
         skill_path,
         skill_root,
         no_skills,
@@ -54,24 +87,38 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         max_turn_input_tokens,
         max_session_input_tokens,
         max_session_output_tokens,
+        max_cost,
+        max_time,
         no_auto_compaction,
         compaction_reserve_tokens,
         compaction_keep_recent_tokens,
         compaction_model,
         max_tokens,
         approval,
+        max_iterations,
+        dry_run,
         session_root,
         session_id,
         attachments,
+        files,
+        file_mode,
+        file_budget,
         mcp_stdio,
         mcp_streamable_http,
         mcp_websocket,
+        show_context,
+        summarize_changes,
+        artifacts_dir,
+        image_out,
+        quiet_tools,
+        verbose_tools,
         prompt,
     } = args;
 
-    let cwd = std::env::current_dir()?;
+    let workspace = ChatWorkspace::resolve(workspace)?;
+    let workspace_root = workspace.root().to_path_buf();
     if list_agents {
-        let subagent_profiles = load_cli_subagent_profiles(&cwd, agent)?;
+        let subagent_profiles = load_cli_subagent_profiles(&workspace_root, agent)?;
         let mut stdout = std::io::stdout();
         print_agent_profiles(&subagent_profiles.registry, &mut stdout)?;
         return Ok(());
@@ -79,13 +126,40 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
     let config = RociConfig::from_env();
     let registry = Arc::new(roci::default_registry());
 
-    let model: roci::models::LanguageModel = model_arg.parse().map_err(|_| {
-        format!(
-            "Invalid model format: '{}'. Use provider:model (e.g. openai:gpt-4o)",
-            model_arg
-        )
-    })?;
-    let mut candidates = vec![model];
+    let skill_options = SkillResourceOptions {
+        enabled: !no_skills,
+        explicit_paths: workspace.paths(skill_path),
+        extra_roots: workspace.paths(skill_root),
+    };
+
+    let resources = roci::resource::ResourceLoader::new()
+        .with_skill_options(skill_options)
+        .load(&workspace_root)?;
+    print_resource_diagnostics(&resources);
+    for (provider, rules) in &resources.settings.chat_formats {
+        config.set_chat_format_rules(provider, rules.clone());
+    }
+
+    let preset = preset
+        .map(|name| {
+            PresetCatalog::from_settings(&resources.settings)
+                .get(&name)
+                .cloned()
+        })
+        .transpose()?;
+    let run_defaults = ChatRunDefaults::resolve(
+        ChatRunFlags {
+            model: model_arg,
+            temperature,
+            approval,
+            max_iterations,
+            tools: allowed_tools,
+            system,
+            preset,
+        },
+        RunRequestDefaults::from_settings(&resources.settings),
+    )?;
+    let mut candidates = vec![run_defaults.model.clone()];
     for candidate in candidate_models {
         let parsed = candidate.parse().map_err(|_| {
             format!(
@@ -111,37 +185,40 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         ChatRetryModeArg::Persistent => Some(RetryMode::Persistent),
     };
 
-    let skill_options = SkillResourceOptions {
-        enabled: !no_skills,
-        explicit_paths: skill_path,
-        extra_roots: skill_root,
-    };
-
-    let resources = roci::resource::ResourceLoader::new()
-        .with_skill_options(skill_options)
-        .load(&cwd)?;
-    print_resource_diagnostics(&resources);
-
     let prompt = expand_chat_prompt(&prompt, &resources);
-    let prompt_input = build_prompt_input(prompt, &attachments);
-    let resource_system_prompt = build_resource_system_prompt(system, &resources);
-    let skill_system_prompt =
-        merge_system_prompt_with_skills(resource_system_prompt, &resources.skills.skills);
+    let prompt = append_file_context(prompt, &workspace.paths(files), file_mode, file_budget)?;
+    let prompt_input = build_prompt_input(prompt, &workspace.paths(attachments));
     let mcp_runtime =
         build_mcp_runtime_wiring(&mcp_stdio, &mcp_streamable_http, &mcp_websocket).await?;
-    let system_prompt = merge_mcp_instructions(
-        skill_system_prompt.as_deref(),
+    let tool_visibility_policy = tool_visibility_policy_from_args(
+        no_tools || run_defaults.hide_tools,
+        run_defaults.allowed_tools.iter().map(String::as_str),
+        exclude_tools.iter().map(String::as_str),
+    );
+    let tool_overrides =
+        roci_tools::builtin::ToolOverrides::from_settings(&resources.settings.tool_overrides);
+    let tools = roci_tools::builtin::tool_catalog_with_overrides(tool_overrides)?
+        .resolve(&tool_visibility_policy);
+    let chat_prompt = ChatSystemPrompt::new(
+        run_defaults.system.clone(),
+        &resources,
         &mcp_runtime.instructions,
-        MCPInstructionMergePolicy::AppendBlock,
     );
-
-    let mut settings = roci::types::GenerationSettings::default();
-    if let Some(t) = temperature {
-        settings.temperature = Some(t);
-    }
-    if let Some(max) = max_tokens {
-        settings.max_tokens = Some(max);
+    let composed_prompt = chat_prompt.compose(&tools);
+    print_system_prompt_diagnostics(&composed_prompt);
+    if let Some(mode) = &show_context {
+        show_context_report(
+            mode,
+            &composed_prompt.messages(),
+            &prompt_input,
+            &candidates[0],
+            &registry,
+            &config,
+            context_window_override,
+        )?;
     }
+
+    let settings = run_defaults.generation_settings(max_tokens)?;
     let context_budget = build_context_budget(
         context_window_override,
         reserve_output_tokens,
@@ -149,6 +226,12 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         max_session_input_tokens,
         max_session_output_tokens,
     );
+    if max_cost.is_some() {
+        for candidate in &candidates {
+            remote_catalog::refresh_provider_catalog(&config, candidate.provider_name()).await;
+        }
+    }
+    let run_budget = build_run_budget(max_cost, max_time);
     let compaction = {
         let default = CompactionSettings::default();
         CompactionSettings {
@@ -160,15 +243,13 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
     };
 
     let coordinator = Arc::new(HumanInteractionCoordinator::new());
-    let mut renderer = RuntimeEventRenderer::spawn(coordinator.clone());
-    let approval_policy = approval_policy_from_arg(approval);
-    let approval_handler =
-        (approval == ChatApprovalArg::Ask).then(|| renderer.build_approval_handler());
-    let tool_visibility_policy = tool_visibility_policy_from_args(
-        no_tools,
-        allowed_tools.iter().map(String::as_str),
-        exclude_tools.iter().map(String::as_str),
+    let mut renderer = RuntimeEventRenderer::spawn(
+        coordinator.clone(),
+        ToolOutputMode::from_flags(quiet_tools, verbose_tools),
     );
+    let approval_policy = approval_policy_from_arg(run_defaults.approval);
+    let approval_handler =
+        (run_defaults.approval == ChatApprovalArg::Ask).then(|| renderer.build_approval_handler());
     let session = session_root
         .map(|root| {
             let id = match session_id {
@@ -194,7 +275,8 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
     };
     let selected_agent_profile =
         select_session_agent_profile(agent.as_deref(), persisted_agent_profile.as_deref());
-    let subagent_profiles = load_cli_subagent_profiles(&cwd, selected_agent_profile.clone())?;
+    let subagent_profiles =
+        load_cli_subagent_profiles(&workspace_root, selected_agent_profile.clone())?;
     if let Some(state) = session_state.as_mut() {
         let store = LocalSessionStore::new(state.session_config.root.clone());
         persist_explicit_agent_profile(&store, state, agent.as_deref())?;
@@ -207,7 +289,7 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
                     .create(CreateSessionOptions {
                         id: Some(session_config.id.clone()),
                         title: None,
-                        host_cwd: Some(cwd.clone()),
+                        host_cwd: Some(workspace_root.clone()),
                         import_source: None,
                         default_thread_id: None,
                         model_preferences: SessionModelPreferences {
@@ -219,10 +301,14 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
             );
         }
     }
-    let tools = roci_tools::builtin::tool_catalog().resolve(&tool_visibility_policy);
+    let mut plugins: Vec<Arc<dyn RunPlugin>> = vec![Arc::new(DemoHooksPlugin)];
+    if let Some(defaults) = run_defaults.run_plugin() {
+        plugins.push(Arc::new(defaults));
+    }
     let agent_config = AgentConfig {
         candidates,
-        system_prompt,
+        system_prompt: chat_prompt.base,
+        prompt_sections: chat_prompt.sections,
         tools,
         tool_visibility_policy,
         dynamic_tool_providers: mcp_runtime.dynamic_tool_providers,
@@ -235,7 +321,8 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         approval_handler,
         session_id: None,
         session,
-        workspace_root: Some(cwd.clone()),
+        workspace_root: Some(workspace_root.clone()),
+        artifacts_dir,
         sandbox_provider: None,
         steering_mode: QueueDrainMode::All,
         follow_up_mode: QueueDrainMode::All,
@@ -252,16 +339,17 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
         compaction,
         session_before_compact: None,
         session_before_tree: None,
-        pre_tool_use: Some(Arc::new(|call, _cancel| {
-            demo_pre_tool_use_hook(&call.name, &call.id);
-            Box::pin(async { Ok(PreToolUseHookResult::Continue) })
-        })),
-        post_tool_use: Some(Arc::new(|call, result| {
-            demo_post_tool_use_hook(&call.name, &call.id);
-            Box::pin(async move { Ok(result) })
-        })),
+        pre_turn_execution: None,
+        pre_tool_use: None,
+        post_tool_use: None,
+        plugins,
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         context_budget,
+        run_budget,
+        dry_run,
+        dry_run_results: DryRunResults::Simulate,
         chat: Default::default(),
         subagents: subagent_profiles.into_config(!no_subagents),
         human_interaction_coordinator: Some(coordinator.clone()),
@@ -279,8 +367,24 @@ pub async fn handle_chat(args: ChatArgs) -> Result<(), Box<dyn std::error::Error
     renderer.finish().await;
     let result = result?;
     println!();
+    if summarize_changes {
+        print!("{}", render_change_summary(&result.changes));
+    }
+    if !result.artifacts.is_empty() {
+        print!("{}", render_artifact_summary(&result.artifacts));
+    }
+    let images = images_from_last_turn(&result.messages)?;
+    if !images.is_empty() {
+        let dir = image_out.unwrap_or_else(|| workspace_root.clone());
+        for path in save_generated_images(&images, &dir)? {
+            println!("saved image: {}", path.display());
+        }
+    }
 
-    if result.status == RunStatus::Failed {
+    if matches!(
+        result.status,
+        RunStatus::Failed | RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded
+    ) {
         if let Some(err) = result.error {
             return Err(err.into());
         }
@@ -307,6 +411,61 @@ fn build_prompt_input(prompt: String, attachment_paths: &[PathBuf]) -> PromptInp
         .with_attachments(attachment_paths.iter().cloned().map(Attachment::file))
 }
 
+fn append_file_context(
+    prompt: String,
+    paths: &[PathBuf],
+    mode: ChatFileModeArg,
+    max_tokens: usize,
+) -> Result<String, FileContextError> {
+    if paths.is_empty() {
+        return Ok(prompt);
+    }
+    let mode = match mode {
+        ChatFileModeArg::Strict => FileContextMode::Strict,
+        ChatFileModeArg::Summary => FileContextMode::Summary,
+    };
+    let context = assemble_file_context(paths, &FileContextOptions { max_tokens, mode })?;
+    Ok(context.append_to(&prompt))
+}
+
+/// Print the context report for the first request and optionally dump its messages.
+fn show_context_report(
+    mode: &ChatShowContextArg,
+    system_sections: &[roci::types::ModelMessage],
+    prompt_input: &PromptInput,
+    model: &roci::models::LanguageModel,
+    registry: &roci::provider::ProviderRegistry,
+    config: &RociConfig,
+    context_window_override: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let capabilities = registry
+        .create_provider_for(model, config)
+        .ok()
+        .map(|provider| provider.capabilities().clone());
+    let messages = context_preview_messages(system_sections, prompt_input, capabilities.as_ref());
+    let mut report = ContextReport::analyze(&messages, model);
+    if let Some(window) = context_window_override.or_else(|| {
+        capabilities
+            .as_ref()
+            .map(|capabilities| capabilities.context_length)
+    }) {
+        report = report.with_context_window(window);
+    }
+    eprint!("{}", render_context_report(&report));
+    eprint!(
+        "{}",
+        render_message_lints(
+            model.provider_name(),
+            &roci::provider::lint_messages(&messages, model.provider_name())
+        )
+    );
+    if let ChatShowContextArg::Dump(path) = mode {
+        write_context_dump(path, &messages)?;
+        eprintln!("Context messages written to {}", path.display());
+    }
+    Ok(())
+}
+
 fn build_context_budget(
     context_window_override: Option<usize>,
     reserve_output_tokens: Option<usize>,
@@ -333,12 +492,55 @@ fn build_context_budget(
     })
 }
 
-fn demo_pre_tool_use_hook(tool_name: &str, tool_call_id: &str) {
-    eprintln!("[hook] preToolUse called (tool={tool_name}, id={tool_call_id})");
+fn build_run_budget(max_cost: Option<f64>, max_time: Option<Duration>) -> Option<RunBudget> {
+    if max_cost.is_none() && max_time.is_none() {
+        return None;
+    }
+    Some(RunBudget {
+        max_cost_usd: max_cost,
+        max_wall_clock: max_time,
+        pricing: RemoteCatalogCache::global().pricing_table(PricingTable::default()),
+        ..RunBudget::default()
+    })
 }
 
-fn demo_post_tool_use_hook(tool_name: &str, tool_call_id: &str) {
-    eprintln!("[hook] postToolUse called (tool={tool_name}, id={tool_call_id})");
+/// Logs every tool call before and after execution.
+struct DemoHooksPlugin;
+
+#[async_trait]
+impl RunPlugin for DemoHooksPlugin {
+    fn name(&self) -> &str {
+        "demo-hooks"
+    }
+
+    async fn pre_tool_use(
+        &self,
+        call: &AgentToolCall,
+        context: &HookContext,
+        _cancel: CancellationToken,
+    ) -> Result<PreToolUseHookResult, RociError> {
+        eprintln!(
+            "[hook] preToolUse called (tool={}, id={}, turn={}, prior_calls={})",
+            call.name,
+            call.id,
+            context.turn_index,
+            context.invocations_of(&call.name)
+        );
+        Ok(PreToolUseHookResult::Continue)
+    }
+
+    async fn post_tool_use(
+        &self,
+        call: &AgentToolCall,
+        result: AgentToolResult,
+        context: &HookContext,
+    ) -> Result<AgentToolResult, RociError> {
+        eprintln!(
+            "[hook] postToolUse called (tool={}, id={}, turn={}, is_error={})",
+            call.name, call.id, context.turn_index, result.is_error
+        );
+        Ok(result)
+    }
 }
 
 fn approval_policy_from_arg(arg: ChatApprovalArg) -> ApprovalPolicy {
@@ -349,7 +551,7 @@ fn approval_policy_from_arg(arg: ChatApprovalArg) -> ApprovalPolicy {
     }
 }
 
-fn tool_visibility_policy_from_args<'a>(
+pub(crate) fn tool_visibility_policy_from_args<'a>(
     no_tools: bool,
     allowed_tools: impl IntoIterator<Item = &'a str>,
     excluded_tools: impl IntoIterator<Item = &'a str>,
@@ -364,6 +566,7 @@ fn tool_visibility_policy_from_args<'a>(
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
+    use std::time::Duration;
 
     use roci::agent_loop::ApprovalAction;
     use roci::attachments::Attachment;
@@ -375,10 +578,10 @@ mod tests {
     use tempfile::tempdir;
 
     use super::{
-        approval_policy_from_arg, build_context_budget, build_prompt_input,
-        persist_explicit_agent_profile,
+        append_file_context, approval_policy_from_arg, build_context_budget, build_prompt_input,
+        build_run_budget, persist_explicit_agent_profile,
     };
-    use crate::cli::ChatApprovalArg;
+    use crate::cli::{ChatApprovalArg, ChatFileModeArg};
 
     #[test]
     fn copilot_provider_available_in_default_registry() {
@@ -389,6 +592,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn append_file_context_leaves_prompt_alone_without_files() {
+        let prompt = append_file_context("Explain".to_string(), &[], ChatFileModeArg::Strict, 1)
+            .expect("no files should never exceed the budget");
+        assert_eq!(prompt, "Explain");
+    }
+
+    #[test]
+    fn append_file_context_inlines_files_after_prompt() {
+        let dir = tempdir().expect("tempdir should be created");
+        let path = dir.path().join("lib.py");
+        std::fs::write(&path, "print('hi')\n").expect("fixture should be written");
+
+        let prompt = append_file_context(
+            "Explain".to_string(),
+            std::slice::from_ref(&path),
+            ChatFileModeArg::Strict,
+            1_000,
+        )
+        .expect("small file should fit");
+
+        assert_eq!(
+            prompt,
+            format!(
+                "Explain\n\n```python title=\"{}\"\nprint('hi')\n```",
+                path.display()
+            )
+        );
+    }
+
     #[test]
     fn build_prompt_input_preserves_paths_and_count() {
         let prompt = "Describe this report";
@@ -441,6 +674,15 @@ mod tests {
         assert!(budget.max_session_output_tokens.is_none());
     }
 
+    #[test]
+    fn build_run_budget_only_when_a_limit_is_set() {
+        assert!(build_run_budget(None, None).is_none());
+        let budget = build_run_budget(Some(1.5), Some(Duration::from_secs(60))).unwrap();
+        assert_eq!(budget.max_cost_usd, Some(1.5));
+        assert_eq!(budget.max_wall_clock, Some(Duration::from_secs(60)));
+        assert!(budget.max_total_tokens.is_none());
+    }
+
     #[tokio::test]
     async fn explicit_agent_override_persists_without_changing_model_preferences() {
         let root = tempdir().unwrap();
@@ -450,6 +692,7 @@ mod tests {
             selected_model: Some(LanguageModel::Known {
                 provider_key: "openai".into(),
                 model_id: "gpt-5".into(),
+                provider_options: Default::default(),
             }),
             reasoning_effort: Some(ReasoningEffort::High),
             agent_profile: Some("builtin:developer".into()),
@@ -470,6 +713,7 @@ mod tests {
                     selected_model: Some(LanguageModel::Known {
                         provider_key: "google".into(),
                         model_id: "gemini-2.5-pro".into(),
+                        provider_options: Default::default(),
                     }),
                     reasoning_effort: Some(ReasoningEffort::Medium),
                     agent_profile: Some("builtin:planner".into()),
@@ -485,6 +729,7 @@ mod tests {
             Some(LanguageModel::Known {
                 provider_key: "google".into(),
                 model_id: "gemini-2.5-pro".into(),
+                provider_options: Default::default(),
             })
         );
         assert_eq!(
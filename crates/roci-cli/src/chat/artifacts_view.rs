@@ -0,0 +1,81 @@
+use std::fmt::Write as _;
+
+use roci::tools::{ArtifactLocation, RunArtifact};
+
+/// One line per artifact registered during the run: its name, MIME type,
+/// size, and where it lives. Inline artifacts have no path to show.
+pub(crate) fn render_artifact_summary(artifacts: &[RunArtifact]) -> String {
+    let name_width = artifacts
+        .iter()
+        .map(|artifact| artifact.name.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let noun = if artifacts.len() == 1 {
+        "artifact"
+    } else {
+        "artifacts"
+    };
+    let _ = writeln!(out, "{} {noun}:", artifacts.len());
+    for artifact in artifacts {
+        let _ = write!(out, "  {:<name_width$}  {}", artifact.name, artifact.mime);
+        if let Some(size) = artifact.size {
+            let _ = write!(out, "  {size} B");
+        }
+        match &artifact.location {
+            ArtifactLocation::File { path, .. } => {
+                let _ = write!(out, "  {}", path.display());
+            }
+            ArtifactLocation::Inline { .. } => out.push_str("  (in memory)"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::render_artifact_summary;
+    use roci::tools::{ArtifactLocation, RunArtifact};
+
+    fn artifact(name: &str, mime: &str, location: ArtifactLocation, size: u64) -> RunArtifact {
+        RunArtifact {
+            name: name.to_string(),
+            mime: mime.to_string(),
+            description: None,
+            location,
+            size: Some(size),
+            tool_call_id: Some("call-1".to_string()),
+            tool_name: Some("write_file".to_string()),
+        }
+    }
+
+    #[test]
+    fn lists_artifacts_with_type_size_and_location() {
+        let rendered = render_artifact_summary(&[
+            artifact(
+                "report.md",
+                "text/markdown",
+                ArtifactLocation::File {
+                    path: PathBuf::from("out/report.md"),
+                    spilled: false,
+                },
+                12,
+            ),
+            artifact(
+                "chart.png",
+                "image/png",
+                ArtifactLocation::Inline { bytes: vec![0; 4] },
+                4,
+            ),
+        ]);
+
+        assert_eq!(
+            rendered,
+            "2 artifacts:\n  report.md  text/markdown  12 B  out/report.md\n  chart.png  image/png  4 B  (in memory)\n"
+        );
+    }
+}
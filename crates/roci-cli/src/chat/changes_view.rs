@@ -0,0 +1,78 @@
+use std::fmt::Write as _;
+
+use roci::tools::FileChange;
+
+/// Compact `--summarize-changes` listing: one `A`/`M`/`D` line per file with
+/// its line-count delta. Entries inferred from shell commands are flagged
+/// as unverified.
+pub(crate) fn render_change_summary(changes: &[FileChange]) -> String {
+    if changes.is_empty() {
+        return "No file changes recorded.\n".to_string();
+    }
+    let path_width = changes
+        .iter()
+        .map(|change| change.path.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let noun = if changes.len() == 1 { "file" } else { "files" };
+    let _ = writeln!(out, "Changed {} {noun}:", changes.len());
+    for change in changes {
+        let delta = format!("+{} -{}", change.lines_added, change.lines_removed);
+        let _ = write!(
+            out,
+            "  {} {:<path_width$}  {delta}",
+            change.kind.marker(),
+            change.path
+        );
+        if !change.verified {
+            out.push_str("  (unverified)");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_change_summary;
+    use roci::tools::{FileChange, FileChangeKind};
+
+    fn change(
+        path: &str,
+        kind: FileChangeKind,
+        lines: (usize, usize),
+        verified: bool,
+    ) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            kind,
+            old_hash: None,
+            new_hash: None,
+            lines_added: lines.0,
+            lines_removed: lines.1,
+            verified,
+            tools: vec!["write_file".to_string()],
+        }
+    }
+
+    #[test]
+    fn lists_files_with_markers_and_line_deltas() {
+        let rendered = render_change_summary(&[
+            change("src/main.rs", FileChangeKind::Modified, (3, 1), true),
+            change("a.txt", FileChangeKind::Added, (2, 0), true),
+            change("old.log", FileChangeKind::Deleted, (0, 7), false),
+        ]);
+
+        assert_eq!(
+            rendered,
+            "Changed 3 files:\n  M src/main.rs  +3 -1\n  A a.txt        +2 -0\n  D old.log      +0 -7  (unverified)\n"
+        );
+    }
+
+    #[test]
+    fn empty_changes_say_so() {
+        assert_eq!(render_change_summary(&[]), "No file changes recorded.\n");
+    }
+}
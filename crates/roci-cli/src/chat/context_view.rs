@@ -0,0 +1,253 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use roci::attachments::{compile_prompt_input, PromptInput};
+use roci::context::ContextReport;
+use roci::models::ModelCapabilities;
+use roci::provider::MessageLint;
+use roci::types::ModelMessage;
+
+/// Messages of the first request: system prompt sections, then the prompt.
+///
+/// Attachments are compiled when the model's capabilities are known; otherwise
+/// the prompt is reported as plain text.
+pub(crate) fn context_preview_messages(
+    system_sections: &[ModelMessage],
+    prompt_input: &PromptInput,
+    capabilities: Option<&ModelCapabilities>,
+) -> Vec<ModelMessage> {
+    let prompt = capabilities
+        .and_then(|capabilities| compile_prompt_input(prompt_input, capabilities).ok())
+        .map(|compiled| compiled.message)
+        .unwrap_or_else(|| ModelMessage::user(prompt_input.text.clone()));
+    let mut messages = system_sections.to_vec();
+    messages.push(prompt);
+    messages
+}
+
+pub(crate) fn render_context_report(report: &ContextReport) -> String {
+    let largest = report
+        .largest_contributors(ContextReport::LARGEST_CONTRIBUTORS)
+        .into_iter()
+        .filter(|entry| entry.tokens > 0)
+        .collect::<Vec<_>>();
+    let origin_width = report
+        .entries
+        .iter()
+        .map(|entry| entry.origin.to_string().chars().count())
+        .chain(std::iter::once("origin".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Context for {} ({} messages)",
+        report.model,
+        report.entries.len()
+    );
+    let _ = writeln!(
+        out,
+        "    {:>3}  {:<9}  {:<origin_width$}  {:>9}  {:>8}  {:>6}",
+        "#", "role", "origin", "chars", "tokens", "share"
+    );
+    for entry in &report.entries {
+        let flag = if largest.iter().any(|large| large.index == entry.index) {
+            '*'
+        } else {
+            ' '
+        };
+        let _ = writeln!(
+            out,
+            "  {flag} {:>3}  {:<9}  {:<origin_width$}  {:>9}  {:>8}  {:>5.1}%",
+            entry.index,
+            format!("{:?}", entry.role).to_lowercase(),
+            entry.origin.to_string(),
+            entry.chars,
+            entry.tokens,
+            report.share_percent(entry),
+        );
+    }
+
+    let _ = write!(
+        out,
+        "Total: {} chars, ~{} tokens",
+        report.total_chars, report.total_tokens
+    );
+    match (report.context_window, report.window_percent()) {
+        (Some(window), Some(percent)) => {
+            let _ = writeln!(out, " ({percent:.1}% of {window}-token window)");
+        }
+        _ => out.push_str(" (context window unknown)\n"),
+    }
+    if !largest.is_empty() {
+        let labels = largest
+            .iter()
+            .map(|entry| format!("#{} {}", entry.index, entry.origin))
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "Largest (*): {}", labels.join(", "));
+    }
+    out
+}
+
+/// Transcript rule lints for `provider`, one per line.
+pub(crate) fn render_message_lints(provider: &str, lints: &[MessageLint]) -> String {
+    if lints.is_empty() {
+        return format!("Lints ({provider}): none\n");
+    }
+    let mut out = format!("Lints ({provider}):\n");
+    for lint in lints {
+        let status = if lint.kind.is_repaired() {
+            "repaired before send"
+        } else {
+            "sent as is"
+        };
+        let _ = writeln!(out, "  {lint} ({status})");
+    }
+    out
+}
+
+/// Write the analyzed messages, origins included, as pretty JSON.
+pub(crate) fn write_context_dump(
+    path: &Path,
+    messages: &[ModelMessage],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(messages)?;
+    std::fs::write(path, json)
+        .map_err(|err| format!("failed to write context dump {}: {err}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{
+        context_preview_messages, render_context_report, render_message_lints, write_context_dump,
+    };
+    use crate::chat::resource_prompt::ChatSystemPrompt;
+    use roci::attachments::PromptInput;
+    use roci::context::ContextReport;
+    use roci::models::LanguageModel;
+    use roci::resource::ResourceLoader;
+    use roci::types::{MessageOrigin, ModelMessage};
+
+    fn model() -> LanguageModel {
+        "openai:gpt-4o".parse().expect("model should parse")
+    }
+
+    #[test]
+    fn report_attributes_loaded_resources_to_their_origins() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let skill_dir = cwd.join(".roci/skills/sample-skill");
+
+        fs::create_dir_all(home.join(".roci/agent")).expect("agent dir should be created");
+        fs::create_dir_all(&skill_dir).expect("skill dir should be created");
+        fs::write(cwd.join("AGENTS.md"), "project context ".repeat(200))
+            .expect("project context should be written");
+        fs::write(cwd.join(".roci/SYSTEM.md"), "project system")
+            .expect("project system should be written");
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: sample-skill\ndescription: Sample skill\n---\n",
+        )
+        .expect("skill file should be written");
+
+        let resources = ResourceLoader::new()
+            .load_with_home(&cwd, Some(&home))
+            .expect("bundle should load");
+        let sections = ChatSystemPrompt::new(None, &resources, &[])
+            .compose(&[])
+            .messages();
+        let messages =
+            context_preview_messages(&sections, &PromptInput::new("summarize the repo"), None);
+        let report = ContextReport::analyze(&messages, &model()).with_context_window(128_000);
+
+        let origins = report
+            .entries
+            .iter()
+            .map(|entry| entry.origin.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            origins,
+            vec![
+                MessageOrigin::SystemPrompt {
+                    section: "base".to_string()
+                },
+                MessageOrigin::ContextFile {
+                    path: cwd.join("AGENTS.md")
+                },
+                MessageOrigin::Skills,
+                MessageOrigin::User,
+            ]
+        );
+        assert_eq!(
+            report.total_tokens,
+            report
+                .entries
+                .iter()
+                .map(|entry| entry.tokens)
+                .sum::<usize>()
+        );
+        assert_eq!(report.largest_contributors(1)[0].index, 1);
+
+        let rendered = render_context_report(&report);
+        assert!(rendered.contains("Context for openai:gpt-4o (4 messages)"));
+        assert!(rendered.contains(&format!("context:{}", cwd.join("AGENTS.md").display())));
+        assert!(rendered.contains(&format!("~{} tokens", report.total_tokens)));
+        assert!(rendered.contains("of 128000-token window"));
+    }
+
+    #[test]
+    fn report_without_window_says_so() {
+        let messages = vec![ModelMessage::user("hi")];
+        let report = ContextReport::analyze(&messages, &model());
+
+        assert!(render_context_report(&report).contains("(context window unknown)"));
+    }
+
+    #[test]
+    fn lints_render_with_repair_status() {
+        let messages = vec![
+            ModelMessage::user("one"),
+            ModelMessage::user("two"),
+            ModelMessage::assistant("done"),
+        ];
+        let lints = roci::provider::lint_messages(&messages, "anthropic");
+
+        let rendered = render_message_lints("anthropic", &lints);
+
+        assert_eq!(
+            rendered,
+            "Lints (anthropic):\n  message 1: consecutive user messages (repaired before send)\n  \
+             message 2: transcript ends with an assistant message (sent as is)\n"
+        );
+        assert_eq!(
+            render_message_lints("openai", &[]),
+            "Lints (openai): none\n"
+        );
+    }
+
+    #[test]
+    fn dump_writes_messages_with_origins() {
+        let temp = tempdir().expect("temp dir should be created");
+        let path = temp.path().join("context.json");
+        let messages =
+            vec![
+                ModelMessage::system("base").with_origin(MessageOrigin::SystemPrompt {
+                    section: "base".to_string(),
+                }),
+            ];
+
+        write_context_dump(&path, &messages).expect("dump should be written");
+
+        let dumped: Vec<ModelMessage> =
+            serde_json::from_str(&fs::read_to_string(&path).expect("dump should be readable"))
+                .expect("dump should parse");
+        assert_eq!(dumped, messages);
+    }
+}
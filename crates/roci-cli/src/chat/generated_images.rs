@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use roci::error::RociError;
+use roci::types::{ContentPart, GeneratedImage, ModelMessage, Role};
+
+/// Images in the assistant replies after the last user message, so a resumed
+/// session does not re-save images from earlier prompts.
+pub(crate) fn images_from_last_turn(
+    messages: &[ModelMessage],
+) -> Result<Vec<GeneratedImage>, RociError> {
+    let turn_start = messages
+        .iter()
+        .rposition(|message| message.role == Role::User)
+        .map_or(0, |index| index + 1);
+    messages[turn_start..]
+        .iter()
+        .filter(|message| message.role == Role::Assistant)
+        .flat_map(|message| &message.content)
+        .filter_map(|part| match part {
+            ContentPart::Image(image) => Some(GeneratedImage::decode(image)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Write each image to `dir` as `roci-image-<timestamp>-<n>.<ext>` and return
+/// the paths in order.
+pub(crate) fn save_generated_images(
+    images: &[GeneratedImage],
+    dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let path = dir.join(format!(
+                "roci-image-{stamp}-{}.{}",
+                index + 1,
+                image.extension()
+            ));
+            std::fs::write(&path, &image.bytes)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci::types::ImageContent;
+
+    fn assistant_with_image(data: &str) -> ModelMessage {
+        ModelMessage {
+            role: Role::Assistant,
+            content: vec![
+                ContentPart::Text {
+                    text: "here it is".to_string(),
+                },
+                ContentPart::Image(ImageContent {
+                    data: data.to_string(),
+                    mime_type: "image/png".to_string(),
+                }),
+            ],
+            name: None,
+            timestamp: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn only_images_after_the_last_user_message_are_returned() {
+        let messages = vec![
+            ModelMessage::user("first"),
+            assistant_with_image("b2xk"),
+            ModelMessage::user("second"),
+            assistant_with_image("bmV3"),
+        ];
+
+        let images = images_from_last_turn(&messages).unwrap();
+
+        assert_eq!(
+            images,
+            vec![GeneratedImage {
+                bytes: b"new".to_vec(),
+                mime: "image/png".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn saved_images_use_the_mime_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let images = vec![
+            GeneratedImage {
+                bytes: b"png".to_vec(),
+                mime: "image/png".to_string(),
+            },
+            GeneratedImage {
+                bytes: b"jpeg".to_vec(),
+                mime: "image/jpeg".to_string(),
+            },
+        ];
+
+        let paths = save_generated_images(&images, &dir.path().join("out")).unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].to_string_lossy().ends_with("-1.png"));
+        assert!(paths[1].to_string_lossy().ends_with("-2.jpg"));
+        assert_eq!(std::fs::read(&paths[1]).unwrap(), b"jpeg");
+    }
+}
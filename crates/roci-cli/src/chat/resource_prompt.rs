@@ -1,57 +1,51 @@
-use roci::resource::{ContextFileResource, ResourceBundle};
+use std::sync::Arc;
+
+use roci::mcp::MCPInstructionSource;
+use roci::resource::{
+    compose_agent_system_prompt, ComposedSystemPrompt, PromptSection, ResourceBundle,
+    SystemPromptComposer,
+};
+use roci::tools::Tool;
 
 pub(crate) fn expand_chat_prompt(prompt: &str, resources: &ResourceBundle) -> String {
     resources.prompt_templates.expand_input(prompt)
 }
 
-pub(crate) fn build_resource_system_prompt(
-    base: Option<String>,
-    resources: &ResourceBundle,
-) -> Option<String> {
-    let mut sections = Vec::new();
-
-    if let Some(base_prompt) = base.or_else(|| resources.context.system_prompt.clone()) {
-        let trimmed = base_prompt.trim();
-        if !trimmed.is_empty() {
-            sections.push(trimmed.to_string());
-        }
-    }
+/// Inputs of the chat system prompt, split the way [`AgentConfig`] takes them.
+///
+/// [`AgentConfig`]: roci::agent::AgentConfig
+pub(crate) struct ChatSystemPrompt {
+    /// `--system`, falling back to the discovered `SYSTEM.md`.
+    pub(crate) base: Option<String>,
+    /// Resource appends, project context, skills, then MCP server instructions.
+    pub(crate) sections: Vec<PromptSection>,
+}
 
-    for append in &resources.context.append_system_prompts {
-        let trimmed = append.trim();
-        if !trimmed.is_empty() {
-            sections.push(trimmed.to_string());
+impl ChatSystemPrompt {
+    pub(crate) fn new(
+        base: Option<String>,
+        resources: &ResourceBundle,
+        mcp_instructions: &[MCPInstructionSource],
+    ) -> Self {
+        Self {
+            base: base.or_else(|| resources.context.system_prompt.clone()),
+            sections: SystemPromptComposer::new()
+                .with_resources(resources)
+                .with_mcp_instructions(mcp_instructions)
+                .into_sections(),
         }
     }
 
-    if let Some(project_context) = render_project_context_section(&resources.context.context_files)
-    {
-        sections.push(project_context);
+    /// The system prompt exactly as the agent runtime composes it for `tools`.
+    pub(crate) fn compose(&self, tools: &[Arc<dyn Tool>]) -> ComposedSystemPrompt {
+        compose_agent_system_prompt(self.base.clone(), &self.sections, tools)
     }
-
-    if sections.is_empty() {
-        return None;
-    }
-
-    Some(sections.join("\n\n"))
 }
 
-pub(crate) fn render_project_context_section(
-    context_files: &[ContextFileResource],
-) -> Option<String> {
-    if context_files.is_empty() {
-        return None;
-    }
-
-    let mut section = String::from("## Project Context");
-    for file in context_files {
-        section.push_str("\n\n### ");
-        section.push_str(&file.path.display().to_string());
-        section.push('\n');
-        section.push_str(file.content.trim());
+pub(crate) fn print_system_prompt_diagnostics(prompt: &ComposedSystemPrompt) {
+    for diagnostic in &prompt.diagnostics {
+        eprintln!("⚠️  system prompt {diagnostic}");
     }
-
-    Some(section)
 }
 
 pub(crate) fn print_resource_diagnostics(resources: &ResourceBundle) {
@@ -63,6 +57,14 @@ pub(crate) fn print_resource_diagnostics(resources: &ResourceBundle) {
 pub(crate) fn collect_resource_diagnostic_messages(resources: &ResourceBundle) -> Vec<String> {
     let mut messages = Vec::new();
 
+    for diagnostic in &resources.settings.diagnostics {
+        messages.push(format!(
+            "settings {}: {}",
+            diagnostic.path.display(),
+            diagnostic.message
+        ));
+    }
+
     for diagnostic in &resources.context.diagnostics {
         messages.push(format!(
             "resource file {}: {}",
@@ -120,13 +122,146 @@ mod tests {
 
     use tempfile::tempdir;
 
-    use super::{
-        build_resource_system_prompt, collect_resource_diagnostic_messages, expand_chat_prompt,
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use futures::stream::{self, BoxStream, StreamExt};
+    use roci::agent::{AgentConfig, AgentRuntime};
+    use roci::agent_loop::ApprovalPolicy;
+    use roci::config::RociConfig;
+    use roci::error::RociError;
+    use roci::mcp::{MCPInstructionSource, MCPServerMetadata};
+    use roci::models::{LanguageModel, ModelCapabilities};
+    use roci::provider::{
+        ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
     };
+    use roci::tools::{AgentTool, AgentToolParameters, Tool};
+    use roci::types::{Role, StreamEventType, TextStreamDelta};
+
+    use super::{collect_resource_diagnostic_messages, expand_chat_prompt, ChatSystemPrompt};
     use roci::resource::{
         ContextFileResource, ContextPromptResources, PromptTemplateLoader, ResourceBundle,
-        ResourceDiagnostic, ResourceSettings,
+        ResourceDiagnostic, ResourceLoader, ResourceSettings,
     };
+    use roci::skills::merge_system_prompt_with_skills;
+    use roci::types::MessageOrigin;
+
+    /// Records every request and answers with a single text delta.
+    struct RecordingProvider {
+        requests: Arc<Mutex<Vec<ProviderRequest>>>,
+        capabilities: ModelCapabilities,
+    }
+
+    #[async_trait]
+    impl ModelProvider for RecordingProvider {
+        fn provider_name(&self) -> &str {
+            "recording"
+        }
+
+        fn model_id(&self) -> &str {
+            "model"
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            &self.capabilities
+        }
+
+        async fn generate_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            Err(RociError::UnsupportedOperation(
+                "stream-only stub provider".to_string(),
+            ))
+        }
+
+        async fn stream_text(
+            &self,
+            request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            self.requests
+                .lock()
+                .expect("requests lock")
+                .push(request.clone());
+            let delta = |event_type, text: &str| TextStreamDelta {
+                text: text.to_string(),
+                event_type,
+                tool_call: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            };
+            Ok(stream::iter([
+                Ok(delta(StreamEventType::TextDelta, "ok")),
+                Ok(delta(StreamEventType::Done, "")),
+            ])
+            .boxed())
+        }
+    }
+
+    struct RecordingFactory {
+        requests: Arc<Mutex<Vec<ProviderRequest>>>,
+    }
+
+    impl ProviderFactory for RecordingFactory {
+        fn provider_keys(&self) -> &[&str] {
+            &["recording"]
+        }
+
+        fn requires_credentials(&self, _provider_key: &str) -> bool {
+            false
+        }
+
+        fn create(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            _model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            Ok(Box::new(RecordingProvider {
+                requests: self.requests.clone(),
+                capabilities: ModelCapabilities {
+                    supports_tools: true,
+                    ..ModelCapabilities::default()
+                },
+            }))
+        }
+    }
+
+    fn guided_tool(name: &str, guidance: &str) -> Arc<dyn Tool> {
+        Arc::new(
+            AgentTool::new(
+                name,
+                format!("{name} tool"),
+                AgentToolParameters::empty(),
+                |_args, _ctx| async { Ok(serde_json::Value::Null) },
+            )
+            .with_prompt_guidance(guidance),
+        )
+    }
+
+    fn load_resources_with_skill(temp: &std::path::Path) -> ResourceBundle {
+        let home = temp.join("home");
+        let cwd = temp.join("workspace");
+        let skill_dir = cwd.join(".roci/skills/sample-skill");
+
+        fs::create_dir_all(&home).expect("home dir should be created");
+        fs::create_dir_all(&skill_dir).expect("skill dir should be created");
+        fs::write(cwd.join("AGENTS.md"), "project context")
+            .expect("project context should be written");
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: sample-skill\ndescription: Sample skill\n---\n",
+        )
+        .expect("skill file should be written");
+        ResourceLoader::new()
+            .load_with_home(&cwd, Some(&home))
+            .expect("bundle should load")
+    }
 
     #[test]
     fn system_prompt_uses_cli_base_then_appends_append_prompt_and_project_context() {
@@ -149,9 +284,12 @@ mod tests {
             },
             prompt_templates: Default::default(),
             skills: Default::default(),
+            sources: Default::default(),
         };
 
-        let assembled = build_resource_system_prompt(Some("cli system".to_string()), &resources)
+        let assembled = ChatSystemPrompt::new(Some("cli system".to_string()), &resources, &[])
+            .compose(&[])
+            .render()
             .expect("assembled system prompt should exist");
 
         assert!(assembled.starts_with("cli system"));
@@ -161,6 +299,92 @@ mod tests {
         assert!(assembled.contains("claude context"));
     }
 
+    #[test]
+    fn system_prompt_sections_join_to_the_merged_skill_prompt() {
+        let temp = tempdir().expect("temp dir should be created");
+        let resources = load_resources_with_skill(temp.path());
+
+        let chat_prompt = ChatSystemPrompt::new(Some("cli system".to_string()), &resources, &[]);
+        let composed = chat_prompt.compose(&[]);
+
+        let without_skills = ResourceBundle {
+            skills: Default::default(),
+            ..resources.clone()
+        };
+        let expected = merge_system_prompt_with_skills(
+            ChatSystemPrompt::new(Some("cli system".to_string()), &without_skills, &[])
+                .compose(&[])
+                .render(),
+            &resources.skills.skills,
+        );
+        assert_eq!(composed.render(), expected);
+        assert_eq!(
+            composed.messages().last().unwrap().origin(),
+            Some(&MessageOrigin::Skills)
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_preview_matches_the_system_prompt_the_agent_sends() {
+        let temp = tempdir().expect("temp dir should be created");
+        let resources = load_resources_with_skill(temp.path());
+        let mcp_instructions = vec![
+            MCPInstructionSource {
+                server: MCPServerMetadata::with_label("tracker", "Issue Tracker"),
+                instructions: "File issues with labels.".to_string(),
+            },
+            MCPInstructionSource {
+                server: MCPServerMetadata::new("docs"),
+                instructions: "Search docs first.".to_string(),
+            },
+        ];
+        let mut tools = roci_tools::builtin::tool_catalog().resolve(&Default::default());
+        tools.push(guided_tool("zz_guided", "Call this last."));
+        let chat_prompt = ChatSystemPrompt::new(
+            Some("cli system".to_string()),
+            &resources,
+            &mcp_instructions,
+        );
+        let preview = chat_prompt
+            .compose(&tools)
+            .render()
+            .expect("preview prompt");
+        assert!(preview.contains("[server:Issue Tracker]"));
+        assert!(preview.ends_with("## Tool Guidance\n\n### zz_guided\nCall this last."));
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(RecordingFactory {
+            requests: requests.clone(),
+        }));
+        let agent = AgentRuntime::new(
+            Arc::new(registry),
+            RociConfig::new().with_token_store(None),
+            AgentConfig {
+                candidates: vec![LanguageModel::Known {
+                    provider_key: "recording".into(),
+                    model_id: "model".into(),
+                    provider_options: Default::default(),
+                }],
+                system_prompt: chat_prompt.base.clone(),
+                prompt_sections: chat_prompt.sections.clone(),
+                tools,
+                approval_policy: ApprovalPolicy::always(),
+                ..AgentConfig::default()
+            },
+        );
+        agent.prompt("hello").await.expect("run should succeed");
+
+        let requests = requests.lock().expect("requests lock");
+        let sent = requests[0]
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .map(|message| message.text())
+            .collect::<Vec<_>>();
+        assert_eq!(sent, vec![preview]);
+    }
+
     #[test]
     fn system_prompt_falls_back_to_resource_system_when_cli_system_is_missing() {
         let resources = ResourceBundle {
@@ -173,9 +397,12 @@ mod tests {
             },
             prompt_templates: Default::default(),
             skills: Default::default(),
+            sources: Default::default(),
         };
 
-        let assembled = build_resource_system_prompt(None, &resources);
+        let assembled = ChatSystemPrompt::new(None, &resources, &[])
+            .compose(&[])
+            .render();
         assert_eq!(assembled.as_deref(), Some("system from file"));
     }
 
@@ -200,6 +427,7 @@ mod tests {
             context: ContextPromptResources::default(),
             prompt_templates,
             skills: Default::default(),
+            sources: Default::default(),
         };
 
         assert_eq!(
@@ -242,6 +470,7 @@ mod tests {
             },
             prompt_templates,
             skills: Default::default(),
+            sources: Default::default(),
         };
 
         let diagnostics = collect_resource_diagnostic_messages(&resources);
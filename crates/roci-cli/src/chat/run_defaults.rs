@@ -0,0 +1,344 @@
+//! Chat flags merged with workspace run defaults from `settings.json`.
+
+use roci::agent_loop::RunRequestDefaults;
+use roci::error::RociError;
+use roci::generation::Preset;
+use roci::models::LanguageModel;
+use roci::resource::ApprovalPreset;
+use roci::types::GenerationSettings;
+
+use crate::cli::ChatApprovalArg;
+
+const BUILTIN_MODEL: &str = "openai:gpt-4o";
+
+/// Chat flags that fall back to settings defaults when omitted.
+pub(super) struct ChatRunFlags {
+    pub model: Option<String>,
+    pub temperature: Option<f64>,
+    pub approval: Option<ChatApprovalArg>,
+    pub max_iterations: Option<usize>,
+    pub tools: Vec<String>,
+    pub system: Option<String>,
+    /// Preset selected with `--preset`.
+    pub preset: Option<Preset>,
+}
+
+/// Effective chat run options.
+///
+/// Each value comes from the CLI flag, then the selected preset, then project
+/// settings, then global settings, then the built-in default. Project-over-global is resolved by
+/// the settings loader.
+#[derive(Debug)]
+pub(super) struct ChatRunDefaults {
+    pub model: LanguageModel,
+    pub temperature: Option<f64>,
+    pub approval: ChatApprovalArg,
+    pub max_iterations: Option<usize>,
+    pub allowed_tools: Vec<String>,
+    /// Settings set an empty tool allowlist.
+    pub hide_tools: bool,
+    pub system: Option<String>,
+    pub preset: Option<Preset>,
+}
+
+impl ChatRunDefaults {
+    pub(super) fn resolve(
+        flags: ChatRunFlags,
+        defaults: RunRequestDefaults,
+    ) -> Result<Self, String> {
+        let model = match flags.model {
+            Some(arg) => arg.parse().map_err(|_| {
+                format!("Invalid model format: '{arg}'. Use provider:model (e.g. openai:gpt-4o)")
+            })?,
+            None => match defaults.model {
+                Some(model) => model,
+                None => BUILTIN_MODEL
+                    .parse()
+                    .expect("built-in chat model is a valid selector"),
+            },
+        };
+        let approval = flags
+            .approval
+            .or(defaults.approval.map(approval_arg))
+            .unwrap_or(ChatApprovalArg::Ask);
+        let (allowed_tools, hide_tools) = match (flags.tools, defaults.tools) {
+            (flags, _) if !flags.is_empty() => (flags, false),
+            (_, Some(settings)) => {
+                let hide = settings.is_empty();
+                (settings, hide)
+            }
+            (_, None) => (Vec::new(), false),
+        };
+        let preset = flags.preset;
+        let preset_temperature = preset.as_ref().and_then(|p| p.settings.temperature);
+        let preset_system = preset.as_ref().and_then(|p| p.system_prompt.clone());
+        Ok(Self {
+            model,
+            temperature: flags
+                .temperature
+                .or(preset_temperature)
+                .or(defaults.temperature),
+            approval,
+            max_iterations: flags.max_iterations.or(defaults.max_iterations),
+            allowed_tools,
+            hide_tools,
+            system: flags.system.or(preset_system).or(defaults.system_prompt),
+            preset,
+        })
+    }
+
+    /// Generation settings from the preset, with resolved temperature and the
+    /// `--max-tokens` flag taking precedence.
+    pub(super) fn generation_settings(
+        &self,
+        max_tokens: Option<u32>,
+    ) -> Result<GenerationSettings, RociError> {
+        let mut settings = GenerationSettings::default();
+        if let Some(preset) = &self.preset {
+            preset.apply_to_settings(&mut settings)?;
+        }
+        if let Some(temperature) = self.temperature {
+            settings.temperature = Some(temperature);
+        }
+        if let Some(max_tokens) = max_tokens {
+            settings.max_tokens = Some(max_tokens);
+        }
+        Ok(settings)
+    }
+
+    /// Defaults the agent config has no field for, applied to every run.
+    pub(super) fn run_plugin(&self) -> Option<RunRequestDefaults> {
+        self.max_iterations
+            .map(|max_iterations| RunRequestDefaults {
+                max_iterations: Some(max_iterations),
+                ..RunRequestDefaults::default()
+            })
+    }
+}
+
+fn approval_arg(preset: ApprovalPreset) -> ChatApprovalArg {
+    match preset {
+        ApprovalPreset::Ask => ChatApprovalArg::Ask,
+        ApprovalPreset::Always => ChatApprovalArg::Always,
+        ApprovalPreset::Never => ChatApprovalArg::Never,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use roci::resource::{ResourceBundle, ResourceLoader, SkillResourceOptions};
+    use tempfile::tempdir;
+
+    use roci::generation::PresetCatalog;
+
+    use super::*;
+    use crate::chat::tool_visibility_policy_from_args;
+
+    fn flags() -> ChatRunFlags {
+        ChatRunFlags {
+            model: None,
+            temperature: None,
+            approval: None,
+            max_iterations: None,
+            tools: Vec::new(),
+            system: None,
+            preset: None,
+        }
+    }
+
+    fn load(home: &Path, cwd: &Path) -> ResourceBundle {
+        ResourceLoader::new()
+            .with_skill_options(SkillResourceOptions {
+                enabled: false,
+                ..SkillResourceOptions::default()
+            })
+            .load_with_home(cwd, Some(home))
+            .expect("resources should load")
+    }
+
+    fn write_settings(dir: &Path, json: &str) {
+        fs::create_dir_all(dir).expect("settings dir should be created");
+        fs::write(dir.join("settings.json"), json).expect("settings should be written");
+    }
+
+    #[test]
+    fn built_ins_apply_without_flags_or_settings() {
+        let resolved = ChatRunDefaults::resolve(flags(), RunRequestDefaults::default()).unwrap();
+
+        assert_eq!(resolved.model.to_string(), BUILTIN_MODEL);
+        assert_eq!(resolved.approval, ChatApprovalArg::Ask);
+        assert!(resolved.temperature.is_none());
+        assert!(resolved.allowed_tools.is_empty());
+        assert!(!resolved.hide_tools);
+        assert!(resolved.run_plugin().is_none());
+    }
+
+    #[test]
+    fn flags_override_project_settings_which_override_global_settings() {
+        let temp = tempdir().unwrap();
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        write_settings(
+            &home.join(".roci/agent"),
+            r#"{ "defaults": {
+                "model": "openai:gpt-4.1",
+                "temperature": 0.9,
+                "approval": "never",
+                "max_iterations": 10
+            } }"#,
+        );
+        write_settings(
+            &cwd.join(".roci"),
+            r#"{ "defaults": { "model": "anthropic:claude-sonnet-4-5", "max_iterations": 30 } }"#,
+        );
+        let defaults = RunRequestDefaults::from_settings(&load(&home, &cwd).settings);
+
+        let resolved = ChatRunDefaults::resolve(
+            ChatRunFlags {
+                temperature: Some(0.1),
+                ..flags()
+            },
+            defaults,
+        )
+        .unwrap();
+
+        assert_eq!(resolved.model.to_string(), "anthropic:claude-sonnet-4-5");
+        assert_eq!(resolved.temperature, Some(0.1));
+        assert_eq!(resolved.approval, ChatApprovalArg::Never);
+        assert_eq!(resolved.max_iterations, Some(30));
+        assert_eq!(
+            resolved
+                .run_plugin()
+                .and_then(|plugin| plugin.max_iterations),
+            Some(30)
+        );
+    }
+
+    #[test]
+    fn settings_tool_allowlist_filters_builtin_tools() {
+        let temp = tempdir().unwrap();
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        write_settings(
+            &cwd.join(".roci"),
+            r#"{ "defaults": { "tools": ["read_file", "grep"] } }"#,
+        );
+        let defaults = RunRequestDefaults::from_settings(&load(&home, &cwd).settings);
+        let resolved = ChatRunDefaults::resolve(flags(), defaults).unwrap();
+
+        let policy = tool_visibility_policy_from_args(
+            resolved.hide_tools,
+            resolved.allowed_tools.iter().map(String::as_str),
+            std::iter::empty(),
+        );
+        let tools = roci_tools::builtin::tool_catalog().resolve(&policy);
+
+        let mut names = tools
+            .iter()
+            .map(|tool| tool.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["grep", "read_file"]);
+        assert!(roci_tools::builtin::all_tools().len() > names.len());
+    }
+
+    #[test]
+    fn tool_flags_replace_the_settings_allowlist() {
+        let defaults = RunRequestDefaults {
+            tools: Some(Vec::new()),
+            ..RunRequestDefaults::default()
+        };
+
+        let resolved = ChatRunDefaults::resolve(
+            ChatRunFlags {
+                tools: vec!["shell".to_string()],
+                ..flags()
+            },
+            defaults.clone(),
+        )
+        .unwrap();
+        assert_eq!(resolved.allowed_tools, vec!["shell"]);
+        assert!(!resolved.hide_tools);
+
+        let resolved = ChatRunDefaults::resolve(flags(), defaults).unwrap();
+        assert!(resolved.hide_tools);
+    }
+
+    #[test]
+    fn flags_override_preset_values_which_override_settings_defaults() {
+        let temp = tempdir().unwrap();
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        write_settings(
+            &cwd.join(".roci"),
+            r#"{
+                "defaults": { "temperature": 0.9 },
+                "presets": {
+                    "terse": {
+                        "settings": { "temperature": 0.2, "max_tokens": 128, "top_p": 0.5 },
+                        "system_prompt": "Be terse."
+                    }
+                }
+            }"#,
+        );
+        let settings = load(&home, &cwd).settings;
+        let preset = PresetCatalog::from_settings(&settings)
+            .get("terse")
+            .unwrap()
+            .clone();
+        let defaults = RunRequestDefaults::from_settings(&settings);
+
+        let from_preset = ChatRunDefaults::resolve(
+            ChatRunFlags {
+                preset: Some(preset.clone()),
+                ..flags()
+            },
+            defaults.clone(),
+        )
+        .unwrap();
+        assert_eq!(from_preset.temperature, Some(0.2));
+        assert_eq!(from_preset.system.as_deref(), Some("Be terse."));
+        let generation = from_preset.generation_settings(None).unwrap();
+        assert_eq!(generation.max_tokens, Some(128));
+        assert_eq!(generation.top_p, Some(0.5));
+
+        let from_flags = ChatRunDefaults::resolve(
+            ChatRunFlags {
+                temperature: Some(0.0),
+                system: Some("flag".to_string()),
+                preset: Some(preset),
+                ..flags()
+            },
+            defaults,
+        )
+        .unwrap();
+        assert_eq!(from_flags.system.as_deref(), Some("flag"));
+        let generation = from_flags.generation_settings(Some(512)).unwrap();
+        assert_eq!(generation.temperature, Some(0.0));
+        assert_eq!(generation.max_tokens, Some(512));
+        assert_eq!(generation.top_p, Some(0.5));
+    }
+
+    #[test]
+    fn unknown_preset_lists_presets_from_settings() {
+        let temp = tempdir().unwrap();
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        write_settings(
+            &home.join(".roci/agent"),
+            r#"{ "presets": { "creative": {}, "terse": {} } }"#,
+        );
+        let settings = load(&home, &cwd).settings;
+
+        let err = PresetCatalog::from_settings(&settings)
+            .get("verbose")
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("unknown preset 'verbose'; available: creative, terse"));
+    }
+}
@@ -3,6 +3,7 @@ use std::io::{self, IsTerminal, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use roci::agent::{
@@ -11,24 +12,35 @@ use roci::agent::{
     ToolExecutionSnapshot,
 };
 use roci::agent_loop::{
-    AgentEvent, ApprovalDecision, ApprovalHandler, ApprovalRequest, RetryEventKind,
+    AgentEvent, AgentEventEnvelope, AgentEventSink, ApprovalDecision, ApprovalHandler,
+    ApprovalRequest, RetryEventKind,
 };
 use roci::human_interaction::{
     HumanInteractionPayload, HumanInteractionRequest, HumanInteractionResponse,
     HumanInteractionResponsePayload, ToolPermissionDecision, UiElicitationField,
     UiElicitationResponse,
 };
+use roci::tools::diff::ChangePreview;
 use roci::types::{ContentPart, Role};
 use tokio::task::JoinHandle as TaskJoinHandle;
 
 use super::resource_prompt::truncate_preview;
+use super::tool_progress::{StatusLine, ToolOutputMode, ToolProgress, SPINNER_INTERVAL};
 use super::user_input::{default_prompt_fn, handle_prompt_request, PromptFn};
 
 type ApprovalPromptFn = Arc<dyn Fn(ApprovalRequest) -> ApprovalDecision + Send + Sync>;
 
+/// Argument payloads smaller than this stream too fast to be worth a progress line.
+const TOOL_ARGUMENT_PROGRESS_MIN_BYTES: usize = 4 * 1024;
+
 enum TerminalCommand {
     RuntimeEvent(Box<AgentRuntimeEventPayload>),
     HumanInteractionRequest(Box<HumanInteractionRequest>),
+    ToolArgumentsProgress {
+        name: String,
+        partial_args_len: usize,
+    },
+    SystemNotice(String),
     ApprovalRequest {
         request: ApprovalRequest,
         response_tx: tokio::sync::oneshot::Sender<ApprovalDecision>,
@@ -46,11 +58,15 @@ pub(crate) struct RuntimeEventRenderer {
 }
 
 impl RuntimeEventRenderer {
-    pub(crate) fn spawn(coordinator: Arc<HumanInteractionCoordinator>) -> Self {
-        Self::spawn_with_prompt_fns(
+    pub(crate) fn spawn(
+        coordinator: Arc<HumanInteractionCoordinator>,
+        tool_output: ToolOutputMode,
+    ) -> Self {
+        Self::spawn_terminal(
             coordinator,
             default_prompt_fn(),
             default_approval_prompt_fn(),
+            tool_output,
         )
     }
 
@@ -68,34 +84,19 @@ impl RuntimeEventRenderer {
         prompt_fn: PromptFn,
         approval_prompt_fn: ApprovalPromptFn,
     ) -> Self {
-        let (command_tx, command_rx) = mpsc::channel();
-        let shutdown = Arc::new(AtomicBool::new(false));
-        let thread_shutdown = shutdown.clone();
-        let handle = tokio::runtime::Handle::current();
-        let terminal_handle = std::thread::spawn(move || {
-            drive_terminal(
-                command_rx,
-                coordinator,
-                prompt_fn,
-                approval_prompt_fn,
-                handle,
-                thread_shutdown,
-            );
-        });
-
-        Self {
-            command_tx,
-            shutdown,
-            subscription_handle: None,
-            terminal_handle: Some(terminal_handle),
-        }
+        Self::spawn_terminal(
+            coordinator,
+            prompt_fn,
+            approval_prompt_fn,
+            ToolOutputMode::default(),
+        )
     }
 
-    #[cfg(not(test))]
-    fn spawn_with_prompt_fns(
+    fn spawn_terminal(
         coordinator: Arc<HumanInteractionCoordinator>,
         prompt_fn: PromptFn,
         approval_prompt_fn: ApprovalPromptFn,
+        tool_output: ToolOutputMode,
     ) -> Self {
         let (command_tx, command_rx) = mpsc::channel();
         let shutdown = Arc::new(AtomicBool::new(false));
@@ -109,6 +110,7 @@ impl RuntimeEventRenderer {
                 approval_prompt_fn,
                 handle,
                 thread_shutdown,
+                tool_output,
             );
         });
 
@@ -120,13 +122,27 @@ impl RuntimeEventRenderer {
         }
     }
 
-    pub(crate) fn build_agent_sink(&self) -> Arc<dyn Fn(AgentEvent) + Send + Sync> {
+    pub(crate) fn build_agent_sink(&self) -> AgentEventSink {
         let command_tx = self.command_tx.clone();
-        Arc::new(move |event: AgentEvent| {
-            if let AgentEvent::HumanInteractionRequested { request } = event {
+        Arc::new(move |envelope: AgentEventEnvelope| match envelope.event {
+            AgentEvent::HumanInteractionRequested { request } => {
                 let _ =
                     command_tx.send(TerminalCommand::HumanInteractionRequest(Box::new(request)));
             }
+            AgentEvent::ToolCallArgumentsDelta {
+                name,
+                partial_args_len,
+                ..
+            } if partial_args_len >= TOOL_ARGUMENT_PROGRESS_MIN_BYTES => {
+                let _ = command_tx.send(TerminalCommand::ToolArgumentsProgress {
+                    name,
+                    partial_args_len,
+                });
+            }
+            AgentEvent::System { message } => {
+                let _ = command_tx.send(TerminalCommand::SystemNotice(message));
+            }
+            _ => {}
         })
     }
 
@@ -269,10 +285,31 @@ fn drive_terminal(
     approval_prompt_fn: ApprovalPromptFn,
     handle: tokio::runtime::Handle,
     shutdown: Arc<AtomicBool>,
+    tool_output: ToolOutputMode,
 ) {
-    let mut renderer = ChatRenderer::default();
+    let mut renderer = ChatRenderer::new(tool_output, io::stderr().is_terminal());
 
-    while let Ok(command) = command_rx.recv() {
+    loop {
+        let command = match renderer.tool_progress_interval() {
+            Some(interval) => match command_rx.recv_timeout(interval) {
+                Ok(command) => command,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    renderer.tick_tool_progress(&mut io::stderr());
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            },
+            None => match command_rx.recv() {
+                Ok(command) => command,
+                Err(_) => break,
+            },
+        };
+        if !matches!(
+            command,
+            TerminalCommand::RuntimeEvent(_) | TerminalCommand::ToolArgumentsProgress { .. }
+        ) {
+            renderer.clear_tool_status(&mut io::stderr());
+        }
         match command {
             TerminalCommand::RuntimeEvent(payload) => {
                 if renderer.render_payload(*payload) {
@@ -289,6 +326,19 @@ fn drive_terminal(
                     shutdown.clone(),
                 );
             }
+            TerminalCommand::ToolArgumentsProgress {
+                name,
+                partial_args_len,
+            } => {
+                renderer.render_tool_arguments_progress(
+                    &name,
+                    partial_args_len,
+                    &mut std::io::stderr(),
+                );
+            }
+            TerminalCommand::SystemNotice(message) => {
+                eprintln!("\n[roci] {message}");
+            }
             TerminalCommand::ApprovalRequest {
                 request,
                 response_tx,
@@ -380,10 +430,15 @@ fn prompt_for_approval(request: ApprovalRequest) -> ApprovalDecision {
         eprintln!("  reason: {}", truncate_preview(reason, 200));
     }
     if !request.payload.is_null() {
-        eprintln!(
-            "  payload: {}",
-            truncate_preview(&request.payload.to_string(), 400)
-        );
+        let mut payload = request.payload.clone();
+        let changes = payload
+            .as_object_mut()
+            .and_then(|payload| payload.remove("changes"))
+            .unwrap_or_default();
+        eprintln!("  payload: {}", truncate_preview(&payload.to_string(), 400));
+        for line in approval_change_lines(&changes) {
+            eprintln!("{line}");
+        }
     }
     if let Some(update) = request.suggested_policy_change.as_ref() {
         eprintln!(
@@ -426,6 +481,60 @@ fn prompt_for_approval(request: ApprovalRequest) -> ApprovalDecision {
     }
 }
 
+/// Diff lines shown per file in an approval prompt.
+const APPROVAL_DIFF_MAX_LINES: usize = 80;
+
+/// Render the `changes` previews of an approval payload for the terminal.
+fn approval_change_lines(changes: &serde_json::Value) -> Vec<String> {
+    let previews =
+        serde_json::from_value::<Vec<ChangePreview>>(changes.clone()).unwrap_or_default();
+    let mut lines = Vec::new();
+    for preview in previews {
+        match preview {
+            ChangePreview::Text {
+                path,
+                diff,
+                lines_added,
+                lines_removed,
+                truncated,
+            } => {
+                lines.push(format!(
+                    "  change: {path} (+{lines_added} -{lines_removed})"
+                ));
+                let diff_lines = diff.lines().collect::<Vec<_>>();
+                lines.extend(
+                    diff_lines
+                        .iter()
+                        .take(APPROVAL_DIFF_MAX_LINES)
+                        .map(|line| format!("    {line}")),
+                );
+                let hidden = diff_lines.len().saturating_sub(APPROVAL_DIFF_MAX_LINES);
+                if hidden > 0 {
+                    lines.push(format!("    … {hidden} more lines"));
+                } else if truncated {
+                    lines.push("    … diff truncated".to_string());
+                }
+            }
+            ChangePreview::Binary {
+                path,
+                old_size,
+                new_size,
+                ..
+            } => {
+                let size = |size: Option<u64>| {
+                    size.map_or_else(|| "none".to_string(), |size| format!("{size} bytes"))
+                };
+                lines.push(format!(
+                    "  change: {path} (binary, {} -> {})",
+                    size(old_size),
+                    size(new_size)
+                ));
+            }
+        }
+    }
+    lines
+}
+
 fn prompt_for_ui_elicitation(
     request: &roci::human_interaction::UiElicitationRequest,
 ) -> UiElicitationResponse {
@@ -554,9 +663,41 @@ struct ChatRenderer {
     completed_message_ids: HashSet<MessageId>,
     started_tool_call_ids: HashSet<String>,
     completed_tool_call_ids: HashSet<String>,
+    tool_output: ToolOutputMode,
+    tool_progress: ToolProgress,
+    tool_status: StatusLine,
 }
 
 impl ChatRenderer {
+    fn new(tool_output: ToolOutputMode, stderr_is_tty: bool) -> Self {
+        Self {
+            tool_output,
+            tool_progress: ToolProgress::new(stderr_is_tty),
+            ..Self::default()
+        }
+    }
+
+    /// Spinner interval while a quiet-mode status line is animating.
+    fn tool_progress_interval(&self) -> Option<Duration> {
+        self.tool_progress
+            .is_animating()
+            .then_some(SPINNER_INTERVAL)
+    }
+
+    fn tick_tool_progress(&mut self, stderr: &mut impl Write) {
+        let frames = self.tool_progress.tick(Instant::now());
+        self.tool_status.write(frames, stderr);
+    }
+
+    fn clear_tool_status(&mut self, stderr: &mut impl Write) {
+        self.tool_status.clear(stderr);
+    }
+
+    fn finish_tool_batch(&mut self, stderr: &mut impl Write) {
+        let frames = self.tool_progress.finish(Instant::now());
+        self.tool_status.write(frames, stderr);
+    }
+
     fn render_snapshot(&mut self, snapshot: RuntimeSnapshot) {
         let mut stdout = std::io::stdout();
         let mut stderr = std::io::stderr();
@@ -595,10 +736,21 @@ impl ChatRenderer {
         stdout: &mut impl Write,
         stderr: &mut impl Write,
     ) -> bool {
+        if !matches!(
+            payload,
+            AgentRuntimeEventPayload::ToolStarted { .. }
+                | AgentRuntimeEventPayload::ToolUpdated { .. }
+                | AgentRuntimeEventPayload::ToolCompleted { .. }
+        ) {
+            self.tool_status.clear(stderr);
+        }
         match payload {
             AgentRuntimeEventPayload::MessageStarted { message }
             | AgentRuntimeEventPayload::MessageUpdated { message }
             | AgentRuntimeEventPayload::MessageCompleted { message } => {
+                if message.payload.role == Role::Assistant && !message.payload.text().is_empty() {
+                    self.finish_tool_batch(stderr);
+                }
                 self.render_message_snapshot(message, stdout);
             }
             AgentRuntimeEventPayload::ToolStarted { tool } => {
@@ -660,7 +812,7 @@ impl ChatRenderer {
                 }
             }
             AgentRuntimeEventPayload::PlanUpdated { plan } => {
-                let _ = writeln!(stderr, "\n[plan] {}", truncate_preview(&plan.plan, 200));
+                let _ = writeln!(stderr, "\n[plan]\n{}", truncate_preview(&plan.plan, 600));
             }
             AgentRuntimeEventPayload::DiffUpdated { diff } => {
                 let _ = writeln!(stderr, "\n[diff]\n{}", truncate_preview(&diff.diff, 400));
@@ -820,7 +972,10 @@ impl ChatRenderer {
             }
             AgentRuntimeEventPayload::TurnCompleted { .. }
             | AgentRuntimeEventPayload::TurnFailed { .. }
-            | AgentRuntimeEventPayload::TurnCanceled { .. } => return true,
+            | AgentRuntimeEventPayload::TurnCanceled { .. } => {
+                self.finish_tool_batch(stderr);
+                return true;
+            }
             AgentRuntimeEventPayload::TurnQueued { .. }
             | AgentRuntimeEventPayload::TurnStarted { .. } => {}
         }
@@ -868,25 +1023,64 @@ impl ChatRenderer {
     }
 
     fn render_tool_start(&mut self, tool: &ToolExecutionSnapshot, stderr: &mut impl Write) {
-        if self.started_tool_call_ids.insert(tool.tool_call_id.clone()) {
-            let _ = writeln!(stderr, "\n⚡ {} ({})", tool.tool_name, tool.tool_call_id);
+        if !self.started_tool_call_ids.insert(tool.tool_call_id.clone()) {
+            return;
+        }
+        match self.tool_output {
+            ToolOutputMode::Quiet => {
+                let frames =
+                    self.tool_progress
+                        .start(&tool.tool_call_id, &tool.tool_name, Instant::now());
+                self.tool_status.write(frames, stderr);
+            }
+            ToolOutputMode::Normal => {
+                let _ = writeln!(stderr, "\n⚡ {} ({})", tool.tool_name, tool.tool_call_id);
+            }
+            ToolOutputMode::Verbose => {
+                let _ = writeln!(stderr, "\n⚡ {} ({})", tool.tool_name, tool.tool_call_id);
+                let _ = writeln!(stderr, "  args: {}", tool.args);
+            }
         }
     }
 
+    fn render_tool_arguments_progress(
+        &self,
+        name: &str,
+        partial_args_len: usize,
+        stderr: &mut impl Write,
+    ) {
+        if self.tool_output == ToolOutputMode::Quiet {
+            return;
+        }
+        let _ = writeln!(
+            stderr,
+            "  … {name}: writing arguments ({} KB)",
+            partial_args_len / 1024
+        );
+    }
+
     fn render_tool_update(&self, tool: ToolExecutionSnapshot, stderr: &mut impl Write) {
+        if self.tool_output == ToolOutputMode::Quiet {
+            return;
+        }
         let Some(partial_result) = tool.partial_result else {
             return;
         };
-        let preview = if let Some(text) = partial_result.content.iter().find_map(|part| {
-            if let ContentPart::Text { text } = part {
-                Some(text.as_str())
-            } else {
-                None
-            }
-        }) {
-            truncate_preview(text, 80)
+        let text = partial_result
+            .content
+            .iter()
+            .find_map(|part| {
+                if let ContentPart::Text { text } = part {
+                    Some(text.clone())
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_else(|| partial_result.details.to_string());
+        let preview = if self.tool_output == ToolOutputMode::Verbose {
+            text
         } else {
-            truncate_preview(&partial_result.details.to_string(), 80)
+            truncate_preview(&text, 80)
         };
         let _ = writeln!(stderr, "  … {}: {preview}", tool.tool_name);
     }
@@ -898,8 +1092,24 @@ impl ChatRenderer {
         let Some(result) = tool.final_result else {
             return;
         };
+        if self.tool_output == ToolOutputMode::Quiet {
+            let frames = self.tool_progress.complete(
+                &tool.tool_call_id,
+                &tool.tool_name,
+                result.is_error,
+                &tool_result_text(&result.result),
+                Instant::now(),
+            );
+            self.tool_status.write(frames, stderr);
+            self.completed_tool_call_ids.insert(tool.tool_call_id);
+            return;
+        }
         self.completed_tool_call_ids.insert(tool.tool_call_id);
-        let preview = truncate_preview(&result.result.to_string(), 200);
+        let preview = if self.tool_output == ToolOutputMode::Verbose {
+            result.result.to_string()
+        } else {
+            truncate_preview(&result.result.to_string(), 200)
+        };
         if result.is_error {
             let _ = writeln!(stderr, "  ❌ {preview}");
         } else {
@@ -908,6 +1118,14 @@ impl ChatRenderer {
     }
 }
 
+/// Tool result as printed in full: string results unquoted.
+fn tool_result_text(result: &serde_json::Value) -> String {
+    match result {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
 fn format_label(label: Option<&str>) -> String {
     label
         .map(|label| format!(" label={}", truncate_preview(label, 48)))
@@ -934,7 +1152,7 @@ mod tests {
         MessageStatus, SubagentMessageSnapshot, SubagentRuntimeSnapshot, SubagentToolCallSnapshot,
         ThreadId, ToolStatus, TurnId, TurnSnapshot, TurnStatus,
     };
-    use roci::agent_loop::{ApprovalKind, ToolUpdatePayload};
+    use roci::agent_loop::{ApprovalKind, RunId, ToolUpdatePayload};
     use roci::models::LanguageModel;
     use roci::tools::{
         AskUserPrompt, UserInputRequest, UserInputRequestId, UserInputResponse, UserInputResult,
@@ -1010,6 +1228,7 @@ mod tests {
             model: Some(LanguageModel::Known {
                 provider_key: "test".to_string(),
                 model_id: "model".to_string(),
+                provider_options: Default::default(),
             }),
             parent_turn_id: None,
             parent_tool_call_id: Some("parent-call".to_string()),
@@ -1147,6 +1366,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn quiet_chat_renderer_prints_completions_and_full_failures_without_tty() {
+        let thread_id = ThreadId::new();
+        let mut renderer = ChatRenderer::new(ToolOutputMode::Quiet, false);
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        let mut failing_tool = tool_snapshot(thread_id);
+        failing_tool.tool_call_id = "call_2".to_string();
+        failing_tool.tool_name = "shell".to_string();
+        let long_error = "x".repeat(300);
+        for (tool, result) in [
+            (tool_snapshot(thread_id), serde_json::json!({ "ok": true })),
+            (failing_tool, serde_json::json!(long_error)),
+        ] {
+            let mut completed = tool.clone();
+            completed.status = ToolStatus::Completed;
+            completed.final_result = Some(AgentToolResult {
+                tool_call_id: tool.tool_call_id.clone(),
+                is_error: result.is_string(),
+                result,
+            });
+            renderer.render_payload_to(
+                AgentRuntimeEventPayload::ToolStarted { tool },
+                &mut stdout,
+                &mut stderr,
+            );
+            renderer.render_payload_to(
+                AgentRuntimeEventPayload::ToolCompleted { tool: completed },
+                &mut stdout,
+                &mut stderr,
+            );
+        }
+        renderer.render_tool_arguments_progress("write_file", 9 * 1024, &mut stderr);
+
+        let stderr = String::from_utf8(stderr).unwrap();
+        let lines = stderr.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2, "{stderr}");
+        assert!(lines[0].starts_with("✓ search "), "{stderr}");
+        assert!(lines[1].starts_with("✗ shell failed after "), "{stderr}");
+        assert!(lines[1].ends_with(&format!(": {long_error}")), "{stderr}");
+        assert!(renderer.tool_progress_interval().is_none());
+    }
+
+    #[test]
+    fn approval_change_lines_render_diffs_and_binary_summaries() {
+        let changes = serde_json::to_value(vec![
+            ChangePreview::between("notes.txt", Some(b"old\n"), Some(b"new\n")),
+            ChangePreview::between("logo.png", None, Some(b"\0png")),
+        ])
+        .expect("serialize previews");
+
+        assert_eq!(
+            approval_change_lines(&changes),
+            vec![
+                "  change: notes.txt (+1 -1)",
+                "    --- a/notes.txt",
+                "    +++ b/notes.txt",
+                "    @@ -1 +1 @@",
+                "    -old",
+                "    +new",
+                "  change: logo.png (binary, none -> 4 bytes)",
+            ]
+        );
+        assert!(approval_change_lines(&serde_json::Value::Null).is_empty());
+    }
+
+    #[test]
+    fn chat_renderer_reports_tool_argument_progress_in_kilobytes() {
+        let renderer = ChatRenderer::default();
+        let mut stderr = Vec::new();
+
+        renderer.render_tool_arguments_progress("write_file", 9 * 1024 + 10, &mut stderr);
+
+        assert_eq!(
+            String::from_utf8(stderr).expect("utf8 stderr"),
+            "  … write_file: writing arguments (9 KB)\n"
+        );
+    }
+
     #[test]
     fn chat_renderer_renders_subagent_lifecycle_to_stderr() {
         let mut renderer = ChatRenderer::default();
@@ -1318,14 +1617,20 @@ mod tests {
         );
 
         let sink = renderer.build_agent_sink();
-        sink(AgentEvent::MessageStart {
-            message: ModelMessage::assistant("ignore"),
-        });
-        sink(AgentEvent::HumanInteractionRequested {
-            request: roci::human_interaction::HumanInteractionRequest::from_user_input(
-                request.clone(),
-            ),
-        });
+        sink(AgentEventEnvelope::new(
+            RunId::nil(),
+            AgentEvent::MessageStart {
+                message: ModelMessage::assistant("ignore"),
+            },
+        ));
+        sink(AgentEventEnvelope::new(
+            RunId::nil(),
+            AgentEvent::HumanInteractionRequested {
+                request: roci::human_interaction::HumanInteractionRequest::from_user_input(
+                    request.clone(),
+                ),
+            },
+        ));
 
         let response = pending.wait_user_input(Some(100)).await.unwrap();
         assert_eq!(response.request_id, request.request_id);
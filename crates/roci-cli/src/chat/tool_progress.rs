@@ -0,0 +1,382 @@
+//! Grouped tool progress for `--quiet-tools`.
+//!
+//! [`ToolProgress`] turns tool starts and completions into [`ProgressFrame`]s
+//! without printing anything; [`StatusLine`] writes them. On a TTY a batch
+//! of tools shares one status line redrawn in place. Otherwise each
+//! completion gets its own line. Failures are always printed in full.
+//!
+//! A batch runs from the first tool start until [`ToolProgress::finish`],
+//! which the renderer calls when assistant text resumes or the turn ends.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// How the chat renderer shows tool activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ToolOutputMode {
+    /// A line per start, update, and completion, with truncated previews.
+    #[default]
+    Normal,
+    /// One status line per batch; failures in full.
+    Quiet,
+    /// Like `Normal`, plus arguments and untruncated results.
+    Verbose,
+}
+
+impl ToolOutputMode {
+    pub(crate) fn from_flags(quiet: bool, verbose: bool) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, true) => Self::Verbose,
+            (false, false) => Self::Normal,
+        }
+    }
+}
+
+/// Spinner redraw interval while a quiet batch has running tools.
+pub(crate) const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Tools listed on the status line; older ones collapse into `+N more`.
+const STATUS_TOOLS_MAX: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ProgressFrame {
+    /// Redraw the status line in place.
+    Status(String),
+    /// Print a full line, replacing the status line if one is shown.
+    Line(String),
+}
+
+struct TrackedTool {
+    id: String,
+    name: String,
+    started_at: Instant,
+    /// `Some(is_error)` once completed.
+    outcome: Option<bool>,
+}
+
+#[derive(Default)]
+pub(crate) struct ToolProgress {
+    tty: bool,
+    tools: Vec<TrackedTool>,
+    spinner: usize,
+}
+
+impl ToolProgress {
+    pub(crate) fn new(tty: bool) -> Self {
+        Self {
+            tty,
+            tools: Vec::new(),
+            spinner: 0,
+        }
+    }
+
+    /// `true` while the status line needs spinner ticks.
+    pub(crate) fn is_animating(&self) -> bool {
+        self.tty && self.tools.iter().any(|tool| tool.outcome.is_none())
+    }
+
+    pub(crate) fn start(&mut self, id: &str, name: &str, now: Instant) -> Vec<ProgressFrame> {
+        if self.tools.iter().any(|tool| tool.id == id) {
+            return Vec::new();
+        }
+        self.tools.push(TrackedTool {
+            id: id.to_string(),
+            name: name.to_string(),
+            started_at: now,
+            outcome: None,
+        });
+        self.status_frame(now)
+    }
+
+    /// Record a completion. `result` is printed in full when `is_error`.
+    pub(crate) fn complete(
+        &mut self,
+        id: &str,
+        name: &str,
+        is_error: bool,
+        result: &str,
+        now: Instant,
+    ) -> Vec<ProgressFrame> {
+        let index = match self.tools.iter().position(|tool| tool.id == id) {
+            Some(index) => index,
+            None => {
+                self.tools.push(TrackedTool {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    started_at: now,
+                    outcome: None,
+                });
+                self.tools.len() - 1
+            }
+        };
+        let tool = &mut self.tools[index];
+        if tool.outcome.is_some() {
+            return Vec::new();
+        }
+        tool.outcome = Some(is_error);
+        let elapsed = format_elapsed(now.saturating_duration_since(tool.started_at));
+
+        let mut frames = Vec::new();
+        if is_error {
+            frames.push(ProgressFrame::Line(format!(
+                "✗ {} failed after {elapsed}: {result}",
+                tool.name
+            )));
+        } else if !self.tty {
+            frames.push(ProgressFrame::Line(format!("✓ {} {elapsed}", tool.name)));
+        }
+        frames.extend(self.status_frame(now));
+        frames
+    }
+
+    /// Advance the spinner.
+    pub(crate) fn tick(&mut self, now: Instant) -> Vec<ProgressFrame> {
+        if !self.is_animating() {
+            return Vec::new();
+        }
+        self.spinner = (self.spinner + 1) % SPINNER.len();
+        self.status_frame(now)
+    }
+
+    /// Close the batch, leaving its final status as a permanent line on a TTY.
+    pub(crate) fn finish(&mut self, now: Instant) -> Vec<ProgressFrame> {
+        if self.tools.is_empty() {
+            return Vec::new();
+        }
+        let frames = if self.tty {
+            vec![ProgressFrame::Line(self.status_text(now))]
+        } else {
+            Vec::new()
+        };
+        self.tools.clear();
+        self.spinner = 0;
+        frames
+    }
+
+    fn status_frame(&self, now: Instant) -> Vec<ProgressFrame> {
+        if self.tty {
+            vec![ProgressFrame::Status(self.status_text(now))]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn status_text(&self, now: Instant) -> String {
+        let total = self.tools.len();
+        let noun = if total == 1 { "tool" } else { "tools" };
+        let running = self.tools.iter().any(|tool| tool.outcome.is_none());
+        let mut text = if running {
+            format!("⚙ {total} {noun} running… ")
+        } else {
+            format!("⚙ {total} {noun} done: ")
+        };
+
+        let hidden = total.saturating_sub(STATUS_TOOLS_MAX);
+        if hidden > 0 {
+            text.push_str(&format!("+{hidden} more, "));
+        }
+        let shown = self.tools[hidden..]
+            .iter()
+            .map(|tool| match tool.outcome {
+                Some(false) => format!("{} ✓", tool.name),
+                Some(true) => format!("{} ✗", tool.name),
+                None => format!(
+                    "{} {} {}",
+                    tool.name,
+                    SPINNER[self.spinner],
+                    format_elapsed(now.saturating_duration_since(tool.started_at))
+                ),
+            })
+            .collect::<Vec<_>>();
+        text.push_str(&shown.join(", "));
+        text
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:.1}s", elapsed.as_secs_f64())
+}
+
+/// Writes [`ProgressFrame`]s, tracking whether a status line is on screen.
+#[derive(Default)]
+pub(crate) struct StatusLine {
+    shown: bool,
+}
+
+impl StatusLine {
+    pub(crate) fn write(&mut self, frames: Vec<ProgressFrame>, out: &mut impl Write) {
+        for frame in frames {
+            match frame {
+                ProgressFrame::Status(text) => {
+                    let _ = write!(out, "\r\x1b[2K{text}");
+                    self.shown = true;
+                }
+                ProgressFrame::Line(text) => {
+                    if std::mem::take(&mut self.shown) {
+                        let _ = write!(out, "\r\x1b[2K");
+                    }
+                    let _ = writeln!(out, "{text}");
+                }
+            }
+        }
+        let _ = out.flush();
+    }
+
+    /// Erase the status line before unrelated output; the next frame redraws it.
+    pub(crate) fn clear(&mut self, out: &mut impl Write) {
+        if std::mem::take(&mut self.shown) {
+            let _ = write!(out, "\r\x1b[2K");
+            let _ = out.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn tty_batch_redraws_one_status_line() {
+        let t0 = Instant::now();
+        let mut progress = ToolProgress::new(true);
+
+        let mut frames = Vec::new();
+        frames.extend(progress.start("1", "read_file", t0));
+        frames.extend(progress.start("2", "grep", at(t0, 10)));
+        frames.extend(progress.start("3", "shell", at(t0, 20)));
+        frames.extend(progress.complete("1", "read_file", false, "{}", at(t0, 300)));
+        frames.extend(progress.complete("2", "grep", false, "{}", at(t0, 400)));
+        frames.extend(progress.tick(at(t0, 4_220)));
+        frames.extend(progress.complete("3", "shell", false, "{}", at(t0, 5_000)));
+        frames.extend(progress.finish(at(t0, 5_100)));
+
+        assert_eq!(
+            frames,
+            vec![
+                ProgressFrame::Status("⚙ 1 tool running… read_file ⠋ 0.0s".into()),
+                ProgressFrame::Status("⚙ 2 tools running… read_file ⠋ 0.0s, grep ⠋ 0.0s".into()),
+                ProgressFrame::Status(
+                    "⚙ 3 tools running… read_file ⠋ 0.0s, grep ⠋ 0.0s, shell ⠋ 0.0s".into()
+                ),
+                ProgressFrame::Status(
+                    "⚙ 3 tools running… read_file ✓, grep ⠋ 0.3s, shell ⠋ 0.3s".into()
+                ),
+                ProgressFrame::Status(
+                    "⚙ 3 tools running… read_file ✓, grep ✓, shell ⠋ 0.4s".into()
+                ),
+                ProgressFrame::Status(
+                    "⚙ 3 tools running… read_file ✓, grep ✓, shell ⠙ 4.2s".into()
+                ),
+                ProgressFrame::Status("⚙ 3 tools done: read_file ✓, grep ✓, shell ✓".into()),
+                ProgressFrame::Line("⚙ 3 tools done: read_file ✓, grep ✓, shell ✓".into()),
+            ]
+        );
+        assert!(!progress.is_animating());
+        assert!(progress.finish(at(t0, 6_000)).is_empty());
+    }
+
+    #[test]
+    fn failures_print_in_full_then_redraw_status() {
+        let t0 = Instant::now();
+        let mut progress = ToolProgress::new(true);
+        progress.start("1", "shell", t0);
+        progress.start("2", "grep", t0);
+
+        let frames = progress.complete("1", "shell", true, "exit 2: no such file", at(t0, 1_500));
+
+        assert_eq!(
+            frames,
+            vec![
+                ProgressFrame::Line("✗ shell failed after 1.5s: exit 2: no such file".into()),
+                ProgressFrame::Status("⚙ 2 tools running… shell ✗, grep ⠋ 1.5s".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn non_tty_prints_one_line_per_completion() {
+        let t0 = Instant::now();
+        let mut progress = ToolProgress::new(false);
+
+        let mut frames = Vec::new();
+        frames.extend(progress.start("1", "read_file", t0));
+        frames.extend(progress.start("2", "shell", t0));
+        frames.extend(progress.tick(at(t0, 100)));
+        frames.extend(progress.complete("1", "read_file", false, "{}", at(t0, 240)));
+        frames.extend(progress.complete("2", "shell", true, "timed out", at(t0, 2_000)));
+        frames.extend(progress.complete("2", "shell", true, "timed out", at(t0, 2_100)));
+        frames.extend(progress.finish(at(t0, 2_200)));
+
+        assert_eq!(
+            frames,
+            vec![
+                ProgressFrame::Line("✓ read_file 0.2s".into()),
+                ProgressFrame::Line("✗ shell failed after 2.0s: timed out".into()),
+            ]
+        );
+        assert!(!progress.is_animating());
+    }
+
+    #[test]
+    fn long_batches_collapse_older_tools() {
+        let t0 = Instant::now();
+        let mut progress = ToolProgress::new(true);
+        for index in 0..7 {
+            let id = index.to_string();
+            progress.start(&id, &format!("t{index}"), t0);
+            progress.complete(&id, &format!("t{index}"), false, "{}", t0);
+        }
+
+        assert_eq!(
+            progress.finish(t0),
+            vec![ProgressFrame::Line(
+                "⚙ 7 tools done: +2 more, t2 ✓, t3 ✓, t4 ✓, t5 ✓, t6 ✓".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn status_line_clears_before_full_lines() {
+        let mut status = StatusLine::default();
+        let mut out = Vec::new();
+
+        status.write(
+            vec![
+                ProgressFrame::Status("a".into()),
+                ProgressFrame::Status("b".into()),
+                ProgressFrame::Line("done".into()),
+                ProgressFrame::Line("next".into()),
+            ],
+            &mut out,
+        );
+        status.clear(&mut out);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\r\x1b[2Ka\r\x1b[2Kb\r\x1b[2Kdone\nnext\n"
+        );
+    }
+
+    #[test]
+    fn mode_from_flags() {
+        assert_eq!(
+            ToolOutputMode::from_flags(false, false),
+            ToolOutputMode::Normal
+        );
+        assert_eq!(
+            ToolOutputMode::from_flags(true, false),
+            ToolOutputMode::Quiet
+        );
+        assert_eq!(
+            ToolOutputMode::from_flags(false, true),
+            ToolOutputMode::Verbose
+        );
+    }
+}
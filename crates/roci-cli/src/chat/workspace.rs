@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+/// Directory the chat agent works in: `--workspace`, or the process cwd.
+///
+/// Resources, skills, prompt templates, and subagent profiles are discovered
+/// from it, builtin tools resolve paths inside it, and relative `--skill-path`,
+/// `--skill-root`, `--file`, and `--attach` arguments resolve against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChatWorkspace {
+    root: PathBuf,
+}
+
+impl ChatWorkspace {
+    /// Resolve `--workspace` against the process cwd. The directory must exist.
+    pub(crate) fn resolve(workspace: Option<PathBuf>) -> std::io::Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let root = match workspace {
+            Some(path) => cwd.join(path).canonicalize().map_err(|error| {
+                std::io::Error::new(error.kind(), format!("workspace: {error}"))
+            })?,
+            None => cwd,
+        };
+        if !root.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotADirectory,
+                format!("workspace {} is not a directory", root.display()),
+            ));
+        }
+        Ok(Self { root })
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` when absolute, otherwise `path` under the workspace.
+    pub(crate) fn path(&self, path: PathBuf) -> PathBuf {
+        if path.is_absolute() {
+            path
+        } else {
+            self.root.join(path)
+        }
+    }
+
+    pub(crate) fn paths(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths.into_iter().map(|path| self.path(path)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use roci::resource::{ResourceLoader, SkillResourceOptions};
+    use roci::tools::{ToolArguments, ToolExecutionContext};
+    use tempfile::tempdir;
+
+    use super::ChatWorkspace;
+    use crate::chat::append_file_context;
+    use crate::chat::resource_prompt::expand_chat_prompt;
+    use crate::cli::ChatFileModeArg;
+
+    fn write(path: &std::path::Path, contents: &str) {
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+        fs::write(path, contents).expect("write fixture");
+    }
+
+    #[test]
+    fn missing_workspace_is_rejected() {
+        let dir = tempdir().expect("tempdir");
+        let error = ChatWorkspace::resolve(Some(dir.path().join("missing")))
+            .expect_err("missing workspace");
+        assert!(error.to_string().starts_with("workspace: "), "{error}");
+    }
+
+    #[test]
+    fn relative_paths_resolve_inside_the_workspace() {
+        let dir = tempdir().expect("tempdir");
+        let workspace = ChatWorkspace::resolve(Some(dir.path().to_path_buf())).expect("workspace");
+        let root = workspace.root().to_path_buf();
+
+        assert_ne!(root, std::env::current_dir().expect("cwd"));
+        assert_eq!(workspace.path("notes.md".into()), root.join("notes.md"));
+        assert_eq!(
+            workspace.paths(vec!["/etc/hosts".into(), "a/b".into()]),
+            vec![std::path::PathBuf::from("/etc/hosts"), root.join("a/b")]
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_assembly_resolves_resources_templates_and_tools_in_the_workspace() {
+        let dir = tempdir().expect("tempdir");
+        let home = tempdir().expect("home tempdir");
+        let root = dir.path();
+        write(&root.join("AGENTS.md"), "workspace context");
+        write(&root.join(".roci/prompts/plan.md"), "plan for $1");
+        write(
+            &root.join("extra/review/SKILL.md"),
+            "---\nname: review\ndescription: Reviews changes\n---\nReview carefully.\n",
+        );
+        write(&root.join("src/lib.rs"), "pub fn answer() -> u32 { 42 }\n");
+        let workspace = ChatWorkspace::resolve(Some(root.to_path_buf())).expect("workspace");
+
+        let resources = ResourceLoader::new()
+            .with_skill_options(SkillResourceOptions {
+                enabled: true,
+                explicit_paths: workspace.paths(vec!["extra/review".into()]),
+                extra_roots: Vec::new(),
+            })
+            .load_with_home(workspace.root(), Some(home.path()))
+            .expect("load resources");
+        assert!(resources
+            .context
+            .context_files
+            .iter()
+            .any(|file| file.path.starts_with(workspace.root())
+                && file.content == "workspace context"));
+        assert_eq!(
+            expand_chat_prompt("/plan tests", &resources),
+            "plan for tests"
+        );
+        assert!(resources
+            .skills
+            .skills
+            .iter()
+            .any(|skill| skill.name == "review" && skill.file_path.starts_with(workspace.root())));
+
+        let prompt = append_file_context(
+            "Explain".to_string(),
+            &workspace.paths(vec!["src/lib.rs".into()]),
+            ChatFileModeArg::Strict,
+            1_000,
+        )
+        .expect("file context");
+        assert!(prompt.contains("pub fn answer()"), "{prompt}");
+
+        let ctx = ToolExecutionContext {
+            workspace_root: Some(workspace.root().to_path_buf()),
+            ..ToolExecutionContext::default()
+        };
+        let output = roci_tools::builtin::read_file_tool()
+            .execute(
+                &ToolArguments::new(serde_json::json!({ "path": "src/lib.rs" })),
+                &ctx,
+            )
+            .await
+            .expect("read_file");
+        assert!(output.to_string().contains("pub fn answer()"), "{output}");
+    }
+}
@@ -1,35 +1,68 @@
-//! CLI auth command handlers for login, status, and logout.
+//! CLI auth command handlers for login, completion, status, and logout.
 
+use std::path::Path;
 use std::sync::Arc;
 
-use roci::auth::service::{AuthPollResult, AuthStep};
-use roci::auth::store::FileTokenStore;
+use roci::auth::pending::PendingLoginStore;
+use roci::auth::refresh::RefreshStatusStore;
+use roci::auth::service::{AuthPollResult, AuthService, AuthStep};
+use roci::auth::store::{FileTokenStore, TokenStoreConfig};
+use roci::auth::token::Token;
+
+use super::{CompleteArgs, LoginArgs};
 
 /// Handle `roci-agent auth login <provider>`.
-pub async fn handle_login(provider: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let store = Arc::new(FileTokenStore::new_default());
-    let svc = roci::default_auth_service(store);
+pub async fn handle_login(
+    args: LoginArgs,
+    store_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = args.provider.as_str();
+    let config = store_config(store_path);
+    let svc = auth_service(&config);
 
     match svc.start_login(provider).await? {
-        AuthStep::Imported { .. } => {
+        AuthStep::Imported { token } => {
+            clear_refresh_status(&config, svc.store_key(provider)?);
             println!("Imported existing credentials for {provider}");
+            if args.print_token_env {
+                print_token_env(svc.store_key(provider)?, &token);
+            }
         }
         AuthStep::DeviceCode {
             verification_url,
             user_code,
             interval,
+            expires_at,
             session,
-            ..
         } => {
             println!("Visit: {verification_url}");
             println!("Enter code: {user_code}");
+
+            if args.no_poll {
+                svc.defer_device_code(&PendingLoginStore::new(config), provider, &session)?;
+                let store_flag = store_path
+                    .map(|path| format!(" --store-path {}", path.display()))
+                    .unwrap_or_default();
+                println!(
+                    "Code expires {}. After authorizing, finish with:",
+                    expires_at.format("%Y-%m-%d %H:%M")
+                );
+                println!("  roci-agent auth{store_flag} complete {provider} --code {user_code}");
+                println!("or run `roci-agent auth{store_flag} status`.");
+                return Ok(());
+            }
+
             println!("Waiting for authorization...");
 
             loop {
                 tokio::time::sleep(interval).await;
                 match svc.poll_device_code(provider, &session).await? {
-                    AuthPollResult::Authorized { .. } => {
+                    AuthPollResult::Authorized { token } => {
+                        clear_refresh_status(&config, svc.store_key(provider)?);
                         println!("{provider} login successful!");
+                        if args.print_token_env {
+                            print_token_env(svc.store_key(provider)?, &token);
+                        }
                         return Ok(());
                     }
                     AuthPollResult::Pending => continue,
@@ -68,26 +101,96 @@ pub async fn handle_login(provider: &str) -> Result<(), Box<dyn std::error::Erro
                 std::process::exit(1);
             }
 
-            svc.complete_pkce_with_session(provider, response, &state, Some(&session_data))
+            let token = svc
+                .complete_pkce_with_session(provider, response, &state, Some(&session_data))
                 .await?;
+            clear_refresh_status(&config, svc.store_key(provider)?);
             println!("{provider} login successful!");
+            if args.print_token_env {
+                print_token_env(svc.store_key(provider)?, &token);
+            }
         }
     }
 
     Ok(())
 }
 
+/// Handle `roci-agent auth complete <provider>`.
+pub async fn handle_complete(
+    args: CompleteArgs,
+    store_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = args.provider.as_str();
+    let config = store_config(store_path);
+    let svc = auth_service(&config);
+    let pending = PendingLoginStore::new(config.clone());
+
+    match svc
+        .resume_device_code(&pending, provider, args.code.as_deref())
+        .await?
+    {
+        None => Err(format!(
+            "no pending {provider} login; start one with `roci-agent auth login {provider} --no-poll`"
+        )
+        .into()),
+        Some(AuthPollResult::Authorized { token }) => {
+            clear_refresh_status(&config, svc.store_key(provider)?);
+            println!("{provider} login successful!");
+            if args.print_token_env {
+                print_token_env(svc.store_key(provider)?, &token);
+            }
+            Ok(())
+        }
+        Some(AuthPollResult::Pending | AuthPollResult::SlowDown { .. }) => Err(
+            "authorization still pending; approve the code, then run this command again".into(),
+        ),
+        Some(AuthPollResult::Denied) => Err("authorization denied".into()),
+        Some(AuthPollResult::Expired) => Err("device code expired, please log in again".into()),
+    }
+}
+
 /// Handle `roci-agent auth status`.
-pub async fn handle_status() -> Result<(), Box<dyn std::error::Error>> {
-    let store = Arc::new(FileTokenStore::new_default());
-    let svc = roci::default_auth_service(store);
+pub async fn handle_status(store_path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = store_config(store_path);
+    let svc = auth_service(&config);
+    let pending = PendingLoginStore::new(config.clone());
+    let refresh_statuses = RefreshStatusStore::new(config.clone());
+
+    // Finish any `--no-poll` logins the user has since authorized.
+    let store_keys: Vec<String> = svc
+        .all_statuses()
+        .into_iter()
+        .map(|(_, key, _)| key.to_string())
+        .collect();
+    for key in store_keys {
+        match svc.resume_device_code(&pending, &key, None).await {
+            Ok(Some(AuthPollResult::Authorized { .. })) => {
+                clear_refresh_status(&config, &key);
+                println!("Completed pending {key} login");
+            }
+            Ok(Some(AuthPollResult::Denied)) => println!("Pending {key} login was denied"),
+            Ok(Some(AuthPollResult::Expired)) => println!("Pending {key} login expired"),
+            Ok(_) => {}
+            Err(e) => println!("Pending {key} login: Error: {e}"),
+        }
+    }
 
     println!("Authentication Status\n");
 
-    for (name, _key, result) in svc.all_statuses() {
+    for (name, key, result) in svc.all_statuses() {
         match result {
             Ok(Some(token)) => {
-                let status = if let Some(expires) = token.expires_at {
+                let relogin = refresh_statuses
+                    .load(key)
+                    .ok()
+                    .flatten()
+                    .filter(|status| status.needs_relogin);
+                let status = if let Some(relogin) = relogin {
+                    format!(
+                        "Needs re-login (background refresh failed: {})",
+                        relogin.last_error.as_deref().unwrap_or("unknown error")
+                    )
+                } else if let Some(expires) = token.expires_at {
                     if expires > chrono::Utc::now() {
                         format!("Logged in (expires {})", expires.format("%Y-%m-%d %H:%M"))
                     } else {
@@ -98,7 +201,15 @@ pub async fn handle_status() -> Result<(), Box<dyn std::error::Error>> {
                 };
                 println!("  {name}: {status}");
             }
-            Ok(None) => println!("  {name}: Not logged in"),
+            Ok(None) => match pending.load(key) {
+                Ok(Some(session)) => println!(
+                    "  {name}: Login pending (enter code {} at {}, expires {})",
+                    session.user_code,
+                    session.verification_url,
+                    session.expires_at.format("%Y-%m-%d %H:%M")
+                ),
+                _ => println!("  {name}: Not logged in"),
+            },
             Err(e) => println!("  {name}: Error: {e}"),
         }
     }
@@ -120,11 +231,52 @@ pub async fn handle_status() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Handle `roci-agent auth logout <provider>`.
-pub async fn handle_logout(provider: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let store = Arc::new(FileTokenStore::new_default());
-    let svc = roci::default_auth_service(store);
+pub async fn handle_logout(
+    provider: &str,
+    store_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = store_config(store_path);
+    let svc = auth_service(&config);
 
     svc.logout(provider)?;
+    if let Ok(key) = svc.store_key(provider) {
+        clear_refresh_status(&config, key);
+    }
     println!("Logged out from {provider}");
     Ok(())
 }
+
+fn store_config(store_path: Option<&Path>) -> TokenStoreConfig {
+    TokenStoreConfig::new(
+        store_path
+            .map(Path::to_path_buf)
+            .unwrap_or_else(TokenStoreConfig::default_dir),
+    )
+}
+
+fn auth_service(config: &TokenStoreConfig) -> AuthService {
+    roci::default_auth_service(Arc::new(FileTokenStore::new(config.clone())))
+}
+
+/// Forget background refresh failures once the user has logged in again.
+fn clear_refresh_status(config: &TokenStoreConfig, store_key: &str) {
+    if let Err(e) = RefreshStatusStore::new(config.clone()).clear(store_key) {
+        eprintln!("Warning: could not reset {store_key} refresh status: {e}");
+    }
+}
+
+/// Env var that supplies the token a provider otherwise reads from the store.
+fn token_env_var(store_key: &str) -> Option<&'static str> {
+    match store_key {
+        "openai-codex" => Some("OPENAI_CODEX_TOKEN"),
+        "claude-code" => Some("ANTHROPIC_API_KEY"),
+        _ => None,
+    }
+}
+
+fn print_token_env(store_key: &str, token: &Token) {
+    match token_env_var(store_key) {
+        Some(var) => println!("export {var}='{}'", token.access_token),
+        None => eprintln!("{store_key} has no token env var; it reads the token store"),
+    }
+}
@@ -3,6 +3,7 @@
 pub mod auth;
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Parser, Subcommand, ValueEnum};
 
@@ -24,8 +25,19 @@ pub enum Commands {
     Audio(AudioArgs),
     /// Chat with an AI model
     Chat(ChatArgs),
+    /// Run one prompt against several models and compare the answers
+    Compare(CompareArgs),
+    /// Import a Claude Code or Codex session into the session store
+    Import(ImportArgs),
+    /// Replay one iteration of a recorded run transcript
+    Inspect(InspectArgs),
     /// Inspect available models
     Models(ModelsArgs),
+    /// Redact secrets and home paths from a saved JSONL transcript
+    Redact(RedactArgs),
+    /// Serve an OpenAI-compatible chat completions endpoint
+    #[cfg(feature = "serve")]
+    Serve(ServeArgs),
     /// Manage durable agent sessions
     Session(SessionArgs),
     /// Manage installed skills
@@ -67,16 +79,22 @@ pub enum ToolContractsSmokeCaseArg {
 /// Arguments for the `auth` subcommand group.
 #[derive(Parser, Debug)]
 pub struct AuthArgs {
+    /// Token store directory (default: ~/.roci)
+    #[arg(long, value_name = "DIR", global = true)]
+    pub store_path: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: AuthCommands,
 }
 
-/// Auth subcommands for login, status, and logout.
+/// Auth subcommands for login, status, completion, and logout.
 #[derive(Subcommand, Debug)]
 pub enum AuthCommands {
     /// Login to a provider
     Login(LoginArgs),
-    /// Show authentication status
+    /// Finish a login started with `--no-poll`
+    Complete(CompleteArgs),
+    /// Show authentication status (finishes pending `--no-poll` logins)
     Status,
     /// Logout from a provider
     Logout(LogoutArgs),
@@ -87,6 +105,29 @@ pub enum AuthCommands {
 pub struct LoginArgs {
     /// Provider to login to (copilot, codex, claude)
     pub provider: String,
+
+    /// Print the device code and exit instead of waiting for authorization
+    #[arg(long)]
+    pub no_poll: bool,
+
+    /// After login, print an `export` line for the provider's token env var
+    #[arg(long)]
+    pub print_token_env: bool,
+}
+
+/// Arguments for `roci-agent auth complete`.
+#[derive(Parser, Debug)]
+pub struct CompleteArgs {
+    /// Provider whose pending login to finish (copilot, codex)
+    pub provider: String,
+
+    /// User code shown by `auth login --no-poll`, checked against the pending login
+    #[arg(long)]
+    pub code: Option<String>,
+
+    /// After login, print an `export` line for the provider's token env var
+    #[arg(long)]
+    pub print_token_env: bool,
 }
 
 /// Arguments for `roci-agent auth logout`.
@@ -207,12 +248,53 @@ fn parse_positive_usize(value: &str) -> Result<usize, String> {
     }
 }
 
+fn parse_max_cost(value: &str) -> Result<f64, String> {
+    let cost = value
+        .trim_start_matches('$')
+        .parse::<f64>()
+        .map_err(|_| "max-cost must be a positive number of USD".to_string())?;
+    if cost.is_finite() && cost > 0.0 {
+        Ok(cost)
+    } else {
+        Err("max-cost must be a positive number of USD".to_string())
+    }
+}
+
+fn parse_show_context(value: &str) -> Result<ChatShowContextArg, String> {
+    if value == "table" {
+        return Ok(ChatShowContextArg::Table);
+    }
+    match value.strip_prefix("dump:") {
+        Some(path) if !path.is_empty() => Ok(ChatShowContextArg::Dump(PathBuf::from(path))),
+        _ => Err("show-context must be empty, `table`, or `dump:<path>`".to_string()),
+    }
+}
+
+fn parse_max_time(value: &str) -> Result<Duration, String> {
+    let error = || "max-time must be a positive duration like 90, 90s, 5m, or 1h".to_string();
+    let (amount, unit_secs) = match value.char_indices().last() {
+        Some((index, 's')) => (&value[..index], 1),
+        Some((index, 'm')) => (&value[..index], 60),
+        Some((index, 'h')) => (&value[..index], 3_600),
+        _ => (value, 1),
+    };
+    let amount = amount.parse::<u64>().map_err(|_| error())?;
+    if amount == 0 {
+        return Err(error());
+    }
+    amount
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(error)
+}
+
 /// Arguments for the `chat` subcommand.
 #[derive(Parser, Debug)]
 pub struct ChatArgs {
-    /// Model to use (format: provider:model, e.g., openai:gpt-4o or codex:gpt-5.3-codex-spark)
-    #[arg(short, long, default_value = "openai:gpt-4o")]
-    pub model: String,
+    /// Model to use (format: provider:model, e.g., openai:gpt-4o or codex:gpt-5.3-codex-spark).
+    /// Defaults to `defaults.model` in settings, then openai:gpt-4o.
+    #[arg(short, long)]
+    pub model: Option<String>,
 
     /// Additional fallback model candidate to try after the primary model. Repeatable.
     #[arg(long = "candidate-model", value_name = "PROVIDER:MODEL")]
@@ -234,6 +316,18 @@ pub struct ChatArgs {
     #[arg(short, long)]
     pub temperature: Option<f64>,
 
+    /// Named generation preset from the `presets` settings. Explicit flags
+    /// override its values.
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Directory the agent works in instead of the current directory.
+    /// Resources, skills, and prompt templates are discovered from it, tools
+    /// resolve paths inside it, and relative --skill-path, --skill-root,
+    /// --file, and --attach paths resolve against it.
+    #[arg(long, value_name = "PATH")]
+    pub workspace: Option<PathBuf>,
+
     /// Explicit skill path (file or directory)
     #[arg(long, value_name = "PATH")]
     pub skill_path: Vec<PathBuf>,
@@ -302,6 +396,14 @@ pub struct ChatArgs {
     )]
     pub max_session_output_tokens: Option<usize>,
 
+    /// Stop the run once its estimated cost reaches this many USD
+    #[arg(long = "max-cost", value_name = "USD", value_parser = parse_max_cost)]
+    pub max_cost: Option<f64>,
+
+    /// Stop the run after this much wall-clock time (e.g. 90, 90s, 5m, 1h)
+    #[arg(long = "max-time", value_name = "DURATION", value_parser = parse_max_time)]
+    pub max_time: Option<Duration>,
+
     /// Disable auto compaction summary+pruning
     #[arg(long = "no-auto-compaction")]
     pub no_auto_compaction: bool,
@@ -326,9 +428,17 @@ pub struct ChatArgs {
     #[arg(long = "compaction-model", value_name = "PROVIDER:MODEL")]
     pub compaction_model: Option<String>,
 
-    /// Tool approval behavior
-    #[arg(long, value_enum, default_value_t = ChatApprovalArg::Ask)]
-    pub approval: ChatApprovalArg,
+    /// Tool approval behavior. Defaults to `defaults.approval` in settings, then ask.
+    #[arg(long, value_enum)]
+    pub approval: Option<ChatApprovalArg>,
+
+    /// Maximum model/tool iterations per run. Defaults to `defaults.max_iterations` in settings.
+    #[arg(long = "max-iterations", value_name = "COUNT", value_parser = parse_positive_usize)]
+    pub max_iterations: Option<usize>,
+
+    /// Plan tool calls without executing them; each call gets a simulated result
+    #[arg(long)]
+    pub dry_run: bool,
 
     /// Durable session root directory. When set, chat events/resources are stored under <root>/<session-id>.
     #[arg(long, value_name = "PATH")]
@@ -342,6 +452,23 @@ pub struct ChatArgs {
     #[arg(long = "attach", value_name = "PATH")]
     pub attachments: Vec<PathBuf>,
 
+    /// Text file to inline into the prompt as a fenced code block. Repeatable.
+    #[arg(long = "file", value_name = "PATH")]
+    pub files: Vec<PathBuf>,
+
+    /// What to do when `--file` contents exceed `--file-budget`
+    #[arg(long = "file-mode", value_enum, default_value_t = ChatFileModeArg::Strict)]
+    pub file_mode: ChatFileModeArg,
+
+    /// Estimated token budget for all `--file` contents
+    #[arg(
+        long = "file-budget",
+        value_name = "TOKENS",
+        default_value_t = 32_000,
+        value_parser = parse_positive_usize
+    )]
+    pub file_budget: usize,
+
     /// MCP stdio server spec (repeatable). Format: `key=value` pairs separated by commas.
     /// Keys: `id`, `label`, `command`, `arg` (repeat for multiple args).
     /// Example: `--mcp-stdio 'id=local,label=Local Files,command=npx,arg=-y,arg=@modelcontextprotocol/server-filesystem,arg=.'`
@@ -360,6 +487,42 @@ pub struct ChatArgs {
     #[arg(long = "mcp-websocket", value_name = "SPEC")]
     pub mcp_websocket: Vec<String>,
 
+    /// Print per-message context usage before the run. `--show-context=dump:<path>`
+    /// also writes the analyzed messages to <path> as JSON.
+    #[arg(
+        long = "show-context",
+        value_name = "dump:PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "table",
+        value_parser = parse_show_context
+    )]
+    pub show_context: Option<ChatShowContextArg>,
+
+    /// Print the files changed by tools after the run, with add/modify/delete
+    /// markers and line-count deltas. Shell-inferred entries are marked unverified.
+    #[arg(long = "summarize-changes")]
+    pub summarize_changes: bool,
+
+    /// Directory for tool artifacts too large to keep in memory. Each run
+    /// spills into a subdirectory named after its run id.
+    #[arg(long = "artifacts-dir", value_name = "DIR")]
+    pub artifacts_dir: Option<PathBuf>,
+
+    /// Directory for images generated by the model. Defaults to the
+    /// workspace.
+    #[arg(long = "image-out", value_name = "DIR")]
+    pub image_out: Option<PathBuf>,
+
+    /// Collapse tool activity into one status line per batch. Failures are
+    /// still printed in full.
+    #[arg(long = "quiet-tools", conflicts_with = "verbose_tools")]
+    pub quiet_tools: bool,
+
+    /// Print tool arguments and untruncated tool results and updates.
+    #[arg(long = "verbose-tools")]
+    pub verbose_tools: bool,
+
     /// User prompt (positional)
     pub prompt: Option<String>,
 }
@@ -372,6 +535,24 @@ pub enum ChatApprovalArg {
     Never,
 }
 
+/// CLI-local handling of `--file` contents over budget.
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ChatFileModeArg {
+    /// Fail with guidance
+    Strict,
+    /// Keep the head and tail of each oversized file
+    Summary,
+}
+
+/// CLI-local `--show-context` mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChatShowContextArg {
+    /// Print the context table
+    Table,
+    /// Print the table and write the analyzed messages to a file
+    Dump(PathBuf),
+}
+
 /// CLI-local retry mode values for chat.
 #[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
 pub enum ChatRetryModeArg {
@@ -379,6 +560,141 @@ pub enum ChatRetryModeArg {
     Persistent,
 }
 
+/// Arguments for `roci-agent serve`.
+///
+/// Requests must carry `Authorization: Bearer <token>` when `--token`,
+/// `ROCI_SERVE_TOKEN`, or `serve.token` in settings is set, checked in that
+/// order. Binding a non-loopback address requires one of them, or
+/// `--no-auth`.
+#[cfg(feature = "serve")]
+#[derive(Parser, Debug)]
+pub struct ServeArgs {
+    /// Model every request runs against (format: provider:model)
+    #[arg(short, long, default_value = "openai:gpt-4o")]
+    pub model: String,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Address to bind
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: std::net::IpAddr,
+
+    /// Bearer token clients must present (default: $ROCI_SERVE_TOKEN, then
+    /// `serve.token` in settings)
+    #[arg(long, value_name = "TOKEN", conflicts_with = "no_auth")]
+    pub token: Option<String>,
+
+    /// Serve without a bearer token, even on a non-loopback address
+    #[arg(long)]
+    pub no_auth: bool,
+
+    /// Runs allowed at once; further requests queue (default: unlimited)
+    #[arg(long)]
+    pub max_concurrent_runs: Option<usize>,
+
+    /// Requests allowed to wait for a run slot before new ones get 429
+    #[arg(long, default_value_t = 32)]
+    pub queue_capacity: usize,
+
+    /// Seconds a request may wait for a run slot before it fails
+    #[arg(long)]
+    pub queue_timeout_secs: Option<u64>,
+}
+
+/// Arguments for `roci-agent compare`.
+#[derive(Parser, Debug)]
+pub struct CompareArgs {
+    /// Model to compare as provider:model; repeat for each candidate.
+    #[arg(
+        short = 'm',
+        long = "model",
+        value_name = "PROVIDER:MODEL",
+        required = true
+    )]
+    pub models: Vec<String>,
+
+    /// Prompt sent to every model.
+    pub prompt: String,
+
+    /// System prompt sent to every model.
+    #[arg(short, long)]
+    pub system: Option<String>,
+
+    /// Model that scores the answers, as provider:model.
+    #[arg(long, value_name = "PROVIDER:MODEL")]
+    pub judge: Option<String>,
+
+    /// Rubric the judge scores against.
+    #[arg(long, value_name = "TEXT", requires = "judge")]
+    pub rubric: Option<String>,
+
+    /// Maximum model requests in flight at once.
+    #[arg(long, default_value_t = 4, value_parser = parse_positive_usize)]
+    pub concurrency: usize,
+
+    /// Print results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for `roci-agent redact`.
+#[derive(Parser, Debug)]
+pub struct RedactArgs {
+    /// JSONL transcript to redact.
+    pub input: PathBuf,
+
+    /// Where to write the redacted transcript; stdout when omitted.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for `roci-agent import`.
+#[derive(Parser, Debug)]
+pub struct ImportArgs {
+    /// Format of the session file.
+    #[arg(long = "from", value_enum, value_name = "FORMAT")]
+    pub from: ImportFormatArg,
+
+    /// Session file to import.
+    pub path: PathBuf,
+
+    /// Id of the roci session to create.
+    #[arg(long, value_name = "NAME")]
+    pub session: String,
+
+    /// Session root directory. Defaults to the app data session directory.
+    #[arg(long, value_name = "PATH")]
+    pub root: Option<PathBuf>,
+
+    /// Print a JSON summary.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Session file formats accepted by `roci-agent import`.
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ImportFormatArg {
+    ClaudeCode,
+    Codex,
+}
+
+/// Arguments for `roci-agent inspect`.
+#[derive(Parser, Debug)]
+pub struct InspectArgs {
+    /// JSONL transcript written by a run with a transcript writer.
+    pub transcript: PathBuf,
+
+    /// Iteration to show (1-based). Lists all iterations when omitted.
+    #[arg(long, value_name = "N")]
+    pub iteration: Option<usize>,
+
+    /// Print JSON instead of the text view.
+    #[arg(long)]
+    pub json: bool,
+}
+
 /// Arguments for the `models` subcommand group.
 #[derive(Parser, Debug)]
 pub struct ModelsArgs {
@@ -406,9 +722,18 @@ pub struct ModelsListArgs {
     #[arg(long, value_name = "PROVIDER")]
     pub provider: Option<String>,
 
+    /// Only show models whose id or display name contains this text (case-insensitive)
+    #[arg(long, value_name = "TEXT")]
+    pub search: Option<String>,
+
     /// Print models as JSON.
     #[arg(long)]
     pub json: bool,
+
+    /// Show the feature matrix of registered providers instead of models,
+    /// from static metadata only (no network calls)
+    #[arg(long, conflicts_with = "search")]
+    pub matrix: bool,
 }
 
 /// Arguments for hidden `roci-agent models switch-smoke`.
@@ -681,6 +1006,152 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_auth_login_no_poll_with_store_path() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "auth",
+            "login",
+            "codex",
+            "--no-poll",
+            "--print-token-env",
+            "--store-path",
+            "/secrets/roci",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Auth(auth) => {
+                assert_eq!(auth.store_path, Some(PathBuf::from("/secrets/roci")));
+                match auth.command {
+                    AuthCommands::Login(args) => {
+                        assert_eq!(args.provider, "codex");
+                        assert!(args.no_poll);
+                        assert!(args.print_token_env);
+                    }
+                    other => panic!("expected Login, got {other:?}"),
+                }
+            }
+            other => panic!("expected Auth, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_auth_complete_with_code() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "auth",
+            "complete",
+            "codex",
+            "--code",
+            "ABCD-1234",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Auth(auth) => {
+                assert_eq!(auth.store_path, None);
+                match auth.command {
+                    AuthCommands::Complete(args) => {
+                        assert_eq!(args.provider, "codex");
+                        assert_eq!(args.code.as_deref(), Some("ABCD-1234"));
+                        assert!(!args.print_token_env);
+                    }
+                    other => panic!("expected Complete, got {other:?}"),
+                }
+            }
+            other => panic!("expected Auth, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_compare_repeated_models_and_judge() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "compare",
+            "-m",
+            "openai:gpt-4o",
+            "--model",
+            "anthropic:claude-sonnet-4-5",
+            "--judge",
+            "openai:gpt-4o-mini",
+            "--rubric",
+            "Prefer concise answers",
+            "--concurrency",
+            "2",
+            "Explain ownership",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Compare(args) => {
+                assert_eq!(
+                    args.models,
+                    vec!["openai:gpt-4o", "anthropic:claude-sonnet-4-5"]
+                );
+                assert_eq!(args.prompt, "Explain ownership");
+                assert_eq!(args.judge.as_deref(), Some("openai:gpt-4o-mini"));
+                assert_eq!(args.rubric.as_deref(), Some("Prefer concise answers"));
+                assert_eq!(args.concurrency, 2);
+                assert!(!args.json);
+            }
+            other => panic!("expected Compare, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_redact_input_and_output() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "redact",
+            "transcript.jsonl",
+            "-o",
+            "clean.jsonl",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Redact(args) => {
+                assert_eq!(args.input, PathBuf::from("transcript.jsonl"));
+                assert_eq!(args.output, Some(PathBuf::from("clean.jsonl")));
+            }
+            other => panic!("expected Redact, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_compare_requires_a_model_and_positive_concurrency() {
+        assert!(Cli::try_parse_from(["roci-agent", "compare", "hello"]).is_err());
+        assert!(Cli::try_parse_from([
+            "roci-agent",
+            "compare",
+            "-m",
+            "openai:gpt-4o",
+            "--concurrency",
+            "0",
+            "hello",
+        ])
+        .is_err());
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn parse_serve_port_and_model() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "serve",
+            "--port",
+            "9090",
+            "--model",
+            "anthropic:claude-sonnet-4",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Serve(args) => {
+                assert_eq!(args.port, 9090);
+                assert_eq!(args.model, "anthropic:claude-sonnet-4");
+                assert!(args.host.is_loopback());
+            }
+            other => panic!("expected Serve, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_auth_status() {
         let cli = Cli::try_parse_from(["roci-agent", "auth", "status"]).unwrap();
@@ -704,17 +1175,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_chat_with_preset() {
+        let cli = Cli::try_parse_from(["roci-agent", "chat", "--preset", "terse", "hi"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.preset.as_deref(), Some("terse"));
+                assert_eq!(args.prompt.as_deref(), Some("hi"));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_chat_with_workspace() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--workspace",
+            "/srv/repo",
+            "--skill-path",
+            "skills/review",
+            "hi",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.workspace, Some(PathBuf::from("/srv/repo")));
+                assert_eq!(args.skill_path, vec![PathBuf::from("skills/review")]);
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_chat_with_defaults() {
         let cli = Cli::try_parse_from(["roci-agent", "chat"]).unwrap();
         match cli.command {
             Commands::Chat(args) => {
-                assert_eq!(args.model, "openai:gpt-4o");
+                assert!(args.model.is_none());
                 assert!(args.candidate_models.is_empty());
                 assert_eq!(args.retry_mode, ChatRetryModeArg::Bounded);
                 assert_eq!(args.max_retry_attempts, 3);
                 assert!(args.system.is_none());
                 assert!(args.temperature.is_none());
+                assert!(args.preset.is_none());
                 assert!(args.skill_path.is_empty());
                 assert!(args.skill_root.is_empty());
                 assert!(!args.no_skills);
@@ -730,16 +1235,28 @@ mod tests {
                 assert!(args.max_turn_input_tokens.is_none());
                 assert!(args.max_session_input_tokens.is_none());
                 assert!(args.max_session_output_tokens.is_none());
+                assert!(args.max_cost.is_none());
+                assert!(args.max_time.is_none());
                 assert!(!args.no_auto_compaction);
                 assert!(args.compaction_reserve_tokens.is_none());
                 assert!(args.compaction_keep_recent_tokens.is_none());
                 assert!(args.compaction_model.is_none());
-                assert_eq!(args.approval, ChatApprovalArg::Ask);
+                assert!(args.approval.is_none());
+                assert!(args.max_iterations.is_none());
                 assert!(args.session_root.is_none());
                 assert!(args.session_id.is_none());
+                assert!(args.files.is_empty());
+                assert_eq!(args.file_mode, ChatFileModeArg::Strict);
+                assert_eq!(args.file_budget, 32_000);
                 assert!(args.mcp_stdio.is_empty());
                 assert!(args.mcp_streamable_http.is_empty());
                 assert!(args.mcp_websocket.is_empty());
+                assert!(args.show_context.is_none());
+                assert!(!args.summarize_changes);
+                assert!(args.image_out.is_none());
+                assert!(args.workspace.is_none());
+                assert!(!args.quiet_tools);
+                assert!(!args.verbose_tools);
                 assert!(args.prompt.is_none());
             }
             other => panic!("expected Chat, got {other:?}"),
@@ -754,6 +1271,7 @@ mod tests {
                 ModelsCommands::List(args) => {
                     assert!(args.provider.is_none());
                     assert!(!args.json);
+                    assert!(!args.matrix);
                 }
                 other => panic!("expected List, got {other:?}"),
             },
@@ -784,6 +1302,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_models_list_matrix() {
+        let cli = Cli::try_parse_from(["roci-agent", "models", "list", "--matrix"]).unwrap();
+        match cli.command {
+            Commands::Models(models) => match models.command {
+                ModelsCommands::List(args) => assert!(args.matrix),
+                other => panic!("expected List, got {other:?}"),
+            },
+            other => panic!("expected Models, got {other:?}"),
+        }
+        assert!(Cli::try_parse_from([
+            "roci-agent",
+            "models",
+            "list",
+            "--matrix",
+            "--search",
+            "gpt"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn parse_models_switch_smoke_hidden_command() {
         let cli = Cli::try_parse_from([
@@ -972,12 +1511,14 @@ mod tests {
             "1024",
             "--approval",
             "always",
+            "--max-iterations",
+            "12",
             "Hello world",
         ])
         .unwrap();
         match cli.command {
             Commands::Chat(args) => {
-                assert_eq!(args.model, "anthropic:claude-4-sonnet");
+                assert_eq!(args.model.as_deref(), Some("anthropic:claude-4-sonnet"));
                 assert!(args.candidate_models.is_empty());
                 assert_eq!(args.retry_mode, ChatRetryModeArg::Bounded);
                 assert_eq!(args.max_retry_attempts, 3);
@@ -1002,7 +1543,8 @@ mod tests {
                 assert!(args.compaction_reserve_tokens.is_none());
                 assert!(args.compaction_keep_recent_tokens.is_none());
                 assert!(args.compaction_model.is_none());
-                assert_eq!(args.approval, ChatApprovalArg::Always);
+                assert_eq!(args.approval, Some(ChatApprovalArg::Always));
+                assert_eq!(args.max_iterations, Some(12));
                 assert!(args.session_root.is_none());
                 assert!(args.session_id.is_none());
                 assert!(args.mcp_stdio.is_empty());
@@ -1057,6 +1599,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_chat_with_run_budget_flags() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--max-cost",
+            "$0.25",
+            "--max-time",
+            "5m",
+            "budget-check",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.max_cost, Some(0.25));
+                assert_eq!(args.max_time, Some(Duration::from_secs(300)));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+
+        assert_eq!(parse_max_time("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_max_time("2h"), Ok(Duration::from_secs(7_200)));
+        assert!(parse_max_time("0s").is_err());
+        assert!(parse_max_time("soon").is_err());
+        assert!(parse_max_cost("0").is_err());
+        assert!(parse_max_cost("-1").is_err());
+    }
+
+    #[test]
+    fn parse_chat_with_dry_run() {
+        let cli = Cli::try_parse_from(["roci-agent", "chat", "--dry-run", "plan it"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => assert!(args.dry_run),
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_chat_with_no_skills() {
         let cli = Cli::try_parse_from(["roci-agent", "chat", "--no-skills", "prompt"]).unwrap();
@@ -1328,6 +1907,141 @@ mod tests {
         assert!(Cli::try_parse_from(["roci-agent", "chat", "--attach"]).is_err());
     }
 
+    #[test]
+    fn parse_chat_with_repeatable_files_and_summary_mode() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--file",
+            "src/main.rs",
+            "--file",
+            "Cargo.toml",
+            "--file-mode",
+            "summary",
+            "--file-budget",
+            "4000",
+            "prompt text",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(
+                    args.files,
+                    vec![PathBuf::from("src/main.rs"), PathBuf::from("Cargo.toml")]
+                );
+                assert_eq!(args.file_mode, ChatFileModeArg::Summary);
+                assert_eq!(args.file_budget, 4000);
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_chat_show_context_table_and_dump() {
+        let cli =
+            Cli::try_parse_from(["roci-agent", "chat", "--show-context", "prompt text"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.show_context, Some(ChatShowContextArg::Table));
+                assert_eq!(args.prompt.as_deref(), Some("prompt text"));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--show-context=dump:/tmp/context.json",
+            "prompt text",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Chat(args) => assert_eq!(
+                args.show_context,
+                Some(ChatShowContextArg::Dump(PathBuf::from("/tmp/context.json")))
+            ),
+            other => panic!("expected Chat, got {other:?}"),
+        }
+
+        assert!(Cli::try_parse_from(["roci-agent", "chat", "--show-context=dump:"]).is_err());
+        assert!(Cli::try_parse_from(["roci-agent", "chat", "--show-context=json"]).is_err());
+    }
+
+    #[test]
+    fn parse_chat_tool_output_flags() {
+        let cli = Cli::try_parse_from(["roci-agent", "chat", "--quiet-tools", "prompt"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert!(args.quiet_tools);
+                assert!(!args.verbose_tools);
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+
+        let cli = Cli::try_parse_from(["roci-agent", "chat", "--verbose-tools", "prompt"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => assert!(args.verbose_tools),
+            other => panic!("expected Chat, got {other:?}"),
+        }
+
+        assert!(Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--quiet-tools",
+            "--verbose-tools",
+            "prompt"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parse_chat_artifacts_dir() {
+        let cli = Cli::try_parse_from([
+            "roci-agent",
+            "chat",
+            "--artifacts-dir",
+            "out/artifacts",
+            "prompt text",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.artifacts_dir, Some(PathBuf::from("out/artifacts")));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_chat_image_out() {
+        let cli =
+            Cli::try_parse_from(["roci-agent", "chat", "--image-out", "renders", "draw"]).unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert_eq!(args.image_out, Some(PathBuf::from("renders")));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_chat_summarize_changes() {
+        let cli = Cli::try_parse_from(["roci-agent", "chat", "--summarize-changes", "prompt text"])
+            .unwrap();
+        match cli.command {
+            Commands::Chat(args) => {
+                assert!(args.summarize_changes);
+                assert_eq!(args.prompt.as_deref(), Some("prompt text"));
+            }
+            other => panic!("expected Chat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_chat_rejects_zero_file_budget() {
+        assert!(Cli::try_parse_from(["roci-agent", "chat", "--file-budget", "0"]).is_err());
+    }
+
     #[test]
     fn parse_chat_with_candidate_models_and_retry_controls() {
         let cli = Cli::try_parse_from([
@@ -1349,7 +2063,7 @@ mod tests {
 
         match cli.command {
             Commands::Chat(args) => {
-                assert_eq!(args.model, "openai:gpt-4o");
+                assert_eq!(args.model.as_deref(), Some("openai:gpt-4o"));
                 assert_eq!(
                     args.candidate_models,
                     vec![
@@ -0,0 +1,408 @@
+use std::io::{IsTerminal, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use roci::config::RociConfig;
+use roci::generation::{compare, CompareJudge, CompareOptions, CompareResult};
+use roci::models::LanguageModel;
+use roci::provider::ProviderRegistry;
+use roci::types::{GenerationSettings, ModelMessage};
+
+use crate::cli::CompareArgs;
+
+const DEFAULT_RUBRIC: &str =
+    "Score each answer for correctness, completeness, and clarity. Prefer answers that address the request directly.";
+const DEFAULT_TERMINAL_WIDTH: usize = 120;
+const MIN_COLUMN_WIDTH: usize = 20;
+const COLUMN_GAP: &str = " | ";
+
+pub async fn handle_compare(args: CompareArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = Arc::new(roci::default_registry());
+    let config = RociConfig::from_env();
+    let side_by_side = std::io::stdout().is_terminal();
+    let mut stdout = std::io::stdout();
+    run_compare(args, registry, config, side_by_side, &mut stdout).await
+}
+
+pub(crate) async fn run_compare(
+    args: CompareArgs,
+    registry: Arc<ProviderRegistry>,
+    config: RociConfig,
+    side_by_side: bool,
+    writer: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let models = args
+        .models
+        .iter()
+        .map(|model| LanguageModel::from_str(model))
+        .collect::<Result<Vec<_>, _>>()?;
+    let judge = args
+        .judge
+        .as_deref()
+        .map(LanguageModel::from_str)
+        .transpose()?
+        .map(|model| {
+            CompareJudge::new(
+                model,
+                args.rubric
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_RUBRIC.to_string()),
+            )
+        });
+
+    let mut messages = Vec::new();
+    if let Some(system) = args.system {
+        messages.push(ModelMessage::system(system));
+    }
+    messages.push(ModelMessage::user(args.prompt));
+
+    let options = CompareOptions {
+        max_concurrency: args.concurrency,
+        judge,
+    };
+    let result = compare(
+        &registry,
+        &config,
+        &models,
+        messages,
+        GenerationSettings::default(),
+        options,
+    )
+    .await;
+
+    if args.json {
+        writeln!(writer, "{}", serde_json::to_string_pretty(&result)?)?;
+    } else {
+        render_summary(&result, writer)?;
+        writeln!(writer)?;
+        if side_by_side && result.candidates.len() > 1 {
+            render_side_by_side(&result, terminal_width(), writer)?;
+        } else {
+            render_sequential(&result, writer)?;
+        }
+        render_judgement(&result, writer)?;
+    }
+
+    if result.candidates.iter().all(|candidate| !candidate.is_ok()) {
+        return Err("every model in the comparison failed".into());
+    }
+    Ok(())
+}
+
+fn render_summary(result: &CompareResult, writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, "MODEL\tLATENCY_MS\tINPUT\tOUTPUT\tSTATUS")?;
+    for candidate in &result.candidates {
+        let status = match &candidate.error {
+            Some(error) => format!("error: {error}"),
+            None => "ok".to_string(),
+        };
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}",
+            candidate.model,
+            candidate.latency_ms,
+            candidate.usage.input_tokens,
+            candidate.usage.output_tokens,
+            status
+        )?;
+    }
+    Ok(())
+}
+
+fn render_sequential(result: &CompareResult, writer: &mut impl Write) -> std::io::Result<()> {
+    for (index, candidate) in result.candidates.iter().enumerate() {
+        if index > 0 {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "== {} ==", candidate.model)?;
+        writeln!(writer, "{}", candidate_body(candidate))?;
+    }
+    Ok(())
+}
+
+fn render_side_by_side(
+    result: &CompareResult,
+    width: usize,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let count = result.candidates.len();
+    let gaps = COLUMN_GAP.len() * count.saturating_sub(1);
+    let column_width = (width.saturating_sub(gaps) / count).max(MIN_COLUMN_WIDTH);
+
+    let columns = result
+        .candidates
+        .iter()
+        .map(|candidate| {
+            let mut lines = wrap(&candidate.model.to_string(), column_width);
+            lines.push("-".repeat(column_width));
+            lines.extend(wrap(&candidate_body(candidate), column_width));
+            lines
+        })
+        .collect::<Vec<_>>();
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+    for row in 0..rows {
+        let line = columns
+            .iter()
+            .map(|column| {
+                let cell = column.get(row).map(String::as_str).unwrap_or("");
+                format!("{cell:<column_width$}")
+            })
+            .collect::<Vec<_>>()
+            .join(COLUMN_GAP);
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn render_judgement(result: &CompareResult, writer: &mut impl Write) -> std::io::Result<()> {
+    let Some(judgement) = &result.judgement else {
+        return Ok(());
+    };
+    writeln!(writer)?;
+    writeln!(writer, "== judge: {} ==", judgement.model)?;
+    if let Some(error) = &judgement.error {
+        writeln!(writer, "error: {error}")?;
+        return Ok(());
+    }
+    writeln!(writer, "MODEL\tSCORE\tRATIONALE")?;
+    for score in &judgement.scores {
+        let model = &result.candidates[score.candidate].model;
+        writeln!(writer, "{}\t{}\t{}", model, score.score, score.rationale)?;
+    }
+    Ok(())
+}
+
+fn candidate_body(candidate: &roci::generation::CompareCandidate) -> String {
+    match (&candidate.text, &candidate.error) {
+        (_, Some(error)) => format!("error: {error}"),
+        (Some(text), None) => text.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Wrap `text` into lines of at most `width` characters, breaking on
+/// whitespace where possible and preserving blank lines.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word;
+            while word.chars().count() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let split = word
+                    .char_indices()
+                    .nth(width)
+                    .map_or(word.len(), |(index, _)| index);
+                lines.push(word[..split].to_string());
+                word = &word[split..];
+            }
+            if word.is_empty() {
+                continue;
+            }
+            let needed = if line.is_empty() {
+                word.chars().count()
+            } else {
+                line.chars().count() + 1 + word.chars().count()
+            };
+            if needed > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::stream::BoxStream;
+    use roci::error::RociError;
+    use roci::models::ModelCapabilities;
+    use roci::provider::{ModelProvider, ProviderFactory, ProviderRequest, ProviderResponse};
+    use roci::types::{FinishReason, TextStreamDelta, Usage};
+
+    struct StubProvider {
+        model_id: String,
+        capabilities: ModelCapabilities,
+    }
+
+    #[async_trait]
+    impl ModelProvider for StubProvider {
+        fn provider_name(&self) -> &str {
+            "stub"
+        }
+
+        fn model_id(&self) -> &str {
+            &self.model_id
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            &self.capabilities
+        }
+
+        async fn generate_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            if self.model_id == "broken" {
+                return Err(RociError::Stream("connection reset".into()));
+            }
+            Ok(ProviderResponse {
+                text: format!("answer from {}", self.model_id),
+                usage: Usage {
+                    input_tokens: 7,
+                    output_tokens: 4,
+                    total_tokens: 11,
+                    ..Usage::default()
+                },
+                tool_calls: Vec::new(),
+                finish_reason: Some(FinishReason::Stop),
+                thinking: Vec::new(),
+                metadata: std::collections::HashMap::new(),
+                refusal: None,
+                images: Vec::new(),
+                response_metadata: None,
+            })
+        }
+
+        async fn stream_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            panic!("compare does not stream")
+        }
+    }
+
+    struct StubFactory;
+
+    impl ProviderFactory for StubFactory {
+        fn provider_keys(&self) -> &[&str] {
+            &["stub"]
+        }
+
+        fn requires_credentials(&self, _provider_key: &str) -> bool {
+            false
+        }
+
+        fn create(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            Ok(Box::new(StubProvider {
+                model_id: model_id.to_string(),
+                capabilities: ModelCapabilities::default(),
+            }))
+        }
+    }
+
+    fn registry() -> Arc<ProviderRegistry> {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(StubFactory));
+        Arc::new(registry)
+    }
+
+    fn args(models: &[&str]) -> CompareArgs {
+        CompareArgs {
+            models: models.iter().map(|model| model.to_string()).collect(),
+            prompt: "hello".to_string(),
+            system: None,
+            judge: None,
+            rubric: None,
+            concurrency: 4,
+            json: false,
+        }
+    }
+
+    async fn render(args: CompareArgs, side_by_side: bool) -> (String, bool) {
+        let mut output = Vec::new();
+        let ok = run_compare(
+            args,
+            registry(),
+            RociConfig::default(),
+            side_by_side,
+            &mut output,
+        )
+        .await
+        .is_ok();
+        (String::from_utf8(output).unwrap(), ok)
+    }
+
+    #[tokio::test]
+    async fn sequential_output_keeps_going_past_a_failed_model() {
+        let (output, ok) = render(args(&["stub:alpha", "stub:broken", "stub:beta"]), false).await;
+
+        assert!(ok);
+        assert!(output.starts_with("MODEL\tLATENCY_MS\tINPUT\tOUTPUT\tSTATUS\n"));
+        assert!(output.contains("\t7\t4\tok\n"));
+        assert!(output.contains("stub:broken\t"));
+        assert!(output.contains("error: "));
+        let alpha = output.find("== stub:alpha ==").unwrap();
+        let broken = output.find("== stub:broken ==").unwrap();
+        let beta = output.find("== stub:beta ==").unwrap();
+        assert!(alpha < broken && broken < beta);
+        assert!(output.contains("answer from beta"));
+    }
+
+    #[tokio::test]
+    async fn side_by_side_output_puts_answers_in_columns() {
+        let (output, ok) = render(args(&["stub:alpha", "stub:beta"]), true).await;
+
+        assert!(ok);
+        let row = output
+            .lines()
+            .find(|line| line.contains("answer from alpha"))
+            .unwrap();
+        assert!(row.contains(COLUMN_GAP));
+        assert!(row.contains("answer from beta"));
+    }
+
+    #[tokio::test]
+    async fn json_output_serializes_every_candidate() {
+        let mut compare_args = args(&["stub:alpha", "stub:broken"]);
+        compare_args.json = true;
+        let (output, ok) = render(compare_args, false).await;
+
+        assert!(ok);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let candidates = json["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0]["text"], "answer from alpha");
+        assert!(candidates[1]["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn all_failures_are_reported_as_an_error() {
+        let (output, ok) = render(args(&["stub:broken"]), false).await;
+
+        assert!(!ok);
+        assert!(output.contains("== stub:broken =="));
+    }
+
+    #[test]
+    fn wrap_breaks_on_whitespace_and_splits_long_words() {
+        assert_eq!(
+            wrap("one two three\n\nabcdefghij", 7),
+            vec!["one two", "three", "", "abcdefg", "hij"]
+        );
+    }
+}
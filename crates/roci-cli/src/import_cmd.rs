@@ -0,0 +1,153 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use roci::session::{CreateSessionOptions, LocalSessionStore, SessionId};
+use roci::types::interop::{import_session, ImportWarning, SessionFormat};
+
+use crate::cli::{ImportArgs, ImportFormatArg};
+use crate::session_cmd::resolve_root;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+struct ImportSummary {
+    id: String,
+    root: PathBuf,
+    format: SessionFormat,
+    version: String,
+    messages: usize,
+    warnings: Vec<ImportWarning>,
+}
+
+pub async fn handle_import(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let json = args.json;
+    let summary = import(args).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        println!(
+            "Imported {} message(s) from {} {} into session {}",
+            summary.messages, summary.format, summary.version, summary.id
+        );
+        println!("Root: {}", summary.root.display());
+        write_warnings(&summary.warnings, &mut std::io::stderr())?;
+    }
+    Ok(())
+}
+
+async fn import(args: ImportArgs) -> Result<ImportSummary, Box<dyn std::error::Error>> {
+    let format = match args.from {
+        ImportFormatArg::ClaudeCode => SessionFormat::ClaudeCode,
+        ImportFormatArg::Codex => SessionFormat::Codex,
+    };
+    let id = SessionId::parse(args.session)?;
+    let root = resolve_root(args.root)?;
+    let input = std::fs::read_to_string(&args.path)
+        .map_err(|err| format!("failed to read {}: {err}", args.path.display()))?;
+    let imported = import_session(format, &input)?;
+    let messages = imported.messages.len();
+
+    let store = LocalSessionStore::new(root.clone());
+    let state = store
+        .import_history(
+            CreateSessionOptions {
+                id: Some(id),
+                title: None,
+                host_cwd: Some(std::env::current_dir()?),
+                import_source: Some(args.path),
+                model_preferences: Default::default(),
+                default_thread_id: None,
+            },
+            imported.messages,
+        )
+        .await?;
+    Ok(ImportSummary {
+        id: state.metadata.id.to_string(),
+        root,
+        format,
+        version: imported.version,
+        messages,
+        warnings: imported.warnings,
+    })
+}
+
+/// List skipped entries on stderr so stdout stays a clean summary.
+fn write_warnings(warnings: &[ImportWarning], writer: &mut impl Write) -> std::io::Result<()> {
+    if warnings.is_empty() {
+        return Ok(());
+    }
+    writeln!(writer, "Skipped {} entr(ies):", warnings.len())?;
+    for warning in warnings {
+        writeln!(writer, "  {warning}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci::types::Role;
+    use tempfile::tempdir;
+
+    const CLAUDE_CODE_SESSION: &str = concat!(
+        r#"{"type":"user","version":"2.0.14","message":{"role":"user","content":"list files"}}"#,
+        "\n",
+        r#"{"type":"assistant","version":"2.0.14","message":{"id":"msg_1","role":"assistant","content":[{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]}}"#,
+        "\n",
+        r#"{"type":"user","version":"2.0.14","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"Cargo.toml"}]}}"#,
+        "\n",
+        r#"{"type":"progress","version":"2.0.14"}"#,
+        "\n",
+    );
+
+    #[tokio::test]
+    async fn import_writes_history_into_a_new_session() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        std::fs::write(&path, CLAUDE_CODE_SESSION).unwrap();
+        let root = dir.path().join("sessions");
+
+        let summary = import(ImportArgs {
+            from: ImportFormatArg::ClaudeCode,
+            path: path.clone(),
+            session: "migrated".to_string(),
+            root: Some(root.clone()),
+            json: true,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(summary.id, "migrated");
+        assert_eq!(summary.messages, 3);
+        assert_eq!(summary.warnings.len(), 1);
+        let state = LocalSessionStore::new(root)
+            .open(SessionId::parse("migrated").unwrap())
+            .await
+            .unwrap();
+        let roles = state
+            .model_messages
+            .iter()
+            .map(|message| message.role)
+            .collect::<Vec<_>>();
+        assert_eq!(roles, vec![Role::User, Role::Assistant, Role::Tool]);
+        assert_eq!(state.model_messages[1].tool_calls()[0].id, "toolu_1");
+        assert_eq!(state.metadata.import_source, Some(path));
+    }
+
+    #[test]
+    fn warnings_are_listed_with_their_lines() {
+        let mut out = Vec::new();
+
+        write_warnings(
+            &[ImportWarning {
+                line: 4,
+                message: "unsupported entry type `progress`".to_string(),
+            }],
+            &mut out,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Skipped 1 entr(ies):\n  line 4: unsupported entry type `progress`\n"
+        );
+    }
+}
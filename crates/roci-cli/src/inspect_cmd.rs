@@ -0,0 +1,232 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use roci::agent_loop::replay::{
+    IterationState, StateReconstructor, TranscriptDiagnostic, TranscriptEvent,
+};
+use roci::context::ContextReport;
+use roci::models::LanguageModel;
+
+use crate::chat::context_view::render_context_report;
+use crate::cli::InspectArgs;
+
+pub fn handle_inspect(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let replay = StateReconstructor::open(&args.transcript)
+        .map_err(|err| format!("failed to read {}: {err}", args.transcript.display()))?;
+    let iteration = match args.iteration {
+        Some(number) => Some(replay.iteration(number).ok_or_else(|| {
+            format!(
+                "transcript has no iteration {number} ({} recorded)",
+                replay.iterations().len()
+            )
+        })?),
+        None => None,
+    };
+
+    if args.json {
+        let value = match iteration {
+            Some(state) => serde_json::json!({
+                "iteration": state,
+                "diagnostics": replay.diagnostics(),
+            }),
+            None => serde_json::json!({
+                "run": replay.run(),
+                "outcome": replay.outcome(),
+                "iterations": replay
+                    .iterations()
+                    .iter()
+                    .map(|state| serde_json::json!({
+                        "iteration": state.iteration,
+                        "model": state.model,
+                        "provider_requests": state.provider_requests,
+                        "messages": state.messages.len(),
+                        "tools": state.tools.len(),
+                        "events": state.events.len(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "diagnostics": replay.diagnostics(),
+            }),
+        };
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    match iteration {
+        Some(state) => print!("{}", render_iteration(state, replay.iterations().len())),
+        None => print!("{}", render_overview(&replay)),
+    }
+    write_diagnostics(replay.diagnostics(), &mut std::io::stderr())?;
+    Ok(())
+}
+
+fn render_overview(replay: &StateReconstructor) -> String {
+    let mut out = String::new();
+    let status = replay
+        .outcome()
+        .map(|outcome| format!("{:?}", outcome.status).to_lowercase())
+        .unwrap_or_else(|| "unfinished".to_string());
+    match replay.run() {
+        Some(run) => {
+            let _ = writeln!(out, "Run {} ({}), {status}", run.run_id, run.model);
+        }
+        None => {
+            let _ = writeln!(out, "Run <unknown>, {status}");
+        }
+    }
+    let _ = writeln!(
+        out,
+        "  {:>4}  {:>8}  {:>8}  {:>5}  {:>6}",
+        "#", "requests", "messages", "tools", "events"
+    );
+    for state in replay.iterations() {
+        let _ = writeln!(
+            out,
+            "  {:>4}  {:>8}  {:>8}  {:>5}  {:>6}",
+            state.iteration,
+            state.provider_requests,
+            state.messages.len(),
+            state.tools.len(),
+            state.events.len()
+        );
+    }
+    out
+}
+
+/// The `--show-context` breakdown of one iteration's request, followed by
+/// its tools, settings, and events.
+fn render_iteration(state: &IterationState, total: usize) -> String {
+    let mut out = String::new();
+    let model = state.model.as_deref().unwrap_or("no provider request");
+    let _ = writeln!(
+        out,
+        "Iteration {} of {total} ({model}, {} provider request(s))",
+        state.iteration, state.provider_requests
+    );
+    match state
+        .model
+        .as_deref()
+        .and_then(|model| model.parse::<LanguageModel>().ok())
+    {
+        Some(model) => out.push_str(&render_context_report(&ContextReport::analyze(
+            &state.messages,
+            &model,
+        ))),
+        None => {
+            let _ = writeln!(out, "Messages: {}", state.messages.len());
+        }
+    }
+
+    let names = state
+        .tools
+        .iter()
+        .map(|tool| tool.name.as_str())
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        out.push_str("Tools: none\n");
+    } else {
+        let _ = writeln!(out, "Tools ({}): {}", names.len(), names.join(", "));
+    }
+    if let Some(settings) = &state.settings {
+        let settings = serde_json::to_value(settings)
+            .ok()
+            .and_then(|value| value.as_object().cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>();
+        if settings.is_empty() {
+            out.push_str("Settings: defaults\n");
+        } else {
+            let _ = writeln!(out, "Settings: {}", settings.join(", "));
+        }
+    }
+
+    let _ = writeln!(out, "Events ({}):", state.events.len());
+    for event in &state.events {
+        let (stream, kind) = event_label(event);
+        let _ = writeln!(out, "  {:>5}  {stream:<5}  {kind}", event.seq());
+    }
+    out
+}
+
+/// Stream and serde tag of an event, e.g. `("run", "tool_result")`.
+fn event_label(event: &TranscriptEvent) -> (&'static str, String) {
+    let (stream, value) = match event {
+        TranscriptEvent::Run(event) => ("run", serde_json::to_value(&event.payload)),
+        TranscriptEvent::Agent(envelope) => ("agent", serde_json::to_value(&envelope.event)),
+    };
+    let kind = value
+        .ok()
+        .and_then(|value| {
+            value
+                .get("type")
+                .and_then(|kind| kind.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    (stream, kind)
+}
+
+/// List integrity problems on stderr so stdout stays the view.
+fn write_diagnostics(
+    diagnostics: &[TranscriptDiagnostic],
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    writeln!(writer, "Transcript problems ({}):", diagnostics.len())?;
+    for diagnostic in diagnostics {
+        writeln!(writer, "  {diagnostic}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = concat!(
+        r#"{"record":"run_started","schema_version":1,"run_id":"00000000-0000-0000-0000-000000000001","model":"openai:gpt-4o","started_at":"2026-01-01T00:00:00Z"}"#,
+        "\n",
+        r#"{"record":"iteration","iteration":1}"#,
+        "\n",
+        r#"{"record":"provider_request","iteration":1,"model":"openai:gpt-4o","base":0,"messages":[{"role":"user","content":[{"type":"text","text":"list files"}]}],"tools":[{"name":"ls","description":"list","parameters":{"type":"object"}}],"settings":{"temperature":0.5}}"#,
+        "\n",
+        r#"{"record":"run_event","run_id":"00000000-0000-0000-0000-000000000001","seq":1,"timestamp":"2026-01-01T00:00:01Z","stream":"lifecycle","payload":{"type":"lifecycle","state":"started"}}"#,
+        "\n",
+        r#"{"record":"run_event","run_id":"00000000-0000-0000-0000-000000000001","seq":3,"timestamp":"2026-01-01T00:00:02Z","stream":"assistant","payload":{"type":"assistant_delta","text":"ok"}}"#,
+        "\n",
+    );
+
+    #[test]
+    fn iteration_view_breaks_down_the_request() {
+        let replay = StateReconstructor::parse(TRANSCRIPT);
+        let state = replay.iteration(1).expect("iteration 1");
+
+        let view = render_iteration(state, 1);
+
+        assert!(view.starts_with("Iteration 1 of 1 (openai:gpt-4o, 1 provider request(s))\n"));
+        assert!(
+            view.contains("Context for openai:gpt-4o (1 messages)"),
+            "{view}"
+        );
+        assert!(view.contains("Tools (1): ls\n"), "{view}");
+        assert!(view.contains("Settings: temperature=0.5\n"), "{view}");
+        assert!(view.contains("      3  run    assistant_delta\n"), "{view}");
+    }
+
+    #[test]
+    fn gaps_and_missing_finish_are_reported() {
+        let replay = StateReconstructor::parse(TRANSCRIPT);
+        let mut out = Vec::new();
+
+        write_diagnostics(replay.diagnostics(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Transcript problems (2):\n  line 5: event 2 is missing\n  no `run_finished` record; the run was still going or the file was cut short\n"
+        );
+    }
+}
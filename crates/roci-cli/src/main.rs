@@ -3,8 +3,14 @@
 mod audio_cmd;
 mod chat;
 mod cli;
+mod compare_cmd;
 mod errors;
+mod import_cmd;
+mod inspect_cmd;
 mod models_cmd;
+mod redact_cmd;
+#[cfg(feature = "serve")]
+mod serve;
 mod session_cmd;
 mod skills_cmd;
 mod tool_contracts_smoke;
@@ -16,19 +22,36 @@ use cli::{AudioCommands, AuthCommands, Cli, Commands};
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let catalog_cache = roci::roci_providers::remote_catalog::RemoteCatalogCache::global();
+    catalog_cache.set_disk_path(Some(
+        roci::roci_providers::remote_catalog::RemoteCatalogCache::default_disk_path(),
+    ));
 
     let result = match cli.command {
         Commands::Auth(auth_args) => match auth_args.command {
-            AuthCommands::Login(args) => cli::auth::handle_login(&args.provider).await,
-            AuthCommands::Status => cli::auth::handle_status().await,
-            AuthCommands::Logout(args) => cli::auth::handle_logout(&args.provider).await,
+            AuthCommands::Login(args) => {
+                cli::auth::handle_login(args, auth_args.store_path.as_deref()).await
+            }
+            AuthCommands::Complete(args) => {
+                cli::auth::handle_complete(args, auth_args.store_path.as_deref()).await
+            }
+            AuthCommands::Status => cli::auth::handle_status(auth_args.store_path.as_deref()).await,
+            AuthCommands::Logout(args) => {
+                cli::auth::handle_logout(&args.provider, auth_args.store_path.as_deref()).await
+            }
         },
         Commands::Audio(audio_args) => match audio_args.command {
             AudioCommands::Transcribe(args) => audio_cmd::handle_transcribe(args).await,
             AudioCommands::Speak(args) => audio_cmd::handle_speak(args).await,
         },
         Commands::Chat(chat_args) => chat::handle_chat(chat_args).await,
+        Commands::Compare(compare_args) => compare_cmd::handle_compare(compare_args).await,
+        Commands::Import(import_args) => import_cmd::handle_import(import_args).await,
+        Commands::Inspect(inspect_args) => inspect_cmd::handle_inspect(inspect_args),
         Commands::Models(models_args) => models_cmd::handle_models(models_args).await,
+        Commands::Redact(redact_args) => redact_cmd::handle_redact(redact_args).await,
+        #[cfg(feature = "serve")]
+        Commands::Serve(serve_args) => serve::handle_serve(serve_args).await,
         Commands::Session(session_args) => session_cmd::handle_session(session_args).await,
         Commands::Skills(skills_args) => skills_cmd::handle_skills(skills_args).await,
         Commands::ToolContractsSmoke(args) => {
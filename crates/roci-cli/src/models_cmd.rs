@@ -6,7 +6,7 @@ use roci::agent::{AgentConfig, AgentRuntime};
 use roci::agent_loop::RunStatus;
 use roci::config::RociConfig;
 use roci::models::{LanguageModel, ModelCatalogSource, ModelInfo, ModelListOptions};
-use roci::provider::ProviderRegistry;
+use roci::provider::{ProviderFeatureReport, ProviderRegistry};
 use roci::types::Role;
 
 use crate::cli::{
@@ -43,25 +43,43 @@ pub(crate) async fn run_list(
     config: RociConfig,
     writer: &mut impl Write,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if args.matrix {
+        return write_feature_matrix(args, &registry, &config, writer);
+    }
     let options = ModelListOptions {
         provider_key: args.provider,
         ..ModelListOptions::default()
     };
     let catalog = registry.list_models(&config, &options).await?;
+    let needle = args.search.as_deref().map(str::to_lowercase);
+    let matches = |model: &&ModelInfo| {
+        needle.as_deref().is_none_or(|needle| {
+            model.model_id.to_lowercase().contains(needle)
+                || model
+                    .display_name
+                    .as_deref()
+                    .is_some_and(|name| name.to_lowercase().contains(needle))
+        })
+    };
+    let offline_skipped = registry
+        .provider_keys()
+        .into_iter()
+        .filter(|provider_key| registry.is_blocked_offline(provider_key, &config))
+        .collect::<Vec<_>>();
 
     if args.json {
-        writeln!(
-            writer,
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "models": catalog.models(),
-            }))?
-        )?;
+        let mut json = serde_json::json!({
+            "models": catalog.models().iter().filter(matches).collect::<Vec<_>>(),
+        });
+        if config.is_offline() {
+            json["offline_skipped"] = serde_json::json!(offline_skipped);
+        }
+        writeln!(writer, "{}", serde_json::to_string_pretty(&json)?)?;
         return Ok(());
     }
 
     writeln!(writer, "PROVIDER\tMODEL\tCONTEXT\tTOOLS\tVISION\tSOURCE")?;
-    let mut models = catalog.models().iter().collect::<Vec<_>>();
+    let mut models = catalog.models().iter().filter(matches).collect::<Vec<_>>();
     models.sort_by(|left, right| {
         left.provider_key
             .cmp(&right.provider_key)
@@ -79,10 +97,119 @@ pub(crate) async fn run_list(
             source_label(model)
         )?;
     }
+    let mut warnings = catalog
+        .models()
+        .iter()
+        .filter_map(|model| {
+            model
+                .metadata
+                .get("warning")
+                .and_then(|value| value.as_str())
+        })
+        .collect::<Vec<_>>();
+    warnings.sort_unstable();
+    warnings.dedup();
+    for warning in warnings {
+        writeln!(writer, "warning: {warning}")?;
+    }
+    if !offline_skipped.is_empty() {
+        writeln!(
+            writer,
+            "offline mode: skipped {} (requires network access)",
+            offline_skipped.join(", ")
+        )?;
+    }
 
     Ok(())
 }
 
+/// Print [`ProviderRegistry::feature_matrix`], one row per provider key.
+///
+/// Model columns read `yes` or `no` when all or none of the provider's
+/// known families have the capability, `some` otherwise, and `-` when the
+/// provider lists no families ahead of time.
+fn write_feature_matrix(
+    args: ModelsListArgs,
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    writer: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut matrix = registry.feature_matrix(config);
+    if let Some(provider_key) = args.provider.as_deref() {
+        if !registry.has_provider(provider_key) {
+            return Err(roci::error::RociError::ModelNotFound(format!(
+                "No provider factory registered for '{provider_key}'"
+            ))
+            .into());
+        }
+        matrix.retain(|report| report.provider_key == provider_key);
+    }
+
+    if args.json {
+        writeln!(
+            writer,
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "providers": matrix }))?
+        )?;
+        return Ok(());
+    }
+
+    writeln!(
+        writer,
+        "PROVIDER\tCREDENTIALS\tMODELS\tSTREAMING\tTOOLS\tPARALLEL_TOOLS\tVISION\tSTRUCTURED\tREASONING\tPROMPT_CACHING\tAUDIO\tEMBEDDINGS\tIMAGE_GEN"
+    )?;
+    for report in &matrix {
+        let families = |supports: fn(&roci::models::ModelCapabilities) -> bool| {
+            family_support(report, supports)
+        };
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            report.provider_key,
+            credentials_label(report),
+            report.model_families.len(),
+            families(|capabilities| capabilities.supports_streaming),
+            families(|capabilities| capabilities.supports_tools),
+            yes_no(report.features.parallel_tool_calls),
+            families(|capabilities| capabilities.supports_vision),
+            families(|capabilities| capabilities.supports_json_schema),
+            families(|capabilities| capabilities.supports_reasoning),
+            yes_no(report.features.prompt_caching),
+            yes_no(report.features.audio),
+            yes_no(report.features.embeddings),
+            yes_no(report.features.image_generation),
+        )?;
+    }
+    Ok(())
+}
+
+fn family_support(
+    report: &ProviderFeatureReport,
+    supports: fn(&roci::models::ModelCapabilities) -> bool,
+) -> &'static str {
+    let families = &report.model_families;
+    let supported = families
+        .iter()
+        .filter(|family| supports(&family.capabilities))
+        .count();
+    match supported {
+        _ if families.is_empty() => "-",
+        0 => "no",
+        count if count == families.len() => "yes",
+        _ => "some",
+    }
+}
+
+fn credentials_label(report: &ProviderFeatureReport) -> &'static str {
+    if !report.requires_credentials {
+        "not-needed"
+    } else if report.has_credentials {
+        "yes"
+    } else {
+        "missing"
+    }
+}
+
 pub(crate) async fn run_switch_smoke(
     args: ModelsSwitchSmokeArgs,
     registry: Arc<ProviderRegistry>,
@@ -204,7 +331,9 @@ mod tests {
     use super::*;
     use roci::error::RociError;
     use roci::models::{ModelCapabilities, ModelCatalog, ModelInputCapabilities, ModelPolicy};
-    use roci::provider::{ModelProvider, ProviderFactory};
+    use roci::provider::{
+        ModelFamily, ModelProvider, ProviderFactory, ProviderFeatureMetadata, ProviderFeatures,
+    };
     use std::sync::Mutex;
 
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -292,6 +421,7 @@ mod tests {
             source: ModelCatalogSource::Dynamic {
                 endpoint: "/sentinel/models".to_string(),
             },
+            pricing: None,
             metadata: Default::default(),
         }
     }
@@ -304,7 +434,9 @@ mod tests {
         run_list(
             ModelsListArgs {
                 provider: Some("sentinel".to_string()),
+                search: None,
                 json: true,
+                matrix: false,
             },
             registry,
             RociConfig::new().with_token_store(None),
@@ -333,7 +465,9 @@ mod tests {
         run_list(
             ModelsListArgs {
                 provider: None,
+                search: None,
                 json: false,
+                matrix: false,
             },
             registry,
             RociConfig::new().with_token_store(None),
@@ -356,6 +490,121 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn run_list_search_filters_models_case_insensitively() {
+        let (registry, _) = registry_with_stub();
+        let mut matched = Vec::new();
+        let mut unmatched = Vec::new();
+
+        run_list(
+            ModelsListArgs {
+                provider: None,
+                search: Some("SENTINEL".to_string()),
+                json: true,
+                matrix: false,
+            },
+            registry.clone(),
+            RociConfig::new().with_token_store(None),
+            &mut matched,
+        )
+        .await
+        .unwrap();
+        run_list(
+            ModelsListArgs {
+                provider: None,
+                search: Some("sonnet".to_string()),
+                json: true,
+                matrix: false,
+            },
+            registry,
+            RociConfig::new().with_token_store(None),
+            &mut unmatched,
+        )
+        .await
+        .unwrap();
+
+        let matched: serde_json::Value = serde_json::from_slice(&matched).unwrap();
+        let unmatched: serde_json::Value = serde_json::from_slice(&unmatched).unwrap();
+        assert_eq!(matched["models"][0]["model_id"], "sentinel-model");
+        assert_eq!(unmatched["models"], serde_json::json!([]));
+    }
+
+    struct RemoteFactory;
+
+    impl ProviderFactory for RemoteFactory {
+        fn provider_keys(&self) -> &[&str] {
+            &["remote"]
+        }
+
+        fn requires_credentials(&self, _provider_key: &str) -> bool {
+            false
+        }
+
+        fn resolved_base_url(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            _model_id: &str,
+        ) -> Option<String> {
+            Some("https://api.example.com/v1".to_string())
+        }
+
+        fn create(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            _model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            panic!("models list must not create providers")
+        }
+    }
+
+    #[tokio::test]
+    async fn run_list_annotates_providers_skipped_in_offline_mode() {
+        let factory = Arc::new(StubFactory::default());
+        let mut registry = ProviderRegistry::new();
+        registry.register(factory);
+        registry.register(Arc::new(RemoteFactory));
+        let config = RociConfig::new().with_token_store(None);
+        config.set_offline(true);
+        let mut human = Vec::new();
+        let mut json = Vec::new();
+        let registry = Arc::new(registry);
+
+        run_list(
+            ModelsListArgs {
+                provider: None,
+                search: None,
+                json: false,
+                matrix: false,
+            },
+            registry.clone(),
+            config.clone(),
+            &mut human,
+        )
+        .await
+        .unwrap();
+        run_list(
+            ModelsListArgs {
+                provider: None,
+                search: None,
+                json: true,
+                matrix: false,
+            },
+            registry,
+            config,
+            &mut json,
+        )
+        .await
+        .unwrap();
+
+        let human = String::from_utf8(human).unwrap();
+        assert!(human.contains("sentinel\tsentinel-model"));
+        assert!(human.contains("offline mode: skipped remote (requires network access)"));
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["offline_skipped"], serde_json::json!(["remote"]));
+    }
+
     #[tokio::test]
     async fn run_switch_smoke_exercises_runtime_model_switch() {
         let (registry, calls) = registry_with_stub();
@@ -389,7 +638,9 @@ mod tests {
         let err = run_list(
             ModelsListArgs {
                 provider: Some("missing".to_string()),
+                search: None,
                 json: false,
+                matrix: false,
             },
             registry,
             RociConfig::new().with_token_store(None),
@@ -404,4 +655,109 @@ mod tests {
         );
         assert!(calls.lock().expect("calls lock").is_empty());
     }
+
+    struct MatrixFactory;
+
+    impl ProviderFactory for MatrixFactory {
+        fn provider_keys(&self) -> &[&str] {
+            &["matrix"]
+        }
+
+        fn feature_metadata(&self, _provider_key: &str) -> ProviderFeatureMetadata {
+            let family = |name: &str, supports_reasoning| ModelFamily {
+                name: name.to_string(),
+                capabilities: ModelCapabilities {
+                    supports_streaming: true,
+                    supports_tools: true,
+                    supports_reasoning,
+                    ..ModelCapabilities::default()
+                },
+            };
+            ProviderFeatureMetadata {
+                model_families: vec![family("matrix-fast", false), family("matrix-deep", true)],
+                features: ProviderFeatures {
+                    parallel_tool_calls: true,
+                    embeddings: true,
+                    ..ProviderFeatures::default()
+                },
+            }
+        }
+
+        fn create(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            _model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            panic!("the feature matrix must not create providers")
+        }
+    }
+
+    fn matrix_registry() -> Arc<ProviderRegistry> {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(StubFactory::default()));
+        registry.register(Arc::new(MatrixFactory));
+        Arc::new(registry)
+    }
+
+    fn matrix_args(json: bool) -> ModelsListArgs {
+        ModelsListArgs {
+            provider: None,
+            search: None,
+            json,
+            matrix: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn run_list_matrix_renders_feature_table() {
+        let mut output = Vec::new();
+
+        run_list(
+            matrix_args(false),
+            matrix_registry(),
+            RociConfig::new().with_token_store(None),
+            &mut output,
+        )
+        .await
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "PROVIDER\tCREDENTIALS\tMODELS\tSTREAMING\tTOOLS\tPARALLEL_TOOLS\tVISION\tSTRUCTURED\tREASONING\tPROMPT_CACHING\tAUDIO\tEMBEDDINGS\tIMAGE_GEN",
+                "matrix\tmissing\t2\tyes\tyes\tyes\tno\tno\tsome\tno\tno\tyes\tno",
+                "sentinel\tnot-needed\t0\t-\t-\tno\t-\t-\t-\tno\tno\tno\tno",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn run_list_matrix_tracks_credentials_and_provider_filter() {
+        let config = RociConfig::new().with_token_store(None);
+        config.set_api_key("matrix", "secret".to_string());
+        let mut output = Vec::new();
+
+        run_list(
+            ModelsListArgs {
+                provider: Some("matrix".to_string()),
+                ..matrix_args(true)
+            },
+            matrix_registry(),
+            config,
+            &mut output,
+        )
+        .await
+        .unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let providers = json["providers"].as_array().expect("providers");
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0]["provider_key"], "matrix");
+        assert_eq!(providers[0]["has_credentials"], true);
+        assert_eq!(providers[0]["features"]["embeddings"], true);
+        assert_eq!(providers[0]["model_families"][1]["name"], "matrix-deep");
+    }
 }
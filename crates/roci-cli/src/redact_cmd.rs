@@ -0,0 +1,75 @@
+use std::io::Write;
+
+use roci::resource::ResourceSettingsLoader;
+use roci::util::redact::{RedactionReport, Redactor};
+
+use crate::cli::RedactArgs;
+
+pub async fn handle_redact(args: RedactArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let cwd = std::env::current_dir()?;
+    let settings = ResourceSettingsLoader::new().load(&cwd)?;
+    let redactor = Redactor::new().with_settings(&settings.redaction)?;
+    let input = std::fs::read_to_string(&args.input)
+        .map_err(|err| format!("failed to read {}: {err}", args.input.display()))?;
+
+    let (redacted, report) = redactor.redact_jsonl(&input)?;
+    match &args.output {
+        Some(path) => std::fs::write(path, redacted)
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))?,
+        None => std::io::stdout().write_all(redacted.as_bytes())?,
+    }
+    write_report(&report, &mut std::io::stderr())?;
+    Ok(())
+}
+
+/// Summarize a pass on stderr so stdout stays a clean transcript.
+fn write_report(report: &RedactionReport, writer: &mut impl Write) -> std::io::Result<()> {
+    if report.is_empty() {
+        return writeln!(writer, "No secrets found.");
+    }
+    writeln!(
+        writer,
+        "Redacted {} value(s), {} occurrence(s):",
+        report.findings.len(),
+        report.total_occurrences()
+    )?;
+    for finding in &report.findings {
+        writeln!(
+            writer,
+            "  {}  x{}",
+            finding.placeholder, finding.occurrences
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_each_placeholder_with_its_count() {
+        let (_, report) = Redactor::new()
+            .redact_jsonl(
+                "{\"content\":\"sk-abcdefghijklmnopqrstuvwx and sk-abcdefghijklmnopqrstuvwx\"}\n",
+            )
+            .unwrap();
+        let mut out = Vec::new();
+
+        write_report(&report, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Redacted 1 value(s), 2 occurrence(s):\n  [REDACTED:openai-key:1]  x2\n"
+        );
+    }
+
+    #[test]
+    fn empty_report_says_nothing_was_found() {
+        let mut out = Vec::new();
+
+        write_report(&RedactionReport::default(), &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "No secrets found.\n");
+    }
+}
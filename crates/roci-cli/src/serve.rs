@@ -0,0 +1,307 @@
+//! `roci-agent serve`: OpenAI-compatible chat completions over HTTP.
+//!
+//! Every request becomes an independent run on one shared [`LoopRunner`].
+//! Responses are either a single `chat.completion` body or, with
+//! `"stream": true`, `chat.completion.chunk` server-sent events.
+
+mod completion;
+mod openai;
+#[cfg(test)]
+mod tests;
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full, Limited, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{HeaderValue, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, RETRY_AFTER};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use hyper_util::server::graceful::GracefulShutdown;
+use roci::agent_loop::{LoopRunner, RunnerOptions};
+use roci::config::RociConfig;
+use roci::error::RociError;
+use roci::models::LanguageModel;
+use roci::resource::ResourceSettingsLoader;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+use crate::cli::ServeArgs;
+use openai::{error_body, ChatCompletionRequest};
+
+/// Environment variable holding the bearer token clients must present.
+const TOKEN_ENV: &str = "ROCI_SERVE_TOKEN";
+/// Largest accepted request body.
+const MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+type ServeBody = UnsyncBoxBody<Bytes, Infallible>;
+
+pub async fn handle_serve(args: ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let model = LanguageModel::from_str(&args.model)?;
+    let runner =
+        LoopRunner::with_registry(RociConfig::from_env(), Arc::new(roci::default_registry()))
+            .with_options(runner_options(&args))?;
+    let settings = ResourceSettingsLoader::new().load(&std::env::current_dir()?)?;
+    let token = serve_token(&args, std::env::var(TOKEN_ENV).ok(), settings.serve.token)?;
+    let state = ServeState::new(runner, model, args.model, token);
+
+    let server = Server::bind(SocketAddr::new(args.host, args.port), state).await?;
+    eprintln!(
+        "Serving {} at http://{}/v1/chat/completions{}",
+        server.state.model_label,
+        server.local_addr()?,
+        if server.state.token.is_some() {
+            ""
+        } else {
+            " (no auth; pass --token, set ROCI_SERVE_TOKEN, or set serve.token in settings to require a bearer token)"
+        }
+    );
+    server
+        .run(async {
+            let _ = tokio::signal::ctrl_c().await;
+            eprintln!("Shutting down; draining in-flight requests...");
+        })
+        .await;
+    Ok(())
+}
+
+/// The bearer token clients must present: `--token`, else `env_token`,
+/// else `settings_token` (`serve.token` in settings).
+///
+/// Serving a non-loopback address without a token is refused unless
+/// `--no-auth` opts out, since the server spends the configured provider keys.
+fn serve_token(
+    args: &ServeArgs,
+    env_token: Option<String>,
+    settings_token: Option<String>,
+) -> Result<Option<String>, String> {
+    if args.no_auth {
+        return Ok(None);
+    }
+    let token = [args.token.clone(), env_token, settings_token]
+        .into_iter()
+        .flatten()
+        .find(|token| !token.trim().is_empty());
+    if token.is_none() && !args.host.is_loopback() {
+        return Err(format!(
+            "refusing to serve {} without auth: pass --token, set {TOKEN_ENV}, set serve.token in settings, or pass --no-auth",
+            args.host
+        ));
+    }
+    Ok(token)
+}
+
+fn runner_options(args: &ServeArgs) -> RunnerOptions {
+    let mut options = RunnerOptions::new().with_queue_capacity(args.queue_capacity);
+    if let Some(max) = args.max_concurrent_runs {
+        options = options.with_max_concurrent_runs(max);
+    }
+    if let Some(secs) = args.queue_timeout_secs {
+        options = options.with_queue_timeout(std::time::Duration::from_secs(secs));
+    }
+    options
+}
+
+/// State shared by every connection.
+pub(crate) struct ServeState {
+    runner: LoopRunner,
+    model: LanguageModel,
+    model_label: String,
+    token: Option<String>,
+}
+
+impl ServeState {
+    pub(crate) fn new(
+        runner: LoopRunner,
+        model: LanguageModel,
+        model_label: impl Into<String>,
+        token: Option<String>,
+    ) -> Self {
+        Self {
+            runner,
+            model,
+            model_label: model_label.into(),
+            token,
+        }
+    }
+
+    fn authorized(&self, request: &Request<Incoming>) -> bool {
+        let Some(expected) = &self.token else {
+            return true;
+        };
+        request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|provided| constant_time_eq(provided.as_bytes(), expected.as_bytes()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Bound listener serving chat completions until shut down.
+pub(crate) struct Server {
+    listener: TcpListener,
+    state: Arc<ServeState>,
+}
+
+impl Server {
+    pub(crate) async fn bind(addr: SocketAddr, state: ServeState) -> std::io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            state: Arc::new(state),
+        })
+    }
+
+    pub(crate) fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept connections until `shutdown` resolves, then stop accepting and
+    /// wait for in-flight requests, including open streams, to finish.
+    pub(crate) async fn run(self, shutdown: impl Future<Output = ()>) {
+        let graceful = GracefulShutdown::new();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let Ok((stream, _)) = accepted else { continue };
+                    let state = self.state.clone();
+                    let service = service_fn(move |request| route(state.clone(), request));
+                    let connection = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service);
+                    let connection = graceful.watch(connection);
+                    tokio::spawn(async move {
+                        let _ = connection.await;
+                    });
+                }
+                _ = &mut shutdown => break,
+            }
+        }
+        drop(self.listener);
+        graceful.shutdown().await;
+    }
+}
+
+async fn route(
+    state: Arc<ServeState>,
+    request: Request<Incoming>,
+) -> Result<Response<ServeBody>, Infallible> {
+    if !state.authorized(&request) {
+        return Ok(json_response(
+            StatusCode::UNAUTHORIZED,
+            &error_body("missing or invalid bearer token", "invalid_request_error"),
+        ));
+    }
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::POST, "/v1/chat/completions") => chat_completions(&state, request).await,
+        (&Method::GET, "/v1/models") => json_response(
+            StatusCode::OK,
+            &json!({
+                "object": "list",
+                "data": [{ "id": state.model_label, "object": "model", "owned_by": "roci" }],
+            }),
+        ),
+        _ => json_response(
+            StatusCode::NOT_FOUND,
+            &error_body("unknown route", "invalid_request_error"),
+        ),
+    };
+    Ok(response)
+}
+
+async fn chat_completions(state: &ServeState, request: Request<Incoming>) -> Response<ServeBody> {
+    let body = match Limited::new(request.into_body(), MAX_REQUEST_BYTES)
+        .collect()
+        .await
+    {
+        Ok(body) => body.to_bytes(),
+        Err(err) => {
+            return json_response(
+                StatusCode::BAD_REQUEST,
+                &error_body(
+                    &format!("failed to read body: {err}"),
+                    "invalid_request_error",
+                ),
+            );
+        }
+    };
+    let request: ChatCompletionRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return json_response(
+                StatusCode::BAD_REQUEST,
+                &error_body(&format!("invalid request: {err}"), "invalid_request_error"),
+            );
+        }
+    };
+
+    let completion = match completion::start(state, &request).await {
+        Ok(completion) => completion,
+        Err(err) => return error_response(&err),
+    };
+    if request.stream {
+        return sse_response(completion.stream(request.include_usage()));
+    }
+    match completion.respond().await {
+        Ok(body) => json_response(StatusCode::OK, &body),
+        Err(body) => json_response(StatusCode::BAD_GATEWAY, &body),
+    }
+}
+
+fn error_response(err: &RociError) -> Response<ServeBody> {
+    if let RociError::RateLimited { retry_after_ms } = err {
+        // The runner's admission queue is full.
+        let mut response = json_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &error_body("server is at capacity; retry later", "rate_limit_exceeded"),
+        );
+        if let Some(ms) = retry_after_ms {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(ms.div_ceil(1000).max(1)));
+        }
+        return response;
+    }
+    let status = match err {
+        RociError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let kind = if status == StatusCode::BAD_REQUEST {
+        "invalid_request_error"
+    } else {
+        "server_error"
+    };
+    json_response(status, &error_body(&err.to_string(), kind))
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<ServeBody> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())).boxed_unsync());
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn sse_response(frames: tokio::sync::mpsc::Receiver<Bytes>) -> Response<ServeBody> {
+    let stream = futures::stream::unfold(frames, |mut frames| async move {
+        let frame = frames.recv().await?;
+        Some((Ok::<_, Infallible>(Frame::data(frame)), frames))
+    });
+    let mut response = Response::new(StreamBody::new(stream).boxed_unsync());
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
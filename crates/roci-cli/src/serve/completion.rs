@@ -0,0 +1,231 @@
+//! One chat completion: a run on the shared runner, observed through its
+//! event stream and rendered as an OpenAI response or SSE chunks.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use roci::agent_loop::{
+    ApprovalDecision, ApprovalHandler, ApprovalPolicy, RunEvent, RunEventPayload, RunHandle,
+    RunRequest, RunResult, RunStatus, Runner,
+};
+use roci::error::RociError;
+use roci::types::AgentToolCall;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use super::openai::{error_body, tool_call_json, ChatCompletionRequest, CompletionMeta};
+use super::ServeState;
+
+/// Started run plus the channels needed to observe it.
+pub(super) struct Completion {
+    meta: CompletionMeta,
+    handle: RunHandle,
+    events: mpsc::UnboundedReceiver<RunEvent>,
+    client_calls: Arc<AtomicBool>,
+}
+
+/// Assistant output accumulated from run events.
+#[derive(Default)]
+struct Transcript {
+    text: String,
+    calls: Vec<AgentToolCall>,
+}
+
+impl Transcript {
+    /// Record `event`, returning the text it streamed, if any.
+    fn observe(&mut self, event: RunEvent) -> Option<String> {
+        match event.payload {
+            RunEventPayload::AssistantDelta { text } => {
+                self.text.push_str(&text);
+                Some(text)
+            }
+            RunEventPayload::ToolCallStarted { call, .. } => {
+                if !self.calls.iter().any(|known| known.id == call.id) {
+                    self.calls.push(call);
+                }
+                None
+            }
+            RunEventPayload::ToolCallDelta { call_id, delta } => {
+                if let Some(call) = self.calls.iter_mut().find(|call| call.id == call_id) {
+                    call.arguments = delta;
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Start a run for `request` against the configured model.
+///
+/// Client-supplied tools are registered so the model can call them, and the
+/// approval handler cancels the run at the first call so the client can
+/// execute it and send the result in a follow-up request.
+pub(super) async fn start(
+    state: &ServeState,
+    request: &ChatCompletionRequest,
+) -> Result<Completion, RociError> {
+    let messages = request.run_messages()?;
+    let settings = request.settings()?;
+    let tools = request.client_tools()?;
+
+    let (event_tx, events) = mpsc::unbounded_channel();
+    let client_calls = Arc::new(AtomicBool::new(false));
+    let approval_flag = client_calls.clone();
+    let approval_handler: ApprovalHandler = Arc::new(move |_request| {
+        approval_flag.store(true, Ordering::SeqCst);
+        Box::pin(async { ApprovalDecision::Cancel })
+    });
+
+    let mut run = RunRequest::new(state.model.clone(), messages)
+        .with_tools(tools)
+        .with_event_sink(Arc::new(move |event| {
+            let _ = event_tx.send(event);
+        }))
+        .with_approval_policy(ApprovalPolicy::ask())
+        .with_approval_handler(approval_handler);
+    run.settings = settings;
+
+    let handle = state.runner.start(run).await?;
+    Ok(Completion {
+        meta: CompletionMeta {
+            id: format!("chatcmpl-{}", handle.run_id()),
+            created: chrono::Utc::now().timestamp(),
+            model: state.model_label.clone(),
+        },
+        handle,
+        events,
+        client_calls,
+    })
+}
+
+/// Why a run ended, in OpenAI terms.
+fn finish_reason(
+    result: &RunResult,
+    transcript: &Transcript,
+    client_calls: bool,
+) -> Result<&'static str, String> {
+    if client_calls && !transcript.calls.is_empty() {
+        return Ok("tool_calls");
+    }
+    match result.status {
+        RunStatus::Completed => Ok("stop"),
+        RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded => Ok("length"),
+        RunStatus::Canceled => Err("run canceled".to_string()),
+        RunStatus::Failed | RunStatus::Queued | RunStatus::Running => Err(result
+            .error
+            .clone()
+            .unwrap_or_else(|| "run failed".to_string())),
+    }
+}
+
+impl Completion {
+    /// Wait for the run and build a `chat.completion` body.
+    ///
+    /// # Errors
+    ///
+    /// Returns an OpenAI error body when the run failed.
+    pub(super) async fn respond(mut self) -> Result<Value, Value> {
+        let result = self.handle.wait().await;
+        let mut transcript = Transcript::default();
+        while let Ok(event) = self.events.try_recv() {
+            transcript.observe(event);
+        }
+        let reason = finish_reason(
+            &result,
+            &transcript,
+            self.client_calls.load(Ordering::SeqCst),
+        )
+        .map_err(|message| error_body(&message, "server_error"))?;
+        let calls = if reason == "tool_calls" {
+            transcript.calls.as_slice()
+        } else {
+            &[]
+        };
+        Ok(self
+            .meta
+            .response(&transcript.text, calls, reason, result.usage_delta.as_ref()))
+    }
+
+    /// Stream the run as SSE frames.
+    ///
+    /// Frames are produced by a task that owns the run; the run is aborted
+    /// if the receiver is dropped before it finishes.
+    pub(super) fn stream(self, include_usage: bool) -> mpsc::Receiver<Bytes> {
+        let (frames, rx) = mpsc::channel(64);
+        tokio::spawn(self.drive(frames, include_usage));
+        rx
+    }
+
+    async fn drive(self, frames: mpsc::Sender<Bytes>, include_usage: bool) {
+        let Completion {
+            meta,
+            mut handle,
+            mut events,
+            client_calls,
+        } = self;
+        let mut abort = handle.take_abort_sender();
+        let mut transcript = Transcript::default();
+        let mut connected = send(
+            &frames,
+            &meta.chunk(json!({ "role": "assistant", "content": "" }), None),
+        )
+        .await;
+
+        let wait = handle.wait();
+        tokio::pin!(wait);
+        let result = loop {
+            tokio::select! {
+                result = &mut wait => break result,
+                Some(event) = events.recv() => {
+                    let Some(text) = transcript.observe(event) else { continue };
+                    if connected {
+                        connected = send(&frames, &content_chunk(&meta, text)).await;
+                    }
+                    if !connected {
+                        if let Some(abort) = abort.take() {
+                            let _ = abort.send(());
+                        }
+                    }
+                }
+            }
+        };
+        while let Ok(event) = events.try_recv() {
+            if let Some(text) = transcript.observe(event) {
+                send(&frames, &content_chunk(&meta, text)).await;
+            }
+        }
+
+        match finish_reason(&result, &transcript, client_calls.load(Ordering::SeqCst)) {
+            Ok(reason) => {
+                if reason == "tool_calls" {
+                    for (index, call) in transcript.calls.iter().enumerate() {
+                        let delta = json!({ "tool_calls": [tool_call_json(call, Some(index))] });
+                        send(&frames, &meta.chunk(delta, None)).await;
+                    }
+                }
+                send(&frames, &meta.chunk(json!({}), Some(reason))).await;
+                if include_usage {
+                    send(&frames, &meta.usage_chunk(result.usage_delta.as_ref())).await;
+                }
+            }
+            Err(message) => {
+                send(&frames, &error_body(&message, "server_error")).await;
+            }
+        }
+        let _ = frames.send(Bytes::from_static(b"data: [DONE]\n\n")).await;
+    }
+}
+
+fn content_chunk(meta: &CompletionMeta, text: String) -> Value {
+    meta.chunk(json!({ "content": text }), None)
+}
+
+/// Send one `data:` frame; `false` once the client has gone away.
+async fn send(frames: &mpsc::Sender<Bytes>, value: &Value) -> bool {
+    frames
+        .send(Bytes::from(format!("data: {value}\n\n")))
+        .await
+        .is_ok()
+}
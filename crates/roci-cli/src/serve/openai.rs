@@ -0,0 +1,477 @@
+//! OpenAI chat completions wire format and its translation into runs.
+
+use std::sync::Arc;
+
+use roci::error::RociError;
+use roci::tools::{AgentTool, AgentToolParameters, Tool};
+use roci::types::{
+    AgentToolCall, ContentPart, GenerationSettings, ModelMessage, ToolChoice, Usage,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Body of `POST /v1/chat/completions`. Unknown fields are ignored.
+#[derive(Debug, Deserialize)]
+pub(super) struct ChatCompletionRequest {
+    pub(super) messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub(super) stream: bool,
+    #[serde(default)]
+    stream_options: Option<StreamOptions>,
+    #[serde(default)]
+    temperature: Option<f64>,
+    #[serde(default)]
+    top_p: Option<f64>,
+    #[serde(default)]
+    max_tokens: Option<u32>,
+    #[serde(default)]
+    max_completion_tokens: Option<u32>,
+    #[serde(default)]
+    stop: Option<StopSequences>,
+    #[serde(default)]
+    presence_penalty: Option<f64>,
+    #[serde(default)]
+    frequency_penalty: Option<f64>,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    tools: Vec<ChatTool>,
+    #[serde(default)]
+    tool_choice: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamOptions {
+    #[serde(default)]
+    include_usage: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StopSequences {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<MessageContent>,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+    #[serde(default)]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MessageContent {
+    Text(String),
+    Parts(Vec<MessagePart>),
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagePart {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatToolCall {
+    id: String,
+    function: FunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatTool {
+    #[serde(rename = "type")]
+    kind: String,
+    function: FunctionDefinition,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDefinition {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    parameters: Option<Value>,
+}
+
+impl ChatCompletionRequest {
+    /// Whether a streamed response should end with a usage chunk.
+    pub(super) fn include_usage(&self) -> bool {
+        self.stream_options
+            .as_ref()
+            .is_some_and(|options| options.include_usage)
+    }
+
+    /// Conversation as run messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for unknown roles, non-text
+    /// content parts, and tool messages without `tool_call_id`.
+    pub(super) fn run_messages(&self) -> Result<Vec<ModelMessage>, RociError> {
+        if self.messages.is_empty() {
+            return Err(invalid("messages must not be empty"));
+        }
+        self.messages.iter().map(ChatMessage::to_model).collect()
+    }
+
+    /// Sampling settings carried by the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for an unrecognized
+    /// `tool_choice`.
+    pub(super) fn settings(&self) -> Result<GenerationSettings, RociError> {
+        Ok(GenerationSettings {
+            max_tokens: self.max_completion_tokens.or(self.max_tokens),
+            temperature: self.temperature,
+            top_p: self.top_p,
+            stop_sequences: self.stop.as_ref().map(|stop| match stop {
+                StopSequences::One(stop) => vec![stop.clone()],
+                StopSequences::Many(stops) => stops.clone(),
+            }),
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+            seed: self.seed,
+            tool_choice: self.tool_choice.as_ref().map(tool_choice).transpose()?,
+            user: self.user.clone(),
+            ..GenerationSettings::default()
+        })
+    }
+
+    /// Tools the client executes itself.
+    ///
+    /// They are advertised to the model but never run on the server: a call
+    /// to one ends the completion with `finish_reason: "tool_calls"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for non-function tools.
+    pub(super) fn client_tools(&self) -> Result<Vec<Arc<dyn Tool>>, RociError> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                if tool.kind != "function" {
+                    return Err(invalid(format!("unsupported tool type '{}'", tool.kind)));
+                }
+                let function = &tool.function;
+                let parameters = function
+                    .parameters
+                    .clone()
+                    .map(AgentToolParameters::from_schema)
+                    .unwrap_or_else(AgentToolParameters::empty);
+                let name = function.name.clone();
+                let tool: Arc<dyn Tool> = Arc::new(AgentTool::new(
+                    function.name.clone(),
+                    function.description.clone().unwrap_or_default(),
+                    parameters,
+                    move |_args, _ctx| {
+                        let name = name.clone();
+                        async move {
+                            Err(RociError::ToolExecution {
+                                tool_name: name,
+                                message: "client-side tool cannot run on the server".to_string(),
+                            })
+                        }
+                    },
+                ));
+                Ok(tool)
+            })
+            .collect()
+    }
+}
+
+impl ChatMessage {
+    fn to_model(&self) -> Result<ModelMessage, RociError> {
+        let text = self.text()?;
+        match self.role.as_str() {
+            "system" | "developer" => Ok(ModelMessage::system(text)),
+            "user" => Ok(ModelMessage::user(text)),
+            "assistant" => {
+                let mut message = ModelMessage::assistant(text.clone());
+                message.content.clear();
+                if !text.is_empty() {
+                    message.content.push(ContentPart::Text { text });
+                }
+                for call in &self.tool_calls {
+                    message.content.push(ContentPart::ToolCall(AgentToolCall {
+                        id: call.id.clone(),
+                        name: call.function.name.clone(),
+                        arguments: serde_json::from_str(&call.function.arguments)
+                            .unwrap_or_else(|_| Value::String(call.function.arguments.clone())),
+                        called_as: None,
+                        recipient: None,
+                    }));
+                }
+                Ok(message)
+            }
+            "tool" => {
+                let call_id = self
+                    .tool_call_id
+                    .clone()
+                    .ok_or_else(|| invalid("tool messages require tool_call_id"))?;
+                Ok(ModelMessage::tool_result(
+                    call_id,
+                    Value::String(text),
+                    false,
+                ))
+            }
+            other => Err(invalid(format!("unsupported message role '{other}'"))),
+        }
+    }
+
+    fn text(&self) -> Result<String, RociError> {
+        match &self.content {
+            None => Ok(String::new()),
+            Some(MessageContent::Text(text)) => Ok(text.clone()),
+            Some(MessageContent::Parts(parts)) => parts
+                .iter()
+                .map(|part| match (part.kind.as_str(), &part.text) {
+                    ("text", Some(text)) => Ok(text.as_str()),
+                    (kind, _) => Err(invalid(format!("unsupported content part '{kind}'"))),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|texts| texts.concat()),
+        }
+    }
+}
+
+fn tool_choice(value: &Value) -> Result<ToolChoice, RociError> {
+    match value {
+        Value::String(choice) => match choice.as_str() {
+            "auto" => Ok(ToolChoice::Auto),
+            "none" => Ok(ToolChoice::None),
+            "required" => Ok(ToolChoice::Required),
+            other => Err(invalid(format!("unsupported tool_choice '{other}'"))),
+        },
+        Value::Object(_) => value
+            .pointer("/function/name")
+            .and_then(Value::as_str)
+            .map(|name| ToolChoice::Function(name.to_string()))
+            .ok_or_else(|| invalid("tool_choice object requires function.name")),
+        _ => Err(invalid("tool_choice must be a string or object")),
+    }
+}
+
+fn invalid(message: impl Into<String>) -> RociError {
+    RociError::InvalidArgument(message.into())
+}
+
+/// Identity shared by every chunk of one completion.
+#[derive(Debug, Clone)]
+pub(super) struct CompletionMeta {
+    pub(super) id: String,
+    pub(super) created: i64,
+    pub(super) model: String,
+}
+
+impl CompletionMeta {
+    /// `chat.completion` response body.
+    pub(super) fn response(
+        &self,
+        text: &str,
+        calls: &[AgentToolCall],
+        finish_reason: &str,
+        usage: Option<&Usage>,
+    ) -> Value {
+        let mut message = json!({
+            "role": "assistant",
+            "content": if text.is_empty() && !calls.is_empty() { Value::Null } else { json!(text) },
+        });
+        if !calls.is_empty() {
+            message["tool_calls"] = calls
+                .iter()
+                .map(|call| tool_call_json(call, None))
+                .collect();
+        }
+        json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "message": message, "finish_reason": finish_reason }],
+            "usage": usage_json(usage),
+        })
+    }
+
+    /// `chat.completion.chunk` carrying `delta`.
+    pub(super) fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    }
+
+    /// Trailing chunk for `stream_options.include_usage`.
+    pub(super) fn usage_chunk(&self, usage: Option<&Usage>) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [],
+            "usage": usage_json(usage),
+        })
+    }
+}
+
+/// Tool call as OpenAI serializes it; `index` is set in stream deltas.
+pub(super) fn tool_call_json(call: &AgentToolCall, index: Option<usize>) -> Value {
+    let arguments = match &call.arguments {
+        Value::String(arguments) => arguments.clone(),
+        arguments => arguments.to_string(),
+    };
+    let mut value = json!({
+        "id": call.id,
+        "type": "function",
+        "function": { "name": call.name, "arguments": arguments },
+    });
+    if let Some(index) = index {
+        value["index"] = json!(index);
+    }
+    value
+}
+
+fn usage_json(usage: Option<&Usage>) -> Value {
+    let usage = usage.cloned().unwrap_or_default();
+    json!({
+        "prompt_tokens": usage.input_tokens,
+        "completion_tokens": usage.output_tokens,
+        "total_tokens": usage.total_tokens,
+    })
+}
+
+/// OpenAI-style error body.
+pub(super) fn error_body(message: &str, kind: &str) -> Value {
+    json!({ "error": { "message": message, "type": kind, "param": null, "code": null } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci::types::Role;
+
+    fn request(body: Value) -> ChatCompletionRequest {
+        serde_json::from_value(body).expect("valid request")
+    }
+
+    #[test]
+    fn messages_translate_roles_tool_calls_and_results() {
+        let messages = request(json!({
+            "messages": [
+                { "role": "system", "content": "be brief" },
+                { "role": "user", "content": [{ "type": "text", "text": "weather?" }] },
+                {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+                    }]
+                },
+                { "role": "tool", "tool_call_id": "call_1", "content": "sunny" }
+            ]
+        }))
+        .run_messages()
+        .unwrap();
+
+        let roles = messages.iter().map(|m| m.role).collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            vec![Role::System, Role::User, Role::Assistant, Role::Tool]
+        );
+        assert_eq!(messages[1].text(), "weather?");
+        let ContentPart::ToolCall(call) = &messages[2].content[0] else {
+            panic!("expected tool call part");
+        };
+        assert_eq!(call.arguments, json!({ "city": "Paris" }));
+        assert_eq!(messages[2].content.len(), 1);
+        let ContentPart::ToolResult(result) = &messages[3].content[0] else {
+            panic!("expected tool result part");
+        };
+        assert_eq!(result.tool_call_id, "call_1");
+        assert_eq!(result.result, json!("sunny"));
+    }
+
+    #[test]
+    fn unsupported_roles_and_parts_are_invalid_arguments() {
+        for message in [
+            json!({ "role": "narrator", "content": "x" }),
+            json!({ "role": "user", "content": [{ "type": "image_url" }] }),
+            json!({ "role": "tool", "content": "orphan" }),
+        ] {
+            let err = request(json!({ "messages": [message] }))
+                .run_messages()
+                .unwrap_err();
+            assert!(matches!(err, RociError::InvalidArgument(_)), "{err}");
+        }
+    }
+
+    #[test]
+    fn settings_map_sampling_fields_and_tool_choice() {
+        let settings = request(json!({
+            "messages": [],
+            "temperature": 0.2,
+            "max_tokens": 50,
+            "max_completion_tokens": 64,
+            "stop": "END",
+            "tool_choice": { "type": "function", "function": { "name": "get_weather" } }
+        }))
+        .settings()
+        .unwrap();
+
+        assert_eq!(settings.temperature, Some(0.2));
+        assert_eq!(settings.max_tokens, Some(64));
+        assert_eq!(settings.stop_sequences, Some(vec!["END".to_string()]));
+        assert!(matches!(
+            settings.tool_choice,
+            Some(ToolChoice::Function(ref name)) if name == "get_weather"
+        ));
+    }
+
+    #[test]
+    fn response_serializes_tool_calls_with_string_arguments() {
+        let meta = CompletionMeta {
+            id: "chatcmpl-1".to_string(),
+            created: 1,
+            model: "stub:echo".to_string(),
+        };
+        let call = AgentToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: json!({ "city": "Paris" }),
+            called_as: None,
+            recipient: None,
+        };
+
+        let response = meta.response("", &[call], "tool_calls", None);
+
+        let message = &response["choices"][0]["message"];
+        assert_eq!(message["content"], Value::Null);
+        assert_eq!(
+            message["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Paris\"}"
+        );
+        assert_eq!(response["usage"]["total_tokens"], 0);
+    }
+}
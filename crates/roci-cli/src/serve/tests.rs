@@ -0,0 +1,565 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use roci::models::ModelCapabilities;
+use roci::provider::{
+    ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
+};
+use roci::types::{AgentToolCall, Role, StreamEventType, TextStreamDelta, Usage};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use super::*;
+
+/// Echoes the last user message; calls `get_weather` when the client
+/// supplied tools and asked about the weather; delays when asked to be slow.
+struct EchoProvider {
+    capabilities: ModelCapabilities,
+}
+
+fn delta(event_type: StreamEventType, text: &str) -> TextStreamDelta {
+    TextStreamDelta {
+        text: text.to_string(),
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+#[async_trait]
+impl ModelProvider for EchoProvider {
+    fn provider_name(&self) -> &str {
+        "stub"
+    }
+
+    fn model_id(&self) -> &str {
+        "echo"
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    async fn generate_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        Err(RociError::UnsupportedOperation(
+            "stream-only stub provider".to_string(),
+        ))
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        let prompt = request
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::User)
+            .map(|message| message.text())
+            .unwrap_or_default();
+        let has_tools = request
+            .tools
+            .as_ref()
+            .is_some_and(|tools| !tools.is_empty());
+        let mut deltas = Vec::new();
+        if has_tools && prompt.contains("weather") {
+            let mut call = delta(StreamEventType::ToolCallDelta, "");
+            call.tool_call = Some(AgentToolCall {
+                id: "call_weather".to_string(),
+                name: "get_weather".to_string(),
+                arguments: json!({ "city": "Paris" }),
+                called_as: None,
+                recipient: None,
+            });
+            deltas.push(call);
+        } else {
+            deltas.push(delta(StreamEventType::TextDelta, "echo: "));
+            deltas.push(delta(StreamEventType::TextDelta, &prompt));
+        }
+        let mut done = delta(StreamEventType::Done, "");
+        done.usage = Some(Usage {
+            input_tokens: 3,
+            output_tokens: 2,
+            total_tokens: 5,
+            ..Usage::default()
+        });
+        deltas.push(done);
+
+        let pause = if prompt.contains("slow") {
+            Duration::from_millis(300)
+        } else {
+            Duration::ZERO
+        };
+        Ok(stream::iter(deltas)
+            .then(move |delta| async move {
+                tokio::time::sleep(pause).await;
+                Ok(delta)
+            })
+            .boxed())
+    }
+}
+
+struct EchoFactory;
+
+impl ProviderFactory for EchoFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["stub"]
+    }
+
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(EchoProvider {
+            capabilities: ModelCapabilities {
+                supports_tools: true,
+                ..ModelCapabilities::default()
+            },
+        }))
+    }
+}
+
+struct TestServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl TestServer {
+    async fn start(token: Option<&str>) -> Self {
+        Self::start_with_options(token, RunnerOptions::default()).await
+    }
+
+    async fn start_with_options(token: Option<&str>, options: RunnerOptions) -> Self {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(EchoFactory));
+        let runner =
+            LoopRunner::with_registry(RociConfig::new().with_token_store(None), Arc::new(registry))
+                .with_options(options)
+                .unwrap();
+        let model = LanguageModel::from_str("stub:echo").unwrap();
+        let state = ServeState::new(runner, model, "stub:echo", token.map(str::to_string));
+        let server = Server::bind("127.0.0.1:0".parse().unwrap(), state)
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        let task = tokio::spawn(server.run(async {
+            let _ = shutdown_rx.await;
+        }));
+        Self {
+            addr,
+            shutdown: Some(shutdown),
+            task,
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}/v1/chat/completions", self.addr)
+    }
+
+    async fn post(&self, body: Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(self.url())
+            .json(&body)
+            .send()
+            .await
+            .unwrap()
+    }
+}
+
+/// `data:` payloads of an SSE body, `[DONE]` included.
+fn sse_events(body: &str) -> Vec<String> {
+    body.split("\n\n")
+        .filter_map(|frame| frame.strip_prefix("data: "))
+        .map(str::to_string)
+        .collect()
+}
+
+fn chunks(body: &str) -> Vec<Value> {
+    sse_events(body)
+        .iter()
+        .filter(|event| event.as_str() != "[DONE]")
+        .map(|event| serde_json::from_str(event).unwrap())
+        .collect()
+}
+
+fn weather_tools() -> Value {
+    json!([{
+        "type": "function",
+        "function": {
+            "name": "get_weather",
+            "description": "Current weather for a city",
+            "parameters": {
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+                "required": ["city"]
+            }
+        }
+    }])
+}
+
+#[tokio::test]
+async fn non_streaming_completion_returns_text_finish_reason_and_usage() {
+    let server = TestServer::start(None).await;
+
+    let response = server
+        .post(json!({
+            "model": "ignored",
+            "messages": [
+                { "role": "system", "content": "be brief" },
+                { "role": "user", "content": "hello" }
+            ],
+            "temperature": 0.1
+        }))
+        .await;
+
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["object"], "chat.completion");
+    assert_eq!(body["model"], "stub:echo");
+    assert_eq!(body["choices"][0]["message"]["role"], "assistant");
+    assert_eq!(body["choices"][0]["message"]["content"], "echo: hello");
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(body["usage"]["prompt_tokens"], 3);
+    assert_eq!(body["usage"]["completion_tokens"], 2);
+    assert_eq!(body["usage"]["total_tokens"], 5);
+}
+
+#[tokio::test]
+async fn streaming_completion_emits_chunks_usage_and_done() {
+    let server = TestServer::start(None).await;
+
+    let response = server
+        .post(json!({
+            "messages": [{ "role": "user", "content": "hi" }],
+            "stream": true,
+            "stream_options": { "include_usage": true }
+        }))
+        .await;
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()[CONTENT_TYPE.as_str()],
+        "text/event-stream"
+    );
+    let body = response.text().await.unwrap();
+    assert_eq!(sse_events(&body).last().unwrap(), "[DONE]");
+    let chunks = chunks(&body);
+    assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+    let text = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect::<String>();
+    assert_eq!(text, "echo: hi");
+    let finish = chunks
+        .iter()
+        .find_map(|chunk| chunk["choices"][0]["finish_reason"].as_str())
+        .unwrap();
+    assert_eq!(finish, "stop");
+    let usage = chunks.last().unwrap();
+    assert_eq!(usage["choices"], json!([]));
+    assert_eq!(usage["usage"]["total_tokens"], 5);
+}
+
+#[tokio::test]
+async fn client_tools_are_returned_as_tool_calls_without_running() {
+    let server = TestServer::start(None).await;
+
+    let response = server
+        .post(json!({
+            "messages": [{ "role": "user", "content": "what's the weather?" }],
+            "tools": weather_tools()
+        }))
+        .await;
+
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    let choice = &body["choices"][0];
+    assert_eq!(choice["finish_reason"], "tool_calls");
+    assert_eq!(choice["message"]["content"], Value::Null);
+    let call = &choice["message"]["tool_calls"][0];
+    assert_eq!(call["id"], "call_weather");
+    assert_eq!(call["type"], "function");
+    assert_eq!(call["function"]["name"], "get_weather");
+    let arguments: Value =
+        serde_json::from_str(call["function"]["arguments"].as_str().unwrap()).unwrap();
+    assert_eq!(arguments, json!({ "city": "Paris" }));
+}
+
+#[tokio::test]
+async fn streaming_client_tool_calls_arrive_as_tool_call_deltas() {
+    let server = TestServer::start(None).await;
+
+    let body = server
+        .post(json!({
+            "messages": [{ "role": "user", "content": "weather in Paris" }],
+            "tools": weather_tools(),
+            "stream": true
+        }))
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    let chunks = chunks(&body);
+    let call = chunks
+        .iter()
+        .find_map(|chunk| chunk["choices"][0]["delta"]["tool_calls"].get(0).cloned())
+        .expect("tool call delta");
+    assert_eq!(call["index"], 0);
+    assert_eq!(call["function"]["name"], "get_weather");
+    let finish = chunks
+        .iter()
+        .find_map(|chunk| chunk["choices"][0]["finish_reason"].as_str())
+        .unwrap();
+    assert_eq!(finish, "tool_calls");
+    assert_eq!(sse_events(&body).last().unwrap(), "[DONE]");
+}
+
+#[tokio::test]
+async fn tool_results_from_the_client_continue_the_conversation() {
+    let server = TestServer::start(None).await;
+
+    let response = server
+        .post(json!({
+            "messages": [
+                { "role": "user", "content": "weather?" },
+                {
+                    "role": "assistant",
+                    "tool_calls": [{
+                        "id": "call_weather",
+                        "type": "function",
+                        "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+                    }]
+                },
+                { "role": "tool", "tool_call_id": "call_weather", "content": "sunny" },
+                { "role": "user", "content": "thanks" }
+            ],
+            "tools": weather_tools()
+        }))
+        .await;
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(body["choices"][0]["message"]["content"], "echo: thanks");
+}
+
+#[tokio::test]
+async fn bearer_token_is_required_when_configured() {
+    let server = TestServer::start(Some("s3cret")).await;
+    let client = reqwest::Client::new();
+    let body = json!({ "messages": [{ "role": "user", "content": "hi" }] });
+
+    let missing = client.post(server.url()).json(&body).send().await.unwrap();
+    assert_eq!(missing.status(), 401);
+    let error: Value = missing.json().await.unwrap();
+    assert_eq!(error["error"]["type"], "invalid_request_error");
+
+    let wrong = client
+        .post(server.url())
+        .bearer_auth("nope")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(wrong.status(), 401);
+
+    let ok = client
+        .post(server.url())
+        .bearer_auth("s3cret")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(ok.status(), 200);
+}
+
+fn serve_args(args: &[&str]) -> ServeArgs {
+    use clap::Parser;
+    ServeArgs::parse_from(std::iter::once("serve").chain(args.iter().copied()))
+}
+
+#[test]
+fn non_loopback_hosts_require_a_token_or_no_auth() {
+    let public = serve_args(&["--host", "0.0.0.0"]);
+    let error = serve_token(&public, None, None).unwrap_err();
+    assert!(error.contains("--no-auth"), "{error}");
+    assert_eq!(
+        serve_token(&public, Some("env".to_string()), None),
+        Ok(Some("env".to_string()))
+    );
+    assert_eq!(
+        serve_token(&public, None, Some("settings".to_string())),
+        Ok(Some("settings".to_string()))
+    );
+
+    let opted_out = serve_args(&["--host", "0.0.0.0", "--no-auth"]);
+    assert_eq!(
+        serve_token(
+            &opted_out,
+            Some("env".to_string()),
+            Some("settings".to_string())
+        ),
+        Ok(None)
+    );
+
+    assert_eq!(serve_token(&serve_args(&[]), None, None), Ok(None));
+}
+
+#[test]
+fn serve_token_prefers_flag_then_env_then_settings() {
+    let env = || Some("env".to_string());
+    let settings = || Some("settings".to_string());
+
+    let flagged = serve_args(&["--token", "flag"]);
+    assert_eq!(
+        serve_token(&flagged, env(), settings()),
+        Ok(Some("flag".to_string()))
+    );
+    let unflagged = serve_args(&[]);
+    assert_eq!(
+        serve_token(&unflagged, env(), settings()),
+        Ok(Some("env".to_string()))
+    );
+    assert_eq!(
+        serve_token(&unflagged, Some(" ".to_string()), settings()),
+        Ok(Some("settings".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn invalid_requests_and_unknown_routes_return_openai_errors() {
+    let server = TestServer::start(None).await;
+
+    let bad_role = server
+        .post(json!({ "messages": [{ "role": "narrator", "content": "x" }] }))
+        .await;
+    assert_eq!(bad_role.status(), 400);
+    let error: Value = bad_role.json().await.unwrap();
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("narrator"));
+
+    let malformed = reqwest::Client::new()
+        .post(server.url())
+        .body("{not json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(malformed.status(), 400);
+
+    let missing = reqwest::get(format!("http://{}/v1/embeddings", server.addr))
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+
+    let models: Value = reqwest::get(format!("http://{}/v1/models", server.addr))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(models["data"][0]["id"], "stub:echo");
+}
+
+#[tokio::test]
+async fn concurrent_requests_run_independently() {
+    let server = TestServer::start(None).await;
+
+    let requests = (0..8).map(|i| {
+        let server = &server;
+        async move {
+            let body: Value = server
+                .post(json!({
+                    "messages": [{ "role": "user", "content": format!("request {i}") }]
+                }))
+                .await
+                .json()
+                .await
+                .unwrap();
+            (i, body)
+        }
+    });
+    let responses = futures::future::join_all(requests).await;
+
+    let mut ids = std::collections::HashSet::new();
+    for (i, body) in responses {
+        assert_eq!(
+            body["choices"][0]["message"]["content"],
+            format!("echo: request {i}")
+        );
+        assert!(ids.insert(body["id"].as_str().unwrap().to_string()));
+    }
+}
+
+#[tokio::test]
+async fn shutdown_drains_in_flight_streams() {
+    let mut server = TestServer::start(None).await;
+
+    let response = server
+        .post(json!({
+            "messages": [{ "role": "user", "content": "slow" }],
+            "stream": true
+        }))
+        .await;
+    assert_eq!(response.status(), 200);
+    server.shutdown.take().unwrap().send(()).unwrap();
+
+    let body = response.text().await.unwrap();
+    let text = chunks(&body)
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect::<String>();
+    assert_eq!(text, "echo: slow");
+    assert_eq!(sse_events(&body).last().unwrap(), "[DONE]");
+
+    tokio::time::timeout(Duration::from_secs(5), server.task)
+        .await
+        .expect("server stops after draining")
+        .unwrap();
+    assert!(tokio::net::TcpStream::connect(server.addr).await.is_err());
+}
+
+#[tokio::test]
+async fn requests_over_capacity_get_429_with_retry_after() {
+    let server =
+        TestServer::start_with_options(None, RunnerOptions::new().with_max_concurrent_runs(1))
+            .await;
+
+    let slow = server
+        .post(json!({
+            "messages": [{ "role": "user", "content": "slow" }],
+            "stream": true
+        }))
+        .await;
+    assert_eq!(slow.status(), 200);
+    let rejected = server
+        .post(json!({ "messages": [{ "role": "user", "content": "hi" }] }))
+        .await;
+
+    assert_eq!(rejected.status(), 429);
+    assert_eq!(rejected.headers()["retry-after"], "1");
+    let body: Value = rejected.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "rate_limit_exceeded");
+    assert!(slow.text().await.unwrap().ends_with("data: [DONE]\n\n"));
+}
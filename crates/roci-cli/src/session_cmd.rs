@@ -293,7 +293,9 @@ async fn recover_import_session(
     })
 }
 
-fn resolve_root(override_root: Option<PathBuf>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+pub(crate) fn resolve_root(
+    override_root: Option<PathBuf>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
     if let Some(root) = override_root {
         return Ok(root);
     }
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use roci::auth::{FileTokenStore, Token, TokenStore, TokenStoreConfig};
+use tempfile::tempdir;
+
+fn binary_path() -> PathBuf {
+    std::env::var_os("CARGO_BIN_EXE_roci-agent")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("target/debug/roci-agent"))
+}
+
+/// Run `roci-agent auth <args>` with an empty home so the default store is unused.
+fn run_auth(home: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(binary_path())
+        .arg("auth")
+        .args(args)
+        .env("HOME", home)
+        .env("CODEX_HOME", home.join(".codex"))
+        .output()
+        .expect("failed to run roci-agent auth")
+}
+
+fn output_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+fn codex_token() -> Token {
+    Token {
+        access_token: "codex-access".to_string(),
+        refresh_token: Some("codex-refresh".to_string()),
+        id_token: None,
+        expires_at: None,
+        last_refresh: None,
+        scopes: None,
+        account_id: None,
+    }
+}
+
+#[test]
+fn store_path_overrides_the_default_token_store() {
+    let home = tempdir().expect("home dir");
+    let secrets = tempdir().expect("store dir");
+    let store_path = secrets.path().to_str().expect("utf-8 path");
+    FileTokenStore::new(TokenStoreConfig::new(secrets.path().to_path_buf()))
+        .save("openai-codex", "default", &codex_token())
+        .expect("save token");
+
+    let status = run_auth(home.path(), &["--store-path", store_path, "status"]);
+    assert!(status.status.success(), "{}", output_string(&status.stderr));
+    assert!(output_string(&status.stdout).contains("Codex: Logged in"));
+
+    let default_status = run_auth(home.path(), &["status"]);
+    assert!(output_string(&default_status.stdout).contains("Codex: Not logged in"));
+
+    let logout = run_auth(
+        home.path(),
+        &["logout", "codex", "--store-path", store_path],
+    );
+    assert!(logout.status.success(), "{}", output_string(&logout.stderr));
+    assert!(!secrets.path().join("openai-codex.toml").exists());
+}
+
+#[test]
+fn complete_without_pending_login_fails_with_hint() {
+    let home = tempdir().expect("home dir");
+    let secrets = tempdir().expect("store dir");
+    let store_path = secrets.path().to_str().expect("utf-8 path");
+
+    let output = run_auth(
+        home.path(),
+        &["--store-path", store_path, "complete", "codex"],
+    );
+
+    assert!(!output.status.success());
+    assert!(output_string(&output.stderr).contains("auth login codex --no-poll"));
+}
@@ -0,0 +1,245 @@
+//! Agent-loop overhead on a long conversation.
+//!
+//! Runs a stub provider that calls a no-op tool for 49 iterations and then
+//! answers, starting from a 100- and a 1000-message history, so the time
+//! measured is the runner's own work: building provider requests, hooks,
+//! compaction checks, and events. Each history is run twice: as is, where
+//! provider requests share the run history, and with a `transform_context`
+//! hook that hands back a copy, which is what every provider call paid before
+//! the history was shared. Run with:
+//!
+//! ```text
+//! cargo bench -p roci-core --features agent --bench agent_loop_history
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use roci_core::agent_loop::{
+    ApprovalPolicy, LoopRunner, RunRequest, RunStatus, Runner, TransformContextFn,
+    TransformContextHookResult,
+};
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::models::{LanguageModel, ModelCapabilities};
+use roci_core::provider::{
+    ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
+};
+use roci_core::tools::{AgentTool, AgentToolParameters, Tool, ToolExecutionContext};
+use roci_core::types::{AgentToolCall, ModelMessage, StreamEventType, TextStreamDelta, Usage};
+
+const HISTORY_LENGTHS: [usize; 2] = [100, 1000];
+const ITERATIONS: usize = 50;
+const WARMUP_RUNS: usize = 2;
+const MEASURED_RUNS: usize = 20;
+
+struct ToolLoopProvider {
+    calls: AtomicUsize,
+    capabilities: ModelCapabilities,
+}
+
+#[async_trait]
+impl ModelProvider for ToolLoopProvider {
+    fn provider_name(&self) -> &str {
+        "bench"
+    }
+
+    fn model_id(&self) -> &str {
+        "tool-loop"
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    async fn generate_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        Err(RociError::UnsupportedOperation(
+            "the benchmark provider only streams".to_string(),
+        ))
+    }
+
+    async fn stream_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let first = if call + 1 < ITERATIONS {
+            delta(
+                StreamEventType::ToolCallDelta,
+                String::new(),
+                Some(AgentToolCall {
+                    id: format!("call-{call}"),
+                    name: "noop".to_string(),
+                    arguments: serde_json::json!({}),
+                    called_as: None,
+                    recipient: None,
+                }),
+            )
+        } else {
+            delta(StreamEventType::TextDelta, "done".to_string(), None)
+        };
+        let mut done = delta(StreamEventType::Done, String::new(), None);
+        done.usage = Some(Usage::default());
+        Ok(Box::pin(stream::iter([Ok(first), Ok(done)])))
+    }
+}
+
+fn delta(
+    event_type: StreamEventType,
+    text: String,
+    tool_call: Option<AgentToolCall>,
+) -> TextStreamDelta {
+    TextStreamDelta {
+        text,
+        event_type,
+        tool_call,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+struct BenchFactory;
+
+impl ProviderFactory for BenchFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["bench"]
+    }
+
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(ToolLoopProvider {
+            calls: AtomicUsize::new(0),
+            capabilities: ModelCapabilities::default(),
+        }))
+    }
+}
+
+/// A conversation of alternating turns with enough text per message that
+/// copying it shows up.
+fn history(len: usize) -> Vec<ModelMessage> {
+    let mut messages = vec![ModelMessage::system("You are a benchmark.")];
+    for index in 1..len {
+        let text = format!(
+            "message {index}: {}",
+            "lorem ipsum dolor sit amet ".repeat(40)
+        );
+        messages.push(if index % 2 == 1 {
+            ModelMessage::user(text)
+        } else {
+            ModelMessage::assistant(text)
+        });
+    }
+    messages
+}
+
+/// Replaces the history with a copy of itself before every provider call.
+fn copy_history() -> TransformContextFn {
+    Arc::new(|payload| {
+        let messages = payload.messages.to_vec();
+        Box::pin(async move { Ok(TransformContextHookResult::ReplaceMessages { messages }) })
+    })
+}
+
+async fn run_once(
+    runner: &LoopRunner,
+    tool: &Arc<dyn Tool>,
+    history: &[ModelMessage],
+    transform: Option<&TransformContextFn>,
+) {
+    let model = LanguageModel::Custom {
+        provider: "bench".to_string(),
+        model_id: "tool-loop".to_string(),
+    };
+    let mut request = RunRequest::new(model, history.to_vec())
+        .with_tools(vec![tool.clone()])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_max_iterations(ITERATIONS + 1);
+    if let Some(transform) = transform {
+        request = request.with_transform_context(transform.clone());
+    }
+    let result = runner
+        .start(request)
+        .await
+        .expect("run starts")
+        .wait()
+        .await;
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+}
+
+async fn measure(
+    runner: &LoopRunner,
+    tool: &Arc<dyn Tool>,
+    history: &[ModelMessage],
+    transform: Option<&TransformContextFn>,
+) -> Vec<Duration> {
+    for _ in 0..WARMUP_RUNS {
+        run_once(runner, tool, history, transform).await;
+    }
+    let mut samples = Vec::with_capacity(MEASURED_RUNS);
+    for _ in 0..MEASURED_RUNS {
+        let started = Instant::now();
+        run_once(runner, tool, history, transform).await;
+        samples.push(started.elapsed());
+    }
+    samples.sort();
+    samples
+}
+
+fn report(label: &str, samples: &[Duration]) {
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    println!(
+        "  {label:<16} min {:?}  median {:?}  mean {:?}  per iteration {:?}",
+        samples[0],
+        samples[samples.len() / 2],
+        mean,
+        mean / ITERATIONS as u32
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let mut registry = ProviderRegistry::new();
+    registry.register(Arc::new(BenchFactory));
+    let runner = LoopRunner::with_registry(RociConfig::default(), Arc::new(registry));
+    let tool: Arc<dyn Tool> = Arc::new(AgentTool::new(
+        "noop",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args, _ctx: ToolExecutionContext| async { Ok(serde_json::json!({ "ok": true })) },
+    ));
+    let copy = copy_history();
+
+    runtime.block_on(async {
+        println!("agent_loop_history: {ITERATIONS} iterations, {MEASURED_RUNS} runs");
+        for len in HISTORY_LENGTHS {
+            let history = history(len);
+            let shared = measure(&runner, &tool, &history, None).await;
+            let copied = measure(&runner, &tool, &history, Some(&copy)).await;
+            println!("{len} messages");
+            report("shared history", &shared);
+            report("copied history", &copied);
+        }
+    });
+}
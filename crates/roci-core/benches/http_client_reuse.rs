@@ -0,0 +1,98 @@
+//! Per-request latency with the shared HTTP client versus a fresh client.
+//!
+//! A local keep-alive server stands in for a provider endpoint and delays
+//! the first response on each new connection, simulating the TCP and TLS
+//! setup of a distant endpoint. The shared client pays that once; a client
+//! built per request pays it every time. Run with:
+//!
+//! ```text
+//! cargo bench -p roci-core --bench http_client_reuse
+//! ```
+
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use roci_core::provider::http::shared_client;
+
+const CONNECTION_SETUP: Duration = Duration::from_millis(20);
+const WARMUP_REQUESTS: usize = 2;
+const MEASURED_REQUESTS: usize = 50;
+const BODY: &str = r#"{"ok":true}"#;
+
+async fn serve(listener: TcpListener) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve_connection(stream));
+    }
+}
+
+async fn serve_connection(mut stream: TcpStream) {
+    tokio::time::sleep(CONNECTION_SETUP).await;
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        // Bench requests are bodiless GETs, so headers end each request.
+        while let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            buffer.drain(..end + 4);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{BODY}",
+                BODY.len()
+            );
+            if stream.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+async fn measure(url: &str, client: impl Fn() -> reqwest::Client) -> Vec<Duration> {
+    for _ in 0..WARMUP_REQUESTS {
+        client().get(url).send().await.expect("request");
+    }
+    let mut samples = Vec::with_capacity(MEASURED_REQUESTS);
+    for _ in 0..MEASURED_REQUESTS {
+        let started = Instant::now();
+        let response = client().get(url).send().await.expect("request");
+        response.bytes().await.expect("body");
+        samples.push(started.elapsed());
+    }
+    samples.sort();
+    samples
+}
+
+fn report(label: &str, samples: &[Duration]) {
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    println!(
+        "  {label:<14} min {:?}  median {:?}  mean {:?}",
+        samples[0],
+        samples[samples.len() / 2],
+        mean
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+
+    runtime.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("http://{}/", listener.local_addr().expect("addr"));
+        tokio::spawn(serve(listener));
+
+        let shared = measure(&url, || shared_client().clone()).await;
+        let fresh = measure(&url, reqwest::Client::new).await;
+
+        println!(
+            "http_client_reuse: {MEASURED_REQUESTS} requests, {CONNECTION_SETUP:?} simulated connection setup"
+        );
+        report("shared client", &shared);
+        report("fresh client", &fresh);
+    });
+}
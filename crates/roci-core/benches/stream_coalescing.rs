@@ -0,0 +1,169 @@
+//! Event volume of a firehose stream with and without coalescing.
+//!
+//! Runs a stub provider that streams a few thousand one-token text deltas
+//! as fast as it can, once with every delta emitted and once with
+//! [`StreamCoalescing`], and reports the events each run emitted and how
+//! long it took. Run with:
+//!
+//! ```text
+//! cargo bench -p roci-core --features agent --bench stream_coalescing
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use roci_core::agent_loop::{
+    LoopRunner, RunEventSink, RunRequest, RunStatus, Runner, StreamCoalescing,
+};
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::models::{LanguageModel, ModelCapabilities};
+use roci_core::provider::{
+    ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
+};
+use roci_core::types::{ModelMessage, StreamEventType, TextStreamDelta, Usage};
+
+const DELTAS: usize = 4_000;
+const WARMUP_RUNS: usize = 2;
+const MEASURED_RUNS: usize = 20;
+
+struct FirehoseProvider {
+    capabilities: ModelCapabilities,
+}
+
+#[async_trait]
+impl ModelProvider for FirehoseProvider {
+    fn provider_name(&self) -> &str {
+        "bench"
+    }
+
+    fn model_id(&self) -> &str {
+        "firehose"
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    async fn generate_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        Err(RociError::UnsupportedOperation(
+            "the benchmark provider only streams".to_string(),
+        ))
+    }
+
+    async fn stream_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        let tokens =
+            (0..DELTAS).map(|index| Ok(delta(StreamEventType::TextDelta, format!("t{index} "))));
+        let mut done = delta(StreamEventType::Done, String::new());
+        done.usage = Some(Usage::default());
+        Ok(Box::pin(stream::iter(tokens.chain([Ok(done)]))))
+    }
+}
+
+fn delta(event_type: StreamEventType, text: String) -> TextStreamDelta {
+    TextStreamDelta {
+        text,
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+struct BenchFactory;
+
+impl ProviderFactory for BenchFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["bench"]
+    }
+
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(FirehoseProvider {
+            capabilities: ModelCapabilities::default(),
+        }))
+    }
+}
+
+/// Run once and return the number of events the sink received.
+async fn run_once(runner: &LoopRunner, coalescing: Option<StreamCoalescing>) -> usize {
+    let model = LanguageModel::Custom {
+        provider: "bench".to_string(),
+        model_id: "firehose".to_string(),
+    };
+    let events = Arc::new(AtomicUsize::new(0));
+    let counted = events.clone();
+    let sink: RunEventSink = Arc::new(move |_event| {
+        counted.fetch_add(1, Ordering::Relaxed);
+    });
+    let mut request = RunRequest::new(model, vec![ModelMessage::user("stream")]);
+    request.stream_coalescing = coalescing;
+    request.event_sink = Some(sink);
+    let result = runner
+        .start(request)
+        .await
+        .expect("run starts")
+        .wait()
+        .await;
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    events.load(Ordering::Relaxed)
+}
+
+async fn measure(runner: &LoopRunner, label: &str, coalescing: Option<StreamCoalescing>) {
+    for _ in 0..WARMUP_RUNS {
+        run_once(runner, coalescing).await;
+    }
+    let mut samples = Vec::with_capacity(MEASURED_RUNS);
+    let mut events = 0;
+    for _ in 0..MEASURED_RUNS {
+        let started = Instant::now();
+        events = run_once(runner, coalescing).await;
+        samples.push(started.elapsed());
+    }
+    samples.sort();
+    let mean = samples.iter().sum::<Duration>() / MEASURED_RUNS as u32;
+    println!(
+        "  {label:<10} events {events:>5}  min {:?}  median {:?}  mean {:?}",
+        samples[0],
+        samples[MEASURED_RUNS / 2],
+        mean
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let mut registry = ProviderRegistry::new();
+    registry.register(Arc::new(BenchFactory));
+    let runner = LoopRunner::with_registry(RociConfig::default(), Arc::new(registry));
+
+    runtime.block_on(async {
+        println!("stream_coalescing: {DELTAS} text deltas, {MEASURED_RUNS} runs");
+        measure(&runner, "off", None).await;
+        measure(&runner, "coalesced", Some(StreamCoalescing::default())).await;
+    });
+}
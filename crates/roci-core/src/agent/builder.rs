@@ -0,0 +1,219 @@
+//! Fluent construction of an [`Agent`] from a model, tools, skills, and
+//! plugins.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::agent_loop::{ApprovalHandler, ApprovalPolicy, RunPlugin};
+use crate::config::RociConfig;
+use crate::error::RociError;
+use crate::memory::Memory;
+use crate::models::LanguageModel;
+use crate::provider::ProviderRegistry;
+use crate::skills::{load_skills, LoadSkillsOptions};
+use crate::tools::tool::Tool;
+use crate::types::GenerationSettings;
+
+use super::Agent;
+
+/// Collects an agent's setup and checks it as a whole in
+/// [`build`](Self::build), so mistakes surface before the first run.
+///
+/// ```ignore
+/// let mut agent = Agent::builder()
+///     .model("anthropic:claude-sonnet-4")
+///     .registry(Arc::new(roci::default_registry()))
+///     .system_prompt("You are a careful reviewer.")
+///     .tools(roci_tools::builtin::all_tools())
+///     .skills_from(&cwd)
+///     .approval(ApprovalPolicy::always())
+///     .build(RociConfig::from_env())?;
+/// let reply = agent.run("Review the diff").await?;
+/// ```
+pub struct AgentBuilder {
+    model: Option<String>,
+    registry: Option<Arc<ProviderRegistry>>,
+    system_prompt: Option<String>,
+    tools: Vec<Arc<dyn Tool>>,
+    skill_paths: Vec<PathBuf>,
+    plugins: Vec<Arc<dyn RunPlugin>>,
+    memory: Option<Arc<dyn Memory>>,
+    approval_policy: ApprovalPolicy,
+    approval_handler: Option<ApprovalHandler>,
+    settings: GenerationSettings,
+}
+
+impl Default for AgentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentBuilder {
+    pub fn new() -> Self {
+        Self {
+            model: None,
+            registry: None,
+            system_prompt: None,
+            tools: Vec::new(),
+            skill_paths: Vec::new(),
+            plugins: Vec::new(),
+            memory: None,
+            approval_policy: ApprovalPolicy::ask(),
+            approval_handler: None,
+            settings: GenerationSettings::default(),
+        }
+    }
+
+    /// Model selector such as `anthropic:claude-sonnet-4`. Required.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Providers to build the model with. Required unless the model's
+    /// provider is registered elsewhere, since the default registry is empty.
+    pub fn registry(mut self, registry: Arc<ProviderRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn tools(mut self, tools: impl IntoIterator<Item = Arc<dyn Tool>>) -> Self {
+        self.tools.extend(tools);
+        self
+    }
+
+    /// Load skills from a skill file or a directory searched recursively.
+    /// The path must exist when the agent is built.
+    pub fn skills_from(mut self, path: impl Into<PathBuf>) -> Self {
+        self.skill_paths.push(path.into());
+        self
+    }
+
+    pub fn with_plugin(mut self, plugin: Arc<dyn RunPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    pub fn memory(mut self, memory: Arc<dyn Memory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Tool approval policy. Defaults to [`ApprovalPolicy::ask`].
+    pub fn approval(mut self, policy: ApprovalPolicy) -> Self {
+        self.approval_policy = policy;
+        self
+    }
+
+    pub fn approval_handler(mut self, handler: ApprovalHandler) -> Self {
+        self.approval_handler = Some(handler);
+        self
+    }
+
+    pub fn settings(mut self, settings: GenerationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Validate the setup and create the agent.
+    ///
+    /// Fails with [`RociError::Configuration`] when the model is missing,
+    /// unparsable, or has no registered provider, when a skill path does
+    /// not exist, or when two tools (plugin tools included) share a name.
+    pub fn build(self, config: RociConfig) -> Result<Agent, RociError> {
+        let model = self
+            .model
+            .ok_or_else(|| RociError::Configuration("agent builder needs a model".to_string()))?;
+        let model: LanguageModel = model.parse()?;
+        let registry = self
+            .registry
+            .unwrap_or_else(|| Arc::new(ProviderRegistry::new()));
+        if !registry.has_provider(model.provider_name()) {
+            return Err(RociError::Configuration(format!(
+                "no provider registered for '{}' (pass one with AgentBuilder::registry)",
+                model.provider_name()
+            )));
+        }
+        check_unique_tool_names(&self.tools, &self.plugins)?;
+
+        if let Some(missing) = self.skill_paths.iter().find(|path| !path.exists()) {
+            return Err(RociError::Configuration(format!(
+                "skill path {} does not exist",
+                missing.display()
+            )));
+        }
+        let skills = if self.skill_paths.is_empty() {
+            Vec::new()
+        } else {
+            load_skills(&LoadSkillsOptions {
+                explicit_paths: self.skill_paths,
+                ..LoadSkillsOptions::default()
+            })
+            .skills
+        };
+
+        let mut agent = Agent::new(model, registry)
+            .with_config(config)
+            .with_skills(skills)
+            .with_approval_policy(self.approval_policy)
+            .with_settings(self.settings);
+        if let Some(prompt) = self.system_prompt {
+            agent = agent.with_system_prompt(prompt);
+        }
+        for tool in self.tools {
+            agent = agent.with_tool_ref(tool);
+        }
+        for plugin in self.plugins {
+            agent = agent.with_plugin(plugin);
+        }
+        if let Some(memory) = self.memory {
+            agent = agent.with_memory(memory);
+        }
+        if let Some(handler) = self.approval_handler {
+            agent = agent.with_approval_handler(handler);
+        }
+        Ok(agent)
+    }
+}
+
+fn check_unique_tool_names(
+    tools: &[Arc<dyn Tool>],
+    plugins: &[Arc<dyn RunPlugin>],
+) -> Result<(), RociError> {
+    let mut owners = HashMap::<String, String>::new();
+    let sources = tools
+        .iter()
+        .map(|tool| (tool.clone(), "tools".to_string()))
+        .chain(plugins.iter().flat_map(|plugin| {
+            let owner = format!("plugin '{}'", plugin.name());
+            plugin
+                .tools()
+                .into_iter()
+                .map(move |tool| (tool, owner.clone()))
+        }));
+    for (tool, owner) in sources {
+        if let Some(first) = owners.insert(tool.name().to_string(), owner.clone()) {
+            return Err(RociError::Configuration(format!(
+                "tool '{}' is defined twice (by {first} and {owner})",
+                tool.name()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "builder_tests.rs"]
+mod tests;
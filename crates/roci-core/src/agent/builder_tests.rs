@@ -0,0 +1,347 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+
+use super::*;
+use crate::agent_loop::{RunEventPayload, RunEventSink};
+use crate::models::ModelCapabilities;
+use crate::provider::{ModelProvider, ProviderFactory, ProviderRequest, ProviderResponse};
+use crate::tools::{
+    AgentTool, AgentToolParameters, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+};
+use crate::types::{
+    AgentToolCall, ContentPart, FinishReason, ModelMessage, Role, StreamEventType, TextStreamDelta,
+};
+
+enum Reply {
+    ToolCall(&'static str),
+    Text(&'static str),
+    Fail,
+}
+
+/// Answers each provider call with the next scripted reply and records the
+/// requests it was sent.
+#[derive(Clone)]
+struct ScriptedFactory {
+    replies: Arc<Mutex<VecDeque<Reply>>>,
+    requests: Arc<Mutex<Vec<ProviderRequest>>>,
+}
+
+impl ScriptedFactory {
+    fn new(replies: Vec<Reply>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(replies.into())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn registry(&self) -> Arc<ProviderRegistry> {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(self.clone()));
+        Arc::new(registry)
+    }
+
+    fn requests(&self) -> Vec<ProviderRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl ProviderFactory for ScriptedFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["stub"]
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(ScriptedProvider {
+            factory: self.clone(),
+            capabilities: ModelCapabilities::default(),
+        }))
+    }
+}
+
+struct ScriptedProvider {
+    factory: ScriptedFactory,
+    capabilities: ModelCapabilities,
+}
+
+fn delta(event_type: StreamEventType) -> TextStreamDelta {
+    TextStreamDelta {
+        text: String::new(),
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+#[async_trait]
+impl ModelProvider for ScriptedProvider {
+    fn provider_name(&self) -> &str {
+        "stub"
+    }
+
+    fn model_id(&self) -> &str {
+        "test-model"
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    async fn generate_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        panic!("agent runs should stream through the runner")
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        self.factory.requests.lock().unwrap().push(request.clone());
+        let reply = self.factory.replies.lock().unwrap().pop_front();
+        let deltas = match reply {
+            Some(Reply::ToolCall(name)) => vec![
+                TextStreamDelta {
+                    tool_call: Some(AgentToolCall {
+                        id: format!("call-{name}"),
+                        name: name.to_string(),
+                        arguments: serde_json::json!({}),
+                        called_as: None,
+                        recipient: None,
+                    }),
+                    ..delta(StreamEventType::ToolCallDelta)
+                },
+                TextStreamDelta {
+                    finish_reason: Some(FinishReason::ToolCalls),
+                    ..delta(StreamEventType::Done)
+                },
+            ],
+            Some(Reply::Text(text)) => vec![
+                TextStreamDelta {
+                    text: text.to_string(),
+                    ..delta(StreamEventType::TextDelta)
+                },
+                TextStreamDelta {
+                    finish_reason: Some(FinishReason::Stop),
+                    ..delta(StreamEventType::Done)
+                },
+            ],
+            Some(Reply::Fail) | None => {
+                return Err(RociError::api(400, "scripted failure"));
+            }
+        };
+        Ok(Box::pin(stream::iter(deltas.into_iter().map(Ok))))
+    }
+}
+
+fn lookup_tool(name: &str) -> Arc<dyn Tool> {
+    Arc::new(
+        AgentTool::new(
+            name,
+            "look something up",
+            AgentToolParameters::empty(),
+            |_args, _ctx| async { Ok(serde_json::json!({ "value": 42 })) },
+        )
+        .with_static_safety(
+            ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read),
+            ToolSafetySummary {
+                read_only_by_default: true,
+                destructive_by_default: false,
+                concurrency_safe_by_default: true,
+                approval_kind: ToolSafetyKind::Read,
+            },
+        ),
+    )
+}
+
+struct LookupPlugin;
+
+impl RunPlugin for LookupPlugin {
+    fn name(&self) -> &str {
+        "lookup-plugin"
+    }
+
+    fn tools(&self) -> Vec<Arc<dyn Tool>> {
+        vec![lookup_tool("lookup")]
+    }
+}
+
+fn config() -> RociConfig {
+    RociConfig::new().with_token_store(None)
+}
+
+fn roles(messages: &[ModelMessage]) -> Vec<Role> {
+    messages.iter().map(|message| message.role).collect()
+}
+
+fn configuration_error(builder: AgentBuilder) -> String {
+    match builder.build(config()) {
+        Err(RociError::Configuration(message)) => message,
+        Err(other) => panic!("expected a configuration error, got {other}"),
+        Ok(_) => panic!("expected the builder to fail"),
+    }
+}
+
+#[tokio::test]
+async fn runs_continue_one_conversation_including_tool_exchanges() {
+    let factory = ScriptedFactory::new(vec![
+        Reply::ToolCall("lookup"),
+        Reply::Text("it is 42"),
+        Reply::Text("still 42"),
+    ]);
+    let mut agent = Agent::builder()
+        .model("stub:test-model")
+        .registry(factory.registry())
+        .system_prompt("Answer briefly.")
+        .tool(lookup_tool("lookup"))
+        .build(config())
+        .unwrap();
+
+    assert_eq!(agent.run("what is the value?").await.unwrap(), "it is 42");
+    assert_eq!(agent.run("and now?").await.unwrap(), "still 42");
+
+    let requests = factory.requests();
+    assert_eq!(requests.len(), 3);
+    let last = &requests[2].messages;
+    assert_eq!(
+        roles(last),
+        vec![
+            Role::System,
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant,
+            Role::User,
+        ]
+    );
+    assert!(last[0].text().starts_with("Answer briefly."));
+    assert!(matches!(
+        &last[3].content[0],
+        ContentPart::ToolResult(result) if result.tool_call_id == "call-lookup"
+    ));
+    assert_eq!(last[5].text(), "and now?");
+    let history = agent.conversation().messages();
+    assert_eq!(history.len(), 6);
+    assert!(history.iter().all(|message| message.role != Role::System));
+    assert_eq!(history[5].text(), "still 42");
+}
+
+#[tokio::test]
+async fn failed_run_leaves_the_conversation_unchanged() {
+    let factory = ScriptedFactory::new(vec![Reply::Text("hello"), Reply::Fail]);
+    let mut agent = Agent::builder()
+        .model("stub:test-model")
+        .registry(factory.registry())
+        .build(config())
+        .unwrap();
+
+    agent.run("hi").await.unwrap();
+    let error = agent.run("break").await.unwrap_err();
+
+    assert!(error.to_string().contains("scripted failure"), "{error}");
+    assert_eq!(
+        roles(agent.conversation().messages()),
+        vec![Role::User, Role::Assistant]
+    );
+}
+
+#[tokio::test]
+async fn run_streaming_sends_run_events_to_the_sink() {
+    let factory = ScriptedFactory::new(vec![Reply::Text("streamed")]);
+    let mut agent = Agent::builder()
+        .model("stub:test-model")
+        .registry(factory.registry())
+        .build(config())
+        .unwrap();
+    let deltas = Arc::new(Mutex::new(String::new()));
+    let sink_deltas = deltas.clone();
+    let sink: RunEventSink = Arc::new(move |event| {
+        if let RunEventPayload::AssistantDelta { text } = event.payload {
+            sink_deltas.lock().unwrap().push_str(&text);
+        }
+    });
+
+    let reply = agent.run_streaming("hi", sink).await.unwrap();
+
+    assert_eq!(reply, "streamed");
+    assert_eq!(*deltas.lock().unwrap(), "streamed");
+    assert_eq!(agent.conversation().len(), 2);
+}
+
+#[tokio::test]
+async fn plugin_tools_and_skills_reach_the_provider() {
+    let dir = tempfile::tempdir().unwrap();
+    let skill_dir = dir.path().join("skills/release-notes");
+    std::fs::create_dir_all(&skill_dir).unwrap();
+    std::fs::write(
+        skill_dir.join("SKILL.md"),
+        "---\nname: release-notes\ndescription: Draft release notes from merged changes\n---\n\nSteps.\n",
+    )
+    .unwrap();
+    let factory = ScriptedFactory::new(vec![Reply::Text("done")]);
+    let mut agent = Agent::builder()
+        .model("stub:test-model")
+        .registry(factory.registry())
+        .skills_from(dir.path().join("skills"))
+        .with_plugin(Arc::new(LookupPlugin))
+        .build(config())
+        .unwrap();
+
+    agent.run("hi").await.unwrap();
+
+    let request = &factory.requests()[0];
+    let tools = request.tools.as_ref().expect("plugin tools are sent");
+    assert_eq!(tools[0].name, "lookup");
+    assert!(request.messages[0].text().contains("release-notes"));
+}
+
+#[test]
+fn build_rejects_incoherent_setups() {
+    let registry = ScriptedFactory::new(Vec::new()).registry();
+
+    assert!(configuration_error(Agent::builder()).contains("needs a model"));
+    assert!(configuration_error(Agent::builder().model("unknown:model"))
+        .contains("no provider registered for 'unknown'"));
+    let missing = configuration_error(
+        Agent::builder()
+            .model("stub:test-model")
+            .registry(registry.clone())
+            .skills_from("/definitely/not/a/skill/dir"),
+    );
+    assert!(missing.contains("/definitely/not/a/skill/dir"), "{missing}");
+    let duplicate = configuration_error(
+        Agent::builder()
+            .model("stub:test-model")
+            .registry(registry.clone())
+            .tools(vec![lookup_tool("lookup"), lookup_tool("lookup")]),
+    );
+    assert_eq!(
+        duplicate,
+        "tool 'lookup' is defined twice (by tools and tools)"
+    );
+    let plugin_clash = configuration_error(
+        Agent::builder()
+            .model("stub:test-model")
+            .registry(registry)
+            .tool(lookup_tool("lookup"))
+            .with_plugin(Arc::new(LookupPlugin)),
+    );
+    assert_eq!(
+        plugin_clash,
+        "tool 'lookup' is defined twice (by tools and plugin 'lookup-plugin')"
+    );
+}
@@ -1,5 +1,9 @@
 //! Conversation message history management.
 
+use crate::config::RociConfig;
+use crate::error::RociError;
+use crate::generation::explore::{explore, ExploreOptions, VariantOutcome, VariantSpec};
+use crate::provider::ProviderRegistry;
 use crate::types::ModelMessage;
 
 /// Manages a conversation's message history.
@@ -52,4 +56,36 @@ impl Conversation {
     pub fn is_empty(&self) -> bool {
         self.messages.is_empty()
     }
+
+    /// Try `next_user_message` under each variant without changing the
+    /// history. See [`explore`].
+    pub async fn explore(
+        &self,
+        registry: &ProviderRegistry,
+        config: &RociConfig,
+        next_user_message: impl Into<String>,
+        variants: Vec<VariantSpec>,
+        options: ExploreOptions,
+    ) -> Vec<VariantOutcome> {
+        explore(
+            registry,
+            config,
+            &self.messages,
+            next_user_message,
+            variants,
+            options,
+        )
+        .await
+    }
+
+    /// Append an explored turn: its user message and answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] when the variant failed; the
+    /// history is then unchanged.
+    pub fn commit(&mut self, outcome: &VariantOutcome) -> Result<(), RociError> {
+        self.messages.extend(outcome.branch()?);
+        Ok(())
+    }
 }
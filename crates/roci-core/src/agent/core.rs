@@ -11,15 +11,19 @@ use tokio::sync::oneshot;
 
 use crate::agent_loop::{
     ApprovalHandler, ApprovalPolicy, LoopRunner, RunEvent, RunEventPayload, RunEventSink,
-    RunLifecycle, RunRequest, RunStatus, Runner,
+    RunLifecycle, RunPlugin, RunRequest, RunResult, RunStatus, Runner,
 };
 use crate::config::RociConfig;
 use crate::error::RociError;
+use crate::memory::Memory;
 use crate::models::{LanguageModel, ModelCandidates};
 use crate::provider::ProviderRegistry;
+use crate::resource::{compose_agent_system_prompt, SystemPromptComposer};
+use crate::skills::Skill;
 use crate::tools::tool::Tool;
 use crate::types::*;
 
+use super::builder::AgentBuilder;
 use super::conversation::Conversation;
 
 /// An AI agent that maintains conversation state and can use tools.
@@ -29,6 +33,9 @@ pub struct Agent {
     registry: Arc<ProviderRegistry>,
     system_prompt: Option<String>,
     tools: Vec<Arc<dyn Tool>>,
+    skills: Vec<Skill>,
+    plugins: Vec<Arc<dyn RunPlugin>>,
+    memory: Option<Arc<dyn Memory>>,
     approval_policy: ApprovalPolicy,
     approval_handler: Option<ApprovalHandler>,
     settings: GenerationSettings,
@@ -36,6 +43,12 @@ pub struct Agent {
 }
 
 impl Agent {
+    /// Start a fluent [`AgentBuilder`], which validates the whole setup in
+    /// [`build`](AgentBuilder::build).
+    pub fn builder() -> AgentBuilder {
+        AgentBuilder::new()
+    }
+
     /// Create a new agent with an explicit provider registry.
     pub fn new(model: LanguageModel, registry: Arc<ProviderRegistry>) -> Self {
         Self {
@@ -44,6 +57,9 @@ impl Agent {
             registry,
             system_prompt: None,
             tools: Vec::new(),
+            skills: Vec::new(),
+            plugins: Vec::new(),
+            memory: None,
             approval_policy: ApprovalPolicy::ask(),
             approval_handler: None,
             settings: GenerationSettings::default(),
@@ -75,6 +91,24 @@ impl Agent {
         self
     }
 
+    /// Advertise `skills` in the system prompt catalog.
+    pub fn with_skills(mut self, skills: Vec<Skill>) -> Self {
+        self.skills = skills;
+        self
+    }
+
+    /// Add a run plugin to every run of this agent.
+    pub fn with_plugin(mut self, plugin: Arc<dyn RunPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    /// Set the long-term memory store available to memory tools.
+    pub fn with_memory(mut self, memory: Arc<dyn Memory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
     /// Set the tool approval policy for this agent.
     ///
     /// Defaults to [`ApprovalPolicy::ask`]: explicitly safe tools run
@@ -103,29 +137,14 @@ impl Agent {
 
         if !self.tools.is_empty() {
             let result = self.run_with_tools(messages, None).await?.wait().await;
-            return match result.status {
-                RunStatus::Completed => {
-                    let text = final_assistant_text(&result.messages).unwrap_or_default();
-                    self.conversation.add_assistant_message(&text);
-                    Ok(text)
-                }
-                RunStatus::Failed => {
-                    Err(RociError::Stream(result.error.unwrap_or_else(|| {
-                        "agent run failed without error message".to_string()
-                    })))
-                }
-                RunStatus::Canceled => Err(RociError::Stream("agent run canceled".to_string())),
-                RunStatus::Running => Err(RociError::InvalidState(
-                    "agent run returned before completion".to_string(),
-                )),
-            };
+            let text = completed_text(&result)?;
+            self.conversation.add_assistant_message(&text);
+            return Ok(text);
         }
 
-        let provider = self.registry.create_provider(
-            self.active_model().provider_name(),
-            self.active_model().model_id(),
-            &self.config,
-        )?;
+        let provider = self
+            .registry
+            .create_provider_for(self.active_model(), &self.config)?;
 
         let result = crate::generation::text::generate_text(
             provider.as_ref(),
@@ -171,11 +190,9 @@ impl Agent {
             return Ok(stream.boxed());
         }
 
-        let provider = self.registry.create_provider(
-            self.active_model().provider_name(),
-            self.active_model().model_id(),
-            &self.config,
-        )?;
+        let provider = self
+            .registry
+            .create_provider_for(self.active_model(), &self.config)?;
         let provider = Arc::from(provider);
 
         crate::generation::stream::stream_text_with_tools(
@@ -188,6 +205,26 @@ impl Agent {
         .await
     }
 
+    /// Run `prompt` through the agent loop and return the final reply.
+    ///
+    /// Unlike [`execute`](Self::execute), every call goes through the
+    /// [`LoopRunner`], and the whole exchange, tool calls and results
+    /// included, is kept in the conversation for the next call. A failed
+    /// run leaves the conversation as it was.
+    pub async fn run(&mut self, prompt: impl Into<String>) -> Result<String, RociError> {
+        self.run_prompt(prompt.into(), None).await
+    }
+
+    /// Like [`run`](Self::run), sending every run event to `sink` as it
+    /// happens.
+    pub async fn run_streaming(
+        &mut self,
+        prompt: impl Into<String>,
+        sink: RunEventSink,
+    ) -> Result<String, RociError> {
+        self.run_prompt(prompt.into(), Some(sink)).await
+    }
+
     /// Get the conversation history.
     pub fn conversation(&self) -> &Conversation {
         &self.conversation
@@ -198,10 +235,38 @@ impl Agent {
         self.conversation.clear();
     }
 
+    async fn run_prompt(
+        &mut self,
+        prompt: String,
+        event_sink: Option<RunEventSink>,
+    ) -> Result<String, RociError> {
+        let mut messages = self.messages_for_run();
+        messages.push(ModelMessage::user(prompt));
+        let result = self
+            .run_with_tools(messages, event_sink)
+            .await?
+            .wait()
+            .await;
+        let text = completed_text(&result)?;
+        self.conversation.clear();
+        for message in result.messages {
+            if !matches!(message.role, Role::System) {
+                self.conversation.add_message(message);
+            }
+        }
+        Ok(text)
+    }
+
     fn messages_for_run(&self) -> Vec<ModelMessage> {
         let mut messages = Vec::new();
-        if let Some(ref sys) = self.system_prompt {
-            messages.push(ModelMessage::system(sys.clone()));
+        let sections = SystemPromptComposer::new()
+            .with_skills(&self.skills)
+            .into_sections();
+        let composed =
+            compose_agent_system_prompt(self.system_prompt.clone(), &sections, &self.tools)
+                .render();
+        if let Some(sys) = composed {
+            messages.push(ModelMessage::system(sys));
         }
         messages.extend(self.conversation.messages().iter().cloned());
         messages
@@ -220,6 +285,12 @@ impl Agent {
         if let Some(handler) = &self.approval_handler {
             request = request.with_approval_handler(handler.clone());
         }
+        for plugin in &self.plugins {
+            request = request.with_plugin(plugin.clone());
+        }
+        if let Some(memory) = &self.memory {
+            request = request.with_memory(memory.clone());
+        }
         request.settings = self.settings.clone();
         if let Some(sink) = event_sink {
             request = request.with_event_sink(sink);
@@ -285,6 +356,22 @@ impl Drop for ToolStream<'_> {
     }
 }
 
+/// The final reply of a completed run, or the run's failure as an error.
+fn completed_text(result: &RunResult) -> Result<String, RociError> {
+    match result.status {
+        RunStatus::Completed => Ok(final_assistant_text(&result.messages).unwrap_or_default()),
+        RunStatus::Failed | RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded => {
+            Err(RociError::Stream(result.error.clone().unwrap_or_else(
+                || "agent run failed without error message".to_string(),
+            )))
+        }
+        RunStatus::Canceled => Err(RociError::Stream("agent run canceled".to_string())),
+        RunStatus::Queued | RunStatus::Running => Err(RociError::InvalidState(
+            "agent run returned before completion".to_string(),
+        )),
+    }
+}
+
 fn final_assistant_text(messages: &[ModelMessage]) -> Option<String> {
     messages
         .iter()
@@ -309,11 +396,17 @@ fn run_event_to_stream_item(
             Some(Ok(delta))
         }
         RunEventPayload::Lifecycle {
-            state: RunLifecycle::Failed { error },
+            state:
+                RunLifecycle::Failed { error }
+                | RunLifecycle::BudgetExceeded { error, .. }
+                | RunLifecycle::DeadlineExceeded { error, .. },
         } => Some(Err(RociError::Stream(error))),
         RunEventPayload::Lifecycle {
             state: RunLifecycle::Canceled,
         } => Some(Err(RociError::Stream("agent run canceled".to_string()))),
+        RunEventPayload::Lifecycle {
+            state: RunLifecycle::Queued { .. },
+        } => None,
         RunEventPayload::AssistantDelta { text } => Some(Ok(TextStreamDelta {
             text,
             event_type: StreamEventType::TextDelta,
@@ -323,7 +416,10 @@ fn run_event_to_stream_item(
             reasoning: None,
             reasoning_signature: None,
             reasoning_type: None,
+            image: None,
+            response_metadata: None,
         })),
+        RunEventPayload::AssistantImage { image } => Some(Ok(TextStreamDelta::image(image))),
         RunEventPayload::ReasoningDelta { text } => Some(Ok(TextStreamDelta {
             text: String::new(),
             event_type: StreamEventType::Reasoning,
@@ -333,8 +429,11 @@ fn run_event_to_stream_item(
             reasoning: Some(text),
             reasoning_signature: None,
             reasoning_type: None,
+            image: None,
+            response_metadata: None,
         })),
-        RunEventPayload::ToolCallStarted { call } | RunEventPayload::ToolCallCompleted { call } => {
+        RunEventPayload::ToolCallStarted { call, .. }
+        | RunEventPayload::ToolCallCompleted { call } => {
             if let Ok(mut calls) = tool_calls.lock() {
                 calls.insert(call.id.clone(), call.clone());
             }
@@ -350,10 +449,21 @@ fn run_event_to_stream_item(
         }
         RunEventPayload::Error { message } => Some(Err(RociError::Stream(message))),
         RunEventPayload::ToolResult { .. }
+        | RunEventPayload::ToolCallArgumentsDelta { .. }
         | RunEventPayload::PlanUpdated { .. }
         | RunEventPayload::DiffUpdated { .. }
         | RunEventPayload::ApprovalRequired { .. }
-        | RunEventPayload::Retry { .. } => None,
+        | RunEventPayload::Retry { .. }
+        | RunEventPayload::BudgetWarning { .. }
+        | RunEventPayload::Heartbeat { .. }
+        | RunEventPayload::Progress { .. }
+        | RunEventPayload::FirstTokenSloMissed { .. }
+        | RunEventPayload::ToolsUpdated { .. }
+        | RunEventPayload::ToolsTruncated { .. }
+        | RunEventPayload::ChangeSummary { .. }
+        | RunEventPayload::ArtifactAdded { .. }
+        | RunEventPayload::TaskStarted { .. }
+        | RunEventPayload::TaskCompleted { .. } => None,
     }
 }
 
@@ -367,6 +477,8 @@ fn stream_delta(event_type: StreamEventType) -> TextStreamDelta {
         reasoning: None,
         reasoning_signature: None,
         reasoning_type: None,
+        image: None,
+        response_metadata: None,
     }
 }
 
@@ -380,6 +492,8 @@ fn tool_call_delta(call: AgentToolCall) -> TextStreamDelta {
         reasoning: None,
         reasoning_signature: None,
         reasoning_type: None,
+        image: None,
+        response_metadata: None,
     }
 }
 
@@ -101,6 +101,8 @@ impl ModelProvider for ToolLoopProvider {
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -111,6 +113,8 @@ impl ModelProvider for ToolLoopProvider {
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])))
             }
@@ -140,6 +144,8 @@ impl ModelProvider for ToolLoopProvider {
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -150,6 +156,8 @@ impl ModelProvider for ToolLoopProvider {
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])))
             }
@@ -1,12 +1,15 @@
 //! Agent system: multi-turn conversations with tool execution.
 
+mod builder;
 pub mod conversation;
 mod core;
 pub mod message;
 pub mod runtime;
 #[cfg(feature = "agent")]
 pub mod subagents;
+pub mod summarizer;
 
+pub use builder::AgentBuilder;
 pub use conversation::Conversation;
 pub use core::Agent;
 pub use message::{convert_to_llm, AgentMessage, AgentMessageExt};
@@ -19,3 +22,4 @@ pub use runtime::{
 };
 #[cfg(feature = "agent")]
 pub use runtime::{AgentSubagentConfig, HumanInteractionCoordinator};
+pub use summarizer::{summarize_conversation, SummaryKind};
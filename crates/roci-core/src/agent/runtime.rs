@@ -165,6 +165,8 @@ pub(super) struct RuntimeEventPublishRequest {
     pub error_slot: Option<Arc<StdMutex<Option<AgentRuntimeError>>>>,
 }
 
+pub(super) type RuntimeEventAck = oneshot::Receiver<Result<Vec<RuntimeCursor>, AgentRuntimeError>>;
+
 impl AgentRuntime {
     /// Create a new agent runtime with the given configuration.
     pub fn new(
@@ -491,6 +491,7 @@ mod tests {
         LanguageModel::Known {
             provider_key: "test".to_string(),
             model_id: "semantic-events".to_string(),
+            provider_options: Default::default(),
         }
     }
 
@@ -1980,6 +1980,7 @@ mod tests {
         LanguageModel::Known {
             provider_key: "test".to_string(),
             model_id: "semantic-projector".to_string(),
+            provider_options: Default::default(),
         }
     }
 
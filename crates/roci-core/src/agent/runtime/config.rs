@@ -4,15 +4,17 @@ use std::sync::Arc;
 
 use crate::agent_loop::events::RetryMode;
 use crate::agent_loop::runner::{
-    AgentEventSink, BeforeAgentStartHook, ConvertToLlmFn, PostToolUseHook, PreToolUseHook,
-    RetryBackoffPolicy, TransformContextFn,
+    AgentEventSink, BeforeAgentStartHook, ConvertToLlmFn, DryRunResults, PostToolUseHook,
+    PreToolUseHook, PreTurnExecutionHook, RetryBackoffPolicy, RunBudget, RunPlugin,
+    TransformContextFn,
 };
 use crate::agent_loop::{ApprovalHandler, ApprovalPolicy};
 use crate::context::ContextBudget;
 use crate::error::RociError;
 use crate::models::{LanguageModel, SharedModelHealthRegistry};
 use crate::provider::ProviderPayloadCallback;
-use crate::resource::CompactionSettings;
+use crate::resource::{CompactionSettings, PromptSection};
+use crate::security::pii::ResponseFilter;
 use crate::session::SessionConfig;
 use crate::tools::catalog::ToolVisibilityPolicy;
 use crate::tools::dynamic::DynamicToolProvider;
@@ -61,6 +63,10 @@ pub struct AgentConfig {
     pub candidates: Vec<LanguageModel>,
     /// Optional system prompt prepended to the first turn.
     pub system_prompt: Option<String>,
+    /// Extra system prompt sections (resources, skills, MCP instructions)
+    /// composed after `system_prompt` with guidance from `tools`; see
+    /// [`SystemPromptComposer`](crate::resource::SystemPromptComposer).
+    pub prompt_sections: Vec<PromptSection>,
     /// Tools available for tool-use loops.
     pub tools: Vec<Arc<dyn Tool>>,
     /// Policy deciding which resolved tools are visible to the model.
@@ -94,6 +100,8 @@ pub struct AgentConfig {
     /// The runtime canonicalizes and validates this directory during construction.
     /// It is independent from durable session storage.
     pub workspace_root: Option<PathBuf>,
+    /// Root for spilled run artifacts; see [`RunRequest::artifacts_dir`](crate::agent_loop::RunRequest::artifacts_dir).
+    pub artifacts_dir: Option<PathBuf>,
     /// Optional sandbox provider exposed to command-capable tools.
     pub sandbox_provider: Option<Arc<dyn SandboxProvider>>,
     /// Drain mode for steering queue retrieval.
@@ -137,10 +145,19 @@ pub struct AgentConfig {
     pub session_before_compact: Option<SessionBeforeCompactHook>,
     /// Optional lifecycle hook for `session_before_tree`.
     pub session_before_tree: Option<SessionBeforeTreeHook>,
+    /// Optional hook called with each turn before any of its tools run.
+    pub pre_turn_execution: Option<PreTurnExecutionHook>,
     /// Optional hook called before each tool execution.
     pub pre_tool_use: Option<PreToolUseHook>,
     /// Optional hook called after each tool execution (including synthetic errors).
     pub post_tool_use: Option<PostToolUseHook>,
+    /// Plugins added to every run, after `pre_tool_use`/`post_tool_use`.
+    pub plugins: Vec<Arc<dyn RunPlugin>>,
+    /// Filter applied to assistant text before it is stored in the session.
+    /// See [`RunRequest::response_filter`](crate::agent_loop::RunRequest::response_filter).
+    pub response_filter: Option<Arc<dyn ResponseFilter>>,
+    /// Also apply `response_filter` to tool results.
+    pub filter_tool_results: bool,
     /// Default timeout for user input requests in milliseconds.
     pub user_input_timeout_ms: Option<u64>,
     /// Optional context budget for per-turn and per-session token limits.
@@ -150,6 +167,12 @@ pub struct AgentConfig {
     /// checked against per-turn and cumulative session limits before
     /// streaming begins.
     pub context_budget: Option<ContextBudget>,
+    /// Optional token, cost, and wall-clock ceiling applied to each run.
+    pub run_budget: Option<RunBudget>,
+    /// Plan tool calls without executing them. See
+    /// [`RunRequest::dry_run`](crate::agent_loop::RunRequest::dry_run).
+    pub dry_run: bool,
+    pub dry_run_results: DryRunResults,
     /// Chat runtime contract and event configuration.
     pub chat: ChatRuntimeConfig,
     /// Optional sub-agent runtime configuration.
@@ -171,8 +194,10 @@ impl Default for AgentConfig {
             candidates: vec![LanguageModel::Known {
                 provider_key: "openai".to_string(),
                 model_id: "gpt-4o".to_string(),
+                provider_options: Default::default(),
             }],
             system_prompt: None,
+            prompt_sections: Vec::new(),
             tools: Vec::new(),
             tool_visibility_policy: ToolVisibilityPolicy::default(),
             dynamic_tool_providers: Vec::new(),
@@ -202,10 +227,18 @@ impl Default for AgentConfig {
             compaction: CompactionSettings::default(),
             session_before_compact: None,
             session_before_tree: None,
+            pre_turn_execution: None,
             pre_tool_use: None,
             post_tool_use: None,
+            plugins: Vec::new(),
+            response_filter: None,
+            filter_tool_results: false,
             user_input_timeout_ms: None,
             context_budget: None,
+            run_budget: None,
+            dry_run: false,
+            dry_run_results: DryRunResults::default(),
+            artifacts_dir: None,
             chat: ChatRuntimeConfig::default(),
             #[cfg(feature = "agent")]
             subagents: None,
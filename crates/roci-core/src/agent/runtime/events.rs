@@ -6,7 +6,7 @@ use super::{
 };
 use crate::agent_loop::approvals::ApprovalDecision;
 use crate::agent_loop::runner::AgentEventSink;
-use crate::agent_loop::AgentEvent;
+use crate::agent_loop::{AgentEvent, AgentEventEnvelope};
 use crate::session::LocalSessionResources;
 
 pub(super) fn project_plan_update_and_mirror(
@@ -217,12 +217,25 @@ fn project_agent_event(
                 session_resources,
             )?);
         }
+        AgentEvent::PlanUpdate { steps } => {
+            if run_state.suppress_plan_events {
+                return Ok(events);
+            }
+            events.extend(ensure_turn_started(projector, run_state, turn_id)?);
+            events.extend(project_plan_update_and_mirror(
+                projector,
+                turn_id,
+                crate::tools::plan::render_plan(steps),
+                session_resources,
+            )?);
+        }
         AgentEvent::DiffUpdated { diff } => {
             events.extend(ensure_turn_started(projector, run_state, turn_id)?);
             events.push(projector.update_diff(turn_id, diff.clone())?);
         }
         AgentEvent::AgentStart { .. }
         | AgentEvent::AgentEnd { .. }
+        | AgentEvent::ToolCallArgumentsDelta { .. }
         | AgentEvent::TurnEnd { .. }
         | AgentEvent::Error { .. }
         | AgentEvent::System { .. } => {}
@@ -260,7 +273,8 @@ impl AgentRuntime {
             ..ChatProjectionRunState::default()
         }));
 
-        let sink: AgentEventSink = Arc::new(move |event: AgentEvent| {
+        let sink: AgentEventSink = Arc::new(move |envelope: AgentEventEnvelope| {
+            let event = &envelope.event;
             if let (Ok(mut projector), Ok(mut run_state)) =
                 (chat_projector.lock(), projection_run_state.lock())
             {
@@ -268,7 +282,7 @@ impl AgentRuntime {
                     &mut projector,
                     &mut run_state,
                     turn_id,
-                    &event,
+                    event,
                     session_resources.as_deref(),
                 )
                 .and_then(|events| {
@@ -293,7 +307,7 @@ impl AgentRuntime {
 
             if let AgentEvent::TurnStart {
                 turn_index: idx, ..
-            } = &event
+            } = event
             {
                 if let Ok(mut value) = turn_index.try_lock() {
                     *value = *idx;
@@ -314,7 +328,7 @@ impl AgentRuntime {
                 let _ = snapshot_tx.send(snapshot);
             }
             if let Some(ref sink) = original_sink {
-                sink(event);
+                sink(envelope);
             }
         });
 
@@ -9,6 +9,7 @@ use crate::models::{
     FileInputCapabilities, ImageInputCapabilities, LanguageModel, ModelCapabilities,
     ModelInputCapabilities,
 };
+use crate::resource::compose_agent_system_prompt;
 use crate::types::{ModelMessage, Role, Usage};
 
 impl AgentRuntime {
@@ -163,7 +164,7 @@ impl AgentRuntime {
             return;
         }
 
-        let system_prompt = self.system_prompt.lock().await.clone();
+        let system_prompt = self.composed_system_prompt().await;
         let snapshot = {
             let messages = self.messages.lock().await;
             let mut snapshot = messages.clone();
@@ -288,11 +289,9 @@ impl AgentRuntime {
         }
         let mut common: Option<ModelCapabilities> = None;
         for model in candidates {
-            let provider = self.registry.create_provider(
-                model.provider_name(),
-                model.model_id(),
-                &self.roci_config,
-            )?;
+            let provider = self
+                .registry
+                .create_provider_for(model, &self.roci_config)?;
             let capabilities = provider.capabilities().clone();
             common = Some(match common {
                 Some(current) => intersect_prompt_capabilities(current, capabilities),
@@ -302,11 +301,19 @@ impl AgentRuntime {
         common.ok_or_else(|| RociError::Configuration("model candidates cannot be empty".into()))
     }
 
+    /// System prompt for a fresh conversation: the base prompt, configured
+    /// sections, and tool guidance, composed the same way the CLI previews it.
+    async fn composed_system_prompt(&self) -> Option<String> {
+        let base = self.system_prompt.lock().await.clone();
+        let tools = self.tools.lock().await.clone();
+        compose_agent_system_prompt(base, &self.config.prompt_sections, &tools).render()
+    }
+
     async fn prompt_user_message(
         &self,
         user_message: ModelMessage,
     ) -> Result<RunResult, RociError> {
-        let system_prompt = self.system_prompt.lock().await.clone();
+        let system_prompt = self.composed_system_prompt().await;
         let mut msgs = self.messages.lock().await;
         let previous_messages = msgs.clone();
         let mut turn_messages = Vec::new();
@@ -476,7 +483,8 @@ impl AgentRuntime {
     /// canceled turns. Returns [`AgentRuntimeError::StaleRuntime`] when the
     /// turn id revision no longer matches the current thread revision.
     pub async fn cancel_turn(&self, turn_id: TurnId) -> Result<TurnSnapshot, AgentRuntimeError> {
-        let (previous_status, events, canceled) = {
+        self.ensure_runtime_event_publisher().await;
+        let (previous_status, is_active, ack_rx, canceled) = {
             let mut projector =
                 self.chat_projector
                     .lock()
@@ -484,6 +492,11 @@ impl AgentRuntime {
                         message: "chat projector lock poisoned".into(),
                     })?;
             let previous = projector.turn_snapshot(turn_id)?;
+            // The head turn may already be calling the provider before its
+            // start event has been projected.
+            let is_active = projector
+                .read_thread(turn_id.thread_id())
+                .is_ok_and(|thread| thread.active_turn_id == Some(turn_id));
             let mut events = projector.cancel_pending_approvals(turn_id)?;
             let event = projector.cancel_turn(turn_id)?;
             let canceled = match &event.payload {
@@ -495,17 +508,20 @@ impl AgentRuntime {
                 }
             };
             events.push(event);
-            (previous.status, events, canceled)
+            // Send before releasing the projector so a running turn cannot
+            // publish later sequence numbers ahead of the cancellation.
+            let ack_rx = self.send_runtime_events(events)?;
+            (previous.status, is_active, ack_rx, canceled)
         };
 
-        if previous_status == TurnStatus::Running {
+        if previous_status == TurnStatus::Running || is_active {
             let abort_sent = self.abort_active_provider_call().await;
             if abort_sent {
                 self.transition_running_to_aborting().await;
             }
         }
 
-        self.publish_runtime_events(events).await?;
+        Self::await_runtime_events(ack_rx).await?;
 
         Ok(canceled)
     }
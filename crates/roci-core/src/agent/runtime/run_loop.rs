@@ -9,7 +9,7 @@ use crate::agent_loop::runner::{
     AutoCompactionConfig, BeforeAgentStartHookPayload, BeforeAgentStartHookResult,
     CompactionHandler, FollowUpMessagesFn, RunEventSink, RunHooks, SteeringMessagesFn,
 };
-use crate::agent_loop::ApprovalPolicy;
+use crate::agent_loop::{AgentEvent, AgentEventEnvelope, ApprovalPolicy, EventTags};
 use crate::agent_loop::{RunHandle, RunRequest, RunResult, RunStatus, Runner};
 use crate::error::RociError;
 use crate::models::{ModelCandidates, ModelHealthTracker};
@@ -257,52 +257,6 @@ impl AgentRuntime {
             return Ok(RunResult::canceled_with_messages(initial_messages));
         }
 
-        #[cfg(feature = "agent")]
-        let user_input_callback = {
-            let coordinator = self.human_interaction_coordinator.clone();
-            let ui_event_sink = intercepting_sink.clone();
-            let config_timeout = self.config.user_input_timeout_ms;
-            let cb: crate::tools::user_input::RequestUserInputFn = Arc::new(
-                move |request: crate::tools::UserInputRequest| {
-                    let coordinator = coordinator.clone();
-                    let sink = ui_event_sink.clone();
-                    Box::pin(async move {
-                        let human_request =
-                            crate::human_interaction::HumanInteractionRequest::from_user_input(
-                                request.clone(),
-                            );
-                        let rx = coordinator
-                            .create_request(human_request.clone())
-                            .await
-                            .map_err(crate::tools::UserInputError::from)?;
-                        sink(crate::agent_loop::AgentEvent::HumanInteractionRequested {
-                            request: human_request,
-                        });
-                        let effective_timeout = request.timeout_ms.or(config_timeout);
-                        match rx.wait_user_input(effective_timeout).await {
-                            Ok(response) => {
-                                sink(crate::agent_loop::AgentEvent::HumanInteractionResolved {
-                                    response:
-                                        crate::human_interaction::HumanInteractionResponse::from_user_input(
-                                            response.clone(),
-                                        ),
-                                });
-                                Ok(response)
-                            }
-                            Err(error) => {
-                                sink(crate::agent_loop::AgentEvent::HumanInteractionCanceled {
-                                    request_id: request.request_id,
-                                    reason: Some(error.to_string()),
-                                });
-                                Err(error)
-                            }
-                        }
-                    })
-                },
-            );
-            cb
-        };
-
         let candidates = self.candidates.lock().await.clone();
         let candidates = ModelCandidates::new(candidates)?;
         let primary_model = candidates.primary().clone();
@@ -343,6 +297,9 @@ impl AgentRuntime {
         if let Some(workspace_root) = &self.config.workspace_root {
             request = request.with_workspace_root(workspace_root.clone());
         }
+        if let Some(artifacts_dir) = &self.config.artifacts_dir {
+            request = request.with_artifacts_dir(artifacts_dir.clone());
+        }
         if let Some(sandbox_provider) = &self.sandbox_provider {
             request = request.with_sandbox_provider(sandbox_provider.clone());
         }
@@ -350,9 +307,24 @@ impl AgentRuntime {
         if let Some(ref budget) = self.config.context_budget {
             request = request.with_context_budget(budget.clone());
         }
+        if let Some(ref budget) = self.config.run_budget {
+            request = request.with_budget(budget.clone());
+        }
+        if self.config.dry_run {
+            request = request.with_dry_run(self.config.dry_run_results);
+        }
+        for plugin in &self.config.plugins {
+            request = request.with_plugin(plugin.clone());
+        }
+        if let Some(filter) = &self.config.response_filter {
+            request = request
+                .with_response_filter(filter.clone())
+                .with_filtered_tool_results(self.config.filter_tool_results);
+        }
 
         #[cfg(feature = "agent")]
         {
+            let user_input_callback = self.build_user_input_callback(&request);
             request = request
                 .with_user_input_callback(user_input_callback)
                 .with_human_interaction_coordinator(self.human_interaction_coordinator.clone())
@@ -394,6 +366,7 @@ impl AgentRuntime {
 
         let mut run_hooks = RunHooks {
             compaction: None,
+            pre_turn_execution: self.config.pre_turn_execution.clone(),
             pre_tool_use: self.config.pre_tool_use.clone(),
             post_tool_use: self.config.post_tool_use.clone(),
         };
@@ -404,24 +377,25 @@ impl AgentRuntime {
             let registry = self.registry.clone();
             let roci_config = self.roci_config.clone();
             let run_model = primary_model.clone();
-            let compaction_hook: CompactionHandler = Arc::new(move |messages, _cancel| {
-                let compaction_settings = compaction_settings.clone();
-                let session_before_compact = session_before_compact.clone();
-                let registry = registry.clone();
-                let roci_config = roci_config.clone();
-                let run_model = run_model.clone();
-                Box::pin(async move {
-                    AgentRuntime::compact_messages_with_model(
-                        messages,
-                        &run_model,
-                        &compaction_settings,
-                        session_before_compact.as_ref(),
-                        &registry,
-                        &roci_config,
-                    )
-                    .await
-                })
-            });
+            let compaction_hook: CompactionHandler =
+                Arc::new(move |messages, _context, _cancel| {
+                    let compaction_settings = compaction_settings.clone();
+                    let session_before_compact = session_before_compact.clone();
+                    let registry = registry.clone();
+                    let roci_config = roci_config.clone();
+                    let run_model = run_model.clone();
+                    Box::pin(async move {
+                        AgentRuntime::compact_messages_with_model(
+                            messages,
+                            &run_model,
+                            &compaction_settings,
+                            session_before_compact.as_ref(),
+                            &registry,
+                            &roci_config,
+                        )
+                        .await
+                    })
+                });
             run_hooks.compaction = Some(compaction_hook);
             request = request.with_auto_compaction(AutoCompactionConfig {
                 reserve_tokens: self.config.compaction.reserve_tokens,
@@ -539,7 +513,7 @@ impl AgentRuntime {
                         }
                     }
                 }
-                RunStatus::Failed => {
+                RunStatus::Failed | RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded => {
                     self.fail_chat_turn(
                         turn_id,
                         result
@@ -550,7 +524,7 @@ impl AgentRuntime {
                     .await
                 }
                 RunStatus::Canceled => self.cancel_chat_turn(turn_id).await,
-                RunStatus::Running => Ok(()),
+                RunStatus::Queued | RunStatus::Running => Ok(()),
             },
             Err(err) => self.fail_chat_turn(turn_id, err.to_string()).await,
         };
@@ -563,7 +537,10 @@ impl AgentRuntime {
                     && result.status != RunStatus::Canceled =>
             {
                 *self.messages.lock().await = result.messages.clone();
-                if result.status == RunStatus::Failed {
+                if matches!(
+                    result.status,
+                    RunStatus::Failed | RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded
+                ) {
                     *self.last_error.lock().await = result.error.clone();
                 } else {
                     *self.last_error.lock().await = None;
@@ -615,6 +592,75 @@ impl AgentRuntime {
         run_result
     }
 
+    /// Build the `request_user_input` callback for `request`.
+    ///
+    /// Human-interaction events are emitted through the request's agent event
+    /// sink, stamped with the same run identity as runner-emitted events.
+    #[cfg(feature = "agent")]
+    fn build_user_input_callback(
+        &self,
+        request: &RunRequest,
+    ) -> crate::tools::user_input::RequestUserInputFn {
+        let coordinator = self.human_interaction_coordinator.clone();
+        let ui_event_sink = request.agent_event_sink.clone();
+        let run_id = request.run_id;
+        let model = Some(request.active_model().to_string());
+        let tags = EventTags::new(request.event_tags.clone());
+        let seq = request.event_sequence.clone();
+        let config_timeout = self.config.user_input_timeout_ms;
+        Arc::new(move |request: crate::tools::UserInputRequest| {
+            let coordinator = coordinator.clone();
+            let sink = ui_event_sink.clone();
+            let model = model.clone();
+            let tags = tags.clone();
+            let seq = seq.clone();
+            Box::pin(async move {
+                let emit = |event: AgentEvent| {
+                    if let Some(sink) = &sink {
+                        sink(AgentEventEnvelope {
+                            run_id,
+                            seq: seq.next(),
+                            timestamp: chrono::Utc::now(),
+                            model: model.clone(),
+                            tags: tags.clone(),
+                            event,
+                        });
+                    }
+                };
+                let human_request =
+                    crate::human_interaction::HumanInteractionRequest::from_user_input(
+                        request.clone(),
+                    );
+                let rx = coordinator
+                    .create_request(human_request.clone())
+                    .await
+                    .map_err(crate::tools::UserInputError::from)?;
+                emit(AgentEvent::HumanInteractionRequested {
+                    request: human_request,
+                });
+                let effective_timeout = request.timeout_ms.or(config_timeout);
+                match rx.wait_user_input(effective_timeout).await {
+                    Ok(response) => {
+                        emit(AgentEvent::HumanInteractionResolved {
+                            response:
+                                crate::human_interaction::HumanInteractionResponse::from_user_input(
+                                    response.clone(),
+                                ),
+                        });
+                        Ok(response)
+                    }
+                    Err(error) => {
+                        emit(AgentEvent::HumanInteractionCanceled {
+                            request_id: request.request_id,
+                            reason: Some(error.to_string()),
+                        });
+                        Err(error)
+                    }
+                }
+            })
+        })
+    }
+
     fn build_retry_event_sink(
         &self,
         turn_id: TurnId,
@@ -4,8 +4,8 @@ use tokio::sync::{watch, MutexGuard};
 
 use super::{
     AgentRuntime, AgentRuntimeError, AgentRuntimeEvent, AgentRuntimeEventPayload, AgentSnapshot,
-    AgentState, RuntimeCursor, RuntimeEventPublishRequest, RuntimeSnapshot, RuntimeSubscription,
-    SessionResourceSnapshot, ThreadId, ThreadSnapshot,
+    AgentState, RuntimeCursor, RuntimeEventAck, RuntimeEventPublishRequest, RuntimeSnapshot,
+    RuntimeSubscription, SessionResourceSnapshot, ThreadId, ThreadSnapshot,
 };
 use crate::error::RociError;
 use crate::session::{LogicalPath, SessionResourceMetadata};
@@ -244,23 +244,39 @@ impl AgentRuntime {
         events: Vec<AgentRuntimeEvent>,
     ) -> Result<(), AgentRuntimeError> {
         self.ensure_runtime_event_publisher().await;
+        let ack_rx = self.send_runtime_events(events)?;
+        Self::await_runtime_events(ack_rx).await
+    }
+
+    /// Queue projected events without waiting for the store to persist them.
+    ///
+    /// Callers that project under the chat projector lock should send before
+    /// releasing it so event sequence numbers reach the store in order.
+    pub(super) fn send_runtime_events(
+        &self,
+        events: Vec<AgentRuntimeEvent>,
+    ) -> Result<RuntimeEventAck, AgentRuntimeError> {
         let (ack_tx, ack_rx) = tokio::sync::oneshot::channel();
-        {
-            let _send_guard = self.runtime_event_send_lock.lock().map_err(|_| {
-                AgentRuntimeError::ProjectionFailed {
-                    message: "runtime event send lock poisoned".to_string(),
-                }
+        let _send_guard = self.runtime_event_send_lock.lock().map_err(|_| {
+            AgentRuntimeError::ProjectionFailed {
+                message: "runtime event send lock poisoned".to_string(),
+            }
+        })?;
+        self.runtime_event_publish_tx
+            .send(RuntimeEventPublishRequest {
+                events,
+                ack_tx: Some(ack_tx),
+                error_slot: None,
+            })
+            .map_err(|_| AgentRuntimeError::ProjectionFailed {
+                message: "runtime event publisher closed".to_string(),
             })?;
-            self.runtime_event_publish_tx
-                .send(RuntimeEventPublishRequest {
-                    events,
-                    ack_tx: Some(ack_tx),
-                    error_slot: None,
-                })
-                .map_err(|_| AgentRuntimeError::ProjectionFailed {
-                    message: "runtime event publisher closed".to_string(),
-                })?;
-        }
+        Ok(ack_rx)
+    }
+
+    pub(super) async fn await_runtime_events(
+        ack_rx: RuntimeEventAck,
+    ) -> Result<(), AgentRuntimeError> {
         ack_rx
             .await
             .map_err(|_| AgentRuntimeError::ProjectionFailed {
@@ -141,11 +141,9 @@ impl AgentRuntime {
             Some(model) => LanguageModel::from_str(model)?,
             None => model,
         };
-        let provider = self.registry.create_provider(
-            summary_model.provider_name(),
-            summary_model.model_id(),
-            &self.roci_config,
-        )?;
+        let provider = self
+            .registry
+            .create_provider_for(&summary_model, &self.roci_config)?;
 
         let transcript = serialize_messages_for_summary(&selected_entries);
         let summary_prompt = format!(
@@ -156,7 +154,8 @@ impl AgentRuntime {
                 messages: vec![
                     ModelMessage::system("You create precise branch transition summaries"),
                     ModelMessage::user(summary_prompt),
-                ],
+                ]
+                .into(),
                 settings: GenerationSettings::default(),
                 tools: None,
                 response_format: None,
@@ -166,6 +165,7 @@ impl AgentRuntime {
                 payload_callback: None,
                 session_id: None,
                 transport: None,
+                tool_call_ids: None,
             })
             .await?;
 
@@ -383,11 +383,7 @@ impl AgentRuntime {
         let summary_text = match summary_override {
             Some(summary) => summary,
             None => {
-                let provider = registry.create_provider(
-                    summary_model.provider_name(),
-                    summary_model.model_id(),
-                    roci_config,
-                )?;
+                let provider = registry.create_provider_for(&summary_model, roci_config)?;
                 let transcript = serialize_messages_for_summary(&messages_to_summarize);
                 let summary_prompt = format!(
                     "Summarize the conversation transcript into concise bullets focused on user goals, constraints, progress, decisions, next steps, and critical context.\n\nTranscript:\n{transcript}"
@@ -399,7 +395,8 @@ impl AgentRuntime {
                                 "You create precise conversation compaction summaries",
                             ),
                             ModelMessage::user(summary_prompt),
-                        ],
+                        ]
+                        .into(),
                         settings: GenerationSettings::default(),
                         tools: None,
                         response_format: None,
@@ -409,6 +406,7 @@ impl AgentRuntime {
                         payload_callback: None,
                         session_id: None,
                         transport: None,
+                        tool_call_ids: None,
                     })
                     .await?;
                 summary_response.text.trim().to_string()
@@ -298,6 +298,8 @@ impl ModelProvider for BlockingTextProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })
         })
         .chain(stream::pending());
@@ -1285,6 +1285,8 @@ impl ModelProvider for CountingBlockingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })
         })
         .chain(stream::pending());
@@ -1436,7 +1438,7 @@ impl ModelProvider for CandidateFallbackProvider {
         self.requests
             .lock()
             .expect("requests lock")
-            .push((self.model_id.clone(), request.messages.clone()));
+            .push((self.model_id.clone(), request.messages.to_vec()));
         if self.model_id.contains("timeout") {
             return Err(RociError::Timeout(10));
         }
@@ -1450,6 +1452,8 @@ impl ModelProvider for CandidateFallbackProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -1460,6 +1464,8 @@ impl ModelProvider for CandidateFallbackProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(stream::iter(events)))
@@ -1531,7 +1537,7 @@ impl ModelProvider for FullMessageRecordingProvider {
         self.requests
             .lock()
             .expect("requests lock")
-            .push(request.messages.clone());
+            .push(request.messages.to_vec());
         let events: Vec<Result<TextStreamDelta, RociError>> = vec![
             Ok(TextStreamDelta {
                 text: "ok".to_string(),
@@ -1542,6 +1548,8 @@ impl ModelProvider for FullMessageRecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -1552,6 +1560,8 @@ impl ModelProvider for FullMessageRecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(stream::iter(events)))
@@ -1646,6 +1656,8 @@ impl ModelProvider for RecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -1656,6 +1668,8 @@ impl ModelProvider for RecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(stream::iter(events)))
@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 use crate::agent::subagents::events::build_child_event_sink;
 use crate::agent::subagents::types::{SubagentEvent, SubagentId};
-use crate::agent_loop::AgentEvent;
+use crate::agent_loop::{AgentEvent, AgentEventEnvelope};
 
 // ---------------------------------------------------------------------------
 // build_child_event_sink
@@ -20,7 +20,7 @@ fn event_sink_wraps_agent_event_with_label() {
     let event = AgentEvent::AgentStart {
         run_id: Uuid::new_v4(),
     };
-    sink(event);
+    sink(AgentEventEnvelope::new(Uuid::new_v4(), event));
 
     let received = rx.try_recv().unwrap();
     match received {
@@ -43,9 +43,12 @@ fn event_sink_wraps_agent_event_without_label() {
     let id: SubagentId = Uuid::new_v4();
     let sink = build_child_event_sink(id, None, tx);
 
-    sink(AgentEvent::AgentStart {
-        run_id: Uuid::new_v4(),
-    });
+    sink(AgentEventEnvelope::new(
+        Uuid::new_v4(),
+        AgentEvent::AgentStart {
+            run_id: Uuid::new_v4(),
+        },
+    ));
 
     let received = rx.try_recv().unwrap();
     match received {
@@ -65,13 +68,19 @@ fn event_sink_forwards_multiple_events() {
     let id: SubagentId = Uuid::new_v4();
     let sink = build_child_event_sink(id, Some("multi".into()), tx);
 
-    sink(AgentEvent::AgentStart {
-        run_id: Uuid::new_v4(),
-    });
-    sink(AgentEvent::AgentEnd {
-        run_id: Uuid::new_v4(),
-        messages: Vec::new(),
-    });
+    sink(AgentEventEnvelope::new(
+        Uuid::new_v4(),
+        AgentEvent::AgentStart {
+            run_id: Uuid::new_v4(),
+        },
+    ));
+    sink(AgentEventEnvelope::new(
+        Uuid::new_v4(),
+        AgentEvent::AgentEnd {
+            run_id: Uuid::new_v4(),
+            messages: Vec::new(),
+        },
+    ));
 
     let first = rx.try_recv().unwrap();
     let second = rx.try_recv().unwrap();
@@ -97,9 +106,12 @@ fn event_sink_does_not_panic_when_no_receivers() {
     let sink = build_child_event_sink(id, None, tx);
 
     // Drop the receiver -- sink should silently discard
-    sink(AgentEvent::AgentStart {
-        run_id: Uuid::new_v4(),
-    });
+    sink(AgentEventEnvelope::new(
+        Uuid::new_v4(),
+        AgentEvent::AgentStart {
+            run_id: Uuid::new_v4(),
+        },
+    ));
 }
 
 // ---------------------------------------------------------------------------
@@ -124,10 +136,12 @@ async fn submit_user_input_unknown_request_returns_error() {
     let model = LanguageModel::Known {
         provider_key: "test".into(),
         model_id: "test-model".into(),
+        provider_options: Default::default(),
     };
     let config = AgentConfig {
         candidates: vec![model],
         system_prompt: None,
+        prompt_sections: Vec::new(),
         tools: Vec::new(),
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -157,12 +171,20 @@ async fn submit_user_input_unknown_request_returns_error() {
         compaction: crate::resource::CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         subagents: None,
         human_interaction_coordinator: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         chat: Default::default(),
     };
 
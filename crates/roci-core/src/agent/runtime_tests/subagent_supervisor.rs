@@ -42,6 +42,7 @@ fn make_test_model() -> LanguageModel {
     LanguageModel::Known {
         provider_key: "test".into(),
         model_id: "test-model".into(),
+        provider_options: Default::default(),
     }
 }
 
@@ -63,6 +64,7 @@ fn make_base_config() -> AgentConfig {
     AgentConfig {
         candidates: vec![make_test_model()],
         system_prompt: None,
+        prompt_sections: Vec::new(),
         tools: Vec::new(),
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -92,12 +94,20 @@ fn make_base_config() -> AgentConfig {
         compaction: CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         #[cfg(feature = "agent")]
         human_interaction_coordinator: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         chat: Default::default(),
         #[cfg(feature = "agent")]
         subagents: None,
@@ -222,6 +232,8 @@ impl ModelProvider for RecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -232,6 +244,8 @@ impl ModelProvider for RecordingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ])))
     }
@@ -353,6 +367,8 @@ impl ModelProvider for BlockingAskUserProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -363,6 +379,8 @@ impl ModelProvider for BlockingAskUserProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ])))
     }
@@ -412,6 +430,7 @@ fn make_blocking_ask_user_supervisor() -> SubagentSupervisor {
     base_config.candidates = vec![LanguageModel::Known {
         provider_key: "test".into(),
         model_id: "test-model".into(),
+        provider_options: Default::default(),
     }];
     base_config.tools = vec![ask_user_tool];
 
@@ -443,7 +462,7 @@ fn captured_request_messages(requests: &Arc<Mutex<Vec<ProviderRequest>>>) -> Vec
         .lock()
         .expect("request capture lock should not be poisoned");
     assert_eq!(requests.len(), 1, "expected exactly one provider request");
-    requests[0].messages.clone()
+    requests[0].messages.to_vec()
 }
 
 fn assert_test_system_prompt(message: &ModelMessage) {
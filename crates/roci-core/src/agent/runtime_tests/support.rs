@@ -96,6 +96,10 @@ impl ModelProvider for SummaryProvider {
             tool_calls: Vec::new(),
             finish_reason: None,
             thinking: Vec::new(),
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
         })
     }
 
@@ -115,6 +119,7 @@ pub(super) fn test_agent_config() -> AgentConfig {
     AgentConfig {
         candidates: vec![model],
         system_prompt: None,
+        prompt_sections: Vec::new(),
         tools: Vec::new(),
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -144,10 +149,18 @@ pub(super) fn test_agent_config() -> AgentConfig {
         compaction: CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         chat: Default::default(),
         #[cfg(feature = "agent")]
         subagents: None,
@@ -451,6 +464,8 @@ impl ModelProvider for StreamingTextProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -466,6 +481,8 @@ impl ModelProvider for StreamingTextProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(futures::stream::iter(events)))
@@ -511,6 +528,8 @@ impl ModelProvider for GatedStreamingProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })
         })))
     }
@@ -554,6 +573,8 @@ impl ModelProvider for StreamingChunksProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }));
         }
         events.push(Ok(TextStreamDelta {
@@ -570,6 +591,8 @@ impl ModelProvider for StreamingChunksProvider {
             reasoning: None,
             reasoning_signature: None,
             reasoning_type: None,
+            image: None,
+            response_metadata: None,
         }));
         Ok(Box::pin(futures::stream::iter(events)))
     }
@@ -612,6 +635,8 @@ impl ModelProvider for ReasoningTextProvider {
                 reasoning: Some("think ".to_string()),
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -622,6 +647,8 @@ impl ModelProvider for ReasoningTextProvider {
                 reasoning: Some("more".to_string()),
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: "answer".to_string(),
@@ -632,6 +659,8 @@ impl ModelProvider for ReasoningTextProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -647,6 +676,8 @@ impl ModelProvider for ReasoningTextProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(futures::stream::iter(events)))
@@ -691,6 +722,8 @@ impl ModelProvider for PlanJsonProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -701,6 +734,8 @@ impl ModelProvider for PlanJsonProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ];
         Ok(Box::pin(futures::stream::iter(events)))
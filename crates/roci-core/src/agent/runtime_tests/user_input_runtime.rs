@@ -97,6 +97,8 @@ impl ModelProvider for AskUserProvider {
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
                 Ok(TextStreamDelta {
                     text: String::new(),
@@ -107,6 +109,8 @@ impl ModelProvider for AskUserProvider {
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
             ]
         } else {
@@ -120,6 +124,8 @@ impl ModelProvider for AskUserProvider {
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
                 Ok(TextStreamDelta {
                     text: String::new(),
@@ -130,6 +136,8 @@ impl ModelProvider for AskUserProvider {
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
             ]
         };
@@ -187,8 +195,8 @@ async fn prompt_emits_user_input_event_and_submit_user_input_unblocks_tool() {
     config.event_sink = Some({
         let event_requests = event_requests.clone();
         let agent_slot = agent_slot.clone();
-        Arc::new(move |event| {
-            if let AgentEvent::HumanInteractionRequested { request } = event {
+        Arc::new(move |envelope| {
+            if let AgentEvent::HumanInteractionRequested { request } = envelope.event {
                 let request = request
                     .to_user_input()
                     .expect("human interaction should be ask_user");
@@ -284,8 +292,8 @@ async fn abort_while_waiting_for_user_input_unblocks_run() {
     config.tools = vec![ask_user_tool];
     config.event_sink = Some({
         let request_seen_tx = Arc::clone(&request_seen_tx);
-        Arc::new(move |event| {
-            if let AgentEvent::HumanInteractionRequested { request } = event {
+        Arc::new(move |envelope| {
+            if let AgentEvent::HumanInteractionRequested { request } = envelope.event {
                 if let Some(tx) = request_seen_tx.lock().expect("event lock").take() {
                     let request = request
                         .to_user_input()
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use tokio::sync::broadcast;
 
 use crate::agent_loop::runner::AgentEventSink;
-use crate::agent_loop::AgentEvent;
+use crate::agent_loop::{AgentEvent, AgentEventEnvelope};
 
 use super::types::{SubagentEvent, SubagentId};
 
@@ -39,7 +39,8 @@ pub(crate) fn build_child_event_sink_with_critical_sink(
     event_tx: broadcast::Sender<SubagentEvent>,
     critical_sink: Option<CriticalSubagentEventSink>,
 ) -> AgentEventSink {
-    Arc::new(move |event: AgentEvent| {
+    Arc::new(move |envelope: AgentEventEnvelope| {
+        let event = envelope.event;
         if matches!(&event, AgentEvent::MessageUpdate { message, .. } if message.text().is_empty())
         {
             return;
@@ -74,11 +75,14 @@ mod tests {
         let sink = build_child_event_sink_with_critical_sink(id, None, tx, Some(critical_sink));
 
         for sequence in 0..300 {
-            sink(AgentEvent::ToolExecutionStart {
-                tool_call_id: sequence.to_string(),
-                tool_name: "stress".into(),
-                args: serde_json::Value::Null,
-            });
+            sink(AgentEventEnvelope::new(
+                Uuid::new_v4(),
+                AgentEvent::ToolExecutionStart {
+                    tool_call_id: sequence.to_string(),
+                    tool_name: "stress".into(),
+                    args: serde_json::Value::Null,
+                },
+            ));
         }
 
         let observed = observed.lock().unwrap();
@@ -106,7 +110,7 @@ mod tests {
         let event = AgentEvent::AgentStart {
             run_id: Uuid::new_v4(),
         };
-        sink(event);
+        sink(AgentEventEnvelope::new(Uuid::new_v4(), event));
 
         let received = rx.try_recv().unwrap();
         match received {
@@ -129,9 +133,12 @@ mod tests {
         let id = Uuid::new_v4();
         let sink = build_child_event_sink(id, None, tx);
 
-        sink(AgentEvent::AgentStart {
-            run_id: Uuid::new_v4(),
-        });
+        sink(AgentEventEnvelope::new(
+            Uuid::new_v4(),
+            AgentEvent::AgentStart {
+                run_id: Uuid::new_v4(),
+            },
+        ));
 
         let received = rx.try_recv().unwrap();
         match received {
@@ -147,19 +154,24 @@ mod tests {
         let (tx, mut rx) = broadcast::channel(16);
         let sink = build_child_event_sink(Uuid::new_v4(), None, tx);
 
-        sink(AgentEvent::MessageUpdate {
-            message: crate::types::ModelMessage::assistant(""),
-            assistant_message_event: crate::types::TextStreamDelta {
-                text: String::new(),
-                event_type: crate::types::StreamEventType::TextDelta,
-                tool_call: None,
-                finish_reason: None,
-                usage: None,
-                reasoning: None,
-                reasoning_signature: None,
-                reasoning_type: None,
+        sink(AgentEventEnvelope::new(
+            Uuid::new_v4(),
+            AgentEvent::MessageUpdate {
+                message: crate::types::ModelMessage::assistant(""),
+                assistant_message_event: crate::types::TextStreamDelta {
+                    text: String::new(),
+                    event_type: crate::types::StreamEventType::TextDelta,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: None,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                },
             },
-        });
+        ));
 
         assert!(rx.try_recv().is_err());
     }
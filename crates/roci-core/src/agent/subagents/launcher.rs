@@ -98,6 +98,7 @@ impl SubagentLauncher for InProcessLauncher {
 /// - tools: replace with profile-selected child tools.
 /// - user input coordinator: replace with supervisor coordinator.
 /// - compaction: inherit. Chat config: reset to avoid sharing parent event store.
+/// - response filter: inherit, so child output is stored under the same rules.
 pub(super) fn build_child_config(
     parent: &AgentConfig,
     candidates: Vec<LanguageModel>,
@@ -119,6 +120,7 @@ pub(super) fn build_child_config(
     Ok(AgentConfig {
         candidates,
         system_prompt: None,
+        prompt_sections: Vec::new(),
         tools,
         tool_visibility_policy: parent.tool_visibility_policy.clone(),
         event_sink,
@@ -134,6 +136,7 @@ pub(super) fn build_child_config(
         session_id: None,
         session: None,
         workspace_root: parent.workspace_root.clone(),
+        artifacts_dir: parent.artifacts_dir.clone(),
         sandbox_provider: parent.sandbox_provider.clone(),
         steering_mode: parent.steering_mode,
         follow_up_mode: parent.follow_up_mode,
@@ -150,10 +153,18 @@ pub(super) fn build_child_config(
         compaction: parent.compaction.clone(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: parent.response_filter.clone(),
+        filter_tool_results: parent.filter_tool_results,
         user_input_timeout_ms: parent.user_input_timeout_ms,
         context_budget: parent.context_budget.clone(),
+        run_budget: parent.run_budget.clone(),
+        // Children of a dry run must not execute tools either.
+        dry_run: parent.dry_run,
+        dry_run_results: parent.dry_run_results,
         chat: Default::default(),
         #[cfg(feature = "agent")]
         subagents: None,
@@ -279,6 +290,7 @@ mod tests {
             candidates: vec![LanguageModel::Known {
                 provider_key: "test".to_string(),
                 model_id: "test-model".to_string(),
+                provider_options: Default::default(),
             }],
             workspace_root: Some(workspace_root),
             ..AgentConfig::default()
@@ -334,6 +346,7 @@ mod tests {
         let model = LanguageModel::Known {
             provider_key: "test".into(),
             model_id: "test-model".into(),
+            provider_options: Default::default(),
         };
         let parent = AgentConfig {
             system_prompt: Some("parent prompt".into()),
@@ -362,6 +375,7 @@ mod tests {
         let model = LanguageModel::Known {
             provider_key: "test".into(),
             model_id: "test-model".into(),
+            provider_options: Default::default(),
         };
         let parent = AgentConfig {
             subagents: Some(Default::default()),
@@ -400,7 +414,7 @@ mod tests {
             before_agent_start: Some(Arc::new(|_| {
                 Box::pin(async { Ok(BeforeAgentStartHookResult::Continue) })
             })),
-            pre_tool_use: Some(Arc::new(|_, _| {
+            pre_tool_use: Some(Arc::new(|_, _, _| {
                 Box::pin(async { Ok(PreToolUseHookResult::Continue) })
             })),
             approval_policy: ApprovalPolicy::never(),
@@ -428,6 +442,7 @@ mod tests {
         let model = LanguageModel::Known {
             provider_key: "test".into(),
             model_id: "test-model".into(),
+            provider_options: Default::default(),
         };
 
         let cfg = build_child_config(
@@ -473,6 +488,7 @@ mod tests {
         assert!(cfg.session_before_tree.is_none());
         assert!(cfg.pre_tool_use.is_none());
         assert!(cfg.post_tool_use.is_none());
+        assert!(cfg.plugins.is_empty());
         assert_eq!(cfg.chat, Default::default(), "chat config resets");
     }
 
@@ -531,6 +547,7 @@ mod tests {
             vec![LanguageModel::Known {
                 provider_key: "test".into(),
                 model_id: "test-model".into(),
+                provider_options: Default::default(),
             }],
             Vec::new(),
             Some("maximum"),
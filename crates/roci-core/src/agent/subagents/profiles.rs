@@ -243,6 +243,7 @@ impl SubagentProfileRegistry {
                 .map(|candidate| LanguageModel::Known {
                     provider_key: candidate.provider.clone(),
                     model_id: candidate.model.clone(),
+                    provider_options: Default::default(),
                 })
                 .collect(),
             reasoning_effort: first.reasoning_effort.clone(),
@@ -271,6 +272,7 @@ impl SubagentProfileRegistry {
                     model: LanguageModel::Known {
                         provider_key: candidate.provider.clone(),
                         model_id: candidate.model.clone(),
+                        provider_options: Default::default(),
                     },
                     reasoning_effort: candidate.reasoning_effort.clone(),
                 });
@@ -1560,7 +1562,8 @@ inherits = "parent"
             resolved.model,
             LanguageModel::Known {
                 provider_key: "lmstudio".into(),
-                model_id: "local-model".into()
+                model_id: "local-model".into(),
+                provider_options: Default::default(),
             }
         );
         assert_eq!(resolved.reasoning_effort.as_deref(), Some("medium"));
@@ -1602,7 +1605,8 @@ inherits = "parent"
             resolved.model,
             LanguageModel::Known {
                 provider_key: "anthropic".into(),
-                model_id: "claude-sonnet-4.5".into()
+                model_id: "claude-sonnet-4.5".into(),
+                provider_options: Default::default(),
             }
         );
         assert_eq!(resolved.reasoning_effort.as_deref(), Some("medium"));
@@ -1646,7 +1650,8 @@ inherits = "parent"
             resolved.model,
             LanguageModel::Known {
                 provider_key: "anthropic".into(),
-                model_id: "claude-sonnet-4.5".into()
+                model_id: "claude-sonnet-4.5".into(),
+                provider_options: Default::default(),
             }
         );
         assert_eq!(resolved.reasoning_effort.as_deref(), Some("medium"));
@@ -1682,7 +1687,8 @@ inherits = "parent"
             resolved.model,
             LanguageModel::Known {
                 provider_key: "anthropic".into(),
-                model_id: "claude-sonnet-4.5".into()
+                model_id: "claude-sonnet-4.5".into(),
+                provider_options: Default::default(),
             }
         );
     }
@@ -1710,7 +1716,8 @@ inherits = "parent"
             resolved,
             LanguageModel::Known {
                 provider_key: "anthropic".into(),
-                model_id: "claude-sonnet-4.5".into()
+                model_id: "claude-sonnet-4.5".into(),
+                provider_options: Default::default(),
             }
         );
     }
@@ -1767,7 +1774,8 @@ inherits = "parent"
             resolved.model,
             LanguageModel::Known {
                 provider_key: "lmstudio".into(),
-                model_id: "local-model".into()
+                model_id: "local-model".into(),
+                provider_options: Default::default(),
             }
         );
         assert_eq!(resolved.reasoning_effort.as_deref(), Some("medium"));
@@ -30,6 +30,7 @@ fn test_model() -> LanguageModel {
     LanguageModel::Known {
         provider_key: "test".into(),
         model_id: "test-model".into(),
+        provider_options: Default::default(),
     }
 }
 
@@ -230,6 +231,8 @@ impl ModelProvider for TestProvider {
             reasoning: None,
             reasoning_signature: None,
             reasoning_type: None,
+            image: None,
+            response_metadata: None,
         };
         if self.completes {
             let done = TextStreamDelta {
@@ -241,6 +244,8 @@ impl ModelProvider for TestProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             };
             Ok(Box::pin(stream::iter(vec![Ok(text_delta), Ok(done)])))
         } else {
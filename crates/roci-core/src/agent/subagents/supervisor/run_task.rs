@@ -99,9 +99,11 @@ pub(super) async fn run_child_task(
         Ok(rr) => {
             let st = match rr.status {
                 RunStatus::Completed => SubagentStatus::Completed,
-                RunStatus::Failed => SubagentStatus::Failed,
+                RunStatus::Failed | RunStatus::BudgetExceeded | RunStatus::DeadlineExceeded => {
+                    SubagentStatus::Failed
+                }
                 RunStatus::Canceled => SubagentStatus::Aborted,
-                RunStatus::Running => SubagentStatus::Running,
+                RunStatus::Queued | RunStatus::Running => SubagentStatus::Running,
             };
             let result = SubagentRunResult {
                 subagent_id: child_id,
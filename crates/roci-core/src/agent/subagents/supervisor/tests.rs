@@ -73,6 +73,7 @@ fn make_test_model() -> LanguageModel {
     LanguageModel::Known {
         provider_key: "test".into(),
         model_id: "test-model".into(),
+        provider_options: Default::default(),
     }
 }
 
@@ -137,6 +138,7 @@ fn make_base_config() -> AgentConfig {
     AgentConfig {
         candidates: vec![make_test_model()],
         system_prompt: None,
+        prompt_sections: Vec::new(),
         tools: Vec::new(),
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -166,12 +168,20 @@ fn make_base_config() -> AgentConfig {
         compaction: CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         #[cfg(feature = "agent")]
         human_interaction_coordinator: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         chat: Default::default(),
         #[cfg(feature = "agent")]
         subagents: None,
@@ -442,10 +452,12 @@ async fn spawn_applies_tool_policy_and_reasoning_effort() {
             LanguageModel::Known {
                 provider_key: "test".into(),
                 model_id: "test-model".into(),
+                provider_options: Default::default(),
             },
             LanguageModel::Known {
                 provider_key: "local".into(),
                 model_id: "fallback-model".into(),
+                provider_options: Default::default(),
             },
         ]
     );
@@ -462,10 +474,12 @@ async fn spawn_inherits_parent_candidates_when_profile_candidates_are_empty() {
         LanguageModel::Known {
             provider_key: "test".into(),
             model_id: "parent-primary".into(),
+            provider_options: Default::default(),
         },
         LanguageModel::Known {
             provider_key: "local".into(),
             model_id: "parent-fallback".into(),
+            provider_options: Default::default(),
         },
     ];
     let (supervisor, _, captured_config) = make_supervisor_with_mock_config(
@@ -598,6 +612,7 @@ async fn spawn_filters_unauth_remote_primary_to_local_fallback() {
         vec![LanguageModel::Known {
             provider_key: "local".into(),
             model_id: "local-model".into(),
+            provider_options: Default::default(),
         }]
     );
     assert_eq!(cfg.settings.reasoning_effort, Some(ReasoningEffort::Medium));
@@ -976,6 +991,8 @@ impl ModelProvider for BlockingStreamProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })
         })
         .chain(stream::pending());
@@ -1088,6 +1105,10 @@ impl ModelProvider for DelayedProvider {
             tool_calls: Vec::new(),
             finish_reason: None,
             thinking: Vec::new(),
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
         })
     }
 
@@ -1111,6 +1132,8 @@ impl ModelProvider for DelayedProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             });
             yield Ok(TextStreamDelta {
                 text: String::new(),
@@ -1121,6 +1144,8 @@ impl ModelProvider for DelayedProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             });
         }))
     }
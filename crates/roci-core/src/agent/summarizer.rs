@@ -0,0 +1,390 @@
+//! Short conversation summaries for run titles and session listings.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::RociError;
+use crate::generation::text::generate_text;
+use crate::provider::ModelProvider;
+use crate::types::{GenerationSettings, ModelMessage, Role};
+
+/// Messages taken from the start of the history (the opening request).
+pub const SUMMARY_HEAD_MESSAGES: usize = 2;
+/// Messages taken from the end of the history (the latest exchange).
+pub const SUMMARY_TAIL_MESSAGES: usize = 6;
+/// Per-message character cap inside the summary prompt.
+pub const SUMMARY_MESSAGE_CHARS: usize = 800;
+
+/// Shape of the summary produced by [`summarize_conversation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SummaryKind {
+    /// A few words, suitable for a session list ("Fix flaky auth test").
+    Title,
+    /// One sentence describing the conversation so far.
+    OneLine,
+    /// A short paragraph.
+    Paragraph,
+}
+
+impl SummaryKind {
+    /// Maximum length of the returned summary, in characters.
+    #[must_use]
+    pub const fn max_chars(self) -> usize {
+        match self {
+            Self::Title => 60,
+            Self::OneLine => 140,
+            Self::Paragraph => 600,
+        }
+    }
+
+    const fn max_tokens(self) -> u32 {
+        match self {
+            Self::Title => 24,
+            Self::OneLine => 64,
+            Self::Paragraph => 256,
+        }
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Self::Title => {
+                "Write a title of at most six words for the conversation below. \
+                 Reply with the title only: no quotes, no trailing punctuation."
+            }
+            Self::OneLine => {
+                "Summarize the conversation below in one sentence. \
+                 Reply with the sentence only."
+            }
+            Self::Paragraph => {
+                "Summarize the conversation below in a short paragraph covering the goal, \
+                 progress, and open questions. Reply with the paragraph only."
+            }
+        }
+    }
+}
+
+/// Summarize a conversation with `model`.
+///
+/// Only a bounded slice of `messages` is sent: the opening messages, the most
+/// recent ones, and a marker for what was omitted. The result is trimmed to
+/// [`SummaryKind::max_chars`] at a word boundary. Callers typically pass a
+/// cheap model.
+///
+/// # Errors
+///
+/// Returns an error when there is no user or assistant text to summarize, the
+/// provider fails, or the model returns an empty summary.
+pub async fn summarize_conversation(
+    model: &dyn ModelProvider,
+    messages: &[ModelMessage],
+    kind: SummaryKind,
+) -> Result<String, RociError> {
+    let prompt = build_summary_prompt(messages, kind).ok_or_else(|| {
+        RociError::InvalidArgument("conversation has no text to summarize".to_string())
+    })?;
+    let settings = GenerationSettings {
+        max_tokens: Some(kind.max_tokens()),
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+    let result = generate_text(model, prompt, settings, &[]).await?;
+    let summary = clean_summary(&result.text, kind);
+    if summary.is_empty() {
+        return Err(RociError::InvalidState(
+            "summary model returned no text".to_string(),
+        ));
+    }
+    Ok(summary)
+}
+
+/// Title used when summarization is unavailable.
+#[must_use]
+pub fn fallback_title(now: DateTime<Utc>) -> String {
+    format!("Session {}", now.format("%Y-%m-%d %H:%M"))
+}
+
+/// Truncate `text` to at most `max_chars` characters, cutting at the last
+/// word boundary and appending an ellipsis when anything was dropped.
+#[must_use]
+pub fn truncate_at_word_boundary(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+    let head: String = text.chars().take(max_chars - 1).collect();
+    let cut = match head.rfind(char::is_whitespace) {
+        Some(index) if index > 0 => &head[..index],
+        _ => head.as_str(),
+    };
+    let cut = cut.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ':'));
+    format!("{cut}…")
+}
+
+fn build_summary_prompt(messages: &[ModelMessage], kind: SummaryKind) -> Option<Vec<ModelMessage>> {
+    let lines = messages
+        .iter()
+        .filter_map(|message| {
+            let label = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                Role::System | Role::Tool => return None,
+            };
+            let text = message.text();
+            let text = text.trim();
+            (!text.is_empty()).then(|| {
+                format!(
+                    "{label}: {}",
+                    truncate_at_word_boundary(text, SUMMARY_MESSAGE_CHARS)
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let transcript = if lines.len() <= SUMMARY_HEAD_MESSAGES + SUMMARY_TAIL_MESSAGES {
+        lines.join("\n\n")
+    } else {
+        let omitted = lines.len() - SUMMARY_HEAD_MESSAGES - SUMMARY_TAIL_MESSAGES;
+        let mut selected = lines[..SUMMARY_HEAD_MESSAGES].to_vec();
+        selected.push(format!("[{omitted} earlier messages omitted]"));
+        selected.extend_from_slice(&lines[lines.len() - SUMMARY_TAIL_MESSAGES..]);
+        selected.join("\n\n")
+    };
+
+    Some(vec![
+        ModelMessage::system(kind.instruction()),
+        ModelMessage::user(format!("<conversation>\n{transcript}\n</conversation>")),
+    ])
+}
+
+fn clean_summary(text: &str, kind: SummaryKind) -> String {
+    let text = text.trim();
+    let text = match kind {
+        SummaryKind::Title | SummaryKind::OneLine => text.lines().next().unwrap_or_default(),
+        SummaryKind::Paragraph => text,
+    };
+    let mut text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+        .to_string();
+    if kind == SummaryKind::Title {
+        text = text.trim_end_matches('.').to_string();
+    }
+    truncate_at_word_boundary(&text, kind.max_chars())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use futures::stream::BoxStream;
+
+    use super::*;
+    use crate::models::ModelCapabilities;
+    use crate::provider::{ProviderRequest, ProviderResponse};
+    use crate::types::{FinishReason, TextStreamDelta, Usage};
+
+    struct RecordingProvider {
+        reply: Result<String, String>,
+        requests: Arc<Mutex<Vec<ProviderRequest>>>,
+    }
+
+    impl RecordingProvider {
+        fn replying(text: &str) -> Self {
+            Self {
+                reply: Ok(text.to_string()),
+                requests: Arc::default(),
+            }
+        }
+
+        fn failing() -> Self {
+            Self {
+                reply: Err("summary model unavailable".to_string()),
+                requests: Arc::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ModelProvider for RecordingProvider {
+        fn provider_name(&self) -> &str {
+            "recording"
+        }
+
+        fn model_id(&self) -> &str {
+            "cheap"
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            static CAPABILITIES: std::sync::OnceLock<ModelCapabilities> =
+                std::sync::OnceLock::new();
+            CAPABILITIES.get_or_init(ModelCapabilities::default)
+        }
+
+        async fn generate_text(
+            &self,
+            request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            self.requests.lock().unwrap().push(request.clone());
+            match &self.reply {
+                Ok(text) => Ok(ProviderResponse {
+                    text: text.clone(),
+                    usage: Usage::default(),
+                    tool_calls: vec![],
+                    finish_reason: Some(FinishReason::Stop),
+                    thinking: vec![],
+                    metadata: Default::default(),
+                    refusal: None,
+                    images: Vec::new(),
+                    response_metadata: None,
+                }),
+                Err(message) => Err(RociError::Provider {
+                    provider: "recording".to_string(),
+                    message: message.clone(),
+                }),
+            }
+        }
+
+        async fn stream_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            panic!("summaries do not stream")
+        }
+    }
+
+    fn long_history(turns: usize) -> Vec<ModelMessage> {
+        (0..turns)
+            .flat_map(|turn| {
+                [
+                    ModelMessage::user(format!("question {turn} {}", "detail ".repeat(400))),
+                    ModelMessage::assistant(format!("answer {turn}")),
+                ]
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn summary_prompt_is_bounded_to_head_and_tail_messages() {
+        let provider = RecordingProvider::replying("Fix flaky auth test");
+        let history = long_history(20);
+
+        summarize_conversation(&provider, &history, SummaryKind::Title)
+            .await
+            .expect("summary should succeed");
+
+        let requests = provider.requests.lock().unwrap();
+        let request = &requests[0];
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].role, Role::System);
+        assert_eq!(request.settings.max_tokens, Some(24));
+        let transcript = request.messages[1].text();
+        assert!(transcript.contains("question 0 "));
+        assert!(transcript.contains("answer 0"));
+        assert!(transcript.contains("[32 earlier messages omitted]"));
+        assert!(!transcript.contains("question 5 "));
+        assert!(transcript.contains("answer 19"));
+        let entries = transcript.split("\n\n").count();
+        assert_eq!(
+            entries,
+            SUMMARY_HEAD_MESSAGES + SUMMARY_TAIL_MESSAGES + 1,
+            "head, omission marker, and tail"
+        );
+        let budget = (SUMMARY_HEAD_MESSAGES + SUMMARY_TAIL_MESSAGES)
+            * (SUMMARY_MESSAGE_CHARS + "Assistant: ".len() + 2)
+            + 200;
+        assert!(transcript.chars().count() < budget);
+    }
+
+    #[tokio::test]
+    async fn summary_prompt_skips_system_and_tool_messages() {
+        let provider = RecordingProvider::replying("Research Postgres migration");
+        let history = vec![
+            ModelMessage::system("secret system prompt"),
+            ModelMessage::user("plan the postgres migration"),
+        ];
+
+        summarize_conversation(&provider, &history, SummaryKind::OneLine)
+            .await
+            .expect("summary should succeed");
+
+        let requests = provider.requests.lock().unwrap();
+        let transcript = requests[0].messages[1].text();
+        assert!(transcript.contains("User: plan the postgres migration"));
+        assert!(!transcript.contains("secret system prompt"));
+    }
+
+    #[tokio::test]
+    async fn summary_is_cleaned_and_truncated_at_word_boundary() {
+        let provider = RecordingProvider::replying(
+            "\"Investigate the intermittently failing authentication integration tests in CI.\"\nextra",
+        );
+
+        let title = summarize_conversation(
+            &provider,
+            &[ModelMessage::user("auth tests flake")],
+            SummaryKind::Title,
+        )
+        .await
+        .expect("summary should succeed");
+
+        assert!(title.chars().count() <= SummaryKind::Title.max_chars());
+        assert_eq!(
+            title,
+            "Investigate the intermittently failing authentication…"
+        );
+    }
+
+    #[tokio::test]
+    async fn summarize_rejects_history_without_text() {
+        let provider = RecordingProvider::replying("unused");
+
+        let err = summarize_conversation(
+            &provider,
+            &[ModelMessage::system("only system")],
+            SummaryKind::Title,
+        )
+        .await
+        .expect_err("empty history should fail");
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(provider.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn summarize_surfaces_provider_failures() {
+        let provider = RecordingProvider::failing();
+
+        let err = summarize_conversation(
+            &provider,
+            &[ModelMessage::user("hello")],
+            SummaryKind::Title,
+        )
+        .await
+        .expect_err("provider failure should surface");
+
+        assert!(matches!(err, RociError::Provider { .. }));
+    }
+
+    #[test]
+    fn truncate_keeps_short_text_and_cuts_long_words() {
+        assert_eq!(truncate_at_word_boundary("short", 10), "short");
+        assert_eq!(truncate_at_word_boundary("abcdefghij", 5), "abcd…");
+        assert_eq!(truncate_at_word_boundary("one two three", 9), "one two…");
+    }
+
+    #[test]
+    fn fallback_title_uses_timestamp() {
+        let now = DateTime::parse_from_rfc3339("2026-05-08T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(fallback_title(now), "Session 2026-05-08 03:04");
+    }
+}
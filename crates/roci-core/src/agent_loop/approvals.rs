@@ -7,6 +7,8 @@ use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use crate::resource::ApprovalPreset;
+
 /// Tool approval policy for a run.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ApprovalPolicy {
@@ -42,9 +44,21 @@ impl ApprovalPolicy {
         }
     }
 
+    /// Never prompt: read-only calls run, every other call is declined.
     pub fn never() -> Self {
         Self {
             default_action: ApprovalAction::Deny,
+            rules: vec![ApprovalRule {
+                source: ApprovalRuleSource::BuiltIn,
+                reason: Some("read-only tool calls run without approval".to_string()),
+                ..ApprovalRule::new(
+                    "read_only_effects",
+                    ApprovalAction::Allow,
+                    ApprovalMatcher::ToolEffects {
+                        effects: crate::tools::ToolEffects::ReadOnly,
+                    },
+                )
+            }],
             ..Self::ask()
         }
     }
@@ -55,6 +69,16 @@ impl ApprovalPolicy {
     }
 }
 
+impl From<ApprovalPreset> for ApprovalPolicy {
+    fn from(preset: ApprovalPreset) -> Self {
+        match preset {
+            ApprovalPreset::Ask => Self::ask(),
+            ApprovalPreset::Always => Self::always(),
+            ApprovalPreset::Never => Self::never(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ApprovalAction {
@@ -119,6 +143,9 @@ pub enum ApprovalMatcher {
     ToolKind {
         kind: crate::tools::ToolSafetyKind,
     },
+    ToolEffects {
+        effects: crate::tools::ToolEffects,
+    },
     CommandExecutable {
         executable: String,
     },
@@ -164,6 +191,8 @@ pub struct ApprovalContext {
     pub tool_name: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_kind: Option<crate::tools::ToolSafetyKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<crate::tools::ToolEffects>,
     #[serde(default)]
     pub preview: serde_json::Value,
     #[serde(default)]
@@ -481,6 +510,9 @@ fn matcher_specificity(
         ApprovalMatcher::ToolKind { kind } if Some(*kind) == context.tool_kind => {
             Some(ApprovalSpecificity::CategoryOrKind)
         }
+        ApprovalMatcher::ToolEffects { effects } if Some(*effects) == context.effects => {
+            Some(ApprovalSpecificity::CategoryOrKind)
+        }
         ApprovalMatcher::CommandExecutable { executable } => context
             .command
             .as_ref()
@@ -628,6 +660,7 @@ mod tests {
             tool_call_id: "call-1".to_string(),
             tool_name: "shell".to_string(),
             tool_kind: Some(crate::tools::ToolSafetyKind::CommandExecution),
+            effects: Some(crate::tools::ToolEffects::Mutating),
             preview: serde_json::json!({"command": "echo hi"}),
             metadata: serde_json::Value::Null,
             command: None,
@@ -650,6 +683,45 @@ mod tests {
         assert_eq!(ApprovalPolicy::never().default_action, ApprovalAction::Deny);
     }
 
+    #[test]
+    fn never_allows_only_read_only_effects() {
+        let policy = ApprovalPolicy::never();
+        let mut ctx = context();
+
+        assert_eq!(policy.evaluate(&ctx).action, ApprovalAction::Deny);
+
+        ctx.effects = Some(crate::tools::ToolEffects::ReadOnly);
+        let evaluation = policy.evaluate(&ctx);
+        assert_eq!(evaluation.action, ApprovalAction::Allow);
+        assert_eq!(evaluation.matched_rules[0].rule_id, "read_only_effects");
+
+        for effects in [
+            crate::tools::ToolEffects::Network,
+            crate::tools::ToolEffects::Destructive,
+        ] {
+            ctx.effects = Some(effects);
+            assert_eq!(policy.evaluate(&ctx).action, ApprovalAction::Deny);
+        }
+        ctx.effects = None;
+        assert_eq!(policy.evaluate(&ctx).action, ApprovalAction::Deny);
+    }
+
+    #[test]
+    fn never_keeps_safety_floors_for_read_only_calls() {
+        let mut ctx = context();
+        ctx.effects = Some(crate::tools::ToolEffects::ReadOnly);
+        ctx.action_floor = Some(ApprovalSafetyFloor {
+            id: "blocked".to_string(),
+            effect: ApprovalAction::Deny,
+            reason: "blocked path".to_string(),
+        });
+
+        assert_eq!(
+            ApprovalPolicy::never().evaluate(&ctx).action,
+            ApprovalAction::Deny
+        );
+    }
+
     #[test]
     fn deny_beats_ask_and_allow() {
         let policy = ApprovalPolicy {
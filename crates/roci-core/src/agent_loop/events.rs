@@ -1,13 +1,21 @@
 //! Run event stream types.
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::types::message::ContentPart;
-use crate::types::{AgentToolCall, AgentToolResult, ModelMessage, TextStreamDelta};
+use crate::provider::ToolLimits;
+use crate::tools::artifacts::RunArtifact;
+use crate::tools::changes::FileChange;
+use crate::tools::plan::PlanStep;
+use crate::types::message::{ContentPart, ImageContent};
+use crate::types::{AgentToolCall, AgentToolResult, ModelMessage, TextStreamDelta, Usage};
 
 use super::approvals::{ApprovalDecision, ApprovalRequest};
-use super::types::RunId;
+use super::types::{RunId, RunStatus};
 
 /// Retry behavior for provider failures.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -48,6 +56,8 @@ pub enum FailureCategory {
     InvalidRequest,
     Tool,
     Canceled,
+    /// The run-wide retry budget ran out before the failure could be retried.
+    RetryBudget,
     Unknown,
 }
 
@@ -78,6 +88,12 @@ pub struct RetryEvent {
     pub candidates_remaining: usize,
     pub partial_output_seen: bool,
     pub next_action: RetryNextAction,
+    /// Retries left in the run-wide budget after this event.
+    #[serde(default)]
+    pub retries_remaining: usize,
+    /// Backoff time left in the run-wide budget after this event.
+    #[serde(default)]
+    pub retry_delay_remaining_ms: u64,
 }
 
 /// Stream category for events.
@@ -98,10 +114,70 @@ pub enum RunEventStream {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum RunLifecycle {
+    /// The run is waiting for a slot; `position` is its 1-based place in the
+    /// queue when it joined.
+    Queued {
+        position: usize,
+    },
     Started,
     Completed,
-    Failed { error: String },
+    Failed {
+        error: String,
+    },
     Canceled,
+    /// The run hit a [`RunBudget`](super::RunBudget) limit and stopped.
+    BudgetExceeded {
+        reading: BudgetReading,
+        error: String,
+    },
+    /// The run reached [`RunRequest::deadline`](super::RunRequest::deadline)
+    /// and stopped.
+    DeadlineExceeded {
+        error: String,
+        /// The run's messages end with a final assistant answer.
+        has_final_text: bool,
+    },
+}
+
+/// A limit tracked by [`RunBudget`](super::RunBudget).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    TotalTokens,
+    CostUsd,
+    WallClock,
+}
+
+/// Spend measured against one budget limit. Wall-clock values are seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BudgetReading {
+    pub limit: BudgetLimit,
+    pub used: f64,
+    pub max: f64,
+}
+
+impl BudgetReading {
+    /// Fraction of the limit spent; `1.0` or more means exceeded.
+    pub fn fraction(&self) -> f64 {
+        if self.max > 0.0 {
+            self.used / self.max
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// What the run was waiting on when a [`RunEventPayload::Heartbeat`] fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeartbeatPhase {
+    /// The provider has not streamed anything yet for this attempt.
+    AwaitingFirstDelta,
+    /// A tool batch is still executing.
+    ToolExecution,
+    /// The provider is loading the model before the first iteration; see
+    /// [`RunRequest::warm_up`](super::RunRequest::warm_up).
+    WarmingUp,
 }
 
 /// Concrete event payloads emitted by the agent loop.
@@ -114,11 +190,22 @@ pub enum RunEventPayload {
     AssistantDelta {
         text: String,
     },
+    /// A complete image generated by the model; emitted once per image.
+    AssistantImage {
+        image: ImageContent,
+    },
     ReasoningDelta {
         text: String,
     },
     ToolCallStarted {
         call: AgentToolCall,
+        /// The arguments were malformed JSON and the runner repaired them;
+        /// see [`RunRequest::repair_tool_arguments`](super::RunRequest::repair_tool_arguments).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        repaired: bool,
+        /// The run is a dry run; see [`RunRequest::dry_run`](super::RunRequest::dry_run).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        dry_run: bool,
     },
     ToolCallDelta {
         call_id: String,
@@ -127,8 +214,24 @@ pub enum RunEventPayload {
     ToolCallCompleted {
         call: AgentToolCall,
     },
+    /// Throttled progress while a tool call's arguments are still streaming.
+    ToolCallArgumentsDelta {
+        call_id: String,
+        name: String,
+        /// Bytes of raw argument JSON received so far.
+        partial_args_len: usize,
+        /// Sanitized prefix of the raw arguments received so far.
+        preview: String,
+    },
     ToolResult {
         result: AgentToolResult,
+        /// The call ran with repaired arguments.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        repaired: bool,
+        /// The call was not executed and `result` is synthetic; see
+        /// [`RunRequest::dry_run`](super::RunRequest::dry_run).
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        dry_run: bool,
     },
     PlanUpdated {
         plan: String,
@@ -145,18 +248,209 @@ pub enum RunEventPayload {
     Retry {
         event: RetryEvent,
     },
+    /// A budget limit crossed its warning threshold; emitted once per limit.
+    BudgetWarning {
+        reading: BudgetReading,
+    },
+    /// Liveness signal while nothing else is being emitted; see
+    /// [`RunRequest::heartbeat_interval`](super::RunRequest::heartbeat_interval).
+    /// Never persisted.
+    Heartbeat {
+        phase: HeartbeatPhase,
+        /// Time spent in `phase` so far.
+        elapsed_ms: u64,
+    },
+    /// Output of the current provider stream against its `max_tokens`.
+    ///
+    /// Emitted at most every 250 ms while a stream with
+    /// [`GenerationSettings::max_tokens`](crate::types::GenerationSettings::max_tokens)
+    /// set produces output, never after its `Done`. Counts restart with each
+    /// provider request. Never persisted.
+    Progress {
+        /// Provider-reported output tokens when the stream carries usage,
+        /// otherwise a ~4 characters per token estimate.
+        output_tokens_estimate: u32,
+        max_tokens: u32,
+        /// `output_tokens_estimate / max_tokens`, at most 1.0.
+        fraction: f64,
+    },
+    /// No content arrived within
+    /// [`RunRequest::ttft_slo`](super::RunRequest::ttft_slo); the request was
+    /// aborted and the turn moved from one candidate to the next.
+    FirstTokenSloMissed {
+        /// `provider:model` of the abandoned candidate.
+        from: String,
+        /// `provider:model` of the candidate now answering.
+        to: String,
+        /// Time waited for the first content delta.
+        waited_ms: u64,
+        slo_ms: u64,
+    },
+    /// The tool names advertised to the model changed; see
+    /// [`RunRequest::tools_provider`](super::RunRequest::tools_provider).
+    ToolsUpdated {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// The provider's tool limits left no room for `dropped`, which were not
+    /// advertised; see
+    /// [`ToolOverflowPolicy::TruncateByPriority`](super::ToolOverflowPolicy::TruncateByPriority).
+    /// Emitted when the dropped set changes.
+    ToolsTruncated {
+        dropped: Vec<String>,
+        limits: ToolLimits,
+    },
+    /// Net file changes recorded by tools during the run; emitted once, just
+    /// before the terminal lifecycle event, when the run changed any files.
+    ChangeSummary {
+        changes: Vec<FileChange>,
+    },
+    /// A tool registered an output with the run; emitted after the owning
+    /// call's result.
+    ArtifactAdded {
+        artifact: RunArtifact,
+    },
+    /// A [`RunRequest::task_list`](super::RunRequest::task_list) task
+    /// started; its events follow until the matching `TaskCompleted`.
+    TaskStarted {
+        task_id: String,
+        /// 0-based position in the task list.
+        index: usize,
+        total: usize,
+    },
+    /// A task ended. The run moves on to the next task whatever its status.
+    TaskCompleted {
+        task_id: String,
+        index: usize,
+        status: RunStatus,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+/// Caller-supplied tags stamped on every event of a run.
+///
+/// Set via [`RunRequest::event_tags`](super::RunRequest::event_tags). The map
+/// is shared behind an `Arc`, so stamping it on each event is a pointer copy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventTags(Arc<HashMap<String, String>>);
+
+impl EventTags {
+    pub fn new(tags: HashMap<String, String>) -> Self {
+        Self(Arc::new(tags))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_map(&self) -> &HashMap<String, String> {
+        &self.0
+    }
+}
+
+impl From<HashMap<String, String>> for EventTags {
+    fn from(tags: HashMap<String, String>) -> Self {
+        Self::new(tags)
+    }
+}
+
+impl Serialize for EventTags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.as_ref().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EventTags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Sequence counter shared by a run's [`RunEvent`] and [`AgentEventEnvelope`]
+/// streams.
+///
+/// Clones share the counter. Numbers start at 1 and are strictly increasing
+/// across both streams, so merging the streams by `seq` recovers emission
+/// order. Sinks are fed through separate queues: order events by `seq`, not
+/// by arrival or timestamp.
+#[derive(Debug, Clone)]
+pub struct EventSequence(Arc<AtomicU64>);
+
+impl EventSequence {
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicU64::new(1)))
+    }
+
+    /// Take the next sequence number.
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+impl Default for EventSequence {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Envelope for streaming run events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunEvent {
     pub run_id: RunId,
+    /// Position in the run's [`EventSequence`], shared with agent events.
     pub seq: u64,
     pub timestamp: DateTime<Utc>,
+    /// Model the run started with (`provider:model`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Caller-supplied run tags.
+    #[serde(default, skip_serializing_if = "EventTags::is_empty")]
+    pub tags: EventTags,
     pub stream: RunEventStream,
     pub payload: RunEventPayload,
 }
 
+/// Envelope delivered to an [`AgentEventSink`](super::AgentEventSink).
+///
+/// Carries the same run identity as [`RunEvent`] so consumers multiplexing
+/// many runs onto one channel can route events without a side table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentEventEnvelope {
+    pub run_id: RunId,
+    /// Position in the run's [`EventSequence`], shared with [`RunEvent`].
+    /// `0` for envelopes built outside a run, such as subagent notices.
+    #[serde(default)]
+    pub seq: u64,
+    #[serde(default = "Utc::now")]
+    pub timestamp: DateTime<Utc>,
+    /// Model the run started with (`provider:model`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Caller-supplied run tags.
+    #[serde(default, skip_serializing_if = "EventTags::is_empty")]
+    pub tags: EventTags,
+    pub event: AgentEvent,
+}
+
+impl AgentEventEnvelope {
+    /// Unsequenced envelope with no model or tags.
+    pub fn new(run_id: RunId, event: AgentEvent) -> Self {
+        Self {
+            run_id,
+            seq: 0,
+            timestamp: Utc::now(),
+            model: None,
+            tags: EventTags::default(),
+            event,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // AgentEvent — pi-mono aligned event system
 // ---------------------------------------------------------------------------
@@ -171,6 +465,20 @@ pub struct ToolUpdatePayload {
     pub details: serde_json::Value,
 }
 
+/// Per-turn totals carried by [`AgentEvent::TurnEnd`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TurnSummary {
+    /// Characters of assistant text streamed in the turn.
+    pub text_chars: usize,
+    /// Tool calls the model requested.
+    pub tool_calls: usize,
+    /// Tool results marked as errors.
+    pub tool_failures: usize,
+    /// Token usage of the turn's model calls, retries included.
+    pub usage: Usage,
+    pub duration_ms: u64,
+}
+
 /// High-level agent events aligned with pi-mono's event system.
 ///
 /// These events provide turn-level boundaries and streaming tool updates
@@ -188,6 +496,12 @@ pub enum AgentEvent {
     },
 
     // -- Turn boundaries --
+    //
+    // A turn is one model response plus the tool batch it requested.
+    // `turn_index` counts from 1. Retrying the response on a fallback model
+    // stays in the same turn. Every `TurnStart` is followed by exactly one
+    // `TurnEnd` with the same index, including when the run fails or is
+    // canceled mid-turn.
     TurnStart {
         run_id: RunId,
         turn_index: usize,
@@ -197,6 +511,11 @@ pub enum AgentEvent {
         turn_index: usize,
         assistant_message: Option<ModelMessage>,
         tool_results: Vec<AgentToolResult>,
+        #[serde(default)]
+        summary: TurnSummary,
+        /// The run ended before the turn completed.
+        #[serde(default)]
+        aborted: bool,
     },
 
     // -- Message streaming --
@@ -211,6 +530,20 @@ pub enum AgentEvent {
         message: ModelMessage,
     },
 
+    // -- Tool call argument streaming --
+    /// Throttled progress while a tool call's arguments are still streaming.
+    ///
+    /// Stops once the call is finalized; the complete call arrives through
+    /// `MessageUpdate` as before.
+    ToolCallArgumentsDelta {
+        call_id: String,
+        name: String,
+        /// Bytes of raw argument JSON received so far.
+        partial_args_len: usize,
+        /// Sanitized prefix of the raw arguments received so far.
+        preview: String,
+    },
+
     // -- Tool execution (enhanced with streaming) --
     ToolExecutionStart {
         tool_call_id: String,
@@ -255,6 +588,13 @@ pub enum AgentEvent {
     PlanUpdated {
         plan: String,
     },
+    /// Structured plan replaced by a plan tool (for example `update_plan`).
+    ///
+    /// Emitted during the tool call, after `ToolExecutionStart` and before
+    /// `ToolExecutionEnd`.
+    PlanUpdate {
+        steps: Vec<PlanStep>,
+    },
     DiffUpdated {
         diff: String,
     },
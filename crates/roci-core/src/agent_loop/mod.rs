@@ -3,10 +3,14 @@
 pub mod approvals;
 pub(crate) mod compaction;
 pub mod events;
+pub mod replay;
 pub mod runner;
+pub mod transcript;
 pub mod types;
+pub mod webhook;
 
 pub use approvals::*;
 pub use events::*;
 pub use runner::*;
 pub use types::*;
+pub use webhook::*;
@@ -0,0 +1,386 @@
+//! Rebuild per-iteration run state from a [`transcript`](super::transcript).
+//!
+//! [`StateReconstructor`] reads a JSONL transcript and yields, for each
+//! iteration, the messages and tool definitions sent to the provider, the
+//! generation settings, and the events emitted while the iteration ran.
+//! Problems in the file are reported as [`TranscriptDiagnostic`]s instead of
+//! failing the read, so a truncated transcript still replays up to the cut.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::events::{AgentEventEnvelope, RunEvent};
+use super::transcript::{TranscriptRecord, TRANSCRIPT_SCHEMA_VERSION};
+use super::types::{RunId, RunStatus};
+use crate::provider::ToolDefinition;
+use crate::types::{GenerationSettings, ModelMessage};
+
+/// Header of the transcribed run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptRun {
+    pub run_id: RunId,
+    pub model: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How the transcribed run ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptOutcome {
+    pub status: RunStatus,
+    pub error: Option<String>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A run or agent event from a transcript. Both payloads are boxed so a
+/// small event does not take the size of the largest.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum TranscriptEvent {
+    Run(Box<RunEvent>),
+    Agent(Box<AgentEventEnvelope>),
+}
+
+impl TranscriptEvent {
+    /// Position in the run's shared event sequence.
+    pub fn seq(&self) -> u64 {
+        match self {
+            Self::Run(event) => event.seq,
+            Self::Agent(envelope) => envelope.seq,
+        }
+    }
+}
+
+/// State of the run during one iteration.
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationState {
+    /// 1-based, as counted by the runner.
+    pub iteration: usize,
+    /// Model of the last provider request, if one was sent.
+    pub model: Option<String>,
+    /// Messages of the last provider request, as sent.
+    pub messages: Vec<ModelMessage>,
+    /// Tool definitions advertised with the last provider request.
+    pub tools: Vec<ToolDefinition>,
+    pub settings: Option<GenerationSettings>,
+    /// Provider requests made; more than one means retries or fallback.
+    pub provider_requests: usize,
+    /// Events emitted during the iteration, in transcript order.
+    pub events: Vec<TranscriptEvent>,
+}
+
+impl IterationState {
+    fn new(iteration: usize) -> Self {
+        Self {
+            iteration,
+            model: None,
+            messages: Vec::new(),
+            tools: Vec::new(),
+            settings: None,
+            provider_requests: 0,
+            events: Vec::new(),
+        }
+    }
+}
+
+/// Kind of problem found in a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// A line is not a transcript record.
+    Malformed,
+    /// The file ends early: a cut-off last line or no `run_finished` record.
+    Truncated,
+    /// Event sequence numbers skip values.
+    MissingEvents,
+    /// Records contradict each other, so replayed state may be wrong.
+    Inconsistent,
+    /// The transcript was written by an incompatible version.
+    Unsupported,
+}
+
+/// A problem found while reading a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptDiagnostic {
+    /// 1-based line, when the problem belongs to one.
+    pub line: Option<usize>,
+    pub kind: DiagnosticKind,
+    pub message: String,
+}
+
+impl std::fmt::Display for TranscriptDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Per-iteration state rebuilt from a run transcript.
+#[derive(Debug, Clone, Default)]
+pub struct StateReconstructor {
+    run: Option<TranscriptRun>,
+    preamble: Vec<TranscriptEvent>,
+    iterations: Vec<IterationState>,
+    outcome: Option<TranscriptOutcome>,
+    diagnostics: Vec<TranscriptDiagnostic>,
+}
+
+impl StateReconstructor {
+    /// Read the transcript at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error when the file cannot be read. Problems in its
+    /// contents are reported by [`diagnostics`](Self::diagnostics).
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Rebuild state from transcript text.
+    pub fn parse(input: &str) -> Self {
+        let mut state = Self::default();
+        let mut full_messages: Vec<ModelMessage> = Vec::new();
+        let mut seqs = Vec::new();
+        let mut read_any = false;
+        let lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let last_line = lines.clone().last().map(|(index, _)| index);
+
+        for (index, text) in lines {
+            let line = index + 1;
+            let record = match serde_json::from_str::<TranscriptRecord>(text) {
+                Ok(record) => record,
+                Err(err) if Some(index) == last_line && !input.ends_with('\n') => {
+                    state.diagnose(
+                        Some(line),
+                        DiagnosticKind::Truncated,
+                        format!("last line is cut off ({err})"),
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    state.diagnose(Some(line), DiagnosticKind::Malformed, err.to_string());
+                    continue;
+                }
+            };
+            if !read_any && !matches!(record, TranscriptRecord::RunStarted { .. }) {
+                state.diagnose(
+                    Some(line),
+                    DiagnosticKind::Truncated,
+                    "transcript does not start with a `run_started` record",
+                );
+            }
+            read_any = true;
+            if state.outcome.is_some() {
+                state.diagnose(
+                    Some(line),
+                    DiagnosticKind::Inconsistent,
+                    "record after `run_finished`",
+                );
+            }
+            match record {
+                TranscriptRecord::RunStarted {
+                    schema_version,
+                    run_id,
+                    model,
+                    started_at,
+                } => {
+                    if state.run.is_some() {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::Inconsistent,
+                            "second `run_started` record; transcripts hold one run",
+                        );
+                        continue;
+                    }
+                    if schema_version != TRANSCRIPT_SCHEMA_VERSION {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::Unsupported,
+                            format!(
+                                "schema version {schema_version} (supported: {TRANSCRIPT_SCHEMA_VERSION})"
+                            ),
+                        );
+                    }
+                    state.run = Some(TranscriptRun {
+                        run_id,
+                        model,
+                        started_at,
+                    });
+                }
+                TranscriptRecord::Iteration { iteration } => {
+                    let expected = state.iterations.len() + 1;
+                    if iteration != expected {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::Inconsistent,
+                            format!("iteration {iteration} follows iteration {}", expected - 1),
+                        );
+                    }
+                    state.iterations.push(IterationState::new(iteration));
+                }
+                TranscriptRecord::ProviderRequest(summary) => {
+                    if state.iterations.is_empty() {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::MissingEvents,
+                            format!(
+                                "provider request for iteration {} has no `iteration` record",
+                                summary.iteration
+                            ),
+                        );
+                        state
+                            .iterations
+                            .push(IterationState::new(summary.iteration));
+                    }
+                    let current = state.iterations.len() - 1;
+                    if summary.iteration != state.iterations[current].iteration {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::Inconsistent,
+                            format!(
+                                "provider request for iteration {} inside iteration {}",
+                                summary.iteration, state.iterations[current].iteration
+                            ),
+                        );
+                    }
+                    if summary.base > full_messages.len() {
+                        state.diagnose(
+                            Some(line),
+                            DiagnosticKind::MissingEvents,
+                            format!(
+                                "provider request builds on {} earlier message(s) but only {} are known",
+                                summary.base,
+                                full_messages.len()
+                            ),
+                        );
+                    }
+                    full_messages.truncate(summary.base);
+                    full_messages.extend(summary.messages);
+                    let iteration = &mut state.iterations[current];
+                    iteration.model = Some(summary.model);
+                    iteration.messages.clone_from(&full_messages);
+                    iteration.tools = summary.tools;
+                    iteration.settings = Some(summary.settings);
+                    iteration.provider_requests += 1;
+                }
+                TranscriptRecord::RunEvent(event) => {
+                    seqs.push((event.seq, line));
+                    state.push_event(TranscriptEvent::Run(event));
+                }
+                TranscriptRecord::AgentEvent(envelope) => {
+                    // Envelopes built outside the run carry no sequence.
+                    if envelope.seq != 0 {
+                        seqs.push((envelope.seq, line));
+                    }
+                    state.push_event(TranscriptEvent::Agent(envelope));
+                }
+                TranscriptRecord::RunFinished {
+                    status,
+                    error,
+                    finished_at,
+                } => {
+                    state.outcome = Some(TranscriptOutcome {
+                        status,
+                        error,
+                        finished_at,
+                    });
+                }
+            }
+        }
+
+        state.check_sequence(seqs);
+        if !read_any {
+            state.diagnose(None, DiagnosticKind::Truncated, "transcript has no records");
+        } else if state.outcome.is_none() {
+            state.diagnose(
+                None,
+                DiagnosticKind::Truncated,
+                "no `run_finished` record; the run was still going or the file was cut short",
+            );
+        }
+        state
+    }
+
+    /// The run header, if the transcript has one.
+    pub fn run(&self) -> Option<&TranscriptRun> {
+        self.run.as_ref()
+    }
+
+    /// Events emitted before the first iteration.
+    pub fn preamble(&self) -> &[TranscriptEvent] {
+        &self.preamble
+    }
+
+    pub fn iterations(&self) -> &[IterationState] {
+        &self.iterations
+    }
+
+    /// State of iteration `iteration` (1-based).
+    pub fn iteration(&self, iteration: usize) -> Option<&IterationState> {
+        self.iterations
+            .iter()
+            .find(|state| state.iteration == iteration)
+    }
+
+    /// How the run ended, if the transcript records it.
+    pub fn outcome(&self) -> Option<&TranscriptOutcome> {
+        self.outcome.as_ref()
+    }
+
+    pub fn diagnostics(&self) -> &[TranscriptDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Whether the transcript was read without any diagnostics.
+    pub fn is_intact(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    fn push_event(&mut self, event: TranscriptEvent) {
+        match self.iterations.last_mut() {
+            Some(iteration) => iteration.events.push(event),
+            None => self.preamble.push(event),
+        }
+    }
+
+    /// Report gaps and repeats in event sequence numbers, which start at 1.
+    ///
+    /// Concurrent tool tasks can write events slightly out of order, so the
+    /// numbers are checked as a set.
+    fn check_sequence(&mut self, mut seqs: Vec<(u64, usize)>) {
+        seqs.sort_unstable();
+        let mut expected = 1;
+        for (seq, line) in seqs {
+            if seq < expected {
+                self.diagnose(
+                    Some(line),
+                    DiagnosticKind::Inconsistent,
+                    format!("event {seq} appears more than once"),
+                );
+                continue;
+            }
+            if seq > expected {
+                let missing = if seq - expected == 1 {
+                    format!("event {expected} is missing")
+                } else {
+                    format!("events {expected}..={} are missing", seq - 1)
+                };
+                self.diagnose(Some(line), DiagnosticKind::MissingEvents, missing);
+            }
+            expected = seq + 1;
+        }
+    }
+
+    fn diagnose(&mut self, line: Option<usize>, kind: DiagnosticKind, message: impl Into<String>) {
+        self.diagnostics.push(TranscriptDiagnostic {
+            line,
+            kind,
+            message: message.into(),
+        });
+    }
+}
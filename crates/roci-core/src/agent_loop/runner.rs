@@ -5,6 +5,7 @@ use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
@@ -16,18 +17,26 @@ use crate::agent::message::AgentMessage;
 use crate::config::RociConfig;
 use crate::context::ContextBudget;
 use crate::error::RociError;
-use crate::models::{LanguageModel, ModelCandidates, ModelHealthTracker};
+use crate::memory::Memory;
+use crate::models::{LanguageModel, ModelCandidates, ModelHealthTracker, PricingTable};
 use crate::provider::{self, ProviderRegistry};
+use crate::security::pii::ResponseFilter;
 use crate::session::{LogicalPath, SessionFs};
 use crate::tools::catalog::ToolVisibilityPolicy;
-use crate::tools::tool::{SandboxProvider, Tool};
-use crate::types::{AgentToolCall, AgentToolResult, GenerationSettings, ModelMessage};
+use crate::tools::tool::{SandboxProvider, Tool, ToolErrorClass};
+use crate::types::{
+    AgentToolCall, AgentToolResult, GenerationSettings, ModelMessage, ResponseFormat,
+};
 
 use super::approvals::{ApprovalDecision, ApprovalHandler, ApprovalPolicy};
 use super::events::{
-    AgentEvent, RetryMode, RunEvent, RunEventPayload, RunEventStream, RunLifecycle,
+    AgentEvent, AgentEventEnvelope, EventSequence, RetryMode, RunEvent, RunEventPayload,
+    RunEventStream, RunLifecycle,
 };
-use super::types::{RunId, RunResult};
+use super::transcript::TranscriptWriter;
+use super::types::{RunId, RunResult, RunStatus};
+use super::webhook::WebhookConfig;
+use admission::AdmissionController;
 
 /// Callback used for streaming run events.
 pub type RunEventSink = Arc<dyn Fn(RunEvent) + Send + Sync>;
@@ -35,12 +44,57 @@ pub type RunEventSink = Arc<dyn Fn(RunEvent) + Send + Sync>;
 pub type CompactionHandler = Arc<
     dyn Fn(
             Vec<ModelMessage>,
+            CompactionHookContext,
             CancellationToken,
         )
             -> Pin<Box<dyn Future<Output = Result<Option<Vec<ModelMessage>>, RociError>> + Send>>
         + Send
         + Sync,
 >;
+/// Run state passed to a [`CompactionHandler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactionHookContext {
+    pub run_id: RunId,
+    /// Index of the turn about to call the provider, as in
+    /// [`AgentEvent::TurnStart`].
+    pub turn_index: usize,
+    /// Loop iteration (1-based).
+    pub iteration: usize,
+    /// Estimated prompt tokens that triggered compaction.
+    pub estimated_tokens: usize,
+    /// Context window the estimate was measured against.
+    pub context_window: usize,
+}
+
+/// Run state passed to [`PreToolUseHook`] and [`PostToolUseHook`].
+///
+/// Both hooks see the same context for a given call.
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub run_id: RunId,
+    /// Index of the turn that requested the call, as in
+    /// [`AgentEvent::TurnStart`].
+    pub turn_index: usize,
+    /// Loop iteration (1-based).
+    pub iteration: usize,
+    /// Calls of each tool requested earlier in the run, keyed by tool name.
+    /// The current call is not counted.
+    pub tool_invocations: HashMap<String, usize>,
+    /// Consecutive earlier tool batches in which every call failed. The run
+    /// fails when this reaches [`RunRequest::max_tool_failures`].
+    pub consecutive_failures: usize,
+    /// Message history as of the start of the tool batch, including the
+    /// assistant message that requested the call.
+    pub messages: Arc<[ModelMessage]>,
+}
+
+impl HookContext {
+    /// Calls of `tool_name` requested earlier in the run.
+    pub fn invocations_of(&self, tool_name: &str) -> usize {
+        self.tool_invocations.get(tool_name).copied().unwrap_or(0)
+    }
+}
+
 /// Decision returned by pre-tool-use hook.
 #[derive(Debug, Clone, PartialEq)]
 pub enum PreToolUseHookResult {
@@ -53,17 +107,63 @@ pub enum PreToolUseHookResult {
 pub type PreToolUseHook = Arc<
     dyn Fn(
             AgentToolCall,
+            HookContext,
             CancellationToken,
         ) -> Pin<Box<dyn Future<Output = Result<PreToolUseHookResult, RociError>> + Send>>
         + Send
         + Sync,
 >;
 
+/// Payload for the `pre_turn_execution` hook.
+#[derive(Debug, Clone)]
+pub struct PreTurnExecutionHookPayload {
+    pub run_id: RunId,
+    /// Index of the turn, as in [`AgentEvent::TurnStart`].
+    pub turn_index: usize,
+    /// Loop iteration (1-based).
+    pub iteration: usize,
+    /// Assistant text of the turn.
+    pub text: String,
+    /// Every tool call the model proposed, in order.
+    pub tool_calls: Vec<AgentToolCall>,
+    /// Message history before the turn's assistant message.
+    pub messages: Arc<[ModelMessage]>,
+    pub cancellation_token: CancellationToken,
+}
+
+/// Decision returned by `pre_turn_execution`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreTurnExecutionHookResult {
+    Continue,
+    /// Execute these calls instead. Calls can be dropped, reordered, or
+    /// edited; an empty list ends the turn without tools.
+    ModifyCalls(Vec<AgentToolCall>),
+    /// Execute no calls. `feedback` is added as a user message and the loop
+    /// moves to the next iteration. The turn counts toward
+    /// [`RunRequest::max_iterations`] but not toward tool-failure limits.
+    AbortTurn {
+        feedback: String,
+    },
+}
+
+/// Hook that sees a whole turn before any of its tools run.
+///
+/// Runs before [`PreToolUseHook`], which then sees each surviving call.
+pub type PreTurnExecutionHook = Arc<
+    dyn Fn(
+            PreTurnExecutionHookPayload,
+        )
+            -> Pin<Box<dyn Future<Output = Result<PreTurnExecutionHookResult, RociError>> + Send>>
+        + Send
+        + Sync,
+>;
+
 /// Hook that can rewrite any tool result before persistence/context assembly.
 pub type PostToolUseHook = Arc<
     dyn Fn(
             AgentToolCall,
             AgentToolResult,
+            HookContext,
         ) -> Pin<Box<dyn Future<Output = Result<AgentToolResult, RociError>> + Send>>
         + Send
         + Sync,
@@ -79,6 +179,10 @@ pub type SteeringMessagesFn = MessageBatchFn;
 /// Callback to retrieve follow-up messages after the inner loop completes.
 pub type FollowUpMessagesFn = MessageBatchFn;
 
+/// Async callback returning the tools for a loop iteration (1-based).
+pub type ToolsProviderFn =
+    Arc<dyn Fn(usize) -> Pin<Box<dyn Future<Output = Vec<Arc<dyn Tool>>> + Send>> + Send + Sync>;
+
 pub(crate) fn canonical_workspace_root(root: &Path) -> Result<PathBuf, RociError> {
     let canonical = root.canonicalize().map_err(|error| {
         RociError::Configuration(format!(
@@ -133,7 +237,9 @@ pub type BeforeAgentStartHook = Arc<
 pub struct TransformContextHookPayload {
     pub run_id: RunId,
     pub model: LanguageModel,
-    pub messages: Vec<ModelMessage>,
+    /// History for the next provider call, shared with the runner. Return
+    /// [`TransformContextHookResult::ReplaceMessages`] to change it.
+    pub messages: Arc<Vec<ModelMessage>>,
     pub cancellation_token: CancellationToken,
 }
 
@@ -185,7 +291,10 @@ pub type ConvertToLlmFn = Arc<
 >;
 
 /// Sink for high-level AgentEvent emission (separate from RunEvent).
-pub type AgentEventSink = Arc<dyn Fn(AgentEvent) + Send + Sync>;
+///
+/// Each event arrives wrapped in an [`AgentEventEnvelope`] stamped with the
+/// run id, model, and [`RunRequest::event_tags`].
+pub type AgentEventSink = Arc<dyn Fn(AgentEventEnvelope) + Send + Sync>;
 
 /// Async callback that resolves an API key for the active model at request time.
 pub type GetApiKeyFn = Arc<
@@ -197,6 +306,7 @@ pub type GetApiKeyFn = Arc<
 #[derive(Clone, Default)]
 pub struct RunHooks {
     pub compaction: Option<CompactionHandler>,
+    pub pre_turn_execution: Option<PreTurnExecutionHook>,
     pub pre_tool_use: Option<PreToolUseHook>,
     pub post_tool_use: Option<PostToolUseHook>,
 }
@@ -233,6 +343,127 @@ impl Default for RetryBackoffPolicy {
     }
 }
 
+impl RetryBackoffPolicy {
+    /// Jittered delay before retry number `retry`, counting from 1.
+    pub(crate) fn delay_for_retry(&self, retry: u32) -> Duration {
+        let max_delay_ms = self.max_delay_ms.max(1);
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let base_ms = (self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(exponent))
+            .min(max_delay_ms as f64) as u64;
+        Duration::from_millis(engine::jittered_backoff_ms(
+            base_ms,
+            self.jitter_ratio,
+            max_delay_ms,
+        ))
+    }
+}
+
+/// Retries for tool calls that fail transiently.
+///
+/// Failures are classified with [`Tool::classify_error`] and retried inside
+/// the tool phase, so the model sees one result per call. Only calls whose
+/// [`ToolEffects`](crate::tools::ToolEffects) resolve to read-only are
+/// retried; mutating, network, and destructive calls fail on the first error.
+/// A retry whose backoff would end after [`RunRequest::deadline`] is skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRetryPolicy {
+    /// Attempts including the first call.
+    pub max_attempts: u32,
+    /// Delays between attempts. Its `max_attempts` is ignored.
+    pub backoff: RetryBackoffPolicy,
+    /// Failure classes worth another attempt.
+    pub retry_on: Vec<ToolErrorClass>,
+}
+
+impl Default for ToolRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: RetryBackoffPolicy::default(),
+            retry_on: ToolErrorClass::TRANSIENT.to_vec(),
+        }
+    }
+}
+
+impl ToolRetryPolicy {
+    pub(crate) fn retries(&self, class: ToolErrorClass, attempts: u32) -> bool {
+        attempts < self.max_attempts && self.retry_on.contains(&class)
+    }
+}
+
+/// Hard spend ceiling for one run.
+///
+/// Token and cost limits are checked against the run's aggregated usage
+/// before each LLM phase; the wall-clock limit also interrupts an in-flight
+/// LLM call or tool batch. Crossing a limit ends the run with
+/// [`RunStatus::BudgetExceeded`](super::RunStatus::BudgetExceeded), keeping the
+/// conversation so far. A [`RunEventPayload::BudgetWarning`] is emitted once
+/// per limit at [`RunBudget::WARNING_FRACTION`] of it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunBudget {
+    /// Maximum input plus output tokens across all LLM calls.
+    pub max_total_tokens: Option<u64>,
+    /// Maximum estimated cost in USD, priced with [`RunBudget::pricing`].
+    pub max_cost_usd: Option<f64>,
+    /// Maximum elapsed time from run start.
+    pub max_wall_clock: Option<Duration>,
+    /// Pricing used for `max_cost_usd`; defaults to [`PricingTable::default`].
+    pub pricing: PricingTable,
+}
+
+impl RunBudget {
+    /// Fraction of a limit at which the warning event fires.
+    pub const WARNING_FRACTION: f64 = 0.8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_total_tokens(mut self, max_total_tokens: u64) -> Self {
+        self.max_total_tokens = Some(max_total_tokens);
+        self
+    }
+
+    pub fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    pub fn with_max_wall_clock(mut self, max_wall_clock: Duration) -> Self {
+        self.max_wall_clock = Some(max_wall_clock);
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: PricingTable) -> Self {
+        self.pricing = pricing;
+        self
+    }
+}
+
+/// What a run does when it reaches [`RunRequest::deadline`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeadlineMode {
+    /// Stop at the deadline with the messages so far.
+    #[default]
+    Terminate,
+    /// Stop `reserve` before the deadline and make one last call, without
+    /// tools and with a small output cap, asking the model to conclude. The
+    /// call is cut off at the deadline. `reserve` must not be zero.
+    WrapUp { reserve: Duration },
+}
+
+/// What a run does with [`RunRequest::prefill`] when the provider cannot
+/// continue a trailing assistant message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrefillFallback {
+    /// Fail the LLM phase with a configuration error.
+    #[default]
+    Reject,
+    /// Prepend a system note asking the model to continue the prefill
+    /// without repeating it.
+    Emulate,
+}
+
 /// Request payload to start a run.
 #[derive(Clone)]
 pub struct RunRequest {
@@ -242,24 +473,88 @@ pub struct RunRequest {
     pub messages: Vec<ModelMessage>,
     pub settings: GenerationSettings,
     pub tools: Vec<Arc<dyn Tool>>,
+    /// Rebuilds the tool set at the top of each iteration, replacing `tools`.
+    ///
+    /// Tools are matched by name across iterations; calls already issued run
+    /// against the set that was advertised for them. Changes in the set of
+    /// names emit [`RunEventPayload::ToolsUpdated`]. Plugin tools are appended
+    /// to every returned set.
+    pub tools_provider: Option<ToolsProviderFn>,
     /// Optional durable session filesystem exposed to tools.
     pub session_fs: Option<Arc<dyn SessionFs + Send + Sync>>,
     /// Optional logical current directory inside the durable session filesystem.
     pub session_cwd: Option<LogicalPath>,
     /// Canonical trusted host workspace exposed to coding tools.
+    ///
+    /// Builtin tools resolve relative paths inside it instead of the process
+    /// cwd, and approval rules see tool paths anchored at it.
     pub workspace_root: Option<PathBuf>,
+    /// Root for artifacts too large to keep in memory; each run spills into a
+    /// subdirectory named after its run id. Defaults to `roci-artifacts`
+    /// under the system temp dir.
+    pub artifacts_dir: Option<PathBuf>,
+    /// Keep the run's scratch directory after the run and report it in
+    /// [`RunResult::scratch_dir`]. Defaults to `false`, which removes it.
+    pub keep_scratch: bool,
+    /// Endpoints sent a [`RunSummary`](super::RunSummary) when the run ends;
+    /// outcomes land in [`RunResult::webhook_deliveries`].
+    pub webhooks: Vec<WebhookConfig>,
+    /// Records provider requests and every emitted event for replay with
+    /// [`StateReconstructor`](super::replay::StateReconstructor).
+    pub transcript: Option<TranscriptWriter>,
     /// Optional sandbox provider exposed to command-capable tools.
     pub sandbox_provider: Option<Arc<dyn SandboxProvider>>,
+    /// Optional long-term memory exposed to memory tools.
+    pub memory: Option<Arc<dyn Memory>>,
     /// Policy deciding which tools are visible to provider/tool resolution.
     pub tool_visibility_policy: ToolVisibilityPolicy,
+    /// What happens when the tools exceed the provider's
+    /// [`ToolLimits`](provider::ToolLimits). Defaults to failing the run.
+    pub tool_overflow: ToolOverflowPolicy,
     pub approval_policy: ApprovalPolicy,
     pub approval_handler: Option<ApprovalHandler>,
+    /// Model turns allowed before the run asks to extend or fails.
+    /// Defaults to 20; must be at least 1.
+    pub max_iterations: Option<usize>,
+    /// Consecutive failed tool batches allowed before the run fails.
+    /// Defaults to 8; must be at least 1.
+    pub max_tool_failures: Option<usize>,
+    /// Iterations granted by each accepted extension. Defaults to 20; must be
+    /// at least 1.
+    pub iteration_extension: Option<usize>,
+    /// Extensions allowed per run. Defaults to 3; must be at least 1.
+    pub max_iteration_extensions: Option<usize>,
+    /// Free-form run metadata.
+    ///
+    /// `runner.max_total_retries` and `runner.max_total_retry_delay_ms` tune
+    /// the run retry budget; other `runner.*` keys are rejected at start, and
+    /// the keys for the typed limits above are deprecated. `provider.header.*`,
+    /// `provider.body.user`, and `provider.metadata.*` opt into provider
+    /// routing fields (see [`provider::routing`]). Other keys are not sent.
     pub metadata: HashMap<String, String>,
+    /// Caller-supplied tags stamped on every [`RunEvent`] and
+    /// [`AgentEventEnvelope`] of this run.
+    pub event_tags: HashMap<String, String>,
+    /// Numbers every [`RunEvent`] and [`AgentEventEnvelope`] of this run.
+    ///
+    /// Shared by both streams so merging them by `seq` gives emission order.
+    /// Requests built for one run should keep the default fresh counter.
+    pub event_sequence: EventSequence,
     pub event_sink: Option<RunEventSink>,
+    /// Events queued for `event_sink` and `agent_event_sink` before
+    /// `event_overflow_policy` applies.
+    ///
+    /// Sinks run on a dedicated blocking task, so a slow sink delays event
+    /// delivery but never the run. The result is sent once the queue drains.
+    pub event_queue_capacity: usize,
+    pub event_overflow_policy: EventOverflowPolicy,
     pub hooks: RunHooks,
     pub auto_compaction: Option<AutoCompactionConfig>,
     /// Per-run retry/backoff policy for retryable provider failures.
     pub retry_backoff: RetryBackoffPolicy,
+    /// Retries for transiently failing read-only tool calls. `None`, the
+    /// default, reports every failure to the model as is.
+    pub tool_retry: Option<ToolRetryPolicy>,
     /// Retry behavior for transient provider failures.
     pub retry_mode: RetryMode,
     /// Optional per-run model health tracker.
@@ -280,7 +575,17 @@ pub struct RunRequest {
     pub transport: Option<String>,
     /// Optional cap for server-requested retry delays in milliseconds.
     /// `Some(0)` disables the cap.
+    ///
+    /// Also caps the synthesized backoff for provider-overloaded errors,
+    /// which carry no server delay hint.
     pub max_retry_delay_ms: Option<u64>,
+    /// Retry a stream that fails with a provider-overloaded error after text
+    /// was emitted. Providers that support an
+    /// [assistant prefix](provider::ModelProvider::supports_assistant_prefix)
+    /// continue from the partial text, which is stored together with the
+    /// continuation as one assistant message; others restart the reply.
+    /// Defaults to `false`.
+    pub resume_partial_on_overload: bool,
     /// Optional per-request provider API key override.
     pub api_key_override: Option<String>,
     /// Optional provider-scoped API key overrides.
@@ -295,6 +600,106 @@ pub struct RunRequest {
     pub provider_payload_callback: Option<provider::ProviderPayloadCallback>,
     /// Optional context budget for preflight budget enforcement.
     pub context_budget: Option<ContextBudget>,
+    /// Optional token, cost, and wall-clock ceiling for this run.
+    pub budget: Option<RunBudget>,
+    /// Time by which the run must end, with a final answer or with what it
+    /// has.
+    ///
+    /// Provider calls and tool batches are cut off when it passes, retries
+    /// whose backoff would outlast it are skipped, and tools see it as
+    /// [`ToolExecutionContext::deadline`](crate::tools::tool::ToolExecutionContext::deadline).
+    /// The run then ends with [`RunStatus::DeadlineExceeded`] as
+    /// `deadline_mode` directs.
+    pub deadline: Option<std::time::Instant>,
+    /// Handling of `deadline`. Defaults to [`DeadlineMode::Terminate`].
+    pub deadline_mode: DeadlineMode,
+    /// Emit [`RunEventPayload::Heartbeat`] at this interval while waiting for
+    /// the provider's first delta or for a long-running tool batch.
+    ///
+    /// Heartbeats do not reset the stream idle timeout. A zero interval
+    /// disables them.
+    pub heartbeat_interval: Option<Duration>,
+    /// Time-to-first-token limit for each provider stream.
+    ///
+    /// When no content delta arrives within this long, the request is aborted
+    /// and the turn moves to the next of `candidates`, emitting
+    /// [`RunEventPayload::FirstTokenSloMissed`]. Once content has arrived the
+    /// stream is never switched. Inactive on the last candidate.
+    pub ttft_slo: Option<Duration>,
+    /// Merge bursts of text deltas before they are emitted.
+    ///
+    /// Cuts per-delta event overhead on providers that stream many tiny
+    /// deltas. Text is held back for at most the configured window and never
+    /// past another kind of delta, so stored messages are unchanged. `None`,
+    /// the default, emits every delta as it arrives.
+    pub stream_coalescing: Option<StreamCoalescing>,
+    /// Format the run's final answer must take, parsed into
+    /// [`RunResult::structured_output`].
+    ///
+    /// Tools work as usual. Providers that
+    /// [accept a format alongside tools](provider::ModelProvider::supports_response_format_with_tools)
+    /// get it on every call; others answer freely and then make one extra
+    /// constrained call without tools. An answer that fails validation gets
+    /// one repair call before the run fails with the validation errors.
+    /// Each of these calls counts as an iteration. Must be a JSON format.
+    pub final_response_schema: Option<ResponseFormat>,
+    /// Load the model before the first iteration when the provider
+    /// [supports it](provider::ModelProvider::supports_warm_up).
+    ///
+    /// A [`HeartbeatPhase::WarmingUp`](super::HeartbeatPhase::WarmingUp)
+    /// heartbeat is emitted as the load starts. A failed warm-up is logged
+    /// and the run proceeds. Defaults to `false`.
+    pub warm_up: bool,
+    /// Text the next assistant reply must start with.
+    ///
+    /// Sent as a trailing assistant message to providers that support
+    /// [`supports_assistant_prefix`](provider::ModelProvider::supports_assistant_prefix)
+    /// and handled per [`RunRequest::prefill_fallback`] elsewhere. The persisted
+    /// assistant message is the prefill followed by the completion; streamed
+    /// text deltas carry only the completion. Cannot be combined with tools.
+    pub prefill: Option<String>,
+    /// Handling of `prefill` for providers without assistant-prefix support.
+    pub prefill_fallback: PrefillFallback,
+    /// Plugins folded into tools, hooks, and event sinks at run start.
+    pub plugins: Vec<Arc<dyn RunPlugin>>,
+    /// Rewrites assistant text before it is stored.
+    ///
+    /// Applied to assistant messages appended to history (and so to
+    /// [`RunResult::messages`]) and to `MessageEnd` agent events. Run event
+    /// deltas and `MessageStart`/`MessageUpdate` keep the raw text, so a live
+    /// view sees what the model produced while sessions store the filtered
+    /// form. See [`PiiRedactor`](crate::security::pii::PiiRedactor).
+    pub response_filter: Option<Arc<dyn ResponseFilter>>,
+    /// Also apply `response_filter` to every string in tool-result messages.
+    /// The model then sees the filtered results too. Defaults to `false`.
+    pub filter_tool_results: bool,
+    /// Repair tool-call arguments that arrive as malformed JSON (trailing
+    /// commas, single quotes, raw control characters in strings, unclosed
+    /// braces) instead of failing validation. Repaired calls are flagged on
+    /// their `ToolCallStarted` and `ToolResult` events. Defaults to `true`;
+    /// turn off for strict deployments.
+    pub repair_tool_arguments: bool,
+    /// Plan tool calls without executing them: each call records a synthetic
+    /// result from [`Self::dry_run_results`] instead of calling the tool, and
+    /// its `ToolCallStarted` and `ToolResult` events are flagged `dry_run`.
+    /// Hooks and approvals still run. Defaults to `false`.
+    pub dry_run: bool,
+    pub dry_run_results: DryRunResults,
+    /// Independent prompts to work through one after another instead of a
+    /// single conversation.
+    ///
+    /// Each task runs as its own sub-run of `messages` plus the task prompt,
+    /// sharing the tools, settings, and limits, which apply per task. A task
+    /// that fails is recorded in [`RunResult::task_results`] and the next one
+    /// starts; only an abort stops the list. Tasks are bracketed by
+    /// [`RunEventPayload::TaskStarted`] and [`RunEventPayload::TaskCompleted`],
+    /// and the run emits one `Started` and one terminal lifecycle event.
+    /// Cannot be combined with `transcript`. `None`, the default, runs
+    /// `messages` as usual.
+    pub task_list: Option<Vec<TaskSpec>>,
+    /// What each task of `task_list` sees of the tasks before it. Defaults to
+    /// [`TaskCarryOver::None`].
+    pub task_carry_over: TaskCarryOver,
     /// Cumulative session input tokens from all previous runs (frozen at run start).
     pub prior_session_input_tokens: usize,
     /// Cumulative session output tokens from all previous runs (frozen at run start).
@@ -335,18 +740,34 @@ impl RunRequest {
             messages,
             settings: GenerationSettings::default(),
             tools: Vec::new(),
+            tools_provider: None,
             session_fs: None,
             session_cwd: None,
             workspace_root: None,
+            artifacts_dir: None,
+            keep_scratch: false,
+            webhooks: Vec::new(),
+            transcript: None,
             sandbox_provider: None,
+            memory: None,
             tool_visibility_policy: ToolVisibilityPolicy::default(),
+            tool_overflow: ToolOverflowPolicy::default(),
             approval_policy: ApprovalPolicy::ask(),
             approval_handler: None,
+            max_iterations: None,
+            max_tool_failures: None,
+            iteration_extension: None,
+            max_iteration_extensions: None,
             metadata: HashMap::new(),
+            event_tags: HashMap::new(),
+            event_sequence: EventSequence::new(),
             event_sink: None,
+            event_queue_capacity: DEFAULT_EVENT_QUEUE_CAPACITY,
+            event_overflow_policy: EventOverflowPolicy::default(),
             hooks: RunHooks::default(),
             auto_compaction: None,
             retry_backoff,
+            tool_retry: None,
             retry_mode: RetryMode::Bounded {
                 max_attempts: retry_backoff.max_attempts.max(1),
             },
@@ -359,6 +780,7 @@ impl RunRequest {
             session_id: None,
             transport: None,
             max_retry_delay_ms: None,
+            resume_partial_on_overload: false,
             api_key_override: None,
             api_key_overrides: HashMap::new(),
             get_api_key: None,
@@ -366,6 +788,24 @@ impl RunRequest {
             provider_metadata: HashMap::new(),
             provider_payload_callback: None,
             context_budget: None,
+            budget: None,
+            deadline: None,
+            deadline_mode: DeadlineMode::default(),
+            heartbeat_interval: None,
+            ttft_slo: None,
+            stream_coalescing: None,
+            final_response_schema: None,
+            warm_up: false,
+            prefill: None,
+            prefill_fallback: PrefillFallback::default(),
+            plugins: Vec::new(),
+            response_filter: None,
+            filter_tool_results: false,
+            repair_tool_arguments: true,
+            dry_run: false,
+            dry_run_results: DryRunResults::default(),
+            task_list: None,
+            task_carry_over: TaskCarryOver::default(),
             prior_session_input_tokens: 0,
             prior_session_output_tokens: 0,
             #[cfg(feature = "agent")]
@@ -409,6 +849,21 @@ impl RunRequest {
         self
     }
 
+    pub fn with_tools_provider(mut self, provider: ToolsProviderFn) -> Self {
+        self.tools_provider = Some(provider);
+        self
+    }
+
+    pub fn with_response_filter(mut self, filter: Arc<dyn ResponseFilter>) -> Self {
+        self.response_filter = Some(filter);
+        self
+    }
+
+    pub fn with_filtered_tool_results(mut self, enabled: bool) -> Self {
+        self.filter_tool_results = enabled;
+        self
+    }
+
     pub fn with_session_context(
         mut self,
         session_fs: Arc<dyn SessionFs + Send + Sync>,
@@ -425,21 +880,48 @@ impl RunRequest {
         self
     }
 
+    #[must_use]
+    pub fn with_artifacts_dir(mut self, artifacts_dir: PathBuf) -> Self {
+        self.artifacts_dir = Some(artifacts_dir);
+        self
+    }
+
     pub fn with_sandbox_provider(mut self, provider: Arc<dyn SandboxProvider>) -> Self {
         self.sandbox_provider = Some(provider);
         self
     }
 
+    pub fn with_memory(mut self, memory: Arc<dyn Memory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
     pub fn with_tool_visibility_policy(mut self, policy: ToolVisibilityPolicy) -> Self {
         self.tool_visibility_policy = policy;
         self
     }
 
+    pub fn with_tool_overflow(mut self, policy: ToolOverflowPolicy) -> Self {
+        self.tool_overflow = policy;
+        self
+    }
+
     pub fn with_event_sink(mut self, sink: RunEventSink) -> Self {
         self.event_sink = Some(sink);
         self
     }
 
+    pub fn with_event_queue(mut self, capacity: usize, policy: EventOverflowPolicy) -> Self {
+        self.event_queue_capacity = capacity;
+        self.event_overflow_policy = policy;
+        self
+    }
+
+    pub fn with_event_tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.event_tags = tags;
+        self
+    }
+
     pub fn with_approval_policy(mut self, policy: ApprovalPolicy) -> Self {
         self.approval_policy = policy;
         self
@@ -495,11 +977,82 @@ impl RunRequest {
         self
     }
 
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn with_max_tool_failures(mut self, max_tool_failures: usize) -> Self {
+        self.max_tool_failures = Some(max_tool_failures);
+        self
+    }
+
+    /// Grant `extension` more iterations per accepted extension, at most
+    /// `max_extensions` times.
+    pub fn with_iteration_extensions(mut self, extension: usize, max_extensions: usize) -> Self {
+        self.iteration_extension = Some(extension);
+        self.max_iteration_extensions = Some(max_extensions);
+        self
+    }
+
     pub fn with_max_retry_delay_ms(mut self, max_retry_delay_ms: u64) -> Self {
         self.max_retry_delay_ms = Some(max_retry_delay_ms);
         self
     }
 
+    pub fn with_resume_partial_on_overload(mut self, enabled: bool) -> Self {
+        self.resume_partial_on_overload = enabled;
+        self
+    }
+
+    pub fn with_keep_scratch(mut self, keep: bool) -> Self {
+        self.keep_scratch = keep;
+        self
+    }
+
+    pub fn with_webhook(mut self, webhook: WebhookConfig) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    pub fn with_transcript(mut self, transcript: TranscriptWriter) -> Self {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    pub fn with_warm_up(mut self, enabled: bool) -> Self {
+        self.warm_up = enabled;
+        self
+    }
+
+    pub fn with_repair_tool_arguments(mut self, enabled: bool) -> Self {
+        self.repair_tool_arguments = enabled;
+        self
+    }
+
+    /// Run as a dry run whose tool calls record `results` instead of
+    /// executing.
+    pub fn with_dry_run(mut self, results: DryRunResults) -> Self {
+        self.dry_run = true;
+        self.dry_run_results = results;
+        self
+    }
+
+    pub fn with_task_list(mut self, tasks: Vec<TaskSpec>) -> Self {
+        self.task_list = Some(tasks);
+        self
+    }
+
+    pub fn with_task_carry_over(mut self, carry_over: TaskCarryOver) -> Self {
+        self.task_carry_over = carry_over;
+        self
+    }
+
+    pub fn with_tool_retry(mut self, policy: ToolRetryPolicy) -> Self {
+        self.tool_retry = Some(policy);
+        self
+    }
+
     pub fn with_retry_backoff(mut self, retry_backoff: RetryBackoffPolicy) -> Self {
         self.retry_backoff = retry_backoff;
         if matches!(self.retry_mode, RetryMode::Bounded { .. }) {
@@ -556,6 +1109,58 @@ impl RunRequest {
         self
     }
 
+    pub fn with_deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_deadline_mode(mut self, mode: DeadlineMode) -> Self {
+        self.deadline_mode = mode;
+        self
+    }
+
+    pub fn with_budget(mut self, budget: RunBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    pub fn with_ttft_slo(mut self, slo: Duration) -> Self {
+        self.ttft_slo = Some(slo);
+        self
+    }
+
+    pub fn with_stream_coalescing(mut self, coalescing: StreamCoalescing) -> Self {
+        self.stream_coalescing = Some(coalescing);
+        self
+    }
+
+    pub fn with_final_response_schema(mut self, format: ResponseFormat) -> Self {
+        self.final_response_schema = Some(format);
+        self
+    }
+
+    pub fn with_prefill(mut self, prefill: impl Into<String>) -> Self {
+        self.prefill = Some(prefill.into());
+        self
+    }
+
+    pub fn with_prefill_fallback(mut self, fallback: PrefillFallback) -> Self {
+        self.prefill_fallback = fallback;
+        self
+    }
+
+    /// Add a plugin. Plugins compose in the order they are added; see
+    /// [`RunPlugin`].
+    pub fn with_plugin(mut self, plugin: Arc<dyn RunPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
     pub fn with_prior_session_usage(mut self, input_tokens: usize, output_tokens: usize) -> Self {
         self.prior_session_input_tokens = input_tokens;
         self.prior_session_output_tokens = output_tokens;
@@ -597,6 +1202,7 @@ pub struct RunHandle {
     abort_tx: Option<oneshot::Sender<()>>,
     result_rx: oneshot::Receiver<RunResult>,
     input_tx: Option<mpsc::UnboundedSender<ModelMessage>>,
+    status: SharedRunStatus,
 }
 
 impl RunHandle {
@@ -618,6 +1224,7 @@ impl RunHandle {
                 abort_tx: Some(abort_tx),
                 result_rx,
                 input_tx: Some(input_tx),
+                status: SharedRunStatus::new(RunStatus::Running),
             },
             abort_rx,
             result_tx,
@@ -629,6 +1236,16 @@ impl RunHandle {
         self.run_id
     }
 
+    /// Current status: [`RunStatus::Queued`] while the run waits for a slot,
+    /// `Running` until it ends, then the result's status.
+    pub fn status(&self) -> RunStatus {
+        self.status.get()
+    }
+
+    pub(crate) fn shared_status(&self) -> SharedRunStatus {
+        self.status.clone()
+    }
+
     pub fn abort(&mut self) -> bool {
         if let Some(tx) = self.abort_tx.take() {
             return tx.send(()).is_ok();
@@ -661,30 +1278,93 @@ pub trait Runner: Send + Sync {
 }
 
 /// Default agent-loop runner (tool loop + approvals + event stream).
+///
+/// Clones share one admission queue; see [`LoopRunner::with_options`].
 #[derive(Clone)]
 pub struct LoopRunner {
-    config: RociConfig,
+    config: RunnerConfig,
     provider_factory: ProviderFactory,
+    admission: Arc<AdmissionController>,
 }
 
 impl LoopRunner {
     /// Create with an `Arc<ProviderRegistry>` for dynamic provider resolution.
     pub fn with_registry(config: RociConfig, registry: Arc<ProviderRegistry>) -> Self {
         Self {
-            config,
-            provider_factory: Arc::new(move |model, cfg| {
-                registry.create_provider(model.provider_name(), model.model_id(), cfg)
-            }),
+            config: RunnerConfig::Fixed(config),
+            provider_factory: registry_factory(registry),
+            admission: AdmissionController::new(RunnerOptions::default()),
         }
     }
 
+    /// Create a runner that snapshots [`RociConfig::global`] at the start of
+    /// each run, so runs started after a
+    /// [`global_reload`](RociConfig::global_reload) use the new config while
+    /// runs already in flight keep theirs.
+    pub fn with_global_config(registry: Arc<ProviderRegistry>) -> Self {
+        Self {
+            config: RunnerConfig::Global,
+            provider_factory: registry_factory(registry),
+            admission: AdmissionController::new(RunnerOptions::default()),
+        }
+    }
+
+    /// Cap concurrent runs, queueing or rejecting the excess.
+    ///
+    /// Queued runs report [`RunStatus::Queued`], emit
+    /// [`RunLifecycle::Queued`], and can be aborted while they wait. When the
+    /// queue is full, [`Runner::start`] fails with [`RociError::RateLimited`]
+    /// carrying a retry-after estimated from recent run durations. Replaces
+    /// the admission queue, so call it before cloning the runner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Configuration`] when `max_concurrent_runs` is 0.
+    pub fn with_options(mut self, options: RunnerOptions) -> Result<Self, RociError> {
+        AdmissionController::validate(&options)?;
+        self.admission = AdmissionController::new(options);
+        Ok(self)
+    }
+
+    /// Running, queued, and rejected run counts across this runner's clones.
+    pub fn metrics(&self) -> RunnerMetrics {
+        self.admission.metrics()
+    }
+
     #[cfg(test)]
     fn with_provider_factory(config: RociConfig, provider_factory: ProviderFactory) -> Self {
         Self {
-            config,
+            config: RunnerConfig::Fixed(config),
             provider_factory,
+            admission: AdmissionController::new(RunnerOptions::default()),
         }
     }
+
+    #[cfg(test)]
+    fn following_global_config(mut self) -> Self {
+        self.config = RunnerConfig::Global;
+        self
+    }
+}
+
+/// Where a [`LoopRunner`] gets the config for each run.
+#[derive(Clone)]
+enum RunnerConfig {
+    Fixed(RociConfig),
+    Global,
+}
+
+impl RunnerConfig {
+    fn snapshot(&self) -> RociConfig {
+        match self {
+            Self::Fixed(config) => config.clone(),
+            Self::Global => RociConfig::global(),
+        }
+    }
+}
+
+fn registry_factory(registry: Arc<ProviderRegistry>) -> ProviderFactory {
+    Arc::new(move |model, cfg| registry.create_provider_for(model, cfg))
 }
 
 type ProviderFactory = Arc<
@@ -693,11 +1373,43 @@ type ProviderFactory = Arc<
         + Sync,
 >;
 
+mod admission;
+mod argument_progress;
+mod argument_repair;
+mod budget;
+mod coalescing;
 mod control;
+mod deadline;
+mod defaults;
+mod dispatch;
+mod dry_run;
 mod engine;
+mod final_response;
+mod first_token;
+mod heartbeat;
+mod history;
 mod limits;
 mod message_events;
+mod message_lint;
+mod plugin;
+mod prefill;
+mod progress;
+mod retry_budget;
+mod task_list;
+mod tool_overflow;
 mod tooling;
+mod turns;
+mod warm_up;
+
+pub(crate) use admission::SharedRunStatus;
+pub use admission::{RunnerMetrics, RunnerOptions};
+pub use coalescing::StreamCoalescing;
+pub use defaults::RunRequestDefaults;
+pub use dispatch::{EventOverflowPolicy, DEFAULT_EVENT_QUEUE_CAPACITY};
+pub use dry_run::DryRunResults;
+pub use plugin::RunPlugin;
+pub use task_list::{TaskCarryOver, TaskSpec};
+pub use tool_overflow::ToolOverflowPolicy;
 
 #[cfg(test)]
 #[path = "runner/tests/mod.rs"]
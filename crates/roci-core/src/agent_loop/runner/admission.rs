@@ -0,0 +1,297 @@
+//! Admission control: caps concurrent runs of one [`LoopRunner`](super::LoopRunner).
+//!
+//! Runs beyond [`RunnerOptions::max_concurrent_runs`] wait in a FIFO queue of
+//! [`RunnerOptions::queue_capacity`] entries; once it is full, `start` fails
+//! with [`RociError::RateLimited`] and a retry-after estimated from recent run
+//! durations.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::agent_loop::{RunId, RunStatus};
+use crate::error::RociError;
+
+/// Retry-after suggested before any run has finished.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Weight of the newest run in the average run duration.
+const DURATION_SMOOTHING: f64 = 0.2;
+
+/// Admission limits for a [`LoopRunner`](super::LoopRunner).
+///
+/// The default admits every run immediately.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunnerOptions {
+    /// Runs allowed to execute at once. `None` means unlimited; must be at
+    /// least 1 when set.
+    pub max_concurrent_runs: Option<usize>,
+    /// Runs allowed to wait for a slot. Further runs are rejected.
+    pub queue_capacity: usize,
+    /// Longest a run waits in the queue before it fails.
+    pub queue_timeout: Option<Duration>,
+}
+
+impl RunnerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_concurrent_runs(mut self, max: usize) -> Self {
+        self.max_concurrent_runs = Some(max);
+        self
+    }
+
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    pub fn with_queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Admission counters of a [`LoopRunner`](super::LoopRunner), shared by its
+/// clones.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunnerMetrics {
+    /// Runs executing now.
+    pub running: usize,
+    /// Runs waiting for a slot.
+    pub queued: usize,
+    /// Runs rejected because the queue was full.
+    pub rejected: u64,
+    /// Recent average duration of finished runs; `None` until one finishes.
+    pub average_run_duration: Option<Duration>,
+}
+
+/// A shared run status that [`RunHandle::status`](super::RunHandle::status)
+/// reads.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedRunStatus(Arc<Mutex<RunStatus>>);
+
+impl SharedRunStatus {
+    pub(crate) fn new(status: RunStatus) -> Self {
+        Self(Arc::new(Mutex::new(status)))
+    }
+
+    pub(crate) fn get(&self) -> RunStatus {
+        self.0
+            .lock()
+            .map(|status| *status)
+            .unwrap_or(RunStatus::Running)
+    }
+
+    pub(crate) fn set(&self, status: RunStatus) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = status;
+        }
+    }
+}
+
+/// Outcome of asking for a run slot.
+pub(super) enum Admission {
+    Running(RunPermit),
+    Queued(QueueTicket),
+}
+
+/// Hands out run slots for one runner and its clones.
+#[derive(Debug)]
+pub(super) struct AdmissionController {
+    options: RunnerOptions,
+    state: Mutex<AdmissionState>,
+}
+
+#[derive(Debug, Default)]
+struct AdmissionState {
+    running: usize,
+    waiting: VecDeque<Waiter>,
+    rejected: u64,
+    average_run_secs: Option<f64>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    run_id: RunId,
+    grant: oneshot::Sender<RunPermit>,
+}
+
+impl AdmissionController {
+    pub(super) fn new(options: RunnerOptions) -> Arc<Self> {
+        Arc::new(Self {
+            options,
+            state: Mutex::default(),
+        })
+    }
+
+    pub(super) fn validate(options: &RunnerOptions) -> Result<(), RociError> {
+        if options.max_concurrent_runs == Some(0) {
+            return Err(RociError::Configuration(
+                "max_concurrent_runs must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Take a slot, join the queue, or fail when the queue is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::RateLimited`] with a suggested retry-after.
+    pub(super) fn admit(self: &Arc<Self>, run_id: RunId) -> Result<Admission, RociError> {
+        let mut state = self.lock();
+        let limit = self.options.max_concurrent_runs.unwrap_or(usize::MAX);
+        if state.running < limit && state.waiting.is_empty() {
+            state.running += 1;
+            return Ok(Admission::Running(RunPermit::new(self.clone())));
+        }
+        if state.waiting.len() >= self.options.queue_capacity {
+            state.rejected += 1;
+            let retry_after = self.retry_after(&state);
+            return Err(RociError::RateLimited {
+                retry_after_ms: Some(u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX)),
+            });
+        }
+        let (grant, granted) = oneshot::channel();
+        state.waiting.push_back(Waiter { run_id, grant });
+        Ok(Admission::Queued(QueueTicket {
+            controller: self.clone(),
+            run_id,
+            position: state.waiting.len(),
+            granted,
+            timeout: self.options.queue_timeout,
+        }))
+    }
+
+    pub(super) fn metrics(&self) -> RunnerMetrics {
+        let state = self.lock();
+        RunnerMetrics {
+            running: state.running,
+            queued: state.waiting.len(),
+            rejected: state.rejected,
+            average_run_duration: state.average_run_secs.map(Duration::from_secs_f64),
+        }
+    }
+
+    /// Time until a run rejected now could expect a slot: one average run
+    /// per full round of the queue ahead of it.
+    fn retry_after(&self, state: &AdmissionState) -> Duration {
+        let Some(average) = state.average_run_secs else {
+            return DEFAULT_RETRY_AFTER;
+        };
+        let slots = self.options.max_concurrent_runs.unwrap_or(1).max(1);
+        let rounds = (state.waiting.len() + 1).div_ceil(slots);
+        Duration::from_secs_f64(average * rounds as f64).max(Duration::from_millis(1))
+    }
+
+    /// Record a finished run and hand its slot to the oldest waiter.
+    fn release(self: &Arc<Self>, ran_for: Duration) {
+        let mut state = self.lock();
+        let secs = ran_for.as_secs_f64();
+        state.average_run_secs = Some(match state.average_run_secs {
+            Some(average) => average + DURATION_SMOOTHING * (secs - average),
+            None => secs,
+        });
+        while let Some(waiter) = state.waiting.pop_front() {
+            // The send fails when the run stopped waiting; dropping the
+            // returned permit would re-enter `release` under the lock.
+            match waiter.grant.send(RunPermit::new(self.clone())) {
+                Ok(()) => return,
+                Err(permit) => permit.forget(),
+            }
+        }
+        state.running = state.running.saturating_sub(1);
+    }
+
+    fn leave_queue(&self, run_id: RunId) {
+        self.lock().waiting.retain(|waiter| waiter.run_id != run_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AdmissionState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A run slot, released when the run task ends.
+#[derive(Debug)]
+pub(super) struct RunPermit {
+    controller: Option<Arc<AdmissionController>>,
+    started: Instant,
+}
+
+impl RunPermit {
+    fn new(controller: Arc<AdmissionController>) -> Self {
+        Self {
+            controller: Some(controller),
+            started: Instant::now(),
+        }
+    }
+
+    /// Drop without releasing; the slot stays taken by whoever holds it next.
+    fn forget(mut self) {
+        self.controller = None;
+    }
+}
+
+impl Drop for RunPermit {
+    fn drop(&mut self) {
+        if let Some(controller) = self.controller.take() {
+            controller.release(self.started.elapsed());
+        }
+    }
+}
+
+/// A place in the queue. Dropping it leaves the queue.
+#[derive(Debug)]
+pub(super) struct QueueTicket {
+    controller: Arc<AdmissionController>,
+    run_id: RunId,
+    position: usize,
+    granted: oneshot::Receiver<RunPermit>,
+    timeout: Option<Duration>,
+}
+
+impl QueueTicket {
+    /// 1-based position when the run joined the queue.
+    pub(super) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Wait for a slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns the failure message when the queue timeout elapses first.
+    pub(super) async fn wait(mut self) -> Result<RunPermit, String> {
+        let granted = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, &mut self.granted).await {
+                Ok(granted) => granted,
+                Err(_) => {
+                    return Err(format!(
+                        "run waited in the admission queue for longer than {}ms",
+                        timeout.as_millis()
+                    ))
+                }
+            },
+            None => (&mut self.granted).await,
+        };
+        granted.map_err(|_| "admission queue closed".to_string())
+    }
+}
+
+impl Drop for QueueTicket {
+    fn drop(&mut self) {
+        self.controller.leave_queue(self.run_id);
+        // A permit granted after the run stopped waiting frees its slot.
+        self.granted.close();
+        if let Ok(permit) = self.granted.try_recv() {
+            drop(permit);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Throttled progress for tool-call arguments that are still streaming.
+
+use std::collections::{HashMap, HashSet};
+
+use tokio::time::{Duration, Instant};
+
+/// Emit progress at least every this many newly received argument bytes.
+pub(super) const ARGUMENT_PROGRESS_BYTES: usize = 1024;
+/// Emit progress after this much time when new bytes arrived in between.
+pub(super) const ARGUMENT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Maximum characters kept for the argument preview.
+pub(super) const ARGUMENT_PREVIEW_CHARS: usize = 120;
+
+/// Progress snapshot for one streaming tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ArgumentProgressUpdate {
+    pub(super) call_id: String,
+    pub(super) name: String,
+    pub(super) partial_args_len: usize,
+    pub(super) preview: String,
+}
+
+#[derive(Debug)]
+struct CallProgress {
+    name: String,
+    len: usize,
+    preview: String,
+    preview_chars: usize,
+    emitted_len: usize,
+    emitted_at: Option<Instant>,
+}
+
+/// Per-attempt accumulator for argument fragments, keyed by call id.
+///
+/// The first fragment of a call always produces an update; later fragments
+/// produce one once [`ARGUMENT_PROGRESS_BYTES`] new bytes arrived or
+/// [`ARGUMENT_PROGRESS_INTERVAL`] elapsed. Finalized calls never update again.
+#[derive(Debug, Default)]
+pub(super) struct ArgumentProgress {
+    calls: HashMap<String, CallProgress>,
+    finalized: HashSet<String>,
+}
+
+impl ArgumentProgress {
+    pub(super) fn record(
+        &mut self,
+        call_id: &str,
+        name: &str,
+        fragment: &str,
+        now: Instant,
+    ) -> Option<ArgumentProgressUpdate> {
+        if fragment.is_empty() || self.finalized.contains(call_id) {
+            return None;
+        }
+        let progress = self
+            .calls
+            .entry(call_id.to_string())
+            .or_insert_with(|| CallProgress {
+                name: name.to_string(),
+                len: 0,
+                preview: String::new(),
+                preview_chars: 0,
+                emitted_len: 0,
+                emitted_at: None,
+            });
+        progress.len += fragment.len();
+        for ch in fragment.chars() {
+            if progress.preview_chars >= ARGUMENT_PREVIEW_CHARS {
+                break;
+            }
+            progress
+                .preview
+                .push(if ch.is_control() { ' ' } else { ch });
+            progress.preview_chars += 1;
+        }
+
+        let due = match progress.emitted_at {
+            None => true,
+            Some(emitted_at) => {
+                progress.len - progress.emitted_len >= ARGUMENT_PROGRESS_BYTES
+                    || now.saturating_duration_since(emitted_at) >= ARGUMENT_PROGRESS_INTERVAL
+            }
+        };
+        if !due {
+            return None;
+        }
+        progress.emitted_len = progress.len;
+        progress.emitted_at = Some(now);
+        Some(ArgumentProgressUpdate {
+            call_id: call_id.to_string(),
+            name: progress.name.clone(),
+            partial_args_len: progress.len,
+            preview: progress.preview.clone(),
+        })
+    }
+
+    /// Stop tracking a call once its complete arguments arrived.
+    pub(super) fn finalize(&mut self, call_id: &str) {
+        self.calls.remove(call_id);
+        self.finalized.insert(call_id.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttles_by_bytes_and_reports_running_length() {
+        let mut progress = ArgumentProgress::default();
+        let now = Instant::now();
+        let chunk = "a".repeat(300);
+
+        let updates = (0..8)
+            .filter_map(|_| progress.record("call-1", "write_file", &chunk, now))
+            .map(|update| update.partial_args_len)
+            .collect::<Vec<_>>();
+
+        assert_eq!(updates, vec![300, 1500]);
+    }
+
+    #[test]
+    fn emits_after_interval_even_below_byte_threshold() {
+        let mut progress = ArgumentProgress::default();
+        let now = Instant::now();
+
+        assert!(progress.record("call-1", "write_file", "{", now).is_some());
+        assert!(progress.record("call-1", "write_file", "\"", now).is_none());
+        let update = progress
+            .record(
+                "call-1",
+                "write_file",
+                "p",
+                now + ARGUMENT_PROGRESS_INTERVAL,
+            )
+            .expect("interval elapsed");
+        assert_eq!(update.partial_args_len, 3);
+    }
+
+    #[test]
+    fn preview_is_bounded_and_sanitized() {
+        let mut progress = ArgumentProgress::default();
+        let fragment = format!("{{\"content\":\"line\n{}", "x".repeat(500));
+
+        let update = progress
+            .record("call-1", "write_file", &fragment, Instant::now())
+            .expect("first fragment");
+
+        assert_eq!(update.preview.chars().count(), ARGUMENT_PREVIEW_CHARS);
+        assert!(!update.preview.contains('\n'));
+    }
+
+    #[test]
+    fn finalized_calls_stop_reporting() {
+        let mut progress = ArgumentProgress::default();
+        let now = Instant::now();
+        progress.record("call-1", "write_file", "{", now);
+
+        progress.finalize("call-1");
+
+        assert!(progress
+            .record("call-1", "write_file", &"a".repeat(4096), now)
+            .is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//! Deterministic repair of malformed tool-call argument JSON.
+//!
+//! Providers keep arguments that fail to parse as a JSON string holding the
+//! raw text. When [`RunRequest::repair_tool_arguments`](super::RunRequest::repair_tool_arguments)
+//! is on, the runner retries those with a fixed set of normalizations and
+//! nothing else:
+//!
+//! - trailing commas before `}`/`]` (or the end of input) are dropped,
+//! - single-quoted strings become double-quoted,
+//! - raw control characters inside strings are escaped,
+//! - braces and brackets still open at the end of input are closed.
+//!
+//! Input that still fails to parse, or that does not parse to an object, is
+//! left as it was. Unterminated strings are never closed, since that would
+//! invent the end of a value.
+
+use serde_json::Value;
+
+use crate::types::AgentToolCall;
+
+/// Larger argument payloads are never repaired.
+const MAX_REPAIR_BYTES: usize = 1024 * 1024;
+
+/// Replace `call.arguments` with its repair when it holds unparseable raw
+/// JSON that the normalizations fix. Returns whether it did.
+pub(super) fn repair_call_arguments(call: &mut AgentToolCall) -> bool {
+    let Value::String(raw) = &call.arguments else {
+        return false;
+    };
+    if serde_json::from_str::<Value>(raw).is_ok() {
+        return false;
+    }
+    match repair_arguments(raw) {
+        Some(repaired) => {
+            call.arguments = repaired;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parse `raw` after the normalizations; `None` when it stays malformed or
+/// is not an object.
+pub(super) fn repair_arguments(raw: &str) -> Option<Value> {
+    if raw.len() > MAX_REPAIR_BYTES {
+        return None;
+    }
+    let mut out = String::with_capacity(raw.len() + 8);
+    let mut closers = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+
+    for ch in raw.chars() {
+        let Some(open) = quote else {
+            match ch {
+                '"' | '\'' => {
+                    quote = Some(ch);
+                    out.push('"');
+                }
+                '{' => {
+                    closers.push('}');
+                    out.push(ch);
+                }
+                '[' => {
+                    closers.push(']');
+                    out.push(ch);
+                }
+                '}' | ']' => {
+                    if closers.pop() != Some(ch) {
+                        return None;
+                    }
+                    drop_trailing_comma(&mut out);
+                    out.push(ch);
+                }
+                _ => out.push(ch),
+            }
+            continue;
+        };
+        if escaped {
+            escaped = false;
+            // `\'` is only an escape inside single quotes, where it becomes
+            // a plain apostrophe.
+            if !(open == '\'' && ch == '\'') {
+                out.push('\\');
+            }
+            out.push(ch);
+            continue;
+        }
+        match ch {
+            '\\' => escaped = true,
+            _ if ch == open => {
+                quote = None;
+                out.push('"');
+            }
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ if ch.is_control() => out.push_str(&format!("\\u{:04x}", u32::from(ch))),
+            _ => out.push(ch),
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    drop_trailing_comma(&mut out);
+    while let Some(closer) = closers.pop() {
+        out.push(closer);
+    }
+
+    serde_json::from_str::<Value>(&out)
+        .ok()
+        .filter(Value::is_object)
+}
+
+/// Remove a comma that is the last non-whitespace character of `out`.
+fn drop_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end();
+    if trimmed.ends_with(',') {
+        out.truncate(trimmed.len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{ "name", "raw", "repaired" }` entries; `repaired` is `null` for
+    /// input that must stay unrecoverable.
+    const CORPUS: &str = include_str!("../../../tests/fixtures/tool_arguments/malformed.json");
+
+    #[test]
+    fn corpus_repairs_match_expectations() {
+        let corpus: Vec<Value> = serde_json::from_str(CORPUS).expect("corpus parses");
+        assert!(corpus.len() >= 10);
+        for case in corpus {
+            let name = case["name"].as_str().expect("case name");
+            let raw = case["raw"].as_str().expect("case raw");
+            let expected = match &case["repaired"] {
+                Value::Null => None,
+                value => Some(value.clone()),
+            };
+            assert!(
+                serde_json::from_str::<Value>(raw).is_err(),
+                "{name}: fixture must be malformed"
+            );
+            assert_eq!(repair_arguments(raw), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn valid_and_non_string_arguments_are_left_alone() {
+        let mut parsed = AgentToolCall {
+            id: "call-1".to_string(),
+            name: "tool".to_string(),
+            arguments: serde_json::json!({ "path": "a" }),
+            called_as: None,
+            recipient: None,
+        };
+        assert!(!repair_call_arguments(&mut parsed));
+
+        // A double-encoded object is valid JSON; unwrapping it is not one of
+        // the listed repairs.
+        let mut encoded = AgentToolCall {
+            arguments: Value::String(r#"{"path": "a"}"#.to_string()),
+            ..parsed.clone()
+        };
+        assert!(!repair_call_arguments(&mut encoded));
+        assert_eq!(encoded.arguments, Value::String(r#"{"path": "a"}"#.into()));
+
+        let mut malformed = AgentToolCall {
+            arguments: Value::String(r#"{"path": "a",}"#.to_string()),
+            ..parsed
+        };
+        assert!(repair_call_arguments(&mut malformed));
+        assert_eq!(malformed.arguments, serde_json::json!({ "path": "a" }));
+    }
+
+    #[test]
+    fn oversized_arguments_are_not_repaired() {
+        let raw = format!("{{\"text\": \"{}\",}}", "a".repeat(MAX_REPAIR_BYTES));
+
+        assert_eq!(repair_arguments(&raw), None);
+    }
+}
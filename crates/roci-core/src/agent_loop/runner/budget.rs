@@ -0,0 +1,215 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::time::Instant;
+
+use crate::error::RociError;
+use crate::models::LanguageModel;
+use crate::types::Usage;
+
+use super::control::{AgentEventEmitter, RunEventEmitter};
+use super::{AgentEvent, RunBudget, RunEventPayload, RunEventStream, RunRequest};
+use crate::agent_loop::{BudgetLimit, BudgetReading};
+
+/// Enforces a request's [`RunBudget`] over the life of one run.
+pub(super) struct BudgetTracker {
+    budget: Option<RunBudget>,
+    started_at: Instant,
+    /// Usage already folded into `cost_usd`.
+    priced_usage: Usage,
+    cost_usd: f64,
+    /// Indexed by `BudgetLimit as usize`.
+    warned: [AtomicBool; 3],
+}
+
+impl BudgetTracker {
+    pub(super) fn new(budget: Option<RunBudget>) -> Self {
+        Self {
+            budget,
+            started_at: Instant::now(),
+            priced_usage: Usage::default(),
+            cost_usd: 0.0,
+            warned: Default::default(),
+        }
+    }
+
+    /// Price the usage accrued since the last call with `model`'s rates and
+    /// emit warnings for token or cost limits that crossed the threshold.
+    pub(super) fn record_usage(
+        &mut self,
+        emitter: &RunEventEmitter,
+        agent_emitter: &AgentEventEmitter,
+        model: &LanguageModel,
+        run_usage: &Usage,
+    ) {
+        let Some(budget) = self.budget.as_ref() else {
+            return;
+        };
+        if budget.max_cost_usd.is_some() {
+            let delta = usage_since(run_usage, &self.priced_usage);
+            if let Some(pricing) = budget.pricing.lookup(model) {
+                self.cost_usd += pricing.cost_usd(&delta);
+            }
+        }
+        self.priced_usage = run_usage.clone();
+        for reading in self.usage_readings(run_usage) {
+            self.warn_if_near(emitter, agent_emitter, reading);
+        }
+    }
+
+    /// First limit the run has reached, if any, after emitting any pending
+    /// warnings.
+    pub(super) fn exceeded(
+        &self,
+        emitter: &RunEventEmitter,
+        agent_emitter: &AgentEventEmitter,
+        run_usage: &Usage,
+    ) -> Option<BudgetReading> {
+        let mut readings = self.usage_readings(run_usage);
+        readings.extend(self.wall_clock_reading());
+        for reading in &readings {
+            self.warn_if_near(emitter, agent_emitter, *reading);
+        }
+        readings
+            .into_iter()
+            .find(|reading| reading.fraction() >= 1.0)
+    }
+
+    /// Resolves when the wall-clock limit runs out, emitting its warning on
+    /// the way; never resolves without a wall-clock limit.
+    pub(super) async fn wall_clock_expired(
+        &self,
+        emitter: &RunEventEmitter,
+        agent_emitter: &AgentEventEmitter,
+    ) -> BudgetReading {
+        let Some(max) = self
+            .budget
+            .as_ref()
+            .and_then(|budget| budget.max_wall_clock)
+        else {
+            return std::future::pending().await;
+        };
+        tokio::time::sleep_until(self.started_at + max.mul_f64(RunBudget::WARNING_FRACTION)).await;
+        if let Some(reading) = self.wall_clock_reading() {
+            self.warn_if_near(emitter, agent_emitter, reading);
+        }
+        tokio::time::sleep_until(self.started_at + max).await;
+        self.wall_clock_reading()
+            .expect("wall-clock limit is configured")
+    }
+
+    fn usage_readings(&self, run_usage: &Usage) -> Vec<BudgetReading> {
+        let Some(budget) = self.budget.as_ref() else {
+            return Vec::new();
+        };
+        let mut readings = Vec::new();
+        if let Some(max) = budget.max_total_tokens {
+            readings.push(BudgetReading {
+                limit: BudgetLimit::TotalTokens,
+                used: total_tokens(run_usage) as f64,
+                max: max as f64,
+            });
+        }
+        if let Some(max) = budget.max_cost_usd {
+            readings.push(BudgetReading {
+                limit: BudgetLimit::CostUsd,
+                used: self.cost_usd,
+                max,
+            });
+        }
+        readings
+    }
+
+    fn wall_clock_reading(&self) -> Option<BudgetReading> {
+        let max = self.budget.as_ref()?.max_wall_clock?;
+        Some(BudgetReading {
+            limit: BudgetLimit::WallClock,
+            used: self.started_at.elapsed().as_secs_f64(),
+            max: max.as_secs_f64(),
+        })
+    }
+
+    fn warn_if_near(
+        &self,
+        emitter: &RunEventEmitter,
+        agent_emitter: &AgentEventEmitter,
+        reading: BudgetReading,
+    ) {
+        if reading.fraction() < RunBudget::WARNING_FRACTION
+            || self.warned[reading.limit as usize].swap(true, Ordering::Relaxed)
+        {
+            return;
+        }
+        emitter.emit(
+            RunEventStream::System,
+            RunEventPayload::BudgetWarning { reading },
+        );
+        agent_emitter.emit(AgentEvent::System {
+            message: format!(
+                "run budget {:.0}% spent: {}",
+                reading.fraction() * 100.0,
+                describe_spend(&reading)
+            ),
+        });
+    }
+}
+
+/// Reject cost budgets that cannot be priced for every candidate model.
+pub(super) fn validate_budget(request: &RunRequest) -> Result<(), RociError> {
+    let Some(budget) = request.budget.as_ref() else {
+        return Ok(());
+    };
+    if budget.max_cost_usd.is_none() {
+        return Ok(());
+    }
+    match request
+        .candidates
+        .iter()
+        .find(|model| budget.pricing.lookup(model).is_none())
+    {
+        Some(model) => Err(RociError::Configuration(format!(
+            "no pricing for model {model}; add it to RunBudget::pricing to enforce max_cost_usd"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Error message recorded on a run stopped by `reading`.
+pub(super) fn budget_exceeded_message(reading: &BudgetReading) -> String {
+    format!("run budget exceeded: {}", describe_spend(reading))
+}
+
+fn describe_spend(reading: &BudgetReading) -> String {
+    match reading.limit {
+        BudgetLimit::TotalTokens => {
+            format!("used {} of {} tokens", reading.used, reading.max)
+        }
+        BudgetLimit::CostUsd => {
+            format!("spent ${:.4} of ${:.4}", reading.used, reading.max)
+        }
+        BudgetLimit::WallClock => {
+            format!("ran {:.1}s of {:.1}s", reading.used, reading.max)
+        }
+    }
+}
+
+fn total_tokens(usage: &Usage) -> u64 {
+    u64::from(usage.total_tokens)
+        .max(u64::from(usage.input_tokens) + u64::from(usage.output_tokens))
+}
+
+fn usage_since(current: &Usage, previous: &Usage) -> Usage {
+    let since = |current: Option<u32>, previous: Option<u32>| {
+        current.map(|tokens| tokens.saturating_sub(previous.unwrap_or(0)))
+    };
+    Usage {
+        input_tokens: current.input_tokens.saturating_sub(previous.input_tokens),
+        output_tokens: current.output_tokens.saturating_sub(previous.output_tokens),
+        total_tokens: current.total_tokens.saturating_sub(previous.total_tokens),
+        cache_read_tokens: since(current.cache_read_tokens, previous.cache_read_tokens),
+        cache_creation_tokens: since(
+            current.cache_creation_tokens,
+            previous.cache_creation_tokens,
+        ),
+        reasoning_tokens: since(current.reasoning_tokens, previous.reasoning_tokens),
+    }
+}
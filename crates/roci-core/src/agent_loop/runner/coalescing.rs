@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::time::{self, Instant};
+
+use crate::error::RociError;
+use crate::types::{StreamEventType, TextStreamDelta};
+
+type DeltaStream = BoxStream<'static, Result<TextStreamDelta, RociError>>;
+
+/// Merging of text deltas from fast providers; see
+/// [`RunRequest::stream_coalescing`](super::RunRequest::stream_coalescing).
+///
+/// A text delta arriving within `burst_gap` of the previous delta starts a
+/// batch. Text deltas that follow are appended to it until `window` has
+/// passed since it started, it holds `max_chars` characters, or any other
+/// delta arrives, which is passed on after the batch. Slower streams pass
+/// through unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamCoalescing {
+    /// Longest a text delta is held back. Defaults to 16 ms.
+    pub window: Duration,
+    /// Characters after which a batch is emitted early. Defaults to 256.
+    pub max_chars: usize,
+    /// Arrival gap at or under which deltas count as a burst. Defaults to
+    /// 4 ms.
+    pub burst_gap: Duration,
+}
+
+impl Default for StreamCoalescing {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(16),
+            max_chars: 256,
+            burst_gap: Duration::from_millis(4),
+        }
+    }
+}
+
+impl StreamCoalescing {
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    pub fn with_burst_gap(mut self, burst_gap: Duration) -> Self {
+        self.burst_gap = burst_gap;
+        self
+    }
+}
+
+/// A delta carrying nothing but text, which is safe to merge.
+fn is_plain_text(delta: &TextStreamDelta) -> bool {
+    delta.event_type == StreamEventType::TextDelta
+        && delta.tool_call.is_none()
+        && delta.finish_reason.is_none()
+        && delta.usage.is_none()
+        && delta.reasoning.is_none()
+        && delta.reasoning_signature.is_none()
+        && delta.reasoning_type.is_none()
+        && delta.image.is_none()
+        && delta.response_metadata.is_none()
+}
+
+/// Text deltas being merged and when they must be emitted.
+struct Batch {
+    delta: TextStreamDelta,
+    chars: usize,
+    flush_at: Instant,
+}
+
+/// Wrap `stream` so bursts of text deltas arrive merged per `coalescing`.
+///
+/// The concatenated text is unchanged and no delta moves past another kind
+/// of delta, an error, or the end of the stream.
+pub(super) fn coalesce_text_deltas(
+    stream: DeltaStream,
+    coalescing: Option<StreamCoalescing>,
+) -> DeltaStream {
+    let Some(coalescing) = coalescing else {
+        return stream;
+    };
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let mut batch: Option<Batch> = None;
+        let mut last_arrival: Option<Instant> = None;
+        loop {
+            let next = match &batch {
+                Some(pending) => match time::timeout_at(pending.flush_at, stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        if let Some(expired) = batch.take() {
+                            yield Ok(expired.delta);
+                        }
+                        continue;
+                    }
+                },
+                None => stream.next().await,
+            };
+            let Some(item) = next else {
+                if let Some(pending) = batch.take() {
+                    yield Ok(pending.delta);
+                }
+                break;
+            };
+            let now = Instant::now();
+            let in_burst = last_arrival
+                .is_some_and(|previous| now.duration_since(previous) <= coalescing.burst_gap);
+            last_arrival = Some(now);
+            match item {
+                Ok(delta) if is_plain_text(&delta) => {
+                    let chars = delta.text.chars().count();
+                    match batch.as_mut() {
+                        Some(pending) => {
+                            pending.delta.text.push_str(&delta.text);
+                            pending.chars += chars;
+                        }
+                        None if in_burst => {
+                            batch = Some(Batch {
+                                delta,
+                                chars,
+                                flush_at: now + coalescing.window,
+                            });
+                        }
+                        None => {
+                            yield Ok(delta);
+                            continue;
+                        }
+                    }
+                    if batch.as_ref().is_some_and(|pending| pending.chars >= coalescing.max_chars) {
+                        if let Some(full) = batch.take() {
+                            yield Ok(full.delta);
+                        }
+                    }
+                }
+                other => {
+                    if let Some(pending) = batch.take() {
+                        yield Ok(pending.delta);
+                    }
+                    yield other;
+                }
+            }
+        }
+    })
+}
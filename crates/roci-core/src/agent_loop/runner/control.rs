@@ -3,11 +3,17 @@ use super::super::approvals::{
     ApprovalFilesystemAccess, ApprovalGrant, ApprovalGrantKey, ApprovalHandler, ApprovalKind,
     ApprovalPolicy, ApprovalRequest, ApprovalSafetyFloor,
 };
-use super::super::events::{AgentEvent, RunEvent, RunEventPayload, RunEventStream, RunLifecycle};
-use super::super::types::{RunId, RunResult};
+use super::super::events::{
+    AgentEvent, AgentEventEnvelope, EventSequence, EventTags, RunEvent, RunEventPayload,
+    RunEventStream,
+};
+use super::super::types::RunId;
+use super::argument_progress::ArgumentProgress;
 use super::message_events::{
-    assistant_message_snapshot, emit_message_end_if_open, emit_message_start_if_needed,
+    assistant_message_snapshot, emit_message_end_if_open, emit_message_end_with_images_if_open,
+    emit_message_start_if_needed, StoredMessageFilter,
 };
+use super::turns::TurnTracker;
 use super::{AgentEventSink, RunEventSink};
 use crate::human_interaction::{
     HumanInteractionCoordinator, HumanInteractionError, HumanInteractionPayload,
@@ -15,33 +21,23 @@ use crate::human_interaction::{
     HumanInteractionSource, ToolPermissionKind, ToolPermissionRequest, ToolPermissionResponse,
     ToolPermissionSessionApprovals, ToolPermissionSessionKey,
 };
-use crate::tools::ToolFilesystemAccess;
-use crate::tools::{Tool, ToolActionFloor, ToolSafetyKind, ToolSafetyPlan};
-use crate::types::{AgentToolCall, ModelMessage, StreamEventType, TextStreamDelta};
-use std::sync::Arc;
+use crate::security::redaction::SecretRedactor;
+use crate::tools::{ChangePreview, ToolFilesystemAccess};
+use crate::tools::{Tool, ToolActionFloor, ToolEffects, ToolSafetyKind, ToolSafetyPlan};
+use crate::types::{
+    AgentToolCall, AgentToolResult, FinishReason, ImageContent, ModelMessage, StreamEventType,
+    TextStreamDelta, Usage,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 pub(super) struct StreamDeltaState<'a> {
     pub(super) iteration_text: &'a mut String,
     pub(super) tool_calls: &'a mut Vec<AgentToolCall>,
+    pub(super) images: &'a mut Vec<ImageContent>,
     pub(super) stream_done: &'a mut bool,
     pub(super) message_open: &'a mut bool,
-}
-
-pub(super) fn emit_failed_result(
-    emitter: &RunEventEmitter,
-    reason: impl Into<String>,
-    messages: &[ModelMessage],
-) -> RunResult {
-    let reason = reason.into();
-    emitter.emit(
-        RunEventStream::Lifecycle,
-        RunEventPayload::Lifecycle {
-            state: RunLifecycle::Failed {
-                error: reason.clone(),
-            },
-        },
-    );
-    RunResult::failed_with_messages(reason, messages.to_vec())
+    pub(super) argument_progress: &'a mut ArgumentProgress,
 }
 
 pub(super) fn process_stream_delta(
@@ -49,13 +45,16 @@ pub(super) fn process_stream_delta(
     agent_emitter: &AgentEventEmitter,
     mut delta: TextStreamDelta,
     tools: &[Arc<dyn Tool>],
+    repair_arguments: bool,
     state: StreamDeltaState<'_>,
 ) -> Option<String> {
     let StreamDeltaState {
         iteration_text,
         tool_calls,
+        images,
         stream_done,
         message_open,
+        argument_progress,
     } = state;
 
     match delta.event_type {
@@ -72,7 +71,11 @@ pub(super) fn process_stream_delta(
                 }
                 let mut tc = tc;
                 super::tooling::normalize_tool_call_alias(tools, &mut tc);
+                if repair_arguments && super::argument_repair::repair_call_arguments(&mut tc) {
+                    emitter.mark_arguments_repaired(&tc.id);
+                }
                 delta.tool_call = Some(tc.clone());
+                argument_progress.finalize(&tc.id);
 
                 emit_message_start_if_needed(
                     agent_emitter,
@@ -91,9 +94,14 @@ pub(super) fn process_stream_delta(
                     );
                 } else {
                     tool_calls.push(tc.clone());
+                    let repaired = emitter.arguments_repaired(&tc.id);
                     emitter.emit(
                         RunEventStream::Tool,
-                        RunEventPayload::ToolCallStarted { call: tc },
+                        RunEventPayload::ToolCallStarted {
+                            call: tc,
+                            repaired,
+                            dry_run: emitter.dry_run(),
+                        },
                     );
                 }
                 agent_emitter.emit(AgentEvent::MessageUpdate {
@@ -109,6 +117,33 @@ pub(super) fn process_stream_delta(
                 );
             }
         }
+        StreamEventType::ToolCallArgumentsDelta => {
+            let mut tc = delta.tool_call?;
+            let fragment = tc.arguments.as_str()?.to_string();
+            if tc.id.trim().is_empty() {
+                return None;
+            }
+            super::tooling::normalize_tool_call_alias(tools, &mut tc);
+            if let Some(update) =
+                argument_progress.record(&tc.id, &tc.name, &fragment, tokio::time::Instant::now())
+            {
+                emitter.emit(
+                    RunEventStream::Tool,
+                    RunEventPayload::ToolCallArgumentsDelta {
+                        call_id: update.call_id.clone(),
+                        name: update.name.clone(),
+                        partial_args_len: update.partial_args_len,
+                        preview: update.preview.clone(),
+                    },
+                );
+                agent_emitter.emit(AgentEvent::ToolCallArgumentsDelta {
+                    call_id: update.call_id,
+                    name: update.name,
+                    partial_args_len: update.partial_args_len,
+                    preview: update.preview,
+                });
+            }
+        }
         StreamEventType::Reasoning => {
             if let Some(reasoning) = delta.reasoning.as_ref() {
                 if !reasoning.is_empty() {
@@ -135,9 +170,34 @@ pub(super) fn process_stream_delta(
                 }
             }
         }
-        StreamEventType::TextDelta => {
-            if !delta.text.is_empty() {
-                iteration_text.push_str(&delta.text);
+        // A refusal is the assistant's answer for this turn, so it streams
+        // into the message text like any other reply.
+        StreamEventType::TextDelta | StreamEventType::RefusalDelta if !delta.text.is_empty() => {
+            iteration_text.push_str(&delta.text);
+            emit_message_start_if_needed(agent_emitter, message_open, iteration_text, tool_calls);
+            emitter.emit(
+                RunEventStream::Assistant,
+                RunEventPayload::AssistantDelta {
+                    text: delta.text.clone(),
+                },
+            );
+            agent_emitter.emit(AgentEvent::MessageUpdate {
+                message: assistant_message_snapshot(iteration_text, tool_calls),
+                assistant_message_event: delta,
+            });
+        }
+        StreamEventType::Error => {
+            let message = if delta.text.trim().is_empty() {
+                "stream error".to_string()
+            } else {
+                delta.text
+            };
+            emit_message_end_if_open(agent_emitter, message_open, iteration_text, tool_calls);
+            return Some(message);
+        }
+        StreamEventType::Image => {
+            if let Some(image) = delta.image.clone() {
+                images.push(image.clone());
                 emit_message_start_if_needed(
                     agent_emitter,
                     message_open,
@@ -146,9 +206,7 @@ pub(super) fn process_stream_delta(
                 );
                 emitter.emit(
                     RunEventStream::Assistant,
-                    RunEventPayload::AssistantDelta {
-                        text: delta.text.clone(),
-                    },
+                    RunEventPayload::AssistantImage { image },
                 );
                 agent_emitter.emit(AgentEvent::MessageUpdate {
                     message: assistant_message_snapshot(iteration_text, tool_calls),
@@ -156,18 +214,18 @@ pub(super) fn process_stream_delta(
                 });
             }
         }
-        StreamEventType::Error => {
-            let message = if delta.text.trim().is_empty() {
-                "stream error".to_string()
-            } else {
-                delta.text
-            };
-            emit_message_end_if_open(agent_emitter, message_open, iteration_text, tool_calls);
-            return Some(message);
-        }
         StreamEventType::Done => {
+            if let Some(FinishReason::Other(raw)) = &delta.finish_reason {
+                tracing::debug!(finish_reason = %raw, "provider finish reason has no named variant");
+            }
             *stream_done = true;
-            emit_message_end_if_open(agent_emitter, message_open, iteration_text, tool_calls);
+            emit_message_end_with_images_if_open(
+                agent_emitter,
+                message_open,
+                iteration_text,
+                images,
+                tool_calls,
+            );
         }
         _ => {}
     }
@@ -176,28 +234,74 @@ pub(super) fn process_stream_delta(
 
 pub(super) struct RunEventEmitter {
     run_id: RunId,
-    seq: std::sync::atomic::AtomicU64,
+    model: Option<String>,
+    tags: EventTags,
+    seq: EventSequence,
     sink: Option<RunEventSink>,
+    /// Calls whose arguments were repaired, so their results carry the note.
+    repaired_calls: Mutex<HashSet<String>>,
+    /// Flags tool events of a dry run.
+    dry_run: bool,
 }
 
 impl RunEventEmitter {
     pub(super) fn new(run_id: RunId, sink: Option<RunEventSink>) -> Self {
         Self {
             run_id,
-            seq: std::sync::atomic::AtomicU64::new(1),
+            model: None,
+            tags: EventTags::default(),
+            seq: EventSequence::new(),
             sink,
+            repaired_calls: Mutex::default(),
+            dry_run: false,
+        }
+    }
+
+    /// Flag tool call and result events as a dry run's.
+    pub(super) fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub(super) fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Number events from `seq`, shared with the run's agent emitter.
+    pub(super) fn with_sequence(mut self, seq: EventSequence) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Stamp every emitted event with the run's model and tags.
+    pub(super) fn with_identity(mut self, model: Option<String>, tags: EventTags) -> Self {
+        self.model = model;
+        self.tags = tags;
+        self
+    }
+
+    pub(super) fn mark_arguments_repaired(&self, call_id: &str) {
+        if let Ok(mut repaired) = self.repaired_calls.lock() {
+            repaired.insert(call_id.to_string());
         }
     }
 
+    pub(super) fn arguments_repaired(&self, call_id: &str) -> bool {
+        self.repaired_calls
+            .lock()
+            .is_ok_and(|repaired| repaired.contains(call_id))
+    }
+
     pub(super) fn emit(&self, stream: RunEventStream, payload: RunEventPayload) {
         let Some(sink) = &self.sink else {
             return;
         };
-        let seq = self.seq.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
         (sink)(RunEvent {
             run_id: self.run_id,
-            seq,
+            seq: self.seq.next(),
             timestamp: chrono::Utc::now(),
+            model: self.model.clone(),
+            tags: self.tags.clone(),
             stream,
             payload,
         });
@@ -206,17 +310,105 @@ impl RunEventEmitter {
 
 #[derive(Clone)]
 pub(super) struct AgentEventEmitter {
+    run_id: RunId,
+    model: Option<String>,
+    tags: EventTags,
+    seq: EventSequence,
     sink: Option<AgentEventSink>,
+    stored_messages: StoredMessageFilter,
+    turns: TurnTracker,
 }
 
 impl AgentEventEmitter {
-    pub(super) fn new(sink: Option<AgentEventSink>) -> Self {
-        Self { sink }
+    pub(super) fn new(run_id: RunId, sink: Option<AgentEventSink>) -> Self {
+        Self {
+            run_id,
+            model: None,
+            tags: EventTags::default(),
+            seq: EventSequence::new(),
+            sink,
+            stored_messages: StoredMessageFilter::default(),
+            turns: TurnTracker::default(),
+        }
+    }
+
+    /// Stamp every emitted envelope with the run's model and tags.
+    pub(super) fn with_identity(mut self, model: Option<String>, tags: EventTags) -> Self {
+        self.model = model;
+        self.tags = tags;
+        self
+    }
+
+    /// Number envelopes from `seq`, shared with the run's event emitter.
+    pub(super) fn with_sequence(mut self, seq: EventSequence) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Filter applied to `MessageEnd` and to messages appended to history.
+    /// Start and update events keep the raw text.
+    pub(super) fn with_stored_messages(mut self, stored_messages: StoredMessageFilter) -> Self {
+        self.stored_messages = stored_messages;
+        self
+    }
+
+    pub(super) fn stored_messages(&self) -> &StoredMessageFilter {
+        &self.stored_messages
+    }
+
+    /// Emit `TurnStart` unless a turn is already open.
+    pub(super) fn begin_turn(&self, run_usage: &Usage) {
+        if let Some(event) = self.turns.begin(self.run_id, run_usage) {
+            self.emit(event);
+        }
+    }
+
+    /// Index of the open turn, or of the next one when none is open.
+    pub(super) fn turn_index(&self) -> usize {
+        self.turns.current_index()
+    }
+
+    /// Count a model response toward the open turn's summary.
+    pub(super) fn record_turn_response(&self, text: &str, tool_calls: usize, run_usage: &Usage) {
+        self.turns.record_response(text, tool_calls, run_usage);
+    }
+
+    /// Keep the tool batch's progress for a `TurnEnd` emitted on abort.
+    pub(super) fn record_turn_progress(
+        &self,
+        assistant_message: Option<&ModelMessage>,
+        tool_results: &[AgentToolResult],
+    ) {
+        self.turns.record_progress(assistant_message, tool_results);
+    }
+
+    pub(super) fn end_turn(
+        &self,
+        assistant_message: Option<ModelMessage>,
+        tool_results: Vec<AgentToolResult>,
+    ) {
+        if let Some(event) = self.turns.end(self.run_id, assistant_message, tool_results) {
+            self.emit(event);
+        }
+    }
+
+    /// Emit an aborted `TurnEnd` for a turn the run is leaving open.
+    pub(super) fn abort_turn(&self, run_usage: &Usage) {
+        if let Some(event) = self.turns.abort(self.run_id, run_usage) {
+            self.emit(event);
+        }
     }
 
     pub(super) fn emit(&self, event: AgentEvent) {
         if let Some(sink) = &self.sink {
-            (sink)(event);
+            (sink)(AgentEventEnvelope {
+                run_id: self.run_id,
+                seq: self.seq.next(),
+                timestamp: chrono::Utc::now(),
+                model: self.model.clone(),
+                tags: self.tags.clone(),
+                event,
+            });
         }
     }
 }
@@ -234,6 +426,10 @@ pub(super) async fn resolve_approval(
     safety_plan: &ToolSafetyPlan,
 ) -> ApprovalDecision {
     let tool_kind = safety_plan.approval.kind;
+    let effects = ToolEffects::for_call(
+        tool.map_or(ToolEffects::Mutating, |tool| tool.effects()),
+        safety_plan,
+    );
     let allow_session = safety_plan.approval.allow_session;
     let permission_kind = permission_kind_for_tool_metadata(tool_kind, tool.is_some());
     let session_key =
@@ -266,6 +462,7 @@ pub(super) async fn resolve_approval(
         tool_call_id: call.id.clone(),
         tool_name: call.name.clone(),
         tool_kind: Some(tool_kind),
+        effects: Some(effects),
         preview: serde_json::json!({
             "tool_name": call.name.clone(),
             "tool_call_id": call.id.clone(),
@@ -317,6 +514,7 @@ pub(super) async fn resolve_approval(
             "command": sanitized_command_for_payload(context.command.as_ref()),
             "filesystem": context.filesystem,
             "evaluation": sanitized_evaluation_for_payload(&evaluation),
+            "changes": sanitized_changes_for_payload(&safety_plan.change_previews),
         }),
         suggested_policy_change: None,
     };
@@ -436,6 +634,14 @@ fn sanitized_command_for_payload(
     })
 }
 
+/// Change previews with secrets in diff text redacted.
+fn sanitized_changes_for_payload(previews: &[ChangePreview]) -> serde_json::Value {
+    let previews = serde_json::to_value(previews).unwrap_or_default();
+    SecretRedactor::new_default()
+        .redact_json(&previews)
+        .redacted
+}
+
 fn sanitized_grant_for_payload(grant: &ApprovalGrant) -> Option<ApprovalGrant> {
     match grant {
         ApprovalGrant::Exact { key } => Some(ApprovalGrant::Exact {
@@ -684,7 +890,8 @@ mod tests {
         let coordinator = Arc::new(HumanInteractionCoordinator::new());
         let sink_events = events.clone();
         let sink_coordinator = coordinator.clone();
-        let sink: AgentEventSink = Arc::new(move |event| {
+        let sink: AgentEventSink = Arc::new(move |envelope| {
+            let event = envelope.event;
             if let AgentEvent::HumanInteractionRequested { request } = &event {
                 let coordinator = sink_coordinator.clone();
                 let request_id = request.request_id;
@@ -697,7 +904,7 @@ mod tests {
             sink_events.lock().expect("agent event lock").push(event);
         });
         (
-            AgentEventEmitter::new(Some(sink)),
+            AgentEventEmitter::new(Uuid::new_v4(), Some(sink)),
             events,
             coordinator.clone(),
         )
@@ -744,7 +951,7 @@ mod tests {
     #[tokio::test]
     async fn ask_policy_requires_approval_for_shell_and_write_file() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let shell_plan = ToolSafetyPlan::approval_required(ToolSafetyKind::CommandExecution);
         let write_plan = ToolSafetyPlan::approval_required(ToolSafetyKind::FileChange);
         let shell = tool("shell", shell_plan.clone());
@@ -816,10 +1023,83 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn policy_gating_follows_tool_effects() {
+        let read_only_plan = ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read);
+        let mutating_plan = ToolSafetyPlan::approval_required(ToolSafetyKind::FileChange);
+        let network_plan = ToolSafetyPlan::approval_required(ToolSafetyKind::Other);
+        let mut destructive_plan = ToolSafetyPlan::approval_required(ToolSafetyKind::Other);
+        destructive_plan.destructive = true;
+        let cases = [
+            (tool("read", read_only_plan.clone()), read_only_plan),
+            (tool("edit", mutating_plan.clone()), mutating_plan),
+            (
+                tool("fetch", network_plan.clone()).with_effects(ToolEffects::Network),
+                network_plan,
+            ),
+            (tool("purge", destructive_plan.clone()), destructive_plan),
+        ];
+        // (policy, expected decision per case, expected handler prompts)
+        let expectations = [
+            (ApprovalPolicy::always(), [ApprovalDecision::Accept; 4], 0),
+            (ApprovalPolicy::ask(), [ApprovalDecision::Accept; 4], 3),
+            (
+                ApprovalPolicy::never(),
+                [
+                    ApprovalDecision::Accept,
+                    ApprovalDecision::Decline,
+                    ApprovalDecision::Decline,
+                    ApprovalDecision::Decline,
+                ],
+                0,
+            ),
+        ];
+
+        for (policy, expected, expected_prompts) in expectations {
+            let (emitter, _events) = emitter_with_events();
+            let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
+            let approvals = session_approvals();
+            let prompts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let handler_prompts = prompts.clone();
+            let handler: ApprovalHandler = Arc::new(move |_request| {
+                handler_prompts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Box::pin(async { ApprovalDecision::Accept })
+            });
+
+            for ((tool, plan), expected) in cases.iter().zip(expected) {
+                let decision = resolve_approval(
+                    &emitter,
+                    &agent_emitter,
+                    &policy,
+                    Some(&handler),
+                    None,
+                    &approvals,
+                    &tool_call(tool.name()),
+                    Some(tool),
+                    plan,
+                )
+                .await;
+                assert_eq!(
+                    decision,
+                    expected,
+                    "{:?} for {}",
+                    policy.default_action,
+                    tool.name()
+                );
+            }
+            assert_eq!(
+                prompts.load(std::sync::atomic::Ordering::SeqCst),
+                expected_prompts,
+                "{:?}",
+                policy.default_action
+            );
+        }
+    }
+
     #[tokio::test]
     async fn always_policy_prompts_for_destructive_shell_floor() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let shell_plan = ToolSafetyPlan::from_command_insight(
             crate::security::command::classify_shell_command("rm -rf target"),
         );
@@ -870,7 +1150,7 @@ mod tests {
     #[tokio::test]
     async fn deny_action_floor_declines_without_prompt() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let mut plan = ToolSafetyPlan::approval_required(ToolSafetyKind::CommandExecution);
         plan.approval.action_floor = Some(ToolActionFloor::Deny);
         plan.approval.reason = Some("blocked by tool safety plan".to_string());
@@ -899,13 +1179,13 @@ mod tests {
         let (emitter, events) = emitter_with_events();
         let agent_events = Arc::new(Mutex::new(Vec::<AgentEvent>::new()));
         let sink_agent_events = agent_events.clone();
-        let agent_sink: AgentEventSink = Arc::new(move |event| {
+        let agent_sink: AgentEventSink = Arc::new(move |envelope| {
             sink_agent_events
                 .lock()
                 .expect("agent event lock")
-                .push(event);
+                .push(envelope.event);
         });
-        let agent_emitter = AgentEventEmitter::new(Some(agent_sink));
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), Some(agent_sink));
         let shell_plan = ToolSafetyPlan::from_command_insight(
             crate::security::command::classify_shell_command("rm -rf sk-secret-leak-123"),
         );
@@ -971,10 +1251,54 @@ mod tests {
         assert!(agent_request.payload.get("arguments").is_none());
     }
 
+    #[tokio::test]
+    async fn approval_payload_carries_redacted_change_previews() {
+        let (emitter, events) = emitter_with_events();
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
+        let mut plan = ToolSafetyPlan::approval_required(ToolSafetyKind::FileChange);
+        plan.change_previews = vec![crate::tools::ChangePreview::between(
+            "config.env",
+            Some(b"MODE=dev\n"),
+            Some(b"MODE=prod\nAPI_KEY=sk-live-abcdef\n"),
+        )];
+        let write_file = tool("write_file", plan.clone());
+        let call = AgentToolCall {
+            id: "write-call".to_string(),
+            name: "write_file".to_string(),
+            arguments: serde_json::json!({ "path": "config.env" }),
+            called_as: None,
+            recipient: None,
+        };
+
+        resolve_approval(
+            &emitter,
+            &agent_emitter,
+            &ApprovalPolicy::ask(),
+            None,
+            None,
+            &session_approvals(),
+            &call,
+            Some(&write_file),
+            &plan,
+        )
+        .await;
+
+        let events = events.lock().expect("event lock");
+        let RunEventPayload::ApprovalRequired { request } = &events[0].payload else {
+            panic!("expected approval request");
+        };
+        let change = &request.payload["changes"][0];
+        assert_eq!(change["kind"], "text");
+        assert_eq!(change["path"], "config.env");
+        let diff = change["diff"].as_str().expect("diff text");
+        assert!(diff.contains("-MODE=dev\n+MODE=prod\n"), "{diff}");
+        assert!(!diff.contains("sk-live-abcdef"), "{diff}");
+    }
+
     #[tokio::test]
     async fn grep_without_path_uses_current_directory_for_filesystem_matchers() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let grep_plan = ToolSafetyPlan::file_search(".");
         let grep = tool("grep", grep_plan.clone());
         let approvals = session_approvals();
@@ -1020,7 +1344,7 @@ mod tests {
     #[tokio::test]
     async fn filesystem_boundary_matchers_use_lexically_normalized_path_facts() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let read_plan = ToolSafetyPlan::file_read("../secret");
         let read_file = tool("read_file", read_plan.clone());
         let approvals = session_approvals();
@@ -1063,7 +1387,7 @@ mod tests {
     #[tokio::test]
     async fn ask_policy_auto_accepts_explicit_safe_tools() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let read_plan = ToolSafetyPlan::file_read("README.md");
         let read_file = tool("read_file", read_plan.clone());
         let approvals = session_approvals();
@@ -1088,7 +1412,7 @@ mod tests {
     #[tokio::test]
     async fn allow_session_false_auto_accepts_and_downgrades_session_accept() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let approvals = session_approvals();
         let host_plan = ToolSafetyPlan::host_input();
         let host_tool = tool("ask_user", host_plan.clone());
@@ -1155,7 +1479,7 @@ mod tests {
     #[tokio::test]
     async fn ask_policy_requires_approval_for_custom_default_and_unknown_tools() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let approvals = session_approvals();
 
         let custom = AgentTool::new(
@@ -1349,7 +1673,7 @@ mod tests {
     #[tokio::test]
     async fn legacy_session_grant_does_not_override_explicit_ask_rule() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let approvals = session_approvals();
         let call = AgentToolCall {
             id: "shell-call".to_string(),
@@ -1402,7 +1726,7 @@ mod tests {
     #[tokio::test]
     async fn legacy_session_grant_does_not_override_explicit_deny_rule() {
         let (emitter, events) = emitter_with_events();
-        let agent_emitter = AgentEventEmitter::new(None);
+        let agent_emitter = AgentEventEmitter::new(Uuid::new_v4(), None);
         let approvals = session_approvals();
         let call = AgentToolCall {
             id: "shell-call".to_string(),
@@ -0,0 +1,189 @@
+//! Enforces [`RunRequest::deadline`] over the life of one run.
+
+use std::collections::HashSet;
+
+use tokio::time::Instant;
+
+use crate::error::RociError;
+use crate::types::{ContentPart, ModelMessage, Role};
+
+use super::history::History;
+use super::{DeadlineMode, RunRequest};
+
+/// Output cap of the wrap-up call.
+const WRAP_UP_MAX_TOKENS: u32 = 512;
+
+const WRAP_UP_PROMPT: &str = "Time is up. Stop working and reply now with your final answer, \
+summarizing what you found and what is left undone. Do not call tools.";
+
+pub(super) fn validate_deadline(request: &RunRequest) -> Result<(), RociError> {
+    if let DeadlineMode::WrapUp { reserve } = request.deadline_mode {
+        if reserve.is_zero() {
+            return Err(RociError::Configuration(
+                "deadline_mode wrap_up reserve must be greater than zero".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The run's deadline and, in [`DeadlineMode::WrapUp`], whether the wrap-up
+/// call has started.
+pub(super) struct RunDeadline {
+    deadline: Option<Instant>,
+    mode: DeadlineMode,
+    wrapping_up: bool,
+}
+
+impl RunDeadline {
+    pub(super) fn new(request: &RunRequest) -> Self {
+        Self {
+            deadline: request.deadline.map(Instant::from_std),
+            mode: request.deadline_mode,
+            wrapping_up: false,
+        }
+    }
+
+    /// When the run's work is interrupted: the deadline, or the wrap-up
+    /// reserve before it until the wrap-up call starts.
+    fn interrupt_at(&self) -> Option<Instant> {
+        let deadline = self.deadline?;
+        match self.mode {
+            DeadlineMode::WrapUp { reserve } if !self.wrapping_up => {
+                Some(deadline.checked_sub(reserve).unwrap_or(deadline))
+            }
+            _ => Some(deadline),
+        }
+    }
+
+    /// Whether the run's work should be interrupted now.
+    pub(super) fn due(&self) -> bool {
+        self.interrupt_at()
+            .is_some_and(|interrupt_at| Instant::now() >= interrupt_at)
+    }
+
+    /// Resolves when the run's work should be interrupted; never resolves
+    /// without a deadline.
+    pub(super) async fn interrupted(&self) {
+        match self.interrupt_at() {
+            Some(interrupt_at) => tokio::time::sleep_until(interrupt_at).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Start the wrap-up call when the mode asks for one and it has not
+    /// started yet. Returns whether it did.
+    pub(super) fn begin_wrap_up(&mut self) -> bool {
+        if self.wrapping_up || !matches!(self.mode, DeadlineMode::WrapUp { .. }) {
+            return false;
+        }
+        self.wrapping_up = true;
+        true
+    }
+
+    pub(super) fn wrapping_up(&self) -> bool {
+        self.wrapping_up
+    }
+}
+
+/// `request` for the wrap-up call, its output capped at
+/// [`WRAP_UP_MAX_TOKENS`].
+pub(super) fn wrap_up_request(request: &RunRequest) -> RunRequest {
+    let mut request = request.clone();
+    request.settings.max_tokens = Some(
+        request
+            .settings
+            .max_tokens
+            .map_or(WRAP_UP_MAX_TOKENS, |max| max.min(WRAP_UP_MAX_TOKENS)),
+    );
+    request
+}
+
+/// The message asking the model to conclude.
+pub(super) fn wrap_up_prompt() -> ModelMessage {
+    ModelMessage::user(WRAP_UP_PROMPT)
+}
+
+/// Answer the tool calls of the last assistant message that have no result
+/// yet as canceled, so the messages stay a valid conversation.
+pub(super) fn close_open_tool_calls(messages: &mut History) {
+    let Some(position) = messages
+        .iter()
+        .rposition(|message| message.role == Role::Assistant)
+    else {
+        return;
+    };
+    let answered: HashSet<String> = messages[position + 1..]
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|part| match part {
+            ContentPart::ToolResult(result) => Some(result.tool_call_id.clone()),
+            _ => None,
+        })
+        .collect();
+    let open: Vec<String> = messages[position]
+        .tool_calls()
+        .into_iter()
+        .filter(|call| !answered.contains(&call.id))
+        .map(|call| call.id.clone())
+        .collect();
+    for id in open {
+        messages.push(ModelMessage::tool_result(
+            id,
+            serde_json::json!({ "error": "canceled" }),
+            true,
+        ));
+    }
+}
+
+/// Whether the run's last message is an assistant answer: text and no tool
+/// calls.
+pub(super) fn ends_with_final_text(messages: &[ModelMessage]) -> bool {
+    messages.last().is_some_and(|message| {
+        message.role == Role::Assistant
+            && !message.text().trim().is_empty()
+            && message.tool_calls().is_empty()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AgentToolCall;
+
+    fn call(id: &str) -> ContentPart {
+        ContentPart::ToolCall(AgentToolCall {
+            id: id.to_string(),
+            name: "tool".to_string(),
+            arguments: serde_json::json!({}),
+            called_as: None,
+            recipient: None,
+        })
+    }
+
+    #[test]
+    fn open_tool_calls_are_answered_as_canceled() {
+        let mut assistant = ModelMessage::assistant("");
+        assistant.content = vec![call("a"), call("b")];
+        let mut messages = History::new(vec![
+            ModelMessage::user("go"),
+            assistant,
+            ModelMessage::tool_result("a", serde_json::json!("done"), false),
+        ]);
+
+        close_open_tool_calls(&mut messages);
+
+        assert_eq!(messages.len(), 4);
+        let ContentPart::ToolResult(result) = &messages[3].content[0] else {
+            panic!("expected a tool result");
+        };
+        assert_eq!(result.tool_call_id, "b");
+        assert!(result.is_error);
+        assert!(!ends_with_final_text(&messages));
+
+        messages.push(ModelMessage::assistant("all done"));
+        close_open_tool_calls(&mut messages);
+        assert_eq!(messages.len(), 5);
+        assert!(ends_with_final_text(&messages));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::models::LanguageModel;
+use crate::resource::{ApprovalPreset, ResourceSettings};
+use crate::types::{ModelMessage, Role};
+
+use super::{RunPlugin, RunRequest};
+
+/// Run defaults from the workspace `defaults` settings, applied to a
+/// [`RunRequest`].
+///
+/// [`apply`](Self::apply) overwrites every request field a default is set
+/// for, so apply defaults first and caller overrides after. Registered as a
+/// [`RunPlugin`], the defaults are applied at the start of every run.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunRequestDefaults {
+    /// Replaces the primary model candidate; fallback candidates are kept.
+    pub model: Option<LanguageModel>,
+    pub temperature: Option<f64>,
+    pub approval: Option<ApprovalPreset>,
+    pub max_iterations: Option<usize>,
+    /// Tool allowlist. An empty list hides every tool.
+    pub tools: Option<Vec<String>>,
+    /// Replaces a leading system message, or is inserted as one.
+    pub system_prompt: Option<String>,
+}
+
+impl RunRequestDefaults {
+    pub fn from_settings(settings: &ResourceSettings) -> Self {
+        let defaults = &settings.defaults;
+        Self {
+            model: defaults.model.clone(),
+            temperature: defaults.temperature,
+            approval: defaults.approval,
+            max_iterations: defaults.max_iterations,
+            tools: defaults.tools.clone(),
+            system_prompt: defaults.system_prompt.clone(),
+        }
+    }
+
+    pub fn apply(&self, request: &mut RunRequest) {
+        if let Some(model) = &self.model {
+            request.candidates[0] = model.clone();
+            request.active_candidate_index = 0;
+        }
+        if let Some(temperature) = self.temperature {
+            request.settings.temperature = Some(temperature);
+        }
+        if let Some(approval) = self.approval {
+            request.approval_policy = approval.into();
+        }
+        if let Some(max_iterations) = self.max_iterations {
+            request.max_iterations = Some(max_iterations);
+        }
+        if let Some(tools) = &self.tools {
+            if tools.is_empty() {
+                request.tool_visibility_policy.set_no_tools(true);
+            } else {
+                request
+                    .tool_visibility_policy
+                    .extend_allow(tools.iter().cloned());
+            }
+        }
+        if let Some(system_prompt) = &self.system_prompt {
+            let message = ModelMessage::system(system_prompt.clone());
+            match request.messages.first_mut() {
+                Some(first) if first.role == Role::System => *first = message,
+                _ => request.messages.insert(0, message),
+            }
+        }
+    }
+}
+
+impl RunPlugin for RunRequestDefaults {
+    fn name(&self) -> &str {
+        "run-defaults"
+    }
+
+    fn modify_request(&self, request: &mut RunRequest) {
+        self.apply(request);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent_loop::{ApprovalAction, ApprovalPolicy};
+    use crate::resource::RunDefaultsSettings;
+
+    use super::super::limits::RunnerLimits;
+
+    fn request() -> RunRequest {
+        RunRequest::new(
+            "openai:gpt-4o".parse().unwrap(),
+            vec![ModelMessage::system("caller"), ModelMessage::user("hi")],
+        )
+    }
+
+    #[test]
+    fn unset_defaults_leave_the_request_alone() {
+        let mut request = request();
+
+        RunRequestDefaults::default().apply(&mut request);
+
+        assert_eq!(request.active_model().to_string(), "openai:gpt-4o");
+        assert!(request.settings.temperature.is_none());
+        assert_eq!(request.approval_policy, ApprovalPolicy::ask());
+        assert!(request.metadata.is_empty());
+        assert!(request.tool_visibility_policy.allow().is_empty());
+        assert_eq!(request.messages.len(), 2);
+    }
+
+    #[test]
+    fn settings_defaults_apply_to_the_request() {
+        let settings = ResourceSettings {
+            defaults: RunDefaultsSettings {
+                model: Some("anthropic:claude-sonnet-4-5".parse().unwrap()),
+                temperature: Some(0.2),
+                approval: Some(ApprovalPreset::Never),
+                max_iterations: Some(7),
+                tools: Some(vec!["read_file".to_string()]),
+                system_prompt_file: None,
+                system_prompt: Some("workspace".to_string()),
+            },
+            ..Default::default()
+        };
+        let mut request = request();
+
+        RunRequestDefaults::from_settings(&settings).apply(&mut request);
+
+        assert_eq!(
+            request.active_model().to_string(),
+            "anthropic:claude-sonnet-4-5"
+        );
+        assert_eq!(request.settings.temperature, Some(0.2));
+        assert_eq!(request.approval_policy.default_action, ApprovalAction::Deny);
+        assert_eq!(RunnerLimits::from_request(&request).max_iterations, 7);
+        assert!(request.tool_visibility_policy.allows("read_file"));
+        assert!(!request.tool_visibility_policy.allows("shell"));
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].text(), "workspace");
+    }
+
+    #[test]
+    fn empty_tool_allowlist_hides_every_tool() {
+        let mut request = request();
+
+        RunRequestDefaults {
+            tools: Some(Vec::new()),
+            ..Default::default()
+        }
+        .apply(&mut request);
+
+        assert!(request.tool_visibility_policy.is_no_tools());
+    }
+}
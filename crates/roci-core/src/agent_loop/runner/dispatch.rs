@@ -0,0 +1,493 @@
+//! Off-loop delivery of run and agent events to caller sinks.
+//!
+//! Sinks are plain callbacks and may block. [`EventDispatcher`] swaps the
+//! request's sinks for enqueueing ones and calls the originals from a
+//! dedicated blocking task, so the loop never waits on a sink. The queue is
+//! shared by both sinks, keeping their relative order.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+use super::{
+    AgentEvent, AgentEventEnvelope, AgentEventSink, RunEvent, RunEventPayload, RunEventSink,
+    RunRequest,
+};
+use crate::agent_loop::EmitterStats;
+use crate::types::StreamEventType;
+
+/// Queued events before [`RunRequest::event_overflow_policy`] applies.
+pub const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// What happens to an event emitted while the sink queue is full.
+///
+/// Either way, lifecycle events (run start and end) are always queued, and
+/// progress events such as deltas and heartbeats are dropped before anything
+/// else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventOverflowPolicy {
+    /// Drop the oldest queued event.
+    DropOldest,
+    /// Append a text or reasoning delta to the newest queued delta of the
+    /// same kind, so the delivered text stays complete. Other events drop
+    /// the oldest heartbeat, progress update, or tool-call delta, and only
+    /// then the oldest text or reasoning delta.
+    #[default]
+    CoalesceDeltas,
+}
+
+/// A queued event. Both payloads are boxed so a queue slot is a pointer
+/// rather than the size of the largest event.
+enum Queued {
+    Run(Box<RunEvent>),
+    Agent(Box<AgentEventEnvelope>),
+}
+
+impl Queued {
+    fn is_lifecycle(&self) -> bool {
+        match self {
+            Self::Run(event) => matches!(event.payload, RunEventPayload::Lifecycle { .. }),
+            Self::Agent(envelope) => matches!(
+                envelope.event,
+                AgentEvent::AgentStart { .. } | AgentEvent::AgentEnd { .. }
+            ),
+        }
+    }
+
+    fn is_progress(&self) -> bool {
+        match self {
+            Self::Run(event) => matches!(
+                event.payload,
+                RunEventPayload::AssistantDelta { .. }
+                    | RunEventPayload::ReasoningDelta { .. }
+                    | RunEventPayload::ToolCallDelta { .. }
+                    | RunEventPayload::ToolCallArgumentsDelta { .. }
+                    | RunEventPayload::Heartbeat { .. }
+                    | RunEventPayload::Progress { .. }
+            ),
+            Self::Agent(envelope) => matches!(
+                envelope.event,
+                AgentEvent::MessageUpdate { .. }
+                    | AgentEvent::ToolCallArgumentsDelta { .. }
+                    | AgentEvent::ToolExecutionUpdate { .. }
+            ),
+        }
+    }
+
+    /// Text and reasoning deltas, which [`EventOverflowPolicy::CoalesceDeltas`]
+    /// keeps over other progress events.
+    fn is_text_delta(&self) -> bool {
+        match self {
+            Self::Run(event) => matches!(
+                event.payload,
+                RunEventPayload::AssistantDelta { .. } | RunEventPayload::ReasoningDelta { .. }
+            ),
+            Self::Agent(envelope) => matches!(
+                &envelope.event,
+                AgentEvent::MessageUpdate { assistant_message_event, .. }
+                    if matches!(
+                        assistant_message_event.event_type,
+                        StreamEventType::TextDelta | StreamEventType::Reasoning
+                    )
+            ),
+        }
+    }
+
+    /// Fold `next` into `self` when both are deltas of one kind.
+    fn absorb(&mut self, next: &Queued) -> bool {
+        match (self, next) {
+            (Self::Run(queued), Self::Run(next)) if queued.stream == next.stream => {
+                match (&mut queued.payload, &next.payload) {
+                    (
+                        RunEventPayload::AssistantDelta { text },
+                        RunEventPayload::AssistantDelta { text: next_text },
+                    )
+                    | (
+                        RunEventPayload::ReasoningDelta { text },
+                        RunEventPayload::ReasoningDelta { text: next_text },
+                    ) => {
+                        text.push_str(next_text);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            (Self::Agent(queued), Self::Agent(next)) => match (&mut queued.event, &next.event) {
+                (
+                    AgentEvent::MessageUpdate {
+                        message,
+                        assistant_message_event: delta,
+                    },
+                    AgentEvent::MessageUpdate {
+                        message: next_message,
+                        assistant_message_event: next_delta,
+                    },
+                ) if delta.event_type == next_delta.event_type
+                    && matches!(
+                        delta.event_type,
+                        StreamEventType::TextDelta | StreamEventType::Reasoning
+                    ) =>
+                {
+                    *message = next_message.clone();
+                    delta.text.push_str(&next_delta.text);
+                    if let Some(next_reasoning) = &next_delta.reasoning {
+                        delta
+                            .reasoning
+                            .get_or_insert_with(String::new)
+                            .push_str(next_reasoning);
+                    }
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Fold `item` into the newest queued delta of its kind. The search only
+/// passes over progress events, so text never moves across a message or tool
+/// boundary.
+fn coalesce(queue: &mut VecDeque<Queued>, item: &Queued) -> bool {
+    for queued in queue.iter_mut().rev() {
+        if queued.absorb(item) {
+            return true;
+        }
+        if !queued.is_progress() {
+            return false;
+        }
+    }
+    false
+}
+
+struct QueueState {
+    queue: VecDeque<Queued>,
+    closed: bool,
+    stats: EmitterStats,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+    capacity: usize,
+    policy: EventOverflowPolicy,
+}
+
+impl Shared {
+    fn push(&self, item: Queued) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.closed {
+            state.stats.dropped += 1;
+            return;
+        }
+        // Lifecycle events may exceed the capacity; that headroom is the
+        // reserve that keeps terminal events from being dropped.
+        if state.queue.len() >= self.capacity && !item.is_lifecycle() {
+            let coalescing = self.policy == EventOverflowPolicy::CoalesceDeltas;
+            if coalescing && coalesce(&mut state.queue, &item) {
+                state.stats.coalesced += 1;
+                return;
+            }
+            let victim = coalescing
+                .then(|| {
+                    state
+                        .queue
+                        .iter()
+                        .position(|queued| queued.is_progress() && !queued.is_text_delta())
+                })
+                .flatten()
+                .or_else(|| state.queue.iter().position(Queued::is_progress))
+                .or_else(|| state.queue.iter().position(|queued| !queued.is_lifecycle()));
+            state.stats.dropped += 1;
+            match victim {
+                Some(index) => {
+                    state.queue.remove(index);
+                }
+                None => return,
+            }
+        }
+        state.queue.push_back(item);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    fn next(&self) -> Option<Queued> {
+        let mut state = self.state.lock().ok()?;
+        loop {
+            if let Some(item) = state.queue.pop_front() {
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.ready.wait(state).ok()?;
+        }
+    }
+
+    fn deliver(&self, run_sink: Option<RunEventSink>, agent_sink: Option<AgentEventSink>) {
+        while let Some(item) = self.next() {
+            match item {
+                Queued::Run(event) => {
+                    if let Some(sink) = &run_sink {
+                        sink(*event);
+                    }
+                }
+                Queued::Agent(envelope) => {
+                    if let Some(sink) = &agent_sink {
+                        sink(*envelope);
+                    }
+                }
+            }
+            if let Ok(mut state) = self.state.lock() {
+                state.stats.emitted += 1;
+            }
+        }
+    }
+}
+
+/// Per-run queue between the emitters and the caller's sinks.
+pub(super) struct EventDispatcher {
+    shared: Arc<Shared>,
+    worker: tokio::task::JoinHandle<()>,
+}
+
+impl EventDispatcher {
+    /// Route the request's sinks through a bounded queue serviced by a
+    /// blocking task. `None` when the request has no sinks.
+    pub(super) fn install(request: &mut RunRequest) -> Option<Self> {
+        let run_sink = request.event_sink.take();
+        let agent_sink = request.agent_event_sink.take();
+        if run_sink.is_none() && agent_sink.is_none() {
+            return None;
+        }
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState {
+                queue: VecDeque::new(),
+                closed: false,
+                stats: EmitterStats::default(),
+            }),
+            ready: Condvar::new(),
+            capacity: request.event_queue_capacity.max(1),
+            policy: request.event_overflow_policy,
+        });
+        if run_sink.is_some() {
+            let shared = shared.clone();
+            request.event_sink = Some(Arc::new(move |event| {
+                shared.push(Queued::Run(Box::new(event)))
+            }));
+        }
+        if agent_sink.is_some() {
+            let shared = shared.clone();
+            request.agent_event_sink = Some(Arc::new(move |envelope| {
+                shared.push(Queued::Agent(Box::new(envelope)))
+            }));
+        }
+
+        let worker_shared = shared.clone();
+        let worker =
+            tokio::task::spawn_blocking(move || worker_shared.deliver(run_sink, agent_sink));
+        Some(Self { shared, worker })
+    }
+
+    /// Deliver everything queued so far, stop the worker, and report its
+    /// counters. Events emitted afterwards are counted as dropped.
+    pub(super) async fn finish(mut self) -> EmitterStats {
+        self.close();
+        let _ = (&mut self.worker).await;
+        self.shared
+            .state
+            .lock()
+            .map(|state| state.stats)
+            .unwrap_or_default()
+    }
+
+    fn close(&self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.closed = true;
+        }
+        self.shared.ready.notify_all();
+    }
+}
+
+/// A run dropped before [`EventDispatcher::finish`] (for example, when its
+/// task is aborted) still releases the worker; otherwise the blocking task
+/// would keep the tokio runtime from shutting down.
+impl Drop for EventDispatcher {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent_loop::{EventTags, HeartbeatPhase, RunEventStream, RunLifecycle};
+    use uuid::Uuid;
+
+    fn run_event(stream: RunEventStream, payload: RunEventPayload) -> Queued {
+        Queued::Run(Box::new(RunEvent {
+            run_id: Uuid::nil(),
+            seq: 0,
+            timestamp: chrono::Utc::now(),
+            model: None,
+            tags: EventTags::default(),
+            stream,
+            payload,
+        }))
+    }
+
+    fn delta(text: &str) -> Queued {
+        run_event(
+            RunEventStream::Assistant,
+            RunEventPayload::AssistantDelta {
+                text: text.to_string(),
+            },
+        )
+    }
+
+    fn reasoning_delta(text: &str) -> Queued {
+        run_event(
+            RunEventStream::Reasoning,
+            RunEventPayload::ReasoningDelta {
+                text: text.to_string(),
+            },
+        )
+    }
+
+    fn lifecycle(state: RunLifecycle) -> Queued {
+        run_event(
+            RunEventStream::Lifecycle,
+            RunEventPayload::Lifecycle { state },
+        )
+    }
+
+    fn shared(capacity: usize, policy: EventOverflowPolicy) -> Shared {
+        Shared {
+            state: Mutex::new(QueueState {
+                queue: VecDeque::new(),
+                closed: false,
+                stats: EmitterStats::default(),
+            }),
+            ready: Condvar::new(),
+            capacity,
+            policy,
+        }
+    }
+
+    fn texts(shared: &Shared) -> Vec<String> {
+        let state = shared.state.lock().unwrap();
+        state
+            .queue
+            .iter()
+            .map(|queued| match queued {
+                Queued::Run(event) => match &event.payload {
+                    RunEventPayload::AssistantDelta { text } => text.clone(),
+                    RunEventPayload::ReasoningDelta { text } => format!("reasoning {text}"),
+                    RunEventPayload::Lifecycle { state } => format!("{state:?}"),
+                    other => format!("{other:?}"),
+                },
+                Queued::Agent(_) => "agent".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_queue_coalesces_consecutive_deltas() {
+        let shared = shared(2, EventOverflowPolicy::CoalesceDeltas);
+        for text in ["a", "b", "c", "d"] {
+            shared.push(delta(text));
+        }
+
+        assert_eq!(texts(&shared), vec!["a", "bcd"]);
+        let stats = shared.state.lock().unwrap().stats;
+        assert_eq!((stats.coalesced, stats.dropped), (2, 0));
+    }
+
+    #[test]
+    fn full_queue_coalesces_interleaved_text_and_reasoning() {
+        let shared = shared(2, EventOverflowPolicy::CoalesceDeltas);
+        for (text, reasoning) in [("a", "x"), ("b", "y"), ("c", "z")] {
+            shared.push(delta(text));
+            shared.push(reasoning_delta(reasoning));
+        }
+
+        assert_eq!(texts(&shared), vec!["abc", "reasoning xyz"]);
+        let stats = shared.state.lock().unwrap().stats;
+        assert_eq!((stats.coalesced, stats.dropped), (4, 0));
+    }
+
+    #[test]
+    fn coalescing_evicts_heartbeats_before_text() {
+        let shared = shared(2, EventOverflowPolicy::CoalesceDeltas);
+        shared.push(delta("a"));
+        shared.push(run_event(
+            RunEventStream::System,
+            RunEventPayload::Heartbeat {
+                phase: HeartbeatPhase::ToolExecution,
+                elapsed_ms: 10,
+            },
+        ));
+        shared.push(run_event(
+            RunEventStream::System,
+            RunEventPayload::Error {
+                message: "boom".to_string(),
+            },
+        ));
+
+        let texts = texts(&shared);
+        assert_eq!(texts[0], "a");
+        assert!(texts[1].contains("boom"));
+        assert_eq!(shared.state.lock().unwrap().stats.dropped, 1);
+    }
+
+    #[test]
+    fn coalescing_stops_at_non_progress_events() {
+        let shared = shared(2, EventOverflowPolicy::CoalesceDeltas);
+        shared.push(delta("a"));
+        shared.push(run_event(
+            RunEventStream::System,
+            RunEventPayload::Error {
+                message: "boom".to_string(),
+            },
+        ));
+        shared.push(delta("b"));
+
+        let texts = texts(&shared);
+        assert!(texts[0].contains("boom"));
+        assert_eq!(texts[1], "b");
+    }
+
+    #[test]
+    fn drop_oldest_discards_progress_before_other_events() {
+        let shared = shared(2, EventOverflowPolicy::DropOldest);
+        shared.push(run_event(
+            RunEventStream::System,
+            RunEventPayload::Error {
+                message: "boom".to_string(),
+            },
+        ));
+        shared.push(delta("a"));
+        shared.push(delta("b"));
+        shared.push(delta("c"));
+
+        assert_eq!(texts(&shared).len(), 2);
+        assert_eq!(texts(&shared)[1], "c");
+        assert!(texts(&shared)[0].contains("boom"));
+        assert_eq!(shared.state.lock().unwrap().stats.dropped, 2);
+    }
+
+    #[test]
+    fn lifecycle_events_use_reserved_capacity() {
+        let shared = shared(1, EventOverflowPolicy::DropOldest);
+        shared.push(delta("a"));
+        shared.push(lifecycle(RunLifecycle::Completed));
+
+        assert_eq!(texts(&shared), vec!["a", "Completed"]);
+
+        shared.push(delta("b"));
+        assert_eq!(texts(&shared), vec!["Completed", "b"]);
+        assert_eq!(shared.state.lock().unwrap().stats.dropped, 1);
+    }
+}
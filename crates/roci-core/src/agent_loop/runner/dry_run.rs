@@ -0,0 +1,39 @@
+//! Synthetic tool results for dry runs, which plan tool calls without
+//! executing them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::tools::arguments::ToolArguments;
+use crate::tools::tool::DRY_RUN_NOT_EXECUTED;
+use crate::tools::Tool;
+
+/// Where a dry run's synthetic tool results come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunResults {
+    /// The same [`DRY_RUN_NOT_EXECUTED`] payload for every call.
+    #[default]
+    Canned,
+    /// Each tool's [`Tool::simulate`] output.
+    Simulate,
+}
+
+/// The result recorded for a call to `tool` instead of executing it.
+///
+/// The envelope tells the model the call did not run:
+/// `{"dry_run": true, "executed": false, "output": ...}`.
+pub(super) fn simulated_result(
+    tool: &dyn Tool,
+    args: &ToolArguments,
+    results: DryRunResults,
+) -> serde_json::Value {
+    let output = match results {
+        DryRunResults::Canned => serde_json::Value::String(DRY_RUN_NOT_EXECUTED.to_string()),
+        DryRunResults::Simulate => tool.simulate(args),
+    };
+    serde_json::json!({
+        "dry_run": true,
+        "executed": false,
+        "output": output,
+    })
+}
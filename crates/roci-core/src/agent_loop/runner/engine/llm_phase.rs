@@ -1,34 +1,60 @@
+use std::borrow::Cow;
+
 use futures::StreamExt;
 use tokio::sync::{mpsc, oneshot};
 use tokio::time::{self, Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
+use super::super::argument_progress::ArgumentProgress;
+use super::super::coalescing::coalesce_text_deltas;
 use super::super::control::{
     process_stream_delta, AgentEventEmitter, RunEventEmitter, StreamDeltaState,
 };
+use super::super::first_token::{
+    is_content_delta, within_first_token_deadline, FirstTokenDeadline,
+};
+use super::super::heartbeat::{with_heartbeat, Heartbeat};
+use super::super::history::{History, HistoryMark};
 use super::super::message_events::{
     assistant_message_snapshot, emit_message_end_if_open, emit_message_lifecycle,
 };
-use super::super::tooling::normalize_tool_call_alias;
+use super::super::prefill::{apply_prefill, prefilled_text};
+use super::super::progress::OutputProgress;
+use super::super::retry_budget::RetryBudget;
+use super::super::tooling::{aliased_tool, normalize_tool_call_alias};
 use super::super::{
-    ConvertToLlmHookPayload, ConvertToLlmHookResult, RunEventPayload, RunEventStream, RunRequest,
-    TransformContextHookPayload, TransformContextHookResult,
+    CompactionHookContext, ConvertToLlmHookPayload, ConvertToLlmHookResult, RunEventPayload,
+    RunEventStream, RunRequest, TransformContextHookPayload, TransformContextHookResult,
 };
 use crate::agent::message::{convert_to_llm, AgentMessage};
-use crate::agent_loop::{FailureCategory, RetryEvent, RetryEventKind, RetryMode, RetryNextAction};
+use crate::agent_loop::{
+    FailureCategory, HeartbeatPhase, RetryEvent, RetryEventKind, RetryMode, RetryNextAction,
+};
+use crate::config::RociConfig;
 use crate::context::{
     estimate_context_usage, estimate_message_tokens, AbortReason, CompactionProgress,
     OverflowRecoveryPolicy, RecoveryAction, RecoveryEvent, RecoveryState,
 };
-use crate::error::RociError;
+use crate::error::{ErrorCategory, RociError};
 use crate::provider::{self, ProviderRequest, ToolDefinition};
 use crate::tools::Tool;
 use crate::types::Role;
-use crate::types::{AgentToolCall, ContentPart, GenerationSettings, ModelMessage, Usage};
+use crate::types::{
+    AgentToolCall, ContentPart, GenerationSettings, ImageContent, ModelMessage, ResponseFormat,
+    ResponseMetadata, Usage,
+};
 use crate::util::debug::roci_debug_enabled;
 use std::fmt::Write;
 use std::sync::Arc;
 
+fn first_token_slo_missed(deadline: Option<FirstTokenDeadline>) -> LlmPhaseOutcome {
+    let deadline = deadline.expect("only an armed first-token deadline expires");
+    LlmPhaseOutcome::FirstTokenSloMissed {
+        waited: deadline.waited(),
+        slo: deadline.slo(),
+    }
+}
+
 /// Compute the effective usage for a single provider call, merging it into
 /// the run-local accumulator.
 ///
@@ -73,15 +99,25 @@ fn finalize_call_usage(
 /// the entire history heuristically.
 pub(super) struct ExactUsageAnchor {
     /// The provider messages from the prior call.
-    pub(super) provider_messages: Vec<ModelMessage>,
+    pub(super) provider_messages: AnchorMessages,
     /// Provider-reported prompt (input) tokens for those messages.
     pub(super) prompt_tokens: usize,
 }
 
+/// The messages an [`ExactUsageAnchor`] counted.
+pub(super) enum AnchorMessages {
+    /// The run history, sent unedited. Kept as a mark rather than a second
+    /// handle so the next append does not have to copy the history.
+    History(HistoryMark),
+    /// A copy edited for the provider.
+    Sent(Arc<Vec<ModelMessage>>),
+}
+
 pub(super) enum LlmPhaseOutcome {
     Ready {
         iteration_text: String,
         tool_calls: Vec<AgentToolCall>,
+        images: Vec<ImageContent>,
     },
     Canceled {
         assistant_message: Option<ModelMessage>,
@@ -91,31 +127,72 @@ pub(super) enum LlmPhaseOutcome {
         assistant_message: Option<ModelMessage>,
         failure_category: FailureCategory,
     },
+    /// No content arrived within [`RunRequest::ttft_slo`]; the request was
+    /// aborted before any output.
+    FirstTokenSloMissed { waited: Duration, slo: Duration },
 }
 
 pub(super) struct LlmPhaseArgs<'a> {
     pub(super) request: &'a RunRequest,
+    /// Config the provider was built from; asked for a replacement key when
+    /// a call fails authentication.
+    pub(super) config: &'a RociConfig,
     pub(super) provider: &'a dyn provider::ModelProvider,
     pub(super) tool_defs: &'a Option<Vec<ToolDefinition>>,
-    pub(super) messages: &'a mut Vec<ModelMessage>,
+    pub(super) messages: &'a mut History,
     pub(super) emitter: &'a RunEventEmitter,
     pub(super) agent_emitter: &'a AgentEventEmitter,
     pub(super) input_rx: &'a mut mpsc::UnboundedReceiver<ModelMessage>,
     pub(super) abort_rx: &'a mut oneshot::Receiver<()>,
     pub(super) run_cancel_token: &'a CancellationToken,
     pub(super) iteration: usize,
+    pub(super) tool_call_ids: &'a provider::ToolCallIdAllocator,
     /// Run-local usage accumulator; merged after each provider call
     /// (including failed, canceled, and error exits after streaming began).
     pub(super) run_usage: &'a mut Usage,
+    /// Last provider response metadata seen in the run.
+    pub(super) response_metadata: &'a mut Option<ResponseMetadata>,
+    /// Payload sizes of every provider call in the run that reported them.
+    pub(super) payload_log: &'a super::PayloadLog,
     /// Optional anchor from a prior call for exact-prefix token estimation.
     pub(super) exact_anchor: &'a mut Option<ExactUsageAnchor>,
     /// Start time for current candidate retry lane.
     pub(super) retry_started_at: &'a Instant,
+    /// Retries and backoff left for the rest of the run.
+    pub(super) retry_budget: &'a mut RetryBudget,
+    /// Text the reply must start with, until a reply has been produced.
+    pub(super) prefill: Option<&'a str>,
+    /// Format this call must answer in, overriding the request settings.
+    pub(super) response_format: Option<ResponseFormat>,
+}
+
+/// Keep `metadata` as the run's latest and log the call's payload sizes.
+fn record_response_metadata(
+    metadata: &ResponseMetadata,
+    latest: &mut Option<ResponseMetadata>,
+    payload_log: &super::PayloadLog,
+    iteration: usize,
+) {
+    if let Some(payload) = metadata.payload {
+        if roci_debug_enabled() {
+            tracing::debug!(
+                iteration,
+                request_bytes = payload.request_bytes,
+                request_sent_bytes = payload.request_sent_bytes,
+                request_gzipped = payload.request_gzipped,
+                response_bytes = payload.response_bytes,
+                "provider payload sizes"
+            );
+        }
+        payload_log.lock().unwrap().push(payload);
+    }
+    *latest = Some(metadata.clone());
 }
 
 pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
     let LlmPhaseArgs {
         request,
+        config,
         provider,
         tool_defs,
         messages,
@@ -125,10 +202,22 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         abort_rx,
         run_cancel_token,
         iteration,
+        tool_call_ids,
         run_usage,
+        response_metadata,
+        payload_log,
         exact_anchor,
         retry_started_at,
+        retry_budget,
+        prefill,
+        response_format,
     } = args;
+    // Owned once an overloaded stream is resumed, since the partial reply
+    // becomes part of the prefill.
+    let mut prefill = prefill.map(Cow::Borrowed);
+    // Retries and overflow recovery reuse the iteration, so they reproduce
+    // the same generated tool-call IDs.
+    let tool_call_ids = tool_call_ids.for_iteration(iteration);
 
     while let Ok(message) = input_rx.try_recv() {
         emit_message_lifecycle(agent_emitter, &message);
@@ -142,11 +231,12 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         }
     }
 
-    let should_compact = request.auto_compaction.as_ref().is_some_and(|config| {
+    let compaction_trigger = request.auto_compaction.as_ref().and_then(|config| {
         let usage = estimate_context_usage(&*messages, provider.capabilities().context_length);
-        usage.used_tokens > usage.context_window.saturating_sub(config.reserve_tokens)
+        (usage.used_tokens > usage.context_window.saturating_sub(config.reserve_tokens))
+            .then_some(usage)
     });
-    if should_compact {
+    if let Some(usage) = compaction_trigger {
         let Some(compact) = request.hooks.compaction.as_ref() else {
             return LlmPhaseOutcome::Failed {
                 reason: "auto-compaction is enabled but no compaction hook is configured"
@@ -155,8 +245,16 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                 failure_category: FailureCategory::Configuration,
             };
         };
+        let context = CompactionHookContext {
+            run_id: request.run_id,
+            turn_index: agent_emitter.turn_index(),
+            iteration,
+            estimated_tokens: usage.used_tokens,
+            context_window: usage.context_window,
+        };
         let compaction_cancel_token = run_cancel_token.child_token();
-        let compaction_future = compact(messages.clone(), compaction_cancel_token.clone());
+        let compaction_future =
+            compact(messages.to_vec(), context, compaction_cancel_token.clone());
         tokio::pin!(compaction_future);
         let compaction_result = tokio::select! {
             _ = &mut *abort_rx => {
@@ -170,7 +268,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         };
         match compaction_result {
             Ok(Some(compacted)) => {
-                *messages = compacted;
+                messages.replace(compacted);
             }
             Ok(None) => {}
             Err(err) => {
@@ -194,14 +292,28 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
     // Carries per-attempt adjustments such as a reduced `max_tokens`
     // budget without mutating the original run request.
     let mut effective_settings = request.settings.clone();
+    if response_format.is_some() {
+        effective_settings.response_format = response_format;
+    }
     let policy = OverflowRecoveryPolicy::new();
     let mut recovery_state = RecoveryState::new();
     let mut in_overflow_episode = false;
     let mut staged_provider_request: Option<ProviderRequest> = None;
+    // One retry with a refreshed key per phase, outside the retry budget.
+    let mut credential_refreshed = false;
 
     'attempts: loop {
+        // Stopped by the first delta; each retry attempt starts a new one.
+        let mut heartbeat = Heartbeat::start(
+            emitter,
+            request.heartbeat_interval,
+            HeartbeatPhase::AwaitingFirstDelta,
+        );
+        // Restarted with each provider request; disarmed by the first
+        // content delta.
+        let mut first_token;
         let (mut stream, last_provider_messages) = loop {
-            let provider_request = match staged_provider_request.take() {
+            let mut provider_request = match staged_provider_request.take() {
                 Some(request) => request,
                 None => match build_provider_request(
                     request,
@@ -211,6 +323,8 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                     abort_rx,
                     run_cancel_token,
                     &effective_settings,
+                    prefill.as_deref(),
+                    &tool_call_ids,
                 )
                 .await
                 {
@@ -218,14 +332,21 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                     Err(outcome) => return outcome,
                 },
             };
+            if provider_request.api_key_override.is_none() {
+                provider_request.api_key_override =
+                    config.refreshed_api_key(request.active_model().provider_name());
+            }
 
             // -- Preflight budget check (5.3) --
             // Uses exact-anchor path when available: if the prior call's
             // provider messages are a prefix of the current request, combine
             // exact provider counts with a heuristic tail estimate.
             if let Some(ref budget) = request.context_budget {
-                let turn_input_tokens =
-                    estimate_turn_input(&provider_request.messages, exact_anchor.as_ref());
+                let turn_input_tokens = estimate_turn_input(
+                    &provider_request.messages,
+                    exact_anchor.as_ref(),
+                    messages,
+                );
                 let prior_input =
                     request.prior_session_input_tokens + run_usage.input_tokens as usize;
                 let prior_output =
@@ -243,7 +364,34 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                 }
             }
 
-            match provider.stream_text(&provider_request).await {
+            if let Some(transcript) = &request.transcript {
+                transcript.record_request(iteration, request.active_model(), &provider_request);
+            }
+
+            // Awaiting response headers can take as long as the provider
+            // likes; abort drops the request future, which closes the
+            // connection.
+            first_token = FirstTokenDeadline::start(
+                request
+                    .ttft_slo
+                    .filter(|_| request.candidates_remaining() > 0),
+            );
+            let stream_result = tokio::select! {
+                biased;
+                _ = cancellation(abort_rx, run_cancel_token) => {
+                    return LlmPhaseOutcome::Canceled {
+                        assistant_message: None,
+                    };
+                }
+                result = within_first_token_deadline(
+                    first_token,
+                    with_heartbeat(heartbeat.as_mut(), provider.stream_text(&provider_request)),
+                ) => result,
+            };
+            let Some(stream_result) = stream_result else {
+                return first_token_slo_missed(first_token);
+            };
+            match stream_result {
                 Ok(stream) => {
                     if in_overflow_episode {
                         emit_overflow_recovery(
@@ -253,10 +401,22 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             },
                         );
                     }
-                    break (stream, provider_request.messages);
+                    break (
+                        coalesce_text_deltas(stream, request.stream_coalescing),
+                        provider_request.messages,
+                    );
                 }
-                Err(RociError::RateLimited { retry_after_ms }) => {
-                    let server_retry_after_ms = retry_after_ms.filter(|delay| *delay > 0);
+                Err(err) if matches!(err, RociError::RateLimited { .. }) || err.is_overloaded() => {
+                    // Overloaded providers (e.g. Anthropic 529) behave like a
+                    // rate limit without a retry-after hint: retry the same
+                    // call on a synthesized backoff without using an iteration.
+                    let overloaded = err.is_overloaded();
+                    let server_retry_after_ms = match err {
+                        RociError::RateLimited { retry_after_ms } => {
+                            retry_after_ms.filter(|delay| *delay > 0)
+                        }
+                        _ => None,
+                    };
                     if let Some(retry_after_ms) = server_retry_after_ms {
                         if let Some(max_retry_delay_ms) = request.max_retry_delay_ms {
                             if max_retry_delay_ms > 0 && retry_after_ms > max_retry_delay_ms {
@@ -271,21 +431,37 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                         }
                     }
                     if attempt >= max_attempts {
+                        let limited = if overloaded {
+                            "provider overloaded"
+                        } else {
+                            "rate limited"
+                        };
                         return LlmPhaseOutcome::Failed {
                             reason: format!(
-                                "rate limited after {attempt} attempts; retry budget exhausted"
+                                "{limited} after {attempt} attempts; retry budget exhausted"
                             ),
                             assistant_message: None,
                             failure_category: FailureCategory::RateLimit,
                         };
                     }
                     let delay_ms = server_retry_after_ms.unwrap_or_else(|| {
-                        jittered_backoff_ms(
-                            next_backoff_ms,
-                            request.retry_backoff.jitter_ratio,
-                            request.retry_backoff.max_delay_ms.max(1),
-                        )
+                        if overloaded {
+                            overload_retry_delay_ms(next_backoff_ms, request)
+                        } else {
+                            jittered_backoff_ms(
+                                next_backoff_ms,
+                                request.retry_backoff.jitter_ratio,
+                                request.retry_backoff.max_delay_ms.max(1),
+                            )
+                        }
                     });
+                    if let Err(reason) = retry_budget.consume(delay_ms) {
+                        return LlmPhaseOutcome::Failed {
+                            reason,
+                            assistant_message: None,
+                            failure_category: FailureCategory::RetryBudget,
+                        };
+                    }
                     emit_retry_event(
                         request,
                         emitter,
@@ -294,6 +470,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                         FailureCategory::RateLimit,
                         RetryStep::sleep(delay_ms),
                         retry_started_at,
+                        retry_budget,
                     );
                     if !sleep_with_cancellation(
                         abort_rx,
@@ -310,6 +487,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             FailureCategory::Canceled,
                             RetryStep::cancel(),
                             retry_started_at,
+                            retry_budget,
                         );
                         return LlmPhaseOutcome::Canceled {
                             assistant_message: None,
@@ -323,6 +501,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                         FailureCategory::RateLimit,
                         RetryStep::resume_same_candidate(),
                         retry_started_at,
+                        retry_budget,
                     );
                     attempt += 1;
                     if server_retry_after_ms.is_none() {
@@ -330,6 +509,40 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                     }
                 }
                 Err(err) => {
+                    // -- Credential refresh (separate from generic retry) --
+                    if !credential_refreshed && err.category() == ErrorCategory::Authentication {
+                        credential_refreshed = true;
+                        let refresh = config.refresh_credential(
+                            request.active_model().provider_name(),
+                            provider_request.api_key_override.as_deref(),
+                            &err,
+                        );
+                        let refreshed_key = tokio::select! {
+                            biased;
+                            _ = cancellation(abort_rx, run_cancel_token) => {
+                                return LlmPhaseOutcome::Canceled {
+                                    assistant_message: None,
+                                };
+                            }
+                            key = refresh => key,
+                        };
+                        if let Some(key) = refreshed_key {
+                            emit_retry_event(
+                                request,
+                                emitter,
+                                RetryEventKind::RetryResuming,
+                                attempt,
+                                FailureCategory::Auth,
+                                RetryStep::resume_same_candidate(),
+                                retry_started_at,
+                                retry_budget,
+                            );
+                            provider_request.api_key_override = Some(key);
+                            staged_provider_request = Some(provider_request);
+                            continue;
+                        }
+                    }
+
                     // -- Overflow recovery (separate from generic retry) --
                     if let Some(signal) = provider.classify_overflow(&err) {
                         if !in_overflow_episode {
@@ -398,9 +611,19 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         .map(estimate_message_tokens)
                                         .sum();
 
+                                    let context = CompactionHookContext {
+                                        run_id: request.run_id,
+                                        turn_index: agent_emitter.turn_index(),
+                                        iteration,
+                                        estimated_tokens: tokens_before,
+                                        context_window: provider.capabilities().context_length,
+                                    };
                                     let compaction_cancel_token = run_cancel_token.child_token();
-                                    let compaction_future =
-                                        compact(messages.clone(), compaction_cancel_token.clone());
+                                    let compaction_future = compact(
+                                        messages.clone(),
+                                        context,
+                                        compaction_cancel_token.clone(),
+                                    );
                                     tokio::pin!(compaction_future);
                                     let compaction_result = tokio::select! {
                                         _ = &mut *abort_rx => {
@@ -421,7 +644,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
 
                                     match compaction_result {
                                         Ok(Some(compacted)) => {
-                                            if compacted == *messages {
+                                            if compacted == **messages {
                                                 return LlmPhaseOutcome::Failed {
                                                 reason:
                                                     "overflow recovery compaction made no changes"
@@ -430,6 +653,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                                 failure_category: FailureCategory::Overflow,
                                             };
                                             }
+                                            let compacted = messages.replacement(compacted);
                                             let next_provider_request =
                                                 match build_provider_request(
                                                     request,
@@ -439,6 +663,8 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                                     abort_rx,
                                                     run_cancel_token,
                                                     &effective_settings,
+                                                    prefill.as_deref(),
+                                                    &tool_call_ids,
                                                 )
                                                 .await
                                                 {
@@ -538,6 +764,13 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             request.retry_backoff.jitter_ratio,
                             request.retry_backoff.max_delay_ms.max(1),
                         );
+                        if let Err(reason) = retry_budget.consume(delay_ms) {
+                            return LlmPhaseOutcome::Failed {
+                                reason,
+                                assistant_message: None,
+                                failure_category: FailureCategory::RetryBudget,
+                            };
+                        }
                         emit_retry_event(
                             request,
                             emitter,
@@ -546,6 +779,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             failure_category,
                             RetryStep::sleep(delay_ms),
                             retry_started_at,
+                            retry_budget,
                         );
                         if !sleep_with_cancellation(
                             abort_rx,
@@ -562,6 +796,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                 FailureCategory::Canceled,
                                 RetryStep::cancel(),
                                 retry_started_at,
+                                retry_budget,
                             );
                             return LlmPhaseOutcome::Canceled {
                                 assistant_message: None,
@@ -575,6 +810,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             failure_category,
                             RetryStep::resume_same_candidate(),
                             retry_started_at,
+                            retry_budget,
                         );
                         attempt += 1;
                         next_backoff_ms = next_backoff_ms_for_policy(next_backoff_ms, request);
@@ -591,6 +827,9 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
 
         let mut iteration_text = String::new();
         let mut tool_calls: Vec<AgentToolCall> = Vec::new();
+        let mut images: Vec<ImageContent> = Vec::new();
+        let mut argument_progress = ArgumentProgress::default();
+        let mut output_progress = OutputProgress::start(request.settings.max_tokens);
         let mut stream_done = false;
         let mut message_open = false;
         let mut call_usage: Option<Usage> = None;
@@ -600,8 +839,8 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         loop {
             if let Some(ref mut sleep) = idle_sleep {
                 tokio::select! {
-                    _ = &mut *abort_rx => {
-                        run_cancel_token.cancel();
+                    biased;
+                    _ = cancellation(abort_rx, run_cancel_token) => {
                         emit_message_end_if_open(
                             agent_emitter,
                             &mut message_open,
@@ -633,6 +872,16 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             &tool_calls,
                         ) {
                             let delay_ms = retry_delay_ms(next_backoff_ms, request);
+                            if let Err(reason) = retry_budget.consume(delay_ms) {
+                                return LlmPhaseOutcome::Failed {
+                                    reason,
+                                    assistant_message: assistant_snapshot_if_present(
+                                        &iteration_text,
+                                        &tool_calls,
+                                    ),
+                                    failure_category: FailureCategory::RetryBudget,
+                                };
+                            }
                             emit_retry_event(
                                 request,
                                 emitter,
@@ -641,6 +890,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                 FailureCategory::Timeout,
                                 RetryStep::sleep(delay_ms),
                                 retry_started_at,
+                                retry_budget,
                             );
                             if !sleep_with_cancellation(
                                 abort_rx,
@@ -657,6 +907,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                     FailureCategory::Canceled,
                                     RetryStep::cancel(),
                                     retry_started_at,
+                                    retry_budget,
                                 );
                                 return LlmPhaseOutcome::Canceled {
                                     assistant_message: None,
@@ -670,6 +921,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                 FailureCategory::Timeout,
                                 RetryStep::resume_same_candidate(),
                                 retry_started_at,
+                                retry_budget,
                             );
                             attempt += 1;
                             next_backoff_ms = next_backoff_ms_for_policy(next_backoff_ms, request);
@@ -684,26 +936,52 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             failure_category: FailureCategory::Timeout,
                         };
                     }
-                    delta = stream.next() => {
+                    delta = within_first_token_deadline(
+                        first_token,
+                        with_heartbeat(heartbeat.as_mut(), stream.next()),
+                    ) => {
+                        let Some(delta) = delta else {
+                            // Dropping the stream aborts the request.
+                            finalize_call_usage(call_usage, &last_provider_messages, &iteration_text, &tool_calls, run_usage);
+                            return first_token_slo_missed(first_token);
+                        };
                         let Some(delta) = delta else { break; };
                         match delta {
                             Ok(delta) => {
+                                heartbeat = None;
+                                if is_content_delta(&delta) {
+                                    first_token = None;
+                                }
                                 sleep.as_mut().reset(
                                     time::Instant::now() + Duration::from_millis(idle_timeout_ms),
                                 );
                                 if let Some(ref u) = delta.usage {
                                     call_usage = Some(u.clone());
                                 }
+                                if let Some(metadata) = &delta.response_metadata {
+                                    record_response_metadata(
+                                        metadata,
+                                        response_metadata,
+                                        payload_log,
+                                        iteration,
+                                    );
+                                }
+                                let progress = output_progress
+                                    .as_mut()
+                                    .and_then(|progress| progress.observe(&delta));
                                 if let Some(reason) = process_stream_delta(
                                     emitter,
                                     agent_emitter,
                                     delta,
                                     &request.tools,
+                                    request.repair_tool_arguments,
                                     StreamDeltaState {
                                         iteration_text: &mut iteration_text,
                                         tool_calls: &mut tool_calls,
+                                        images: &mut images,
                                         stream_done: &mut stream_done,
                                         message_open: &mut message_open,
+                                        argument_progress: &mut argument_progress,
                                     },
                                 ) {
                                     emit_message_end_if_open(
@@ -722,6 +1000,9 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category: FailureCategory::InvalidRequest,
                                     };
                                 }
+                                if let Some(progress) = progress {
+                                    emitter.emit(RunEventStream::System, progress);
+                                }
                                 if stream_done {
                                     break;
                                 }
@@ -735,15 +1016,49 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                 );
                                 finalize_call_usage(call_usage, &last_provider_messages, &iteration_text, &tool_calls, run_usage);
                                 let failure_category = failure_category_for_error(&err);
-                                if should_retry_same_candidate(
+                                let retry_partial = should_retry_partial_after_overload(
                                     request,
                                     attempt,
                                     max_attempts,
-                                    failure_category,
+                                    &err,
                                     &iteration_text,
                                     &tool_calls,
-                                ) {
-                                    let delay_ms = retry_delay_ms(next_backoff_ms, request);
+                                );
+                                // Providers that cannot continue a trailing
+                                // assistant message restart the reply instead.
+                                let resume_partial =
+                                    retry_partial && provider.supports_assistant_prefix();
+                                if retry_partial
+                                    || should_retry_same_candidate(
+                                        request,
+                                        attempt,
+                                        max_attempts,
+                                        failure_category,
+                                        &iteration_text,
+                                        &tool_calls,
+                                    )
+                                {
+                                    let delay_ms = retry_delay_ms_for_error(next_backoff_ms, request, &err);
+                                    if let Err(reason) = retry_budget.consume(delay_ms) {
+                                        return LlmPhaseOutcome::Failed {
+                                            reason,
+                                            assistant_message: assistant_snapshot_if_present(
+                                                &iteration_text,
+                                                &tool_calls,
+                                            ),
+                                            failure_category: FailureCategory::RetryBudget,
+                                        };
+                                    }
+                                    if resume_partial {
+                                        // The retried request ends with the
+                                        // partial text as a prefill, so the model
+                                        // continues it and the stored reply is
+                                        // one message.
+                                        prefill = Some(Cow::Owned(prefilled_text(
+                                            prefill.as_deref(),
+                                            iteration_text.clone(),
+                                        )));
+                                    }
                                     emit_retry_event(
                                         request,
                                         emitter,
@@ -752,6 +1067,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category,
                                         RetryStep::sleep(delay_ms),
                                         retry_started_at,
+                                        retry_budget,
                                     );
                                     if !sleep_with_cancellation(
                                         abort_rx,
@@ -768,6 +1084,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                             FailureCategory::Canceled,
                                             RetryStep::cancel(),
                                             retry_started_at,
+                                            retry_budget,
                                         );
                                         return LlmPhaseOutcome::Canceled {
                                             assistant_message: None,
@@ -781,6 +1098,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category,
                                         RetryStep::resume_same_candidate(),
                                         retry_started_at,
+                                        retry_budget,
                                     );
                                     attempt += 1;
                                     next_backoff_ms = next_backoff_ms_for_policy(next_backoff_ms, request);
@@ -800,8 +1118,8 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                 }
             } else {
                 tokio::select! {
-                    _ = &mut *abort_rx => {
-                        run_cancel_token.cancel();
+                    biased;
+                    _ = cancellation(abort_rx, run_cancel_token) => {
                         emit_message_end_if_open(
                             agent_emitter,
                             &mut message_open,
@@ -816,23 +1134,49 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                             ),
                         };
                     }
-                    delta = stream.next() => {
+                    delta = within_first_token_deadline(
+                        first_token,
+                        with_heartbeat(heartbeat.as_mut(), stream.next()),
+                    ) => {
+                        let Some(delta) = delta else {
+                            // Dropping the stream aborts the request.
+                            finalize_call_usage(call_usage, &last_provider_messages, &iteration_text, &tool_calls, run_usage);
+                            return first_token_slo_missed(first_token);
+                        };
                         let Some(delta) = delta else { break; };
                         match delta {
                             Ok(delta) => {
+                                heartbeat = None;
+                                if is_content_delta(&delta) {
+                                    first_token = None;
+                                }
                                 if let Some(ref u) = delta.usage {
                                     call_usage = Some(u.clone());
                                 }
+                                if let Some(metadata) = &delta.response_metadata {
+                                    record_response_metadata(
+                                        metadata,
+                                        response_metadata,
+                                        payload_log,
+                                        iteration,
+                                    );
+                                }
+                                let progress = output_progress
+                                    .as_mut()
+                                    .and_then(|progress| progress.observe(&delta));
                                 if let Some(reason) = process_stream_delta(
                                     emitter,
                                     agent_emitter,
                                     delta,
                                     &request.tools,
+                                    request.repair_tool_arguments,
                                     StreamDeltaState {
                                         iteration_text: &mut iteration_text,
                                         tool_calls: &mut tool_calls,
+                                        images: &mut images,
                                         stream_done: &mut stream_done,
                                         message_open: &mut message_open,
+                                        argument_progress: &mut argument_progress,
                                     },
                                 ) {
                                     emit_message_end_if_open(
@@ -851,6 +1195,9 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category: FailureCategory::InvalidRequest,
                                     };
                                 }
+                                if let Some(progress) = progress {
+                                    emitter.emit(RunEventStream::System, progress);
+                                }
                                 if stream_done {
                                     break;
                                 }
@@ -864,15 +1211,49 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                 );
                                 finalize_call_usage(call_usage, &last_provider_messages, &iteration_text, &tool_calls, run_usage);
                                 let failure_category = failure_category_for_error(&err);
-                                if should_retry_same_candidate(
+                                let retry_partial = should_retry_partial_after_overload(
                                     request,
                                     attempt,
                                     max_attempts,
-                                    failure_category,
+                                    &err,
                                     &iteration_text,
                                     &tool_calls,
-                                ) {
-                                    let delay_ms = retry_delay_ms(next_backoff_ms, request);
+                                );
+                                // Providers that cannot continue a trailing
+                                // assistant message restart the reply instead.
+                                let resume_partial =
+                                    retry_partial && provider.supports_assistant_prefix();
+                                if retry_partial
+                                    || should_retry_same_candidate(
+                                        request,
+                                        attempt,
+                                        max_attempts,
+                                        failure_category,
+                                        &iteration_text,
+                                        &tool_calls,
+                                    )
+                                {
+                                    let delay_ms = retry_delay_ms_for_error(next_backoff_ms, request, &err);
+                                    if let Err(reason) = retry_budget.consume(delay_ms) {
+                                        return LlmPhaseOutcome::Failed {
+                                            reason,
+                                            assistant_message: assistant_snapshot_if_present(
+                                                &iteration_text,
+                                                &tool_calls,
+                                            ),
+                                            failure_category: FailureCategory::RetryBudget,
+                                        };
+                                    }
+                                    if resume_partial {
+                                        // The retried request ends with the
+                                        // partial text as a prefill, so the model
+                                        // continues it and the stored reply is
+                                        // one message.
+                                        prefill = Some(Cow::Owned(prefilled_text(
+                                            prefill.as_deref(),
+                                            iteration_text.clone(),
+                                        )));
+                                    }
                                     emit_retry_event(
                                         request,
                                         emitter,
@@ -881,6 +1262,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category,
                                         RetryStep::sleep(delay_ms),
                                         retry_started_at,
+                                        retry_budget,
                                     );
                                     if !sleep_with_cancellation(
                                         abort_rx,
@@ -897,6 +1279,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                             FailureCategory::Canceled,
                                             RetryStep::cancel(),
                                             retry_started_at,
+                                            retry_budget,
                                         );
                                         return LlmPhaseOutcome::Canceled {
                                             assistant_message: None,
@@ -910,6 +1293,7 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
                                         failure_category,
                                         RetryStep::resume_same_candidate(),
                                         retry_started_at,
+                                        retry_budget,
                                     );
                                     attempt += 1;
                                     next_backoff_ms = next_backoff_ms_for_policy(next_backoff_ms, request);
@@ -954,8 +1338,13 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         // anchor with a fail-open zero-token prefix.
         if let Some(ref usage) = call_usage {
             if usage.input_tokens > 0 {
+                let provider_messages = if messages.is_shared_by(&last_provider_messages) {
+                    AnchorMessages::History(messages.mark())
+                } else {
+                    AnchorMessages::Sent(last_provider_messages)
+                };
                 *exact_anchor = Some(ExactUsageAnchor {
-                    provider_messages: last_provider_messages,
+                    provider_messages,
                     prompt_tokens: usage.input_tokens as usize,
                 });
             }
@@ -979,28 +1368,33 @@ pub(super) async fn run_llm_phase(args: LlmPhaseArgs<'_>) -> LlmPhaseOutcome {
         }
 
         return LlmPhaseOutcome::Ready {
-            iteration_text,
+            iteration_text: prefilled_text(prefill.as_deref(), iteration_text),
             tool_calls,
+            images,
         };
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn build_provider_request(
     request: &RunRequest,
     provider: &dyn provider::ModelProvider,
     tool_defs: &Option<Vec<ToolDefinition>>,
-    messages: &[ModelMessage],
+    messages: &History,
     abort_rx: &mut oneshot::Receiver<()>,
     run_cancel_token: &CancellationToken,
     effective_settings: &GenerationSettings,
+    prefill: Option<&str>,
+    tool_call_ids: &provider::ToolCallIdAllocator,
 ) -> Result<ProviderRequest, LlmPhaseOutcome> {
-    let mut transformed = messages.to_vec();
+    // Shares the run history until a step below edits it.
+    let mut transformed = messages.shared();
     if let Some(ref transform) = request.transform_context {
         let transform_cancel = run_cancel_token.child_token();
         let transform_payload = TransformContextHookPayload {
             run_id: request.run_id,
             model: request.active_model().clone(),
-            messages: transformed.clone(),
+            messages: Arc::clone(&transformed),
             cancellation_token: transform_cancel.clone(),
         };
         let transform_future = transform(transform_payload);
@@ -1022,9 +1416,11 @@ async fn build_provider_request(
             result = &mut transform_future => result,
         };
 
-        transformed = match transform_result {
-            Ok(TransformContextHookResult::Continue) => transformed,
-            Ok(TransformContextHookResult::ReplaceMessages { messages }) => messages,
+        match transform_result {
+            Ok(TransformContextHookResult::Continue) => {}
+            Ok(TransformContextHookResult::ReplaceMessages { messages }) => {
+                transformed = Arc::new(messages);
+            }
             Ok(TransformContextHookResult::Cancel { reason }) => {
                 return Err(LlmPhaseOutcome::Failed {
                     reason: reason
@@ -1045,11 +1441,10 @@ async fn build_provider_request(
 
     insert_available_tools_metadata(&mut transformed, &request.tools);
 
-    let llm_context = if let Some(ref convert) = request.convert_to_llm {
+    let mut llm_context = if let Some(ref convert) = request.convert_to_llm {
         let convert_cancel = run_cancel_token.child_token();
-        let agent_messages: Vec<AgentMessage> = transformed
-            .iter()
-            .cloned()
+        let agent_messages: Vec<AgentMessage> = Arc::unwrap_or_clone(transformed)
+            .into_iter()
             .map(AgentMessage::from_model)
             .collect();
         let convert_payload = ConvertToLlmHookPayload {
@@ -1078,8 +1473,8 @@ async fn build_provider_request(
         };
 
         match convert_result {
-            Ok(ConvertToLlmHookResult::Continue) => convert_to_llm(&agent_messages),
-            Ok(ConvertToLlmHookResult::ReplaceMessages { messages }) => messages,
+            Ok(ConvertToLlmHookResult::Continue) => Arc::new(convert_to_llm(&agent_messages)),
+            Ok(ConvertToLlmHookResult::ReplaceMessages { messages }) => Arc::new(messages),
             Ok(ConvertToLlmHookResult::Cancel { reason }) => {
                 return Err(LlmPhaseOutcome::Failed {
                     reason: reason
@@ -1100,10 +1495,17 @@ async fn build_provider_request(
         transformed
     };
 
-    let llm_context = normalize_tool_call_aliases_for_provider(&llm_context, &request.tools);
+    normalize_tool_call_aliases_for_provider(&mut llm_context, &request.tools);
     let provider_messages =
-        provider::sanitize_messages_for_provider(&llm_context, provider.provider_name());
-    Ok(ProviderRequest {
+        provider::sanitize_shared_messages_for_provider(llm_context, provider.provider_name());
+    debug_assert!(
+        provider::lint_messages(&provider_messages, provider.provider_name())
+            .iter()
+            .all(|lint| !lint.kind.is_repaired()),
+        "sanitized messages still break {} transcript rules",
+        provider.provider_name()
+    );
+    let mut provider_request = ProviderRequest {
         messages: provider_messages,
         settings: effective_settings.clone(),
         tools: tool_defs.clone(),
@@ -1114,28 +1516,52 @@ async fn build_provider_request(
         payload_callback: request.provider_payload_callback.clone(),
         session_id: request.session_id.clone(),
         transport: request.transport.clone(),
-    })
+        tool_call_ids: Some(tool_call_ids.clone()),
+    };
+    provider::ProviderRouting::from_run_metadata(&request.metadata).apply(
+        &mut provider_request,
+        provider::ProviderRoutingSupport::for_provider(provider.provider_name()),
+    );
+    if let Some(prefill) = prefill {
+        apply_prefill(
+            provider,
+            request.prefill_fallback,
+            prefill,
+            &mut provider_request,
+        )
+        .map_err(|err| LlmPhaseOutcome::Failed {
+            reason: err.to_string(),
+            assistant_message: None,
+            failure_category: failure_category_for_error(&err),
+        })?;
+    }
+    Ok(provider_request)
 }
 
 fn normalize_tool_call_aliases_for_provider(
-    messages: &[ModelMessage],
+    messages: &mut Arc<Vec<ModelMessage>>,
     tools: &[Arc<dyn Tool>],
-) -> Vec<ModelMessage> {
-    messages
+) {
+    let aliased = messages
         .iter()
-        .cloned()
-        .map(|mut message| {
-            for part in &mut message.content {
-                if let ContentPart::ToolCall(call) = part {
-                    normalize_tool_call_alias(tools, call);
-                }
-            }
-            message
-        })
-        .collect()
+        .flat_map(|message| &message.content)
+        .any(|part| {
+            matches!(part, ContentPart::ToolCall(call) if aliased_tool(tools, &call.name).is_some())
+        });
+    if !aliased {
+        return;
+    }
+    for part in Arc::make_mut(messages)
+        .iter_mut()
+        .flat_map(|message| message.content.iter_mut())
+    {
+        if let ContentPart::ToolCall(call) = part {
+            normalize_tool_call_alias(tools, call);
+        }
+    }
 }
 
-fn insert_available_tools_metadata(messages: &mut Vec<ModelMessage>, tools: &[Arc<dyn Tool>]) {
+fn insert_available_tools_metadata(messages: &mut Arc<Vec<ModelMessage>>, tools: &[Arc<dyn Tool>]) {
     let Some(metadata) = render_available_tools_metadata(tools) else {
         return;
     };
@@ -1143,7 +1569,7 @@ fn insert_available_tools_metadata(messages: &mut Vec<ModelMessage>, tools: &[Ar
         .iter()
         .take_while(|message| message.role == Role::System)
         .count();
-    messages.insert(insert_at, ModelMessage::system(metadata));
+    Arc::make_mut(messages).insert(insert_at, ModelMessage::system(metadata));
 }
 
 fn render_available_tools_metadata(tools: &[Arc<dyn Tool>]) -> Option<String> {
@@ -1278,6 +1704,7 @@ fn format_overflow_recovery_abort(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn emit_retry_event(
     request: &RunRequest,
     emitter: &RunEventEmitter,
@@ -1286,6 +1713,7 @@ fn emit_retry_event(
     failure_category: FailureCategory,
     step: RetryStep,
     retry_started_at: &Instant,
+    retry_budget: &RetryBudget,
 ) {
     let model = request.active_model();
     emitter.emit(
@@ -1305,6 +1733,8 @@ fn emit_retry_event(
                 candidates_remaining: request.candidates_remaining(),
                 partial_output_seen: false,
                 next_action: step.next_action,
+                retries_remaining: retry_budget.retries_remaining(),
+                retry_delay_remaining_ms: retry_budget.delay_remaining_ms(),
             },
         },
     );
@@ -1353,6 +1783,21 @@ fn should_retry_same_candidate(
         && !matches!(request.retry_mode, RetryMode::Bounded { max_attempts: 0 })
 }
 
+fn should_retry_partial_after_overload(
+    request: &RunRequest,
+    attempt: u32,
+    max_attempts: u32,
+    error: &RociError,
+    iteration_text: &str,
+    tool_calls: &[AgentToolCall],
+) -> bool {
+    request.resume_partial_on_overload
+        && attempt < max_attempts
+        && error.is_overloaded()
+        && !iteration_text.is_empty()
+        && tool_calls.is_empty()
+}
+
 fn is_transient_retry_category(category: FailureCategory) -> bool {
     matches!(
         category,
@@ -1371,6 +1816,29 @@ fn retry_delay_ms(next_backoff_ms: u64, request: &RunRequest) -> u64 {
     )
 }
 
+fn retry_delay_ms_for_error(next_backoff_ms: u64, request: &RunRequest, error: &RociError) -> u64 {
+    if error.is_overloaded() {
+        overload_retry_delay_ms(next_backoff_ms, request)
+    } else {
+        retry_delay_ms(next_backoff_ms, request)
+    }
+}
+
+/// Synthesized jittered backoff for overloaded providers.
+///
+/// Capped by `max_retry_delay_ms` when set, otherwise by the policy maximum.
+fn overload_retry_delay_ms(next_backoff_ms: u64, request: &RunRequest) -> u64 {
+    let max_delay_ms = request
+        .max_retry_delay_ms
+        .filter(|delay| *delay > 0)
+        .unwrap_or(request.retry_backoff.max_delay_ms);
+    jittered_backoff_ms(
+        next_backoff_ms,
+        request.retry_backoff.jitter_ratio,
+        max_delay_ms.max(1),
+    )
+}
+
 fn elapsed_retry_ms(retry_started_at: &Instant) -> u64 {
     u64::try_from(retry_started_at.elapsed().as_millis()).unwrap_or(u64::MAX)
 }
@@ -1397,15 +1865,24 @@ async fn sleep_with_cancellation(
     duration: Duration,
 ) -> bool {
     tokio::select! {
-        _ = &mut *abort_rx => {
-            run_cancel_token.cancel();
-            false
-        }
-        _ = run_cancel_token.cancelled() => false,
+        biased;
+        _ = cancellation(abort_rx, run_cancel_token) => false,
         _ = time::sleep(duration) => true,
     }
 }
 
+/// Resolves once the run is aborted or its cancel token fires. An abort also
+/// cancels the token so in-flight hooks and tools observe it.
+///
+/// Returning from the phase on this signal drops the provider stream, which
+/// tears down the underlying HTTP response.
+async fn cancellation(abort_rx: &mut oneshot::Receiver<()>, run_cancel_token: &CancellationToken) {
+    tokio::select! {
+        _ = &mut *abort_rx => run_cancel_token.cancel(),
+        _ = run_cancel_token.cancelled() => {}
+    }
+}
+
 fn next_backoff_ms_for_policy(current_backoff_ms: u64, request: &RunRequest) -> u64 {
     let multiplier = request.retry_backoff.multiplier.max(1.0);
     let max_delay_ms = request.retry_backoff.max_delay_ms.max(1);
@@ -1414,7 +1891,11 @@ fn next_backoff_ms_for_policy(current_backoff_ms: u64, request: &RunRequest) ->
         .min(max_delay_ms)
 }
 
-fn jittered_backoff_ms(base_delay_ms: u64, jitter_ratio: f64, max_delay_ms: u64) -> u64 {
+pub(in crate::agent_loop::runner) fn jittered_backoff_ms(
+    base_delay_ms: u64,
+    jitter_ratio: f64,
+    max_delay_ms: u64,
+) -> u64 {
     let jitter_ratio = jitter_ratio.max(0.0);
     let min_factor = (1.0 - jitter_ratio).max(0.0);
     let max_factor = 1.0 + jitter_ratio;
@@ -1464,15 +1945,22 @@ fn assistant_snapshot_if_present(
 ///
 /// Falls back to a full heuristic recount when:
 /// - No anchor is available.
+/// - The anchor marked a history that compaction has since replaced.
 /// - The anchor messages are not a prefix of the current messages.
 fn estimate_turn_input(
     current_messages: &[ModelMessage],
     anchor: Option<&ExactUsageAnchor>,
+    history: &History,
 ) -> usize {
-    if let Some(anchor) = anchor {
-        let prefix_len = anchor.provider_messages.len();
+    let anchor_messages = anchor.and_then(|anchor| match &anchor.provider_messages {
+        AnchorMessages::History(mark) => history.prefix(*mark),
+        AnchorMessages::Sent(messages) => Some(messages.as_slice()),
+    });
+    if let (Some(anchor), Some(anchor_messages)) = (anchor, anchor_messages) {
+        let prefix_len = anchor_messages.len();
         if current_messages.len() >= prefix_len
-            && current_messages[..prefix_len] == anchor.provider_messages[..]
+            && (current_messages.as_ptr() == anchor_messages.as_ptr()
+                || current_messages[..prefix_len] == *anchor_messages)
         {
             // Anchor is a prefix — estimate only the tail.
             let tail_tokens: usize = current_messages[prefix_len..]
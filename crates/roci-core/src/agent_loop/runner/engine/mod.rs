@@ -1,74 +1,226 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
+use tokio::sync::oneshot;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
 use crate::models::{HealthSignal, ModelHealthKey};
 use crate::provider::{self, ToolDefinition};
-use crate::tools::{ToolCatalog, ToolOrigin};
-use crate::types::{ModelMessage, Usage};
+use crate::tools::{
+    ArtifactStore, ChangeLog, FileChange, PlanStore, RunArtifact, ScratchDir, Tool, ToolCatalog,
+    ToolOrigin,
+};
+use crate::types::{ModelMessage, PayloadSizes, ResponseMetadata, Usage};
 
+use super::admission::{Admission, SharedRunStatus};
+use super::budget::{budget_exceeded_message, validate_budget, BudgetTracker};
 use super::canonical_workspace_root;
 use super::control::{
-    emit_failed_result, resolve_iteration_limit_approval, AgentEventEmitter,
-    IterationLimitApprovalContext, RunEventEmitter,
+    resolve_iteration_limit_approval, AgentEventEmitter, IterationLimitApprovalContext,
+    RunEventEmitter,
+};
+use super::deadline::{
+    close_open_tool_calls, ends_with_final_text, validate_deadline, wrap_up_prompt,
+    wrap_up_request, RunDeadline,
+};
+use super::dispatch::EventDispatcher;
+use super::final_response::{validate_final_response_schema, FinalResponse, FinalResponseReview};
+use super::history::History;
+use super::limits::{validate_runner_limits, RunnerLimits};
+use super::message_events::{
+    assistant_message_with_images, emit_message_lifecycle, StoredMessageFilter,
 };
-use super::limits::RunnerLimits;
-use super::message_events::emit_message_lifecycle;
+use super::message_lint::validate_message_lints;
+use super::plugin::apply_plugins;
+use super::prefill::validate_prefill;
+use super::retry_budget::RetryBudget;
+use super::task_list::start_task_list;
+use super::tool_overflow::fit_tools;
+use super::tooling::emit_artifacts_added;
+use super::warm_up::warm_up_provider;
 use super::{AgentEvent, ApprovalDecision, LoopRunner, RunEventPayload, RunEventStream, RunHandle};
 use super::{RunLifecycle, RunRequest, RunResult, Runner};
-use crate::agent_loop::{FailureCategory, RetryEvent, RetryEventKind, RetryMode, RetryNextAction};
+use crate::agent_loop::transcript::TranscriptWriter;
+use crate::agent_loop::webhook::RunWebhooks;
+use crate::agent_loop::{
+    BudgetReading, EventTags, FailureCategory, RetryEvent, RetryEventKind, RetryMode,
+    RetryNextAction, RunStatus,
+};
 use crate::util::debug::roci_debug_enabled;
 
 mod llm_phase;
 mod tool_phase;
 
+pub(super) use llm_phase::jittered_backoff_ms;
+
 use llm_phase::{
     failure_category_for_error, run_llm_phase, ExactUsageAnchor, LlmPhaseArgs, LlmPhaseOutcome,
 };
 use tool_phase::{run_tool_phase, ToolPhaseArgs, ToolPhaseOutcome};
 
-fn canceled_result(
-    request: &RunRequest,
-    emitter: &RunEventEmitter,
-    agent_emitter: &AgentEventEmitter,
-    messages: &[ModelMessage],
-    run_usage: Usage,
-) -> RunResult {
-    if let Some(health) = request.model_health.as_ref() {
-        health.observe(HealthSignal::Canceled {
-            key: ModelHealthKey::from_model(request.active_model()),
-            observed_at_ms: now_ms(),
+/// How a run ended short of completing, for [`RunOutputs::finish`].
+enum RunEnd {
+    Canceled,
+    Failed(String),
+    BudgetExceeded(BudgetReading),
+    /// Tool calls the deadline cut off are answered as canceled.
+    DeadlineExceeded(String),
+}
+
+/// Per-run state that every result reports, filled in by the phases.
+struct RunOutputs {
+    /// Plan state maintained by plan tools.
+    plan_store: PlanStore,
+    /// Files changed by tools.
+    change_log: ChangeLog,
+    /// Tool outputs registered during the run; large ones spill here.
+    artifacts: ArtifactStore,
+    /// Metadata of the last provider response.
+    response_metadata: Option<ResponseMetadata>,
+}
+
+impl RunOutputs {
+    fn new(request: &RunRequest) -> Self {
+        Self {
+            plan_store: PlanStore::new(),
+            change_log: ChangeLog::new(),
+            artifacts: ArtifactStore::new(
+                request
+                    .artifacts_dir
+                    .clone()
+                    .unwrap_or_else(|| std::env::temp_dir().join("roci-artifacts"))
+                    .join(request.run_id.to_string()),
+            ),
+            response_metadata: None,
+        }
+    }
+
+    /// Announce the run's artifacts and changes, then its end, and build the
+    /// result carrying them.
+    fn finish(
+        &self,
+        request: &RunRequest,
+        emitter: &RunEventEmitter,
+        agent_emitter: &AgentEventEmitter,
+        end: RunEnd,
+        messages: &[ModelMessage],
+        run_usage: Usage,
+    ) -> RunResult {
+        let artifacts = flush_artifacts(emitter, &self.artifacts);
+        let changes = emit_change_summary(emitter, &self.change_log);
+        let (state, result) = match end {
+            RunEnd::Canceled => {
+                if let Some(health) = request.model_health.as_ref() {
+                    health.observe(HealthSignal::Canceled {
+                        key: ModelHealthKey::from_model(request.active_model()),
+                        observed_at_ms: now_ms(),
+                    });
+                }
+                (
+                    RunLifecycle::Canceled,
+                    RunResult::canceled_with_messages(messages.to_vec()),
+                )
+            }
+            RunEnd::Failed(error) => (
+                RunLifecycle::Failed {
+                    error: error.clone(),
+                },
+                RunResult::failed_with_messages(error, messages.to_vec()),
+            ),
+            RunEnd::BudgetExceeded(reading) => {
+                let error = budget_exceeded_message(&reading);
+                (
+                    RunLifecycle::BudgetExceeded {
+                        reading,
+                        error: error.clone(),
+                    },
+                    RunResult::budget_exceeded_with_messages(error, messages.to_vec()),
+                )
+            }
+            RunEnd::DeadlineExceeded(error) => {
+                let mut messages = History::new(messages.to_vec());
+                close_open_tool_calls(&mut messages);
+                let has_final_text = ends_with_final_text(&messages);
+                (
+                    RunLifecycle::DeadlineExceeded {
+                        error: error.clone(),
+                        has_final_text,
+                    },
+                    RunResult::deadline_exceeded_with_messages(
+                        error,
+                        messages.into_vec(),
+                        has_final_text,
+                    ),
+                )
+            }
+        };
+        emitter.emit(
+            RunEventStream::Lifecycle,
+            RunEventPayload::Lifecycle { state },
+        );
+        agent_emitter.abort_turn(&run_usage);
+        agent_emitter.emit(AgentEvent::AgentEnd {
+            run_id: request.run_id,
+            messages: result.messages.clone(),
         });
+        if roci_debug_enabled() {
+            tracing::debug!(
+                run_id = %request.run_id,
+                status = ?result.status,
+                error = ?result.error,
+                "roci run ended"
+            );
+        }
+        self.attach(request, result, changes, artifacts, run_usage)
     }
-    emitter.emit(
-        RunEventStream::Lifecycle,
-        RunEventPayload::Lifecycle {
-            state: RunLifecycle::Canceled,
-        },
-    );
-    agent_emitter.emit(AgentEvent::AgentEnd {
-        run_id: request.run_id,
-        messages: messages.to_vec(),
-    });
-    if roci_debug_enabled() {
-        tracing::debug!(run_id = %request.run_id, "roci run canceled");
+
+    fn attach(
+        &self,
+        request: &RunRequest,
+        result: RunResult,
+        changes: Vec<FileChange>,
+        artifacts: Vec<RunArtifact>,
+        run_usage: Usage,
+    ) -> RunResult {
+        result
+            .with_usage_delta(run_usage)
+            .with_plan(self.plan_store.steps())
+            .with_changes(changes)
+            .with_artifacts(artifacts)
+            .with_model(request.active_model().clone())
+            .with_response_metadata(self.response_metadata.clone())
     }
-    RunResult::canceled_with_messages(messages.to_vec()).with_usage_delta(run_usage)
 }
 
-fn failed_result(
-    request: &RunRequest,
-    emitter: &RunEventEmitter,
-    agent_emitter: &AgentEventEmitter,
-    messages: &[ModelMessage],
-    reason: impl Into<String>,
-    run_usage: Usage,
-) -> RunResult {
-    agent_emitter.emit(AgentEvent::AgentEnd {
-        run_id: request.run_id,
-        messages: messages.to_vec(),
-    });
-    emit_failed_result(emitter, reason, messages).with_usage_delta(run_usage)
+const DEADLINE_REACHED: &str = "run deadline reached";
+
+/// Why a phase stopped before finishing.
+enum Interruption {
+    Budget(BudgetReading),
+    Deadline,
+}
+
+/// Announce artifacts not yet reported after a tool result and return every
+/// artifact of the run.
+fn flush_artifacts(emitter: &RunEventEmitter, artifacts: &ArtifactStore) -> Vec<RunArtifact> {
+    emit_artifacts_added(emitter, artifacts.take_unannounced());
+    artifacts.artifacts()
+}
+
+/// Emit the run's net file changes ahead of its terminal lifecycle event.
+fn emit_change_summary(emitter: &RunEventEmitter, change_log: &ChangeLog) -> Vec<FileChange> {
+    let changes = change_log.changes();
+    if !changes.is_empty() {
+        emitter.emit(
+            RunEventStream::Tool,
+            RunEventPayload::ChangeSummary {
+                changes: changes.clone(),
+            },
+        );
+    }
+    changes
 }
 
 fn should_advance_candidate(
@@ -92,10 +244,12 @@ fn is_transient_for_advance(category: FailureCategory) -> bool {
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn emit_candidate_advancing(
     request: &RunRequest,
     emitter: &RunEventEmitter,
     retry_started_at: Instant,
+    retry_budget: &RetryBudget,
     from_index: usize,
     from: &crate::models::LanguageModel,
     failure_category: FailureCategory,
@@ -118,6 +272,8 @@ fn emit_candidate_advancing(
                 candidates_remaining: request.candidates_remaining(),
                 partial_output_seen,
                 next_action: RetryNextAction::AdvanceCandidate,
+                retries_remaining: retry_budget.retries_remaining(),
+                retry_delay_remaining_ms: retry_budget.delay_remaining_ms(),
             },
         },
     );
@@ -135,6 +291,7 @@ fn emit_retry_exhausted(
     request: &RunRequest,
     emitter: &RunEventEmitter,
     retry_started_at: Instant,
+    retry_budget: &RetryBudget,
     failure_category: FailureCategory,
     partial_output_seen: bool,
 ) {
@@ -156,6 +313,8 @@ fn emit_retry_exhausted(
                 candidates_remaining: request.candidates_remaining(),
                 partial_output_seen,
                 next_action: RetryNextAction::ReturnFailure,
+                retries_remaining: retry_budget.retries_remaining(),
+                retry_delay_remaining_ms: retry_budget.delay_remaining_ms(),
             },
         },
     );
@@ -241,6 +400,118 @@ async fn resolve_active_provider_api_key(
     Ok(())
 }
 
+/// Everything that handles a run's result after the run builds it.
+pub(super) struct ResultDelivery {
+    /// Queue feeding the caller's event sinks, drained before the result.
+    pub(super) dispatcher: Option<EventDispatcher>,
+    /// The run handle's end of the result channel.
+    pub(super) handle_tx: oneshot::Sender<RunResult>,
+    /// The handle's status, set to the result's just before it is sent.
+    pub(super) status: SharedRunStatus,
+    pub(super) scratch: ScratchDir,
+    /// Keep the scratch directory and report its path on the result.
+    pub(super) keep_scratch: bool,
+    pub(super) webhooks: Option<RunWebhooks>,
+    pub(super) transcript: Option<TranscriptWriter>,
+    pub(super) payload_log: Option<PayloadLog>,
+}
+
+impl ResultDelivery {
+    /// Hand the run's result to its handle once queued events have reached
+    /// the sinks, so callers observe every event before the result.
+    ///
+    /// The run's scratch directory is removed first unless `keep_scratch` is
+    /// set, in which case its path is reported on the result. Payload sizes
+    /// the LLM phase logged, if any, are attached first. The transcript, if
+    /// any, is closed with the run's status. Webhooks are delivered last, so
+    /// their outcomes ride on the result too.
+    pub(super) async fn deliver(self, result_rx: oneshot::Receiver<RunResult>) {
+        let Self {
+            dispatcher,
+            handle_tx,
+            status,
+            scratch,
+            keep_scratch,
+            webhooks,
+            transcript,
+            payload_log,
+        } = self;
+        let result = result_rx.await;
+        let scratch_dir = scratch.finish(keep_scratch);
+        let stats = match dispatcher {
+            Some(dispatcher) => Some(dispatcher.finish().await),
+            None => None,
+        };
+        if let Ok(result) = result {
+            let result = match payload_log {
+                Some(log) => result.with_payload_sizes(std::mem::take(&mut *log.lock().unwrap())),
+                None => result,
+            };
+            let result = match stats {
+                Some(stats) => result.with_emitter_stats(stats),
+                None => result,
+            };
+            let result = match scratch_dir {
+                Some(path) => result.with_scratch_dir(path),
+                None => result,
+            };
+            if let Some(transcript) = transcript {
+                transcript.record_finish(&result);
+            }
+            let result = match webhooks {
+                Some(webhooks) => {
+                    let deliveries = webhooks.deliver(&result).await;
+                    result.with_webhook_deliveries(deliveries)
+                }
+                None => result,
+            };
+            status.set(result.status);
+            let _ = handle_tx.send(result);
+        }
+    }
+}
+
+/// Request and response sizes of each provider call in a run, in call order.
+pub(super) type PayloadLog = Arc<Mutex<Vec<PayloadSizes>>>;
+
+fn tool_definitions(tools: &[Arc<dyn Tool>]) -> Option<Vec<ToolDefinition>> {
+    if tools.is_empty() {
+        return None;
+    }
+    Some(
+        tools
+            .iter()
+            .map(|t| ToolDefinition::new(t.name(), t.prompt(), t.parameters().schema.clone()))
+            .collect(),
+    )
+}
+
+/// Names added and removed going from `previous` to `next`, in `next` and
+/// `previous` order respectively.
+fn tool_name_changes(
+    previous: &[Arc<dyn Tool>],
+    next: &[Arc<dyn Tool>],
+) -> (Vec<String>, Vec<String>) {
+    let names = |tools: &[Arc<dyn Tool>]| {
+        tools
+            .iter()
+            .map(|tool| tool.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    let (previous, next) = (names(previous), names(next));
+    let added = next
+        .iter()
+        .filter(|name| !previous.contains(name))
+        .cloned()
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|name| !next.contains(name))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
 fn now_ms() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -254,16 +525,50 @@ fn now_ms() -> u64 {
 #[async_trait]
 impl Runner for LoopRunner {
     async fn start(&self, mut request: RunRequest) -> Result<RunHandle, crate::error::RociError> {
+        if let Some(tasks) = request.task_list.take() {
+            return start_task_list(self, request, tasks).await;
+        }
         validate_retry_mode(request.retry_mode)?;
+        validate_budget(&request)?;
+        validate_deadline(&request)?;
         request.workspace_root = request
             .workspace_root
             .as_deref()
             .map(canonical_workspace_root)
             .transpose()?;
+        apply_plugins(&mut request)?;
+        validate_runner_limits(&request)?;
         request.tools = ToolCatalog::from_tools(request.tools, ToolOrigin::Custom)?
             .resolve(&request.tool_visibility_policy);
-        let (handle, mut abort_rx, result_tx, mut input_rx) = RunHandle::new(request.run_id);
-        let config = self.config.clone();
+        validate_prefill(&request)?;
+        validate_final_response_schema(&request)?;
+        validate_message_lints(&request)?;
+        let admission = self.admission.admit(request.run_id)?;
+        let dispatcher = EventDispatcher::install(&mut request);
+        if let Some(transcript) = request.transcript.clone() {
+            transcript.attach(&mut request);
+        }
+        let (handle, mut abort_rx, handle_result_tx, mut input_rx) = RunHandle::new(request.run_id);
+        let status = handle.shared_status();
+        if matches!(admission, Admission::Queued(_)) {
+            status.set(RunStatus::Queued);
+        }
+        let (result_tx, result_rx) = oneshot::channel();
+        // Intermediate files written by tools; created on first use.
+        let scratch = ScratchDir::for_run(request.run_id);
+        let payload_log = PayloadLog::default();
+        let delivery = ResultDelivery {
+            dispatcher,
+            handle_tx: handle_result_tx,
+            status: status.clone(),
+            scratch: scratch.clone(),
+            keep_scratch: request.keep_scratch,
+            webhooks: RunWebhooks::from_request(&request),
+            transcript: request.transcript.clone(),
+            payload_log: Some(payload_log.clone()),
+        };
+        tokio::spawn(delivery.deliver(result_rx));
+        let config = self.config.snapshot();
         let provider_factory = self.provider_factory.clone();
 
         tokio::spawn(async move {
@@ -275,8 +580,68 @@ impl Runner for LoopRunner {
                 );
             }
             let limits = RunnerLimits::from_request(&request);
-            let emitter = RunEventEmitter::new(request.run_id, request.event_sink.clone());
-            let agent_emitter = AgentEventEmitter::new(request.agent_event_sink.clone());
+            // Run identity is stamped once; each event only clones the Arc'd tags.
+            let event_model = Some(request.active_model().to_string());
+            let event_tags = EventTags::new(request.event_tags.clone());
+            let emitter = RunEventEmitter::new(request.run_id, request.event_sink.clone())
+                .with_identity(event_model.clone(), event_tags.clone())
+                .with_sequence(request.event_sequence.clone())
+                .with_dry_run(request.dry_run);
+            let agent_emitter =
+                AgentEventEmitter::new(request.run_id, request.agent_event_sink.clone())
+                    .with_identity(event_model, event_tags)
+                    .with_sequence(request.event_sequence.clone())
+                    .with_stored_messages(StoredMessageFilter::new(
+                        request.response_filter.clone(),
+                        request.filter_tool_results,
+                    ));
+            let mut outputs = RunOutputs::new(&request);
+
+            // Holds the run's admission slot until the task ends.
+            let _permit = match admission {
+                Admission::Running(permit) => permit,
+                Admission::Queued(ticket) => {
+                    emitter.emit(
+                        RunEventStream::Lifecycle,
+                        RunEventPayload::Lifecycle {
+                            state: RunLifecycle::Queued {
+                                position: ticket.position(),
+                            },
+                        },
+                    );
+                    let waited = tokio::select! {
+                        _ = &mut abort_rx => None,
+                        granted = ticket.wait() => Some(granted),
+                    };
+                    match waited {
+                        Some(Ok(permit)) => permit,
+                        Some(Err(reason)) => {
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::Failed(reason),
+                                &request.messages,
+                                Usage::default(),
+                            ));
+                            return;
+                        }
+                        None => {
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::Canceled,
+                                &request.messages,
+                                Usage::default(),
+                            ));
+                            return;
+                        }
+                    }
+                }
+            };
+            status.set(RunStatus::Running);
+
             emitter.emit(
                 RunEventStream::Lifecycle,
                 RunEventPayload::Lifecycle {
@@ -294,12 +659,14 @@ impl Runner for LoopRunner {
                     max_tool_failures = limits.max_tool_failures,
                     iteration_extension = limits.iteration_extension,
                     max_iteration_extensions = limits.max_iteration_extensions,
+                    max_total_retries = limits.max_total_retries,
+                    max_total_retry_delay_ms = limits.max_total_retry_delay_ms,
                     "roci runner limits"
                 );
             }
 
-            let mut messages = request.messages.clone();
-            for message in &messages {
+            let mut messages = History::new(request.messages.clone());
+            for message in messages.iter() {
                 emit_message_lifecycle(&agent_emitter, message);
             }
 
@@ -308,61 +675,94 @@ impl Runner for LoopRunner {
             // Anchor from the last successful provider call for exact-prefix
             // token estimation in preflight budget checks.
             let mut exact_anchor: Option<ExactUsageAnchor> = None;
+            let mut budget = BudgetTracker::new(request.budget.clone());
+            // Provider retries are capped across iterations, not per call.
+            let mut retry_budget = RetryBudget::new(&limits).with_deadline(request.deadline);
+            let mut deadline = RunDeadline::new(&request);
+            // Applies to the first assistant reply of the run only.
+            let mut pending_prefill = request.prefill.clone();
+            // Only the model the run starts with is warmed up.
+            let mut pending_warm_up = request.warm_up;
+            // Steers the reply that ends the tool loop into the final schema.
+            let mut final_response = FinalResponse::default();
+            let mut final_reply = String::new();
+            let no_tool_defs = None;
+            // Fills in and disambiguates provider tool-call IDs for the run.
+            let tool_call_ids =
+                provider::ToolCallIdAllocator::new(&request.run_id.simple().to_string()[..8]);
             let mut active_provider: Option<(usize, Box<dyn provider::ModelProvider>)> = None;
             let mut retry_started_at = Instant::now();
 
             if let Err(err) = provider::validate_transport_preference(request.transport.as_deref())
             {
-                let _ = result_tx.send(failed_result(
+                let _ = result_tx.send(outputs.finish(
                     &request,
                     &emitter,
                     &agent_emitter,
+                    RunEnd::Failed(err.to_string()),
                     &messages,
-                    err.to_string(),
                     run_usage,
                 ));
                 return;
             }
 
-            let tool_defs: Option<Vec<ToolDefinition>> = if request.tools.is_empty() {
-                None
-            } else {
-                Some(
-                    request
-                        .tools
-                        .iter()
-                        .map(|t| ToolDefinition {
-                            name: t.name().to_string(),
-                            description: t.prompt().to_string(),
-                            parameters: t.parameters().schema.clone(),
-                        })
-                        .collect(),
-                )
-            };
+            let mut tool_defs = tool_definitions(&request.tools);
+            // Tools the provider's limits last forced out, if any.
+            let mut truncated_tools: Vec<String> = Vec::new();
 
             let mut iteration = 0usize;
             let mut consecutive_failed_iterations = 0usize;
+            let mut tool_invocations = HashMap::new();
             let mut max_iterations = limits.max_iterations;
             let mut iteration_extensions_used = 0usize;
-            let mut turn_index = 0usize;
-            let run_cancel_token = CancellationToken::new();
+            // Replaced after a deadline interrupt so the wrap-up call can run.
+            let mut run_cancel_token = CancellationToken::new();
 
             'outer: loop {
                 'inner: loop {
+                    if let Some(reading) = budget.exceeded(&emitter, &agent_emitter, &run_usage) {
+                        let _ = result_tx.send(outputs.finish(
+                            &request,
+                            &emitter,
+                            &agent_emitter,
+                            RunEnd::BudgetExceeded(reading),
+                            &messages,
+                            run_usage,
+                        ));
+                        return;
+                    }
+
+                    if deadline.due() {
+                        if !deadline.begin_wrap_up() {
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::DeadlineExceeded(DEADLINE_REACHED.to_string()),
+                                &messages,
+                                run_usage,
+                            ));
+                            return;
+                        }
+                        close_open_tool_calls(&mut messages);
+                        let prompt = wrap_up_prompt();
+                        emit_message_lifecycle(&agent_emitter, &prompt);
+                        messages.push(prompt);
+                    }
+
                     iteration += 1;
-                    turn_index += 1;
-                    agent_emitter.emit(AgentEvent::TurnStart {
-                        run_id: request.run_id,
-                        turn_index,
-                    });
+                    if let Some(transcript) = &request.transcript {
+                        transcript.record_iteration(iteration);
+                    }
+                    agent_emitter.begin_turn(&run_usage);
 
                     if let Err(err) = resolve_active_provider_api_key(&mut request, &config).await {
-                        let _ = result_tx.send(failed_result(
+                        let _ = result_tx.send(outputs.finish(
                             &request,
                             &emitter,
                             &agent_emitter,
+                            RunEnd::Failed(err.to_string()),
                             &messages,
-                            err.to_string(),
                             run_usage,
                         ));
                         return;
@@ -375,12 +775,12 @@ impl Runner for LoopRunner {
                             Ok(provider) => Some((request.active_candidate_index, provider)),
                             Err(err) => {
                                 observe_failure(&request, failure_category_for_error(&err));
-                                let _ = result_tx.send(failed_result(
+                                let _ = result_tx.send(outputs.finish(
                                     &request,
                                     &emitter,
                                     &agent_emitter,
+                                    RunEnd::Failed(err.to_string()),
                                     &messages,
-                                    err.to_string(),
                                     run_usage,
                                 ));
                                 return;
@@ -394,18 +794,30 @@ impl Runner for LoopRunner {
                         .1
                         .as_ref();
 
-                    if iteration > max_iterations {
+                    if pending_warm_up {
+                        pending_warm_up = false;
+                        tokio::select! {
+                            _ = &mut abort_rx => {
+                                run_cancel_token.cancel();
+                                let _ = result_tx.send(outputs.finish(&request, &emitter, &agent_emitter, RunEnd::Canceled, &messages, run_usage));
+                                return;
+                            }
+                            _ = warm_up_provider(provider, &emitter, request.heartbeat_interval) => {}
+                        }
+                    }
+
+                    if iteration > max_iterations && !deadline.wrapping_up() {
                         if iteration_extensions_used >= limits.max_iteration_extensions {
                             let reason = format!(
                                 "tool loop exceeded max iterations (max_iterations={}, extensions_used={})",
                                 max_iterations, iteration_extensions_used
                             );
-                            let _ = result_tx.send(failed_result(
+                            let _ = result_tx.send(outputs.finish(
                                 &request,
                                 &emitter,
                                 &agent_emitter,
+                                RunEnd::Failed(reason),
                                 &messages,
-                                reason,
                                 run_usage,
                             ));
                             return;
@@ -427,13 +839,7 @@ impl Runner for LoopRunner {
                         let decision = tokio::select! {
                             _ = &mut abort_rx => {
                                 run_cancel_token.cancel();
-                                let _ = result_tx.send(canceled_result(
-                                    &request,
-                                    &emitter,
-                                    &agent_emitter,
-                                    &messages,
-                                    run_usage,
-                                ));
+                                let _ = result_tx.send(outputs.finish(&request, &emitter, &agent_emitter, RunEnd::Canceled, &messages, run_usage));
                                 return;
                             }
                             decision = &mut approval => decision,
@@ -456,10 +862,11 @@ impl Runner for LoopRunner {
                                 }
                             }
                             ApprovalDecision::Cancel => {
-                                let _ = result_tx.send(canceled_result(
+                                let _ = result_tx.send(outputs.finish(
                                     &request,
                                     &emitter,
                                     &agent_emitter,
+                                    RunEnd::Canceled,
                                     &messages,
                                     run_usage,
                                 ));
@@ -469,12 +876,12 @@ impl Runner for LoopRunner {
                                 let reason = format!(
                                     "tool loop exceeded max iterations (max_iterations={max_iterations}); continuation declined"
                                 );
-                                let _ = result_tx.send(failed_result(
+                                let _ = result_tx.send(outputs.finish(
                                     &request,
                                     &emitter,
                                     &agent_emitter,
+                                    RunEnd::Failed(reason),
                                     &messages,
-                                    reason,
                                     run_usage,
                                 ));
                                 return;
@@ -482,35 +889,172 @@ impl Runner for LoopRunner {
                         }
                     }
 
-                    let (iteration_text, tool_calls) = match run_llm_phase(LlmPhaseArgs {
-                        request: &request,
-                        provider,
-                        tool_defs: &tool_defs,
-                        messages: &mut messages,
-                        emitter: &emitter,
-                        agent_emitter: &agent_emitter,
-                        input_rx: &mut input_rx,
-                        abort_rx: &mut abort_rx,
-                        run_cancel_token: &run_cancel_token,
-                        iteration,
-                        run_usage: &mut run_usage,
-                        exact_anchor: &mut exact_anchor,
-                        retry_started_at: &retry_started_at,
-                    })
-                    .await
-                    {
+                    if let Some(tools_provider) = request.tools_provider.clone() {
+                        let tools = ToolCatalog::from_tools(
+                            tools_provider(iteration).await,
+                            ToolOrigin::Custom,
+                        )
+                        .map(|catalog| catalog.resolve(&request.tool_visibility_policy));
+                        let tools = match tools {
+                            Ok(tools) => tools,
+                            Err(err) => {
+                                let _ = result_tx.send(outputs.finish(
+                                    &request,
+                                    &emitter,
+                                    &agent_emitter,
+                                    RunEnd::Failed(err.to_string()),
+                                    &messages,
+                                    run_usage,
+                                ));
+                                return;
+                            }
+                        };
+                        let (added, removed) = tool_name_changes(&request.tools, &tools);
+                        if !added.is_empty() || !removed.is_empty() {
+                            emitter.emit(
+                                RunEventStream::Tool,
+                                RunEventPayload::ToolsUpdated { added, removed },
+                            );
+                        }
+                        request.tools = tools;
+                        tool_defs = tool_definitions(&request.tools);
+                    }
+
+                    let response_format = final_response
+                        .next_call_format(request.final_response_schema.as_ref(), provider);
+                    let call_tool_defs =
+                        if final_response.withholds_tools() || deadline.wrapping_up() {
+                            &no_tool_defs
+                        } else {
+                            &tool_defs
+                        };
+                    let tool_limits = config
+                        .tool_limits_for(provider.provider_name())
+                        .unwrap_or_else(|| provider.tool_limits());
+                    let fitted = match fit_tools(
+                        &request.tools,
+                        call_tool_defs,
+                        tool_limits,
+                        request.tool_overflow,
+                        &format!("{}:{}", provider.provider_name(), provider.model_id()),
+                    ) {
+                        Ok(fitted) => fitted,
+                        Err(err) => {
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::Failed(err.to_string()),
+                                &messages,
+                                run_usage,
+                            ));
+                            return;
+                        }
+                    };
+                    let dropped = fitted
+                        .as_ref()
+                        .map(|fitted| fitted.dropped.clone())
+                        .unwrap_or_default();
+                    if dropped != truncated_tools {
+                        if !dropped.is_empty() {
+                            emitter.emit(
+                                RunEventStream::Tool,
+                                RunEventPayload::ToolsTruncated {
+                                    dropped: dropped.clone(),
+                                    limits: tool_limits,
+                                },
+                            );
+                        }
+                        truncated_tools = dropped;
+                    }
+                    let call_tool_defs = match &fitted {
+                        Some(fitted) => &fitted.definitions,
+                        None => call_tool_defs,
+                    };
+                    let wrap_up = deadline.wrapping_up().then(|| wrap_up_request(&request));
+                    let llm_outcome = tokio::select! {
+                        outcome = run_llm_phase(LlmPhaseArgs {
+                            request: wrap_up.as_ref().unwrap_or(&request),
+                            config: &config,
+                            provider,
+                            tool_defs: call_tool_defs,
+                            messages: &mut messages,
+                            emitter: &emitter,
+                            agent_emitter: &agent_emitter,
+                            input_rx: &mut input_rx,
+                            abort_rx: &mut abort_rx,
+                            run_cancel_token: &run_cancel_token,
+                            iteration,
+                            tool_call_ids: &tool_call_ids,
+                            run_usage: &mut run_usage,
+                            response_metadata: &mut outputs.response_metadata,
+                            payload_log: &payload_log,
+                            exact_anchor: &mut exact_anchor,
+                            retry_started_at: &retry_started_at,
+                            retry_budget: &mut retry_budget,
+                            prefill: pending_prefill.as_deref(),
+                            response_format,
+                        }) => Ok(outcome),
+                        reading = budget.wall_clock_expired(&emitter, &agent_emitter) => {
+                            Err(Interruption::Budget(reading))
+                        }
+                        _ = deadline.interrupted() => Err(Interruption::Deadline),
+                    };
+                    let llm_outcome = match llm_outcome {
+                        Ok(outcome) => outcome,
+                        Err(Interruption::Deadline) => {
+                            run_cancel_token.cancel();
+                            run_cancel_token = CancellationToken::new();
+                            agent_emitter.abort_turn(&run_usage);
+                            continue 'inner;
+                        }
+                        Err(Interruption::Budget(reading)) => {
+                            run_cancel_token.cancel();
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::BudgetExceeded(reading),
+                                &messages,
+                                run_usage,
+                            ));
+                            return;
+                        }
+                    };
+                    budget.record_usage(
+                        &emitter,
+                        &agent_emitter,
+                        request.active_model(),
+                        &run_usage,
+                    );
+
+                    let (iteration_text, tool_calls, images) = match llm_outcome {
                         LlmPhaseOutcome::Ready {
                             iteration_text,
                             tool_calls,
-                        } => (iteration_text, tool_calls),
+                            images,
+                        } => {
+                            pending_prefill = None;
+                            agent_emitter.record_turn_response(
+                                &iteration_text,
+                                tool_calls.len(),
+                                &run_usage,
+                            );
+                            if request.final_response_schema.is_some() {
+                                final_reply.clone_from(&iteration_text);
+                            }
+                            (iteration_text, tool_calls, images)
+                        }
                         LlmPhaseOutcome::Canceled { assistant_message } => {
                             if let Some(message) = assistant_message {
-                                messages.push(message);
+                                agent_emitter.record_turn_response(&message.text(), 0, &run_usage);
+                                messages.push(agent_emitter.stored_messages().assistant(message));
                             }
-                            let _ = result_tx.send(canceled_result(
+                            let _ = result_tx.send(outputs.finish(
                                 &request,
                                 &emitter,
                                 &agent_emitter,
+                                RunEnd::Canceled,
                                 &messages,
                                 run_usage,
                             ));
@@ -523,7 +1067,19 @@ impl Runner for LoopRunner {
                         } => {
                             let partial_output_seen = assistant_message.is_some();
                             if let Some(message) = assistant_message {
-                                messages.push(message);
+                                agent_emitter.record_turn_response(&message.text(), 0, &run_usage);
+                                messages.push(agent_emitter.stored_messages().assistant(message));
+                            }
+                            if retry_budget.deadline_reached() || deadline.wrapping_up() {
+                                let _ = result_tx.send(outputs.finish(
+                                    &request,
+                                    &emitter,
+                                    &agent_emitter,
+                                    RunEnd::DeadlineExceeded(reason),
+                                    &messages,
+                                    run_usage,
+                                ));
+                                return;
                             }
                             if should_advance_candidate(
                                 &request,
@@ -540,6 +1096,7 @@ impl Runner for LoopRunner {
                                     &request,
                                     &emitter,
                                     retry_started_at,
+                                    &retry_budget,
                                     from_index,
                                     &from,
                                     failure_category,
@@ -563,56 +1120,146 @@ impl Runner for LoopRunner {
                                 &request,
                                 &emitter,
                                 retry_started_at,
+                                &retry_budget,
                                 failure_category,
                                 partial_output_seen,
                             );
                             observe_retry_exhausted(&request, failure_category);
-                            let _ = result_tx.send(failed_result(
+                            let _ = result_tx.send(outputs.finish(
                                 &request,
                                 &emitter,
                                 &agent_emitter,
+                                RunEnd::Failed(reason),
                                 &messages,
-                                reason,
                                 run_usage,
                             ));
                             return;
                         }
+                        LlmPhaseOutcome::FirstTokenSloMissed { waited, slo } => {
+                            let from_index = request.active_candidate_index;
+                            let to_index = from_index + 1;
+                            let from = request.active_model().clone();
+                            request.active_candidate_index = to_index;
+                            let to = request.active_model().clone();
+                            emitter.emit(
+                                RunEventStream::System,
+                                RunEventPayload::FirstTokenSloMissed {
+                                    from: from.to_string(),
+                                    to: to.to_string(),
+                                    waited_ms: u64::try_from(waited.as_millis())
+                                        .unwrap_or(u64::MAX),
+                                    slo_ms: u64::try_from(slo.as_millis()).unwrap_or(u64::MAX),
+                                },
+                            );
+                            if let Some(health) = request.model_health.as_ref() {
+                                health.observe(HealthSignal::CandidateAdvanced {
+                                    from_index,
+                                    to_index,
+                                    from: ModelHealthKey::from_model(&from),
+                                    to: ModelHealthKey::from_model(&to),
+                                    reason: FailureCategory::Timeout,
+                                    observed_at_ms: now_ms(),
+                                });
+                            }
+                            retry_started_at = Instant::now();
+                            active_provider = None;
+                            continue 'inner;
+                        }
                     };
 
-                    match run_tool_phase(ToolPhaseArgs {
-                        request: &request,
-                        limits,
-                        messages: &mut messages,
-                        emitter: &emitter,
-                        agent_emitter: &agent_emitter,
-                        abort_rx: &mut abort_rx,
-                        run_cancel_token: &run_cancel_token,
-                        turn_index,
-                        tool_calls: &tool_calls,
-                        iteration_text,
-                        consecutive_failed_iterations: &mut consecutive_failed_iterations,
-                    })
-                    .await
-                    {
+                    if deadline.wrapping_up() {
+                        // Tools were withheld; any calls the model made anyway
+                        // are dropped.
+                        let assistant_message = (!iteration_text.is_empty() || !images.is_empty())
+                            .then(|| {
+                                agent_emitter.stored_messages().assistant(
+                                    assistant_message_with_images(&iteration_text, &images, &[]),
+                                )
+                            });
+                        if let Some(message) = &assistant_message {
+                            messages.push(message.clone());
+                        }
+                        agent_emitter.end_turn(assistant_message, Vec::new());
+                        let _ = result_tx.send(outputs.finish(
+                            &request,
+                            &emitter,
+                            &agent_emitter,
+                            RunEnd::DeadlineExceeded(DEADLINE_REACHED.to_string()),
+                            &messages,
+                            run_usage,
+                        ));
+                        return;
+                    }
+
+                    let tool_outcome = tokio::select! {
+                        outcome = run_tool_phase(ToolPhaseArgs {
+                            request: &request,
+                            limits,
+                            messages: &mut messages,
+                            emitter: &emitter,
+                            agent_emitter: &agent_emitter,
+                            plan_store: &outputs.plan_store,
+                            change_log: &outputs.change_log,
+                            artifacts: &outputs.artifacts,
+                            scratch: &scratch,
+                            abort_rx: &mut abort_rx,
+                            run_cancel_token: &run_cancel_token,
+                            tool_calls: &tool_calls,
+                            iteration,
+                            iteration_text,
+                            images,
+                            consecutive_failed_iterations: &mut consecutive_failed_iterations,
+                            tool_invocations: &mut tool_invocations,
+                        }) => Ok(outcome),
+                        reading = budget.wall_clock_expired(&emitter, &agent_emitter) => {
+                            Err(Interruption::Budget(reading))
+                        }
+                        _ = deadline.interrupted() => Err(Interruption::Deadline),
+                    };
+                    let tool_outcome = match tool_outcome {
+                        Ok(outcome) => outcome,
+                        Err(Interruption::Deadline) => {
+                            // Interrupt tools still running in the batch.
+                            run_cancel_token.cancel();
+                            run_cancel_token = CancellationToken::new();
+                            agent_emitter.abort_turn(&run_usage);
+                            continue 'inner;
+                        }
+                        Err(Interruption::Budget(reading)) => {
+                            // Interrupt tools still running in the batch.
+                            run_cancel_token.cancel();
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::BudgetExceeded(reading),
+                                &messages,
+                                run_usage,
+                            ));
+                            return;
+                        }
+                    };
+                    match tool_outcome {
                         ToolPhaseOutcome::ContinueInner => continue 'inner,
                         ToolPhaseOutcome::BreakInner => break 'inner,
                         ToolPhaseOutcome::Canceled => {
-                            let _ = result_tx.send(canceled_result(
+                            let _ = result_tx.send(outputs.finish(
                                 &request,
                                 &emitter,
                                 &agent_emitter,
+                                RunEnd::Canceled,
                                 &messages,
                                 run_usage,
                             ));
                             return;
                         }
                         ToolPhaseOutcome::Failed(reason) => {
-                            let _ = result_tx.send(failed_result(
+                            let _ = result_tx.send(outputs.finish(
                                 &request,
                                 &emitter,
                                 &agent_emitter,
+                                RunEnd::Failed(reason),
                                 &messages,
-                                reason,
                                 run_usage,
                             ));
                             return;
@@ -631,6 +1278,34 @@ impl Runner for LoopRunner {
                     }
                 }
 
+                let mut structured_output = None;
+                if let (Some(format), Some((_, provider))) = (
+                    request.final_response_schema.as_ref(),
+                    active_provider.as_ref(),
+                ) {
+                    match final_response.review(format, provider.as_ref(), &final_reply) {
+                        FinalResponseReview::Accept(value) => structured_output = Some(value),
+                        FinalResponseReview::Retry(message) => {
+                            emit_message_lifecycle(&agent_emitter, &message);
+                            messages.push(message);
+                            continue 'outer;
+                        }
+                        FinalResponseReview::Fail(reason) => {
+                            let _ = result_tx.send(outputs.finish(
+                                &request,
+                                &emitter,
+                                &agent_emitter,
+                                RunEnd::Failed(reason),
+                                &messages,
+                                run_usage,
+                            ));
+                            return;
+                        }
+                    }
+                }
+
+                let artifacts = flush_artifacts(&emitter, &outputs.artifacts);
+                let changes = emit_change_summary(&emitter, &outputs.change_log);
                 emitter.emit(
                     RunEventStream::Lifecycle,
                     RunEventPayload::Lifecycle {
@@ -640,10 +1315,12 @@ impl Runner for LoopRunner {
                 observe_success(&request);
                 agent_emitter.emit(AgentEvent::AgentEnd {
                     run_id: request.run_id,
-                    messages: messages.clone(),
+                    messages: messages.to_vec(),
                 });
-                let _ = result_tx
-                    .send(RunResult::completed_with_messages(messages).with_usage_delta(run_usage));
+                let result = RunResult::completed_with_messages(messages.into_vec());
+                let mut result = outputs.attach(&request, result, changes, artifacts, run_usage);
+                result.structured_output = structured_output;
+                let _ = result_tx.send(result);
                 if roci_debug_enabled() {
                     tracing::debug!(run_id = %request.run_id, "roci run completed");
                 }
@@ -1,22 +1,32 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 
-use crate::types::{AgentToolCall, AgentToolResult, ModelMessage};
+use crate::tools::{ArtifactStore, ChangeLog, PlanStore, ScratchDir, ToolMessageQueue};
+use crate::types::{AgentToolCall, AgentToolResult, ImageContent, ModelMessage};
 
 use super::super::control::{
     approval_allows_execution, resolve_approval, AgentEventEmitter, RunEventEmitter,
 };
+use super::super::heartbeat::{with_heartbeat, Heartbeat};
+use super::super::history::History;
 use super::super::limits::RunnerLimits;
-use super::super::message_events::assistant_message_snapshot;
+use super::super::message_events::assistant_message_with_images;
 use super::super::message_events::emit_message_lifecycle;
 use super::super::tooling::{
-    append_skipped_tool_call, append_tool_result, apply_pre_tool_use_hook, canceled_tool_result,
-    declined_tool_result, emit_tool_execution_end, emit_tool_execution_start,
+    append_emitted_messages, append_skipped_tool_call, append_tool_result, apply_pre_tool_use_hook,
+    canceled_tool_result, declined_tool_result, emit_tool_execution_end, emit_tool_execution_start,
     execute_parallel_tool_calls, execute_tool_call, finalize_tool_result, resolve_tool_call,
-    safety_plan_for_finalized_call, validate_finalized_tool_call, ResolvedToolCall,
-    ToolExecutionInputs, ToolExecutionOutcome,
+    safety_plan_for_finalized_call, validate_finalized_tool_call, BatchHookContexts,
+    ResolvedToolCall, ToolExecutionInputs, ToolExecutionOutcome,
+};
+use super::super::{
+    ApprovalDecision, HookContext, PreTurnExecutionHookPayload, PreTurnExecutionHookResult,
+    RunRequest,
 };
-use super::super::{AgentEvent, ApprovalDecision, RunRequest};
+use crate::agent_loop::HeartbeatPhase;
 
 pub(super) enum ToolPhaseOutcome {
     ContinueInner,
@@ -28,15 +38,79 @@ pub(super) enum ToolPhaseOutcome {
 pub(super) struct ToolPhaseArgs<'a> {
     pub(super) request: &'a RunRequest,
     pub(super) limits: RunnerLimits,
-    pub(super) messages: &'a mut Vec<ModelMessage>,
+    pub(super) messages: &'a mut History,
     pub(super) emitter: &'a RunEventEmitter,
     pub(super) agent_emitter: &'a AgentEventEmitter,
+    pub(super) plan_store: &'a PlanStore,
+    pub(super) change_log: &'a ChangeLog,
+    pub(super) artifacts: &'a ArtifactStore,
+    pub(super) scratch: &'a ScratchDir,
     pub(super) abort_rx: &'a mut oneshot::Receiver<()>,
     pub(super) run_cancel_token: &'a CancellationToken,
-    pub(super) turn_index: usize,
     pub(super) tool_calls: &'a [AgentToolCall],
+    pub(super) iteration: usize,
     pub(super) iteration_text: String,
+    pub(super) images: Vec<ImageContent>,
     pub(super) consecutive_failed_iterations: &'a mut usize,
+    /// Calls of each tool requested earlier in the run.
+    pub(super) tool_invocations: &'a mut HashMap<String, usize>,
+}
+
+/// Abort the tool batch, keeping its progress for the aborted `TurnEnd`.
+fn canceled(
+    agent_emitter: &AgentEventEmitter,
+    assistant_message: Option<&ModelMessage>,
+    tool_results: &[AgentToolResult],
+) -> ToolPhaseOutcome {
+    agent_emitter.record_turn_progress(assistant_message, tool_results);
+    ToolPhaseOutcome::Canceled
+}
+
+enum TurnDecision {
+    Continue,
+    ModifyCalls(Vec<AgentToolCall>),
+    Abort { feedback: String },
+    Canceled,
+    Failed(String),
+}
+
+/// Run the `pre_turn_execution` hook on the proposed turn.
+#[allow(clippy::too_many_arguments)]
+async fn pre_turn_execution(
+    request: &RunRequest,
+    agent_emitter: &AgentEventEmitter,
+    messages: &[ModelMessage],
+    abort_rx: &mut oneshot::Receiver<()>,
+    run_cancel_token: &CancellationToken,
+    tool_calls: &[AgentToolCall],
+    iteration: usize,
+    iteration_text: &str,
+) -> TurnDecision {
+    let Some(hook) = request.hooks.pre_turn_execution.as_ref() else {
+        return TurnDecision::Continue;
+    };
+    let payload = PreTurnExecutionHookPayload {
+        run_id: request.run_id,
+        turn_index: agent_emitter.turn_index(),
+        iteration,
+        text: iteration_text.to_string(),
+        tool_calls: tool_calls.to_vec(),
+        messages: Arc::from(messages),
+        cancellation_token: run_cancel_token.child_token(),
+    };
+    let result = tokio::select! {
+        _ = &mut *abort_rx => {
+            run_cancel_token.cancel();
+            return TurnDecision::Canceled;
+        }
+        result = hook(payload) => result,
+    };
+    match result {
+        Ok(PreTurnExecutionHookResult::Continue) => TurnDecision::Continue,
+        Ok(PreTurnExecutionHookResult::ModifyCalls(calls)) => TurnDecision::ModifyCalls(calls),
+        Ok(PreTurnExecutionHookResult::AbortTurn { feedback }) => TurnDecision::Abort { feedback },
+        Err(err) => TurnDecision::Failed(format!("pre_turn_execution hook failed: {err}")),
+    }
 }
 
 pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome {
@@ -46,14 +120,58 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         messages,
         emitter,
         agent_emitter,
+        plan_store,
+        change_log,
+        artifacts,
+        scratch,
         abort_rx,
         run_cancel_token,
-        turn_index,
         tool_calls,
+        iteration,
         iteration_text,
+        images,
         consecutive_failed_iterations,
+        tool_invocations,
     } = args;
 
+    let modified_calls;
+    let tool_calls = match pre_turn_execution(
+        request,
+        agent_emitter,
+        messages,
+        abort_rx,
+        run_cancel_token,
+        tool_calls,
+        iteration,
+        &iteration_text,
+    )
+    .await
+    {
+        TurnDecision::Continue => tool_calls,
+        TurnDecision::ModifyCalls(calls) => {
+            modified_calls = calls;
+            modified_calls.as_slice()
+        }
+        TurnDecision::Abort { feedback } => {
+            // The calls never ran, so the stored turn keeps only its text.
+            let assistant_message = (!iteration_text.is_empty() || !images.is_empty()).then(|| {
+                agent_emitter
+                    .stored_messages()
+                    .assistant(assistant_message_with_images(&iteration_text, &images, &[]))
+            });
+            if let Some(message) = &assistant_message {
+                messages.push(message.clone());
+            }
+            agent_emitter.end_turn(assistant_message, Vec::new());
+            let feedback = ModelMessage::user(feedback);
+            emit_message_lifecycle(agent_emitter, &feedback);
+            messages.push(feedback);
+            return ToolPhaseOutcome::ContinueInner;
+        }
+        TurnDecision::Canceled => return canceled(agent_emitter, None, &[]),
+        TurnDecision::Failed(reason) => return ToolPhaseOutcome::Failed(reason),
+    };
+
     let resolved_tool_calls = tool_calls
         .iter()
         .map(|call| resolve_tool_call(&request.tools, call))
@@ -63,25 +181,26 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         .map(|resolved| resolved.call.clone())
         .collect::<Vec<_>>();
 
-    let assistant_message = if iteration_text.is_empty() && normalized_tool_calls.is_empty() {
-        None
-    } else {
-        Some(assistant_message_snapshot(
-            &iteration_text,
-            &normalized_tool_calls,
-        ))
-    };
+    let assistant_message =
+        if iteration_text.is_empty() && images.is_empty() && normalized_tool_calls.is_empty() {
+            None
+        } else {
+            Some(
+                agent_emitter
+                    .stored_messages()
+                    .assistant(assistant_message_with_images(
+                        &iteration_text,
+                        &images,
+                        &normalized_tool_calls,
+                    )),
+            )
+        };
     if let Some(message) = assistant_message.as_ref() {
         messages.push(message.clone());
     }
 
     if normalized_tool_calls.is_empty() {
-        agent_emitter.emit(AgentEvent::TurnEnd {
-            run_id: request.run_id,
-            turn_index,
-            assistant_message,
-            tool_results: vec![],
-        });
+        agent_emitter.end_turn(assistant_message, Vec::new());
         return ToolPhaseOutcome::BreakInner;
     }
 
@@ -89,18 +208,49 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
     let mut turn_tool_results: Vec<AgentToolResult> = Vec::new();
     let mut steering_interrupted = false;
     let mut pending_parallel_calls: Vec<ResolvedToolCall> = Vec::new();
+    // Messages queued by tools; appended after this batch's tool results.
+    let emitted_messages = ToolMessageQueue::new();
+    let conversation: Arc<[ModelMessage]> = Arc::from(messages.as_slice());
+    let hook_contexts = BatchHookContexts::new(
+        HookContext {
+            run_id: request.run_id,
+            turn_index: agent_emitter.turn_index(),
+            iteration,
+            tool_invocations: tool_invocations.clone(),
+            consecutive_failures: *consecutive_failed_iterations,
+            messages: conversation.clone(),
+        },
+        &normalized_tool_calls,
+        tool_invocations,
+    );
     let tool_inputs = ToolExecutionInputs::new(
         request.session_fs.clone(),
         request.session_cwd.clone(),
         request.workspace_root.clone(),
         request.sandbox_provider.clone(),
+        plan_store.clone(),
+        change_log.clone(),
+        artifacts.clone(),
+        scratch.clone(),
+        request.memory.clone(),
+        request.run_id,
         #[cfg(feature = "agent")]
         request.user_input_callback.as_ref(),
+    )
+    .with_conversation(conversation, &emitted_messages)
+    .with_tool_retry(request.tool_retry.as_ref())
+    .with_deadline(request.deadline)
+    .with_dry_run(request.dry_run.then_some(request.dry_run_results));
+    let mut heartbeat = Heartbeat::start(
+        emitter,
+        request.heartbeat_interval,
+        HeartbeatPhase::ToolExecution,
     );
 
     for (call_idx, resolved_call) in resolved_tool_calls.iter().cloned().enumerate() {
         let pre_tool_use = apply_pre_tool_use_hook(
             &request.hooks,
+            &hook_contexts,
             &resolved_call.call,
             run_cancel_token.child_token(),
         );
@@ -108,7 +258,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         let pre_tool_use_result = tokio::select! {
             _ = &mut *abort_rx => {
                 run_cancel_token.cancel();
-                return ToolPhaseOutcome::Canceled;
+                return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
             }
             result = &mut pre_tool_use => result,
         };
@@ -125,6 +275,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                             for parallel_call in &pending_parallel_calls {
                                 let canceled_result = finalize_tool_result(
                                     &request.hooks,
+                                    &hook_contexts,
                                     &parallel_call.call,
                                     parallel_call.tool.as_deref(),
                                     canceled_tool_result(&parallel_call.call),
@@ -136,19 +287,23 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                                     &canceled_result,
                                 );
                             }
-                            return ToolPhaseOutcome::Canceled;
+                            return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
                         }
-                        results = execute_parallel_tool_calls(
-                            &pending_parallel_calls,
-                            agent_emitter,
-                            run_cancel_token.child_token(),
-                            tool_inputs.clone(),
+                        results = with_heartbeat(
+                            heartbeat.as_mut(),
+                            execute_parallel_tool_calls(
+                                &pending_parallel_calls,
+                                agent_emitter,
+                                run_cancel_token.child_token(),
+                                tool_inputs.clone(),
+                            ),
                         ) => results,
                     };
                     pending_parallel_calls.clear();
                     for parallel_outcome in parallel_results {
                         let final_result = finalize_tool_result(
                             &request.hooks,
+                            &hook_contexts,
                             &parallel_outcome.call,
                             parallel_outcome.tool.as_deref(),
                             parallel_outcome.result,
@@ -162,6 +317,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                         let final_result = append_tool_result(
                             emitter,
                             agent_emitter,
+                            artifacts,
                             &parallel_outcome.call,
                             final_result,
                             &mut iteration_failures,
@@ -173,6 +329,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                 emit_tool_execution_start(agent_emitter, &resolved_call.call);
                 let final_result = finalize_tool_result(
                     &request.hooks,
+                    &hook_contexts,
                     &resolved_call.call,
                     resolved_call.tool.as_deref(),
                     result,
@@ -182,6 +339,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                 let final_result = append_tool_result(
                     emitter,
                     agent_emitter,
+                    artifacts,
                     &resolved_call.call,
                     final_result,
                     &mut iteration_failures,
@@ -195,6 +353,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                         for remaining_call in &resolved_tool_calls[call_idx + 1..] {
                             let skipped = append_skipped_tool_call(
                                 &request.hooks,
+                                &hook_contexts,
                                 emitter,
                                 agent_emitter,
                                 &remaining_call.call,
@@ -205,6 +364,12 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                             .await;
                             turn_tool_results.push(skipped);
                         }
+                        append_emitted_messages(
+                            agent_emitter,
+                            &emitted_messages,
+                            &normalized_tool_calls,
+                            messages,
+                        );
                         for msg in steering {
                             emit_message_lifecycle(agent_emitter, &msg);
                             messages.push(msg);
@@ -234,6 +399,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                         for parallel_call in &pending_parallel_calls {
                             let canceled_result = finalize_tool_result(
                                 &request.hooks,
+                                &hook_contexts,
                                 &parallel_call.call,
                                 parallel_call.tool.as_deref(),
                                 canceled_tool_result(&parallel_call.call),
@@ -245,19 +411,23 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                                 &canceled_result,
                             );
                         }
-                        return ToolPhaseOutcome::Canceled;
+                        return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
                     }
-                    results = execute_parallel_tool_calls(
-                        &pending_parallel_calls,
-                        agent_emitter,
-                        run_cancel_token.child_token(),
-                        tool_inputs.clone(),
+                    results = with_heartbeat(
+                        heartbeat.as_mut(),
+                        execute_parallel_tool_calls(
+                            &pending_parallel_calls,
+                            agent_emitter,
+                            run_cancel_token.child_token(),
+                            tool_inputs.clone(),
+                        ),
                     ) => results,
                 };
                 pending_parallel_calls.clear();
                 for parallel_outcome in parallel_results {
                     let final_result = finalize_tool_result(
                         &request.hooks,
+                        &hook_contexts,
                         &parallel_outcome.call,
                         parallel_outcome.tool.as_deref(),
                         parallel_outcome.result,
@@ -267,6 +437,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     let final_result = append_tool_result(
                         emitter,
                         agent_emitter,
+                        artifacts,
                         &parallel_outcome.call,
                         final_result,
                         &mut iteration_failures,
@@ -278,6 +449,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
 
             let final_result = finalize_tool_result(
                 &request.hooks,
+                &hook_contexts,
                 &resolved_call.call,
                 resolved_call.tool.as_deref(),
                 result,
@@ -286,6 +458,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
             let final_result = append_tool_result(
                 emitter,
                 agent_emitter,
+                artifacts,
                 &resolved_call.call,
                 final_result,
                 &mut iteration_failures,
@@ -299,6 +472,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     for remaining_call in &resolved_tool_calls[call_idx + 1..] {
                         let skipped = append_skipped_tool_call(
                             &request.hooks,
+                            &hook_contexts,
                             emitter,
                             agent_emitter,
                             &remaining_call.call,
@@ -309,6 +483,12 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                         .await;
                         turn_tool_results.push(skipped);
                     }
+                    append_emitted_messages(
+                        agent_emitter,
+                        &emitted_messages,
+                        &normalized_tool_calls,
+                        messages,
+                    );
                     for msg in steering {
                         emit_message_lifecycle(agent_emitter, &msg);
                         messages.push(msg);
@@ -319,9 +499,20 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
             }
             continue;
         }
-        resolved_call.safety_plan =
-            safety_plan_for_finalized_call(&resolved_call.call, resolved_call.tool.as_deref());
+        resolved_call.safety_plan = safety_plan_for_finalized_call(
+            &resolved_call.call,
+            resolved_call.tool.as_deref(),
+            request.workspace_root.as_deref(),
+        );
         let approval_tool = resolved_call.tool.clone();
+        if let Some(tool) = approval_tool.as_deref() {
+            let prompts_human = request.approval_handler.is_some()
+                || request.human_interaction_coordinator.is_some();
+            if prompts_human && !resolved_call.safety_plan.read_only {
+                resolved_call.safety_plan.change_previews =
+                    tool_inputs.change_previews(tool, &resolved_call.call).await;
+            }
+        }
         let decision = {
             let approval = resolve_approval(
                 emitter,
@@ -338,7 +529,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
             tokio::select! {
                 _ = &mut *abort_rx => {
                     run_cancel_token.cancel();
-                    return ToolPhaseOutcome::Canceled;
+                    return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
                 }
                 decision = &mut approval => decision,
             }
@@ -346,7 +537,11 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
 
         if matches!(decision, ApprovalDecision::Cancel) {
             run_cancel_token.cancel();
-            return ToolPhaseOutcome::Canceled;
+            return canceled(
+                agent_emitter,
+                assistant_message.as_ref(),
+                &turn_tool_results,
+            );
         }
 
         let can_execute = approval_allows_execution(decision);
@@ -365,6 +560,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     for parallel_call in &pending_parallel_calls {
                         let canceled_result = finalize_tool_result(
                             &request.hooks,
+                            &hook_contexts,
                             &parallel_call.call,
                             parallel_call.tool.as_deref(),
                             canceled_tool_result(&parallel_call.call),
@@ -376,19 +572,23 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                             &canceled_result,
                         );
                     }
-                    return ToolPhaseOutcome::Canceled;
+                    return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
                 }
-                results = execute_parallel_tool_calls(
-                    &pending_parallel_calls,
-                    agent_emitter,
-                    run_cancel_token.child_token(),
-                    tool_inputs.clone(),
+                results = with_heartbeat(
+                    heartbeat.as_mut(),
+                    execute_parallel_tool_calls(
+                        &pending_parallel_calls,
+                        agent_emitter,
+                        run_cancel_token.child_token(),
+                        tool_inputs.clone(),
+                    ),
                 ) => results,
             };
             pending_parallel_calls.clear();
             for parallel_outcome in parallel_results {
                 let final_result = finalize_tool_result(
                     &request.hooks,
+                    &hook_contexts,
                     &parallel_outcome.call,
                     parallel_outcome.tool.as_deref(),
                     parallel_outcome.result,
@@ -398,6 +598,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                 let final_result = append_tool_result(
                     emitter,
                     agent_emitter,
+                    artifacts,
                     &parallel_outcome.call,
                     final_result,
                     &mut iteration_failures,
@@ -412,6 +613,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     for remaining_call in &resolved_tool_calls[call_idx + 1..] {
                         let skipped = append_skipped_tool_call(
                             &request.hooks,
+                            &hook_contexts,
                             emitter,
                             agent_emitter,
                             &remaining_call.call,
@@ -422,6 +624,12 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                         .await;
                         turn_tool_results.push(skipped);
                     }
+                    append_emitted_messages(
+                        agent_emitter,
+                        &emitted_messages,
+                        &normalized_tool_calls,
+                        messages,
+                    );
                     for msg in steering {
                         emit_message_lifecycle(agent_emitter, &msg);
                         messages.push(msg);
@@ -441,19 +649,23 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     run_cancel_token.cancel();
                     let canceled_result = finalize_tool_result(
                         &request.hooks,
+                        &hook_contexts,
                         &call_for_cancel,
                         tool_for_cancel.as_deref(),
                         canceled_tool_result(&call_for_cancel),
                     )
                     .await;
                     emit_tool_execution_end(agent_emitter, &call_for_cancel, &canceled_result);
-                    return ToolPhaseOutcome::Canceled;
+                    return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
                 }
-                outcome = execute_tool_call(
-                    resolved_call,
-                    agent_emitter,
-                    run_cancel_token.child_token(),
-                    tool_inputs.clone(),
+                outcome = with_heartbeat(
+                    heartbeat.as_mut(),
+                    execute_tool_call(
+                        resolved_call,
+                        agent_emitter,
+                        run_cancel_token.child_token(),
+                        tool_inputs.clone(),
+                    ),
                 ) => outcome,
             }
         } else {
@@ -465,6 +677,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         };
         let final_result = finalize_tool_result(
             &request.hooks,
+            &hook_contexts,
             &outcome.call,
             outcome.tool.as_deref(),
             outcome.result,
@@ -477,6 +690,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         let final_result = append_tool_result(
             emitter,
             agent_emitter,
+            artifacts,
             &outcome.call,
             final_result,
             &mut iteration_failures,
@@ -490,6 +704,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                 for remaining_call in &resolved_tool_calls[call_idx + 1..] {
                     let skipped = append_skipped_tool_call(
                         &request.hooks,
+                        &hook_contexts,
                         emitter,
                         agent_emitter,
                         &remaining_call.call,
@@ -500,6 +715,12 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     .await;
                     turn_tool_results.push(skipped);
                 }
+                append_emitted_messages(
+                    agent_emitter,
+                    &emitted_messages,
+                    &normalized_tool_calls,
+                    messages,
+                );
                 for msg in steering {
                     emit_message_lifecycle(agent_emitter, &msg);
                     messages.push(msg);
@@ -511,12 +732,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
     }
 
     if steering_interrupted {
-        agent_emitter.emit(AgentEvent::TurnEnd {
-            run_id: request.run_id,
-            turn_index,
-            assistant_message: assistant_message.clone(),
-            tool_results: turn_tool_results,
-        });
+        agent_emitter.end_turn(assistant_message, turn_tool_results);
         return ToolPhaseOutcome::ContinueInner;
     }
 
@@ -530,6 +746,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                 for parallel_call in &pending_parallel_calls {
                     let canceled_result = finalize_tool_result(
                         &request.hooks,
+                        &hook_contexts,
                         &parallel_call.call,
                         parallel_call.tool.as_deref(),
                         canceled_tool_result(&parallel_call.call),
@@ -537,19 +754,23 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
                     .await;
                     emit_tool_execution_end(agent_emitter, &parallel_call.call, &canceled_result);
                 }
-                return ToolPhaseOutcome::Canceled;
+                return canceled(agent_emitter, assistant_message.as_ref(), &turn_tool_results);
             }
-            results = execute_parallel_tool_calls(
-                &pending_parallel_calls,
-                agent_emitter,
-                run_cancel_token.child_token(),
-                tool_inputs.clone(),
+            results = with_heartbeat(
+                heartbeat.as_mut(),
+                execute_parallel_tool_calls(
+                    &pending_parallel_calls,
+                    agent_emitter,
+                    run_cancel_token.child_token(),
+                    tool_inputs.clone(),
+                ),
             ) => results,
         };
         pending_parallel_calls.clear();
         for parallel_outcome in parallel_results {
             let final_result = finalize_tool_result(
                 &request.hooks,
+                &hook_contexts,
                 &parallel_outcome.call,
                 parallel_outcome.tool.as_deref(),
                 parallel_outcome.result,
@@ -559,6 +780,7 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
             let final_result = append_tool_result(
                 emitter,
                 agent_emitter,
+                artifacts,
                 &parallel_outcome.call,
                 final_result,
                 &mut iteration_failures,
@@ -568,12 +790,13 @@ pub(super) async fn run_tool_phase(args: ToolPhaseArgs<'_>) -> ToolPhaseOutcome
         }
     }
 
-    agent_emitter.emit(AgentEvent::TurnEnd {
-        run_id: request.run_id,
-        turn_index,
-        assistant_message,
-        tool_results: turn_tool_results,
-    });
+    append_emitted_messages(
+        agent_emitter,
+        &emitted_messages,
+        &normalized_tool_calls,
+        messages,
+    );
+    agent_emitter.end_turn(assistant_message, turn_tool_results);
 
     if iteration_failures == normalized_tool_calls.len() {
         *consecutive_failed_iterations = consecutive_failed_iterations.saturating_add(1);
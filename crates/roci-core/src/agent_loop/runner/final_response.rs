@@ -0,0 +1,144 @@
+use crate::error::RociError;
+use crate::generation::object::strip_code_fences;
+use crate::provider::schema::normalize_schema_for_provider;
+use crate::provider::ModelProvider;
+use crate::tools::validation::validate_arguments;
+use crate::types::{ModelMessage, ResponseFormat};
+
+use super::RunRequest;
+
+/// Reject a final response schema that does not ask for JSON.
+pub(super) fn validate_final_response_schema(request: &RunRequest) -> Result<(), RociError> {
+    if matches!(request.final_response_schema, Some(ResponseFormat::Text)) {
+        return Err(RociError::Configuration(
+            "final_response_schema must be a JSON format".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// What to do with a reply that ended the tool loop.
+pub(super) enum FinalResponseReview {
+    /// The reply is the structured answer.
+    Accept(serde_json::Value),
+    /// Append the message and make one more constrained call without tools.
+    Retry(ModelMessage),
+    /// The reply failed validation after its repair call.
+    Fail(String),
+}
+
+/// Progress toward a schema-conforming final answer for one run.
+#[derive(Debug, Default)]
+pub(super) struct FinalResponse {
+    /// The next call carries the format and no tools.
+    constrained_call: bool,
+    repair_used: bool,
+    /// Whether the last call carried the format.
+    last_call_formatted: bool,
+}
+
+impl FinalResponse {
+    /// Format for the next call, when it should carry one.
+    pub(super) fn next_call_format(
+        &mut self,
+        format: Option<&ResponseFormat>,
+        provider: &dyn ModelProvider,
+    ) -> Option<ResponseFormat> {
+        let format = format
+            .filter(|_| self.constrained_call || provider.supports_response_format_with_tools())
+            .and_then(|format| provider_format(format, provider));
+        self.last_call_formatted = format.is_some();
+        format
+    }
+
+    /// Whether the next call is the constrained one, sent without tools.
+    pub(super) fn withholds_tools(&self) -> bool {
+        self.constrained_call
+    }
+
+    /// Check `reply`, the text of the turn that ended the tool loop.
+    pub(super) fn review(
+        &mut self,
+        format: &ResponseFormat,
+        provider: &dyn ModelProvider,
+        reply: &str,
+    ) -> FinalResponseReview {
+        if !self.constrained_call && !self.last_call_formatted {
+            self.constrained_call = true;
+            return FinalResponseReview::Retry(ModelMessage::user(answer_instruction(
+                format, provider,
+            )));
+        }
+        match parse_reply(format, reply) {
+            Ok(value) => FinalResponseReview::Accept(value),
+            Err(error) if !self.repair_used => {
+                self.constrained_call = true;
+                self.repair_used = true;
+                FinalResponseReview::Retry(ModelMessage::user(format!(
+                    "Your answer did not match the required format: {error}. \
+                     Reply again with only the corrected JSON.{}",
+                    schema_reminder(format, provider)
+                )))
+            }
+            Err(error) => FinalResponseReview::Fail(format!(
+                "final response failed schema validation: {error}"
+            )),
+        }
+    }
+}
+
+/// `format` as the provider can enforce it; `None` leaves it to the prompt.
+fn provider_format(
+    format: &ResponseFormat,
+    provider: &dyn ModelProvider,
+) -> Option<ResponseFormat> {
+    let capabilities = provider.capabilities();
+    match format {
+        ResponseFormat::Text => None,
+        ResponseFormat::JsonSchema { schema, name } if capabilities.supports_json_schema => {
+            Some(ResponseFormat::JsonSchema {
+                schema: normalize_schema_for_provider(schema, provider.provider_name()),
+                name: name.clone(),
+            })
+        }
+        _ if capabilities.supports_json_mode || capabilities.supports_json_schema => {
+            Some(ResponseFormat::JsonObject)
+        }
+        _ => None,
+    }
+}
+
+fn answer_instruction(format: &ResponseFormat, provider: &dyn ModelProvider) -> String {
+    format!(
+        "Now give your final answer as JSON only, with no markdown or explanation.{}",
+        schema_reminder(format, provider)
+    )
+}
+
+/// The schema spelled out, for providers that cannot enforce it themselves.
+fn schema_reminder(format: &ResponseFormat, provider: &dyn ModelProvider) -> String {
+    match format {
+        ResponseFormat::JsonSchema { schema, .. }
+            if !provider.capabilities().supports_json_schema =>
+        {
+            format!(
+                " It must match this schema:\n```json\n{}\n```",
+                serde_json::to_string_pretty(schema).unwrap_or_default()
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+fn parse_reply(format: &ResponseFormat, reply: &str) -> Result<serde_json::Value, String> {
+    let value: serde_json::Value = serde_json::from_str(&strip_code_fences(reply))
+        .map_err(|err| format!("invalid JSON: {err}"))?;
+    match format {
+        ResponseFormat::JsonSchema { schema, .. } => validate_arguments(&value, schema)?,
+        ResponseFormat::JsonObject if !value.is_object() => {
+            return Err("expected a JSON object".to_string());
+        }
+        _ => {}
+    }
+    Ok(value)
+}
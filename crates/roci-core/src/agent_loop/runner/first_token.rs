@@ -0,0 +1,59 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::{self, Instant};
+
+use crate::types::{StreamEventType, TextStreamDelta};
+
+/// Deadline for the first content delta of one provider request; see
+/// [`RunRequest::ttft_slo`](super::RunRequest::ttft_slo).
+#[derive(Debug, Clone, Copy)]
+pub(super) struct FirstTokenDeadline {
+    slo: Duration,
+    started_at: Instant,
+}
+
+impl FirstTokenDeadline {
+    /// Deadline starting now, or `None` when there is no SLO.
+    pub(super) fn start(slo: Option<Duration>) -> Option<Self> {
+        slo.map(|slo| Self {
+            slo,
+            started_at: Instant::now(),
+        })
+    }
+
+    pub(super) fn slo(&self) -> Duration {
+        self.slo
+    }
+
+    pub(super) fn waited(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Await `future`, or return `None` once `deadline` passes. Dropping the
+/// future aborts the request it drives.
+pub(super) async fn within_first_token_deadline<F: Future>(
+    deadline: Option<FirstTokenDeadline>,
+    future: F,
+) -> Option<F::Output> {
+    let Some(deadline) = deadline else {
+        return Some(future.await);
+    };
+    time::timeout_at(deadline.started_at + deadline.slo, future)
+        .await
+        .ok()
+}
+
+/// Whether `delta` carries model output, which disarms the deadline.
+pub(super) fn is_content_delta(delta: &TextStreamDelta) -> bool {
+    match delta.event_type {
+        StreamEventType::TextDelta
+        | StreamEventType::ToolCallDelta
+        | StreamEventType::ToolCallArgumentsDelta
+        | StreamEventType::Reasoning
+        | StreamEventType::RefusalDelta
+        | StreamEventType::Image => true,
+        StreamEventType::Start | StreamEventType::Done | StreamEventType::Error => false,
+    }
+}
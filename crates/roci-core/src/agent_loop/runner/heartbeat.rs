@@ -0,0 +1,69 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::{self, Instant};
+
+use super::control::RunEventEmitter;
+use super::{RunEventPayload, RunEventStream};
+use crate::agent_loop::HeartbeatPhase;
+
+/// Emits [`RunEventPayload::Heartbeat`] while awaited futures are pending.
+///
+/// Heartbeats only fire inside [`with_heartbeat`], so nothing is emitted once
+/// the phase that owns the ticker has returned.
+pub(super) struct Heartbeat<'a> {
+    emitter: &'a RunEventEmitter,
+    phase: HeartbeatPhase,
+    interval: Duration,
+    started_at: Instant,
+    next_at: Instant,
+}
+
+impl<'a> Heartbeat<'a> {
+    /// Ticker for `phase`, or `None` when heartbeats are disabled.
+    pub(super) fn start(
+        emitter: &'a RunEventEmitter,
+        interval: Option<Duration>,
+        phase: HeartbeatPhase,
+    ) -> Option<Self> {
+        let interval = interval.filter(|interval| !interval.is_zero())?;
+        let started_at = Instant::now();
+        Some(Self {
+            emitter,
+            phase,
+            interval,
+            started_at,
+            next_at: started_at + interval,
+        })
+    }
+
+    fn beat(&mut self) {
+        let now = Instant::now();
+        self.next_at = now + self.interval;
+        self.emitter.emit(
+            RunEventStream::System,
+            RunEventPayload::Heartbeat {
+                phase: self.phase,
+                elapsed_ms: now.duration_since(self.started_at).as_millis() as u64,
+            },
+        );
+    }
+}
+
+/// Await `future`, emitting heartbeats each interval until it resolves.
+pub(super) async fn with_heartbeat<F: Future>(
+    heartbeat: Option<&mut Heartbeat<'_>>,
+    future: F,
+) -> F::Output {
+    let Some(heartbeat) = heartbeat else {
+        return future.await;
+    };
+    tokio::pin!(future);
+    loop {
+        tokio::select! {
+            biased;
+            output = &mut future => return output,
+            _ = time::sleep_until(heartbeat.next_at) => heartbeat.beat(),
+        }
+    }
+}
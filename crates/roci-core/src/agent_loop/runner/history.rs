@@ -0,0 +1,123 @@
+//! The run's conversation, shared with the provider requests built from it.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::types::ModelMessage;
+
+/// Message history of a run.
+///
+/// Provider requests, hooks, and usage anchors hold the same allocation
+/// through [`shared`](Self::shared); appending copies it only while one of
+/// them is still alive. The history only grows, except when compaction
+/// [`replace`](Self::replace)s it, so a [`HistoryMark`] taken earlier names a
+/// prefix of the current history until then.
+#[derive(Debug)]
+pub(super) struct History {
+    messages: Arc<Vec<ModelMessage>>,
+    /// Bumped on every replacement, invalidating earlier marks.
+    generation: u64,
+}
+
+/// The first `len` messages of a [`History`] at some generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct HistoryMark {
+    generation: u64,
+    len: usize,
+}
+
+impl History {
+    pub(super) fn new(messages: Vec<ModelMessage>) -> Self {
+        Self {
+            messages: Arc::new(messages),
+            generation: 0,
+        }
+    }
+
+    pub(super) fn push(&mut self, message: ModelMessage) {
+        Arc::make_mut(&mut self.messages).push(message);
+    }
+
+    /// Swap in a new history, e.g. a compacted one.
+    pub(super) fn replace(&mut self, messages: Vec<ModelMessage>) {
+        *self = self.replacement(messages);
+    }
+
+    /// A history to swap in for this one later; marks taken from this
+    /// history do not apply to it.
+    pub(super) fn replacement(&self, messages: Vec<ModelMessage>) -> Self {
+        Self {
+            messages: Arc::new(messages),
+            generation: self.generation + 1,
+        }
+    }
+
+    /// The history itself, without copying it.
+    pub(super) fn shared(&self) -> Arc<Vec<ModelMessage>> {
+        Arc::clone(&self.messages)
+    }
+
+    /// Whether `messages` is this history's allocation.
+    pub(super) fn is_shared_by(&self, messages: &Arc<Vec<ModelMessage>>) -> bool {
+        Arc::ptr_eq(&self.messages, messages)
+    }
+
+    pub(super) fn mark(&self) -> HistoryMark {
+        HistoryMark {
+            generation: self.generation,
+            len: self.messages.len(),
+        }
+    }
+
+    /// The messages `mark` named, unless the history was replaced since.
+    pub(super) fn prefix(&self, mark: HistoryMark) -> Option<&[ModelMessage]> {
+        (mark.generation == self.generation)
+            .then(|| self.messages.get(..mark.len))
+            .flatten()
+    }
+
+    pub(super) fn into_vec(self) -> Vec<ModelMessage> {
+        Arc::unwrap_or_clone(self.messages)
+    }
+}
+
+impl Deref for History {
+    type Target = Vec<ModelMessage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushes_copy_only_while_shared() {
+        let mut history = History::new(vec![ModelMessage::user("hi")]);
+        let before = history.shared();
+
+        history.push(ModelMessage::assistant("hello"));
+        assert_eq!(before.len(), 1);
+        assert!(!history.is_shared_by(&before));
+
+        drop(before);
+        let allocation = Arc::as_ptr(&history.messages);
+        history.push(ModelMessage::user("again"));
+        assert_eq!(Arc::as_ptr(&history.messages), allocation);
+    }
+
+    #[test]
+    fn marks_survive_appends_but_not_replacement() {
+        let first = ModelMessage::user("hi");
+        let mut history = History::new(vec![first.clone()]);
+        let mark = history.mark();
+
+        history.push(ModelMessage::assistant("hello"));
+        assert_eq!(history.prefix(mark), Some(&[first.clone()][..]));
+
+        history.replace(vec![first]);
+        assert_eq!(history.prefix(mark), None);
+    }
+}
@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 
+use crate::error::RociError;
+
 use super::RunRequest;
 
 const DEFAULT_MAX_ITERATIONS: usize = 20;
 const DEFAULT_MAX_TOOL_FAILURES: usize = 8;
 const DEFAULT_ITERATION_EXTENSION: usize = 20;
 const DEFAULT_MAX_ITERATION_EXTENSIONS: usize = 3;
+const DEFAULT_MAX_TOTAL_RETRIES: usize = 20;
+const DEFAULT_MAX_TOTAL_RETRY_DELAY_MS: usize = 15 * 60 * 1000;
 const RUNNER_MAX_ITERATIONS_ENV: &str = "HOMIE_ROCI_RUNNER_MAX_ITERATIONS";
 const RUNNER_MAX_TOOL_FAILURES_ENV: &str = "HOMIE_ROCI_RUNNER_MAX_TOOL_FAILURES";
 const RUNNER_ITERATION_EXTENSION_ENV: &str = "HOMIE_ROCI_RUNNER_ITERATION_EXTENSION";
 const RUNNER_MAX_ITERATION_EXTENSIONS_ENV: &str = "HOMIE_ROCI_RUNNER_MAX_ITERATION_EXTENSIONS";
+const RUNNER_MAX_TOTAL_RETRIES_ENV: &str = "HOMIE_ROCI_RUNNER_MAX_TOTAL_RETRIES";
+const RUNNER_MAX_TOTAL_RETRY_DELAY_MS_ENV: &str = "HOMIE_ROCI_RUNNER_MAX_TOTAL_RETRY_DELAY_MS";
 const RUNNER_MAX_ITERATIONS_KEYS: [&str; 3] = [
     "runner.max_iterations",
     "agent_loop.max_iterations",
@@ -30,6 +36,26 @@ const RUNNER_MAX_ITERATION_EXTENSIONS_KEYS: [&str; 3] = [
     "agent_loop.max_iteration_extensions",
     "max_iteration_extensions",
 ];
+const RUNNER_MAX_TOTAL_RETRIES_KEYS: [&str; 3] = [
+    "runner.max_total_retries",
+    "agent_loop.max_total_retries",
+    "max_total_retries",
+];
+const RUNNER_MAX_TOTAL_RETRY_DELAY_MS_KEYS: [&str; 3] = [
+    "runner.max_total_retry_delay_ms",
+    "agent_loop.max_total_retry_delay_ms",
+    "max_total_retry_delay_ms",
+];
+const RUNNER_METADATA_PREFIX: &str = "runner.";
+/// Every `runner.*` metadata key the runner reads.
+const RUNNER_METADATA_KEYS: [&str; 6] = [
+    RUNNER_MAX_ITERATIONS_KEYS[0],
+    RUNNER_MAX_TOOL_FAILURES_KEYS[0],
+    RUNNER_ITERATION_EXTENSION_KEYS[0],
+    RUNNER_MAX_ITERATION_EXTENSIONS_KEYS[0],
+    RUNNER_MAX_TOTAL_RETRIES_KEYS[0],
+    RUNNER_MAX_TOTAL_RETRY_DELAY_MS_KEYS[0],
+];
 
 #[derive(Debug, Clone, Copy)]
 pub(super) struct RunnerLimits {
@@ -37,58 +63,156 @@ pub(super) struct RunnerLimits {
     pub(super) max_tool_failures: usize,
     pub(super) iteration_extension: usize,
     pub(super) max_iteration_extensions: usize,
+    /// Provider retries allowed across every iteration of the run.
+    pub(super) max_total_retries: usize,
+    /// Backoff sleep allowed across every iteration of the run.
+    pub(super) max_total_retry_delay_ms: u64,
 }
 
 impl RunnerLimits {
+    /// Resolve limits for `request`.
+    ///
+    /// Each limit comes from the typed [`RunRequest`] field, then run
+    /// metadata, then the `HOMIE_ROCI_RUNNER_*` environment variable, then
+    /// the built-in default. Metadata for a limit with a typed field is
+    /// deprecated and logs a warning when used.
     pub(super) fn from_request(request: &RunRequest) -> Self {
+        let metadata = &request.metadata;
         Self {
-            max_iterations: parse_runner_limit(
-                &request.metadata,
-                &RUNNER_MAX_ITERATIONS_KEYS,
-                RUNNER_MAX_ITERATIONS_ENV,
-                DEFAULT_MAX_ITERATIONS,
-            ),
-            max_tool_failures: parse_runner_limit(
-                &request.metadata,
-                &RUNNER_MAX_TOOL_FAILURES_KEYS,
-                RUNNER_MAX_TOOL_FAILURES_ENV,
-                DEFAULT_MAX_TOOL_FAILURES,
-            ),
-            iteration_extension: parse_runner_limit(
-                &request.metadata,
-                &RUNNER_ITERATION_EXTENSION_KEYS,
-                RUNNER_ITERATION_EXTENSION_ENV,
-                DEFAULT_ITERATION_EXTENSION,
-            ),
-            max_iteration_extensions: parse_runner_limit(
-                &request.metadata,
-                &RUNNER_MAX_ITERATION_EXTENSIONS_KEYS,
-                RUNNER_MAX_ITERATION_EXTENSIONS_ENV,
-                DEFAULT_MAX_ITERATION_EXTENSIONS,
+            max_iterations: request.max_iterations.unwrap_or_else(|| {
+                deprecated_runner_limit(
+                    metadata,
+                    &RUNNER_MAX_ITERATIONS_KEYS,
+                    "max_iterations",
+                    RUNNER_MAX_ITERATIONS_ENV,
+                    DEFAULT_MAX_ITERATIONS,
+                )
+            }),
+            max_tool_failures: request.max_tool_failures.unwrap_or_else(|| {
+                deprecated_runner_limit(
+                    metadata,
+                    &RUNNER_MAX_TOOL_FAILURES_KEYS,
+                    "max_tool_failures",
+                    RUNNER_MAX_TOOL_FAILURES_ENV,
+                    DEFAULT_MAX_TOOL_FAILURES,
+                )
+            }),
+            iteration_extension: request.iteration_extension.unwrap_or_else(|| {
+                deprecated_runner_limit(
+                    metadata,
+                    &RUNNER_ITERATION_EXTENSION_KEYS,
+                    "iteration_extension",
+                    RUNNER_ITERATION_EXTENSION_ENV,
+                    DEFAULT_ITERATION_EXTENSION,
+                )
+            }),
+            max_iteration_extensions: request.max_iteration_extensions.unwrap_or_else(|| {
+                deprecated_runner_limit(
+                    metadata,
+                    &RUNNER_MAX_ITERATION_EXTENSIONS_KEYS,
+                    "max_iteration_extensions",
+                    RUNNER_MAX_ITERATION_EXTENSIONS_ENV,
+                    DEFAULT_MAX_ITERATION_EXTENSIONS,
+                )
+            }),
+            max_total_retries: parse_runner_limit(
+                metadata,
+                &RUNNER_MAX_TOTAL_RETRIES_KEYS,
+                RUNNER_MAX_TOTAL_RETRIES_ENV,
+                DEFAULT_MAX_TOTAL_RETRIES,
             ),
+            max_total_retry_delay_ms: parse_runner_limit(
+                metadata,
+                &RUNNER_MAX_TOTAL_RETRY_DELAY_MS_KEYS,
+                RUNNER_MAX_TOTAL_RETRY_DELAY_MS_ENV,
+                DEFAULT_MAX_TOTAL_RETRY_DELAY_MS,
+            ) as u64,
         }
     }
 }
 
-fn parse_runner_limit(
+/// Reject typed limits of zero and `runner.*` metadata keys the runner does
+/// not read, so a typo fails the run instead of being ignored.
+pub(super) fn validate_runner_limits(request: &RunRequest) -> Result<(), RociError> {
+    for (field, value) in [
+        ("max_iterations", request.max_iterations),
+        ("max_tool_failures", request.max_tool_failures),
+        ("iteration_extension", request.iteration_extension),
+        ("max_iteration_extensions", request.max_iteration_extensions),
+    ] {
+        if value == Some(0) {
+            return Err(RociError::InvalidArgument(format!(
+                "RunRequest::{field} must be at least 1"
+            )));
+        }
+    }
+
+    let mut unknown = request
+        .metadata
+        .keys()
+        .filter(|key| {
+            key.starts_with(RUNNER_METADATA_PREFIX) && !RUNNER_METADATA_KEYS.contains(&key.as_str())
+        })
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort_unstable();
+    let mut valid = RUNNER_METADATA_KEYS.to_vec();
+    valid.sort_unstable();
+    Err(RociError::InvalidArgument(format!(
+        "unknown runner metadata key(s): {}; valid keys: {}",
+        unknown.join(", "),
+        valid.join(", ")
+    )))
+}
+
+fn deprecated_runner_limit(
     metadata: &HashMap<String, String>,
     keys: &[&str],
+    field: &str,
     env_key: &str,
     default: usize,
 ) -> usize {
-    for key in keys {
-        if let Some(value) = metadata.get(*key) {
-            if let Some(parsed) = parse_positive_usize(value) {
-                return parsed;
-            }
-        }
+    if let Some((key, parsed)) = metadata_limit(metadata, keys) {
+        tracing::warn!(
+            key,
+            "run metadata key is deprecated; set RunRequest::{field} instead"
+        );
+        return parsed;
     }
-    if let Ok(value) = std::env::var(env_key) {
-        if let Some(parsed) = parse_positive_usize(&value) {
-            return parsed;
-        }
-    }
-    default
+    env_limit(env_key).unwrap_or(default)
+}
+
+fn parse_runner_limit(
+    metadata: &HashMap<String, String>,
+    keys: &[&str],
+    env_key: &str,
+    default: usize,
+) -> usize {
+    metadata_limit(metadata, keys)
+        .map(|(_, parsed)| parsed)
+        .or_else(|| env_limit(env_key))
+        .unwrap_or(default)
+}
+
+fn metadata_limit<'a>(
+    metadata: &HashMap<String, String>,
+    keys: &[&'a str],
+) -> Option<(&'a str, usize)> {
+    keys.iter().find_map(|key| {
+        metadata
+            .get(*key)
+            .and_then(|value| parse_positive_usize(value))
+            .map(|parsed| (*key, parsed))
+    })
+}
+
+fn env_limit(env_key: &str) -> Option<usize> {
+    std::env::var(env_key)
+        .ok()
+        .and_then(|value| parse_positive_usize(&value))
 }
 
 fn parse_positive_usize(value: &str) -> Option<usize> {
@@ -99,3 +223,118 @@ fn parse_positive_usize(value: &str) -> Option<usize> {
         Some(parsed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ModelMessage;
+
+    fn request() -> RunRequest {
+        RunRequest::new(
+            "openai:gpt-4o".parse().unwrap(),
+            vec![ModelMessage::user("hi")],
+        )
+    }
+
+    fn with_metadata(mut request: RunRequest, entries: &[(&str, &str)]) -> RunRequest {
+        for (key, value) in entries {
+            request
+                .metadata
+                .insert((*key).to_string(), (*value).to_string());
+        }
+        request
+    }
+
+    #[test]
+    fn typed_fields_take_precedence_over_metadata() {
+        let request = with_metadata(
+            request()
+                .with_max_iterations(5)
+                .with_max_tool_failures(2)
+                .with_iteration_extensions(4, 1),
+            &[
+                ("runner.max_iterations", "50"),
+                ("runner.max_tool_failures", "9"),
+                ("runner.iteration_extension", "40"),
+                ("runner.max_iteration_extensions", "7"),
+            ],
+        );
+
+        let limits = RunnerLimits::from_request(&request);
+
+        assert_eq!(limits.max_iterations, 5);
+        assert_eq!(limits.max_tool_failures, 2);
+        assert_eq!(limits.iteration_extension, 4);
+        assert_eq!(limits.max_iteration_extensions, 1);
+    }
+
+    #[test]
+    fn metadata_is_a_fallback_for_unset_typed_fields() {
+        let request = with_metadata(
+            request().with_max_iterations(5),
+            &[
+                ("runner.max_iterations", "50"),
+                ("runner.max_tool_failures", "3"),
+                ("agent_loop.iteration_extension", "6"),
+                ("runner.max_total_retries", "2"),
+            ],
+        );
+
+        let limits = RunnerLimits::from_request(&request);
+
+        assert_eq!(limits.max_iterations, 5);
+        assert_eq!(limits.max_tool_failures, 3);
+        assert_eq!(limits.iteration_extension, 6);
+        assert_eq!(limits.max_total_retries, 2);
+    }
+
+    #[test]
+    fn unset_limits_use_defaults() {
+        let limits = RunnerLimits::from_request(&request());
+
+        assert_eq!(limits.max_iterations, DEFAULT_MAX_ITERATIONS);
+        assert_eq!(limits.max_tool_failures, DEFAULT_MAX_TOOL_FAILURES);
+        assert_eq!(limits.iteration_extension, DEFAULT_ITERATION_EXTENSION);
+        assert_eq!(
+            limits.max_iteration_extensions,
+            DEFAULT_MAX_ITERATION_EXTENSIONS
+        );
+    }
+
+    #[test]
+    fn unknown_runner_metadata_keys_are_rejected_with_valid_keys() {
+        let request = with_metadata(
+            request(),
+            &[
+                ("runner.max_iteration", "4"),
+                ("runner.max_tool_failures", "2"),
+                ("provider.body.user", "user-1"),
+            ],
+        );
+
+        let err = validate_runner_limits(&request).unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("runner.max_iteration;"), "{message}");
+        assert!(message.contains("runner.max_iterations"), "{message}");
+        assert!(
+            message.contains("runner.max_total_retry_delay_ms"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn known_runner_metadata_keys_are_accepted() {
+        let entries = RUNNER_METADATA_KEYS.map(|key| (key, "3"));
+        let request = with_metadata(request(), &entries);
+
+        assert!(validate_runner_limits(&request).is_ok());
+    }
+
+    #[test]
+    fn zero_typed_limits_are_rejected() {
+        let err = validate_runner_limits(&request().with_max_tool_failures(0)).unwrap_err();
+
+        assert!(err.to_string().contains("max_tool_failures"));
+    }
+}
@@ -1,15 +1,90 @@
-use crate::types::{message::ContentPart, AgentToolCall, ModelMessage};
+use std::sync::Arc;
+
+use crate::security::pii::ResponseFilter;
+use crate::types::{message::ContentPart, AgentToolCall, ImageContent, ModelMessage};
 
 use super::control::AgentEventEmitter;
 use super::AgentEvent;
 
+/// [`RunRequest::response_filter`](super::RunRequest::response_filter) as
+/// applied to messages on their way into history and `MessageEnd`.
+#[derive(Clone, Default)]
+pub(super) struct StoredMessageFilter {
+    filter: Option<Arc<dyn ResponseFilter>>,
+    tool_results: bool,
+}
+
+impl StoredMessageFilter {
+    pub(super) fn new(filter: Option<Arc<dyn ResponseFilter>>, tool_results: bool) -> Self {
+        Self {
+            filter,
+            tool_results,
+        }
+    }
+
+    /// Filter the text parts of an assistant message.
+    pub(super) fn assistant(&self, mut message: ModelMessage) -> ModelMessage {
+        let Some(filter) = self.filter.as_ref() else {
+            return message;
+        };
+        for part in &mut message.content {
+            if let ContentPart::Text { text } = part {
+                if !text.is_empty() {
+                    *text = filter.filter(text);
+                }
+            }
+        }
+        message
+    }
+
+    /// Filter every string in a tool-result message, when enabled.
+    pub(super) fn tool_result(&self, mut message: ModelMessage) -> ModelMessage {
+        let Some(filter) = self.filter.as_ref().filter(|_| self.tool_results) else {
+            return message;
+        };
+        for part in &mut message.content {
+            if let ContentPart::ToolResult(result) = part {
+                filter_json_strings(filter.as_ref(), &mut result.result);
+            }
+        }
+        message
+    }
+}
+
+fn filter_json_strings(filter: &dyn ResponseFilter, value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => *text = filter.filter(text),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                filter_json_strings(filter, value);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for value in map.values_mut() {
+                filter_json_strings(filter, value);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn build_assistant_message(iteration_text: &str, tool_calls: &[AgentToolCall]) -> ModelMessage {
+    assistant_message_with_images(iteration_text, &[], tool_calls)
+}
+
+/// Assistant message with generated images between the text and tool calls.
+pub(super) fn assistant_message_with_images(
+    iteration_text: &str,
+    images: &[ImageContent],
+    tool_calls: &[AgentToolCall],
+) -> ModelMessage {
     let mut content: Vec<ContentPart> = Vec::new();
     if !iteration_text.is_empty() {
         content.push(ContentPart::Text {
             text: iteration_text.to_string(),
         });
     }
+    content.extend(images.iter().cloned().map(ContentPart::Image));
     for call in tool_calls {
         content.push(ContentPart::ToolCall(call.clone()));
     }
@@ -46,10 +121,34 @@ pub(super) fn emit_message_end_if_open(
     message_open: &mut bool,
     iteration_text: &str,
     tool_calls: &[AgentToolCall],
+) {
+    emit_message_end_with_images_if_open(
+        agent_emitter,
+        message_open,
+        iteration_text,
+        &[],
+        tool_calls,
+    );
+}
+
+/// Like [`emit_message_end_if_open`], for a response that completed with
+/// generated images.
+pub(super) fn emit_message_end_with_images_if_open(
+    agent_emitter: &AgentEventEmitter,
+    message_open: &mut bool,
+    iteration_text: &str,
+    images: &[ImageContent],
+    tool_calls: &[AgentToolCall],
 ) {
     if *message_open {
         agent_emitter.emit(AgentEvent::MessageEnd {
-            message: build_assistant_message(iteration_text, tool_calls),
+            message: agent_emitter
+                .stored_messages()
+                .assistant(assistant_message_with_images(
+                    iteration_text,
+                    images,
+                    tool_calls,
+                )),
         });
         *message_open = false;
     }
@@ -0,0 +1,75 @@
+use crate::error::RociError;
+use crate::provider::{lint_messages, MessageLintSeverity};
+
+use super::RunRequest;
+
+/// Reject a history that breaks a rule of a candidate's provider which the
+/// sanitize pass cannot repair, instead of failing later with a provider 400.
+pub(super) fn validate_message_lints(request: &RunRequest) -> Result<(), RociError> {
+    let mut providers = Vec::new();
+    for model in &request.candidates {
+        let provider = model.provider_name();
+        if providers.contains(&provider) {
+            continue;
+        }
+        providers.push(provider);
+        let blocking = lint_messages(&request.messages, provider)
+            .into_iter()
+            .filter(|lint| {
+                lint.kind.severity() == MessageLintSeverity::Error && !lint.kind.is_repaired()
+            })
+            .map(|lint| lint.to_string())
+            .collect::<Vec<_>>();
+        if !blocking.is_empty() {
+            return Err(RociError::InvalidArgument(format!(
+                "messages break {provider} transcript rules: {}",
+                blocking.join("; ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LanguageModel;
+    use crate::types::ModelMessage;
+
+    fn request(provider: &str, messages: Vec<ModelMessage>) -> RunRequest {
+        RunRequest::new(
+            LanguageModel::Custom {
+                provider: provider.to_string(),
+                model_id: "model".to_string(),
+            },
+            messages,
+        )
+    }
+
+    #[test]
+    fn trailing_assistant_is_rejected_for_anthropic() {
+        let messages = vec![ModelMessage::user("hi"), ModelMessage::assistant("hello")];
+
+        let err = validate_message_lints(&request("anthropic", messages)).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("message 1: transcript ends with an assistant message"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn repairable_lints_do_not_block_the_run() {
+        let messages = vec![ModelMessage::user("one"), ModelMessage::user("two")];
+
+        assert!(validate_message_lints(&request("anthropic", messages)).is_ok());
+    }
+
+    #[test]
+    fn unknown_providers_accept_any_history() {
+        let messages = vec![ModelMessage::assistant("hello")];
+
+        assert!(validate_message_lints(&request("stub", messages)).is_ok());
+    }
+}
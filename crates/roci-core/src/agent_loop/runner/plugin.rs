@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::RociError;
+use crate::tools::tool::Tool;
+use crate::types::{AgentToolCall, AgentToolResult};
+
+use super::{
+    AgentEventEnvelope, AgentEventSink, HookContext, PostToolUseHook, PreToolUseHook,
+    PreToolUseHookResult, RunEvent, RunEventSink, RunRequest, ToolsProviderFn,
+};
+
+/// Bundle of tools, tool hooks, and event listeners added to a run with
+/// [`RunRequest::with_plugin`].
+///
+/// Every method has a no-op default, so a plugin implements only what it
+/// contributes. Plugins are applied when the run starts, in registration
+/// order, after any hooks set directly on the request:
+///
+/// - [`modify_request`](Self::modify_request) runs first for every plugin.
+/// - Plugin tools are appended to [`RunRequest::tools`]; a name that another
+///   plugin or the request already uses fails the run start.
+/// - Pre-tool-use hooks are chained: each sees the call as rewritten by the
+///   hooks before it. The first `Block` (or error) stops the chain and later
+///   pre-hooks are skipped. If any hook replaced the arguments and none
+///   blocked, the last replacement wins.
+/// - Post-tool-use hooks are chained the same way, each receiving the result
+///   of the previous one. They run for blocked calls too.
+/// - Events fan out to every plugin, then to the request's own sink.
+#[async_trait]
+pub trait RunPlugin: Send + Sync {
+    /// Name used in diagnostics such as tool conflict errors.
+    fn name(&self) -> &str;
+
+    /// Tools this plugin adds to the run.
+    fn tools(&self) -> Vec<Arc<dyn Tool>> {
+        Vec::new()
+    }
+
+    /// Inspect, rewrite, or block a tool call before it executes.
+    async fn pre_tool_use(
+        &self,
+        _call: &AgentToolCall,
+        _context: &HookContext,
+        _cancel: CancellationToken,
+    ) -> Result<PreToolUseHookResult, RociError> {
+        Ok(PreToolUseHookResult::Continue)
+    }
+
+    /// Rewrite a tool result before it is persisted.
+    async fn post_tool_use(
+        &self,
+        _call: &AgentToolCall,
+        result: AgentToolResult,
+        _context: &HookContext,
+    ) -> Result<AgentToolResult, RociError> {
+        Ok(result)
+    }
+
+    /// Observe a run event.
+    fn on_event(&self, _event: &RunEvent) {}
+
+    /// Observe an agent event.
+    fn on_agent_event(&self, _event: &AgentEventEnvelope) {}
+
+    /// Adjust the request before the run starts.
+    fn modify_request(&self, _request: &mut RunRequest) {}
+}
+
+/// Fold the request's plugins into its tools, hooks, and event sinks.
+pub(super) fn apply_plugins(request: &mut RunRequest) -> Result<(), RociError> {
+    let plugins = std::mem::take(&mut request.plugins);
+    if plugins.is_empty() {
+        return Ok(());
+    }
+    for plugin in &plugins {
+        plugin.modify_request(request);
+    }
+
+    let mut owners: HashMap<String, String> = request
+        .tools
+        .iter()
+        .map(|tool| (tool.name().to_string(), "the request".to_string()))
+        .collect();
+    let mut plugin_tools = Vec::new();
+    for plugin in &plugins {
+        for tool in plugin.tools() {
+            let owner = format!("plugin '{}'", plugin.name());
+            if let Some(existing) = owners.get(tool.name()) {
+                return Err(RociError::InvalidState(format!(
+                    "tool '{}' from {owner} conflicts with a tool from {existing}",
+                    tool.name()
+                )));
+            }
+            owners.insert(tool.name().to_string(), owner);
+            plugin_tools.push(tool);
+        }
+    }
+    request.tools.extend(plugin_tools.iter().cloned());
+    if let Some(provider) = request.tools_provider.take() {
+        request.tools_provider = Some(append_plugin_tools(provider, plugin_tools.into()));
+    }
+
+    let plugins: Arc<[Arc<dyn RunPlugin>]> = plugins.into();
+    request.hooks.pre_tool_use = Some(chain_pre_tool_use(
+        request.hooks.pre_tool_use.take(),
+        plugins.clone(),
+    ));
+    request.hooks.post_tool_use = Some(chain_post_tool_use(
+        request.hooks.post_tool_use.take(),
+        plugins.clone(),
+    ));
+    request.event_sink = Some(fan_out_events(request.event_sink.take(), plugins.clone()));
+    request.agent_event_sink = Some(fan_out_agent_events(
+        request.agent_event_sink.take(),
+        plugins,
+    ));
+    Ok(())
+}
+
+fn append_plugin_tools(
+    provider: ToolsProviderFn,
+    plugin_tools: Arc<[Arc<dyn Tool>]>,
+) -> ToolsProviderFn {
+    Arc::new(move |iteration| {
+        let tools = provider(iteration);
+        let plugin_tools = plugin_tools.clone();
+        Box::pin(async move {
+            let mut tools = tools.await;
+            tools.extend(plugin_tools.iter().cloned());
+            tools
+        })
+    })
+}
+
+fn chain_pre_tool_use(
+    first: Option<PreToolUseHook>,
+    plugins: Arc<[Arc<dyn RunPlugin>]>,
+) -> PreToolUseHook {
+    Arc::new(move |call, context, cancel| {
+        let first = first.clone();
+        let plugins = plugins.clone();
+        Box::pin(async move {
+            let mut call = call;
+            let mut replaced = false;
+            if let Some(first) = first {
+                let result = first(call.clone(), context.clone(), cancel.clone()).await?;
+                if let Some(block) = apply_pre_tool_use_result(result, &mut call, &mut replaced) {
+                    return Ok(block);
+                }
+            }
+            for plugin in plugins.iter() {
+                let result = plugin.pre_tool_use(&call, &context, cancel.clone()).await?;
+                if let Some(block) = apply_pre_tool_use_result(result, &mut call, &mut replaced) {
+                    return Ok(block);
+                }
+            }
+            Ok(if replaced {
+                PreToolUseHookResult::ReplaceArgs {
+                    args: call.arguments,
+                }
+            } else {
+                PreToolUseHookResult::Continue
+            })
+        })
+    })
+}
+
+/// Fold one hook decision into `call`, returning it when it blocks.
+fn apply_pre_tool_use_result(
+    result: PreToolUseHookResult,
+    call: &mut AgentToolCall,
+    replaced: &mut bool,
+) -> Option<PreToolUseHookResult> {
+    match result {
+        PreToolUseHookResult::Continue => None,
+        PreToolUseHookResult::Block { .. } => Some(result),
+        PreToolUseHookResult::ReplaceArgs { args } => {
+            call.arguments = args;
+            *replaced = true;
+            None
+        }
+    }
+}
+
+fn chain_post_tool_use(
+    first: Option<PostToolUseHook>,
+    plugins: Arc<[Arc<dyn RunPlugin>]>,
+) -> PostToolUseHook {
+    Arc::new(move |call, result, context| {
+        let first = first.clone();
+        let plugins = plugins.clone();
+        Box::pin(async move {
+            let mut result = match first {
+                Some(first) => first(call.clone(), result, context.clone()).await?,
+                None => result,
+            };
+            for plugin in plugins.iter() {
+                result = plugin.post_tool_use(&call, result, &context).await?;
+            }
+            Ok(result)
+        })
+    })
+}
+
+fn fan_out_events(first: Option<RunEventSink>, plugins: Arc<[Arc<dyn RunPlugin>]>) -> RunEventSink {
+    Arc::new(move |event| {
+        for plugin in plugins.iter() {
+            plugin.on_event(&event);
+        }
+        if let Some(first) = first.as_ref() {
+            first(event);
+        }
+    })
+}
+
+fn fan_out_agent_events(
+    first: Option<AgentEventSink>,
+    plugins: Arc<[Arc<dyn RunPlugin>]>,
+) -> AgentEventSink {
+    Arc::new(move |event| {
+        for plugin in plugins.iter() {
+            plugin.on_agent_event(&event);
+        }
+        if let Some(first) = first.as_ref() {
+            first(event);
+        }
+    })
+}
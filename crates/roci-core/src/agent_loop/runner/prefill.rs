@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::error::RociError;
+use crate::provider::{ModelProvider, ProviderRequest};
+use crate::types::ModelMessage;
+
+use super::{PrefillFallback, RunRequest};
+
+/// Reject prefill combined with tools or a tools provider.
+///
+/// A reply that opens with a tool call has no defined relationship to the
+/// prefilled text, and providers disagree on whether tool calls may follow
+/// it at all, so the combination is refused up front.
+pub(super) fn validate_prefill(request: &RunRequest) -> Result<(), RociError> {
+    if request.prefill.is_some() && (!request.tools.is_empty() || request.tools_provider.is_some())
+    {
+        return Err(RociError::InvalidArgument(
+            "prefill cannot be combined with tools".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Make `provider_request` steer the reply to start with `prefill`.
+pub(super) fn apply_prefill(
+    provider: &dyn ModelProvider,
+    fallback: PrefillFallback,
+    prefill: &str,
+    provider_request: &mut ProviderRequest,
+) -> Result<(), RociError> {
+    if provider.supports_assistant_prefix() {
+        Arc::make_mut(&mut provider_request.messages).push(ModelMessage::assistant(prefill));
+        provider_request.settings.assistant_prefix = Some(true);
+        return Ok(());
+    }
+    match fallback {
+        PrefillFallback::Reject => Err(RociError::Configuration(format!(
+            "provider {} cannot continue an assistant prefill; use PrefillFallback::Emulate to approximate it",
+            provider.provider_name()
+        ))),
+        PrefillFallback::Emulate => {
+            Arc::make_mut(&mut provider_request.messages)
+                .insert(0, ModelMessage::system(emulation_note(prefill)));
+            Ok(())
+        }
+    }
+}
+
+fn emulation_note(prefill: &str) -> String {
+    format!(
+        "Your reply has already been started with the text between the markers below. \
+         Continue directly from where it ends; do not repeat it.\n\
+         <reply-start>\n{prefill}\n</reply-start>"
+    )
+}
+
+/// Persisted assistant text: the prefill followed by the completion.
+pub(super) fn prefilled_text(prefill: Option<&str>, completion: String) -> String {
+    match prefill {
+        Some(prefill) => format!("{prefill}{completion}"),
+        None => completion,
+    }
+}
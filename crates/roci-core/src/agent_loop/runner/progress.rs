@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::RunEventPayload;
+use crate::types::{StreamEventType, TextStreamDelta};
+
+/// Shortest gap between two [`RunEventPayload::Progress`] events.
+pub(super) const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Output produced by one provider stream, measured against `max_tokens`.
+///
+/// Counts provider-reported output tokens when a delta carries usage and
+/// otherwise estimates ~4 characters per token of text, reasoning, and tool
+/// arguments, the same heuristic as
+/// [`estimate_text_tokens`](crate::context::estimate_text_tokens).
+pub(super) struct OutputProgress {
+    max_tokens: u32,
+    chars: usize,
+    reported_tokens: Option<u32>,
+    saw_argument_fragments: bool,
+    emitted_tokens: u32,
+    last_emitted_at: Option<Instant>,
+    done: bool,
+}
+
+impl OutputProgress {
+    /// Tracker for a stream limited to `max_tokens`, or `None` without a limit.
+    pub(super) fn start(max_tokens: Option<u32>) -> Option<Self> {
+        let max_tokens = max_tokens.filter(|max_tokens| *max_tokens > 0)?;
+        Some(Self {
+            max_tokens,
+            chars: 0,
+            reported_tokens: None,
+            saw_argument_fragments: false,
+            emitted_tokens: 0,
+            last_emitted_at: None,
+            done: false,
+        })
+    }
+
+    /// Account for `delta` and return the progress event due, if any.
+    ///
+    /// Events are at least [`PROGRESS_INTERVAL`] apart, never report fewer
+    /// tokens than the one before, and stop at the stream's `Done`.
+    pub(super) fn observe(&mut self, delta: &TextStreamDelta) -> Option<RunEventPayload> {
+        if self.done {
+            return None;
+        }
+        if delta.event_type == StreamEventType::Done {
+            self.done = true;
+            return None;
+        }
+        if let Some(usage) = &delta.usage {
+            if usage.output_tokens > 0 {
+                self.reported_tokens = Some(usage.output_tokens);
+            }
+        }
+        self.chars += match delta.event_type {
+            StreamEventType::TextDelta | StreamEventType::RefusalDelta => {
+                delta.text.chars().count()
+            }
+            StreamEventType::Reasoning => delta
+                .reasoning
+                .as_deref()
+                .map_or(0, |reasoning| reasoning.chars().count()),
+            StreamEventType::ToolCallArgumentsDelta => {
+                self.saw_argument_fragments = true;
+                delta
+                    .tool_call
+                    .as_ref()
+                    .and_then(|call| call.arguments.as_str())
+                    .map_or(0, |fragment| fragment.chars().count())
+            }
+            StreamEventType::ToolCallDelta if !self.saw_argument_fragments => delta
+                .tool_call
+                .as_ref()
+                .map_or(0, |call| call.arguments.to_string().chars().count()),
+            _ => 0,
+        };
+        let tokens = self
+            .reported_tokens
+            .unwrap_or_else(|| u32::try_from(self.chars.div_ceil(4)).unwrap_or(u32::MAX))
+            .max(self.emitted_tokens);
+        if tokens == 0 || tokens == self.emitted_tokens {
+            return None;
+        }
+        let now = Instant::now();
+        if self
+            .last_emitted_at
+            .is_some_and(|at| now.duration_since(at) < PROGRESS_INTERVAL)
+        {
+            return None;
+        }
+        self.emitted_tokens = tokens;
+        self.last_emitted_at = Some(now);
+        Some(RunEventPayload::Progress {
+            output_tokens_estimate: tokens,
+            max_tokens: self.max_tokens,
+            fraction: (f64::from(tokens) / f64::from(self.max_tokens)).min(1.0),
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use tokio::time::{Duration, Instant};
+
+use super::limits::RunnerLimits;
+
+/// Provider retries and backoff spent across every iteration of one run.
+///
+/// Per-call retry policy decides whether a single provider call may be
+/// retried; this budget caps the sum over the whole run so a long agent loop
+/// cannot retry indefinitely one call at a time.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RetryBudget {
+    max_retries: usize,
+    max_delay_ms: u64,
+    retries: usize,
+    delay_ms: u64,
+    /// Retries whose backoff ends after this are refused.
+    deadline: Option<Instant>,
+    deadline_reached: bool,
+}
+
+impl RetryBudget {
+    pub(super) fn new(limits: &RunnerLimits) -> Self {
+        Self {
+            max_retries: limits.max_total_retries,
+            max_delay_ms: limits.max_total_retry_delay_ms,
+            retries: 0,
+            delay_ms: 0,
+            deadline: None,
+            deadline_reached: false,
+        }
+    }
+
+    /// Refuse retries whose backoff would end after `deadline`.
+    pub(super) fn with_deadline(mut self, deadline: Option<std::time::Instant>) -> Self {
+        self.deadline = deadline.map(Instant::from_std);
+        self
+    }
+
+    /// Charge one retry that sleeps `delay_ms` before resuming.
+    ///
+    /// Returns the run failure reason when the retry does not fit in what is
+    /// left or would end after the deadline; nothing is charged in that
+    /// case.
+    pub(super) fn consume(&mut self, delay_ms: u64) -> Result<(), String> {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() + Duration::from_millis(delay_ms) > deadline {
+                self.deadline_reached = true;
+                return Err(format!(
+                    "run deadline reached: a {delay_ms}ms retry backoff would end after it"
+                ));
+            }
+        }
+        let delay_total = self.delay_ms.saturating_add(delay_ms);
+        if self.retries >= self.max_retries || delay_total > self.max_delay_ms {
+            return Err(format!(
+                "run retry budget exhausted: {} of {} retries used, {}ms of {}ms retry delay used (next retry needs {delay_ms}ms)",
+                self.retries, self.max_retries, self.delay_ms, self.max_delay_ms
+            ));
+        }
+        self.retries += 1;
+        self.delay_ms = delay_total;
+        Ok(())
+    }
+
+    pub(super) fn retries_remaining(&self) -> usize {
+        self.max_retries.saturating_sub(self.retries)
+    }
+
+    pub(super) fn delay_remaining_ms(&self) -> u64 {
+        self.max_delay_ms.saturating_sub(self.delay_ms)
+    }
+
+    /// Whether a retry was refused because of the deadline.
+    pub(super) fn deadline_reached(&self) -> bool {
+        self.deadline_reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(max_retries: usize, max_delay_ms: u64) -> RetryBudget {
+        RetryBudget {
+            max_retries,
+            max_delay_ms,
+            retries: 0,
+            delay_ms: 0,
+            deadline: None,
+            deadline_reached: false,
+        }
+    }
+
+    #[test]
+    fn consume_charges_retries_and_delay_until_exhausted() {
+        let mut budget = budget(2, 1_000);
+
+        budget.consume(100).unwrap();
+        assert_eq!(budget.retries_remaining(), 1);
+        assert_eq!(budget.delay_remaining_ms(), 900);
+        budget.consume(100).unwrap();
+
+        let reason = budget.consume(1).unwrap_err();
+        assert!(reason.contains("2 of 2 retries used"), "{reason}");
+        assert_eq!(budget.retries_remaining(), 0);
+    }
+
+    #[test]
+    fn delay_that_overruns_the_budget_is_rejected_without_charging() {
+        let mut budget = budget(5, 1_000);
+        budget.consume(800).unwrap();
+
+        let reason = budget.consume(300).unwrap_err();
+
+        assert!(
+            reason.contains("800ms of 1000ms retry delay used"),
+            "{reason}"
+        );
+        assert_eq!(budget.retries_remaining(), 4);
+        assert_eq!(budget.delay_remaining_ms(), 200);
+        budget.consume(200).unwrap();
+    }
+
+    #[test]
+    fn retry_that_outlasts_the_deadline_is_refused() {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(1);
+        let mut budget = budget(5, 60_000).with_deadline(Some(deadline));
+        budget.consume(10).unwrap();
+        assert!(!budget.deadline_reached());
+
+        let reason = budget.consume(5_000).unwrap_err();
+
+        assert!(reason.contains("deadline"), "{reason}");
+        assert!(budget.deadline_reached());
+        assert_eq!(budget.retries_remaining(), 4);
+    }
+}
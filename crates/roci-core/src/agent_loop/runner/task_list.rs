@@ -0,0 +1,338 @@
+//! Task-list mode: one run working through independent prompts.
+//!
+//! Each [`TaskSpec`] runs as a sub-run of the same [`LoopRunner`], started
+//! from a copy of the request whose messages are the shared context plus
+//! the task prompt. Sub-runs keep the run id, event sequence, and sinks, so
+//! their events read as part of the run; their own lifecycle events are
+//! dropped in favor of the task boundary events and the run's single
+//! `Started` and terminal lifecycle events. Webhooks fire once, for the run.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::RociError;
+use crate::tools::ScratchDir;
+use crate::types::{ModelMessage, Role, Usage};
+
+use super::control::RunEventEmitter;
+use super::dispatch::EventDispatcher;
+use super::engine::ResultDelivery;
+use super::plugin::apply_plugins;
+use super::{
+    LoopRunner, RunEvent, RunEventPayload, RunEventSink, RunEventStream, RunHandle, RunLifecycle,
+    RunRequest, RunResult, RunStatus, Runner,
+};
+use crate::agent_loop::webhook::RunWebhooks;
+use crate::agent_loop::{EventTags, TaskResult};
+
+/// One prompt of a [`RunRequest::task_list`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskSpec {
+    /// Names the task in events and [`TaskResult`]s; unique within the list.
+    pub id: String,
+    /// Sent as the user message that starts the task.
+    pub prompt: String,
+}
+
+impl TaskSpec {
+    pub fn new(id: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            prompt: prompt.into(),
+        }
+    }
+}
+
+/// What a task sees of the tasks that ran before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskCarryOver {
+    /// Only the request's messages.
+    #[default]
+    None,
+    /// The request's messages and a system note with each earlier task's
+    /// final reply or error.
+    Summary,
+    /// The request's messages and every message of the earlier tasks.
+    Full,
+}
+
+/// Start a run that works through `tasks`; see [`RunRequest::task_list`].
+///
+/// # Errors
+///
+/// Returns [`RociError::Configuration`] when the list is empty, a task id is
+/// empty or repeated, or the request records a transcript.
+pub(super) async fn start_task_list(
+    runner: &LoopRunner,
+    mut request: RunRequest,
+    tasks: Vec<TaskSpec>,
+) -> Result<RunHandle, RociError> {
+    validate_tasks(&request, &tasks)?;
+    apply_plugins(&mut request)?;
+    let dispatcher = EventDispatcher::install(&mut request);
+    let (handle, abort_rx, handle_result_tx, input_rx) = RunHandle::new(request.run_id);
+    let (result_tx, result_rx) = oneshot::channel();
+    let delivery = ResultDelivery {
+        dispatcher,
+        handle_tx: handle_result_tx,
+        status: handle.shared_status(),
+        scratch: ScratchDir::for_run(request.run_id),
+        keep_scratch: request.keep_scratch,
+        webhooks: RunWebhooks::from_request(&request),
+        transcript: None,
+        payload_log: None,
+    };
+    tokio::spawn(delivery.deliver(result_rx));
+    let runner = runner.clone();
+    tokio::spawn(async move {
+        let result = run_tasks(&runner, request, tasks, abort_rx, input_rx).await;
+        let _ = result_tx.send(result);
+    });
+    Ok(handle)
+}
+
+fn validate_tasks(request: &RunRequest, tasks: &[TaskSpec]) -> Result<(), RociError> {
+    if tasks.is_empty() {
+        return Err(RociError::Configuration(
+            "task_list must contain at least one task".to_string(),
+        ));
+    }
+    let mut ids = HashSet::new();
+    for task in tasks {
+        if task.id.trim().is_empty() {
+            return Err(RociError::Configuration(
+                "task_list ids must not be empty".to_string(),
+            ));
+        }
+        if !ids.insert(task.id.as_str()) {
+            return Err(RociError::Configuration(format!(
+                "task_list id '{}' is used more than once",
+                task.id
+            )));
+        }
+    }
+    if request.transcript.is_some() {
+        return Err(RociError::Configuration(
+            "task_list cannot be combined with a transcript".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn run_tasks(
+    runner: &LoopRunner,
+    request: RunRequest,
+    tasks: Vec<TaskSpec>,
+    mut abort_rx: oneshot::Receiver<()>,
+    mut input_rx: mpsc::UnboundedReceiver<ModelMessage>,
+) -> RunResult {
+    let emitter = RunEventEmitter::new(request.run_id, request.event_sink.clone())
+        .with_identity(
+            Some(request.active_model().to_string()),
+            EventTags::new(request.event_tags.clone()),
+        )
+        .with_sequence(request.event_sequence.clone());
+    emitter.emit(
+        RunEventStream::Lifecycle,
+        RunEventPayload::Lifecycle {
+            state: RunLifecycle::Started,
+        },
+    );
+
+    let total = tasks.len();
+    // The request's messages followed by every message the tasks added.
+    let mut messages = request.messages.clone();
+    let mut task_results: Vec<TaskResult> = Vec::with_capacity(total);
+    let mut usage = Usage::default();
+    let mut plan = Vec::new();
+    let mut changes = Vec::new();
+    let mut artifacts = Vec::new();
+    let mut payload_sizes = Vec::new();
+    let mut model = None;
+    let mut canceled = false;
+
+    for (index, task) in tasks.into_iter().enumerate() {
+        emitter.emit(
+            RunEventStream::Lifecycle,
+            RunEventPayload::TaskStarted {
+                task_id: task.id.clone(),
+                index,
+                total,
+            },
+        );
+        let mut context = match request.task_carry_over {
+            TaskCarryOver::None => request.messages.clone(),
+            TaskCarryOver::Full => messages.clone(),
+            TaskCarryOver::Summary => {
+                let mut context = request.messages.clone();
+                context.extend(carry_over_summary(&task_results));
+                context
+            }
+        };
+        let context_len = context.len();
+        context.push(ModelMessage::user(task.prompt));
+
+        let (result, aborted) = run_task(
+            runner,
+            task_request(&request, context),
+            &mut abort_rx,
+            &mut input_rx,
+        )
+        .await;
+
+        let task_messages = result
+            .messages
+            .get(context_len..)
+            .map(<[ModelMessage]>::to_vec)
+            .unwrap_or_default();
+        messages.extend(task_messages.iter().cloned());
+        if let Some(task_usage) = &result.usage_delta {
+            usage.merge(task_usage);
+        }
+        if !result.plan.is_empty() {
+            plan = result.plan;
+        }
+        changes.extend(result.changes);
+        artifacts.extend(result.artifacts);
+        payload_sizes.extend(result.payload_sizes);
+        model = result.model.or(model);
+        emitter.emit(
+            RunEventStream::Lifecycle,
+            RunEventPayload::TaskCompleted {
+                task_id: task.id.clone(),
+                index,
+                status: result.status,
+                error: result.error.clone(),
+            },
+        );
+        task_results.push(TaskResult {
+            id: task.id,
+            status: result.status,
+            error: result.error,
+            messages: task_messages,
+            usage: result.usage_delta,
+        });
+        if aborted {
+            canceled = true;
+            break;
+        }
+    }
+
+    let (state, result) = if canceled {
+        (
+            RunLifecycle::Canceled,
+            RunResult::canceled_with_messages(messages),
+        )
+    } else {
+        (
+            RunLifecycle::Completed,
+            RunResult::completed_with_messages(messages),
+        )
+    };
+    emitter.emit(
+        RunEventStream::Lifecycle,
+        RunEventPayload::Lifecycle { state },
+    );
+    let result = result
+        .with_usage_delta(usage)
+        .with_plan(plan)
+        .with_changes(changes)
+        .with_artifacts(artifacts)
+        .with_payload_sizes(payload_sizes)
+        .with_task_results(task_results);
+    match model {
+        Some(model) => result.with_model(model),
+        None => result,
+    }
+}
+
+/// The sub-run request for one task.
+fn task_request(request: &RunRequest, messages: Vec<ModelMessage>) -> RunRequest {
+    let mut task = request.clone();
+    task.messages = messages;
+    task.event_sink = request.event_sink.clone().map(without_lifecycle);
+    // The run delivers webhooks and removes the shared scratch directory.
+    task.webhooks = Vec::new();
+    task.keep_scratch = true;
+    task
+}
+
+/// Pass every event but lifecycle ones on to `sink`.
+fn without_lifecycle(sink: RunEventSink) -> RunEventSink {
+    Arc::new(move |event: RunEvent| {
+        if !matches!(event.payload, RunEventPayload::Lifecycle { .. }) {
+            sink(event);
+        }
+    })
+}
+
+/// Run one task to its end, forwarding queued messages and an abort of the
+/// run. Returns whether the run was aborted while the task ran.
+async fn run_task(
+    runner: &LoopRunner,
+    request: RunRequest,
+    abort_rx: &mut oneshot::Receiver<()>,
+    input_rx: &mut mpsc::UnboundedReceiver<ModelMessage>,
+) -> (RunResult, bool) {
+    let messages = request.messages.clone();
+    let mut handle = match runner.start(request).await {
+        Ok(handle) => handle,
+        Err(err) => {
+            return (
+                RunResult::failed_with_messages(err.to_string(), messages),
+                false,
+            )
+        }
+    };
+    let input_tx = handle.input_tx.clone();
+    let mut abort_tx = handle.take_abort_sender();
+    let mut aborted = false;
+    let wait = handle.wait();
+    tokio::pin!(wait);
+    loop {
+        tokio::select! {
+            result = &mut wait => return (result, aborted),
+            _ = &mut *abort_rx, if !aborted => {
+                aborted = true;
+                if let Some(abort_tx) = abort_tx.take() {
+                    let _ = abort_tx.send(());
+                }
+            }
+            Some(message) = input_rx.recv() => {
+                if let Some(input_tx) = &input_tx {
+                    let _ = input_tx.send(message);
+                }
+            }
+        }
+    }
+}
+
+/// System note with the outcome of each earlier task; `None` before the
+/// first task.
+fn carry_over_summary(results: &[TaskResult]) -> Option<ModelMessage> {
+    if results.is_empty() {
+        return None;
+    }
+    let mut summary = "Outcome of the earlier tasks:".to_string();
+    for result in results {
+        let outcome = match result.status {
+            RunStatus::Completed => result
+                .messages
+                .iter()
+                .rev()
+                .find(|message| message.role == Role::Assistant)
+                .map(ModelMessage::text)
+                .unwrap_or_default(),
+            _ => format!(
+                "did not finish: {}",
+                result.error.as_deref().unwrap_or("no reason given")
+            ),
+        };
+        summary.push_str(&format!("\n- {}: {}", result.id, outcome));
+    }
+    Some(ModelMessage::system(summary))
+}
@@ -0,0 +1,205 @@
+use super::*;
+use crate::agent_loop::{RunStatus, RunnerOptions};
+use tokio::time::{timeout, Duration};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+fn limited_runner(options: RunnerOptions) -> LoopRunner {
+    let (runner, _requests) = test_runner(ProviderScenario::DelayedTextWithUsage);
+    runner.with_options(options).expect("valid options")
+}
+
+fn lifecycle(events: &[RunEvent]) -> Vec<RunLifecycle> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::Lifecycle { state } => Some(state.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Start a run whose `Started` lifecycle event appends `label` to `order`.
+async fn start_labeled(
+    runner: &LoopRunner,
+    label: &'static str,
+    order: &Arc<std::sync::Mutex<Vec<&'static str>>>,
+) -> (RunHandle, Arc<std::sync::Mutex<Vec<RunEvent>>>) {
+    let (sink, events) = capture_events();
+    let order = order.clone();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user(label)]);
+    request.event_sink = Some(Arc::new(move |event: RunEvent| {
+        if matches!(
+            event.payload,
+            RunEventPayload::Lifecycle {
+                state: RunLifecycle::Started
+            }
+        ) {
+            order.lock().expect("order lock").push(label);
+        }
+        sink(event);
+    }));
+    let handle = runner.start(request).await.expect("start run");
+    (handle, events)
+}
+
+#[tokio::test]
+async fn runs_over_the_limit_queue_and_start_in_order() {
+    let runner = limited_runner(
+        RunnerOptions::new()
+            .with_max_concurrent_runs(1)
+            .with_queue_capacity(2),
+    );
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let (first, _) = start_labeled(&runner, "first", &order).await;
+    let (second, second_events) = start_labeled(&runner, "second", &order).await;
+    let (third, third_events) = start_labeled(&runner, "third", &order).await;
+
+    assert_eq!(first.status(), RunStatus::Running);
+    assert_eq!(second.status(), RunStatus::Queued);
+    assert_eq!(third.status(), RunStatus::Queued);
+    let metrics = runner.metrics();
+    assert_eq!((metrics.running, metrics.queued), (1, 2));
+
+    for handle in [first, second, third] {
+        let result = timeout(Duration::from_secs(5), handle.wait())
+            .await
+            .expect("run wait timeout");
+        assert_eq!(result.status, RunStatus::Completed);
+    }
+    assert_eq!(*order.lock().unwrap(), ["first", "second", "third"]);
+    for (events, position) in [(second_events, 1), (third_events, 2)] {
+        let states = lifecycle(&events.lock().unwrap());
+        assert!(
+            matches!(
+                states.as_slice(),
+                [RunLifecycle::Queued { position: p }, RunLifecycle::Started, RunLifecycle::Completed]
+                    if *p == position
+            ),
+            "{states:?}"
+        );
+    }
+    let metrics = runner.metrics();
+    assert_eq!((metrics.running, metrics.queued), (0, 0));
+    assert!(metrics.average_run_duration.is_some());
+}
+
+#[tokio::test]
+async fn queued_runs_can_be_aborted_while_waiting() {
+    let runner = limited_runner(
+        RunnerOptions::new()
+            .with_max_concurrent_runs(1)
+            .with_queue_capacity(1),
+    );
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (first, _) = start_labeled(&runner, "first", &order).await;
+    let (mut queued, queued_events) = start_labeled(&runner, "queued", &order).await;
+
+    assert!(queued.abort());
+    let result = timeout(Duration::from_secs(2), queued.wait())
+        .await
+        .expect("queued run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert_eq!(runner.metrics().queued, 0);
+    let states = lifecycle(&queued_events.lock().unwrap());
+    assert!(
+        matches!(
+            states.as_slice(),
+            [RunLifecycle::Queued { position: 1 }, RunLifecycle::Canceled]
+        ),
+        "{states:?}"
+    );
+    let result = timeout(Duration::from_secs(2), first.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(*order.lock().unwrap(), ["first"]);
+    assert_eq!(runner.metrics().running, 0);
+}
+
+#[tokio::test]
+async fn runs_beyond_queue_capacity_are_rejected_with_retry_after() {
+    let runner = limited_runner(
+        RunnerOptions::new()
+            .with_max_concurrent_runs(1)
+            .with_queue_capacity(1),
+    );
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (first, _) = start_labeled(&runner, "first", &order).await;
+    let (second, _) = start_labeled(&runner, "second", &order).await;
+
+    let rejected = runner
+        .start(RunRequest::new(
+            test_model(),
+            vec![ModelMessage::user("third")],
+        ))
+        .await
+        .expect_err("queue is full");
+
+    // No run has finished, so the default retry-after applies.
+    assert!(matches!(
+        rejected,
+        RociError::RateLimited {
+            retry_after_ms: Some(1000)
+        }
+    ));
+    assert_eq!(runner.metrics().rejected, 1);
+    for handle in [first, second] {
+        let result = timeout(Duration::from_secs(5), handle.wait())
+            .await
+            .expect("run wait timeout");
+        assert_eq!(result.status, RunStatus::Completed);
+    }
+
+    let (busy, _) = start_labeled(&runner, "busy", &order).await;
+    let (_waiting, _) = start_labeled(&runner, "waiting", &order).await;
+    let Err(RociError::RateLimited {
+        retry_after_ms: Some(retry_after_ms),
+    }) = runner
+        .start(RunRequest::new(test_model(), vec![ModelMessage::user("x")]))
+        .await
+    else {
+        panic!("expected a rate-limit rejection");
+    };
+    let average = runner.metrics().average_run_duration.expect("average");
+    // Two average runs: the one in the queue and the one that would follow.
+    let expected = u64::try_from((average * 2).as_millis()).unwrap();
+    assert!(retry_after_ms.abs_diff(expected) <= 1, "{retry_after_ms}ms");
+    drop(busy);
+}
+
+#[tokio::test]
+async fn queued_runs_fail_after_the_queue_timeout() {
+    let runner = limited_runner(
+        RunnerOptions::new()
+            .with_max_concurrent_runs(1)
+            .with_queue_capacity(1)
+            .with_queue_timeout(Duration::from_millis(20)),
+    );
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (first, _) = start_labeled(&runner, "first", &order).await;
+    let (queued, _) = start_labeled(&runner, "queued", &order).await;
+
+    let result = timeout(Duration::from_secs(2), queued.wait())
+        .await
+        .expect("queued run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert!(result
+        .error
+        .as_deref()
+        .is_some_and(|error| error.contains("admission queue")));
+    assert_eq!(runner.metrics().queued, 0);
+    drop(first);
+}
+
+#[test]
+fn zero_concurrent_runs_is_rejected() {
+    let (runner, _requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+
+    let result = runner.with_options(RunnerOptions::new().with_max_concurrent_runs(0));
+
+    assert!(matches!(result, Err(RociError::Configuration(_))));
+}
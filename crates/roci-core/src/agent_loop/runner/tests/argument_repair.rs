@@ -0,0 +1,83 @@
+use super::*;
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+/// Run the malformed-arguments scenario against a tool that echoes `path`.
+async fn run_malformed_call(repair: bool) -> (RunResult, Vec<RunEvent>, usize) {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolMalformedArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("call schema_tool")])
+        .with_tools(vec![tracked_schema_path_tool(executions.clone())])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink)
+        .with_repair_tool_arguments(repair);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+    let events = events.lock().expect("event lock").clone();
+    (result, events, executions.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn malformed_arguments_are_repaired_and_flagged() {
+    let (result, events, executions) = run_malformed_call(true).await;
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(executions, 1);
+    let started = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            RunEventPayload::ToolCallStarted { call, repaired, .. } => Some((call, *repaired)),
+            _ => None,
+        })
+        .expect("tool call started");
+    assert!(started.1, "ToolCallStarted must note the repair");
+    assert_eq!(
+        started.0.arguments,
+        serde_json::json!({ "path": "/tmp/test" })
+    );
+    let (tool_result, repaired) = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            RunEventPayload::ToolResult {
+                result, repaired, ..
+            } => Some((result, *repaired)),
+            _ => None,
+        })
+        .expect("tool result");
+    assert!(repaired, "ToolResult must note the repair");
+    assert!(!tool_result.is_error);
+    assert_eq!(tool_result.result["path"], "/tmp/test");
+
+    // History keeps the repaired arguments so the next request is valid JSON.
+    let call = result
+        .messages
+        .iter()
+        .flat_map(|message| message.tool_calls())
+        .next()
+        .expect("assistant tool call in history");
+    assert_eq!(call.arguments, serde_json::json!({ "path": "/tmp/test" }));
+}
+
+#[tokio::test]
+async fn strict_runs_report_malformed_arguments_as_validation_errors() {
+    let (result, events, executions) = run_malformed_call(false).await;
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(executions, 0);
+    assert!(events.iter().all(|event| !matches!(
+        event.payload,
+        RunEventPayload::ToolCallStarted { repaired: true, .. }
+            | RunEventPayload::ToolResult { repaired: true, .. }
+    )));
+    let tool_results = tool_results_from_events(&events);
+    assert_eq!(tool_results.len(), 1);
+    let (_, result_json, is_error) = &tool_results[0];
+    assert!(is_error);
+    assert!(result_json["error"]
+        .as_str()
+        .is_some_and(|error| error.contains("Argument validation failed")));
+}
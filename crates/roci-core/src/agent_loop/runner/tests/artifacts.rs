@@ -0,0 +1,231 @@
+use super::*;
+use crate::agent_loop::RunStatus;
+use crate::tools::{
+    Artifact, ArtifactLocation, RunArtifact, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+    DEFAULT_ARTIFACT_SPILL_BYTES,
+};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+/// `noop_tool` stand-in that registers one small and one oversized output.
+fn reporting_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "registers outputs",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, ctx: ToolExecutionContext| async move {
+            ctx.add_artifact(
+                Artifact::bytes("summary.txt", b"all good\n".to_vec(), "text/plain")
+                    .with_description("run summary"),
+            )?;
+            ctx.add_artifact(Artifact::bytes(
+                "dump.bin",
+                vec![7; DEFAULT_ARTIFACT_SPILL_BYTES + 1],
+                "application/octet-stream",
+            ))?;
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))
+}
+
+/// Concurrency-safe tool that registers `count` outputs after `delay`.
+fn parallel_reporting_tool(name: &str, delay: Duration, count: usize) -> Arc<dyn Tool> {
+    let tool_name = name.to_string();
+    Arc::new(
+        AgentTool::new(
+            name,
+            format!("{tool_name} tool"),
+            AgentToolParameters::empty(),
+            move |_args, ctx: ToolExecutionContext| {
+                let tool_name = tool_name.clone();
+                async move {
+                    tokio::time::sleep(delay).await;
+                    for index in 0..count {
+                        ctx.add_artifact(Artifact::bytes(
+                            format!("{tool_name}-{index}.txt"),
+                            tool_name.as_bytes().to_vec(),
+                            "text/plain",
+                        ))?;
+                        tokio::task::yield_now().await;
+                    }
+                    Ok(serde_json::json!({ "tool": tool_name }))
+                }
+            },
+        )
+        .with_static_safety(
+            ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read),
+            ToolSafetySummary {
+                read_only_by_default: true,
+                destructive_by_default: false,
+                concurrency_safe_by_default: true,
+                approval_kind: ToolSafetyKind::Read,
+            },
+        ),
+    )
+}
+
+fn artifact_events(events: &[RunEvent]) -> Vec<(usize, RunArtifact)> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(index, event)| match &event.payload {
+            RunEventPayload::ArtifactAdded { artifact } => Some((index, artifact.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn tool_result_index(events: &[RunEvent], tool_call_id: &str) -> usize {
+    events
+        .iter()
+        .position(|event| {
+            matches!(
+                &event.payload,
+                RunEventPayload::ToolResult { result, .. } if result.tool_call_id == tool_call_id
+            )
+        })
+        .expect("tool result event")
+}
+
+#[tokio::test]
+async fn small_artifacts_stay_inline_and_large_ones_spill_to_the_run_dir() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let root = tempfile::tempdir().expect("artifacts temp dir");
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("report")])
+        .with_tools(vec![reporting_tool()])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_artifacts_dir(root.path().to_path_buf());
+    request.event_sink = Some(sink);
+    let run_id = request.run_id;
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    assert_eq!(result.artifacts.len(), 2);
+    let summary = &result.artifacts[0];
+    assert_eq!(summary.name, "summary.txt");
+    assert_eq!(summary.description.as_deref(), Some("run summary"));
+    assert_eq!(summary.tool_name.as_deref(), Some("noop_tool"));
+    assert_eq!(
+        summary.location,
+        ArtifactLocation::Inline {
+            bytes: b"all good\n".to_vec()
+        }
+    );
+
+    let dump = &result.artifacts[1];
+    assert_eq!(dump.size, Some(DEFAULT_ARTIFACT_SPILL_BYTES as u64 + 1));
+    let ArtifactLocation::File { path, spilled } = &dump.location else {
+        panic!("large artifact should spill, got {:?}", dump.location);
+    };
+    assert!(spilled);
+    assert!(path.starts_with(root.path().join(run_id.to_string())));
+    assert_eq!(
+        std::fs::read(path).expect("read spilled artifact").len(),
+        DEFAULT_ARTIFACT_SPILL_BYTES + 1
+    );
+
+    let events = events.lock().expect("events lock");
+    let announced = artifact_events(&events);
+    assert_eq!(
+        announced
+            .iter()
+            .map(|(_, artifact)| artifact.clone())
+            .collect::<Vec<_>>(),
+        result.artifacts
+    );
+    let call_id = summary.tool_call_id.as_deref().expect("tool call id");
+    let result_index = tool_result_index(&events, call_id);
+    assert!(announced.iter().all(|(index, _)| *index > result_index));
+    let completed = events
+        .iter()
+        .position(|event| {
+            matches!(
+                event.payload,
+                RunEventPayload::Lifecycle {
+                    state: RunLifecycle::Completed
+                }
+            )
+        })
+        .expect("completed event");
+    assert!(announced.iter().all(|(index, _)| *index < completed));
+}
+
+#[tokio::test]
+async fn runs_without_artifacts_report_none() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("look")]);
+    request.tools = vec![Arc::new(AgentTool::new(
+        "noop_tool",
+        "reads only",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))];
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert!(result.artifacts.is_empty());
+    assert!(artifact_events(&events.lock().expect("events lock")).is_empty());
+}
+
+#[tokio::test]
+async fn parallel_batches_collect_every_artifact_after_its_own_result() {
+    let (runner, _requests) = test_runner(ProviderScenario::ParallelSafeBatchThenComplete);
+    let (sink, events) = capture_events();
+    let root = tempfile::tempdir().expect("artifacts temp dir");
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("parallel")])
+        .with_tools(vec![
+            // The first call finishes last, so registration order differs from call order.
+            parallel_reporting_tool("read", Duration::from_millis(50), 20),
+            parallel_reporting_tool("ls", Duration::ZERO, 20),
+        ])
+        .with_artifacts_dir(root.path().to_path_buf());
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    assert_eq!(result.artifacts.len(), 40);
+    for (call_id, tool) in [("safe-read-1", "read"), ("safe-ls-2", "ls")] {
+        let names = result
+            .artifacts
+            .iter()
+            .filter(|artifact| artifact.tool_call_id.as_deref() == Some(call_id))
+            .map(|artifact| artifact.name.clone())
+            .collect::<Vec<_>>();
+        let expected = (0..20)
+            .map(|index| format!("{tool}-{index}.txt"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+
+    let events = events.lock().expect("events lock");
+    let announced = artifact_events(&events);
+    assert_eq!(announced.len(), 40);
+    let read_result = tool_result_index(&events, "safe-read-1");
+    let ls_result = tool_result_index(&events, "safe-ls-2");
+    assert!(read_result < ls_result);
+    for (index, artifact) in &announced {
+        match artifact.tool_call_id.as_deref() {
+            Some("safe-read-1") => assert!(*index > read_result && *index < ls_result),
+            Some("safe-ls-2") => assert!(*index > ls_result),
+            other => panic!("unexpected tool call id {other:?}"),
+        }
+    }
+}
@@ -7,13 +7,14 @@ async fn auto_compaction_triggers_when_context_exceeds_reserved_window() {
     let calls_clone = calls.clone();
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
     request.hooks = RunHooks {
-        compaction: Some(Arc::new(move |_messages, _cancel| {
+        compaction: Some(Arc::new(move |_messages, _context, _cancel| {
             let calls_clone = calls_clone.clone();
             Box::pin(async move {
                 calls_clone.fetch_add(1, Ordering::SeqCst);
                 Ok(None)
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -42,7 +43,7 @@ async fn auto_compaction_replaces_messages_before_provider_call() {
         ],
     );
     request.hooks = RunHooks {
-        compaction: Some(Arc::new(move |messages, _cancel| {
+        compaction: Some(Arc::new(move |messages, _context, _cancel| {
             Box::pin(async move {
                 Ok(Some(vec![
                     messages[0].clone(),
@@ -54,6 +55,7 @@ async fn auto_compaction_replaces_messages_before_provider_call() {
                 ]))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -83,13 +85,14 @@ async fn compaction_failure_fails_run_and_surfaces_error() {
     let (runner, _requests) = test_runner(ProviderScenario::MissingOptionalFields);
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
     request.hooks = RunHooks {
-        compaction: Some(Arc::new(move |_messages, _cancel| {
+        compaction: Some(Arc::new(move |_messages, _context, _cancel| {
             Box::pin(async {
                 Err(RociError::InvalidState(
                     "forced compaction failure".to_string(),
                 ))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -118,7 +121,7 @@ async fn abort_during_auto_compaction_cancels_compaction_token_and_run() {
 
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
     request.hooks = RunHooks {
-        compaction: Some(Arc::new(move |_messages, cancel| {
+        compaction: Some(Arc::new(move |_messages, _context, cancel| {
             let started_tx = started_tx.clone();
             let compaction_cancel_observed = compaction_cancel_observed_for_hook.clone();
             Box::pin(async move {
@@ -132,6 +135,7 @@ async fn abort_during_auto_compaction_cancels_compaction_token_and_run() {
                 std::future::pending::<Result<Option<Vec<ModelMessage>>, RociError>>().await
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -5,7 +5,9 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::time::{sleep, timeout, Duration};
 
-use support::{test_model, test_runner, ProviderScenario};
+use crate::agent_loop::{BudgetLimit, RunBudget};
+use crate::models::{ModelPricing, PricingTable};
+use support::{capture_events, test_model, test_runner, ProviderScenario};
 
 #[tokio::test]
 async fn no_budget_configured_preserves_existing_behavior() {
@@ -575,3 +577,141 @@ async fn exact_anchor_allows_request_that_full_heuristic_would_reject() {
         "accumulated output from both calls"
     );
 }
+
+// ── run budget ──
+
+fn noop_tool() -> Arc<dyn crate::tools::tool::Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({"ok": true}))
+        },
+    ))
+}
+
+fn budget_warnings(events: &[RunEvent]) -> Vec<BudgetLimit> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::BudgetWarning { reading } => Some(reading.limit),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn token_budget_stops_run_before_next_llm_phase() {
+    let (runner, requests) = test_runner(ProviderScenario::RepeatedToolCallWithLargeUsage);
+    let (sink, events) = capture_events();
+    // Each call reports 50k tokens: 50k, 100k (warning), 150k (exceeded).
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("loop forever")])
+        .with_tools(vec![noop_tool()])
+        .with_event_sink(sink)
+        .with_budget(RunBudget::new().with_max_total_tokens(120_000));
+
+    let handle = runner.start(request).await.unwrap();
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run should stop at the token budget");
+
+    assert_eq!(result.status, RunStatus::BudgetExceeded);
+    let error = result.error.as_deref().unwrap();
+    assert!(error.contains("150000 of 120000 tokens"), "got: {error}");
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    let usage = result.usage_delta.expect("usage should be reported");
+    assert_eq!(usage.input_tokens, 120_000);
+    assert_eq!(usage.output_tokens, 30_000);
+    // User prompt plus three completed assistant/tool-result iterations.
+    assert_eq!(result.messages.len(), 7);
+
+    let events = events.lock().unwrap();
+    assert_eq!(budget_warnings(&events), vec![BudgetLimit::TotalTokens]);
+    assert!(events.iter().any(|event| matches!(
+        &event.payload,
+        RunEventPayload::Lifecycle {
+            state: RunLifecycle::BudgetExceeded { reading, .. },
+        } if reading.limit == BudgetLimit::TotalTokens
+    )));
+}
+
+#[tokio::test]
+async fn cost_budget_uses_pricing_table() {
+    let (runner, requests) = test_runner(ProviderScenario::RepeatedToolCallWithLargeUsage);
+    // $1/M input and $2/M output: each call costs $0.04 + $0.02.
+    let pricing =
+        PricingTable::empty().with_model("stub", "stub-model", ModelPricing::new(1.0, 2.0));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("loop forever")])
+        .with_tools(vec![noop_tool()])
+        .with_budget(
+            RunBudget::new()
+                .with_max_cost_usd(0.10)
+                .with_pricing(pricing),
+        );
+
+    let handle = runner.start(request).await.unwrap();
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run should stop at the cost budget");
+
+    assert_eq!(result.status, RunStatus::BudgetExceeded);
+    let error = result.error.as_deref().unwrap();
+    assert!(error.contains("spent $0.1200 of $0.1000"), "got: {error}");
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(result.messages.len(), 5);
+}
+
+#[tokio::test]
+async fn cost_budget_without_pricing_is_rejected_at_start() {
+    let (runner, requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_budget(RunBudget::new().with_max_cost_usd(1.0));
+
+    let err = runner
+        .start(request)
+        .await
+        .expect_err("unpriced model should be rejected");
+    assert!(
+        err.to_string().contains("no pricing for model"),
+        "got: {err}"
+    );
+    assert!(requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn wall_clock_budget_interrupts_long_tool_batch() {
+    let (runner, requests) = test_runner(ProviderScenario::RepeatedToolCallWithLargeUsage);
+    let (sink, events) = capture_events();
+    let slow_tool: Arc<dyn crate::tools::tool::Tool> = Arc::new(AgentTool::new(
+        "noop_tool",
+        "sleeps far past the budget",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            sleep(Duration::from_secs(30)).await;
+            Ok(serde_json::json!({"ok": true}))
+        },
+    ));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("run a slow tool")])
+        .with_tools(vec![slow_tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink)
+        .with_budget(RunBudget::new().with_max_wall_clock(Duration::from_millis(200)));
+
+    let handle = runner.start(request).await.unwrap();
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("wall-clock budget should interrupt the tool batch");
+
+    assert_eq!(result.status, RunStatus::BudgetExceeded);
+    let error = result.error.as_deref().unwrap();
+    assert!(error.contains("of 0.2s"), "got: {error}");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    // The prompt and the assistant tool call survive the interruption.
+    assert_eq!(result.messages.len(), 2);
+    assert_eq!(result.messages[1].tool_calls().len(), 1);
+    assert_eq!(
+        budget_warnings(&events.lock().unwrap()),
+        vec![BudgetLimit::WallClock]
+    );
+}
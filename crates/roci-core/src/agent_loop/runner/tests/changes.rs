@@ -0,0 +1,163 @@
+use super::*;
+use crate::agent_loop::{ApprovalRequest, RunStatus};
+use crate::tools::{FileChange, FileChangeKind};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+/// `noop_tool` stand-in that edits files through the run's change log the
+/// way the builtin file tools do.
+fn editing_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "records file edits",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, ctx: ToolExecutionContext| async move {
+            let changes = ctx.changes.expect("runs provide a change log");
+            changes.record("write_file", "src/new.rs", None, Some(b"fn main() {}\n"));
+            changes.record(
+                "write_file",
+                "src/lib.rs",
+                Some(b"mod a;\nmod b;\n"),
+                Some(b"mod a;\nmod c;\nmod d;\n"),
+            );
+            changes.record_unverified("shell", "build.log", None, Some(b"ok\n"));
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))
+}
+
+fn change_summaries(events: &[RunEvent]) -> Vec<(usize, Vec<FileChange>)> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(index, event)| match &event.payload {
+            RunEventPayload::ChangeSummary { changes } => Some((index, changes.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn run_result_and_summary_event_carry_tool_changes() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("edit")]);
+    request.tools = vec![editing_tool()];
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let summary = result
+        .changes
+        .iter()
+        .map(|change| {
+            (
+                change.path.as_str(),
+                change.kind,
+                change.lines_added,
+                change.lines_removed,
+                change.verified,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("src/new.rs", FileChangeKind::Added, 1, 0, true),
+            ("src/lib.rs", FileChangeKind::Modified, 2, 1, true),
+            ("build.log", FileChangeKind::Added, 1, 0, false),
+        ]
+    );
+    assert!(result.changes[1].old_hash.is_some());
+    assert_ne!(result.changes[1].old_hash, result.changes[1].new_hash);
+
+    let events = events.lock().expect("events lock");
+    let summaries = change_summaries(&events);
+    assert_eq!(summaries.len(), 1);
+    let (index, changes) = &summaries[0];
+    assert_eq!(changes, &result.changes);
+    assert!(matches!(
+        events[index + 1].payload,
+        RunEventPayload::Lifecycle {
+            state: RunLifecycle::Completed
+        }
+    ));
+}
+
+#[tokio::test]
+async fn runs_without_file_changes_emit_no_summary() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("look")]);
+    request.tools = vec![Arc::new(AgentTool::new(
+        "noop_tool",
+        "reads only",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))];
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert!(result.changes.is_empty());
+    assert!(change_summaries(&events.lock().expect("events lock")).is_empty());
+}
+
+#[tokio::test]
+async fn approval_requests_carry_tool_change_previews() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let payloads = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("edit")]);
+    request.tools = vec![Arc::new(
+        AgentTool::new(
+            "noop_tool",
+            "writes a file",
+            AgentToolParameters::empty(),
+            |_args, _ctx: ToolExecutionContext| async move { Ok(serde_json::json!({})) },
+        )
+        .with_approval_preview(|_args, ctx: ToolExecutionContext| async move {
+            assert!(ctx.changes.is_none(), "previews must not record changes");
+            vec![crate::tools::ChangePreview::between(
+                "notes.txt",
+                Some(b"old\n"),
+                Some(b"new\n"),
+            )]
+        }),
+    )];
+    request.approval_policy = ApprovalPolicy::ask();
+    request.approval_handler = Some(Arc::new({
+        let payloads = payloads.clone();
+        move |approval: ApprovalRequest| {
+            payloads
+                .lock()
+                .expect("payloads lock")
+                .push(approval.payload);
+            Box::pin(async { ApprovalDecision::Accept })
+        }
+    }));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let payloads = payloads.lock().expect("payloads lock");
+    assert_eq!(payloads.len(), 1);
+    assert_eq!(payloads[0]["changes"][0]["path"], "notes.txt");
+    assert_eq!(
+        payloads[0]["changes"][0]["diff"],
+        "--- a/notes.txt\n+++ b/notes.txt\n@@ -1 +1 @@\n-old\n+new\n"
+    );
+}
@@ -0,0 +1,105 @@
+use std::sync::atomic::AtomicUsize;
+
+use super::*;
+use crate::agent_loop::RunStatus;
+
+use support::{global_config_runner, ProviderScenario};
+
+fn config_with_key(key: &str) -> RociConfig {
+    let config = RociConfig::new();
+    config.set_api_key("stub", key.to_string());
+    config
+}
+
+fn stub_model(model_id: &str) -> LanguageModel {
+    LanguageModel::Custom {
+        provider: "stub".to_string(),
+        model_id: model_id.to_string(),
+    }
+}
+
+/// `noop_tool` stand-in that reports it started, then waits for `release`.
+fn gated_tool(
+    started: Arc<tokio::sync::Notify>,
+    release: Arc<tokio::sync::Notify>,
+) -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "waits for the test",
+        AgentToolParameters::empty(),
+        move |_args: ToolArguments, _ctx: ToolExecutionContext| {
+            let started = started.clone();
+            let release = release.clone();
+            async move {
+                started.notify_one();
+                release.notified().await;
+                Ok(serde_json::json!({ "ok": true }))
+            }
+        },
+    ))
+}
+
+#[tokio::test]
+async fn key_rotation_reaches_new_runs_without_touching_runs_in_flight() {
+    let reloads = Arc::new(AtomicUsize::new(0));
+    let hook_reloads = reloads.clone();
+    RociConfig::on_global_reload(move || {
+        hook_reloads.fetch_add(1, Ordering::SeqCst);
+    });
+    RociConfig::global_reload(config_with_key("old-key"));
+    let reloads_before = reloads.load(Ordering::SeqCst);
+    let (runner, configs) = global_config_runner(vec![
+        ("slow", ProviderScenario::ToolCallWithUsageThenTextWithUsage),
+        ("fast", ProviderScenario::TextOnlyWithUsage),
+    ]);
+    let started = Arc::new(tokio::sync::Notify::new());
+    let release = Arc::new(tokio::sync::Notify::new());
+
+    let first = runner
+        .start(
+            RunRequest::new(stub_model("slow"), vec![ModelMessage::user("long job")])
+                .with_tools(vec![gated_tool(started.clone(), release.clone())])
+                .with_approval_policy(ApprovalPolicy::always()),
+        )
+        .await
+        .expect("start first run");
+    timeout(Duration::from_secs(3), started.notified())
+        .await
+        .expect("first run reached its tool");
+
+    let previous = RociConfig::global_reload(config_with_key("new-key")).expect("old config");
+    assert_eq!(previous.get_api_key("stub").as_deref(), Some("old-key"));
+    assert_eq!(reloads.load(Ordering::SeqCst), reloads_before + 1);
+
+    let second = runner
+        .start(RunRequest::new(
+            stub_model("fast"),
+            vec![ModelMessage::user("quick")],
+        ))
+        .await
+        .expect("start second run");
+    let second = timeout(Duration::from_secs(3), second.wait())
+        .await
+        .expect("second run wait timeout");
+    release.notify_one();
+    let first = timeout(Duration::from_secs(3), first.wait())
+        .await
+        .expect("first run wait timeout");
+
+    assert_eq!(first.status, RunStatus::Completed, "{:?}", first.error);
+    assert_eq!(second.status, RunStatus::Completed, "{:?}", second.error);
+    let keys = configs
+        .lock()
+        .expect("configs lock")
+        .iter()
+        .map(|config| config.get_api_key("stub"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        vec![Some("old-key".to_string()), Some("new-key".to_string())]
+    );
+    assert_eq!(
+        RociConfig::global().get_api_key("stub").as_deref(),
+        Some("new-key")
+    );
+}
@@ -0,0 +1,181 @@
+use super::*;
+use crate::agent_loop::RunStatus;
+use crate::error::ErrorCategory;
+use crate::models::ModelCapabilities;
+use crate::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use crate::types::{TextStreamDelta, Usage};
+use futures::stream::{self, BoxStream};
+
+use support::capture_events;
+
+/// Accepts only `new-key`, sent as the request override or built in from
+/// the config; records the key each call used.
+struct RotatedKeyProvider {
+    key: Option<String>,
+    capabilities: ModelCapabilities,
+    keys_used: Arc<std::sync::Mutex<Vec<Option<String>>>>,
+}
+
+#[async_trait]
+impl ModelProvider for RotatedKeyProvider {
+    fn provider_name(&self) -> &str {
+        "stub"
+    }
+
+    fn model_id(&self) -> &str {
+        "stub-model"
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    async fn generate_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        Err(RociError::UnsupportedOperation(
+            "stream-only test provider".to_string(),
+        ))
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        let key = request.api_key_override.clone().or(self.key.clone());
+        self.keys_used.lock().expect("keys lock").push(key.clone());
+        if key.as_deref() != Some("new-key") {
+            return Err(RociError::api(401, "Incorrect API key provided"));
+        }
+        let delta = |text: &str, event_type| {
+            Ok(TextStreamDelta {
+                text: text.to_string(),
+                event_type,
+                tool_call: None,
+                finish_reason: None,
+                usage: (event_type == StreamEventType::Done).then(Usage::default),
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            })
+        };
+        Ok(Box::pin(stream::iter(vec![
+            delta("ok", StreamEventType::TextDelta),
+            delta("", StreamEventType::Done),
+        ])))
+    }
+}
+
+fn rotated_key_runner(
+    config: RociConfig,
+) -> (LoopRunner, Arc<std::sync::Mutex<Vec<Option<String>>>>) {
+    let keys_used = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider_keys = keys_used.clone();
+    let factory: ProviderFactory = Arc::new(move |_model, config| {
+        Ok(Box::new(RotatedKeyProvider {
+            key: config.get_api_key("stub"),
+            capabilities: ModelCapabilities::default(),
+            keys_used: provider_keys.clone(),
+        }))
+    });
+    (
+        LoopRunner::with_provider_factory(config, factory),
+        keys_used,
+    )
+}
+
+fn config_with_old_key() -> RociConfig {
+    let config = RociConfig::new().with_token_store(None);
+    config.set_api_key("stub", "old-key".to_string());
+    config
+}
+
+async fn run_to_end(runner: &LoopRunner, sink: Option<RunEventSink>) -> RunResult {
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")]);
+    request.event_sink = sink;
+    request
+        .metadata
+        .insert("runner.max_total_retries".to_string(), "3".to_string());
+    let handle = runner.start(request).await.expect("start run");
+    timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout")
+}
+
+fn retry_events(events: &[RunEvent]) -> Vec<RetryEvent> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::Retry { event } => Some(event.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn auth_failure_retries_once_with_the_refreshed_key() {
+    let config = config_with_old_key();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let handler_calls = calls.clone();
+    config.on_auth_failure(move |provider, error| {
+        assert_eq!(provider, "stub");
+        assert_eq!(error.category(), ErrorCategory::Authentication);
+        handler_calls.fetch_add(1, Ordering::SeqCst);
+        async { Some("new-key".to_string()) }
+    });
+    let (runner, keys_used) = rotated_key_runner(config.clone());
+    let (sink, events) = capture_events();
+
+    let result = run_to_end(&runner, Some(sink)).await;
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        *keys_used.lock().expect("keys lock"),
+        vec![Some("old-key".to_string()), Some("new-key".to_string())]
+    );
+    assert_eq!(config.get_api_key("stub").as_deref(), Some("new-key"));
+    let retries = retry_events(&events.lock().expect("event lock"));
+    assert_eq!(retries.len(), 1);
+    assert_eq!(retries[0].kind, RetryEventKind::RetryResuming);
+    assert_eq!(retries[0].failure_category, FailureCategory::Auth);
+    assert_eq!(retries[0].next_action, RetryNextAction::ResumeSameCandidate);
+    assert_eq!(retries[0].attempt, 1);
+    assert_eq!(retries[0].retries_remaining, 3);
+
+    // An independent run builds its provider from the updated config.
+    let result = run_to_end(&runner, None).await;
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        keys_used.lock().expect("keys lock").last(),
+        Some(&Some("new-key".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn declined_auth_failure_fails_the_run_as_before() {
+    let config = config_with_old_key();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let handler_calls = calls.clone();
+    config.on_auth_failure(move |_provider, _error| {
+        handler_calls.fetch_add(1, Ordering::SeqCst);
+        async { None }
+    });
+    let (runner, keys_used) = rotated_key_runner(config.clone());
+    let (sink, events) = capture_events();
+
+    let result = run_to_end(&runner, Some(sink)).await;
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(keys_used.lock().expect("keys lock").len(), 1);
+    assert_eq!(config.get_api_key("stub").as_deref(), Some("old-key"));
+    assert!(retry_events(&events.lock().expect("event lock"))
+        .iter()
+        .all(|retry| retry.kind != RetryEventKind::RetryResuming));
+}
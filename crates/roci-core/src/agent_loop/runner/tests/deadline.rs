@@ -0,0 +1,211 @@
+use super::*;
+use crate::agent_loop::{DeadlineMode, RunLifecycle};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::time::sleep;
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+/// How far past its deadline a run may end.
+const TOLERANCE: Duration = Duration::from_millis(250);
+
+/// A `noop_tool` that sleeps well past any deadline in these tests and
+/// records the time left it was told about.
+fn slow_tool(remaining: Arc<Mutex<Option<Duration>>>) -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "sleeps far past the deadline",
+        AgentToolParameters::empty(),
+        move |_args: ToolArguments, ctx: ToolExecutionContext| {
+            let remaining = remaining.clone();
+            async move {
+                *remaining.lock().unwrap() = Some(ctx.remaining_timeout(Duration::from_secs(30)));
+                sleep(Duration::from_secs(30)).await;
+                Ok(serde_json::json!({ "ok": true }))
+            }
+        },
+    ))
+}
+
+fn deadline_state(events: &[RunEvent]) -> Option<(String, bool)> {
+    events.iter().find_map(|event| match &event.payload {
+        RunEventPayload::Lifecycle {
+            state:
+                RunLifecycle::DeadlineExceeded {
+                    error,
+                    has_final_text,
+                },
+        } => Some((error.clone(), *has_final_text)),
+        _ => None,
+    })
+}
+
+#[tokio::test]
+async fn slow_stream_is_cut_off_at_the_deadline() {
+    let (runner, requests) = test_runner(ProviderScenario::PartialTextThenIdle);
+    let (sink, events) = capture_events();
+    let deadline = Instant::now() + Duration::from_millis(300);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_deadline(deadline)
+        .with_event_sink(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    let ended = Instant::now();
+    assert!(ended >= deadline);
+    assert!(
+        ended - deadline < TOLERANCE,
+        "ended {:?} late",
+        ended - deadline
+    );
+    assert_eq!(result.status, RunStatus::DeadlineExceeded);
+    assert!(!result.has_final_text);
+    assert_eq!(result.messages.len(), 1);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    assert_eq!(
+        deadline_state(&events.lock().unwrap()),
+        Some(("run deadline reached".to_string(), false))
+    );
+}
+
+#[tokio::test]
+async fn slow_tool_is_interrupted_and_its_call_answered_as_canceled() {
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let remaining = Arc::new(Mutex::new(None));
+    let deadline = Instant::now() + Duration::from_millis(400);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("run a slow tool")])
+        .with_tools(vec![slow_tool(remaining.clone())])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_deadline(deadline);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    let ended = Instant::now();
+    assert!(
+        ended - deadline < TOLERANCE,
+        "ended {:?} late",
+        ended - deadline
+    );
+    assert_eq!(result.status, RunStatus::DeadlineExceeded);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    // The tool saw the deadline instead of its own 30s timeout.
+    let remaining = remaining.lock().unwrap().expect("tool ran");
+    assert!(remaining <= Duration::from_millis(400), "{remaining:?}");
+    // The prompt, the tool call, and its canceled result.
+    assert_eq!(result.messages.len(), 3);
+    let ContentPart::ToolResult(tool_result) = &result.messages[2].content[0] else {
+        panic!("expected a tool result");
+    };
+    assert_eq!(tool_result.tool_call_id, "tc-anchor-1");
+    assert!(tool_result.is_error);
+    assert!(!result.has_final_text);
+}
+
+#[tokio::test]
+async fn wrap_up_mode_makes_one_last_call_without_tools() {
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let deadline = Instant::now() + Duration::from_millis(600);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("run a slow tool")])
+        .with_tools(vec![slow_tool(Arc::default())])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_deadline(deadline)
+        .with_deadline_mode(DeadlineMode::WrapUp {
+            reserve: Duration::from_millis(300),
+        })
+        .with_event_sink(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    // The wrap-up call returns at once, well before the deadline itself.
+    assert!(Instant::now() < deadline);
+    assert_eq!(result.status, RunStatus::DeadlineExceeded);
+    assert!(result.has_final_text);
+    assert_eq!(result.messages.last().unwrap().text(), "done");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    let wrap_up = &requests[1];
+    assert!(wrap_up.tools.is_none());
+    assert!(wrap_up.settings.max_tokens.is_some_and(|max| max <= 512));
+    let texts: Vec<String> = wrap_up.messages.iter().map(ModelMessage::text).collect();
+    assert_eq!(texts.len(), 4);
+    assert!(texts[3].starts_with("Time is up."), "{}", texts[3]);
+    assert_eq!(
+        deadline_state(&events.lock().unwrap()),
+        Some(("run deadline reached".to_string(), true))
+    );
+}
+
+#[tokio::test]
+async fn wrap_up_call_is_cut_off_at_the_deadline() {
+    let (runner, requests) = test_runner(ProviderScenario::PartialTextThenIdle);
+    let deadline = Instant::now() + Duration::from_millis(400);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_deadline(deadline)
+        .with_deadline_mode(DeadlineMode::WrapUp {
+            reserve: Duration::from_millis(200),
+        });
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    let ended = Instant::now();
+    assert!(ended >= deadline);
+    assert!(
+        ended - deadline < TOLERANCE,
+        "ended {:?} late",
+        ended - deadline
+    );
+    assert_eq!(result.status, RunStatus::DeadlineExceeded);
+    assert!(!result.has_final_text);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn retry_backoff_past_the_deadline_is_skipped() {
+    let (runner, requests) = test_runner(ProviderScenario::RetryableTimeoutThenComplete);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_deadline(Instant::now() + Duration::from_secs(2))
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 10_000,
+            jitter_ratio: 0.0,
+            ..Default::default()
+        });
+
+    let started = Instant::now();
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(result.status, RunStatus::DeadlineExceeded);
+    assert!(result.error.unwrap().contains("deadline"));
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn wrap_up_without_a_reserve_is_rejected() {
+    let (runner, _requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request = RunRequest::new(test_model(), Vec::new())
+        .with_deadline(Instant::now() + Duration::from_secs(1))
+        .with_deadline_mode(DeadlineMode::WrapUp {
+            reserve: Duration::ZERO,
+        });
+
+    let error = runner.start(request).await.expect_err("zero reserve");
+
+    assert!(matches!(error, RociError::Configuration(message) if message.contains("reserve")));
+}
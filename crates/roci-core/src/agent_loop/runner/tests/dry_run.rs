@@ -0,0 +1,146 @@
+use super::*;
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+/// `noop_tool` counting its executions, with a custom simulation.
+struct CountingTool {
+    params: AgentToolParameters,
+    executions: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Tool for CountingTool {
+    fn name(&self) -> &str {
+        "noop_tool"
+    }
+
+    fn description(&self) -> &str {
+        "counts executions"
+    }
+
+    fn parameters(&self) -> &AgentToolParameters {
+        &self.params
+    }
+
+    async fn execute(
+        &self,
+        _args: &ToolArguments,
+        _ctx: &ToolExecutionContext,
+    ) -> Result<serde_json::Value, RociError> {
+        self.executions.fetch_add(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "ok": true }))
+    }
+
+    fn simulate(&self, _args: &ToolArguments) -> serde_json::Value {
+        serde_json::json!({ "would": "do nothing" })
+    }
+}
+
+async fn dry_run_with(results: DryRunResults) -> (RunResult, Vec<RunEvent>, usize) {
+    let executions = Arc::new(AtomicUsize::new(0));
+    let tool: Arc<dyn Tool> = Arc::new(CountingTool {
+        params: AgentToolParameters::empty(),
+        executions: executions.clone(),
+    });
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("plan it")])
+        .with_tools(vec![tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink)
+        .with_dry_run(results);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let events = events.lock().expect("events lock").clone();
+    (result, events, executions.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn dry_runs_record_canned_results_without_executing_tools() {
+    let (result, events, executions) = dry_run_with(DryRunResults::Canned).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(executions, 0);
+    assert!(events.iter().any(|event| matches!(
+        event.payload,
+        RunEventPayload::ToolCallStarted { dry_run: true, .. }
+    )));
+    let tool_result = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            RunEventPayload::ToolResult {
+                result,
+                dry_run: true,
+                ..
+            } => Some(result),
+            _ => None,
+        })
+        .expect("dry-run tool result");
+    assert!(!tool_result.is_error);
+    assert_eq!(
+        tool_result.result,
+        serde_json::json!({
+            "dry_run": true,
+            "executed": false,
+            "output": crate::tools::tool::DRY_RUN_NOT_EXECUTED,
+        })
+    );
+
+    // The model sees the synthetic result in history and finishes the turn.
+    let history = serde_json::to_string(&result.messages).expect("serialize history");
+    assert!(history.contains(crate::tools::tool::DRY_RUN_NOT_EXECUTED));
+}
+
+#[tokio::test]
+async fn simulated_dry_runs_use_each_tools_simulation() {
+    let (result, events, executions) = dry_run_with(DryRunResults::Simulate).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(executions, 0);
+    let tool_result = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            RunEventPayload::ToolResult { result, .. } => Some(result),
+            _ => None,
+        })
+        .expect("tool result");
+    assert_eq!(
+        tool_result.result["output"],
+        serde_json::json!({ "would": "do nothing" })
+    );
+}
+
+#[tokio::test]
+async fn runs_without_dry_run_execute_tools() {
+    let executions = Arc::new(AtomicUsize::new(0));
+    let tool: Arc<dyn Tool> = Arc::new(CountingTool {
+        params: AgentToolParameters::empty(),
+        executions: executions.clone(),
+    });
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("do it")])
+        .with_tools(vec![tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(executions.load(Ordering::SeqCst), 1);
+    assert!(events
+        .lock()
+        .expect("events lock")
+        .iter()
+        .all(|event| !matches!(
+            event.payload,
+            RunEventPayload::ToolCallStarted { dry_run: true, .. }
+                | RunEventPayload::ToolResult { dry_run: true, .. }
+        )));
+}
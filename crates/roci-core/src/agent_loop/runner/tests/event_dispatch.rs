@@ -0,0 +1,113 @@
+use super::*;
+use crate::agent_loop::{EventOverflowPolicy, RunLifecycle, RunStatus};
+use std::time::Instant;
+use tokio::time::{timeout, Duration};
+
+use support::{test_model, test_runner, ProviderScenario};
+
+const SINK_DELAY: Duration = Duration::from_millis(25);
+
+fn slow_sink() -> (RunEventSink, Arc<std::sync::Mutex<Vec<RunEvent>>>) {
+    let events = Arc::new(std::sync::Mutex::new(Vec::<RunEvent>::new()));
+    let sink_events = events.clone();
+    let sink: RunEventSink = Arc::new(move |event| {
+        std::thread::sleep(SINK_DELAY);
+        if let Ok(mut guard) = sink_events.lock() {
+            guard.push(event);
+        }
+    });
+    (sink, events)
+}
+
+fn expected_text() -> String {
+    (0..50).map(|index| format!("{index},")).collect()
+}
+
+fn delivered_text(events: &[RunEvent]) -> String {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::AssistantDelta { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn completed_delivered(events: &[RunEvent]) -> bool {
+    events.iter().any(|event| {
+        matches!(
+            event.payload,
+            RunEventPayload::Lifecycle {
+                state: RunLifecycle::Completed
+            }
+        )
+    })
+}
+
+#[tokio::test]
+async fn slow_sink_coalesces_deltas_without_stalling_the_run() {
+    let (runner, _requests) = test_runner(ProviderScenario::ManyTextDeltas);
+    let (sink, events) = slow_sink();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_event_queue(4, EventOverflowPolicy::CoalesceDeltas);
+    request.event_sink = Some(sink);
+
+    let started = Instant::now();
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let elapsed = started.elapsed();
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let stats = result.emitter_stats.expect("emitter stats");
+    assert!(stats.coalesced > 0, "expected coalesced deltas: {stats:?}");
+    assert_eq!(stats.dropped, 0);
+
+    // Delivering every delta synchronously would take at least 50 sink calls.
+    assert!(
+        elapsed < SINK_DELAY * 40,
+        "run took {elapsed:?} with stats {stats:?}"
+    );
+
+    let events = events.lock().expect("event lock");
+    assert_eq!(stats.emitted, events.len() as u64);
+    assert_eq!(delivered_text(&events), expected_text());
+    assert!(completed_delivered(&events));
+}
+
+#[tokio::test]
+async fn slow_sink_with_drop_oldest_counts_dropped_events() {
+    let (runner, _requests) = test_runner(ProviderScenario::ManyTextDeltas);
+    let (sink, events) = slow_sink();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_event_queue(4, EventOverflowPolicy::DropOldest);
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let stats = result.emitter_stats.expect("emitter stats");
+    assert!(stats.dropped > 0, "expected dropped events: {stats:?}");
+    assert_eq!(stats.coalesced, 0);
+
+    let events = events.lock().expect("event lock");
+    assert_ne!(delivered_text(&events), expected_text());
+    assert!(completed_delivered(&events));
+}
+
+#[tokio::test]
+async fn runs_without_sinks_report_no_emitter_stats() {
+    let (runner, _requests) = test_runner(ProviderScenario::ManyTextDeltas);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")]);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    assert!(result.emitter_stats.is_none());
+}
@@ -0,0 +1,119 @@
+use super::*;
+
+use crate::provider::ProviderRequest;
+use crate::types::ResponseFormat;
+
+fn answer_format() -> ResponseFormat {
+    ResponseFormat::JsonSchema {
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": { "answer": { "type": "string" } },
+            "required": ["answer"],
+        }),
+        name: "answer".to_string(),
+    }
+}
+
+async fn run_with_schema(scenario: ProviderScenario) -> (RunResult, Vec<ProviderRequest>) {
+    let (runner, requests) = test_runner(scenario);
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("call schema_tool")])
+        .with_final_response_schema(answer_format());
+    request.tools = vec![schema_tool()];
+    request.approval_policy = ApprovalPolicy::always();
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+    let requests = requests.lock().expect("request lock").clone();
+    (result, requests)
+}
+
+#[tokio::test]
+async fn inline_format_is_sent_with_tools_on_every_call() {
+    let (result, requests) = run_with_schema(ProviderScenario::StructuredAnswerInline).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(
+        result.structured_output,
+        Some(serde_json::json!({ "answer": "done" }))
+    );
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        assert!(request.tools.is_some());
+        assert!(matches!(
+            request.response_format,
+            Some(ResponseFormat::JsonSchema { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn extra_constrained_call_follows_a_free_answer() {
+    let (result, requests) = run_with_schema(ProviderScenario::StructuredAnswerExtraCall).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(
+        result.structured_output,
+        Some(serde_json::json!({ "answer": "done" }))
+    );
+    assert_eq!(requests.len(), 3);
+    assert!(requests[..2]
+        .iter()
+        .all(|request| request.tools.is_some() && request.response_format.is_none()));
+    let constrained = &requests[2];
+    assert!(constrained.tools.is_none());
+    assert!(matches!(
+        constrained.response_format,
+        Some(ResponseFormat::JsonSchema { .. })
+    ));
+    let instruction = constrained.messages.last().expect("instruction message");
+    assert_eq!(instruction.role, crate::types::Role::User);
+}
+
+#[tokio::test]
+async fn invalid_answer_gets_one_repair_call() {
+    let (result, requests) = run_with_schema(ProviderScenario::StructuredAnswerNeedsRepair).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(
+        result.structured_output,
+        Some(serde_json::json!({ "answer": "done" }))
+    );
+    assert_eq!(requests.len(), 3);
+    let repair = requests[2].messages.last().expect("repair message");
+    assert!(repair
+        .text()
+        .contains("field 'answer' expected type 'string', got number"));
+    assert!(requests[2].tools.is_none());
+}
+
+#[tokio::test]
+async fn answer_still_invalid_after_repair_fails_the_run() {
+    let (result, requests) = run_with_schema(ProviderScenario::StructuredAnswerInvalid).await;
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(
+        result.error.as_deref(),
+        Some(
+            "final response failed schema validation: \
+             field 'answer' expected type 'string', got number"
+        )
+    );
+    assert!(result.structured_output.is_none());
+    assert_eq!(requests.len(), 3);
+}
+
+#[tokio::test]
+async fn text_final_response_schema_is_rejected() {
+    let (runner, _requests) = test_runner(ProviderScenario::MissingOptionalFields);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_final_response_schema(ResponseFormat::Text);
+
+    let err = runner
+        .start(request)
+        .await
+        .expect_err("text format rejected");
+
+    assert!(matches!(err, RociError::Configuration(_)));
+}
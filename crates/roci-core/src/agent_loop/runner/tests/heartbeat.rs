@@ -0,0 +1,174 @@
+use super::*;
+use crate::agent_loop::{HeartbeatPhase, RunStatus};
+use tokio::time::{sleep, timeout, Duration};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+fn heartbeats(events: &[RunEvent]) -> Vec<(usize, HeartbeatPhase, u64)> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(index, event)| match event.payload {
+            RunEventPayload::Heartbeat { phase, elapsed_ms } => Some((index, phase, elapsed_ms)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn heartbeats_are_emitted_until_the_first_delta_arrives() {
+    let (runner, _requests) = test_runner(ProviderScenario::DelayedTextWithUsage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_heartbeat_interval(Duration::from_millis(40));
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let events = events.lock().expect("event lock");
+    let heartbeats = heartbeats(&events);
+    assert!(
+        heartbeats.len() >= 2,
+        "expected heartbeats during the 200ms wait, got {heartbeats:?}"
+    );
+    assert!(heartbeats
+        .iter()
+        .all(|(_, phase, _)| *phase == HeartbeatPhase::AwaitingFirstDelta));
+    assert!(heartbeats
+        .windows(2)
+        .all(|pair| pair[0].2 < pair[1].2 && pair[1].2 - pair[0].2 >= 40));
+
+    let first_text = events
+        .iter()
+        .position(|event| matches!(event.payload, RunEventPayload::AssistantDelta { .. }))
+        .expect("text delta");
+    assert!(heartbeats.iter().all(|(index, _, _)| *index < first_text));
+}
+
+#[tokio::test]
+async fn heartbeats_are_disabled_by_default() {
+    let (runner, _requests) = test_runner(ProviderScenario::DelayedTextWithUsage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")]);
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    assert!(heartbeats(&events.lock().expect("event lock")).is_empty());
+}
+
+#[tokio::test]
+async fn heartbeats_are_emitted_while_tools_execute() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let slow_tool: Arc<dyn crate::tools::tool::Tool> = Arc::new(AgentTool::new(
+        "noop_tool",
+        "sleeps briefly",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            sleep(Duration::from_millis(200)).await;
+            Ok(serde_json::json!({"ok": true}))
+        },
+    ));
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_tools(vec![slow_tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_heartbeat_interval(Duration::from_millis(40));
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let heartbeats = heartbeats(&events.lock().expect("event lock"));
+    assert!(
+        heartbeats
+            .iter()
+            .filter(|(_, phase, _)| *phase == HeartbeatPhase::ToolExecution)
+            .count()
+            >= 2,
+        "expected heartbeats during the 200ms tool call, got {heartbeats:?}"
+    );
+}
+
+#[tokio::test]
+async fn warm_up_emits_heartbeats_before_the_first_call() {
+    let (runner, requests) = test_runner(ProviderScenario::SlowWarmUp);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_warm_up(true)
+        .with_heartbeat_interval(Duration::from_millis(40));
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(requests.lock().expect("request lock").len(), 1);
+
+    let events = events.lock().expect("event lock");
+    let warm_up: Vec<_> = heartbeats(&events)
+        .into_iter()
+        .filter(|(_, phase, _)| *phase == HeartbeatPhase::WarmingUp)
+        .collect();
+    assert!(
+        warm_up.len() >= 3,
+        "expected an initial heartbeat and beats during the 150ms warm-up, got {warm_up:?}"
+    );
+    assert_eq!(warm_up[0].2, 0);
+    let first_text = events
+        .iter()
+        .position(|event| matches!(event.payload, RunEventPayload::AssistantDelta { .. }))
+        .expect("text delta");
+    assert!(warm_up.iter().all(|(index, _, _)| *index < first_text));
+}
+
+#[tokio::test]
+async fn warm_up_is_skipped_unless_requested() {
+    let (runner, _requests) = test_runner(ProviderScenario::SlowWarmUp);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_heartbeat_interval(Duration::from_millis(40));
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    assert!(heartbeats(&events.lock().expect("event lock"))
+        .iter()
+        .all(|(_, phase, _)| *phase != HeartbeatPhase::WarmingUp));
+}
+
+#[tokio::test]
+async fn failed_warm_up_lets_the_run_proceed() {
+    let (runner, requests) = test_runner(ProviderScenario::FailedWarmUp);
+    let (sink, events) = capture_events();
+    let mut request =
+        RunRequest::new(test_model(), vec![ModelMessage::user("hi")]).with_warm_up(true);
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(requests.lock().expect("request lock").len(), 1);
+
+    let heartbeats = heartbeats(&events.lock().expect("event lock"));
+    assert_eq!(heartbeats.len(), 1);
+    assert_eq!(heartbeats[0].1, HeartbeatPhase::WarmingUp);
+}
@@ -20,8 +20,8 @@ mod support;
 mod workspace;
 
 use support::{
-    capture_agent_events, capture_events, test_model, test_runner, test_runner_by_model,
-    ProviderScenario,
+    capture_agent_envelopes, capture_agent_events, capture_events, test_model, test_runner,
+    test_runner_by_model, ProviderScenario,
 };
 
 struct UpdateStreamingTool {
@@ -216,7 +216,7 @@ fn tool_result_ids_from_events(events: &[RunEvent]) -> Vec<String> {
     events
         .iter()
         .filter_map(|event| match &event.payload {
-            RunEventPayload::ToolResult { result } => Some(result.tool_call_id.clone()),
+            RunEventPayload::ToolResult { result, .. } => Some(result.tool_call_id.clone()),
             _ => None,
         })
         .collect()
@@ -274,7 +274,7 @@ fn tool_results_from_events(events: &[RunEvent]) -> Vec<(String, serde_json::Val
     events
         .iter()
         .filter_map(|event| match &event.payload {
-            RunEventPayload::ToolResult { result } => Some((
+            RunEventPayload::ToolResult { result, .. } => Some((
                 result.tool_call_id.clone(),
                 result.result.clone(),
                 result.is_error,
@@ -284,11 +284,34 @@ fn tool_results_from_events(events: &[RunEvent]) -> Vec<(String, serde_json::Val
         .collect()
 }
 
+mod admission;
+mod argument_repair;
+mod artifacts;
 mod auto_compaction;
 mod budget;
+mod changes;
+mod config_reload;
+mod credential_refresh;
+mod deadline;
+mod dry_run;
+mod event_dispatch;
+mod final_response;
+mod heartbeat;
+mod output_progress;
 mod overflow_recovery;
+mod plugins;
+mod replay;
 mod request_pipeline;
+mod response_filter;
 mod retry;
 mod schema_and_hooks;
+mod scratch;
+mod stream_coalescing;
 mod stream_lifecycle;
+mod task_list;
 mod tool_execution;
+mod tool_limits;
+mod tool_retry;
+mod tools_provider;
+mod turns;
+mod webhooks;
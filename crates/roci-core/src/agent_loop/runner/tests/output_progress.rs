@@ -0,0 +1,145 @@
+use super::*;
+use crate::agent_loop::RunStatus;
+use crate::types::{TextStreamDelta, Usage};
+use tokio::time::{advance, timeout, Duration};
+
+use super::progress::{OutputProgress, PROGRESS_INTERVAL};
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+fn progress_events(events: &[RunEvent]) -> Vec<(u32, u32, f64)> {
+    events
+        .iter()
+        .filter_map(|event| match event.payload {
+            RunEventPayload::Progress {
+                output_tokens_estimate,
+                max_tokens,
+                fraction,
+            } => Some((output_tokens_estimate, max_tokens, fraction)),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn run_many_text_deltas(max_tokens: Option<u32>) -> Vec<RunEvent> {
+    let (runner, _requests) = test_runner(ProviderScenario::ManyTextDeltas);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")]);
+    request.settings.max_tokens = max_tokens;
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    let events = events.lock().expect("event lock").clone();
+    events
+}
+
+fn delta(event_type: StreamEventType, text: &str) -> TextStreamDelta {
+    TextStreamDelta {
+        text: text.to_string(),
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+fn fraction(payload: Option<RunEventPayload>) -> Option<f64> {
+    match payload {
+        Some(RunEventPayload::Progress { fraction, .. }) => Some(fraction),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn progress_is_absent_without_max_tokens() {
+    let events = run_many_text_deltas(None).await;
+    assert!(progress_events(&events).is_empty());
+}
+
+#[tokio::test]
+async fn progress_is_throttled_across_a_burst_of_deltas() {
+    let events = run_many_text_deltas(Some(100)).await;
+
+    // Fifty deltas arrive well within one interval.
+    assert_eq!(progress_events(&events), vec![(1, 100, 0.01)]);
+    let progress = events
+        .iter()
+        .position(|event| matches!(event.payload, RunEventPayload::Progress { .. }))
+        .expect("progress event");
+    let first_text = events
+        .iter()
+        .position(|event| matches!(event.payload, RunEventPayload::AssistantDelta { .. }))
+        .expect("text delta");
+    assert!(first_text < progress);
+}
+
+#[tokio::test(start_paused = true)]
+async fn progress_rises_monotonically_and_clamps_at_one() {
+    let mut progress = OutputProgress::start(Some(5)).expect("tracker");
+    let eight_chars = delta(StreamEventType::TextDelta, "abcdefgh");
+
+    let mut fractions = Vec::new();
+    for _ in 0..4 {
+        fractions.push(fraction(progress.observe(&eight_chars)));
+        advance(PROGRESS_INTERVAL).await;
+    }
+    assert_eq!(
+        fractions,
+        vec![Some(0.4), Some(0.8), Some(1.0), Some(1.0)],
+        "2 tokens per delta against a limit of 5"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn progress_waits_out_the_interval_and_prefers_reported_usage() {
+    let mut progress = OutputProgress::start(Some(100)).expect("tracker");
+    let text = delta(StreamEventType::TextDelta, "abcd");
+
+    assert_eq!(fraction(progress.observe(&text)), Some(0.01));
+    advance(PROGRESS_INTERVAL / 2).await;
+    assert_eq!(fraction(progress.observe(&text)), None);
+    advance(PROGRESS_INTERVAL / 2).await;
+    assert_eq!(fraction(progress.observe(&text)), Some(0.03));
+
+    advance(PROGRESS_INTERVAL).await;
+    let reported = TextStreamDelta {
+        usage: Some(Usage {
+            output_tokens: 40,
+            ..Usage::default()
+        }),
+        ..delta(StreamEventType::TextDelta, "abcd")
+    };
+    assert_eq!(fraction(progress.observe(&reported)), Some(0.4));
+}
+
+#[tokio::test(start_paused = true)]
+async fn progress_stops_at_done() {
+    let mut progress = OutputProgress::start(Some(100)).expect("tracker");
+    assert!(progress
+        .observe(&delta(StreamEventType::TextDelta, "abcd"))
+        .is_some());
+    advance(PROGRESS_INTERVAL).await;
+
+    let done = TextStreamDelta {
+        usage: Some(Usage {
+            output_tokens: 90,
+            ..Usage::default()
+        }),
+        ..delta(StreamEventType::Done, "")
+    };
+    assert!(progress.observe(&done).is_none());
+    advance(PROGRESS_INTERVAL).await;
+    assert!(progress
+        .observe(&delta(StreamEventType::TextDelta, "late text"))
+        .is_none());
+    assert!(OutputProgress::start(None).is_none());
+    assert!(OutputProgress::start(Some(0)).is_none());
+}
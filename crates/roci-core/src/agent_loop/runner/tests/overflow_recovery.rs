@@ -85,7 +85,7 @@ async fn classified_untyped_overflow_uses_compaction_lane() {
             ..Default::default()
         });
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
@@ -99,6 +99,7 @@ async fn classified_untyped_overflow_uses_compaction_lane() {
                 ]))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -162,7 +163,7 @@ async fn output_overflow_uses_one_reduction_then_fixed_compaction_ladder() {
         });
     request.settings.max_tokens = Some(1024);
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |_messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |_messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 let call_index = compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
@@ -175,6 +176,7 @@ async fn output_overflow_uses_one_reduction_then_fixed_compaction_ladder() {
                 }
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -250,7 +252,7 @@ async fn compaction_progress_uses_provider_facing_messages_after_transform() {
         Box::pin(async move {
             let messages = payload
                 .messages
-                .into_iter()
+                .iter()
                 .map(|message| ModelMessage::user(message.text().repeat(2_000)))
                 .collect();
             Ok(TransformContextHookResult::ReplaceMessages { messages })
@@ -258,13 +260,14 @@ async fn compaction_progress_uses_provider_facing_messages_after_transform() {
     }));
 
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
                 Ok(Some(messages.into_iter().skip(1).collect()))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -0,0 +1,386 @@
+use super::*;
+
+type Log = Arc<std::sync::Mutex<Vec<String>>>;
+
+struct TestPlugin {
+    name: &'static str,
+    pre: PreToolUseHookResult,
+    tools: Vec<Arc<dyn Tool>>,
+    log: Log,
+    events: Arc<AtomicUsize>,
+    agent_events: Arc<AtomicUsize>,
+}
+
+impl TestPlugin {
+    fn new(name: &'static str, log: &Log) -> Self {
+        Self {
+            name,
+            pre: PreToolUseHookResult::Continue,
+            tools: Vec::new(),
+            log: log.clone(),
+            events: Arc::new(AtomicUsize::new(0)),
+            agent_events: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn replacing_path(name: &'static str, path: &str, log: &Log) -> Self {
+        Self {
+            pre: PreToolUseHookResult::ReplaceArgs {
+                args: serde_json::json!({ "path": path }),
+            },
+            ..Self::new(name, log)
+        }
+    }
+
+    fn blocking(name: &'static str, log: &Log) -> Self {
+        Self {
+            pre: PreToolUseHookResult::Block {
+                reason: Some(format!("blocked-by-{name}")),
+            },
+            ..Self::new(name, log)
+        }
+    }
+
+    fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+}
+
+#[async_trait]
+impl RunPlugin for TestPlugin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn tools(&self) -> Vec<Arc<dyn Tool>> {
+        self.tools.clone()
+    }
+
+    async fn pre_tool_use(
+        &self,
+        call: &AgentToolCall,
+        _context: &HookContext,
+        _cancel: CancellationToken,
+    ) -> Result<PreToolUseHookResult, RociError> {
+        self.log
+            .lock()
+            .expect("log lock")
+            .push(format!("{}:pre:{}", self.name, call.arguments["path"]));
+        Ok(self.pre.clone())
+    }
+
+    async fn post_tool_use(
+        &self,
+        _call: &AgentToolCall,
+        mut result: AgentToolResult,
+        _context: &HookContext,
+    ) -> Result<AgentToolResult, RociError> {
+        self.log
+            .lock()
+            .expect("log lock")
+            .push(format!("{}:post", self.name));
+        if let Some(map) = result.result.as_object_mut() {
+            let order = map
+                .entry("post_order")
+                .or_insert_with(|| serde_json::json!([]));
+            if let Some(order) = order.as_array_mut() {
+                order.push(serde_json::json!(self.name));
+            }
+        }
+        Ok(result)
+    }
+
+    fn on_event(&self, _event: &RunEvent) {
+        self.events.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_agent_event(&self, _event: &AgentEventEnvelope) {
+        self.agent_events.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn new_log() -> Log {
+    Arc::new(std::sync::Mutex::new(Vec::new()))
+}
+
+fn logged(log: &Log) -> Vec<String> {
+    log.lock().expect("log lock").clone()
+}
+
+fn schema_request(executions: &Arc<AtomicUsize>, sink: RunEventSink) -> RunRequest {
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("call schema_tool")]);
+    request.tools = vec![tracked_schema_path_tool(executions.clone())];
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+    request
+}
+
+async fn run_to_completion(runner: &LoopRunner, request: RunRequest) {
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+}
+
+fn single_tool_result(events: &Arc<std::sync::Mutex<Vec<RunEvent>>>) -> (serde_json::Value, bool) {
+    let events = events.lock().expect("event lock");
+    let tool_results = tool_results_from_events(&events);
+    assert_eq!(tool_results.len(), 1, "expected exactly one tool result");
+    let (_id, result, is_error) = tool_results[0].clone();
+    (result, is_error)
+}
+
+#[tokio::test]
+async fn plugin_pre_hooks_chain_replaced_args_in_registration_order() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let request = schema_request(&executions, sink)
+        .with_plugin(Arc::new(TestPlugin::replacing_path(
+            "first",
+            "/tmp/first",
+            &log,
+        )))
+        .with_plugin(Arc::new(TestPlugin::new("observer", &log)))
+        .with_plugin(Arc::new(TestPlugin::replacing_path(
+            "second",
+            "/tmp/second",
+            &log,
+        )));
+
+    run_to_completion(&runner, request).await;
+
+    assert_eq!(executions.load(Ordering::SeqCst), 1);
+    let (result, is_error) = single_tool_result(&events);
+    assert!(!is_error);
+    assert_eq!(result["path"], serde_json::json!("/tmp/second"));
+    assert_eq!(
+        result["post_order"],
+        serde_json::json!(["first", "observer", "second"])
+    );
+    assert_eq!(
+        logged(&log),
+        vec![
+            "first:pre:\"/tmp/test\"",
+            "observer:pre:\"/tmp/first\"",
+            "second:pre:\"/tmp/first\"",
+            "first:post",
+            "observer:post",
+            "second:post",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn plugin_block_after_replace_args_blocks_and_runs_all_post_hooks() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let request = schema_request(&executions, sink)
+        .with_plugin(Arc::new(TestPlugin::replacing_path(
+            "rewriter",
+            "/tmp/rewritten",
+            &log,
+        )))
+        .with_plugin(Arc::new(TestPlugin::blocking("blocker", &log)));
+
+    run_to_completion(&runner, request).await;
+
+    assert_eq!(executions.load(Ordering::SeqCst), 0);
+    let (result, is_error) = single_tool_result(&events);
+    assert!(is_error, "blocked call must be an error");
+    assert_eq!(result["source"], serde_json::json!("pre_tool_use"));
+    assert_eq!(result["error"], serde_json::json!("blocked-by-blocker"));
+    assert_eq!(
+        result["post_order"],
+        serde_json::json!(["rewriter", "blocker"])
+    );
+    assert_eq!(
+        logged(&log),
+        vec![
+            "rewriter:pre:\"/tmp/test\"",
+            "blocker:pre:\"/tmp/rewritten\"",
+            "rewriter:post",
+            "blocker:post",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn plugin_block_before_replace_args_skips_later_pre_hooks() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let request = schema_request(&executions, sink)
+        .with_plugin(Arc::new(TestPlugin::blocking("blocker", &log)))
+        .with_plugin(Arc::new(TestPlugin::replacing_path(
+            "rewriter",
+            "/tmp/rewritten",
+            &log,
+        )));
+
+    run_to_completion(&runner, request).await;
+
+    assert_eq!(executions.load(Ordering::SeqCst), 0);
+    let (result, is_error) = single_tool_result(&events);
+    assert!(is_error, "blocked call must be an error");
+    assert_eq!(result["error"], serde_json::json!("blocked-by-blocker"));
+    assert_eq!(
+        result["post_order"],
+        serde_json::json!(["blocker", "rewriter"])
+    );
+    assert_eq!(
+        logged(&log),
+        vec!["blocker:pre:\"/tmp/test\"", "blocker:post", "rewriter:post"]
+    );
+}
+
+#[tokio::test]
+async fn plugin_hooks_run_after_request_hooks() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let mut request =
+        schema_request(&executions, sink).with_plugin(Arc::new(TestPlugin::new("plugin", &log)));
+    request.hooks = RunHooks {
+        compaction: None,
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
+            Box::pin(async {
+                Ok(PreToolUseHookResult::ReplaceArgs {
+                    args: serde_json::json!({ "path": "/tmp/from-request" }),
+                })
+            })
+        })),
+        post_tool_use: Some(Arc::new(|_call, mut result, _context| {
+            Box::pin(async move {
+                if let Some(map) = result.result.as_object_mut() {
+                    map.insert("post_order".to_string(), serde_json::json!(["request"]));
+                }
+                Ok(result)
+            })
+        })),
+    };
+
+    run_to_completion(&runner, request).await;
+
+    let (result, _is_error) = single_tool_result(&events);
+    assert_eq!(result["path"], serde_json::json!("/tmp/from-request"));
+    assert_eq!(
+        result["post_order"],
+        serde_json::json!(["request", "plugin"])
+    );
+    assert_eq!(logged(&log)[0], "plugin:pre:\"/tmp/from-request\"");
+}
+
+#[tokio::test]
+async fn plugin_events_fan_out_to_every_plugin_and_request_sink() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let (agent_sink, agent_events) = capture_agent_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let first = TestPlugin::new("first", &log);
+    let second = TestPlugin::new("second", &log);
+    let counters = [
+        (first.events.clone(), first.agent_events.clone()),
+        (second.events.clone(), second.agent_events.clone()),
+    ];
+    let request = schema_request(&executions, sink)
+        .with_agent_event_sink(agent_sink)
+        .with_plugin(Arc::new(first))
+        .with_plugin(Arc::new(second));
+
+    run_to_completion(&runner, request).await;
+
+    let run_event_count = events.lock().expect("event lock").len();
+    let agent_event_count = agent_events.lock().expect("event lock").len();
+    assert!(run_event_count > 0);
+    assert!(agent_event_count > 0);
+    for (plugin_events, plugin_agent_events) in counters {
+        assert_eq!(plugin_events.load(Ordering::SeqCst), run_event_count);
+        assert_eq!(
+            plugin_agent_events.load(Ordering::SeqCst),
+            agent_event_count
+        );
+    }
+}
+
+#[tokio::test]
+async fn plugin_tools_are_added_and_modify_request_applies() {
+    struct PolicyPlugin;
+
+    #[async_trait]
+    impl RunPlugin for PolicyPlugin {
+        fn name(&self) -> &str {
+            "policy"
+        }
+
+        fn modify_request(&self, request: &mut RunRequest) {
+            request.approval_policy = ApprovalPolicy::always();
+        }
+    }
+
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let (sink, events) = capture_events();
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("call schema_tool")])
+        .with_event_sink(sink)
+        .with_approval_policy(ApprovalPolicy::never())
+        .with_plugin(Arc::new(
+            TestPlugin::new("tools", &log).with_tool(tracked_schema_path_tool(executions.clone())),
+        ))
+        .with_plugin(Arc::new(PolicyPlugin));
+
+    run_to_completion(&runner, request).await;
+
+    assert_eq!(executions.load(Ordering::SeqCst), 1);
+    let (result, is_error) = single_tool_result(&events);
+    assert!(!is_error);
+    assert_eq!(result["path"], serde_json::json!("/tmp/test"));
+}
+
+#[tokio::test]
+async fn plugin_tool_name_conflicts_fail_run_start() {
+    let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
+    let executions = Arc::new(AtomicUsize::new(0));
+    let log = new_log();
+
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("call schema_tool")])
+        .with_plugin(Arc::new(
+            TestPlugin::new("a", &log).with_tool(tracked_schema_path_tool(executions.clone())),
+        ))
+        .with_plugin(Arc::new(
+            TestPlugin::new("b", &log).with_tool(tracked_schema_path_tool(executions.clone())),
+        ));
+    let err = runner
+        .start(request)
+        .await
+        .expect_err("duplicate plugin tools should be rejected");
+    assert!(
+        err.to_string()
+            .contains("tool 'schema_tool' from plugin 'b' conflicts with a tool from plugin 'a'"),
+        "unexpected error: {err}"
+    );
+
+    let (sink, _events) = capture_events();
+    let request = schema_request(&executions, sink).with_plugin(Arc::new(
+        TestPlugin::new("a", &log).with_tool(tracked_schema_path_tool(executions.clone())),
+    ));
+    let err = runner
+        .start(request)
+        .await
+        .expect_err("plugin tools shadowing request tools should be rejected");
+    assert!(
+        err.to_string()
+            .contains("conflicts with a tool from the request"),
+        "unexpected error: {err}"
+    );
+}
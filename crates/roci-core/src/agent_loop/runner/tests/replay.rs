@@ -0,0 +1,122 @@
+use super::*;
+use crate::agent_loop::replay::{DiagnosticKind, StateReconstructor, TranscriptEvent};
+use crate::agent_loop::transcript::TranscriptWriter;
+
+fn noop_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))
+}
+
+/// Run a tool call then a text reply with a transcript; returns the
+/// transcript text and the requests the stub provider received.
+async fn transcribed_run(
+    path: &std::path::Path,
+) -> (String, Vec<crate::provider::ProviderRequest>) {
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, _events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("use the tool")])
+        .with_tools(vec![noop_tool()])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink)
+        .with_transcript(TranscriptWriter::create(path).expect("create transcript"));
+    request.settings.temperature = Some(0.2);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    let transcript = std::fs::read_to_string(path).expect("read transcript");
+    let requests = requests.lock().expect("requests lock").clone();
+    (transcript, requests)
+}
+
+#[tokio::test]
+async fn transcript_replays_each_provider_request() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let (transcript, requests) = transcribed_run(&dir.path().join("run.jsonl")).await;
+
+    let replay = StateReconstructor::parse(&transcript);
+
+    assert!(replay.is_intact(), "{:?}", replay.diagnostics());
+    assert_eq!(replay.iterations().len(), requests.len());
+    for (state, sent) in replay.iterations().iter().zip(&requests) {
+        assert_eq!(state.messages, *sent.messages);
+        assert_eq!(state.settings.as_ref(), Some(&sent.settings));
+        let sent_tools = sent.tools.as_deref().unwrap_or_default();
+        assert_eq!(
+            state
+                .tools
+                .iter()
+                .map(|tool| &tool.name)
+                .collect::<Vec<_>>(),
+            sent_tools.iter().map(|tool| &tool.name).collect::<Vec<_>>()
+        );
+        assert_eq!(state.provider_requests, 1);
+    }
+    assert_eq!(
+        replay.outcome().map(|outcome| outcome.status),
+        Some(RunStatus::Completed)
+    );
+    assert!(replay.preamble().iter().any(|event| matches!(
+        event,
+        TranscriptEvent::Agent(envelope) if matches!(envelope.event, AgentEvent::AgentStart { .. })
+    )));
+    let first = replay.iteration(1).expect("first iteration");
+    assert!(first.events.iter().any(|event| matches!(
+        event,
+        TranscriptEvent::Run(run) if matches!(run.payload, RunEventPayload::ToolResult { .. })
+    )));
+    let last = replay.iteration(2).expect("second iteration");
+    assert!(last.events.iter().any(|event| matches!(
+        event,
+        TranscriptEvent::Agent(envelope) if matches!(envelope.event, AgentEvent::AgentEnd { .. })
+    )));
+}
+
+#[tokio::test]
+async fn dropped_and_cut_off_lines_are_diagnosed() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let (transcript, requests) = transcribed_run(&dir.path().join("run.jsonl")).await;
+    let lines = transcript.lines().collect::<Vec<_>>();
+    let dropped = lines
+        .iter()
+        .position(|line| line.contains("\"record\":\"run_event\""))
+        .expect("a run event line");
+    let mut damaged = lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != dropped)
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    // Cut the run_finished record in half, as a crash mid-write would.
+    let cut = damaged.len() - lines.last().expect("last line").len() / 2;
+    damaged.truncate(cut);
+
+    let replay = StateReconstructor::parse(&damaged);
+
+    let kinds = replay
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.kind)
+        .collect::<Vec<_>>();
+    assert!(kinds.contains(&DiagnosticKind::MissingEvents), "{kinds:?}");
+    assert!(kinds.contains(&DiagnosticKind::Truncated), "{kinds:?}");
+    assert!(replay
+        .diagnostics()
+        .iter()
+        .any(|diagnostic| diagnostic.message.contains("is missing")));
+    assert!(replay.outcome().is_none());
+    assert_eq!(replay.iterations().len(), requests.len());
+    assert_eq!(
+        replay.iterations().last().map(|state| &state.messages),
+        requests.last().map(|sent| &*sent.messages)
+    );
+}
@@ -1,6 +1,7 @@
 use super::*;
 
 use crate::tools::ToolPromptMetadata;
+use crate::types::Role;
 
 #[tokio::test]
 async fn no_panic_when_stream_optional_fields_missing() {
@@ -428,6 +429,63 @@ async fn provider_request_fields_are_forwarded_to_provider() {
     );
 }
 
+#[tokio::test]
+async fn namespaced_run_metadata_becomes_provider_headers() {
+    let (runner, requests) = test_runner(ProviderScenario::MissingOptionalFields);
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+    request.metadata.insert(
+        "provider.header.Helicone-Property-Team".to_string(),
+        "platform".to_string(),
+    );
+    request
+        .metadata
+        .insert("provider.body.user".to_string(), "user-42".to_string());
+    request
+        .metadata
+        .insert("runner.max_iterations".to_string(), "4".to_string());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let requests = requests.lock().expect("request lock");
+    let first = requests.first().expect("provider request");
+    assert_eq!(
+        first
+            .headers
+            .get("helicone-property-team")
+            .and_then(|value| value.to_str().ok()),
+        Some("platform")
+    );
+    assert_eq!(first.headers.len(), 1);
+    // The stub provider is not OpenAI-family, so body fields stay unset.
+    assert!(first.settings.user.is_none());
+    assert!(first.metadata.is_empty());
+}
+
+#[tokio::test]
+async fn run_metadata_is_not_sent_without_provider_namespace() {
+    let (runner, requests) = test_runner(ProviderScenario::MissingOptionalFields);
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+    request
+        .metadata
+        .insert("team".to_string(), "platform".to_string());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let requests = requests.lock().expect("request lock");
+    let first = requests.first().expect("provider request");
+    assert!(first.headers.is_empty());
+    assert!(first.metadata.is_empty());
+    assert!(first.settings.user.is_none());
+}
+
 #[tokio::test]
 async fn fallback_resolves_api_key_for_active_provider() {
     let (runner, requests) = test_runner_by_model(vec![
@@ -474,6 +532,24 @@ async fn fallback_resolves_api_key_for_active_provider() {
     );
 }
 
+#[tokio::test]
+async fn misspelled_runner_metadata_key_is_rejected_at_start() {
+    let (runner, requests) = test_runner(ProviderScenario::MissingOptionalFields);
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+    request
+        .metadata
+        .insert("runner.max_iteratons".to_string(), "4".to_string());
+
+    let err = match runner.start(request).await {
+        Ok(_) => panic!("misspelled runner key must be rejected"),
+        Err(err) => err.to_string(),
+    };
+
+    assert!(err.contains("runner.max_iteratons"), "{err}");
+    assert!(err.contains("runner.max_iterations"), "{err}");
+    assert!(requests.lock().expect("request lock").is_empty());
+}
+
 #[tokio::test]
 async fn unsupported_request_transport_is_rejected_before_provider_call() {
     let (runner, requests) = test_runner(ProviderScenario::MissingOptionalFields);
@@ -595,6 +671,36 @@ async fn transform_context_runs_before_convert_to_llm() {
     );
 }
 
+#[tokio::test]
+async fn provider_request_shares_the_history_when_nothing_edits_it() {
+    let (runner, requests) = test_runner(ProviderScenario::MissingOptionalFields);
+    let transform_seen = Arc::new(std::sync::Mutex::new(None::<Arc<Vec<ModelMessage>>>));
+    let transform_seen_for_hook = transform_seen.clone();
+
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+    request.transform_context = Some(Arc::new(move |payload| {
+        *transform_seen_for_hook.lock().expect("capture lock") = Some(payload.messages);
+        Box::pin(async { Ok(TransformContextHookResult::Continue) })
+    }));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let seen = transform_seen
+        .lock()
+        .expect("capture lock")
+        .clone()
+        .expect("transform_context should run");
+    let requests = requests.lock().expect("request lock");
+    assert!(
+        Arc::ptr_eq(&seen, &requests[0].messages),
+        "an unedited history should reach the provider without a copy"
+    );
+}
+
 #[tokio::test]
 async fn transform_context_hook_cancel_fails_run_with_reason() {
     let (runner, _requests) = test_runner(ProviderScenario::MissingOptionalFields);
@@ -820,7 +926,7 @@ async fn typed_overflow_error_triggers_compaction_recovery() {
 
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("overflow me")]);
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
@@ -834,6 +940,7 @@ async fn typed_overflow_error_triggers_compaction_recovery() {
                 ]))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -864,13 +971,14 @@ async fn untyped_overflow_error_does_not_trigger_compaction_recovery() {
 
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("overflow me")]);
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |_messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |_messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
                 Ok(None)
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -898,7 +1006,7 @@ async fn typed_overflow_fails_after_bounded_recovery_attempts() {
 
     let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("overflow me")]);
     request.hooks = RunHooks {
-        compaction: Some(std::sync::Arc::new(move |_messages, _cancel| {
+        compaction: Some(std::sync::Arc::new(move |_messages, _context, _cancel| {
             let compaction_calls_for_hook = compaction_calls_for_hook.clone();
             Box::pin(async move {
                 compaction_calls_for_hook.fetch_add(1, Ordering::SeqCst);
@@ -907,6 +1015,7 @@ async fn typed_overflow_fails_after_bounded_recovery_attempts() {
                 )]))
             })
         })),
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
     };
@@ -971,3 +1080,92 @@ async fn cancel_during_stream_preserves_latest_assistant_snapshot() {
         "cancel should preserve latest assistant snapshot when available"
     );
 }
+
+#[tokio::test]
+async fn prefill_is_sent_as_trailing_assistant_and_persisted_with_completion() {
+    let (runner, requests) = test_runner(ProviderScenario::AssistantPrefixText);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("answer in json")])
+        .with_prefill("{\"answer\":");
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let requests = requests.lock().expect("request lock");
+    let sent = requests.first().expect("provider request");
+    assert_eq!(sent.settings.assistant_prefix, Some(true));
+    let trailing = sent.messages.last().expect("trailing message");
+    assert_eq!(trailing.role, Role::Assistant);
+    assert_eq!(trailing.text(), "{\"answer\":");
+    let persisted = result.messages.last().expect("assistant message");
+    assert_eq!(persisted.role, Role::Assistant);
+    assert_eq!(persisted.text(), "{\"answer\": \"ok\"}");
+}
+
+#[tokio::test]
+async fn prefill_is_emulated_in_system_prompt_when_requested() {
+    let (runner, requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("greet")])
+        .with_prefill("Well, ")
+        .with_prefill_fallback(PrefillFallback::Emulate);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let requests = requests.lock().expect("request lock");
+    let sent = requests.first().expect("provider request");
+    assert_eq!(sent.settings.assistant_prefix, None);
+    assert_eq!(sent.messages[0].role, Role::System);
+    assert!(sent.messages[0].text().contains("Well, "));
+    assert_eq!(sent.messages.last().expect("last").role, Role::User);
+    assert_eq!(
+        result.messages.last().expect("assistant message").text(),
+        "Well, hello"
+    );
+}
+
+#[tokio::test]
+async fn prefill_is_rejected_by_default_when_provider_lacks_prefix_support() {
+    let (runner, requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request =
+        RunRequest::new(test_model(), vec![ModelMessage::user("greet")]).with_prefill("Well, ");
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert!(result
+        .error
+        .as_deref()
+        .is_some_and(|error| error.contains("cannot continue an assistant prefill")));
+    assert!(requests.lock().expect("request lock").is_empty());
+}
+
+#[tokio::test]
+async fn prefill_with_tools_is_rejected_at_start() {
+    let (runner, _requests) = test_runner(ProviderScenario::AssistantPrefixText);
+    let tool: Arc<dyn Tool> = Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({"ok": true}))
+        },
+    ));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("go")])
+        .with_tools(vec![tool])
+        .with_prefill("{");
+
+    let err = runner
+        .start(request)
+        .await
+        .expect_err("prefill with tools must be rejected");
+    assert!(matches!(err, RociError::InvalidArgument(_)));
+}
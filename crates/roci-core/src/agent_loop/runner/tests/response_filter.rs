@@ -0,0 +1,115 @@
+use super::*;
+use crate::agent_loop::RunStatus;
+use crate::security::pii::PiiRedactor;
+
+use support::{capture_agent_events, capture_events, test_model, test_runner, ProviderScenario};
+
+fn greeting_redactor() -> Arc<dyn ResponseFilter> {
+    Arc::new(
+        PiiRedactor::new_default()
+            .with_pattern(r"\bhello\b", "[REDACTED_GREETING]")
+            .expect("valid pattern"),
+    )
+}
+
+fn contact_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "returns contact details",
+        AgentToolParameters::empty(),
+        |_args, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "contacts": [{ "email": "jane@example.com" }] }))
+        },
+    ))
+}
+
+fn stored_tool_result(messages: &[ModelMessage]) -> serde_json::Value {
+    messages
+        .iter()
+        .find_map(|message| {
+            message.content.iter().find_map(|part| match part {
+                ContentPart::ToolResult(result) => Some(result.result.clone()),
+                _ => None,
+            })
+        })
+        .expect("tool result message")
+}
+
+#[tokio::test]
+async fn events_carry_raw_text_while_stored_messages_are_filtered() {
+    let (runner, _requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let (sink, events) = capture_events();
+    let (agent_sink, agent_events) = capture_agent_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_response_filter(greeting_redactor());
+    request.event_sink = Some(sink);
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    assert_eq!(
+        assistant_text_content(&result.messages),
+        "[REDACTED_GREETING]"
+    );
+    let streamed = events
+        .lock()
+        .expect("events lock")
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::AssistantDelta { text } => Some(text.clone()),
+            _ => None,
+        })
+        .collect::<String>();
+    assert_eq!(streamed, "hello");
+
+    let agent_events = agent_events.lock().expect("agent events lock");
+    let ended = agent_events
+        .iter()
+        .filter_map(|event| match event {
+            AgentEvent::MessageEnd { message }
+                if matches!(message.role, crate::types::Role::Assistant) =>
+            {
+                Some(assistant_text_content(std::slice::from_ref(message)))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ended, vec!["[REDACTED_GREETING]".to_string()]);
+}
+
+#[tokio::test]
+async fn tool_results_are_filtered_only_when_enabled() {
+    for enabled in [false, true] {
+        let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+        let (sink, events) = capture_events();
+        let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("look up")])
+            .with_response_filter(greeting_redactor())
+            .with_filtered_tool_results(enabled);
+        request.tools = vec![contact_tool()];
+        request.approval_policy = ApprovalPolicy::always();
+        request.event_sink = Some(sink);
+
+        let handle = runner.start(request).await.expect("start run");
+        let result = timeout(Duration::from_secs(3), handle.wait())
+            .await
+            .expect("run wait timeout");
+        assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+        let expected_email = if enabled {
+            "[REDACTED_EMAIL]"
+        } else {
+            "jane@example.com"
+        };
+        assert_eq!(
+            stored_tool_result(&result.messages),
+            serde_json::json!({ "contacts": [{ "email": expected_email }] })
+        );
+        let events = events.lock().expect("events lock");
+        let (_, emitted, _) = &tool_results_from_events(&events)[0];
+        assert_eq!(emitted["contacts"][0]["email"], "jane@example.com");
+    }
+}
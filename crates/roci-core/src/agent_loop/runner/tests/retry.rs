@@ -17,6 +17,40 @@ fn retry_events(events: &[RunEvent]) -> Vec<RetryEvent> {
         .collect()
 }
 
+#[tokio::test]
+async fn retried_attempts_generate_the_same_tool_call_ids() {
+    let (runner, requests) = test_runner(ProviderScenario::RetryableTimeoutThenComplete);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 2,
+            initial_delay_ms: 1,
+            multiplier: 1.0,
+            jitter_ratio: 0.0,
+            max_delay_ms: 1,
+        });
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let requests = requests.lock().expect("request lock");
+    assert_eq!(requests.len(), 2);
+    let generated = requests
+        .iter()
+        .map(|request| {
+            let mut ids = request
+                .tool_call_ids
+                .as_ref()
+                .expect("runner supplies a tool-call ID allocator")
+                .begin_response();
+            (ids.assign(None, "read"), ids.assign(Some("call_0"), "read"))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(generated[0], generated[1]);
+}
+
 #[tokio::test]
 async fn retry_events_schedule_resume_then_complete_before_advancing() {
     let (runner, _requests) = test_runner(ProviderScenario::RetryableTimeoutThenComplete);
@@ -196,6 +230,36 @@ async fn persistent_retry_cancel_does_not_advance_candidate() {
         .any(|event| event.kind == RetryEventKind::CandidateAdvancing));
 }
 
+#[tokio::test]
+async fn abort_interrupts_retry_backoff_sleep_promptly() {
+    let (runner, _requests) = test_runner(ProviderScenario::RateLimitedWithoutRetryHint);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_event_sink(sink)
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 10_000,
+            multiplier: 1.0,
+            jitter_ratio: 0.0,
+            max_delay_ms: 10_000,
+        });
+
+    let mut handle = runner.start(request).await.expect("start run");
+    wait_for_retry_event(&events, RetryEventKind::RetryScheduled).await;
+    let aborted_at = std::time::Instant::now();
+    assert!(handle.abort());
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let latency = aborted_at.elapsed();
+
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert!(
+        latency < Duration::from_millis(150),
+        "abort took {latency:?} during a 10s backoff"
+    );
+}
+
 #[tokio::test]
 async fn bounded_zero_retry_mode_rejects_configuration() {
     let (runner, _requests) = test_runner(ProviderScenario::MissingOptionalFields);
@@ -245,6 +309,262 @@ async fn partial_output_failure_does_not_advance_candidate() {
     }));
 }
 
+fn overload_backoff() -> RetryBackoffPolicy {
+    RetryBackoffPolicy {
+        max_attempts: 2,
+        initial_delay_ms: 1,
+        multiplier: 1.0,
+        jitter_ratio: 0.0,
+        max_delay_ms: 1,
+    }
+}
+
+#[tokio::test]
+async fn overloaded_before_first_token_retries_with_synthesized_backoff() {
+    let (runner, requests) = test_runner(ProviderScenario::OverloadedThenComplete);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_event_sink(sink)
+        .with_retry_backoff(overload_backoff());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(requests.lock().expect("requests lock").len(), 2);
+    let retry_events = retry_events(&events.lock().expect("events lock"));
+    assert!(retry_events.iter().any(|event| {
+        event.kind == RetryEventKind::RetryScheduled
+            && event.failure_category == FailureCategory::RateLimit
+            && event.sleep_ms == Some(1)
+    }));
+}
+
+#[tokio::test]
+async fn overloaded_after_partial_text_fails_without_opt_in() {
+    let (runner, requests) = test_runner(ProviderScenario::TextThenOverloadedThenComplete);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_retry_backoff(overload_backoff());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(requests.lock().expect("requests lock").len(), 1);
+}
+
+#[tokio::test]
+async fn overloaded_after_partial_text_resumes_when_enabled() {
+    let (runner, requests) = test_runner(ProviderScenario::PrefixTextThenOverloadedThenContinues);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_retry_backoff(overload_backoff())
+        .with_resume_partial_on_overload(true);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let requests = requests.lock().expect("requests lock");
+    assert_eq!(requests.len(), 2);
+    let continued = requests[1]
+        .messages
+        .last()
+        .expect("resumed request has messages");
+    assert!(matches!(continued.role, crate::types::Role::Assistant));
+    assert_eq!(continued.text(), "partial");
+    assert_eq!(requests[1].settings.assistant_prefix, Some(true));
+
+    let roles: Vec<_> = result.messages.iter().map(|message| message.role).collect();
+    assert_eq!(
+        roles,
+        vec![crate::types::Role::User, crate::types::Role::Assistant]
+    );
+    assert_eq!(result.messages[1].text(), "partial rest");
+}
+
+#[tokio::test]
+async fn overloaded_after_partial_text_restarts_without_prefix_support() {
+    let (runner, requests) = test_runner(ProviderScenario::TextThenOverloadedThenComplete);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_retry_backoff(overload_backoff())
+        .with_resume_partial_on_overload(true);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let requests = requests.lock().expect("requests lock");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].messages.len(), 1);
+    assert_eq!(requests[1].settings.assistant_prefix, None);
+
+    let roles: Vec<_> = result.messages.iter().map(|message| message.role).collect();
+    assert_eq!(
+        roles,
+        vec![crate::types::Role::User, crate::types::Role::Assistant]
+    );
+    assert_eq!(result.messages[1].text(), "done");
+}
+
+#[tokio::test]
+async fn run_retry_budget_spans_iterations_and_fails_fast_when_exhausted() {
+    let (runner, requests) = test_runner(ProviderScenario::RateLimitedAroundToolCall);
+    let (sink, events) = capture_events();
+    let noop_tool: Arc<dyn crate::tools::tool::Tool> = Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_tools(vec![noop_tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink);
+    request
+        .metadata
+        .insert("runner.max_total_retries".to_string(), "2".to_string());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    // Iteration 1 retries once, iteration 2 retries once, and the third
+    // rate limit fails even though the per-call policy would allow it.
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(requests.lock().expect("requests lock").len(), 4);
+    let error = result.error.expect("failure reason");
+    assert!(error.contains("run retry budget exhausted"), "{error}");
+    assert!(error.contains("2 of 2 retries used"), "{error}");
+
+    let retry_events = retry_events(&events.lock().expect("events lock"));
+    let remaining = retry_events
+        .iter()
+        .filter(|event| event.kind == RetryEventKind::RetryScheduled)
+        .map(|event| event.retries_remaining)
+        .collect::<Vec<_>>();
+    assert_eq!(remaining, vec![1, 0]);
+    assert!(retry_events.iter().any(|event| {
+        event.kind == RetryEventKind::RetryExhausted
+            && event.failure_category == FailureCategory::RetryBudget
+            && event.retries_remaining == 0
+    }));
+}
+
+fn first_token_slo_misses(events: &[RunEvent]) -> Vec<(String, String, u64, u64)> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::FirstTokenSloMissed {
+                from,
+                to,
+                waited_ms,
+                slo_ms,
+            } => Some((from.clone(), to.clone(), *waited_ms, *slo_ms)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn missed_first_token_slo_aborts_the_stream_and_falls_back() {
+    let (runner, requests) = test_runner_by_model(vec![
+        ("slow", ProviderScenario::SlowFirstDelta),
+        ("fast", ProviderScenario::TextOnlyWithUsage),
+    ]);
+    let (sink, events) = capture_events();
+    let request = RunRequest::with_candidates(
+        vec![model("slow"), model("fast")],
+        vec![ModelMessage::user("hello")],
+    )
+    .unwrap()
+    .with_event_sink(sink)
+    .with_ttft_slo(Duration::from_millis(100));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(result.model, Some(model("fast")));
+    assert_eq!(
+        result.messages.last().map(ModelMessage::text).as_deref(),
+        Some("hello")
+    );
+    assert_eq!(requests.lock().expect("requests lock").len(), 2);
+    assert_eq!(
+        support::SLOW_FIRST_DELTA_STREAMS_DROPPED.load(Ordering::SeqCst),
+        1,
+        "the slow stream is dropped, aborting its request"
+    );
+    let misses = first_token_slo_misses(&events.lock().expect("events lock"));
+    assert_eq!(misses.len(), 1);
+    let (from, to, waited_ms, slo_ms) = &misses[0];
+    assert_eq!(from, "stub:slow");
+    assert_eq!(to, "stub:fast");
+    assert_eq!(*slo_ms, 100);
+    assert!((100..2_000).contains(waited_ms), "waited {waited_ms}ms");
+}
+
+#[tokio::test]
+async fn first_token_slo_does_not_switch_once_content_arrived() {
+    let (runner, requests) = test_runner_by_model(vec![
+        ("paused", ProviderScenario::TextThenPauseThenDone),
+        ("fast", ProviderScenario::TextOnlyWithUsage),
+    ]);
+    let (sink, events) = capture_events();
+    let request = RunRequest::with_candidates(
+        vec![model("paused"), model("fast")],
+        vec![ModelMessage::user("hello")],
+    )
+    .unwrap()
+    .with_event_sink(sink)
+    .with_ttft_slo(Duration::from_millis(50));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(result.model, Some(model("paused")));
+    assert_eq!(
+        result.messages.last().map(ModelMessage::text).as_deref(),
+        Some("hello")
+    );
+    assert_eq!(requests.lock().expect("requests lock").len(), 1);
+    assert!(first_token_slo_misses(&events.lock().expect("events lock")).is_empty());
+}
+
+#[tokio::test]
+async fn first_token_slo_is_inactive_on_the_last_candidate() {
+    let (runner, _requests) = test_runner(ProviderScenario::DelayedTextWithUsage);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")])
+        .with_event_sink(sink)
+        .with_ttft_slo(Duration::from_millis(20));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(result.model, Some(test_model()));
+    assert!(first_token_slo_misses(&events.lock().expect("events lock")).is_empty());
+}
+
 async fn wait_for_retry_event(events: &Arc<std::sync::Mutex<Vec<RunEvent>>>, kind: RetryEventKind) {
     timeout(Duration::from_secs(2), async {
         loop {
@@ -175,7 +175,8 @@ async fn pre_tool_use_hook_can_block_and_skip_tool_execution() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Ok(PreToolUseHookResult::Block {
                     reason: Some("blocked-by-test".to_string()),
@@ -218,7 +219,8 @@ async fn pre_tool_use_hook_replace_args_are_used_by_tool() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Ok(PreToolUseHookResult::ReplaceArgs {
                     args: serde_json::json!({ "path": "/tmp/replaced-by-hook" }),
@@ -268,7 +270,8 @@ async fn invalid_finalized_tool_args_skip_approval_and_execution() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Ok(PreToolUseHookResult::ReplaceArgs {
                     args: serde_json::json!({}),
@@ -358,7 +361,8 @@ async fn pre_tool_use_hook_replace_args_are_used_by_approval() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Ok(PreToolUseHookResult::ReplaceArgs {
                     args: serde_json::json!({ "command": "echo approval-rewritten" }),
@@ -454,7 +458,8 @@ async fn pre_tool_use_hook_rewritten_args_can_fall_through_approval_default() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Ok(PreToolUseHookResult::ReplaceArgs {
                     args: serde_json::json!({ "command": "echo approval-not-matching" }),
@@ -499,7 +504,8 @@ async fn pre_tool_use_hook_error_returns_synthetic_tool_error() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|_call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|_call, _context, _cancel| {
             Box::pin(async {
                 Err(RociError::InvalidState(
                     "forced pre hook failure".to_string(),
@@ -553,7 +559,8 @@ async fn pre_tool_use_block_result_survives_parallel_flush_and_steering() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
-        pre_tool_use: Some(Arc::new(|call, _cancel| {
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(|call, _context, _cancel| {
             Box::pin(async move {
                 if call.name == "ls" {
                     Ok(PreToolUseHookResult::Block {
@@ -594,6 +601,94 @@ async fn pre_tool_use_block_result_survives_parallel_flush_and_steering() {
         .contains("blocked-before-steering"));
 }
 
+#[tokio::test]
+async fn tool_hooks_observe_invocation_counts_and_turn_index_across_turns() {
+    let (runner, _requests) = test_runner(ProviderScenario::RepeatedParallelSafeBatchThenComplete);
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("run tools")]);
+    request.tools = vec![
+        tracked_safe_success_tool(
+            "read",
+            Duration::from_millis(5),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        ),
+        tracked_safe_success_tool(
+            "ls",
+            Duration::from_millis(5),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        ),
+    ];
+    request.approval_policy = ApprovalPolicy::always();
+    let pre_seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let post_seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let pre_seen_for_hook = pre_seen.clone();
+    let post_seen_for_hook = post_seen.clone();
+    request.hooks = RunHooks {
+        compaction: None,
+        pre_turn_execution: None,
+        pre_tool_use: Some(Arc::new(move |call, context, _cancel| {
+            pre_seen_for_hook
+                .lock()
+                .expect("pre seen lock")
+                .push((call.id, context));
+            Box::pin(async { Ok(PreToolUseHookResult::Continue) })
+        })),
+        post_tool_use: Some(Arc::new(move |call, result, context| {
+            post_seen_for_hook
+                .lock()
+                .expect("post seen lock")
+                .push((call.id, context));
+            Box::pin(async move { Ok(result) })
+        })),
+    };
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(4), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let pre_seen = pre_seen.lock().expect("pre seen lock");
+    let observed: Vec<_> = pre_seen
+        .iter()
+        .map(|(id, context)| {
+            (
+                id.as_str(),
+                context.turn_index,
+                context.iteration,
+                context.invocations_of("read"),
+                context.invocations_of("ls"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        observed,
+        vec![
+            ("safe-read-0", 1, 1, 0, 0),
+            ("safe-ls-0", 1, 1, 1, 0),
+            ("safe-read-1", 2, 2, 1, 1),
+            ("safe-ls-1", 2, 2, 2, 1),
+        ]
+    );
+    assert!(pre_seen
+        .iter()
+        .all(|(_, context)| context.run_id == pre_seen[0].1.run_id
+            && context.consecutive_failures == 0));
+    assert!(pre_seen[2].1.messages.len() > pre_seen[0].1.messages.len());
+
+    let post_seen = post_seen.lock().expect("post seen lock");
+    assert_eq!(post_seen.len(), pre_seen.len());
+    for (id, context) in post_seen.iter() {
+        let (_, pre_context) = pre_seen
+            .iter()
+            .find(|(pre_id, _)| pre_id == id)
+            .expect("post hook call should have seen a pre hook");
+        assert_eq!(context.turn_index, pre_context.turn_index);
+        assert_eq!(context.tool_invocations, pre_context.tool_invocations);
+    }
+}
+
 #[tokio::test]
 async fn post_tool_use_hook_can_mutate_tool_result() {
     let (runner, _requests) = test_runner(ProviderScenario::SchemaToolValidArgs);
@@ -605,8 +700,9 @@ async fn post_tool_use_hook_can_mutate_tool_result() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
-        post_tool_use: Some(Arc::new(|_call, mut result| {
+        post_tool_use: Some(Arc::new(|_call, mut result, _context| {
             Box::pin(async move {
                 if let Some(map) = result.result.as_object_mut() {
                     map.insert("post_mutated".to_string(), serde_json::json!(true));
@@ -665,8 +761,9 @@ async fn post_tool_use_hook_can_mutate_tool_result_size_is_capped() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
-        post_tool_use: Some(Arc::new(|_call, mut result| {
+        post_tool_use: Some(Arc::new(|_call, mut result, _context| {
             Box::pin(async move {
                 if let Some(map) = result.result.as_object_mut() {
                     map.insert("post_mutated".to_string(), serde_json::json!(true));
@@ -727,8 +824,9 @@ async fn tool_result_size_caps_validation_error_after_post_hook_growth() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
-        post_tool_use: Some(Arc::new(|_call, mut result| {
+        post_tool_use: Some(Arc::new(|_call, mut result, _context| {
             Box::pin(async move {
                 if let Some(map) = result.result.as_object_mut() {
                     map.insert("expanded".to_string(), serde_json::json!("z".repeat(600)));
@@ -787,8 +885,9 @@ async fn post_tool_use_hook_runs_for_skipped_synthetic_errors() {
     let seen_calls_for_hook = seen_calls.clone();
     request.hooks = RunHooks {
         compaction: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
-        post_tool_use: Some(Arc::new(move |call, mut result| {
+        post_tool_use: Some(Arc::new(move |call, mut result, _context| {
             let seen_calls_for_hook = seen_calls_for_hook.clone();
             Box::pin(async move {
                 seen_calls_for_hook
@@ -849,8 +948,9 @@ async fn post_tool_use_hook_error_returns_deterministic_synthetic_error() {
     request.event_sink = Some(sink);
     request.hooks = RunHooks {
         compaction: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
-        post_tool_use: Some(Arc::new(|_call, _result| {
+        post_tool_use: Some(Arc::new(|_call, _result, _context| {
             Box::pin(async {
                 Err(RociError::InvalidState(
                     "forced post hook failure".to_string(),
@@ -881,3 +981,148 @@ async fn post_tool_use_hook_error_returns_deterministic_synthetic_error() {
         .unwrap_or_default()
         .contains("forced post hook failure"));
 }
+
+/// A request running the `read` + `ls` batch, with a `pre_tool_use` hook that
+/// appends `tool:<name>` to `seen` for each call it sees.
+fn turn_hook_request(
+    seen: Arc<std::sync::Mutex<Vec<String>>>,
+    pre_turn_execution: PreTurnExecutionHook,
+) -> (RunRequest, Arc<std::sync::Mutex<Vec<RunEvent>>>) {
+    let (sink, events) = capture_events();
+    let active_calls = Arc::new(AtomicUsize::new(0));
+    let max_active_calls = Arc::new(AtomicUsize::new(0));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("run tools")]);
+    request.tools = ["read", "ls"]
+        .into_iter()
+        .map(|name| {
+            tracked_safe_success_tool(
+                name,
+                Duration::from_millis(1),
+                active_calls.clone(),
+                max_active_calls.clone(),
+            )
+        })
+        .collect();
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+    request.hooks = RunHooks {
+        compaction: None,
+        pre_turn_execution: Some(pre_turn_execution),
+        pre_tool_use: Some(Arc::new(move |call, _context, _cancel| {
+            seen.lock().unwrap().push(format!("tool:{}", call.name));
+            Box::pin(async { Ok(PreToolUseHookResult::Continue) })
+        })),
+        post_tool_use: None,
+    };
+    (request, events)
+}
+
+#[tokio::test]
+async fn pre_turn_execution_hook_sees_the_whole_turn_before_per_call_hooks() {
+    let (runner, _requests) = test_runner(ProviderScenario::ParallelSafeBatchThenComplete);
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let turn_seen = seen.clone();
+    let (request, events) = turn_hook_request(
+        seen.clone(),
+        Arc::new(move |payload| {
+            let calls = payload
+                .tool_calls
+                .iter()
+                .map(|call| call.name.as_str())
+                .collect::<Vec<_>>()
+                .join(",");
+            turn_seen
+                .lock()
+                .unwrap()
+                .push(format!("turn:{}:{calls}", payload.iteration));
+            Box::pin(async { Ok(PreTurnExecutionHookResult::Continue) })
+        }),
+    );
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        ["turn:1:read,ls", "tool:read", "tool:ls", "turn:2:"]
+    );
+    let events = events.lock().expect("event lock");
+    assert_eq!(tool_results_from_events(&events).len(), 2);
+}
+
+#[tokio::test]
+async fn pre_turn_execution_hook_modified_calls_replace_the_proposed_ones() {
+    let (runner, requests) = test_runner(ProviderScenario::ParallelSafeBatchThenComplete);
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (request, events) = turn_hook_request(
+        seen.clone(),
+        Arc::new(|payload| {
+            let kept = payload
+                .tool_calls
+                .into_iter()
+                .filter(|call| call.name == "read")
+                .collect();
+            Box::pin(async { Ok(PreTurnExecutionHookResult::ModifyCalls(kept)) })
+        }),
+    );
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(*seen.lock().unwrap(), ["tool:read"]);
+    let events = events.lock().expect("event lock");
+    let tool_results = tool_results_from_events(&events);
+    assert_eq!(tool_results.len(), 1);
+    assert_eq!(tool_results[0].0, "safe-read-1");
+    let requests = requests.lock().unwrap();
+    let assistant = &requests[1].messages[1];
+    let call_ids = assistant
+        .tool_calls()
+        .into_iter()
+        .map(|call| call.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(call_ids, ["safe-read-1"]);
+}
+
+#[tokio::test]
+async fn pre_turn_execution_hook_abort_sends_feedback_without_running_tools() {
+    let (runner, requests) = test_runner(ProviderScenario::ParallelSafeBatchThenComplete);
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (mut request, events) = turn_hook_request(
+        seen.clone(),
+        Arc::new(|payload| {
+            let decision = if payload.tool_calls.is_empty() {
+                PreTurnExecutionHookResult::Continue
+            } else {
+                PreTurnExecutionHookResult::AbortTurn {
+                    feedback: "run one tool at a time".to_string(),
+                }
+            };
+            Box::pin(async move { Ok(decision) })
+        }),
+    );
+    request.max_tool_failures = Some(1);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run should complete without timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert!(seen.lock().unwrap().is_empty());
+    let events = events.lock().expect("event lock");
+    assert!(tool_results_from_events(&events).is_empty());
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    let next = &requests[1].messages;
+    assert!(next.iter().all(|message| message.tool_calls().is_empty()));
+    let feedback = next.last().expect("feedback message");
+    assert_eq!(feedback.role, crate::types::Role::User);
+    assert_eq!(feedback.text(), "run one tool at a time");
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::*;
+use crate::agent_loop::RunStatus;
+
+use support::{test_model, test_runner, ProviderScenario};
+
+/// `noop_tool` stand-in that writes a file to the scratch directory and
+/// reports where it went.
+fn scratch_writing_tool(seen: Arc<Mutex<Option<PathBuf>>>) -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "writes scratch output",
+        AgentToolParameters::empty(),
+        move |_args: ToolArguments, ctx: ToolExecutionContext| {
+            let seen = seen.clone();
+            async move {
+                let dir = ctx.scratch_dir()?;
+                assert!(dir.is_dir());
+                std::fs::write(dir.join("partial.json"), "{}")?;
+                *seen.lock().expect("scratch lock") = Some(dir);
+                Ok(serde_json::json!({ "ok": true }))
+            }
+        },
+    ))
+}
+
+async fn run_with_scratch_tool(keep_scratch: bool) -> (RunResult, PathBuf) {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let seen = Arc::new(Mutex::new(None));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("work")])
+        .with_tools(vec![scratch_writing_tool(seen.clone())])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_keep_scratch(keep_scratch);
+    let run_id = request.run_id;
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let dir = seen
+        .lock()
+        .expect("scratch lock")
+        .clone()
+        .expect("tool saw a scratch dir");
+    assert_eq!(dir, std::env::temp_dir().join(format!("roci-run-{run_id}")));
+    (result, dir)
+}
+
+#[tokio::test]
+async fn scratch_dir_is_removed_when_the_run_ends() {
+    let (result, dir) = run_with_scratch_tool(false).await;
+
+    assert!(!dir.exists());
+    assert_eq!(result.scratch_dir, None);
+}
+
+#[tokio::test]
+async fn keep_scratch_reports_the_directory_on_the_result() {
+    let (result, dir) = run_with_scratch_tool(true).await;
+
+    assert_eq!(result.scratch_dir.as_deref(), Some(dir.as_path()));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("partial.json")).expect("kept scratch file"),
+        "{}"
+    );
+    std::fs::remove_dir_all(&dir).expect("remove kept scratch dir");
+}
+
+#[tokio::test]
+async fn runs_that_never_use_scratch_create_no_directory() {
+    let (runner, _requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request =
+        RunRequest::new(test_model(), vec![ModelMessage::user("hi")]).with_keep_scratch(true);
+    let run_id = request.run_id;
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(result.scratch_dir, None);
+    assert!(!std::env::temp_dir()
+        .join(format!("roci-run-{run_id}"))
+        .exists());
+}
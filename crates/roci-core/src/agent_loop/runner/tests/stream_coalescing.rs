@@ -0,0 +1,158 @@
+use super::*;
+use crate::agent_loop::{RunStatus, StreamCoalescing};
+use crate::types::{Role, TextStreamDelta};
+use futures::stream::{self, StreamExt};
+use tokio::time::{timeout, Duration, Instant};
+
+use super::coalescing::coalesce_text_deltas;
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+#[derive(Debug, PartialEq)]
+enum Streamed {
+    Text(String),
+    Reasoning(String),
+    Image,
+}
+
+fn streamed(events: &[RunEvent]) -> Vec<Streamed> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::AssistantDelta { text } => Some(Streamed::Text(text.clone())),
+            RunEventPayload::ReasoningDelta { text } => Some(Streamed::Reasoning(text.clone())),
+            RunEventPayload::AssistantImage { .. } => Some(Streamed::Image),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn run_with(
+    scenario: ProviderScenario,
+    coalescing: Option<StreamCoalescing>,
+) -> (RunResult, Vec<RunEvent>) {
+    let (runner, _requests) = test_runner(scenario);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")]);
+    request.stream_coalescing = coalescing;
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+    let events = events.lock().expect("event lock").clone();
+    (result, events)
+}
+
+fn final_assistant(result: &RunResult) -> &ModelMessage {
+    result
+        .messages
+        .iter()
+        .rev()
+        .find(|message| message.role == Role::Assistant)
+        .expect("assistant message")
+}
+
+#[tokio::test]
+async fn coalescing_merges_fast_text_deltas_without_changing_the_message() {
+    let (plain, plain_events) = run_with(ProviderScenario::ManyTextDeltas, None).await;
+    let (merged, merged_events) = run_with(
+        ProviderScenario::ManyTextDeltas,
+        Some(StreamCoalescing::default()),
+    )
+    .await;
+
+    let plain_deltas = streamed(&plain_events);
+    let merged_deltas = streamed(&merged_events);
+    assert_eq!(plain_deltas.len(), 50);
+    assert!(
+        merged_deltas.len() < 10,
+        "expected merged deltas, got {merged_deltas:?}"
+    );
+    assert_eq!(
+        final_assistant(&merged).content,
+        final_assistant(&plain).content
+    );
+}
+
+#[tokio::test]
+async fn coalescing_never_merges_across_non_text_deltas() {
+    let coalescing = StreamCoalescing::default().with_burst_gap(Duration::from_secs(1));
+    let (result, events) = run_with(
+        ProviderScenario::TextBurstsAroundNonTextDeltas,
+        Some(coalescing),
+    )
+    .await;
+
+    assert_eq!(
+        streamed(&events),
+        vec![
+            Streamed::Text("a ".to_string()),
+            Streamed::Text("b c ".to_string()),
+            Streamed::Reasoning("thinking".to_string()),
+            Streamed::Text("d e f ".to_string()),
+            Streamed::Image,
+            Streamed::Text("g h i ".to_string()),
+        ]
+    );
+    assert_eq!(final_assistant(&result).text(), "a b c d e f g h i ");
+}
+
+#[tokio::test]
+async fn coalescing_passes_slow_deltas_through() {
+    let (_result, events) = run_with(
+        ProviderScenario::TextThenPauseThenDone,
+        Some(StreamCoalescing::default()),
+    )
+    .await;
+
+    assert_eq!(
+        streamed(&events),
+        vec![
+            Streamed::Text("hel".to_string()),
+            Streamed::Text("lo".to_string()),
+        ]
+    );
+}
+
+fn text(text: &str) -> Result<TextStreamDelta, RociError> {
+    Ok(TextStreamDelta {
+        text: text.to_string(),
+        event_type: StreamEventType::TextDelta,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn coalescing_flushes_a_batch_when_its_window_expires() {
+    let pause = stream::once(tokio::time::sleep(Duration::from_millis(100)))
+        .filter_map(|()| async { None::<Result<TextStreamDelta, RociError>> });
+    let provider = stream::iter([text("a"), text("b"), text("c")])
+        .chain(pause)
+        .chain(stream::iter([text("d")]))
+        .boxed();
+    let coalescing = StreamCoalescing::default().with_window(Duration::from_millis(16));
+
+    let started = Instant::now();
+    let received: Vec<(String, Duration)> = coalesce_text_deltas(provider, Some(coalescing))
+        .map(|delta| (delta.expect("delta").text, started.elapsed()))
+        .collect()
+        .await;
+
+    assert_eq!(
+        received,
+        vec![
+            ("a".to_string(), Duration::ZERO),
+            ("bc".to_string(), Duration::from_millis(16)),
+            ("d".to_string(), Duration::from_millis(100)),
+        ]
+    );
+}
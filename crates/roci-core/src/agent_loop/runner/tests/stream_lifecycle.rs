@@ -354,3 +354,317 @@ async fn turn_end_includes_assistant_message_for_text_only_turn() {
     assert_eq!(assistant_message.text(), "done");
     assert!(tool_results.is_empty());
 }
+
+#[tokio::test]
+async fn streaming_tool_arguments_emit_throttled_progress_before_tool_call() {
+    let (runner, _requests) = test_runner(ProviderScenario::StreamingToolArgumentsThenComplete);
+    let (sink, events) = capture_events();
+    let (agent_sink, agent_events) = capture_agent_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("write it")]);
+    request.tools = vec![update_streaming_tool(false)];
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let events = events.lock().expect("event lock");
+    let progress_lens = events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::ToolCallArgumentsDelta {
+                call_id,
+                name,
+                partial_args_len,
+                ..
+            } => {
+                assert_eq!(call_id, "args-call-1");
+                assert_eq!(name, "update_tool");
+                Some(*partial_args_len)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(progress_lens, vec![300, 1500, 2700]);
+    let last_progress_idx = events
+        .iter()
+        .rposition(|event| {
+            matches!(
+                event.payload,
+                RunEventPayload::ToolCallArgumentsDelta { .. }
+            )
+        })
+        .expect("argument progress event");
+    let started_idx = events
+        .iter()
+        .position(|event| matches!(event.payload, RunEventPayload::ToolCallStarted { .. }))
+        .expect("tool call started event");
+    assert!(last_progress_idx < started_idx);
+
+    let agent_events = agent_events.lock().expect("agent event lock");
+    let agent_progress_lens = agent_events
+        .iter()
+        .filter_map(|event| match event {
+            AgentEvent::ToolCallArgumentsDelta {
+                partial_args_len,
+                preview,
+                ..
+            } => {
+                assert!(preview.chars().count() <= 120);
+                Some(*partial_args_len)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(agent_progress_lens, progress_lens);
+}
+
+/// Upper bound on the time from `abort()` to a terminal result.
+const ABORT_LATENCY_BOUND: Duration = Duration::from_millis(150);
+
+#[tokio::test]
+async fn abort_between_slow_deltas_cancels_promptly_and_keeps_partial_text() {
+    let (runner, _requests) = test_runner(ProviderScenario::SlowTextDeltas);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("write a lot")]);
+
+    let mut handle = runner.start(request).await.expect("start run");
+    tokio::time::sleep(Duration::from_millis(275)).await;
+    let aborted_at = std::time::Instant::now();
+    assert!(handle.abort(), "abort should be accepted");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let latency = aborted_at.elapsed();
+
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert!(
+        latency < ABORT_LATENCY_BOUND,
+        "abort took {latency:?}, expected under {ABORT_LATENCY_BOUND:?}"
+    );
+    let partial = result
+        .messages
+        .iter()
+        .rev()
+        .find(|message| message.role == crate::types::Role::Assistant)
+        .map(ModelMessage::text)
+        .expect("partial assistant message");
+    assert!(
+        partial.starts_with("chunk0 chunk1 "),
+        "unexpected partial text: {partial:?}"
+    );
+}
+
+#[tokio::test]
+async fn abort_while_awaiting_response_headers_cancels_promptly() {
+    let (runner, requests) = test_runner(ProviderScenario::SlowResponseHeaders);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+
+    let mut handle = runner.start(request).await.expect("start run");
+    timeout(Duration::from_secs(2), async {
+        while requests.lock().expect("request lock").is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("provider should be called");
+    let aborted_at = std::time::Instant::now();
+    assert!(handle.abort(), "abort should be accepted");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let latency = aborted_at.elapsed();
+
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert!(
+        latency < ABORT_LATENCY_BOUND,
+        "abort took {latency:?}, expected under {ABORT_LATENCY_BOUND:?}"
+    );
+}
+
+#[tokio::test]
+async fn refusal_completes_as_an_assistant_turn() {
+    let (runner, _requests) = test_runner(ProviderScenario::Refusal);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hello")]);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let reply = result.messages.last().expect("assistant reply");
+    assert_eq!(reply.role, crate::types::Role::Assistant);
+    assert_eq!(reply.text(), "I can't help with that.");
+}
+
+#[tokio::test]
+async fn generated_image_is_kept_on_the_assistant_message_and_survives_serde() {
+    let (runner, _requests) = test_runner(ProviderScenario::GeneratedImage);
+    let (sink, events) = capture_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("draw a cat")]);
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let image = crate::types::ImageContent {
+        data: "aW1hZ2U=".to_string(),
+        mime_type: "image/png".to_string(),
+    };
+    let reply = result.messages.last().expect("assistant reply");
+    assert_eq!(reply.role, crate::types::Role::Assistant);
+    assert_eq!(
+        reply.content,
+        vec![
+            ContentPart::Text {
+                text: "here it is".to_string(),
+            },
+            ContentPart::Image(image.clone()),
+        ]
+    );
+    let restored: ModelMessage =
+        serde_json::from_str(&serde_json::to_string(reply).expect("serialize reply"))
+            .expect("deserialize reply");
+    assert_eq!(restored.content, reply.content);
+    assert!(events
+        .lock()
+        .expect("event lock")
+        .iter()
+        .any(|event| matches!(
+            &event.payload,
+            RunEventPayload::AssistantImage { image: streamed } if *streamed == image
+        )));
+}
+
+#[tokio::test]
+async fn run_and_agent_events_merge_by_seq_into_emission_order() {
+    #[allow(clippy::large_enum_variant)]
+    enum Merged {
+        Run(RunEvent),
+        Agent(AgentEvent),
+    }
+
+    let (runner, _requests) = test_runner(ProviderScenario::ToolUpdateThenComplete);
+    let (run_sink, run_events) = capture_events();
+    let (agent_sink, agent_envelopes) = capture_agent_envelopes();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("run update tool")]);
+    request.tools = vec![update_streaming_tool(false)];
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(run_sink);
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let mut merged: Vec<(u64, Merged)> = run_events
+        .lock()
+        .expect("run event lock")
+        .drain(..)
+        .map(|event| (event.seq, Merged::Run(event)))
+        .chain(
+            agent_envelopes
+                .lock()
+                .expect("agent envelope lock")
+                .drain(..)
+                .map(|envelope| (envelope.seq, Merged::Agent(envelope.event))),
+        )
+        .collect();
+    merged.sort_by_key(|(seq, _)| *seq);
+    let seqs = merged.iter().map(|(seq, _)| *seq).collect::<Vec<_>>();
+    assert_eq!(
+        seqs,
+        (1..=merged.len() as u64).collect::<Vec<_>>(),
+        "seq is gapless and unique across both streams"
+    );
+
+    let mut message_open = false;
+    let mut tool_started = false;
+    let mut tool_updates = 0;
+    for (_, event) in &merged {
+        match event {
+            Merged::Agent(AgentEvent::MessageStart { .. }) => message_open = true,
+            Merged::Agent(AgentEvent::MessageUpdate { .. }) => {
+                assert!(message_open, "MessageUpdate before its MessageStart");
+            }
+            Merged::Agent(AgentEvent::MessageEnd { .. }) => message_open = false,
+            Merged::Agent(AgentEvent::ToolExecutionStart { tool_call_id, .. })
+                if tool_call_id == "update-tool-1" =>
+            {
+                tool_started = true;
+            }
+            Merged::Agent(AgentEvent::ToolExecutionUpdate { tool_call_id, .. })
+                if tool_call_id == "update-tool-1" =>
+            {
+                assert!(
+                    tool_started,
+                    "ToolExecutionUpdate before ToolExecutionStart"
+                );
+                tool_updates += 1;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(tool_updates, 2);
+
+    let terminal = merged
+        .iter()
+        .rposition(|(_, event)| {
+            matches!(
+                event,
+                Merged::Run(RunEvent {
+                    payload: RunEventPayload::Lifecycle {
+                        state: RunLifecycle::Completed
+                    },
+                    ..
+                })
+            )
+        })
+        .expect("terminal lifecycle event");
+    assert!(matches!(
+        merged[terminal + 1..]
+            .iter()
+            .map(|(_, event)| event)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        [Merged::Agent(AgentEvent::AgentEnd { .. })]
+    ));
+}
+
+#[tokio::test]
+async fn run_result_keeps_the_last_response_metadata() {
+    let (runner, _requests) = test_runner(ProviderScenario::ResponseMetadataPerCall);
+    let noop_tool: Arc<dyn Tool> = Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args, _ctx: ToolExecutionContext| async move { Ok(serde_json::json!({ "ok": true })) },
+    ));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("go")])
+        .with_tools(vec![noop_tool])
+        .with_approval_policy(ApprovalPolicy::always());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    let metadata = result.response_metadata.expect("response metadata");
+    assert_eq!(metadata.provider_request_id.as_deref(), Some("req_1"));
+    let sent: Vec<_> = result
+        .payload_sizes
+        .iter()
+        .map(|sizes| (sizes.request_bytes, sizes.request_sent_bytes))
+        .collect();
+    assert_eq!(sent, vec![(1_000, 100), (2_000, 200)]);
+}
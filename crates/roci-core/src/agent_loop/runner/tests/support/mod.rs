@@ -2,10 +2,11 @@ use super::*;
 
 use crate::context::overflow::{OverflowKind, OverflowRetryHint, OverflowSignal};
 use crate::models::ModelCapabilities;
-use crate::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use crate::provider::{ModelProvider, ProviderRequest, ProviderResponse, WarmUpReport};
 use crate::types::TextStreamDelta;
-use crate::types::{StreamEventType, Usage};
+use crate::types::{PayloadSizes, ResponseMetadata, StreamEventType, Usage};
 use futures::stream::{self, BoxStream};
+use futures::StreamExt;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::time::Duration;
 
@@ -20,6 +21,14 @@ pub(super) enum ProviderScenario {
     RateLimitedThenComplete,
     RateLimitedExceedsCap,
     RateLimitedWithoutRetryHint,
+    /// Provider-overloaded error (HTTP 529) on call 0, then text "done" on call 1+.
+    OverloadedThenComplete,
+    /// Call 0 streams "partial" then fails with a provider-overloaded error;
+    /// call 1+ streams text "done".
+    TextThenOverloadedThenComplete,
+    /// Supports an assistant prefix. Call 0 streams "partial" then fails with
+    /// a provider-overloaded error; call 1+ streams text " rest".
+    PrefixTextThenOverloadedThenContinues,
     RetryableTimeoutThenComplete,
     RetryableTimeoutExhausted,
     StreamTimeoutThenComplete,
@@ -34,23 +43,40 @@ pub(super) enum ProviderScenario {
     /// InputOverflow (simulating text-based detection); succeeds on call 1.
     ClassifiedOverflowThenComplete,
     ParallelSafeBatchThenComplete,
+    /// Calls 0 and 1: parallel-safe "read" + "ls" batch, with call ids
+    /// suffixed by the call index. Call 2+: Done.
+    RepeatedParallelSafeBatchThenComplete,
     MutatingBatchThenComplete,
     MixedTextAndParallelBatchThenComplete,
     DuplicateToolCallDeltaThenComplete,
     StreamEndsWithoutDoneThenComplete,
     ToolUpdateThenComplete,
+    /// Call 0: ten 300-byte argument fragments for "update_tool", the final tool
+    /// call, then one late fragment. Call 1+: text "done".
+    StreamingToolArgumentsThenComplete,
     /// Tool call for "schema_tool" with empty args on call 0, then text "done" on call 1+.
     SchemaToolBadArgs,
     /// Tool call for "schema_tool" with valid args on call 0, then text "done" on call 1+.
     SchemaToolValidArgs,
     /// Tool call for "schema_tool" with type-mismatched args on call 0, then text "done" on call 1+.
     SchemaToolTypeMismatch,
+    /// Tool call for "schema_tool" whose args are the raw malformed JSON
+    /// `{'path': '/tmp/test',}` on call 0, then text "done" on call 1+.
+    SchemaToolMalformedArgs,
     /// Emits partial assistant text then idles; used to exercise run abort path.
     PartialTextThenIdle,
     /// Opens a stream and then idles before any delta arrives.
     IdleBeforeAnyDelta,
     /// Emits "hello" text + Done with provider-reported usage (input=50, output=10).
     TextOnlyWithUsage,
+    /// Same events as `TextOnlyWithUsage`, after a 200ms wait for the first delta.
+    DelayedTextWithUsage,
+    /// Same events as `TextOnlyWithUsage`, after a five second wait for the
+    /// first delta. Each stream dropped counts toward
+    /// [`SLOW_FIRST_DELTA_STREAMS_DROPPED`].
+    SlowFirstDelta,
+    /// Streams "hel", pauses 200ms, then streams "lo" and Done.
+    TextThenPauseThenDone,
     /// Emits text delta with partial usage then a stream error; verifies that
     /// mid-stream failures still finalize usage into the run accumulator.
     TextWithUsageThenStreamError,
@@ -58,6 +84,81 @@ pub(super) enum ProviderScenario {
     /// Call 1+: text "done" + usage (input=60, output=5).
     /// Used to exercise multi-iteration exact-anchor budget estimation.
     ToolCallWithUsageThenTextWithUsage,
+    /// Every call: tool call for "noop_tool" + usage (input=40_000, output=10_000).
+    /// Used to drive run budgets past their limits.
+    RepeatedToolCallWithLargeUsage,
+    /// Supports assistant prefix; every call streams the text ` "ok"}`.
+    AssistantPrefixText,
+    /// Streams fifty short text deltas ("0," through "49,") then Done.
+    ManyTextDeltas,
+    /// Streams text deltas "a " through "i " in threes, separated by one
+    /// reasoning delta and one PNG image, then Done.
+    TextBurstsAroundNonTextDeltas,
+    /// Streams the refusal "I can't help with that." then Done with
+    /// `FinishReason::Refusal`.
+    Refusal,
+    /// Streams "here it is", then one PNG image, then Done.
+    GeneratedImage,
+    /// Call 1: tool call for "noop_tool". Every other call is rate limited
+    /// with a 1ms retry-after hint.
+    RateLimitedAroundToolCall,
+    /// Streams "chunk0 ", "chunk1 ", ... one text delta every 50ms, for ten
+    /// seconds, then Done.
+    SlowTextDeltas,
+    /// `stream_text` itself waits five seconds before returning, as when a
+    /// provider is slow to send response headers.
+    SlowResponseHeaders,
+    /// Supports warm-up, which takes 150ms; every call streams like
+    /// `TextOnlyWithUsage`.
+    SlowWarmUp,
+    /// Supports warm-up, which fails; every call streams like
+    /// `TextOnlyWithUsage`.
+    FailedWarmUp,
+    /// Supports JSON schema alongside tools. Call 0: valid "schema_tool" call.
+    /// Call 1+: text `{"answer":"done"}`.
+    StructuredAnswerInline,
+    /// Supports JSON schema without tools. Call 0: valid "schema_tool" call.
+    /// Call 1: text "done". Call 2+: text `{"answer":"done"}`.
+    StructuredAnswerExtraCall,
+    /// Supports JSON schema without tools. Call 0: text "done". Call 1: text
+    /// `{"answer":42}`. Call 2+: text `{"answer":"done"}`.
+    StructuredAnswerNeedsRepair,
+    /// Supports JSON schema without tools; every call streams `{"answer":42}`.
+    StructuredAnswerInvalid,
+    /// Same events as `ToolCallWithUsageThenTextWithUsage`, with response
+    /// metadata on each Done delta naming the call: `req_0`, `req_1`, ...
+    /// Call `n` reports `1000 * (n + 1)` request bytes gzipped to `100 * (n + 1)`.
+    ResponseMetadataPerCall,
+    /// Every call streams like `TextOnlyWithUsage`, except calls whose last
+    /// message mentions "fail", which fail like `ImmediateStreamError`.
+    FailsPromptsMentioningFail,
+}
+
+/// Streams dropped by [`ProviderScenario::SlowFirstDelta`]; only one test
+/// uses that scenario.
+pub(super) static SLOW_FIRST_DELTA_STREAMS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct CountDrop(&'static AtomicUsize);
+
+impl Drop for CountDrop {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn text_delta(text: &str) -> Result<TextStreamDelta, RociError> {
+    Ok(TextStreamDelta {
+        text: text.to_string(),
+        event_type: StreamEventType::TextDelta,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    })
 }
 
 struct StubProvider {
@@ -75,7 +176,16 @@ impl StubProvider {
         Self {
             scenario,
             calls: AtomicUsize::new(0),
-            capabilities: ModelCapabilities::default(),
+            capabilities: ModelCapabilities {
+                supports_json_schema: matches!(
+                    scenario,
+                    ProviderScenario::StructuredAnswerInline
+                        | ProviderScenario::StructuredAnswerExtraCall
+                        | ProviderScenario::StructuredAnswerNeedsRepair
+                        | ProviderScenario::StructuredAnswerInvalid
+                ),
+                ..ModelCapabilities::default()
+            },
             requests,
         }
     }
@@ -95,6 +205,36 @@ impl ModelProvider for StubProvider {
         &self.capabilities
     }
 
+    fn supports_assistant_prefix(&self) -> bool {
+        matches!(
+            self.scenario,
+            ProviderScenario::AssistantPrefixText
+                | ProviderScenario::PrefixTextThenOverloadedThenContinues
+        )
+    }
+
+    fn supports_response_format_with_tools(&self) -> bool {
+        matches!(self.scenario, ProviderScenario::StructuredAnswerInline)
+    }
+
+    fn supports_warm_up(&self) -> bool {
+        matches!(
+            self.scenario,
+            ProviderScenario::SlowWarmUp | ProviderScenario::FailedWarmUp
+        )
+    }
+
+    async fn warm_up(&self) -> Result<WarmUpReport, RociError> {
+        if matches!(self.scenario, ProviderScenario::FailedWarmUp) {
+            return Err(RociError::api(500, "model failed to load"));
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        Ok(WarmUpReport {
+            load_duration: Some(Duration::from_millis(150)),
+            ..WarmUpReport::default()
+        })
+    }
+
     async fn generate_text(
         &self,
         _request: &ProviderRequest,
@@ -113,6 +253,42 @@ impl ModelProvider for StubProvider {
             .expect("request lock")
             .push(request.clone());
         let call_index = self.calls.fetch_add(1, Ordering::SeqCst);
+        if matches!(self.scenario, ProviderScenario::SlowResponseHeaders) {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            return Ok(Box::pin(stream::pending()));
+        }
+        if matches!(self.scenario, ProviderScenario::SlowTextDeltas) {
+            let chunks = stream::iter(0..200).then(|index| async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(TextStreamDelta {
+                    text: format!("chunk{index} "),
+                    event_type: StreamEventType::TextDelta,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: None,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                })
+            });
+            let done = stream::once(async {
+                Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::Done,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: Some(Usage::default()),
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                })
+            });
+            return Ok(Box::pin(chunks.chain(done)));
+        }
         if matches!(self.scenario, ProviderScenario::IdleBeforeAnyDelta) {
             return Ok(Box::pin(stream::pending()));
         }
@@ -129,6 +305,8 @@ impl ModelProvider for StubProvider {
                             reasoning: None,
                             reasoning_signature: None,
                             reasoning_type: None,
+                            image: None,
+                            response_metadata: None,
                         }),
                         1,
                     )),
@@ -144,6 +322,8 @@ impl ModelProvider for StubProvider {
                                 reasoning: None,
                                 reasoning_signature: None,
                                 reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
                             }),
                             2,
                         ))
@@ -153,6 +333,77 @@ impl ModelProvider for StubProvider {
             });
             return Ok(Box::pin(stream));
         }
+        if matches!(self.scenario, ProviderScenario::DelayedTextWithUsage) {
+            let events = scenario_events::events_for_scenario(
+                ProviderScenario::TextOnlyWithUsage,
+                call_index,
+            )?;
+            let delay = stream::once(tokio::time::sleep(Duration::from_millis(200)))
+                .filter_map(|()| async { None::<Result<TextStreamDelta, RociError>> });
+            return Ok(Box::pin(delay.chain(stream::iter(events))));
+        }
+        if matches!(self.scenario, ProviderScenario::SlowFirstDelta) {
+            let events = scenario_events::events_for_scenario(
+                ProviderScenario::TextOnlyWithUsage,
+                call_index,
+            )?;
+            let guard = CountDrop(&SLOW_FIRST_DELTA_STREAMS_DROPPED);
+            let delay = stream::once(tokio::time::sleep(Duration::from_secs(5)))
+                .filter_map(|()| async { None::<Result<TextStreamDelta, RociError>> });
+            let events = stream::iter(events).map(move |event| {
+                let _ = &guard;
+                event
+            });
+            return Ok(Box::pin(delay.chain(events)));
+        }
+        if matches!(self.scenario, ProviderScenario::TextThenPauseThenDone) {
+            let pause = stream::once(tokio::time::sleep(Duration::from_millis(200)))
+                .filter_map(|()| async { None::<Result<TextStreamDelta, RociError>> });
+            let done = TextStreamDelta {
+                event_type: StreamEventType::Done,
+                usage: Some(Usage::default()),
+                ..text_delta("")?
+            };
+            return Ok(Box::pin(
+                stream::iter([text_delta("hel")])
+                    .chain(pause)
+                    .chain(stream::iter([text_delta("lo"), Ok(done)])),
+            ));
+        }
+        if matches!(self.scenario, ProviderScenario::FailsPromptsMentioningFail) {
+            let fails = request
+                .messages
+                .last()
+                .is_some_and(|message| message.text().contains("fail"));
+            let scenario = if fails {
+                ProviderScenario::ImmediateStreamError
+            } else {
+                ProviderScenario::TextOnlyWithUsage
+            };
+            let events = scenario_events::events_for_scenario(scenario, call_index)?;
+            return Ok(Box::pin(stream::iter(events)));
+        }
+        if matches!(self.scenario, ProviderScenario::ResponseMetadataPerCall) {
+            let mut events = scenario_events::events_for_scenario(
+                ProviderScenario::ToolCallWithUsageThenTextWithUsage,
+                call_index,
+            )?;
+            for delta in events.iter_mut().flatten() {
+                if delta.event_type == StreamEventType::Done {
+                    delta.response_metadata = Some(Box::new(ResponseMetadata {
+                        provider_request_id: Some(format!("req_{call_index}")),
+                        payload: Some(PayloadSizes {
+                            request_bytes: 1_000 * (call_index as u64 + 1),
+                            request_sent_bytes: 100 * (call_index as u64 + 1),
+                            request_gzipped: true,
+                            response_bytes: Some(50),
+                        }),
+                        ..ResponseMetadata::default()
+                    }));
+                }
+            }
+            return Ok(Box::pin(stream::iter(events)));
+        }
         let events = scenario_events::events_for_scenario(self.scenario, call_index)?;
         Ok(Box::pin(stream::iter(events)))
     }
@@ -181,6 +432,15 @@ impl ModelProvider for StubProvider {
 
 pub(super) fn test_runner(
     scenario: ProviderScenario,
+) -> (LoopRunner, Arc<std::sync::Mutex<Vec<ProviderRequest>>>) {
+    test_runner_with_config(RociConfig::new(), scenario)
+}
+
+/// [`test_runner`] with a caller-supplied config; the stub provider is named
+/// `"stub"` for per-provider settings.
+pub(super) fn test_runner_with_config(
+    config: RociConfig,
+    scenario: ProviderScenario,
 ) -> (LoopRunner, Arc<std::sync::Mutex<Vec<ProviderRequest>>>) {
     let requests = Arc::new(std::sync::Mutex::new(Vec::<ProviderRequest>::new()));
     let provider_requests = requests.clone();
@@ -190,9 +450,37 @@ pub(super) fn test_runner(
             provider_requests.clone(),
         )))
     });
+    (LoopRunner::with_provider_factory(config, factory), requests)
+}
+
+/// Runner following [`RociConfig::global`] that records the config each
+/// provider is built with. Scenarios are picked by model id as in
+/// [`test_runner_by_model`].
+pub(super) fn global_config_runner(
+    scenarios: Vec<(&'static str, ProviderScenario)>,
+) -> (LoopRunner, Arc<std::sync::Mutex<Vec<RociConfig>>>) {
+    let scenarios = std::collections::HashMap::<String, ProviderScenario>::from_iter(
+        scenarios
+            .into_iter()
+            .map(|(model_id, scenario)| (model_id.to_string(), scenario)),
+    );
+    let configs = Arc::new(std::sync::Mutex::new(Vec::<RociConfig>::new()));
+    let provider_configs = configs.clone();
+    let requests = Arc::new(std::sync::Mutex::new(Vec::<ProviderRequest>::new()));
+    let factory: ProviderFactory = Arc::new(move |model, config| {
+        provider_configs
+            .lock()
+            .expect("configs lock")
+            .push(config.clone());
+        let scenario = scenarios
+            .get(model.model_id())
+            .copied()
+            .unwrap_or(ProviderScenario::MissingOptionalFields);
+        Ok(Box::new(StubProvider::new(scenario, requests.clone())))
+    });
     (
-        LoopRunner::with_provider_factory(RociConfig::new(), factory),
-        requests,
+        LoopRunner::with_provider_factory(RociConfig::new(), factory).following_global_config(),
+        configs,
     )
 }
 
@@ -244,10 +532,24 @@ pub(super) fn capture_events() -> (RunEventSink, Arc<std::sync::Mutex<Vec<RunEve
 pub(super) fn capture_agent_events() -> (AgentEventSink, Arc<std::sync::Mutex<Vec<AgentEvent>>>) {
     let events = Arc::new(std::sync::Mutex::new(Vec::<AgentEvent>::new()));
     let sink_events = events.clone();
-    let sink: AgentEventSink = Arc::new(move |event| {
+    let sink: AgentEventSink = Arc::new(move |envelope| {
         if let Ok(mut guard) = sink_events.lock() {
-            guard.push(event);
+            guard.push(envelope.event);
         }
     });
     (sink, events)
 }
+
+pub(super) fn capture_agent_envelopes() -> (
+    AgentEventSink,
+    Arc<std::sync::Mutex<Vec<AgentEventEnvelope>>>,
+) {
+    let envelopes = Arc::new(std::sync::Mutex::new(Vec::<AgentEventEnvelope>::new()));
+    let sink_envelopes = envelopes.clone();
+    let sink: AgentEventSink = Arc::new(move |envelope| {
+        if let Ok(mut guard) = sink_envelopes.lock() {
+            guard.push(envelope);
+        }
+    });
+    (sink, envelopes)
+}
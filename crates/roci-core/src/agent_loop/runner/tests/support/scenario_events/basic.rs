@@ -1,7 +1,9 @@
 use super::super::ProviderScenario;
 use crate::error::RociError;
 use crate::error::{ErrorCode, ErrorDetails};
-use crate::types::{AgentToolCall, StreamEventType, TextStreamDelta, Usage};
+use crate::types::{
+    AgentToolCall, FinishReason, ImageContent, StreamEventType, TextStreamDelta, Usage,
+};
 
 fn typed_overflow_error() -> RociError {
     RociError::api_with_details(
@@ -16,6 +18,21 @@ fn typed_overflow_error() -> RociError {
     )
 }
 
+fn text_delta(text: &str) -> TextStreamDelta {
+    TextStreamDelta {
+        text: text.to_string(),
+        event_type: StreamEventType::TextDelta,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
 pub(super) fn events_for_scenario(
     scenario: ProviderScenario,
     call_index: usize,
@@ -31,6 +48,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -41,6 +60,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: "done".to_string(),
@@ -51,6 +72,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -61,6 +84,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ]),
         ProviderScenario::TextThenStreamError => Ok(vec![
@@ -73,6 +98,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: "upstream stream failure".to_string(),
@@ -83,6 +110,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ]),
         ProviderScenario::ImmediateStreamError => Ok(vec![Err(RociError::Stream(
@@ -104,6 +133,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -114,6 +145,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ]),
         ProviderScenario::RateLimitedThenComplete => {
@@ -131,6 +164,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })])
         }
         ProviderScenario::RateLimitedExceedsCap => Err(RociError::RateLimited {
@@ -139,6 +174,30 @@ pub(super) fn events_for_scenario(
         ProviderScenario::RateLimitedWithoutRetryHint => Err(RociError::RateLimited {
             retry_after_ms: None,
         }),
+        ProviderScenario::OverloadedThenComplete => {
+            if call_index == 0 {
+                return Err(RociError::overloaded(529, "Overloaded"));
+            }
+            Ok(vec![Ok(text_delta("done"))])
+        }
+        ProviderScenario::TextThenOverloadedThenComplete => {
+            if call_index == 0 {
+                return Ok(vec![
+                    Ok(text_delta("partial")),
+                    Err(RociError::overloaded(529, "Overloaded")),
+                ]);
+            }
+            Ok(vec![Ok(text_delta("done"))])
+        }
+        ProviderScenario::PrefixTextThenOverloadedThenContinues => {
+            if call_index == 0 {
+                return Ok(vec![
+                    Ok(text_delta("partial")),
+                    Err(RociError::overloaded(529, "Overloaded")),
+                ]);
+            }
+            Ok(vec![Ok(text_delta(" rest"))])
+        }
         ProviderScenario::RetryableTimeoutThenComplete => {
             if call_index == 0 {
                 return Err(RociError::Timeout(10));
@@ -152,6 +211,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })])
         }
         ProviderScenario::RetryableTimeoutExhausted => Err(RociError::Timeout(10)),
@@ -168,6 +229,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })])
         }
         ProviderScenario::ContextOverflowThenComplete => {
@@ -183,6 +246,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             })])
         }
         ProviderScenario::ContextOverflowAlways => Err(typed_overflow_error()),
@@ -203,6 +268,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
                 Ok(TextStreamDelta {
                     text: String::new(),
@@ -213,6 +280,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
             ])
         }
@@ -233,6 +302,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
                 Ok(TextStreamDelta {
                     text: String::new(),
@@ -243,10 +314,14 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 }),
             ])
         }
-        ProviderScenario::TextOnlyWithUsage => Ok(vec![
+        ProviderScenario::TextOnlyWithUsage
+        | ProviderScenario::SlowWarmUp
+        | ProviderScenario::FailedWarmUp => Ok(vec![
             Ok(TextStreamDelta {
                 text: "hello".to_string(),
                 event_type: StreamEventType::TextDelta,
@@ -256,6 +331,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -271,6 +348,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ]),
         ProviderScenario::TextWithUsageThenStreamError => Ok(vec![
@@ -288,6 +367,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Err(RociError::Stream(
                 "simulated mid-stream failure".to_string(),
@@ -312,6 +393,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -327,6 +410,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -341,6 +426,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -356,10 +443,194 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             }
         }
+        ProviderScenario::RepeatedToolCallWithLargeUsage => Ok(vec![
+            Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::ToolCallDelta,
+                tool_call: Some(AgentToolCall {
+                    id: format!("tc-large-{call_index}"),
+                    name: "noop_tool".to_string(),
+                    arguments: serde_json::json!({}),
+                    called_as: None,
+                    recipient: None,
+                }),
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+            Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: None,
+                usage: Some(Usage {
+                    input_tokens: 40_000,
+                    output_tokens: 10_000,
+                    total_tokens: 50_000,
+                    ..Usage::default()
+                }),
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+        ]),
+        ProviderScenario::AssistantPrefixText => Ok(vec![
+            Ok(TextStreamDelta {
+                text: " \"ok\"}".to_string(),
+                event_type: StreamEventType::TextDelta,
+                tool_call: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+            Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+        ]),
+        ProviderScenario::ManyTextDeltas => {
+            let mut events: Vec<Result<TextStreamDelta, RociError>> = (0..50)
+                .map(|index| Ok(text_delta(&format!("{index},"))))
+                .collect();
+            events.push(Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }));
+            Ok(events)
+        }
+        ProviderScenario::TextBurstsAroundNonTextDeltas => Ok(vec![
+            Ok(text_delta("a ")),
+            Ok(text_delta("b ")),
+            Ok(text_delta("c ")),
+            Ok(TextStreamDelta {
+                event_type: StreamEventType::Reasoning,
+                reasoning: Some("thinking".to_string()),
+                ..text_delta("")
+            }),
+            Ok(text_delta("d ")),
+            Ok(text_delta("e ")),
+            Ok(text_delta("f ")),
+            Ok(TextStreamDelta::image(ImageContent {
+                data: "aW1hZ2U=".to_string(),
+                mime_type: "image/png".to_string(),
+            })),
+            Ok(text_delta("g ")),
+            Ok(text_delta("h ")),
+            Ok(text_delta("i ")),
+            Ok(TextStreamDelta {
+                event_type: StreamEventType::Done,
+                finish_reason: Some(FinishReason::Stop),
+                ..text_delta("")
+            }),
+        ]),
+        ProviderScenario::Refusal => Ok(vec![
+            Ok(TextStreamDelta {
+                event_type: StreamEventType::RefusalDelta,
+                ..text_delta("I can't help with that.")
+            }),
+            Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: Some(FinishReason::Refusal),
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+        ]),
+        ProviderScenario::GeneratedImage => Ok(vec![
+            Ok(text_delta("here it is")),
+            Ok(TextStreamDelta::image(ImageContent {
+                data: "aW1hZ2U=".to_string(),
+                mime_type: "image/png".to_string(),
+            })),
+            Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: Some(FinishReason::Stop),
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            }),
+        ]),
+        ProviderScenario::RateLimitedAroundToolCall => {
+            if call_index != 1 {
+                return Err(RociError::RateLimited {
+                    retry_after_ms: Some(1),
+                });
+            }
+            Ok(vec![
+                Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::ToolCallDelta,
+                    tool_call: Some(AgentToolCall {
+                        id: "tc-between-limits".to_string(),
+                        name: "noop_tool".to_string(),
+                        arguments: serde_json::json!({}),
+                        called_as: None,
+                        recipient: None,
+                    }),
+                    finish_reason: None,
+                    usage: None,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                }),
+                Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::Done,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: None,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                }),
+            ])
+        }
         _ => unreachable!(),
     }
 }
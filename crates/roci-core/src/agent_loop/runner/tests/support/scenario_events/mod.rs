@@ -4,6 +4,7 @@ use crate::types::TextStreamDelta;
 
 mod basic;
 mod schema;
+mod structured;
 mod tooling;
 
 pub(super) fn events_for_scenario(
@@ -18,6 +19,9 @@ pub(super) fn events_for_scenario(
         | ProviderScenario::RateLimitedThenComplete
         | ProviderScenario::RateLimitedExceedsCap
         | ProviderScenario::RateLimitedWithoutRetryHint
+        | ProviderScenario::OverloadedThenComplete
+        | ProviderScenario::TextThenOverloadedThenComplete
+        | ProviderScenario::PrefixTextThenOverloadedThenContinues
         | ProviderScenario::RetryableTimeoutThenComplete
         | ProviderScenario::RetryableTimeoutExhausted
         | ProviderScenario::StreamTimeoutThenComplete
@@ -30,26 +34,50 @@ pub(super) fn events_for_scenario(
             basic::events_for_scenario(scenario, call_index)
         }
         ProviderScenario::ParallelSafeBatchThenComplete
+        | ProviderScenario::RepeatedParallelSafeBatchThenComplete
         | ProviderScenario::MutatingBatchThenComplete
         | ProviderScenario::MixedTextAndParallelBatchThenComplete
         | ProviderScenario::DuplicateToolCallDeltaThenComplete
         | ProviderScenario::StreamEndsWithoutDoneThenComplete
-        | ProviderScenario::ToolUpdateThenComplete => {
+        | ProviderScenario::ToolUpdateThenComplete
+        | ProviderScenario::StreamingToolArgumentsThenComplete => {
             tooling::events_for_scenario(scenario, call_index)
         }
         ProviderScenario::SchemaToolBadArgs
         | ProviderScenario::SchemaToolValidArgs
-        | ProviderScenario::SchemaToolTypeMismatch => {
+        | ProviderScenario::SchemaToolTypeMismatch
+        | ProviderScenario::SchemaToolMalformedArgs => {
             schema::events_for_scenario(scenario, call_index)
         }
-        ProviderScenario::PartialTextThenIdle | ProviderScenario::IdleBeforeAnyDelta => {
-            Err(RociError::InvalidState(
-                "idle stream scenarios are generated directly by the stub stream".to_string(),
-            ))
+        ProviderScenario::StructuredAnswerInline
+        | ProviderScenario::StructuredAnswerExtraCall
+        | ProviderScenario::StructuredAnswerNeedsRepair
+        | ProviderScenario::StructuredAnswerInvalid => {
+            structured::events_for_scenario(scenario, call_index)
         }
+        ProviderScenario::PartialTextThenIdle
+        | ProviderScenario::IdleBeforeAnyDelta
+        | ProviderScenario::DelayedTextWithUsage
+        | ProviderScenario::SlowFirstDelta
+        | ProviderScenario::TextThenPauseThenDone
+        | ProviderScenario::SlowTextDeltas
+        | ProviderScenario::SlowResponseHeaders
+        | ProviderScenario::ResponseMetadataPerCall
+        | ProviderScenario::FailsPromptsMentioningFail => Err(RociError::InvalidState(
+            "delayed stream scenarios are generated directly by the stub stream".to_string(),
+        )),
         ProviderScenario::TextOnlyWithUsage
+        | ProviderScenario::SlowWarmUp
+        | ProviderScenario::FailedWarmUp
         | ProviderScenario::TextWithUsageThenStreamError
-        | ProviderScenario::ToolCallWithUsageThenTextWithUsage => {
+        | ProviderScenario::ToolCallWithUsageThenTextWithUsage
+        | ProviderScenario::RepeatedToolCallWithLargeUsage
+        | ProviderScenario::AssistantPrefixText
+        | ProviderScenario::ManyTextDeltas
+        | ProviderScenario::TextBurstsAroundNonTextDeltas
+        | ProviderScenario::Refusal
+        | ProviderScenario::GeneratedImage
+        | ProviderScenario::RateLimitedAroundToolCall => {
             basic::events_for_scenario(scenario, call_index)
         }
     }
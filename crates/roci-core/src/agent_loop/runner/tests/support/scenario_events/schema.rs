@@ -10,6 +10,9 @@ pub(super) fn events_for_scenario(
         ProviderScenario::SchemaToolBadArgs => serde_json::json!({}),
         ProviderScenario::SchemaToolValidArgs => serde_json::json!({ "path": "/tmp/test" }),
         ProviderScenario::SchemaToolTypeMismatch => serde_json::json!({ "path": 42 }),
+        ProviderScenario::SchemaToolMalformedArgs => {
+            serde_json::Value::String("{'path': '/tmp/test',}".to_string())
+        }
         _ => unreachable!(),
     };
 
@@ -30,6 +33,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -40,6 +45,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ])
     } else {
@@ -53,6 +60,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
             Ok(TextStreamDelta {
                 text: String::new(),
@@ -63,6 +72,8 @@ pub(super) fn events_for_scenario(
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }),
         ])
     }
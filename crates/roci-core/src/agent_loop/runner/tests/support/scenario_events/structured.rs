@@ -0,0 +1,77 @@
+use super::super::ProviderScenario;
+use crate::error::RociError;
+use crate::types::{AgentToolCall, StreamEventType, TextStreamDelta, Usage};
+
+pub(super) fn events_for_scenario(
+    scenario: ProviderScenario,
+    call_index: usize,
+) -> Result<Vec<Result<TextStreamDelta, RociError>>, RociError> {
+    const VALID: &str = r#"{"answer":"done"}"#;
+    const INVALID: &str = r#"{"answer":42}"#;
+    let text = match (scenario, call_index) {
+        (ProviderScenario::StructuredAnswerInline, 0)
+        | (ProviderScenario::StructuredAnswerExtraCall, 0) => return Ok(tool_call_events()),
+        (ProviderScenario::StructuredAnswerInline, _) => VALID,
+        (ProviderScenario::StructuredAnswerExtraCall, 1) => "done",
+        (ProviderScenario::StructuredAnswerExtraCall, _) => VALID,
+        (ProviderScenario::StructuredAnswerNeedsRepair, 0) => "done",
+        (ProviderScenario::StructuredAnswerNeedsRepair, 1) => INVALID,
+        (ProviderScenario::StructuredAnswerNeedsRepair, _) => VALID,
+        (ProviderScenario::StructuredAnswerInvalid, _) => INVALID,
+        _ => unreachable!(),
+    };
+    Ok(vec![
+        Ok(TextStreamDelta {
+            text: text.to_string(),
+            event_type: StreamEventType::TextDelta,
+            tool_call: None,
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        }),
+        Ok(done()),
+    ])
+}
+
+fn tool_call_events() -> Vec<Result<TextStreamDelta, RociError>> {
+    vec![
+        Ok(TextStreamDelta {
+            text: String::new(),
+            event_type: StreamEventType::ToolCallDelta,
+            tool_call: Some(AgentToolCall {
+                id: "schema-call-1".to_string(),
+                name: "schema_tool".to_string(),
+                arguments: serde_json::json!({ "path": "/tmp/test" }),
+                called_as: None,
+                recipient: None,
+            }),
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        }),
+        Ok(done()),
+    ]
+}
+
+fn done() -> TextStreamDelta {
+    TextStreamDelta {
+        text: String::new(),
+        event_type: StreamEventType::Done,
+        tool_call: None,
+        finish_reason: None,
+        usage: Some(Usage::default()),
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
@@ -25,6 +25,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -41,6 +43,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -51,6 +55,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -63,6 +69,61 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                })])
+            }
+        }
+        ProviderScenario::RepeatedParallelSafeBatchThenComplete => {
+            if call_index < 2 {
+                let tool_call_delta = |id: String, name: &str| {
+                    Ok(TextStreamDelta {
+                        text: String::new(),
+                        event_type: StreamEventType::ToolCallDelta,
+                        tool_call: Some(AgentToolCall {
+                            id,
+                            name: name.to_string(),
+                            arguments: serde_json::json!({}),
+                            called_as: None,
+                            recipient: None,
+                        }),
+                        finish_reason: None,
+                        usage: None,
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    })
+                };
+                Ok(vec![
+                    tool_call_delta(format!("safe-read-{call_index}"), "read"),
+                    tool_call_delta(format!("safe-ls-{call_index}"), "ls"),
+                    Ok(TextStreamDelta {
+                        text: String::new(),
+                        event_type: StreamEventType::Done,
+                        tool_call: None,
+                        finish_reason: None,
+                        usage: Some(Usage::default()),
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    }),
+                ])
+            } else {
+                Ok(vec![Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::Done,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: Some(Usage::default()),
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 })])
             }
         }
@@ -84,6 +145,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -100,6 +163,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -116,6 +181,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -126,6 +193,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -138,6 +207,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 })])
             }
         }
@@ -153,6 +224,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -169,6 +242,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -185,6 +260,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -195,6 +272,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -208,6 +287,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -218,6 +299,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             }
@@ -240,6 +323,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -256,6 +341,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -266,6 +353,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -278,6 +367,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 })])
             }
         }
@@ -298,6 +389,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 })])
             } else {
                 Ok(vec![Ok(TextStreamDelta {
@@ -309,6 +402,8 @@ pub(super) fn events_for_scenario(
                     reasoning: None,
                     reasoning_signature: None,
                     reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
                 })])
             }
         }
@@ -330,6 +425,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -340,6 +437,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             } else {
@@ -353,6 +452,85 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    }),
+                    Ok(TextStreamDelta {
+                        text: String::new(),
+                        event_type: StreamEventType::Done,
+                        tool_call: None,
+                        finish_reason: None,
+                        usage: Some(Usage::default()),
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    }),
+                ])
+            }
+        }
+        ProviderScenario::StreamingToolArgumentsThenComplete => {
+            if call_index == 0 {
+                let fragment = "a".repeat(300);
+                let mut events = (0..10)
+                    .map(|_| {
+                        Ok(TextStreamDelta::tool_call_arguments(
+                            "args-call-1",
+                            "update_tool",
+                            fragment.clone(),
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                events.push(Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::ToolCallDelta,
+                    tool_call: Some(AgentToolCall {
+                        id: "args-call-1".to_string(),
+                        name: "update_tool".to_string(),
+                        arguments: serde_json::json!({ "path": "README.md" }),
+                        called_as: None,
+                        recipient: None,
+                    }),
+                    finish_reason: None,
+                    usage: None,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                }));
+                events.push(Ok(TextStreamDelta::tool_call_arguments(
+                    "args-call-1",
+                    "update_tool",
+                    "a".repeat(4096),
+                )));
+                events.push(Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::Done,
+                    tool_call: None,
+                    finish_reason: None,
+                    usage: Some(Usage::default()),
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                }));
+                Ok(events)
+            } else {
+                Ok(vec![
+                    Ok(TextStreamDelta {
+                        text: "done".to_string(),
+                        event_type: StreamEventType::TextDelta,
+                        tool_call: None,
+                        finish_reason: None,
+                        usage: None,
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                     Ok(TextStreamDelta {
                         text: String::new(),
@@ -363,6 +541,8 @@ pub(super) fn events_for_scenario(
                         reasoning: None,
                         reasoning_signature: None,
                         reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
                     }),
                 ])
             }
@@ -0,0 +1,191 @@
+use super::*;
+use crate::agent_loop::{TaskCarryOver, TaskSpec};
+use crate::provider::ProviderRequest;
+use crate::types::{Role, Usage};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+fn three_tasks() -> Vec<TaskSpec> {
+    vec![
+        TaskSpec::new("first", "write the intro"),
+        TaskSpec::new("second", "this one will fail"),
+        TaskSpec::new("third", "write the outro"),
+    ]
+}
+
+fn task_request(carry_over: TaskCarryOver) -> RunRequest {
+    RunRequest::new(test_model(), vec![ModelMessage::system("be brief")])
+        .with_task_list(three_tasks())
+        .with_task_carry_over(carry_over)
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        })
+}
+
+/// The last user prompt of each provider request.
+fn prompts(requests: &[ProviderRequest]) -> Vec<String> {
+    requests
+        .iter()
+        .map(|request| request.messages.last().expect("prompt").text())
+        .collect()
+}
+
+#[tokio::test]
+async fn failed_task_is_recorded_and_the_next_task_still_runs() {
+    let (runner, requests) = test_runner(ProviderScenario::FailsPromptsMentioningFail);
+    let (sink, events) = capture_events();
+    let request = task_request(TaskCarryOver::None).with_event_sink(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    let outcomes: Vec<_> = result
+        .task_results
+        .iter()
+        .map(|task| (task.id.as_str(), task.status))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("first", RunStatus::Completed),
+            ("second", RunStatus::Failed),
+            ("third", RunStatus::Completed),
+        ]
+    );
+    let [first, second, third] = result.task_results.as_slice() else {
+        panic!("expected three task results");
+    };
+    assert_eq!(first.messages[0].text(), "write the intro");
+    assert_eq!(first.messages.last().unwrap().text(), "hello");
+    assert!(first.error.is_none());
+    assert_eq!(second.messages[0].text(), "this one will fail");
+    assert!(second
+        .error
+        .as_deref()
+        .is_some_and(|error| error.contains("simulated immediate stream failure")));
+    assert_eq!(third.messages[0].text(), "write the outro");
+    assert_eq!(
+        third.usage.as_ref().map(|usage| usage.input_tokens),
+        Some(50)
+    );
+    let mut task_usage = Usage::default();
+    for task in &result.task_results {
+        task_usage.merge(task.usage.as_ref().unwrap_or(&Usage::default()));
+    }
+    assert_eq!(result.usage_delta, Some(task_usage));
+
+    // Each task starts from the shared context alone.
+    let requests = requests.lock().unwrap();
+    assert_eq!(
+        prompts(&requests),
+        ["write the intro", "this one will fail", "write the outro"]
+    );
+    assert!(requests
+        .iter()
+        .all(|request| request.messages.len() == 2 && request.messages[0].role == Role::System));
+
+    let markers: Vec<String> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::Lifecycle { state } => Some(format!("{state:?}")),
+            RunEventPayload::TaskStarted { task_id, index, .. } => {
+                Some(format!("start {task_id} {index}"))
+            }
+            RunEventPayload::TaskCompleted {
+                task_id, status, ..
+            } => Some(format!("end {task_id} {status:?}")),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        markers,
+        [
+            "Started",
+            "start first 0",
+            "end first Completed",
+            "start second 1",
+            "end second Failed",
+            "start third 2",
+            "end third Completed",
+            "Completed",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn carry_over_shares_earlier_tasks_with_later_ones() {
+    let (runner, requests) = test_runner(ProviderScenario::FailsPromptsMentioningFail);
+    let handle = runner
+        .start(task_request(TaskCarryOver::Full))
+        .await
+        .expect("start run");
+    let result = timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.task_results.len(), 3);
+    let third = requests.lock().unwrap()[2].messages.clone();
+    let texts: Vec<String> = third.iter().map(ModelMessage::text).collect();
+    assert_eq!(
+        texts,
+        [
+            "be brief",
+            "write the intro",
+            "hello",
+            "this one will fail",
+            "write the outro"
+        ]
+    );
+    // The shared context once, then every task's messages.
+    assert_eq!(result.messages.len(), 6);
+
+    let (runner, requests) = test_runner(ProviderScenario::FailsPromptsMentioningFail);
+    let handle = runner
+        .start(task_request(TaskCarryOver::Summary))
+        .await
+        .expect("start run");
+    timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout");
+    let third = requests.lock().unwrap()[2].messages.clone();
+    assert_eq!(third.len(), 3);
+    let summary = third[1].text();
+    assert!(summary.contains("- first: hello"), "{summary}");
+    assert!(summary.contains("- second: did not finish"), "{summary}");
+}
+
+#[tokio::test]
+async fn aborting_stops_the_task_list() {
+    let (runner, requests) = test_runner(ProviderScenario::PartialTextThenIdle);
+    let mut handle = runner
+        .start(task_request(TaskCarryOver::None))
+        .await
+        .expect("start run");
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(handle.abort());
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Canceled);
+    assert_eq!(result.task_results.len(), 1);
+    assert_eq!(result.task_results[0].status, RunStatus::Canceled);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn task_lists_with_repeated_ids_are_rejected() {
+    let (runner, _requests) = test_runner(ProviderScenario::TextOnlyWithUsage);
+    let request = RunRequest::new(test_model(), Vec::new())
+        .with_task_list(vec![TaskSpec::new("same", "a"), TaskSpec::new("same", "b")]);
+
+    let error = runner.start(request).await.expect_err("duplicate ids");
+
+    assert!(matches!(error, RociError::Configuration(message) if message.contains("'same'")));
+}
@@ -2,7 +2,9 @@ use super::*;
 
 use crate::session::{LocalSessionFs, LogicalPath, SessionFs};
 use crate::tools::SandboxProvider;
+use crate::tools::{PlanStep, PlanStepStatus};
 use crate::tools::{ToolResultSizePolicy, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary};
+use crate::types::Role;
 
 #[derive(Debug, Default)]
 struct RecordingSandboxProvider {
@@ -395,6 +397,201 @@ async fn run_request_threads_session_context_to_tools() {
     assert_eq!(contexts.as_slice(), &[(Some("work".to_string()), true)]);
 }
 
+fn plan_tool(steps: Vec<PlanStep>) -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "replaces the run plan",
+        AgentToolParameters::empty(),
+        move |_args, ctx: ToolExecutionContext| {
+            let steps = steps.clone();
+            async move {
+                let store = ctx.plan.expect("runner provides a plan store");
+                if !steps.is_empty() {
+                    store.update(steps)?;
+                }
+                Ok(serde_json::json!({ "ok": true }))
+            }
+        },
+    ))
+}
+
+#[tokio::test]
+async fn plan_tool_updates_emit_plan_event_inside_tool_lifecycle() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let steps = vec![
+        PlanStep::new("inspect code", PlanStepStatus::InProgress),
+        PlanStep::new("write fix", PlanStepStatus::Pending),
+    ];
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("plan it")])
+        .with_tools(vec![plan_tool(steps.clone())])
+        .with_approval_policy(ApprovalPolicy::always());
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert_eq!(result.plan, steps);
+    let events = agent_events.lock().expect("agent event lock");
+    let position = |predicate: &dyn Fn(&AgentEvent) -> bool| {
+        events.iter().position(predicate).expect("expected event")
+    };
+    let start_idx = position(&|event| matches!(event, AgentEvent::ToolExecutionStart { .. }));
+    let plan_idx = position(
+        &|event| matches!(event, AgentEvent::PlanUpdate { steps: emitted } if *emitted == steps),
+    );
+    let end_idx = position(&|event| matches!(event, AgentEvent::ToolExecutionEnd { .. }));
+    assert!(start_idx < plan_idx);
+    assert!(plan_idx < end_idx);
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| matches!(event, AgentEvent::PlanUpdate { .. }))
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn tools_that_leave_plan_unchanged_emit_no_plan_event() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("run tool")])
+        .with_tools(vec![plan_tool(Vec::new())])
+        .with_approval_policy(ApprovalPolicy::always());
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Completed);
+    assert!(result.plan.is_empty());
+    assert!(!agent_events
+        .lock()
+        .expect("agent event lock")
+        .iter()
+        .any(|event| matches!(event, AgentEvent::PlanUpdate { .. })));
+}
+
+fn emitting_tool(
+    name: &str,
+    delay: Duration,
+    snapshots: Arc<std::sync::Mutex<Vec<Vec<ModelMessage>>>>,
+) -> Arc<dyn Tool> {
+    let tool_name = name.to_string();
+    Arc::new(
+        AgentTool::new(
+            tool_name.clone(),
+            format!("{tool_name} tool"),
+            AgentToolParameters::empty(),
+            move |_args, ctx: ToolExecutionContext| {
+                let tool_name = tool_name.clone();
+                let snapshots = snapshots.clone();
+                async move {
+                    let conversation = ctx.conversation.clone().expect("runner shares history");
+                    snapshots
+                        .lock()
+                        .expect("snapshot lock")
+                        .push(conversation.to_vec());
+                    tokio::time::sleep(delay).await;
+                    ctx.emit_message(ModelMessage::user(format!("note from {tool_name}")))?;
+                    Ok(serde_json::json!({ "tool": tool_name }))
+                }
+            },
+        )
+        .with_static_safety(
+            ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read),
+            read_only_safety_summary(ToolSafetyKind::Read),
+        ),
+    )
+}
+
+#[tokio::test]
+async fn tools_see_pre_batch_history_and_emitted_messages_reach_next_request() {
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let snapshots = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("take notes")])
+        .with_tools(vec![emitting_tool(
+            "noop_tool",
+            Duration::ZERO,
+            snapshots.clone(),
+        )])
+        .with_approval_policy(ApprovalPolicy::always());
+    request.agent_event_sink = Some(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(2), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let requests = requests.lock().expect("request lock");
+    assert!(requests.len() >= 2);
+    let second_request_messages = &requests[1].messages;
+    let snapshots = snapshots.lock().expect("snapshot lock");
+    assert_eq!(snapshots.len(), 1);
+    // The snapshot is the history up to and including the assistant tool call.
+    assert_eq!(snapshots[0].as_slice(), &second_request_messages[..2]);
+    assert_eq!(
+        tool_result_ids_from_messages(second_request_messages),
+        vec!["tc-anchor-1".to_string()]
+    );
+    let emitted = second_request_messages.last().expect("emitted message");
+    assert_eq!(emitted.role, Role::User);
+    assert_eq!(emitted.text(), "note from noop_tool");
+
+    let events = agent_events.lock().expect("agent event lock");
+    assert!(events.iter().any(|event| matches!(
+        event,
+        AgentEvent::MessageEnd { message } if message.text() == "note from noop_tool"
+    )));
+}
+
+#[tokio::test]
+async fn emitted_messages_follow_tool_results_in_call_order() {
+    let (runner, requests) = test_runner(ProviderScenario::ParallelSafeBatchThenComplete);
+    let snapshots = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("parallel notes")]);
+    request.tools = vec![
+        // The first call finishes last, so emission order differs from call order.
+        emitting_tool("read", Duration::from_millis(100), snapshots.clone()),
+        emitting_tool("ls", Duration::ZERO, snapshots.clone()),
+    ];
+    request.approval_policy = ApprovalPolicy::always();
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let requests = requests.lock().expect("request lock");
+    let tail = requests[1]
+        .messages
+        .iter()
+        .skip(2)
+        .map(|message| (message.role, message.text()))
+        .collect::<Vec<_>>();
+    assert_eq!(tail[0].0, Role::Tool);
+    assert_eq!(tail[1].0, Role::Tool);
+    assert_eq!(
+        tail[2..],
+        [
+            (Role::User, "note from read".to_string()),
+            (Role::User, "note from ls".to_string()),
+        ]
+    );
+    let snapshots = snapshots.lock().expect("snapshot lock");
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0], snapshots[1]);
+}
+
 #[tokio::test]
 async fn run_request_threads_sandbox_provider_to_tools() {
     let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
@@ -527,7 +724,7 @@ async fn alias_tool_call_normalizes_to_canonical_before_execution_and_persistenc
     let started_call = events
         .iter()
         .find_map(|event| match &event.payload {
-            RunEventPayload::ToolCallStarted { call } => Some(call),
+            RunEventPayload::ToolCallStarted { call, .. } => Some(call),
             _ => None,
         })
         .expect("tool call started event");
@@ -633,8 +830,8 @@ fn tool_permission_responder(
     let coordinator = Arc::new(HumanInteractionCoordinator::new());
     let (agent_sink, agent_events) = capture_agent_events();
     let sink_coordinator = coordinator.clone();
-    let sink: AgentEventSink = Arc::new(move |event| {
-        if let AgentEvent::HumanInteractionRequested { request } = &event {
+    let sink: AgentEventSink = Arc::new(move |envelope| {
+        if let AgentEvent::HumanInteractionRequested { request } = &envelope.event {
             let coordinator = sink_coordinator.clone();
             let request_id = request.request_id;
             tokio::spawn(async move {
@@ -643,7 +840,7 @@ fn tool_permission_responder(
                     .await;
             });
         }
-        agent_sink(event);
+        agent_sink(envelope);
     });
     (coordinator, sink, agent_events)
 }
@@ -973,6 +1170,72 @@ async fn mixed_text_and_parallel_tools_are_batched_before_single_followup() {
     );
 }
 
+#[tokio::test]
+async fn event_tags_and_model_are_stamped_on_lifecycle_delta_and_tool_events() {
+    let (runner, _requests) = test_runner(ProviderScenario::MixedTextAndParallelBatchThenComplete);
+    let (sink, events) = capture_events();
+    let (agent_sink, envelopes) = capture_agent_envelopes();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("mixed stream")])
+        .with_event_tags(HashMap::from([("tenant".to_string(), "acme".to_string())]));
+    request.tools = vec![
+        tracked_safe_success_tool(
+            "read",
+            Duration::from_millis(10),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        ),
+        tracked_safe_success_tool(
+            "ls",
+            Duration::from_millis(10),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        ),
+    ];
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+    request.agent_event_sink = Some(agent_sink);
+    let run_id = request.run_id;
+    let model = test_model().to_string();
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let events = events.lock().expect("event lock");
+    assert!(events
+        .iter()
+        .any(|event| matches!(event.payload, RunEventPayload::Lifecycle { .. })));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event.payload, RunEventPayload::AssistantDelta { .. })));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event.payload, RunEventPayload::ToolResult { .. })));
+    for event in events.iter() {
+        assert_eq!(event.run_id, run_id);
+        assert_eq!(event.model.as_deref(), Some(model.as_str()));
+        assert_eq!(event.tags.get("tenant"), Some("acme"));
+    }
+
+    let envelopes = envelopes.lock().expect("envelope lock");
+    assert!(envelopes
+        .iter()
+        .any(|envelope| matches!(envelope.event, AgentEvent::AgentStart { .. })));
+    assert!(envelopes
+        .iter()
+        .any(|envelope| matches!(envelope.event, AgentEvent::MessageUpdate { .. })));
+    assert!(envelopes
+        .iter()
+        .any(|envelope| matches!(envelope.event, AgentEvent::ToolExecutionEnd { .. })));
+    for envelope in envelopes.iter() {
+        assert_eq!(envelope.run_id, run_id);
+        assert_eq!(envelope.model.as_deref(), Some(model.as_str()));
+        assert_eq!(envelope.tags.get("tenant"), Some("acme"));
+    }
+}
+
 #[tokio::test]
 async fn duplicate_tool_call_deltas_are_deduplicated_by_call_id() {
     let (runner, requests) = test_runner(ProviderScenario::DuplicateToolCallDeltaThenComplete);
@@ -1064,7 +1327,7 @@ async fn approval_uses_same_duplicate_named_tool_instance_as_execution() {
     let tool_results = events
         .iter()
         .filter_map(|event| match &event.payload {
-            RunEventPayload::ToolResult { result } => Some(result),
+            RunEventPayload::ToolResult { result, .. } => Some(result),
             _ => None,
         })
         .collect::<Vec<_>>();
@@ -1101,7 +1364,7 @@ async fn tool_permission_denial_returns_tool_denial_result() {
     let tool_results = events
         .iter()
         .filter_map(|event| match &event.payload {
-            RunEventPayload::ToolResult { result } => Some(result),
+            RunEventPayload::ToolResult { result, .. } => Some(result),
             _ => None,
         })
         .collect::<Vec<_>>();
@@ -1191,3 +1454,77 @@ async fn stream_end_without_done_falls_back_to_tool_execution_and_completion() {
         "stream-end fallback should not emit failed lifecycle"
     );
 }
+
+#[tokio::test]
+async fn never_policy_declines_mutating_calls_and_runs_read_only_calls() {
+    let (runner, _requests) = test_runner(ProviderScenario::MutatingBatchThenComplete);
+    let (sink, events) = capture_events();
+    let active_calls = Arc::new(AtomicUsize::new(0));
+    let max_active_calls = Arc::new(AtomicUsize::new(0));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("mutating tools")]);
+    request.tools = vec![
+        tracked_success_tool(
+            "apply_patch",
+            Duration::from_millis(10),
+            active_calls.clone(),
+            max_active_calls.clone(),
+        ),
+        tracked_safe_success_tool(
+            "read",
+            Duration::from_millis(10),
+            active_calls.clone(),
+            max_active_calls.clone(),
+        ),
+        tracked_safe_success_tool(
+            "ls",
+            Duration::from_millis(10),
+            active_calls,
+            max_active_calls,
+        ),
+    ];
+    request.approval_policy = ApprovalPolicy::never();
+    request.approval_handler = Some(Arc::new(|_request| {
+        Box::pin(async { ApprovalDecision::Accept })
+    }));
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let events = events.lock().expect("event lock");
+    assert!(events
+        .iter()
+        .all(|event| !matches!(event.payload, RunEventPayload::ApprovalRequired { .. })));
+    let results = events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::ToolResult { result, .. } => {
+                Some((result.tool_call_id.as_str(), result.is_error))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        vec![
+            ("mutating-call-1", true),
+            ("safe-read-2", false),
+            ("safe-ls-3", false),
+        ]
+    );
+    let declined = events
+        .iter()
+        .find_map(|event| match &event.payload {
+            RunEventPayload::ToolResult { result, .. }
+                if result.tool_call_id == "mutating-call-1" =>
+            {
+                Some(result.result.clone())
+            }
+            _ => None,
+        })
+        .expect("declined result");
+    assert_eq!(declined["error"], "approval declined");
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::agent_loop::replay::StateReconstructor;
+use crate::agent_loop::transcript::TranscriptWriter;
+use crate::agent_loop::RunStatus;
+use crate::provider::ToolLimits;
+use tokio::time::{timeout, Duration};
+
+use support::{capture_events, test_model, test_runner_with_config, ProviderScenario};
+
+fn ranked_tool(name: &'static str, priority: i32) -> Arc<dyn Tool> {
+    Arc::new(
+        AgentTool::new(
+            name,
+            "test tool",
+            AgentToolParameters::empty(),
+            |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+                Ok(serde_json::json!({ "ok": true }))
+            },
+        )
+        .with_priority(priority),
+    )
+}
+
+/// Config capping the stub provider at two tools.
+fn two_tool_config() -> RociConfig {
+    let config = RociConfig::new();
+    config.set_tool_limits(
+        "stub",
+        Some(ToolLimits {
+            max_tools: Some(2),
+            ..ToolLimits::default()
+        }),
+    );
+    config
+}
+
+fn three_tools() -> Vec<Arc<dyn Tool>> {
+    vec![
+        ranked_tool("optional_tool", -1),
+        ranked_tool("core_tool", 10),
+        ranked_tool("plain_tool", 0),
+    ]
+}
+
+#[tokio::test]
+async fn tools_over_the_provider_limit_fail_before_the_call() {
+    let (runner, requests) =
+        test_runner_with_config(two_tool_config(), ProviderScenario::TextOnlyWithUsage);
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_tools(three_tools())
+        .with_approval_policy(ApprovalPolicy::always());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+
+    assert_eq!(result.status, RunStatus::Failed);
+    let error = result.error.expect("limit error");
+    assert!(
+        error.contains("3 tools are advertised but at most 2 are accepted"),
+        "{error}"
+    );
+    assert!(requests.lock().expect("request lock").is_empty());
+}
+
+#[tokio::test]
+async fn truncation_drops_the_lowest_priority_tools_and_reports_them() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("run.jsonl");
+    let (runner, requests) =
+        test_runner_with_config(two_tool_config(), ProviderScenario::TextOnlyWithUsage);
+    let (sink, events) = capture_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_tools(three_tools())
+        .with_tool_overflow(ToolOverflowPolicy::TruncateByPriority)
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_event_sink(sink)
+        .with_transcript(TranscriptWriter::create(&path).expect("create transcript"));
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let requests = requests.lock().expect("request lock");
+    let advertised: Vec<&str> = requests[0]
+        .tools
+        .iter()
+        .flatten()
+        .map(|tool| tool.name.as_str())
+        .collect();
+    assert_eq!(advertised, vec!["core_tool", "plain_tool"]);
+
+    let truncations: Vec<_> = events
+        .lock()
+        .expect("events lock")
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::ToolsTruncated { dropped, limits } => Some((dropped.clone(), *limits)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(truncations.len(), 1);
+    assert_eq!(truncations[0].0, vec!["optional_tool"]);
+    assert_eq!(truncations[0].1.max_tools, Some(2));
+
+    let transcript = std::fs::read_to_string(&path).expect("read transcript");
+    let replay = StateReconstructor::parse(&transcript);
+    let first = replay.iteration(1).expect("first iteration");
+    assert_eq!(
+        first
+            .tools
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["core_tool", "plain_tool"]
+    );
+}
@@ -0,0 +1,207 @@
+use super::*;
+use crate::tools::ToolEffects;
+
+/// `noop_tool` stand-in that times out on its first `failures` calls.
+fn flaky_tool(failures: usize, effects: ToolEffects, calls: Arc<AtomicUsize>) -> Arc<dyn Tool> {
+    Arc::new(
+        AgentTool::new(
+            "noop_tool",
+            "flaky lookup",
+            AgentToolParameters::empty(),
+            move |_args: ToolArguments, _ctx: ToolExecutionContext| {
+                let calls = calls.clone();
+                async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    if call <= failures {
+                        return Err(RociError::Timeout(call as u64));
+                    }
+                    Ok(serde_json::json!({ "call": call }))
+                }
+            },
+        )
+        .with_effects(effects),
+    )
+}
+
+fn fast_retry(max_attempts: u32) -> ToolRetryPolicy {
+    ToolRetryPolicy {
+        max_attempts,
+        backoff: RetryBackoffPolicy {
+            initial_delay_ms: 1,
+            max_delay_ms: 5,
+            ..RetryBackoffPolicy::default()
+        },
+        ..ToolRetryPolicy::default()
+    }
+}
+
+async fn run_flaky_tool(tool: Arc<dyn Tool>, policy: ToolRetryPolicy) -> Vec<AgentEvent> {
+    run_flaky_tool_with(tool, policy, |request| request).await
+}
+
+async fn run_flaky_tool_with(
+    tool: Arc<dyn Tool>,
+    policy: ToolRetryPolicy,
+    configure: impl FnOnce(RunRequest) -> RunRequest,
+) -> Vec<AgentEvent> {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("look it up")])
+        .with_tools(vec![tool])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_tool_retry(policy)
+        .with_agent_event_sink(agent_sink);
+    let request = configure(request);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    let events = agent_events.lock().expect("agent events lock").clone();
+    events
+}
+
+fn tool_result(events: &[AgentEvent]) -> (serde_json::Value, bool) {
+    events
+        .iter()
+        .find_map(|event| match event {
+            AgentEvent::ToolExecutionEnd {
+                result, is_error, ..
+            } => Some((result.result.clone(), *is_error)),
+            _ => None,
+        })
+        .expect("expected ToolExecutionEnd")
+}
+
+fn retry_updates(events: &[AgentEvent]) -> Vec<serde_json::Value> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            AgentEvent::ToolExecutionUpdate { partial_result, .. } => {
+                partial_result.details.get("retry").cloned()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn start_count(events: &[AgentEvent]) -> usize {
+    events
+        .iter()
+        .filter(|event| matches!(event, AgentEvent::ToolExecutionStart { .. }))
+        .count()
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_until_the_tool_succeeds() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let tool = flaky_tool(2, ToolEffects::ReadOnly, calls.clone());
+
+    let events = run_flaky_tool(tool, fast_retry(3)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        tool_result(&events),
+        (serde_json::json!({ "call": 3 }), false)
+    );
+    assert_eq!(start_count(&events), 1);
+    let updates = retry_updates(&events);
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0]["attempt"], 2);
+    assert_eq!(updates[1]["attempt"], 3);
+    assert_eq!(updates[0]["error_class"], "timeout");
+}
+
+#[tokio::test]
+async fn mutating_tools_are_never_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let tool = flaky_tool(2, ToolEffects::Mutating, calls.clone());
+
+    let events = run_flaky_tool(tool, fast_retry(3)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let (result, is_error) = tool_result(&events);
+    assert!(is_error);
+    assert_eq!(result, serde_json::json!({ "error": "Timeout after 1ms" }));
+    assert!(retry_updates(&events).is_empty());
+}
+
+#[tokio::test]
+async fn exhausted_retries_report_every_attempt() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let tool = flaky_tool(usize::MAX, ToolEffects::ReadOnly, calls.clone());
+
+    let events = run_flaky_tool(tool, fast_retry(3)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    let (result, is_error) = tool_result(&events);
+    assert!(is_error);
+    assert_eq!(
+        result,
+        serde_json::json!({
+            "error": "Timeout after 3ms",
+            "attempts": 3,
+            "attempt_errors": [
+                "Timeout after 1ms",
+                "Timeout after 2ms",
+                "Timeout after 3ms",
+            ],
+        })
+    );
+    assert_eq!(start_count(&events), 1);
+    assert_eq!(retry_updates(&events).len(), 2);
+}
+
+#[tokio::test]
+async fn permanent_failures_are_not_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let tool = Arc::new(
+        AgentTool::new(
+            "noop_tool",
+            "rejects its input",
+            AgentToolParameters::empty(),
+            {
+                let calls = calls.clone();
+                move |_args: ToolArguments, _ctx: ToolExecutionContext| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err(RociError::InvalidArgument("bad query".into())) }
+                }
+            },
+        )
+        .with_effects(ToolEffects::ReadOnly),
+    );
+
+    let events = run_flaky_tool(tool, fast_retry(3)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        tool_result(&events).0,
+        serde_json::json!({ "error": "Invalid argument: bad query" })
+    );
+}
+
+#[tokio::test]
+async fn retries_whose_backoff_passes_the_deadline_are_skipped() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let tool = flaky_tool(1, ToolEffects::ReadOnly, calls.clone());
+    let policy = ToolRetryPolicy {
+        max_attempts: 3,
+        backoff: RetryBackoffPolicy {
+            initial_delay_ms: 60_000,
+            max_delay_ms: 60_000,
+            ..RetryBackoffPolicy::default()
+        },
+        ..ToolRetryPolicy::default()
+    };
+    let deadline = std::time::Instant::now() + Duration::from_secs(2);
+
+    let events = run_flaky_tool_with(tool, policy, |request| request.with_deadline(deadline)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        tool_result(&events),
+        (serde_json::json!({ "error": "Timeout after 1ms" }), true)
+    );
+    assert!(retry_updates(&events).is_empty());
+}
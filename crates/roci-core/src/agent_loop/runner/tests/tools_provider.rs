@@ -0,0 +1,139 @@
+use super::*;
+use crate::agent_loop::RunStatus;
+use crate::provider::ProviderRequest;
+use tokio::time::{timeout, Duration};
+
+use support::{capture_events, test_model, test_runner, ProviderScenario};
+
+fn named_tool(name: &'static str) -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        name,
+        "test tool",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))
+}
+
+fn advertised(request: &ProviderRequest) -> Vec<String> {
+    request
+        .tools
+        .iter()
+        .flatten()
+        .map(|tool| tool.name.clone())
+        .collect()
+}
+
+fn tools_updates(events: &[RunEvent]) -> Vec<(Vec<String>, Vec<String>)> {
+    events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            RunEventPayload::ToolsUpdated { added, removed } => {
+                Some((added.clone(), removed.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn tools_provider_rebuilds_advertised_tools_each_iteration() {
+    // Call 0 invokes "noop_tool"; the provider drops it and adds "later_tool"
+    // for iteration 2.
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let iterations = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = iterations.clone();
+    let provider: ToolsProviderFn = Arc::new(move |iteration| {
+        seen.lock().expect("iteration lock").push(iteration);
+        Box::pin(async move {
+            if iteration == 1 {
+                vec![named_tool("noop_tool")]
+            } else {
+                vec![named_tool("later_tool")]
+            }
+        })
+    });
+    let mut request =
+        RunRequest::new(test_model(), vec![ModelMessage::user("hi")]).with_tools_provider(provider);
+    request.approval_policy = ApprovalPolicy::always();
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let requests = requests.lock().expect("request lock");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(advertised(&requests[0]), vec!["noop_tool"]);
+    assert_eq!(advertised(&requests[1]), vec!["later_tool"]);
+    assert_eq!(*iterations.lock().expect("iteration lock"), vec![1, 2]);
+
+    // The call issued while "noop_tool" was advertised still ran after the
+    // tool was removed from the next iteration's set.
+    let tool_results = result
+        .messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|part| match part {
+            ContentPart::ToolResult(result) => Some(result),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(tool_results.len(), 1);
+    assert!(!tool_results[0].is_error, "{:?}", tool_results[0].result);
+
+    let events = events.lock().expect("event lock");
+    assert_eq!(
+        tools_updates(&events),
+        vec![
+            (vec!["noop_tool".to_string()], vec![]),
+            (
+                vec!["later_tool".to_string()],
+                vec!["noop_tool".to_string()]
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unchanged_tool_names_do_not_emit_tools_updated() {
+    let (runner, requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (sink, events) = capture_events();
+    let provider: ToolsProviderFn =
+        Arc::new(|_iteration| Box::pin(async { vec![named_tool("noop_tool")] }));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_tools(vec![named_tool("noop_tool")])
+        .with_tools_provider(provider);
+    request.event_sink = Some(sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+
+    let requests = requests.lock().expect("request lock");
+    assert!(requests
+        .iter()
+        .all(|request| advertised(request) == vec!["noop_tool"]));
+    assert!(tools_updates(&events.lock().expect("event lock")).is_empty());
+}
+
+#[tokio::test]
+async fn tools_provider_cannot_be_combined_with_prefill() {
+    let (runner, _requests) = test_runner(ProviderScenario::AssistantPrefixText);
+    let provider: ToolsProviderFn = Arc::new(|_iteration| Box::pin(async { Vec::new() }));
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_tools_provider(provider)
+        .with_prefill("{");
+
+    let err = runner.start(request).await.expect_err("start should fail");
+
+    assert!(err
+        .to_string()
+        .contains("prefill cannot be combined with tools"));
+}
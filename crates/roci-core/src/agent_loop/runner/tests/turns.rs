@@ -0,0 +1,168 @@
+use super::*;
+
+use crate::agent_loop::TurnSummary;
+use support::{capture_agent_events, test_model, test_runner, ProviderScenario};
+
+/// `(turn_index, summary, aborted)` for each `TurnEnd`, after checking that
+/// every `TurnStart` is closed by exactly one `TurnEnd` before the next opens.
+fn paired_turns(events: &[AgentEvent]) -> Vec<(usize, TurnSummary, bool)> {
+    let mut open = None;
+    let mut ended = Vec::new();
+    for event in events {
+        match event {
+            AgentEvent::TurnStart { turn_index, .. } => {
+                assert_eq!(
+                    open, None,
+                    "turn {turn_index} started while a turn was open"
+                );
+                assert_eq!(*turn_index, ended.len() + 1, "turn indices count from 1");
+                open = Some(*turn_index);
+            }
+            AgentEvent::TurnEnd {
+                turn_index,
+                summary,
+                aborted,
+                ..
+            } => {
+                assert_eq!(open.take(), Some(*turn_index), "unpaired TurnEnd");
+                ended.push((*turn_index, summary.clone(), *aborted));
+            }
+            AgentEvent::AgentEnd { .. } => {
+                assert_eq!(open, None, "run ended with an open turn");
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(open, None, "run ended with an open turn");
+    ended
+}
+
+fn noop_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "noop_tool",
+        "does nothing",
+        AgentToolParameters::empty(),
+        |_args: ToolArguments, _ctx: ToolExecutionContext| async move {
+            Ok(serde_json::json!({ "ok": true }))
+        },
+    ))
+}
+
+#[tokio::test]
+async fn completed_run_summarizes_each_turn() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolCallWithUsageThenTextWithUsage);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("use the tool")])
+        .with_tools(vec![noop_tool()])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_agent_event_sink(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Completed);
+
+    let turns = paired_turns(&agent_events.lock().expect("agent event lock"));
+    assert_eq!(turns.len(), 2);
+    let (_, tool_turn, aborted) = &turns[0];
+    assert!(!aborted);
+    assert_eq!(tool_turn.tool_calls, 1);
+    assert_eq!(tool_turn.tool_failures, 0);
+    assert_eq!(tool_turn.text_chars, 0);
+    assert_eq!(tool_turn.usage.input_tokens, 50);
+    assert_eq!(tool_turn.usage.output_tokens, 10);
+    let (_, text_turn, aborted) = &turns[1];
+    assert!(!aborted);
+    assert_eq!(text_turn.tool_calls, 0);
+    assert_eq!(text_turn.text_chars, "done".len());
+    assert_eq!(text_turn.usage.input_tokens, 60);
+    assert_eq!(text_turn.usage.output_tokens, 5);
+}
+
+#[tokio::test]
+async fn failed_model_call_ends_the_turn_as_aborted() {
+    let (runner, _requests) = test_runner(ProviderScenario::ImmediateStreamError);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        })
+        .with_agent_event_sink(agent_sink);
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Failed);
+
+    let turns = paired_turns(&agent_events.lock().expect("agent event lock"));
+    assert_eq!(turns.len(), 1);
+    let (turn_index, summary, aborted) = &turns[0];
+    assert_eq!(*turn_index, 1);
+    assert!(aborted);
+    assert_eq!(summary.tool_calls, 0);
+}
+
+#[tokio::test]
+async fn failing_tools_are_counted_per_turn() {
+    let (runner, _requests) = test_runner(ProviderScenario::RepeatedToolFailure);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("run tool")])
+        .with_tools(vec![failing_tool()])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_agent_event_sink(agent_sink);
+    request
+        .metadata
+        .insert("runner.max_tool_failures".to_string(), "2".to_string());
+
+    let handle = runner.start(request).await.expect("start run");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Failed);
+
+    let turns = paired_turns(&agent_events.lock().expect("agent event lock"));
+    assert_eq!(turns.len(), 2);
+    for (_, summary, aborted) in &turns {
+        assert!(!aborted, "tool batches finished before the run failed");
+        assert_eq!(summary.tool_calls, 1);
+        assert_eq!(summary.tool_failures, 1);
+    }
+}
+
+#[tokio::test]
+async fn canceling_during_tools_ends_the_turn_as_aborted() {
+    let (runner, _requests) = test_runner(ProviderScenario::ToolUpdateThenComplete);
+    let (agent_sink, agent_events) = capture_agent_events();
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("cancel update tool")])
+        .with_tools(vec![update_streaming_tool(true)])
+        .with_approval_policy(ApprovalPolicy::always())
+        .with_agent_event_sink(agent_sink);
+
+    let mut handle = runner.start(request).await.expect("start run");
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert!(handle.abort(), "abort should be accepted");
+    let result = timeout(Duration::from_secs(3), handle.wait())
+        .await
+        .expect("run wait timeout");
+    assert_eq!(result.status, RunStatus::Canceled);
+
+    let events = agent_events.lock().expect("agent event lock");
+    let turns = paired_turns(&events);
+    assert_eq!(turns.len(), 1);
+    let (_, summary, aborted) = &turns[0];
+    assert!(aborted);
+    assert_eq!(summary.tool_calls, 1);
+    let assistant_message = events.iter().find_map(|event| match event {
+        AgentEvent::TurnEnd {
+            assistant_message, ..
+        } => assistant_message.clone(),
+        _ => None,
+    });
+    assert!(
+        assistant_message.is_some(),
+        "aborted turn should keep the assistant tool-call message"
+    );
+}
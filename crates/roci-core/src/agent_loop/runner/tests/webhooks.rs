@@ -0,0 +1,176 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use super::*;
+use crate::agent_loop::{
+    webhook_signature, RunStatus, RunSummary, WebhookConfig, WebhookDelivery,
+    RUN_SUMMARY_SCHEMA_VERSION, WEBHOOK_SIGNATURE_HEADER,
+};
+use crate::models::{ModelPricing, PricingTable};
+
+use support::{test_model, test_runner, ProviderScenario};
+
+async fn run(scenario: ProviderScenario, request: RunRequest) -> RunResult {
+    let (runner, _requests) = test_runner(scenario);
+    let handle = runner.start(request).await.expect("start run");
+    timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("run wait timeout")
+}
+
+fn hook(server: &MockServer, route: &str) -> WebhookConfig {
+    WebhookConfig::new(format!("{}{route}", server.uri()))
+        .with_retry_delay(Duration::from_millis(1))
+}
+
+async fn received_summaries(server: &MockServer, route: &str) -> Vec<RunSummary> {
+    server
+        .received_requests()
+        .await
+        .expect("request recording")
+        .iter()
+        .filter(|request| request.url.path() == route)
+        .map(|request| serde_json::from_slice(&request.body).expect("run summary body"))
+        .collect()
+}
+
+#[tokio::test]
+async fn completed_runs_post_a_signed_summary() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/done"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    let pricing =
+        PricingTable::empty().with_model("stub", "stub-model", ModelPricing::new(1.0, 2.0));
+    let mut request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_budget(RunBudget::new().with_pricing(pricing))
+        .with_webhook(hook(&server, "/done").with_secret("s3cret"));
+    request
+        .event_tags
+        .insert("job".to_string(), "nightly".to_string());
+    let run_id = request.run_id;
+
+    let result = run(ProviderScenario::TextOnlyWithUsage, request).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    assert_eq!(
+        result.webhook_deliveries,
+        vec![WebhookDelivery {
+            url: format!("{}/done", server.uri()),
+            delivered: true,
+            attempts: 1,
+            status_code: Some(204),
+            error: None,
+        }]
+    );
+    let requests = server.received_requests().await.expect("request recording");
+    let [posted] = &requests[..] else {
+        panic!("expected one webhook request, got {}", requests.len());
+    };
+    assert_eq!(
+        posted.headers[WEBHOOK_SIGNATURE_HEADER].to_str().unwrap(),
+        webhook_signature("s3cret", &posted.body)
+    );
+    assert_eq!(posted.headers["content-type"], "application/json");
+    let summary: RunSummary = serde_json::from_slice(&posted.body).expect("run summary body");
+    assert_eq!(summary.schema_version, RUN_SUMMARY_SCHEMA_VERSION);
+    assert_eq!(summary.run_id, run_id);
+    assert_eq!(summary.status, RunStatus::Completed);
+    assert_eq!(summary.error, None);
+    assert_eq!(summary.model.as_deref(), Some("stub:stub-model"));
+    assert_eq!(summary.usage, result.usage_delta);
+    let cost = summary.cost_usd.expect("priced run");
+    assert!((cost - 70.0 / 1_000_000.0).abs() < 1e-12, "{cost}");
+    assert_eq!(summary.finished_at, result.finished_at);
+    assert!(summary.started_at <= summary.finished_at);
+    assert_eq!(summary.tags["job"], "nightly");
+}
+
+#[tokio::test]
+async fn failed_runs_notify_only_subscribed_webhooks() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_retry_backoff(RetryBackoffPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        })
+        .with_webhook(hook(&server, "/failures").with_events(vec![RunStatus::Failed]))
+        .with_webhook(hook(&server, "/successes").with_events(vec![RunStatus::Completed]));
+
+    let result = run(ProviderScenario::ImmediateStreamError, request).await;
+
+    assert_eq!(result.status, RunStatus::Failed);
+    assert_eq!(result.webhook_deliveries.len(), 1);
+    assert!(result.webhook_deliveries[0].url.ends_with("/failures"));
+    assert!(result.webhook_deliveries[0].delivered);
+    let summaries = received_summaries(&server, "/failures").await;
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].status, RunStatus::Failed);
+    assert_eq!(summaries[0].error, result.error);
+    assert!(received_summaries(&server, "/successes").await.is_empty());
+    assert!(
+        !server.received_requests().await.expect("request recording")[0]
+            .headers
+            .contains_key(WEBHOOK_SIGNATURE_HEADER)
+    );
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_and_failures_never_fail_the_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/down"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/rejects"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+    let request = RunRequest::new(test_model(), vec![ModelMessage::user("hi")])
+        .with_webhook(hook(&server, "/flaky"))
+        .with_webhook(hook(&server, "/down").with_max_attempts(2))
+        .with_webhook(hook(&server, "/rejects"));
+
+    let result = run(ProviderScenario::TextOnlyWithUsage, request).await;
+
+    assert_eq!(result.status, RunStatus::Completed, "{:?}", result.error);
+    let outcomes = result
+        .webhook_deliveries
+        .iter()
+        .map(|delivery| (delivery.delivered, delivery.attempts, delivery.status_code))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        vec![
+            (true, 3, Some(200)),
+            (false, 2, Some(500)),
+            (false, 1, Some(400)),
+        ]
+    );
+    assert_eq!(
+        result.webhook_deliveries[1].error.as_deref(),
+        Some("HTTP 500 Internal Server Error")
+    );
+    assert_eq!(received_summaries(&server, "/flaky").await.len(), 3);
+    assert_eq!(received_summaries(&server, "/down").await.len(), 2);
+    assert_eq!(received_summaries(&server, "/rejects").await.len(), 1);
+}
@@ -0,0 +1,79 @@
+//! Keeps the tools advertised with each provider call within the
+//! provider's [`ToolLimits`].
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RociError;
+use crate::provider::{ToolDefinition, ToolLimits};
+use crate::tools::Tool;
+
+/// What a run does when its tools exceed the provider's [`ToolLimits`].
+///
+/// Providers that hit their limits may reject the request or silently drop
+/// definitions, so the check runs before every provider call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOverflowPolicy {
+    /// Fail the run before the call, naming the broken limit.
+    #[default]
+    FailFast,
+    /// Leave out the lowest-[`priority`](Tool::priority) tools until the rest
+    /// fit, ties going to the tool registered first, and emit
+    /// [`RunEventPayload::ToolsTruncated`](super::RunEventPayload::ToolsTruncated).
+    TruncateByPriority,
+}
+
+/// Definitions that fit after truncation, and the names left out.
+pub(super) struct FittedTools {
+    pub(super) definitions: Option<Vec<ToolDefinition>>,
+    pub(super) dropped: Vec<String>,
+}
+
+/// Check `definitions`, built from `tools` in order, against `limits`.
+///
+/// Returns `None` when they fit as they are.
+///
+/// # Errors
+///
+/// Returns [`RociError::Configuration`] when they do not fit and `policy` is
+/// [`ToolOverflowPolicy::FailFast`].
+pub(super) fn fit_tools(
+    tools: &[Arc<dyn Tool>],
+    definitions: &Option<Vec<ToolDefinition>>,
+    limits: ToolLimits,
+    policy: ToolOverflowPolicy,
+    model: &str,
+) -> Result<Option<FittedTools>, RociError> {
+    let Some(definitions) = definitions.as_deref() else {
+        return Ok(None);
+    };
+    let Some(violation) = limits.violation(definitions) else {
+        return Ok(None);
+    };
+    if policy == ToolOverflowPolicy::FailFast {
+        return Err(RociError::Configuration(format!(
+            "tools exceed the limits of {model}: {violation}; register fewer tools or \
+             use ToolOverflowPolicy::TruncateByPriority"
+        )));
+    }
+    let priorities: Vec<i32> = tools
+        .iter()
+        .map(|tool| tool.priority().unwrap_or(0))
+        .collect();
+    let dropped = limits.overflow(definitions, &priorities);
+    let kept = definitions
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| dropped.binary_search(index).is_err())
+        .map(|(_, definition)| definition.clone())
+        .collect::<Vec<_>>();
+    Ok(Some(FittedTools {
+        definitions: (!kept.is_empty()).then_some(kept),
+        dropped: dropped
+            .into_iter()
+            .map(|index| definitions[index].name.clone())
+            .collect(),
+    }))
+}
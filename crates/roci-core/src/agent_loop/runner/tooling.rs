@@ -1,18 +1,27 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use futures::future;
 use tokio_util::sync::CancellationToken;
 
+use crate::error::RociError;
+use crate::memory::Memory;
 use crate::session::{LogicalPath, SessionFs};
 use crate::tools::SandboxProvider;
-use crate::tools::{tool::Tool, ToolArguments, ToolSafetyPlan, ToolUpdateCallback};
+use crate::tools::{
+    tool::Tool, ArtifactStore, ChangeLog, ChangePreview, PlanStore, ScratchDir, ToolArguments,
+    ToolEffects, ToolMessageQueue, ToolSafetyPlan, ToolUpdateCallback,
+};
 use crate::types::{AgentToolCall, AgentToolResult, ModelMessage};
 
 use super::super::events::{RunEventPayload, RunEventStream, ToolUpdatePayload};
+use super::super::types::RunId;
 use super::control::{AgentEventEmitter, RunEventEmitter};
+use super::dry_run::{simulated_result, DryRunResults};
+use super::history::History;
 use super::message_events::emit_message_lifecycle;
-use super::{AgentEvent, PreToolUseHookResult, RunHooks};
+use super::{AgentEvent, HookContext, PreToolUseHookResult, RunHooks, ToolRetryPolicy};
 
 const TOOL_RESULT_SIZE_LIMIT_REASON: &str = "tool_result_size_limit_exceeded";
 const TOOL_RESULT_PREVIEW_MARKER: &str = "...<truncated>...";
@@ -53,16 +62,34 @@ pub(super) struct ToolExecutionInputs<'a> {
     session_cwd: Option<LogicalPath>,
     workspace_root: Option<PathBuf>,
     sandbox_provider: Option<Arc<dyn SandboxProvider>>,
+    plan_store: PlanStore,
+    change_log: ChangeLog,
+    artifacts: ArtifactStore,
+    scratch: ScratchDir,
+    memory: Option<Arc<dyn Memory>>,
+    run_id: RunId,
+    conversation: Option<Arc<[ModelMessage]>>,
+    message_queue: Option<&'a ToolMessageQueue>,
+    tool_retry: Option<&'a ToolRetryPolicy>,
+    deadline: Option<std::time::Instant>,
+    dry_run: Option<DryRunResults>,
     #[cfg(feature = "agent")]
     user_input_callback: Option<&'a crate::tools::user_input::RequestUserInputFn>,
 }
 
 impl<'a> ToolExecutionInputs<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         session_fs: Option<Arc<dyn SessionFs + Send + Sync>>,
         session_cwd: Option<LogicalPath>,
         workspace_root: Option<PathBuf>,
         sandbox_provider: Option<Arc<dyn SandboxProvider>>,
+        plan_store: PlanStore,
+        change_log: ChangeLog,
+        artifacts: ArtifactStore,
+        scratch: ScratchDir,
+        memory: Option<Arc<dyn Memory>>,
+        run_id: RunId,
         #[cfg(feature = "agent")] user_input_callback: Option<
             &'a crate::tools::user_input::RequestUserInputFn,
         >,
@@ -72,10 +99,74 @@ impl<'a> ToolExecutionInputs<'a> {
             session_cwd,
             workspace_root,
             sandbox_provider,
+            plan_store,
+            change_log,
+            artifacts,
+            scratch,
+            memory,
+            run_id,
+            conversation: None,
+            message_queue: None,
+            tool_retry: None,
+            deadline: None,
+            dry_run: None,
             #[cfg(feature = "agent")]
             user_input_callback,
         }
     }
+
+    /// Share the pre-batch conversation with tools and collect the messages
+    /// they emit into `message_queue`.
+    pub(super) fn with_conversation(
+        mut self,
+        conversation: Arc<[ModelMessage]>,
+        message_queue: &'a ToolMessageQueue,
+    ) -> Self {
+        self.conversation = Some(conversation);
+        self.message_queue = Some(message_queue);
+        self
+    }
+
+    /// Retry transient failures of read-only calls under `policy`.
+    pub(super) fn with_tool_retry(mut self, policy: Option<&'a ToolRetryPolicy>) -> Self {
+        self.tool_retry = policy;
+        self
+    }
+
+    /// Tell tools when the run must end.
+    pub(super) fn with_deadline(mut self, deadline: Option<std::time::Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Record `results` for every call instead of executing the tool.
+    pub(super) fn with_dry_run(mut self, results: Option<DryRunResults>) -> Self {
+        self.dry_run = results;
+        self
+    }
+
+    /// Ask `tool` what `call` would change, for the approval request.
+    ///
+    /// The preview context carries filesystem access only: no plan, change
+    /// log, or message sink, so a preview cannot record side effects.
+    pub(super) async fn change_previews(
+        &self,
+        tool: &dyn Tool,
+        call: &AgentToolCall,
+    ) -> Vec<ChangePreview> {
+        let ctx = crate::tools::tool::ToolExecutionContext {
+            tool_call_id: Some(call.id.clone()),
+            tool_name: Some(call.name.clone()),
+            session_fs: self.session_fs.clone(),
+            session_cwd: self.session_cwd.clone(),
+            workspace_root: self.workspace_root.clone(),
+            sandbox_provider: self.sandbox_provider.clone(),
+            scratch: Some(self.scratch.clone()),
+            ..Default::default()
+        };
+        tool.approval_preview(&ToolArguments::new(call.arguments.clone()), &ctx)
+            .await
+    }
 }
 
 pub(super) fn resolve_tool_call(tools: &[Arc<dyn Tool>], call: &AgentToolCall) -> ResolvedToolCall {
@@ -103,13 +194,21 @@ pub(super) fn resolve_tool_call(tools: &[Arc<dyn Tool>], call: &AgentToolCall) -
     }
 }
 
+/// The tool that `name` is an alias of, if it is not a tool's own name.
+pub(super) fn aliased_tool<'a>(
+    tools: &'a [Arc<dyn Tool>],
+    name: &str,
+) -> Option<&'a Arc<dyn Tool>> {
+    tools
+        .iter()
+        .find(|tool| name != tool.name() && tool.aliases().iter().any(|alias| alias == name))
+}
+
 pub(super) fn normalize_tool_call_alias<'a>(
     tools: &'a [Arc<dyn Tool>],
     call: &mut AgentToolCall,
 ) -> Option<&'a Arc<dyn Tool>> {
-    let tool = tools.iter().find(|tool| {
-        call.name != tool.name() && tool.aliases().iter().any(|alias| alias == &call.name)
-    })?;
+    let tool = aliased_tool(tools, &call.name)?;
     let called_as = call.name.clone();
     call.called_as.get_or_insert(called_as);
     call.name = tool.name().to_string();
@@ -140,17 +239,30 @@ pub(super) fn validate_finalized_tool_call(
         .map_err(|validation_error| validation_error_result(call, validation_error))
 }
 
+/// Safety plan of `call`, with relative filesystem paths anchored at
+/// `workspace_root` (where tools resolve them) so approval rules see the
+/// paths the call will touch.
 pub(super) fn safety_plan_for_finalized_call(
     call: &AgentToolCall,
     tool: Option<&dyn Tool>,
+    workspace_root: Option<&Path>,
 ) -> ToolSafetyPlan {
     let Some(tool) = tool else {
         return ToolSafetyPlan::default();
     };
     let safety_args = ToolArguments::new(call.arguments.clone());
-    let plan = tool.safety(&safety_args);
+    let mut plan = tool.safety(&safety_args);
     match plan.validate() {
-        Ok(()) => plan,
+        Ok(()) => {
+            if let Some(root) = workspace_root {
+                for access in &mut plan.filesystem {
+                    if access.path.is_relative() {
+                        access.path = root.join(&access.path);
+                    }
+                }
+            }
+            plan
+        }
         Err(error) => {
             tracing::warn!(
                 tool_name = tool.name(),
@@ -183,15 +295,48 @@ fn pre_tool_use_block_result(call: &AgentToolCall, reason: Option<String>) -> Ag
     synthetic_hook_error_result(call, "pre_tool_use", error)
 }
 
+/// Hook contexts for one tool batch, keyed by call id.
+pub(super) struct BatchHookContexts {
+    base: HookContext,
+    by_call: HashMap<String, HookContext>,
+}
+
+impl BatchHookContexts {
+    /// Give each call the invocation counts from before it, then add the
+    /// batch's calls to `tool_invocations`.
+    pub(super) fn new(
+        base: HookContext,
+        calls: &[AgentToolCall],
+        tool_invocations: &mut HashMap<String, usize>,
+    ) -> Self {
+        let mut by_call = HashMap::with_capacity(calls.len());
+        for call in calls {
+            by_call
+                .entry(call.id.clone())
+                .or_insert_with(|| HookContext {
+                    tool_invocations: tool_invocations.clone(),
+                    ..base.clone()
+                });
+            *tool_invocations.entry(call.name.clone()).or_default() += 1;
+        }
+        Self { base, by_call }
+    }
+
+    pub(super) fn for_call(&self, call: &AgentToolCall) -> HookContext {
+        self.by_call.get(&call.id).unwrap_or(&self.base).clone()
+    }
+}
+
 pub(super) async fn apply_pre_tool_use_hook(
     hooks: &RunHooks,
+    contexts: &BatchHookContexts,
     call: &AgentToolCall,
     cancel: CancellationToken,
 ) -> Result<AgentToolCall, AgentToolResult> {
     let Some(hook) = hooks.pre_tool_use.as_ref() else {
         return Ok(call.clone());
     };
-    match hook(call.clone(), cancel).await {
+    match hook(call.clone(), contexts.for_call(call), cancel).await {
         Ok(PreToolUseHookResult::Continue) => Ok(call.clone()),
         Ok(PreToolUseHookResult::Block { reason }) => Err(pre_tool_use_block_result(call, reason)),
         Ok(PreToolUseHookResult::ReplaceArgs { args }) => {
@@ -209,6 +354,7 @@ pub(super) async fn apply_pre_tool_use_hook(
 
 pub(super) async fn apply_post_tool_use_hook(
     hooks: &RunHooks,
+    contexts: &BatchHookContexts,
     call: &AgentToolCall,
     result: AgentToolResult,
 ) -> AgentToolResult {
@@ -216,7 +362,7 @@ pub(super) async fn apply_post_tool_use_hook(
         return result;
     };
     let original_result = result.clone();
-    match hook(call.clone(), result).await {
+    match hook(call.clone(), result, contexts.for_call(call)).await {
         Ok(next) => next,
         Err(err) => AgentToolResult {
             tool_call_id: original_result.tool_call_id.clone(),
@@ -255,11 +401,12 @@ pub(super) fn apply_result_size_policy(
 
 pub(super) async fn finalize_tool_result(
     hooks: &RunHooks,
+    contexts: &BatchHookContexts,
     call: &AgentToolCall,
     tool: Option<&dyn Tool>,
     result: AgentToolResult,
 ) -> AgentToolResult {
-    let result = apply_post_tool_use_hook(hooks, call, result).await;
+    let result = apply_post_tool_use_hook(hooks, contexts, call, result).await;
     apply_result_size_policy(call, tool, result)
 }
 
@@ -365,6 +512,16 @@ pub(super) async fn execute_tool_call(
     inputs: ToolExecutionInputs<'_>,
 ) -> ToolExecutionOutcome {
     let ResolvedToolCall { call, tool, .. } = resolved;
+    // A dry run never reaches `execute_ext`, whatever the tool does.
+    if let (Some(planned), Some(results)) = (tool.as_deref(), inputs.dry_run) {
+        let args = ToolArguments::new(call.arguments.clone());
+        let result = AgentToolResult {
+            tool_call_id: call.id.clone(),
+            result: simulated_result(planned, &args, results),
+            is_error: false,
+        };
+        return ToolExecutionOutcome { call, tool, result };
+    }
     match tool {
         Some(tool) => {
             let args = ToolArguments::new(call.arguments.clone());
@@ -376,9 +533,21 @@ pub(super) async fn execute_tool_call(
                 session_cwd: inputs.session_cwd,
                 workspace_root: inputs.workspace_root,
                 sandbox_provider: inputs.sandbox_provider,
+                plan: Some(inputs.plan_store.clone()),
+                changes: Some(inputs.change_log.clone()),
+                artifacts: Some(inputs.artifacts.clone()),
+                scratch: Some(inputs.scratch),
+                memory: inputs.memory,
+                run_id: Some(inputs.run_id),
+                conversation: inputs.conversation.clone(),
+                message_sink: inputs
+                    .message_queue
+                    .map(|queue| queue.sink(call.id.clone())),
+                deadline: inputs.deadline,
                 #[cfg(feature = "agent")]
                 request_user_input: inputs.user_input_callback.cloned(),
             };
+            let plan_revision = inputs.plan_store.revision();
             let call_id = call.id.clone();
             let call_name = call.name.clone();
             let call_args = call.arguments.clone();
@@ -392,7 +561,55 @@ pub(super) async fn execute_tool_call(
                         partial_result,
                     });
                 });
-            let result = match tool.execute_ext(&args, &ctx, cancel, Some(on_update)).await {
+            let retry = inputs.tool_retry.filter(|_| {
+                ToolEffects::for_call(tool.effects(), &tool.safety(&args)).is_read_only()
+            });
+            let mut attempt_errors = Vec::new();
+            let outcome = loop {
+                let error = match tool
+                    .execute_ext(&args, &ctx, cancel.clone(), Some(on_update.clone()))
+                    .await
+                {
+                    Ok(val) => break Ok(val),
+                    Err(error) => error,
+                };
+                let Some(policy) = retry else {
+                    break Err(error);
+                };
+                attempt_errors.push(error.to_string());
+                let attempts = attempt_errors.len() as u32;
+                let class = tool.classify_error(&error);
+                if !policy.retries(class, attempts) || cancel.is_cancelled() {
+                    break Err(error);
+                }
+                let delay = policy.backoff.delay_for_retry(attempts);
+                // A retry that wakes after the deadline would only be canceled;
+                // hand the model this error instead.
+                if inputs
+                    .deadline
+                    .is_some_and(|deadline| std::time::Instant::now() + delay >= deadline)
+                {
+                    break Err(error);
+                }
+                on_update(ToolUpdatePayload {
+                    content: Vec::new(),
+                    details: serde_json::json!({
+                        "retry": {
+                            "attempt": attempts + 1,
+                            "max_attempts": policy.max_attempts,
+                            "error_class": class,
+                            "error": error.to_string(),
+                            "delay_ms": delay.as_millis() as u64,
+                        }
+                    }),
+                });
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break Err(error),
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            };
+            let result = match outcome {
                 Ok(val) => AgentToolResult {
                     tool_call_id: call.id.clone(),
                     result: val,
@@ -400,10 +617,17 @@ pub(super) async fn execute_tool_call(
                 },
                 Err(error) => AgentToolResult {
                     tool_call_id: call.id.clone(),
-                    result: serde_json::json!({ "error": error.to_string() }),
+                    result: tool_error_value(&error, attempt_errors),
                     is_error: true,
                 },
             };
+            // Emitted before the caller's ToolExecutionEnd so UIs render the
+            // new plan as part of the tool call that produced it.
+            if inputs.plan_store.revision() != plan_revision {
+                agent_emitter.emit(AgentEvent::PlanUpdate {
+                    steps: inputs.plan_store.steps(),
+                });
+            }
             ToolExecutionOutcome {
                 call,
                 tool: Some(tool),
@@ -422,6 +646,19 @@ pub(super) async fn execute_tool_call(
     }
 }
 
+/// Error payload for a failed call; calls that were retried also report
+/// every attempt's error, oldest first.
+fn tool_error_value(error: &RociError, attempt_errors: Vec<String>) -> serde_json::Value {
+    if attempt_errors.len() < 2 {
+        return serde_json::json!({ "error": error.to_string() });
+    }
+    serde_json::json!({
+        "error": error.to_string(),
+        "attempts": attempt_errors.len(),
+        "attempt_errors": attempt_errors,
+    })
+}
+
 pub(super) async fn execute_parallel_tool_calls(
     calls: &[ResolvedToolCall],
     agent_emitter: &AgentEventEmitter,
@@ -446,19 +683,48 @@ pub(super) async fn execute_parallel_tool_calls(
 pub(super) fn append_tool_result(
     emitter: &RunEventEmitter,
     agent_emitter: &AgentEventEmitter,
+    artifacts: &ArtifactStore,
     call: &AgentToolCall,
     result: AgentToolResult,
     iteration_failures: &mut usize,
-    messages: &mut Vec<ModelMessage>,
+    messages: &mut History,
 ) -> AgentToolResult {
-    append_final_tool_result(
+    let result = append_final_tool_result(
         emitter,
         agent_emitter,
         call,
         result,
         iteration_failures,
         messages,
-    )
+    );
+    emit_artifacts_added(emitter, artifacts.take_unannounced_for(&call.id));
+    result
+}
+
+/// Announce artifacts registered by tool calls; the runner emits them after
+/// the owning call's result so parallel batches stay in result order.
+pub(super) fn emit_artifacts_added(
+    emitter: &RunEventEmitter,
+    artifacts: Vec<crate::tools::RunArtifact>,
+) {
+    for artifact in artifacts {
+        emitter.emit(
+            RunEventStream::Tool,
+            RunEventPayload::ArtifactAdded { artifact },
+        );
+    }
+}
+
+pub(super) fn append_emitted_messages(
+    agent_emitter: &AgentEventEmitter,
+    message_queue: &ToolMessageQueue,
+    calls: &[AgentToolCall],
+    messages: &mut History,
+) {
+    for message in message_queue.drain_in_call_order(calls.iter().map(|call| call.id.as_str())) {
+        emit_message_lifecycle(agent_emitter, &message);
+        messages.push(message);
+    }
 }
 
 fn append_final_tool_result(
@@ -467,7 +733,7 @@ fn append_final_tool_result(
     call: &AgentToolCall,
     result: AgentToolResult,
     iteration_failures: &mut usize,
-    messages: &mut Vec<ModelMessage>,
+    messages: &mut History,
 ) -> AgentToolResult {
     if result.is_error {
         *iteration_failures = iteration_failures.saturating_add(1);
@@ -477,6 +743,8 @@ fn append_final_tool_result(
         RunEventStream::Tool,
         RunEventPayload::ToolResult {
             result: result.clone(),
+            repaired: emitter.arguments_repaired(&call.id),
+            dry_run: emitter.dry_run(),
         },
     );
     emitter.emit(
@@ -484,24 +752,29 @@ fn append_final_tool_result(
         RunEventPayload::ToolCallCompleted { call: call.clone() },
     );
 
-    let tool_result_message = ModelMessage::tool_result(
-        result.tool_call_id.clone(),
-        result.result.clone(),
-        result.is_error,
-    );
+    let tool_result_message =
+        agent_emitter
+            .stored_messages()
+            .tool_result(ModelMessage::tool_result(
+                result.tool_call_id.clone(),
+                result.result.clone(),
+                result.is_error,
+            ));
     emit_message_lifecycle(agent_emitter, &tool_result_message);
     messages.push(tool_result_message);
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn append_skipped_tool_call(
     hooks: &RunHooks,
+    contexts: &BatchHookContexts,
     emitter: &RunEventEmitter,
     agent_emitter: &AgentEventEmitter,
     call: &AgentToolCall,
     tool: Option<&dyn Tool>,
     iteration_failures: &mut usize,
-    messages: &mut Vec<ModelMessage>,
+    messages: &mut History,
 ) -> AgentToolResult {
     let skipped_result = AgentToolResult {
         tool_call_id: call.id.clone(),
@@ -509,7 +782,7 @@ pub(super) async fn append_skipped_tool_call(
         is_error: true,
     };
     emit_tool_execution_start(agent_emitter, call);
-    let skipped_result = finalize_tool_result(hooks, call, tool, skipped_result).await;
+    let skipped_result = finalize_tool_result(hooks, contexts, call, tool, skipped_result).await;
     emit_tool_execution_end(agent_emitter, call, &skipped_result);
     append_final_tool_result(
         emitter,
@@ -545,8 +818,33 @@ mod tests {
             recipient: None,
         };
 
-        let plan = safety_plan_for_finalized_call(&call, Some(&tool));
+        let plan = safety_plan_for_finalized_call(&call, Some(&tool), None);
 
         assert_eq!(plan, ToolSafetyPlan::default());
     }
+
+    #[test]
+    fn relative_plan_paths_are_anchored_at_the_workspace_root() {
+        let tool = AgentTool::new(
+            "read_file",
+            "read plan fixture",
+            AgentToolParameters::empty(),
+            |_args, _ctx| async { Ok(serde_json::json!({"ok": true})) },
+        )
+        .with_static_safety(ToolSafetyPlan::file_read("src/lib.rs"), Default::default());
+        let call = AgentToolCall {
+            id: "call-1".to_string(),
+            name: "read_file".to_string(),
+            arguments: serde_json::json!({}),
+            called_as: None,
+            recipient: None,
+        };
+        let root = Path::new("/srv/repo");
+
+        let anchored = safety_plan_for_finalized_call(&call, Some(&tool), Some(root));
+        let unanchored = safety_plan_for_finalized_call(&call, Some(&tool), None);
+
+        assert_eq!(anchored.filesystem[0].path, root.join("src/lib.rs"));
+        assert_eq!(unanchored.filesystem[0].path, PathBuf::from("src/lib.rs"));
+    }
 }
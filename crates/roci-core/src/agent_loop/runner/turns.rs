@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::Instant;
+
+use super::super::types::RunId;
+use super::AgentEvent;
+use crate::agent_loop::TurnSummary;
+use crate::types::{AgentToolResult, ModelMessage, Usage};
+
+/// Pairs [`AgentEvent::TurnStart`] with exactly one [`AgentEvent::TurnEnd`].
+///
+/// A turn opens before the model call and closes after its tool batch, or
+/// is aborted when the run ends first. Cloning is cheap; clones share the
+/// open turn.
+#[derive(Clone, Default)]
+pub(super) struct TurnTracker {
+    state: Arc<Mutex<TurnState>>,
+}
+
+#[derive(Default)]
+struct TurnState {
+    last_index: usize,
+    open: Option<OpenTurn>,
+}
+
+struct OpenTurn {
+    index: usize,
+    started_at: Instant,
+    usage_at_start: Usage,
+    usage: Usage,
+    text_chars: usize,
+    tool_calls: usize,
+    assistant_message: Option<ModelMessage>,
+    tool_results: Vec<AgentToolResult>,
+}
+
+impl OpenTurn {
+    fn end(self, run_id: RunId, aborted: bool) -> AgentEvent {
+        let summary = TurnSummary {
+            text_chars: self.text_chars,
+            tool_calls: self.tool_calls,
+            tool_failures: self
+                .tool_results
+                .iter()
+                .filter(|result| result.is_error)
+                .count(),
+            usage: usage_since(&self.usage_at_start, &self.usage),
+            duration_ms: self.started_at.elapsed().as_millis() as u64,
+        };
+        AgentEvent::TurnEnd {
+            run_id,
+            turn_index: self.index,
+            assistant_message: self.assistant_message,
+            tool_results: self.tool_results,
+            summary,
+            aborted,
+        }
+    }
+}
+
+impl TurnTracker {
+    /// Open the next turn; `None` when a turn is already open, as when the
+    /// response is retried on a fallback model.
+    pub(super) fn begin(&self, run_id: RunId, run_usage: &Usage) -> Option<AgentEvent> {
+        let mut state = self.lock();
+        if state.open.is_some() {
+            return None;
+        }
+        state.last_index += 1;
+        let index = state.last_index;
+        state.open = Some(OpenTurn {
+            index,
+            started_at: Instant::now(),
+            usage_at_start: run_usage.clone(),
+            usage: run_usage.clone(),
+            text_chars: 0,
+            tool_calls: 0,
+            assistant_message: None,
+            tool_results: Vec::new(),
+        });
+        Some(AgentEvent::TurnStart {
+            run_id,
+            turn_index: index,
+        })
+    }
+
+    /// Index of the open turn, or of the next one when none is open.
+    pub(super) fn current_index(&self) -> usize {
+        let state = self.lock();
+        match state.open.as_ref() {
+            Some(turn) => turn.index,
+            None => state.last_index + 1,
+        }
+    }
+
+    /// Count a model response against the open turn.
+    pub(super) fn record_response(&self, text: &str, tool_calls: usize, run_usage: &Usage) {
+        if let Some(turn) = self.lock().open.as_mut() {
+            turn.text_chars += text.chars().count();
+            turn.tool_calls += tool_calls;
+            turn.usage = run_usage.clone();
+        }
+    }
+
+    /// Keep what the tool batch produced so far, for an aborted end.
+    pub(super) fn record_progress(
+        &self,
+        assistant_message: Option<&ModelMessage>,
+        tool_results: &[AgentToolResult],
+    ) {
+        if let Some(turn) = self.lock().open.as_mut() {
+            turn.assistant_message = assistant_message.cloned();
+            turn.tool_results = tool_results.to_vec();
+        }
+    }
+
+    /// Close the open turn after its tool batch.
+    pub(super) fn end(
+        &self,
+        run_id: RunId,
+        assistant_message: Option<ModelMessage>,
+        tool_results: Vec<AgentToolResult>,
+    ) -> Option<AgentEvent> {
+        let mut turn = self.lock().open.take()?;
+        turn.assistant_message = assistant_message;
+        turn.tool_results = tool_results;
+        Some(turn.end(run_id, false))
+    }
+
+    /// Close the open turn, if any, because the run is ending.
+    pub(super) fn abort(&self, run_id: RunId, run_usage: &Usage) -> Option<AgentEvent> {
+        let mut turn = self.lock().open.take()?;
+        turn.usage = run_usage.clone();
+        Some(turn.end(run_id, true))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TurnState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn usage_since(start: &Usage, now: &Usage) -> Usage {
+    let since = |start: Option<u32>, now: Option<u32>| {
+        now.map(|now| now.saturating_sub(start.unwrap_or(0)))
+    };
+    Usage {
+        input_tokens: now.input_tokens.saturating_sub(start.input_tokens),
+        output_tokens: now.output_tokens.saturating_sub(start.output_tokens),
+        total_tokens: now.total_tokens.saturating_sub(start.total_tokens),
+        cache_read_tokens: since(start.cache_read_tokens, now.cache_read_tokens),
+        cache_creation_tokens: since(start.cache_creation_tokens, now.cache_creation_tokens),
+        reasoning_tokens: since(start.reasoning_tokens, now.reasoning_tokens),
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use super::control::RunEventEmitter;
+use super::heartbeat::{with_heartbeat, Heartbeat};
+use super::{RunEventPayload, RunEventStream};
+use crate::agent_loop::HeartbeatPhase;
+use crate::provider::ModelProvider;
+
+/// Warm up `provider` for [`RunRequest::warm_up`](super::RunRequest::warm_up).
+///
+/// Emits an initial [`HeartbeatPhase::WarmingUp`] heartbeat so hosts can show
+/// that the model is loading, then beats every `heartbeat_interval` until the
+/// load finishes. Failures are logged; the first request may still succeed.
+pub(super) async fn warm_up_provider(
+    provider: &dyn ModelProvider,
+    emitter: &RunEventEmitter,
+    heartbeat_interval: Option<Duration>,
+) {
+    if !provider.supports_warm_up() {
+        return;
+    }
+    emitter.emit(
+        RunEventStream::System,
+        RunEventPayload::Heartbeat {
+            phase: HeartbeatPhase::WarmingUp,
+            elapsed_ms: 0,
+        },
+    );
+    let mut heartbeat = Heartbeat::start(emitter, heartbeat_interval, HeartbeatPhase::WarmingUp);
+    match with_heartbeat(heartbeat.as_mut(), provider.warm_up()).await {
+        Ok(report) => tracing::debug!(
+            provider = provider.provider_name(),
+            model = provider.model_id(),
+            already_loaded = report.already_loaded,
+            load_ms = report
+                .load_duration
+                .map(|duration| duration.as_millis() as u64),
+            "model warm-up finished"
+        ),
+        Err(error) => tracing::warn!(
+            provider = provider.provider_name(),
+            model = provider.model_id(),
+            %error,
+            "model warm-up failed; continuing without it"
+        ),
+    }
+}
@@ -0,0 +1,225 @@
+//! JSONL run transcripts for replay with
+//! [`StateReconstructor`](super::replay::StateReconstructor).
+//!
+//! A run with [`RunRequest::transcript`] set writes one [`TranscriptRecord`]
+//! per line: a header, a marker at the top of each iteration, a summary of
+//! every provider request, every run and agent event as it is emitted, and
+//! the terminal status. Recording happens before events reach the sink queue,
+//! so a slow or overflowing sink never leaves holes in the transcript.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::events::{AgentEventEnvelope, RunEvent};
+use super::runner::{AgentEventSink, RunEventSink, RunRequest};
+use super::types::{RunId, RunResult, RunStatus};
+use crate::error::RociError;
+use crate::models::LanguageModel;
+use crate::provider::{ProviderRequest, ToolDefinition};
+use crate::types::{GenerationSettings, ModelMessage};
+
+/// Version of the [`TranscriptRecord`] format, bumped on breaking changes.
+pub const TRANSCRIPT_SCHEMA_VERSION: u32 = 1;
+
+/// One line of a run transcript. Request and event payloads are boxed so
+/// the other records stay small.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum TranscriptRecord {
+    RunStarted {
+        schema_version: u32,
+        run_id: RunId,
+        /// Model the run started with (`provider:model`).
+        model: String,
+        started_at: DateTime<Utc>,
+    },
+    /// Start of an iteration; later records belong to it until the next one.
+    Iteration {
+        iteration: usize,
+    },
+    ProviderRequest(Box<ProviderRequestSummary>),
+    RunEvent(Box<RunEvent>),
+    AgentEvent(Box<AgentEventEnvelope>),
+    RunFinished {
+        status: RunStatus,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        finished_at: DateTime<Utc>,
+    },
+}
+
+/// What was sent to the provider for one call.
+///
+/// Messages are stored as a delta: the first `base` messages equal those of
+/// the run's previous provider request, and `messages` holds the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderRequestSummary {
+    pub iteration: usize,
+    /// Model the request went to (`provider:model`).
+    pub model: String,
+    pub base: usize,
+    pub messages: Vec<ModelMessage>,
+    /// Tool definitions advertised with the request.
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    pub settings: GenerationSettings,
+}
+
+/// Appends a run's [`TranscriptRecord`]s to a JSONL file.
+///
+/// Cheap to clone; clones write to the same file. Use one writer per run.
+/// Each record is flushed as it is written. A write error is logged once and
+/// ends recording without affecting the run.
+#[derive(Clone)]
+pub struct TranscriptWriter {
+    inner: Arc<Mutex<TranscriptFile>>,
+}
+
+struct TranscriptFile {
+    path: PathBuf,
+    out: Option<BufWriter<File>>,
+    /// Messages of the last provider request, the base of the next delta.
+    previous_messages: Option<Arc<Vec<ModelMessage>>>,
+}
+
+impl std::fmt::Debug for TranscriptWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self
+            .inner
+            .lock()
+            .map(|file| file.path.clone())
+            .unwrap_or_default();
+        f.debug_struct("TranscriptWriter")
+            .field("path", &path)
+            .finish()
+    }
+}
+
+impl TranscriptWriter {
+    /// Create (or truncate) the transcript file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Io`] when the file cannot be created.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, RociError> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(TranscriptFile {
+                path,
+                out: Some(BufWriter::new(file)),
+                previous_messages: None,
+            })),
+        })
+    }
+
+    /// Path of the transcript file.
+    pub fn path(&self) -> PathBuf {
+        self.inner
+            .lock()
+            .map(|file| file.path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Write the header and route the request's event sinks through the
+    /// transcript. Called once the sinks are final.
+    pub(crate) fn attach(&self, request: &mut RunRequest) {
+        self.record(&TranscriptRecord::RunStarted {
+            schema_version: TRANSCRIPT_SCHEMA_VERSION,
+            run_id: request.run_id,
+            model: request.active_model().to_string(),
+            started_at: Utc::now(),
+        });
+        let run_sink = request.event_sink.take();
+        let writer = self.clone();
+        request.event_sink = Some(Arc::new(move |event: RunEvent| {
+            writer.record(&TranscriptRecord::RunEvent(Box::new(event.clone())));
+            if let Some(sink) = &run_sink {
+                sink(event);
+            }
+        }) as RunEventSink);
+        let agent_sink = request.agent_event_sink.take();
+        let writer = self.clone();
+        request.agent_event_sink = Some(Arc::new(move |envelope: AgentEventEnvelope| {
+            writer.record(&TranscriptRecord::AgentEvent(Box::new(envelope.clone())));
+            if let Some(sink) = &agent_sink {
+                sink(envelope);
+            }
+        }) as AgentEventSink);
+    }
+
+    pub(crate) fn record_iteration(&self, iteration: usize) {
+        self.record(&TranscriptRecord::Iteration { iteration });
+    }
+
+    pub(crate) fn record_request(
+        &self,
+        iteration: usize,
+        model: &LanguageModel,
+        request: &ProviderRequest,
+    ) {
+        let Ok(mut file) = self.inner.lock() else {
+            return;
+        };
+        let base = file
+            .previous_messages
+            .as_ref()
+            .map(|previous| {
+                previous
+                    .iter()
+                    .zip(request.messages.iter())
+                    .take_while(|(previous, next)| previous == next)
+                    .count()
+            })
+            .unwrap_or_default();
+        let summary = ProviderRequestSummary {
+            iteration,
+            model: model.to_string(),
+            base,
+            messages: request.messages[base..].to_vec(),
+            tools: request.tools.clone().unwrap_or_default(),
+            settings: request.settings.clone(),
+        };
+        file.previous_messages = Some(request.messages.clone());
+        file.write(&TranscriptRecord::ProviderRequest(Box::new(summary)));
+    }
+
+    pub(crate) fn record_finish(&self, result: &RunResult) {
+        self.record(&TranscriptRecord::RunFinished {
+            status: result.status,
+            error: result.error.clone(),
+            finished_at: result.finished_at,
+        });
+    }
+
+    fn record(&self, record: &TranscriptRecord) {
+        if let Ok(mut file) = self.inner.lock() {
+            file.write(record);
+        }
+    }
+}
+
+impl TranscriptFile {
+    fn write(&mut self, record: &TranscriptRecord) {
+        let Some(out) = self.out.as_mut() else {
+            return;
+        };
+        let written = serde_json::to_writer(&mut *out, record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| out.write_all(b"\n"))
+            .and_then(|()| out.flush());
+        if let Err(err) = written {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %err,
+                "roci transcript write failed; recording stopped"
+            );
+            self.out = None;
+        }
+    }
+}
@@ -1,10 +1,18 @@
 //! Core run types for the agent loop.
 
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::types::{ModelMessage, Usage};
+use crate::models::LanguageModel;
+use crate::tools::artifacts::RunArtifact;
+use crate::tools::changes::FileChange;
+use crate::tools::plan::PlanStep;
+use crate::types::{ModelMessage, PayloadSizes, ResponseMetadata, Usage};
+
+use super::webhook::WebhookDelivery;
 
 /// Unique run identifier.
 pub type RunId = Uuid;
@@ -13,10 +21,18 @@ pub type RunId = Uuid;
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum RunStatus {
+    /// Waiting for a slot under the runner's
+    /// [`RunnerOptions`](super::RunnerOptions).
+    Queued,
     Running,
     Completed,
     Failed,
     Canceled,
+    /// Stopped by a [`RunBudget`](super::RunBudget) limit; `error` names the limit.
+    BudgetExceeded,
+    /// Stopped at [`RunRequest::deadline`](super::RunRequest::deadline);
+    /// [`RunResult::has_final_text`] tells whether an answer was reached.
+    DeadlineExceeded,
 }
 
 /// Result of a run.
@@ -38,6 +54,85 @@ pub struct RunResult {
     /// `None` for pre-provider failures and zero-usage cases.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub usage_delta: Option<Usage>,
+    /// Final plan maintained through plan tools during the run.
+    ///
+    /// Empty when no plan was recorded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plan: Vec<PlanStep>,
+    /// Net file changes recorded by tools during the run.
+    ///
+    /// Empty when no tool changed a file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FileChange>,
+    /// Outputs registered by tools during the run, in registration order.
+    ///
+    /// Empty when no tool registered an artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<RunArtifact>,
+    /// Event delivery counters; `None` when the run had no event sinks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emitter_stats: Option<EmitterStats>,
+    /// Candidate active when the run ended: the model that answered, unless
+    /// the run failed first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<LanguageModel>,
+    /// Final answer parsed and validated against
+    /// [`RunRequest::final_response_schema`](super::RunRequest::final_response_schema).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_output: Option<serde_json::Value>,
+    /// Request id and rate limits from the last provider response of the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_metadata: Option<ResponseMetadata>,
+    /// Request and response sizes of each provider call that reported them,
+    /// in call order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payload_sizes: Vec<PayloadSizes>,
+    /// Scratch directory left in place by
+    /// [`RunRequest::keep_scratch`](super::RunRequest::keep_scratch); `None`
+    /// when it was removed or never created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scratch_dir: Option<PathBuf>,
+    /// Outcome of each [`RunRequest::webhooks`](super::RunRequest::webhooks)
+    /// delivery, in request order. Unsubscribed webhooks are omitted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhook_deliveries: Vec<WebhookDelivery>,
+    /// Outcome of each [`RunRequest::task_list`](super::RunRequest::task_list)
+    /// task that started, in list order. Empty outside task-list mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub task_results: Vec<TaskResult>,
+    /// The messages end with an assistant reply that has text and no tool
+    /// calls. Set only on [`RunStatus::DeadlineExceeded`] results.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_final_text: bool,
+}
+
+/// Outcome of one task of a [`RunRequest::task_list`](super::RunRequest::task_list) run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    /// [`TaskSpec::id`](super::TaskSpec::id) of the task.
+    pub id: String,
+    pub status: RunStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Messages the task added: its prompt, then every reply and tool result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<ModelMessage>,
+    /// Token usage of the task alone; `None` when it accrued none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// How a run's events fared on the way to its sinks.
+///
+/// `emitted + dropped + coalesced` is the number of events the run produced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmitterStats {
+    /// Events handed to a sink.
+    pub emitted: u64,
+    /// Events discarded because the sink queue was full.
+    pub dropped: u64,
+    /// Deltas merged into a queued delta because the sink queue was full.
+    pub coalesced: u64,
 }
 
 impl RunResult {
@@ -52,6 +147,18 @@ impl RunResult {
             messages,
             finished_at: Utc::now(),
             usage_delta: None,
+            plan: Vec::new(),
+            changes: Vec::new(),
+            artifacts: Vec::new(),
+            emitter_stats: None,
+            model: None,
+            structured_output: None,
+            response_metadata: None,
+            payload_sizes: Vec::new(),
+            scratch_dir: None,
+            webhook_deliveries: Vec::new(),
+            task_results: Vec::new(),
+            has_final_text: false,
         }
     }
 
@@ -66,6 +173,18 @@ impl RunResult {
             messages,
             finished_at: Utc::now(),
             usage_delta: None,
+            plan: Vec::new(),
+            changes: Vec::new(),
+            artifacts: Vec::new(),
+            emitter_stats: None,
+            model: None,
+            structured_output: None,
+            response_metadata: None,
+            payload_sizes: Vec::new(),
+            scratch_dir: None,
+            webhook_deliveries: Vec::new(),
+            task_results: Vec::new(),
+            has_final_text: false,
         }
     }
 
@@ -80,6 +199,40 @@ impl RunResult {
             messages,
             finished_at: Utc::now(),
             usage_delta: None,
+            plan: Vec::new(),
+            changes: Vec::new(),
+            artifacts: Vec::new(),
+            emitter_stats: None,
+            model: None,
+            structured_output: None,
+            response_metadata: None,
+            payload_sizes: Vec::new(),
+            scratch_dir: None,
+            webhook_deliveries: Vec::new(),
+            task_results: Vec::new(),
+            has_final_text: false,
+        }
+    }
+
+    pub fn budget_exceeded_with_messages(
+        error: impl Into<String>,
+        messages: Vec<ModelMessage>,
+    ) -> Self {
+        Self {
+            status: RunStatus::BudgetExceeded,
+            ..Self::failed_with_messages(error, messages)
+        }
+    }
+
+    pub fn deadline_exceeded_with_messages(
+        error: impl Into<String>,
+        messages: Vec<ModelMessage>,
+        has_final_text: bool,
+    ) -> Self {
+        Self {
+            status: RunStatus::DeadlineExceeded,
+            has_final_text,
+            ..Self::failed_with_messages(error, messages)
         }
     }
 
@@ -94,4 +247,70 @@ impl RunResult {
         }
         self
     }
+
+    /// Attach the final plan recorded during the run.
+    pub fn with_plan(mut self, plan: Vec<PlanStep>) -> Self {
+        self.plan = plan;
+        self
+    }
+
+    /// Attach the net file changes recorded during the run.
+    pub fn with_changes(mut self, changes: Vec<FileChange>) -> Self {
+        self.changes = changes;
+        self
+    }
+
+    /// Attach the artifacts registered during the run.
+    pub fn with_artifacts(mut self, artifacts: Vec<RunArtifact>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Record the model active when the run ended.
+    pub fn with_model(mut self, model: LanguageModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Attach the validated structured final answer.
+    pub fn with_structured_output(mut self, output: serde_json::Value) -> Self {
+        self.structured_output = Some(output);
+        self
+    }
+
+    /// Record the last provider response metadata seen, if any.
+    pub fn with_response_metadata(mut self, metadata: Option<ResponseMetadata>) -> Self {
+        self.response_metadata = metadata;
+        self
+    }
+
+    /// Attach the payload sizes of the run's provider calls.
+    pub fn with_payload_sizes(mut self, sizes: Vec<PayloadSizes>) -> Self {
+        self.payload_sizes = sizes;
+        self
+    }
+
+    /// Report the scratch directory kept after the run.
+    pub fn with_scratch_dir(mut self, path: PathBuf) -> Self {
+        self.scratch_dir = Some(path);
+        self
+    }
+
+    /// Record how the run summary webhooks fared.
+    pub fn with_webhook_deliveries(mut self, deliveries: Vec<WebhookDelivery>) -> Self {
+        self.webhook_deliveries = deliveries;
+        self
+    }
+
+    /// Attach the per-task outcomes of a task-list run.
+    pub fn with_task_results(mut self, results: Vec<TaskResult>) -> Self {
+        self.task_results = results;
+        self
+    }
+
+    /// Attach event delivery counters.
+    pub fn with_emitter_stats(mut self, stats: EmitterStats) -> Self {
+        self.emitter_stats = Some(stats);
+        self
+    }
 }
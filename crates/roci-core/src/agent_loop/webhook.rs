@@ -0,0 +1,305 @@
+//! Run summaries POSTed to webhooks when a run reaches a terminal state.
+//!
+//! Delivery happens after the run has ended and before its result reaches the
+//! [`RunHandle`](super::RunHandle): failures are retried on 5xx, 429, and
+//! transport errors, then recorded in [`RunResult::webhook_deliveries`]. They
+//! never change the run's status.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::runner::RunRequest;
+use super::types::{RunId, RunResult, RunStatus};
+use crate::models::PricingTable;
+use crate::provider::http::shared_client;
+use crate::types::Usage;
+use crate::util::hmac::{hex, hmac_sha256};
+
+/// Version of the [`RunSummary`] document, bumped on breaking changes.
+pub const RUN_SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>` when the webhook
+/// has a secret. See [`webhook_signature`].
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-roci-signature";
+
+/// Where and when to POST a [`RunSummary`].
+#[derive(Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Extra headers sent with every attempt.
+    pub headers: HeaderMap,
+    /// Terminal statuses that trigger delivery; empty means every one.
+    pub events: Vec<RunStatus>,
+    /// Shared secret for [`WEBHOOK_SIGNATURE_HEADER`]. `None` sends unsigned.
+    pub secret: Option<String>,
+    /// Limit for each attempt. Defaults to 10 seconds.
+    pub timeout: Duration,
+    /// Attempts including the first. Defaults to 3.
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each later one. Defaults to
+    /// 500 ms.
+    pub retry_delay: Duration,
+}
+
+impl WebhookConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: HeaderMap::new(),
+            events: Vec::new(),
+            secret: None,
+            timeout: Duration::from_secs(10),
+            max_attempts: 3,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn with_events(mut self, events: Vec<RunStatus>) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    fn subscribes(&self, status: RunStatus) -> bool {
+        self.events.is_empty() || self.events.contains(&status)
+    }
+}
+
+impl std::fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field("url", &self.url)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("events", &self.events)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("timeout", &self.timeout)
+            .field("max_attempts", &self.max_attempts)
+            .field("retry_delay", &self.retry_delay)
+            .finish()
+    }
+}
+
+/// Versioned, machine-readable summary of a finished run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// [`RUN_SUMMARY_SCHEMA_VERSION`] at the time of sending.
+    pub schema_version: u32,
+    pub run_id: RunId,
+    pub status: RunStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Model active when the run ended, as `provider:model`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Usage priced with the run budget's table, or the default one. `None`
+    /// when the model has no pricing or the run reported no usage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// The run's [`RunRequest::event_tags`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Outcome of delivering a [`RunSummary`] to one webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub delivered: bool,
+    pub attempts: u32,
+    /// HTTP status of the last response, if one arrived.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Why the last attempt failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Value of [`WEBHOOK_SIGNATURE_HEADER`] for `body` signed with `secret`.
+///
+/// Receivers recompute it over the raw request body and compare.
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hex(&hmac_sha256(secret.as_bytes(), body)))
+}
+
+/// A run's webhooks plus what its summary needs from the request.
+pub(crate) struct RunWebhooks {
+    webhooks: Vec<WebhookConfig>,
+    run_id: RunId,
+    started_at: DateTime<Utc>,
+    tags: BTreeMap<String, String>,
+    pricing: PricingTable,
+}
+
+impl RunWebhooks {
+    /// `None` when the request has no webhooks.
+    pub(crate) fn from_request(request: &RunRequest) -> Option<Self> {
+        if request.webhooks.is_empty() {
+            return None;
+        }
+        Some(Self {
+            webhooks: request.webhooks.clone(),
+            run_id: request.run_id,
+            started_at: Utc::now(),
+            tags: request
+                .event_tags
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            pricing: request
+                .budget
+                .as_ref()
+                .map(|budget| budget.pricing.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    fn summary(&self, result: &RunResult) -> RunSummary {
+        let usage = result.usage_delta.clone();
+        let cost_usd = result
+            .model
+            .as_ref()
+            .and_then(|model| self.pricing.lookup(model))
+            .zip(usage.as_ref())
+            .map(|(pricing, usage)| pricing.cost_usd(usage));
+        RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            run_id: self.run_id,
+            status: result.status,
+            error: result.error.clone(),
+            model: result.model.as_ref().map(ToString::to_string),
+            started_at: self.started_at,
+            finished_at: result.finished_at,
+            duration_ms: (result.finished_at - self.started_at)
+                .num_milliseconds()
+                .max(0) as u64,
+            usage,
+            cost_usd,
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// POST the summary of `result` to every webhook subscribed to its status.
+    pub(crate) async fn deliver(&self, result: &RunResult) -> Vec<WebhookDelivery> {
+        let subscribed = self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.subscribes(result.status))
+            .collect::<Vec<_>>();
+        if subscribed.is_empty() {
+            return Vec::new();
+        }
+        let body = match serde_json::to_vec(&self.summary(result)) {
+            Ok(body) => body,
+            Err(error) => {
+                return subscribed
+                    .into_iter()
+                    .map(|webhook| WebhookDelivery {
+                        url: webhook.url.clone(),
+                        delivered: false,
+                        attempts: 0,
+                        status_code: None,
+                        error: Some(format!("failed to serialize run summary: {error}")),
+                    })
+                    .collect();
+            }
+        };
+        futures::future::join_all(
+            subscribed
+                .into_iter()
+                .map(|webhook| deliver_one(webhook, &body)),
+        )
+        .await
+    }
+}
+
+async fn deliver_one(webhook: &WebhookConfig, body: &[u8]) -> WebhookDelivery {
+    let signature = webhook
+        .secret
+        .as_deref()
+        .map(|secret| webhook_signature(secret, body));
+    let max_attempts = webhook.max_attempts.max(1);
+    let mut delay = webhook.retry_delay;
+    let mut delivery = WebhookDelivery {
+        url: webhook.url.clone(),
+        delivered: false,
+        attempts: 0,
+        status_code: None,
+        error: None,
+    };
+    while delivery.attempts < max_attempts {
+        if delivery.attempts > 0 {
+            tokio::time::sleep(delay).await;
+            delay = delay.saturating_mul(2);
+        }
+        delivery.attempts += 1;
+        let mut request = shared_client()
+            .post(&webhook.url)
+            .timeout(webhook.timeout)
+            .headers(webhook.headers.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(signature) = signature.as_deref() {
+            request = request.header(WEBHOOK_SIGNATURE_HEADER, signature);
+        }
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                delivery.status_code = Some(status.as_u16());
+                if status.is_success() {
+                    delivery.delivered = true;
+                    delivery.error = None;
+                    return delivery;
+                }
+                delivery.error = Some(format!("HTTP {status}"));
+                if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                    break;
+                }
+            }
+            Err(error) => {
+                delivery.status_code = None;
+                delivery.error = Some(error.to_string());
+            }
+        }
+    }
+    tracing::warn!(
+        url = %webhook.url,
+        attempts = delivery.attempts,
+        error = delivery.error.as_deref().unwrap_or_default(),
+        "run summary webhook delivery failed"
+    );
+    delivery
+}
@@ -98,6 +98,7 @@ pub fn compile_prompt_input(
 
     let metadata = (!attachments.is_empty()).then(|| ModelMessageMetadata {
         attachments: attachments.clone(),
+        origin: None,
     });
 
     Ok(CompiledPromptInput {
@@ -0,0 +1,360 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::context::estimate_text_tokens;
+
+/// Bytes inspected for NUL when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+/// Matches the ~4 chars/token heuristic behind [`estimate_text_tokens`].
+const CHARS_PER_TOKEN: usize = 4;
+/// Room reserved for the `... [lines a-b of n elided] ...` marker.
+const ELISION_MARKER_CHARS: usize = 48;
+
+/// What to do when attached files exceed the token budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileContextMode {
+    /// Fail with [`FileContextError::OverBudget`].
+    #[default]
+    Strict,
+    /// Keep the head and tail of oversized files and mark the elided lines.
+    Summary,
+}
+
+/// Budget for [`assemble_file_context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileContextOptions {
+    /// Estimated token ceiling for all rendered files together.
+    pub max_tokens: usize,
+    pub mode: FileContextMode,
+}
+
+impl Default for FileContextOptions {
+    fn default() -> Self {
+        Self {
+            max_tokens: 32_000,
+            mode: FileContextMode::Strict,
+        }
+    }
+}
+
+/// Lines left out of one file in summary mode (1-based, inclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileElision {
+    pub first_line: usize,
+    pub last_line: usize,
+    pub total_lines: usize,
+}
+
+/// One file as rendered into the prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileContextEntry {
+    pub path: PathBuf,
+    pub language: Option<String>,
+    pub estimated_tokens: usize,
+    pub elision: Option<FileElision>,
+}
+
+/// Files rendered as fenced code blocks, ready to append to a prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileContext {
+    pub text: String,
+    pub files: Vec<FileContextEntry>,
+}
+
+impl FileContext {
+    /// `prompt` followed by the rendered files.
+    pub fn append_to(&self, prompt: &str) -> String {
+        if self.text.is_empty() {
+            prompt.to_string()
+        } else if prompt.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{prompt}\n\n{}", self.text)
+        }
+    }
+}
+
+/// Failure to assemble file context.
+#[derive(Debug, Error)]
+pub enum FileContextError {
+    #[error("'{path}' is a directory; attach its files individually, e.g. with a shell glob like '{path}/*.rs'")]
+    Directory { path: String },
+    #[error("failed to read '{path}': {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("'{path}' looks like a binary file; only text files can be attached")]
+    Binary { path: String },
+    #[error(
+        "attached files are about {tokens} tokens, over the {max} token budget; attach fewer files, raise the budget, or use summary mode to keep the head and tail of each file"
+    )]
+    OverBudget { tokens: usize, max: usize },
+}
+
+/// Read `paths` and render each as a fenced code block annotated with its
+/// path, within `options.max_tokens`.
+///
+/// Directories and binary files (detected by content, not extension) are
+/// rejected. In [`FileContextMode::Summary`] files that do not fit keep their
+/// first and last lines around an elision marker, and a closing note lists
+/// what was left out.
+pub fn assemble_file_context(
+    paths: &[PathBuf],
+    options: &FileContextOptions,
+) -> Result<FileContext, FileContextError> {
+    let files = paths
+        .iter()
+        .map(|path| read_text_file(path).map(|content| (path, content)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let full_blocks = files
+        .iter()
+        .map(|(path, content)| render_block(path, content))
+        .collect::<Vec<_>>();
+    let full_tokens = full_blocks
+        .iter()
+        .map(|block| estimate_text_tokens(block))
+        .sum::<usize>();
+
+    if full_tokens > options.max_tokens && options.mode == FileContextMode::Strict {
+        return Err(FileContextError::OverBudget {
+            tokens: full_tokens,
+            max: options.max_tokens,
+        });
+    }
+
+    let shares = if full_tokens > options.max_tokens {
+        token_shares(&full_blocks, options.max_tokens)
+    } else {
+        vec![usize::MAX; files.len()]
+    };
+
+    let mut blocks = Vec::with_capacity(files.len());
+    let mut entries = Vec::with_capacity(files.len());
+    for (((path, content), full_block), share) in files.iter().zip(full_blocks).zip(shares) {
+        let (block, elision) = if estimate_text_tokens(&full_block) <= share {
+            (full_block, None)
+        } else {
+            let overhead = render_block(path, "").len() + ELISION_MARKER_CHARS;
+            let max_chars = share
+                .saturating_mul(CHARS_PER_TOKEN)
+                .saturating_sub(overhead);
+            let (excerpt, elision) = head_and_tail(content, max_chars);
+            (render_block(path, &excerpt), elision)
+        };
+        entries.push(FileContextEntry {
+            path: path.to_path_buf(),
+            language: language_for_path(path).map(str::to_string),
+            estimated_tokens: estimate_text_tokens(&block),
+            elision,
+        });
+        blocks.push(block);
+    }
+
+    let mut text = blocks.join("\n\n");
+    if let Some(note) = elision_note(&entries) {
+        text.push_str("\n\n");
+        text.push_str(&note);
+    }
+    Ok(FileContext {
+        text,
+        files: entries,
+    })
+}
+
+fn read_text_file(path: &Path) -> Result<String, FileContextError> {
+    let display = path.display().to_string();
+    let metadata = fs::metadata(path).map_err(|source| FileContextError::Read {
+        path: display.clone(),
+        source,
+    })?;
+    if metadata.is_dir() {
+        return Err(FileContextError::Directory { path: display });
+    }
+    let bytes = fs::read(path).map_err(|source| FileContextError::Read {
+        path: display.clone(),
+        source,
+    })?;
+    if looks_binary(&bytes) {
+        return Err(FileContextError::Binary { path: display });
+    }
+    String::from_utf8(bytes).map_err(|_| FileContextError::Binary { path: display })
+}
+
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// Split `max_tokens` across files: files under an equal share keep their
+/// size, and what they leave unused is split among the rest.
+fn token_shares(blocks: &[String], max_tokens: usize) -> Vec<usize> {
+    let tokens = blocks
+        .iter()
+        .map(|block| estimate_text_tokens(block))
+        .collect::<Vec<_>>();
+    let equal_share = max_tokens / blocks.len().max(1);
+    let small_total = tokens
+        .iter()
+        .filter(|&&tokens| tokens <= equal_share)
+        .sum::<usize>();
+    let large_count = tokens
+        .iter()
+        .filter(|&&tokens| tokens > equal_share)
+        .count();
+    let large_share = max_tokens.saturating_sub(small_total) / large_count.max(1);
+    tokens
+        .into_iter()
+        .map(|tokens| {
+            if tokens <= equal_share {
+                tokens
+            } else {
+                large_share
+            }
+        })
+        .collect()
+}
+
+/// Keep whole lines from the start and end of `content` within `max_chars`,
+/// joined by an elision marker.
+fn head_and_tail(content: &str, max_chars: usize) -> (String, Option<FileElision>) {
+    let lines = content.lines().collect::<Vec<_>>();
+    let half = max_chars / 2;
+
+    let mut head = 0;
+    let mut head_chars = 0;
+    while head < lines.len() && head_chars + lines[head].len() < half {
+        head_chars += lines[head].len() + 1;
+        head += 1;
+    }
+    let mut tail = lines.len();
+    let mut tail_chars = 0;
+    while tail > head && tail_chars + lines[tail - 1].len() < half {
+        tail_chars += lines[tail - 1].len() + 1;
+        tail -= 1;
+    }
+    if tail <= head {
+        return (content.to_string(), None);
+    }
+
+    let elision = FileElision {
+        first_line: head + 1,
+        last_line: tail,
+        total_lines: lines.len(),
+    };
+    let mut excerpt = lines[..head].join("\n");
+    if head > 0 {
+        excerpt.push('\n');
+    }
+    excerpt.push_str(&format!(
+        "... [lines {}-{} of {} elided] ...",
+        elision.first_line, elision.last_line, elision.total_lines
+    ));
+    if tail < lines.len() {
+        excerpt.push('\n');
+        excerpt.push_str(&lines[tail..].join("\n"));
+    }
+    (excerpt, Some(elision))
+}
+
+fn render_block(path: &Path, content: &str) -> String {
+    let fence = fence_for(content);
+    let language = language_for_path(path).unwrap_or("");
+    let mut block = format!("{fence}{language} title=\"{}\"\n{content}", path.display());
+    if !content.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str(&fence);
+    block
+}
+
+/// A backtick fence longer than any backtick run inside `content`.
+fn fence_for(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in content.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+fn elision_note(entries: &[FileContextEntry]) -> Option<String> {
+    let elided = entries
+        .iter()
+        .filter_map(|entry| {
+            entry.elision.map(|elision| {
+                format!(
+                    "- {}: lines {}-{} of {} omitted",
+                    entry.path.display(),
+                    elision.first_line,
+                    elision.last_line,
+                    elision.total_lines
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if elided.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Note: these files were shortened to fit the context budget; the omitted lines were not shown to you:\n{}",
+        elided.join("\n")
+    ))
+}
+
+/// Fenced code block language for `path`, from its extension or file name.
+pub fn language_for_path(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?;
+    match file_name {
+        "Dockerfile" => return Some("dockerfile"),
+        "Makefile" | "makefile" => return Some("makefile"),
+        _ => {}
+    }
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let language = match extension.as_str() {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" => "powershell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "xml" => "xml",
+        "md" | "markdown" => "markdown",
+        "lua" => "lua",
+        "ex" | "exs" => "elixir",
+        "hs" => "haskell",
+        "scala" => "scala",
+        "dart" => "dart",
+        "proto" => "protobuf",
+        _ => return None,
+    };
+    Some(language)
+}
@@ -1,6 +1,7 @@
 //! Host-facing attachment contract and V1 resolver.
 
 mod compiler;
+mod file_context;
 mod preflight;
 mod renderer;
 mod resolver;
@@ -10,6 +11,10 @@ pub use compiler::{
     compile_prompt_input, AttachmentContentKind, AttachmentDisplayMetadata, AttachmentSourceKind,
     CompiledPromptInput,
 };
+pub use file_context::{
+    assemble_file_context, language_for_path, FileContext, FileContextEntry, FileContextError,
+    FileContextMode, FileContextOptions, FileElision,
+};
 pub use preflight::{
     preflight_resolved_attachments, AttachmentPreflightError, AttachmentPreflightReport,
 };
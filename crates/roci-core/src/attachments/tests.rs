@@ -444,3 +444,142 @@ fn compile_prompt_input_file_metadata_omits_raw_path() {
     assert!(json.contains("secret-notes.txt"));
     assert!(!json.contains(dir.path().to_string_lossy().as_ref()));
 }
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count).map(|n| format!("line {n:04}\n")).collect()
+}
+
+#[test]
+fn file_context_fences_files_with_language_and_path() {
+    let dir = tempdir().expect("tempdir should be created");
+    let path = dir.path().join("main.rs");
+    fs::write(&path, "fn main() {}\n").expect("fixture should be written");
+
+    let context =
+        assemble_file_context(std::slice::from_ref(&path), &FileContextOptions::default())
+            .expect("small file should fit");
+
+    let expected = format!("```rust title=\"{}\"\nfn main() {{}}\n```", path.display());
+    assert_eq!(context.text, expected);
+    assert_eq!(context.files[0].language.as_deref(), Some("rust"));
+    assert_eq!(context.files[0].elision, None);
+    assert_eq!(
+        context.append_to("Review this"),
+        format!("Review this\n\n{expected}")
+    );
+}
+
+#[test]
+fn file_context_fence_outgrows_backticks_in_content() {
+    let dir = tempdir().expect("tempdir should be created");
+    let path = dir.path().join("README.md");
+    fs::write(&path, "```sh\nls\n```\n").expect("fixture should be written");
+
+    let context = assemble_file_context(&[path], &FileContextOptions::default())
+        .expect("small file should fit");
+
+    assert!(context.text.starts_with("````markdown title="));
+    assert!(context.text.ends_with("\n````"));
+}
+
+#[test]
+fn file_context_strict_mode_rejects_over_budget() {
+    let dir = tempdir().expect("tempdir should be created");
+    let path = dir.path().join("big.txt");
+    fs::write(&path, numbered_lines(200)).expect("fixture should be written");
+
+    let err = assemble_file_context(
+        &[path],
+        &FileContextOptions {
+            max_tokens: 100,
+            mode: FileContextMode::Strict,
+        },
+    )
+    .expect_err("strict mode should refuse oversized files");
+
+    assert!(matches!(err, FileContextError::OverBudget { max: 100, .. }));
+    assert!(err.to_string().contains("summary mode"));
+}
+
+#[test]
+fn file_context_summary_mode_keeps_head_and_tail_and_reports_elision() {
+    let dir = tempdir().expect("tempdir should be created");
+    let big = dir.path().join("big.txt");
+    let small = dir.path().join("small.txt");
+    fs::write(&big, numbered_lines(200)).expect("fixture should be written");
+    fs::write(&small, "tiny\n").expect("fixture should be written");
+
+    let context = assemble_file_context(
+        &[big.clone(), small],
+        &FileContextOptions {
+            max_tokens: 200,
+            mode: FileContextMode::Summary,
+        },
+    )
+    .expect("summary mode should elide");
+
+    let elision = context.files[0].elision.expect("big file should be elided");
+    assert!(elision.first_line > 1);
+    assert!(elision.last_line < 200);
+    assert_eq!(elision.total_lines, 200);
+    assert_eq!(context.files[1].elision, None);
+
+    assert!(context.text.contains("line 0001"));
+    assert!(context.text.contains("line 0200"));
+    assert!(!context.text.contains("line 0100"));
+    assert!(context.text.contains("tiny"));
+    assert!(context.text.contains(&format!(
+        "... [lines {}-{} of 200 elided] ...",
+        elision.first_line, elision.last_line
+    )));
+    assert!(context.text.contains(&format!(
+        "- {}: lines {}-{} of 200 omitted",
+        big.display(),
+        elision.first_line,
+        elision.last_line
+    )));
+    let file_tokens: usize = context.files.iter().map(|file| file.estimated_tokens).sum();
+    assert!(file_tokens <= 200, "file_tokens={file_tokens}");
+}
+
+#[test]
+fn file_context_summary_mode_leaves_fitting_files_whole() {
+    let dir = tempdir().expect("tempdir should be created");
+    let path = dir.path().join("notes.txt");
+    fs::write(&path, numbered_lines(10)).expect("fixture should be written");
+
+    let context = assemble_file_context(
+        &[path],
+        &FileContextOptions {
+            max_tokens: 1_000,
+            mode: FileContextMode::Summary,
+        },
+    )
+    .expect("file should fit");
+
+    assert_eq!(context.files[0].elision, None);
+    assert!(!context.text.contains("Note:"));
+}
+
+#[test]
+fn file_context_rejects_directories_with_glob_hint() {
+    let dir = tempdir().expect("tempdir should be created");
+
+    let err = assemble_file_context(&[dir.path().to_path_buf()], &FileContextOptions::default())
+        .expect_err("directories should be rejected");
+
+    assert!(matches!(err, FileContextError::Directory { .. }));
+    assert!(err.to_string().contains("glob"));
+}
+
+#[test]
+fn file_context_sniffs_binary_content_regardless_of_extension() {
+    let dir = tempdir().expect("tempdir should be created");
+    let path = dir.path().join("looks_like_text.rs");
+    fs::write(&path, b"fn main() {}\0\x01\x02").expect("fixture should be written");
+
+    let err = assemble_file_context(&[path], &FileContextOptions::default())
+        .expect_err("binary content should be refused");
+
+    assert!(matches!(err, FileContextError::Binary { .. }));
+}
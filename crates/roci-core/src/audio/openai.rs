@@ -1,22 +1,29 @@
 //! OpenAI audio providers (Whisper transcription + TTS).
 
+use std::path::Path;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use reqwest::header::CONTENT_TYPE;
 use serde::Deserialize;
 use uuid::Uuid;
 
 use super::openai_helpers::{
-    build_transcription_multipart, content_type_matches_expected_audio,
-    is_supported_transcription_mime, normalize_mime_type, transcription_extension_for_mime,
-    trim_trailing_slash, tts_format_name,
+    build_transcription_multipart, expected_audio_mime_types, is_supported_transcription_mime,
+    normalize_mime_type, transcription_extension_for_mime, trim_trailing_slash, tts_format_name,
 };
 use super::transcription::AudioProvider;
-use super::tts::SpeechProvider;
-use super::types::{AudioFormat, SpeechRequest, TranscriptionResult, TranscriptionSegment};
+use super::tts::{AudioChunker, SpeechProvider};
+use super::types::{
+    AudioChunk, AudioFormat, SpeechRequest, TranscriptionResult, TranscriptionSegment, Voice,
+};
 use crate::error::RociError;
-use crate::provider::http::{bearer_headers, shared_client, status_to_error};
+use crate::provider::http::{
+    bearer_headers, download_to_path, download_to_writer, shared_client, status_to_error,
+    DownloadOptions, DownloadProgressCallback,
+};
 use crate::util::retry::RetryPolicy;
 use crate::util::timeout::with_timeout;
 
@@ -24,6 +31,14 @@ const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
 const DEFAULT_WHISPER_MODEL: &str = "whisper-1";
 const DEFAULT_TTS_MODEL: &str = "tts-1";
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_SPEECH_BYTES: u64 = 256 * 1024 * 1024;
+const DEFAULT_SPEECH_RESUME_ATTEMPTS: u32 = 2;
+/// Built-in speech voices; the API has no endpoint to list them.
+const OPENAI_VOICES: [&str; 11] = [
+    "alloy", "ash", "ballad", "coral", "echo", "fable", "nova", "onyx", "sage", "shimmer", "verse",
+];
+/// Voices the `tts-1` family does not support.
+const GPT_TTS_ONLY_VOICES: [&str; 2] = ["ballad", "verse"];
 
 /// OpenAI Whisper transcription provider (`/audio/transcriptions`).
 #[derive(Debug, Clone)]
@@ -179,6 +194,9 @@ impl AudioProvider for OpenAiWhisperTranscriptionProvider {
 }
 
 /// OpenAI TTS provider (`/audio/speech`).
+///
+/// Audio is streamed from the response in chunks;
+/// [`SpeechProvider::save_to`] writes it straight to disk.
 #[derive(Debug, Clone)]
 pub struct OpenAiTtsProvider {
     api_key: String,
@@ -186,6 +204,7 @@ pub struct OpenAiTtsProvider {
     model: String,
     timeout: Duration,
     retry_policy: RetryPolicy,
+    download: DownloadOptions,
 }
 
 impl OpenAiTtsProvider {
@@ -196,6 +215,7 @@ impl OpenAiTtsProvider {
             model: DEFAULT_TTS_MODEL.to_string(),
             timeout: DEFAULT_TIMEOUT,
             retry_policy: RetryPolicy::default(),
+            download: default_speech_download(),
         }
     }
 
@@ -206,6 +226,7 @@ impl OpenAiTtsProvider {
             model: DEFAULT_TTS_MODEL.to_string(),
             timeout: DEFAULT_TIMEOUT,
             retry_policy: RetryPolicy::default(),
+            download: default_speech_download(),
         }
     }
 
@@ -224,6 +245,18 @@ impl OpenAiTtsProvider {
         self
     }
 
+    /// Reject speech responses larger than `max_bytes` (default 256 MiB).
+    pub fn with_max_audio_bytes(mut self, max_bytes: u64) -> Self {
+        self.download.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Report bytes received while speech audio downloads.
+    pub fn with_progress(mut self, on_progress: DownloadProgressCallback) -> Self {
+        self.download.on_progress = Some(on_progress);
+        self
+    }
+
     fn validate_request(&self, request: &SpeechRequest) -> Result<(), RociError> {
         if self.api_key.trim().is_empty() {
             return Err(RociError::Authentication(
@@ -250,7 +283,7 @@ impl OpenAiTtsProvider {
         Ok(())
     }
 
-    async fn generate_speech_once(&self, request: &SpeechRequest) -> Result<Vec<u8>, RociError> {
+    fn speech_request(&self, request: &SpeechRequest) -> reqwest::RequestBuilder {
         let mut payload = serde_json::json!({
             "model": self.model.clone(),
             "input": request.text.clone(),
@@ -262,30 +295,155 @@ impl OpenAiTtsProvider {
         }
 
         let url = format!("{}/audio/speech", trim_trailing_slash(&self.base_url));
-        let headers = bearer_headers(&self.api_key);
+        shared_client()
+            .post(url)
+            .headers(bearer_headers(&self.api_key))
+            .json(&payload)
+    }
 
-        with_timeout(self.timeout, async {
-            let response = shared_client()
-                .post(url)
-                .headers(headers)
-                .json(&payload)
-                .send()
-                .await?;
+    fn download_options(&self, format: AudioFormat) -> DownloadOptions {
+        self.download
+            .clone()
+            .with_content_types(expected_audio_mime_types(format).iter().copied())
+    }
 
-            parse_tts_response(response, request.format).await
+    /// Send the speech request and check the response head, leaving the
+    /// body unread.
+    async fn open_speech_stream(
+        &self,
+        request: &SpeechRequest,
+    ) -> Result<reqwest::Response, RociError> {
+        let response = with_timeout(self.timeout, async {
+            Ok(self.speech_request(request).send().await?)
         })
-        .await
+        .await?;
+        let status = response.status().as_u16();
+        if status != 200 {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_to_error(status, &body));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let expected = expected_audio_mime_types(request.format);
+        let mime = normalize_mime_type(&content_type).unwrap_or_default();
+        if !expected.contains(&mime) {
+            let body = response.text().await.unwrap_or_default();
+            let detail = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|value| value["error"]["message"].as_str().map(str::to_string))
+                .map(|message| format!(": {message}"))
+                .unwrap_or_default();
+            return Err(RociError::InvalidState(format!(
+                "unexpected speech content type '{content_type}'; expected one of {}{detail}",
+                expected.join(", ")
+            )));
+        }
+        Ok(response)
+    }
+
+    async fn generate_speech_once(&self, request: &SpeechRequest) -> Result<Vec<u8>, RociError> {
+        let options = self.download_options(request.format);
+        let mut audio = Vec::new();
+        with_timeout(
+            self.timeout,
+            download_to_writer(|| self.speech_request(request), &mut audio, &options),
+        )
+        .await?;
+        if audio.is_empty() {
+            return Err(empty_speech_error());
+        }
+        Ok(audio)
     }
 }
 
 #[async_trait]
 impl SpeechProvider for OpenAiTtsProvider {
+    /// Streams the chunked `/audio/speech` body as it arrives.
+    ///
+    /// Opening the stream is retried per the retry policy; the timeout
+    /// bounds the wait for the response and for each chunk after it. Once
+    /// audio has been yielded a failure ends the stream with an error rather
+    /// than restarting playback.
+    fn stream_speech<'a>(
+        &'a self,
+        request: &'a SpeechRequest,
+    ) -> BoxStream<'a, Result<AudioChunk, RociError>> {
+        Box::pin(async_stream::try_stream! {
+            self.validate_request(request)?;
+            let response = self
+                .retry_policy
+                .execute(|| self.open_speech_stream(request))
+                .await?;
+            let max_bytes = self.download.max_bytes;
+            let mut chunker = AudioChunker::new(request.format);
+            let mut body = response.bytes_stream();
+            let mut received = 0u64;
+            while let Some(bytes) = with_timeout(self.timeout, async {
+                body.next().await.transpose().map_err(RociError::from)
+            })
+            .await?
+            {
+                received += bytes.len() as u64;
+                if let Some(max) = max_bytes.filter(|max| received > *max) {
+                    Err::<(), _>(RociError::InvalidState(format!(
+                        "speech stream exceeds the {max} byte size limit"
+                    )))?;
+                }
+                if let Some(chunk) = chunker.push(&bytes) {
+                    yield chunk;
+                }
+            }
+            if received == 0 {
+                Err::<(), _>(empty_speech_error())?;
+            }
+            yield chunker.finish()?;
+        })
+    }
+
+    async fn list_voices(&self) -> Result<Vec<Voice>, RociError> {
+        let tts_family = self.model.starts_with("tts-1");
+        Ok(OPENAI_VOICES
+            .iter()
+            .filter(|id| !(tts_family && GPT_TTS_ONLY_VOICES.contains(id)))
+            .map(|id| Voice {
+                id: (*id).to_string(),
+                name: Some(capitalize(id)),
+                provider: "openai".to_string(),
+                description: None,
+                preview_url: None,
+            })
+            .collect())
+    }
+
     async fn generate_speech(&self, request: &SpeechRequest) -> Result<Vec<u8>, RociError> {
         self.validate_request(request)?;
         self.retry_policy
             .execute(|| self.generate_speech_once(request))
             .await
     }
+
+    async fn save_to(&self, request: &SpeechRequest, path: &Path) -> Result<u64, RociError> {
+        self.validate_request(request)?;
+        let options = self.download_options(request.format);
+        let written = self
+            .retry_policy
+            .execute(|| {
+                with_timeout(
+                    self.timeout,
+                    download_to_path(move || self.speech_request(request), path, &options),
+                )
+            })
+            .await?;
+        if written == 0 {
+            let _ = tokio::fs::remove_file(path).await;
+            return Err(empty_speech_error());
+        }
+        Ok(written)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -350,58 +508,271 @@ async fn parse_transcription_response(
     })
 }
 
-async fn parse_tts_response(
-    response: reqwest::Response,
-    format: AudioFormat,
-) -> Result<Vec<u8>, RociError> {
-    let status = response.status().as_u16();
-    if status != 200 {
-        let body = response.text().await.unwrap_or_default();
-        return Err(status_to_error(status, &body));
-    }
+fn default_speech_download() -> DownloadOptions {
+    DownloadOptions::new()
+        .with_max_bytes(DEFAULT_MAX_SPEECH_BYTES)
+        .with_max_resume_attempts(DEFAULT_SPEECH_RESUME_ATTEMPTS)
+}
 
-    let content_type = response
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|v| v.to_str().ok())
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
         .unwrap_or_default()
-        .to_ascii_lowercase();
+}
 
-    if content_type.starts_with("application/json") {
-        let body = response.text().await.unwrap_or_default();
-        if let Some(message) = extract_openai_error_message(&body) {
-            return Err(RociError::Provider {
+fn empty_speech_error() -> RociError {
+    RociError::InvalidState("Speech response contained empty audio payload".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::audio::Voice;
+
+    fn speech_request() -> SpeechRequest {
+        SpeechRequest {
+            text: "hello".to_string(),
+            voice: Voice {
+                id: "alloy".to_string(),
+                name: None,
                 provider: "openai".to_string(),
-                message,
-            });
+                description: None,
+                preview_url: None,
+            },
+            format: AudioFormat::Mp3,
+            speed: None,
         }
-        return Err(RociError::InvalidState(
-            "Expected audio payload, got JSON response".to_string(),
-        ));
     }
 
-    if !content_type_matches_expected_audio(&content_type, format) {
-        return Err(RociError::InvalidState(format!(
-            "Unexpected speech response MIME type '{content_type}' for format {:?}",
-            format
-        )));
+    async fn speech_server(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/speech"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        server
     }
 
-    let bytes = response.bytes().await?;
-    if bytes.is_empty() {
-        return Err(RociError::InvalidState(
-            "Speech response contained empty audio payload".to_string(),
-        ));
+    #[tokio::test]
+    async fn save_to_streams_audio_to_file_with_progress() {
+        let audio = vec![7u8; 2 * 1024 * 1024];
+        let server = speech_server(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "audio/mpeg")
+                .set_body_bytes(audio.clone()),
+        )
+        .await;
+        let downloaded = Arc::new(AtomicU64::new(0));
+        let seen = downloaded.clone();
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), server.uri())
+            .with_progress(Arc::new(move |progress| {
+                seen.store(progress.bytes_downloaded, Ordering::SeqCst);
+            }));
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("speech.mp3");
+
+        let written = provider.save_to(&speech_request(), &target).await.unwrap();
+
+        assert_eq!(written, audio.len() as u64);
+        assert_eq!(std::fs::read(&target).unwrap(), audio);
+        assert_eq!(downloaded.load(Ordering::SeqCst), audio.len() as u64);
     }
 
-    Ok(bytes.to_vec())
-}
+    #[tokio::test]
+    async fn generate_speech_enforces_max_audio_bytes() {
+        let server = speech_server(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "audio/mpeg")
+                .set_body_bytes(vec![1u8; 4096]),
+        )
+        .await;
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), server.uri())
+            .with_max_audio_bytes(1024);
+
+        let err = provider
+            .generate_speech(&speech_request())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("1024 byte size limit"), "{err}");
+    }
+
+    /// Serve one speech response whose body is sent as separate HTTP
+    /// chunks, pausing between them so each arrives on its own.
+    async fn chunked_speech_server(content_type: &'static str, pieces: Vec<Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: {content_type}\r\ntransfer-encoding: chunked\r\n\r\n"
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+            for piece in pieces {
+                socket
+                    .write_all(format!("{:x}\r\n", piece.len()).as_bytes())
+                    .await
+                    .unwrap();
+                socket.write_all(&piece).await.unwrap();
+                socket.write_all(b"\r\n").await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            socket.write_all(b"0\r\n\r\n").await.unwrap();
+            socket.shutdown().await.unwrap();
+        });
+        format!("http://{addr}")
+    }
 
-fn extract_openai_error_message(body: &str) -> Option<String> {
-    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
-    parsed
-        .get("error")
-        .and_then(|error| error.get("message"))
-        .and_then(|message| message.as_str())
-        .map(ToString::to_string)
+    async fn collect_stream(
+        provider: &OpenAiTtsProvider,
+        request: &SpeechRequest,
+    ) -> Vec<Result<AudioChunk, RociError>> {
+        provider.stream_speech(request).collect().await
+    }
+
+    #[tokio::test]
+    async fn stream_speech_frames_pcm_chunks_and_ends_with_final_marker() {
+        let pieces = vec![vec![1, 2, 3], vec![4, 5, 6, 7], vec![8], vec![9, 10]];
+        let expected = pieces.concat();
+        let base_url = chunked_speech_server("audio/pcm", pieces).await;
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), base_url);
+        let request = SpeechRequest {
+            format: AudioFormat::Pcm16,
+            ..speech_request()
+        };
+
+        let chunks = collect_stream(&provider, &request)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let (last, audio) = chunks.split_last().unwrap();
+        assert!(last.is_final && last.data.is_empty());
+        assert!(audio.len() > 1, "expected several audio chunks: {audio:?}");
+        assert!(audio
+            .iter()
+            .all(|chunk| !chunk.is_final && chunk.data.len() % 2 == 0));
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.format == AudioFormat::Pcm16));
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.sequence)
+                .collect::<Vec<_>>(),
+            (0..chunks.len() as u64).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            audio
+                .iter()
+                .flat_map(|chunk| chunk.data.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_speech_requests_the_chosen_format() {
+        let audio = vec![3u8; 64 * 1024];
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/speech"))
+            .and(body_partial_json(
+                serde_json::json!({ "response_format": "opus" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "audio/opus")
+                    .set_body_bytes(audio.clone()),
+            )
+            .mount(&server)
+            .await;
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), server.uri());
+        let request = SpeechRequest {
+            format: AudioFormat::Opus,
+            ..speech_request()
+        };
+
+        let chunks = collect_stream(&provider, &request)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let (last, body) = chunks.split_last().unwrap();
+        assert!(last.is_final);
+        assert_eq!(
+            body.iter()
+                .flat_map(|chunk| chunk.data.clone())
+                .collect::<Vec<_>>(),
+            audio
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_speech_error_ends_the_stream_without_a_final_marker() {
+        let server = speech_server(ResponseTemplate::new(200).set_body_raw(
+            r#"{"error":{"message":"voice not found"}}"#,
+            "application/json",
+        ))
+        .await;
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), server.uri());
+
+        let items = collect_stream(&provider, &speech_request()).await;
+
+        assert_eq!(items.len(), 1);
+        let err = items.into_iter().next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("voice not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn list_voices_matches_the_model_family() {
+        let tts = OpenAiTtsProvider::new("test-key".to_string());
+        let gpt = OpenAiTtsProvider::new("test-key".to_string()).with_model("gpt-4o-mini-tts");
+
+        let tts_voices = tts.list_voices().await.unwrap();
+        let gpt_voices = gpt.list_voices().await.unwrap();
+
+        assert!(tts_voices.iter().any(|voice| voice.id == "alloy"
+            && voice.name.as_deref() == Some("Alloy")
+            && voice.provider == "openai"));
+        assert!(!tts_voices.iter().any(|voice| voice.id == "verse"));
+        assert!(gpt_voices.iter().any(|voice| voice.id == "verse"));
+    }
+
+    #[tokio::test]
+    async fn json_error_body_is_reported_instead_of_audio() {
+        let server = speech_server(ResponseTemplate::new(200).set_body_raw(
+            r#"{"error":{"message":"voice not found"}}"#,
+            "application/json",
+        ))
+        .await;
+        let provider = OpenAiTtsProvider::new_with_base_url("test-key".to_string(), server.uri());
+
+        let err = provider
+            .generate_speech(&speech_request())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("voice not found"), "{err}");
+    }
 }
@@ -97,25 +97,14 @@ pub(super) fn tts_format_name(format: AudioFormat) -> &'static str {
     }
 }
 
-pub(super) fn content_type_matches_expected_audio(content_type: &str, format: AudioFormat) -> bool {
-    let mime = content_type
-        .split(';')
-        .next()
-        .map(str::trim)
-        .unwrap_or_default();
-
-    if mime.is_empty() {
-        return false;
-    }
-
+/// MIME types the speech endpoint may answer with for `format`.
+pub(super) fn expected_audio_mime_types(format: AudioFormat) -> &'static [&'static str] {
     match format {
-        AudioFormat::Mp3 => matches!(mime, "audio/mpeg" | "audio/mp3"),
-        AudioFormat::Opus => matches!(mime, "audio/opus" | "audio/ogg" | "application/ogg"),
-        AudioFormat::Aac => matches!(mime, "audio/aac" | "audio/mp4"),
-        AudioFormat::Flac => matches!(mime, "audio/flac" | "audio/x-flac"),
-        AudioFormat::Wav => matches!(mime, "audio/wav" | "audio/x-wav" | "audio/wave"),
-        AudioFormat::Pcm16 => {
-            matches!(mime, "audio/pcm" | "audio/l16" | "application/octet-stream")
-        }
+        AudioFormat::Mp3 => &["audio/mpeg", "audio/mp3"],
+        AudioFormat::Opus => &["audio/opus", "audio/ogg", "application/ogg"],
+        AudioFormat::Aac => &["audio/aac", "audio/mp4"],
+        AudioFormat::Flac => &["audio/flac", "audio/x-flac"],
+        AudioFormat::Wav => &["audio/wav", "audio/x-wav", "audio/wave"],
+        AudioFormat::Pcm16 => &["audio/pcm", "audio/l16", "application/octet-stream"],
     }
 }
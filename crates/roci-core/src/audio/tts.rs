@@ -1,8 +1,11 @@
 //! Text-to-speech trait.
 
+use std::path::Path;
+
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
-use super::types::SpeechRequest;
+use super::types::{AudioChunk, AudioFormat, SpeechRequest, Voice};
 use crate::error::RociError;
 
 /// Trait for text-to-speech providers.
@@ -10,4 +13,151 @@ use crate::error::RociError;
 pub trait SpeechProvider: Send + Sync {
     /// Generate speech audio from text.
     async fn generate_speech(&self, request: &SpeechRequest) -> Result<Vec<u8>, RociError>;
+
+    /// Generate speech audio and write it to `path`, returning the bytes
+    /// written.
+    ///
+    /// The default buffers the audio via [`SpeechProvider::generate_speech`];
+    /// providers that can stream the response override it.
+    async fn save_to(&self, request: &SpeechRequest, path: &Path) -> Result<u64, RociError> {
+        let audio = self.generate_speech(request).await?;
+        tokio::fs::write(path, &audio).await?;
+        Ok(audio.len() as u64)
+    }
+
+    /// Stream speech audio as it is synthesized.
+    ///
+    /// Chunks are numbered from 0 and the stream ends with a final marker
+    /// chunk (see [`AudioChunk`]). [`AudioFormat::Pcm16`] chunks always hold
+    /// whole samples. The default yields the buffered output of
+    /// [`SpeechProvider::generate_speech`] as one chunk.
+    fn stream_speech<'a>(
+        &'a self,
+        request: &'a SpeechRequest,
+    ) -> BoxStream<'a, Result<AudioChunk, RociError>> {
+        Box::pin(async_stream::try_stream! {
+            let audio = self.generate_speech(request).await?;
+            let mut chunker = AudioChunker::new(request.format);
+            if let Some(chunk) = chunker.push(&audio) {
+                yield chunk;
+            }
+            yield chunker.finish()?;
+        })
+    }
+
+    /// Voices this provider can speak with.
+    ///
+    /// The default reports none.
+    async fn list_voices(&self) -> Result<Vec<Voice>, RociError> {
+        Ok(Vec::new())
+    }
+}
+
+/// Numbers streamed audio into [`AudioChunk`]s, holding back a split PCM
+/// sample until its second byte arrives.
+#[derive(Debug)]
+pub(super) struct AudioChunker {
+    format: AudioFormat,
+    sequence: u64,
+    pending: Vec<u8>,
+}
+
+impl AudioChunker {
+    pub(super) fn new(format: AudioFormat) -> Self {
+        Self {
+            format,
+            sequence: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Frame `bytes` into the next chunk, or `None` when nothing complete
+    /// is ready yet.
+    pub(super) fn push(&mut self, bytes: &[u8]) -> Option<AudioChunk> {
+        self.pending.extend_from_slice(bytes);
+        let ready = match self.format {
+            AudioFormat::Pcm16 => self.pending.len() - self.pending.len() % 2,
+            _ => self.pending.len(),
+        };
+        if ready == 0 {
+            return None;
+        }
+        let rest = self.pending.split_off(ready);
+        let data = std::mem::replace(&mut self.pending, rest);
+        Some(self.chunk(data, false))
+    }
+
+    /// The final marker chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Stream`] when the audio ended inside a PCM
+    /// sample.
+    pub(super) fn finish(mut self) -> Result<AudioChunk, RociError> {
+        if !self.pending.is_empty() {
+            return Err(RociError::Stream(format!(
+                "speech stream ended inside a PCM sample ({} trailing byte)",
+                self.pending.len()
+            )));
+        }
+        Ok(self.chunk(Vec::new(), true))
+    }
+
+    fn chunk(&mut self, data: Vec<u8>, is_final: bool) -> AudioChunk {
+        let chunk = AudioChunk {
+            data,
+            format: self.format,
+            sequence: self.sequence,
+            is_final,
+        };
+        self.sequence += 1;
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcm_chunks_hold_whole_samples() {
+        let mut chunker = AudioChunker::new(AudioFormat::Pcm16);
+
+        let first = chunker.push(&[1, 2, 3]).unwrap();
+        assert!(chunker.push(&[]).is_none());
+        let second = chunker.push(&[4]).unwrap();
+        assert!(chunker.push(&[5]).is_none());
+        let third = chunker.push(&[6]).unwrap();
+        let last = chunker.finish().unwrap();
+
+        assert_eq!(first.data, vec![1, 2]);
+        assert_eq!(second.data, vec![3, 4]);
+        assert_eq!(third.data, vec![5, 6]);
+        assert_eq!(
+            [
+                first.sequence,
+                second.sequence,
+                third.sequence,
+                last.sequence
+            ],
+            [0, 1, 2, 3]
+        );
+        assert!(last.is_final && last.data.is_empty());
+    }
+
+    #[test]
+    fn pcm_stream_ending_mid_sample_is_an_error() {
+        let mut chunker = AudioChunker::new(AudioFormat::Pcm16);
+        chunker.push(&[1, 2, 3]);
+
+        assert!(chunker.finish().is_err());
+    }
+
+    #[test]
+    fn compressed_chunks_pass_through_unchanged() {
+        let mut chunker = AudioChunker::new(AudioFormat::Mp3);
+
+        assert_eq!(chunker.push(&[1, 2, 3]).unwrap().data, vec![1, 2, 3]);
+        assert_eq!(chunker.finish().unwrap().sequence, 1);
+    }
 }
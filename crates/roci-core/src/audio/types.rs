@@ -20,6 +20,11 @@ pub struct Voice {
     pub id: String,
     pub name: Option<String>,
     pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// URL of a sample clip, when the provider publishes one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_url: Option<String>,
 }
 
 /// Result of audio transcription.
@@ -44,6 +49,23 @@ pub struct TranscriptionSegment {
 pub struct SpeechRequest {
     pub text: String,
     pub voice: Voice,
+    /// Output encoding. [`AudioFormat::Pcm16`] has no container or encoder
+    /// delay, so streamed playback can start on the first chunk; compressed
+    /// formats such as [`AudioFormat::Mp3`] and [`AudioFormat::Opus`] are
+    /// several times smaller.
     pub format: AudioFormat,
     pub speed: Option<f64>,
 }
+
+/// A piece of streamed speech audio.
+///
+/// A complete stream ends with a chunk whose `is_final` is set and whose
+/// `data` is empty; a stream that stops without one was cut short.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioChunk {
+    pub data: Vec<u8>,
+    pub format: AudioFormat,
+    /// Position in the stream, starting at 0.
+    pub sequence: u64,
+    pub is_final: bool,
+}
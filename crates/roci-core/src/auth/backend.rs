@@ -62,6 +62,28 @@ pub trait AuthBackend: Send + Sync {
     /// Get current auth status for this backend.
     fn get_status(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError>;
 
+    /// The stored token whose expiry drives background refresh.
+    ///
+    /// Defaults to [`get_status`](Self::get_status). Backends that call the
+    /// API with a derived short-lived token return that one instead.
+    fn refreshable_token(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError> {
+        self.get_status(store)
+    }
+
+    /// Refresh `token` (as returned by
+    /// [`refreshable_token`](Self::refreshable_token)) and persist the result.
+    ///
+    /// Returns `Ok(None)` when the backend cannot refresh without a new
+    /// login, which is the default.
+    async fn refresh(
+        &self,
+        store: &Arc<dyn TokenStore>,
+        token: &Token,
+    ) -> Result<Option<Token>, AuthError> {
+        let _ = (store, token);
+        Ok(None)
+    }
+
     /// Remove stored tokens for this backend.
     fn logout(&self, store: &Arc<dyn TokenStore>) -> Result<(), AuthError>;
 }
@@ -1,9 +1,10 @@
 //! Device-code session types.
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 /// Device-code session details for OAuth providers.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceCodeSession {
     pub provider: String,
     pub verification_url: String,
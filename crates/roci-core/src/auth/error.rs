@@ -5,7 +5,7 @@ use thiserror::Error;
 use crate::error::RociError;
 
 /// Normalized authentication errors across providers.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum AuthError {
     #[error("Not logged in")]
     NotLoggedIn,
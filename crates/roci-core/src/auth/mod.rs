@@ -3,6 +3,8 @@
 pub mod backend;
 pub mod device_code;
 pub mod error;
+pub mod pending;
+pub mod refresh;
 pub mod service;
 pub mod store;
 pub mod token;
@@ -10,6 +12,10 @@ pub mod token;
 pub use backend::AuthBackend;
 pub use device_code::DeviceCodeSession;
 pub use error::AuthError;
+pub use pending::PendingLoginStore;
+pub use refresh::{
+    RefreshOutcome, RefreshSchedule, RefreshStatus, RefreshStatusStore, RefreshTaskHandle,
+};
 pub use service::{AuthPollResult, AuthService, AuthStep};
 pub use store::{FileTokenStore, TokenStore, TokenStoreConfig};
 pub use token::Token;
@@ -0,0 +1,121 @@
+//! Device-code sessions persisted between processes.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+
+use super::device_code::DeviceCodeSession;
+use super::error::AuthError;
+use super::store::{normalize_label, TokenStoreConfig};
+
+/// File-backed store for device-code sessions awaiting authorization.
+///
+/// Lets one process start a device-code login and a later one finish it.
+/// Sessions live in `<base_dir>/pending/<provider>.json` and are discarded
+/// once past their `expires_at`.
+#[derive(Debug, Clone)]
+pub struct PendingLoginStore {
+    dir: PathBuf,
+}
+
+impl PendingLoginStore {
+    pub fn new(config: TokenStoreConfig) -> Self {
+        Self {
+            dir: config.base_dir.join("pending"),
+        }
+    }
+
+    pub fn new_default() -> Self {
+        Self::new(TokenStoreConfig::new(TokenStoreConfig::default_dir()))
+    }
+
+    /// Persist `session`, replacing any pending login for `provider`.
+    pub fn save(&self, provider: &str, session: &DeviceCodeSession) -> Result<(), AuthError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.session_path(provider);
+        fs::write(&path, serde_json::to_string(session)?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+
+    /// Load the pending login for `provider`.
+    ///
+    /// An expired session is removed and reported as absent.
+    pub fn load(&self, provider: &str) -> Result<Option<DeviceCodeSession>, AuthError> {
+        let path = self.session_path(provider);
+        let raw = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(AuthError::Io(err.to_string())),
+        };
+        let session: DeviceCodeSession = serde_json::from_str(&raw)?;
+        if Utc::now() >= session.expires_at {
+            self.clear(provider)?;
+            return Ok(None);
+        }
+        Ok(Some(session))
+    }
+
+    pub fn clear(&self, provider: &str) -> Result<(), AuthError> {
+        match fs::remove_file(self.session_path(provider)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(AuthError::Io(err.to_string())),
+        }
+    }
+
+    fn session_path(&self, provider: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", normalize_label(provider)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn session(expires_in: Duration) -> DeviceCodeSession {
+        DeviceCodeSession {
+            provider: "openai-codex".to_string(),
+            verification_url: "https://example.com/device".to_string(),
+            user_code: "ABCD-1234".to_string(),
+            device_code: "device-1".to_string(),
+            interval_secs: 5,
+            expires_at: Utc::now() + expires_in,
+        }
+    }
+
+    #[test]
+    fn session_round_trips_until_cleared() {
+        let dir = TempDir::new().unwrap();
+        let store = PendingLoginStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+        store
+            .save("openai-codex", &session(Duration::minutes(10)))
+            .unwrap();
+
+        let loaded = store.load("openai-codex").unwrap().unwrap();
+        assert_eq!(loaded.user_code, "ABCD-1234");
+        assert_eq!(loaded.device_code, "device-1");
+
+        store.clear("openai-codex").unwrap();
+        assert!(store.load("openai-codex").unwrap().is_none());
+    }
+
+    #[test]
+    fn expired_session_is_discarded_on_load() {
+        let dir = TempDir::new().unwrap();
+        let store = PendingLoginStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+        store
+            .save("openai-codex", &session(Duration::seconds(-1)))
+            .unwrap();
+
+        assert!(store.load("openai-codex").unwrap().is_none());
+        assert!(!dir.path().join("pending/openai-codex.json").exists());
+    }
+}
@@ -0,0 +1,687 @@
+//! Background refresh of stored OAuth tokens.
+//!
+//! [`AuthService::start_refresh_task`] refreshes tokens shortly before they
+//! expire, so a long-lived process does not pay the refresh (or fail) on its
+//! first request after sitting idle. Failed refreshes back off, and enough of
+//! them mark the credential as needing a new login, which a
+//! [`RefreshStatusStore`] records for `auth status` to report.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use super::error::AuthError;
+use super::service::AuthService;
+use super::store::{normalize_label, TokenStoreConfig};
+use super::token::Token;
+
+/// Called with the outcome of every refresh the task attempts.
+pub type RefreshCallback = Arc<dyn Fn(&RefreshOutcome) + Send + Sync>;
+
+/// Result of one background refresh attempt, keyed by backend store key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshOutcome {
+    Refreshed {
+        provider: String,
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// The refresh failed and is retried no earlier than `retry_at`.
+    Failed {
+        provider: String,
+        error: String,
+        retry_at: DateTime<Utc>,
+    },
+    /// The refresh failed for good; the provider is skipped until the user
+    /// logs in again.
+    NeedsRelogin { provider: String, error: String },
+}
+
+/// How often the refresh task runs and how it treats failures.
+#[derive(Clone)]
+pub struct RefreshSchedule {
+    interval: Duration,
+    window: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_failures: u32,
+    status_store: Option<RefreshStatusStore>,
+    on_outcome: Option<RefreshCallback>,
+}
+
+impl RefreshSchedule {
+    /// Check tokens every `interval`, refreshing those that expire within
+    /// 10 minutes. Failures back off from 30 seconds up to 30 minutes, and
+    /// the fifth consecutive one marks the credential as needing re-login.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            window: Duration::from_secs(10 * 60),
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(30 * 60),
+            max_failures: 5,
+            status_store: None,
+            on_outcome: None,
+        }
+    }
+
+    /// Refresh tokens that expire within `window` of a check.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Wait `initial` after the first failure, doubling per failure up to
+    /// `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Consecutive failures after which the credential needs re-login.
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Persist failure state so it survives restarts and shows in
+    /// `auth status`. Without a store it is kept in memory.
+    pub fn with_status_store(mut self, store: RefreshStatusStore) -> Self {
+        self.status_store = Some(store);
+        self
+    }
+
+    pub fn on_outcome<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RefreshOutcome) + Send + Sync + 'static,
+    {
+        self.on_outcome = Some(Arc::new(callback));
+        self
+    }
+
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Background refresh state of one credential.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RefreshStatus {
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// No refresh is attempted before this time.
+    pub retry_at: Option<DateTime<Utc>>,
+    pub needs_relogin: bool,
+}
+
+/// File-backed store for [`RefreshStatus`] records.
+///
+/// Records live in `<base_dir>/refresh/<provider>.json` and exist only while
+/// a credential has failing refreshes. Clear a provider's record after a
+/// new login so the refresh task picks it up again.
+#[derive(Debug, Clone)]
+pub struct RefreshStatusStore {
+    dir: PathBuf,
+}
+
+impl RefreshStatusStore {
+    pub fn new(config: TokenStoreConfig) -> Self {
+        Self {
+            dir: config.base_dir.join("refresh"),
+        }
+    }
+
+    pub fn new_default() -> Self {
+        Self::new(TokenStoreConfig::new(TokenStoreConfig::default_dir()))
+    }
+
+    pub fn load(&self, provider: &str) -> Result<Option<RefreshStatus>, AuthError> {
+        match fs::read_to_string(self.status_path(provider)) {
+            Ok(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(AuthError::Io(err.to_string())),
+        }
+    }
+
+    pub fn save(&self, provider: &str, status: &RefreshStatus) -> Result<(), AuthError> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.status_path(provider), serde_json::to_string(status)?)?;
+        Ok(())
+    }
+
+    pub fn clear(&self, provider: &str) -> Result<(), AuthError> {
+        match fs::remove_file(self.status_path(provider)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(AuthError::Io(err.to_string())),
+        }
+    }
+
+    fn status_path(&self, provider: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", normalize_label(provider)))
+    }
+}
+
+/// Handle to a task started by [`AuthService::start_refresh_task`].
+///
+/// Dropping the handle stops the task as well.
+pub struct RefreshTaskHandle {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl RefreshTaskHandle {
+    /// Stop the task, abandoning a refresh pass in progress, and wait for it
+    /// to exit. A refresh already sent to a backend still completes for
+    /// anyone sharing it.
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+pub(super) struct RefreshScheduler {
+    service: Arc<AuthService>,
+    schedule: RefreshSchedule,
+    /// Failure state when the schedule has no status store.
+    statuses: HashMap<String, RefreshStatus>,
+}
+
+impl RefreshScheduler {
+    pub(super) fn new(service: Arc<AuthService>, schedule: RefreshSchedule) -> Self {
+        Self {
+            service,
+            schedule,
+            statuses: HashMap::new(),
+        }
+    }
+
+    pub(super) fn spawn(self) -> RefreshTaskHandle {
+        let (stop, stopped) = oneshot::channel();
+        RefreshTaskHandle {
+            stop,
+            task: tokio::spawn(self.run(stopped)),
+        }
+    }
+
+    async fn run(mut self, mut stopped: oneshot::Receiver<()>) {
+        let mut ticker = tokio::time::interval(self.schedule.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = &mut stopped => return,
+                _ = ticker.tick() => {}
+            }
+            tokio::select! {
+                _ = &mut stopped => return,
+                _ = self.refresh_due(Utc::now()) => {}
+            }
+        }
+    }
+
+    /// Refresh every token due at `now`, skipping credentials that are
+    /// backing off or need re-login.
+    async fn refresh_due(&mut self, now: DateTime<Utc>) -> Vec<RefreshOutcome> {
+        let mut outcomes = Vec::new();
+        for backend in self.service.backends().to_vec() {
+            let provider = backend.store_key();
+            let status = self.status(provider);
+            if status.needs_relogin || status.retry_at.is_some_and(|at| now < at) {
+                continue;
+            }
+            let token = match backend.refreshable_token(self.service.store()) {
+                Ok(Some(token)) => token,
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::warn!(provider, error = %err, "could not load token for refresh");
+                    continue;
+                }
+            };
+            if !expires_within(&token, now, self.schedule.window) {
+                continue;
+            }
+            let outcome = match self.service.refresh_token(provider).await {
+                Ok(Some(token)) => {
+                    tracing::info!(provider, expires_at = ?token.expires_at, "refreshed token");
+                    self.set_status(provider, RefreshStatus::default());
+                    RefreshOutcome::Refreshed {
+                        provider: provider.to_string(),
+                        expires_at: token.expires_at,
+                    }
+                }
+                Ok(None) => continue,
+                Err(err) => self.record_failure(provider, status, &err, now),
+            };
+            if let Some(callback) = &self.schedule.on_outcome {
+                callback(&outcome);
+            }
+            outcomes.push(outcome);
+        }
+        outcomes
+    }
+
+    fn record_failure(
+        &mut self,
+        provider: &str,
+        status: RefreshStatus,
+        err: &AuthError,
+        now: DateTime<Utc>,
+    ) -> RefreshOutcome {
+        let failures = status.consecutive_failures + 1;
+        let error = err.to_string();
+        let gave_up = failures >= self.schedule.max_failures
+            || matches!(err, AuthError::ExpiredOrInvalidGrant);
+        if gave_up {
+            tracing::warn!(
+                provider,
+                failures,
+                error,
+                "token refresh failed; re-login needed"
+            );
+            self.set_status(
+                provider,
+                RefreshStatus {
+                    consecutive_failures: failures,
+                    last_error: Some(error.clone()),
+                    retry_at: None,
+                    needs_relogin: true,
+                },
+            );
+            return RefreshOutcome::NeedsRelogin {
+                provider: provider.to_string(),
+                error,
+            };
+        }
+        let retry_at = chrono::Duration::from_std(self.schedule.backoff(failures))
+            .ok()
+            .and_then(|backoff| now.checked_add_signed(backoff))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        tracing::warn!(provider, failures, error, %retry_at, "token refresh failed; backing off");
+        self.set_status(
+            provider,
+            RefreshStatus {
+                consecutive_failures: failures,
+                last_error: Some(error.clone()),
+                retry_at: Some(retry_at),
+                needs_relogin: false,
+            },
+        );
+        RefreshOutcome::Failed {
+            provider: provider.to_string(),
+            error,
+            retry_at,
+        }
+    }
+
+    /// Read from the status store when there is one, so a record cleared
+    /// by a new login elsewhere takes effect on the next check.
+    fn status(&self, provider: &str) -> RefreshStatus {
+        let Some(store) = &self.schedule.status_store else {
+            return self.statuses.get(provider).cloned().unwrap_or_default();
+        };
+        store
+            .load(provider)
+            .unwrap_or_else(|err| {
+                tracing::warn!(provider, error = %err, "could not read token refresh status");
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_status(&mut self, provider: &str, status: RefreshStatus) {
+        let healthy = status == RefreshStatus::default();
+        if let Some(store) = &self.schedule.status_store {
+            let result = if healthy {
+                store.clear(provider)
+            } else {
+                store.save(provider, &status)
+            };
+            if let Err(err) = result {
+                tracing::warn!(provider, error = %err, "could not save token refresh status");
+            }
+        }
+        if healthy {
+            self.statuses.remove(provider);
+        } else {
+            self.statuses.insert(provider.to_string(), status);
+        }
+    }
+}
+
+/// Whether `token` expires within `window` of `now`. Tokens without an
+/// expiry are never due.
+fn expires_within(token: &Token, now: DateTime<Utc>, window: Duration) -> bool {
+    let window = chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+    token
+        .expires_at
+        .is_some_and(|expires_at| expires_at - now <= window)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::auth::backend::AuthBackend;
+    use crate::auth::device_code::DeviceCodeSession;
+    use crate::auth::service::{AuthPollResult, AuthStep};
+    use crate::auth::store::{FileTokenStore, TokenStore};
+
+    /// Refreshes the `mock` token to one valid for an hour, or fails with
+    /// a network error while `fail` is set.
+    #[derive(Default)]
+    struct MockBackend {
+        fail: AtomicBool,
+        refreshes: AtomicUsize,
+        delay: Option<Duration>,
+    }
+
+    #[async_trait]
+    impl AuthBackend for MockBackend {
+        fn aliases(&self) -> &[&str] {
+            &["mock"]
+        }
+
+        fn display_name(&self) -> &str {
+            "Mock"
+        }
+
+        fn store_key(&self) -> &str {
+            "mock"
+        }
+
+        async fn start_login(&self, _store: &Arc<dyn TokenStore>) -> Result<AuthStep, AuthError> {
+            Err(AuthError::Unsupported("mock login".into()))
+        }
+
+        async fn poll_device_code(
+            &self,
+            _store: &Arc<dyn TokenStore>,
+            _session: &DeviceCodeSession,
+        ) -> Result<AuthPollResult, AuthError> {
+            Err(AuthError::Unsupported("mock login".into()))
+        }
+
+        async fn complete_pkce(
+            &self,
+            _store: &Arc<dyn TokenStore>,
+            _code: &str,
+            _state: &str,
+        ) -> Result<Token, AuthError> {
+            Err(AuthError::Unsupported("mock login".into()))
+        }
+
+        fn get_status(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError> {
+            store.load("mock", "default")
+        }
+
+        fn logout(&self, store: &Arc<dyn TokenStore>) -> Result<(), AuthError> {
+            store.clear("mock", "default")
+        }
+
+        async fn refresh(
+            &self,
+            store: &Arc<dyn TokenStore>,
+            _token: &Token,
+        ) -> Result<Option<Token>, AuthError> {
+            let attempt = self.refreshes.fetch_add(1, Ordering::SeqCst) + 1;
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay).await;
+            }
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(AuthError::Network("connection reset".into()));
+            }
+            let refreshed = token(
+                &format!("refreshed-{attempt}"),
+                Some(chrono::Duration::hours(1)),
+            );
+            store.save("mock", "default", &refreshed)?;
+            Ok(Some(refreshed))
+        }
+    }
+
+    fn token(access_token: &str, expires_in: Option<chrono::Duration>) -> Token {
+        Token {
+            access_token: access_token.to_string(),
+            refresh_token: Some("refresh".to_string()),
+            id_token: None,
+            expires_at: expires_in.map(|expires_in| Utc::now() + expires_in),
+            last_refresh: None,
+            scopes: None,
+            account_id: None,
+        }
+    }
+
+    fn service_with(backend: Arc<MockBackend>) -> (TempDir, Arc<AuthService>) {
+        let dir = TempDir::new().unwrap();
+        let store = Arc::new(FileTokenStore::new(TokenStoreConfig::new(
+            dir.path().to_path_buf(),
+        )));
+        let mut service = AuthService::new(store);
+        service.register_backend(backend);
+        (dir, Arc::new(service))
+    }
+
+    fn save_token(service: &AuthService, expires_in: Option<chrono::Duration>) {
+        service
+            .store()
+            .save("mock", "default", &token("current", expires_in))
+            .unwrap();
+    }
+
+    fn scheduler(service: &Arc<AuthService>, schedule: RefreshSchedule) -> RefreshScheduler {
+        RefreshScheduler::new(Arc::clone(service), schedule)
+    }
+
+    #[tokio::test]
+    async fn refreshes_only_tokens_expiring_within_the_window() {
+        let backend = Arc::new(MockBackend::default());
+        let (_dir, service) = service_with(backend.clone());
+        let mut scheduler = scheduler(
+            &service,
+            RefreshSchedule::new(Duration::from_secs(60)).with_window(Duration::from_secs(600)),
+        );
+
+        save_token(&service, None);
+        assert!(scheduler.refresh_due(Utc::now()).await.is_empty());
+        save_token(&service, Some(chrono::Duration::hours(1)));
+        assert!(scheduler.refresh_due(Utc::now()).await.is_empty());
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 0);
+
+        save_token(&service, Some(chrono::Duration::minutes(5)));
+        let outcomes = scheduler.refresh_due(Utc::now()).await;
+
+        assert!(matches!(
+            outcomes.as_slice(),
+            [RefreshOutcome::Refreshed { provider, expires_at: Some(_) }] if provider == "mock"
+        ));
+        assert_eq!(
+            service.get_status("mock").unwrap().unwrap().access_token,
+            "refreshed-1"
+        );
+        // The refreshed token is good for an hour, so the next check skips it.
+        assert!(scheduler.refresh_due(Utc::now()).await.is_empty());
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_refreshes_back_off_exponentially_and_reset_on_success() {
+        let backend = Arc::new(MockBackend::default());
+        backend.fail.store(true, Ordering::SeqCst);
+        let (_dir, service) = service_with(backend.clone());
+        save_token(&service, Some(chrono::Duration::minutes(1)));
+        let outcomes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = outcomes.clone();
+        let mut scheduler = scheduler(
+            &service,
+            RefreshSchedule::new(Duration::from_secs(60))
+                .with_backoff(Duration::from_secs(30), Duration::from_secs(90))
+                .on_outcome(move |outcome| recorded.lock().unwrap().push(outcome.clone())),
+        );
+        let start = Utc::now();
+
+        scheduler.refresh_due(start).await;
+        // Still backing off: no attempt.
+        scheduler
+            .refresh_due(start + chrono::Duration::seconds(29))
+            .await;
+        let second = start + chrono::Duration::seconds(30);
+        scheduler.refresh_due(second).await;
+        let third = second + chrono::Duration::seconds(60);
+        scheduler.refresh_due(third).await;
+
+        let retry_ats = outcomes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|outcome| match outcome {
+                RefreshOutcome::Failed {
+                    retry_at, error, ..
+                } => {
+                    assert!(error.contains("connection reset"), "{error}");
+                    *retry_at
+                }
+                other => panic!("expected a failure, got {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            retry_ats,
+            vec![
+                start + chrono::Duration::seconds(30),
+                second + chrono::Duration::seconds(60),
+                // Capped at the maximum backoff.
+                third + chrono::Duration::seconds(90),
+            ]
+        );
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 3);
+        assert_eq!(scheduler.status("mock").consecutive_failures, 3);
+
+        backend.fail.store(false, Ordering::SeqCst);
+        let outcomes = scheduler
+            .refresh_due(third + chrono::Duration::seconds(90))
+            .await;
+        assert!(matches!(outcomes[0], RefreshOutcome::Refreshed { .. }));
+        assert_eq!(scheduler.status("mock"), RefreshStatus::default());
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_mark_the_credential_for_relogin_until_cleared() {
+        let backend = Arc::new(MockBackend::default());
+        backend.fail.store(true, Ordering::SeqCst);
+        let (dir, service) = service_with(backend.clone());
+        save_token(&service, Some(chrono::Duration::minutes(1)));
+        let statuses = RefreshStatusStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+        let mut scheduler = scheduler(
+            &service,
+            RefreshSchedule::new(Duration::from_secs(60))
+                .with_backoff(Duration::ZERO, Duration::ZERO)
+                .with_max_failures(2)
+                .with_status_store(statuses.clone()),
+        );
+        let now = Utc::now();
+
+        assert!(matches!(
+            scheduler.refresh_due(now).await[0],
+            RefreshOutcome::Failed { .. }
+        ));
+        let outcomes = scheduler.refresh_due(now).await;
+
+        assert!(matches!(
+            &outcomes[0],
+            RefreshOutcome::NeedsRelogin { provider, error }
+                if provider == "mock" && error.contains("connection reset")
+        ));
+        let status = statuses.load("mock").unwrap().expect("persisted status");
+        assert!(status.needs_relogin);
+        assert_eq!(status.consecutive_failures, 2);
+        assert!(scheduler.refresh_due(now).await.is_empty());
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 2);
+
+        // A new login clears the record and the task resumes refreshing.
+        statuses.clear("mock").unwrap();
+        backend.fail.store(false, Ordering::SeqCst);
+        assert!(matches!(
+            scheduler.refresh_due(now).await[0],
+            RefreshOutcome::Refreshed { .. }
+        ));
+        assert!(statuses.load("mock").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn concurrent_refreshes_share_one_backend_call() {
+        let backend = Arc::new(MockBackend {
+            delay: Some(Duration::from_millis(50)),
+            ..MockBackend::default()
+        });
+        let (_dir, service) = service_with(backend.clone());
+        save_token(&service, Some(chrono::Duration::minutes(1)));
+        let mut scheduler = scheduler(&service, RefreshSchedule::new(Duration::from_secs(60)));
+
+        let (lazy, scheduled) = tokio::join!(
+            service.refresh_token("mock"),
+            scheduler.refresh_due(Utc::now())
+        );
+
+        assert_eq!(lazy.unwrap().unwrap().access_token, "refreshed-1");
+        assert!(matches!(scheduled[0], RefreshOutcome::Refreshed { .. }));
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn auth_failures_for_a_backend_store_key_retry_with_the_refreshed_token() {
+        let backend = Arc::new(MockBackend::default());
+        let (_dir, service) = service_with(backend.clone());
+        save_token(&service, Some(chrono::Duration::hours(1)));
+        let config = crate::config::RociConfig::new().with_token_store(None);
+        service.refresh_on_auth_failure(&config);
+        let error = crate::error::RociError::Authentication("token expired".into());
+
+        let key = config.refresh_credential("mock", None, &error).await;
+        let unrelated = config.refresh_credential("openai", None, &error).await;
+
+        assert_eq!(key.as_deref(), Some("refreshed-1"));
+        assert_eq!(unrelated, None);
+        assert_eq!(backend.refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_task_runs_until_stopped() {
+        let backend = Arc::new(MockBackend::default());
+        let (_dir, service) = service_with(backend.clone());
+        save_token(&service, Some(chrono::Duration::minutes(1)));
+        let (sender, mut outcomes) = tokio::sync::mpsc::unbounded_channel();
+
+        let handle = service.start_refresh_task(
+            RefreshSchedule::new(Duration::from_millis(10)).on_outcome(move |outcome| {
+                let _ = sender.send(outcome.clone());
+            }),
+        );
+        let outcome = tokio::time::timeout(Duration::from_secs(2), outcomes.recv())
+            .await
+            .expect("refresh outcome")
+            .unwrap();
+        assert!(matches!(outcome, RefreshOutcome::Refreshed { .. }));
+
+        handle.stop().await;
+        assert!(outcomes.recv().await.is_none());
+    }
+}
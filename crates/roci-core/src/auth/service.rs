@@ -1,15 +1,20 @@
 //! Generic auth service orchestrator using registered backends.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt, Shared};
 
 use super::backend::AuthBackend;
 use super::device_code::DeviceCodeSession;
 use super::error::AuthError;
+use super::pending::PendingLoginStore;
+use super::refresh::{RefreshSchedule, RefreshScheduler, RefreshTaskHandle};
 use super::store::TokenStore;
 use super::token::Token;
+use crate::config::RociConfig;
 
 /// Initial step returned by [`AuthService::start_login`].
 ///
@@ -60,8 +65,12 @@ pub enum AuthPollResult {
 pub struct AuthService {
     store: Arc<dyn TokenStore>,
     backends: Vec<Arc<dyn AuthBackend>>,
+    /// Refreshes in flight, by backend store key.
+    refreshing: Arc<Mutex<HashMap<String, TokenRefresh>>>,
 }
 
+type TokenRefresh = Shared<BoxFuture<'static, Result<Option<Token>, AuthError>>>;
+
 pub type BackendStatus<'a> = (&'a str, &'a str, Result<Option<Token>, AuthError>);
 
 impl AuthService {
@@ -69,6 +78,7 @@ impl AuthService {
         Self {
             store,
             backends: Vec::new(),
+            refreshing: Arc::default(),
         }
     }
 
@@ -93,6 +103,51 @@ impl AuthService {
         backend.poll_device_code(&self.store, session).await
     }
 
+    /// Persist a device-code session so a later process can finish the login
+    /// with [`resume_device_code`](Self::resume_device_code).
+    pub fn defer_device_code(
+        &self,
+        pending: &PendingLoginStore,
+        provider: &str,
+        session: &DeviceCodeSession,
+    ) -> Result<(), AuthError> {
+        let backend = self.find_backend(provider)?;
+        pending.save(backend.store_key(), session)
+    }
+
+    /// Poll a deferred device-code session once.
+    ///
+    /// Returns `None` when no unexpired session is pending for `provider`.
+    /// A `user_code` that differs from the pending session's is rejected. The
+    /// session is kept while authorization is pending and removed otherwise.
+    pub async fn resume_device_code(
+        &self,
+        pending: &PendingLoginStore,
+        provider: &str,
+        user_code: Option<&str>,
+    ) -> Result<Option<AuthPollResult>, AuthError> {
+        let backend = self.find_backend(provider)?;
+        let Some(session) = pending.load(backend.store_key())? else {
+            return Ok(None);
+        };
+        if let Some(code) = user_code {
+            if !code.trim().eq_ignore_ascii_case(&session.user_code) {
+                return Err(AuthError::InvalidResponse(format!(
+                    "code {code} does not match the pending {} login",
+                    backend.display_name()
+                )));
+            }
+        }
+        let result = backend.poll_device_code(&self.store, &session).await?;
+        if !matches!(
+            result,
+            AuthPollResult::Pending | AuthPollResult::SlowDown { .. }
+        ) {
+            pending.clear(backend.store_key())?;
+        }
+        Ok(Some(result))
+    }
+
     /// Complete a PKCE authorization-code exchange.
     pub async fn complete_pkce(
         &self,
@@ -153,11 +208,103 @@ impl AuthService {
             .collect()
     }
 
+    /// Refresh the stored token for `provider` now and persist the result.
+    ///
+    /// Returns `Ok(None)` when the backend cannot refresh tokens. Concurrent
+    /// calls for one backend, from the refresh task or
+    /// [`refresh_on_auth_failure`](Self::refresh_on_auth_failure), share a
+    /// single refresh.
+    pub async fn refresh_token(&self, provider: &str) -> Result<Option<Token>, AuthError> {
+        let backend = Arc::clone(self.find_backend(provider)?);
+        let key = backend.store_key().to_string();
+        let call = {
+            let mut refreshing = self
+                .refreshing
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some(call) = refreshing.get(&key) {
+                call.clone()
+            } else {
+                let store = Arc::clone(&self.store);
+                let in_flight = Arc::clone(&self.refreshing);
+                let task_key = key.clone();
+                // Spawned so a dropped caller never cancels the refresh for
+                // the others.
+                let task = tokio::spawn(async move {
+                    let result = match backend.refreshable_token(&store) {
+                        Ok(Some(token)) => backend.refresh(&store, &token).await,
+                        Ok(None) => Err(AuthError::NotLoggedIn),
+                        Err(err) => Err(err),
+                    };
+                    in_flight
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .remove(&task_key);
+                    result
+                });
+                let call = async move {
+                    task.await.unwrap_or_else(|err| {
+                        Err(AuthError::Io(format!("token refresh task failed: {err}")))
+                    })
+                }
+                .boxed()
+                .shared();
+                refreshing.insert(key, call.clone());
+                call
+            }
+        };
+        call.await
+    }
+
+    /// Answer `config`'s auth failures by refreshing the token of the
+    /// backend whose store key is the failing provider (e.g.
+    /// `github-copilot`), retrying with the new access token.
+    ///
+    /// Providers without a matching backend are left to fail as before.
+    pub fn refresh_on_auth_failure(self: &Arc<Self>, config: &RociConfig) {
+        let service = Arc::clone(self);
+        config.on_auth_failure(move |provider, _error| {
+            let service = Arc::clone(&service);
+            let provider = provider.to_string();
+            async move {
+                let backend = service.find_backend(&provider).ok()?;
+                if backend.store_key() != provider {
+                    return None;
+                }
+                match service.refresh_token(&provider).await {
+                    Ok(token) => token.map(|token| token.access_token),
+                    Err(err) => {
+                        tracing::warn!(provider, error = %err, "token refresh after auth failure failed");
+                        None
+                    }
+                }
+            }
+        });
+    }
+
+    /// Start refreshing stored tokens in the background.
+    ///
+    /// Every `schedule` interval the task refreshes tokens expiring within
+    /// the schedule's window. Must be called inside a Tokio runtime. The
+    /// task runs until the returned handle is stopped or dropped.
+    pub fn start_refresh_task(self: &Arc<Self>, schedule: RefreshSchedule) -> RefreshTaskHandle {
+        RefreshScheduler::new(Arc::clone(self), schedule).spawn()
+    }
+
+    /// Token store key of the backend handling `provider`.
+    pub fn store_key(&self, provider: &str) -> Result<&str, AuthError> {
+        Ok(self.find_backend(provider)?.store_key())
+    }
+
     /// Access the underlying token store.
     pub fn store(&self) -> &Arc<dyn TokenStore> {
         &self.store
     }
 
+    pub(super) fn backends(&self) -> &[Arc<dyn AuthBackend>] {
+        &self.backends
+    }
+
     fn find_backend(&self, alias: &str) -> Result<&Arc<dyn AuthBackend>, AuthError> {
         let normalized = alias.to_lowercase();
         self.backends
@@ -127,7 +127,7 @@ fn default_roci_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(".roci"))
 }
 
-fn normalize_label(value: &str) -> String {
+pub(super) fn normalize_label(value: &str) -> String {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return "default".to_string();
@@ -0,0 +1,150 @@
+//! Chat-format overrides for local models with picky prompt templates.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RociError;
+
+/// What happens to system messages before a request is sent.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SystemMessageStrategy {
+    /// Send system messages as they are.
+    #[default]
+    Keep,
+    /// Merge system text into the start of the first user message.
+    SystemToFirstUser,
+    /// Leave system messages out.
+    DropSystem,
+    /// Render system text through a template, replacing `{system}` (or
+    /// appending when the template has no placeholder), and put the result
+    /// at the start of the first user message.
+    PrependSystemTag(String),
+}
+
+impl FromStr for SystemMessageStrategy {
+    type Err = RociError;
+
+    /// Parse `keep`, `system_to_first_user`, `drop_system`, or
+    /// `prepend_system_tag(<template>)`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(template) = value
+            .strip_prefix("prepend_system_tag(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Ok(Self::PrependSystemTag(template.to_string()));
+        }
+        match value {
+            "keep" => Ok(Self::Keep),
+            "system_to_first_user" => Ok(Self::SystemToFirstUser),
+            "drop_system" => Ok(Self::DropSystem),
+            _ => Err(RociError::Configuration(format!(
+                "unknown chat format '{value}' (expected keep, system_to_first_user, drop_system, or prepend_system_tag(<template>))"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SystemMessageStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keep => f.write_str("keep"),
+            Self::SystemToFirstUser => f.write_str("system_to_first_user"),
+            Self::DropSystem => f.write_str("drop_system"),
+            Self::PrependSystemTag(template) => write!(f, "prepend_system_tag({template})"),
+        }
+    }
+}
+
+impl TryFrom<String> for SystemMessageStrategy {
+    type Error = RociError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SystemMessageStrategy> for String {
+    fn from(value: SystemMessageStrategy) -> Self {
+        value.to_string()
+    }
+}
+
+/// How messages are reshaped for one model before serialization.
+///
+/// The default sends messages unchanged. Tool-result messages are never
+/// rewritten.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChatFormat {
+    #[serde(default)]
+    pub system: SystemMessageStrategy,
+    /// Wire role names to send instead of `system`, `user`, or `assistant`.
+    #[serde(default)]
+    pub role_names: BTreeMap<String, String>,
+}
+
+impl ChatFormat {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Parse a `role=name` list separated by commas, e.g.
+    /// `assistant=model,user=human`.
+    pub fn parse_role_names(value: &str) -> Result<BTreeMap<String, String>, RociError> {
+        value
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                let (role, name) = entry.split_once('=').ok_or_else(|| {
+                    RociError::Configuration(format!(
+                        "role name mapping '{entry}' must look like role=name"
+                    ))
+                })?;
+                Ok((role.trim().to_string(), name.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// Reject remappings of anything but `system`, `user`, and `assistant`;
+    /// tool results keep their role so they stay linked to their calls.
+    pub fn validate(&self) -> Result<(), RociError> {
+        for (role, name) in &self.role_names {
+            if !matches!(role.as_str(), "system" | "user" | "assistant") {
+                return Err(RociError::Configuration(format!(
+                    "chat format cannot rename role '{role}' (only system, user, and assistant)"
+                )));
+            }
+            if name.is_empty() {
+                return Err(RociError::Configuration(format!(
+                    "chat format role name for '{role}' is empty"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A [`ChatFormat`] for the models whose id matches a glob (`*` and `?`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatFormatRule {
+    pub model: String,
+    #[serde(flatten)]
+    pub format: ChatFormat,
+}
+
+impl ChatFormatRule {
+    pub fn new(model: impl Into<String>, format: ChatFormat) -> Self {
+        Self {
+            model: model.into(),
+            format,
+        }
+    }
+
+    pub fn matches(&self, model_id: &str) -> bool {
+        crate::security::filesystem::glob_matches(&self.model, model_id)
+    }
+}
@@ -0,0 +1,111 @@
+//! Replacement credentials after a provider rejects the current ones.
+//!
+//! A handler registered with
+//! [`RociConfig::on_auth_failure`](super::RociConfig::on_auth_failure) is
+//! asked for a new key when a call fails authentication, for example after
+//! the key was rotated. Concurrent failures for one provider share a single
+//! handler call.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+use crate::error::RociError;
+
+/// Supplies a replacement key for a provider after an authentication
+/// failure, or `None` to leave the failure as it is.
+pub type AuthFailureHandler =
+    Arc<dyn Fn(&str, &RociError) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+
+type Refresh = Shared<BoxFuture<'static, Option<String>>>;
+
+#[derive(Default)]
+pub(super) struct CredentialRefresh {
+    handler: RwLock<Option<AuthFailureHandler>>,
+    in_flight: Mutex<HashMap<String, Refresh>>,
+    /// Keys installed by the handler and not replaced since, per provider.
+    refreshed: Mutex<HashMap<String, String>>,
+}
+
+impl CredentialRefresh {
+    pub(super) fn set_handler(&self, handler: AuthFailureHandler) {
+        *self.handler.write().unwrap_or_else(PoisonError::into_inner) = Some(handler);
+    }
+
+    pub(super) fn has_handler(&self) -> bool {
+        self.handler
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    pub(super) fn refreshed_key(&self, provider: &str) -> Option<String> {
+        lock(&self.refreshed).get(provider).cloned()
+    }
+
+    /// Stop treating the provider's key as refreshed, because it was set
+    /// some other way.
+    pub(super) fn forget(&self, provider: &str) {
+        lock(&self.refreshed).remove(provider);
+    }
+
+    /// Ask the handler for a replacement after a call sent with
+    /// `failed_key` was rejected, joining a request already in flight for
+    /// the provider. A call sent before the last refresh just gets the
+    /// refreshed key.
+    ///
+    /// The handler runs on its own task, so a caller that is dropped never
+    /// cancels it for the others. An accepted key is written to `api_keys`.
+    pub(super) async fn refresh(
+        self: &Arc<Self>,
+        api_keys: &Arc<RwLock<HashMap<String, String>>>,
+        provider: &str,
+        failed_key: Option<&str>,
+        error: &RociError,
+    ) -> Option<String> {
+        let handler = self
+            .handler
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()?;
+        let call = {
+            let mut in_flight = lock(&self.in_flight);
+            if let Some(call) = in_flight.get(provider) {
+                call.clone()
+            } else {
+                if let Some(key) = self
+                    .refreshed_key(provider)
+                    .filter(|key| failed_key != Some(key.as_str()))
+                {
+                    return Some(key);
+                }
+                let upstream = handler(provider, error);
+                let refresh = Arc::clone(self);
+                let api_keys = Arc::clone(api_keys);
+                let provider = provider.to_string();
+                let task_provider = provider.clone();
+                let task = tokio::spawn(async move {
+                    let key = upstream.await.filter(|key| !key.is_empty());
+                    if let Some(key) = &key {
+                        api_keys
+                            .write()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .insert(task_provider.clone(), key.clone());
+                        lock(&refresh.refreshed).insert(task_provider.clone(), key.clone());
+                    }
+                    lock(&refresh.in_flight).remove(&task_provider);
+                    key
+                });
+                let call = async move { task.await.ok().flatten() }.boxed().shared();
+                in_flight.insert(provider, call.clone());
+                call
+            }
+        };
+        call.await
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
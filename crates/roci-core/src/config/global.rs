@@ -0,0 +1,75 @@
+//! Process-wide configuration that can be replaced at runtime.
+//!
+//! # Consistency model
+//!
+//! [`RociConfig::global`] hands out a snapshot. Reloading swaps in a new
+//! config; it never edits the old one, so a snapshot taken before the reload
+//! keeps the old keys and URLs for as long as it is held. Runners built with
+//! [`LoopRunner::with_global_config`](crate::agent_loop::LoopRunner::with_global_config)
+//! take one snapshot per run at start, so a run sees a single config from
+//! start to finish and runs started after a reload see the new one.
+//!
+//! Snapshots share state with their source: `set_api_key` on a snapshot is
+//! visible to every holder of that snapshot, including in-flight runs. Rotate
+//! credentials with [`RociConfig::global_reload`] instead.
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use super::RociConfig;
+
+type ReloadHook = Arc<dyn Fn() + Send + Sync>;
+
+static GLOBAL: RwLock<Option<RociConfig>> = RwLock::new(None);
+static RELOAD_HOOKS: Mutex<Vec<ReloadHook>> = Mutex::new(Vec::new());
+
+impl RociConfig {
+    /// Snapshot of the process-wide config, loaded with
+    /// [`from_env`](Self::from_env) on first use.
+    pub fn global() -> RociConfig {
+        if let Some(config) = GLOBAL
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_ref()
+        {
+            return config.clone();
+        }
+        GLOBAL
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_or_insert_with(Self::from_env)
+            .clone()
+    }
+
+    /// Atomically replace the process-wide config and return the previous
+    /// one, if it was ever loaded.
+    ///
+    /// Existing snapshots are unaffected. Hooks registered with
+    /// [`on_global_reload`](Self::on_global_reload) run after the swap, so
+    /// caches they clear are refilled from the new config.
+    pub fn global_reload(config: RociConfig) -> Option<RociConfig> {
+        let previous = GLOBAL
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .replace(config);
+        let hooks = RELOAD_HOOKS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        for hook in hooks {
+            hook();
+        }
+        previous
+    }
+
+    /// Run `hook` after every [`global_reload`](Self::global_reload).
+    ///
+    /// For caches derived from config values, such as probed capabilities
+    /// or credential-backed token sources. Hooks run on the reloading thread
+    /// and should be quick.
+    pub fn on_global_reload(hook: impl Fn() + Send + Sync + 'static) {
+        RELOAD_HOOKS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(Arc::new(hook));
+    }
+}
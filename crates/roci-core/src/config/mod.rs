@@ -1,11 +1,27 @@
-//! Configuration system (layered: code > env > credential file).
+//! Configuration system (layered: code > env > credential file), with a
+//! reloadable process-wide instance.
 
 use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
 use crate::auth::store::TokenStore;
+use crate::error::RociError;
 use crate::models::ProviderKey;
+use crate::provider::offline::is_loopback_url;
+use crate::provider::ToolLimits;
+
+mod chat_format;
+mod credential_refresh;
+mod global;
+
+pub use chat_format::{ChatFormat, ChatFormatRule, SystemMessageStrategy};
+pub use credential_refresh::AuthFailureHandler;
+
+use credential_refresh::CredentialRefresh;
 
 /// Layered configuration for Roci.
 ///
@@ -17,7 +33,15 @@ pub struct RociConfig {
     api_keys: Arc<RwLock<HashMap<String, String>>>,
     base_urls: Arc<RwLock<HashMap<String, String>>>,
     account_ids: Arc<RwLock<HashMap<String, String>>>,
+    google_vertex: Arc<RwLock<Option<GoogleVertexConfig>>>,
+    anthropic_compat: Arc<RwLock<AnthropicCompatConfig>>,
+    chat_formats: Arc<RwLock<HashMap<String, Vec<ChatFormatRule>>>>,
+    request_compression: Arc<RwLock<HashMap<String, RequestCompression>>>,
+    tool_limits: Arc<RwLock<HashMap<String, ToolLimits>>>,
+    credential_refresh: Arc<CredentialRefresh>,
     token_store: Option<Arc<dyn TokenStore>>,
+    capability_probing: Arc<AtomicBool>,
+    offline: Arc<AtomicBool>,
 }
 
 impl fmt::Debug for RociConfig {
@@ -26,7 +50,18 @@ impl fmt::Debug for RociConfig {
             .field("api_keys", &self.api_keys)
             .field("base_urls", &self.base_urls)
             .field("account_ids", &self.account_ids)
+            .field("google_vertex", &self.google_vertex)
+            .field("anthropic_compat", &self.anthropic_compat)
+            .field("chat_formats", &self.chat_formats)
+            .field("request_compression", &self.request_compression)
+            .field("tool_limits", &self.tool_limits)
+            .field(
+                "auth_failure_handler",
+                &self.credential_refresh.has_handler(),
+            )
             .field("token_store", &self.token_store.as_ref().map(|_| ".."))
+            .field("capability_probing", &self.capability_probing_enabled())
+            .field("offline", &self.is_offline())
             .finish()
     }
 }
@@ -37,6 +72,141 @@ impl Default for RociConfig {
     }
 }
 
+/// Vertex AI target for the Google provider.
+///
+/// When set, Google models are served from Vertex AI with service-account
+/// OAuth instead of the Gemini API key endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoogleVertexConfig {
+    pub project: String,
+    /// Vertex region such as `us-central1`, or `global`.
+    pub location: String,
+    /// Service-account JSON key file.
+    pub credentials_path: Option<PathBuf>,
+}
+
+impl GoogleVertexConfig {
+    pub const DEFAULT_LOCATION: &'static str = "us-central1";
+
+    pub fn new(project: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            location: Self::DEFAULT_LOCATION.to_string(),
+            credentials_path: None,
+        }
+    }
+
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = location.into();
+        self
+    }
+
+    pub fn with_credentials_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.credentials_path = Some(path.into());
+        self
+    }
+}
+
+/// Request-shape overrides for `anthropic-compatible` endpoints (GLM,
+/// MiniMax, ...) that implement only part of the Messages API.
+///
+/// The default describes Anthropic itself, so leaving it unset changes
+/// nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnthropicCompatConfig {
+    /// Send tool definitions. When false, tools are left out and earlier
+    /// tool calls and results are replayed as text.
+    pub supports_tools: bool,
+    /// Send `tool_choice` alongside tools.
+    pub supports_tool_choice: bool,
+    /// Send the `thinking` parameter and replay thinking blocks.
+    pub supports_thinking: bool,
+    /// Upper bound for `max_tokens`; a thinking budget is lowered to fit.
+    pub max_output_tokens: Option<u32>,
+    pub system_prompt: SystemPromptPlacement,
+}
+
+/// Gzip for large request bodies sent to one provider.
+///
+/// Bodies smaller than `min_bytes` go out uncompressed. An endpoint that
+/// rejects the encoding is sent plain bodies for the rest of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestCompression {
+    pub min_bytes: usize,
+}
+
+impl RequestCompression {
+    /// Smallest body worth compressing by default.
+    pub const DEFAULT_MIN_BYTES: usize = 64 * 1024;
+
+    pub fn new(min_bytes: usize) -> Self {
+        Self { min_bytes }
+    }
+}
+
+impl Default for RequestCompression {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MIN_BYTES)
+    }
+}
+
+/// Where the system prompt goes in a Messages request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SystemPromptPlacement {
+    /// The top-level `system` field, as Anthropic expects.
+    #[default]
+    TopLevel,
+    /// A leading `{"role": "system"}` message.
+    FirstMessage,
+}
+
+impl Default for AnthropicCompatConfig {
+    fn default() -> Self {
+        Self {
+            supports_tools: true,
+            supports_tool_choice: true,
+            supports_thinking: true,
+            max_output_tokens: None,
+            system_prompt: SystemPromptPlacement::TopLevel,
+        }
+    }
+}
+
+impl AnthropicCompatConfig {
+    /// Read the `ANTHROPIC_COMPAT_*` overrides through `lookup`. Unset or
+    /// unparseable values keep their Anthropic default.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |name: &str, default: bool| match lookup(name)
+            .map(|value| value.trim().to_ascii_lowercase())
+        {
+            Some(value) if matches!(value.as_str(), "0" | "false" | "off" | "no") => false,
+            Some(value) if matches!(value.as_str(), "1" | "true" | "on" | "yes") => true,
+            _ => default,
+        };
+        let defaults = Self::default();
+        Self {
+            supports_tools: flag("ANTHROPIC_COMPAT_SUPPORTS_TOOLS", defaults.supports_tools),
+            supports_tool_choice: flag(
+                "ANTHROPIC_COMPAT_SUPPORTS_TOOL_CHOICE",
+                defaults.supports_tool_choice,
+            ),
+            supports_thinking: flag(
+                "ANTHROPIC_COMPAT_SUPPORTS_THINKING",
+                defaults.supports_thinking,
+            ),
+            max_output_tokens: lookup("ANTHROPIC_COMPAT_MAX_OUTPUT_TOKENS")
+                .and_then(|value| value.trim().parse().ok()),
+            system_prompt: match lookup("ANTHROPIC_COMPAT_SYSTEM_PROMPT")
+                .map(|value| value.trim().to_ascii_lowercase())
+                .as_deref()
+            {
+                Some("first_message" | "message") => SystemPromptPlacement::FirstMessage,
+                _ => defaults.system_prompt,
+            },
+        }
+    }
+}
+
 fn get_from_map(
     map: &RwLock<HashMap<String, String>>,
     provider: &str,
@@ -63,7 +233,15 @@ impl RociConfig {
             api_keys: Arc::new(RwLock::new(HashMap::new())),
             base_urls: Arc::new(RwLock::new(HashMap::new())),
             account_ids: Arc::new(RwLock::new(HashMap::new())),
+            google_vertex: Arc::new(RwLock::new(None)),
+            anthropic_compat: Arc::new(RwLock::new(AnthropicCompatConfig::default())),
+            chat_formats: Arc::new(RwLock::new(HashMap::new())),
+            request_compression: Arc::new(RwLock::new(HashMap::new())),
+            tool_limits: Arc::new(RwLock::new(HashMap::new())),
+            credential_refresh: Arc::new(CredentialRefresh::default()),
             token_store: Some(Arc::new(crate::auth::store::FileTokenStore::new_default())),
+            capability_probing: Arc::new(AtomicBool::new(true)),
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -95,6 +273,8 @@ impl RociConfig {
             ("GROK_API_KEY", ProviderKey::Grok),
             ("GROQ_API_KEY", ProviderKey::Groq),
             ("MISTRAL_API_KEY", ProviderKey::Mistral),
+            ("OLLAMA_API_KEY", ProviderKey::Ollama),
+            ("LMSTUDIO_API_KEY", ProviderKey::LmStudio),
             ("AZURE_OPENAI_API_KEY", ProviderKey::Azure),
         ];
 
@@ -106,6 +286,7 @@ impl RociConfig {
         for (env_var, provider) in [
             ("TOGETHER_API_KEY", "together"),
             ("OPENROUTER_API_KEY", "openrouter"),
+            ("CODESTRAL_API_KEY", "codestral"),
         ] {
             if let Ok(key) = std::env::var(env_var) {
                 config.set_api_key(provider, key);
@@ -121,6 +302,7 @@ impl RociConfig {
             ("ANTHROPIC_BASE_URL", ProviderKey::Anthropic),
             ("OLLAMA_BASE_URL", ProviderKey::Ollama),
             ("LMSTUDIO_BASE_URL", ProviderKey::LmStudio),
+            ("MISTRAL_BASE_URL", ProviderKey::Mistral),
             ("AZURE_OPENAI_ENDPOINT", ProviderKey::Azure),
         ];
 
@@ -129,6 +311,35 @@ impl RociConfig {
                 config.set_base_url(provider.as_str(), url);
             }
         }
+        if let Ok(url) = std::env::var("CODESTRAL_BASE_URL") {
+            config.set_base_url("codestral", url);
+        }
+
+        if let Some(project) = non_empty_env("GOOGLE_VERTEX_PROJECT") {
+            let mut vertex = GoogleVertexConfig::new(project);
+            if let Some(location) = non_empty_env("GOOGLE_VERTEX_LOCATION") {
+                vertex = vertex.with_location(location);
+            }
+            if let Some(path) = non_empty_env("GOOGLE_APPLICATION_CREDENTIALS") {
+                vertex = vertex.with_credentials_path(path);
+            }
+            config.set_google_vertex(Some(vertex));
+        }
+
+        config.set_anthropic_compat(AnthropicCompatConfig::from_lookup(non_empty_env));
+
+        if let Ok(value) = std::env::var("ROCI_CAPABILITY_PROBING") {
+            let value = value.trim().to_ascii_lowercase();
+            if matches!(value.as_str(), "0" | "false" | "off" | "no") {
+                config.set_capability_probing(false);
+            }
+        }
+        if let Ok(value) = std::env::var("ROCI_OFFLINE") {
+            let value = value.trim().to_ascii_lowercase();
+            if matches!(value.as_str(), "1" | "true" | "on" | "yes") {
+                config.set_offline(true);
+            }
+        }
 
         config
     }
@@ -138,6 +349,48 @@ impl RociConfig {
             .write()
             .unwrap()
             .insert(provider.to_string(), key);
+        self.credential_refresh.forget(provider);
+    }
+
+    /// Register the handler asked for a replacement key when a provider
+    /// rejects its credentials, replacing any earlier one.
+    ///
+    /// The handler gets the provider key and the error, and may fetch a new
+    /// key (from a secrets manager, say) before answering. A returned key is
+    /// stored like [`set_api_key`](Self::set_api_key) and the failed call is
+    /// retried with it once; `None` leaves the failure as it is.
+    /// Concurrent failures for one provider share a single handler call.
+    pub fn on_auth_failure<F, Fut>(&self, handler: F)
+    where
+        F: Fn(&str, &RociError) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        let handler: AuthFailureHandler =
+            Arc::new(move |provider, error| Box::pin(handler(provider, error)));
+        self.credential_refresh.set_handler(handler);
+    }
+
+    /// Ask the [`on_auth_failure`](Self::on_auth_failure) handler for a key
+    /// to retry with, after a call sent with `failed_key` (`None` for the
+    /// provider's own key) was rejected.
+    ///
+    /// Returns `None` without a handler or when it declines. A call sent
+    /// before the last refresh gets the refreshed key without asking again.
+    pub async fn refresh_credential(
+        &self,
+        provider: &str,
+        failed_key: Option<&str>,
+        error: &RociError,
+    ) -> Option<String> {
+        self.credential_refresh
+            .refresh(&self.api_keys, provider, failed_key, error)
+            .await
+    }
+
+    /// The key a refresh installed for `provider`, unless it was set some
+    /// other way since.
+    pub fn refreshed_api_key(&self, provider: &str) -> Option<String> {
+        self.credential_refresh.refreshed_key(provider)
     }
 
     /// Resolve an API key for a provider.
@@ -201,12 +454,141 @@ impl RociConfig {
         self.get_account_id(provider.as_str())
     }
 
-    /// Check if a provider has credentials configured (explicit key or token store).
+    /// Route Google models through Vertex AI, or back to the Gemini API with `None`.
+    pub fn set_google_vertex(&self, vertex: Option<GoogleVertexConfig>) {
+        *self.google_vertex.write().unwrap() = vertex;
+    }
+
+    pub fn google_vertex(&self) -> Option<GoogleVertexConfig> {
+        self.google_vertex.read().ok()?.clone()
+    }
+
+    /// Adapt `anthropic-compatible` requests to what the endpoint accepts.
+    pub fn set_anthropic_compat(&self, compat: AnthropicCompatConfig) {
+        *self.anthropic_compat.write().unwrap() = compat;
+    }
+
+    pub fn anthropic_compat(&self) -> AnthropicCompatConfig {
+        self.anthropic_compat
+            .read()
+            .map(|compat| *compat)
+            .unwrap_or_default()
+    }
+
+    /// Chat-format overrides for `provider`'s models, tried in order; the
+    /// first rule whose glob matches the model id applies.
+    pub fn set_chat_format_rules(&self, provider: &str, rules: Vec<ChatFormatRule>) {
+        self.chat_formats
+            .write()
+            .unwrap()
+            .insert(provider.to_string(), rules);
+    }
+
+    /// The chat format of the first rule for `provider` matching `model_id`.
+    pub fn chat_format_for(&self, provider: &str, model_id: &str) -> Option<ChatFormat> {
+        self.chat_formats
+            .read()
+            .ok()?
+            .get(provider)?
+            .iter()
+            .find(|rule| rule.matches(model_id))
+            .map(|rule| rule.format.clone())
+    }
+
+    /// Gzip `provider`'s large request bodies, or stop with `None`. Only
+    /// providers that support it (OpenAI and Anthropic) read this.
+    pub fn set_request_compression(&self, provider: &str, compression: Option<RequestCompression>) {
+        let mut map = self.request_compression.write().unwrap();
+        match compression {
+            Some(compression) => map.insert(provider.to_string(), compression),
+            None => map.remove(provider),
+        };
+    }
+
+    pub fn request_compression_for(&self, provider: &str) -> Option<RequestCompression> {
+        self.request_compression.read().ok()?.get(provider).copied()
+    }
+
+    /// Replace the tool limits `provider`'s models report, or go back to
+    /// them with `None`.
+    pub fn set_tool_limits(&self, provider: &str, limits: Option<ToolLimits>) {
+        let mut map = self.tool_limits.write().unwrap();
+        match limits {
+            Some(limits) => map.insert(provider.to_string(), limits),
+            None => map.remove(provider),
+        };
+    }
+
+    pub fn tool_limits_for(&self, provider: &str) -> Option<ToolLimits> {
+        self.tool_limits.read().ok()?.get(provider).copied()
+    }
+
+    /// Enable or disable capability probing of self-hosted endpoints.
+    ///
+    /// Disable for air-gapped setups or when probe requests are unwanted;
+    /// providers then use their static capability defaults.
+    pub fn set_capability_probing(&self, enabled: bool) {
+        self.capability_probing.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether capability probing is enabled (default `true`).
+    pub fn capability_probing_enabled(&self) -> bool {
+        self.capability_probing.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable offline mode.
+    ///
+    /// In offline mode, providers whose resolved base URL is not a loopback
+    /// address fail at creation instead of attempting a connection.
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    /// Whether offline mode is enabled (default `false`).
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// Whether `provider` needs a credential to be usable.
+    ///
+    /// False for providers whose [`ProviderKey::requires_credentials`] is
+    /// false and for `openai-compatible` pointed at a loopback base URL
+    /// (its own, or the OpenAI one it falls back to).
+    /// Unknown providers are assumed to need one.
+    pub fn requires_credentials(&self, provider: &str) -> bool {
+        match ProviderKey::parse(provider) {
+            Some(ProviderKey::OpenAiCompatible) => !self
+                .get_base_url_for(ProviderKey::OpenAiCompatible)
+                .or_else(|| self.get_base_url_for(ProviderKey::OpenAi))
+                .is_some_and(|base_url| is_loopback_url(&base_url)),
+            Some(key) => key.requires_credentials(),
+            None => true,
+        }
+    }
+
+    /// Check if a provider has credentials configured (explicit key, token
+    /// store, or a Vertex AI target for Google). Providers that need no
+    /// credential always have them.
     pub fn has_credentials(&self, provider: &str) -> bool {
+        if !self.requires_credentials(provider) {
+            return true;
+        }
+        if ProviderKey::parse(provider) == Some(ProviderKey::Google)
+            && self.google_vertex().is_some()
+        {
+            return true;
+        }
         self.get_api_key(provider).is_some()
     }
 }
 
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +597,35 @@ mod tests {
     use chrono::{Duration, Utc};
     use tempfile::TempDir;
 
+    #[test]
+    fn anthropic_compat_defaults_to_anthropic_and_reads_overrides() {
+        assert_eq!(
+            AnthropicCompatConfig::from_lookup(|_| None),
+            AnthropicCompatConfig::default()
+        );
+
+        let vars = HashMap::from([
+            ("ANTHROPIC_COMPAT_SUPPORTS_TOOLS", "false"),
+            ("ANTHROPIC_COMPAT_SUPPORTS_THINKING", "0"),
+            ("ANTHROPIC_COMPAT_SUPPORTS_TOOL_CHOICE", "maybe"),
+            ("ANTHROPIC_COMPAT_MAX_OUTPUT_TOKENS", "8192"),
+            ("ANTHROPIC_COMPAT_SYSTEM_PROMPT", "first_message"),
+        ]);
+        let compat =
+            AnthropicCompatConfig::from_lookup(|name| vars.get(name).map(|v| v.to_string()));
+
+        assert_eq!(
+            compat,
+            AnthropicCompatConfig {
+                supports_tools: false,
+                supports_tool_choice: true,
+                supports_thinking: false,
+                max_output_tokens: Some(8192),
+                system_prompt: SystemPromptPlacement::FirstMessage,
+            }
+        );
+    }
+
     fn config_with_temp_store(dir: &std::path::Path) -> RociConfig {
         let store = FileTokenStore::new(TokenStoreConfig::new(dir.to_path_buf()));
         RociConfig::new().with_token_store(Some(Arc::new(store)))
@@ -343,6 +754,132 @@ mod tests {
         );
     }
 
+    #[test]
+    fn offline_defaults_off_and_toggles_across_clones() {
+        let config = RociConfig::new().with_token_store(None);
+        assert!(!config.is_offline());
+
+        let clone = config.clone();
+        clone.set_offline(true);
+
+        assert!(config.is_offline());
+    }
+
+    #[test]
+    fn capability_probing_defaults_on_and_toggles_across_clones() {
+        let config = RociConfig::new().with_token_store(None);
+        let clone = config.clone();
+        assert!(config.capability_probing_enabled());
+
+        clone.set_capability_probing(false);
+
+        assert!(!config.capability_probing_enabled());
+    }
+
+    #[test]
+    fn chat_format_rules_pick_the_first_matching_glob_per_provider() {
+        let config = RociConfig::new().with_token_store(None);
+        let merge = ChatFormat {
+            system: SystemMessageStrategy::SystemToFirstUser,
+            ..ChatFormat::default()
+        };
+        let drop = ChatFormat {
+            system: SystemMessageStrategy::DropSystem,
+            ..ChatFormat::default()
+        };
+        config.set_chat_format_rules(
+            "ollama",
+            vec![
+                ChatFormatRule::new("gemma*", merge.clone()),
+                ChatFormatRule::new("*", drop.clone()),
+            ],
+        );
+
+        assert_eq!(config.chat_format_for("ollama", "gemma2:9b"), Some(merge));
+        assert_eq!(config.chat_format_for("ollama", "llama3.3"), Some(drop));
+        assert_eq!(config.chat_format_for("lmstudio", "gemma2:9b"), None);
+    }
+
+    #[test]
+    fn chat_format_strategies_round_trip_through_strings() {
+        for value in [
+            "keep",
+            "system_to_first_user",
+            "drop_system",
+            "prepend_system_tag(<<SYS>>{system}<</SYS>>)",
+        ] {
+            let strategy: SystemMessageStrategy = value.parse().unwrap();
+            assert_eq!(strategy.to_string(), value);
+        }
+        assert!("merge".parse::<SystemMessageStrategy>().is_err());
+
+        let rule: ChatFormatRule = serde_json::from_value(serde_json::json!({
+            "model": "phi-*",
+            "system": "prepend_system_tag(<|system|>{system}<|end|>)",
+            "role_names": { "assistant": "model" },
+        }))
+        .unwrap();
+        assert!(rule.matches("phi-3-mini"));
+        assert_eq!(
+            rule.format.system,
+            SystemMessageStrategy::PrependSystemTag("<|system|>{system}<|end|>".to_string())
+        );
+        assert_eq!(rule.format.role_names["assistant"], "model");
+
+        let tool_rename = ChatFormat {
+            role_names: ChatFormat::parse_role_names("tool=function").unwrap(),
+            ..ChatFormat::default()
+        };
+        assert!(tool_rename.validate().is_err());
+        assert!(ChatFormat::parse_role_names("assistant").is_err());
+    }
+
+    #[test]
+    fn local_providers_have_credentials_without_a_key() {
+        let config = RociConfig::new().with_token_store(None);
+
+        assert!(!config.requires_credentials("ollama"));
+        assert!(config.has_credentials("ollama"));
+        assert!(config.has_credentials("lmstudio"));
+        assert!(config.requires_credentials("openai"));
+        assert!(!config.has_credentials("openai"));
+        assert!(config.requires_credentials("some-unknown-provider"));
+    }
+
+    #[test]
+    fn openai_compatible_needs_no_key_only_on_loopback() {
+        let config = RociConfig::new().with_token_store(None);
+        assert!(!config.has_credentials("openai-compatible"));
+
+        config.set_base_url("openai-compatible", "http://127.0.0.1:8000/v1".to_string());
+        assert!(config.has_credentials("openai-compatible"));
+
+        config.set_base_url(
+            "openai-compatible",
+            "https://llm.example.com/v1".to_string(),
+        );
+        assert!(!config.has_credentials("openai-compatible"));
+    }
+
+    #[test]
+    fn google_vertex_counts_as_google_credentials() {
+        let config = RociConfig::new().with_token_store(None);
+        assert!(!config.has_credentials("google"));
+
+        let vertex = GoogleVertexConfig::new("my-project")
+            .with_credentials_path("/keys/sa.json")
+            .with_location("europe-west4");
+        config.set_google_vertex(Some(vertex.clone()));
+
+        assert_eq!(config.google_vertex(), Some(vertex));
+        assert!(config.has_credentials("google"));
+        assert!(!config.has_credentials("openai"));
+        assert_eq!(
+            GoogleVertexConfig::new("p").location,
+            GoogleVertexConfig::DEFAULT_LOCATION
+        );
+    }
+
     #[test]
     fn github_copilot_falls_back_to_token_store() {
         let dir = TempDir::new().unwrap();
@@ -357,4 +894,68 @@ mod tests {
             Some("copilot-token".to_string()),
         );
     }
+
+    #[tokio::test]
+    async fn concurrent_auth_failures_share_one_handler_call() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let config = RociConfig::new().with_token_store(None);
+        config.set_api_key("openai", "old-key".to_string());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = calls.clone();
+        config.on_auth_failure(move |provider, _error| {
+            assert_eq!(provider, "openai");
+            handler_calls.fetch_add(1, Ordering::SeqCst);
+            async {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                Some("new-key".to_string())
+            }
+        });
+        let error = RociError::api(401, "invalid api key");
+
+        let keys = futures::future::join_all(
+            (0..8).map(|_| config.refresh_credential("openai", None, &error)),
+        )
+        .await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(keys.iter().all(|key| key.as_deref() == Some("new-key")));
+        assert_eq!(config.get_api_key("openai").as_deref(), Some("new-key"));
+        assert_eq!(
+            config.refreshed_api_key("openai").as_deref(),
+            Some("new-key")
+        );
+
+        // A call sent before the refresh reuses its key; one sent with the
+        // refreshed key asks again.
+        let stale = config.refresh_credential("openai", None, &error).await;
+        assert_eq!(stale.as_deref(), Some("new-key"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        config
+            .refresh_credential("openai", Some("new-key"), &error)
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        config.set_api_key("openai", "manual-key".to_string());
+        assert_eq!(config.refreshed_api_key("openai"), None);
+    }
+
+    #[tokio::test]
+    async fn declined_or_missing_auth_failure_handler_keeps_the_key() {
+        let config = RociConfig::new().with_token_store(None);
+        config.set_api_key("anthropic", "old-key".to_string());
+        let error = RociError::Authentication("expired".to_string());
+
+        assert_eq!(
+            config.refresh_credential("anthropic", None, &error).await,
+            None
+        );
+        config.on_auth_failure(|_provider, _error| async { None });
+        assert_eq!(
+            config.refresh_credential("anthropic", None, &error).await,
+            None
+        );
+        assert_eq!(config.get_api_key("anthropic").as_deref(), Some("old-key"));
+        assert_eq!(config.refreshed_api_key("anthropic"), None);
+    }
 }
@@ -10,12 +10,14 @@
 //! - [`budget`] — token budget configuration, snapshots, and decision types
 //! - [`overflow`] — overflow detection and classification contracts
 //! - [`recovery`] — overflow recovery policy, decision types, and event contracts
+//! - [`report`] — per-message breakdown of context-window usage by origin
 //! - [`compaction`] — compaction-preparation helpers and types
 
 pub mod budget;
 pub mod compaction;
 pub mod overflow;
 pub mod recovery;
+pub mod report;
 pub mod tokens;
 
 pub use self::budget::{
@@ -32,6 +34,7 @@ pub use self::recovery::{
     AbortReason, CompactionProgress, OverflowRecoveryPolicy, RecoveryAction, RecoveryDecision,
     RecoveryEvent, RecoveryReason, RecoveryState,
 };
+pub use self::report::{ContextReport, ContextReportEntry};
 pub use self::tokens::{
     estimate_context_usage, estimate_message_tokens, estimate_text_tokens, ContextUsage,
     ContextUsageSnapshot, CountAccuracy, HeuristicTokenCounter, SnapshotConfidence, SnapshotSource,
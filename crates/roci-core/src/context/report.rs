@@ -0,0 +1,233 @@
+//! Per-message breakdown of what a request puts in the context window.
+//!
+//! [`ContextReport::analyze`] attributes each message's characters and
+//! heuristic token estimate to its [`MessageOrigin`], so hosts can show which
+//! system prompt sections, context files, or tool results dominate a request.
+
+use crate::models::LanguageModel;
+use crate::types::{ContentPart, MessageOrigin, ModelMessage, Role};
+
+use super::tokens::estimate_message_tokens;
+
+/// One analyzed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextReportEntry {
+    /// Position in the analyzed message list.
+    pub index: usize,
+    pub role: Role,
+    pub origin: MessageOrigin,
+    /// Characters of message content, including serialized tool payloads.
+    pub chars: usize,
+    /// Heuristic token estimate, including per-message framing.
+    pub tokens: usize,
+}
+
+/// Token and character statistics for a full message list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextReport {
+    pub model: LanguageModel,
+    pub entries: Vec<ContextReportEntry>,
+    pub total_chars: usize,
+    /// Sum of entry tokens; equals [`estimate_context_usage`](super::estimate_context_usage)
+    /// for the same messages.
+    pub total_tokens: usize,
+    /// Window the totals are compared against, when known.
+    pub context_window: Option<usize>,
+}
+
+impl ContextReport {
+    /// Suggested count for hosts that flag the
+    /// [`largest_contributors`](Self::largest_contributors).
+    pub const LARGEST_CONTRIBUTORS: usize = 3;
+
+    /// Analyze `messages` as they would be sent to `model`.
+    ///
+    /// The model's context window is not known without its provider; set it
+    /// with [`with_context_window`](Self::with_context_window).
+    pub fn analyze(messages: &[ModelMessage], model: &LanguageModel) -> Self {
+        let entries = messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| ContextReportEntry {
+                index,
+                role: message.role,
+                origin: message.effective_origin(),
+                chars: message_chars(message),
+                tokens: estimate_message_tokens(message),
+            })
+            .collect::<Vec<_>>();
+        Self {
+            model: model.clone(),
+            total_chars: entries.iter().map(|entry| entry.chars).sum(),
+            total_tokens: entries.iter().map(|entry| entry.tokens).sum(),
+            entries,
+            context_window: None,
+        }
+    }
+
+    pub fn with_context_window(mut self, context_window: usize) -> Self {
+        self.context_window = Some(context_window);
+        self
+    }
+
+    /// Percentage of the context window used, when the window is known.
+    pub fn window_percent(&self) -> Option<f64> {
+        let window = self.context_window.filter(|window| *window > 0)?;
+        Some(self.total_tokens as f64 * 100.0 / window as f64)
+    }
+
+    /// Share of the total tokens taken by `entry`, as a percentage.
+    pub fn share_percent(&self, entry: &ContextReportEntry) -> f64 {
+        if self.total_tokens == 0 {
+            return 0.0;
+        }
+        entry.tokens as f64 * 100.0 / self.total_tokens as f64
+    }
+
+    /// The `count` entries with the most tokens, largest first. Ties keep
+    /// message order.
+    pub fn largest_contributors(&self, count: usize) -> Vec<&ContextReportEntry> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.index.cmp(&b.index)));
+        entries.truncate(count);
+        entries
+    }
+}
+
+fn message_chars(message: &ModelMessage) -> usize {
+    let content = message
+        .content
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => text.chars().count(),
+            ContentPart::Image(image) => image.data.len() + image.mime_type.chars().count(),
+            ContentPart::ToolCall(call) => {
+                call.id.chars().count()
+                    + call.name.chars().count()
+                    + call.arguments.to_string().chars().count()
+                    + call.recipient.as_deref().map_or(0, |r| r.chars().count())
+            }
+            ContentPart::ToolResult(result) => {
+                result.tool_call_id.chars().count() + result.result.to_string().chars().count()
+            }
+            ContentPart::Thinking(thinking) => {
+                thinking.thinking.chars().count() + thinking.signature.chars().count()
+            }
+            ContentPart::RedactedThinking(thinking) => {
+                thinking.data.chars().count() + thinking.signature.chars().count()
+            }
+        })
+        .sum::<usize>();
+    content
+        + message
+            .name
+            .as_deref()
+            .map_or(0, |name| name.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::context::estimate_context_usage;
+
+    fn model() -> LanguageModel {
+        LanguageModel::Custom {
+            provider: "test".to_string(),
+            model_id: "model".to_string(),
+        }
+    }
+
+    fn messages() -> Vec<ModelMessage> {
+        vec![
+            ModelMessage::system("You are helpful.").with_origin(MessageOrigin::SystemPrompt {
+                section: "base".to_string(),
+            }),
+            ModelMessage::system("## Project Context\n\nUse tabs.".repeat(20)).with_origin(
+                MessageOrigin::ContextFile {
+                    path: PathBuf::from("/repo/AGENTS.md"),
+                },
+            ),
+            ModelMessage::user("hello"),
+            ModelMessage::tool_result("call_1", serde_json::json!({"ok": true}), false),
+        ]
+    }
+
+    #[test]
+    fn totals_sum_entries_and_match_context_usage() {
+        let messages = messages();
+        let report = ContextReport::analyze(&messages, &model()).with_context_window(1_000);
+
+        assert_eq!(
+            report.total_tokens,
+            report.entries.iter().map(|e| e.tokens).sum::<usize>()
+        );
+        assert_eq!(
+            report.total_tokens,
+            estimate_context_usage(&messages, 1_000).used_tokens
+        );
+        assert_eq!(report.entries[2].chars, "hello".len());
+        assert_eq!(
+            report.total_chars,
+            report.entries.iter().map(|e| e.chars).sum::<usize>()
+        );
+        let percent = report.window_percent().expect("window is set");
+        assert!((percent - report.total_tokens as f64 / 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn entries_use_tagged_origins_and_fall_back_to_roles() {
+        let report = ContextReport::analyze(&messages(), &model());
+
+        let origins = report
+            .entries
+            .iter()
+            .map(|entry| entry.origin.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            origins,
+            vec![
+                MessageOrigin::SystemPrompt {
+                    section: "base".to_string()
+                },
+                MessageOrigin::ContextFile {
+                    path: PathBuf::from("/repo/AGENTS.md")
+                },
+                MessageOrigin::User,
+                MessageOrigin::ToolResult,
+            ]
+        );
+        assert_eq!(report.context_window, None);
+        assert_eq!(report.window_percent(), None);
+    }
+
+    #[test]
+    fn largest_contributors_are_ordered_by_tokens() {
+        let report = ContextReport::analyze(&messages(), &model());
+
+        let largest = report.largest_contributors(2);
+
+        assert_eq!(largest.len(), 2);
+        assert_eq!(largest[0].index, 1);
+        assert!(largest[0].tokens >= largest[1].tokens);
+        assert!(report.share_percent(largest[0]) > 50.0);
+    }
+
+    #[test]
+    fn origin_round_trips_through_serde_and_is_skipped_when_absent() {
+        let tagged = ModelMessage::user("hi").with_origin(MessageOrigin::ContextFile {
+            path: PathBuf::from("AGENTS.md"),
+        });
+        let json = serde_json::to_value(&tagged).unwrap();
+        assert_eq!(
+            json["metadata"]["origin"],
+            serde_json::json!({"kind": "context_file", "path": "AGENTS.md"})
+        );
+        let parsed: ModelMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.origin(), tagged.origin());
+
+        let untagged = serde_json::to_value(ModelMessage::user("hi")).unwrap();
+        assert!(untagged.get("metadata").is_none());
+    }
+}
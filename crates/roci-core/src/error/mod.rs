@@ -60,6 +60,22 @@ pub enum RociError {
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    #[error("Schema violation: {}", describe_violations(.0))]
+    SchemaViolation(Vec<crate::generation::SchemaViolation>),
+
+    #[error("Model refused: {message}")]
+    Refused { message: String },
+
+    /// A background response stream dropped and could not be resumed. The
+    /// response keeps running server-side; poll it by `response_id`.
+    #[error("Background response {response_id} interrupted: {message}")]
+    BackgroundInterrupted {
+        response_id: String,
+        /// Text streamed before the interruption.
+        partial_text: String,
+        message: String,
+    },
+
     #[error("missing credential for provider {provider}")]
     MissingCredential { provider: String },
 
@@ -92,15 +108,53 @@ impl RociError {
         }
     }
 
+    /// Create a provider-overloaded error.
+    ///
+    /// Overloaded providers (Anthropic 529 / `overloaded_error`) do not send a
+    /// retry-after hint, so callers back off on their own schedule.
+    pub fn overloaded(status: u16, message: impl Into<String>) -> Self {
+        Self::api_with_details(
+            status,
+            message,
+            ErrorDetails {
+                code: Some(ErrorCode::Overloaded),
+                provider_code: Some("overloaded_error".to_string()),
+                param: None,
+                request_id: None,
+            },
+        )
+    }
+
+    /// Whether the provider reported it is temporarily overloaded.
+    pub fn is_overloaded(&self) -> bool {
+        match self {
+            Self::Api {
+                status, details, ..
+            } => {
+                *status == 529
+                    || details
+                        .as_ref()
+                        .is_some_and(|details| details.code == Some(ErrorCode::Overloaded))
+            }
+            _ => false,
+        }
+    }
+
     /// Classify this error into a category.
+    ///
+    /// Overloaded errors classify as [`ErrorCategory::RateLimit`] so retry
+    /// policies treat them like a rate limit without a server delay hint.
     pub fn category(&self) -> ErrorCategory {
+        if self.is_overloaded() {
+            return ErrorCategory::RateLimit;
+        }
         match self {
             Self::Authentication(_) => ErrorCategory::Authentication,
             Self::RateLimited { .. } => ErrorCategory::RateLimit,
             Self::Network(_) => ErrorCategory::Network,
             Self::Timeout(_) => ErrorCategory::Timeout,
             Self::Configuration(_) => ErrorCategory::Configuration,
-            Self::Serialization(_) => ErrorCategory::Serialization,
+            Self::Serialization(_) | Self::SchemaViolation(_) => ErrorCategory::Serialization,
             Self::Api { status, .. } => match status {
                 401 | 403 => ErrorCategory::Authentication,
                 429 => ErrorCategory::RateLimit,
@@ -140,6 +194,14 @@ impl RociError {
     }
 }
 
+fn describe_violations(violations: &[crate::generation::SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// Convenience alias.
 pub type Result<T> = std::result::Result<T, RociError>;
 
@@ -185,6 +247,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn overloaded_errors_classify_as_retryable_rate_limit() {
+        let typed = RociError::overloaded(200, "Overloaded");
+        let status_only = RociError::api(529, "Overloaded");
+
+        for err in [typed, status_only] {
+            assert!(err.is_overloaded());
+            assert_eq!(err.category(), ErrorCategory::RateLimit);
+            assert!(err.is_retryable());
+        }
+        assert!(!RociError::api(503, "unavailable").is_overloaded());
+    }
+
     #[test]
     fn missing_credential_has_authentication_category() {
         let err = RociError::MissingCredential {
@@ -254,6 +329,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn background_interrupted_display_includes_response_id() {
+        let err = RociError::BackgroundInterrupted {
+            response_id: "resp_123".to_string(),
+            partial_text: "partial".to_string(),
+            message: "connection reset".to_string(),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("resp_123"), "expected response id: {msg}");
+        assert!(!err.is_retryable());
+    }
+
     #[test]
     fn missing_configuration_display_includes_key_and_provider() {
         let err = RociError::MissingConfiguration {
@@ -15,6 +15,8 @@ pub enum ErrorCode {
     ContextLengthExceeded,
     ServerError,
     ServiceUnavailable,
+    /// Provider is temporarily overloaded (for example Anthropic 529).
+    Overloaded,
     Timeout,
     Unknown,
 }
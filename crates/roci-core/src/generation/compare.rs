@@ -0,0 +1,452 @@
+//! Run one prompt against several models and line the results up.
+//!
+//! [`compare`] sends the same request to every model concurrently, bounded
+//! by [`CompareOptions::max_concurrency`], and returns one
+//! [`CompareCandidate`] per model in input order. A model that cannot be
+//! created or fails to answer records its error instead of failing the
+//! comparison. With a [`CompareJudge`], a further structured-output call
+//! scores the successful answers against a rubric.
+
+use std::time::Instant;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::config::RociConfig;
+use crate::models::LanguageModel;
+use crate::provider::ProviderRegistry;
+use crate::types::*;
+
+/// Requests in flight at once when [`CompareOptions::max_concurrency`] is
+/// not set.
+pub const DEFAULT_COMPARE_CONCURRENCY: usize = 4;
+
+/// Judge model and the rubric it scores candidates against.
+#[derive(Debug, Clone)]
+pub struct CompareJudge {
+    pub model: LanguageModel,
+    pub rubric: String,
+}
+
+impl CompareJudge {
+    pub fn new(model: LanguageModel, rubric: impl Into<String>) -> Self {
+        Self {
+            model,
+            rubric: rubric.into(),
+        }
+    }
+}
+
+/// Options for [`compare`].
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    /// Maximum candidate requests in flight; at least one.
+    pub max_concurrency: usize,
+    pub judge: Option<CompareJudge>,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_COMPARE_CONCURRENCY,
+            judge: None,
+        }
+    }
+}
+
+/// One model's answer, or the error it produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareCandidate {
+    pub model: LanguageModel,
+    /// Generated text; `None` when the request failed.
+    pub text: Option<String>,
+    pub usage: Usage,
+    /// Wall time of the request, including provider creation.
+    pub latency_ms: u64,
+    pub finish_reason: Option<FinishReason>,
+    pub error: Option<String>,
+}
+
+impl CompareCandidate {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Judge score for one candidate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateScore {
+    /// Index into [`CompareResult::candidates`].
+    pub candidate: usize,
+    /// Score from 1 (worst) to 10 (best).
+    pub score: f64,
+    pub rationale: String,
+}
+
+/// Outcome of the judge step.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareJudgement {
+    pub model: LanguageModel,
+    /// Scores for the candidates that answered, in candidate order.
+    pub scores: Vec<CandidateScore>,
+    pub usage: Usage,
+    /// Set when the judge call failed; `scores` is then empty.
+    pub error: Option<String>,
+}
+
+/// Aligned results of [`compare`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareResult {
+    /// One entry per requested model, in request order.
+    pub candidates: Vec<CompareCandidate>,
+    /// Present when a judge was configured and at least one candidate
+    /// answered.
+    pub judgement: Option<CompareJudgement>,
+}
+
+/// Run `messages` against every model in `models` and collect the results.
+///
+/// Candidates are created through `registry` and run without tools.
+/// Failures, including unknown providers and missing credentials, are
+/// recorded on the candidate rather than returned.
+pub async fn compare(
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    models: &[LanguageModel],
+    messages: Vec<ModelMessage>,
+    settings: GenerationSettings,
+    options: CompareOptions,
+) -> CompareResult {
+    let candidates = stream::iter(models.iter().cloned())
+        .map(|model| run_candidate(registry, config, model, messages.clone(), settings.clone()))
+        .buffered(options.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let judgement = match options.judge {
+        Some(judge) if candidates.iter().any(CompareCandidate::is_ok) => {
+            Some(run_judge(registry, config, judge, &messages, &candidates).await)
+        }
+        _ => None,
+    };
+
+    CompareResult {
+        candidates,
+        judgement,
+    }
+}
+
+/// Run one tool-less request against `model`, recording failures and the
+/// wall time (including provider creation) on the candidate.
+pub(super) async fn run_candidate(
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    model: LanguageModel,
+    messages: Vec<ModelMessage>,
+    settings: GenerationSettings,
+) -> CompareCandidate {
+    let started = Instant::now();
+    let result = match registry.create_provider_for(&model, config) {
+        Ok(provider) => {
+            super::text::generate_text(provider.as_ref(), messages, settings, &[]).await
+        }
+        Err(err) => Err(err),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(result) => CompareCandidate {
+            model,
+            text: Some(result.text),
+            usage: result.usage,
+            latency_ms,
+            finish_reason: result.finish_reason,
+            error: None,
+        },
+        Err(err) => CompareCandidate {
+            model,
+            text: None,
+            usage: Usage::default(),
+            latency_ms,
+            finish_reason: None,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+#[derive(Deserialize)]
+struct JudgeScores {
+    scores: Vec<JudgeScore>,
+}
+
+#[derive(Deserialize)]
+struct JudgeScore {
+    candidate: usize,
+    score: f64,
+    #[serde(default)]
+    rationale: String,
+}
+
+async fn run_judge(
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    judge: CompareJudge,
+    messages: &[ModelMessage],
+    candidates: &[CompareCandidate],
+) -> CompareJudgement {
+    let result = match registry.create_provider_for(&judge.model, config) {
+        Ok(provider) => {
+            super::object::generate_object::<JudgeScores>(
+                provider.as_ref(),
+                judge_messages(&judge.rubric, messages, candidates),
+                GenerationSettings::default(),
+                judge_schema(),
+                "candidate_scores",
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(result) => CompareJudgement {
+            model: judge.model,
+            scores: aligned_scores(result.object, candidates),
+            usage: result.usage,
+            error: None,
+        },
+        Err(err) => CompareJudgement {
+            model: judge.model,
+            scores: Vec::new(),
+            usage: Usage::default(),
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+/// Build the judge conversation: rubric as the system prompt, then the
+/// original conversation and each answering candidate under a numbered,
+/// model-blind label.
+fn judge_messages(
+    rubric: &str,
+    messages: &[ModelMessage],
+    candidates: &[CompareCandidate],
+) -> Vec<ModelMessage> {
+    let system = format!(
+        "You are judging answers from several assistants to the same conversation. \
+         Score each candidate from 1 (worst) to 10 (best) using this rubric:\n\n{rubric}\n\n\
+         Return one score per candidate, identified by its candidate number, with a short rationale."
+    );
+    let mut prompt = String::from("## Conversation\n");
+    for message in messages {
+        let role = match message.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        };
+        prompt.push_str(&format!("\n[{role}]\n{}\n", message.text()));
+    }
+    for (index, candidate) in candidates.iter().enumerate() {
+        if let Some(text) = &candidate.text {
+            prompt.push_str(&format!("\n## Candidate {}\n{text}\n", index + 1));
+        }
+    }
+    vec![ModelMessage::system(system), ModelMessage::user(prompt)]
+}
+
+fn judge_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "scores": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "candidate": { "type": "integer", "description": "Candidate number" },
+                        "score": { "type": "number", "minimum": 1, "maximum": 10 },
+                        "rationale": { "type": "string" }
+                    },
+                    "required": ["candidate", "score", "rationale"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["scores"],
+        "additionalProperties": false
+    })
+}
+
+/// Map 1-based judge numbering back to candidate indices, dropping scores
+/// for unknown or failed candidates and duplicates.
+fn aligned_scores(judged: JudgeScores, candidates: &[CompareCandidate]) -> Vec<CandidateScore> {
+    let mut scores = Vec::<CandidateScore>::new();
+    for entry in judged.scores {
+        let Some(index) = entry.candidate.checked_sub(1) else {
+            continue;
+        };
+        let answered = candidates.get(index).is_some_and(CompareCandidate::is_ok);
+        if !answered || scores.iter().any(|score| score.candidate == index) {
+            continue;
+        }
+        scores.push(CandidateScore {
+            candidate: index,
+            score: entry.score,
+            rationale: entry.rationale,
+        });
+    }
+    scores.sort_by_key(|score| score.candidate);
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::generation::test_support::{model, registry};
+    use crate::provider::ProviderRequest;
+
+    fn reply(model_id: &str, _request: &ProviderRequest) -> String {
+        match model_id {
+            "judge" => r#"{"scores":[{"candidate":3,"score":4,"rationale":"terse"},{"candidate":1,"score":9,"rationale":"clear"},{"candidate":2,"score":1,"rationale":"failed"}]}"#.to_string(),
+            _ => format!("answer from {model_id}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn results_stay_aligned_with_models_and_failures_are_recorded() {
+        let (registry, _calls) = registry(reply);
+        let models = vec![
+            model("slow"),
+            model("broken"),
+            LanguageModel::Known {
+                provider_key: "missing".to_string(),
+                model_id: "m".to_string(),
+                provider_options: Default::default(),
+            },
+            model("fast"),
+        ];
+
+        let result = compare(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &models,
+            vec![ModelMessage::user("hi")],
+            GenerationSettings::default(),
+            CompareOptions::default(),
+        )
+        .await;
+
+        let got = result
+            .candidates
+            .iter()
+            .map(|candidate| candidate.model.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(got, models);
+        assert_eq!(
+            result.candidates[0].text.as_deref(),
+            Some("answer from slow")
+        );
+        assert_eq!(result.candidates[0].usage.output_tokens, 3);
+        assert_eq!(result.candidates[0].finish_reason, Some(FinishReason::Stop));
+        assert!(result.candidates[0].latency_ms >= 60);
+        assert!(result.candidates[1]
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("connection reset")));
+        assert!(result.candidates[2].error.is_some());
+        assert_eq!(
+            result.candidates[3].text.as_deref(),
+            Some("answer from fast")
+        );
+        assert!(result.judgement.is_none());
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_bounded() {
+        let (registry, calls) = registry(reply);
+        let models = (0..6).map(|i| model(&format!("m{i}"))).collect::<Vec<_>>();
+
+        let result = compare(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &models,
+            vec![ModelMessage::user("hi")],
+            GenerationSettings::default(),
+            CompareOptions {
+                max_concurrency: 2,
+                judge: None,
+            },
+        )
+        .await;
+
+        assert!(result.candidates.iter().all(CompareCandidate::is_ok));
+        assert_eq!(calls.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn judge_scores_answering_candidates_without_naming_models() {
+        let (registry, calls) = registry(reply);
+        let models = vec![model("fast"), model("broken"), model("slow")];
+
+        let result = compare(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &models,
+            vec![ModelMessage::user("Explain ownership")],
+            GenerationSettings::default(),
+            CompareOptions {
+                judge: Some(CompareJudge::new(model("judge"), "Prefer clear answers.")),
+                ..CompareOptions::default()
+            },
+        )
+        .await;
+
+        let judgement = result.judgement.expect("judge ran");
+        assert_eq!(judgement.error, None);
+        assert_eq!(
+            judgement
+                .scores
+                .iter()
+                .map(|score| (score.candidate, score.score))
+                .collect::<Vec<_>>(),
+            vec![(0, 9.0), (2, 4.0)],
+            "scores map back to candidates; the failed candidate is dropped"
+        );
+
+        let requests = calls.requests_to("judge");
+        let request = &requests[0];
+        assert!(request
+            .messages
+            .iter()
+            .any(|message| message.role == Role::System
+                && message.text().contains("Prefer clear answers.")));
+        let prompt = request.messages.last().expect("judge prompt").text();
+        assert!(prompt.contains("Explain ownership"));
+        assert!(prompt.contains("## Candidate 1\nanswer from fast"));
+        assert!(prompt.contains("## Candidate 3\nanswer from slow"));
+        assert!(!prompt.contains("## Candidate 2"));
+        assert!(!prompt.contains("mock:"), "candidates are model-blind");
+    }
+
+    #[tokio::test]
+    async fn judge_is_skipped_when_every_candidate_fails() {
+        let (registry, calls) = registry(reply);
+
+        let result = compare(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &[model("broken")],
+            vec![ModelMessage::user("hi")],
+            GenerationSettings::default(),
+            CompareOptions {
+                judge: Some(CompareJudge::new(model("judge"), "rubric")),
+                ..CompareOptions::default()
+            },
+        )
+        .await;
+
+        assert!(result.judgement.is_none());
+        assert!(calls.requests_to("judge").is_empty());
+    }
+}
@@ -2,8 +2,10 @@
 //!
 //! These helpers do not resolve providers from configuration or a registry.
 //! Callers must pass an already constructed provider; the helpers only turn a
-//! single prompt into a user message and run with default settings.
+//! single prompt into a user message and run with default settings or a named
+//! preset.
 
+use super::preset::PresetCatalog;
 use crate::error::RociError;
 use crate::provider::ModelProvider;
 use crate::types::*;
@@ -19,6 +21,25 @@ pub async fn generate(
     Ok(result.text)
 }
 
+/// Generate text from a single user prompt with the named preset's settings,
+/// system prompt, and response format.
+///
+/// Preset transforms are run plugins and only take effect on agent runs; use
+/// [`PresetCatalog::apply`] there.
+pub async fn generate_with_preset(
+    provider: &dyn ModelProvider,
+    presets: &PresetCatalog,
+    preset_name: &str,
+    prompt: impl Into<String>,
+) -> Result<String, RociError> {
+    let preset = presets.get(preset_name)?;
+    let mut settings = GenerationSettings::default();
+    preset.apply_to_settings(&mut settings)?;
+    let result =
+        super::text::generate_text(provider, preset.messages(prompt), settings, &[]).await?;
+    Ok(result.text)
+}
+
 /// Stream text from a single user prompt with default settings and no stop conditions.
 pub async fn stream(
     provider: std::sync::Arc<dyn ModelProvider>,
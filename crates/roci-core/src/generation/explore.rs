@@ -0,0 +1,354 @@
+//! Try the next turn of a conversation under several setting variants.
+//!
+//! [`explore`] clones the history once per [`VariantSpec`], applies its
+//! overrides (model, temperature, system prompt suffix), appends the same
+//! next user message, and runs the variants concurrently, bounded by
+//! [`ExploreOptions::max_concurrency`]. Outcomes come back in variant order
+//! and are never written to the source history; commit the chosen one with
+//! [`Conversation::commit`](crate::agent::Conversation::commit) or
+//! [`VariantOutcome::branch`].
+
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+use super::compare::run_candidate;
+use crate::config::RociConfig;
+use crate::error::RociError;
+use crate::models::LanguageModel;
+use crate::provider::ProviderRegistry;
+use crate::types::*;
+
+/// Variants in flight at once when [`ExploreOptions::max_concurrency`] is
+/// not set.
+pub const DEFAULT_EXPLORE_CONCURRENCY: usize = 4;
+
+/// Overrides for one variant; unset fields keep the base model and settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariantSpec {
+    pub label: String,
+    pub model: Option<LanguageModel>,
+    pub temperature: Option<f64>,
+    /// Appended to the first system message, or sent as the system message
+    /// when the history has none.
+    pub system_suffix: Option<String>,
+}
+
+impl VariantSpec {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_model(mut self, model: LanguageModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_system_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.system_suffix = Some(suffix.into());
+        self
+    }
+
+    /// The history this variant sends: `history`, the suffixed system
+    /// prompt, then `next`.
+    fn messages(&self, history: &[ModelMessage], next: &ModelMessage) -> Vec<ModelMessage> {
+        let mut messages = history.to_vec();
+        if let Some(suffix) = &self.system_suffix {
+            match messages
+                .iter_mut()
+                .find(|message| message.role == Role::System)
+            {
+                Some(system) => system.content.push(ContentPart::Text {
+                    text: format!("\n\n{suffix}"),
+                }),
+                None => messages.insert(0, ModelMessage::system(suffix.clone())),
+            }
+        }
+        messages.push(next.clone());
+        messages
+    }
+}
+
+/// Base model and settings for [`explore`].
+#[derive(Debug, Clone)]
+pub struct ExploreOptions {
+    pub model: LanguageModel,
+    pub settings: GenerationSettings,
+    /// Maximum variant requests in flight; at least one.
+    pub max_concurrency: usize,
+}
+
+impl ExploreOptions {
+    pub fn new(model: LanguageModel) -> Self {
+        Self {
+            model,
+            settings: GenerationSettings::default(),
+            max_concurrency: DEFAULT_EXPLORE_CONCURRENCY,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GenerationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+}
+
+/// One variant's answer to the next turn, or the error it produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantOutcome {
+    pub label: String,
+    /// Model the variant ran against.
+    pub model: LanguageModel,
+    /// The user message every variant answered.
+    pub user_message: ModelMessage,
+    /// Generated text; `None` when the request failed.
+    pub text: Option<String>,
+    pub usage: Usage,
+    /// Wall time of the request, including provider creation.
+    pub latency_ms: u64,
+    pub finish_reason: Option<FinishReason>,
+    pub error: Option<String>,
+}
+
+impl VariantOutcome {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The user message and the answer, ready to append to the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] when the variant failed.
+    pub fn branch(&self) -> Result<[ModelMessage; 2], RociError> {
+        match (&self.text, &self.error) {
+            (Some(text), None) => Ok([
+                self.user_message.clone(),
+                ModelMessage::assistant(text.clone()),
+            ]),
+            (_, error) => Err(RociError::InvalidArgument(format!(
+                "variant '{}' has no answer to commit: {}",
+                self.label,
+                error.as_deref().unwrap_or("no text")
+            ))),
+        }
+    }
+}
+
+/// Run `next_user_message` after `history` once per variant.
+///
+/// Variants are created through `registry` and run without tools. Failures,
+/// including unknown providers and missing credentials, are recorded on the
+/// outcome rather than returned.
+pub async fn explore(
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    history: &[ModelMessage],
+    next_user_message: impl Into<String>,
+    variants: Vec<VariantSpec>,
+    options: ExploreOptions,
+) -> Vec<VariantOutcome> {
+    let next = ModelMessage::user(next_user_message);
+    stream::iter(variants)
+        .map(|variant| run_variant(registry, config, history, &next, variant, &options))
+        .buffered(options.max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+async fn run_variant(
+    registry: &ProviderRegistry,
+    config: &RociConfig,
+    history: &[ModelMessage],
+    next: &ModelMessage,
+    variant: VariantSpec,
+    options: &ExploreOptions,
+) -> VariantOutcome {
+    let messages = variant.messages(history, next);
+    let model = variant.model.unwrap_or_else(|| options.model.clone());
+    let mut settings = options.settings.clone();
+    if let Some(temperature) = variant.temperature {
+        settings.temperature = Some(temperature);
+    }
+    let candidate = run_candidate(registry, config, model, messages, settings).await;
+    VariantOutcome {
+        label: variant.label,
+        model: candidate.model,
+        user_message: next.clone(),
+        text: candidate.text,
+        usage: candidate.usage,
+        latency_ms: candidate.latency_ms,
+        finish_reason: candidate.finish_reason,
+        error: candidate.error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::generation::test_support::{model, registry};
+    use crate::provider::ProviderRequest;
+
+    fn reply(model_id: &str, request: &ProviderRequest) -> String {
+        let temperature = request
+            .settings
+            .temperature
+            .map_or("default".to_string(), |t| t.to_string());
+        format!("{model_id} at {temperature}")
+    }
+
+    fn history() -> Vec<ModelMessage> {
+        vec![
+            ModelMessage::system("Be brief."),
+            ModelMessage::user("hi"),
+            ModelMessage::assistant("hello"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn variants_apply_their_overrides_and_keep_their_order() {
+        let (registry, calls) = registry(reply);
+        let history = history();
+
+        let outcomes = explore(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &history,
+            "and now?",
+            vec![
+                VariantSpec::new("cold").with_temperature(0.2),
+                VariantSpec::new("other model").with_model(model("alt")),
+                VariantSpec::new("pirate").with_system_suffix("Talk like a pirate."),
+                VariantSpec::new("broken").with_model(model("broken")),
+            ],
+            ExploreOptions::new(model("base")),
+        )
+        .await;
+
+        let labels = outcomes
+            .iter()
+            .map(|outcome| outcome.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["cold", "other model", "pirate", "broken"]);
+        assert_eq!(outcomes[0].text.as_deref(), Some("base at 0.2"));
+        assert_eq!(outcomes[0].usage.output_tokens, 3);
+        assert!(outcomes[0].latency_ms >= 40);
+        assert_eq!(outcomes[1].text.as_deref(), Some("alt at default"));
+        assert_eq!(outcomes[1].model, model("alt"));
+        assert!(outcomes[3]
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("connection reset")));
+        assert!(outcomes[3].branch().is_err());
+
+        let requests = calls.requests.lock().unwrap();
+        for (_, request) in requests.iter() {
+            assert_eq!(request.messages.len(), history.len() + 1);
+            assert_eq!(request.messages.last().unwrap().text(), "and now?");
+        }
+        let pirate = requests
+            .iter()
+            .find(|(_, request)| request.messages[0].text().contains("pirate"))
+            .expect("pirate request");
+        assert_eq!(
+            pirate.1.messages[0].text(),
+            "Be brief.\n\nTalk like a pirate."
+        );
+        assert_eq!(history[0].text(), "Be brief.");
+    }
+
+    #[tokio::test]
+    async fn variants_run_concurrently_within_the_bound() {
+        let (registry, calls) = registry(reply);
+        let variants = (0..6)
+            .map(|i| VariantSpec::new(format!("v{i}")).with_temperature(f64::from(i) / 10.0))
+            .collect::<Vec<_>>();
+
+        let started = Instant::now();
+        let outcomes = explore(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &history(),
+            "next",
+            variants,
+            ExploreOptions::new(model("base")).with_max_concurrency(3),
+        )
+        .await;
+
+        assert!(outcomes.iter().all(VariantOutcome::is_ok));
+        assert_eq!(calls.max_in_flight.load(Ordering::SeqCst), 3);
+        assert!(started.elapsed() < Duration::from_millis(6 * 40));
+    }
+
+    #[tokio::test]
+    async fn system_suffix_becomes_the_system_message_when_there_is_none() {
+        let (registry, calls) = registry(reply);
+
+        explore(
+            &registry,
+            &RociConfig::new().with_token_store(None),
+            &[],
+            "hi",
+            vec![VariantSpec::new("terse").with_system_suffix("Be terse.")],
+            ExploreOptions::new(model("base")),
+        )
+        .await;
+
+        let requests = calls.requests.lock().unwrap();
+        let messages = &requests[0].1.messages;
+        assert_eq!(messages[0].role, Role::System);
+        assert_eq!(messages[0].text(), "Be terse.");
+        assert_eq!(messages[1].text(), "hi");
+    }
+
+    #[cfg(feature = "agent")]
+    #[tokio::test]
+    async fn conversation_is_untouched_until_an_outcome_is_committed() {
+        use crate::agent::Conversation;
+
+        let (registry, _calls) = registry(reply);
+        let mut conversation = Conversation::new();
+        for message in history() {
+            conversation.add_message(message);
+        }
+        let before = conversation.messages().to_vec();
+
+        let outcomes = conversation
+            .explore(
+                &registry,
+                &RociConfig::new().with_token_store(None),
+                "and now?",
+                vec![
+                    VariantSpec::new("cold").with_temperature(0.2),
+                    VariantSpec::new("hot").with_temperature(1.0),
+                ],
+                ExploreOptions::new(model("base")),
+            )
+            .await;
+
+        assert_eq!(conversation.messages(), before.as_slice());
+        conversation.commit(&outcomes[1]).expect("commit hot");
+        assert_eq!(conversation.len(), before.len() + 2);
+        assert_eq!(&conversation.messages()[..before.len()], before.as_slice());
+        let branch = conversation.last_n(2);
+        assert_eq!(branch[0].role, Role::User);
+        assert_eq!(branch[0].text(), "and now?");
+        assert_eq!(branch[1].role, Role::Assistant);
+        assert_eq!(branch[1].text(), "base at 1");
+    }
+}
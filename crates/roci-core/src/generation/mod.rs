@@ -1,11 +1,29 @@
 //! Text, streaming, and structured output generation.
 
+pub mod compare;
 pub mod convenience;
+pub mod explore;
 pub mod object;
+pub mod partial_json;
+pub mod preset;
 pub mod stream;
+pub mod stream_object;
 pub mod text;
 
-pub use convenience::{generate, stream};
+#[cfg(test)]
+mod test_support;
+
+pub use compare::{
+    compare, CandidateScore, CompareCandidate, CompareJudge, CompareJudgement, CompareOptions,
+    CompareResult,
+};
+pub use convenience::{generate, generate_with_preset, stream};
+pub use explore::{explore, ExploreOptions, VariantOutcome, VariantSpec};
 pub use object::generate_object;
+pub use partial_json::{parse_partial_json, PartialJson};
+pub use preset::{Preset, PresetCatalog};
 pub use stream::{stream_text, stream_text_with_tools};
+pub use stream_object::{
+    stream_object, ObjectStreamEvent, SchemaViolation, StreamObjectOptions, ViolationMode,
+};
 pub use text::generate_text;
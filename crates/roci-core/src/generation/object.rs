@@ -17,10 +17,40 @@ pub async fn generate_object<T: DeserializeOwned>(
     schema: serde_json::Value,
     type_name: &str,
 ) -> Result<GenerateObjectResult<T>, RociError> {
+    let settings = request_object_format(provider, &mut messages, settings, &schema, type_name);
+    let result = super::text::generate_text(provider, messages, settings, &[]).await?;
+    if let Some(message) = result.refusal {
+        return Err(RociError::Refused { message });
+    }
+
+    // Parse the JSON from the response
+    let raw_text = result.text.trim().to_string();
+    // Strip potential markdown code fences
+    let json_text = strip_code_fences(&raw_text);
+
+    let object: T = serde_json::from_str(&json_text).map_err(RociError::Serialization)?;
+
+    Ok(GenerateObjectResult {
+        object,
+        raw_text,
+        usage: result.usage,
+        finish_reason: result.finish_reason,
+    })
+}
+
+/// Ask the model for JSON matching `schema`, through the response format when
+/// the provider supports one and a system instruction otherwise.
+pub(crate) fn request_object_format(
+    provider: &dyn ModelProvider,
+    messages: &mut Vec<ModelMessage>,
+    settings: GenerationSettings,
+    schema: &serde_json::Value,
+    type_name: &str,
+) -> GenerationSettings {
     let supports_json_schema = provider.capabilities().supports_json_schema;
     let supports_json_mode = provider.capabilities().supports_json_mode;
     let normalized_schema =
-        crate::provider::schema::normalize_schema_for_provider(&schema, provider.provider_name());
+        crate::provider::schema::normalize_schema_for_provider(schema, provider.provider_name());
 
     let mut settings = settings;
 
@@ -45,26 +75,11 @@ pub async fn generate_object<T: DeserializeOwned>(
         );
         messages.insert(0, ModelMessage::system(schema_instruction));
     }
-
-    let result = super::text::generate_text(provider, messages, settings, &[]).await?;
-
-    // Parse the JSON from the response
-    let raw_text = result.text.trim().to_string();
-    // Strip potential markdown code fences
-    let json_text = strip_code_fences(&raw_text);
-
-    let object: T = serde_json::from_str(&json_text).map_err(RociError::Serialization)?;
-
-    Ok(GenerateObjectResult {
-        object,
-        raw_text,
-        usage: result.usage,
-        finish_reason: result.finish_reason,
-    })
+    settings
 }
 
 /// Strip markdown code fences from JSON response.
-fn strip_code_fences(text: &str) -> String {
+pub(crate) fn strip_code_fences(text: &str) -> String {
     let trimmed = text.trim();
     if trimmed.starts_with("```") {
         let without_opening = if let Some(rest) = trimmed.strip_prefix("```json") {
@@ -0,0 +1,359 @@
+//! Parsing of incomplete JSON text, as it accumulates during a stream.
+
+use serde_json::{Map, Number, Value};
+
+/// The value parsed from a JSON prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialJson {
+    /// Everything parsed so far. Open containers are closed, an open string
+    /// keeps the characters seen so far, and a trailing number, literal, or
+    /// key without a value is left out because it may still change.
+    pub value: Value,
+    /// JSON Pointer to the string still being streamed, if the text ends
+    /// inside one.
+    pub open_string: Option<String>,
+    /// Whether the text held a whole JSON value.
+    pub complete: bool,
+}
+
+/// Parse a prefix of a JSON document.
+///
+/// Returns `Ok(None)` when no value has started yet.
+///
+/// # Errors
+///
+/// Returns a description of the first syntax error when the text cannot be
+/// the start of a JSON document.
+pub fn parse_partial_json(text: &str) -> Result<Option<PartialJson>, String> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        truncated: false,
+        open_string: None,
+    };
+    let value = parser.value("")?;
+    if !parser.truncated {
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.unexpected());
+        }
+    }
+    Ok(value.map(|value| PartialJson {
+        value,
+        open_string: parser.open_string,
+        complete: !parser.truncated,
+    }))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    truncated: bool,
+    open_string: Option<String>,
+}
+
+impl Parser<'_> {
+    fn value(&mut self, path: &str) -> Result<Option<Value>, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => {
+                self.truncated = true;
+                Ok(None)
+            }
+            Some(b'{') => self.object(path).map(Some),
+            Some(b'[') => self.array(path).map(Some),
+            Some(b'"') => {
+                let (value, complete) = self.string()?;
+                if !complete {
+                    self.open_string = Some(path.to_string());
+                }
+                Ok(Some(Value::String(value)))
+            }
+            Some(b't' | b'f' | b'n') => self.literal(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn object(&mut self, path: &str) -> Result<Value, String> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    self.truncated = true;
+                    return Ok(Value::Object(map));
+                }
+                Some(b'}') if map.is_empty() => {
+                    self.pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                Some(b'"') => {}
+                Some(_) => return Err(self.unexpected()),
+            }
+            let (key, complete) = self.string()?;
+            self.skip_whitespace();
+            if !complete || self.peek().is_none() {
+                self.truncated = true;
+                return Ok(Value::Object(map));
+            }
+            if self.peek() != Some(b':') {
+                return Err(self.unexpected());
+            }
+            self.pos += 1;
+            let value = self.value(&format!("{path}/{}", escape_pointer(&key)))?;
+            if let Some(value) = value {
+                map.insert(key, value);
+            }
+            if self.truncated {
+                return Ok(Value::Object(map));
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    self.truncated = true;
+                    return Ok(Value::Object(map));
+                }
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                Some(_) => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            let value = self.value(&format!("{path}/{}", items.len()))?;
+            if let Some(value) = value {
+                items.push(value);
+            }
+            if self.truncated {
+                return Ok(Value::Array(items));
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    self.truncated = true;
+                    return Ok(Value::Array(items));
+                }
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                Some(_) => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Parse a string starting at the opening quote, returning its content
+    /// and whether the closing quote was reached.
+    fn string(&mut self) -> Result<(String, bool), String> {
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(c) = self.text[self.pos..].chars().next() {
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok((out, true)),
+                '\\' => {
+                    let Some(escape) = self.text[self.pos..].chars().next() else {
+                        break;
+                    };
+                    self.pos += escape.len_utf8();
+                    match escape {
+                        '"' | '\\' | '/' => out.push(escape),
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'n' => out.push('\n'),
+                        'r' => out.push('\r'),
+                        't' => out.push('\t'),
+                        'u' => match self.unicode_escape()? {
+                            Some(c) => out.push(c),
+                            None => break,
+                        },
+                        _ => return Err(format!("invalid escape '\\{escape}' in string")),
+                    }
+                }
+                c if (c as u32) < 0x20 => {
+                    return Err(format!("control character U+{:04X} in string", c as u32))
+                }
+                c => out.push(c),
+            }
+        }
+        self.truncated = true;
+        Ok((out, false))
+    }
+
+    /// Decode the digits of a `\u` escape, including a following low
+    /// surrogate. `None` when the text ends before the escape does.
+    fn unicode_escape(&mut self) -> Result<Option<char>, String> {
+        let Some(high) = self.hex4()? else {
+            return Ok(None);
+        };
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(Some(char::from_u32(high).unwrap_or('\u{fffd}')));
+        }
+        let rest = &self.text[self.pos..];
+        if rest.len() < 2 && "\\u".starts_with(rest) {
+            return Ok(None);
+        }
+        if !rest.starts_with("\\u") {
+            return Ok(Some('\u{fffd}'));
+        }
+        self.pos += 2;
+        let Some(low) = self.hex4()? else {
+            return Ok(None);
+        };
+        if !(0xDC00..0xE000).contains(&low) {
+            return Ok(Some('\u{fffd}'));
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        Ok(Some(char::from_u32(code).unwrap_or('\u{fffd}')))
+    }
+
+    fn hex4(&mut self) -> Result<Option<u32>, String> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .unwrap_or(&self.text[self.pos..]);
+        if let Some(bad) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!("invalid character '{bad}' in unicode escape"));
+        }
+        if digits.len() < 4 {
+            self.pos = self.text.len();
+            return Ok(None);
+        }
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).ok())
+    }
+
+    fn literal(&mut self) -> Result<Option<Value>, String> {
+        let rest = &self.text[self.pos..];
+        for (word, value) in [
+            ("true", Value::Bool(true)),
+            ("false", Value::Bool(false)),
+            ("null", Value::Null),
+        ] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(Some(value));
+            }
+            if word.starts_with(rest) {
+                self.pos = self.text.len();
+                self.truncated = true;
+                return Ok(None);
+            }
+        }
+        Err(self.unexpected())
+    }
+
+    fn number(&mut self) -> Result<Option<Value>, String> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        ) {
+            self.pos += 1;
+        }
+        if self.pos == self.text.len() {
+            self.truncated = true;
+            return Ok(None);
+        }
+        let literal = &self.text[start..self.pos];
+        serde_json::from_str::<Number>(literal)
+            .map(|number| Some(Value::Number(number)))
+            .map_err(|_| format!("invalid number '{literal}'"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn unexpected(&self) -> String {
+        match self.text[self.pos..].chars().next() {
+            Some(c) => format!("unexpected character '{c}' at offset {}", self.pos),
+            None => format!("unexpected end of input at offset {}", self.pos),
+        }
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(text: &str) -> PartialJson {
+        parse_partial_json(text).unwrap().unwrap()
+    }
+
+    #[test]
+    fn closes_open_containers_and_keeps_open_string() {
+        let partial = parse(r#"{"name": "Ad", "tags": ["a", "b"#);
+
+        assert_eq!(partial.value, json!({"name": "Ad", "tags": ["a", "b"]}));
+        assert_eq!(partial.open_string.as_deref(), Some("/tags/1"));
+        assert!(!partial.complete);
+    }
+
+    #[test]
+    fn leaves_out_values_that_may_still_change() {
+        assert_eq!(parse(r#"{"count": 12"#).value, json!({}));
+        assert_eq!(parse(r#"{"ok": tr"#).value, json!({}));
+        assert_eq!(parse(r#"{"na"#).value, json!({}));
+        assert_eq!(parse(r#"{"name":"#).value, json!({}));
+        assert_eq!(
+            parse(r#"{"count": 12, "ok": true"#).value,
+            json!({"count": 12, "ok": true})
+        );
+    }
+
+    #[test]
+    fn complete_document_round_trips() {
+        let text = r#"{"a": [1, 2.5, null], "b": {"c": "é\n"}}"#;
+        let partial = parse(text);
+
+        assert!(partial.complete);
+        assert!(partial.open_string.is_none());
+        assert_eq!(partial.value, serde_json::from_str::<Value>(text).unwrap());
+    }
+
+    #[test]
+    fn pointer_paths_escape_keys() {
+        let partial = parse(r#"{"a/b": {"c~d": "x"#);
+
+        assert_eq!(partial.open_string.as_deref(), Some("/a~1b/c~0d"));
+    }
+
+    #[test]
+    fn empty_input_has_no_value() {
+        assert!(parse_partial_json("  ").unwrap().is_none());
+    }
+
+    #[test]
+    fn syntax_errors_are_reported() {
+        assert!(parse_partial_json(r#"{"a" 1"#).is_err());
+        assert!(parse_partial_json(r#"{"a": 1} x"#).is_err());
+        assert!(parse_partial_json("Sure! {").is_err());
+    }
+}
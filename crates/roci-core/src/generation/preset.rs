@@ -0,0 +1,411 @@
+//! Named generation presets.
+//!
+//! A preset bundles generation settings, a system prompt, a response format,
+//! and run transforms under one name. Presets are loaded from the `presets`
+//! section of the resource settings; project presets override global ones
+//! field by field.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+#[cfg(feature = "agent")]
+use std::{collections::HashMap, sync::Arc};
+
+use crate::error::RociError;
+use crate::resource::ResourceSettings;
+use crate::types::{GenerationSettings, ModelMessage, ResponseFormat};
+
+#[cfg(feature = "agent")]
+use crate::agent_loop::{RunPlugin, RunRequest};
+
+/// Reusable bundle of generation overrides.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Preset {
+    pub name: String,
+    /// Fields set here replace the target's; unset fields are left alone.
+    pub settings: GenerationSettings,
+    /// Added after the target's leading system messages.
+    pub system_prompt: Option<String>,
+    pub response_format: Option<ResponseFormat>,
+    /// JSON schema file, resolved against the directory of the settings file
+    /// that named it. Read when the preset is applied and used as a
+    /// `json_schema` response format named after the preset; takes precedence
+    /// over `response_format`.
+    pub schema_file: Option<PathBuf>,
+    /// Names of run transforms, resolved by [`PresetCatalog`].
+    pub transforms: Vec<String>,
+}
+
+impl Preset {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Overlay this preset's settings and response format onto `settings`.
+    pub fn apply_to_settings(&self, settings: &mut GenerationSettings) -> Result<(), RociError> {
+        overlay_settings(settings, self.settings.clone());
+        if let Some(format) = self.resolved_response_format()? {
+            settings.response_format = Some(format);
+        }
+        Ok(())
+    }
+
+    /// `prompt` as a user message, preceded by the preset system prompt.
+    pub fn messages(&self, prompt: impl Into<String>) -> Vec<ModelMessage> {
+        self.system_prompt
+            .iter()
+            .map(|system| ModelMessage::system(system.clone()))
+            .chain(std::iter::once(ModelMessage::user(prompt)))
+            .collect()
+    }
+
+    /// Apply settings, response format, and system prompt to `request`.
+    ///
+    /// Transforms are attached by [`PresetCatalog::apply`], which knows the
+    /// registered ones.
+    #[cfg(feature = "agent")]
+    pub fn apply_to(&self, request: &mut RunRequest) -> Result<(), RociError> {
+        self.apply_to_settings(&mut request.settings)?;
+        if let Some(system) = &self.system_prompt {
+            let insert_at = request
+                .messages
+                .iter()
+                .take_while(|message| message.role == crate::types::Role::System)
+                .count();
+            request
+                .messages
+                .insert(insert_at, ModelMessage::system(system.clone()));
+        }
+        Ok(())
+    }
+
+    fn resolved_response_format(&self) -> Result<Option<ResponseFormat>, RociError> {
+        let Some(path) = &self.schema_file else {
+            return Ok(self.response_format.clone());
+        };
+        let raw = std::fs::read_to_string(path).map_err(|err| {
+            RociError::Configuration(format!(
+                "preset '{}' schema_file {}: {err}",
+                self.name,
+                path.display()
+            ))
+        })?;
+        let schema = serde_json::from_str(&raw).map_err(|err| {
+            RociError::Configuration(format!(
+                "preset '{}' schema_file {} is not valid JSON: {err}",
+                self.name,
+                path.display()
+            ))
+        })?;
+        Ok(Some(ResponseFormat::JsonSchema {
+            schema,
+            name: self.name.clone(),
+        }))
+    }
+}
+
+/// Presets by name, plus the run transforms they may reference.
+#[derive(Clone, Default)]
+pub struct PresetCatalog {
+    presets: BTreeMap<String, Preset>,
+    #[cfg(feature = "agent")]
+    transforms: HashMap<String, Arc<dyn RunPlugin>>,
+}
+
+impl std::fmt::Debug for PresetCatalog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PresetCatalog")
+            .field("presets", &self.presets)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PresetCatalog {
+    pub fn new(presets: impl IntoIterator<Item = Preset>) -> Self {
+        Self {
+            presets: presets
+                .into_iter()
+                .map(|preset| (preset.name.clone(), preset))
+                .collect(),
+            #[cfg(feature = "agent")]
+            transforms: HashMap::new(),
+        }
+    }
+
+    /// Presets from the `presets` settings section.
+    pub fn from_settings(settings: &ResourceSettings) -> Self {
+        Self::new(settings.presets.iter().cloned())
+    }
+
+    /// Register `plugin` as the transform called `name`.
+    #[cfg(feature = "agent")]
+    pub fn with_transform(mut self, name: impl Into<String>, plugin: Arc<dyn RunPlugin>) -> Self {
+        self.transforms.insert(name.into(), plugin);
+        self
+    }
+
+    /// Preset names in sorted order.
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.keys().map(String::as_str).collect()
+    }
+
+    /// The preset called `name`, after checking that its transforms are
+    /// registered. Unknown names list the available ones.
+    pub fn get(&self, name: &str) -> Result<&Preset, RociError> {
+        let preset = self.presets.get(name).ok_or_else(|| {
+            RociError::Configuration(format!(
+                "unknown preset '{name}'; available: {}",
+                list_or_none(self.names())
+            ))
+        })?;
+        if let Some(missing) = preset
+            .transforms
+            .iter()
+            .find(|transform| !self.has_transform(transform))
+        {
+            return Err(RociError::Configuration(format!(
+                "preset '{name}' names unknown transform '{missing}'; available: {}",
+                list_or_none(self.transform_names())
+            )));
+        }
+        Ok(preset)
+    }
+
+    /// Apply the preset called `name` to `request`, attaching its transforms
+    /// as run plugins in the order listed.
+    #[cfg(feature = "agent")]
+    pub fn apply(&self, name: &str, request: &mut RunRequest) -> Result<(), RociError> {
+        let preset = self.get(name)?;
+        preset.apply_to(request)?;
+        for transform in &preset.transforms {
+            request.plugins.push(self.transforms[transform].clone());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "agent")]
+    fn has_transform(&self, name: &str) -> bool {
+        self.transforms.contains_key(name)
+    }
+
+    #[cfg(not(feature = "agent"))]
+    fn has_transform(&self, _name: &str) -> bool {
+        false
+    }
+
+    #[cfg(feature = "agent")]
+    fn transform_names(&self) -> Vec<&str> {
+        let mut names = self
+            .transforms
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    #[cfg(not(feature = "agent"))]
+    fn transform_names(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+fn list_or_none(names: Vec<&str>) -> String {
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Replace each field of `target` that `overrides` sets.
+fn overlay_settings(target: &mut GenerationSettings, overrides: GenerationSettings) {
+    fn overlay<T>(slot: &mut Option<T>, value: Option<T>) {
+        if value.is_some() {
+            *slot = value;
+        }
+    }
+
+    let GenerationSettings {
+        max_tokens,
+        temperature,
+        top_p,
+        top_k,
+        stop_sequences,
+        presence_penalty,
+        frequency_penalty,
+        seed,
+        reasoning_effort,
+        text_verbosity,
+        response_format,
+        openai_responses,
+        anthropic,
+        google,
+        mistral,
+        grok,
+        groq,
+        assistant_prefix,
+        tool_choice,
+        user,
+        stream_idle_timeout_ms,
+    } = overrides;
+    overlay(&mut target.max_tokens, max_tokens);
+    overlay(&mut target.temperature, temperature);
+    overlay(&mut target.top_p, top_p);
+    overlay(&mut target.top_k, top_k);
+    overlay(&mut target.stop_sequences, stop_sequences);
+    overlay(&mut target.presence_penalty, presence_penalty);
+    overlay(&mut target.frequency_penalty, frequency_penalty);
+    overlay(&mut target.seed, seed);
+    overlay(&mut target.reasoning_effort, reasoning_effort);
+    overlay(&mut target.text_verbosity, text_verbosity);
+    overlay(&mut target.response_format, response_format);
+    overlay(&mut target.openai_responses, openai_responses);
+    overlay(&mut target.anthropic, anthropic);
+    overlay(&mut target.google, google);
+    overlay(&mut target.mistral, mistral);
+    overlay(&mut target.grok, grok);
+    overlay(&mut target.groq, groq);
+    overlay(&mut target.assistant_prefix, assistant_prefix);
+    overlay(&mut target.tool_choice, tool_choice);
+    overlay(&mut target.user, user);
+    overlay(&mut target.stream_idle_timeout_ms, stream_idle_timeout_ms);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terse() -> Preset {
+        Preset {
+            settings: GenerationSettings {
+                temperature: Some(0.1),
+                max_tokens: Some(256),
+                ..Default::default()
+            },
+            system_prompt: Some("Be terse.".to_string()),
+            ..Preset::new("terse")
+        }
+    }
+
+    #[test]
+    fn preset_settings_replace_only_the_fields_they_set() {
+        let mut settings = GenerationSettings {
+            temperature: Some(0.9),
+            top_p: Some(0.5),
+            ..Default::default()
+        };
+
+        terse().apply_to_settings(&mut settings).unwrap();
+
+        assert_eq!(settings.temperature, Some(0.1));
+        assert_eq!(settings.max_tokens, Some(256));
+        assert_eq!(settings.top_p, Some(0.5));
+    }
+
+    #[test]
+    fn unknown_preset_lists_the_available_names() {
+        let catalog = PresetCatalog::new([terse(), Preset::new("creative")]);
+
+        let err = catalog.get("verbose").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: unknown preset 'verbose'; available: creative, terse"
+        );
+    }
+
+    #[test]
+    fn unregistered_transform_is_a_configuration_error() {
+        let preset = Preset {
+            transforms: vec!["redact".to_string()],
+            ..Preset::new("safe")
+        };
+        let catalog = PresetCatalog::new([preset]);
+
+        let err = catalog.get("safe").unwrap_err();
+
+        assert!(err.to_string().contains("unknown transform 'redact'"));
+    }
+
+    #[test]
+    fn schema_file_becomes_a_json_schema_response_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answer.json");
+        std::fs::write(&path, r#"{"type":"object"}"#).unwrap();
+        let preset = Preset {
+            response_format: Some(ResponseFormat::JsonObject),
+            schema_file: Some(path),
+            ..Preset::new("answer")
+        };
+        let mut settings = GenerationSettings::default();
+
+        preset.apply_to_settings(&mut settings).unwrap();
+
+        assert_eq!(
+            settings.response_format,
+            Some(ResponseFormat::JsonSchema {
+                schema: serde_json::json!({ "type": "object" }),
+                name: "answer".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn missing_schema_file_names_the_preset_and_path() {
+        let preset = Preset {
+            schema_file: Some(PathBuf::from("/nonexistent/answer.json")),
+            ..Preset::new("answer")
+        };
+
+        let err = preset
+            .apply_to_settings(&mut GenerationSettings::default())
+            .unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("preset 'answer'"), "{message}");
+        assert!(message.contains("/nonexistent/answer.json"), "{message}");
+    }
+
+    #[cfg(feature = "agent")]
+    #[test]
+    fn catalog_apply_inserts_system_prompt_and_attaches_transforms() {
+        struct Marker;
+        impl RunPlugin for Marker {
+            fn name(&self) -> &str {
+                "marker"
+            }
+        }
+
+        let preset = Preset {
+            transforms: vec!["marker".to_string()],
+            ..terse()
+        };
+        let catalog = PresetCatalog::new([preset]).with_transform("marker", Arc::new(Marker));
+        let mut request = RunRequest::new(
+            "openai:gpt-4o".parse().unwrap(),
+            vec![ModelMessage::system("caller"), ModelMessage::user("hi")],
+        );
+
+        catalog.apply("terse", &mut request).unwrap();
+
+        let texts = request
+            .messages
+            .iter()
+            .map(|message| (message.role, message.text()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                (crate::types::Role::System, "caller".to_string()),
+                (crate::types::Role::System, "Be terse.".to_string()),
+                (crate::types::Role::User, "hi".to_string()),
+            ]
+        );
+        assert_eq!(request.settings.temperature, Some(0.1));
+        assert_eq!(request.plugins.len(), 1);
+        assert_eq!(request.plugins[0].name(), "marker");
+    }
+}
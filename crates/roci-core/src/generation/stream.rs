@@ -4,7 +4,7 @@ use futures::stream::BoxStream;
 use futures::StreamExt;
 
 use crate::error::RociError;
-use crate::provider::{ModelProvider, ProviderRequest};
+use crate::provider::{validate_assistant_prefix, ModelProvider, ProviderRequest};
 use crate::stop::StopCondition;
 use crate::tools::tool::Tool;
 use crate::types::*;
@@ -34,6 +34,7 @@ pub async fn stream_text_with_tools(
             "generation::stream_text_with_tools does not execute tools; use Agent, AgentRuntime, or agent_loop::LoopRunner for tool-capable streams".to_string(),
         ));
     }
+    validate_assistant_prefix(provider.as_ref(), &settings)?;
 
     let stream = async_stream::stream! {
         let mut accumulated_text = String::new();
@@ -42,7 +43,7 @@ pub async fn stream_text_with_tools(
         }
 
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: settings.clone(),
             tools: None,
             response_format: settings.response_format.clone(),
@@ -52,6 +53,7 @@ pub async fn stream_text_with_tools(
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let mut inner = match provider.stream_text(&request).await {
             Ok(stream) => stream,
@@ -87,6 +89,8 @@ pub async fn stream_text_with_tools(
                                 reasoning: None,
                                 reasoning_signature: None,
                                 reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
                             });
                             break;
                         }
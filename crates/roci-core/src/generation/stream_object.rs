@@ -0,0 +1,719 @@
+//! Streaming structured output with incremental schema validation.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::object::{request_object_format, strip_code_fences};
+use super::partial_json::{parse_partial_json, PartialJson};
+use crate::error::RociError;
+use crate::provider::{ModelProvider, ProviderRequest};
+use crate::types::*;
+
+/// What [`stream_object`] does when the streamed JSON breaks the schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ViolationMode {
+    /// End the stream with [`RociError::SchemaViolation`] and cancel the
+    /// provider request, so no more tokens are spent.
+    #[default]
+    Abort,
+    /// Emit [`ObjectStreamEvent::Violation`] and keep streaming; the stream
+    /// ends with [`RociError::SchemaViolation`] listing every violation.
+    Continue,
+}
+
+/// Options for [`stream_object`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamObjectOptions {
+    pub on_violation: ViolationMode,
+}
+
+/// A place where the streamed JSON does not match the schema.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value; empty for the document itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+/// One event from [`stream_object`].
+#[derive(Debug, Clone)]
+pub enum ObjectStreamEvent<T> {
+    /// The object parsed so far, emitted whenever it grows.
+    Partial(Value),
+    /// A violation found mid-stream under [`ViolationMode::Continue`].
+    Violation(SchemaViolation),
+    /// The finished, deserialized object. Always the last event.
+    Done(GenerateObjectResult<T>),
+}
+
+/// Stream a typed object, validating the JSON against `schema` as it
+/// arrives.
+///
+/// Type mismatches, unknown properties under `additionalProperties: false`,
+/// and `enum` violations are caught as soon as the offending value starts
+/// streaming; an open string already fails an `enum` once it is no longer a
+/// prefix of any allowed value. Missing `required` properties are only
+/// checked once the document is complete. Subschemas using `anyOf`, `oneOf`,
+/// `allOf`, or `$ref` are not checked until deserialization.
+pub async fn stream_object<T>(
+    provider: Arc<dyn ModelProvider>,
+    mut messages: Vec<ModelMessage>,
+    settings: GenerationSettings,
+    schema: Value,
+    type_name: &str,
+    options: StreamObjectOptions,
+) -> Result<BoxStream<'static, Result<ObjectStreamEvent<T>, RociError>>, RociError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let settings = request_object_format(
+        provider.as_ref(),
+        &mut messages,
+        settings,
+        &schema,
+        type_name,
+    );
+    let request = ProviderRequest {
+        messages: messages.into(),
+        settings: settings.clone(),
+        tools: None,
+        response_format: settings.response_format.clone(),
+        api_key_override: None,
+        headers: reqwest::header::HeaderMap::new(),
+        metadata: std::collections::HashMap::new(),
+        payload_callback: None,
+        session_id: None,
+        transport: None,
+        tool_call_ids: None,
+    };
+    let mut inner = provider.stream_text(&request).await?;
+
+    let stream = async_stream::stream! {
+        let mut state = ObjectStreamState::<T>::new(schema);
+        let mut usage = Usage::default();
+        let mut finish_reason = None;
+        let mut refusal = String::new();
+        while let Some(item) = inner.next().await {
+            let delta = match item {
+                Ok(delta) => delta,
+                Err(error) => {
+                    yield Err(error);
+                    return;
+                }
+            };
+            if let Some(delta_usage) = delta.usage {
+                usage = delta_usage;
+            }
+            if delta.finish_reason.is_some() {
+                finish_reason = delta.finish_reason;
+            }
+            if delta.event_type == StreamEventType::RefusalDelta {
+                refusal.push_str(&delta.text);
+                continue;
+            }
+            if delta.text.is_empty() {
+                continue;
+            }
+            let (partial, violations) = state.push(&delta.text);
+            if !violations.is_empty() && options.on_violation == ViolationMode::Abort {
+                // Dropping the provider stream cancels the request before the
+                // error reaches the caller.
+                drop(inner);
+                yield Err(RociError::SchemaViolation(violations));
+                return;
+            }
+            for violation in violations {
+                yield Ok(ObjectStreamEvent::Violation(violation));
+            }
+            if let Some(partial) = partial {
+                yield Ok(ObjectStreamEvent::Partial(partial));
+            }
+        }
+        if !refusal.is_empty() {
+            yield Err(RociError::Refused { message: refusal });
+            return;
+        }
+        yield state.finish(usage, finish_reason);
+    };
+    Ok(Box::pin(stream))
+}
+
+/// Accumulated text and violations of one [`stream_object`] call.
+struct ObjectStreamState<T> {
+    schema: Value,
+    text: String,
+    last_partial: Option<Value>,
+    violations: Vec<SchemaViolation>,
+    _object: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> ObjectStreamState<T> {
+    fn new(schema: Value) -> Self {
+        Self {
+            schema,
+            text: String::new(),
+            last_partial: None,
+            violations: Vec::new(),
+            _object: PhantomData,
+        }
+    }
+
+    /// Add streamed text, returning the partial object when it changed and
+    /// any violations not reported before.
+    fn push(&mut self, text: &str) -> (Option<Value>, Vec<SchemaViolation>) {
+        self.text.push_str(text);
+        let mut found = Vec::new();
+        let partial = match parse_partial_json(json_body(&self.text)) {
+            Ok(partial) => partial,
+            Err(message) => {
+                found.push(SchemaViolation {
+                    path: String::new(),
+                    message: format!("not valid JSON: {message}"),
+                });
+                None
+            }
+        };
+        if let Some(PartialJson {
+            value, open_string, ..
+        }) = &partial
+        {
+            validate(
+                value,
+                &self.schema,
+                "",
+                open_string.as_deref(),
+                false,
+                &mut found,
+            );
+        }
+        let found = self.record(found);
+        let partial = partial
+            .map(|partial| partial.value)
+            .filter(|value| self.last_partial.as_ref() != Some(value));
+        if let Some(value) = &partial {
+            self.last_partial = Some(value.clone());
+        }
+        (partial, found)
+    }
+
+    /// Keep the violations at paths not reported before.
+    fn record(&mut self, found: Vec<SchemaViolation>) -> Vec<SchemaViolation> {
+        let mut fresh = Vec::new();
+        for violation in found {
+            if self
+                .violations
+                .iter()
+                .all(|known| known.path != violation.path)
+            {
+                self.violations.push(violation.clone());
+                fresh.push(violation);
+            }
+        }
+        fresh
+    }
+
+    fn finish(
+        mut self,
+        usage: Usage,
+        finish_reason: Option<FinishReason>,
+    ) -> Result<ObjectStreamEvent<T>, RociError> {
+        let raw_text = self.text.trim().to_string();
+        let json_text = strip_code_fences(&raw_text);
+        let value = serde_json::from_str::<Value>(&json_text);
+        if let Ok(value) = &value {
+            let mut found = Vec::new();
+            validate(value, &self.schema, "", None, true, &mut found);
+            self.record(found);
+        }
+        if !self.violations.is_empty() {
+            return Err(RociError::SchemaViolation(self.violations));
+        }
+        let object = serde_json::from_value(value?)?;
+        Ok(ObjectStreamEvent::Done(GenerateObjectResult {
+            object,
+            raw_text,
+            usage,
+            finish_reason,
+        }))
+    }
+}
+
+/// The JSON part of streamed text, without a surrounding markdown fence.
+fn json_body(text: &str) -> &str {
+    let trimmed = text.trim_start();
+    let Some(fenced) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    // Hold back until the fence's language tag is complete.
+    let Some(newline) = fenced.find('\n') else {
+        return "";
+    };
+    fenced[newline..].trim_end().trim_end_matches('`')
+}
+
+/// Check `value` against the type skeleton of `schema`, appending what does
+/// not match to `found`.
+///
+/// `open_string` is the pointer of a string that is still streaming; it
+/// only has to be a prefix of an allowed `enum` value. `complete` enables
+/// the `required` check.
+fn validate(
+    value: &Value,
+    schema: &Value,
+    path: &str,
+    open_string: Option<&str>,
+    complete: bool,
+    found: &mut Vec<SchemaViolation>,
+) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if ["anyOf", "oneOf", "allOf", "$ref"]
+        .iter()
+        .any(|key| schema.contains_key(*key))
+    {
+        return;
+    }
+    let mut violation = |message: String| {
+        found.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            violation(format!(
+                "expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        let streaming = open_string == Some(path);
+        let matches = match value {
+            Value::String(prefix) if streaming => allowed
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|option| option.starts_with(prefix.as_str())),
+            _ => allowed.contains(value),
+        };
+        if !matches {
+            let allowed = allowed
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let shown = if streaming {
+                format!("{value} (so far)")
+            } else {
+                value.to_string()
+            };
+            violation(format!("{shown} is not one of {allowed}"));
+            return;
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties");
+            for (key, child) in object {
+                let child_path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (
+                    properties.and_then(|properties| properties.get(key)),
+                    additional,
+                ) {
+                    (Some(child_schema), _) | (None, Some(child_schema @ Value::Object(_))) => {
+                        validate(
+                            child,
+                            child_schema,
+                            &child_path,
+                            open_string,
+                            complete,
+                            found,
+                        )
+                    }
+                    (None, Some(Value::Bool(false))) => found.push(SchemaViolation {
+                        path: child_path,
+                        message: format!("unknown property '{key}'"),
+                    }),
+                    (None, _) => {}
+                }
+            }
+            if complete {
+                for key in schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                {
+                    if !object.contains_key(key) {
+                        found.push(SchemaViolation {
+                            path: path.to_string(),
+                            message: format!("missing required property '{key}'"),
+                        });
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(
+                        item,
+                        item_schema,
+                        &format!("{path}/{index}"),
+                        open_string,
+                        complete,
+                        found,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::ModelCapabilities;
+    use crate::provider::ProviderResponse;
+
+    /// Streams fixed chunks, counting how many were pulled and noting when
+    /// the stream (the provider request) is dropped.
+    struct ChunkProvider {
+        chunks: Mutex<Vec<String>>,
+        pulled: Arc<AtomicUsize>,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    struct CancelGuard(Arc<AtomicBool>);
+
+    impl Drop for CancelGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl ChunkProvider {
+        fn new(chunks: &[&str]) -> Self {
+            Self {
+                chunks: Mutex::new(chunks.iter().map(|chunk| chunk.to_string()).collect()),
+                pulled: Arc::new(AtomicUsize::new(0)),
+                cancelled: Arc::new(AtomicBool::new(false)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ModelProvider for ChunkProvider {
+        fn provider_name(&self) -> &str {
+            "stub"
+        }
+
+        fn model_id(&self) -> &str {
+            "model"
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            static CAPABILITIES: std::sync::OnceLock<ModelCapabilities> =
+                std::sync::OnceLock::new();
+            CAPABILITIES.get_or_init(ModelCapabilities::default)
+        }
+
+        async fn generate_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            panic!("stream_object should not call generate_text")
+        }
+
+        async fn stream_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            let chunks = std::mem::take(&mut *self.chunks.lock().unwrap());
+            let pulled = self.pulled.clone();
+            let guard = CancelGuard(self.cancelled.clone());
+            Ok(Box::pin(async_stream::stream! {
+                let _guard = guard;
+                for chunk in chunks {
+                    pulled.fetch_add(1, Ordering::SeqCst);
+                    yield Ok(TextStreamDelta {
+                        text: chunk,
+                        event_type: StreamEventType::TextDelta,
+                        tool_call: None,
+                        finish_reason: None,
+                        usage: None,
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    });
+                }
+            }))
+        }
+    }
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Ticket {
+        title: String,
+        priority: String,
+        estimate: u32,
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "priority": {"type": "string", "enum": ["low", "high"]},
+                "estimate": {"type": "integer"}
+            },
+            "required": ["title", "priority", "estimate"],
+            "additionalProperties": false
+        })
+    }
+
+    async fn collect(
+        provider: Arc<ChunkProvider>,
+        mode: ViolationMode,
+    ) -> Vec<Result<ObjectStreamEvent<Ticket>, RociError>> {
+        stream_object::<Ticket>(
+            provider,
+            vec![ModelMessage::user("file a ticket")],
+            GenerationSettings::default(),
+            schema(),
+            "Ticket",
+            StreamObjectOptions { on_violation: mode },
+        )
+        .await
+        .unwrap()
+        .collect()
+        .await
+    }
+
+    #[tokio::test]
+    async fn streams_partials_then_the_object() {
+        let provider = Arc::new(ChunkProvider::new(&[
+            "```json\n{\"title\": \"Fix lo",
+            "gin\", \"priority\": \"hi",
+            "gh\", \"estimate\": 3}",
+            "\n```",
+        ]));
+
+        let events = collect(provider, ViolationMode::Abort).await;
+
+        let partials = events
+            .iter()
+            .filter_map(|event| match event {
+                Ok(ObjectStreamEvent::Partial(value)) => Some(value.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(partials[0], json!({"title": "Fix lo"}));
+        assert_eq!(partials[1], json!({"title": "Fix login", "priority": "hi"}));
+        match events.last() {
+            Some(Ok(ObjectStreamEvent::Done(result))) => assert_eq!(
+                result.object,
+                Ticket {
+                    title: "Fix login".into(),
+                    priority: "high".into(),
+                    estimate: 3,
+                }
+            ),
+            other => panic!("expected the object, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn abort_cancels_the_request_at_the_first_violation() {
+        let provider = Arc::new(ChunkProvider::new(&[
+            "{\"title\": \"Fix login\", ",
+            "\"priority\": \"hi",
+            "gh\", \"estimate\": \"thr",
+            "ee days\"}",
+            " ",
+            " ",
+        ]));
+
+        let mut stream = stream_object::<Ticket>(
+            provider.clone(),
+            vec![ModelMessage::user("file a ticket")],
+            GenerationSettings::default(),
+            schema(),
+            "Ticket",
+            StreamObjectOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let error = loop {
+            match stream.next().await {
+                Some(Ok(_)) => continue,
+                Some(Err(error)) => break error,
+                None => panic!("expected a schema violation"),
+            }
+        };
+        match &error {
+            RociError::SchemaViolation(violations) => {
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].path, "/estimate");
+                assert_eq!(violations[0].message, "expected integer, found string");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert_eq!(provider.pulled.load(Ordering::SeqCst), 3);
+        assert!(provider.cancelled.load(Ordering::SeqCst));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn open_string_fails_enum_once_it_matches_no_option() {
+        let provider = Arc::new(ChunkProvider::new(&[
+            "{\"title\": \"x\", \"priority\": \"h",
+            "ur",
+            "ry\", \"estimate\": 1}",
+        ]));
+
+        let events = collect(provider.clone(), ViolationMode::Abort).await;
+
+        assert_eq!(provider.pulled.load(Ordering::SeqCst), 2);
+        match events.last() {
+            Some(Err(RociError::SchemaViolation(violations))) => {
+                assert_eq!(violations[0].path, "/priority");
+                assert!(violations[0].message.contains("\"hur\" (so far)"));
+            }
+            other => panic!("expected an enum violation, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn continue_reports_violations_and_fails_at_the_end() {
+        let provider = Arc::new(ChunkProvider::new(&[
+            "{\"title\": \"x\", \"owner\": \"me\", ",
+            "\"priority\": \"low\"}",
+        ]));
+
+        let events = collect(provider.clone(), ViolationMode::Continue).await;
+
+        assert_eq!(provider.pulled.load(Ordering::SeqCst), 2);
+        let mid_stream = events
+            .iter()
+            .filter_map(|event| match event {
+                Ok(ObjectStreamEvent::Violation(violation)) => Some(violation.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(mid_stream.len(), 1);
+        assert_eq!(mid_stream[0].message, "unknown property 'owner'");
+        match events.last() {
+            Some(Err(RociError::SchemaViolation(violations))) => {
+                let messages = violations
+                    .iter()
+                    .map(|violation| violation.message.as_str())
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    messages,
+                    [
+                        "unknown property 'owner'",
+                        "missing required property 'estimate'"
+                    ]
+                );
+            }
+            other => panic!("expected the collected violations, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn nullable_types_and_nested_items_are_checked() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "note": {"type": ["string", "null"]}
+            }
+        });
+        let mut found = Vec::new();
+
+        validate(
+            &json!({"tags": ["a", 2], "note": null}),
+            &schema,
+            "",
+            None,
+            false,
+            &mut found,
+        );
+
+        assert_eq!(
+            found,
+            vec![SchemaViolation {
+                path: "/tags/1".into(),
+                message: "expected string, found number".into(),
+            }]
+        );
+    }
+}
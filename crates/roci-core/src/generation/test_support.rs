@@ -0,0 +1,164 @@
+//! Mock provider registry shared by the multi-request generation tests.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+
+use crate::config::RociConfig;
+use crate::error::RociError;
+use crate::models::{LanguageModel, ModelCapabilities, ModelCatalog, ModelListOptions};
+use crate::provider::{
+    ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
+};
+use crate::types::*;
+
+/// Builds a model's answer from its id and the request it received.
+pub(super) type Reply = fn(&str, &ProviderRequest) -> String;
+
+/// Requests seen by every provider the registry created.
+#[derive(Default)]
+pub(super) struct Calls {
+    pub in_flight: AtomicUsize,
+    pub max_in_flight: AtomicUsize,
+    /// `(model_id, request)` in arrival order.
+    pub requests: Mutex<Vec<(String, ProviderRequest)>>,
+}
+
+impl Calls {
+    pub fn requests_to(&self, model_id: &str) -> Vec<ProviderRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _)| id == model_id)
+            .map(|(_, request)| request.clone())
+            .collect()
+    }
+}
+
+/// Answers after 40ms (60ms for `slow`), and fails for `broken`.
+struct MockProvider {
+    model_id: String,
+    reply: Reply,
+    calls: Arc<Calls>,
+}
+
+#[async_trait]
+impl ModelProvider for MockProvider {
+    fn provider_name(&self) -> &str {
+        "mock"
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        static CAPABILITIES: std::sync::OnceLock<ModelCapabilities> = std::sync::OnceLock::new();
+        CAPABILITIES.get_or_init(ModelCapabilities::default)
+    }
+
+    async fn generate_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        self.calls
+            .requests
+            .lock()
+            .unwrap()
+            .push((self.model_id.clone(), request.clone()));
+        let now = self.calls.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.calls.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        let delay = match self.model_id.as_str() {
+            "slow" => 60,
+            _ => 40,
+        };
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        self.calls.in_flight.fetch_sub(1, Ordering::SeqCst);
+        if self.model_id == "broken" {
+            return Err(RociError::Stream("connection reset".into()));
+        }
+        Ok(ProviderResponse {
+            text: (self.reply)(&self.model_id, request),
+            usage: Usage {
+                input_tokens: 12,
+                output_tokens: 3,
+                total_tokens: 15,
+                ..Usage::default()
+            },
+            tool_calls: Vec::new(),
+            finish_reason: Some(FinishReason::Stop),
+            thinking: Vec::new(),
+            metadata: std::collections::HashMap::new(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
+        })
+    }
+
+    async fn stream_text(
+        &self,
+        _request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        panic!("mock provider does not stream")
+    }
+}
+
+struct MockFactory {
+    reply: Reply,
+    calls: Arc<Calls>,
+}
+
+impl ProviderFactory for MockFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["mock"]
+    }
+
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn list_models<'a>(
+        &'a self,
+        _config: &'a RociConfig,
+        _provider_key: &'a str,
+        _options: &'a ModelListOptions,
+    ) -> futures::future::BoxFuture<'a, Result<ModelCatalog, RociError>> {
+        Box::pin(async { Ok(ModelCatalog::default()) })
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(MockProvider {
+            model_id: model_id.to_string(),
+            reply: self.reply,
+            calls: self.calls.clone(),
+        }))
+    }
+}
+
+/// A registry whose only provider key is `mock`.
+pub(super) fn registry(reply: Reply) -> (ProviderRegistry, Arc<Calls>) {
+    let calls = Arc::new(Calls::default());
+    let mut registry = ProviderRegistry::new();
+    registry.register(Arc::new(MockFactory {
+        reply,
+        calls: calls.clone(),
+    }));
+    (registry, calls)
+}
+
+pub(super) fn model(id: &str) -> LanguageModel {
+    LanguageModel::Known {
+        provider_key: "mock".to_string(),
+        model_id: id.to_string(),
+        provider_options: Default::default(),
+    }
+}
@@ -3,7 +3,7 @@
 use tracing::debug;
 
 use crate::error::RociError;
-use crate::provider::{ModelProvider, ProviderRequest};
+use crate::provider::{validate_assistant_prefix, ModelProvider, ProviderRequest};
 use crate::tools::tool::Tool;
 use crate::types::*;
 
@@ -19,9 +19,10 @@ pub async fn generate_text(
             "generation::generate_text does not execute tools; use Agent, AgentRuntime, or agent_loop::LoopRunner for tool-capable runs".to_string(),
         ));
     }
+    validate_assistant_prefix(provider, &settings)?;
 
     let request = ProviderRequest {
-        messages: messages.clone(),
+        messages: messages.clone().into(),
         settings: settings.clone(),
         tools: None,
         response_format: settings.response_format.clone(),
@@ -31,6 +32,7 @@ pub async fn generate_text(
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     debug!("generate_text: calling provider");
@@ -40,14 +42,21 @@ pub async fn generate_text(
         tool_calls: response.tool_calls.clone(),
         tool_results: Vec::new(),
         usage: response.usage.clone(),
-        finish_reason: response.finish_reason,
+        finish_reason: response.finish_reason.clone(),
     };
+    let images = response
+        .images
+        .iter()
+        .map(GeneratedImage::decode)
+        .collect::<Result<Vec<_>, _>>()?;
     Ok(GenerateTextResult {
         text: response.text,
         steps: vec![step],
         messages,
         usage: response.usage,
         finish_reason: response.finish_reason,
+        refusal: response.refusal,
+        images,
     })
 }
 
@@ -117,4 +126,23 @@ mod tests {
             matches!(err, RociError::UnsupportedOperation(message) if message.contains("AgentRuntime"))
         );
     }
+
+    #[tokio::test]
+    async fn generate_text_rejects_assistant_prefix_for_unsupported_provider() {
+        let err = generate_text(
+            &StubProvider,
+            vec![ModelMessage::user("hello"), ModelMessage::assistant("Hi")],
+            GenerationSettings {
+                assistant_prefix: Some(true),
+                ..Default::default()
+            },
+            &[],
+        )
+        .await
+        .expect_err("assistant prefix should be rejected");
+
+        assert!(
+            matches!(err, RociError::InvalidArgument(message) if message.contains("assistant_prefix"))
+        );
+    }
 }
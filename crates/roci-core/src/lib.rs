@@ -13,6 +13,7 @@ pub mod context;
 pub mod error;
 pub mod generation;
 pub mod human_interaction;
+pub mod memory;
 pub mod models;
 pub mod prelude;
 pub mod provider;
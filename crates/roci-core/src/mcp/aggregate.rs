@@ -9,12 +9,14 @@ use tokio::sync::Mutex;
 use crate::error::RociError;
 use crate::tools::arguments::ToolArguments;
 use crate::tools::dynamic::{DynamicTool, DynamicToolProvider};
-use crate::tools::tool::{ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary};
+use crate::tools::tool::ToolExecutionContext;
 use crate::tools::types::AgentToolParameters;
 
+use super::bridge::with_mcp_annotations;
 use super::client::MCPClient;
 use super::client_ops::MCPClientOps;
 use super::instructions::{MCPInstructionSource, MCPServerMetadata};
+use super::schema::MCPToolAnnotations;
 use super::server::McpToolIdentity;
 
 /// Tool naming policy used while merging tools across MCP servers.
@@ -159,6 +161,7 @@ pub struct MCPAggregatedTool {
     pub upstream_tool_name: String,
     pub description: String,
     pub parameters: AgentToolParameters,
+    pub annotations: MCPToolAnnotations,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -320,6 +323,7 @@ impl MCPToolAggregator {
                     upstream_tool_name,
                     description: tool.description.unwrap_or_default(),
                     parameters: AgentToolParameters::from_schema(tool.input_schema),
+                    annotations: tool.annotations,
                 };
                 server_tools.push(tool);
             }
@@ -596,17 +600,20 @@ impl MCPToolAggregator {
                 MCPServerFailureCategory::Authentication
             }
             RociError::Timeout(_) => MCPServerFailureCategory::Timeout,
-            RociError::Network(_) | RociError::Io(_) | RociError::Stream(_) => {
-                MCPServerFailureCategory::Transport
-            }
+            RociError::Network(_)
+            | RociError::Io(_)
+            | RociError::Stream(_)
+            | RociError::BackgroundInterrupted { .. } => MCPServerFailureCategory::Transport,
             RociError::Api { .. }
             | RociError::Provider { .. }
             | RociError::ModelNotFound(_)
             | RociError::UnsupportedOperation(_)
-            | RociError::RateLimited { .. } => MCPServerFailureCategory::Provider,
+            | RociError::RateLimited { .. }
+            | RociError::Refused { .. } => MCPServerFailureCategory::Provider,
             RociError::Serialization(_)
             | RociError::InvalidArgument(_)
-            | RociError::InvalidState(_) => MCPServerFailureCategory::Protocol,
+            | RociError::InvalidState(_)
+            | RociError::SchemaViolation(_) => MCPServerFailureCategory::Protocol,
             RociError::ToolExecution { .. } => MCPServerFailureCategory::Unknown,
         };
         MCPServerFailure {
@@ -670,12 +677,9 @@ impl DynamicToolProvider for MCPToolAggregator {
         Ok(tools
             .into_iter()
             .map(|tool| {
-                DynamicTool::new(tool.exposed_name, tool.description, tool.parameters).with_safety(
-                    ToolSafetyPlan::approval_required(ToolSafetyKind::Mcp),
-                    ToolSafetySummary {
-                        approval_kind: ToolSafetyKind::Mcp,
-                        ..ToolSafetySummary::default()
-                    },
+                with_mcp_annotations(
+                    DynamicTool::new(tool.exposed_name, tool.description, tool.parameters),
+                    &tool.annotations,
                 )
             })
             .collect())
@@ -689,12 +693,9 @@ impl DynamicToolProvider for MCPToolAggregator {
         Ok(tools
             .into_iter()
             .map(|tool| {
-                DynamicTool::new(tool.exposed_name, tool.description, tool.parameters).with_safety(
-                    ToolSafetyPlan::approval_required(ToolSafetyKind::Mcp),
-                    ToolSafetySummary {
-                        approval_kind: ToolSafetyKind::Mcp,
-                        ..ToolSafetySummary::default()
-                    },
+                with_mcp_annotations(
+                    DynamicTool::new(tool.exposed_name, tool.description, tool.parameters),
+                    &tool.annotations,
                 )
             })
             .collect())
@@ -735,7 +736,7 @@ mod tests {
     use crate::mcp::client::{
         MCPReadResourceResult, MCPResourceContent, MCPResourceSchema, MCPToolCallResult,
     };
-    use crate::mcp::schema::MCPToolSchema;
+    use crate::mcp::schema::{MCPToolAnnotations, MCPToolSchema};
     use crate::tools::ScopedDynamicToolProvider;
 
     struct MockClientOps {
@@ -886,6 +887,7 @@ mod tests {
                     "q": { "type": "string" }
                 }
             }),
+            annotations: MCPToolAnnotations::default(),
         }
     }
 
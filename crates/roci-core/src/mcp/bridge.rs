@@ -1,34 +1,49 @@
 //! Bridge MCP tools into the Roci tool system.
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use tokio::sync::Mutex;
 
 use crate::error::RociError;
 use crate::tools::arguments::ToolArguments;
 use crate::tools::dynamic::{DynamicTool, DynamicToolProvider};
-use crate::tools::tool::{ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary};
+use crate::tools::tool::{
+    ToolEffects, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+};
 use crate::tools::types::AgentToolParameters;
 
-use super::client::MCPClient;
+use super::client::{MCPCallOptions, MCPClient, MCPToolCallResult};
 use super::client_ops::MCPClientOps;
-use super::schema::MCPToolSchema;
+use super::schema::{MCPToolAnnotations, MCPToolSchema};
 
 /// Adapts an MCP client to the DynamicToolProvider trait.
+///
+/// Through [`Tool::execute_ext`](crate::tools::Tool::execute_ext), a cancelled
+/// run or an expired [`with_call_timeout`](Self::with_call_timeout) sends the
+/// server `notifications/cancelled` for the in-flight call, and the server's
+/// progress notifications arrive as tool updates.
 pub struct MCPToolAdapter {
     client: Mutex<Box<dyn MCPClientOps>>,
+    call_timeout: Option<Duration>,
 }
 
 impl MCPToolAdapter {
     pub fn new(client: MCPClient) -> Self {
-        Self {
-            client: Mutex::new(Box::new(client)),
-        }
+        Self::from_client_ops(Box::new(client))
+    }
+
+    /// Cancel tool calls that run longer than `timeout`.
+    #[must_use]
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = Some(timeout);
+        self
     }
 
-    #[cfg(test)]
     fn from_client_ops(client: Box<dyn MCPClientOps>) -> Self {
         Self {
             client: Mutex::new(client),
+            call_timeout: None,
         }
     }
 }
@@ -50,24 +65,139 @@ impl DynamicToolProvider for MCPToolAdapter {
     ) -> Result<serde_json::Value, RociError> {
         let mut client = self.client.lock().await;
         client.initialize().await?;
-        let result = client.call_tool(name, args.raw().clone()).await?;
+        let options = MCPCallOptions {
+            timeout: self.call_timeout,
+            ..MCPCallOptions::default()
+        };
+        let result = client
+            .call_tool_with(name, args.raw().clone(), options)
+            .await?;
         Ok(result.into_value_or_text())
     }
+
+    #[cfg(feature = "agent")]
+    async fn execute_tool_ext(
+        &self,
+        name: &str,
+        args: &ToolArguments,
+        _ctx: &ToolExecutionContext,
+        cancel: tokio_util::sync::CancellationToken,
+        on_update: Option<crate::tools::ToolUpdateCallback>,
+    ) -> Result<serde_json::Value, RociError> {
+        use crate::agent_loop::events::ToolUpdatePayload;
+
+        let options = MCPCallOptions {
+            cancel,
+            on_progress: on_update.clone().map(|on_update| {
+                std::sync::Arc::new(move |progress: super::MCPProgress| {
+                    on_update(ToolUpdatePayload {
+                        content: Vec::new(),
+                        details: serde_json::json!({
+                            "progress": {
+                                "progress": progress.progress,
+                                "total": progress.total,
+                                "fraction": progress.fraction(),
+                                "message": progress.message,
+                            }
+                        }),
+                    });
+                }) as super::MCPProgressCallback
+            }),
+            timeout: self.call_timeout,
+        };
+        let mut client = self.client.lock().await;
+        client.initialize().await?;
+        let result = client
+            .call_tool_with(name, args.raw().clone(), options)
+            .await?;
+        let (value, media) = rich_result(result);
+        if let (Some(parts), Some(on_update)) = (media, on_update) {
+            on_update(ToolUpdatePayload {
+                content: parts,
+                details: serde_json::json!({ "mcp_content": true }),
+            });
+        }
+        Ok(value)
+    }
+}
+
+/// Result value of a call, plus its content parts when they include images.
+///
+/// Structured content and text-only results keep their
+/// [`into_value_or_text`](MCPToolCallResult::into_value_or_text) value. With
+/// images, the value is the content parts as JSON, so images reach the
+/// result instead of being dropped with the text flattening.
+#[cfg_attr(not(feature = "agent"), allow(dead_code))]
+fn rich_result(
+    result: MCPToolCallResult,
+) -> (serde_json::Value, Option<Vec<crate::types::ContentPart>>) {
+    let parts = result.content_parts();
+    let has_media = parts
+        .iter()
+        .any(|part| matches!(part, crate::types::ContentPart::Image(_)));
+    if !has_media {
+        return (result.into_value_or_text(), None);
+    }
+    if result.structured_content.is_some() {
+        return (result.into_value_or_text(), Some(parts));
+    }
+    let value = serde_json::to_value(&parts).unwrap_or_default();
+    (value, Some(parts))
 }
 
 fn map_mcp_tool_to_dynamic(tool: MCPToolSchema) -> DynamicTool {
-    DynamicTool::new(
-        tool.name,
-        tool.description.unwrap_or_default(),
-        AgentToolParameters::from_schema(tool.input_schema),
+    with_mcp_annotations(
+        DynamicTool::new(
+            tool.name,
+            tool.description.unwrap_or_default(),
+            AgentToolParameters::from_schema(tool.input_schema),
+        ),
+        &tool.annotations,
     )
-    .with_safety(
-        ToolSafetyPlan::approval_required(ToolSafetyKind::Mcp),
+}
+
+/// Apply MCP behavior hints to a bridged tool's safety metadata.
+///
+/// Only explicit hints count: `readOnlyHint` makes the tool read-only,
+/// `destructiveHint` marks it destructive, and `openWorldHint` marks it as
+/// network-facing. Tools without hints stay approval-required and mutating.
+pub(super) fn with_mcp_annotations(
+    tool: DynamicTool,
+    annotations: &MCPToolAnnotations,
+) -> DynamicTool {
+    if annotations.read_only_hint == Some(true) {
+        return tool
+            .with_safety(
+                ToolSafetyPlan::safe_read_only(ToolSafetyKind::Mcp),
+                ToolSafetySummary {
+                    read_only_by_default: true,
+                    destructive_by_default: false,
+                    concurrency_safe_by_default: true,
+                    approval_kind: ToolSafetyKind::Mcp,
+                },
+            )
+            .with_effects(ToolEffects::ReadOnly);
+    }
+
+    let destructive = annotations.destructive_hint == Some(true);
+    let mut plan = ToolSafetyPlan::approval_required(ToolSafetyKind::Mcp);
+    plan.destructive = destructive;
+    let effects = if destructive {
+        ToolEffects::Destructive
+    } else if annotations.open_world_hint == Some(true) {
+        ToolEffects::Network
+    } else {
+        ToolEffects::Mutating
+    };
+    tool.with_safety(
+        plan,
         ToolSafetySummary {
+            destructive_by_default: destructive,
             approval_kind: ToolSafetyKind::Mcp,
             ..ToolSafetySummary::default()
         },
     )
+    .with_effects(effects)
 }
 
 #[cfg(test)]
@@ -168,6 +298,7 @@ mod tests {
                     "q": { "type": "string" }
                 }
             }),
+            annotations: MCPToolAnnotations::default(),
         });
 
         assert_eq!(dynamic.name, "search");
@@ -188,6 +319,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_mcp_tool_to_dynamic_applies_annotation_hints() {
+        let tool = |annotations| MCPToolSchema {
+            name: "tool".into(),
+            description: None,
+            input_schema: json!({ "type": "object" }),
+            annotations,
+        };
+
+        let read_only = map_mcp_tool_to_dynamic(tool(MCPToolAnnotations {
+            read_only_hint: Some(true),
+            ..MCPToolAnnotations::default()
+        }));
+        assert!(read_only.safety.read_only);
+        assert!(read_only.safety.approval.auto_accept_under_ask);
+        assert_eq!(read_only.effects, Some(ToolEffects::ReadOnly));
+
+        let destructive = map_mcp_tool_to_dynamic(tool(MCPToolAnnotations {
+            destructive_hint: Some(true),
+            open_world_hint: Some(true),
+            ..MCPToolAnnotations::default()
+        }));
+        assert!(destructive.safety.destructive);
+        assert!(destructive.safety_summary.destructive_by_default);
+        assert_eq!(destructive.effects, Some(ToolEffects::Destructive));
+
+        let network = map_mcp_tool_to_dynamic(tool(MCPToolAnnotations {
+            open_world_hint: Some(true),
+            ..MCPToolAnnotations::default()
+        }));
+        assert!(!network.safety.approval.auto_accept_under_ask);
+        assert_eq!(network.effects, Some(ToolEffects::Network));
+
+        let unannotated = map_mcp_tool_to_dynamic(tool(MCPToolAnnotations::default()));
+        assert_eq!(unannotated.effects, Some(ToolEffects::Mutating));
+    }
+
     #[tokio::test]
     async fn execute_tool_without_session_errors() {
         let adapter = MCPToolAdapter::new(MCPClient::new(Box::new(NoopTransport)));
@@ -229,4 +397,59 @@ mod tests {
             if tool_name == "search" && message.contains("downstream tool failure")
         ));
     }
+
+    #[cfg(feature = "agent")]
+    #[tokio::test]
+    async fn execute_tool_ext_keeps_images_as_content_parts() {
+        use crate::agent_loop::events::ToolUpdatePayload;
+        use crate::types::{ContentPart, ImageContent};
+
+        let adapter = MCPToolAdapter::from_client_ops(Box::new(MockClientOps {
+            initialize_error: None,
+            list_tools_result: Ok(Vec::new()),
+            call_tool_results: VecDeque::from([Ok(MCPToolCallResult {
+                structured_content: None,
+                text_content: Some("chart".into()),
+                content: vec![
+                    json!({ "type": "text", "text": "chart" }),
+                    json!({ "type": "image", "data": "iVBORw0=", "mimeType": "image/png" }),
+                ],
+            })]),
+        }));
+        let updates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let on_update = {
+            let updates = std::sync::Arc::clone(&updates);
+            std::sync::Arc::new(move |payload: ToolUpdatePayload| {
+                updates
+                    .lock()
+                    .expect("updates mutex should lock")
+                    .push(payload);
+            }) as crate::tools::ToolUpdateCallback
+        };
+
+        let value = adapter
+            .execute_tool_ext(
+                "plot",
+                &ToolArguments::new(json!({})),
+                &ToolExecutionContext::default(),
+                tokio_util::sync::CancellationToken::new(),
+                Some(on_update),
+            )
+            .await
+            .expect("tool call should succeed");
+
+        let parts = vec![
+            ContentPart::Text {
+                text: "chart".into(),
+            },
+            ContentPart::Image(ImageContent {
+                data: "iVBORw0=".into(),
+                mime_type: "image/png".into(),
+            }),
+        ];
+        assert_eq!(value, serde_json::to_value(&parts).unwrap());
+        let updates = updates.lock().expect("updates mutex should lock");
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].content, parts);
+    }
 }
@@ -9,17 +9,23 @@ use std::{
 
 use crate::error::RociError;
 use crate::human_interaction::HumanInteractionCoordinator;
+use crate::types::ContentPart;
 use rmcp::{
     model::{
-        CallToolRequestParams, CallToolResult, JsonObject, ProtocolVersion,
-        ReadResourceRequestParams, ReadResourceResult, Resource, ResourceContents,
+        CallToolRequest, CallToolRequestParams, CallToolResult, ClientRequest, JsonObject,
+        ProtocolVersion, ReadResourceRequestParams, ReadResourceResult, Resource, ResourceContents,
+        ServerResult,
     },
-    service::{ClientInitializeError, ServiceError},
+    service::{ClientInitializeError, PeerRequestOptions, ServiceError},
 };
+use tokio_util::sync::CancellationToken;
 
 use super::elicitation::MCPClientHandler;
 use super::error::{map_client_initialize_error, map_service_error};
-use super::mapping::{coerce_tool_arguments, map_call_result, map_mcp_tool_schema};
+use super::mapping::{
+    coerce_tool_arguments, map_call_result, map_content_part, map_mcp_tool_schema,
+};
+use super::progress::{MCPProgressCallback, MCPProgressRouter};
 use super::transport::{MCPRemoteReconnectPolicy, MCPTransport};
 
 pub type MCPRunningService = super::transport::MCPRunningService;
@@ -49,6 +55,35 @@ pub struct MCPToolCallResult {
     pub content: Vec<serde_json::Value>,
 }
 
+/// Cancellation, progress, and deadline for [`MCPClient::call_tool_with`].
+#[derive(Clone, Default)]
+pub struct MCPCallOptions {
+    /// Cancelling sends `notifications/cancelled` for the request and stops
+    /// waiting for its result.
+    pub cancel: CancellationToken,
+    /// Receives the server's `notifications/progress` for the request.
+    pub on_progress: Option<MCPProgressCallback>,
+    /// Cancels the request the same way when it runs longer.
+    pub timeout: Option<Duration>,
+}
+
+impl std::fmt::Debug for MCPCallOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MCPCallOptions")
+            .field("cancelled", &self.cancel.is_cancelled())
+            .field("on_progress", &self.on_progress.is_some())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// How a cancellable tool call ended.
+enum CallOutcome {
+    Finished(CallToolResult),
+    Cancelled,
+    TimedOut(Duration),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct MCPResourceSchema {
     pub uri: String,
@@ -125,6 +160,11 @@ pub struct MCPReadResourceResult {
 }
 
 impl MCPToolCallResult {
+    /// The `content` array as message content parts.
+    pub fn content_parts(&self) -> Vec<ContentPart> {
+        self.content.iter().map(map_content_part).collect()
+    }
+
     pub fn into_value_or_text(self) -> serde_json::Value {
         if let Some(structured) = self.structured_content {
             return structured;
@@ -161,6 +201,7 @@ pub struct MCPClient {
     session_started_at: Option<Instant>,
     last_session_used_at: Option<Instant>,
     last_reconnect_outcome: Option<MCPRemoteReconnectOutcome>,
+    progress: MCPProgressRouter,
 }
 
 impl MCPClient {
@@ -175,12 +216,16 @@ impl MCPClient {
             session_started_at: None,
             last_session_used_at: None,
             last_reconnect_outcome: None,
+            progress: MCPProgressRouter::default(),
         }
     }
 
     /// Create a client from an already-running rmcp service.
     ///
-    /// Initialization handshake is already handled by rmcp `serve(...)`.
+    /// Initialization handshake is already handled by rmcp `serve(...)`. The
+    /// service's own handler receives progress notifications, so
+    /// [`MCPCallOptions::on_progress`] is not called until a reconnect opens a
+    /// session through the transport.
     pub fn from_running_service(session: MCPRunningService) -> Self {
         Self {
             transport: None,
@@ -191,6 +236,7 @@ impl MCPClient {
             session_started_at: None,
             last_session_used_at: None,
             last_reconnect_outcome: None,
+            progress: MCPProgressRouter::default(),
         }
     }
 
@@ -366,6 +412,48 @@ impl MCPClient {
         map_call_result(name, result)
     }
 
+    /// Execute a tool with cooperative cancellation and progress reporting.
+    ///
+    /// On cancellation or timeout the server is sent `notifications/cancelled`
+    /// for the request, and the call returns without waiting for a result:
+    /// [`RociError::Stream`] when cancelled, [`RociError::Timeout`] when the
+    /// deadline passed.
+    pub async fn call_tool_with(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        options: MCPCallOptions,
+    ) -> Result<MCPToolCallResult, RociError> {
+        let arguments = coerce_tool_arguments(arguments)?;
+        let tool_name = name.to_owned();
+        let outcome = self
+            .with_reconnect(
+                "call_tool",
+                move |client| {
+                    let arguments = arguments.clone();
+                    let tool_name = tool_name.clone();
+                    let options = options.clone();
+                    Box::pin(async move {
+                        client
+                            .call_tool_cancellable(&tool_name, arguments, &options)
+                            .await
+                    })
+                },
+                ReconnectReplayPolicy::DoNotReplayTimeouts,
+            )
+            .await?;
+
+        match outcome {
+            CallOutcome::Finished(result) => map_call_result(name, result),
+            CallOutcome::Cancelled => Err(RociError::Stream(
+                "call_tool: MCP request cancelled by client".into(),
+            )),
+            CallOutcome::TimedOut(timeout) => Err(RociError::Timeout(
+                u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+            )),
+        }
+    }
+
     async fn with_reconnect<T, Op>(
         &mut self,
         context: &'static str,
@@ -521,7 +609,8 @@ impl MCPClient {
     }
 
     fn client_handler(&self, protocol_version: ProtocolVersion) -> MCPClientHandler {
-        let handler = MCPClientHandler::new(protocol_version);
+        let handler =
+            MCPClientHandler::new(protocol_version).with_progress_router(self.progress.clone());
         match &self.human_interaction_coordinator {
             Some(coordinator) => {
                 handler.with_ui_elicitation(self.server_id.clone(), Arc::clone(coordinator))
@@ -561,6 +650,60 @@ impl MCPClient {
             .await
     }
 
+    async fn call_tool_cancellable(
+        &mut self,
+        name: &str,
+        arguments: Option<JsonObject>,
+        options: &MCPCallOptions,
+    ) -> Result<CallOutcome, ServiceError> {
+        if options.cancel.is_cancelled() {
+            return Ok(CallOutcome::Cancelled);
+        }
+        let session = self.session.as_mut().ok_or(ServiceError::TransportClosed)?;
+        let request = ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params: CallToolRequestParams {
+                meta: None,
+                name: name.to_owned().into(),
+                arguments,
+                task: None,
+            },
+            extensions: Default::default(),
+        });
+        let mut handle = session
+            .peer()
+            .send_request_with_option(request, PeerRequestOptions::no_options())
+            .await?;
+        let _progress = options.on_progress.clone().map(|callback| {
+            self.progress
+                .register(handle.progress_token.clone(), callback)
+        });
+
+        let deadline = async {
+            match options.timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        let (outcome, reason) = tokio::select! {
+            response = &mut handle.rx => {
+                return match response.map_err(|_| ServiceError::TransportClosed)?? {
+                    ServerResult::CallToolResult(result) => Ok(CallOutcome::Finished(result)),
+                    _ => Err(ServiceError::UnexpectedResponse),
+                };
+            }
+            _ = options.cancel.cancelled() => (CallOutcome::Cancelled, "cancelled by client"),
+            _ = deadline => (
+                CallOutcome::TimedOut(options.timeout.unwrap_or_default()),
+                "tool call timed out",
+            ),
+        };
+        if let Err(error) = handle.cancel(Some(reason.to_string())).await {
+            tracing::debug!(tool = name, %error, "failed to send MCP cancellation");
+        }
+        Ok(outcome)
+    }
+
     async fn list_resources_from_active_session(&mut self) -> Result<Vec<Resource>, ServiceError> {
         let session = self.session.as_mut().ok_or(ServiceError::TransportClosed)?;
 
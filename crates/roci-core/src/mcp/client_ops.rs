@@ -4,7 +4,9 @@ use async_trait::async_trait;
 
 use crate::error::RociError;
 
-use super::client::{MCPClient, MCPReadResourceResult, MCPResourceSchema, MCPToolCallResult};
+use super::client::{
+    MCPCallOptions, MCPClient, MCPReadResourceResult, MCPResourceSchema, MCPToolCallResult,
+};
 use super::schema::MCPToolSchema;
 
 /// Internal operations required by MCP adapters and aggregators.
@@ -20,6 +22,17 @@ pub(super) trait MCPClientOps: Send {
         name: &str,
         arguments: serde_json::Value,
     ) -> Result<MCPToolCallResult, RociError>;
+
+    /// [`call_tool`](Self::call_tool) with cancellation and progress.
+    /// Defaults to ignoring `options`.
+    async fn call_tool_with(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        _options: MCPCallOptions,
+    ) -> Result<MCPToolCallResult, RociError> {
+        self.call_tool(name, arguments).await
+    }
 }
 
 #[async_trait]
@@ -51,4 +64,13 @@ impl MCPClientOps for MCPClient {
     ) -> Result<MCPToolCallResult, RociError> {
         MCPClient::call_tool(self, name, arguments).await
     }
+
+    async fn call_tool_with(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        options: MCPCallOptions,
+    ) -> Result<MCPToolCallResult, RociError> {
+        MCPClient::call_tool_with(self, name, arguments, options).await
+    }
 }
@@ -33,6 +33,11 @@ enum MockSessionBehavior {
         blob_base64: String,
     },
     CallTool,
+    /// Report progress twice, never answer, and forward the client's
+    /// `notifications/cancelled` messages.
+    HangingCallTool {
+        cancellations: UnboundedSender<serde_json::Value>,
+    },
 }
 
 struct ChannelRmcpTransport {
@@ -74,6 +79,13 @@ impl RmcpTransport<RoleClient> for ChannelRmcpTransport {
 }
 
 fn scripted_running_service(behavior: MockSessionBehavior) -> MCPRunningService {
+    scripted_running_service_with_handler(behavior, ())
+}
+
+fn scripted_running_service_with_handler(
+    behavior: MockSessionBehavior,
+    handler: impl rmcp::ClientHandler,
+) -> MCPRunningService {
     let (outbound_tx, mut outbound_rx) = unbounded_channel::<TxJsonRpcMessage<RoleClient>>();
     let (inbound_tx, inbound_rx) = unbounded_channel::<RxJsonRpcMessage<RoleClient>>();
     let transport = ChannelRmcpTransport::new(outbound_tx, inbound_rx);
@@ -200,12 +212,96 @@ fn scripted_running_service(behavior: MockSessionBehavior) -> MCPRunningService
                     .expect("mock tools/call response should deserialize");
                     let _ = inbound_tx.send(response);
                 }
+                (MockSessionBehavior::HangingCallTool { .. }, "tools/call") => {
+                    let token = value
+                        .pointer("/params/_meta/progressToken")
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null);
+                    for (progress, message) in [(1, "indexing"), (2, "ranking")] {
+                        let notification: ServerJsonRpcMessage = serde_json::from_value(json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/progress",
+                            "params": {
+                                "progressToken": token,
+                                "progress": progress,
+                                "total": 4,
+                                "message": message
+                            }
+                        }))
+                        .expect("mock progress notification should deserialize");
+                        let _ = inbound_tx.send(notification);
+                    }
+                }
+                (
+                    MockSessionBehavior::HangingCallTool { cancellations },
+                    "notifications/cancelled",
+                ) => {
+                    let _ = cancellations.send(value.clone());
+                }
                 _ => {}
             }
         }
     });
 
-    serve_directly(().into_dyn(), transport, None)
+    serve_directly(handler.into_dyn(), transport, None)
+}
+
+/// Opens one scripted session through the client's own handler, so
+/// progress notifications reach the client.
+struct HandlerScriptedTransport {
+    behavior: Option<MockSessionBehavior>,
+}
+
+#[async_trait]
+impl MCPTransport for HandlerScriptedTransport {
+    #[allow(clippy::result_large_err)]
+    async fn connect(
+        &mut self,
+        client_handler: MCPClientHandler,
+    ) -> Result<MCPRunningService, ClientInitializeError> {
+        let behavior = self.behavior.take().ok_or_else(|| {
+            ClientInitializeError::ConnectionClosed("scripted session already used".into())
+        })?;
+        Ok(scripted_running_service_with_handler(
+            behavior,
+            client_handler,
+        ))
+    }
+
+    async fn send(&mut self, _message: serde_json::Value) -> Result<(), RociError> {
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<serde_json::Value, RociError> {
+        Ok(serde_json::Value::Null)
+    }
+
+    async fn close(&mut self) -> Result<(), RociError> {
+        Ok(())
+    }
+}
+
+/// An initialized client whose tool calls hang after two progress
+/// notifications, and the receiver of the cancellations it sends.
+async fn hanging_tool_client() -> (MCPClient, UnboundedReceiver<serde_json::Value>) {
+    let (cancellations, cancelled) = unbounded_channel();
+    let mut client = MCPClient::new(Box::new(HandlerScriptedTransport {
+        behavior: Some(MockSessionBehavior::HangingCallTool { cancellations }),
+    }));
+    client
+        .initialize()
+        .await
+        .expect("initialize should succeed");
+    (client, cancelled)
+}
+
+async fn next_cancellation(
+    cancelled: &mut UnboundedReceiver<serde_json::Value>,
+) -> serde_json::Value {
+    tokio::time::timeout(std::time::Duration::from_secs(2), cancelled.recv())
+        .await
+        .expect("cancellation should be sent")
+        .expect("mock server should stay up")
 }
 
 struct MockBootstrapTransport {
@@ -409,6 +505,115 @@ async fn call_tool_works_after_transport_bootstrap() {
     assert_eq!(result.text_content.as_deref(), Some("tool ok"));
 }
 
+#[tokio::test]
+async fn call_tool_with_reports_progress_and_sends_cancellation() {
+    let (mut client, mut cancelled) = hanging_tool_client().await;
+    let cancel = CancellationToken::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let on_progress = {
+        let seen = Arc::clone(&seen);
+        let cancel = cancel.clone();
+        Arc::new(move |progress: crate::mcp::MCPProgress| {
+            let mut seen = seen.lock().expect("progress mutex should lock");
+            seen.push(progress);
+            if seen.len() == 2 {
+                cancel.cancel();
+            }
+        }) as MCPProgressCallback
+    };
+
+    let err = client
+        .call_tool_with(
+            "slow",
+            json!({}),
+            MCPCallOptions {
+                cancel,
+                on_progress: Some(on_progress),
+                timeout: None,
+            },
+        )
+        .await
+        .expect_err("cancelled call should not wait for a result");
+
+    assert!(matches!(err, RociError::Stream(message) if message.contains("cancelled")));
+    let seen = seen.lock().expect("progress mutex should lock").clone();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].fraction(), Some(0.25));
+    assert_eq!(seen[1].message.as_deref(), Some("ranking"));
+    let notification = next_cancellation(&mut cancelled).await;
+    assert_eq!(notification["params"]["requestId"], json!(0));
+    assert_eq!(notification["params"]["reason"], "cancelled by client");
+}
+
+#[tokio::test]
+async fn call_tool_with_timeout_sends_cancellation() {
+    let (mut client, mut cancelled) = hanging_tool_client().await;
+
+    let err = client
+        .call_tool_with(
+            "slow",
+            json!({}),
+            MCPCallOptions {
+                timeout: Some(std::time::Duration::from_millis(50)),
+                ..MCPCallOptions::default()
+            },
+        )
+        .await
+        .expect_err("call should time out");
+
+    assert!(matches!(err, RociError::Timeout(50)));
+    let notification = next_cancellation(&mut cancelled).await;
+    assert_eq!(notification["params"]["reason"], "tool call timed out");
+}
+
+#[cfg(feature = "agent")]
+#[tokio::test]
+async fn bridged_tool_surfaces_progress_as_updates_and_cancels_on_abort() {
+    use crate::agent_loop::events::ToolUpdatePayload;
+    use crate::tools::dynamic::{DynamicTool, DynamicToolAdapter};
+    use crate::tools::{Tool, ToolArguments, ToolExecutionContext, ToolUpdateCallback};
+
+    let (client, mut cancelled) = hanging_tool_client().await;
+    let tool = DynamicToolAdapter::new(
+        Arc::new(crate::mcp::MCPToolAdapter::new(client)),
+        DynamicTool::new(
+            "slow",
+            "slow tool",
+            crate::tools::AgentToolParameters::empty(),
+        ),
+    );
+    let cancel = CancellationToken::new();
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let on_update: ToolUpdateCallback = {
+        let updates = Arc::clone(&updates);
+        let cancel = cancel.clone();
+        Arc::new(move |payload: ToolUpdatePayload| {
+            let mut updates = updates.lock().expect("updates mutex should lock");
+            updates.push(payload);
+            if updates.len() == 2 {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let err = tool
+        .execute_ext(
+            &ToolArguments::new(json!({})),
+            &ToolExecutionContext::default(),
+            cancel,
+            Some(on_update),
+        )
+        .await
+        .expect_err("aborted tool call should fail");
+
+    assert!(matches!(err, RociError::Stream(_)));
+    let updates = updates.lock().expect("updates mutex should lock").clone();
+    assert_eq!(updates[0].details["progress"]["fraction"], json!(0.25));
+    assert_eq!(updates[1].details["progress"]["message"], "ranking");
+    assert!(updates.iter().all(|update| update.content.is_empty()));
+    next_cancellation(&mut cancelled).await;
+}
+
 #[path = "client_reconnect_tests.rs"]
 mod reconnect_tests;
 
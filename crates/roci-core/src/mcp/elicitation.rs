@@ -7,14 +7,15 @@ use chrono::Utc;
 use rmcp::model::{
     ClientInfo, CreateElicitationRequestParams, CreateElicitationResult, ElicitationAction,
     ElicitationCapability, ElicitationSchema, EnumSchema, ErrorData as McpError,
-    FormElicitationCapability, Meta, MultiSelectEnumSchema, PrimitiveSchema, ProtocolVersion,
-    SingleSelectEnumSchema,
+    FormElicitationCapability, Meta, MultiSelectEnumSchema, PrimitiveSchema,
+    ProgressNotificationParam, ProtocolVersion, SingleSelectEnumSchema,
 };
-use rmcp::service::{RequestContext, RoleClient};
+use rmcp::service::{NotificationContext, RequestContext, RoleClient};
 use rmcp::ClientHandler;
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::progress::MCPProgressRouter;
 use crate::human_interaction::{
     HumanInteractionCoordinator, HumanInteractionPayload, HumanInteractionRequest,
     HumanInteractionResponse, HumanInteractionResponsePayload, HumanInteractionSource,
@@ -27,6 +28,7 @@ const ELICITATION_CREATE_OPERATION: &str = "elicitation/create";
 pub struct MCPClientHandler {
     client_info: ClientInfo,
     ui_elicitation: Option<MCPUiElicitationHandler>,
+    progress: MCPProgressRouter,
 }
 
 #[derive(Debug, Clone)]
@@ -43,9 +45,16 @@ impl MCPClientHandler {
                 ..Default::default()
             },
             ui_elicitation: None,
+            progress: MCPProgressRouter::default(),
         }
     }
 
+    /// Deliver `notifications/progress` through `router`.
+    pub(super) fn with_progress_router(mut self, router: MCPProgressRouter) -> Self {
+        self.progress = router;
+        self
+    }
+
     pub fn with_ui_elicitation(
         mut self,
         server_id: String,
@@ -102,6 +111,14 @@ impl ClientHandler for MCPClientHandler {
         self.handle_create_elicitation(request).await
     }
 
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.progress.dispatch(params);
+    }
+
     fn get_info(&self) -> ClientInfo {
         self.client_info.clone()
     }
@@ -89,6 +89,9 @@ pub enum MCPInstructionMergePolicy {
 }
 
 /// Merge MCP instruction sources with an existing system prompt.
+///
+/// Full system prompts are assembled by
+/// [`SystemPromptComposer`](crate::resource::SystemPromptComposer).
 pub fn merge_mcp_instructions(
     system_prompt: Option<&str>,
     instructions: &[MCPInstructionSource],
@@ -1,16 +1,25 @@
 //! MCP result, content, and schema mapping functions.
 
 use crate::error::RociError;
+use crate::types::{ContentPart, ImageContent};
 use rmcp::model::{CallToolResult, Content, JsonObject, ResourceContents};
 
 use super::client::MCPToolCallResult;
-use super::schema::MCPToolSchema;
+use super::schema::{MCPToolAnnotations, MCPToolSchema};
 
 pub(super) fn map_mcp_tool_schema(tool: rmcp::model::Tool) -> MCPToolSchema {
     MCPToolSchema {
         name: tool.name.to_string(),
         description: tool.description.map(|d| d.to_string()),
         input_schema: serde_json::Value::Object((*tool.input_schema).clone()),
+        annotations: tool
+            .annotations
+            .map(|annotations| MCPToolAnnotations {
+                read_only_hint: annotations.read_only_hint,
+                destructive_hint: annotations.destructive_hint,
+                open_world_hint: annotations.open_world_hint,
+            })
+            .unwrap_or_default(),
     }
 }
 
@@ -57,6 +66,63 @@ fn extract_text_content(content: &[Content]) -> Option<String> {
     }
 }
 
+/// Map one serialized MCP content item to a [`ContentPart`].
+///
+/// Text and images map directly; embedded resources become text or, for
+/// image blobs, an image. Other items become a text placeholder so the
+/// model knows content was omitted.
+pub(super) fn map_content_part(item: &serde_json::Value) -> ContentPart {
+    let str_field = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|field| field.as_str())
+            .map(str::to_string)
+    };
+    let kind = item
+        .get("type")
+        .and_then(|kind| kind.as_str())
+        .unwrap_or("");
+    match kind {
+        "text" => ContentPart::Text {
+            text: str_field(item, "text").unwrap_or_default(),
+        },
+        "image" => match (str_field(item, "data"), str_field(item, "mimeType")) {
+            (Some(data), Some(mime_type)) => ContentPart::Image(ImageContent { data, mime_type }),
+            _ => ContentPart::Text {
+                text: "[MCP image without data omitted]".into(),
+            },
+        },
+        "resource" => {
+            let resource = item.get("resource").cloned().unwrap_or_default();
+            let uri = str_field(&resource, "uri").unwrap_or_default();
+            let mime_type = str_field(&resource, "mimeType");
+            if let Some(text) = str_field(&resource, "text") {
+                return ContentPart::Text { text };
+            }
+            match (str_field(&resource, "blob"), mime_type) {
+                (Some(data), Some(mime_type)) if mime_type.starts_with("image/") => {
+                    ContentPart::Image(ImageContent { data, mime_type })
+                }
+                (_, mime_type) => ContentPart::Text {
+                    text: format!(
+                        "[MCP resource {uri} ({}) omitted]",
+                        mime_type.as_deref().unwrap_or("binary")
+                    ),
+                },
+            }
+        }
+        "resource_link" => ContentPart::Text {
+            text: format!(
+                "[MCP resource link: {}]",
+                str_field(item, "uri").unwrap_or_default()
+            ),
+        },
+        other => ContentPart::Text {
+            text: format!("[MCP {other} content omitted]"),
+        },
+    }
+}
+
 pub(super) fn map_call_result(
     name: &str,
     result: CallToolResult,
@@ -123,6 +189,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_content_part_keeps_images_and_resource_text() {
+        let parts = [
+            json!({ "type": "text", "text": "done" }),
+            json!({ "type": "image", "data": "iVBORw0=", "mimeType": "image/png" }),
+            json!({ "type": "resource", "resource": { "uri": "file:///a.txt", "text": "body" } }),
+            json!({ "type": "resource", "resource": { "uri": "file:///b.png", "mimeType": "image/png", "blob": "AAAA" } }),
+            json!({ "type": "audio", "data": "AAAA", "mimeType": "audio/wav" }),
+        ]
+        .iter()
+        .map(map_content_part)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            parts,
+            vec![
+                ContentPart::Text {
+                    text: "done".into()
+                },
+                ContentPart::Image(ImageContent {
+                    data: "iVBORw0=".into(),
+                    mime_type: "image/png".into(),
+                }),
+                ContentPart::Text {
+                    text: "body".into()
+                },
+                ContentPart::Image(ImageContent {
+                    data: "AAAA".into(),
+                    mime_type: "image/png".into(),
+                }),
+                ContentPart::Text {
+                    text: "[MCP audio content omitted]".into()
+                },
+            ]
+        );
+    }
+
     #[test]
     fn map_mcp_tool_schema_copies_fields() {
         let mut schema = serde_json::Map::new();
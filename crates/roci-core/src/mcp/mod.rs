@@ -8,6 +8,7 @@ pub mod elicitation;
 mod error;
 pub mod instructions;
 mod mapping;
+pub mod progress;
 pub mod schema;
 pub mod server;
 pub mod transport;
@@ -19,13 +20,14 @@ pub use aggregate::{
 };
 pub use bridge::MCPToolAdapter;
 pub use client::{
-    MCPClient, MCPReadResourceResult, MCPRemoteReconnectOutcome, MCPResourceContent,
-    MCPResourceSchema,
+    MCPCallOptions, MCPClient, MCPReadResourceResult, MCPRemoteReconnectOutcome,
+    MCPResourceContent, MCPResourceSchema,
 };
 pub use instructions::{
     merge_mcp_instructions, MCPInstructionMergePolicy, MCPInstructionSource, MCPResourceIdentity,
     MCPServerKind, MCPServerMetadata,
 };
+pub use progress::{MCPProgress, MCPProgressCallback};
 pub use server::{
     McpCallToolResult, McpServerCore, McpServerListedTool, McpServerToolIdentity, McpToolIdentity,
     McpToolSchema,
@@ -0,0 +1,92 @@
+//! Routing of MCP `notifications/progress` to per-request callbacks.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rmcp::model::{ProgressNotificationParam, ProgressToken};
+
+/// Progress an MCP server reported for an in-flight request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MCPProgress {
+    /// Progress so far; increases with each notification.
+    pub progress: f64,
+    /// Total to reach, when the server knows it.
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+impl MCPProgress {
+    /// `progress / total` clamped to `0.0..=1.0`, when a positive total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0.0)
+            .map(|total| (self.progress / total).clamp(0.0, 1.0))
+    }
+}
+
+/// Receives progress for one MCP request.
+pub type MCPProgressCallback = Arc<dyn Fn(MCPProgress) + Send + Sync>;
+
+/// Maps the progress tokens of in-flight requests to their callbacks.
+///
+/// Shared between an [`MCPClient`](super::MCPClient) and the client handler
+/// of each session it opens, so routing survives reconnects.
+#[derive(Clone, Default)]
+pub(super) struct MCPProgressRouter {
+    callbacks: Arc<Mutex<HashMap<ProgressToken, MCPProgressCallback>>>,
+}
+
+impl std::fmt::Debug for MCPProgressRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pending = self.callbacks.lock().map(|map| map.len()).unwrap_or(0);
+        f.debug_struct("MCPProgressRouter")
+            .field("pending", &pending)
+            .finish()
+    }
+}
+
+impl MCPProgressRouter {
+    /// Route progress for `token` to `callback` until the guard drops.
+    pub(super) fn register(
+        &self,
+        token: ProgressToken,
+        callback: MCPProgressCallback,
+    ) -> ProgressRegistration {
+        if let Ok(mut callbacks) = self.callbacks.lock() {
+            callbacks.insert(token.clone(), callback);
+        }
+        ProgressRegistration {
+            router: self.clone(),
+            token,
+        }
+    }
+
+    pub(super) fn dispatch(&self, params: ProgressNotificationParam) {
+        let callback = self
+            .callbacks
+            .lock()
+            .ok()
+            .and_then(|callbacks| callbacks.get(&params.progress_token).cloned());
+        if let Some(callback) = callback {
+            callback(MCPProgress {
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            });
+        }
+    }
+}
+
+/// Removes a progress route when dropped.
+pub(super) struct ProgressRegistration {
+    router: MCPProgressRouter,
+    token: ProgressToken,
+}
+
+impl Drop for ProgressRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut callbacks) = self.router.callbacks.lock() {
+            callbacks.remove(&self.token);
+        }
+    }
+}
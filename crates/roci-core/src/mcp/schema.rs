@@ -8,6 +8,21 @@ pub struct MCPToolSchema {
     pub name: String,
     pub description: Option<String>,
     pub input_schema: serde_json::Value,
+    #[serde(default)]
+    pub annotations: MCPToolAnnotations,
+}
+
+/// Behavior hints an MCP server advertises for a tool.
+///
+/// Hints are unverified; unset hints fall back to fail-closed defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MCPToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
 }
 
 /// Builder for constructing MCP-compatible JSON schemas.
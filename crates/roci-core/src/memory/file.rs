@@ -0,0 +1,282 @@
+//! JSONL-backed [`Memory`] store.
+
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use super::{rank, validate_namespace, Memory, MemoryHit, MemoryMetadata, MemoryRecord};
+use crate::error::RociError;
+
+/// Directory, relative to a home or workspace directory, that holds memory
+/// files.
+pub const MEMORY_DIR: &str = ".roci/memory";
+
+/// Entries kept per namespace before the least recently used are evicted.
+pub const DEFAULT_MAX_ENTRIES: usize = 500;
+
+/// Memory stored as one JSONL file per namespace under a directory.
+///
+/// Every operation reads the namespace file and writes it back whole, so
+/// stores opened on the same directory by separate runs observe each other's
+/// writes. Storing or recalling an entry marks it used; once a namespace
+/// holds more than `max_entries`, the least recently used entries are
+/// dropped.
+#[derive(Debug)]
+pub struct FileMemoryStore {
+    dir: PathBuf,
+    max_entries: usize,
+    lock: Mutex<()>,
+}
+
+impl FileMemoryStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Store under [`MEMORY_DIR`] inside `base`, typically the home directory.
+    pub fn in_dir(base: &Path) -> Self {
+        Self::new(base.join(MEMORY_DIR))
+    }
+
+    /// Cap entries per namespace; at least one entry is always kept.
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, namespace: &str) -> Result<PathBuf, RociError> {
+        validate_namespace(namespace)?;
+        Ok(self.dir.join(format!("{namespace}.jsonl")))
+    }
+
+    fn load(path: &Path) -> Result<Vec<MemoryRecord>, RociError> {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(err) => tracing::warn!(
+                    path = %path.display(),
+                    error = %err,
+                    "skipping unreadable memory entry"
+                ),
+            }
+        }
+        Ok(records)
+    }
+
+    fn save(&self, path: &Path, records: &[MemoryRecord]) -> Result<(), RociError> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = path.with_extension("jsonl.tmp");
+        let mut file = fs::File::create(&tmp)?;
+        for record in records {
+            serde_json::to_writer(&mut file, record)?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn evict(&self, records: &mut Vec<MemoryRecord>) {
+        if records.len() <= self.max_entries {
+            return;
+        }
+        records.sort_by_key(|record| std::cmp::Reverse(record.last_used));
+        records.truncate(self.max_entries);
+        records.sort_by(|a, b| a.stored_at.cmp(&b.stored_at).then(a.key.cmp(&b.key)));
+    }
+
+    fn guard(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn next_use(records: &[MemoryRecord]) -> u64 {
+    records
+        .iter()
+        .map(|record| record.last_used)
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+impl Memory for FileMemoryStore {
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &str,
+        metadata: MemoryMetadata,
+    ) -> Result<MemoryRecord, RociError> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(RociError::InvalidArgument(
+                "memory key must not be empty".to_string(),
+            ));
+        }
+        let path = self.path(namespace)?;
+        let _guard = self.guard();
+        let mut records = Self::load(&path)?;
+        let record = MemoryRecord {
+            key: key.to_string(),
+            value: value.to_string(),
+            metadata,
+            stored_at: Utc::now(),
+            last_used: next_use(&records),
+        };
+        records.retain(|existing| existing.key != key);
+        records.push(record.clone());
+        self.evict(&mut records);
+        self.save(&path, &records)?;
+        Ok(record)
+    }
+
+    fn search(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryHit>, RociError> {
+        let path = self.path(namespace)?;
+        let _guard = self.guard();
+        let mut records = Self::load(&path)?;
+        let hits = rank(&records, query, limit);
+        if hits.is_empty() {
+            return Ok(hits);
+        }
+        let used = next_use(&records);
+        for record in &mut records {
+            if hits.iter().any(|hit| hit.record.key == record.key) {
+                record.last_used = used;
+            }
+        }
+        self.save(&path, &records)?;
+        Ok(hits)
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, RociError> {
+        let path = self.path(namespace)?;
+        let _guard = self.guard();
+        let mut records = Self::load(&path)?;
+        let before = records.len();
+        records.retain(|record| record.key != key.trim());
+        if records.len() == before {
+            return Ok(false);
+        }
+        self.save(&path, &records)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn memories_persist_across_runs() {
+        let dir = tempdir().unwrap();
+
+        let first_run = FileMemoryStore::in_dir(dir.path());
+        first_run
+            .put(
+                "proj",
+                "deploy",
+                "deploy command is make ship",
+                MemoryMetadata::for_run("run-1"),
+            )
+            .unwrap();
+        drop(first_run);
+
+        let second_run = FileMemoryStore::in_dir(dir.path());
+        let hits = second_run.search("proj", "how do I deploy", 5).unwrap();
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].record.value, "deploy command is make ship");
+        assert_eq!(hits[0].record.metadata.run_id.as_deref(), Some("run-1"));
+        assert!(dir.path().join(MEMORY_DIR).join("proj.jsonl").is_file());
+        assert!(second_run.search("other", "deploy", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn put_replaces_existing_key_and_delete_removes_it() {
+        let dir = tempdir().unwrap();
+        let store = FileMemoryStore::new(dir.path());
+        store
+            .put("proj", "indent", "spaces", MemoryMetadata::default())
+            .unwrap();
+        store
+            .put("proj", "indent", "tabs", MemoryMetadata::default())
+            .unwrap();
+
+        let hits = store.search("proj", "indent", 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].record.value, "tabs");
+
+        assert!(store.delete("proj", "indent").unwrap());
+        assert!(!store.delete("proj", "indent").unwrap());
+        assert!(store.search("proj", "", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let dir = tempdir().unwrap();
+        let store = FileMemoryStore::new(dir.path()).with_max_entries(2);
+        store
+            .put("proj", "a", "alpha", MemoryMetadata::default())
+            .unwrap();
+        store
+            .put("proj", "b", "beta", MemoryMetadata::default())
+            .unwrap();
+        // Recalling `a` makes `b` the least recently used entry.
+        store.search("proj", "alpha", 1).unwrap();
+        store
+            .put("proj", "c", "gamma", MemoryMetadata::default())
+            .unwrap();
+
+        let mut keys = store
+            .search("proj", "", 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.record.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn rejects_namespaces_that_escape_the_directory() {
+        let dir = tempdir().unwrap();
+        let store = FileMemoryStore::new(dir.path());
+
+        let err = store
+            .put("../x", "k", "v", MemoryMetadata::default())
+            .unwrap_err();
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+    }
+}
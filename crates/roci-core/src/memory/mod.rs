@@ -0,0 +1,248 @@
+//! Long-term memory shared across runs.
+//!
+//! A [`Memory`] stores short facts under a key inside a namespace and finds
+//! them again by keyword. Namespaces keep projects apart: tools derive one
+//! from the workspace root with [`workspace_namespace`], so facts remembered
+//! in one project are never recalled in another.
+
+mod file;
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::RociError;
+
+pub use file::{FileMemoryStore, DEFAULT_MAX_ENTRIES, MEMORY_DIR};
+
+/// Namespace used when no workspace root is known.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Provenance and free-form labels attached to a stored memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryMetadata {
+    /// Run that stored the memory, if it was stored during a run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl MemoryMetadata {
+    pub fn for_run(run_id: impl Into<String>) -> Self {
+        Self {
+            run_id: Some(run_id.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// One stored memory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub metadata: MemoryMetadata,
+    pub stored_at: DateTime<Utc>,
+    /// Position in the namespace's access order; higher was used more
+    /// recently. Drives LRU eviction.
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+/// A record returned by [`Memory::search`] with its keyword score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryHit {
+    pub record: MemoryRecord,
+    pub score: u32,
+}
+
+/// Keyed long-term memory partitioned by namespace.
+pub trait Memory: Send + Sync {
+    /// Store `value` under `key`, replacing an existing entry with that key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for an invalid namespace or an
+    /// empty key, and an error when the store cannot be written.
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &str,
+        metadata: MemoryMetadata,
+    ) -> Result<MemoryRecord, RociError>;
+
+    /// Up to `limit` records matching `query`, best match first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the namespace is invalid or cannot be read.
+    fn search(
+        &self,
+        namespace: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryHit>, RociError>;
+
+    /// Remove `key`, returning whether it existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the namespace is invalid or cannot be written.
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, RociError>;
+}
+
+/// Namespace for the project rooted at `workspace_root`.
+///
+/// The directory name keeps it readable; a hash of the full path keeps two
+/// checkouts with the same name apart.
+pub fn workspace_namespace(workspace_root: &Path) -> String {
+    let name = workspace_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let slug = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+    let slug = slug.trim_matches('-');
+    let digest = Sha256::digest(workspace_root.to_string_lossy().as_bytes());
+    let hash = digest
+        .iter()
+        .take(6)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    if slug.is_empty() {
+        hash
+    } else {
+        format!("{slug}-{hash}")
+    }
+}
+
+/// Check that `namespace` is usable as a file name.
+pub fn validate_namespace(namespace: &str) -> Result<(), RociError> {
+    let valid = !namespace.is_empty()
+        && !namespace.starts_with('.')
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(RociError::InvalidArgument(format!(
+            "invalid memory namespace '{namespace}': use letters, digits, '-', '_' or '.'"
+        )))
+    }
+}
+
+/// Keyword score of `record` for `query`.
+///
+/// Each distinct query word scores 2 when it appears in the key and 1 when
+/// it appears in the value. Matching is case-insensitive on whole words.
+pub fn keyword_score(record: &MemoryRecord, query: &str) -> u32 {
+    let key_words = words(&record.key).collect::<HashSet<_>>();
+    let value_words = words(&record.value).collect::<HashSet<_>>();
+    words(query)
+        .collect::<HashSet<_>>()
+        .iter()
+        .map(|word| 2 * u32::from(key_words.contains(word)) + u32::from(value_words.contains(word)))
+        .sum()
+}
+
+/// Rank `records` for `query`: score, then newest, then key.
+///
+/// An empty query matches every record, newest first.
+pub fn rank(records: &[MemoryRecord], query: &str, limit: usize) -> Vec<MemoryHit> {
+    let match_all = words(query).next().is_none();
+    let mut hits = records
+        .iter()
+        .map(|record| MemoryHit {
+            score: keyword_score(record, query),
+            record: record.clone(),
+        })
+        .filter(|hit| match_all || hit.score > 0)
+        .collect::<Vec<_>>();
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.record.stored_at.cmp(&a.record.stored_at))
+            .then_with(|| a.record.key.cmp(&b.record.key))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str, value: &str, seconds: i64) -> MemoryRecord {
+        MemoryRecord {
+            key: key.to_string(),
+            value: value.to_string(),
+            metadata: MemoryMetadata::default(),
+            stored_at: DateTime::from_timestamp(seconds, 0).unwrap(),
+            last_used: 0,
+        }
+    }
+
+    #[test]
+    fn ranking_is_deterministic() {
+        let records = vec![
+            record("editor", "user prefers tabs", 10),
+            record("deploy", "deploy command is make ship", 20),
+            record("indent", "tabs in makefiles", 20),
+            record("style", "tabs for rust too", 20),
+        ];
+
+        let keys = |hits: Vec<MemoryHit>| {
+            hits.into_iter()
+                .map(|hit| hit.record.key)
+                .collect::<Vec<_>>()
+        };
+        let first = keys(rank(&records, "Tabs", 10));
+        assert_eq!(first, vec!["indent", "style", "editor"]);
+        for _ in 0..5 {
+            assert_eq!(keys(rank(&records, "tabs", 10)), first);
+        }
+        assert_eq!(keys(rank(&records, "deploy ship", 10)), vec!["deploy"]);
+        assert_eq!(keys(rank(&records, "", 2)), vec!["deploy", "indent"]);
+    }
+
+    #[test]
+    fn key_matches_outrank_value_matches() {
+        let records = vec![
+            record("notes", "the deploy runs nightly", 30),
+            record("deploy", "make ship", 10),
+        ];
+
+        let hits = rank(&records, "deploy", 10);
+
+        assert_eq!(hits[0].record.key, "deploy");
+        assert_eq!(hits[0].score, 2);
+        assert_eq!(hits[1].score, 1);
+    }
+
+    #[test]
+    fn workspace_namespaces_are_valid_and_distinct() {
+        let a = workspace_namespace(Path::new("/work/My App"));
+        let b = workspace_namespace(Path::new("/other/My App"));
+
+        assert!(a.starts_with("my-app-"));
+        assert_ne!(a, b);
+        validate_namespace(&a).unwrap();
+        assert!(validate_namespace("../escape").is_err());
+        assert!(validate_namespace("").is_err());
+    }
+}
@@ -71,6 +71,7 @@ mod tests {
         LanguageModel::Known {
             provider_key: provider.to_string(),
             model_id: model_id.to_string(),
+            provider_options: Default::default(),
         }
     }
 
@@ -4,7 +4,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
-use super::ModelCapabilities;
+use super::{ModelCapabilities, ModelPricing};
 
 /// Provider-neutral metadata for one model.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -16,6 +16,9 @@ pub struct ModelInfo {
     pub capabilities: ModelCapabilities,
     pub policy: ModelPolicy,
     pub source: ModelCatalogSource,
+    /// Token pricing reported by the provider's catalog, when it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub metadata: BTreeMap<String, serde_json::Value>,
 }
@@ -170,6 +173,7 @@ mod tests {
                 default_for_provider: false,
             },
             source,
+            pricing: None,
             metadata: Default::default(),
         }
     }
@@ -4,6 +4,7 @@ pub mod candidates;
 pub mod capabilities;
 pub mod catalog;
 pub mod health;
+pub mod pricing;
 pub mod provider_key;
 pub mod selector;
 
@@ -17,12 +18,16 @@ pub use health::{
     HealthSignal, ModelHealthKey, ModelHealthSnapshot, ModelHealthStatus, ModelHealthTracker,
     SharedModelHealthRegistry,
 };
+pub use pricing::{ModelPricing, PricingTable};
 pub use provider_key::ProviderKey;
 pub use selector::ModelSelector;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
+static NO_PROVIDER_OPTIONS: BTreeMap<String, String> = BTreeMap::new();
+
 /// Top-level language model enum using string-based identifiers.
 ///
 /// Provider-specific model enums live in `roci-providers` and are used
@@ -35,6 +40,10 @@ pub enum LanguageModel {
     Known {
         provider_key: String,
         model_id: String,
+        /// Per-model provider knobs from `provider:model?key=value`; the
+        /// provider factory validates them.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        provider_options: BTreeMap<String, String>,
     },
     /// Unregistered / custom model.
     Custom { provider: String, model_id: String },
@@ -55,10 +64,35 @@ impl LanguageModel {
             Self::Custom { provider, .. } => provider,
         }
     }
+
+    /// Provider options given on the model string; empty for custom models.
+    pub fn provider_options(&self) -> &BTreeMap<String, String> {
+        match self {
+            Self::Known {
+                provider_options, ..
+            } => provider_options,
+            Self::Custom { .. } => &NO_PROVIDER_OPTIONS,
+        }
+    }
+
+    /// One provider option, if set.
+    pub fn provider_option(&self, key: &str) -> Option<&str> {
+        self.provider_options().get(key).map(String::as_str)
+    }
 }
 
 impl fmt::Display for LanguageModel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}", self.provider_name(), self.model_id())
+        write!(f, "{}:{}", self.provider_name(), self.model_id())?;
+        for (index, (key, value)) in self.provider_options().iter().enumerate() {
+            let separator = if index == 0 { '?' } else { '&' };
+            write!(
+                f,
+                "{separator}{}={}",
+                selector::encode_option(key),
+                selector::encode_option(value)
+            )?;
+        }
+        Ok(())
     }
 }
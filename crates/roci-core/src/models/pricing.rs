@@ -0,0 +1,319 @@
+//! Per-model token pricing used to estimate run cost.
+
+use serde::{Deserialize, Serialize};
+
+use super::{LanguageModel, ModelCatalog, ProviderKey};
+use crate::types::Usage;
+
+/// USD prices per million tokens for one model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    #[serde(default)]
+    pub cache_read_per_million: f64,
+    #[serde(default)]
+    pub cache_write_per_million: f64,
+}
+
+impl ModelPricing {
+    pub const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+            cache_read_per_million: 0.0,
+            cache_write_per_million: 0.0,
+        }
+    }
+
+    /// Pricing for models that cost nothing per token, such as local ones.
+    pub const fn free() -> Self {
+        Self::new(0.0, 0.0)
+    }
+
+    pub const fn with_cache(mut self, read_per_million: f64, write_per_million: f64) -> Self {
+        self.cache_read_per_million = read_per_million;
+        self.cache_write_per_million = write_per_million;
+        self
+    }
+
+    /// Estimated cost of `usage` in USD.
+    ///
+    /// Cache reads and writes are charged on top of `input_tokens`. Providers
+    /// that already count cached tokens as input are over-estimated slightly,
+    /// which keeps spend ceilings conservative.
+    pub fn cost_usd(&self, usage: &Usage) -> f64 {
+        let per_million = |tokens: u32, price: f64| f64::from(tokens) * price / 1_000_000.0;
+        per_million(usage.input_tokens, self.input_per_million)
+            + per_million(usage.output_tokens, self.output_per_million)
+            + per_million(
+                usage.cache_read_tokens.unwrap_or(0),
+                self.cache_read_per_million,
+            )
+            + per_million(
+                usage.cache_creation_tokens.unwrap_or(0),
+                self.cache_write_per_million,
+            )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PricingEntry {
+    provider: String,
+    model_prefix: String,
+    pricing: ModelPricing,
+}
+
+/// Model pricing lookup keyed by provider and model id prefix.
+///
+/// The longest matching prefix wins, so `claude-sonnet-4` covers dated
+/// snapshots like `claude-sonnet-4-20250514` while a more specific entry can
+/// still override it. [`PricingTable::default`] ships list prices for common
+/// hosted models and treats Ollama and LM Studio as free.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingTable {
+    entries: Vec<PricingEntry>,
+}
+
+impl PricingTable {
+    /// An empty table with no known models.
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add or replace pricing for models of `provider` whose id starts with
+    /// `model_prefix`. An empty prefix matches every model of the provider.
+    pub fn with_model(
+        mut self,
+        provider: impl Into<String>,
+        model_prefix: impl Into<String>,
+        pricing: ModelPricing,
+    ) -> Self {
+        let provider = provider.into();
+        let model_prefix = model_prefix.into();
+        self.entries
+            .retain(|entry| entry.provider != provider || entry.model_prefix != model_prefix);
+        self.entries.push(PricingEntry {
+            provider,
+            model_prefix,
+            pricing,
+        });
+        self
+    }
+
+    /// Add exact-id entries for every model in `catalog` that carries
+    /// pricing.
+    ///
+    /// An exact id is the longest possible prefix, so catalog pricing takes
+    /// precedence over prefix entries already in the table.
+    pub fn with_catalog(self, catalog: &ModelCatalog) -> Self {
+        catalog
+            .models()
+            .iter()
+            .fold(self, |table, model| match model.pricing {
+                Some(pricing) => table.with_model(&model.provider_key, &model.model_id, pricing),
+                None => table,
+            })
+    }
+
+    /// Pricing for `model`, or `None` when the table has no matching entry.
+    pub fn lookup(&self, model: &LanguageModel) -> Option<ModelPricing> {
+        let provider = ProviderKey::parse(model.provider_name())
+            .map(ProviderKey::as_str)
+            .unwrap_or(model.provider_name());
+        let model_id = model.model_id();
+        self.entries
+            .iter()
+            .filter(|entry| entry.provider == provider && model_id.starts_with(&entry.model_prefix))
+            .max_by_key(|entry| entry.model_prefix.len())
+            .map(|entry| entry.pricing)
+    }
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self::empty()
+            .with_model(
+                "openai",
+                "gpt-4o",
+                ModelPricing::new(2.50, 10.00).with_cache(1.25, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-4o-mini",
+                ModelPricing::new(0.15, 0.60).with_cache(0.075, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-4.1",
+                ModelPricing::new(2.00, 8.00).with_cache(0.50, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-4.1-mini",
+                ModelPricing::new(0.40, 1.60).with_cache(0.10, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-4.1-nano",
+                ModelPricing::new(0.10, 0.40).with_cache(0.025, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-5",
+                ModelPricing::new(1.25, 10.00).with_cache(0.125, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-5-mini",
+                ModelPricing::new(0.25, 2.00).with_cache(0.025, 0.0),
+            )
+            .with_model(
+                "openai",
+                "gpt-5-nano",
+                ModelPricing::new(0.05, 0.40).with_cache(0.005, 0.0),
+            )
+            .with_model(
+                "openai",
+                "o3",
+                ModelPricing::new(2.00, 8.00).with_cache(0.50, 0.0),
+            )
+            .with_model(
+                "openai",
+                "o4-mini",
+                ModelPricing::new(1.10, 4.40).with_cache(0.275, 0.0),
+            )
+            .with_model(
+                "anthropic",
+                "claude-opus-4",
+                ModelPricing::new(15.00, 75.00).with_cache(1.50, 18.75),
+            )
+            .with_model(
+                "anthropic",
+                "claude-sonnet-4",
+                ModelPricing::new(3.00, 15.00).with_cache(0.30, 3.75),
+            )
+            .with_model(
+                "anthropic",
+                "claude-3-7-sonnet",
+                ModelPricing::new(3.00, 15.00).with_cache(0.30, 3.75),
+            )
+            .with_model(
+                "anthropic",
+                "claude-3-5-haiku",
+                ModelPricing::new(0.80, 4.00).with_cache(0.08, 1.00),
+            )
+            .with_model(
+                "google",
+                "gemini-2.5-pro",
+                ModelPricing::new(1.25, 10.00).with_cache(0.31, 0.0),
+            )
+            .with_model(
+                "google",
+                "gemini-2.5-flash",
+                ModelPricing::new(0.30, 2.50).with_cache(0.075, 0.0),
+            )
+            .with_model("ollama", "", ModelPricing::free())
+            .with_model("lmstudio", "", ModelPricing::free())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(provider: &str, model_id: &str) -> LanguageModel {
+        LanguageModel::Known {
+            provider_key: provider.to_string(),
+            model_id: model_id.to_string(),
+            provider_options: Default::default(),
+        }
+    }
+
+    #[test]
+    fn lookup_prefers_longest_prefix() {
+        let table = PricingTable::default();
+        assert_eq!(
+            table.lookup(&model("openai", "gpt-4o-mini-2024-07-18")),
+            Some(ModelPricing::new(0.15, 0.60).with_cache(0.075, 0.0))
+        );
+        assert_eq!(
+            table.lookup(&model("anthropic", "claude-sonnet-4-20250514")),
+            Some(ModelPricing::new(3.00, 15.00).with_cache(0.30, 3.75))
+        );
+        assert_eq!(
+            table.lookup(&model("ollama", "llama3.2")),
+            Some(ModelPricing::free())
+        );
+        assert_eq!(table.lookup(&model("openai", "unknown-model")), None);
+    }
+
+    #[test]
+    fn with_model_replaces_existing_entry() {
+        let table = PricingTable::empty()
+            .with_model("openai", "gpt-4o", ModelPricing::new(1.0, 2.0))
+            .with_model("openai", "gpt-4o", ModelPricing::new(3.0, 4.0));
+        assert_eq!(
+            table.lookup(&model("openai", "gpt-4o")),
+            Some(ModelPricing::new(3.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn catalog_pricing_overrides_prefix_entries() {
+        use crate::models::{ModelCapabilities, ModelCatalogSource, ModelInfo, ModelPolicy};
+
+        let info = |model_id: &str, pricing: Option<ModelPricing>| ModelInfo {
+            provider_key: "openrouter".to_string(),
+            model_id: model_id.to_string(),
+            display_name: None,
+            capabilities: ModelCapabilities::default(),
+            policy: ModelPolicy {
+                requires_credentials: true,
+                local: false,
+                deprecated: false,
+                default_for_provider: false,
+            },
+            source: ModelCatalogSource::Dynamic {
+                endpoint: "/models".to_string(),
+            },
+            pricing,
+            metadata: Default::default(),
+        };
+        let catalog = ModelCatalog::from_models([
+            info(
+                "anthropic/claude-sonnet-4",
+                Some(ModelPricing::new(3.0, 15.0)),
+            ),
+            info("openai/gpt-4o", None),
+        ]);
+        let table = PricingTable::empty()
+            .with_model("openrouter", "", ModelPricing::new(9.0, 9.0))
+            .with_catalog(&catalog);
+
+        assert_eq!(
+            table.lookup(&model("openrouter", "anthropic/claude-sonnet-4")),
+            Some(ModelPricing::new(3.0, 15.0))
+        );
+        assert_eq!(
+            table.lookup(&model("openrouter", "openai/gpt-4o")),
+            Some(ModelPricing::new(9.0, 9.0))
+        );
+    }
+
+    #[test]
+    fn cost_includes_cache_tokens() {
+        let pricing = ModelPricing::new(2.0, 10.0).with_cache(0.5, 4.0);
+        let usage = Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 500_000,
+            cache_read_tokens: Some(2_000_000),
+            cache_creation_tokens: Some(250_000),
+            ..Usage::default()
+        };
+        let cost = pricing.cost_usd(&usage);
+        assert!((cost - (2.0 + 5.0 + 1.0 + 1.0)).abs() < 1e-9, "cost={cost}");
+    }
+}
@@ -73,6 +73,14 @@ impl ProviderKey {
         }
     }
 
+    /// Whether the provider needs a credential to be usable.
+    ///
+    /// Local servers (Ollama, LM Studio) accept unauthenticated requests; a
+    /// key configured for them is still sent, for proxied setups.
+    pub const fn requires_credentials(self) -> bool {
+        !matches!(self, Self::Ollama | Self::LmStudio)
+    }
+
     /// Token store key for OAuth-backed providers.
     pub const fn token_store_key(self) -> Option<&'static str> {
         match self {
@@ -111,6 +119,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn only_local_providers_work_without_credentials() {
+        assert!(!ProviderKey::Ollama.requires_credentials());
+        assert!(!ProviderKey::LmStudio.requires_credentials());
+        for key in [
+            ProviderKey::OpenAi,
+            ProviderKey::Anthropic,
+            ProviderKey::OpenAiCompatible,
+            ProviderKey::Codex,
+        ] {
+            assert!(key.requires_credentials(), "{key:?}");
+        }
+    }
+
     #[test]
     fn azure_as_str() {
         assert_eq!(ProviderKey::Azure.as_str(), "azure");
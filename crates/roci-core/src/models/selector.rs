@@ -1,5 +1,6 @@
 //! Model selection and parsing.
 
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use super::LanguageModel;
@@ -15,21 +16,89 @@ impl ModelSelector {
     /// the provider is registered happens at `ProviderRegistry::create_provider()`
     /// time, not at parse time.
     ///
+    /// Provider options follow a `?` as `key=value` pairs joined by `&`,
+    /// percent-encoded where they contain `&`, `=`, `%`, `?`, `#`, `+`, or
+    /// whitespace: "openai:o3?reasoning_effort=high".
+    ///
     /// Examples: "openai:gpt-4o", "anthropic:claude-opus-4-5-20251101", "ollama:llama3.3"
     pub fn parse(s: &str) -> Result<LanguageModel, RociError> {
-        let (provider, model_id) = s.split_once(':').ok_or_else(|| {
+        let (provider, rest) = s.split_once(':').ok_or_else(|| {
             RociError::InvalidArgument(format!(
                 "Invalid model selector '{s}': expected 'provider:model_id'"
             ))
         })?;
+        let (model_id, provider_options) = match rest.split_once('?') {
+            Some((model_id, query)) => (model_id, parse_options(s, query)?),
+            None => (rest, BTreeMap::new()),
+        };
 
         Ok(LanguageModel::Known {
             provider_key: provider.to_string(),
             model_id: model_id.to_string(),
+            provider_options,
         })
     }
 }
 
+fn parse_options(selector: &str, query: &str) -> Result<BTreeMap<String, String>, RociError> {
+    let invalid = |reason: String| {
+        RociError::InvalidArgument(format!("Invalid model selector '{selector}': {reason}"))
+    };
+    let mut options = BTreeMap::new();
+    for pair in query.split('&') {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("option '{pair}' is not 'key=value'")))?;
+        let key = decode_option(key).map_err(&invalid)?;
+        let value = decode_option(value).map_err(&invalid)?;
+        if key.is_empty() {
+            return Err(invalid("option key is empty".to_string()));
+        }
+        if options.contains_key(&key) {
+            return Err(invalid(format!("option '{key}' is given more than once")));
+        }
+        options.insert(key, value);
+    }
+    Ok(options)
+}
+
+/// Percent-encode the characters that would end or split an option.
+pub(super) fn encode_option(raw: &str) -> String {
+    let mut encoded = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        if matches!(ch, '&' | '=' | '%' | '?' | '#' | '+') || ch.is_whitespace() || ch.is_control()
+        {
+            let mut buf = [0u8; 4];
+            for byte in ch.encode_utf8(&mut buf).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            encoded.push(ch);
+        }
+    }
+    encoded
+}
+
+fn decode_option(raw: &str) -> Result<String, String> {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let byte = raw
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("bad percent-escape in '{raw}'"))?;
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("'{raw}' does not decode to UTF-8"))
+}
+
 impl FromStr for LanguageModel {
     type Err = RociError;
 
@@ -83,6 +152,68 @@ mod tests {
         assert!(ModelSelector::parse("gpt-4o").is_err());
     }
 
+    #[test]
+    fn parse_provider_options() {
+        let model = ModelSelector::parse("openai:o3?reasoning_effort=high").unwrap();
+        assert_eq!(model.model_id(), "o3");
+        assert_eq!(model.provider_option("reasoning_effort"), Some("high"));
+
+        let model = ModelSelector::parse(
+            "openai-compatible:qwen?base_url=http://10.0.0.5:8000/v1&label=a%26b%3Dc%20d",
+        )
+        .unwrap();
+        assert_eq!(model.model_id(), "qwen");
+        assert_eq!(
+            model.provider_option("base_url"),
+            Some("http://10.0.0.5:8000/v1")
+        );
+        assert_eq!(model.provider_option("label"), Some("a&b=c d"));
+    }
+
+    #[test]
+    fn malformed_provider_options_are_errors() {
+        for selector in [
+            "openai:o3?",
+            "openai:o3?reasoning_effort",
+            "openai:o3?=high",
+            "openai:o3?a=1&a=2",
+            "openai:o3?a=%zz",
+            "openai:o3?a=%ff",
+        ] {
+            assert!(ModelSelector::parse(selector).is_err(), "{selector}");
+        }
+    }
+
+    #[test]
+    fn provider_options_round_trip_through_display_and_serde() {
+        let model =
+            ModelSelector::parse("azure:gpt4o-deploy?note=x%26y%20z&api_version=2024-10-21")
+                .unwrap();
+        let text = model.to_string();
+        assert_eq!(
+            text,
+            "azure:gpt4o-deploy?api_version=2024-10-21&note=x%26y%20z"
+        );
+        assert_eq!(text.parse::<LanguageModel>().unwrap(), model);
+
+        let json = serde_json::to_value(&model).unwrap();
+        assert_eq!(
+            serde_json::from_value::<LanguageModel>(json).unwrap(),
+            model
+        );
+        // Models serialized before options existed still load, and models
+        // without options serialize as before.
+        let plain: LanguageModel = serde_json::from_value(serde_json::json!({
+            "Known": { "provider_key": "openai", "model_id": "gpt-4o" }
+        }))
+        .unwrap();
+        assert!(plain.provider_options().is_empty());
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap(),
+            serde_json::json!({ "Known": { "provider_key": "openai", "model_id": "gpt-4o" } })
+        );
+    }
+
     #[test]
     fn roundtrip_display_parse() {
         let model = ModelSelector::parse("openai:gpt-4o").unwrap();
@@ -12,14 +12,15 @@ pub use crate::config::RociConfig;
 pub use crate::error::{Result, RociError};
 pub use crate::models::{
     FileInputCapabilities, ImageInputCapabilities, LanguageModel, ModelCapabilities,
-    ModelInputCapabilities, TextInputCapabilities,
+    ModelInputCapabilities, ModelPricing, PricingTable, TextInputCapabilities,
 };
 pub use crate::provider::{ModelProvider, ProviderFactory, ProviderRegistry};
 pub use crate::resource::{
     BranchSummarySettings, CompactionSettings, ContextFileResource, ContextPromptLoader,
-    ContextPromptResources, LoadedPromptTemplates, PromptDiagnostic, PromptDiagnosticLevel,
-    PromptTemplate, PromptTemplateLoader, ResourceBundle, ResourceDiagnostic, ResourceDirectories,
-    ResourceLoader, ResourceSettings, ResourceSettingsLoader,
+    ContextPromptResources, FetchUrlSettings, LoadedPromptTemplates, PromptDiagnostic,
+    PromptDiagnosticLevel, PromptTemplate, PromptTemplateLoader, ResourceBundle,
+    ResourceDiagnostic, ResourceDirectories, ResourceLoader, ResourceSettings,
+    ResourceSettingsLoader,
 };
 pub use crate::session::{
     AgentRuntimeEvent, CreateSessionOptions, ImportPolicy, LocalProviderLedger, LocalSessionFs,
@@ -37,6 +38,6 @@ pub use crate::session::{
 };
 pub use crate::tools::{AgentTool, AgentToolParameters, Tool, ToolArguments};
 pub use crate::types::{
-    ContentPart, FinishReason, GenerateTextResult, GenerationSettings, ModelMessage,
+    ContentPart, FinishReason, GenerateTextResult, GenerationSettings, MessageOrigin, ModelMessage,
     ModelMessageMetadata, Role, StreamEventType, StreamTextResult, TextStreamDelta, Usage,
 };
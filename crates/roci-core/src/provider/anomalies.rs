@@ -0,0 +1,248 @@
+//! Opt-in reporting of provider responses that drift from the documented shape.
+//!
+//! Response types tolerate missing or unfamiliar fields so a provider change
+//! degrades a reply instead of failing it. That tolerance also hides drift:
+//! a renamed usage field reads as zero tokens. With
+//! [`STRICT_PARSING_ENV`] set, providers run a validation pass over the raw
+//! body or stream event and report each deviation as a [`ResponseAnomaly`],
+//! logged as a `tracing` warning with a snippet of the raw JSON and counted
+//! in [`ResponseMetadata::parse_anomalies`]. Parsing results are the same
+//! either way.
+
+use std::sync::{Arc, Mutex};
+
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::error::RociError;
+use crate::types::{ResponseMetadata, StreamEventType, TextStreamDelta};
+
+/// Environment variable enabling strict parsing (`1` or `true`).
+pub const STRICT_PARSING_ENV: &str = "ROCI_STRICT_PARSING";
+
+/// Characters of raw JSON kept in [`ResponseAnomaly::snippet`].
+pub const ANOMALY_SNIPPET_CHARS: usize = 240;
+
+/// Returns whether [`STRICT_PARSING_ENV`] enables strict parsing.
+pub fn strict_parsing_enabled() -> bool {
+    matches!(
+        std::env::var(STRICT_PARSING_ENV).as_deref(),
+        Ok("1" | "true" | "TRUE")
+    )
+}
+
+/// What kind of deviation a [`ResponseAnomaly`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseAnomalyKind {
+    /// A field the provider documents is absent or has the wrong type.
+    MissingField,
+    /// A finish or stop reason outside the documented set.
+    UnknownFinishReason,
+    /// A content block or delta type the parser does not handle; its content
+    /// is dropped.
+    UnknownContentBlock,
+    /// A body or stream event that could not be parsed at all.
+    Malformed,
+}
+
+/// One deviation found in a provider response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseAnomaly {
+    pub provider: String,
+    pub kind: ResponseAnomalyKind,
+    /// Dotted path of the field, e.g. `usage.input_tokens`.
+    pub field: String,
+    /// The unexpected value, when there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Start of the raw JSON the anomaly was found in.
+    pub snippet: String,
+}
+
+/// Collects the anomalies of one response. Cheap to clone; clones share the
+/// collection, so a stream and its wrapper see the same count.
+///
+/// Disabled collectors ignore every report.
+#[derive(Debug, Clone)]
+pub struct ResponseAnomalies {
+    provider: String,
+    enabled: bool,
+    found: Arc<Mutex<Vec<ResponseAnomaly>>>,
+}
+
+impl ResponseAnomalies {
+    /// A collector for `provider`, enabled per [`strict_parsing_enabled`].
+    pub fn from_env(provider: impl Into<String>) -> Self {
+        Self::new(provider, strict_parsing_enabled())
+    }
+
+    pub fn new(provider: impl Into<String>, enabled: bool) -> Self {
+        Self {
+            provider: provider.into(),
+            enabled,
+            found: Arc::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Report that `field` is missing from `raw`.
+    pub fn missing_field(&self, field: &str, raw: &serde_json::Value) {
+        self.report(ResponseAnomalyKind::MissingField, field, None, raw);
+    }
+
+    /// Report an undocumented finish reason.
+    pub fn unknown_finish_reason(&self, field: &str, value: &str, raw: &serde_json::Value) {
+        self.report(
+            ResponseAnomalyKind::UnknownFinishReason,
+            field,
+            Some(value),
+            raw,
+        );
+    }
+
+    /// Report a content block or delta type the parser drops.
+    pub fn unknown_content_block(&self, field: &str, value: &str, raw: &serde_json::Value) {
+        self.report(
+            ResponseAnomalyKind::UnknownContentBlock,
+            field,
+            Some(value),
+            raw,
+        );
+    }
+
+    /// Report text that is not the JSON the provider documents.
+    pub fn malformed(&self, field: &str, raw_text: &str) {
+        if self.enabled {
+            self.push(ResponseAnomalyKind::Malformed, field, None, raw_text);
+        }
+    }
+
+    /// Anomalies reported so far.
+    pub fn reports(&self) -> Vec<ResponseAnomaly> {
+        self.found
+            .lock()
+            .map(|found| found.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn count(&self) -> u32 {
+        self.found
+            .lock()
+            .map(|found| u32::try_from(found.len()).unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    /// `metadata` with the anomaly count filled in.
+    pub fn annotate(&self, mut metadata: ResponseMetadata) -> ResponseMetadata {
+        metadata.parse_anomalies = self.count();
+        metadata
+    }
+
+    fn report(
+        &self,
+        kind: ResponseAnomalyKind,
+        field: &str,
+        value: Option<&str>,
+        raw: &serde_json::Value,
+    ) {
+        if self.enabled {
+            self.push(kind, field, value, &raw.to_string());
+        }
+    }
+
+    fn push(&self, kind: ResponseAnomalyKind, field: &str, value: Option<&str>, raw: &str) {
+        let snippet = match raw.char_indices().nth(ANOMALY_SNIPPET_CHARS) {
+            Some((end, _)) => format!("{}…", &raw[..end]),
+            None => raw.to_string(),
+        };
+        tracing::warn!(
+            provider = %self.provider,
+            kind = ?kind,
+            field,
+            value,
+            snippet = %snippet,
+            "provider response deviates from its documented shape"
+        );
+        if let Ok(mut found) = self.found.lock() {
+            found.push(ResponseAnomaly {
+                provider: self.provider.clone(),
+                kind,
+                field: field.to_string(),
+                value: value.map(str::to_string),
+                snippet,
+            });
+        }
+    }
+}
+
+/// Set [`ResponseMetadata::parse_anomalies`] on the metadata-carrying
+/// [`StreamEventType::Done`] delta of `stream`.
+///
+/// Apply after
+/// [`with_response_metadata`](super::http::with_response_metadata); anomalies
+/// found after that delta are not counted.
+pub fn with_anomaly_count(
+    stream: BoxStream<'static, Result<TextStreamDelta, RociError>>,
+    anomalies: ResponseAnomalies,
+) -> BoxStream<'static, Result<TextStreamDelta, RociError>> {
+    if !anomalies.is_enabled() {
+        return stream;
+    }
+    Box::pin(stream.map(move |delta| {
+        delta.map(|mut delta| {
+            if delta.event_type == StreamEventType::Done {
+                if let Some(metadata) = delta.response_metadata.as_mut() {
+                    metadata.parse_anomalies = anomalies.count();
+                }
+            }
+            delta
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_collectors_ignore_reports() {
+        let anomalies = ResponseAnomalies::new("anthropic", false);
+
+        anomalies.missing_field("usage", &serde_json::json!({}));
+
+        assert_eq!(anomalies.count(), 0);
+        assert_eq!(
+            anomalies
+                .annotate(ResponseMetadata::default())
+                .parse_anomalies,
+            0
+        );
+    }
+
+    #[test]
+    fn reports_keep_a_bounded_snippet_of_the_raw_json() {
+        let anomalies = ResponseAnomalies::new("openai", true);
+        let raw = serde_json::json!({ "finish_reason": "paused", "pad": "é".repeat(400) });
+
+        anomalies.unknown_finish_reason("choices.0.finish_reason", "paused", &raw);
+
+        let reports = anomalies.clone().reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].kind, ResponseAnomalyKind::UnknownFinishReason);
+        assert_eq!(reports[0].value.as_deref(), Some("paused"));
+        assert_eq!(
+            reports[0].snippet.chars().count(),
+            ANOMALY_SNIPPET_CHARS + 1
+        );
+        assert_eq!(
+            anomalies
+                .annotate(ResponseMetadata::default())
+                .parse_anomalies,
+            1
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! Provider factory trait for creating ModelProvider instances.
 
-use super::ModelProvider;
+use std::collections::BTreeMap;
+
+use super::{ModelProvider, ProviderFeatureMetadata};
 use crate::config::RociConfig;
 use crate::error::RociError;
-use crate::models::{ModelCatalog, ModelListOptions};
+use crate::models::{ModelCatalog, ModelListOptions, ProviderKey};
 use futures::future::BoxFuture;
 
 /// Factory for creating ModelProvider instances from a provider key + model ID.
@@ -12,8 +14,30 @@ pub trait ProviderFactory: Send + Sync {
     fn provider_keys(&self) -> &[&str];
 
     /// Whether this provider key needs credentials before launch-time use.
-    fn requires_credentials(&self, _provider_key: &str) -> bool {
-        true
+    ///
+    /// Defaults to [`ProviderKey::requires_credentials`]; keys that are not
+    /// built-in providers need credentials.
+    fn requires_credentials(&self, provider_key: &str) -> bool {
+        ProviderKey::parse(provider_key).is_none_or(ProviderKey::requires_credentials)
+    }
+
+    /// Base URL requests for `model_id` would be sent to, after config
+    /// overrides. Offline mode uses it to tell loopback endpoints from remote
+    /// ones; `None` means unknown and is never blocked.
+    fn resolved_base_url(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Static feature metadata for `provider_key`: known model families and
+    /// provider-level features. Must not construct providers or touch the
+    /// network; defaults to no families and no features.
+    fn feature_metadata(&self, _provider_key: &str) -> ProviderFeatureMetadata {
+        ProviderFeatureMetadata::default()
     }
 
     /// List models for the given provider key.
@@ -43,6 +67,44 @@ pub trait ProviderFactory: Send + Sync {
         provider_key: &str,
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError>;
+
+    /// Create a ModelProvider for a model string carrying provider options
+    /// (`provider:model?key=value`).
+    ///
+    /// Factories that recognize options override this and check them with
+    /// [`check_provider_options`]; the default accepts none.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &BTreeMap<String, String>,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        check_provider_options(provider_key, options, &[])?;
+        self.create(config, provider_key, model_id)
+    }
+}
+
+/// Reject options outside `supported`, naming the ones that are.
+pub fn check_provider_options(
+    provider_key: &str,
+    options: &BTreeMap<String, String>,
+    supported: &[&str],
+) -> Result<(), RociError> {
+    let Some(unknown) = options
+        .keys()
+        .find(|key| !supported.contains(&key.as_str()))
+    else {
+        return Ok(());
+    };
+    let supported = if supported.is_empty() {
+        "none".to_string()
+    } else {
+        supported.join(", ")
+    };
+    Err(RociError::Configuration(format!(
+        "provider '{provider_key}' does not support model option '{unknown}' (supported: {supported})"
+    )))
 }
 
 #[cfg(test)]
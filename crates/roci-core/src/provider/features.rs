@@ -0,0 +1,54 @@
+//! Static provider feature metadata for capability-aware UIs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ModelCapabilities;
+
+/// Provider-level features that are not per-model [`ModelCapabilities`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderFeatures {
+    /// Models may return several tool calls in one response.
+    pub parallel_tool_calls: bool,
+    /// Requests carry a prompt cache key, or the provider caches prompts
+    /// without one.
+    pub prompt_caching: bool,
+    pub embeddings: bool,
+    /// Speech-to-text or text-to-speech through [`crate::audio`].
+    pub audio: bool,
+    /// Models may return generated images.
+    pub image_generation: bool,
+}
+
+/// A model family and the capabilities its models share.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelFamily {
+    pub name: String,
+    pub capabilities: ModelCapabilities,
+}
+
+/// Feature metadata a factory declares for one provider key, without
+/// constructing a provider or making network calls.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProviderFeatureMetadata {
+    /// Known model families; empty when models are only discovered at runtime.
+    pub model_families: Vec<ModelFamily>,
+    pub features: ProviderFeatures,
+}
+
+/// One provider key in [`ProviderRegistry::feature_matrix`](super::ProviderRegistry::feature_matrix).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProviderFeatureReport {
+    pub provider_key: String,
+    pub requires_credentials: bool,
+    /// Whether the config holds credentials for this key.
+    pub has_credentials: bool,
+    pub model_families: Vec<ModelFamily>,
+    pub features: ProviderFeatures,
+}
+
+impl ProviderFeatureReport {
+    /// Whether the provider can be used with the config the report was built from.
+    pub fn is_available(&self) -> bool {
+        !self.requires_credentials || self.has_credentials
+    }
+}
@@ -1,4 +1,4 @@
-//! Shared HTTP client, SSE parsing, and auth utilities.
+//! Shared HTTP client, SSE parsing, auth, and download utilities.
 
 use std::sync::OnceLock;
 
@@ -6,15 +6,39 @@ use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 
 use crate::error::RociError;
 
+mod download;
+mod options;
+mod payload;
+mod response_headers;
+mod sse;
+
+pub use download::{
+    download_to_path, download_to_writer, DownloadOptions, DownloadProgress,
+    DownloadProgressCallback,
+};
+pub use options::{HttpOptions, DEFAULT_HTTP_TIMEOUT};
+pub use payload::{send_json, with_payload_sizes, ByteCounter};
+pub use response_headers::{
+    response_metadata, with_response_metadata, ResetFormat, ResponseHeaderRules,
+};
+pub use sse::{sse_events, SseDecoder, SseEvent, DEFAULT_EVENT_TYPE};
+
 static SHARED_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
 /// Get (or create) the shared reqwest client.
+///
+/// Every provider sends through this client so its connection pool is reused
+/// across calls, iterations, and concurrent runs. Put per-request headers and
+/// auth on the request builder instead of building another client.
 pub fn shared_client() -> &'static reqwest::Client {
     SHARED_CLIENT.get_or_init(|| {
-        reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .pool_max_idle_per_host(10)
-            .build()
+        HttpOptions::default()
+            .client_builder()
+            .and_then(|builder| {
+                builder
+                    .build()
+                    .map_err(|e| RociError::Configuration(e.to_string()))
+            })
             .expect("Failed to build HTTP client")
     })
 }
@@ -47,20 +71,11 @@ pub fn anthropic_headers(api_key: &str, version: &str, beta: Option<&str>) -> He
     headers
 }
 
-/// Parse an SSE "data:" line, returning None for "[DONE]".
-pub fn parse_sse_data(line: &str) -> Option<&str> {
-    let data = line.strip_prefix("data:")?;
-    let data = data.strip_prefix(' ').unwrap_or(data);
-    if data == "[DONE]" {
-        return None;
-    }
-    Some(data)
-}
-
 /// Extract a retryable error from an HTTP status code.
 pub fn status_to_error(status: u16, body: &str) -> RociError {
     match status {
         401 | 403 => RociError::Authentication(body.to_string()),
+        529 => RociError::overloaded(status, body),
         429 => RociError::RateLimited {
             retry_after_ms: extract_retry_after(body),
         },
@@ -0,0 +1,479 @@
+//! Streamed downloads of large provider outputs (audio, images).
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::StreamExt;
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use reqwest::StatusCode;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::status_to_error;
+use crate::error::RociError;
+
+/// Bytes received so far, and the full size when the server reported it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>,
+}
+
+/// Callback invoked after each chunk is written.
+pub type DownloadProgressCallback = Arc<dyn Fn(DownloadProgress) + Send + Sync>;
+
+/// Limits and hooks for [`download_to_writer`] and [`download_to_path`].
+#[derive(Clone, Default)]
+pub struct DownloadOptions {
+    /// Reject bodies larger than this many bytes.
+    pub max_bytes: Option<u64>,
+    /// Accepted MIME types, compared without parameters. Empty accepts any.
+    pub content_types: Vec<String>,
+    /// Times a dropped connection is resumed with a `Range` request.
+    pub max_resume_attempts: u32,
+    pub on_progress: Option<DownloadProgressCallback>,
+}
+
+impl std::fmt::Debug for DownloadOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadOptions")
+            .field("max_bytes", &self.max_bytes)
+            .field("content_types", &self.content_types)
+            .field("max_resume_attempts", &self.max_resume_attempts)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl DownloadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn with_content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.content_types = content_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_max_resume_attempts(mut self, attempts: u32) -> Self {
+        self.max_resume_attempts = attempts;
+        self
+    }
+
+    pub fn with_progress(mut self, on_progress: DownloadProgressCallback) -> Self {
+        self.on_progress = Some(on_progress);
+        self
+    }
+}
+
+/// Stream a response body into `writer` chunk by chunk.
+///
+/// `request` builds the request and is called again to resume after a
+/// dropped connection, with a `Range` header for the bytes already written.
+/// Servers that ignore the range get the already-written prefix skipped.
+/// Returns the number of bytes written.
+pub async fn download_to_writer<F, W>(
+    request: F,
+    writer: &mut W,
+    options: &DownloadOptions,
+) -> Result<u64, RociError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut written = 0u64;
+    let mut resumes = 0u32;
+    loop {
+        let mut builder = request();
+        if written > 0 {
+            builder = builder.header(RANGE, format!("bytes={written}-"));
+        }
+        let response = match builder.send().await {
+            Ok(response) => response,
+            Err(err) if written > 0 && resumes < options.max_resume_attempts => {
+                resumes += 1;
+                tracing::warn!(error = %err, written, "resuming download after send error");
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_to_error(status.as_u16(), &body));
+        }
+        if let Some(content_type) = unexpected_content_type(&response, options) {
+            let detail = if content_type.starts_with("application/json") {
+                let body = response.text().await.unwrap_or_default();
+                json_error_message(&body).map(|message| format!(": {message}"))
+            } else {
+                None
+            };
+            return Err(RociError::InvalidState(format!(
+                "unexpected download content type '{content_type}'; expected one of {}{}",
+                options.content_types.join(", "),
+                detail.unwrap_or_default()
+            )));
+        }
+
+        let partial = status == StatusCode::PARTIAL_CONTENT;
+        if partial && content_range_start(&response) != Some(written) {
+            return Err(RociError::InvalidState(format!(
+                "download resumed at the wrong offset; expected byte {written}"
+            )));
+        }
+        let mut skip = if partial { 0 } else { written };
+        let total = if partial {
+            content_range_total(&response)
+        } else {
+            response.content_length()
+        };
+        if let (Some(max), Some(total)) = (options.max_bytes, total) {
+            if total > max {
+                return Err(size_limit_error(max));
+            }
+        }
+
+        let mut body = response.bytes_stream();
+        let mut interrupted = false;
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) if resumes < options.max_resume_attempts => {
+                    tracing::warn!(error = %err, written, "resuming interrupted download");
+                    interrupted = true;
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let mut chunk = &chunk[..];
+            if skip > 0 {
+                let skipped = usize::try_from(skip).unwrap_or(usize::MAX).min(chunk.len());
+                chunk = &chunk[skipped..];
+                skip -= skipped as u64;
+            }
+            if chunk.is_empty() {
+                continue;
+            }
+            let next = written + chunk.len() as u64;
+            if let Some(max) = options.max_bytes.filter(|max| next > *max) {
+                return Err(size_limit_error(max));
+            }
+            writer.write_all(chunk).await?;
+            written = next;
+            if let Some(on_progress) = options.on_progress.as_ref() {
+                on_progress(DownloadProgress {
+                    bytes_downloaded: written,
+                    total_bytes: total,
+                });
+            }
+        }
+
+        let incomplete = total.is_some_and(|total| written < total);
+        if interrupted || incomplete {
+            if resumes >= options.max_resume_attempts {
+                return Err(RociError::Stream(format!(
+                    "download ended after {written} bytes of {}",
+                    total.map_or_else(|| "unknown".to_string(), |total| total.to_string())
+                )));
+            }
+            resumes += 1;
+            continue;
+        }
+        writer.flush().await?;
+        return Ok(written);
+    }
+}
+
+/// Stream a response body to `path` without holding it in memory.
+///
+/// The body is written to a `.part` sibling and renamed into place once
+/// complete, so `path` never holds a truncated download.
+pub async fn download_to_path<F>(
+    request: F,
+    path: &Path,
+    options: &DownloadOptions,
+) -> Result<u64, RociError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let part_path = part_path(path);
+    let mut file = tokio::fs::File::create(&part_path).await?;
+    let result = download_to_writer(request, &mut file, options).await;
+    drop(file);
+    match result {
+        Ok(written) => {
+            tokio::fs::rename(&part_path, path).await?;
+            Ok(written)
+        }
+        Err(err) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            Err(err)
+        }
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// The response's content type when `options` restricts it and it is not
+/// accepted.
+fn unexpected_content_type(
+    response: &reqwest::Response,
+    options: &DownloadOptions,
+) -> Option<String> {
+    if options.content_types.is_empty() {
+        return None;
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let mime = content_type
+        .split(';')
+        .next()
+        .map(str::trim)
+        .unwrap_or_default();
+    let accepted = options
+        .content_types
+        .iter()
+        .any(|accepted| accepted.eq_ignore_ascii_case(mime));
+    (!accepted).then_some(content_type)
+}
+
+/// `error.message` from a JSON error body, the shape most providers use.
+fn json_error_message(body: &str) -> Option<String> {
+    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
+    parsed
+        .get("error")
+        .and_then(|error| error.get("message"))
+        .and_then(|message| message.as_str())
+        .map(ToString::to_string)
+}
+
+/// First byte offset of a `Content-Range: bytes <start>-<end>/<total>` header.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let range = content_range(response)?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    let range = content_range(response)?;
+    range.rsplit('/').next()?.trim().parse().ok()
+}
+
+fn content_range(response: &reqwest::Response) -> Option<&str> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .trim()
+        .strip_prefix("bytes ")
+}
+
+fn size_limit_error(max: u64) -> RociError {
+    RociError::InvalidState(format!("download exceeds the {max} byte size limit"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::Pin;
+    use std::sync::Mutex;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Records the size of every write instead of keeping the bytes.
+    #[derive(Default)]
+    struct CountingWriter {
+        total: u64,
+        largest_write: usize,
+        writes: usize,
+    }
+
+    impl AsyncWrite for CountingWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.total += buf.len() as u64;
+            self.largest_write = self.largest_write.max(buf.len());
+            self.writes += 1;
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn large_body(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    async fn serve_audio(body: Vec<u8>) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/audio.mp3"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "audio/mpeg")
+                    .set_body_bytes(body),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn large_body_is_written_in_chunks_with_progress() {
+        let len = 8 * 1024 * 1024;
+        let server = serve_audio(large_body(len)).await;
+        let url = format!("{}/audio.mp3", server.uri());
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let seen = progress.clone();
+        let options = DownloadOptions::new()
+            .with_content_types(["audio/mpeg"])
+            .with_progress(Arc::new(move |update| {
+                seen.lock().unwrap().push(update);
+            }));
+
+        let mut writer = CountingWriter::default();
+        let written =
+            download_to_writer(|| reqwest::Client::new().get(&url), &mut writer, &options)
+                .await
+                .unwrap();
+
+        assert_eq!(written, len as u64);
+        assert_eq!(writer.total, len as u64);
+        assert!(writer.writes > 1);
+        assert!(
+            writer.largest_write < len / 4,
+            "largest write was {} bytes",
+            writer.largest_write
+        );
+        let progress = progress.lock().unwrap();
+        assert_eq!(
+            progress.last().copied(),
+            Some(DownloadProgress {
+                bytes_downloaded: len as u64,
+                total_bytes: Some(len as u64),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn body_over_size_limit_is_rejected_and_leaves_no_file() {
+        let server = serve_audio(large_body(64 * 1024)).await;
+        let url = format!("{}/audio.mp3", server.uri());
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("speech.mp3");
+        let options = DownloadOptions::new().with_max_bytes(1024);
+
+        let err = download_to_path(|| reqwest::Client::new().get(&url), &target, &options)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("1024 byte size limit"), "{err}");
+        assert!(!target.exists());
+        assert!(!part_path(&target).exists());
+    }
+
+    #[tokio::test]
+    async fn unexpected_content_type_is_rejected() {
+        let server = serve_audio(b"audio".to_vec()).await;
+        let url = format!("{}/audio.mp3", server.uri());
+        let options = DownloadOptions::new().with_content_types(["image/png"]);
+
+        let mut sink = Vec::new();
+        let err = download_to_writer(|| reqwest::Client::new().get(&url), &mut sink, &options)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("audio/mpeg"), "{err}");
+        assert!(sink.is_empty());
+    }
+
+    #[tokio::test]
+    async fn dropped_connection_resumes_with_range_request() {
+        let body = large_body(256 * 1024);
+        let half = body.len() / 2;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let server_ranges = ranges.clone();
+        let server_body = body.clone();
+        tokio::spawn(async move {
+            for attempt in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    let read = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                let request = String::from_utf8_lossy(&request).to_ascii_lowercase();
+                let range = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("range: "))
+                    .map(str::to_string);
+                server_ranges.lock().unwrap().push(range);
+                if attempt == 0 {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: audio/mpeg\r\ncontent-length: {}\r\n\r\n",
+                        server_body.len()
+                    );
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&server_body[..half]).await.unwrap();
+                } else {
+                    let head = format!(
+                        "HTTP/1.1 206 Partial Content\r\ncontent-type: audio/mpeg\r\ncontent-length: {}\r\ncontent-range: bytes {half}-{}/{}\r\n\r\n",
+                        server_body.len() - half,
+                        server_body.len() - 1,
+                        server_body.len()
+                    );
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&server_body[half..]).await.unwrap();
+                }
+                socket.shutdown().await.unwrap();
+            }
+        });
+
+        let url = format!("http://{addr}/audio.mp3");
+        let options = DownloadOptions::new().with_max_resume_attempts(1);
+        let mut sink = Vec::new();
+        let written = download_to_writer(|| reqwest::Client::new().get(&url), &mut sink, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(written, body.len() as u64);
+        assert_eq!(sink, body);
+        assert_eq!(
+            *ranges.lock().unwrap(),
+            vec![None, Some(format!("bytes={half}-"))]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Client-wide HTTP settings shared by providers and tools.
+
+use std::time::Duration;
+
+use crate::error::RociError;
+
+/// Default request timeout for clients built from [`HttpOptions`].
+pub const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Idle connections kept per host.
+const POOL_MAX_IDLE_PER_HOST: usize = 10;
+
+/// Proxy, user agent, and timeout applied to every client roci builds.
+///
+/// [`shared_client`](super::shared_client) uses the defaults. Anything that
+/// needs its own client (for example a tool with a different redirect policy)
+/// starts from [`client_builder`](Self::client_builder) and adds only what
+/// differs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpOptions {
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: None,
+            proxy: None,
+            timeout: DEFAULT_HTTP_TIMEOUT,
+        }
+    }
+}
+
+impl HttpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Route requests through `proxy`. Without one, the standard
+    /// `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` environment variables apply.
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// A client builder with these options applied.
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder, RociError> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST);
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.as_str());
+        }
+        if let Some(proxy) = &self.proxy {
+            let parsed = reqwest::Proxy::all(proxy).map_err(|e| {
+                RociError::Configuration(format!("invalid HTTP proxy '{proxy}': {e}"))
+            })?;
+            builder = builder.proxy(parsed);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_the_shared_client() {
+        let options = HttpOptions::default();
+
+        assert_eq!(options.timeout(), DEFAULT_HTTP_TIMEOUT);
+        assert_eq!(options.user_agent(), None);
+        assert_eq!(options.proxy(), None);
+        assert!(options.client_builder().unwrap().build().is_ok());
+    }
+
+    #[test]
+    fn invalid_proxies_are_configuration_errors() {
+        let err = HttpOptions::new()
+            .with_proxy("not a url")
+            .client_builder()
+            .unwrap_err();
+
+        assert!(
+            matches!(err, RociError::Configuration(ref message) if message.contains("not a url"))
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! JSON request sending with optional gzip, and request/response size
+//! accounting for [`PayloadSizes`].
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::{BoxStream, Stream, StreamExt};
+use reqwest::header::{HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+
+use crate::config::RequestCompression;
+use crate::error::RociError;
+use crate::types::{PayloadSizes, TextStreamDelta};
+
+/// Endpoints that rejected a gzipped body; they get plain bodies from then on.
+static GZIP_REJECTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn gzip_rejected() -> &'static Mutex<HashSet<String>> {
+    GZIP_REJECTED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Send `body` as JSON with `request`, gzipped when `compression` allows it.
+///
+/// A 415, or a 400 that blames the encoding, from a gzipped send is retried
+/// once with a plain body, and that endpoint is not gzipped again in this
+/// process. Any other response is returned as is, with the sizes of the body
+/// that was sent.
+///
+/// # Errors
+///
+/// Returns [`RociError::Serialization`] when `body` cannot be encoded,
+/// [`RociError::Io`] when it cannot be compressed, and
+/// [`RociError::Network`] when the request cannot be sent.
+pub async fn send_json(
+    request: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+    compression: Option<RequestCompression>,
+) -> Result<(reqwest::Response, PayloadSizes), RociError> {
+    let json = serde_json::to_vec(body)?;
+    let (client, request) = request.build_split();
+    let mut request = request?;
+    request
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    let endpoint = request.url().as_str().to_string();
+    let plain = PayloadSizes {
+        request_bytes: json.len() as u64,
+        request_sent_bytes: json.len() as u64,
+        request_gzipped: false,
+        response_bytes: None,
+    };
+
+    let gzip = compression.is_some_and(|compression| json.len() >= compression.min_bytes)
+        && !gzip_rejected().lock().unwrap().contains(&endpoint);
+    if !gzip {
+        *request.body_mut() = Some(json.into());
+        return Ok((client.execute(request).await?, plain));
+    }
+
+    let compressed = gzip_bytes(&json)?;
+    let mut fallback = request.try_clone().expect("request without a body clones");
+    *fallback.body_mut() = Some(json.into());
+    request
+        .headers_mut()
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    let sizes = PayloadSizes {
+        request_sent_bytes: compressed.len() as u64,
+        request_gzipped: true,
+        ..plain
+    };
+    *request.body_mut() = Some(compressed.into());
+    let response = client.execute(request).await?;
+
+    let status = response.status().as_u16();
+    if !matches!(status, 400 | 415) {
+        return Ok((response, sizes));
+    }
+    let mut rebuilt = http::Response::builder().status(status);
+    for (name, value) in response.headers() {
+        rebuilt = rebuilt.header(name, value);
+    }
+    let error_body = response.bytes().await?;
+    if status == 400 && !blames_encoding(&error_body) {
+        let response = rebuilt
+            .body(error_body)
+            .expect("status and headers came from a valid response");
+        return Ok((response.into(), sizes));
+    }
+
+    tracing::warn!(
+        endpoint = %endpoint,
+        status,
+        "endpoint rejected a gzipped request body; sending plain bodies from now on"
+    );
+    gzip_rejected().lock().unwrap().insert(endpoint);
+    Ok((client.execute(fallback).await?, plain))
+}
+
+fn gzip_bytes(json: &[u8]) -> Result<Vec<u8>, RociError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json)?;
+    Ok(encoder.finish()?)
+}
+
+/// Whether a 400 body says the server cannot decode the request.
+fn blames_encoding(body: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(body).to_ascii_lowercase();
+    ["gzip", "content-encoding", "compress"]
+        .iter()
+        .any(|needle| text.contains(needle))
+}
+
+/// Counts the bytes of a response body as it streams past.
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Arc<AtomicU64>);
+
+impl ByteCounter {
+    /// Pass `stream` through, adding each chunk's length to this counter.
+    pub fn count<B: AsRef<[u8]>, E>(
+        &self,
+        stream: impl Stream<Item = Result<B, E>>,
+    ) -> impl Stream<Item = Result<B, E>> {
+        let total = self.0.clone();
+        stream.inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                total.fetch_add(chunk.as_ref().len() as u64, Ordering::Relaxed);
+            }
+        })
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Add `sizes` to the response metadata that
+/// [`with_response_metadata`](super::with_response_metadata) put on the Done
+/// delta, with the response size read from `received` at that point.
+pub fn with_payload_sizes(
+    stream: BoxStream<'static, Result<TextStreamDelta, RociError>>,
+    sizes: PayloadSizes,
+    received: ByteCounter,
+) -> BoxStream<'static, Result<TextStreamDelta, RociError>> {
+    Box::pin(stream.map(move |delta| {
+        delta.map(|mut delta| {
+            if let Some(metadata) = delta.response_metadata.as_mut() {
+                metadata.payload = Some(PayloadSizes {
+                    response_bytes: Some(received.get()),
+                    ..sizes
+                });
+            }
+            delta
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_complaints_are_recognized() {
+        assert!(blames_encoding(
+            br#"{"error":{"message":"Unsupported Content-Encoding: gzip"}}"#
+        ));
+        assert!(!blames_encoding(
+            br#"{"error":{"message":"max_tokens is too large"}}"#
+        ));
+    }
+}
@@ -0,0 +1,365 @@
+//! Table-driven parsing of request-id and rate-limit response headers.
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use reqwest::header::HeaderMap;
+
+use crate::error::RociError;
+use crate::types::{RateLimitInfo, ResponseMetadata, StreamEventType, TextStreamDelta};
+
+/// How a rate-limit reset header expresses when the allowance refills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetFormat {
+    /// A duration such as `6m0s`, `1.5s`, or `20ms` (OpenAI).
+    Duration,
+    /// An RFC 3339 timestamp (Anthropic).
+    Timestamp,
+}
+
+/// Which response headers a provider uses for its request id and rate
+/// limits. Names are lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseHeaderRules {
+    /// Request-id headers, first present wins.
+    pub request_id: &'static [&'static str],
+    pub requests_limit: Option<&'static str>,
+    pub requests_remaining: Option<&'static str>,
+    pub requests_reset: Option<&'static str>,
+    pub tokens_limit: Option<&'static str>,
+    pub tokens_remaining: Option<&'static str>,
+    pub tokens_reset: Option<&'static str>,
+    pub reset_format: ResetFormat,
+    /// Headers starting with one of these are kept in
+    /// [`ResponseMetadata::raw_headers_subset`] along with the request id.
+    pub raw_prefixes: &'static [&'static str],
+}
+
+impl ResponseHeaderRules {
+    /// OpenAI and OpenAI-compatible servers (Groq, OpenRouter, Together, ...).
+    pub const OPENAI: Self = Self {
+        request_id: &["x-request-id"],
+        requests_limit: Some("x-ratelimit-limit-requests"),
+        requests_remaining: Some("x-ratelimit-remaining-requests"),
+        requests_reset: Some("x-ratelimit-reset-requests"),
+        tokens_limit: Some("x-ratelimit-limit-tokens"),
+        tokens_remaining: Some("x-ratelimit-remaining-tokens"),
+        tokens_reset: Some("x-ratelimit-reset-tokens"),
+        reset_format: ResetFormat::Duration,
+        raw_prefixes: &["x-ratelimit-"],
+    };
+
+    /// Anthropic Messages API.
+    pub const ANTHROPIC: Self = Self {
+        request_id: &["request-id"],
+        requests_limit: Some("anthropic-ratelimit-requests-limit"),
+        requests_remaining: Some("anthropic-ratelimit-requests-remaining"),
+        requests_reset: Some("anthropic-ratelimit-requests-reset"),
+        tokens_limit: Some("anthropic-ratelimit-tokens-limit"),
+        tokens_remaining: Some("anthropic-ratelimit-tokens-remaining"),
+        tokens_reset: Some("anthropic-ratelimit-tokens-reset"),
+        reset_format: ResetFormat::Timestamp,
+        raw_prefixes: &["anthropic-ratelimit-"],
+    };
+
+    /// AWS Bedrock, which reports a request id but no rate limits.
+    pub const BEDROCK: Self = Self {
+        request_id: &["x-amzn-requestid"],
+        requests_limit: None,
+        requests_remaining: None,
+        requests_reset: None,
+        tokens_limit: None,
+        tokens_remaining: None,
+        tokens_reset: None,
+        reset_format: ResetFormat::Duration,
+        raw_prefixes: &[],
+    };
+}
+
+/// Read the request id and rate limits `rules` describe from `headers`.
+pub fn response_metadata(headers: &HeaderMap, rules: &ResponseHeaderRules) -> ResponseMetadata {
+    metadata_at(headers, rules, chrono::Utc::now())
+}
+
+fn metadata_at(
+    headers: &HeaderMap,
+    rules: &ResponseHeaderRules,
+    now: chrono::DateTime<chrono::Utc>,
+) -> ResponseMetadata {
+    let header = |name: Option<&str>| {
+        name.and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let count = |name| header(name).and_then(|value| value.parse::<u64>().ok());
+    let reset = |name| {
+        header(name).and_then(|value| match rules.reset_format {
+            ResetFormat::Duration => parse_duration_ms(value),
+            ResetFormat::Timestamp => chrono::DateTime::parse_from_rfc3339(value).ok().map(|at| {
+                let millis = (at.with_timezone(&chrono::Utc) - now).num_milliseconds();
+                u64::try_from(millis).unwrap_or(0)
+            }),
+        })
+    };
+
+    let rate_limit = RateLimitInfo {
+        requests_limit: count(rules.requests_limit),
+        requests_remaining: count(rules.requests_remaining),
+        requests_reset_ms: reset(rules.requests_reset),
+        tokens_limit: count(rules.tokens_limit),
+        tokens_remaining: count(rules.tokens_remaining),
+        tokens_reset_ms: reset(rules.tokens_reset),
+    };
+    let raw_headers_subset = headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            rules.request_id.contains(&name)
+                || rules
+                    .raw_prefixes
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+        })
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    ResponseMetadata {
+        provider_request_id: rules
+            .request_id
+            .iter()
+            .find_map(|name| header(Some(name)))
+            .map(str::to_string),
+        rate_limit: (rate_limit != RateLimitInfo::default()).then_some(rate_limit),
+        raw_headers_subset,
+        parse_anomalies: 0,
+        payload: None,
+    }
+}
+
+/// Milliseconds in a Go-style duration such as `1h2m3.5s` or `20ms`.
+fn parse_duration_ms(value: &str) -> Option<u64> {
+    let mut rest = value;
+    let mut total = 0.0f64;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&end| end > 0)?;
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_end] {
+            "h" => 3_600_000.0,
+            "m" => 60_000.0,
+            "s" => 1_000.0,
+            "ms" => 1.0,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[unit_end..];
+    }
+    Some(total.round() as u64)
+}
+
+/// Attach `metadata` to the [`StreamEventType::Done`] delta of `stream`.
+pub fn with_response_metadata(
+    stream: BoxStream<'static, Result<TextStreamDelta, RociError>>,
+    metadata: ResponseMetadata,
+) -> BoxStream<'static, Result<TextStreamDelta, RociError>> {
+    let mut metadata = Some(Box::new(metadata));
+    Box::pin(stream.map(move |delta| {
+        delta.map(|mut delta| {
+            if delta.event_type == StreamEventType::Done {
+                delta.response_metadata = metadata.take();
+            }
+            delta
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_each_provider_format() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        // (label, rules, headers, expected request id, expected rate limit)
+        type Case = (
+            &'static str,
+            ResponseHeaderRules,
+            &'static [(&'static str, &'static str)],
+            Option<&'static str>,
+            Option<RateLimitInfo>,
+        );
+        let cases: &[Case] = &[
+            (
+                "openai",
+                ResponseHeaderRules::OPENAI,
+                &[
+                    ("x-request-id", "req_123"),
+                    ("x-ratelimit-limit-requests", "500"),
+                    ("x-ratelimit-remaining-requests", "499"),
+                    ("x-ratelimit-reset-requests", "120ms"),
+                    ("x-ratelimit-limit-tokens", "30000"),
+                    ("x-ratelimit-remaining-tokens", "29000"),
+                    ("x-ratelimit-reset-tokens", "6m0.5s"),
+                ],
+                Some("req_123"),
+                Some(RateLimitInfo {
+                    requests_limit: Some(500),
+                    requests_remaining: Some(499),
+                    requests_reset_ms: Some(120),
+                    tokens_limit: Some(30_000),
+                    tokens_remaining: Some(29_000),
+                    tokens_reset_ms: Some(360_500),
+                }),
+            ),
+            (
+                "anthropic",
+                ResponseHeaderRules::ANTHROPIC,
+                &[
+                    ("request-id", "req_abc"),
+                    ("anthropic-ratelimit-requests-limit", "50"),
+                    ("anthropic-ratelimit-requests-remaining", "49"),
+                    ("anthropic-ratelimit-requests-reset", "2026-01-01T00:00:01Z"),
+                    ("anthropic-ratelimit-tokens-remaining", "7000"),
+                    ("anthropic-ratelimit-tokens-reset", "2025-12-31T23:59:59Z"),
+                ],
+                Some("req_abc"),
+                Some(RateLimitInfo {
+                    requests_limit: Some(50),
+                    requests_remaining: Some(49),
+                    requests_reset_ms: Some(1_000),
+                    tokens_limit: None,
+                    tokens_remaining: Some(7_000),
+                    tokens_reset_ms: Some(0),
+                }),
+            ),
+            (
+                "bedrock",
+                ResponseHeaderRules::BEDROCK,
+                &[
+                    ("x-amzn-requestid", "abc-123"),
+                    ("x-ratelimit-limit-requests", "5"),
+                ],
+                Some("abc-123"),
+                None,
+            ),
+            (
+                "openai without headers",
+                ResponseHeaderRules::OPENAI,
+                &[("content-type", "application/json")],
+                None,
+                None,
+            ),
+        ];
+
+        for (label, rules, pairs, request_id, rate_limit) in cases {
+            let metadata = metadata_at(&headers(pairs), rules, now);
+            assert_eq!(
+                metadata.provider_request_id.as_deref(),
+                *request_id,
+                "{label}"
+            );
+            assert_eq!(&metadata.rate_limit, rate_limit, "{label}");
+        }
+    }
+
+    #[test]
+    fn raw_subset_keeps_only_request_id_and_rate_limit_headers() {
+        let metadata = response_metadata(
+            &headers(&[
+                ("x-request-id", "req_1"),
+                ("x-ratelimit-remaining-requests", "9"),
+                ("x-ratelimit-limit-project-tokens", "100"),
+                ("set-cookie", "secret"),
+                ("content-type", "application/json"),
+            ]),
+            &ResponseHeaderRules::OPENAI,
+        );
+
+        let names: Vec<&str> = metadata
+            .raw_headers_subset
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "x-ratelimit-limit-project-tokens",
+                "x-ratelimit-remaining-requests",
+                "x-request-id",
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_values_are_ignored() {
+        assert_eq!(parse_duration_ms("1h2m3s"), Some(3_723_000));
+        assert_eq!(parse_duration_ms("0.5s"), Some(500));
+        assert_eq!(parse_duration_ms("soon"), None);
+        assert_eq!(parse_duration_ms("5x"), None);
+
+        let metadata = response_metadata(
+            &headers(&[
+                ("x-ratelimit-remaining-requests", "many"),
+                ("x-ratelimit-reset-requests", "later"),
+            ]),
+            &ResponseHeaderRules::OPENAI,
+        );
+        assert_eq!(metadata.rate_limit, None);
+        assert_eq!(metadata.raw_headers_subset.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn metadata_rides_on_the_done_delta_only() {
+        let text = TextStreamDelta {
+            text: "hi".to_string(),
+            event_type: StreamEventType::TextDelta,
+            tool_call: None,
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        };
+        let done = TextStreamDelta {
+            text: String::new(),
+            event_type: StreamEventType::Done,
+            ..text.clone()
+        };
+        let metadata = ResponseMetadata {
+            provider_request_id: Some("req_1".to_string()),
+            ..ResponseMetadata::default()
+        };
+        let stream = futures::stream::iter([Ok(text), Ok(done)]).boxed();
+
+        let deltas: Vec<_> = with_response_metadata(stream, metadata.clone())
+            .collect()
+            .await;
+
+        assert!(deltas[0].as_ref().unwrap().response_metadata.is_none());
+        assert_eq!(
+            deltas[1].as_ref().unwrap().response_metadata.as_deref(),
+            Some(&metadata)
+        );
+    }
+}
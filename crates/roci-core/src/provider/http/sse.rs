@@ -0,0 +1,391 @@
+//! Incremental Server-Sent Events decoding.
+//!
+//! [`SseDecoder`] follows the WHATWG event-stream parsing rules: lines end
+//! in CRLF, LF, or CR; `data:` fields of one event are joined with newlines;
+//! comment lines are skipped; `id:` persists until replaced; and an event is
+//! dispatched at the blank line that ends it. Bytes are buffered until a
+//! line is complete, so chunk boundaries inside a UTF-8 code point or a CRLF
+//! pair do not change the output.
+
+use futures::{Stream, StreamExt};
+
+/// Event type used when a record has no `event:` field.
+pub const DEFAULT_EVENT_TYPE: &str = "message";
+
+/// One dispatched event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `event:` field, or [`DEFAULT_EVENT_TYPE`].
+    pub event: String,
+    /// `data:` fields joined with `\n`.
+    pub data: String,
+    /// Last event id seen on the stream, including earlier events.
+    pub id: Option<String>,
+    /// Reconnection time in milliseconds, when a `retry:` field arrived
+    /// since the previous event.
+    pub retry: Option<u64>,
+}
+
+impl SseEvent {
+    /// Whether this is the OpenAI-style `[DONE]` terminator.
+    pub fn is_done(&self) -> bool {
+        self.data == "[DONE]"
+    }
+}
+
+/// Incremental event-stream decoder over byte chunks.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    /// Leading bytes of `buffer` already searched for a line ending, so a
+    /// line split across many chunks is scanned once.
+    scanned: usize,
+    /// The previous line ended in CR; a leading LF belongs to it.
+    after_cr: bool,
+    started: bool,
+    event: String,
+    data: String,
+    has_data: bool,
+    last_event_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed `chunk`, returning the events it completes.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        let mut chunk = chunk;
+        if self.after_cr && !chunk.is_empty() {
+            self.after_cr = false;
+            if chunk[0] == b'\n' {
+                chunk = &chunk[1..];
+            }
+        }
+        self.buffer.extend_from_slice(chunk);
+
+        let mut start = 0;
+        let mut index = self.scanned;
+        while index < self.buffer.len() {
+            match self.buffer[index] {
+                b'\n' | b'\r' => {
+                    let line = String::from_utf8_lossy(&self.buffer[start..index]).into_owned();
+                    let crlf = self.buffer[index] == b'\r';
+                    index += 1;
+                    if crlf {
+                        match self.buffer.get(index) {
+                            Some(b'\n') => index += 1,
+                            Some(_) => {}
+                            None => self.after_cr = true,
+                        }
+                    }
+                    start = index;
+                    if let Some(event) = self.line(&line) {
+                        events.push(event);
+                    }
+                }
+                _ => index += 1,
+            }
+        }
+        self.buffer.drain(..start);
+        self.scanned = self.buffer.len();
+        events
+    }
+
+    /// End of stream: flush an unterminated final line and event.
+    ///
+    /// The spec discards an event that is not followed by a blank line;
+    /// servers that close the connection right after the last `data:` line
+    /// are common enough that the pending event is dispatched instead.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            self.scanned = 0;
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            if let Some(event) = self.line(&line) {
+                return Some(event);
+            }
+        }
+        self.line("")
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        let line = if self.started {
+            line
+        } else {
+            self.started = true;
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        };
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.event);
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+        Some(SseEvent {
+            event: if event.is_empty() {
+                DEFAULT_EVENT_TYPE.to_string()
+            } else {
+                event
+            },
+            data: std::mem::take(&mut self.data),
+            id: self.last_event_id.clone().filter(|id| !id.is_empty()),
+            retry: self.retry.take(),
+        })
+    }
+}
+
+/// Decode a response byte stream into events.
+///
+/// Transport errors are passed through and end the stream.
+pub fn sse_events<S, B, E>(bytes: S) -> impl Stream<Item = Result<SseEvent, E>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    async_stream::stream! {
+        let mut decoder = SseDecoder::new();
+        futures::pin_mut!(bytes);
+        while let Some(chunk) = bytes.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    for event in decoder.push(chunk.as_ref()) {
+                        yield Ok(event);
+                    }
+                }
+                Err(err) => {
+                    yield Err(err);
+                    return;
+                }
+            }
+        }
+        if let Some(event) = decoder.finish() {
+            yield Ok(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(input: &[u8]) -> Vec<SseEvent> {
+        let mut decoder = SseDecoder::new();
+        let mut events = decoder.push(input);
+        events.extend(decoder.finish());
+        events
+    }
+
+    fn event(name: &str, data: &str) -> SseEvent {
+        SseEvent {
+            event: name.to_string(),
+            data: data.to_string(),
+            id: None,
+            retry: None,
+        }
+    }
+
+    #[test]
+    fn spec_example_stock_updates() {
+        let events = decode(b"data: YHOO\ndata: +2\ndata: 10\n\n");
+
+        assert_eq!(events, vec![event("message", "YHOO\n+2\n10")]);
+    }
+
+    #[test]
+    fn spec_example_ids_persist_and_comments_are_ignored() {
+        let events = decode(
+            b": test stream\n\ndata: first event\nid: 1\n\ndata:second event\nid\n\ndata:  third event\n\n",
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].data, "first event");
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[1].data, "second event");
+        assert_eq!(events[1].id, None);
+        assert_eq!(events[2].data, " third event");
+    }
+
+    #[test]
+    fn spec_example_empty_data_fields() {
+        let events = decode(b"data\n\ndata\ndata\n\ndata:\n");
+
+        assert_eq!(
+            events,
+            vec![
+                event("message", ""),
+                event("message", "\n"),
+                event("message", "")
+            ]
+        );
+    }
+
+    #[test]
+    fn spec_example_space_after_colon_is_optional() {
+        let events = decode(b"data:test\n\ndata: test\n\n");
+
+        assert_eq!(
+            events,
+            vec![event("message", "test"), event("message", "test")]
+        );
+    }
+
+    #[test]
+    fn event_names_and_retry_are_reported() {
+        let events =
+            decode(b"retry: 1500\nevent: message_start\ndata: {}\n\nretry: soon\ndata: x\n\n");
+
+        assert_eq!(events[0].event, "message_start");
+        assert_eq!(events[0].retry, Some(1500));
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].retry, None);
+    }
+
+    #[test]
+    fn all_line_endings_are_accepted() {
+        let expected = vec![event("a", "1\n2"), event("message", "3")];
+        for input in [
+            &b"event: a\ndata: 1\ndata: 2\n\ndata: 3\n\n"[..],
+            &b"event: a\r\ndata: 1\r\ndata: 2\r\n\r\ndata: 3\r\n\r\n"[..],
+            &b"event: a\rdata: 1\rdata: 2\r\rdata: 3\r\r"[..],
+        ] {
+            assert_eq!(decode(input), expected);
+        }
+    }
+
+    #[test]
+    fn leading_bom_and_unknown_fields_are_ignored() {
+        let events = decode("\u{feff}data: a\nfoo: bar\n\n".as_bytes());
+
+        assert_eq!(events, vec![event("message", "a")]);
+    }
+
+    #[test]
+    fn blank_lines_without_data_dispatch_nothing() {
+        let events = decode(b"event: ping\n\n\n: keepalive\n\ndata: x\n\n");
+
+        assert_eq!(events, vec![event("message", "x")]);
+    }
+
+    #[test]
+    fn split_code_points_and_crlf_pairs_survive_chunking() {
+        let input = "data: h\u{e9}llo \u{1f600}\r\n\r\n".as_bytes();
+        let mut decoder = SseDecoder::new();
+        let mut events = Vec::new();
+        for byte in input {
+            events.extend(decoder.push(std::slice::from_ref(byte)));
+        }
+        events.extend(decoder.finish());
+
+        assert_eq!(events, vec![event("message", "h\u{e9}llo \u{1f600}")]);
+    }
+
+    #[test]
+    fn pushes_only_scan_bytes_not_seen_before() {
+        let mut decoder = SseDecoder::new();
+        for byte in b"data: partial" {
+            assert!(decoder.push(std::slice::from_ref(byte)).is_empty());
+            assert_eq!(decoder.scanned, decoder.buffer.len());
+        }
+
+        let events = decoder.push(b" line\n\n");
+        assert_eq!(events, vec![event("message", "partial line")]);
+        assert_eq!(decoder.scanned, 0);
+        assert!(decoder.buffer.is_empty());
+    }
+
+    #[test]
+    fn finish_flushes_an_unterminated_event() {
+        let mut decoder = SseDecoder::new();
+
+        assert!(decoder.push(b"data: {\"done\":true}").is_empty());
+        assert_eq!(decoder.finish(), Some(event("message", "{\"done\":true}")));
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn random_chunk_boundaries_produce_identical_events() {
+        let corpus = [
+            &b": comment\r\nevent: content_block_delta\r\nid: 7\r\ndata: {\"a\":\r\ndata: 1}\r\n\r\n"[..],
+            "data: caf\u{e9} \u{4e2d}\u{6587} \u{1f680}\n\n".as_bytes(),
+            &b"retry: 3000\rdata: cr only\r\rdata\n\nevent: x\ndata: [DONE]\n\n"[..],
+            "\u{feff}data: first\n\ndata: last".as_bytes(),
+        ];
+        let mut seed = 0x9e37_79b9_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+
+        for input in corpus {
+            let expected = decode(input);
+            assert!(!expected.is_empty());
+            for _ in 0..200 {
+                let mut decoder = SseDecoder::new();
+                let mut events = Vec::new();
+                let mut rest = input;
+                while !rest.is_empty() {
+                    let len = 1 + (next() as usize % rest.len().min(8));
+                    let (chunk, tail) = rest.split_at(len);
+                    events.extend(decoder.push(chunk));
+                    rest = tail;
+                }
+                events.extend(decoder.finish());
+                assert_eq!(events, expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_adapter_passes_errors_through() {
+        let chunks: Vec<Result<&[u8], &str>> = vec![
+            Ok(&b"data: a\n\nda"[..]),
+            Ok(&b"ta: b\n\n"[..]),
+            Err("reset"),
+        ];
+
+        let events = sse_events(futures::stream::iter(chunks))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                Ok(event("message", "a")),
+                Ok(event("message", "b")),
+                Err("reset")
+            ]
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Provider-specific transcript structure rules and a preflight lint.
+//!
+//! [`MessageRules`] is the single description of what each provider accepts.
+//! [`lint_messages`] reports violations; the sanitize pass
+//! ([`super::sanitize_messages_for_provider`]) repairs the ones marked
+//! [`MessageLintKind::is_repaired`] using the same rules.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ContentPart, ModelMessage, Role};
+
+/// Structural rules a provider enforces on a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRules {
+    /// Thinking parts may be replayed; otherwise they are stripped.
+    pub thinking: bool,
+    /// Each tool call must be answered by a tool result directly after the
+    /// assistant message that made it, and every tool result must answer one.
+    pub tool_pairing: bool,
+    /// User and assistant turns alternate; tool results count as user turns.
+    pub alternating_roles: bool,
+    /// The transcript must not end with an assistant message. Prefill is
+    /// applied after linting, so it is not affected.
+    pub no_trailing_assistant: bool,
+    /// System messages are sent apart from the contents, so one that appears
+    /// after the conversation starts is moved ahead of it.
+    pub leading_system_only: bool,
+}
+
+impl MessageRules {
+    /// No rules beyond stripping thinking; used for unknown providers.
+    pub const PERMISSIVE: Self = Self {
+        thinking: false,
+        tool_pairing: false,
+        alternating_roles: false,
+        no_trailing_assistant: false,
+        leading_system_only: false,
+    };
+
+    /// Rules for `provider`, by provider key.
+    pub fn for_provider(provider: &str) -> Self {
+        PROVIDER_RULES
+            .iter()
+            .find(|(providers, _)| providers.contains(&provider))
+            .map_or(Self::PERMISSIVE, |(_, rules)| *rules)
+    }
+}
+
+const ANTHROPIC_RULES: MessageRules = MessageRules {
+    thinking: true,
+    tool_pairing: true,
+    alternating_roles: true,
+    no_trailing_assistant: true,
+    leading_system_only: false,
+};
+
+const GOOGLE_RULES: MessageRules = MessageRules {
+    tool_pairing: true,
+    leading_system_only: true,
+    ..MessageRules::PERMISSIVE
+};
+
+const OPENAI_RULES: MessageRules = MessageRules {
+    tool_pairing: true,
+    ..MessageRules::PERMISSIVE
+};
+
+const PROVIDER_RULES: &[(&[&str], MessageRules)] = &[
+    (
+        &["anthropic", "anthropic-compatible", "bedrock"],
+        ANTHROPIC_RULES,
+    ),
+    (&["google"], GOOGLE_RULES),
+    (
+        &[
+            "openai",
+            "openai-compatible",
+            "github-copilot",
+            "openrouter",
+            "azure",
+            "groq",
+            "grok",
+            "mistral",
+            "together",
+            "lmstudio",
+            "ollama",
+        ],
+        OPENAI_RULES,
+    ),
+];
+
+/// One rule violation, at `index` in the linted transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageLint {
+    pub index: usize,
+    #[serde(flatten)]
+    pub kind: MessageLintKind,
+}
+
+/// What a [`MessageLint`] found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MessageLintKind {
+    /// A tool call with no tool result directly after its assistant message.
+    MissingToolResult { tool_call_id: String },
+    /// A tool result that answers no call of the preceding assistant message,
+    /// or answers one a second time.
+    OrphanToolResult { tool_call_id: String },
+    /// Two user or two assistant messages in a row, ignoring system messages.
+    ConsecutiveRole { role: Role },
+    /// The transcript ends with an assistant message.
+    TrailingAssistant,
+    /// A system message after the first non-system message.
+    SystemAfterContents,
+}
+
+/// How serious a [`MessageLintKind`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageLintSeverity {
+    /// The provider rejects the request unless the transcript is repaired.
+    Error,
+    /// The provider accepts the request but reshapes the transcript.
+    Warning,
+}
+
+impl MessageLintKind {
+    /// Severity of this lint.
+    pub fn severity(&self) -> MessageLintSeverity {
+        match self {
+            Self::SystemAfterContents => MessageLintSeverity::Warning,
+            _ => MessageLintSeverity::Error,
+        }
+    }
+
+    /// Whether the sanitize pass repairs this before the request is sent.
+    pub fn is_repaired(&self) -> bool {
+        matches!(
+            self,
+            Self::MissingToolResult { .. }
+                | Self::OrphanToolResult { .. }
+                | Self::ConsecutiveRole { .. }
+        )
+    }
+}
+
+impl fmt::Display for MessageLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "message {}: ", self.index)?;
+        match &self.kind {
+            MessageLintKind::MissingToolResult { tool_call_id } => {
+                write!(f, "tool call {tool_call_id} has no tool result")
+            }
+            MessageLintKind::OrphanToolResult { tool_call_id } => {
+                write!(f, "tool result {tool_call_id} answers no pending tool call")
+            }
+            MessageLintKind::ConsecutiveRole { role } => {
+                write!(
+                    f,
+                    "consecutive {} messages",
+                    format!("{role:?}").to_lowercase()
+                )
+            }
+            MessageLintKind::TrailingAssistant => {
+                f.write_str("transcript ends with an assistant message")
+            }
+            MessageLintKind::SystemAfterContents => {
+                f.write_str("system message after the conversation started")
+            }
+        }
+    }
+}
+
+/// Check `messages` against the rules of `provider`, ordered by index.
+pub fn lint_messages(messages: &[ModelMessage], provider: &str) -> Vec<MessageLint> {
+    let rules = MessageRules::for_provider(provider);
+    let mut lints = Vec::new();
+    if rules.tool_pairing {
+        lint_tool_pairing(messages, &mut lints);
+    }
+    if rules.alternating_roles {
+        lint_alternating_roles(messages, &mut lints);
+    }
+    if rules.no_trailing_assistant {
+        if let Some((index, _)) = messages
+            .iter()
+            .enumerate()
+            .rfind(|(_, message)| message.role != Role::System)
+            .filter(|(_, message)| message.role == Role::Assistant)
+        {
+            lints.push(MessageLint {
+                index,
+                kind: MessageLintKind::TrailingAssistant,
+            });
+        }
+    }
+    if rules.leading_system_only {
+        lint_system_placement(messages, &mut lints);
+    }
+    lints.sort_by_key(|lint| lint.index);
+    lints
+}
+
+fn lint_tool_pairing(messages: &[ModelMessage], lints: &mut Vec<MessageLint>) {
+    // Calls of the latest assistant message still waiting for a result, one
+    // entry per call so a repeated call id needs a result per occurrence.
+    let mut pending: Vec<(usize, String)> = Vec::new();
+    let flush = |pending: &mut Vec<(usize, String)>, lints: &mut Vec<MessageLint>| {
+        lints.extend(pending.drain(..).map(|(index, tool_call_id)| MessageLint {
+            index,
+            kind: MessageLintKind::MissingToolResult { tool_call_id },
+        }));
+    };
+
+    for (index, message) in messages.iter().enumerate() {
+        match message.role {
+            Role::Assistant => {
+                flush(&mut pending, lints);
+                pending.extend(
+                    message
+                        .tool_calls()
+                        .iter()
+                        .map(|call| (index, call.id.clone())),
+                );
+            }
+            Role::Tool => {
+                let tool_call_id = tool_result_id(message).unwrap_or_default();
+                match pending.iter().position(|(_, id)| id == tool_call_id) {
+                    Some(position) => {
+                        pending.remove(position);
+                    }
+                    None => lints.push(MessageLint {
+                        index,
+                        kind: MessageLintKind::OrphanToolResult {
+                            tool_call_id: tool_call_id.to_string(),
+                        },
+                    }),
+                }
+            }
+            Role::User | Role::System => flush(&mut pending, lints),
+        }
+    }
+    flush(&mut pending, lints);
+}
+
+fn lint_alternating_roles(messages: &[ModelMessage], lints: &mut Vec<MessageLint>) {
+    let mut previous = None;
+    for (index, message) in messages.iter().enumerate() {
+        if message.role == Role::System {
+            continue;
+        }
+        if previous == Some(message.role) && matches!(message.role, Role::User | Role::Assistant) {
+            lints.push(MessageLint {
+                index,
+                kind: MessageLintKind::ConsecutiveRole { role: message.role },
+            });
+        }
+        previous = Some(message.role);
+    }
+}
+
+fn lint_system_placement(messages: &[ModelMessage], lints: &mut Vec<MessageLint>) {
+    let first_content = messages
+        .iter()
+        .position(|message| message.role != Role::System)
+        .unwrap_or(messages.len());
+    lints.extend(
+        messages
+            .iter()
+            .enumerate()
+            .skip(first_content)
+            .filter(|(_, message)| message.role == Role::System)
+            .map(|(index, _)| MessageLint {
+                index,
+                kind: MessageLintKind::SystemAfterContents,
+            }),
+    );
+}
+
+/// Tool call id answered by a tool message.
+pub(super) fn tool_result_id(message: &ModelMessage) -> Option<&str> {
+    if message.role != Role::Tool {
+        return None;
+    }
+    message.content.iter().find_map(|part| match part {
+        ContentPart::ToolResult(result) => Some(result.tool_call_id.as_str()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{sanitize_messages_for_provider, sanitize_shared_messages_for_provider};
+    use crate::types::AgentToolCall;
+    use std::sync::Arc;
+
+    fn calls(ids: &[&str]) -> ModelMessage {
+        ModelMessage {
+            role: Role::Assistant,
+            content: ids
+                .iter()
+                .map(|id| {
+                    ContentPart::ToolCall(AgentToolCall {
+                        id: id.to_string(),
+                        name: "read".to_string(),
+                        arguments: serde_json::json!({}),
+                        called_as: None,
+                        recipient: None,
+                    })
+                })
+                .collect(),
+            name: None,
+            timestamp: None,
+            metadata: None,
+        }
+    }
+
+    fn result(id: &str) -> ModelMessage {
+        ModelMessage::tool_result(id, serde_json::json!({"ok": true}), false)
+    }
+
+    fn user(text: &str) -> ModelMessage {
+        ModelMessage::user(text)
+    }
+
+    fn assistant(text: &str) -> ModelMessage {
+        ModelMessage::assistant(text)
+    }
+
+    fn system(text: &str) -> ModelMessage {
+        ModelMessage::system(text)
+    }
+
+    fn missing(id: &str) -> MessageLintKind {
+        MessageLintKind::MissingToolResult {
+            tool_call_id: id.to_string(),
+        }
+    }
+
+    fn orphan(id: &str) -> MessageLintKind {
+        MessageLintKind::OrphanToolResult {
+            tool_call_id: id.to_string(),
+        }
+    }
+
+    fn consecutive(role: Role) -> MessageLintKind {
+        MessageLintKind::ConsecutiveRole { role }
+    }
+
+    struct Case {
+        name: &'static str,
+        messages: Vec<ModelMessage>,
+        expected: Vec<(usize, MessageLintKind)>,
+    }
+
+    fn case(
+        name: &'static str,
+        messages: Vec<ModelMessage>,
+        expected: Vec<(usize, MessageLintKind)>,
+    ) -> Case {
+        Case {
+            name,
+            messages,
+            expected,
+        }
+    }
+
+    fn check(provider: &str, cases: Vec<Case>) {
+        for case in cases {
+            let lints = lint_messages(&case.messages, provider)
+                .into_iter()
+                .map(|lint| (lint.index, lint.kind))
+                .collect::<Vec<_>>();
+            assert_eq!(lints, case.expected, "{provider}: {}", case.name);
+
+            let sanitized = sanitize_messages_for_provider(&case.messages, provider);
+            let left = lint_messages(&sanitized, provider)
+                .into_iter()
+                .filter(|lint| lint.kind.is_repaired())
+                .collect::<Vec<_>>();
+            assert!(
+                left.is_empty(),
+                "{provider}: {} still has repairable lints after sanitize: {left:?}",
+                case.name
+            );
+
+            let shared = Arc::new(case.messages.clone());
+            let kept = Arc::ptr_eq(
+                &sanitize_shared_messages_for_provider(Arc::clone(&shared), provider),
+                &shared,
+            );
+            assert_eq!(
+                kept,
+                sanitized == case.messages,
+                "{provider}: {} shared history kept: {kept}",
+                case.name
+            );
+        }
+    }
+
+    #[test]
+    fn anthropic_rules() {
+        check(
+            "anthropic",
+            vec![
+                case(
+                    "valid tool round trip",
+                    vec![
+                        system("sys"),
+                        user("hi"),
+                        calls(&["a", "b"]),
+                        result("a"),
+                        result("b"),
+                        assistant("done"),
+                        user("thanks"),
+                    ],
+                    vec![],
+                ),
+                case(
+                    "user after tool results",
+                    vec![user("hi"), calls(&["a"]), result("a"), user("more")],
+                    vec![],
+                ),
+                case(
+                    "consecutive users",
+                    vec![user("one"), system("note"), user("two")],
+                    vec![(2, consecutive(Role::User))],
+                ),
+                case(
+                    "consecutive assistants",
+                    vec![user("hi"), assistant("a"), assistant("b"), user("ok")],
+                    vec![(2, consecutive(Role::Assistant))],
+                ),
+                case(
+                    "trailing assistant",
+                    vec![user("hi"), assistant("hello"), system("late")],
+                    vec![(1, MessageLintKind::TrailingAssistant)],
+                ),
+                case(
+                    "dangling tool call",
+                    vec![user("hi"), calls(&["a"]), user("cancel")],
+                    vec![(1, missing("a"))],
+                ),
+                case(
+                    "late system is allowed",
+                    vec![user("hi"), system("late"), assistant("ok"), user("go")],
+                    vec![],
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn openai_rules() {
+        check(
+            "openai",
+            vec![
+                case(
+                    "valid tool round trip",
+                    vec![user("hi"), calls(&["a"]), result("a"), assistant("done")],
+                    vec![],
+                ),
+                case(
+                    "consecutive users and trailing assistant are allowed",
+                    vec![user("one"), user("two"), assistant("ok")],
+                    vec![],
+                ),
+                case(
+                    "result after an intervening user message",
+                    vec![calls(&["a"]), user("wait"), result("a")],
+                    vec![(0, missing("a")), (2, orphan("a"))],
+                ),
+                case(
+                    "result for an unknown call",
+                    vec![user("hi"), calls(&["a"]), result("a"), result("zz")],
+                    vec![(3, orphan("zz"))],
+                ),
+                case(
+                    "duplicate result",
+                    vec![calls(&["a"]), result("a"), result("a")],
+                    vec![(2, orphan("a"))],
+                ),
+                case(
+                    "repeated call id answered once",
+                    vec![calls(&["a", "a"]), result("a"), user("next")],
+                    vec![(0, missing("a"))],
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn google_rules() {
+        check(
+            "google",
+            vec![
+                case(
+                    "leading system messages",
+                    vec![system("one"), system("two"), user("hi"), assistant("ok")],
+                    vec![],
+                ),
+                case(
+                    "system after contents",
+                    vec![user("hi"), system("late"), assistant("ok")],
+                    vec![(1, MessageLintKind::SystemAfterContents)],
+                ),
+                case(
+                    "dangling tool call",
+                    vec![user("hi"), calls(&["a"])],
+                    vec![(1, missing("a"))],
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown_providers_have_no_structural_rules() {
+        check(
+            "stub",
+            vec![case(
+                "anything goes",
+                vec![result("x"), assistant("a"), assistant("b"), system("late")],
+                vec![],
+            )],
+        );
+    }
+
+    #[test]
+    fn sanitize_merges_consecutive_turns_for_anthropic() {
+        let sanitized = sanitize_messages_for_provider(
+            &[user("one"), system("note"), user("two")],
+            "anthropic",
+        );
+
+        assert_eq!(sanitized.len(), 2);
+        assert_eq!(sanitized[0].text(), "onetwo");
+        assert_eq!(sanitized[1].role, Role::System);
+    }
+
+    #[test]
+    fn lints_serialize_with_machine_readable_kinds() {
+        let lint = MessageLint {
+            index: 3,
+            kind: consecutive(Role::User),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&lint).unwrap(),
+            serde_json::json!({"index": 3, "kind": "consecutive_role", "role": "user"})
+        );
+        assert_eq!(lint.to_string(), "message 3: consecutive user messages");
+    }
+}
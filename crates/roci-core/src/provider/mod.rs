@@ -1,11 +1,20 @@
 //! Model provider trait, registry, and shared utilities.
 
+pub mod anomalies;
 pub mod factory;
+pub mod features;
 pub mod format;
 pub mod http;
+pub mod lint;
+pub mod offline;
 pub mod registry;
+pub mod routing;
 pub mod sanitize;
 pub mod schema;
+pub mod single_flight;
+pub mod tool_call_ids;
+pub mod tool_limits;
+pub mod warm_up;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
@@ -16,13 +25,20 @@ use crate::context::overflow::{OverflowKind, OverflowRetryHint, OverflowSignal};
 use crate::error::{ErrorCode, RociError};
 use crate::models::capabilities::ModelCapabilities;
 use crate::types::{
-    message::{AgentToolCall, ContentPart},
-    FinishReason, GenerationSettings, ModelMessage, TextStreamDelta, Usage,
+    message::{AgentToolCall, ContentPart, ImageContent},
+    FinishReason, GenerationSettings, ModelMessage, ResponseMetadata, TextStreamDelta, Usage,
 };
 
-pub use factory::ProviderFactory;
+pub use factory::{check_provider_options, ProviderFactory};
+pub use features::{ModelFamily, ProviderFeatureMetadata, ProviderFeatureReport, ProviderFeatures};
+pub use lint::{lint_messages, MessageLint, MessageLintKind, MessageLintSeverity, MessageRules};
 pub use registry::ProviderRegistry;
-pub use sanitize::sanitize_messages_for_provider;
+pub use routing::{ProviderRouting, ProviderRoutingSupport};
+pub use sanitize::{sanitize_messages_for_provider, sanitize_shared_messages_for_provider};
+pub use single_flight::{SingleFlight, SingleFlightProvider};
+pub use tool_call_ids::{ResponseToolCallIds, ToolCallIdAllocator};
+pub use tool_limits::ToolLimits;
+pub use warm_up::WarmUpReport;
 
 pub const TRANSPORT_DIRECT: &str = "direct";
 pub const TRANSPORT_PROXY: &str = "proxy";
@@ -33,7 +49,9 @@ pub type ProviderPayloadCallback = Arc<dyn Fn(serde_json::Value) + Send + Sync>;
 /// A request sent to a model provider.
 #[derive(Clone)]
 pub struct ProviderRequest {
-    pub messages: Vec<ModelMessage>,
+    /// Conversation to send. Shared so retries, usage estimates, and hooks
+    /// reuse one copy; use [`Arc::make_mut`] to change it.
+    pub messages: Arc<Vec<ModelMessage>>,
     pub settings: GenerationSettings,
     pub tools: Option<Vec<ToolDefinition>>,
     pub response_format: Option<crate::types::generation::ResponseFormat>,
@@ -52,6 +70,18 @@ pub struct ProviderRequest {
     /// Supported values are `"direct"` and `"proxy"`.
     /// Unsupported values are rejected by the runner before provider execution.
     pub transport: Option<String>,
+    /// Run-wide tool-call ID allocator; parsers fall back to a detached one.
+    pub tool_call_ids: Option<ToolCallIdAllocator>,
+}
+
+impl ProviderRequest {
+    /// Start assigning tool-call IDs for one response to this request.
+    pub fn begin_tool_call_ids(&self) -> ResponseToolCallIds {
+        self.tool_call_ids
+            .clone()
+            .unwrap_or_else(ToolCallIdAllocator::detached)
+            .begin_response()
+    }
 }
 
 impl std::fmt::Debug for ProviderRequest {
@@ -76,6 +106,7 @@ impl std::fmt::Debug for ProviderRequest {
                 &self.session_id.as_ref().map(|_| "<redacted>"),
             )
             .field("transport", &self.transport)
+            .field("tool_call_ids", &self.tool_call_ids)
             .finish()
     }
 }
@@ -98,6 +129,48 @@ pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: serde_json::Value,
+    /// Whether `parameters` fits the strict-mode subset, letting providers
+    /// that support it constrain the arguments the model emits. Arguments are
+    /// still validated locally.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ToolDefinition {
+    /// Build a definition, enabling `strict` when [`schema::strict_schema`]
+    /// accepts `parameters`.
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        let name = name.into();
+        let strict = match schema::strict_schema(&parameters) {
+            Ok(_) => true,
+            Err(reason) => {
+                tracing::debug!(
+                    tool = %name,
+                    %reason,
+                    "tool parameters are outside the strict-mode subset; sending non-strict"
+                );
+                false
+            }
+        };
+        Self {
+            name,
+            description: description.into(),
+            parameters,
+            strict,
+        }
+    }
+
+    /// `parameters` rewritten for strict mode, or `None` when not strict.
+    pub fn strict_parameters(&self) -> Option<serde_json::Value> {
+        if !self.strict {
+            return None;
+        }
+        schema::strict_schema(&self.parameters).ok()
+    }
 }
 
 /// Response from a provider.
@@ -109,6 +182,17 @@ pub struct ProviderResponse {
     pub finish_reason: Option<FinishReason>,
     /// Thinking content blocks (Anthropic extended thinking).
     pub thinking: Vec<ContentPart>,
+    /// Provider-specific response fields without a typed home, such as Grok
+    /// live search `citations`.
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Refusal text when the model declined the request (OpenAI structured
+    /// outputs `refusal`), paired with [`FinishReason::Refusal`](crate::types::FinishReason::Refusal).
+    pub refusal: Option<String>,
+    /// Images generated by the model, base64-encoded as in
+    /// [`ContentPart::Image`].
+    pub images: Vec<ImageContent>,
+    /// Request id and rate-limit headers of the response.
+    pub response_metadata: Option<ResponseMetadata>,
 }
 
 /// Core trait implemented by all model providers.
@@ -132,6 +216,42 @@ pub trait ModelProvider: Send + Sync {
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError>;
 
+    /// Whether the provider can continue a trailing assistant message
+    /// (`GenerationSettings::assistant_prefix`).
+    fn supports_assistant_prefix(&self) -> bool {
+        false
+    }
+
+    /// Whether a request may carry both tools and a `response_format`, so
+    /// the model can call tools and still answer in that format.
+    fn supports_response_format_with_tools(&self) -> bool {
+        false
+    }
+
+    /// Tool count and size limits of the provider's API. Defaults to none.
+    fn tool_limits(&self) -> ToolLimits {
+        ToolLimits::default()
+    }
+
+    /// Whether [`warm_up`](Self::warm_up) can load the model ahead of a request.
+    fn supports_warm_up(&self) -> bool {
+        false
+    }
+
+    /// Load the model into memory so the next request does not pay for a cold
+    /// start, reporting what the server did.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::UnsupportedOperation`] unless
+    /// [`supports_warm_up`](Self::supports_warm_up) is `true`.
+    async fn warm_up(&self) -> Result<WarmUpReport, RociError> {
+        Err(RociError::UnsupportedOperation(format!(
+            "{} does not support model warm-up",
+            self.provider_name()
+        )))
+    }
+
     /// Classify an error as an overflow signal, if applicable.
     ///
     /// The default implementation inspects structured API error details only:
@@ -143,16 +263,35 @@ pub trait ModelProvider: Send + Sync {
     }
 }
 
+/// Reject `assistant_prefix` for providers that cannot honor it.
+pub fn validate_assistant_prefix(
+    provider: &dyn ModelProvider,
+    settings: &GenerationSettings,
+) -> Result<(), RociError> {
+    if settings.assistant_prefix == Some(true) && !provider.supports_assistant_prefix() {
+        return Err(RociError::InvalidArgument(format!(
+            "assistant_prefix is not supported by provider '{}'",
+            provider.provider_name()
+        )));
+    }
+    Ok(())
+}
+
 /// Resolve an API key from config for the given provider, returning an
 /// authentication error with the specified message on failure.
+///
+/// Providers that need no credential resolve to the configured key, or to
+/// an empty string meaning "send no credential".
 pub fn require_api_key(
     config: &crate::config::RociConfig,
     provider: crate::models::ProviderKey,
     missing_message: &'static str,
 ) -> Result<String, RociError> {
-    config
-        .get_api_key_for(provider)
-        .ok_or_else(|| RociError::Authentication(missing_message.to_string()))
+    match config.get_api_key_for(provider) {
+        Some(api_key) => Ok(api_key),
+        None if !config.requires_credentials(provider.as_str()) => Ok(String::new()),
+        None => Err(RociError::Authentication(missing_message.to_string())),
+    }
 }
 
 /// Default typed overflow classification for
@@ -193,7 +332,7 @@ mod tests {
     #[test]
     fn provider_request_debug_redacts_sensitive_fields() {
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -203,6 +342,7 @@ mod tests {
             payload_callback: None,
             session_id: Some("session-secret".to_string()),
             transport: None,
+            tool_call_ids: None,
         };
 
         let debug = format!("{request:?}");
@@ -212,4 +352,27 @@ mod tests {
         assert!(!debug.contains("sk-secret"));
         assert!(!debug.contains("session-secret"));
     }
+
+    #[test]
+    fn require_api_key_succeeds_without_key_for_local_providers() {
+        use crate::config::RociConfig;
+        use crate::models::ProviderKey;
+
+        let config = RociConfig::new().with_token_store(None);
+
+        assert_eq!(
+            require_api_key(&config, ProviderKey::Ollama, "Missing key").unwrap(),
+            ""
+        );
+        assert!(matches!(
+            require_api_key(&config, ProviderKey::Groq, "Missing key"),
+            Err(RociError::Authentication(_))
+        ));
+
+        config.set_api_key("ollama", "proxy-token".to_string());
+        assert_eq!(
+            require_api_key(&config, ProviderKey::Ollama, "Missing key").unwrap(),
+            "proxy-token"
+        );
+    }
 }
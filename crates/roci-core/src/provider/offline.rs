@@ -0,0 +1,112 @@
+//! Offline mode: keep loopback providers usable and fail fast for remote ones.
+
+use std::net::IpAddr;
+
+use crate::config::RociConfig;
+use crate::error::RociError;
+
+/// Whether `url` points at this machine (`localhost`, `127.0.0.0/8`, `::1`).
+///
+/// Unparseable URLs and URLs without a host are treated as remote.
+#[must_use]
+pub fn is_loopback_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return match ip {
+            IpAddr::V4(ip) => ip.is_loopback(),
+            IpAddr::V6(ip) => {
+                ip.is_loopback() || ip.to_ipv4_mapped().is_some_and(|ip| ip.is_loopback())
+            }
+        };
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host == "localhost" || host.ends_with(".localhost")
+}
+
+/// Reject a provider whose resolved base URL needs the network while
+/// `config` is in offline mode.
+///
+/// # Errors
+///
+/// Returns [`RociError::Configuration`] when offline mode is on and
+/// `base_url` is not a loopback address.
+pub fn ensure_reachable_offline(
+    config: &RociConfig,
+    provider_key: &str,
+    base_url: &str,
+) -> Result<(), RociError> {
+    if config.is_offline() && !is_loopback_url(base_url) {
+        return Err(offline_error(provider_key));
+    }
+    Ok(())
+}
+
+/// Error returned for a provider that needs the network in offline mode.
+#[must_use]
+pub fn offline_error(provider_key: &str) -> RociError {
+    RociError::Configuration(format!(
+        "offline mode: provider '{provider_key}' requires network access"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localhost_hostnames_are_loopback() {
+        assert!(is_loopback_url("http://localhost:11434"));
+        assert!(is_loopback_url("http://LOCALHOST:1234/v1"));
+        assert!(is_loopback_url("http://localhost./v1"));
+        assert!(is_loopback_url("http://models.localhost:8080/v1"));
+    }
+
+    #[test]
+    fn ipv4_loopback_range_is_loopback() {
+        assert!(is_loopback_url("http://127.0.0.1:1234/v1"));
+        assert!(is_loopback_url("http://127.8.9.10/v1"));
+    }
+
+    #[test]
+    fn ipv6_loopback_is_loopback() {
+        assert!(is_loopback_url("http://[::1]:11434"));
+        assert!(is_loopback_url("http://[0:0:0:0:0:0:0:1]/v1"));
+        assert!(is_loopback_url("http://[::ffff:127.0.0.1]:8000/v1"));
+    }
+
+    #[test]
+    fn remote_and_malformed_urls_are_not_loopback() {
+        assert!(!is_loopback_url("https://api.openai.com/v1"));
+        assert!(!is_loopback_url("http://192.168.1.100:1234"));
+        assert!(!is_loopback_url("http://[2001:db8::1]/v1"));
+        assert!(!is_loopback_url("http://localhost.example.com/v1"));
+        assert!(!is_loopback_url("http://0.0.0.0:8080"));
+        assert!(!is_loopback_url("not a url"));
+        assert!(!is_loopback_url(""));
+    }
+
+    #[test]
+    fn offline_mode_rejects_remote_base_urls_only() {
+        let config = RociConfig::new().with_token_store(None);
+        assert!(
+            ensure_reachable_offline(&config, "anthropic", "https://api.anthropic.com").is_ok()
+        );
+
+        config.set_offline(true);
+
+        let err = ensure_reachable_offline(&config, "anthropic", "https://api.anthropic.com/v1")
+            .expect_err("remote provider should be rejected offline");
+        assert!(matches!(
+            err,
+            RociError::Configuration(message)
+                if message == "offline mode: provider 'anthropic' requires network access"
+        ));
+        assert!(ensure_reachable_offline(&config, "openai", "http://127.0.0.1:8080/v1").is_ok());
+    }
+}
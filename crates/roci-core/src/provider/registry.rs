@@ -3,10 +3,11 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use super::{ModelProvider, ProviderFactory};
+use super::offline::{ensure_reachable_offline, is_loopback_url, offline_error};
+use super::{ModelProvider, ProviderFactory, ProviderFeatureReport};
 use crate::config::RociConfig;
 use crate::error::RociError;
-use crate::models::{ModelCatalog, ModelListOptions};
+use crate::models::{LanguageModel, ModelCatalog, ModelListOptions};
 
 /// Registry mapping provider keys to their factories.
 ///
@@ -36,14 +37,61 @@ impl ProviderRegistry {
         model_id: &str,
         config: &RociConfig,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
-        self.factories
-            .get(provider_key)
-            .ok_or_else(|| {
-                RociError::ModelNotFound(format!(
-                    "No provider factory registered for '{provider_key}'"
-                ))
-            })?
-            .create(config, provider_key, model_id)
+        let factory = self.factory_for(provider_key, model_id, None, config)?;
+        factory.create(config, provider_key, model_id)
+    }
+
+    /// Create a provider for `model`, passing its provider options to the
+    /// factory, which rejects options it does not recognize.
+    pub fn create_provider_for(
+        &self,
+        model: &LanguageModel,
+        config: &RociConfig,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        let (provider_key, model_id) = (model.provider_name(), model.model_id());
+        let options = model.provider_options();
+        if options.is_empty() {
+            return self.create_provider(provider_key, model_id, config);
+        }
+        let base_url = model.provider_option("base_url");
+        let factory = self.factory_for(provider_key, model_id, base_url, config)?;
+        factory.create_with_options(config, provider_key, model_id, options)
+    }
+
+    /// The factory for `provider_key`, after the offline check of the base
+    /// URL requests would go to (`base_url` when a model option sets it).
+    fn factory_for(
+        &self,
+        provider_key: &str,
+        model_id: &str,
+        base_url: Option<&str>,
+        config: &RociConfig,
+    ) -> Result<&Arc<dyn ProviderFactory>, RociError> {
+        let factory = self.factories.get(provider_key).ok_or_else(|| {
+            RociError::ModelNotFound(format!(
+                "No provider factory registered for '{provider_key}'"
+            ))
+        })?;
+        if config.is_offline() {
+            let base_url = base_url
+                .map(str::to_string)
+                .or_else(|| factory.resolved_base_url(config, provider_key, model_id));
+            if let Some(base_url) = base_url {
+                ensure_reachable_offline(config, provider_key, &base_url)?;
+            }
+        }
+        Ok(factory)
+    }
+
+    /// Whether offline mode makes `provider_key` unavailable because its
+    /// resolved base URL is not a loopback address.
+    pub fn is_blocked_offline(&self, provider_key: &str, config: &RociConfig) -> bool {
+        config.is_offline()
+            && self
+                .factories
+                .get(provider_key)
+                .and_then(|factory| factory.resolved_base_url(config, provider_key, ""))
+                .is_some_and(|base_url| !is_loopback_url(&base_url))
     }
 
     /// Check whether a factory is registered for the given key.
@@ -70,6 +118,9 @@ impl ProviderRegistry {
                     "No provider factory registered for '{provider_key}'"
                 ))
             })?;
+            if self.is_blocked_offline(provider_key, config) {
+                return Err(offline_error(provider_key));
+            }
 
             return factory.list_models(config, provider_key, options).await;
         }
@@ -82,6 +133,10 @@ impl ProviderRegistry {
                 .get(provider_key)
                 .expect("provider key came from registry");
 
+            if self.is_blocked_offline(provider_key, config) {
+                continue;
+            }
+
             // Skip known unavailable remotes before calling `list_models`; explicit
             // provider requests still surface the provider's MissingCredential error.
             if !options.include_unavailable
@@ -103,6 +158,28 @@ impl ProviderRegistry {
         Ok(catalog)
     }
 
+    /// Report credentials, model families, and provider-level features for
+    /// every registered provider key, sorted by key.
+    ///
+    /// Built from factory metadata only: no provider is constructed and no
+    /// network call is made.
+    pub fn feature_matrix(&self, config: &RociConfig) -> Vec<ProviderFeatureReport> {
+        self.provider_keys()
+            .into_iter()
+            .map(|provider_key| {
+                let factory = &self.factories[provider_key];
+                let metadata = factory.feature_metadata(provider_key);
+                ProviderFeatureReport {
+                    provider_key: provider_key.to_string(),
+                    requires_credentials: factory.requires_credentials(provider_key),
+                    has_credentials: has_credentials(config, provider_key),
+                    model_families: metadata.model_families,
+                    features: metadata.features,
+                }
+            })
+            .collect()
+    }
+
     /// List all registered provider keys.
     pub fn provider_keys(&self) -> Vec<&str> {
         let mut keys = self
@@ -116,7 +193,7 @@ impl ProviderRegistry {
 }
 
 fn has_credentials(config: &RociConfig, provider_key: &str) -> bool {
-    config.get_api_key(provider_key).is_some()
+    config.has_credentials(provider_key)
 }
 
 impl Default for ProviderRegistry {
@@ -132,7 +209,10 @@ mod tests {
     use crate::error::RociError;
     use crate::models::capabilities::{ModelCapabilities, ModelInputCapabilities};
     use crate::models::{ModelCatalogSource, ModelInfo, ModelListOptions, ModelPolicy};
-    use crate::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+    use crate::provider::{
+        ModelFamily, ModelProvider, ProviderFeatureMetadata, ProviderFeatures, ProviderRequest,
+        ProviderResponse,
+    };
     use crate::types::{TextStreamDelta, Usage};
     use async_trait::async_trait;
     use futures::future::BoxFuture;
@@ -197,6 +277,7 @@ mod tests {
                 default_for_provider: false,
             },
             source: ModelCatalogSource::Static,
+            pricing: None,
             metadata: Default::default(),
         }
     }
@@ -227,6 +308,10 @@ mod tests {
                 tool_calls: vec![],
                 finish_reason: None,
                 thinking: vec![],
+                metadata: Default::default(),
+                refusal: None,
+                images: Vec::new(),
+                response_metadata: None,
             })
         }
         async fn stream_text(
@@ -256,6 +341,26 @@ mod tests {
         assert_eq!(provider.provider_name(), "stub");
     }
 
+    #[test]
+    fn factories_reject_model_options_they_do_not_know() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(StubFactory));
+        let config = RociConfig::new().with_token_store(None);
+
+        let plain: LanguageModel = "stub:my-model".parse().unwrap();
+        assert!(registry.create_provider_for(&plain, &config).is_ok());
+        let with_option: LanguageModel = "stub:my-model?depth=3".parse().unwrap();
+        let Err(RociError::Configuration(message)) =
+            registry.create_provider_for(&with_option, &config)
+        else {
+            panic!("expected a configuration error");
+        };
+        assert_eq!(
+            message,
+            "provider 'stub' does not support model option 'depth' (supported: none)"
+        );
+    }
+
     #[test]
     fn create_unregistered_fails() {
         let registry = ProviderRegistry::new();
@@ -536,6 +641,10 @@ mod tests {
                 tool_calls: vec![],
                 finish_reason: None,
                 thinking: vec![],
+                metadata: Default::default(),
+                refusal: None,
+                images: Vec::new(),
+                response_metadata: None,
             })
         }
         async fn stream_text(
@@ -612,4 +721,216 @@ mod tests {
             unreachable!("catalog tests must not create providers")
         }
     }
+
+    struct UrlFactory {
+        key: &'static [&'static str],
+        base_url: &'static str,
+    }
+
+    impl ProviderFactory for UrlFactory {
+        fn provider_keys(&self) -> &[&str] {
+            self.key
+        }
+
+        fn requires_credentials(&self, _provider_key: &str) -> bool {
+            false
+        }
+
+        fn resolved_base_url(
+            &self,
+            config: &RociConfig,
+            provider_key: &str,
+            _model_id: &str,
+        ) -> Option<String> {
+            Some(
+                config
+                    .get_base_url(provider_key)
+                    .unwrap_or_else(|| self.base_url.to_string()),
+            )
+        }
+
+        fn list_models<'a>(
+            &'a self,
+            _config: &'a RociConfig,
+            provider_key: &'a str,
+            _options: &'a ModelListOptions,
+        ) -> BoxFuture<'a, Result<ModelCatalog, RociError>> {
+            Box::pin(async move {
+                Ok(ModelCatalog::from_models([catalog_model(
+                    provider_key,
+                    "url-model",
+                    false,
+                )]))
+            })
+        }
+
+        fn create(
+            &self,
+            config: &RociConfig,
+            provider_key: &str,
+            model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            StubFactory.create(config, provider_key, model_id)
+        }
+    }
+
+    fn offline_registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(UrlFactory {
+            key: &["cloud"],
+            base_url: "https://api.example.com/v1",
+        }));
+        registry.register(Arc::new(UrlFactory {
+            key: &["local"],
+            base_url: "http://localhost:11434",
+        }));
+        registry
+    }
+
+    #[test]
+    fn offline_mode_blocks_remote_providers_before_create() {
+        let registry = offline_registry();
+        let config = RociConfig::new().with_token_store(None);
+        config.set_offline(true);
+
+        let err = registry
+            .create_provider("cloud", "model", &config)
+            .err()
+            .expect("remote provider should fail offline");
+        assert!(matches!(
+            err,
+            RociError::Configuration(message)
+                if message == "offline mode: provider 'cloud' requires network access"
+        ));
+        assert!(registry.create_provider("local", "model", &config).is_ok());
+        assert!(registry.is_blocked_offline("cloud", &config));
+        assert!(!registry.is_blocked_offline("local", &config));
+    }
+
+    #[test]
+    fn offline_mode_uses_resolved_base_url_not_provider_name() {
+        let registry = offline_registry();
+        let config = RociConfig::new().with_token_store(None);
+        config.set_offline(true);
+        config.set_base_url("cloud", "http://127.0.0.1:8080/v1".to_string());
+
+        assert!(registry.create_provider("cloud", "model", &config).is_ok());
+        assert!(!registry.is_blocked_offline("cloud", &config));
+    }
+
+    #[tokio::test]
+    async fn offline_mode_skips_remote_providers_when_listing_models() {
+        let registry = offline_registry();
+        let config = RociConfig::new().with_token_store(None);
+        config.set_offline(true);
+
+        let catalog = registry
+            .list_models(&config, &ModelListOptions::default())
+            .await
+            .unwrap();
+        let providers = catalog
+            .models()
+            .iter()
+            .map(|model| model.provider_key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(providers, vec!["local"]);
+
+        let err = registry
+            .list_models(
+                &config,
+                &ModelListOptions {
+                    provider_key: Some("cloud".to_string()),
+                    ..ModelListOptions::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RociError::Configuration(_)));
+    }
+
+    struct FeatureFactory;
+
+    impl ProviderFactory for FeatureFactory {
+        fn provider_keys(&self) -> &[&str] {
+            &["featured"]
+        }
+
+        fn feature_metadata(&self, _provider_key: &str) -> ProviderFeatureMetadata {
+            ProviderFeatureMetadata {
+                model_families: vec![ModelFamily {
+                    name: "featured-vision".to_string(),
+                    capabilities: ModelCapabilities {
+                        supports_vision: true,
+                        supports_tools: true,
+                        supports_streaming: true,
+                        ..ModelCapabilities::default()
+                    },
+                }],
+                features: ProviderFeatures {
+                    parallel_tool_calls: true,
+                    audio: true,
+                    ..ProviderFeatures::default()
+                },
+            }
+        }
+
+        fn create(
+            &self,
+            _config: &RociConfig,
+            _provider_key: &str,
+            _model_id: &str,
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            panic!("feature_matrix must not create providers")
+        }
+    }
+
+    #[test]
+    fn feature_matrix_reports_factory_metadata_without_creating_providers() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(FeatureFactory));
+        registry.register(Arc::new(LocalFactory));
+        let config = RociConfig::new().with_token_store(None);
+
+        let matrix = registry.feature_matrix(&config);
+
+        let keys = matrix
+            .iter()
+            .map(|report| report.provider_key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["featured", "local"]);
+        let featured = &matrix[0];
+        assert!(featured.requires_credentials);
+        assert_eq!(featured.model_families.len(), 1);
+        assert_eq!(featured.model_families[0].name, "featured-vision");
+        assert!(featured.model_families[0].capabilities.supports_vision);
+        assert_eq!(
+            featured.features,
+            ProviderFeatures {
+                parallel_tool_calls: true,
+                audio: true,
+                ..ProviderFeatures::default()
+            }
+        );
+        let local = &matrix[1];
+        assert!(!local.requires_credentials);
+        assert!(local.is_available());
+        assert!(local.model_families.is_empty());
+        assert_eq!(local.features, ProviderFeatures::default());
+    }
+
+    #[test]
+    fn feature_matrix_tracks_credential_presence() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(FeatureFactory));
+        let config = RociConfig::new().with_token_store(None);
+
+        let before = registry.feature_matrix(&config);
+        assert!(!before[0].has_credentials);
+        assert!(!before[0].is_available());
+
+        config.set_api_key("featured", "secret".to_string());
+        let after = registry.feature_matrix(&config);
+        assert!(after[0].has_credentials);
+        assert!(after[0].is_available());
+    }
 }
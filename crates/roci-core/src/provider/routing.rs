@@ -0,0 +1,311 @@
+//! Opt-in mapping from run metadata to provider routing fields.
+//!
+//! Gateways such as LiteLLM or Helicone route requests and attribute cost from
+//! request headers and the OpenAI `user`/`metadata` body fields. Run metadata
+//! only reaches the provider through these namespaced keys:
+//!
+//! - `provider.header.<Name>` becomes the `<Name>` request header.
+//! - `provider.body.user` becomes the `user` body field (OpenAI-family providers).
+//! - `provider.metadata.<key>` is merged into the Responses API `metadata` object.
+//!
+//! Every other key (for example `runner.*` limits) stays local to the run.
+
+use std::collections::HashMap;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use super::ProviderRequest;
+
+/// Run metadata prefix for request headers.
+pub const PROVIDER_HEADER_PREFIX: &str = "provider.header.";
+/// Run metadata key for the OpenAI `user` body field.
+pub const PROVIDER_BODY_USER_KEY: &str = "provider.body.user";
+/// Run metadata prefix for Responses API metadata entries.
+pub const PROVIDER_METADATA_PREFIX: &str = "provider.metadata.";
+
+/// OpenAI's documented limits for Responses metadata entries.
+const MAX_METADATA_KEY_CHARS: usize = 64;
+const MAX_METADATA_VALUE_CHARS: usize = 512;
+
+/// Routing fields a provider adapter can carry on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderRoutingSupport {
+    /// Extra request headers.
+    pub headers: bool,
+    /// The OpenAI `user` body field.
+    pub body_user: bool,
+    /// The Responses API `metadata` object.
+    pub metadata_object: bool,
+}
+
+impl ProviderRoutingSupport {
+    /// Resolve routing support from a provider name.
+    pub fn for_provider(provider: &str) -> Self {
+        Self {
+            headers: true,
+            body_user: supports_body_user(provider),
+            metadata_object: supports_metadata_object(provider),
+        }
+    }
+}
+
+fn supports_body_user(provider: &str) -> bool {
+    matches!(
+        provider,
+        "openai"
+            | "openai-compatible"
+            | "azure"
+            | "github-copilot"
+            | "grok"
+            | "groq"
+            | "lmstudio"
+            | "mistral"
+            | "ollama"
+            | "openrouter"
+            | "together"
+    )
+}
+
+fn supports_metadata_object(provider: &str) -> bool {
+    matches!(provider, "openai")
+}
+
+/// Headers that carry credentials or framing and must not be overridden
+/// from run metadata.
+fn is_protected_header(name: &HeaderName) -> bool {
+    matches!(
+        name.as_str(),
+        "authorization" | "x-api-key" | "api-key" | "host" | "content-length" | "content-type"
+    )
+}
+
+/// Provider routing fields extracted from namespaced run metadata.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRouting {
+    /// Sanitized request headers from `provider.header.*`.
+    pub headers: HeaderMap,
+    /// End-user identifier from `provider.body.user`.
+    pub user: Option<String>,
+    /// Responses metadata entries from `provider.metadata.*`.
+    pub metadata: HashMap<String, String>,
+}
+
+impl ProviderRouting {
+    /// Extract routing fields from run metadata.
+    ///
+    /// Entries that cannot be sent safely (invalid or protected header names,
+    /// values that are empty after sanitization, oversized metadata keys) are
+    /// dropped with a warning instead of failing the run.
+    pub fn from_run_metadata(metadata: &HashMap<String, String>) -> Self {
+        let mut routing = Self::default();
+        for (key, value) in metadata {
+            if let Some(name) = key.strip_prefix(PROVIDER_HEADER_PREFIX) {
+                routing.insert_header(name, value);
+            } else if key == PROVIDER_BODY_USER_KEY {
+                routing.user = sanitize_text(value, MAX_METADATA_VALUE_CHARS);
+            } else if let Some(name) = key.strip_prefix(PROVIDER_METADATA_PREFIX) {
+                routing.insert_metadata(name, value);
+            }
+        }
+        routing
+    }
+
+    /// Whether no routing fields were requested.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.user.is_none() && self.metadata.is_empty()
+    }
+
+    /// Apply routing fields the provider supports to an outgoing request.
+    ///
+    /// Explicit request values win: `provider_headers`, `settings.user`, and
+    /// `provider_metadata` entries are never overwritten.
+    pub fn apply(self, request: &mut ProviderRequest, support: ProviderRoutingSupport) {
+        if support.headers {
+            for (name, value) in &self.headers {
+                if !request.headers.contains_key(name) {
+                    request.headers.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        if support.body_user && request.settings.user.is_none() {
+            request.settings.user = self.user;
+        }
+        if support.metadata_object {
+            for (key, value) in self.metadata {
+                request.metadata.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    fn insert_header(&mut self, raw_name: &str, raw_value: &str) {
+        let Ok(name) = HeaderName::from_bytes(raw_name.trim().as_bytes()) else {
+            tracing::warn!(header = raw_name, "dropping invalid provider header name");
+            return;
+        };
+        if is_protected_header(&name) {
+            tracing::warn!(header = %name, "dropping protected provider header from run metadata");
+            return;
+        }
+        let Some(value) = sanitize_header_value(raw_value) else {
+            tracing::warn!(header = %name, "dropping empty or invalid provider header value");
+            return;
+        };
+        self.headers.insert(name, value);
+    }
+
+    fn insert_metadata(&mut self, raw_key: &str, raw_value: &str) {
+        let key = raw_key.trim();
+        if key.is_empty() || key.chars().count() > MAX_METADATA_KEY_CHARS {
+            tracing::warn!(key = raw_key, "dropping invalid provider metadata key");
+            return;
+        }
+        if let Some(value) = sanitize_text(raw_value, MAX_METADATA_VALUE_CHARS) {
+            self.metadata.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Keep printable ASCII (and tabs) so the value is a valid header everywhere.
+fn sanitize_header_value(raw: &str) -> Option<HeaderValue> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|ch| ch.is_ascii() && (!ch.is_ascii_control() || *ch == '\t'))
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(cleaned).ok()
+}
+
+fn sanitize_text(raw: &str, max_chars: usize) -> Option<String> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|ch| !ch.is_control())
+        .take(max_chars)
+        .collect();
+    let cleaned = cleaned.trim();
+    (!cleaned.is_empty()).then(|| cleaned.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GenerationSettings, ModelMessage};
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            messages: vec![ModelMessage::user("hello")].into(),
+            settings: GenerationSettings::default(),
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: HeaderMap::new(),
+            metadata: HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    fn run_metadata(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn maps_namespaced_keys_for_openai() {
+        let metadata = run_metadata(&[
+            ("provider.header.Helicone-User-Id", "user-42"),
+            ("provider.header.x-litellm-tags", "team-a,\r\nfeature-b"),
+            ("provider.body.user", "user-42"),
+            ("provider.metadata.team", "platform"),
+            ("runner.max_iterations", "4"),
+        ]);
+        let mut request = request();
+
+        ProviderRouting::from_run_metadata(&metadata)
+            .apply(&mut request, ProviderRoutingSupport::for_provider("openai"));
+
+        assert_eq!(request.headers.get("helicone-user-id").unwrap(), "user-42");
+        assert_eq!(
+            request.headers.get("x-litellm-tags").unwrap(),
+            "team-a,feature-b"
+        );
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.settings.user.as_deref(), Some("user-42"));
+        assert_eq!(request.metadata, run_metadata(&[("team", "platform")]));
+    }
+
+    #[test]
+    fn body_fields_are_skipped_for_providers_without_support() {
+        let metadata = run_metadata(&[
+            ("provider.header.x-team", "platform"),
+            ("provider.body.user", "user-42"),
+            ("provider.metadata.team", "platform"),
+        ]);
+        let mut request = request();
+
+        ProviderRouting::from_run_metadata(&metadata).apply(
+            &mut request,
+            ProviderRoutingSupport::for_provider("anthropic"),
+        );
+
+        assert_eq!(request.headers.get("x-team").unwrap(), "platform");
+        assert!(request.settings.user.is_none());
+        assert!(request.metadata.is_empty());
+    }
+
+    #[test]
+    fn unnamespaced_metadata_is_never_forwarded() {
+        let metadata = run_metadata(&[("user", "user-42"), ("runner.max_iterations", "4")]);
+        let routing = ProviderRouting::from_run_metadata(&metadata);
+        assert!(routing.is_empty());
+
+        let mut request = request();
+        routing.apply(&mut request, ProviderRoutingSupport::for_provider("openai"));
+        assert!(request.headers.is_empty());
+        assert!(request.settings.user.is_none());
+        assert!(request.metadata.is_empty());
+    }
+
+    #[test]
+    fn drops_protected_and_invalid_headers() {
+        let metadata = run_metadata(&[
+            ("provider.header.Authorization", "Bearer stolen"),
+            ("provider.header.bad header", "value"),
+            ("provider.header.x-empty", "\r\n"),
+        ]);
+
+        let routing = ProviderRouting::from_run_metadata(&metadata);
+
+        assert!(routing.headers.is_empty());
+    }
+
+    #[test]
+    fn explicit_request_values_win() {
+        let metadata = run_metadata(&[
+            ("provider.header.x-team", "from-metadata"),
+            ("provider.body.user", "from-metadata"),
+            ("provider.metadata.team", "from-metadata"),
+        ]);
+        let mut request = request();
+        request
+            .headers
+            .insert("x-team", HeaderValue::from_static("explicit"));
+        request.settings.user = Some("explicit".to_string());
+        request
+            .metadata
+            .insert("team".to_string(), "explicit".to_string());
+
+        ProviderRouting::from_run_metadata(&metadata)
+            .apply(&mut request, ProviderRoutingSupport::for_provider("openai"));
+
+        assert_eq!(request.headers.get("x-team").unwrap(), "explicit");
+        assert_eq!(request.settings.user.as_deref(), Some("explicit"));
+        assert_eq!(request.metadata["team"], "explicit");
+    }
+}
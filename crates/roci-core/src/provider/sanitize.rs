@@ -1,126 +1,140 @@
 //! Provider-specific transcript sanitization.
+//!
+//! Repairs follow the same [`MessageRules`] that [`super::lint_messages`]
+//! checks, so every lint marked repaired is gone after sanitizing.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use super::lint::{tool_result_id, MessageRules};
 use crate::types::{ContentPart, ModelMessage, Role};
 
 pub fn sanitize_messages_for_provider(
     messages: &[ModelMessage],
     provider: &str,
 ) -> Vec<ModelMessage> {
-    let mut sanitized: Vec<ModelMessage> = if supports_thinking(provider) {
-        messages.to_vec()
-    } else {
-        messages.iter().filter_map(strip_thinking_blocks).collect()
-    };
-
-    if requires_tool_pairing(provider) {
-        sanitized = sanitize_tool_result_pairing(&sanitized);
+    sanitize_owned(messages.to_vec(), MessageRules::for_provider(provider))
+}
+
+/// [`sanitize_messages_for_provider`] for a shared history: returned as is
+/// when it already follows the provider's rules, and copied only to repair
+/// it.
+pub fn sanitize_shared_messages_for_provider(
+    messages: Arc<Vec<ModelMessage>>,
+    provider: &str,
+) -> Arc<Vec<ModelMessage>> {
+    let rules = MessageRules::for_provider(provider);
+    if !needs_sanitizing(&messages, rules) {
+        return messages;
+    }
+    Arc::new(sanitize_owned(Arc::unwrap_or_clone(messages), rules))
+}
+
+fn sanitize_owned(mut messages: Vec<ModelMessage>, rules: MessageRules) -> Vec<ModelMessage> {
+    if !rules.thinking {
+        messages.retain_mut(strip_thinking_blocks);
     }
 
-    sanitized
+    if rules.tool_pairing {
+        messages = sanitize_tool_result_pairing(messages);
+    }
+
+    if rules.alternating_roles {
+        messages = merge_consecutive_roles(messages);
+    }
+
+    messages
 }
 
-fn supports_thinking(provider: &str) -> bool {
-    matches!(provider, "anthropic" | "anthropic-compatible")
+/// Whether [`sanitize_owned`] would change `messages`. Each check assumes the
+/// earlier passes left the messages alone, which holds until one fails.
+fn needs_sanitizing(messages: &[ModelMessage], rules: MessageRules) -> bool {
+    (!rules.thinking
+        && messages
+            .iter()
+            .any(|message| message.content.is_empty() || message.content.iter().any(is_thinking)))
+        || (rules.tool_pairing && !tool_results_paired(messages))
+        || (rules.alternating_roles && has_consecutive_roles(messages))
 }
 
-fn requires_tool_pairing(provider: &str) -> bool {
+fn is_thinking(part: &ContentPart) -> bool {
     matches!(
-        provider,
-        "anthropic"
-            | "anthropic-compatible"
-            | "google"
-            | "openai"
-            | "openai-compatible"
-            | "github-copilot"
-            | "openrouter"
-            | "azure"
-            | "groq"
-            | "grok"
-            | "mistral"
-            | "together"
-            | "lmstudio"
-            | "ollama"
+        part,
+        ContentPart::Thinking(_) | ContentPart::RedactedThinking(_)
     )
 }
 
-fn strip_thinking_blocks(message: &ModelMessage) -> Option<ModelMessage> {
-    let mut parts = Vec::new();
-    for part in &message.content {
-        if matches!(
-            part,
-            ContentPart::Thinking(_) | ContentPart::RedactedThinking(_)
-        ) {
-            continue;
-        }
-        parts.push(part.clone());
-    }
-    if parts.is_empty() {
-        return None;
-    }
-    let mut next = message.clone();
-    next.content = parts;
-    Some(next)
+/// Drop thinking parts, keeping the message only if anything is left.
+fn strip_thinking_blocks(message: &mut ModelMessage) -> bool {
+    message.content.retain(|part| !is_thinking(part));
+    !message.content.is_empty()
 }
 
-fn sanitize_tool_result_pairing(messages: &[ModelMessage]) -> Vec<ModelMessage> {
+fn sanitize_tool_result_pairing(messages: Vec<ModelMessage>) -> Vec<ModelMessage> {
     let mut out: Vec<ModelMessage> = Vec::with_capacity(messages.len());
     let mut seen_tool_results: HashSet<String> = HashSet::new();
+    // Each slot is taken exactly once, moving the message into `out`.
+    let mut messages: Vec<Option<ModelMessage>> = messages.into_iter().map(Some).collect();
 
     let mut i = 0usize;
     while i < messages.len() {
-        let msg = &messages[i];
+        let msg = messages[i].take().expect("message visited once");
         if msg.role != Role::Assistant {
             if msg.role != Role::Tool {
-                out.push(msg.clone());
+                out.push(msg);
             }
             i += 1;
             continue;
         }
 
-        let tool_calls = msg.tool_calls();
-        if tool_calls.is_empty() {
-            out.push(msg.clone());
+        let call_ids: Vec<String> = msg.tool_calls().iter().map(|tc| tc.id.clone()).collect();
+        if call_ids.is_empty() {
+            out.push(msg);
             i += 1;
             continue;
         }
 
-        let tool_call_ids: HashSet<String> = tool_calls.iter().map(|tc| tc.id.clone()).collect();
+        let tool_call_ids: HashSet<&str> = call_ids.iter().map(String::as_str).collect();
         let mut span_results: HashMap<String, ModelMessage> = HashMap::new();
         let mut remainder: Vec<ModelMessage> = Vec::new();
 
         let mut j = i + 1;
         while j < messages.len() {
-            let next = &messages[j];
-            if matches!(next.role, Role::Assistant | Role::User | Role::System) {
+            let role = messages[j].as_ref().expect("message not visited yet").role;
+            if matches!(role, Role::Assistant | Role::User | Role::System) {
                 break;
             }
-            if next.role == Role::Tool {
-                if let Some(id) = extract_tool_result_id(next) {
-                    if tool_call_ids.contains(&id) && !seen_tool_results.contains(&id) {
-                        span_results.insert(id.clone(), next.clone());
-                        seen_tool_results.insert(id);
+            let next = messages[j].take().expect("message not visited yet");
+            if role == Role::Tool {
+                if let Some(id) = tool_result_id(&next).map(str::to_string) {
+                    if tool_call_ids.contains(id.as_str()) && seen_tool_results.insert(id.clone()) {
+                        span_results.insert(id, next);
                     }
                 }
             } else {
-                remainder.push(next.clone());
+                remainder.push(next);
             }
             j += 1;
         }
 
-        out.push(msg.clone());
-        for call in tool_calls {
-            if let Some(existing) = span_results.get(&call.id) {
-                out.push(existing.clone());
+        out.push(msg);
+        for (index, id) in call_ids.iter().enumerate() {
+            // A repeated call ID reuses the same result, so only the last
+            // occurrence may move it.
+            let result = if call_ids[index + 1..].contains(id) {
+                span_results.get(id).cloned()
             } else {
-                out.push(ModelMessage::tool_result(
-                    call.id.clone(),
+                span_results.remove(id)
+            };
+            match result {
+                Some(existing) => out.push(existing),
+                None => out.push(ModelMessage::tool_result(
+                    id.clone(),
                     serde_json::json!({
                         "error": "missing tool result in transcript; inserted synthetic error result",
                     }),
                     true,
-                ));
+                )),
             }
         }
         out.extend(remainder);
@@ -130,22 +144,80 @@ fn sanitize_tool_result_pairing(messages: &[ModelMessage]) -> Vec<ModelMessage>
     out
 }
 
-fn extract_tool_result_id(message: &ModelMessage) -> Option<String> {
-    if message.role != Role::Tool {
-        return None;
+/// Whether every tool result directly follows the assistant message whose
+/// calls it answers, one per call and in call order: the shape
+/// [`sanitize_tool_result_pairing`] produces.
+fn tool_results_paired(messages: &[ModelMessage]) -> bool {
+    let mut seen_tool_results: HashSet<&str> = HashSet::new();
+    let mut i = 0usize;
+    while i < messages.len() {
+        let msg = &messages[i];
+        i += 1;
+        match msg.role {
+            Role::Tool => return false,
+            Role::Assistant => {}
+            Role::System | Role::User => continue,
+        }
+        for call in msg.tool_calls() {
+            let answered = messages.get(i).is_some_and(|next| {
+                next.role == Role::Tool && tool_result_id(next) == Some(call.id.as_str())
+            });
+            if !answered || !seen_tool_results.insert(call.id.as_str()) {
+                return false;
+            }
+            i += 1;
+        }
+    }
+    true
+}
+
+/// Whether [`merge_consecutive_roles`] has anything to fold.
+fn has_consecutive_roles(messages: &[ModelMessage]) -> bool {
+    let mut previous: Option<Role> = None;
+    for message in messages
+        .iter()
+        .filter(|message| message.role != Role::System)
+    {
+        if previous == Some(message.role) && matches!(message.role, Role::User | Role::Assistant) {
+            return true;
+        }
+        previous = Some(message.role);
     }
-    for part in &message.content {
-        if let ContentPart::ToolResult(result) = part {
-            return Some(result.tool_call_id.clone());
+    false
+}
+
+/// Fold each user or assistant message into the previous one of the same
+/// role, skipping over system messages, which providers send separately.
+fn merge_consecutive_roles(messages: Vec<ModelMessage>) -> Vec<ModelMessage> {
+    let mut out: Vec<ModelMessage> = Vec::with_capacity(messages.len());
+    let mut last_turn: Option<usize> = None;
+    for message in messages {
+        if message.role == Role::System {
+            out.push(message);
+            continue;
+        }
+        match last_turn {
+            Some(index)
+                if out[index].role == message.role
+                    && matches!(message.role, Role::User | Role::Assistant) =>
+            {
+                out[index].content.extend(message.content);
+            }
+            _ => {
+                last_turn = Some(out.len());
+                out.push(message);
+            }
         }
     }
-    None
+    out
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sanitize_messages_for_provider;
-    use crate::types::{AgentToolCall, ContentPart, ModelMessage, Role};
+    use std::sync::Arc;
+
+    use super::{sanitize_messages_for_provider, sanitize_shared_messages_for_provider};
+    use crate::types::{AgentToolCall, ContentPart, ModelMessage, Role, ThinkingContent};
 
     fn assistant_tool_call(id: &str, name: &str) -> ModelMessage {
         ModelMessage {
@@ -209,4 +281,30 @@ mod tests {
             .unwrap_or(true);
         assert!(!is_error);
     }
+
+    #[test]
+    fn shared_history_is_copied_only_when_repaired() {
+        let clean = Arc::new(vec![
+            ModelMessage::user("hello"),
+            assistant_tool_call("call-1", "read"),
+            ModelMessage::tool_result("call-1", serde_json::json!({"ok": true}), false),
+            ModelMessage::assistant("done"),
+        ]);
+        let kept = sanitize_shared_messages_for_provider(Arc::clone(&clean), "openai");
+        assert!(Arc::ptr_eq(&kept, &clean));
+
+        let mut thinking = ModelMessage::assistant("answer");
+        thinking.content.insert(
+            0,
+            ContentPart::Thinking(ThinkingContent {
+                thinking: "hmm".to_string(),
+                signature: String::new(),
+            }),
+        );
+        let with_thinking = Arc::new(vec![ModelMessage::user("hello"), thinking]);
+        let repaired = sanitize_shared_messages_for_provider(Arc::clone(&with_thinking), "openai");
+        assert!(!Arc::ptr_eq(&repaired, &with_thinking));
+        assert_eq!(repaired[1].text(), "answer");
+        assert_eq!(repaired[1].content.len(), 1);
+    }
 }
@@ -2,15 +2,164 @@
 
 use serde_json::Value;
 
+/// Keywords outside OpenAI's strict-mode schema subset.
+const STRICT_UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "allOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "dependentRequired",
+    "dependentSchemas",
+    "patternProperties",
+    "propertyNames",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+    "contains",
+    "minProperties",
+    "maxProperties",
+];
+
 /// Normalize a JSON schema for a specific provider.
+///
+/// OpenAI schemas are rewritten with [`strict_schema`] when they fit the
+/// strict-mode subset, and otherwise only get `additionalProperties: false`.
 pub fn normalize_schema_for_provider(schema: &Value, provider_name: &str) -> Value {
     match provider_name {
-        "openai" | "openai-compatible" => ensure_additional_properties_false(schema),
+        "openai" | "openai-compatible" => {
+            strict_schema(schema).unwrap_or_else(|_| ensure_additional_properties_false(schema))
+        }
         "google" => strip_additional_properties(schema),
         _ => schema.clone(),
     }
 }
 
+/// Rewrite `schema` into OpenAI's strict-mode subset.
+///
+/// Strict mode (structured outputs and `strict` function calling) needs
+/// every object closed with `additionalProperties: false` and every property
+/// listed in `required`; an optional property must accept `null` instead. The
+/// rewrite closes objects and marks nullable optional properties required.
+///
+/// Returns why the schema cannot be expressed when the root is not an
+/// object, an object is open or free-form, an optional property has no null
+/// type, or a keyword outside the subset (such as `oneOf`) appears.
+pub fn strict_schema(schema: &Value) -> Result<Value, String> {
+    if !is_object_schema(schema) {
+        return Err("root is not an object schema".to_string());
+    }
+    strict_node(schema, "$")
+}
+
+fn strict_node(schema: &Value, path: &str) -> Result<Value, String> {
+    let Value::Object(obj) = schema else {
+        return Ok(schema.clone());
+    };
+    if let Some(keyword) = STRICT_UNSUPPORTED_KEYWORDS
+        .iter()
+        .find(|keyword| obj.contains_key(**keyword))
+    {
+        return Err(format!("`{path}` uses unsupported keyword `{keyword}`"));
+    }
+
+    let mut next = serde_json::Map::new();
+    for (key, value) in obj {
+        let value = match key.as_str() {
+            "properties" => continue,
+            "items" => match value {
+                Value::Array(items) => Value::Array(
+                    items
+                        .iter()
+                        .map(|item| strict_node(item, &format!("{path}[]")))
+                        .collect::<Result<_, _>>()?,
+                ),
+                item => strict_node(item, &format!("{path}[]"))?,
+            },
+            "anyOf" => match value {
+                Value::Array(variants) => Value::Array(
+                    variants
+                        .iter()
+                        .map(|variant| strict_node(variant, path))
+                        .collect::<Result<_, _>>()?,
+                ),
+                other => other.clone(),
+            },
+            "$defs" | "definitions" => match value {
+                Value::Object(defs) => Value::Object(
+                    defs.iter()
+                        .map(|(name, def)| {
+                            Ok((name.clone(), strict_node(def, &format!("#/{key}/{name}"))?))
+                        })
+                        .collect::<Result<_, String>>()?,
+                ),
+                other => other.clone(),
+            },
+            _ => value.clone(),
+        };
+        next.insert(key.clone(), value);
+    }
+
+    if !is_object_schema(schema) {
+        return Ok(Value::Object(next));
+    }
+    match obj.get("additionalProperties") {
+        None | Some(Value::Bool(false)) => {}
+        Some(_) => return Err(format!("`{path}` allows additional properties")),
+    }
+    let Some(properties) = obj.get("properties") else {
+        if obj.contains_key("additionalProperties") {
+            next.insert("properties".into(), Value::Object(serde_json::Map::new()));
+            next.insert("required".into(), Value::Array(Vec::new()));
+            return Ok(Value::Object(next));
+        }
+        return Err(format!("`{path}` is a free-form object"));
+    };
+    let Value::Object(properties) = properties else {
+        return Err(format!("`{path}` has malformed properties"));
+    };
+    let required: Vec<&str> = obj
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut strict_properties = serde_json::Map::new();
+    for (name, property) in properties {
+        let property_path = format!("{path}.{name}");
+        if !required.contains(&name.as_str()) && !accepts_null(property) {
+            return Err(format!("optional field `{property_path}` has no null type"));
+        }
+        strict_properties.insert(name.clone(), strict_node(property, &property_path)?);
+    }
+    next.insert(
+        "required".into(),
+        Value::Array(
+            strict_properties
+                .keys()
+                .map(|name| Value::String(name.clone()))
+                .collect(),
+        ),
+    );
+    next.insert("properties".into(), Value::Object(strict_properties));
+    next.insert("additionalProperties".into(), Value::Bool(false));
+    Ok(Value::Object(next))
+}
+
+/// Whether `schema` admits `null` through its `type` or an `anyOf` branch.
+fn accepts_null(schema: &Value) -> bool {
+    let is_null_type = |ty: &Value| match ty {
+        Value::String(ty) => ty == "null",
+        Value::Array(types) => types.iter().any(|ty| ty == "null"),
+        _ => false,
+    };
+    schema.get("type").is_some_and(is_null_type)
+        || schema
+            .get("anyOf")
+            .and_then(Value::as_array)
+            .is_some_and(|variants| variants.iter().any(accepts_null))
+}
+
 fn ensure_additional_properties_false(schema: &Value) -> Value {
     match schema {
         Value::Object(obj) => {
@@ -80,7 +229,11 @@ fn strip_additional_properties(schema: &Value) -> Value {
 
 fn is_object_schema(value: &Value) -> bool {
     if let Value::Object(obj) = value {
-        matches!(obj.get("type"), Some(Value::String(t)) if t == "object")
+        match obj.get("type") {
+            Some(Value::String(t)) => t == "object",
+            Some(Value::Array(types)) => types.iter().any(|t| t == "object"),
+            _ => false,
+        }
     } else {
         false
     }
@@ -116,4 +269,98 @@ mod tests {
         let normalized = normalize_schema_for_provider(&schema, "google");
         assert!(normalized.get("additionalProperties").is_none());
     }
+
+    #[test]
+    fn strict_schema_closes_objects_and_requires_nullable_optionals() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "limit": {"type": ["integer", "null"]},
+                "filters": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "field": {"type": "string"},
+                            "value": {"anyOf": [{"type": "string"}, {"type": "null"}]}
+                        },
+                        "required": ["field"]
+                    }
+                }
+            },
+            "required": ["path", "filters"]
+        });
+
+        let strict = strict_schema(&schema).unwrap();
+
+        assert_eq!(strict["additionalProperties"], false);
+        assert_eq!(
+            strict["required"],
+            serde_json::json!(["filters", "limit", "path"])
+        );
+        let item = &strict["properties"]["filters"]["items"];
+        assert_eq!(item["additionalProperties"], false);
+        assert_eq!(item["required"], serde_json::json!(["field", "value"]));
+    }
+
+    #[test]
+    fn strict_schema_explains_incompatible_schemas() {
+        let cases = [
+            (
+                serde_json::json!({"type": "string"}),
+                "root is not an object schema",
+            ),
+            (
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {"path": {"type": "string"}, "depth": {"type": "integer"}},
+                    "required": ["path"]
+                }),
+                "optional field `$.depth` has no null type",
+            ),
+            (
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {"value": {"oneOf": [{"type": "string"}, {"type": "integer"}]}},
+                    "required": ["value"]
+                }),
+                "`$.value` uses unsupported keyword `oneOf`",
+            ),
+            (
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {"env": {"type": "object", "additionalProperties": {"type": "string"}}},
+                    "required": ["env"]
+                }),
+                "`$.env` allows additional properties",
+            ),
+            (
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {"extra": {"type": "object"}},
+                    "required": ["extra"]
+                }),
+                "`$.extra` is a free-form object",
+            ),
+        ];
+
+        for (schema, reason) in cases {
+            assert_eq!(strict_schema(&schema).unwrap_err(), reason);
+        }
+    }
+
+    #[test]
+    fn openai_normalization_falls_back_when_schema_is_not_strict() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}, "depth": {"type": "integer"}},
+            "required": ["path"]
+        });
+
+        let normalized = normalize_schema_for_provider(&schema, "openai");
+
+        assert_eq!(normalized["additionalProperties"], false);
+        assert_eq!(normalized["required"], serde_json::json!(["path"]));
+    }
 }
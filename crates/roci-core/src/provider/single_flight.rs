@@ -0,0 +1,614 @@
+//! Single-flight coalescing of identical concurrent `generate_text` calls.
+//!
+//! Wrap providers through one [`SingleFlight`] group to share upstream
+//! requests: while a request is in flight, identical requests await it
+//! instead of sending their own, and every caller gets a clone of the
+//! response. A completed response can be reused for a short TTL (zero by
+//! default). This is a dedup window, not a response cache.
+//!
+//! Coalescing is opt-in: providers are only affected when wrapped.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use futures::stream::BoxStream;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+
+use super::{
+    ModelProvider, OverflowSignal, ProviderRequest, ProviderResponse, ToolLimits, WarmUpReport,
+};
+use crate::error::RociError;
+use crate::models::ModelCapabilities;
+use crate::types::TextStreamDelta;
+
+type Outcome = Result<ProviderResponse, Arc<RociError>>;
+type SharedCall = Shared<BoxFuture<'static, Outcome>>;
+type RequestKey = [u8; 32];
+
+enum Slot {
+    InFlight(SharedCall),
+    /// A finished call, kept for reuse until `expires_at`.
+    Done {
+        call: SharedCall,
+        expires_at: Instant,
+    },
+}
+
+/// A coalescing group shared by the providers it wraps.
+///
+/// Cloning is cheap and clones share the in-flight map, so providers
+/// created per request handler still coalesce when wrapped by clones of one
+/// group.
+#[derive(Clone, Default)]
+pub struct SingleFlight {
+    ttl: Duration,
+    slots: Arc<Mutex<HashMap<RequestKey, Slot>>>,
+}
+
+impl SingleFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reuse a completed response, success or error, for `ttl` after it
+    /// arrives. Zero (the default) only shares calls still in flight.
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Wrap `provider` so its `generate_text` calls coalesce within this group.
+    pub fn wrap(&self, provider: Arc<dyn ModelProvider>) -> Arc<dyn ModelProvider> {
+        Arc::new(SingleFlightProvider {
+            inner: provider,
+            group: self.clone(),
+        })
+    }
+
+    /// Number of keys currently in flight or inside their reuse window.
+    pub fn len(&self) -> usize {
+        let mut slots = self.lock();
+        prune_expired(&mut slots, Instant::now());
+        slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<RequestKey, Slot>> {
+        self.slots
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Join the call for `key`, starting it with `start` when none is in
+    /// flight or reusable.
+    ///
+    /// The upstream call runs on its own task, so a waiter that is dropped
+    /// (for example, because its caller was canceled) never cancels it for
+    /// the others.
+    fn join(
+        &self,
+        key: RequestKey,
+        start: impl FnOnce() -> BoxFuture<'static, Result<ProviderResponse, RociError>>,
+    ) -> SharedCall {
+        let mut slots = self.lock();
+        let now = Instant::now();
+        prune_expired(&mut slots, now);
+        if let Some(Slot::InFlight(call) | Slot::Done { call, .. }) = slots.get(&key) {
+            return call.clone();
+        }
+
+        let group = self.clone();
+        let upstream = start();
+        let task = tokio::spawn(async move {
+            let outcome = upstream.await.map_err(Arc::new);
+            group.finish(key);
+            outcome
+        });
+        let call = async move {
+            task.await.unwrap_or_else(|error| {
+                Err(Arc::new(RociError::InvalidState(format!(
+                    "coalesced provider call failed to complete: {error}"
+                ))))
+            })
+        }
+        .boxed()
+        .shared();
+        slots.insert(key, Slot::InFlight(call.clone()));
+        call
+    }
+
+    /// Retire the in-flight call for `key`, keeping it for the TTL. The
+    /// shared future has resolved by the time waiters observe `Done`.
+    fn finish(&self, key: RequestKey) {
+        let mut slots = self.lock();
+        let Some(Slot::InFlight(call)) = slots.remove(&key) else {
+            return;
+        };
+        if !self.ttl.is_zero() {
+            slots.insert(
+                key,
+                Slot::Done {
+                    call,
+                    expires_at: Instant::now() + self.ttl,
+                },
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for SingleFlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("ttl", &self.ttl)
+            .field("keys", &self.lock().len())
+            .finish()
+    }
+}
+
+fn prune_expired(slots: &mut HashMap<RequestKey, Slot>, now: Instant) {
+    slots.retain(|_, slot| match slot {
+        Slot::InFlight(_) => true,
+        Slot::Done { expires_at, .. } => *expires_at > now,
+    });
+}
+
+/// A provider whose `generate_text` calls coalesce within a [`SingleFlight`]
+/// group. Streaming calls pass straight through.
+///
+/// Requests carrying a payload callback or a run-wide tool-call ID
+/// allocator are never coalesced, since both tie the call to one caller.
+pub struct SingleFlightProvider {
+    inner: Arc<dyn ModelProvider>,
+    group: SingleFlight,
+}
+
+impl SingleFlightProvider {
+    pub fn new(inner: Arc<dyn ModelProvider>, group: SingleFlight) -> Self {
+        Self { inner, group }
+    }
+}
+
+#[async_trait]
+impl ModelProvider for SingleFlightProvider {
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
+
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn generate_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        if request.payload_callback.is_some() || request.tool_call_ids.is_some() {
+            return self.inner.generate_text(request).await;
+        }
+        let key = request_key(self.inner.as_ref(), request)?;
+        let call = self.group.join(key, || {
+            let inner = Arc::clone(&self.inner);
+            let request = request.clone();
+            async move { inner.generate_text(&request).await }.boxed()
+        });
+        call.await.map_err(|error| copy_error(&error))
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        self.inner.stream_text(request).await
+    }
+
+    fn supports_assistant_prefix(&self) -> bool {
+        self.inner.supports_assistant_prefix()
+    }
+
+    fn supports_response_format_with_tools(&self) -> bool {
+        self.inner.supports_response_format_with_tools()
+    }
+
+    fn tool_limits(&self) -> ToolLimits {
+        self.inner.tool_limits()
+    }
+
+    fn supports_warm_up(&self) -> bool {
+        self.inner.supports_warm_up()
+    }
+
+    async fn warm_up(&self) -> Result<WarmUpReport, RociError> {
+        self.inner.warm_up().await
+    }
+
+    fn classify_overflow(&self, error: &RociError) -> Option<OverflowSignal> {
+        self.inner.classify_overflow(error)
+    }
+}
+
+/// Hash of everything that can change the provider's answer: model,
+/// messages, settings, tools, response format, credentials, headers,
+/// metadata, session, and transport.
+///
+/// Message timestamps are left out: they are never sent upstream, and
+/// callers building the same prompt a moment apart should still coalesce.
+///
+/// Structured parts go through `serde_json::Value`, whose maps sort their
+/// keys, so field order in `HashMap`s does not split identical requests.
+fn request_key(
+    provider: &dyn ModelProvider,
+    request: &ProviderRequest,
+) -> Result<RequestKey, RociError> {
+    let mut hasher = Sha256::new();
+    let mut part = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    };
+    part(provider.provider_name().as_bytes());
+    part(provider.model_id().as_bytes());
+    let mut messages = serde_json::to_value(request.messages.as_ref())?;
+    if let Some(messages) = messages.as_array_mut() {
+        for message in messages {
+            if let Some(message) = message.as_object_mut() {
+                message.remove("timestamp");
+            }
+        }
+    }
+    part(&serde_json::to_vec(&messages)?);
+    part(&canonical_json(&request.settings)?);
+    part(&canonical_json(&request.tools)?);
+    part(&canonical_json(&request.response_format)?);
+    part(&canonical_json(&request.api_key_override)?);
+    let mut headers = request
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_bytes()))
+        .collect::<Vec<_>>();
+    headers.sort_unstable();
+    for (name, value) in headers {
+        part(name.as_bytes());
+        part(value);
+    }
+    part(&canonical_json(&request.metadata)?);
+    part(&canonical_json(&request.session_id)?);
+    part(&canonical_json(&request.transport)?);
+    Ok(hasher.finalize().into())
+}
+
+fn canonical_json(value: &impl serde::Serialize) -> Result<Vec<u8>, RociError> {
+    Ok(serde_json::to_vec(&serde_json::to_value(value)?)?)
+}
+
+/// A waiter's copy of the shared upstream error.
+///
+/// Variants holding foreign error types that cannot be cloned keep their
+/// message: network errors arrive as [`RociError::Io`], and an API error's
+/// `source` is dropped.
+fn copy_error(error: &RociError) -> RociError {
+    match error {
+        RociError::Configuration(message) => RociError::Configuration(message.clone()),
+        RociError::Api {
+            status,
+            message,
+            details,
+            ..
+        } => RociError::Api {
+            status: *status,
+            message: message.clone(),
+            source: None,
+            details: details.clone(),
+        },
+        RociError::Network(error) => RociError::Io(std::io::Error::other(error.to_string())),
+        RociError::Io(error) => RociError::Io(std::io::Error::new(error.kind(), error.to_string())),
+        RociError::Serialization(error) => {
+            RociError::Serialization(serde::de::Error::custom(error.to_string()))
+        }
+        RociError::ModelNotFound(model) => RociError::ModelNotFound(model.clone()),
+        RociError::UnsupportedOperation(message) => {
+            RociError::UnsupportedOperation(message.clone())
+        }
+        RociError::Authentication(message) => RociError::Authentication(message.clone()),
+        RociError::RateLimited { retry_after_ms } => RociError::RateLimited {
+            retry_after_ms: *retry_after_ms,
+        },
+        RociError::Timeout(ms) => RociError::Timeout(*ms),
+        RociError::Stream(message) => RociError::Stream(message.clone()),
+        RociError::ToolExecution { tool_name, message } => RociError::ToolExecution {
+            tool_name: tool_name.clone(),
+            message: message.clone(),
+        },
+        RociError::InvalidArgument(message) => RociError::InvalidArgument(message.clone()),
+        RociError::Provider { provider, message } => RociError::Provider {
+            provider: provider.clone(),
+            message: message.clone(),
+        },
+        RociError::InvalidState(message) => RociError::InvalidState(message.clone()),
+        RociError::SchemaViolation(violations) => RociError::SchemaViolation(violations.clone()),
+        RociError::Refused { message } => RociError::Refused {
+            message: message.clone(),
+        },
+        RociError::BackgroundInterrupted {
+            response_id,
+            partial_text,
+            message,
+        } => RociError::BackgroundInterrupted {
+            response_id: response_id.clone(),
+            partial_text: partial_text.clone(),
+            message: message.clone(),
+        },
+        RociError::MissingCredential { provider } => RociError::MissingCredential {
+            provider: provider.clone(),
+        },
+        RociError::MissingConfiguration { key, provider } => RociError::MissingConfiguration {
+            key: key.clone(),
+            provider: provider.clone(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::types::{GenerationSettings, ModelMessage, Usage};
+
+    struct CountingProvider {
+        calls: AtomicUsize,
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl CountingProvider {
+        fn new(delay: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                calls: AtomicUsize::new(0),
+                delay,
+                fail: false,
+            })
+        }
+
+        fn failing(delay: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                calls: AtomicUsize::new(0),
+                delay,
+                fail: true,
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl ModelProvider for CountingProvider {
+        fn provider_name(&self) -> &str {
+            "counting"
+        }
+
+        fn model_id(&self) -> &str {
+            "model"
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            static CAPABILITIES: std::sync::OnceLock<ModelCapabilities> =
+                std::sync::OnceLock::new();
+            CAPABILITIES.get_or_init(ModelCapabilities::default)
+        }
+
+        async fn generate_text(
+            &self,
+            request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(RociError::api(503, "upstream unavailable"));
+            }
+            Ok(ProviderResponse {
+                text: format!("call {call}: {}", request.messages[0].text()),
+                usage: Usage::default(),
+                tool_calls: Vec::new(),
+                finish_reason: None,
+                thinking: Vec::new(),
+                metadata: HashMap::new(),
+                refusal: None,
+                images: Vec::new(),
+                response_metadata: None,
+            })
+        }
+
+        async fn stream_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            panic!("stream should not be called")
+        }
+    }
+
+    fn request(prompt: &str) -> ProviderRequest {
+        ProviderRequest {
+            messages: Arc::new(vec![ModelMessage::user(prompt)]),
+            settings: GenerationSettings::default(),
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    async fn fan_out(
+        provider: &Arc<dyn ModelProvider>,
+        request: &ProviderRequest,
+        count: usize,
+    ) -> Vec<Result<ProviderResponse, RociError>> {
+        let handles = (0..count)
+            .map(|_| {
+                let provider = Arc::clone(provider);
+                let request = request.clone();
+                tokio::spawn(async move { provider.generate_text(&request).await })
+            })
+            .collect::<Vec<_>>();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.expect("waiter task"));
+        }
+        results
+    }
+
+    #[tokio::test]
+    async fn concurrent_identical_calls_share_one_upstream_request() {
+        let upstream = CountingProvider::new(Duration::from_millis(50));
+        let provider = SingleFlight::new().wrap(upstream.clone());
+
+        let results = fan_out(&provider, &request("hello"), 16).await;
+
+        assert_eq!(upstream.calls(), 1);
+        for result in results {
+            assert_eq!(result.expect("shared response").text, "call 1: hello");
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_reach_every_waiter() {
+        let upstream = CountingProvider::failing(Duration::from_millis(50));
+        let provider = SingleFlight::new().wrap(upstream.clone());
+
+        let results = fan_out(&provider, &request("hello"), 8).await;
+
+        assert_eq!(upstream.calls(), 1);
+        for result in results {
+            let error = result.expect_err("shared error");
+            assert!(
+                matches!(&error, RociError::Api { status: 503, message, .. } if message == "upstream unavailable"),
+                "{error:?}"
+            );
+            assert!(error.is_retryable());
+        }
+    }
+
+    #[tokio::test]
+    async fn different_requests_do_not_coalesce() {
+        let upstream = CountingProvider::new(Duration::from_millis(20));
+        let provider = SingleFlight::new().wrap(upstream.clone());
+        let hello = request("hello");
+        let goodbye = request("goodbye");
+        let mut hotter = request("hello");
+        hotter.settings.temperature = Some(1.5);
+
+        let (a, b, c) = tokio::join!(
+            provider.generate_text(&hello),
+            provider.generate_text(&goodbye),
+            provider.generate_text(&hotter),
+        );
+
+        assert_eq!(upstream.calls(), 3);
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    }
+
+    #[tokio::test]
+    async fn dropping_the_first_waiter_does_not_cancel_the_others() {
+        let upstream = CountingProvider::new(Duration::from_millis(100));
+        let provider = SingleFlight::new().wrap(upstream.clone());
+        let first = {
+            let provider = Arc::clone(&provider);
+            tokio::spawn(async move { provider.generate_text(&request("hello")).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let second = {
+            let provider = Arc::clone(&provider);
+            tokio::spawn(async move { provider.generate_text(&request("hello")).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        first.abort();
+        assert!(first.await.expect_err("aborted").is_cancelled());
+        let response = second.await.expect("waiter task").expect("response");
+
+        assert_eq!(response.text, "call 1: hello");
+        assert_eq!(upstream.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn completed_responses_are_reused_only_within_the_ttl() {
+        let upstream = CountingProvider::new(Duration::ZERO);
+        let group = SingleFlight::new().with_ttl(Duration::from_millis(100));
+        let provider = group.wrap(upstream.clone());
+
+        let first = provider.generate_text(&request("hello")).await.unwrap();
+        let reused = provider.generate_text(&request("hello")).await.unwrap();
+        assert_eq!(upstream.calls(), 1);
+        assert_eq!(reused.text, first.text);
+        assert_eq!(group.len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let fresh = provider.generate_text(&request("hello")).await.unwrap();
+        assert_eq!(upstream.calls(), 2);
+        assert_eq!(fresh.text, "call 2: hello");
+    }
+
+    #[tokio::test]
+    async fn without_a_ttl_sequential_calls_each_reach_the_provider() {
+        let upstream = CountingProvider::new(Duration::ZERO);
+        let group = SingleFlight::new();
+        let provider = group.wrap(upstream.clone());
+
+        provider.generate_text(&request("hello")).await.unwrap();
+        provider.generate_text(&request("hello")).await.unwrap();
+
+        assert_eq!(upstream.calls(), 2);
+        assert!(group.is_empty());
+    }
+
+    #[tokio::test]
+    async fn wrapped_providers_share_the_group() {
+        let upstream = CountingProvider::new(Duration::from_millis(50));
+        let group = SingleFlight::new();
+        let first = group.wrap(upstream.clone());
+        let second = group.wrap(upstream.clone());
+        let hello = request("hello");
+
+        let (a, b) = tokio::join!(first.generate_text(&hello), second.generate_text(&hello));
+
+        assert_eq!(upstream.calls(), 1);
+        assert_eq!(a.unwrap().text, b.unwrap().text);
+    }
+
+    #[tokio::test]
+    async fn payload_callbacks_bypass_coalescing() {
+        let upstream = CountingProvider::new(Duration::from_millis(20));
+        let provider = SingleFlight::new().wrap(upstream.clone());
+        let mut observed = request("hello");
+        observed.payload_callback = Some(Arc::new(|_| {}));
+
+        let (a, b) = tokio::join!(
+            provider.generate_text(&observed),
+            provider.generate_text(&observed),
+        );
+
+        assert_eq!(upstream.calls(), 2);
+        assert!(a.is_ok() && b.is_ok());
+    }
+}
@@ -0,0 +1,239 @@
+//! Run-wide tool-call ID allocation for provider response parsers.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Assigns tool-call IDs that are unique across a run.
+///
+/// Provider parsers call [`ToolCallIdAllocator::begin_response`] once per
+/// response and pass every parsed call through [`ResponseToolCallIds::assign`]:
+///
+/// - A call without a provider ID gets `call_{nonce}_{iteration}_{index}_{tool}`,
+///   so a retry of the same iteration reproduces the same IDs.
+/// - A provider ID already used in this response or an earlier iteration gets
+///   a `-2`, `-3`, ... suffix; [`original_id`](Self::original_id) maps it back.
+///
+/// Clones share state. The runner creates one per run and hands it to
+/// providers through [`ProviderRequest::tool_call_ids`](super::ProviderRequest::tool_call_ids).
+#[derive(Clone)]
+pub struct ToolCallIdAllocator {
+    nonce: Arc<str>,
+    iteration: usize,
+    state: Arc<Mutex<AllocatorState>>,
+}
+
+#[derive(Default)]
+struct AllocatorState {
+    issued: BTreeMap<usize, HashSet<String>>,
+    original_ids: HashMap<String, String>,
+}
+
+impl ToolCallIdAllocator {
+    /// Allocator for one run; `nonce` keeps generated IDs distinct between runs.
+    pub fn new(nonce: impl Into<String>) -> Self {
+        Self {
+            nonce: Arc::from(nonce.into()),
+            iteration: 0,
+            state: Arc::default(),
+        }
+    }
+
+    /// Allocator for a provider call made outside a run.
+    pub fn detached() -> Self {
+        Self::new(uuid::Uuid::new_v4().simple().to_string()[..8].to_string())
+    }
+
+    /// Handle sharing this allocator's state that assigns IDs for `iteration`.
+    pub fn for_iteration(&self, iteration: usize) -> Self {
+        Self {
+            iteration,
+            ..self.clone()
+        }
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Start assigning IDs for one provider response.
+    ///
+    /// IDs issued for this iteration or later ones are released first: a new
+    /// response for the same iteration is a retry, and its calls replace the
+    /// earlier attempt's.
+    pub fn begin_response(&self) -> ResponseToolCallIds {
+        let mut state = self.lock();
+        let released = state.issued.split_off(&self.iteration);
+        for id in released.into_values().flatten() {
+            state.original_ids.remove(&id);
+        }
+        drop(state);
+        ResponseToolCallIds {
+            allocator: self.clone(),
+            next_index: 0,
+        }
+    }
+
+    /// The provider-supplied ID behind a disambiguated `id`, if it was renamed.
+    pub fn original_id(&self, id: &str) -> Option<String> {
+        self.lock().original_ids.get(id).cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AllocatorState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for ToolCallIdAllocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolCallIdAllocator")
+            .field("nonce", &self.nonce)
+            .field("iteration", &self.iteration)
+            .finish()
+    }
+}
+
+/// ID assignment for the calls of a single provider response.
+#[derive(Debug)]
+pub struct ResponseToolCallIds {
+    allocator: ToolCallIdAllocator,
+    next_index: usize,
+}
+
+impl ResponseToolCallIds {
+    /// Final ID for the next call in the response, given the provider's ID
+    /// (`None` or empty when it sent none) and the tool name.
+    pub fn assign(&mut self, provider_id: Option<&str>, tool_name: &str) -> String {
+        let index = self.next_index;
+        self.next_index += 1;
+        let allocator = &self.allocator;
+        let base = match provider_id.map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => id.to_string(),
+            None => format!(
+                "call_{}_{}_{}_{}",
+                allocator.nonce,
+                allocator.iteration,
+                index,
+                id_safe(tool_name)
+            ),
+        };
+
+        let mut state = allocator.lock();
+        let in_use = |state: &AllocatorState, id: &str| {
+            state.issued.values().any(|issued| issued.contains(id))
+        };
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while in_use(&state, &id) {
+            id = format!("{base}-{suffix}");
+            suffix += 1;
+        }
+        if id != base && provider_id.is_some_and(|provider_id| provider_id.trim() == base) {
+            state.original_ids.insert(id.clone(), base);
+        }
+        state
+            .issued
+            .entry(allocator.iteration)
+            .or_default()
+            .insert(id.clone());
+        id
+    }
+}
+
+fn id_safe(tool_name: &str) -> String {
+    tool_name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_ids_are_generated_from_nonce_iteration_index_and_tool() {
+        let allocator = ToolCallIdAllocator::new("run1").for_iteration(3);
+        let mut ids = allocator.begin_response();
+
+        assert_eq!(ids.assign(None, "read"), "call_run1_3_0_read");
+        assert_eq!(
+            ids.assign(Some(""), "mcp.search"),
+            "call_run1_3_1_mcp_search"
+        );
+    }
+
+    #[test]
+    fn generated_ids_are_unique_across_iterations() {
+        let allocator = ToolCallIdAllocator::new("run1");
+        let first = allocator
+            .for_iteration(0)
+            .begin_response()
+            .assign(None, "read");
+        let second = allocator
+            .for_iteration(1)
+            .begin_response()
+            .assign(None, "read");
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn duplicate_provider_ids_in_one_response_get_suffixes() {
+        let allocator = ToolCallIdAllocator::new("run1");
+        let mut ids = allocator.begin_response();
+
+        assert_eq!(ids.assign(Some("call_0"), "read"), "call_0");
+        assert_eq!(ids.assign(Some("call_0"), "write"), "call_0-2");
+        assert_eq!(ids.assign(Some("call_0"), "list"), "call_0-3");
+        assert_eq!(allocator.original_id("call_0-2").as_deref(), Some("call_0"));
+        assert_eq!(allocator.original_id("call_0"), None);
+    }
+
+    #[test]
+    fn provider_ids_reused_in_a_later_iteration_are_disambiguated() {
+        let allocator = ToolCallIdAllocator::new("run1");
+        allocator
+            .for_iteration(0)
+            .begin_response()
+            .assign(Some("call_0"), "read");
+
+        let mut ids = allocator.for_iteration(1).begin_response();
+
+        assert_eq!(ids.assign(Some("call_0"), "read"), "call_0-2");
+    }
+
+    #[test]
+    fn retrying_an_iteration_reproduces_its_ids() {
+        let allocator = ToolCallIdAllocator::new("run1");
+        allocator
+            .for_iteration(0)
+            .begin_response()
+            .assign(Some("call_0"), "read");
+        let iteration = allocator.for_iteration(1);
+
+        let mut first_attempt = iteration.begin_response();
+        let first = [
+            first_attempt.assign(None, "read"),
+            first_attempt.assign(Some("call_0"), "read"),
+            first_attempt.assign(Some("call_0"), "read"),
+        ];
+        let mut retry = iteration.begin_response();
+        let second = [
+            retry.assign(None, "read"),
+            retry.assign(Some("call_0"), "read"),
+            retry.assign(Some("call_0"), "read"),
+        ];
+
+        assert_eq!(first, second);
+        assert_eq!(second[1], "call_0-2");
+        assert_eq!(second[2], "call_0-3");
+    }
+}
@@ -0,0 +1,144 @@
+//! How many tool definitions a provider accepts in one request.
+
+use serde::{Deserialize, Serialize};
+
+use super::ToolDefinition;
+
+/// Limits on the tool definitions sent with one request. `None` means the
+/// provider documents no limit.
+///
+/// Providers report theirs through
+/// [`ModelProvider::tool_limits`](super::ModelProvider::tool_limits);
+/// [`RociConfig::set_tool_limits`](crate::config::RociConfig::set_tool_limits)
+/// overrides them, e.g. for compatible endpoints with their own caps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tools: Option<usize>,
+    /// Combined size of the definitions serialized as JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_definition_bytes: Option<usize>,
+}
+
+impl ToolLimits {
+    /// OpenAI rejects requests with more than 128 tools.
+    pub const OPENAI: Self = Self {
+        max_tools: Some(128),
+        max_definition_bytes: None,
+    };
+    /// Gemini accepts at most 128 function declarations.
+    pub const GOOGLE: Self = Self {
+        max_tools: Some(128),
+        max_definition_bytes: None,
+    };
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tools.is_none() && self.max_definition_bytes.is_none()
+    }
+
+    /// Why `tools` do not fit, or `None` when they do.
+    pub fn violation(&self, tools: &[ToolDefinition]) -> Option<String> {
+        if let Some(max) = self.max_tools.filter(|max| tools.len() > *max) {
+            return Some(format!(
+                "{} tools are advertised but at most {max} are accepted",
+                tools.len()
+            ));
+        }
+        let bytes: usize = tools.iter().map(definition_bytes).sum();
+        self.max_definition_bytes
+            .filter(|max| bytes > *max)
+            .map(|max| {
+                format!("tool definitions take {bytes} bytes but at most {max} are accepted")
+            })
+    }
+
+    /// Indexes of the `tools` to drop so the rest fit, in ascending order.
+    ///
+    /// Tools are kept by descending `priorities` (parallel to `tools`), ties
+    /// going to the earlier tool; a tool that would break a limit is dropped
+    /// and smaller ones after it may still be kept.
+    pub fn overflow(&self, tools: &[ToolDefinition], priorities: &[i32]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..tools.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(priorities.get(index).copied()));
+        let mut kept = 0usize;
+        let mut bytes = 0usize;
+        let mut dropped: Vec<usize> = order
+            .into_iter()
+            .filter(|&index| {
+                let size = definition_bytes(&tools[index]);
+                let fits = self.max_tools.is_none_or(|max| kept < max)
+                    && self
+                        .max_definition_bytes
+                        .is_none_or(|max| bytes + size <= max);
+                if fits {
+                    kept += 1;
+                    bytes += size;
+                }
+                !fits
+            })
+            .collect();
+        dropped.sort_unstable();
+        dropped
+    }
+}
+
+fn definition_bytes(tool: &ToolDefinition) -> usize {
+    serde_json::to_vec(tool).map_or(0, |json| json.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, description_len: usize) -> ToolDefinition {
+        ToolDefinition::new(
+            name,
+            "d".repeat(description_len),
+            serde_json::json!({ "type": "object" }),
+        )
+    }
+
+    #[test]
+    fn violations_name_the_broken_limit() {
+        let tools = vec![tool("a", 10), tool("b", 10), tool("c", 10)];
+
+        assert_eq!(ToolLimits::default().violation(&tools), None);
+        let count = ToolLimits {
+            max_tools: Some(2),
+            ..ToolLimits::default()
+        };
+        assert_eq!(
+            count.violation(&tools).as_deref(),
+            Some("3 tools are advertised but at most 2 are accepted")
+        );
+        let bytes = ToolLimits {
+            max_definition_bytes: Some(100),
+            ..ToolLimits::default()
+        };
+        assert!(bytes.violation(&tools).unwrap().contains("at most 100"));
+    }
+
+    #[test]
+    fn overflow_drops_lowest_priority_then_latest_tools() {
+        let tools = vec![tool("a", 10), tool("b", 10), tool("c", 10), tool("d", 10)];
+        let limits = ToolLimits {
+            max_tools: Some(2),
+            ..ToolLimits::default()
+        };
+
+        assert_eq!(limits.overflow(&tools, &[0, 0, 0, 0]), vec![2, 3]);
+        assert_eq!(limits.overflow(&tools, &[0, 5, -1, 5]), vec![0, 2]);
+    }
+
+    #[test]
+    fn overflow_keeps_smaller_tools_that_still_fit_the_byte_budget() {
+        let tools = vec![tool("big", 200), tool("small", 10), tool("other", 10)];
+        let one = definition_bytes(&tools[1]);
+        let limits = ToolLimits {
+            max_definition_bytes: Some(2 * one + 5),
+            ..ToolLimits::default()
+        };
+
+        assert_eq!(limits.overflow(&tools, &[0, 0, 0]), vec![0]);
+    }
+}
@@ -0,0 +1,19 @@
+//! Model warm-up reporting for self-hosted providers.
+
+use std::time::Duration;
+
+/// Outcome of [`ModelProvider::warm_up`](super::ModelProvider::warm_up).
+///
+/// Fields a server does not report are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// The model was already resident, so no load was triggered.
+    pub already_loaded: bool,
+    /// Time spent loading the model. Server-reported when available,
+    /// otherwise the wall time of the load request.
+    pub load_duration: Option<Duration>,
+    /// Bytes of the model held in GPU memory.
+    pub vram_bytes: Option<u64>,
+    /// Context window the model is loaded with.
+    pub context_length: Option<usize>,
+}
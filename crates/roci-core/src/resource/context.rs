@@ -66,11 +66,23 @@ impl ContextPromptLoader {
         &self,
         cwd: &Path,
         home_dir: Option<&Path>,
+    ) -> Result<ContextPromptResources, RociError> {
+        self.load_reusing(cwd, home_dir, &|_| None)
+    }
+
+    /// Like [`load_with_home`](Self::load_with_home), but context files for
+    /// which `reuse` returns content are not read again.
+    pub(super) fn load_reusing(
+        &self,
+        cwd: &Path,
+        home_dir: Option<&Path>,
+        reuse: &dyn Fn(&Path) -> Option<String>,
     ) -> Result<ContextPromptResources, RociError> {
         let resolved_dirs = self.directories.resolve_with_home(cwd, home_dir)?;
         let mut diagnostics = Vec::new();
 
-        let context_files = discover_context_files(&resolved_dirs.agent_dir, cwd, &mut diagnostics);
+        let context_files =
+            discover_context_files(&resolved_dirs.agent_dir, cwd, reuse, &mut diagnostics);
 
         let system_prompt = read_preferred_prompt(
             &resolved_dirs.project_dir.join(SYSTEM_FILE_NAME),
@@ -95,6 +107,7 @@ impl ContextPromptLoader {
 fn discover_context_files(
     global_agent_dir: &Path,
     cwd: &Path,
+    reuse: &dyn Fn(&Path) -> Option<String>,
     diagnostics: &mut Vec<ResourceDiagnostic>,
 ) -> Vec<ContextFileResource> {
     let mut ordered_directories = vec![global_agent_dir.to_path_buf()];
@@ -113,7 +126,11 @@ fn discover_context_files(
             continue;
         }
 
-        match fs::read_to_string(&candidate_path) {
+        let content = match reuse(&candidate_path) {
+            Some(content) => Ok(content),
+            None => fs::read_to_string(&candidate_path),
+        };
+        match content {
             Ok(content) => files.push(ContextFileResource {
                 path: candidate_path,
                 content,
@@ -2,10 +2,12 @@ use std::path::{Path, PathBuf};
 
 use crate::error::RociError;
 
+use crate::skills::loader::load_skills_reusing;
 use crate::skills::{
-    default_skill_roots, load_skills, LoadSkillsOptions, LoadSkillsResult, SkillRoot, SkillSource,
+    default_skill_roots, LoadSkillsOptions, LoadSkillsResult, SkillRoot, SkillSource,
 };
 
+use super::refresh::{RefreshReport, ResourceSources, Unchanged};
 use super::{
     ContextPromptLoader, ContextPromptResources, LoadedPromptTemplates, PromptTemplateLoader,
     ResourceDirectories, ResourceSettings, ResourceSettingsLoader,
@@ -18,6 +20,32 @@ pub struct ResourceBundle {
     pub context: ContextPromptResources,
     pub prompt_templates: LoadedPromptTemplates,
     pub skills: LoadSkillsResult,
+    /// Files the bundle was built from, for [`ResourceBundle::refresh`].
+    pub sources: ResourceSources,
+}
+
+impl ResourceBundle {
+    /// Reload resources for `cwd` and report which files changed since the
+    /// bundle was loaded.
+    ///
+    /// Discovery runs again with the loader that built the bundle, so new
+    /// context files and skills are picked up. Context files, prompt
+    /// templates, and skills whose modification time and size are unchanged
+    /// keep their loaded entries; only modified and added ones are read and
+    /// parsed. Settings and system prompt files are small and always re-read.
+    /// Deleted files are dropped and reported in [`RefreshReport::warnings`]. Callers holding a
+    /// composed system prompt recompose it when
+    /// [`RefreshReport::system_prompt_changed`] is set.
+    pub fn refresh(&mut self, cwd: &Path) -> Result<RefreshReport, RociError> {
+        let (loader, home_dir) = match &self.sources.loader {
+            Some(loader) => (loader.clone(), self.sources.home_dir.clone()),
+            None => (ResourceLoader::new(), env_home_dir()),
+        };
+        let fresh = loader.load_tracked(cwd, home_dir, Some(self))?;
+        let report = self.sources.diff(&fresh.sources);
+        *self = fresh;
+        Ok(report)
+    }
 }
 
 /// Loader for settings, context files, prompt templates, and skills.
@@ -76,7 +104,7 @@ impl ResourceLoader {
     }
 
     pub fn load(&self, cwd: &Path) -> Result<ResourceBundle, RociError> {
-        self.load_with_home(cwd, std::env::var_os("HOME").map(PathBuf::from).as_deref())
+        self.load_tracked(cwd, env_home_dir(), None)
     }
 
     pub fn load_with_home(
@@ -84,12 +112,47 @@ impl ResourceLoader {
         cwd: &Path,
         home_dir: Option<&Path>,
     ) -> Result<ResourceBundle, RociError> {
+        self.load_tracked(cwd, home_dir.map(Path::to_path_buf), None)
+    }
+
+    fn load_tracked(
+        &self,
+        cwd: &Path,
+        home_dir: Option<PathBuf>,
+        previous: Option<&ResourceBundle>,
+    ) -> Result<ResourceBundle, RociError> {
+        let home_dir_path = home_dir.as_deref();
+        let mut bundle = self.load_resources(cwd, home_dir_path, previous.map(Unchanged::new))?;
+        let directories = self
+            .settings_loader
+            .directories()
+            .resolve_with_home(cwd, home_dir_path)?;
+        bundle.sources = ResourceSources::capture(
+            self.clone(),
+            home_dir,
+            &bundle,
+            &directories,
+            previous.map(|previous| &previous.sources),
+        );
+        Ok(bundle)
+    }
+
+    fn load_resources(
+        &self,
+        cwd: &Path,
+        home_dir: Option<&Path>,
+        unchanged: Option<Unchanged<'_>>,
+    ) -> Result<ResourceBundle, RociError> {
+        let unchanged = unchanged.as_ref();
         let settings = self.settings_loader.load_with_home(cwd, home_dir)?;
-        let mut context = self.context_loader.load_with_home(cwd, home_dir)?;
+        let mut context = self
+            .context_loader
+            .load_reusing(cwd, home_dir, &|path| unchanged?.context_file(path))?;
         let prompt_templates = if settings.no_prompt_templates {
             LoadedPromptTemplates::default()
         } else {
-            self.prompt_loader.load_with_home(cwd, home_dir)?
+            self.prompt_loader
+                .load_reusing(cwd, home_dir, &|path| unchanged?.prompt_template(path))?
         };
 
         if settings.no_context_files {
@@ -114,7 +177,7 @@ impl ResourceLoader {
                     explicit_paths: self.skill_options.explicit_paths.clone(),
                     follow_symlinks: true,
                 };
-                load_skills(&options)
+                load_skills_reusing(&options, &|path| unchanged?.skill(path))
             } else {
                 LoadSkillsResult::default()
             };
@@ -124,10 +187,15 @@ impl ResourceLoader {
             context,
             prompt_templates,
             skills,
+            sources: ResourceSources::default(),
         })
     }
 }
 
+fn env_home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -135,6 +203,25 @@ mod tests {
     use tempfile::tempdir;
 
     use super::{ResourceLoader, SkillResourceOptions};
+    use crate::resource::{ResourceChangeKind, ResourceKind, SystemPromptComposer};
+
+    fn write_skill(cwd: &std::path::Path, name: &str, description: &str) {
+        let dir = cwd.join(".roci/skills").join(name);
+        fs::create_dir_all(&dir).expect("skill dir should be created");
+        fs::write(
+            dir.join("SKILL.md"),
+            format!("---\nname: {name}\ndescription: {description}\n---\n"),
+        )
+        .expect("skill file should be written");
+    }
+
+    fn composed(bundle: &super::ResourceBundle) -> String {
+        SystemPromptComposer::new()
+            .with_resources(bundle)
+            .compose()
+            .render()
+            .unwrap_or_default()
+    }
 
     #[test]
     fn loader_aggregates_settings_context_and_prompt_templates() {
@@ -250,4 +337,102 @@ mod tests {
 
         assert!(bundle.skills.skills.is_empty());
     }
+
+    #[test]
+    fn refresh_reports_edited_context_and_skills_and_recomposes_prompt() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        fs::create_dir_all(&home).expect("home dir should be created");
+        fs::create_dir_all(&cwd).expect("workspace should be created");
+        fs::write(cwd.join("AGENTS.md"), "use tabs").expect("context should be written");
+        write_skill(&cwd, "lint", "Run the linter");
+        write_skill(&cwd, "stable", "Unchanged skill");
+
+        let mut bundle = ResourceLoader::new()
+            .load_with_home(&cwd, Some(&home))
+            .expect("bundle should load");
+        assert!(composed(&bundle).contains("use tabs"));
+
+        fs::write(cwd.join("AGENTS.md"), "use four spaces").expect("context should be edited");
+        write_skill(&cwd, "lint", "Run the linter with autofix");
+        write_skill(&cwd, "release", "Cut a release");
+
+        let report = bundle.refresh(&cwd).expect("refresh should succeed");
+
+        assert_eq!(report.summary(), "AGENTS.md updated, 2 skills reloaded");
+        assert!(report.system_prompt_changed());
+        assert!(report.warnings.is_empty());
+        assert!(report
+            .changes
+            .iter()
+            .any(|change| change.kind == ResourceKind::Skill
+                && change.change == ResourceChangeKind::Added
+                && change.path.ends_with("release/SKILL.md")));
+        let prompt = composed(&bundle);
+        assert!(prompt.contains("use four spaces"), "{prompt}");
+        assert!(!prompt.contains("use tabs"), "{prompt}");
+        assert!(prompt.contains("Cut a release"), "{prompt}");
+
+        let unchanged = bundle.refresh(&cwd).expect("refresh should succeed");
+        assert!(unchanged.is_empty());
+        assert_eq!(unchanged.summary(), "");
+    }
+
+    #[test]
+    fn refresh_keeps_entries_of_files_with_unchanged_stamps() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        fs::create_dir_all(&home).expect("home dir should be created");
+        fs::create_dir_all(&cwd).expect("workspace should be created");
+        fs::write(cwd.join("AGENTS.md"), "use tabs").expect("context should be written");
+        write_skill(&cwd, "lint", "Run the linter");
+
+        let mut bundle = ResourceLoader::new()
+            .load_with_home(&cwd, Some(&home))
+            .expect("bundle should load");
+
+        // Same size and restored mtime: refresh must not parse the skill again.
+        let skill_path = cwd.join(".roci/skills/lint/SKILL.md");
+        let modified = fs::metadata(&skill_path)
+            .and_then(|meta| meta.modified())
+            .expect("skill mtime should be readable");
+        write_skill(&cwd, "lint", "Run the LINTER");
+        fs::File::options()
+            .write(true)
+            .open(&skill_path)
+            .and_then(|file| file.set_modified(modified))
+            .expect("skill mtime should be restored");
+        fs::write(cwd.join("AGENTS.md"), "use four spaces").expect("context should be edited");
+
+        let report = bundle.refresh(&cwd).expect("refresh should succeed");
+
+        assert_eq!(report.summary(), "AGENTS.md updated");
+        assert_eq!(bundle.skills.skills[0].description, "Run the linter");
+        assert!(composed(&bundle).contains("use four spaces"));
+    }
+
+    #[test]
+    fn refresh_drops_deleted_files_with_a_warning() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        fs::create_dir_all(&home).expect("home dir should be created");
+        fs::create_dir_all(&cwd).expect("workspace should be created");
+        fs::write(cwd.join("AGENTS.md"), "stale rules").expect("context should be written");
+
+        let mut bundle = ResourceLoader::new()
+            .load_with_home(&cwd, Some(&home))
+            .expect("bundle should load");
+        fs::remove_file(cwd.join("AGENTS.md")).expect("context should be removed");
+
+        let report = bundle.refresh(&cwd).expect("refresh should succeed");
+
+        assert_eq!(report.summary(), "AGENTS.md removed");
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("AGENTS.md"));
+        assert!(bundle.context.context_files.is_empty());
+        assert!(!composed(&bundle).contains("stale rules"));
+    }
 }
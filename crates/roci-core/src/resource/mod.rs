@@ -1,7 +1,9 @@
 pub mod context;
 pub mod loader;
 pub mod prompts;
+pub mod refresh;
 pub mod settings;
+pub mod system_prompt;
 
 pub use context::{
     ContextFileResource, ContextPromptLoader, ContextPromptResources, ResourceDiagnostic,
@@ -11,8 +13,17 @@ pub use prompts::{
     PromptTemplateLoader,
 };
 pub use settings::{
-    BranchSummarySettings, CompactionSettings, ResourceDirectories, ResourceSettings,
-    ResourceSettingsLoader,
+    ApprovalPreset, BranchSummarySettings, CompactionSettings, FetchUrlSettings,
+    RedactionRuleSettings, RedactionSettings, ResourceDirectories, ResourceSettings,
+    ResourceSettingsLoader, RunDefaultsSettings, ServeSettings, ShellSandboxSettings,
+    ToolOverrideSettings,
+};
+pub use system_prompt::{
+    compose_agent_system_prompt, ComposedSection, ComposedSystemPrompt, PromptSection,
+    PromptSectionDiagnostic, PromptSectionKind, SystemPromptComposer,
 };
 
 pub use loader::{ResourceBundle, ResourceLoader, SkillResourceOptions};
+pub use refresh::{
+    RefreshReport, ResourceChange, ResourceChangeKind, ResourceKind, ResourceSources,
+};
@@ -93,6 +93,17 @@ impl PromptTemplateLoader {
         &self,
         cwd: &Path,
         home_dir: Option<&Path>,
+    ) -> Result<LoadedPromptTemplates, RociError> {
+        self.load_reusing(cwd, home_dir, &|_| None)
+    }
+
+    /// Like [`load_with_home`](Self::load_with_home), but template files for
+    /// which `reuse` returns a template are not parsed again.
+    pub(super) fn load_reusing(
+        &self,
+        cwd: &Path,
+        home_dir: Option<&Path>,
+        reuse: &dyn Fn(&Path) -> Option<PromptTemplate>,
     ) -> Result<LoadedPromptTemplates, RociError> {
         let mut loaded = LoadedPromptTemplates::default();
         let resolved = self
@@ -103,18 +114,25 @@ impl PromptTemplateLoader {
 
         load_templates_from_directory(
             &resolved.agent_dir.join("prompts"),
+            reuse,
             &mut loaded.templates,
             &mut loaded.diagnostics,
         );
         load_templates_from_directory(
             &resolved.project_dir.join("prompts"),
+            reuse,
             &mut loaded.templates,
             &mut loaded.diagnostics,
         );
 
         for explicit_path in settings.prompts {
             let normalized = resolve_path(&explicit_path.to_string_lossy(), cwd, home_dir)?;
-            load_templates_from_path(&normalized, &mut loaded.templates, &mut loaded.diagnostics);
+            load_templates_from_path(
+                &normalized,
+                reuse,
+                &mut loaded.templates,
+                &mut loaded.diagnostics,
+            );
         }
 
         Ok(loaded)
@@ -144,11 +162,12 @@ fn collision(path: &Path, previous: &Path, name: &str) -> PromptDiagnostic {
 
 fn load_templates_from_path(
     path: &Path,
+    reuse: &dyn Fn(&Path) -> Option<PromptTemplate>,
     templates: &mut HashMap<String, PromptTemplate>,
     diagnostics: &mut Vec<PromptDiagnostic>,
 ) {
     if path.is_dir() {
-        load_templates_from_directory(path, templates, diagnostics);
+        load_templates_from_directory(path, reuse, templates, diagnostics);
         return;
     }
 
@@ -161,11 +180,12 @@ fn load_templates_from_path(
         return;
     }
 
-    load_template_file(path, templates, diagnostics);
+    load_template_file(path, reuse, templates, diagnostics);
 }
 
 fn load_templates_from_directory(
     directory: &Path,
+    reuse: &dyn Fn(&Path) -> Option<PromptTemplate>,
     templates: &mut HashMap<String, PromptTemplate>,
     diagnostics: &mut Vec<PromptDiagnostic>,
 ) {
@@ -213,15 +233,29 @@ fn load_templates_from_directory(
 
     files.sort();
     for path in files {
-        load_template_file(&path, templates, diagnostics);
+        load_template_file(&path, reuse, templates, diagnostics);
     }
 }
 
 fn load_template_file(
     path: &Path,
+    reuse: &dyn Fn(&Path) -> Option<PromptTemplate>,
     templates: &mut HashMap<String, PromptTemplate>,
     diagnostics: &mut Vec<PromptDiagnostic>,
 ) {
+    let Some(template) = reuse(path).or_else(|| parse_template_file(path, diagnostics)) else {
+        return;
+    };
+    let name = template.name.clone();
+    if let Some(previous) = templates.insert(name.clone(), template) {
+        diagnostics.push(collision(path, &previous.path, &name));
+    }
+}
+
+fn parse_template_file(
+    path: &Path,
+    diagnostics: &mut Vec<PromptDiagnostic>,
+) -> Option<PromptTemplate> {
     let raw = match fs::read_to_string(path) {
         Ok(raw) => raw,
         Err(error) => {
@@ -229,7 +263,7 @@ fn load_template_file(
                 path,
                 format!("Unable to read prompt file: {error}"),
             ));
-            return;
+            return None;
         }
     };
 
@@ -240,7 +274,7 @@ fn load_template_file(
                 path,
                 "Prompt file name could not be converted into a command",
             ));
-            return;
+            return None;
         }
     };
 
@@ -252,16 +286,12 @@ fn load_template_file(
         .or_else(|| first_non_empty_line(&body))
         .unwrap_or_else(|| name.clone());
 
-    let template = PromptTemplate {
-        name: name.clone(),
+    Some(PromptTemplate {
+        name,
         description,
         body,
         path: path.to_path_buf(),
-    };
-
-    if let Some(previous) = templates.insert(name.clone(), template) {
-        diagnostics.push(collision(path, &previous.path, &name));
-    }
+    })
 }
 
 #[derive(Debug, Deserialize)]
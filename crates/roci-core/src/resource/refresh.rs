@@ -0,0 +1,350 @@
+//! Change tracking for loaded resource files.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::loader::ResourceLoader;
+use super::settings::ResolvedResourceDirectories;
+use super::{PromptTemplate, ResourceBundle};
+use crate::skills::{Skill, SkillDiagnostic};
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const SYSTEM_FILE_NAME: &str = "SYSTEM.md";
+const APPEND_SYSTEM_FILE_NAME: &str = "APPEND_SYSTEM.md";
+
+/// Kind of resource a tracked file provides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceKind {
+    Settings,
+    SystemPrompt,
+    ContextFile,
+    PromptTemplate,
+    Skill,
+}
+
+/// How a tracked file changed between two loads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// One file that changed between two loads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceChange {
+    pub path: PathBuf,
+    pub kind: ResourceKind,
+    pub change: ResourceChangeKind,
+}
+
+/// What [`ResourceBundle::refresh`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshReport {
+    /// Changed files, ordered by kind then path.
+    pub changes: Vec<ResourceChange>,
+    /// One entry per deleted file whose content was dropped.
+    pub warnings: Vec<String>,
+}
+
+impl RefreshReport {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether the system prompt derived from the bundle changed, so it must
+    /// be recomposed.
+    pub fn system_prompt_changed(&self) -> bool {
+        self.changes.iter().any(|change| {
+            matches!(
+                change.kind,
+                ResourceKind::SystemPrompt | ResourceKind::ContextFile | ResourceKind::Skill
+            )
+        })
+    }
+
+    /// One-line summary such as `AGENTS.md updated, 2 skills reloaded`.
+    /// Empty when nothing changed.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        for change in &self.changes {
+            if matches!(
+                change.kind,
+                ResourceKind::Settings | ResourceKind::SystemPrompt | ResourceKind::ContextFile
+            ) {
+                let name = change
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| change.path.display().to_string());
+                let verb = match change.change {
+                    ResourceChangeKind::Added => "added",
+                    ResourceChangeKind::Modified => "updated",
+                    ResourceChangeKind::Removed => "removed",
+                };
+                parts.push(format!("{name} {verb}"));
+            }
+        }
+        for (kind, noun) in [
+            (ResourceKind::PromptTemplate, "prompt template"),
+            (ResourceKind::Skill, "skill"),
+        ] {
+            let (reloaded, removed) = self
+                .changes
+                .iter()
+                .filter(|change| change.kind == kind)
+                .fold((0, 0), |(reloaded, removed), change| match change.change {
+                    ResourceChangeKind::Removed => (reloaded, removed + 1),
+                    _ => (reloaded + 1, removed),
+                });
+            if reloaded > 0 {
+                parts.push(format!("{} reloaded", counted(reloaded, noun)));
+            }
+            if removed > 0 {
+                parts.push(format!("{} removed", counted(removed, noun)));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+fn counted(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+impl fmt::Display for RefreshReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary())
+    }
+}
+
+/// Where a [`ResourceBundle`] was loaded from and the state of each file it
+/// read, used by [`ResourceBundle::refresh`].
+///
+/// Bundles built by hand start empty; their first refresh reloads with a
+/// default [`ResourceLoader`] and reports every file as added.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceSources {
+    pub(super) loader: Option<ResourceLoader>,
+    pub(super) home_dir: Option<PathBuf>,
+    files: BTreeMap<PathBuf, FileStamp>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    kind: ResourceKind,
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    /// Whether a file with `modified` and `len` can keep this stamp.
+    fn matches(&self, modified: Option<SystemTime>, len: u64) -> bool {
+        modified.is_some() && self.modified == modified && self.len == len
+    }
+}
+
+impl ResourceSources {
+    /// Record every file `bundle` was built from.
+    ///
+    /// Files whose modification time and size match `previous` keep their
+    /// recorded hash instead of being read again.
+    pub(super) fn capture(
+        loader: ResourceLoader,
+        home_dir: Option<PathBuf>,
+        bundle: &ResourceBundle,
+        directories: &ResolvedResourceDirectories,
+        previous: Option<&ResourceSources>,
+    ) -> Self {
+        let mut files = BTreeMap::new();
+        for (path, kind) in tracked_files(bundle, directories) {
+            let previous = previous.and_then(|sources| sources.files.get(&path));
+            if let Some(stamp) = stamp_file(&path, kind, previous) {
+                files.insert(path, stamp);
+            }
+        }
+        Self {
+            loader: Some(loader),
+            home_dir,
+            files,
+        }
+    }
+
+    /// Tracked files, in path order.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Files that differ between `self` and `next`, as a report.
+    pub(super) fn diff(&self, next: &ResourceSources) -> RefreshReport {
+        let mut changes = Vec::new();
+        for (path, stamp) in &next.files {
+            let change = match self.files.get(path) {
+                None => ResourceChangeKind::Added,
+                Some(previous) if previous.hash != stamp.hash => ResourceChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push(ResourceChange {
+                path: path.clone(),
+                kind: stamp.kind,
+                change,
+            });
+        }
+        let mut warnings = Vec::new();
+        for (path, stamp) in &self.files {
+            if next.files.contains_key(path) {
+                continue;
+            }
+            warnings.push(format!(
+                "{} was removed; dropped its content",
+                path.display()
+            ));
+            changes.push(ResourceChange {
+                path: path.clone(),
+                kind: stamp.kind,
+                change: ResourceChangeKind::Removed,
+            });
+        }
+        changes.sort_by(|left, right| (left.kind, &left.path).cmp(&(right.kind, &right.path)));
+        RefreshReport { changes, warnings }
+    }
+}
+
+/// Parsed entries of a loaded bundle, handed to the loaders on refresh so
+/// that files with unchanged modification time and size are not parsed
+/// again.
+pub(super) struct Unchanged<'a> {
+    bundle: &'a ResourceBundle,
+}
+
+impl<'a> Unchanged<'a> {
+    pub(super) fn new(bundle: &'a ResourceBundle) -> Self {
+        Self { bundle }
+    }
+
+    /// Content of the context file at `path`, if it is unchanged.
+    pub(super) fn context_file(&self, path: &Path) -> Option<String> {
+        if !self.contains(path) {
+            return None;
+        }
+        self.bundle
+            .context
+            .context_files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.content.clone())
+    }
+
+    /// The prompt template loaded from `path`, if it is unchanged.
+    pub(super) fn prompt_template(&self, path: &Path) -> Option<PromptTemplate> {
+        if !self.contains(path) {
+            return None;
+        }
+        self.bundle
+            .prompt_templates
+            .templates()
+            .values()
+            .find(|template| template.path == path)
+            .cloned()
+    }
+
+    /// The skill loaded from `path` and the warnings its parse produced, if
+    /// it is unchanged.
+    pub(super) fn skill(&self, path: &Path) -> Option<(Skill, Vec<SkillDiagnostic>)> {
+        if !self.contains(path) {
+            return None;
+        }
+        let skill = self
+            .bundle
+            .skills
+            .skills
+            .iter()
+            .find(|skill| skill.file_path == path)?;
+        let warnings = self
+            .bundle
+            .skills
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.path == path && diagnostic.collision.is_none())
+            .cloned()
+            .collect();
+        Some((skill.clone(), warnings))
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        let Some(stamp) = self.bundle.sources.files.get(path) else {
+            return false;
+        };
+        file_metadata(path).is_some_and(|(modified, len)| stamp.matches(modified, len))
+    }
+}
+
+fn tracked_files(
+    bundle: &ResourceBundle,
+    directories: &ResolvedResourceDirectories,
+) -> Vec<(PathBuf, ResourceKind)> {
+    let mut files = Vec::new();
+    for dir in [&directories.agent_dir, &directories.project_dir] {
+        files.push((dir.join(SETTINGS_FILE_NAME), ResourceKind::Settings));
+        files.push((dir.join(SYSTEM_FILE_NAME), ResourceKind::SystemPrompt));
+        files.push((
+            dir.join(APPEND_SYSTEM_FILE_NAME),
+            ResourceKind::SystemPrompt,
+        ));
+    }
+    if let Some(path) = &bundle.settings.defaults.system_prompt_file {
+        files.push((path.clone(), ResourceKind::SystemPrompt));
+    }
+    files.extend(
+        bundle
+            .context
+            .context_files
+            .iter()
+            .map(|file| (file.path.clone(), ResourceKind::ContextFile)),
+    );
+    files.extend(
+        bundle
+            .prompt_templates
+            .templates()
+            .values()
+            .map(|template| (template.path.clone(), ResourceKind::PromptTemplate)),
+    );
+    files.extend(
+        bundle
+            .skills
+            .skills
+            .iter()
+            .map(|skill| (skill.file_path.clone(), ResourceKind::Skill)),
+    );
+    files
+}
+
+fn file_metadata(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    let metadata = std::fs::metadata(path).ok().filter(|meta| meta.is_file())?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+fn stamp_file(path: &Path, kind: ResourceKind, previous: Option<&FileStamp>) -> Option<FileStamp> {
+    let (modified, len) = file_metadata(path)?;
+    if let Some(previous) = previous.filter(|stamp| stamp.matches(modified, len)) {
+        return Some(*previous);
+    }
+    let content = std::fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Some(FileStamp {
+        kind,
+        modified,
+        len,
+        hash: hasher.finish(),
+    })
+}
@@ -1,12 +1,41 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 use serde_json::Value;
 
+use super::ResourceDiagnostic;
+use crate::config::ChatFormatRule;
 use crate::error::RociError;
+use crate::generation::Preset;
+use crate::models::LanguageModel;
+use crate::types::{GenerationSettings, ResponseFormat};
 
 const SETTINGS_FILE_NAME: &str = "settings.json";
+const KNOWN_KEYS: [&str; 13] = [
+    "prompts",
+    "no_prompt_templates",
+    "no_context_files",
+    "compaction",
+    "branch_summary",
+    "fetch_url",
+    "shell_sandbox",
+    "redaction",
+    "serve",
+    "tool_overrides",
+    "chat_formats",
+    "defaults",
+    "presets",
+];
+const KNOWN_DEFAULTS_KEYS: [&str; 6] = [
+    "model",
+    "temperature",
+    "approval",
+    "max_iterations",
+    "tools",
+    "system_prompt_file",
+];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResourceDirectories {
@@ -46,13 +75,53 @@ impl ResourceDirectories {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct ResourceSettings {
     pub prompts: Vec<PathBuf>,
     pub no_prompt_templates: bool,
     pub no_context_files: bool,
     pub compaction: CompactionSettings,
     pub branch_summary: BranchSummarySettings,
+    pub fetch_url: FetchUrlSettings,
+    pub shell_sandbox: ShellSandboxSettings,
+    pub redaction: RedactionSettings,
+    pub serve: ServeSettings,
+    /// Per-tool overrides of built-in tool metadata, keyed by tool name.
+    pub tool_overrides: BTreeMap<String, ToolOverrideSettings>,
+    /// Chat-format overrides keyed by provider, for
+    /// [`RociConfig::set_chat_format_rules`](crate::config::RociConfig::set_chat_format_rules).
+    pub chat_formats: BTreeMap<String, Vec<ChatFormatRule>>,
+    pub defaults: RunDefaultsSettings,
+    /// Named generation presets, sorted by name.
+    pub presets: Vec<Preset>,
+    /// Keys this version does not recognize, reported instead of rejected so
+    /// settings written for newer releases still load.
+    pub diagnostics: Vec<ResourceDiagnostic>,
+}
+
+/// Run defaults from the `defaults` section, shared by every run in the
+/// workspace unless the caller overrides them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunDefaultsSettings {
+    pub model: Option<LanguageModel>,
+    pub temperature: Option<f64>,
+    pub approval: Option<ApprovalPreset>,
+    pub max_iterations: Option<usize>,
+    /// Tool allowlist. An empty list hides every tool.
+    pub tools: Option<Vec<String>>,
+    /// Resolved against the directory of the settings file that set it.
+    pub system_prompt_file: Option<PathBuf>,
+    /// Contents of `system_prompt_file`.
+    pub system_prompt: Option<String>,
+}
+
+/// Tool approval presets selectable from settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPreset {
+    Ask,
+    Always,
+    Never,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -89,6 +158,66 @@ impl Default for BranchSummarySettings {
     }
 }
 
+/// Host fence for the `fetch_url` tool.
+///
+/// Entries match the host itself and its subdomains. An empty allowlist
+/// allows every host not on the denylist; the denylist always wins.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FetchUrlSettings {
+    pub allowed_hosts: Vec<String>,
+    pub denied_hosts: Vec<String>,
+}
+
+/// OS-level sandbox for the `shell` tool, applied when the tool is built with
+/// sandbox support.
+///
+/// Writes are limited to the command's working directory, the temp
+/// directory, and `writable_roots`; relative roots resolve against the
+/// working directory.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ShellSandboxSettings {
+    pub enabled: bool,
+    pub writable_roots: Vec<PathBuf>,
+    pub allow_network: bool,
+}
+
+/// Extra rules for transcript redaction, applied after the built-in ones.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RedactionSettings {
+    pub rules: Vec<RedactionRuleSettings>,
+}
+
+/// Settings for `roci-agent serve`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ServeSettings {
+    /// Bearer token clients must present, used when neither `--token` nor
+    /// `ROCI_SERVE_TOKEN` is set.
+    pub token: Option<String>,
+}
+
+/// A named regex whose matches are replaced with `[REDACTED:<name>:<n>]`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RedactionRuleSettings {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// Model-facing changes to one built-in tool; the tool still runs the same
+/// executor.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct ToolOverrideSettings {
+    /// Replaces the tool description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Replaces parameter descriptions, keyed by dotted field path
+    /// (`plan.step` for a field of the `plan` array's items).
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    /// Name the tool is exposed under instead of its built-in name.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ResourceSettingsLoader {
     directories: ResourceDirectories,
@@ -128,16 +257,22 @@ impl ResourceSettingsLoader {
         let resolved_dirs = self.directories.resolve_with_home(cwd, home_dir)?;
 
         let mut merged = Value::Object(Default::default());
-
-        if let Some(global_value) = load_scope_settings(&resolved_dirs.agent_dir, home_dir)? {
-            deep_merge(&mut merged, global_value);
-        }
-
-        if let Some(project_value) = load_scope_settings(&resolved_dirs.project_dir, home_dir)? {
-            deep_merge(&mut merged, project_value);
+        let mut diagnostics = Vec::new();
+
+        for scope_dir in [&resolved_dirs.agent_dir, &resolved_dirs.project_dir] {
+            if let Some(value) = load_scope_settings(scope_dir, home_dir)? {
+                diagnostics.extend(unknown_key_diagnostics(
+                    &value,
+                    &scope_dir.join(SETTINGS_FILE_NAME),
+                ));
+                deep_merge(&mut merged, value);
+            }
         }
 
         let parsed: ResourceSettingsSerde = serde_json::from_value(merged)?;
+        for rule in parsed.chat_formats.values().flatten() {
+            rule.format.validate()?;
+        }
 
         Ok(ResourceSettings {
             prompts: parsed.prompts.into_iter().map(PathBuf::from).collect(),
@@ -145,6 +280,19 @@ impl ResourceSettingsLoader {
             no_context_files: parsed.no_context_files,
             compaction: parsed.compaction.into(),
             branch_summary: parsed.branch_summary.into(),
+            fetch_url: parsed.fetch_url.into(),
+            shell_sandbox: parsed.shell_sandbox.into(),
+            redaction: parsed.redaction.into(),
+            serve: parsed.serve.into(),
+            tool_overrides: parsed.tool_overrides,
+            chat_formats: parsed.chat_formats,
+            defaults: parsed.defaults.try_into()?,
+            presets: parsed
+                .presets
+                .into_iter()
+                .map(|(name, preset)| preset.into_preset(name))
+                .collect(),
+            diagnostics,
         })
     }
 }
@@ -161,6 +309,22 @@ struct ResourceSettingsSerde {
     compaction: CompactionSettingsSerde,
     #[serde(default)]
     branch_summary: BranchSummarySettingsSerde,
+    #[serde(default)]
+    fetch_url: FetchUrlSettingsSerde,
+    #[serde(default)]
+    shell_sandbox: ShellSandboxSettingsSerde,
+    #[serde(default)]
+    redaction: RedactionSettingsSerde,
+    #[serde(default)]
+    serve: ServeSettingsSerde,
+    #[serde(default)]
+    tool_overrides: BTreeMap<String, ToolOverrideSettings>,
+    #[serde(default)]
+    chat_formats: BTreeMap<String, Vec<ChatFormatRule>>,
+    #[serde(default)]
+    defaults: RunDefaultsSettingsSerde,
+    #[serde(default)]
+    presets: BTreeMap<String, PresetSerde>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -223,6 +387,152 @@ impl From<BranchSummarySettingsSerde> for BranchSummarySettings {
     }
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct FetchUrlSettingsSerde {
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+    #[serde(default)]
+    denied_hosts: Vec<String>,
+}
+
+impl From<FetchUrlSettingsSerde> for FetchUrlSettings {
+    fn from(value: FetchUrlSettingsSerde) -> Self {
+        Self {
+            allowed_hosts: value.allowed_hosts,
+            denied_hosts: value.denied_hosts,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ShellSandboxSettingsSerde {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    writable_roots: Vec<String>,
+    #[serde(default)]
+    allow_network: bool,
+}
+
+impl From<ShellSandboxSettingsSerde> for ShellSandboxSettings {
+    fn from(value: ShellSandboxSettingsSerde) -> Self {
+        Self {
+            enabled: value.enabled,
+            writable_roots: value
+                .writable_roots
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            allow_network: value.allow_network,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RedactionSettingsSerde {
+    #[serde(default)]
+    rules: Vec<RedactionRuleSettings>,
+}
+
+impl From<RedactionSettingsSerde> for RedactionSettings {
+    fn from(value: RedactionSettingsSerde) -> Self {
+        Self { rules: value.rules }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ServeSettingsSerde {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+impl From<ServeSettingsSerde> for ServeSettings {
+    fn from(value: ServeSettingsSerde) -> Self {
+        Self { token: value.token }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RunDefaultsSettingsSerde {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    temperature: Option<f64>,
+    #[serde(default)]
+    approval: Option<ApprovalPreset>,
+    #[serde(default)]
+    max_iterations: Option<usize>,
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    system_prompt_file: Option<String>,
+}
+
+impl TryFrom<RunDefaultsSettingsSerde> for RunDefaultsSettings {
+    type Error = RociError;
+
+    fn try_from(value: RunDefaultsSettingsSerde) -> Result<Self, Self::Error> {
+        let model = value
+            .model
+            .map(|model| {
+                model.parse::<LanguageModel>().map_err(|_| {
+                    RociError::Configuration(format!(
+                        "defaults.model '{model}' must use provider:model (e.g. openai:gpt-4o)"
+                    ))
+                })
+            })
+            .transpose()?;
+        let system_prompt_file = value.system_prompt_file.map(PathBuf::from);
+        let system_prompt = system_prompt_file
+            .as_ref()
+            .map(|path| {
+                fs::read_to_string(path).map_err(|err| {
+                    RociError::Configuration(format!(
+                        "defaults.system_prompt_file {}: {err}",
+                        path.display()
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            model,
+            temperature: value.temperature,
+            approval: value.approval,
+            max_iterations: value.max_iterations,
+            tools: value.tools,
+            system_prompt_file,
+            system_prompt,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PresetSerde {
+    #[serde(default)]
+    settings: GenerationSettings,
+    #[serde(default)]
+    system_prompt: Option<String>,
+    #[serde(default)]
+    response_format: Option<ResponseFormat>,
+    #[serde(default)]
+    schema_file: Option<String>,
+    #[serde(default)]
+    transforms: Vec<String>,
+}
+
+impl PresetSerde {
+    fn into_preset(self, name: String) -> Preset {
+        Preset {
+            name,
+            settings: self.settings,
+            system_prompt: self.system_prompt,
+            response_format: self.response_format,
+            schema_file: self.schema_file.map(PathBuf::from),
+            transforms: self.transforms,
+        }
+    }
+}
+
 const fn default_true() -> bool {
     true
 }
@@ -255,10 +565,80 @@ fn load_scope_settings(
     }
 
     resolve_prompts_in_scope(&mut value, scope_dir, home_dir)?;
+    resolve_system_prompt_file_in_scope(&mut value, scope_dir, home_dir)?;
+    resolve_preset_schema_files_in_scope(&mut value, scope_dir, home_dir)?;
 
     Ok(Some(value))
 }
 
+fn unknown_key_diagnostics(value: &Value, path: &Path) -> Vec<ResourceDiagnostic> {
+    let unknown =
+        |object: Option<&serde_json::Map<String, Value>>, known: &[&str], prefix: &str| {
+            object
+                .into_iter()
+                .flat_map(|object| object.keys())
+                .filter(|key| !known.contains(&key.as_str()))
+                .map(move |key| ResourceDiagnostic {
+                    path: path.to_path_buf(),
+                    message: format!("unknown setting '{prefix}{key}' ignored"),
+                })
+                .collect::<Vec<_>>()
+        };
+    let mut diagnostics = unknown(value.as_object(), &KNOWN_KEYS, "");
+    diagnostics.extend(unknown(
+        value.get("defaults").and_then(Value::as_object),
+        &KNOWN_DEFAULTS_KEYS,
+        "defaults.",
+    ));
+    diagnostics
+}
+
+fn resolve_system_prompt_file_in_scope(
+    value: &mut Value,
+    scope_dir: &Path,
+    home_dir: Option<&Path>,
+) -> Result<(), RociError> {
+    let Some(file_value) = value
+        .get_mut("defaults")
+        .and_then(|defaults| defaults.get_mut("system_prompt_file"))
+    else {
+        return Ok(());
+    };
+    let Some(file) = file_value.as_str() else {
+        return Err(RociError::Configuration(format!(
+            "defaults.system_prompt_file in {} must be a string",
+            scope_dir.join(SETTINGS_FILE_NAME).display()
+        )));
+    };
+    let resolved = resolve_path(file, scope_dir, home_dir)?;
+    *file_value = Value::String(resolved.to_string_lossy().into_owned());
+    Ok(())
+}
+
+fn resolve_preset_schema_files_in_scope(
+    value: &mut Value,
+    scope_dir: &Path,
+    home_dir: Option<&Path>,
+) -> Result<(), RociError> {
+    let Some(presets) = value.get_mut("presets").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    for (name, preset) in presets.iter_mut() {
+        let Some(file_value) = preset.get_mut("schema_file") else {
+            continue;
+        };
+        let Some(file) = file_value.as_str() else {
+            return Err(RociError::Configuration(format!(
+                "presets.{name}.schema_file in {} must be a string",
+                scope_dir.join(SETTINGS_FILE_NAME).display()
+            )));
+        };
+        let resolved = resolve_path(file, scope_dir, home_dir)?;
+        *file_value = Value::String(resolved.to_string_lossy().into_owned());
+    }
+    Ok(())
+}
+
 fn resolve_prompts_in_scope(
     value: &mut Value,
     scope_dir: &Path,
@@ -349,7 +729,10 @@ mod tests {
 
     use tempfile::tempdir;
 
-    use super::{ResourceDirectories, ResourceSettingsLoader};
+    use super::{
+        ApprovalPreset, RedactionRuleSettings, ResourceDirectories, ResourceSettingsLoader,
+        ShellSandboxSettings,
+    };
 
     #[test]
     fn default_directories_resolve_to_expected_global_and_project_paths() {
@@ -563,4 +946,394 @@ mod tests {
         assert_eq!(settings.branch_summary.reserve_tokens, 16_384);
         assert_eq!(settings.branch_summary.model, None);
     }
+
+    #[test]
+    fn project_fetch_url_host_lists_replace_global_lists() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{ "fetch_url": { "allowed_hosts": ["docs.rs"], "denied_hosts": ["internal.example"] } }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "fetch_url": { "allowed_hosts": ["github.com", "docs.rs"] } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        assert_eq!(
+            settings.fetch_url.allowed_hosts,
+            vec!["github.com", "docs.rs"]
+        );
+        assert_eq!(settings.fetch_url.denied_hosts, vec!["internal.example"]);
+    }
+
+    #[test]
+    fn tool_overrides_merge_per_tool_and_field() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{ "tool_overrides": { "shell": { "name": "bash", "parameters": { "command": "A bash command" } } } }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "tool_overrides": {
+                "shell": { "description": "Run a command in the repo" },
+                "update_plan": { "parameters": { "plan.step": "One short step" } }
+            } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        let shell = &settings.tool_overrides["shell"];
+        assert_eq!(shell.name.as_deref(), Some("bash"));
+        assert_eq!(
+            shell.description.as_deref(),
+            Some("Run a command in the repo")
+        );
+        assert_eq!(shell.parameters["command"], "A bash command");
+        assert_eq!(
+            settings.tool_overrides["update_plan"].parameters["plan.step"],
+            "One short step"
+        );
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn chat_formats_load_per_provider_and_reject_tool_renames() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "chat_formats": { "ollama": [
+                { "model": "gemma*", "system": "system_to_first_user" },
+                { "model": "*", "role_names": { "assistant": "model" } }
+            ] } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        let rules = &settings.chat_formats["ollama"];
+        assert_eq!(rules.len(), 2);
+        assert!(rules[0].matches("gemma2:9b"));
+        assert_eq!(
+            rules[0].format.system,
+            crate::config::SystemMessageStrategy::SystemToFirstUser
+        );
+        assert_eq!(rules[1].format.role_names["assistant"], "model");
+        assert!(settings.diagnostics.is_empty());
+
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "chat_formats": { "ollama": [ { "model": "*", "role_names": { "tool": "function" } } ] } }"#,
+        )
+        .expect("project settings should be written");
+        let error = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect_err("tool renames are rejected");
+        assert!(error.to_string().contains("'tool'"), "{error}");
+    }
+
+    #[test]
+    fn shell_sandbox_settings_merge_per_field() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{ "shell_sandbox": { "enabled": true, "writable_roots": ["/var/cache/roci"] } }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "shell_sandbox": { "allow_network": true } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        assert_eq!(
+            settings.shell_sandbox,
+            ShellSandboxSettings {
+                enabled: true,
+                writable_roots: vec![PathBuf::from("/var/cache/roci")],
+                allow_network: true,
+            }
+        );
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn project_redaction_rules_replace_global_rules() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{ "redaction": { "rules": [{ "name": "ticket", "pattern": "TICKET-\\d+" }] } }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "redaction": { "rules": [{ "name": "internal-token", "pattern": "itk_[a-z0-9]+" }] } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        assert_eq!(
+            settings.redaction.rules,
+            vec![RedactionRuleSettings {
+                name: "internal-token".to_string(),
+                pattern: "itk_[a-z0-9]+".to_string(),
+            }]
+        );
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn project_serve_token_overrides_global_token() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{ "serve": { "token": "global-token" } }"#,
+        )
+        .expect("global settings should be written");
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+        assert_eq!(settings.serve.token.as_deref(), Some("global-token"));
+
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "serve": { "token": "project-token" } }"#,
+        )
+        .expect("project settings should be written");
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+        assert_eq!(settings.serve.token.as_deref(), Some("project-token"));
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn project_defaults_override_global_defaults_per_key() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(global_dir.join("SYSTEM.md"), "global system").expect("prompt written");
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{
+                "defaults": {
+                    "model": "openai:gpt-4o",
+                    "temperature": 0.7,
+                    "approval": "always",
+                    "tools": ["read_file", "grep", "shell"],
+                    "system_prompt_file": "SYSTEM.md"
+                }
+            }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{
+                "defaults": {
+                    "model": "anthropic:claude-sonnet-4-5",
+                    "max_iterations": 40,
+                    "tools": ["read_file"]
+                }
+            }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+        let defaults = settings.defaults;
+
+        assert_eq!(
+            defaults.model,
+            Some("anthropic:claude-sonnet-4-5".parse().unwrap())
+        );
+        assert_eq!(defaults.temperature, Some(0.7));
+        assert_eq!(defaults.approval, Some(ApprovalPreset::Always));
+        assert_eq!(defaults.max_iterations, Some(40));
+        assert_eq!(defaults.tools, Some(vec!["read_file".to_string()]));
+        assert_eq!(
+            defaults.system_prompt_file,
+            Some(global_dir.join("SYSTEM.md"))
+        );
+        assert_eq!(defaults.system_prompt.as_deref(), Some("global system"));
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn presets_merge_per_field_and_resolve_schema_files_per_scope() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let global_dir = home_dir.join(".roci/agent");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&global_dir).expect("global dir should be created");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+
+        fs::write(
+            global_dir.join("settings.json"),
+            r#"{
+                "presets": {
+                    "terse": {
+                        "settings": { "temperature": 0.2, "max_tokens": 256 },
+                        "system_prompt": "Be terse.",
+                        "schema_file": "schemas/answer.json"
+                    },
+                    "creative": { "settings": { "temperature": 1.0 } }
+                }
+            }"#,
+        )
+        .expect("global settings should be written");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{
+                "presets": {
+                    "terse": {
+                        "settings": { "temperature": 0.0 },
+                        "transforms": ["redact"]
+                    }
+                }
+            }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("settings should load");
+
+        let names = settings
+            .presets
+            .iter()
+            .map(|preset| preset.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["creative", "terse"]);
+        let terse = &settings.presets[1];
+        assert_eq!(terse.settings.temperature, Some(0.0));
+        assert_eq!(terse.settings.max_tokens, Some(256));
+        assert_eq!(terse.system_prompt.as_deref(), Some("Be terse."));
+        assert_eq!(
+            terse.schema_file,
+            Some(global_dir.join("schemas/answer.json"))
+        );
+        assert_eq!(terse.transforms, vec!["redact".to_string()]);
+        assert!(settings.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_not_rejected() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "themes": "dark", "defaults": { "temperature": 0.2, "top_k": 5 } }"#,
+        )
+        .expect("project settings should be written");
+
+        let settings = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .expect("unknown keys should not fail loading");
+
+        assert_eq!(settings.defaults.temperature, Some(0.2));
+        let messages = settings
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "unknown setting 'themes' ignored",
+                "unknown setting 'defaults.top_k' ignored",
+            ]
+        );
+        assert!(settings
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.path == project_dir.join("settings.json")));
+    }
+
+    #[test]
+    fn invalid_default_model_is_a_configuration_error() {
+        let temp = tempdir().expect("temp dir should be created");
+        let home_dir = temp.path().join("home");
+        let cwd = temp.path().join("workspace");
+        let project_dir = cwd.join(".roci");
+        fs::create_dir_all(&project_dir).expect("project dir should be created");
+        fs::write(
+            project_dir.join("settings.json"),
+            r#"{ "defaults": { "model": "gpt-4o" } }"#,
+        )
+        .expect("project settings should be written");
+
+        let err = ResourceSettingsLoader::new()
+            .load_with_home(&cwd, Some(&home_dir))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("defaults.model"));
+    }
 }
@@ -0,0 +1,722 @@
+//! Deterministic system prompt assembly.
+//!
+//! [`SystemPromptComposer`] collects sections from every prompt source (base
+//! prompt, resource appends, project context files, skills, MCP server
+//! instructions, tool guidance) and renders them under a single ordering,
+//! deduplication, and token-budget policy. The agent runtime and the CLI both
+//! render through [`compose_agent_system_prompt`], so a previewed prompt is
+//! the prompt a run sends.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::context::tokens::estimate_text_tokens;
+use crate::resource::ResourceBundle;
+use crate::skills::{format_skills_for_prompt, Skill};
+use crate::tools::tool::Tool;
+use crate::types::{MessageOrigin, ModelMessage};
+
+/// Appended to a section cut down to its token budget.
+const TRIM_MARKER: &str = "[section trimmed to fit its token budget]";
+
+/// Source of a system prompt section. Declaration order is render order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PromptSectionKind {
+    /// The base system prompt.
+    Base,
+    /// An appended system prompt discovered with resources.
+    ResourceAppend,
+    /// One project context file, rendered under `## Project Context`.
+    ProjectContext,
+    /// The skills catalog.
+    Skills,
+    /// Instructions from one MCP server.
+    McpInstructions,
+    /// Usage guidance from one tool's [`Tool::prompt_guidance`].
+    ToolGuidance,
+}
+
+impl PromptSectionKind {
+    /// Stable snake_case name used in diagnostics.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::ResourceAppend => "resource_append",
+            Self::ProjectContext => "project_context",
+            Self::Skills => "skills",
+            Self::McpInstructions => "mcp_instructions",
+            Self::ToolGuidance => "tool_guidance",
+        }
+    }
+
+    /// Heading rendered once, before the first section of this kind.
+    fn heading(self) -> Option<&'static str> {
+        match self {
+            Self::ProjectContext => Some("## Project Context"),
+            Self::McpInstructions => Some("MCP server instructions:"),
+            Self::ToolGuidance => Some("## Tool Guidance"),
+            Self::Base | Self::ResourceAppend | Self::Skills => None,
+        }
+    }
+
+    fn render_body(self, label: &str, text: &str) -> String {
+        match self {
+            Self::ProjectContext | Self::ToolGuidance => format!("### {label}\n{text}"),
+            Self::McpInstructions => format!("[server:{label}]\n{text}"),
+            Self::Base | Self::ResourceAppend | Self::Skills => text.to_string(),
+        }
+    }
+
+    fn origin(self, label: &str) -> MessageOrigin {
+        let section = match self {
+            Self::ProjectContext => {
+                return MessageOrigin::ContextFile {
+                    path: PathBuf::from(label),
+                }
+            }
+            Self::Skills => return MessageOrigin::Skills,
+            Self::Base => "base",
+            Self::ResourceAppend => "append",
+            Self::McpInstructions => "mcp",
+            Self::ToolGuidance => "tool_guidance",
+        };
+        MessageOrigin::SystemPrompt {
+            section: section.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PromptSectionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One registered system prompt section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptSection {
+    pub kind: PromptSectionKind,
+    /// Identifies the section within its kind: context file path, MCP server
+    /// label, or tool name.
+    pub label: String,
+    /// Order within the kind; lower renders first, ties keep registration order.
+    pub priority: i32,
+    pub text: String,
+    /// Token budget for `text`; `None` falls back to the composer's budget for
+    /// the kind, if any.
+    pub max_tokens: Option<usize>,
+}
+
+impl PromptSection {
+    /// Create a section with default priority and no budget.
+    pub fn new(kind: PromptSectionKind, label: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            label: label.into(),
+            priority: 0,
+            text: text.into(),
+            max_tokens: None,
+        }
+    }
+
+    /// Set the order within the section's kind.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the token budget for the section text.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+/// Something the composer changed or dropped while rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptSectionDiagnostic {
+    /// The section exceeded its token budget and was cut.
+    Trimmed {
+        kind: PromptSectionKind,
+        label: String,
+        budget_tokens: usize,
+        original_tokens: usize,
+    },
+    /// The section rendered identically to an earlier one and was dropped.
+    Duplicate {
+        kind: PromptSectionKind,
+        label: String,
+        duplicate_of: String,
+    },
+}
+
+impl fmt::Display for PromptSectionDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trimmed {
+                kind,
+                label,
+                budget_tokens,
+                original_tokens,
+            } => write!(
+                f,
+                "{kind} section `{label}` trimmed from ~{original_tokens} to {budget_tokens} tokens"
+            ),
+            Self::Duplicate {
+                kind,
+                label,
+                duplicate_of,
+            } => write!(
+                f,
+                "{kind} section `{label}` dropped as a duplicate of `{duplicate_of}`"
+            ),
+        }
+    }
+}
+
+/// A rendered section, including any heading it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposedSection {
+    pub kind: PromptSectionKind,
+    pub label: String,
+    pub text: String,
+}
+
+impl ComposedSection {
+    /// Origin-tagged system message for this section.
+    pub fn message(&self) -> ModelMessage {
+        ModelMessage::system(self.text.clone()).with_origin(self.kind.origin(&self.label))
+    }
+}
+
+/// Output of [`SystemPromptComposer::compose`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposedSystemPrompt {
+    pub sections: Vec<ComposedSection>,
+    pub diagnostics: Vec<PromptSectionDiagnostic>,
+}
+
+impl ComposedSystemPrompt {
+    /// The system prompt text: sections separated by blank lines.
+    pub fn render(&self) -> Option<String> {
+        if self.sections.is_empty() {
+            return None;
+        }
+        Some(
+            self.sections
+                .iter()
+                .map(|section| section.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )
+    }
+
+    /// One origin-tagged system message per section, in render order.
+    pub fn messages(&self) -> Vec<ModelMessage> {
+        self.sections.iter().map(ComposedSection::message).collect()
+    }
+}
+
+/// Builder that collects system prompt sections and renders them
+/// deterministically.
+///
+/// Sections render grouped by [`PromptSectionKind`], then by priority, then in
+/// registration order. Text is trimmed, empty sections are skipped, and a
+/// section whose rendered body repeats an earlier one is dropped with a
+/// [`PromptSectionDiagnostic::Duplicate`].
+#[derive(Debug, Clone, Default)]
+pub struct SystemPromptComposer {
+    sections: Vec<PromptSection>,
+    kind_budgets: BTreeMap<PromptSectionKind, usize>,
+}
+
+impl SystemPromptComposer {
+    /// Create an empty composer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register one section.
+    pub fn with_section(mut self, section: PromptSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Register several sections.
+    pub fn with_sections(mut self, sections: impl IntoIterator<Item = PromptSection>) -> Self {
+        self.sections.extend(sections);
+        self
+    }
+
+    /// Register the base prompt, if any.
+    pub fn with_base(self, base: Option<String>) -> Self {
+        match base {
+            Some(base) => {
+                self.with_section(PromptSection::new(PromptSectionKind::Base, "base", base))
+            }
+            None => self,
+        }
+    }
+
+    /// Register resource appends, project context files, and skills.
+    ///
+    /// The resource `SYSTEM.md` is not registered: callers choose between it
+    /// and their own base prompt.
+    pub fn with_resources(mut self, resources: &ResourceBundle) -> Self {
+        for append in &resources.context.append_system_prompts {
+            self.sections.push(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "append",
+                append.clone(),
+            ));
+        }
+        for file in &resources.context.context_files {
+            self.sections.push(PromptSection::new(
+                PromptSectionKind::ProjectContext,
+                file.path.display().to_string(),
+                file.content.clone(),
+            ));
+        }
+        self.with_skills(&resources.skills.skills)
+    }
+
+    /// Register the skills catalog for model-invocable skills.
+    pub fn with_skills(self, skills: &[Skill]) -> Self {
+        let catalog = format_skills_for_prompt(skills);
+        self.with_section(PromptSection::new(
+            PromptSectionKind::Skills,
+            "skills",
+            catalog,
+        ))
+    }
+
+    /// Register MCP server instructions, ordered by server id.
+    #[cfg(feature = "mcp")]
+    pub fn with_mcp_instructions(
+        mut self,
+        instructions: &[crate::mcp::MCPInstructionSource],
+    ) -> Self {
+        let mut sources = instructions.iter().collect::<Vec<_>>();
+        sources.sort_by(|left, right| left.server.id.cmp(&right.server.id));
+        for source in sources {
+            self.sections.push(PromptSection::new(
+                PromptSectionKind::McpInstructions,
+                source.display_label(),
+                source.instructions.clone(),
+            ));
+        }
+        self
+    }
+
+    /// Register [`Tool::prompt_guidance`] from each tool, ordered by tool name.
+    pub fn with_tool_guidance(mut self, tools: &[Arc<dyn Tool>]) -> Self {
+        let mut guidance = tools
+            .iter()
+            .filter_map(|tool| Some((tool.name().to_string(), tool.prompt_guidance()?)))
+            .collect::<Vec<_>>();
+        guidance.sort_by(|left, right| left.0.cmp(&right.0));
+        for (name, text) in guidance {
+            self.sections.push(PromptSection::new(
+                PromptSectionKind::ToolGuidance,
+                name,
+                text,
+            ));
+        }
+        self
+    }
+
+    /// Budget applied to every section of `kind` that has no budget of its own.
+    pub fn with_kind_budget(mut self, kind: PromptSectionKind, max_tokens: usize) -> Self {
+        self.kind_budgets.insert(kind, max_tokens);
+        self
+    }
+
+    /// Registered sections, in registration order.
+    pub fn sections(&self) -> &[PromptSection] {
+        &self.sections
+    }
+
+    /// Consume the composer, returning its registered sections.
+    pub fn into_sections(self) -> Vec<PromptSection> {
+        self.sections
+    }
+
+    /// Order, deduplicate, budget, and render the registered sections.
+    pub fn compose(&self) -> ComposedSystemPrompt {
+        let mut ordered = self.sections.iter().collect::<Vec<_>>();
+        ordered.sort_by_key(|section| (section.kind, section.priority));
+
+        let mut composed = ComposedSystemPrompt::default();
+        let mut seen: HashMap<String, String> = HashMap::new();
+        let mut headed = HashSet::new();
+        for section in ordered {
+            let text = section.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let body = section.kind.render_body(&section.label, text);
+            if let Some(first) = seen.get(&body) {
+                composed
+                    .diagnostics
+                    .push(PromptSectionDiagnostic::Duplicate {
+                        kind: section.kind,
+                        label: section.label.clone(),
+                        duplicate_of: first.clone(),
+                    });
+                continue;
+            }
+            seen.insert(body.clone(), section.label.clone());
+
+            let budget = section
+                .max_tokens
+                .or_else(|| self.kind_budgets.get(&section.kind).copied());
+            let body = match budget.and_then(|budget| trim_to_budget(text, budget)) {
+                Some(trimmed) => {
+                    composed.diagnostics.push(PromptSectionDiagnostic::Trimmed {
+                        kind: section.kind,
+                        label: section.label.clone(),
+                        budget_tokens: budget.unwrap_or_default(),
+                        original_tokens: estimate_text_tokens(text),
+                    });
+                    section.kind.render_body(&section.label, &trimmed)
+                }
+                None => body,
+            };
+
+            let mut rendered = String::new();
+            if let Some(heading) = section.kind.heading() {
+                if headed.insert(section.kind) {
+                    rendered.push_str(heading);
+                    rendered.push_str("\n\n");
+                }
+            }
+            rendered.push_str(&body);
+            composed.sections.push(ComposedSection {
+                kind: section.kind,
+                label: section.label.clone(),
+                text: rendered,
+            });
+        }
+        composed
+    }
+}
+
+/// Compose the system prompt an agent run sends: the base prompt, the
+/// configured sections, and guidance from the run's tools.
+pub fn compose_agent_system_prompt(
+    base: Option<String>,
+    sections: &[PromptSection],
+    tools: &[Arc<dyn Tool>],
+) -> ComposedSystemPrompt {
+    SystemPromptComposer::new()
+        .with_base(base)
+        .with_sections(sections.iter().cloned())
+        .with_tool_guidance(tools)
+        .compose()
+}
+
+/// Cut `text` to the longest prefix within `max_tokens`, followed by
+/// [`TRIM_MARKER`]. Returns `None` when the text already fits.
+fn trim_to_budget(text: &str, max_tokens: usize) -> Option<String> {
+    if estimate_text_tokens(text) <= max_tokens {
+        return None;
+    }
+    let boundaries = text
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(text.len()))
+        .collect::<Vec<_>>();
+    let (mut low, mut high) = (0, boundaries.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if estimate_text_tokens(&text[..boundaries[mid]]) <= max_tokens {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    let kept = text[..boundaries[low]].trim_end();
+    if kept.is_empty() {
+        return Some(TRIM_MARKER.to_string());
+    }
+    Some(format!("{kept}\n{TRIM_MARKER}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::tools::{AgentTool, AgentToolParameters};
+
+    fn guided_tool(name: &str, guidance: &str) -> Arc<dyn Tool> {
+        Arc::new(
+            AgentTool::new(
+                name,
+                format!("{name} tool"),
+                AgentToolParameters::empty(),
+                |_args, _ctx| async { Ok(serde_json::Value::Null) },
+            )
+            .with_prompt_guidance(guidance),
+        )
+    }
+
+    fn plain_tool(name: &str) -> Arc<dyn Tool> {
+        Arc::new(AgentTool::new(
+            name,
+            format!("{name} tool"),
+            AgentToolParameters::empty(),
+            |_args, _ctx| async { Ok(serde_json::Value::Null) },
+        ))
+    }
+
+    fn skill(name: &str) -> Skill {
+        Skill {
+            name: name.to_string(),
+            description: format!("{name} skill"),
+            file_path: PathBuf::from(format!("/skills/{name}/SKILL.md")),
+            base_dir: PathBuf::from(format!("/skills/{name}")),
+            source: crate::skills::SkillSource::Explicit,
+            disable_model_invocation: false,
+        }
+    }
+
+    #[test]
+    fn composes_representative_prompt_golden() {
+        let composed = SystemPromptComposer::new()
+            .with_section(PromptSection::new(
+                PromptSectionKind::McpInstructions,
+                "tracker",
+                "File issues with labels.",
+            ))
+            .with_section(PromptSection::new(
+                PromptSectionKind::McpInstructions,
+                "docs",
+                "Search docs before answering.\n",
+            ))
+            .with_tool_guidance(&[
+                guided_tool("write_file", "Prefer small, focused edits."),
+                plain_tool("list_directory"),
+                guided_tool("shell", "Run tests after changes."),
+            ])
+            .with_skills(&[skill("review")])
+            .with_section(PromptSection::new(
+                PromptSectionKind::ProjectContext,
+                "/repo/AGENTS.md",
+                "Use cargo nextest.\n",
+            ))
+            .with_section(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "append",
+                "  Answer tersely.  ",
+            ))
+            .with_base(Some("You are a coding agent.".to_string()))
+            .with_section(PromptSection::new(
+                PromptSectionKind::ProjectContext,
+                "/repo/CLAUDE.md",
+                "Prefer small commits.",
+            ))
+            .compose();
+
+        let expected = "\
+You are a coding agent.
+
+Answer tersely.
+
+## Project Context
+
+### /repo/AGENTS.md
+Use cargo nextest.
+
+### /repo/CLAUDE.md
+Prefer small commits.
+
+The following skills provide specialized instructions for specific tasks.
+Use the read_file tool to load a skill's file when the task matches its description.
+When a skill file references a relative path, resolve it against the skill directory (parent of SKILL.md) and use that absolute path in tool commands.
+<available_skills>
+  <skill>
+    <name>review</name>
+    <description>review skill</description>
+    <location>/skills/review/SKILL.md</location>
+  </skill>
+</available_skills>
+
+MCP server instructions:
+
+[server:tracker]
+File issues with labels.
+
+[server:docs]
+Search docs before answering.
+
+## Tool Guidance
+
+### shell
+Run tests after changes.
+
+### write_file
+Prefer small, focused edits.";
+        assert_eq!(composed.render().as_deref(), Some(expected));
+        assert!(composed.diagnostics.is_empty());
+        assert_eq!(
+            composed
+                .messages()
+                .iter()
+                .map(|message| message.origin().cloned().expect("origin").to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "system:base",
+                "system:append",
+                "context:/repo/AGENTS.md",
+                "context:/repo/CLAUDE.md",
+                "skills",
+                "system:mcp",
+                "system:mcp",
+                "system:tool_guidance",
+                "system:tool_guidance",
+            ]
+        );
+    }
+
+    #[test]
+    fn priority_orders_within_a_kind_and_ties_keep_registration_order() {
+        let composed = SystemPromptComposer::new()
+            .with_section(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "a",
+                "first",
+            ))
+            .with_section(
+                PromptSection::new(PromptSectionKind::ResourceAppend, "b", "early")
+                    .with_priority(-1),
+            )
+            .with_section(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "c",
+                "second",
+            ))
+            .compose();
+
+        assert_eq!(
+            composed.render().as_deref(),
+            Some("early\n\nfirst\n\nsecond")
+        );
+    }
+
+    #[test]
+    fn duplicate_and_empty_sections_are_dropped_with_diagnostics() {
+        let composed = SystemPromptComposer::new()
+            .with_base(Some("Be careful.".to_string()))
+            .with_section(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "append",
+                "Be careful.\n",
+            ))
+            .with_section(PromptSection::new(
+                PromptSectionKind::ResourceAppend,
+                "blank",
+                "  \n",
+            ))
+            .with_section(PromptSection::new(
+                PromptSectionKind::McpInstructions,
+                "docs",
+                "Same text.",
+            ))
+            .with_section(PromptSection::new(
+                PromptSectionKind::McpInstructions,
+                "wiki",
+                "Same text.",
+            ))
+            .compose();
+
+        assert_eq!(composed.sections.len(), 3);
+        assert_eq!(
+            composed.diagnostics,
+            vec![PromptSectionDiagnostic::Duplicate {
+                kind: PromptSectionKind::ResourceAppend,
+                label: "append".to_string(),
+                duplicate_of: "base".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn sections_over_budget_are_trimmed_with_diagnostics() {
+        let long = "word ".repeat(200);
+        let composed = SystemPromptComposer::new()
+            .with_section(
+                PromptSection::new(PromptSectionKind::ProjectContext, "/repo/AGENTS.md", &long)
+                    .with_max_tokens(10),
+            )
+            .with_section(PromptSection::new(
+                PromptSectionKind::ToolGuidance,
+                "shell",
+                &long,
+            ))
+            .with_kind_budget(PromptSectionKind::ToolGuidance, 20)
+            .with_section(PromptSection::new(PromptSectionKind::Base, "base", "short"))
+            .with_kind_budget(PromptSectionKind::Base, 20)
+            .compose();
+
+        let context = composed.sections[1]
+            .text
+            .strip_prefix("## Project Context\n\n### /repo/AGENTS.md\n")
+            .expect("context heading");
+        let kept = context
+            .strip_suffix(TRIM_MARKER)
+            .expect("trim marker appended");
+        assert!(kept.starts_with("word"));
+        assert!(estimate_text_tokens(kept.trim_end()) <= 10);
+        assert_eq!(composed.sections[0].text, "short");
+        assert_eq!(
+            composed.diagnostics,
+            vec![
+                PromptSectionDiagnostic::Trimmed {
+                    kind: PromptSectionKind::ProjectContext,
+                    label: "/repo/AGENTS.md".to_string(),
+                    budget_tokens: 10,
+                    original_tokens: estimate_text_tokens(long.trim()),
+                },
+                PromptSectionDiagnostic::Trimmed {
+                    kind: PromptSectionKind::ToolGuidance,
+                    label: "shell".to_string(),
+                    budget_tokens: 20,
+                    original_tokens: estimate_text_tokens(long.trim()),
+                },
+            ]
+        );
+        assert_eq!(
+            composed.diagnostics[0].to_string(),
+            format!(
+                "project_context section `/repo/AGENTS.md` trimmed from ~{} to 10 tokens",
+                estimate_text_tokens(long.trim())
+            )
+        );
+    }
+
+    #[test]
+    fn agent_prompt_appends_tool_guidance_to_configured_sections() {
+        let sections = vec![PromptSection::new(
+            PromptSectionKind::ResourceAppend,
+            "append",
+            "Extra rules.",
+        )];
+        let composed = compose_agent_system_prompt(
+            Some("Base.".to_string()),
+            &sections,
+            &[
+                guided_tool("shell", "Quote paths."),
+                plain_tool("read_file"),
+            ],
+        );
+
+        assert_eq!(
+            composed.render().as_deref(),
+            Some("Base.\n\nExtra rules.\n\n## Tool Guidance\n\n### shell\nQuote paths.")
+        );
+        assert_eq!(compose_agent_system_prompt(None, &[], &[]).render(), None);
+    }
+}
@@ -338,7 +338,7 @@ fn contains_symlink_below_boundary(path: &Path, boundary_root: &Path) -> std::io
     Ok(false)
 }
 
-fn glob_matches(pattern: &str, value: &str) -> bool {
+pub(crate) fn glob_matches(pattern: &str, value: &str) -> bool {
     let pattern = pattern.as_bytes();
     let value = value.as_bytes();
     let mut dp = vec![vec![false; value.len() + 1]; pattern.len() + 1];
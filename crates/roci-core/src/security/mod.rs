@@ -1,3 +1,4 @@
 pub mod command;
 pub mod filesystem;
+pub mod pii;
 pub mod redaction;
@@ -0,0 +1,284 @@
+//! Redaction of personal data from stored assistant output.
+//!
+//! [`ResponseFilter`] rewrites text on its way into persisted messages; the
+//! live event stream is never filtered. [`PiiRedactor`] is the regex-based
+//! implementation shipped for emails, phone numbers, and card numbers.
+
+use regex::{Captures, Regex};
+
+use crate::error::RociError;
+
+/// Rewrites assistant (and optionally tool-result) text before it is stored.
+///
+/// Receives the full text of one message part and returns the version to
+/// persist. Closures `Fn(&str) -> String` implement it directly.
+pub trait ResponseFilter: Send + Sync {
+    fn filter(&self, text: &str) -> String;
+}
+
+impl<F> ResponseFilter for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn filter(&self, text: &str) -> String {
+        self(text)
+    }
+}
+
+/// Built-in PII categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CreditCard,
+}
+
+impl PiiKind {
+    fn default_replacement(self) -> &'static str {
+        match self {
+            Self::Email => "[REDACTED_EMAIL]",
+            Self::Phone => "[REDACTED_PHONE]",
+            Self::CreditCard => "[REDACTED_CARD]",
+        }
+    }
+}
+
+/// Regex-based PII redactor.
+///
+/// Patterns apply in order, each replacing its matches with its own token,
+/// so earlier patterns win where matches overlap. Card-number candidates
+/// must also pass a Luhn check, which keeps long IDs and timestamps intact.
+pub struct PiiRedactor {
+    patterns: Vec<PiiPattern>,
+}
+
+struct PiiPattern {
+    kind: Option<PiiKind>,
+    regex: Regex,
+    replacement: String,
+    validate: Option<fn(&str) -> bool>,
+}
+
+impl PiiRedactor {
+    /// Redactor without patterns; add them with [`Self::with_pattern`].
+    pub fn new() -> Self {
+        Self {
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Redactor for emails, card numbers, and phone numbers.
+    pub fn new_default() -> Self {
+        Self {
+            patterns: vec![
+                builtin(
+                    PiiKind::Email,
+                    r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+                    None,
+                ),
+                builtin(
+                    PiiKind::CreditCard,
+                    r"\b(?:\d[ -]?){12,18}\d\b",
+                    Some(passes_luhn),
+                ),
+                builtin(
+                    PiiKind::Phone,
+                    r"(?:\+\d{1,3}(?:[ .-]?\d{2,4}){2,4}|(?:\(\d{3}\) ?|\b\d{3}[ .-]?)\d{3}[ .-]?\d{4})\b",
+                    None,
+                ),
+            ],
+        }
+    }
+
+    /// Replace the token used for a built-in category.
+    #[must_use]
+    pub fn with_replacement(mut self, kind: PiiKind, replacement: impl Into<String>) -> Self {
+        let replacement = replacement.into();
+        for pattern in &mut self.patterns {
+            if pattern.kind == Some(kind) {
+                pattern.replacement = replacement.clone();
+            }
+        }
+        self
+    }
+
+    /// Drop a built-in category.
+    #[must_use]
+    pub fn without(mut self, kind: PiiKind) -> Self {
+        self.patterns.retain(|pattern| pattern.kind != Some(kind));
+        self
+    }
+
+    /// Append a custom pattern replaced by `replacement`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Configuration`] when `regex` does not compile.
+    pub fn with_pattern(
+        mut self,
+        regex: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self, RociError> {
+        let regex = Regex::new(regex)
+            .map_err(|err| RociError::Configuration(format!("invalid PII pattern: {err}")))?;
+        self.patterns.push(PiiPattern {
+            kind: None,
+            regex,
+            replacement: replacement.into(),
+            validate: None,
+        });
+        Ok(self)
+    }
+
+    pub fn redact(&self, input: &str) -> String {
+        let mut text = input.to_string();
+        for pattern in &self.patterns {
+            let redacted = pattern.regex.replace_all(&text, |captures: &Captures<'_>| {
+                let matched = &captures[0];
+                match pattern.validate {
+                    Some(validate) if !validate(matched) => matched.to_string(),
+                    _ => pattern.replacement.clone(),
+                }
+            });
+            text = redacted.into_owned();
+        }
+        text
+    }
+}
+
+impl Default for PiiRedactor {
+    fn default() -> Self {
+        Self::new_default()
+    }
+}
+
+impl std::fmt::Debug for PiiRedactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PiiRedactor")
+            .field(
+                "patterns",
+                &self
+                    .patterns
+                    .iter()
+                    .map(|pattern| pattern.regex.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl ResponseFilter for PiiRedactor {
+    fn filter(&self, text: &str) -> String {
+        self.redact(text)
+    }
+}
+
+fn builtin(kind: PiiKind, regex: &str, validate: Option<fn(&str) -> bool>) -> PiiPattern {
+    PiiPattern {
+        kind: Some(kind),
+        regex: Regex::new(regex).expect("default PII regex should compile"),
+        replacement: kind.default_replacement().to_string(),
+        validate,
+    }
+}
+
+fn passes_luhn(candidate: &str) -> bool {
+    let digits = candidate
+        .chars()
+        .filter_map(|ch| ch.to_digit(10))
+        .collect::<Vec<_>>();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_emails() {
+        let redacted =
+            PiiRedactor::new_default().redact("mail jane.doe+work@mail.example.co.uk today");
+
+        assert_eq!(redacted, "mail [REDACTED_EMAIL] today");
+    }
+
+    #[test]
+    fn redacts_common_phone_shapes() {
+        let redactor = PiiRedactor::new_default();
+
+        assert_eq!(
+            redactor.redact("call 555-123-4567 or (555) 123-4567"),
+            "call [REDACTED_PHONE] or [REDACTED_PHONE]"
+        );
+        assert_eq!(
+            redactor.redact("intl +44 20 7946 0958."),
+            "intl [REDACTED_PHONE]."
+        );
+    }
+
+    #[test]
+    fn redacts_luhn_valid_card_numbers_only() {
+        let redactor = PiiRedactor::new_default();
+
+        assert_eq!(
+            redactor.redact("card 4111 1111 1111 1111 and 4111-1111-1111-1111"),
+            "card [REDACTED_CARD] and [REDACTED_CARD]"
+        );
+        assert_eq!(
+            redactor.redact("ts 1700000000001 id 1234567890123456"),
+            "ts 1700000000001 id 1234567890123456"
+        );
+    }
+
+    #[test]
+    fn leaves_ordinary_text_and_dates_alone() {
+        let text = "Released 2024-01-15, version 1.2.3, see issue #4521.";
+
+        assert_eq!(PiiRedactor::new_default().redact(text), text);
+    }
+
+    #[test]
+    fn replacement_tokens_are_configurable_per_pattern() {
+        let redactor = PiiRedactor::new_default()
+            .with_replacement(PiiKind::Email, "<email>")
+            .without(PiiKind::Phone)
+            .with_pattern(r"\b\d{3}-\d{2}-\d{4}\b", "<ssn>")
+            .expect("valid pattern");
+
+        assert_eq!(
+            redactor.redact("a@b.io 555-123-4567 123-45-6789"),
+            "<email> 555-123-4567 <ssn>"
+        );
+    }
+
+    #[test]
+    fn invalid_custom_pattern_is_a_configuration_error() {
+        let err = PiiRedactor::new().with_pattern("(", "x").unwrap_err();
+
+        assert!(matches!(err, RociError::Configuration(_)));
+    }
+
+    #[test]
+    fn closures_are_response_filters() {
+        let filter = |text: &str| text.to_uppercase();
+
+        assert_eq!(ResponseFilter::filter(&filter, "abc"), "ABC");
+    }
+}
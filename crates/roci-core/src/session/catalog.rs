@@ -233,7 +233,11 @@ impl LocalSessionStore {
         Ok(metadata)
     }
 
-    fn validated_entry(&self, root: &Path, id: &SessionId) -> SessionResult<SessionCatalogEntry> {
+    pub(super) fn validated_entry(
+        &self,
+        root: &Path,
+        id: &SessionId,
+    ) -> SessionResult<SessionCatalogEntry> {
         let path = validate_session_directory(root, id)?;
         let metadata_path = path.join("metadata.json");
         let metadata_file_type = fs::symlink_metadata(&metadata_path)
@@ -473,6 +477,7 @@ mod tests {
             selected_model: Some(crate::models::LanguageModel::Known {
                 provider_key: "openai".into(),
                 model_id: "gpt-5".into(),
+                provider_options: Default::default(),
             }),
             reasoning_effort: Some(crate::types::ReasoningEffort::High),
             agent_profile: Some("builtin:developer".to_string()),
@@ -15,6 +15,10 @@ pub struct SessionMetadata {
     pub id: SessionId,
     /// Optional human-readable title.
     pub title: Option<String>,
+    /// Message count when `title` was last generated from the conversation.
+    /// `None` means the title was set by a host or user, or is unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_title_message_count: Option<usize>,
     /// Session creation timestamp.
     pub created_at: DateTime<Utc>,
     /// Last metadata update timestamp.
@@ -39,6 +43,8 @@ pub struct SessionMetadata {
 struct SessionMetadataWire {
     id: SessionId,
     title: Option<String>,
+    #[serde(default)]
+    generated_title_message_count: Option<usize>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     #[serde(default)]
@@ -64,6 +70,7 @@ impl<'de> Deserialize<'de> for SessionMetadata {
         Ok(Self {
             id: wire.id,
             title: wire.title,
+            generated_title_message_count: wire.generated_title_message_count,
             created_at: wire.created_at,
             updated_at: wire.updated_at,
             last_activity_at: wire.last_activity_at.unwrap_or(wire.updated_at),
@@ -85,6 +92,7 @@ impl SessionMetadata {
         Self {
             id,
             title: None,
+            generated_title_message_count: None,
             created_at: now,
             updated_at: now,
             last_activity_at: now,
@@ -101,6 +109,15 @@ impl SessionMetadata {
     #[cfg(feature = "agent")]
     pub(crate) fn set_title(&mut self, title: Option<String>) {
         self.title = title;
+        self.generated_title_message_count = None;
+        self.updated_at = Utc::now();
+    }
+
+    /// Store a conversation-generated title and the message count it covers.
+    #[cfg(feature = "agent")]
+    pub(crate) fn set_generated_title(&mut self, title: String, message_count: usize) {
+        self.title = Some(title);
+        self.generated_title_message_count = Some(message_count);
         self.updated_at = Utc::now();
     }
 
@@ -16,6 +16,8 @@ mod resources;
 mod snapshot;
 #[cfg(feature = "agent")]
 mod store;
+#[cfg(feature = "agent")]
+mod titles;
 
 #[cfg(feature = "agent")]
 pub use catalog::{SessionArchiveFilter, SessionCatalogEntry, SessionCatalogQuery};
@@ -36,6 +38,8 @@ pub use snapshot::{
 };
 #[cfg(feature = "agent")]
 pub use store::LocalSessionStore;
+#[cfg(feature = "agent")]
+pub use titles::SessionTitler;
 
 #[cfg(feature = "agent")]
 pub use recovery::{
@@ -181,6 +181,29 @@ impl LocalSessionStore {
         Ok(state)
     }
 
+    /// Create a session whose provider history starts as `messages`, such as
+    /// a conversation converted by [`crate::types::interop`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session already exists or files cannot be written.
+    pub async fn import_history(
+        &self,
+        options: CreateSessionOptions,
+        messages: Vec<crate::types::ModelMessage>,
+    ) -> SessionResult<SessionResumeState> {
+        let state = self.create(options).await?;
+        if messages.is_empty() {
+            return Ok(state);
+        }
+        let config = state.session_config.clone();
+        let ledger = LocalProviderLedger::open(config.conventions().provider_ledger_file())?;
+        ledger.append_compacted(state.default_thread_id, messages)?;
+        let lease = state.lease.clone();
+        self.load_state(config, state.default_thread_id, lease)
+            .await
+    }
+
     /// Export a tolerant recovery artifact for a damaged local session.
     ///
     /// # Errors
@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use super::{
+    locks::{ensure_store_root, SessionFileLock, SessionLockKind},
+    LocalSessionStore, SessionId, SessionMetadata, SessionResult,
+};
+use crate::agent::summarizer::{fallback_title, summarize_conversation, SummaryKind};
+use crate::provider::ModelProvider;
+use crate::types::ModelMessage;
+
+/// Generates durable session titles from conversation history.
+///
+/// Titles are generated on the first save of an untitled session and
+/// refreshed once the conversation has grown by `refresh_delta` messages.
+/// Titles set by a host or user are never replaced.
+#[derive(Clone)]
+pub struct SessionTitler {
+    model: Arc<dyn ModelProvider>,
+    refresh_delta: usize,
+}
+
+impl SessionTitler {
+    /// Default number of new messages before a generated title is refreshed.
+    pub const DEFAULT_REFRESH_DELTA: usize = 20;
+
+    /// Create a titler backed by `model`, usually a cheap model.
+    #[must_use]
+    pub fn new(model: Arc<dyn ModelProvider>) -> Self {
+        Self {
+            model,
+            refresh_delta: Self::DEFAULT_REFRESH_DELTA,
+        }
+    }
+
+    /// Set how many new messages trigger a refresh. `0` disables refreshes.
+    #[must_use]
+    pub fn with_refresh_delta(mut self, refresh_delta: usize) -> Self {
+        self.refresh_delta = refresh_delta;
+        self
+    }
+
+    fn should_generate(&self, metadata: &SessionMetadata, message_count: usize) -> bool {
+        match (&metadata.title, metadata.generated_title_message_count) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(_), Some(generated_at)) => {
+                self.refresh_delta > 0
+                    && message_count >= generated_at.saturating_add(self.refresh_delta)
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SessionTitler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionTitler")
+            .field("model", &self.model.model_id())
+            .field("refresh_delta", &self.refresh_delta)
+            .finish()
+    }
+}
+
+impl LocalSessionStore {
+    /// Generate or refresh a session title from `messages` when due.
+    ///
+    /// Call this when saving a session. Summarization failures never fail the
+    /// save: an untitled session gets a timestamp title and an existing title
+    /// is kept. A title changed concurrently by a host or user wins.
+    ///
+    /// Returns the updated metadata, or `None` when the title was left as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the session is missing, invalid, or cannot be written.
+    pub async fn refresh_generated_title(
+        &self,
+        id: &SessionId,
+        messages: &[ModelMessage],
+        titler: &SessionTitler,
+    ) -> SessionResult<Option<SessionMetadata>> {
+        let root = ensure_store_root(&self.root)?;
+        let current = self.validated_entry(&root, id)?.metadata;
+        let message_count = messages.len();
+        if !titler.should_generate(&current, message_count) {
+            return Ok(None);
+        }
+
+        let title =
+            match summarize_conversation(titler.model.as_ref(), messages, SummaryKind::Title).await
+            {
+                Ok(title) => title,
+                Err(error) if current.title.is_none() => {
+                    tracing::warn!(session_id = %id, %error, "session title generation failed");
+                    fallback_title(Utc::now())
+                }
+                Err(error) => {
+                    tracing::debug!(session_id = %id, %error, "keeping existing session title");
+                    return Ok(None);
+                }
+            };
+
+        let _metadata_lock =
+            SessionFileLock::acquire_blocking(&root, id, SessionLockKind::Metadata)?;
+        let entry = self.validated_entry(&root, id)?;
+        if entry.metadata.title != current.title {
+            return Ok(None);
+        }
+        let mut metadata = entry.metadata;
+        metadata.set_generated_title(title, message_count);
+        metadata.replace_existing_at(entry.path.join("metadata.json"))?;
+        Ok(Some(metadata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use futures::stream::BoxStream;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::error::RociError;
+    use crate::models::ModelCapabilities;
+    use crate::provider::{ProviderRequest, ProviderResponse};
+    use crate::session::CreateSessionOptions;
+    use crate::types::{TextStreamDelta, Usage};
+
+    struct TitleProvider {
+        replies: Mutex<Vec<Result<String, String>>>,
+    }
+
+    impl TitleProvider {
+        fn new(replies: Vec<Result<&str, &str>>) -> Arc<Self> {
+            Arc::new(Self {
+                replies: Mutex::new(
+                    replies
+                        .into_iter()
+                        .rev()
+                        .map(|reply| reply.map(str::to_string).map_err(str::to_string))
+                        .collect(),
+                ),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ModelProvider for TitleProvider {
+        fn provider_name(&self) -> &str {
+            "titles"
+        }
+
+        fn model_id(&self) -> &str {
+            "cheap"
+        }
+
+        fn capabilities(&self) -> &ModelCapabilities {
+            static CAPABILITIES: std::sync::OnceLock<ModelCapabilities> =
+                std::sync::OnceLock::new();
+            CAPABILITIES.get_or_init(ModelCapabilities::default)
+        }
+
+        async fn generate_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<ProviderResponse, RociError> {
+            let reply = self
+                .replies
+                .lock()
+                .unwrap()
+                .pop()
+                .expect("unexpected title request");
+            reply
+                .map(|text| ProviderResponse {
+                    text,
+                    usage: Usage::default(),
+                    tool_calls: vec![],
+                    finish_reason: None,
+                    thinking: vec![],
+                    metadata: Default::default(),
+                    refusal: None,
+                    images: Vec::new(),
+                    response_metadata: None,
+                })
+                .map_err(|message| RociError::Provider {
+                    provider: "titles".to_string(),
+                    message,
+                })
+        }
+
+        async fn stream_text(
+            &self,
+            _request: &ProviderRequest,
+        ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+            panic!("titles do not stream")
+        }
+    }
+
+    fn conversation(len: usize) -> Vec<ModelMessage> {
+        (0..len)
+            .map(|index| {
+                if index % 2 == 0 {
+                    ModelMessage::user(format!("question {index}"))
+                } else {
+                    ModelMessage::assistant(format!("answer {index}"))
+                }
+            })
+            .collect()
+    }
+
+    async fn create_session(store: &LocalSessionStore, title: Option<&str>) -> SessionId {
+        let id = SessionId::parse("session-titles").expect("session id should parse");
+        store
+            .create(CreateSessionOptions {
+                id: Some(id.clone()),
+                title: title.map(str::to_string),
+                ..CreateSessionOptions::default()
+            })
+            .await
+            .expect("session should create");
+        id
+    }
+
+    #[tokio::test]
+    async fn generates_title_on_first_save_and_refreshes_after_delta() {
+        let sessions = tempdir().expect("tempdir should be created");
+        let store = LocalSessionStore::new(sessions.path());
+        let id = create_session(&store, None).await;
+        let titler = SessionTitler::new(TitleProvider::new(vec![
+            Ok("Fix flaky auth test"),
+            Ok("Research Postgres migration"),
+        ]))
+        .with_refresh_delta(4);
+
+        let first = store
+            .refresh_generated_title(&id, &conversation(2), &titler)
+            .await
+            .expect("title should save")
+            .expect("untitled session should get a title");
+        assert_eq!(first.title.as_deref(), Some("Fix flaky auth test"));
+        assert_eq!(first.generated_title_message_count, Some(2));
+
+        let unchanged = store
+            .refresh_generated_title(&id, &conversation(5), &titler)
+            .await
+            .expect("title check should succeed");
+        assert!(unchanged.is_none());
+
+        let refreshed = store
+            .refresh_generated_title(&id, &conversation(6), &titler)
+            .await
+            .expect("title should save")
+            .expect("title should refresh after delta");
+        assert_eq!(
+            refreshed.title.as_deref(),
+            Some("Research Postgres migration")
+        );
+        assert_eq!(refreshed.generated_title_message_count, Some(6));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_timestamp_title_when_summarization_fails() {
+        let sessions = tempdir().expect("tempdir should be created");
+        let store = LocalSessionStore::new(sessions.path());
+        let id = create_session(&store, None).await;
+        let titler = SessionTitler::new(TitleProvider::new(vec![Err("offline")]));
+
+        let metadata = store
+            .refresh_generated_title(&id, &conversation(2), &titler)
+            .await
+            .expect("summarization failure must not fail the save")
+            .expect("fallback title should be stored");
+
+        let title = metadata.title.expect("fallback title");
+        assert!(title.starts_with("Session "), "{title}");
+        assert_eq!(metadata.generated_title_message_count, Some(2));
+    }
+
+    #[tokio::test]
+    async fn keeps_existing_generated_title_when_refresh_fails() {
+        let sessions = tempdir().expect("tempdir should be created");
+        let store = LocalSessionStore::new(sessions.path());
+        let id = create_session(&store, None).await;
+        let titler =
+            SessionTitler::new(TitleProvider::new(vec![Ok("First title"), Err("offline")]))
+                .with_refresh_delta(2);
+
+        store
+            .refresh_generated_title(&id, &conversation(2), &titler)
+            .await
+            .expect("title should save");
+        let refreshed = store
+            .refresh_generated_title(&id, &conversation(4), &titler)
+            .await
+            .expect("refresh failure must not fail the save");
+
+        assert!(refreshed.is_none());
+        let entry = store
+            .validated_entry(&ensure_store_root(&store.root).unwrap(), &id)
+            .expect("session should be readable");
+        assert_eq!(entry.metadata.title.as_deref(), Some("First title"));
+    }
+
+    #[tokio::test]
+    async fn never_replaces_host_titles() {
+        let sessions = tempdir().expect("tempdir should be created");
+        let store = LocalSessionStore::new(sessions.path());
+        let id = create_session(&store, Some("Chosen by user")).await;
+        let titler = SessionTitler::new(TitleProvider::new(vec![])).with_refresh_delta(1);
+
+        let result = store
+            .refresh_generated_title(&id, &conversation(10), &titler)
+            .await
+            .expect("title check should succeed");
+
+        assert!(result.is_none());
+    }
+}
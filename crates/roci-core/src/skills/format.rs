@@ -57,6 +57,9 @@ pub fn format_skills_for_prompt(skills: &[Skill]) -> String {
 /// If no visible skills are provided, this returns `base` unchanged.
 /// If `base` is `Some`, the rendered skills block is appended.
 /// If `base` is `None`, returns only the rendered skills block with leading newlines removed.
+///
+/// Full system prompts are assembled by
+/// [`SystemPromptComposer`](crate::resource::SystemPromptComposer).
 pub fn merge_system_prompt_with_skills(base: Option<String>, skills: &[Skill]) -> Option<String> {
     let skills_text = format_skills_for_prompt(skills);
 
@@ -7,7 +7,7 @@ use ignore::WalkBuilder;
 
 use crate::resource::settings::ResolvedResourceDirectories;
 use crate::skills::diagnostics::{SkillCollision, SkillDiagnostic, SkillDiagnosticLevel};
-use crate::skills::frontmatter::parse_skill_file;
+use crate::skills::frontmatter::{parse_skill_file, ParsedSkill};
 use crate::skills::model::{Skill, SkillSource};
 
 const SKILL_FILE_NAME: &str = "SKILL.md";
@@ -109,6 +109,18 @@ struct Candidate {
 /// When multiple loaded skills share the same `name`, the first one wins and a
 /// collision diagnostic is emitted for each losing skill.
 pub fn load_skills(options: &LoadSkillsOptions) -> LoadSkillsResult {
+    load_skills_reusing(options, &|_| None)
+}
+
+/// Returns the skill already loaded from a path, with its parse warnings.
+pub(crate) type ReuseSkill<'a> = dyn Fn(&Path) -> Option<(Skill, Vec<SkillDiagnostic>)> + 'a;
+
+/// Like [`load_skills`], but skill files for which `reuse` returns the skill
+/// and its parse warnings are not parsed again.
+pub(crate) fn load_skills_reusing(
+    options: &LoadSkillsOptions,
+    reuse: &ReuseSkill<'_>,
+) -> LoadSkillsResult {
     let mut diagnostics = Vec::new();
     let mut candidates = Vec::new();
 
@@ -140,7 +152,17 @@ pub fn load_skills(options: &LoadSkillsOptions) -> LoadSkillsResult {
             continue;
         }
 
-        let (parsed, mut parse_diagnostics) = parse_skill_file(&candidate.path);
+        let (parsed, mut parse_diagnostics) = match reuse(&candidate.path) {
+            Some((skill, warnings)) => (
+                Some(ParsedSkill {
+                    name: skill.name,
+                    description: skill.description,
+                    disable_model_invocation: skill.disable_model_invocation,
+                }),
+                warnings,
+            ),
+            None => parse_skill_file(&candidate.path),
+        };
         diagnostics.append(&mut parse_diagnostics);
 
         let Some(parsed) = parsed else {
@@ -1,93 +1,597 @@
 //! Typed access to tool call arguments.
+//!
+//! Accessors report failures as [`RociError::InvalidArgument`] using the same
+//! wording as schema validation (`missing required field 'x'`,
+//! `field 'x' expected type 'integer', got string`), so the model gets the
+//! same actionable feedback whichever check caught the mistake. An explicit
+//! `null` counts as absent.
 
+use serde_json::{Map, Value};
+
+use super::validation::{missing_field_message, type_mismatch_message};
 use crate::error::RociError;
 
 /// Wrapper around tool call arguments providing typed extraction.
 #[derive(Debug, Clone)]
 pub struct ToolArguments {
-    value: serde_json::Value,
+    value: Value,
 }
 
 impl ToolArguments {
-    pub fn new(value: serde_json::Value) -> Self {
+    pub fn new(value: Value) -> Self {
         Self { value }
     }
 
     /// Get the raw JSON value.
-    pub fn raw(&self) -> &serde_json::Value {
+    pub fn raw(&self) -> &Value {
         &self.value
     }
 
+    /// Whether `key` is present with a non-null value.
+    pub fn has(&self, key: &str) -> bool {
+        self.field(key).is_some()
+    }
+
     /// Get a string argument by key.
     pub fn get_str(&self, key: &str) -> Result<&str, RociError> {
-        self.value
-            .get(key)
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing string argument: {key}")))
+        self.typed(key, "string", Value::as_str)
     }
 
-    /// Get an optional string argument.
+    /// Get a string argument, or `default` when it is absent.
+    pub fn get_str_or<'a>(&'a self, key: &str, default: &'a str) -> Result<&'a str, RociError> {
+        self.typed_or(key, default, "string", Value::as_str)
+    }
+
+    /// Get an optional string argument, ignoring values of other types.
     pub fn get_str_opt(&self, key: &str) -> Option<&str> {
         self.value.get(key).and_then(|v| v.as_str())
     }
 
-    /// Get an integer argument.
+    /// Get an integer argument. Whole-number floats such as `3.0` are
+    /// accepted.
     pub fn get_i64(&self, key: &str) -> Result<i64, RociError> {
-        self.value
-            .get(key)
-            .and_then(|v| v.as_i64())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing integer argument: {key}")))
+        self.typed(key, "integer", as_i64)
+    }
+
+    /// Get an integer argument, or `default` when it is absent.
+    pub fn get_i64_or(&self, key: &str, default: i64) -> Result<i64, RociError> {
+        self.typed_or(key, default, "integer", as_i64)
+    }
+
+    /// Get a non-negative integer argument. Whole-number floats such as `3.0`
+    /// are accepted.
+    pub fn get_u64(&self, key: &str) -> Result<u64, RociError> {
+        self.typed(key, "non-negative integer", as_u64)
+    }
+
+    /// Get a non-negative integer argument, or `default` when it is absent.
+    pub fn get_u64_or(&self, key: &str, default: u64) -> Result<u64, RociError> {
+        self.typed_or(key, default, "non-negative integer", as_u64)
     }
 
     /// Get a float argument.
     pub fn get_f64(&self, key: &str) -> Result<f64, RociError> {
-        self.value
-            .get(key)
-            .and_then(|v| v.as_f64())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing float argument: {key}")))
+        self.typed(key, "number", Value::as_f64)
+    }
+
+    /// Get a float argument, or `default` when it is absent.
+    pub fn get_f64_or(&self, key: &str, default: f64) -> Result<f64, RociError> {
+        self.typed_or(key, default, "number", Value::as_f64)
     }
 
     /// Get a boolean argument.
     pub fn get_bool(&self, key: &str) -> Result<bool, RociError> {
-        self.value
-            .get(key)
-            .and_then(|v| v.as_bool())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing boolean argument: {key}")))
+        self.typed(key, "boolean", Value::as_bool)
+    }
+
+    /// Get a boolean argument, or `default` when it is absent.
+    pub fn get_bool_or(&self, key: &str, default: bool) -> Result<bool, RociError> {
+        self.typed_or(key, default, "boolean", Value::as_bool)
     }
 
     /// Get a nested object.
-    pub fn get_object(&self, key: &str) -> Result<&serde_json::Value, RociError> {
-        self.value
-            .get(key)
-            .filter(|v| v.is_object())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing object argument: {key}")))
+    pub fn get_object(&self, key: &str) -> Result<&Map<String, Value>, RociError> {
+        self.typed(key, "object", Value::as_object)
     }
 
     /// Get an array argument.
-    pub fn get_array(&self, key: &str) -> Result<&Vec<serde_json::Value>, RociError> {
-        self.value
-            .get(key)
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| RociError::InvalidArgument(format!("Missing array argument: {key}")))
+    pub fn get_array(&self, key: &str) -> Result<&Vec<Value>, RociError> {
+        self.typed(key, "array", Value::as_array)
+    }
+
+    /// Get an array of strings. A non-string element is reported by index,
+    /// e.g. `field 'paths[2]' expected type 'string', got number`.
+    pub fn get_string_array(&self, key: &str) -> Result<Vec<&str>, RociError> {
+        self.get_array(key)?
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                item.as_str().ok_or_else(|| {
+                    invalid(type_mismatch_message(
+                        &format!("{key}[{index}]"),
+                        "string",
+                        item,
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Get a nested value by dotted path, e.g. `config.retries` or
+    /// `steps.0.name`; numeric segments index arrays.
+    pub fn get_path(&self, path: &str) -> Result<&Value, RociError> {
+        let mut current = &self.value;
+        let mut walked = String::new();
+        for segment in path.split('.') {
+            let next = match current {
+                Value::Object(object) => object.get(segment),
+                Value::Array(items) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get(index)),
+                Value::Null => None,
+                other => {
+                    let parent = if walked.is_empty() {
+                        "arguments"
+                    } else {
+                        &walked
+                    };
+                    return Err(invalid(type_mismatch_message(parent, "object", other)));
+                }
+            };
+            if !walked.is_empty() {
+                walked.push('.');
+            }
+            walked.push_str(segment);
+            current = next
+                .filter(|value| !value.is_null())
+                .ok_or_else(|| invalid(missing_field_message(&walked)))?;
+        }
+        Ok(current)
     }
 
     /// Deserialize the entire arguments into a typed struct.
+    ///
+    /// Same as [`deserialize_into`](Self::deserialize_into).
     pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, RociError> {
+        self.deserialize_into()
+    }
+
+    /// Deserialize the entire arguments into a typed struct.
+    ///
+    /// Arguments that arrive as a JSON string are parsed first, and an empty
+    /// string is treated as `{}`. Serde errors are rewritten in validation
+    /// wording, naming the top-level field that failed when it can be found.
+    pub fn deserialize_into<T: serde::de::DeserializeOwned>(&self) -> Result<T, RociError> {
         let value = match &self.value {
-            serde_json::Value::String(raw) => {
+            Value::String(raw) => {
                 let trimmed = raw.trim();
                 if trimmed.is_empty() {
                     serde_json::json!({})
                 } else {
-                    serde_json::from_str::<serde_json::Value>(trimmed).map_err(|e| {
-                        RociError::InvalidArgument(format!("Failed to deserialize arguments: {e}"))
-                    })?
+                    serde_json::from_str::<Value>(trimmed)
+                        .map_err(|e| invalid(format!("arguments are not valid JSON: {e}")))?
                 }
             }
             other => other.clone(),
         };
-        serde_json::from_value(value).map_err(|e| {
-            RociError::InvalidArgument(format!("Failed to deserialize arguments: {e}"))
+        serde_json::from_value::<T>(value.clone()).map_err(|err| {
+            let message = err.to_string();
+            let field = failing_field::<T>(&value, &message);
+            invalid(describe_serde_error(&message, field.as_deref()))
         })
     }
+
+    /// Value for `key`, treating an explicit `null` as absent.
+    fn field(&self, key: &str) -> Option<&Value> {
+        self.value.get(key).filter(|value| !value.is_null())
+    }
+
+    fn typed<'a, T>(
+        &'a self,
+        key: &str,
+        expected: &str,
+        extract: impl Fn(&'a Value) -> Option<T>,
+    ) -> Result<T, RociError> {
+        let value = self
+            .field(key)
+            .ok_or_else(|| invalid(missing_field_message(key)))?;
+        extract(value).ok_or_else(|| invalid(type_mismatch_message(key, expected, value)))
+    }
+
+    fn typed_or<'a, T>(
+        &'a self,
+        key: &str,
+        default: T,
+        expected: &str,
+        extract: impl Fn(&'a Value) -> Option<T>,
+    ) -> Result<T, RociError> {
+        match self.field(key) {
+            None => Ok(default),
+            Some(value) => {
+                extract(value).ok_or_else(|| invalid(type_mismatch_message(key, expected, value)))
+            }
+        }
+    }
+}
+
+fn invalid(message: String) -> RociError {
+    RociError::InvalidArgument(message)
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| {
+        value
+            .as_f64()
+            .filter(|v| v.fract() == 0.0 && *v >= i64::MIN as f64 && *v <= i64::MAX as f64)
+            .map(|v| v as i64)
+    })
+}
+
+fn as_u64(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| {
+        value
+            .as_f64()
+            .filter(|v| v.fract() == 0.0 && *v >= 0.0 && *v <= u64::MAX as f64)
+            .map(|v| v as u64)
+    })
+}
+
+/// Top-level field behind a serde error: the one whose removal changes the
+/// error. Serde reports the first failure only, so removing any other field
+/// leaves the message as it was.
+fn failing_field<T: serde::de::DeserializeOwned>(value: &Value, message: &str) -> Option<String> {
+    let object = value.as_object()?;
+    object.keys().find_map(|key| {
+        let mut without = object.clone();
+        without.remove(key);
+        match serde_json::from_value::<T>(Value::Object(without)) {
+            Ok(_) => Some(key.clone()),
+            Err(err) if err.to_string() != message => Some(key.clone()),
+            Err(_) => None,
+        }
+    })
+}
+
+/// Rewrite a serde error message in validation wording.
+fn describe_serde_error(message: &str, field: Option<&str>) -> String {
+    if let Some(name) = backticked(message, "missing field ") {
+        return missing_field_message(name);
+    }
+    if let Some(name) = backticked(message, "unknown field ") {
+        return format!("unknown field '{name}'");
+    }
+    let subject = match field {
+        Some(field) => format!("field '{field}'"),
+        None => "arguments".to_string(),
+    };
+    if let Some((got, expected)) = message
+        .strip_prefix("invalid type: ")
+        .and_then(|rest| rest.split_once(", expected "))
+    {
+        return format!(
+            "{subject} expected type '{expected}', got {}",
+            serde_unexpected_type(got)
+        );
+    }
+    if let Some((got, expected)) = message
+        .strip_prefix("invalid value: ")
+        .or_else(|| message.strip_prefix("unknown variant "))
+        .and_then(|rest| rest.split_once(", expected "))
+    {
+        return format!("{subject} expected {expected}, got {got}");
+    }
+    format!("{subject}: {message}")
+}
+
+fn backticked<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    message
+        .strip_prefix(prefix)?
+        .strip_prefix('`')?
+        .split('`')
+        .next()
+}
+
+/// JSON type name for serde's description of an unexpected value.
+fn serde_unexpected_type(description: &str) -> &str {
+    let kinds = [
+        ("string", "string"),
+        ("boolean", "boolean"),
+        ("integer", "number"),
+        ("floating point", "number"),
+        ("unit value", "null"),
+        ("null", "null"),
+        ("map", "object"),
+        ("sequence", "array"),
+    ];
+    kinds
+        .iter()
+        .find(|(prefix, _)| description.starts_with(prefix))
+        .map(|(_, name)| *name)
+        .unwrap_or(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    fn args(value: Value) -> ToolArguments {
+        ToolArguments::new(value)
+    }
+
+    fn message(err: RociError) -> String {
+        match err {
+            RociError::InvalidArgument(message) => message,
+            other => panic!("expected InvalidArgument, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn scalar_getters_read_matching_types() {
+        let args = args(json!({
+            "name": "grep",
+            "count": -3,
+            "limit": 40,
+            "ratio": 0.5,
+            "verbose": true,
+        }));
+
+        assert_eq!(args.get_str("name").unwrap(), "grep");
+        assert_eq!(args.get_i64("count").unwrap(), -3);
+        assert_eq!(args.get_u64("limit").unwrap(), 40);
+        assert_eq!(args.get_f64("ratio").unwrap(), 0.5);
+        assert_eq!(args.get_f64("limit").unwrap(), 40.0);
+        assert!(args.get_bool("verbose").unwrap());
+    }
+
+    #[test]
+    fn integer_getters_accept_whole_floats_only() {
+        let args = args(json!({ "whole": 3.0, "fraction": 2.5, "negative": -1 }));
+
+        assert_eq!(args.get_i64("whole").unwrap(), 3);
+        assert_eq!(args.get_u64("whole").unwrap(), 3);
+        assert_eq!(
+            message(args.get_i64("fraction").unwrap_err()),
+            "field 'fraction' expected type 'integer', got number"
+        );
+        assert_eq!(
+            message(args.get_u64("negative").unwrap_err()),
+            "field 'negative' expected type 'non-negative integer', got number"
+        );
+    }
+
+    #[test]
+    fn missing_and_null_fields_are_reported_as_missing() {
+        let args = args(json!({ "present": null }));
+
+        for err in [
+            args.get_str("absent").map(|_| ()).unwrap_err(),
+            args.get_i64("absent").map(|_| ()).unwrap_err(),
+            args.get_u64("absent").map(|_| ()).unwrap_err(),
+            args.get_f64("absent").map(|_| ()).unwrap_err(),
+            args.get_bool("absent").map(|_| ()).unwrap_err(),
+            args.get_object("absent").map(|_| ()).unwrap_err(),
+            args.get_array("absent").map(|_| ()).unwrap_err(),
+            args.get_string_array("absent").map(|_| ()).unwrap_err(),
+        ] {
+            assert_eq!(message(err), "missing required field 'absent'");
+        }
+        assert_eq!(
+            message(args.get_str("present").unwrap_err()),
+            "missing required field 'present'"
+        );
+        assert!(!args.has("present"));
+        assert!(!args.has("absent"));
+    }
+
+    #[test]
+    fn wrong_types_name_the_field_expected_and_received_types() {
+        let args = args(json!({
+            "text": "x",
+            "number": 1,
+            "flag": false,
+            "list": [],
+            "map": {},
+        }));
+
+        let cases = [
+            (
+                args.get_str("number").map(|_| ()).unwrap_err(),
+                "field 'number' expected type 'string', got number",
+            ),
+            (
+                args.get_i64("text").map(|_| ()).unwrap_err(),
+                "field 'text' expected type 'integer', got string",
+            ),
+            (
+                args.get_u64("flag").map(|_| ()).unwrap_err(),
+                "field 'flag' expected type 'non-negative integer', got boolean",
+            ),
+            (
+                args.get_f64("list").map(|_| ()).unwrap_err(),
+                "field 'list' expected type 'number', got array",
+            ),
+            (
+                args.get_bool("map").map(|_| ()).unwrap_err(),
+                "field 'map' expected type 'boolean', got object",
+            ),
+            (
+                args.get_object("list").map(|_| ()).unwrap_err(),
+                "field 'list' expected type 'object', got array",
+            ),
+            (
+                args.get_array("map").map(|_| ()).unwrap_err(),
+                "field 'map' expected type 'array', got object",
+            ),
+            (
+                args.get_string_array("text").map(|_| ()).unwrap_err(),
+                "field 'text' expected type 'array', got string",
+            ),
+        ];
+        for (err, expected) in cases {
+            assert_eq!(message(err), expected);
+        }
+    }
+
+    #[test]
+    fn get_or_defaults_only_when_absent_or_null() {
+        let args = args(json!({
+            "name": "set",
+            "nothing": null,
+            "limit": "ten",
+        }));
+
+        assert_eq!(args.get_str_or("name", "default").unwrap(), "set");
+        assert_eq!(args.get_str_or("absent", "default").unwrap(), "default");
+        assert_eq!(args.get_i64_or("nothing", -1).unwrap(), -1);
+        assert_eq!(args.get_u64_or("absent", 7).unwrap(), 7);
+        assert_eq!(args.get_f64_or("absent", 1.5).unwrap(), 1.5);
+        assert!(args.get_bool_or("nothing", true).unwrap());
+        assert_eq!(
+            message(args.get_u64_or("limit", 7).unwrap_err()),
+            "field 'limit' expected type 'non-negative integer', got string"
+        );
+        assert_eq!(
+            message(args.get_bool_or("name", false).unwrap_err()),
+            "field 'name' expected type 'boolean', got string"
+        );
+    }
+
+    #[test]
+    fn str_opt_stays_lenient() {
+        let args = args(json!({ "path": 3 }));
+
+        assert_eq!(args.get_str_opt("path"), None);
+        assert_eq!(args.get_str_opt("absent"), None);
+    }
+
+    #[test]
+    fn object_and_array_getters_return_contents() {
+        let args = args(json!({
+            "config": { "retries": 2 },
+            "paths": ["a", "b"],
+        }));
+
+        assert_eq!(args.get_object("config").unwrap()["retries"], 2);
+        assert_eq!(args.get_array("paths").unwrap().len(), 2);
+        assert_eq!(args.get_string_array("paths").unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn string_array_reports_the_bad_element_by_index() {
+        let args = args(json!({ "paths": ["a", "b", 3] }));
+
+        assert_eq!(
+            message(args.get_string_array("paths").unwrap_err()),
+            "field 'paths[2]' expected type 'string', got number"
+        );
+    }
+
+    #[test]
+    fn get_path_walks_objects_and_array_indices() {
+        let args = args(json!({
+            "config": { "retries": 3, "name": "x", "off": null },
+            "steps": [{ "name": "first" }],
+        }));
+
+        assert_eq!(args.get_path("config.retries").unwrap(), &json!(3));
+        assert_eq!(args.get_path("steps.0.name").unwrap(), &json!("first"));
+        assert_eq!(
+            message(args.get_path("config.timeout").unwrap_err()),
+            "missing required field 'config.timeout'"
+        );
+        assert_eq!(
+            message(args.get_path("config.off").unwrap_err()),
+            "missing required field 'config.off'"
+        );
+        assert_eq!(
+            message(args.get_path("steps.4.name").unwrap_err()),
+            "missing required field 'steps.4'"
+        );
+        assert_eq!(
+            message(args.get_path("steps.first").unwrap_err()),
+            "missing required field 'steps.first'"
+        );
+        assert_eq!(
+            message(args.get_path("config.name.first").unwrap_err()),
+            "field 'config.name' expected type 'object', got string"
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Search {
+        query: String,
+        #[serde(default)]
+        limit: Option<u32>,
+        mode: Mode,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum Mode {
+        Fast,
+        Exact,
+    }
+
+    #[test]
+    fn deserialize_into_reads_objects_and_json_strings() {
+        let expected = Search {
+            query: "x".to_string(),
+            limit: Some(5),
+            mode: Mode::Exact,
+        };
+
+        let from_object = args(json!({ "query": "x", "limit": 5, "mode": "exact" }));
+        let from_string = args(json!(r#" {"query":"x","limit":5,"mode":"exact"} "#));
+
+        assert_eq!(from_object.deserialize_into::<Search>().unwrap(), expected);
+        assert_eq!(from_string.deserialize_into::<Search>().unwrap(), expected);
+    }
+
+    #[test]
+    fn deserialize_into_reports_errors_in_validation_wording() {
+        let cases = [
+            (
+                json!({ "mode": "fast" }),
+                "missing required field 'query'".to_string(),
+            ),
+            (
+                json!({ "query": "x", "mode": "fast", "extra": 1 }),
+                "unknown field 'extra'".to_string(),
+            ),
+            (
+                json!({ "query": "x", "limit": "five", "mode": "fast" }),
+                "field 'limit' expected type 'u32', got string".to_string(),
+            ),
+            (
+                json!({ "query": 7, "mode": "fast" }),
+                "field 'query' expected type 'a string', got number".to_string(),
+            ),
+            (
+                json!({ "query": "x", "mode": "slow" }),
+                "field 'mode' expected `fast` or `exact`, got `slow`".to_string(),
+            ),
+            (
+                json!(42),
+                "arguments expected type 'struct Search', got number".to_string(),
+            ),
+        ];
+        for (value, expected) in cases {
+            let err = args(value).deserialize_into::<Search>().unwrap_err();
+            assert_eq!(message(err), expected);
+        }
+    }
+
+    #[test]
+    fn deserialize_into_rejects_malformed_json_strings() {
+        let err = args(json!("{not json"))
+            .deserialize_into::<Search>()
+            .unwrap_err();
+
+        assert!(message(err).starts_with("arguments are not valid JSON:"));
+    }
 }
@@ -0,0 +1,449 @@
+//! Run-scoped registry of outputs produced by tools.
+//!
+//! Tools hand the run a generated report, a rendered chart, or a build log
+//! through [`ToolExecutionContext::add_artifact`](super::ToolExecutionContext::add_artifact)
+//! instead of leaving callers to parse tool results. [`ArtifactStore`]
+//! records each one as a [`RunArtifact`]; byte payloads above the spill
+//! threshold are written to the run's artifact directory so events and the
+//! run result carry a path rather than the bytes.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RociError;
+
+/// Byte artifacts larger than this are spilled to the artifact directory.
+pub const DEFAULT_ARTIFACT_SPILL_BYTES: usize = 64 * 1024;
+
+/// Where an artifact's content comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactSource {
+    /// A file the tool already wrote.
+    Path(PathBuf),
+    /// Content held in memory; spilled to disk when large.
+    Bytes(Vec<u8>),
+}
+
+/// An output a tool registers with the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub name: String,
+    pub path_or_bytes: ArtifactSource,
+    pub mime: String,
+    pub description: Option<String>,
+}
+
+impl Artifact {
+    /// A file on disk, named after its file name, with the MIME type guessed
+    /// from its extension.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+            mime: guess_mime(&path).to_string(),
+            path_or_bytes: ArtifactSource::Path(path),
+            description: None,
+        }
+    }
+
+    pub fn bytes(name: impl Into<String>, bytes: Vec<u8>, mime: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path_or_bytes: ArtifactSource::Bytes(bytes),
+            mime: mime.into(),
+            description: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Where a recorded artifact's content lives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArtifactLocation {
+    File {
+        path: PathBuf,
+        /// `true` when the run wrote the file from in-memory bytes.
+        spilled: bool,
+    },
+    Inline {
+        /// Base64 in serialized form.
+        #[serde(with = "base64_bytes")]
+        bytes: Vec<u8>,
+    },
+}
+
+/// An artifact as recorded on the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunArtifact {
+    pub name: String,
+    pub mime: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub location: ArtifactLocation,
+    /// Size in bytes; `None` when a registered file could not be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl RunArtifact {
+    /// Path of the file holding the content, when it is not inline.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.location {
+            ArtifactLocation::File { path, .. } => Some(path),
+            ArtifactLocation::Inline { .. } => None,
+        }
+    }
+}
+
+/// Shared artifact registry for one run.
+///
+/// Cloning is cheap; clones record into the same registry, so tools running
+/// in parallel can register concurrently.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+    spill_threshold: usize,
+    /// Numbers spill files so equal names from parallel tools never collide.
+    next_spill: Arc<AtomicUsize>,
+    state: Arc<Mutex<ArtifactState>>,
+}
+
+#[derive(Debug, Default)]
+struct ArtifactState {
+    artifacts: Vec<RunArtifact>,
+    /// Indexes into `artifacts` not yet reported as events.
+    unannounced: Vec<usize>,
+}
+
+impl ArtifactStore {
+    /// Registry spilling into `dir`, which is created on first spill.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            spill_threshold: DEFAULT_ARTIFACT_SPILL_BYTES,
+            next_spill: Arc::new(AtomicUsize::new(1)),
+            state: Arc::default(),
+        }
+    }
+
+    /// Replace the size above which byte artifacts are spilled.
+    #[must_use]
+    pub fn with_spill_threshold(mut self, bytes: usize) -> Self {
+        self.spill_threshold = bytes;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Record `artifact` for the tool call that produced it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Io`] when spilled bytes cannot be written.
+    pub fn add(
+        &self,
+        artifact: Artifact,
+        tool_call_id: Option<&str>,
+        tool_name: Option<&str>,
+    ) -> Result<RunArtifact, RociError> {
+        let Artifact {
+            name,
+            path_or_bytes,
+            mime,
+            description,
+        } = artifact;
+        let (location, size) = match path_or_bytes {
+            ArtifactSource::Path(path) => {
+                let size = std::fs::metadata(&path).ok().map(|meta| meta.len());
+                (
+                    ArtifactLocation::File {
+                        path,
+                        spilled: false,
+                    },
+                    size,
+                )
+            }
+            ArtifactSource::Bytes(bytes) if bytes.len() > self.spill_threshold => {
+                let size = bytes.len() as u64;
+                let path = self.spill(&name, &bytes)?;
+                (
+                    ArtifactLocation::File {
+                        path,
+                        spilled: true,
+                    },
+                    Some(size),
+                )
+            }
+            ArtifactSource::Bytes(bytes) => {
+                let size = bytes.len() as u64;
+                (ArtifactLocation::Inline { bytes }, Some(size))
+            }
+        };
+        let artifact = RunArtifact {
+            name,
+            mime,
+            description,
+            location,
+            size,
+            tool_call_id: tool_call_id.map(str::to_string),
+            tool_name: tool_name.map(str::to_string),
+        };
+
+        let mut state = self.lock();
+        let index = state.artifacts.len();
+        state.unannounced.push(index);
+        state.artifacts.push(artifact.clone());
+        Ok(artifact)
+    }
+
+    /// Artifacts recorded so far, in registration order.
+    pub fn artifacts(&self) -> Vec<RunArtifact> {
+        self.lock().artifacts.clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().artifacts.is_empty()
+    }
+
+    /// Take the artifacts of `tool_call_id` not yet reported as events.
+    #[cfg_attr(not(feature = "agent"), allow(dead_code))]
+    pub(crate) fn take_unannounced_for(&self, tool_call_id: &str) -> Vec<RunArtifact> {
+        let mut state = self.lock();
+        let ArtifactState {
+            artifacts,
+            unannounced,
+        } = &mut *state;
+        let mut taken = Vec::new();
+        unannounced.retain(|&index| {
+            let artifact = &artifacts[index];
+            if artifact.tool_call_id.as_deref() == Some(tool_call_id) {
+                taken.push(artifact.clone());
+                false
+            } else {
+                true
+            }
+        });
+        taken
+    }
+
+    /// Take every artifact not yet reported as an event.
+    #[cfg_attr(not(feature = "agent"), allow(dead_code))]
+    pub(crate) fn take_unannounced(&self) -> Vec<RunArtifact> {
+        let mut state = self.lock();
+        let indexes = std::mem::take(&mut state.unannounced);
+        indexes
+            .into_iter()
+            .map(|index| state.artifacts[index].clone())
+            .collect()
+    }
+
+    fn spill(&self, name: &str, bytes: &[u8]) -> Result<PathBuf, RociError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let number = self.next_spill.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .dir
+            .join(format!("{number:03}-{}", file_name_for(name)));
+        std::fs::write(&path, bytes)?;
+        Ok(path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ArtifactState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// MIME type for common artifact extensions; `application/octet-stream`
+/// otherwise.
+pub fn guess_mime(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("txt" | "log") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("html" | "htm") => "text/html",
+        Some("css") => "text/css",
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("yaml" | "yml") => "application/yaml",
+        Some("toml") => "application/toml",
+        Some("js") => "text/javascript",
+        Some("rs" | "py" | "ts" | "go" | "java" | "c" | "h" | "cpp" | "sh") => "text/plain",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Keep spill file names to one safe path component.
+fn file_name_for(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        "artifact".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+mod base64_bytes {
+    use base64::prelude::{Engine as _, BASE64_STANDARD};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64_STANDARD
+            .decode(encoded)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_bytes_stay_inline_and_large_bytes_spill() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path().join("artifacts")).with_spill_threshold(8);
+
+        let small = store
+            .add(
+                Artifact::bytes("note.txt", b"hi".to_vec(), "text/plain"),
+                Some("call_1"),
+                Some("render"),
+            )
+            .unwrap();
+        let large = store
+            .add(
+                Artifact::bytes("chart/1.png", vec![7; 32], "image/png")
+                    .with_description("weekly chart"),
+                Some("call_1"),
+                Some("render"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            small.location,
+            ArtifactLocation::Inline {
+                bytes: b"hi".to_vec()
+            }
+        );
+        assert_eq!(small.size, Some(2));
+        let path = large.path().expect("large bytes are spilled");
+        assert!(path.starts_with(dir.path().join("artifacts")));
+        assert_eq!(path.file_name().unwrap(), "001-chart_1.png");
+        assert_eq!(std::fs::read(path).unwrap(), vec![7; 32]);
+        assert!(matches!(
+            large.location,
+            ArtifactLocation::File { spilled: true, .. }
+        ));
+        assert_eq!(large.size, Some(32));
+        assert_eq!(large.description.as_deref(), Some("weekly chart"));
+        assert_eq!(store.artifacts(), vec![small, large]);
+    }
+
+    #[test]
+    fn file_artifacts_keep_their_path_and_guess_mime() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = dir.path().join("report.md");
+        std::fs::write(&report, "# Report\n").unwrap();
+        let store = ArtifactStore::new(dir.path().join("artifacts"));
+
+        let artifact = store.add(Artifact::file(&report), None, None).unwrap();
+
+        assert_eq!(artifact.name, "report.md");
+        assert_eq!(artifact.mime, "text/markdown");
+        assert_eq!(artifact.path(), Some(report.as_path()));
+        assert_eq!(artifact.size, Some(9));
+        assert!(!dir.path().join("artifacts").exists());
+    }
+
+    #[test]
+    fn unannounced_artifacts_are_taken_once_per_call() {
+        let store = ArtifactStore::new(std::env::temp_dir());
+        for call in ["a", "b", "a"] {
+            store
+                .add(
+                    Artifact::bytes(call, Vec::new(), "text/plain"),
+                    Some(call),
+                    None,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(store.take_unannounced_for("a").len(), 2);
+        assert!(store.take_unannounced_for("a").is_empty());
+        let rest = store.take_unannounced();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].tool_call_id.as_deref(), Some("b"));
+        assert!(store.take_unannounced().is_empty());
+        assert_eq!(store.artifacts().len(), 3);
+    }
+
+    #[test]
+    fn inline_bytes_serialize_as_base64() {
+        let artifact = RunArtifact {
+            name: "n".to_string(),
+            mime: "application/octet-stream".to_string(),
+            description: None,
+            location: ArtifactLocation::Inline {
+                bytes: vec![0, 1, 2],
+            },
+            size: Some(3),
+            tool_call_id: None,
+            tool_name: None,
+        };
+
+        let json = serde_json::to_value(&artifact).unwrap();
+
+        assert_eq!(
+            json["location"],
+            serde_json::json!({"kind": "inline", "bytes": "AAEC"})
+        );
+        assert_eq!(
+            serde_json::from_value::<RunArtifact>(json).unwrap(),
+            artifact
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Run-scoped record of files changed by tools.
+//!
+//! File-writing tools report each change with the content before and after
+//! it. [`ChangeLog`] folds repeated changes to one path into a single
+//! [`FileChange`], so the log describes the run's net effect per file.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// How a file changed over the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl FileChangeKind {
+    /// One-letter marker (`A`, `M`, `D`) for compact listings.
+    pub fn marker(self) -> char {
+        match self {
+            Self::Added => 'A',
+            Self::Modified => 'M',
+            Self::Deleted => 'D',
+        }
+    }
+}
+
+/// Net change to one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Path as the tool reported it: workspace-relative when possible.
+    pub path: String,
+    pub kind: FileChangeKind,
+    /// SHA-256 of the content before the run's first change; `None` when the
+    /// file did not exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_hash: Option<String>,
+    /// SHA-256 of the content after the last change; `None` when deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_hash: Option<String>,
+    /// Lines added, summed over every change to the file.
+    pub lines_added: usize,
+    /// Lines removed, summed over every change to the file.
+    pub lines_removed: usize,
+    /// `false` when every change was inferred from shell command arguments
+    /// rather than reported by the tool that wrote the file.
+    pub verified: bool,
+    /// Tools that changed the file, in first-change order.
+    pub tools: Vec<String>,
+}
+
+/// Shared change log for one run.
+///
+/// Cloning is cheap; clones record into the same log.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeLog {
+    inner: Arc<Mutex<Vec<FileChange>>>,
+}
+
+impl ChangeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a change the tool performed itself. `None` content means the
+    /// file did not exist before, or no longer exists after.
+    pub fn record(&self, tool: &str, path: &str, before: Option<&[u8]>, after: Option<&[u8]>) {
+        self.record_change(tool, path, before, after, true);
+    }
+
+    /// Record a change observed around a shell command. The path was guessed
+    /// from the command's arguments, so the entry stays unverified unless a
+    /// file tool also reports it.
+    pub fn record_unverified(
+        &self,
+        tool: &str,
+        path: &str,
+        before: Option<&[u8]>,
+        after: Option<&[u8]>,
+    ) {
+        self.record_change(tool, path, before, after, false);
+    }
+
+    /// Net changes so far, in first-change order.
+    pub fn changes(&self) -> Vec<FileChange> {
+        self.lock().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn record_change(
+        &self,
+        tool: &str,
+        path: &str,
+        before: Option<&[u8]>,
+        after: Option<&[u8]>,
+        verified: bool,
+    ) {
+        let old_hash = before.map(content_hash);
+        let new_hash = after.map(content_hash);
+        if old_hash == new_hash {
+            return;
+        }
+        let (lines_added, lines_removed) = line_delta(before, after);
+
+        let mut changes = self.lock();
+        let Some(index) = changes.iter().position(|change| change.path == path) else {
+            changes.push(FileChange {
+                path: path.to_string(),
+                kind: change_kind(old_hash.is_some(), new_hash.is_some()),
+                old_hash,
+                new_hash,
+                lines_added,
+                lines_removed,
+                verified,
+                tools: vec![tool.to_string()],
+            });
+            return;
+        };
+
+        let change = &mut changes[index];
+        change.new_hash = new_hash;
+        change.lines_added += lines_added;
+        change.lines_removed += lines_removed;
+        change.verified |= verified;
+        if !change.tools.iter().any(|name| name == tool) {
+            change.tools.push(tool.to_string());
+        }
+        let existed = change.old_hash.is_some();
+        let exists = change.new_hash.is_some();
+        // A file created then deleted, or restored to its original content,
+        // has no net change.
+        if (!existed && !exists) || (existed && change.old_hash == change.new_hash) {
+            changes.remove(index);
+        } else {
+            change.kind = change_kind(existed, exists);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<FileChange>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn change_kind(existed: bool, exists: bool) -> FileChangeKind {
+    match (existed, exists) {
+        (false, _) => FileChangeKind::Added,
+        (true, false) => FileChangeKind::Deleted,
+        (true, true) => FileChangeKind::Modified,
+    }
+}
+
+/// Lowercase hex SHA-256 of `content`.
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Lines added and removed going from `before` to `after`.
+///
+/// Lines shared as a common prefix and suffix are unchanged; everything
+/// between counts as replaced. Exact for single-hunk edits and an upper
+/// bound otherwise.
+fn line_delta(before: Option<&[u8]>, after: Option<&[u8]>) -> (usize, usize) {
+    let before = before.map(String::from_utf8_lossy).unwrap_or_default();
+    let after = after.map(String::from_utf8_lossy).unwrap_or_default();
+    let old = before.lines().collect::<Vec<_>>();
+    let new = after.lines().collect::<Vec<_>>();
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    (new.len() - prefix - suffix, old.len() - prefix - suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_file_counts_every_line_as_added() {
+        let log = ChangeLog::new();
+
+        log.record("write_file", "src/new.rs", None, Some(b"a\nb\nc\n"));
+
+        let changes = log.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, FileChangeKind::Added);
+        assert_eq!(changes[0].old_hash, None);
+        assert_eq!(changes[0].new_hash, Some(content_hash(b"a\nb\nc\n")));
+        assert_eq!((changes[0].lines_added, changes[0].lines_removed), (3, 0));
+        assert!(changes[0].verified);
+    }
+
+    #[test]
+    fn modification_counts_only_the_changed_hunk() {
+        let log = ChangeLog::new();
+
+        log.record(
+            "write_file",
+            "lib.rs",
+            Some(b"one\ntwo\nthree\nfour\n"),
+            Some(b"one\n2\n2.5\nthree\nfour\n"),
+        );
+
+        let change = &log.changes()[0];
+        assert_eq!(change.kind, FileChangeKind::Modified);
+        assert_eq!((change.lines_added, change.lines_removed), (2, 1));
+    }
+
+    #[test]
+    fn repeated_changes_fold_into_one_entry() {
+        let log = ChangeLog::new();
+
+        log.record("write_file", "a.txt", Some(b"x\n"), Some(b"x\ny\n"));
+        log.record_unverified("shell", "a.txt", Some(b"x\ny\n"), None);
+
+        let changes = log.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, FileChangeKind::Deleted);
+        assert_eq!(changes[0].old_hash, Some(content_hash(b"x\n")));
+        assert_eq!(changes[0].new_hash, None);
+        assert_eq!((changes[0].lines_added, changes[0].lines_removed), (1, 2));
+        assert!(changes[0].verified);
+        assert_eq!(changes[0].tools, vec!["write_file", "shell"]);
+    }
+
+    #[test]
+    fn reverted_and_transient_files_drop_out() {
+        let log = ChangeLog::new();
+
+        log.record("write_file", "a.txt", Some(b"x"), Some(b"y"));
+        log.record("write_file", "a.txt", Some(b"y"), Some(b"x"));
+        log.record("write_file", "tmp.txt", None, Some(b"scratch"));
+        log.record_unverified("shell", "tmp.txt", Some(b"scratch"), None);
+        log.record("write_file", "same.txt", Some(b"z"), Some(b"z"));
+
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn shell_only_entries_stay_unverified() {
+        let log = ChangeLog::new();
+
+        log.record_unverified("shell", "out.log", None, Some(b"done\n"));
+
+        let change = &log.changes()[0];
+        assert_eq!(change.kind, FileChangeKind::Added);
+        assert!(!change.verified);
+        assert_eq!(change.kind.marker(), 'A');
+    }
+}
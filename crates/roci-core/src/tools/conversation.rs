@@ -0,0 +1,182 @@
+//! Conversation access for tools running inside an agent run.
+//!
+//! Tools see a read-only snapshot of the conversation as of the current
+//! iteration and can queue extra messages through [`ToolMessageSink`]. The
+//! runner appends queued messages after the tool batch completes, after all
+//! tool results, in tool-call order.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::error::RociError;
+use crate::types::ModelMessage;
+
+/// Maximum number of messages a single tool call may emit.
+pub const MAX_EMITTED_MESSAGES_PER_TOOL_CALL: usize = 8;
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: Vec<(String, ModelMessage)>,
+    closed: bool,
+}
+
+/// Messages emitted by the tool calls of one batch.
+///
+/// Owned by the runner. Tools only hold [`ToolMessageSink`] handles, which
+/// stop accepting messages once the batch is drained or the queue is dropped.
+#[derive(Debug, Default)]
+pub struct ToolMessageQueue {
+    inner: Arc<Mutex<QueueState>>,
+}
+
+impl ToolMessageQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle that records messages on behalf of `tool_call_id`.
+    pub fn sink(&self, tool_call_id: impl Into<String>) -> ToolMessageSink {
+        ToolMessageSink {
+            inner: Arc::downgrade(&self.inner),
+            tool_call_id: tool_call_id.into(),
+        }
+    }
+
+    /// Close the queue and return its messages ordered by `call_ids`.
+    ///
+    /// Messages from the same call keep their emission order. Messages for
+    /// ids missing from `call_ids` come last.
+    pub fn drain_in_call_order<'a>(
+        &self,
+        call_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<ModelMessage> {
+        let mut pending = {
+            let mut state = self.lock();
+            state.closed = true;
+            std::mem::take(&mut state.messages)
+        };
+        let mut ordered = Vec::with_capacity(pending.len());
+        for call_id in call_ids {
+            let (matching, rest) = pending
+                .into_iter()
+                .partition::<Vec<_>, _>(|(emitted_by, _)| emitted_by == call_id);
+            ordered.extend(matching.into_iter().map(|(_, message)| message));
+            pending = rest;
+        }
+        ordered.extend(pending.into_iter().map(|(_, message)| message));
+        ordered
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Per-call handle for queueing messages into the conversation.
+///
+/// Cloning is cheap; clones record messages for the same tool call.
+#[derive(Debug, Clone)]
+pub struct ToolMessageSink {
+    inner: Weak<Mutex<QueueState>>,
+    tool_call_id: String,
+}
+
+impl ToolMessageSink {
+    /// Queue `message` for the runner to append after the tool batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidState`] once the batch has been drained or
+    /// the run has ended, and [`RociError::InvalidArgument`] when the call
+    /// already emitted [`MAX_EMITTED_MESSAGES_PER_TOOL_CALL`] messages.
+    pub fn emit(&self, message: ModelMessage) -> Result<(), RociError> {
+        let closed = || {
+            RociError::InvalidState(format!(
+                "tool call '{}' cannot emit messages after its batch has completed",
+                self.tool_call_id
+            ))
+        };
+        let inner = self.inner.upgrade().ok_or_else(closed)?;
+        let mut state = inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if state.closed {
+            return Err(closed());
+        }
+        let emitted = state
+            .messages
+            .iter()
+            .filter(|(emitted_by, _)| *emitted_by == self.tool_call_id)
+            .count();
+        if emitted >= MAX_EMITTED_MESSAGES_PER_TOOL_CALL {
+            return Err(RociError::InvalidArgument(format!(
+                "tool call '{}' exceeded the limit of {MAX_EMITTED_MESSAGES_PER_TOOL_CALL} emitted messages",
+                self.tool_call_id
+            )));
+        }
+        state.messages.push((self.tool_call_id.clone(), message));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(message: &ModelMessage) -> String {
+        message.text()
+    }
+
+    #[test]
+    fn drains_messages_in_call_order() {
+        let queue = ToolMessageQueue::new();
+        let second = queue.sink("call-2");
+        let first = queue.sink("call-1");
+        second.emit(ModelMessage::user("b1")).unwrap();
+        first.emit(ModelMessage::user("a1")).unwrap();
+        second.emit(ModelMessage::user("b2")).unwrap();
+
+        let drained = queue.drain_in_call_order(["call-1", "call-2"]);
+
+        assert_eq!(
+            drained.iter().map(text).collect::<Vec<_>>(),
+            vec!["a1", "b1", "b2"]
+        );
+    }
+
+    #[test]
+    fn caps_messages_per_tool_call() {
+        let queue = ToolMessageQueue::new();
+        let sink = queue.sink("call-1");
+        for index in 0..MAX_EMITTED_MESSAGES_PER_TOOL_CALL {
+            sink.emit(ModelMessage::user(format!("note {index}")))
+                .unwrap();
+        }
+
+        let err = sink
+            .emit(ModelMessage::user("one too many"))
+            .expect_err("cap should be enforced");
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(queue.sink("call-2").emit(ModelMessage::user("ok")).is_ok());
+    }
+
+    #[test]
+    fn rejects_messages_after_drain_or_drop() {
+        let queue = ToolMessageQueue::new();
+        let sink = queue.sink("call-1");
+        queue.drain_in_call_order(["call-1"]);
+        assert!(matches!(
+            sink.emit(ModelMessage::user("late")),
+            Err(RociError::InvalidState(_))
+        ));
+
+        let queue = ToolMessageQueue::new();
+        let sink = queue.sink("call-1");
+        drop(queue);
+        assert!(matches!(
+            sink.emit(ModelMessage::user("late")),
+            Err(RociError::InvalidState(_))
+        ));
+    }
+}
@@ -0,0 +1,483 @@
+//! Unified diffs and approval-time change previews.
+//!
+//! [`unified_diff`] renders a line diff in the `diff -u` format.
+//! [`ChangePreview`] wraps it for approval payloads: file-writing tools
+//! describe what a call would change before it runs, and binary or oversized
+//! content falls back to a size/hash summary.
+
+use serde::{Deserialize, Serialize};
+
+use super::changes::content_hash;
+
+/// Context lines around each hunk when none are requested.
+pub const DEFAULT_DIFF_CONTEXT: usize = 3;
+/// Largest file, per side, that [`ChangePreview::between`] diffs as text.
+pub const PREVIEW_MAX_INPUT_BYTES: usize = 1024 * 1024;
+/// Cap on the rendered diff carried in a [`ChangePreview`].
+pub const PREVIEW_MAX_DIFF_BYTES: usize = 32 * 1024;
+/// Above this many cells (changed old lines × changed new lines) the diff
+/// stops searching for a minimal edit and reports the span as replaced.
+const LCS_MAX_CELLS: usize = 1_000_000;
+const BINARY_SNIFF_BYTES: usize = 8_192;
+
+/// Rendered unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnifiedDiff {
+    /// Diff text; empty when the inputs are identical.
+    pub text: String,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// Whether `text` was cut at the byte cap. Line counts stay exact.
+    pub truncated: bool,
+}
+
+impl UnifiedDiff {
+    pub fn is_empty(&self) -> bool {
+        self.lines_added == 0 && self.lines_removed == 0
+    }
+}
+
+/// Diff `old` against `new` in unified format.
+///
+/// `old_label`/`new_label` go in the `---`/`+++` headers verbatim, so callers
+/// pass `/dev/null` for a side that does not exist. Output is cut at a line
+/// boundary once it would exceed `max_bytes`.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+    max_bytes: usize,
+) -> UnifiedDiff {
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    let edits = diff_lines(&old_lines, &new_lines);
+
+    let lines_added = edits
+        .iter()
+        .filter(|e| matches!(e, Edit::Insert(_)))
+        .count();
+    let lines_removed = edits
+        .iter()
+        .filter(|e| matches!(e, Edit::Delete(_)))
+        .count();
+    let mut diff = UnifiedDiff {
+        text: String::new(),
+        lines_added,
+        lines_removed,
+        truncated: false,
+    };
+    if diff.is_empty() {
+        return diff;
+    }
+
+    let mut out = DiffWriter::new(max_bytes);
+    out.push(format!("--- {old_label}\n"));
+    out.push(format!("+++ {new_label}\n"));
+    for hunk in hunks(&edits, context) {
+        if !out.push(hunk.header(&edits)) {
+            break;
+        }
+        let complete = edits[hunk.start..hunk.end].iter().all(|edit| {
+            let (prefix, line) = match *edit {
+                Edit::Equal(i, _) => (' ', old_lines[i]),
+                Edit::Delete(i) => ('-', old_lines[i]),
+                Edit::Insert(j) => ('+', new_lines[j]),
+            };
+            let mut rendered = format!("{prefix}{line}");
+            if !line.ends_with('\n') {
+                rendered.push_str("\n\\ No newline at end of file\n");
+            }
+            out.push(rendered)
+        });
+        if !complete {
+            break;
+        }
+    }
+    diff.truncated = out.truncated;
+    diff.text = out.text;
+    diff
+}
+
+/// What an approved call would do to one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangePreview {
+    /// Text change rendered as a unified diff.
+    Text {
+        path: String,
+        diff: String,
+        lines_added: usize,
+        lines_removed: usize,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        truncated: bool,
+    },
+    /// Binary or oversized change; `None` sizes and hashes mean the side
+    /// does not exist.
+    Binary {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_size: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        new_size: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        old_hash: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        new_hash: Option<String>,
+    },
+}
+
+impl ChangePreview {
+    /// Preview replacing `before` with `after` at `path`.
+    ///
+    /// `None` means the file does not exist on that side. Content that is not
+    /// UTF-8, contains NUL bytes, or exceeds [`PREVIEW_MAX_INPUT_BYTES`] gets
+    /// a size/hash summary; text diffs are capped at
+    /// [`PREVIEW_MAX_DIFF_BYTES`].
+    pub fn between(path: impl Into<String>, before: Option<&[u8]>, after: Option<&[u8]>) -> Self {
+        let path = path.into();
+        match (as_previewable_text(before), as_previewable_text(after)) {
+            (Some(old), Some(new)) => {
+                let old_label = if before.is_some() {
+                    format!("a/{path}")
+                } else {
+                    "/dev/null".to_string()
+                };
+                let new_label = if after.is_some() {
+                    format!("b/{path}")
+                } else {
+                    "/dev/null".to_string()
+                };
+                let diff = unified_diff(
+                    old,
+                    new,
+                    &old_label,
+                    &new_label,
+                    DEFAULT_DIFF_CONTEXT,
+                    PREVIEW_MAX_DIFF_BYTES,
+                );
+                Self::Text {
+                    path,
+                    diff: diff.text,
+                    lines_added: diff.lines_added,
+                    lines_removed: diff.lines_removed,
+                    truncated: diff.truncated,
+                }
+            }
+            _ => Self::Binary {
+                path,
+                old_size: before.map(|bytes| bytes.len() as u64),
+                new_size: after.map(|bytes| bytes.len() as u64),
+                old_hash: before.map(content_hash),
+                new_hash: after.map(content_hash),
+            },
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Text { path, .. } | Self::Binary { path, .. } => path,
+        }
+    }
+}
+
+/// Whether `bytes` looks like binary content: NUL bytes near the start or
+/// invalid UTF-8.
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+/// Text view of one preview side; a missing side is empty text.
+fn as_previewable_text(bytes: Option<&[u8]>) -> Option<&str> {
+    match bytes {
+        None => Some(""),
+        Some(bytes) if bytes.len() > PREVIEW_MAX_INPUT_BYTES || looks_binary(bytes) => None,
+        Some(bytes) => std::str::from_utf8(bytes).ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// Line `i` of old equals line `j` of new.
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Line edit script from `old` to `new`.
+///
+/// Common prefix and suffix are matched first; the middle uses an LCS table
+/// when it fits in [`LCS_MAX_CELLS`] and is otherwise reported as replaced.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut edits = (0..prefix).map(|i| Edit::Equal(i, i)).collect::<Vec<_>>();
+    let cells = (old_mid.len() + 1).saturating_mul(new_mid.len() + 1);
+    if cells <= LCS_MAX_CELLS {
+        lcs_edits(old_mid, new_mid, prefix, &mut edits);
+    } else {
+        edits.extend((0..old_mid.len()).map(|i| Edit::Delete(prefix + i)));
+        edits.extend((0..new_mid.len()).map(|j| Edit::Insert(prefix + j)));
+    }
+    edits.extend((0..suffix).map(|k| Edit::Equal(old.len() - suffix + k, new.len() - suffix + k)));
+    edits
+}
+
+fn lcs_edits(old: &[&str], new: &[&str], offset: usize, edits: &mut Vec<Edit>) {
+    let width = new.len() + 1;
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            edits.push(Edit::Equal(offset + i, offset + j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            edits.push(Edit::Delete(offset + i));
+            i += 1;
+        } else {
+            edits.push(Edit::Insert(offset + j));
+            j += 1;
+        }
+    }
+    edits.extend((i..old.len()).map(|i| Edit::Delete(offset + i)));
+    edits.extend((j..new.len()).map(|j| Edit::Insert(offset + j)));
+}
+
+/// Range of edits rendered as one `@@` hunk.
+struct Hunk {
+    start: usize,
+    end: usize,
+}
+
+impl Hunk {
+    fn header(&self, edits: &[Edit]) -> String {
+        // Line positions before the hunk's first edit.
+        let (mut old_pos, mut new_pos) = (0, 0);
+        for edit in &edits[..self.start] {
+            match edit {
+                Edit::Equal(..) => {
+                    old_pos += 1;
+                    new_pos += 1;
+                }
+                Edit::Delete(_) => old_pos += 1,
+                Edit::Insert(_) => new_pos += 1,
+            }
+        }
+        let span = &edits[self.start..self.end];
+        let old_count = span
+            .iter()
+            .filter(|e| !matches!(e, Edit::Insert(_)))
+            .count();
+        let new_count = span
+            .iter()
+            .filter(|e| !matches!(e, Edit::Delete(_)))
+            .count();
+        format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_pos, old_count),
+            hunk_range(new_pos, new_count)
+        )
+    }
+}
+
+fn hunk_range(pos: usize, count: usize) -> String {
+    match count {
+        0 => format!("{pos},0"),
+        1 => format!("{}", pos + 1),
+        _ => format!("{},{count}", pos + 1),
+    }
+}
+
+/// Group changed edits into hunks, merging ones whose context would overlap.
+fn hunks(edits: &[Edit], context: usize) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for (index, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Equal(..)) {
+            continue;
+        }
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(Hunk { start, end }),
+        }
+    }
+    hunks
+}
+
+/// Accumulates diff text until the byte cap.
+struct DiffWriter {
+    text: String,
+    max_bytes: usize,
+    truncated: bool,
+}
+
+impl DiffWriter {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            text: String::new(),
+            max_bytes,
+            truncated: false,
+        }
+    }
+
+    /// Append `chunk` if it fits; returns `false` once the cap is hit.
+    fn push(&mut self, chunk: String) -> bool {
+        if self.truncated || self.text.len() + chunk.len() > self.max_bytes {
+            self.truncated = true;
+            return false;
+        }
+        self.text.push_str(&chunk);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> UnifiedDiff {
+        unified_diff(
+            old,
+            new,
+            "a/f.txt",
+            "b/f.txt",
+            DEFAULT_DIFF_CONTEXT,
+            usize::MAX,
+        )
+    }
+
+    #[test]
+    fn identical_inputs_produce_an_empty_diff() {
+        let diff = diff("one\ntwo\n", "one\ntwo\n");
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.text, "");
+    }
+
+    #[test]
+    fn single_line_change_renders_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+
+        let diff = diff(old, new);
+
+        assert_eq!(
+            diff.text,
+            "--- a/f.txt\n+++ b/f.txt\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+        assert_eq!((diff.lines_added, diff.lines_removed), (1, 1));
+    }
+
+    #[test]
+    fn distant_changes_split_into_separate_hunks() {
+        let old = (1..=20).map(|n| format!("{n}\n")).collect::<String>();
+        let new = (1..=20)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                18 => "eighteen\n".to_string(),
+                n => format!("{n}\n"),
+            })
+            .collect::<String>();
+
+        let diff = unified_diff(&old, &new, "a", "b", 1, usize::MAX);
+
+        assert_eq!(
+            diff.text,
+            "--- a\n+++ b\n@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -17,3 +17,3 @@\n 17\n-18\n+eighteen\n 19\n"
+        );
+    }
+
+    #[test]
+    fn created_file_diffs_against_dev_null() {
+        let preview = ChangePreview::between("new.txt", None, Some(b"hello\n"));
+
+        let ChangePreview::Text {
+            diff, lines_added, ..
+        } = preview
+        else {
+            panic!("expected text preview");
+        };
+        assert_eq!(
+            diff,
+            "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hello\n"
+        );
+        assert_eq!(lines_added, 1);
+    }
+
+    #[test]
+    fn missing_trailing_newline_is_marked() {
+        let diff = diff("a\nb", "a\nc");
+
+        assert!(diff
+            .text
+            .ends_with("-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    fn output_is_cut_at_a_line_boundary_when_over_the_cap() {
+        let old = (0..200).map(|n| format!("line {n}\n")).collect::<String>();
+        let new = old.replace("line", "LINE");
+
+        let diff = unified_diff(&old, &new, "a", "b", 3, 256);
+
+        assert!(diff.truncated);
+        assert!(diff.text.len() <= 256);
+        assert!(diff.text.ends_with('\n'));
+        assert_eq!((diff.lines_added, diff.lines_removed), (200, 200));
+    }
+
+    #[test]
+    fn binary_content_falls_back_to_size_and_hash_summary() {
+        let before = b"\x89PNG\0\x01";
+        let after = b"\x89PNG\0\x02\x03";
+
+        let preview = ChangePreview::between("logo.png", Some(before), Some(after));
+
+        assert_eq!(
+            preview,
+            ChangePreview::Binary {
+                path: "logo.png".to_string(),
+                old_size: Some(6),
+                new_size: Some(7),
+                old_hash: Some(content_hash(before)),
+                new_hash: Some(content_hash(after)),
+            }
+        );
+        let json = serde_json::to_value(&preview).expect("serialize");
+        assert_eq!(json["kind"], "binary");
+    }
+
+    #[test]
+    fn oversized_text_is_summarized_instead_of_diffed() {
+        let big = "x\n".repeat(PREVIEW_MAX_INPUT_BYTES);
+
+        let preview = ChangePreview::between("big.txt", Some(b"x\n"), Some(big.as_bytes()));
+
+        assert!(matches!(preview, ChangePreview::Binary { .. }));
+    }
+}
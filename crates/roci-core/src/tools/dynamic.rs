@@ -5,9 +5,11 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use super::arguments::ToolArguments;
+#[cfg(feature = "agent")]
+use super::tool::ToolUpdateCallback;
 use super::tool::{
-    Tool, ToolExecutionContext, ToolPromptMetadata, ToolResultSizePolicy, ToolSafetyPlan,
-    ToolSafetySummary,
+    Tool, ToolEffects, ToolExecutionContext, ToolPromptMetadata, ToolResultSizePolicy,
+    ToolSafetyPlan, ToolSafetySummary,
 };
 use super::types::AgentToolParameters;
 use crate::error::RociError;
@@ -20,10 +22,16 @@ pub struct DynamicTool {
     pub aliases: Vec<String>,
     pub prompt: Option<String>,
     pub prompt_metadata: ToolPromptMetadata,
+    /// System-prompt guidance exposed through [`Tool::prompt_guidance`].
+    pub prompt_guidance: Option<String>,
+    /// Rank exposed through [`Tool::priority`].
+    pub priority: Option<i32>,
     pub result_policy: ToolResultSizePolicy,
     pub parameters: AgentToolParameters,
     pub safety: ToolSafetyPlan,
     pub safety_summary: ToolSafetySummary,
+    /// Declared effects; derived from `safety_summary` when `None`.
+    pub effects: Option<ToolEffects>,
 }
 
 impl DynamicTool {
@@ -39,10 +47,13 @@ impl DynamicTool {
             aliases: Vec::new(),
             prompt: None,
             prompt_metadata: ToolPromptMetadata::default(),
+            prompt_guidance: None,
+            priority: None,
             result_policy: ToolResultSizePolicy::default(),
             parameters,
             safety: ToolSafetyPlan::default(),
             safety_summary: ToolSafetySummary::default(),
+            effects: None,
         }
     }
 
@@ -52,6 +63,18 @@ impl DynamicTool {
         self.safety_summary = summary;
         self
     }
+
+    /// Set the rank used when provider tool limits force tools out.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set declared effects for a dynamic tool.
+    pub fn with_effects(mut self, effects: ToolEffects) -> Self {
+        self.effects = Some(effects);
+        self
+    }
 }
 
 /// Trait for providers that can discover and execute tools at runtime.
@@ -89,6 +112,22 @@ pub trait DynamicToolProvider: Send + Sync {
         ctx: &ToolExecutionContext,
     ) -> Result<serde_json::Value, RociError>;
 
+    /// Execute a tool with cancellation and streaming updates.
+    ///
+    /// Defaults to [`execute_tool`](Self::execute_tool), ignoring `cancel`
+    /// and `on_update`.
+    #[cfg(feature = "agent")]
+    async fn execute_tool_ext(
+        &self,
+        name: &str,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+        _cancel: tokio_util::sync::CancellationToken,
+        _on_update: Option<ToolUpdateCallback>,
+    ) -> Result<serde_json::Value, RociError> {
+        self.execute_tool(name, args, ctx).await
+    }
+
     /// Execute a tool only if its current route belongs to one of the selected servers.
     async fn execute_tool_for_servers(
         &self,
@@ -217,10 +256,13 @@ pub struct DynamicToolAdapter {
     aliases: Vec<String>,
     prompt: Option<String>,
     prompt_metadata: ToolPromptMetadata,
+    prompt_guidance: Option<String>,
+    priority: Option<i32>,
     result_policy: ToolResultSizePolicy,
     parameters: AgentToolParameters,
     safety: ToolSafetyPlan,
     safety_summary: ToolSafetySummary,
+    effects: Option<ToolEffects>,
 }
 
 impl DynamicToolAdapter {
@@ -233,10 +275,13 @@ impl DynamicToolAdapter {
             aliases: tool.aliases,
             prompt: tool.prompt,
             prompt_metadata: tool.prompt_metadata,
+            prompt_guidance: tool.prompt_guidance,
+            priority: tool.priority,
             result_policy: tool.result_policy,
             parameters: tool.parameters,
             safety: tool.safety,
             safety_summary: tool.safety_summary,
+            effects: tool.effects,
         }
     }
 }
@@ -263,6 +308,14 @@ impl Tool for DynamicToolAdapter {
         self.prompt_metadata.clone()
     }
 
+    fn prompt_guidance(&self) -> Option<String> {
+        self.prompt_guidance.clone()
+    }
+
+    fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
     fn result_policy(&self) -> ToolResultSizePolicy {
         self.result_policy
     }
@@ -279,6 +332,11 @@ impl Tool for DynamicToolAdapter {
         self.safety_summary
     }
 
+    fn effects(&self) -> ToolEffects {
+        self.effects
+            .unwrap_or_else(|| ToolEffects::from_summary(&self.safety_summary))
+    }
+
     async fn execute(
         &self,
         args: &ToolArguments,
@@ -286,6 +344,19 @@ impl Tool for DynamicToolAdapter {
     ) -> Result<serde_json::Value, RociError> {
         self.provider.execute_tool(&self.name, args, ctx).await
     }
+
+    #[cfg(feature = "agent")]
+    async fn execute_ext(
+        &self,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+        cancel: tokio_util::sync::CancellationToken,
+        on_update: Option<ToolUpdateCallback>,
+    ) -> Result<serde_json::Value, RociError> {
+        self.provider
+            .execute_tool_ext(&self.name, args, ctx, cancel, on_update)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -454,6 +525,7 @@ mod tests {
         assert!(!tool.safety.read_only);
         assert!(!tool.safety.destructive);
         assert!(!tool.safety.concurrency_safe);
+        assert_eq!(tool.effects, None);
     }
 
     #[test]
@@ -476,6 +548,7 @@ mod tests {
             plan
         );
         assert_eq!(adapter.safety_summary(), summary);
+        assert_eq!(adapter.effects(), ToolEffects::ReadOnly);
     }
 
     #[test]
@@ -1,28 +1,43 @@
 //! Tool system for function calling.
 
 pub mod arguments;
+pub mod artifacts;
 pub mod catalog;
+pub mod changes;
+pub mod conversation;
+pub mod diff;
 pub mod dynamic;
+pub mod plan;
+pub mod scratch;
 pub mod tool;
 pub mod types;
 pub mod user_input;
 pub mod validation;
 
 pub use arguments::ToolArguments;
+pub use artifacts::{
+    Artifact, ArtifactLocation, ArtifactSource, ArtifactStore, RunArtifact,
+    DEFAULT_ARTIFACT_SPILL_BYTES,
+};
 pub use catalog::{
     catalog_from_groups, count_by_origin, ToolCatalog, ToolDescriptor, ToolOrigin,
     ToolVisibilityPolicy,
 };
+pub use changes::{ChangeLog, FileChange, FileChangeKind};
+pub use conversation::{ToolMessageQueue, ToolMessageSink, MAX_EMITTED_MESSAGES_PER_TOOL_CALL};
+pub use diff::{unified_diff, ChangePreview, UnifiedDiff};
 pub use dynamic::{
     DynamicTool, DynamicToolAdapter, DynamicToolProvider, ScopedDynamicToolProvider,
 };
+pub use plan::{PlanStep, PlanStepStatus, PlanStore};
+pub use scratch::{scratch_relative, ScratchDir, SCRATCH_ENV_VAR, SCRATCH_SCHEME};
 #[cfg(feature = "agent")]
 pub use tool::ToolUpdateCallback;
 pub use tool::{
-    AgentTool, SandboxProvider, Tool, ToolActionFloor, ToolApprovalRequirement,
-    ToolExecutionContext, ToolFilesystemAccess, ToolPromptMetadata, ToolResourceAccess,
-    ToolResourceAccessMode, ToolResultSizePolicy, ToolSafetyKind, ToolSafetyPlan,
-    ToolSafetyPlanInvariant, ToolSafetySummary,
+    AgentTool, SandboxProvider, Tool, ToolActionFloor, ToolApprovalRequirement, ToolEffects,
+    ToolErrorClass, ToolExecutionContext, ToolFilesystemAccess, ToolPromptMetadata,
+    ToolResourceAccess, ToolResourceAccessMode, ToolResultSizePolicy, ToolSafetyKind,
+    ToolSafetyPlan, ToolSafetyPlanInvariant, ToolSafetySummary,
 };
 pub use types::AgentToolParameters;
 pub use user_input::{
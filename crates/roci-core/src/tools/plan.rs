@@ -0,0 +1,259 @@
+//! Run-scoped plan state maintained by the model through a plan tool.
+//!
+//! The model submits the whole plan on every update. [`PlanStore`] validates
+//! each update against the previous plan so status transitions stay coherent
+//! and rejected updates come back with an error the model can act on.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RociError;
+
+/// Status of a single plan step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStepStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+impl PlanStepStatus {
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Completed => "[x]",
+        }
+    }
+}
+
+/// One step of a model-maintained plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// Short description of the step; identifies the step across updates.
+    pub step: String,
+    pub status: PlanStepStatus,
+}
+
+impl PlanStep {
+    pub fn new(step: impl Into<String>, status: PlanStepStatus) -> Self {
+        Self {
+            step: step.into(),
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PlanState {
+    steps: Vec<PlanStep>,
+    revision: u64,
+}
+
+/// Shared plan state for one run.
+///
+/// Cloning is cheap; clones observe the same plan.
+#[derive(Debug, Clone, Default)]
+pub struct PlanStore {
+    inner: Arc<Mutex<PlanState>>,
+}
+
+impl PlanStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current plan steps.
+    pub fn steps(&self) -> Vec<PlanStep> {
+        self.lock().steps.clone()
+    }
+
+    /// Number of accepted updates; changes whenever the plan is replaced.
+    pub fn revision(&self) -> u64 {
+        self.lock().revision
+    }
+
+    /// Validate `steps` against the current plan and replace it.
+    ///
+    /// Returns [`RociError::InvalidArgument`] describing the first rule the
+    /// update violates; the stored plan is left untouched in that case.
+    pub fn update(&self, steps: Vec<PlanStep>) -> Result<Vec<PlanStep>, RociError> {
+        let steps = steps
+            .into_iter()
+            .map(|step| PlanStep::new(step.step.trim(), step.status))
+            .collect::<Vec<_>>();
+        let mut state = self.lock();
+        validate_plan_update(&state.steps, &steps).map_err(RociError::InvalidArgument)?;
+        state.steps = steps.clone();
+        state.revision += 1;
+        Ok(steps)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PlanState> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Check a plan update against the previous plan.
+///
+/// Rules: the plan has at least one step, step descriptions are non-empty and
+/// unique, at most one step is `in_progress`, and a step can only be
+/// `completed` if the previous plan already had it `in_progress` or
+/// `completed`.
+pub fn validate_plan_update(previous: &[PlanStep], next: &[PlanStep]) -> Result<(), String> {
+    if next.is_empty() {
+        return Err("plan must contain at least one step".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for (index, step) in next.iter().enumerate() {
+        if step.step.is_empty() {
+            return Err(format!("step {} has an empty description", index + 1));
+        }
+        if !seen.insert(step.step.as_str()) {
+            return Err(format!(
+                "step '{}' appears more than once; step descriptions must be unique",
+                step.step
+            ));
+        }
+    }
+
+    let in_progress = next
+        .iter()
+        .filter(|step| step.status == PlanStepStatus::InProgress)
+        .map(|step| format!("'{}'", step.step))
+        .collect::<Vec<_>>();
+    if in_progress.len() > 1 {
+        return Err(format!(
+            "only one step may be in_progress at a time, found {}: {}",
+            in_progress.len(),
+            in_progress.join(", ")
+        ));
+    }
+
+    for step in next
+        .iter()
+        .filter(|step| step.status == PlanStepStatus::Completed)
+    {
+        let started = previous.iter().any(|prior| {
+            prior.step == step.step
+                && matches!(
+                    prior.status,
+                    PlanStepStatus::InProgress | PlanStepStatus::Completed
+                )
+        });
+        if !started {
+            return Err(format!(
+                "step '{}' cannot be completed because it was never started; mark it in_progress in an earlier update first",
+                step.step
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Render a plan as a compact checklist, one step per line.
+pub fn render_plan(steps: &[PlanStep]) -> String {
+    steps
+        .iter()
+        .map(|step| format!("{} {}", step.status.marker(), step.step))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(text: &str, status: PlanStepStatus) -> PlanStep {
+        PlanStep::new(text, status)
+    }
+
+    #[test]
+    fn accepts_valid_progression_and_bumps_revision() {
+        let store = PlanStore::new();
+        store
+            .update(vec![
+                step("read code", PlanStepStatus::InProgress),
+                step("write fix", PlanStepStatus::Pending),
+            ])
+            .expect("initial plan");
+        store
+            .update(vec![
+                step("read code", PlanStepStatus::Completed),
+                step("write fix", PlanStepStatus::InProgress),
+            ])
+            .expect("progressed plan");
+
+        assert_eq!(store.revision(), 2);
+        assert_eq!(store.steps()[0].status, PlanStepStatus::Completed);
+    }
+
+    #[test]
+    fn rejects_multiple_in_progress_steps() {
+        let store = PlanStore::new();
+        let err = store
+            .update(vec![
+                step("a", PlanStepStatus::InProgress),
+                step("b", PlanStepStatus::InProgress),
+            ])
+            .unwrap_err();
+
+        assert!(err.to_string().contains("only one step may be in_progress"));
+        assert_eq!(store.revision(), 0);
+        assert!(store.steps().is_empty());
+    }
+
+    #[test]
+    fn rejects_completing_a_step_that_never_started() {
+        let store = PlanStore::new();
+        store
+            .update(vec![
+                step("a", PlanStepStatus::InProgress),
+                step("b", PlanStepStatus::Pending),
+            ])
+            .expect("initial plan");
+
+        let err = store
+            .update(vec![
+                step("a", PlanStepStatus::Completed),
+                step("b", PlanStepStatus::Completed),
+            ])
+            .unwrap_err();
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(err.to_string().contains("'b' cannot be completed"));
+        assert_eq!(store.steps()[0].status, PlanStepStatus::InProgress);
+    }
+
+    #[test]
+    fn rejects_empty_and_duplicate_steps() {
+        assert!(validate_plan_update(&[], &[]).is_err());
+        assert!(validate_plan_update(&[], &[step("", PlanStepStatus::Pending)]).is_err());
+        assert!(validate_plan_update(
+            &[],
+            &[
+                step("a", PlanStepStatus::Pending),
+                step("a", PlanStepStatus::Pending)
+            ]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn renders_compact_checklist() {
+        let rendered = render_plan(&[
+            step("read code", PlanStepStatus::Completed),
+            step("write fix", PlanStepStatus::InProgress),
+            step("run tests", PlanStepStatus::Pending),
+        ]);
+
+        assert_eq!(rendered, "[x] read code\n[~] write fix\n[ ] run tests");
+    }
+}
@@ -0,0 +1,178 @@
+//! Run-scoped scratch directory for intermediate files.
+//!
+//! Each run gets a directory under the system temp dir, named by run id and
+//! created on first use. Tools reach it through
+//! [`ToolExecutionContext::scratch_dir`](super::tool::ToolExecutionContext::scratch_dir),
+//! and builtin file tools accept [`SCRATCH_SCHEME`] paths relative to it.
+//! The runner removes it when the run ends unless `RunRequest::keep_scratch`
+//! is set.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::RociError;
+
+/// Path prefix naming a file relative to the run's scratch directory.
+pub const SCRATCH_SCHEME: &str = "scratch://";
+
+/// Environment variable the `shell` tool sets to the scratch directory.
+pub const SCRATCH_ENV_VAR: &str = "ROCI_RUN_TMP";
+
+/// Lazily created scratch directory for one run.
+///
+/// Cloning is cheap; clones share the directory.
+#[derive(Debug, Clone)]
+pub struct ScratchDir {
+    inner: Arc<ScratchInner>,
+}
+
+#[derive(Debug)]
+struct ScratchInner {
+    path: PathBuf,
+    created: AtomicBool,
+}
+
+impl ScratchDir {
+    /// Scratch directory for `run_id` under the system temp dir.
+    pub fn for_run(run_id: uuid::Uuid) -> Self {
+        Self::new(std::env::temp_dir().join(format!("roci-run-{run_id}")))
+    }
+
+    /// Scratch directory at `path`, created on first use.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(ScratchInner {
+                path,
+                created: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// The directory, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Io`] when the directory cannot be created.
+    pub fn path(&self) -> Result<&Path, RociError> {
+        if !self.inner.created.load(Ordering::Acquire) {
+            std::fs::create_dir_all(&self.inner.path)?;
+            self.inner.created.store(true, Ordering::Release);
+        }
+        Ok(&self.inner.path)
+    }
+
+    /// Whether any tool has used the directory yet.
+    pub fn is_created(&self) -> bool {
+        self.inner.created.load(Ordering::Acquire)
+    }
+
+    /// Resolve `relative` inside the directory, creating the directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for absolute paths and parent
+    /// traversal, plus the errors of [`path`](Self::path).
+    pub fn resolve(&self, relative: &str) -> Result<PathBuf, RociError> {
+        let requested = Path::new(relative);
+        if requested.components().any(|component| {
+            matches!(
+                component,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        }) {
+            return Err(RociError::InvalidArgument(format!(
+                "{SCRATCH_SCHEME}{relative}: scratch paths must stay inside the scratch directory"
+            )));
+        }
+        Ok(self.path()?.join(requested))
+    }
+
+    /// Whether `path` lies inside the directory.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.inner.path)
+            && !path
+                .components()
+                .any(|component| matches!(component, Component::ParentDir))
+    }
+
+    /// End-of-run cleanup: returns the path when kept, otherwise removes the
+    /// directory. Removal is best effort; a failure (such as a handle a tool
+    /// still holds open) is logged and the directory left behind.
+    #[cfg_attr(not(feature = "agent"), allow(dead_code))]
+    pub(crate) fn finish(&self, keep: bool) -> Option<PathBuf> {
+        if !self.is_created() {
+            return None;
+        }
+        if keep {
+            return Some(self.inner.path.clone());
+        }
+        if let Err(error) = std::fs::remove_dir_all(&self.inner.path) {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    path = %self.inner.path.display(),
+                    %error,
+                    "failed to remove run scratch directory"
+                );
+            }
+        }
+        None
+    }
+}
+
+/// The scratch-relative part of `path`, when it uses [`SCRATCH_SCHEME`].
+pub fn scratch_relative(path: &str) -> Option<&str> {
+    path.strip_prefix(SCRATCH_SCHEME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_is_created_on_first_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = ScratchDir::new(dir.path().join("run"));
+        assert!(!scratch.is_created());
+        assert!(!dir.path().join("run").exists());
+
+        let path = scratch.resolve("out/result.json").unwrap();
+
+        assert!(scratch.is_created());
+        assert!(dir.path().join("run").is_dir());
+        assert_eq!(path, dir.path().join("run/out/result.json"));
+        assert!(scratch.contains(&path));
+    }
+
+    #[test]
+    fn resolve_rejects_escaping_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = ScratchDir::new(dir.path().join("run"));
+
+        for path in ["../escape.txt", "/etc/passwd", "a/../../b"] {
+            assert!(
+                matches!(scratch.resolve(path), Err(RociError::InvalidArgument(_))),
+                "{path}"
+            );
+        }
+        assert_eq!(scratch_relative("scratch://notes.md"), Some("notes.md"));
+        assert_eq!(scratch_relative("notes.md"), None);
+    }
+
+    #[test]
+    fn finish_removes_the_directory_unless_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let unused = ScratchDir::new(dir.path().join("unused"));
+        assert_eq!(unused.finish(false), None);
+
+        let removed = ScratchDir::new(dir.path().join("removed"));
+        std::fs::write(removed.resolve("file.txt").unwrap(), "x").unwrap();
+        assert_eq!(removed.finish(false), None);
+        assert!(!dir.path().join("removed").exists());
+
+        let kept = ScratchDir::new(dir.path().join("kept"));
+        kept.path().unwrap();
+        assert_eq!(kept.finish(true), Some(dir.path().join("kept")));
+        assert!(dir.path().join("kept").is_dir());
+    }
+}
@@ -10,9 +10,11 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::arguments::ToolArguments;
+use super::diff::ChangePreview;
 use super::types::AgentToolParameters;
 use crate::error::RociError;
 use crate::session::{LogicalPath, SessionFs};
+use crate::types::ModelMessage;
 
 /// Validates sandbox-sensitive tool operations before execution.
 #[async_trait]
@@ -53,6 +55,26 @@ pub struct ToolExecutionContext {
     pub workspace_root: Option<PathBuf>,
     /// Optional sandbox validator for command-capable tools.
     pub sandbox_provider: Option<Arc<dyn SandboxProvider>>,
+    /// Run-scoped plan state for plan-tracking tools. None outside a run.
+    pub plan: Option<super::plan::PlanStore>,
+    /// Run-scoped log of files changed by tools. None outside a run.
+    pub changes: Option<super::changes::ChangeLog>,
+    /// Run-scoped registry of tool outputs. None outside a run.
+    pub artifacts: Option<super::artifacts::ArtifactStore>,
+    /// Run-scoped scratch directory. None outside a run.
+    pub scratch: Option<super::scratch::ScratchDir>,
+    /// Long-term memory shared across runs. None if not configured.
+    pub memory: Option<Arc<dyn crate::memory::Memory>>,
+    /// Id of the run executing the tool. None outside a run.
+    pub run_id: Option<uuid::Uuid>,
+    /// Conversation as of the current iteration, shared by every call in the
+    /// tool batch. None outside a run.
+    pub conversation: Option<Arc<[ModelMessage]>>,
+    /// Queue for messages appended after the tool batch. None outside a run.
+    pub message_sink: Option<super::conversation::ToolMessageSink>,
+    /// When the run must end; see [`remaining_timeout`](Self::remaining_timeout).
+    /// None without a run deadline.
+    pub deadline: Option<std::time::Instant>,
     /// Callback to request user input. None if not configured.
     #[cfg(feature = "agent")]
     pub request_user_input: Option<super::user_input::RequestUserInputFn>,
@@ -68,12 +90,92 @@ impl Default for ToolExecutionContext {
             session_cwd: None,
             workspace_root: None,
             sandbox_provider: None,
+            plan: None,
+            changes: None,
+            artifacts: None,
+            scratch: None,
+            memory: None,
+            run_id: None,
+            conversation: None,
+            message_sink: None,
+            deadline: None,
             #[cfg(feature = "agent")]
             request_user_input: None,
         }
     }
 }
 
+impl ToolExecutionContext {
+    /// `timeout` cut down to the time left before the run deadline.
+    pub fn remaining_timeout(&self, timeout: std::time::Duration) -> std::time::Duration {
+        match self.deadline {
+            Some(deadline) => {
+                timeout.min(deadline.saturating_duration_since(std::time::Instant::now()))
+            }
+            None => timeout,
+        }
+    }
+
+    /// Queue `message` to be appended to the conversation after this tool
+    /// batch completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::UnsupportedOperation`] outside a run, plus the
+    /// errors of [`ToolMessageSink::emit`](super::conversation::ToolMessageSink::emit).
+    pub fn emit_message(&self, message: ModelMessage) -> Result<(), RociError> {
+        let sink = self.message_sink.as_ref().ok_or_else(|| {
+            RociError::UnsupportedOperation(
+                "emitting messages requires a tool call inside an agent run".to_string(),
+            )
+        })?;
+        sink.emit(message)
+    }
+
+    /// The run's scratch directory, created on first call.
+    ///
+    /// Tools write intermediate files here instead of the workspace; the
+    /// runner removes the directory when the run ends unless
+    /// `RunRequest::keep_scratch` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::UnsupportedOperation`] outside a run, plus the
+    /// errors of [`ScratchDir::path`](super::scratch::ScratchDir::path).
+    pub fn scratch_dir(&self) -> Result<PathBuf, RociError> {
+        let scratch = self.scratch.as_ref().ok_or_else(|| {
+            RociError::UnsupportedOperation(
+                "a scratch directory requires a tool call inside an agent run".to_string(),
+            )
+        })?;
+        Ok(scratch.path()?.to_path_buf())
+    }
+
+    /// Register an output of this tool call with the run, which reports it
+    /// in [`RunEventPayload::ArtifactAdded`](crate::agent_loop::RunEventPayload::ArtifactAdded)
+    /// and `RunResult::artifacts`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::UnsupportedOperation`] outside a run, plus the
+    /// errors of [`ArtifactStore::add`](super::artifacts::ArtifactStore::add).
+    pub fn add_artifact(
+        &self,
+        artifact: super::artifacts::Artifact,
+    ) -> Result<super::artifacts::RunArtifact, RociError> {
+        let store = self.artifacts.as_ref().ok_or_else(|| {
+            RociError::UnsupportedOperation(
+                "registering artifacts requires a tool call inside an agent run".to_string(),
+            )
+        })?;
+        store.add(
+            artifact,
+            self.tool_call_id.as_deref(),
+            self.tool_name.as_deref(),
+        )
+    }
+}
+
 #[cfg(feature = "agent")]
 impl std::fmt::Debug for ToolExecutionContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -91,6 +193,18 @@ impl std::fmt::Debug for ToolExecutionContext {
                 "sandbox_provider",
                 &self.sandbox_provider.as_ref().map(|_| "<sandbox_provider>"),
             )
+            .field("plan", &self.plan)
+            .field("changes", &self.changes)
+            .field("artifacts", &self.artifacts)
+            .field("scratch", &self.scratch)
+            .field("memory", &self.memory.as_ref().map(|_| "<memory>"))
+            .field("run_id", &self.run_id)
+            .field(
+                "conversation",
+                &self.conversation.as_ref().map(|messages| messages.len()),
+            )
+            .field("message_sink", &self.message_sink)
+            .field("deadline", &self.deadline)
             .field(
                 "request_user_input",
                 &self.request_user_input.as_ref().map(|_| "<callback>"),
@@ -116,6 +230,18 @@ impl std::fmt::Debug for ToolExecutionContext {
                 "sandbox_provider",
                 &self.sandbox_provider.as_ref().map(|_| "<sandbox_provider>"),
             )
+            .field("plan", &self.plan)
+            .field("changes", &self.changes)
+            .field("artifacts", &self.artifacts)
+            .field("scratch", &self.scratch)
+            .field("memory", &self.memory.as_ref().map(|_| "<memory>"))
+            .field("run_id", &self.run_id)
+            .field(
+                "conversation",
+                &self.conversation.as_ref().map(|messages| messages.len()),
+            )
+            .field("message_sink", &self.message_sink)
+            .field("deadline", &self.deadline)
             .finish()
     }
 }
@@ -232,6 +358,115 @@ impl Default for ToolSafetySummary {
     }
 }
 
+/// Coarse side-effect class of a tool, used for approval gating.
+///
+/// Declared per tool through [`Tool::effects`] and refined per call by the
+/// input-aware [`ToolSafetyPlan`] (see [`ToolEffects::for_call`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolEffects {
+    /// Only reads state; never changes anything outside the run.
+    ReadOnly,
+    /// Changes local state such as files or processes.
+    Mutating,
+    /// Reaches external services whose state may change.
+    Network,
+    /// Irreversible or high-impact changes.
+    Destructive,
+}
+
+impl ToolEffects {
+    /// Effects implied by a static safety summary.
+    pub fn from_summary(summary: &ToolSafetySummary) -> Self {
+        if summary.destructive_by_default {
+            Self::Destructive
+        } else if summary.read_only_by_default {
+            Self::ReadOnly
+        } else {
+            Self::Mutating
+        }
+    }
+
+    /// Effects of a single call: facts from the call's safety plan take
+    /// precedence over the tool's declared effects.
+    pub fn for_call(declared: Self, plan: &ToolSafetyPlan) -> Self {
+        if plan.destructive {
+            Self::Destructive
+        } else if plan.read_only {
+            Self::ReadOnly
+        } else {
+            declared
+        }
+    }
+
+    pub fn is_read_only(self) -> bool {
+        matches!(self, Self::ReadOnly)
+    }
+}
+
+/// Why a tool call failed, as far as retrying it is concerned.
+///
+/// Produced by [`Tool::classify_error`] and matched against the runner's
+/// tool retry policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorClass {
+    RateLimit,
+    Network,
+    Timeout,
+    /// The upstream service failed on its side, such as an HTTP 5xx.
+    Server,
+    /// A resource was briefly unavailable: a held lock, an interrupted or
+    /// reset I/O operation.
+    Busy,
+    /// Retrying would fail the same way.
+    Permanent,
+}
+
+impl ToolErrorClass {
+    /// Every class except [`Permanent`](Self::Permanent).
+    pub const TRANSIENT: [Self; 5] = [
+        Self::RateLimit,
+        Self::Network,
+        Self::Timeout,
+        Self::Server,
+        Self::Busy,
+    ];
+
+    /// Default classification from the error's [`ErrorCategory`] and, for
+    /// I/O errors, its [`std::io::ErrorKind`].
+    ///
+    /// [`ErrorCategory`]: crate::error::ErrorCategory
+    pub fn classify(error: &RociError) -> Self {
+        use crate::error::ErrorCategory;
+        use std::io::ErrorKind;
+
+        match error.category() {
+            ErrorCategory::RateLimit => return Self::RateLimit,
+            ErrorCategory::Network => return Self::Network,
+            ErrorCategory::Timeout => return Self::Timeout,
+            ErrorCategory::Server => return Self::Server,
+            _ => {}
+        }
+        match error {
+            RociError::Io(io) => match io.kind() {
+                ErrorKind::TimedOut => Self::Timeout,
+                ErrorKind::WouldBlock
+                | ErrorKind::Interrupted
+                | ErrorKind::ResourceBusy
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted => Self::Busy,
+                _ => Self::Permanent,
+            },
+            _ => Self::Permanent,
+        }
+    }
+
+    pub fn is_transient(self) -> bool {
+        !matches!(self, Self::Permanent)
+    }
+}
+
 /// Policy for bounding tool result payloads.
 ///
 /// The default 64 KiB cap keeps provider payloads, session ledgers, and event
@@ -280,6 +515,10 @@ pub struct ToolSafetyPlan {
     pub filesystem: Vec<ToolFilesystemAccess>,
     /// Resource access facts, when available.
     pub resources: Vec<ToolResourceAccess>,
+    /// What the call would change, filled from [`Tool::approval_preview`]
+    /// before an approval request is raised.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub change_previews: Vec<ChangePreview>,
 }
 
 impl ToolSafetyPlan {
@@ -483,6 +722,10 @@ impl std::fmt::Display for ToolSafetyPlanInvariant {
 
 impl std::error::Error for ToolSafetyPlanInvariant {}
 
+/// Default [`Tool::simulate`] output: a dry run recorded the call without
+/// executing it.
+pub const DRY_RUN_NOT_EXECUTED: &str = "dry-run: not executed";
+
 /// Core tool trait -- implement to create custom tools.
 ///
 /// Existing implementations only need [`execute`]. The agent loop calls
@@ -516,6 +759,18 @@ pub trait Tool: Send + Sync {
         ToolPromptMetadata::default()
     }
 
+    /// Usage guidance contributed to the system prompt's tool-guidance
+    /// section. `None` (the default) contributes nothing.
+    fn prompt_guidance(&self) -> Option<String> {
+        None
+    }
+
+    /// Rank among the run's tools when a provider's tool limits force some
+    /// out; higher is kept first. `None` ranks as `0`.
+    fn priority(&self) -> Option<i32> {
+        None
+    }
+
     /// Tool result size policy.
     fn result_policy(&self) -> ToolResultSizePolicy {
         ToolResultSizePolicy::default()
@@ -536,6 +791,42 @@ pub trait Tool: Send + Sync {
         ToolSafetySummary::default()
     }
 
+    /// Declared side effects, used by approval policies.
+    ///
+    /// Derived from [`Self::safety_summary`] by default, so tools without
+    /// safety metadata count as [`ToolEffects::Mutating`].
+    fn effects(&self) -> ToolEffects {
+        ToolEffects::from_summary(&self.safety_summary())
+    }
+
+    /// Classify a failed call for the runner's tool retry policy.
+    ///
+    /// Defaults to [`ToolErrorClass::classify`]. Override to mark
+    /// tool-specific failures, such as a lock held by another process, as
+    /// transient.
+    fn classify_error(&self, error: &RociError) -> ToolErrorClass {
+        ToolErrorClass::classify(error)
+    }
+
+    /// Preview of the changes this call would make, shown to approval
+    /// handlers before the call runs.
+    ///
+    /// Must not mutate anything. Defaults to no preview.
+    async fn approval_preview(
+        &self,
+        _args: &ToolArguments,
+        _ctx: &ToolExecutionContext,
+    ) -> Vec<ChangePreview> {
+        Vec::new()
+    }
+
+    /// Stand-in output recorded by a dry run, which never calls
+    /// [`execute`]. Must not mutate anything. Defaults to
+    /// [`DRY_RUN_NOT_EXECUTED`].
+    fn simulate(&self, _args: &ToolArguments) -> serde_json::Value {
+        serde_json::Value::String(DRY_RUN_NOT_EXECUTED.to_string())
+    }
+
     /// Execute the tool with parsed arguments.
     async fn execute(
         &self,
@@ -571,6 +862,14 @@ type ToolHandler = dyn Fn(
 /// Type alias for the tool safety handler function.
 type ToolSafetyHandler = dyn Fn(&ToolArguments) -> ToolSafetyPlan + Send + Sync;
 
+/// Type alias for the approval preview function.
+type ToolPreviewHandler = dyn Fn(
+        ToolArguments,
+        ToolExecutionContext,
+    ) -> Pin<Box<dyn Future<Output = Vec<ChangePreview>> + Send>>
+    + Send
+    + Sync;
+
 /// Closure-based tool for quick tool creation.
 pub struct AgentTool {
     name: String,
@@ -578,10 +877,14 @@ pub struct AgentTool {
     aliases: Vec<String>,
     prompt: Option<String>,
     prompt_metadata: ToolPromptMetadata,
+    prompt_guidance: Option<String>,
+    priority: Option<i32>,
     result_policy: ToolResultSizePolicy,
     parameters: AgentToolParameters,
     safety_summary: ToolSafetySummary,
+    effects: Option<ToolEffects>,
     safety_handler: Arc<ToolSafetyHandler>,
+    preview_handler: Option<Arc<ToolPreviewHandler>>,
     handler: Arc<ToolHandler>,
 }
 
@@ -603,10 +906,14 @@ impl AgentTool {
             aliases: Vec::new(),
             prompt: None,
             prompt_metadata: ToolPromptMetadata::default(),
+            prompt_guidance: None,
+            priority: None,
             result_policy: ToolResultSizePolicy::default(),
             parameters,
             safety_summary: ToolSafetySummary::default(),
+            effects: None,
             safety_handler: Arc::new(|_args| ToolSafetyPlan::default()),
+            preview_handler: None,
             handler: Arc::new(move |args, ctx| Box::pin(handler(args, ctx))),
         }
     }
@@ -629,6 +936,18 @@ impl AgentTool {
         self
     }
 
+    /// Set system-prompt guidance for this tool.
+    pub fn with_prompt_guidance(mut self, guidance: impl Into<String>) -> Self {
+        self.prompt_guidance = Some(guidance.into());
+        self
+    }
+
+    /// Set the rank used when provider tool limits force tools out.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
     /// Set result size policy.
     pub fn with_result_policy(mut self, policy: ToolResultSizePolicy) -> Self {
         self.result_policy = policy;
@@ -652,6 +971,22 @@ impl AgentTool {
         self.safety_handler = Arc::new(safety);
         self
     }
+
+    /// Override the effects otherwise derived from the safety summary.
+    pub fn with_effects(mut self, effects: ToolEffects) -> Self {
+        self.effects = Some(effects);
+        self
+    }
+
+    /// Set a handler that previews a call's changes for approval prompts.
+    pub fn with_approval_preview<F, Fut>(mut self, preview: F) -> Self
+    where
+        F: Fn(ToolArguments, ToolExecutionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Vec<ChangePreview>> + Send + 'static,
+    {
+        self.preview_handler = Some(Arc::new(move |args, ctx| Box::pin(preview(args, ctx))));
+        self
+    }
 }
 
 #[async_trait]
@@ -676,6 +1011,14 @@ impl Tool for AgentTool {
         self.prompt_metadata.clone()
     }
 
+    fn prompt_guidance(&self) -> Option<String> {
+        self.prompt_guidance.clone()
+    }
+
+    fn priority(&self) -> Option<i32> {
+        self.priority
+    }
+
     fn result_policy(&self) -> ToolResultSizePolicy {
         self.result_policy
     }
@@ -692,6 +1035,22 @@ impl Tool for AgentTool {
         self.safety_summary
     }
 
+    fn effects(&self) -> ToolEffects {
+        self.effects
+            .unwrap_or_else(|| ToolEffects::from_summary(&self.safety_summary))
+    }
+
+    async fn approval_preview(
+        &self,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+    ) -> Vec<ChangePreview> {
+        match &self.preview_handler {
+            Some(preview) => preview(args.clone(), ctx.clone()).await,
+            None => Vec::new(),
+        }
+    }
+
     async fn execute(
         &self,
         args: &ToolArguments,
@@ -732,6 +1091,87 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn effects_follow_summary_and_call_plan() {
+        assert_eq!(
+            ToolEffects::from_summary(&ToolSafetySummary::default()),
+            ToolEffects::Mutating
+        );
+        let read_only_summary = ToolSafetySummary {
+            read_only_by_default: true,
+            concurrency_safe_by_default: true,
+            ..ToolSafetySummary::default()
+        };
+        assert_eq!(
+            ToolEffects::from_summary(&read_only_summary),
+            ToolEffects::ReadOnly
+        );
+
+        let listing = ToolSafetyPlan::from_command_insight(
+            crate::security::command::classify_shell_command("ls -la"),
+        );
+        assert_eq!(
+            ToolEffects::for_call(ToolEffects::Mutating, &listing),
+            ToolEffects::ReadOnly
+        );
+        assert_eq!(
+            ToolEffects::for_call(
+                ToolEffects::ReadOnly,
+                &ToolSafetyPlan::file_delete("/tmp/x")
+            ),
+            ToolEffects::Destructive
+        );
+        assert_eq!(
+            ToolEffects::for_call(ToolEffects::Network, &ToolSafetyPlan::default()),
+            ToolEffects::Network
+        );
+    }
+
+    #[test]
+    fn error_class_follows_category_and_io_kind() {
+        assert_eq!(
+            ToolErrorClass::classify(&RociError::Timeout(100)),
+            ToolErrorClass::Timeout
+        );
+        assert_eq!(
+            ToolErrorClass::classify(&RociError::RateLimited {
+                retry_after_ms: None
+            }),
+            ToolErrorClass::RateLimit
+        );
+        assert_eq!(
+            ToolErrorClass::classify(&RociError::Io(std::io::Error::from(
+                std::io::ErrorKind::ResourceBusy
+            ))),
+            ToolErrorClass::Busy
+        );
+        assert_eq!(
+            ToolErrorClass::classify(&RociError::Io(std::io::Error::from(
+                std::io::ErrorKind::NotFound
+            ))),
+            ToolErrorClass::Permanent
+        );
+        assert_eq!(
+            ToolErrorClass::classify(&RociError::InvalidArgument("bad".into())),
+            ToolErrorClass::Permanent
+        );
+    }
+
+    #[test]
+    fn agent_tool_effects_override_summary() {
+        let tool = AgentTool::new(
+            "plan",
+            "records a plan",
+            AgentToolParameters::empty(),
+            |_args, _ctx| async { Ok(serde_json::Value::Null) },
+        )
+        .with_static_safety(ToolSafetyPlan::host_input(), ToolSafetySummary::default());
+        assert_eq!(tool.effects(), ToolEffects::Mutating);
+
+        let tool = tool.with_effects(ToolEffects::ReadOnly);
+        assert_eq!(tool.effects(), ToolEffects::ReadOnly);
+    }
+
     #[test]
     fn validate_rejects_destructive_read_only() {
         let mut plan = ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read);
@@ -26,7 +26,7 @@ pub fn validate_arguments(
         for field in required {
             if let Some(name) = field.as_str() {
                 if !obj.contains_key(name) {
-                    return Err(format!("missing required field '{name}'"));
+                    return Err(missing_field_message(name));
                 }
             }
         }
@@ -40,12 +40,7 @@ pub fn validate_arguments(
             if let Some(prop_schema) = properties.get(key) {
                 if let Some(expected_type) = prop_schema.get("type").and_then(|v| v.as_str()) {
                     if !value_matches_type(value, expected_type) {
-                        return Err(format!(
-                            "field '{}' expected type '{}', got {}",
-                            key,
-                            expected_type,
-                            json_type_name(value)
-                        ));
+                        return Err(type_mismatch_message(key, expected_type, value));
                     }
                 }
             }
@@ -68,6 +63,23 @@ fn value_matches_type(value: &serde_json::Value, expected: &str) -> bool {
     }
 }
 
+/// Validation message for a required field that is absent.
+pub(crate) fn missing_field_message(field: &str) -> String {
+    format!("missing required field '{field}'")
+}
+
+/// Validation message for a field whose JSON type does not match.
+pub(crate) fn type_mismatch_message(
+    field: &str,
+    expected: &str,
+    value: &serde_json::Value,
+) -> String {
+    format!(
+        "field '{field}' expected type '{expected}', got {}",
+        json_type_name(value)
+    )
+}
+
 fn json_type_name(value: &serde_json::Value) -> &'static str {
     match value {
         serde_json::Value::Null => "null",
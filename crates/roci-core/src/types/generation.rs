@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use strum::{Display, EnumString};
 
 /// Settings controlling text generation.
-#[derive(Debug, Clone, Builder, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Builder, Serialize, Deserialize, Default, PartialEq)]
 pub struct GenerationSettings {
     pub max_tokens: Option<u32>,
     pub temperature: Option<f64>,
@@ -22,13 +22,21 @@ pub struct GenerationSettings {
     pub openai_responses: Option<OpenAiResponsesOptions>,
     pub anthropic: Option<AnthropicOptions>,
     pub google: Option<GoogleOptions>,
+    pub mistral: Option<MistralOptions>,
+    pub grok: Option<GrokOptions>,
+    pub groq: Option<GroqOptions>,
+    /// Continue the trailing assistant message instead of starting a new turn.
+    ///
+    /// Only providers that support prefix completion accept this; others
+    /// reject the request.
+    pub assistant_prefix: Option<bool>,
     pub tool_choice: Option<ToolChoice>,
     pub user: Option<String>,
     pub stream_idle_timeout_ms: Option<u64>,
 }
 
 /// OpenAI Responses API request options.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct OpenAiResponsesOptions {
     pub parallel_tool_calls: Option<bool>,
     pub previous_response_id: Option<String>,
@@ -37,6 +45,12 @@ pub struct OpenAiResponsesOptions {
     pub service_tier: Option<OpenAiServiceTier>,
     pub truncation: Option<OpenAiTruncation>,
     pub store: Option<bool>,
+    /// Run the response in background mode (`background: true`, which also
+    /// forces `store: true`). Streams that drop are resumed from the last
+    /// received sequence number.
+    pub background: Option<bool>,
+    /// Resume attempts for a dropped background stream. Defaults to 3.
+    pub max_background_resumes: Option<u32>,
 }
 
 /// OpenAI service tier for Responses API requests.
@@ -86,7 +100,7 @@ pub enum TextVerbosity {
 }
 
 /// Anthropic-specific request options.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct AnthropicOptions {
     /// Enable extended thinking with a budget.
     pub thinking: Option<ThinkingMode>,
@@ -118,7 +132,7 @@ pub enum CacheControl {
 }
 
 /// Google/Gemini-specific request options.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct GoogleOptions {
     /// Thinking configuration for Gemini 2.5+/3 models.
     pub thinking_config: Option<GoogleThinkingConfig>,
@@ -126,6 +140,135 @@ pub struct GoogleOptions {
     pub safety_settings: Option<GoogleSafetyLevel>,
 }
 
+/// Mistral chat completion options.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MistralOptions {
+    /// Prepend Mistral's safety system prompt to the conversation.
+    pub safe_prompt: Option<bool>,
+}
+
+/// xAI Grok chat completion options.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct GrokOptions {
+    /// Live search, sent as `search_parameters`.
+    pub search: Option<GrokSearchParameters>,
+}
+
+/// Grok live search parameters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrokSearchParameters {
+    pub mode: GrokSearchMode,
+    /// Include source URLs in the response (xAI defaults to true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_citations: Option<bool>,
+    /// Only search data from this date on (serialized as `YYYY-MM-DD`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_date: Option<chrono::NaiveDate>,
+    /// Only search data up to this date (serialized as `YYYY-MM-DD`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_date: Option<chrono::NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_search_results: Option<u32>,
+    /// Sources to search; xAI searches web and X when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<GrokSearchSource>,
+}
+
+impl GrokSearchParameters {
+    pub fn new(mode: GrokSearchMode) -> Self {
+        Self {
+            mode,
+            return_citations: None,
+            from_date: None,
+            to_date: None,
+            max_search_results: None,
+            sources: Vec::new(),
+        }
+    }
+}
+
+/// Whether Grok searches before answering.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GrokSearchMode {
+    /// Never search.
+    Off,
+    /// Let the model decide.
+    Auto,
+    /// Always search.
+    On,
+}
+
+/// Groq chat completion options.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct GroqOptions {
+    /// How reasoning models return their reasoning; only valid for models
+    /// that support reasoning.
+    pub reasoning_format: Option<GroqReasoningFormat>,
+    pub service_tier: Option<GroqServiceTier>,
+}
+
+/// Where a Groq reasoning model puts its reasoning.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GroqReasoningFormat {
+    /// Inline in the content, wrapped in `<think>` tags.
+    Raw,
+    /// In a separate `reasoning` field.
+    Parsed,
+    /// Not returned.
+    Hidden,
+}
+
+/// Groq service tier.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GroqServiceTier {
+    OnDemand,
+    Flex,
+    Auto,
+}
+
+/// A Grok live search data source.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GrokSearchSource {
+    Web {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        country: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        allowed_websites: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        excluded_websites: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        safe_search: Option<bool>,
+    },
+    X {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        included_x_handles: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        excluded_x_handles: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        post_favorite_count: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        post_view_count: Option<u32>,
+    },
+    News {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        country: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        excluded_websites: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        safe_search: Option<bool>,
+    },
+    Rss {
+        links: Vec<String>,
+    },
+}
+
 /// Google Gemini thinking configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GoogleThinkingConfig {
@@ -185,13 +328,107 @@ pub enum ResponseFormat {
 }
 
 /// Why generation finished.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display, EnumString)]
-#[serde(rename_all = "snake_case")]
-#[strum(serialize_all = "snake_case")]
+///
+/// Providers map the reasons they document onto the named variants and keep
+/// any other value verbatim in [`FinishReason::Other`]. Serializes as the
+/// snake_case variant name, or the raw string for `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum FinishReason {
     Stop,
     Length,
     ToolCalls,
     ContentFilter,
     Error,
+    /// The model declined the request; the refusal text is reported
+    /// separately from the answer text.
+    Refusal,
+    /// A provider value with no named variant, as the provider sent it.
+    Other(String),
+}
+
+impl FinishReason {
+    /// The snake_case name, or the raw value for [`FinishReason::Other`].
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Stop => "stop",
+            Self::Length => "length",
+            Self::ToolCalls => "tool_calls",
+            Self::ContentFilter => "content_filter",
+            Self::Error => "error",
+            Self::Refusal => "refusal",
+            Self::Other(raw) => raw,
+        }
+    }
+
+    pub fn is_other(&self) -> bool {
+        matches!(self, Self::Other(_))
+    }
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for FinishReason {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s.to_string()))
+    }
+}
+
+impl From<String> for FinishReason {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "stop" => Self::Stop,
+            "length" => Self::Length,
+            "tool_calls" => Self::ToolCalls,
+            "content_filter" => Self::ContentFilter,
+            "error" => Self::Error,
+            "refusal" => Self::Refusal,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<FinishReason> for String {
+    fn from(value: FinishReason) -> Self {
+        match value {
+            FinishReason::Other(raw) => raw,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finish_reasons_round_trip_through_serde() {
+        for reason in [
+            FinishReason::Stop,
+            FinishReason::ToolCalls,
+            FinishReason::Refusal,
+            FinishReason::Other("max_output_tokens".to_string()),
+        ] {
+            let json = serde_json::to_value(&reason).unwrap();
+            assert_eq!(json, serde_json::json!(reason.as_str()));
+            assert_eq!(
+                serde_json::from_value::<FinishReason>(json).unwrap(),
+                reason
+            );
+        }
+        assert_eq!(
+            serde_json::from_str::<FinishReason>(r#""pause_turn""#).unwrap(),
+            FinishReason::Other("pause_turn".to_string())
+        );
+        assert_eq!(
+            "content_filter".parse::<FinishReason>(),
+            Ok(FinishReason::ContentFilter)
+        );
+    }
 }
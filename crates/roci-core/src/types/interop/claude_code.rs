@@ -0,0 +1,224 @@
+//! Claude Code sessions: `~/.claude/projects/<project>/<session-id>.jsonl`.
+//!
+//! Conversation lines have `type` `user` or `assistant`, an Anthropic
+//! Messages API `message`, and the writing CLI's `version`. An assistant turn
+//! is split across lines sharing `message.id`; those are merged back into one
+//! message. Tool results arrive as `tool_result` blocks in user messages and
+//! become [`Role::Tool`] messages.
+
+use serde_json::Value;
+
+use super::{
+    major_version, parse_lines, str_field, timestamp_field, ImportedSession, Importer,
+    InteropError, SessionFormat,
+};
+use crate::types::message::{
+    AgentToolCall, AgentToolResult, ContentPart, ImageContent, RedactedThinkingContent, Role,
+    ThinkingContent,
+};
+
+const SUPPORTED_MAJOR_VERSIONS: &[u64] = &[1, 2];
+const SUPPORTED_VERSIONS: &str = "1.x, 2.x";
+
+/// Line types that carry no conversation content.
+const METADATA_TYPES: &[&str] = &["summary", "system", "file-history-snapshot"];
+
+/// Convert a Claude Code session file.
+///
+/// # Errors
+///
+/// Returns [`InteropError::UnrecognizedFormat`] when no line records a
+/// version, and [`InteropError::UnsupportedVersion`] for versions outside
+/// 1.x and 2.x.
+pub fn import_claude_code(input: &str) -> Result<ImportedSession, InteropError> {
+    let mut importer = Importer::default();
+    let entries = parse_lines(input, &mut importer);
+    let version = entries
+        .iter()
+        .find_map(|(_, entry)| str_field(entry, "version"))
+        .ok_or_else(|| InteropError::UnrecognizedFormat {
+            format: SessionFormat::ClaudeCode,
+            reason: "no entry records a `version`".to_string(),
+        })?
+        .to_string();
+    if !major_version(&version).is_some_and(|major| SUPPORTED_MAJOR_VERSIONS.contains(&major)) {
+        return Err(InteropError::UnsupportedVersion {
+            format: SessionFormat::ClaudeCode,
+            version,
+            supported: SUPPORTED_VERSIONS,
+        });
+    }
+
+    let mut last_assistant_id = None;
+    for (line, entry) in &entries {
+        let line = *line;
+        match str_field(entry, "type") {
+            Some("user" | "assistant") => {}
+            Some(kind) if METADATA_TYPES.contains(&kind) => continue,
+            Some(kind) => {
+                importer.warn(line, format!("unsupported entry type `{kind}`"));
+                continue;
+            }
+            None => {
+                importer.warn(line, "entry has no `type`");
+                continue;
+            }
+        }
+        if entry.get("isSidechain").and_then(Value::as_bool) == Some(true) {
+            importer.warn(line, "dropped subagent (sidechain) message");
+            continue;
+        }
+        let Some(message) = entry.get("message") else {
+            importer.warn(line, "entry has no `message`");
+            continue;
+        };
+        let timestamp = timestamp_field(entry);
+        match str_field(message, "role") {
+            Some("assistant") => {
+                let id = str_field(message, "id").map(str::to_string);
+                let parts = assistant_parts(message, line, &mut importer);
+                let continues_turn = id.is_some()
+                    && id == last_assistant_id
+                    && importer
+                        .messages
+                        .last()
+                        .is_some_and(|last| last.role == Role::Assistant);
+                if continues_turn {
+                    if let Some(last) = importer.messages.last_mut() {
+                        last.content.extend(parts);
+                    }
+                } else {
+                    importer.push(Role::Assistant, parts, timestamp);
+                }
+                last_assistant_id = id;
+            }
+            Some("user") => {
+                last_assistant_id = None;
+                push_user(message, line, timestamp, &mut importer);
+            }
+            other => importer.warn(
+                line,
+                format!("unsupported message role `{}`", other.unwrap_or("<none>")),
+            ),
+        }
+    }
+    Ok(importer.finish(SessionFormat::ClaudeCode, version))
+}
+
+fn content_blocks(message: &Value) -> Vec<Value> {
+    match message.get("content") {
+        Some(Value::String(text)) => vec![serde_json::json!({ "type": "text", "text": text })],
+        Some(Value::Array(blocks)) => blocks.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn assistant_parts(message: &Value, line: usize, importer: &mut Importer) -> Vec<ContentPart> {
+    let mut parts = Vec::new();
+    for block in content_blocks(message) {
+        match str_field(&block, "type") {
+            Some("text") => parts.push(ContentPart::Text {
+                text: str_field(&block, "text").unwrap_or_default().to_string(),
+            }),
+            Some("tool_use") => parts.push(ContentPart::ToolCall(AgentToolCall {
+                id: str_field(&block, "id").unwrap_or_default().to_string(),
+                name: str_field(&block, "name").unwrap_or_default().to_string(),
+                arguments: block.get("input").cloned().unwrap_or(Value::Null),
+                called_as: None,
+                recipient: None,
+            })),
+            Some("thinking") => parts.push(ContentPart::Thinking(ThinkingContent {
+                thinking: str_field(&block, "thinking")
+                    .unwrap_or_default()
+                    .to_string(),
+                signature: str_field(&block, "signature")
+                    .unwrap_or_default()
+                    .to_string(),
+            })),
+            Some("redacted_thinking") => {
+                parts.push(ContentPart::RedactedThinking(RedactedThinkingContent {
+                    data: str_field(&block, "data").unwrap_or_default().to_string(),
+                    signature: String::new(),
+                }))
+            }
+            other => importer.warn(
+                line,
+                format!("dropped assistant `{}` block", other.unwrap_or("<untyped>")),
+            ),
+        }
+    }
+    parts
+}
+
+/// Push a user entry: its tool results as [`Role::Tool`] messages, then any
+/// remaining content as a user message.
+fn push_user(
+    message: &Value,
+    line: usize,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    importer: &mut Importer,
+) {
+    let mut parts = Vec::new();
+    for block in content_blocks(message) {
+        match str_field(&block, "type") {
+            Some("text") => parts.push(ContentPart::Text {
+                text: str_field(&block, "text").unwrap_or_default().to_string(),
+            }),
+            Some("image") => match image(&block) {
+                Some(image) => parts.push(ContentPart::Image(image)),
+                None => importer.warn(line, "dropped image without base64 source"),
+            },
+            Some("tool_result") => {
+                let result = AgentToolResult {
+                    tool_call_id: str_field(&block, "tool_use_id")
+                        .unwrap_or_default()
+                        .to_string(),
+                    result: tool_result_value(block.get("content"), line, importer),
+                    is_error: block.get("is_error").and_then(Value::as_bool) == Some(true),
+                };
+                importer.push(Role::Tool, vec![ContentPart::ToolResult(result)], timestamp);
+            }
+            other => importer.warn(
+                line,
+                format!("dropped user `{}` block", other.unwrap_or("<untyped>")),
+            ),
+        }
+    }
+    importer.push(Role::User, parts, timestamp);
+}
+
+fn image(block: &Value) -> Option<ImageContent> {
+    let source = block.get("source")?;
+    if str_field(source, "type")? != "base64" {
+        return None;
+    }
+    Some(ImageContent {
+        data: str_field(source, "data")?.to_string(),
+        mime_type: str_field(source, "media_type")?.to_string(),
+    })
+}
+
+/// Tool result content as a string: text blocks are joined, other blocks
+/// dropped with a warning.
+fn tool_result_value(content: Option<&Value>, line: usize, importer: &mut Importer) -> Value {
+    match content {
+        None | Some(Value::Null) => Value::String(String::new()),
+        Some(Value::Array(blocks)) => {
+            let mut texts = Vec::new();
+            for block in blocks {
+                match str_field(block, "type") {
+                    Some("text") => texts.push(str_field(block, "text").unwrap_or_default()),
+                    other => importer.warn(
+                        line,
+                        format!(
+                            "dropped `{}` block from tool result",
+                            other.unwrap_or("<untyped>")
+                        ),
+                    ),
+                }
+            }
+            Value::String(texts.join("\n"))
+        }
+        Some(other) => other.clone(),
+    }
+}
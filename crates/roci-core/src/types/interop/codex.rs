@@ -0,0 +1,234 @@
+//! Codex CLI rollouts: `~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`.
+//!
+//! Two layouts exist. Legacy files start with a bare session header (`id`,
+//! `timestamp`, `instructions`) followed by bare Responses API items and
+//! `record_type` markers, and record no CLI version. Current files wrap each
+//! line as `{timestamp, type, payload}` and open with a `session_meta` line
+//! carrying `cli_version`. Only `response_item` lines hold conversation;
+//! `event_msg` and `turn_context` lines repeat or annotate it.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::{
+    image_from_data_url, major_version, parse_lines, str_field, timestamp_field, ImportedSession,
+    Importer, InteropError, SessionFormat,
+};
+use crate::types::message::{AgentToolCall, AgentToolResult, ContentPart, Role};
+
+/// Version reported for files that predate `cli_version`.
+pub const LEGACY_CODEX_VERSION: &str = "legacy";
+
+const SUPPORTED_MAJOR_VERSIONS: &[u64] = &[0];
+const SUPPORTED_VERSIONS: &str = "legacy, 0.x";
+
+/// Envelope types that carry no conversation content of their own.
+const METADATA_TYPES: &[&str] = &["session_meta", "event_msg", "turn_context"];
+
+/// Convert a Codex CLI rollout file.
+///
+/// # Errors
+///
+/// Returns [`InteropError::UnrecognizedFormat`] when the file matches neither
+/// layout, and [`InteropError::UnsupportedVersion`] for a `cli_version`
+/// outside 0.x.
+pub fn import_codex(input: &str) -> Result<ImportedSession, InteropError> {
+    let mut importer = Importer::default();
+    let entries = parse_lines(input, &mut importer);
+    let session_meta = entries
+        .iter()
+        .find(|(_, entry)| str_field(entry, "type") == Some("session_meta"));
+
+    if let Some((_, meta)) = session_meta {
+        let version = meta
+            .get("payload")
+            .and_then(|payload| str_field(payload, "cli_version"))
+            .ok_or_else(|| InteropError::UnrecognizedFormat {
+                format: SessionFormat::Codex,
+                reason: "`session_meta` has no `cli_version`".to_string(),
+            })?
+            .to_string();
+        if !major_version(&version).is_some_and(|major| SUPPORTED_MAJOR_VERSIONS.contains(&major)) {
+            return Err(InteropError::UnsupportedVersion {
+                format: SessionFormat::Codex,
+                version,
+                supported: SUPPORTED_VERSIONS,
+            });
+        }
+        for (line, entry) in &entries {
+            match str_field(entry, "type") {
+                Some("response_item") => match entry.get("payload") {
+                    Some(item) => {
+                        push_item(item, *line, timestamp_field(entry), &mut importer);
+                    }
+                    None => importer.warn(*line, "`response_item` has no `payload`"),
+                },
+                Some(kind) if METADATA_TYPES.contains(&kind) => {}
+                Some(kind) => importer.warn(*line, format!("unsupported entry type `{kind}`")),
+                None => importer.warn(*line, "entry has no `type`"),
+            }
+        }
+        return Ok(importer.finish(SessionFormat::Codex, version));
+    }
+
+    let is_legacy_header = entries.first().is_some_and(|(_, header)| {
+        header.get("type").is_none()
+            && header.get("id").is_some()
+            && header.get("timestamp").is_some()
+    });
+    if !is_legacy_header {
+        return Err(InteropError::UnrecognizedFormat {
+            format: SessionFormat::Codex,
+            reason: "no `session_meta` line and no legacy session header".to_string(),
+        });
+    }
+    for (line, entry) in entries.iter().skip(1) {
+        if entry.get("record_type").is_some() {
+            continue;
+        }
+        push_item(entry, *line, None, &mut importer);
+    }
+    Ok(importer.finish(SessionFormat::Codex, LEGACY_CODEX_VERSION.to_string()))
+}
+
+/// Convert one Responses API item.
+fn push_item(item: &Value, line: usize, timestamp: Option<DateTime<Utc>>, importer: &mut Importer) {
+    match str_field(item, "type") {
+        Some("message") => push_message(item, line, timestamp, importer),
+        Some("function_call") => {
+            let arguments = str_field(item, "arguments").unwrap_or("{}");
+            let arguments = serde_json::from_str(arguments)
+                .unwrap_or_else(|_| Value::String(arguments.to_string()));
+            push_tool_call(item, arguments, timestamp, importer);
+        }
+        Some("custom_tool_call") => {
+            let input = str_field(item, "input").unwrap_or_default();
+            push_tool_call(
+                item,
+                serde_json::json!({ "input": input }),
+                timestamp,
+                importer,
+            );
+        }
+        Some("local_shell_call") => {
+            let arguments = item.get("action").cloned().unwrap_or(Value::Null);
+            importer.push_assistant_part(
+                ContentPart::ToolCall(AgentToolCall {
+                    id: call_id(item),
+                    name: "local_shell".to_string(),
+                    arguments,
+                    called_as: None,
+                    recipient: None,
+                }),
+                timestamp,
+            );
+        }
+        Some("function_call_output" | "custom_tool_call_output") => {
+            let (result, is_error) = tool_output(item.get("output"));
+            importer.push(
+                Role::Tool,
+                vec![ContentPart::ToolResult(AgentToolResult {
+                    tool_call_id: call_id(item),
+                    result,
+                    is_error,
+                })],
+                timestamp,
+            );
+        }
+        Some("reasoning") => importer.warn(line, "dropped reasoning item"),
+        Some(kind) => importer.warn(line, format!("dropped `{kind}` item")),
+        None => importer.warn(line, "item has no `type`"),
+    }
+}
+
+fn push_message(
+    item: &Value,
+    line: usize,
+    timestamp: Option<DateTime<Utc>>,
+    importer: &mut Importer,
+) {
+    let role = match str_field(item, "role") {
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        Some("developer" | "system") => Role::System,
+        other => {
+            importer.warn(
+                line,
+                format!("unsupported message role `{}`", other.unwrap_or("<none>")),
+            );
+            return;
+        }
+    };
+    let mut parts = Vec::new();
+    for content in item
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match str_field(content, "type") {
+            Some("input_text" | "output_text" | "text") => parts.push(ContentPart::Text {
+                text: str_field(content, "text").unwrap_or_default().to_string(),
+            }),
+            Some("input_image") => {
+                match str_field(content, "image_url").and_then(image_from_data_url) {
+                    Some(image) => parts.push(ContentPart::Image(image)),
+                    None => importer.warn(line, "dropped image without a data URL"),
+                }
+            }
+            other => importer.warn(
+                line,
+                format!("dropped `{}` content", other.unwrap_or("<untyped>")),
+            ),
+        }
+    }
+    importer.push(role, parts, timestamp);
+}
+
+fn push_tool_call(
+    item: &Value,
+    arguments: Value,
+    timestamp: Option<DateTime<Utc>>,
+    importer: &mut Importer,
+) {
+    importer.push_assistant_part(
+        ContentPart::ToolCall(AgentToolCall {
+            id: call_id(item),
+            name: str_field(item, "name").unwrap_or_default().to_string(),
+            arguments,
+            called_as: None,
+            recipient: None,
+        }),
+        timestamp,
+    );
+}
+
+fn call_id(item: &Value) -> String {
+    str_field(item, "call_id")
+        .or_else(|| str_field(item, "id"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Tool output and whether it reports a failure.
+///
+/// Legacy outputs are JSON-encoded `{output, metadata}` strings; current ones
+/// are plain strings or `{content, success}` objects.
+fn tool_output(output: Option<&Value>) -> (Value, bool) {
+    match output {
+        Some(Value::String(text)) => {
+            let parsed = text
+                .trim_start()
+                .starts_with('{')
+                .then(|| serde_json::from_str::<Value>(text).ok())
+                .flatten();
+            (parsed.unwrap_or_else(|| Value::String(text.clone())), false)
+        }
+        Some(Value::Object(object)) if object.contains_key("content") => (
+            object["content"].clone(),
+            object.get("success").and_then(Value::as_bool) == Some(false),
+        ),
+        Some(other) => (other.clone(), false),
+        None => (Value::String(String::new()), false),
+    }
+}
@@ -0,0 +1,228 @@
+//! Import conversations recorded by other coding agents.
+//!
+//! Each importer turns one session file into [`ModelMessage`]s, keeping tool
+//! call ids so calls and results stay paired. Entries that have no roci
+//! equivalent are dropped and listed in [`ImportedSession::warnings`]. Both
+//! source formats change between releases, so each importer checks the
+//! recorded version first and refuses ones it was not written against.
+
+mod claude_code;
+mod codex;
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::message::{ContentPart, ImageContent, ModelMessage, Role};
+
+pub use claude_code::import_claude_code;
+pub use codex::{import_codex, LEGACY_CODEX_VERSION};
+
+/// Session file formats that can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionFormat {
+    /// Claude Code project session JSONL.
+    ClaudeCode,
+    /// Codex CLI rollout JSONL.
+    Codex,
+}
+
+impl SessionFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "claude-code",
+            Self::Codex => "codex",
+        }
+    }
+}
+
+impl fmt::Display for SessionFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SessionFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "claude-code" => Ok(Self::ClaudeCode),
+            "codex" => Ok(Self::Codex),
+            other => Err(format!(
+                "unknown session format `{other}`; expected `claude-code` or `codex`"
+            )),
+        }
+    }
+}
+
+/// A converted session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSession {
+    pub format: SessionFormat,
+    /// Version recorded in the file, such as the writing CLI's version.
+    pub version: String,
+    pub messages: Vec<ModelMessage>,
+    /// Entries dropped or only partly converted, in file order.
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// An entry that could not be fully converted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportWarning {
+    /// 1-based line in the source file.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Why a session file could not be imported at all.
+#[derive(Debug, thiserror::Error)]
+pub enum InteropError {
+    #[error("unsupported {format} session version {version} (supported: {supported})")]
+    UnsupportedVersion {
+        format: SessionFormat,
+        version: String,
+        supported: &'static str,
+    },
+    #[error("not a {format} session file: {reason}")]
+    UnrecognizedFormat {
+        format: SessionFormat,
+        reason: String,
+    },
+}
+
+/// Convert a session file's contents in the given format.
+///
+/// # Errors
+///
+/// Returns [`InteropError`] when the file's version is unsupported or the
+/// content is not a session of that format.
+pub fn import_session(format: SessionFormat, input: &str) -> Result<ImportedSession, InteropError> {
+    match format {
+        SessionFormat::ClaudeCode => import_claude_code(input),
+        SessionFormat::Codex => import_codex(input),
+    }
+}
+
+/// Accumulates messages and warnings while walking a session file.
+#[derive(Default)]
+struct Importer {
+    messages: Vec<ModelMessage>,
+    warnings: Vec<ImportWarning>,
+}
+
+impl Importer {
+    fn warn(&mut self, line: usize, message: impl Into<String>) {
+        self.warnings.push(ImportWarning {
+            line,
+            message: message.into(),
+        });
+    }
+
+    fn push(&mut self, role: Role, content: Vec<ContentPart>, timestamp: Option<DateTime<Utc>>) {
+        if content.is_empty() {
+            return;
+        }
+        self.messages.push(ModelMessage {
+            role,
+            content,
+            name: None,
+            timestamp,
+            metadata: None,
+        });
+    }
+
+    /// Append `part` to the trailing assistant message, or start one.
+    fn push_assistant_part(&mut self, part: ContentPart, timestamp: Option<DateTime<Utc>>) {
+        match self.messages.last_mut() {
+            Some(last) if last.role == Role::Assistant => last.content.push(part),
+            _ => self.push(Role::Assistant, vec![part], timestamp),
+        }
+    }
+
+    fn finish(self, format: SessionFormat, version: String) -> ImportedSession {
+        ImportedSession {
+            format,
+            version,
+            messages: self.messages,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// Parse non-blank JSONL lines with their 1-based line numbers; lines that
+/// are not JSON objects become warnings.
+fn parse_lines(input: &str, importer: &mut Importer) -> Vec<(usize, Value)> {
+    let mut entries = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(line) {
+            Ok(value) if value.is_object() => entries.push((index + 1, value)),
+            Ok(_) => importer.warn(index + 1, "line is not a JSON object"),
+            Err(err) => importer.warn(index + 1, format!("invalid JSON: {err}")),
+        }
+    }
+    entries
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn timestamp_field(value: &Value) -> Option<DateTime<Utc>> {
+    str_field(value, "timestamp")
+        .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Leading numeric component of a dotted version string.
+fn major_version(version: &str) -> Option<u64> {
+    version.split('.').next()?.parse().ok()
+}
+
+/// Image from a `data:<mime>;base64,<data>` URL.
+fn image_from_data_url(url: &str) -> Option<ImageContent> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    Some(ImageContent {
+        data: data.to_string(),
+        mime_type: mime_type.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_names_round_trip() {
+        for format in [SessionFormat::ClaudeCode, SessionFormat::Codex] {
+            assert_eq!(format.as_str().parse::<SessionFormat>(), Ok(format));
+        }
+        assert!("cursor".parse::<SessionFormat>().is_err());
+    }
+
+    #[test]
+    fn data_urls_become_images() {
+        assert_eq!(
+            image_from_data_url("data:image/png;base64,iVBORw0KGgo="),
+            Some(ImageContent {
+                data: "iVBORw0KGgo=".to_string(),
+                mime_type: "image/png".to_string(),
+            })
+        );
+        assert_eq!(image_from_data_url("https://example.com/a.png"), None);
+    }
+}
@@ -1,5 +1,8 @@
 //! Message types for model communication.
 
+use std::fmt;
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -26,17 +29,55 @@ pub struct ModelMessage {
 pub struct ModelMessageMetadata {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub attachments: Vec<AttachmentDisplayMetadata>,
+    /// Where the message content came from, when tagged at construction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<MessageOrigin>,
 }
 
 impl ModelMessageMetadata {
     fn is_none_or_empty(metadata: &Option<Self>) -> bool {
         match metadata {
-            Some(metadata) => metadata.attachments.is_empty(),
+            Some(metadata) => metadata.attachments.is_empty() && metadata.origin.is_none(),
             None => true,
         }
     }
 }
 
+/// Source of a message's content, used to attribute context-window usage.
+///
+/// Messages without an origin are attributed by role; see
+/// [`ModelMessage::effective_origin`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MessageOrigin {
+    /// A section of the system prompt, such as `base`, `append`, or `mcp`.
+    SystemPrompt {
+        section: String,
+    },
+    /// A context file such as `AGENTS.md` inlined into the system prompt.
+    ContextFile {
+        path: PathBuf,
+    },
+    /// The catalog of skills the model can load.
+    Skills,
+    User,
+    Assistant,
+    ToolResult,
+}
+
+impl fmt::Display for MessageOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SystemPrompt { section } => write!(f, "system:{section}"),
+            Self::ContextFile { path } => write!(f, "context:{}", path.display()),
+            Self::Skills => f.write_str("skills"),
+            Self::User => f.write_str("user"),
+            Self::Assistant => f.write_str("assistant"),
+            Self::ToolResult => f.write_str("tool_result"),
+        }
+    }
+}
+
 impl ModelMessage {
     /// Create a system message.
     pub fn system(text: impl Into<String>) -> Self {
@@ -90,6 +131,32 @@ impl ModelMessage {
         }
     }
 
+    /// Tag the message with where its content came from.
+    pub fn with_origin(mut self, origin: MessageOrigin) -> Self {
+        self.metadata.get_or_insert_with(Default::default).origin = Some(origin);
+        self
+    }
+
+    /// The origin the message was tagged with, if any.
+    pub fn origin(&self) -> Option<&MessageOrigin> {
+        self.metadata.as_ref()?.origin.as_ref()
+    }
+
+    /// The tagged origin, or one derived from the role when untagged.
+    pub fn effective_origin(&self) -> MessageOrigin {
+        if let Some(origin) = self.origin() {
+            return origin.clone();
+        }
+        match self.role {
+            Role::System => MessageOrigin::SystemPrompt {
+                section: "system".to_string(),
+            },
+            Role::User => MessageOrigin::User,
+            Role::Assistant => MessageOrigin::Assistant,
+            Role::Tool => MessageOrigin::ToolResult,
+        }
+    }
+
     /// Extract the text content, concatenating all text parts.
     pub fn text(&self) -> String {
         self.content
@@ -1,13 +1,16 @@
 //! Core types for Roci.
 
 pub mod generation;
+pub mod interop;
 pub mod message;
+pub mod response_metadata;
 pub mod results;
 pub mod stream;
 pub mod usage;
 
 pub use generation::*;
 pub use message::*;
+pub use response_metadata::*;
 pub use results::*;
 pub use stream::*;
 pub use usage::*;
@@ -0,0 +1,67 @@
+//! Request ids and rate-limit state reported in provider response headers.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// What a provider reported about a successful response in its headers.
+///
+/// Built by each provider's HTTP layer with
+/// [`response_metadata`](crate::provider::http::response_metadata).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseMetadata {
+    /// Provider-assigned request id, for quoting in support tickets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_request_id: Option<String>,
+    /// Rate-limit allowance left after this request, when reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitInfo>,
+    /// The request-id and rate-limit headers as sent, by lowercase name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub raw_headers_subset: BTreeMap<String, String>,
+    /// Deviations from the provider's documented response shape, counted
+    /// only under strict parsing. See [`crate::provider::anomalies`].
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub parse_anomalies: u32,
+    /// Bytes sent and received for this call, when the provider measured them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadSizes>,
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+/// Request and response body sizes for one provider call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadSizes {
+    /// Serialized request body before any compression.
+    pub request_bytes: u64,
+    /// Request body as sent over the wire; smaller when gzipped.
+    pub request_sent_bytes: u64,
+    /// Whether the request body was sent with `Content-Encoding: gzip`.
+    #[serde(default)]
+    pub request_gzipped: bool,
+    /// Response body bytes received, once the body has been read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_bytes: Option<u64>,
+}
+
+/// Request and token allowances from rate-limit headers.
+///
+/// Reset times are relative to when the response arrived.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_remaining: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_reset_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_remaining: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_reset_ms: Option<u64>,
+}
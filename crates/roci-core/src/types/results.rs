@@ -1,10 +1,12 @@
 //! Generation result types.
 
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
 
 use super::generation::FinishReason;
-use super::message::ModelMessage;
+use super::message::{ImageContent, ModelMessage};
 use super::usage::Usage;
+use crate::error::RociError;
 
 /// Result of a text generation call.
 #[derive(Debug, Clone)]
@@ -19,6 +21,46 @@ pub struct GenerateTextResult {
     pub usage: Usage,
     /// Why the final step finished.
     pub finish_reason: Option<FinishReason>,
+    /// Refusal text when the model declined; `text` is then usually empty.
+    pub refusal: Option<String>,
+    /// Images generated by the model (Gemini image models, OpenAI
+    /// `image_generation`).
+    pub images: Vec<GeneratedImage>,
+}
+
+/// Decoded image produced by the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedImage {
+    pub bytes: Vec<u8>,
+    pub mime: String,
+}
+
+impl GeneratedImage {
+    /// Decode the base64 data of an image content part.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Api`] when the data is not valid base64.
+    pub fn decode(image: &ImageContent) -> Result<Self, RociError> {
+        let bytes = BASE64_STANDARD.decode(&image.data).map_err(|err| {
+            RociError::api(200, format!("generated image is not valid base64: {err}"))
+        })?;
+        Ok(Self {
+            bytes,
+            mime: image.mime_type.clone(),
+        })
+    }
+
+    /// File extension for the image's MIME type, `bin` when unknown.
+    pub fn extension(&self) -> &'static str {
+        match self.mime.as_str() {
+            "image/png" => "png",
+            "image/jpeg" | "image/jpg" => "jpg",
+            "image/webp" => "webp",
+            "image/gif" => "gif",
+            _ => "bin",
+        }
+    }
 }
 
 /// A single generation step (one model call).
@@ -3,7 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use super::generation::FinishReason;
-use super::message::AgentToolCall;
+use super::message::{AgentToolCall, ImageContent};
+use super::response_metadata::ResponseMetadata;
 use super::usage::Usage;
 
 /// A delta emitted during streaming.
@@ -31,6 +32,57 @@ pub struct TextStreamDelta {
     /// Reasoning block type ("thinking" or "redacted_thinking").
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning_type: Option<String>,
+    /// Generated image (only on [`StreamEventType::Image`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageContent>,
+    /// Request id and rate-limit headers of the response (only on the
+    /// [`StreamEventType::Done`] delta).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_metadata: Option<Box<ResponseMetadata>>,
+}
+
+impl TextStreamDelta {
+    /// Build a [`StreamEventType::ToolCallArgumentsDelta`] for a streaming tool call.
+    pub fn tool_call_arguments(
+        call_id: impl Into<String>,
+        name: impl Into<String>,
+        fragment: impl Into<String>,
+    ) -> Self {
+        Self {
+            text: String::new(),
+            event_type: StreamEventType::ToolCallArgumentsDelta,
+            tool_call: Some(AgentToolCall {
+                id: call_id.into(),
+                name: name.into(),
+                arguments: serde_json::Value::String(fragment.into()),
+                called_as: None,
+                recipient: None,
+            }),
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        }
+    }
+
+    /// Build a [`StreamEventType::Image`] carrying a complete generated image.
+    pub fn image(image: ImageContent) -> Self {
+        Self {
+            text: String::new(),
+            event_type: StreamEventType::Image,
+            tool_call: None,
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: Some(image),
+            response_metadata: None,
+        }
+    }
 }
 
 /// Type of stream event.
@@ -41,8 +93,20 @@ pub enum StreamEventType {
     TextDelta,
     /// Tool call being built.
     ToolCallDelta,
+    /// Raw argument fragment for a tool call that is still streaming.
+    ///
+    /// `tool_call` carries the call id and name, with the fragment as a JSON
+    /// string in `arguments`. Progress only: the finalized call still arrives
+    /// as [`StreamEventType::ToolCallDelta`].
+    ToolCallArgumentsDelta,
     /// Reasoning/thinking delta (Anthropic extended thinking).
     Reasoning,
+    /// Refusal text streamed in `text` when the model declines (OpenAI
+    /// structured outputs `refusal`).
+    RefusalDelta,
+    /// Generated image in `image`. Images arrive whole, so each is emitted
+    /// once rather than as deltas.
+    Image,
     /// Stream started.
     Start,
     /// Stream finished.
@@ -1,5 +1,7 @@
-//! Utility modules: debug, retry, and timeout.
+//! Utility modules: debug, HMAC signing, redaction, retry, and timeout.
 
 pub mod debug;
+pub mod hmac;
+pub mod redact;
 pub mod retry;
 pub mod timeout;
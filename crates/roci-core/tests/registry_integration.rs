@@ -91,6 +91,10 @@ impl ModelProvider for MockProvider {
             tool_calls: vec![],
             finish_reason: None,
             thinking: vec![],
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
         })
     }
 
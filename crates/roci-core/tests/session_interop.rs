@@ -0,0 +1,197 @@
+use std::path::Path;
+
+use roci_core::types::interop::{
+    import_session, ImportedSession, InteropError, SessionFormat, LEGACY_CODEX_VERSION,
+};
+use roci_core::types::{ContentPart, ModelMessage, Role};
+
+fn import_fixture(format: SessionFormat, name: &str) -> ImportedSession {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/interop")
+        .join(name);
+    let input = std::fs::read_to_string(&path).expect("fixture should be readable");
+    import_session(format, &input).expect("fixture should import")
+}
+
+fn roles(messages: &[ModelMessage]) -> Vec<Role> {
+    messages.iter().map(|message| message.role).collect()
+}
+
+fn tool_call_ids(messages: &[ModelMessage]) -> Vec<&str> {
+    messages
+        .iter()
+        .flat_map(|message| message.tool_calls())
+        .map(|call| call.id.as_str())
+        .collect()
+}
+
+fn tool_result_ids(messages: &[ModelMessage]) -> Vec<&str> {
+    messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|part| match part {
+            ContentPart::ToolResult(result) => Some(result.tool_call_id.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn warning_lines(session: &ImportedSession) -> Vec<usize> {
+    session
+        .warnings
+        .iter()
+        .map(|warning| warning.line)
+        .collect()
+}
+
+#[test]
+fn claude_code_v1_merges_split_assistant_turns() {
+    let session = import_fixture(SessionFormat::ClaudeCode, "claude_code_v1.jsonl");
+
+    assert_eq!(session.version, "1.0.51");
+    assert_eq!(
+        roles(&session.messages),
+        vec![Role::User, Role::Assistant, Role::Tool, Role::Assistant]
+    );
+    assert_eq!(session.messages[1].text(), "Let me run the tests first.");
+    assert_eq!(tool_call_ids(&session.messages), vec!["toolu_01RunTests"]);
+    assert_eq!(tool_result_ids(&session.messages), vec!["toolu_01RunTests"]);
+    assert_eq!(
+        session.messages[0].timestamp.map(|t| t.to_rfc3339()),
+        Some("2025-07-14T09:12:03.120+00:00".to_string())
+    );
+    assert_eq!(warning_lines(&session), vec![6]);
+    assert!(session.warnings[0].message.contains("sidechain"));
+}
+
+#[test]
+fn claude_code_v2_keeps_thinking_images_and_tool_errors() {
+    let session = import_fixture(SessionFormat::ClaudeCode, "claude_code_v2.jsonl");
+
+    assert_eq!(session.version, "2.0.14");
+    assert_eq!(
+        roles(&session.messages),
+        vec![
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Tool,
+            Role::Assistant
+        ]
+    );
+    assert!(matches!(
+        session.messages[0].content[1],
+        ContentPart::Image(ref image) if image.mime_type == "image/png"
+    ));
+    assert!(matches!(
+        session.messages[1].content[0],
+        ContentPart::Thinking(ref thinking) if thinking.signature == "EpYBCkYIBxgCKkB"
+    ));
+    assert_eq!(
+        tool_call_ids(&session.messages),
+        vec!["toolu_02ReadHeader", "toolu_02Screenshot"]
+    );
+    assert_eq!(
+        tool_result_ids(&session.messages),
+        vec!["toolu_02ReadHeader", "toolu_02Screenshot"]
+    );
+    let ContentPart::ToolResult(failed) = &session.messages[3].content[0] else {
+        panic!("expected tool result");
+    };
+    assert!(failed.is_error);
+    assert_eq!(failed.result, "Connection refused");
+    assert_eq!(warning_lines(&session), vec![7, 8]);
+}
+
+#[test]
+fn codex_legacy_rollout_imports_bare_items() {
+    let session = import_fixture(SessionFormat::Codex, "codex_legacy.jsonl");
+
+    assert_eq!(session.version, LEGACY_CODEX_VERSION);
+    assert_eq!(
+        roles(&session.messages),
+        vec![Role::User, Role::Assistant, Role::Tool, Role::Assistant]
+    );
+    assert_eq!(tool_call_ids(&session.messages), vec!["call_Rel1"]);
+    assert_eq!(tool_result_ids(&session.messages), vec!["call_Rel1"]);
+    let call = session.messages[1].tool_calls()[0];
+    assert_eq!(call.name, "shell");
+    assert_eq!(call.arguments["workdir"], "/home/dev/cli");
+    let ContentPart::ToolResult(output) = &session.messages[2].content[0] else {
+        panic!("expected tool result");
+    };
+    assert_eq!(output.result["metadata"]["exit_code"], 0);
+    assert_eq!(warning_lines(&session), vec![4]);
+}
+
+#[test]
+fn codex_enveloped_rollout_imports_response_items() {
+    let session = import_fixture(SessionFormat::Codex, "codex_v0_44.jsonl");
+
+    assert_eq!(session.version, "0.44.0");
+    assert_eq!(
+        roles(&session.messages),
+        vec![
+            Role::User,
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant
+        ]
+    );
+    assert_eq!(
+        tool_call_ids(&session.messages),
+        vec!["call_Lim1", "call_Patch1"]
+    );
+    assert_eq!(
+        tool_result_ids(&session.messages),
+        vec!["call_Lim1", "call_Patch1"]
+    );
+    let patch = session.messages[4].tool_calls()[0];
+    assert_eq!(patch.name, "apply_patch");
+    assert!(patch.arguments["input"]
+        .as_str()
+        .is_some_and(|input| input.starts_with("*** Begin Patch")));
+    assert_eq!(warning_lines(&session), vec![6, 13]);
+}
+
+#[test]
+fn unsupported_versions_are_rejected() {
+    let claude = r#"{"type":"user","version":"3.1.0","message":{"role":"user","content":"hi"}}"#;
+    let codex = r#"{"timestamp":"2026-01-01T00:00:00Z","type":"session_meta","payload":{"cli_version":"1.2.0"}}"#;
+
+    for (format, input, version) in [
+        (SessionFormat::ClaudeCode, claude, "3.1.0"),
+        (SessionFormat::Codex, codex, "1.2.0"),
+    ] {
+        let error = import_session(format, input).expect_err("version should be rejected");
+        assert!(
+            matches!(&error, InteropError::UnsupportedVersion { version: v, .. } if v == version),
+            "{error}"
+        );
+        assert!(error.to_string().starts_with("unsupported"));
+    }
+}
+
+#[test]
+fn files_of_the_other_format_are_not_recognized() {
+    let codex = import_session(
+        SessionFormat::ClaudeCode,
+        r#"{"timestamp":"2026-01-01T00:00:00Z","type":"session_meta","payload":{}}"#,
+    );
+    assert!(matches!(
+        codex,
+        Err(InteropError::UnrecognizedFormat { .. })
+    ));
+
+    let claude = import_session(
+        SessionFormat::Codex,
+        r#"{"type":"user","version":"2.0.14","message":{"role":"user","content":"hi"}}"#,
+    );
+    assert!(matches!(
+        claude,
+        Err(InteropError::UnrecognizedFormat { .. })
+    ));
+}
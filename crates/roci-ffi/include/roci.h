@@ -0,0 +1,97 @@
+#ifndef ROCI_H
+#define ROCI_H
+
+/* Generated with cbindgen from roci::ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status code returned by every fallible roci function.
+typedef enum RociStatus {
+  ROCI_STATUS_OK = 0,
+  // A required pointer was null or a string was not UTF-8.
+  ROCI_STATUS_INVALID_ARGUMENT = 1,
+  // The model string could not be parsed or has no provider.
+  ROCI_STATUS_INVALID_MODEL = 2,
+  // The provider or network failed; see the envelope's `error`.
+  ROCI_STATUS_GENERATION = 3,
+  // The stream callback asked to stop.
+  ROCI_STATUS_STOPPED = 4,
+  // Rust code panicked; the call had no effect beyond what it reported.
+  ROCI_STATUS_PANIC = 5,
+} RociStatus;
+
+// Opaque configuration handle.
+typedef struct RociConfig RociConfig;
+
+// Called with one NUL-terminated JSON delta (a `TextStreamDelta`). The
+// string is only valid during the call. Return 0 to continue, anything else
+// to stop the stream.
+typedef int (*RociStreamCallback)(const char *delta_json, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a configuration from environment variables and `.env`.
+//
+// Returns null on failure; see [`roci_last_error_message`].
+RociConfig *roci_config_new_from_env(void);
+
+// Release a configuration. Null is ignored.
+//
+// # Safety
+//
+// `config` must come from [`roci_config_new_from_env`] and not be used
+// afterwards.
+void roci_config_free(RociConfig *config);
+
+// Generate text for `prompt` with `model` (`provider:model`).
+//
+// When `out_json` is not null it receives the result envelope, on success
+// and on generation errors alike; free it with [`roci_string_free`].
+//
+// # Safety
+//
+// `config` must be a live handle; `model` and `prompt` NUL-terminated
+// strings; `out_json` null or writable.
+RociStatus roci_generate(const RociConfig *config,
+                         const char *model,
+                         const char *prompt,
+                         char **out_json);
+
+// Stream text for `prompt`, calling `callback` with each delta.
+//
+// `out_json` receives the envelope of the collected text, as for
+// [`roci_generate`]. A callback that stops the stream yields
+// [`RociStatus::Stopped`] with the text received so far.
+//
+// # Safety
+//
+// As [`roci_generate`]; `callback` must be safe to call with `user_data`
+// from the calling thread.
+RociStatus roci_stream_generate(const RociConfig *config,
+                                const char *model,
+                                const char *prompt,
+                                RociStreamCallback callback,
+                                void *user_data,
+                                char **out_json);
+
+// Message of the last error on this thread, or null if the last call
+// succeeded. Free the result with [`roci_string_free`].
+char *roci_last_error_message(void);
+
+// Release a string returned by roci. Null is ignored.
+//
+// # Safety
+//
+// `value` must come from roci and not be used afterwards.
+void roci_string_free(char *value);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ROCI_H */
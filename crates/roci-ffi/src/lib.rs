@@ -0,0 +1,7 @@
+//! Shared library exposing roci's C ABI.
+//!
+//! The functions are defined in `roci::ffi`; this crate links them into a
+//! `cdylib` (`libroci_ffi.so`, `libroci_ffi.dylib`, `roci_ffi.dll`) declared
+//! by `include/roci.h`.
+
+pub use roci::ffi::*;
@@ -0,0 +1,243 @@
+//! Loads the built `cdylib` with `dlopen` and drives it through the C ABI,
+//! as a C caller would.
+
+#![cfg(unix)]
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const STATUS_OK: c_int = 0;
+const STATUS_INVALID_ARGUMENT: c_int = 1;
+const STATUS_INVALID_MODEL: c_int = 2;
+
+const EXPORTED: [&str; 6] = [
+    "roci_config_new_from_env",
+    "roci_config_free",
+    "roci_generate",
+    "roci_stream_generate",
+    "roci_last_error_message",
+    "roci_string_free",
+];
+
+type ConfigNew = unsafe extern "C" fn() -> *mut c_void;
+type ConfigFree = unsafe extern "C" fn(*mut c_void);
+type Generate =
+    unsafe extern "C" fn(*const c_void, *const c_char, *const c_char, *mut *mut c_char) -> c_int;
+type LastError = unsafe extern "C" fn() -> *mut c_char;
+type StringFree = unsafe extern "C" fn(*mut c_char);
+
+struct Library {
+    config_new: ConfigNew,
+    config_free: ConfigFree,
+    generate: Generate,
+    last_error: LastError,
+    string_free: StringFree,
+}
+
+impl Library {
+    /// Open the library once, after pointing the OpenAI provider at a local
+    /// mock so configs created through the ABI never reach the network.
+    fn get() -> &'static Library {
+        static LIBRARY: OnceLock<Library> = OnceLock::new();
+        LIBRARY.get_or_init(|| {
+            std::env::set_var("OPENAI_API_KEY", "test-key");
+            std::env::set_var("OPENAI_BASE_URL", format!("{}/v1", mock_openai()));
+            let path = CString::new(library_path().to_string_lossy().as_bytes()).unwrap();
+            // SAFETY: the path is NUL-terminated; the handle is never closed.
+            let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW) };
+            assert!(!handle.is_null(), "dlopen failed: {}", dl_error());
+            // SAFETY: each symbol has the signature declared in include/roci.h.
+            unsafe {
+                Library {
+                    config_new: std::mem::transmute::<*mut c_void, ConfigNew>(symbol(
+                        handle,
+                        "roci_config_new_from_env",
+                    )),
+                    config_free: std::mem::transmute::<*mut c_void, ConfigFree>(symbol(
+                        handle,
+                        "roci_config_free",
+                    )),
+                    generate: std::mem::transmute::<*mut c_void, Generate>(symbol(
+                        handle,
+                        "roci_generate",
+                    )),
+                    last_error: std::mem::transmute::<*mut c_void, LastError>(symbol(
+                        handle,
+                        "roci_last_error_message",
+                    )),
+                    string_free: std::mem::transmute::<*mut c_void, StringFree>(symbol(
+                        handle,
+                        "roci_string_free",
+                    )),
+                }
+            }
+        })
+    }
+
+    /// Call `roci_generate` and return its status and parsed envelope.
+    fn generate(&self, model: Option<&str>, prompt: &str) -> (c_int, serde_json::Value) {
+        let model = model.map(|model| CString::new(model).unwrap());
+        let prompt = CString::new(prompt).unwrap();
+        let mut out: *mut c_char = std::ptr::null_mut();
+        // SAFETY: arguments follow the contract in include/roci.h.
+        unsafe {
+            let config = (self.config_new)();
+            assert!(!config.is_null());
+            let status = (self.generate)(
+                config,
+                model
+                    .as_ref()
+                    .map_or(std::ptr::null(), |model| model.as_ptr()),
+                prompt.as_ptr(),
+                &mut out,
+            );
+            (self.config_free)(config);
+            let envelope = self.take_string(out).expect("envelope");
+            (status, serde_json::from_str(&envelope).unwrap())
+        }
+    }
+
+    fn last_error(&self) -> Option<String> {
+        // SAFETY: the result is a roci-owned string or null.
+        unsafe { self.take_string((self.last_error)()) }
+    }
+
+    /// Copy and free a roci-owned string.
+    unsafe fn take_string(&self, value: *mut c_char) -> Option<String> {
+        if value.is_null() {
+            return None;
+        }
+        let text = CStr::from_ptr(value).to_string_lossy().into_owned();
+        (self.string_free)(value);
+        Some(text)
+    }
+}
+
+unsafe fn symbol(handle: *mut c_void, name: &str) -> *mut c_void {
+    let name = CString::new(name).unwrap();
+    let symbol = libc::dlsym(handle, name.as_ptr());
+    assert!(!symbol.is_null(), "missing symbol {name:?}: {}", dl_error());
+    symbol
+}
+
+fn dl_error() -> String {
+    // SAFETY: dlerror returns null or a NUL-terminated message.
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        return "unknown error".to_string();
+    }
+    // SAFETY: checked non-null above.
+    unsafe { CStr::from_ptr(error) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// `target/<profile>/libroci_ffi.*`, next to the `deps/` holding this test.
+fn library_path() -> PathBuf {
+    let name = format!(
+        "{}roci_ffi{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    let exe = std::env::current_exe().unwrap();
+    exe.ancestors()
+        .skip(1)
+        .take(2)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| panic!("{name} not found near {}", exe.display()))
+}
+
+/// Serve canned chat completions on a loopback port; returns its base URL.
+fn mock_openai() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            let _ = reader.read_exact(&mut body);
+            let reply = serde_json::json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "hello from roci" },
+                    "finish_reason": "stop",
+                }],
+                "usage": { "prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7 },
+            })
+            .to_string();
+            let _ = write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{reply}",
+                reply.len()
+            );
+        }
+    });
+    url
+}
+
+#[test]
+fn generate_returns_a_text_envelope() {
+    let library = Library::get();
+
+    let (status, envelope) = library.generate(Some("openai:gpt-4o"), "say hello");
+
+    assert_eq!(status, STATUS_OK, "{envelope}");
+    assert_eq!(envelope["text"], "hello from roci");
+    assert_eq!(envelope["usage"]["input_tokens"], 3);
+    assert_eq!(envelope["usage"]["output_tokens"], 4);
+    assert!(envelope["error"].is_null());
+    assert_eq!(library.last_error(), None);
+}
+
+#[test]
+fn invalid_model_reports_an_error_code_and_message() {
+    let library = Library::get();
+
+    let (status, envelope) = library.generate(Some("not-a-model"), "hi");
+
+    assert_eq!(status, STATUS_INVALID_MODEL);
+    assert!(envelope["text"].is_null());
+    assert_eq!(envelope["error"]["code"], STATUS_INVALID_MODEL);
+    let message = envelope["error"]["message"].as_str().unwrap();
+    assert!(!message.is_empty());
+    assert_eq!(library.last_error().as_deref(), Some(message));
+}
+
+#[test]
+fn null_arguments_are_rejected() {
+    let library = Library::get();
+
+    let (status, envelope) = library.generate(None, "hi");
+
+    assert_eq!(status, STATUS_INVALID_ARGUMENT);
+    assert_eq!(envelope["error"]["message"], "model is null");
+}
+
+#[test]
+fn header_declares_every_exported_function() {
+    let header = include_str!("../include/roci.h");
+
+    for name in EXPORTED {
+        assert!(header.contains(&format!("{name}(")), "roci.h lacks {name}");
+    }
+}
@@ -0,0 +1,389 @@
+//! AWS credentials and region for Bedrock.
+//!
+//! Resolution follows the standard AWS chain: environment variables first,
+//! then the selected profile in the shared credentials and config files.
+//! Only static keys (with an optional session token) are supported.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use roci_core::error::RociError;
+
+const DEFAULT_PROFILE: &str = "default";
+
+/// Static AWS credentials used for SigV4 signing.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"..")
+            .field("session_token", &self.session_token.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl AwsCredentials {
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    pub fn with_session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+}
+
+/// Credentials and region resolved from the environment and `~/.aws`.
+#[derive(Debug, Clone)]
+pub struct AwsProfileChain {
+    env: HashMap<String, String>,
+    home_dir: Option<PathBuf>,
+}
+
+impl AwsProfileChain {
+    /// Chain over the process environment and the user's home directory.
+    pub fn from_process_env() -> Self {
+        Self {
+            env: std::env::vars().collect(),
+            home_dir: directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf()),
+        }
+    }
+
+    /// Chain over explicit variables and home directory (for tests and embedding).
+    pub fn new(env: HashMap<String, String>, home_dir: Option<PathBuf>) -> Self {
+        Self { env, home_dir }
+    }
+
+    fn var(&self, name: &str) -> Option<&str> {
+        self.env
+            .get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn profile(&self) -> &str {
+        self.var("AWS_PROFILE").unwrap_or(DEFAULT_PROFILE)
+    }
+
+    fn credentials_file(&self) -> Option<PathBuf> {
+        self.var("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .or_else(|| Some(self.home_dir.as_ref()?.join(".aws").join("credentials")))
+    }
+
+    fn config_file(&self) -> Option<PathBuf> {
+        self.var("AWS_CONFIG_FILE")
+            .map(PathBuf::from)
+            .or_else(|| Some(self.home_dir.as_ref()?.join(".aws").join("config")))
+    }
+
+    /// Profile entries from the credentials file, then the config file.
+    fn profile_entries(&self) -> Result<Vec<HashMap<String, String>>, RociError> {
+        let profile = self.profile();
+        let mut entries = Vec::new();
+        if let Some(path) = self.credentials_file() {
+            if let Some(sections) = read_ini(&path)? {
+                entries.extend(sections.get(profile).cloned());
+            }
+        }
+        if let Some(path) = self.config_file() {
+            if let Some(sections) = read_ini(&path)? {
+                let section = if profile == DEFAULT_PROFILE {
+                    DEFAULT_PROFILE.to_string()
+                } else {
+                    format!("profile {profile}")
+                };
+                entries.extend(sections.get(&section).cloned());
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Resolve credentials from `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY`
+    /// (plus `AWS_SESSION_TOKEN`), else from the `AWS_PROFILE` profile.
+    pub fn credentials(&self) -> Result<AwsCredentials, RociError> {
+        match (
+            self.var("AWS_ACCESS_KEY_ID"),
+            self.var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            (Some(access_key_id), Some(secret_access_key)) => {
+                let mut credentials = AwsCredentials::new(access_key_id, secret_access_key);
+                if let Some(token) = self.var("AWS_SESSION_TOKEN") {
+                    credentials = credentials.with_session_token(token);
+                }
+                return Ok(credentials);
+            }
+            (Some(_), None) => {
+                return Err(RociError::Authentication(
+                    "AWS_ACCESS_KEY_ID is set but AWS_SECRET_ACCESS_KEY is not".to_string(),
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(RociError::Authentication(
+                    "AWS_SECRET_ACCESS_KEY is set but AWS_ACCESS_KEY_ID is not".to_string(),
+                ))
+            }
+            (None, None) => {}
+        }
+
+        let profile = self.profile();
+        for entry in self.profile_entries()? {
+            let access_key_id = entry.get("aws_access_key_id");
+            let secret_access_key = entry.get("aws_secret_access_key");
+            if let (Some(access_key_id), Some(secret_access_key)) =
+                (access_key_id, secret_access_key)
+            {
+                let mut credentials = AwsCredentials::new(access_key_id, secret_access_key);
+                if let Some(token) = entry.get("aws_session_token") {
+                    credentials = credentials.with_session_token(token);
+                }
+                return Ok(credentials);
+            }
+            if entry.contains_key("sso_session")
+                || entry.contains_key("sso_start_url")
+                || entry.contains_key("role_arn")
+                || entry.contains_key("credential_process")
+            {
+                return Err(RociError::Authentication(format!(
+                    "AWS profile '{profile}' uses SSO, role assumption, or a credential \
+                     process, which Bedrock support cannot resolve; export temporary \
+                     credentials with `aws configure export-credentials --profile {profile} \
+                     --format env`"
+                )));
+            }
+        }
+
+        Err(RociError::Authentication(format!(
+            "no AWS credentials found for Bedrock: set AWS_ACCESS_KEY_ID and \
+             AWS_SECRET_ACCESS_KEY, or add aws_access_key_id/aws_secret_access_key to \
+             profile '{profile}' in ~/.aws/credentials (select a profile with AWS_PROFILE)"
+        )))
+    }
+
+    /// Resolve the region from `AWS_REGION`, `AWS_DEFAULT_REGION`, or the
+    /// profile's `region` setting.
+    pub fn region(&self) -> Result<String, RociError> {
+        if let Some(region) = self
+            .var("AWS_REGION")
+            .or_else(|| self.var("AWS_DEFAULT_REGION"))
+        {
+            return Ok(region.to_string());
+        }
+        for entry in self.profile_entries()? {
+            if let Some(region) = entry.get("region").filter(|region| !region.is_empty()) {
+                return Ok(region.clone());
+            }
+        }
+        Err(RociError::MissingConfiguration {
+            key: "AWS_REGION".to_string(),
+            provider: "bedrock".to_string(),
+        })
+    }
+}
+
+/// Key/value pairs per INI section.
+type IniSections = HashMap<String, HashMap<String, String>>;
+
+/// Sections of an INI file, or `None` when the file does not exist.
+fn read_ini(path: &Path) -> Result<Option<IniSections>, RociError> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => Ok(Some(parse_ini(&raw))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(RociError::Configuration(format!(
+            "failed to read AWS config {}: {err}",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_ini(raw: &str) -> IniSections {
+    let mut sections = IniSections::new();
+    let mut current: Option<String> = None;
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+            continue;
+        }
+        let (Some(section), Some((key, value))) = (current.as_ref(), line.split_once('=')) else {
+            continue;
+        };
+        sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(env: &[(&str, &str)], home: Option<&Path>) -> AwsProfileChain {
+        AwsProfileChain::new(
+            env.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            home.map(Path::to_path_buf),
+        )
+    }
+
+    fn write_aws_file(home: &Path, name: &str, contents: &str) {
+        let dir = home.join(".aws");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    #[test]
+    fn environment_credentials_take_precedence() {
+        let home = tempfile::tempdir().unwrap();
+        write_aws_file(
+            home.path(),
+            "credentials",
+            "[default]\naws_access_key_id = FILE\naws_secret_access_key = file-secret\n",
+        );
+        let chain = chain(
+            &[
+                ("AWS_ACCESS_KEY_ID", "ENV"),
+                ("AWS_SECRET_ACCESS_KEY", "env-secret"),
+                ("AWS_SESSION_TOKEN", "session"),
+            ],
+            Some(home.path()),
+        );
+
+        assert_eq!(
+            chain.credentials().unwrap(),
+            AwsCredentials::new("ENV", "env-secret").with_session_token("session")
+        );
+    }
+
+    #[test]
+    fn partial_environment_credentials_are_rejected() {
+        let err = chain(&[("AWS_ACCESS_KEY_ID", "ENV")], None)
+            .credentials()
+            .unwrap_err();
+
+        assert!(
+            matches!(err, RociError::Authentication(ref message) if message.contains("AWS_SECRET_ACCESS_KEY"))
+        );
+    }
+
+    #[test]
+    fn named_profile_reads_credentials_and_config_files() {
+        let home = tempfile::tempdir().unwrap();
+        write_aws_file(
+            home.path(),
+            "credentials",
+            "[default]\naws_access_key_id = DEFAULT\naws_secret_access_key = d\n\n\
+             [work]\naws_access_key_id = WORK\naws_secret_access_key = w\n",
+        );
+        write_aws_file(
+            home.path(),
+            "config",
+            "[default]\nregion = us-east-1\n\n[profile work]\nregion = eu-west-1\n",
+        );
+        let chain = chain(&[("AWS_PROFILE", "work")], Some(home.path()));
+
+        assert_eq!(
+            chain.credentials().unwrap(),
+            AwsCredentials::new("WORK", "w")
+        );
+        assert_eq!(chain.region().unwrap(), "eu-west-1");
+    }
+
+    #[test]
+    fn region_environment_overrides_profile() {
+        let home = tempfile::tempdir().unwrap();
+        write_aws_file(home.path(), "config", "[default]\nregion = us-east-1\n");
+
+        let region = chain(&[("AWS_DEFAULT_REGION", "us-west-2")], Some(home.path()))
+            .region()
+            .unwrap();
+        assert_eq!(region, "us-west-2");
+
+        let region = chain(
+            &[
+                ("AWS_REGION", "ap-south-1"),
+                ("AWS_DEFAULT_REGION", "us-west-2"),
+            ],
+            Some(home.path()),
+        )
+        .region()
+        .unwrap();
+        assert_eq!(region, "ap-south-1");
+    }
+
+    #[test]
+    fn missing_region_names_the_variable() {
+        let home = tempfile::tempdir().unwrap();
+        let err = chain(&[], Some(home.path())).region().unwrap_err();
+
+        assert!(matches!(
+            err,
+            RociError::MissingConfiguration { ref key, ref provider }
+                if key == "AWS_REGION" && provider == "bedrock"
+        ));
+    }
+
+    #[test]
+    fn missing_credentials_explain_the_chain() {
+        let home = tempfile::tempdir().unwrap();
+        let err = chain(&[("AWS_PROFILE", "ci")], Some(home.path()))
+            .credentials()
+            .unwrap_err();
+
+        let RociError::Authentication(message) = err else {
+            panic!("expected authentication error");
+        };
+        assert!(message.contains("AWS_ACCESS_KEY_ID"));
+        assert!(message.contains("profile 'ci'"));
+    }
+
+    #[test]
+    fn sso_profiles_point_at_exported_credentials() {
+        let home = tempfile::tempdir().unwrap();
+        write_aws_file(
+            home.path(),
+            "config",
+            "[profile sso]\nsso_session = corp\nregion = us-east-1\n",
+        );
+        let err = chain(&[("AWS_PROFILE", "sso")], Some(home.path()))
+            .credentials()
+            .unwrap_err();
+
+        assert!(
+            matches!(err, RociError::Authentication(ref message) if message.contains("export-credentials"))
+        );
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let credentials = AwsCredentials::new("AKID", "secret").with_session_token("token");
+        let debug = format!("{credentials:?}");
+
+        assert!(debug.contains("AKID"));
+        assert!(!debug.contains("\"secret\""));
+        assert!(!debug.contains("\"token\""));
+    }
+}
@@ -57,17 +57,7 @@ impl AuthBackend for GitHubCopilotBackend {
         // Exchange Copilot JWT on success; both exchange and save must succeed
         // for the login to be considered successful.
         if let AuthPollResult::Authorized { .. } = &result {
-            let copilot_token = auth.exchange_copilot_token().await?;
-            let api_token = Token {
-                access_token: copilot_token.token,
-                refresh_token: None,
-                id_token: None,
-                expires_at: Some(copilot_token.expires_at),
-                last_refresh: Some(Utc::now()),
-                scopes: None,
-                account_id: Some(copilot_token.base_url),
-            };
-            store.save("github-copilot-api", "default", &api_token)?;
+            save_copilot_api_token(store, &auth).await?;
         }
 
         Ok(result)
@@ -91,6 +81,20 @@ impl AuthBackend for GitHubCopilotBackend {
         }
     }
 
+    fn refreshable_token(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError> {
+        store.load("github-copilot-api", "default")
+    }
+
+    /// Exchange the GitHub token for a new short-lived Copilot API token.
+    async fn refresh(
+        &self,
+        store: &Arc<dyn TokenStore>,
+        _token: &Token,
+    ) -> Result<Option<Token>, AuthError> {
+        let auth = GitHubCopilotAuth::new(store.clone());
+        save_copilot_api_token(store, &auth).await.map(Some)
+    }
+
     fn logout(&self, store: &Arc<dyn TokenStore>) -> Result<(), AuthError> {
         let primary_token = store.load(self.store_key(), "default")?;
 
@@ -245,6 +249,18 @@ impl AuthBackend for ClaudeCodeBackend {
         auth.exchange_code(&session, code).await
     }
 
+    async fn refresh(
+        &self,
+        store: &Arc<dyn TokenStore>,
+        token: &Token,
+    ) -> Result<Option<Token>, AuthError> {
+        if token.refresh_token.is_none() {
+            return Ok(None);
+        }
+        let auth = ClaudeCodeAuth::new(store.clone());
+        auth.refresh_token(token).await.map(Some)
+    }
+
     fn get_status(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError> {
         store.load(self.store_key(), "default")
     }
@@ -258,6 +274,25 @@ impl AuthBackend for ClaudeCodeBackend {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Exchange the stored GitHub token for a Copilot API token and save it.
+async fn save_copilot_api_token(
+    store: &Arc<dyn TokenStore>,
+    auth: &GitHubCopilotAuth,
+) -> Result<Token, AuthError> {
+    let copilot_token = auth.exchange_copilot_token().await?;
+    let api_token = Token {
+        access_token: copilot_token.token,
+        refresh_token: None,
+        id_token: None,
+        expires_at: Some(copilot_token.expires_at),
+        last_refresh: Some(Utc::now()),
+        scopes: None,
+        account_id: Some(copilot_token.base_url),
+    };
+    store.save("github-copilot-api", "default", &api_token)?;
+    Ok(api_token)
+}
+
 fn pkce_session_to_json(session: &PkceSession) -> serde_json::Value {
     serde_json::json!({
         "authorize_url": session.authorize_url,
@@ -489,6 +524,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn copilot_refreshes_the_short_lived_api_token() {
+        let (_dir, store) = temp_store();
+        store
+            .save("github-copilot", "default", &token("primary-token"))
+            .expect("save primary token");
+        store
+            .save("github-copilot-api", "default", &token("api-token"))
+            .expect("save api token");
+
+        let token = GitHubCopilotBackend
+            .refreshable_token(&store)
+            .expect("refreshable token");
+
+        assert_eq!(
+            token.map(|token| token.access_token).as_deref(),
+            Some("api-token")
+        );
+    }
+
+    #[tokio::test]
+    async fn claude_refresh_without_a_refresh_token_needs_a_new_login() {
+        let (_dir, store) = temp_store();
+
+        let refreshed = ClaudeCodeBackend
+            .refresh(&store, &token("access-token"))
+            .await
+            .expect("refresh");
+
+        assert!(refreshed.is_none());
+    }
+
     #[test]
     fn copilot_logout_clears_primary_and_api_token_stores() {
         let (_dir, store) = temp_store();
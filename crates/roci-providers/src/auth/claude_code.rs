@@ -10,6 +10,7 @@ use sha2::{Digest, Sha256};
 use roci_core::auth::AuthError;
 use roci_core::auth::Token;
 use roci_core::auth::TokenStore;
+use roci_core::provider::http::shared_client;
 
 const CLAUDE_CLI_REL_PATH: &str = ".claude/.credentials.json";
 const CLAUDE_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
@@ -67,7 +68,7 @@ pub struct ClaudeCodeAuth {
 impl ClaudeCodeAuth {
     pub fn new(token_store: Arc<dyn TokenStore>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: shared_client().clone(),
             token_store,
             profile: "default".to_string(),
             token_url: CLAUDE_TOKEN_URL.to_string(),
@@ -9,6 +9,7 @@ use roci_core::auth::AuthPollResult;
 use roci_core::auth::DeviceCodeSession;
 use roci_core::auth::Token;
 use roci_core::auth::TokenStore;
+use roci_core::provider::http::shared_client;
 
 const DEFAULT_CLIENT_ID: &str = "Iv1.b507a08c87ecfe98";
 const DEFAULT_DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
@@ -43,7 +44,7 @@ pub struct GitHubCopilotAuth {
 impl GitHubCopilotAuth {
     pub fn new(token_store: std::sync::Arc<dyn TokenStore>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: shared_client().clone(),
             client_id: DEFAULT_CLIENT_ID.to_string(),
             device_code_url: DEFAULT_DEVICE_CODE_URL.to_string(),
             access_token_url: DEFAULT_ACCESS_TOKEN_URL.to_string(),
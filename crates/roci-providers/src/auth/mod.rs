@@ -1,7 +1,11 @@
 //! OAuth flow implementations for built-in providers.
 
+#[cfg(feature = "bedrock")]
+pub mod aws;
 pub mod claude_code;
 pub mod github_copilot;
+#[cfg(feature = "google")]
+pub mod google_service_account;
 pub mod openai_codex;
 
 mod backends;
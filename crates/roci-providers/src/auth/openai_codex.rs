@@ -10,6 +10,7 @@ use roci_core::auth::AuthPollResult;
 use roci_core::auth::DeviceCodeSession;
 use roci_core::auth::Token;
 use roci_core::auth::TokenStore;
+use roci_core::provider::http::shared_client;
 
 const DEFAULT_ISSUER: &str = "https://auth.openai.com";
 const DEFAULT_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
@@ -40,7 +41,7 @@ pub struct OpenAiCodexAuth {
 impl OpenAiCodexAuth {
     pub fn new(token_store: Arc<dyn TokenStore>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: shared_client().clone(),
             issuer: DEFAULT_ISSUER.to_string(),
             client_id: DEFAULT_CLIENT_ID.to_string(),
             refresh_token_url_override: None,
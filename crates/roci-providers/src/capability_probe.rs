@@ -0,0 +1,822 @@
+//! Capability probing for self-hosted OpenAI-compatible endpoints.
+//!
+//! Static model tables cannot describe arbitrary OpenAI-compatible servers
+//! (Ollama, LM Studio, vLLM, llama.cpp, ...). [`probe_capabilities`] asks the
+//! server directly:
+//!
+//! 1. `GET {api_base}/models` (plus the native Ollama / LM Studio model
+//!    endpoints) for the model's context length, when exposed.
+//! 2. One tools-enabled chat completion with `max_tokens: 1`, tagged with the
+//!    [`PROBE_HEADER`] header, to detect function-calling support.
+//!
+//! Results are cached per `(api_base, model)` in [`CapabilityCache`], which
+//! can optionally persist to `~/.roci/capability-cache.json`. Provider
+//! factories consult the cache synchronously in `create()`; they never probe
+//! themselves, so provider creation is never blocked on the network.
+//!
+//! Probing is skipped entirely when
+//! [`RociConfig::capability_probing_enabled`] is `false`, and for remote
+//! endpoints in offline mode. Every probe failure
+//! degrades to the caller's default capabilities and is not cached.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::models::capabilities::ModelCapabilities;
+use roci_core::models::ProviderKey;
+use roci_core::provider::http::shared_client;
+use roci_core::provider::offline::{is_loopback_url, offline_error};
+
+use crate::disk_cache::{default_disk_path, CacheRecord, DiskCache};
+use crate::json::positive_usize;
+
+/// Header attached to every probe request so server logs can tell probes apart.
+pub const PROBE_HEADER: &str = "x-roci-probe";
+/// Value sent in [`PROBE_HEADER`].
+pub const PROBE_HEADER_VALUE: &str = "capabilities";
+/// Default time-to-live for cached probe results.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// File name of the on-disk cache inside `~/.roci`.
+pub const CACHE_FILE_NAME: &str = "capability-cache.json";
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+const PROBE_TOOL_NAME: &str = "roci_capability_probe";
+const CACHE_FILE_VERSION: u32 = 1;
+
+/// Native model-info endpoint to consult when `/models` omits context length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NativeModelInfo {
+    /// No native endpoint; rely on `/models` only.
+    None,
+    /// Ollama `POST {root}/api/show`.
+    Ollama { root_url: String },
+    /// LM Studio `GET {root}/api/v0/models/{model}`.
+    LmStudio { root_url: String },
+}
+
+/// Endpoint and model to probe.
+#[derive(Debug, Clone)]
+pub struct ProbeTarget {
+    /// OpenAI-compatible API base, e.g. `http://localhost:11434/v1`.
+    pub api_base: String,
+    /// Bearer token, if the endpoint requires one.
+    pub api_key: Option<String>,
+    pub model_id: String,
+    pub native: NativeModelInfo,
+}
+
+impl ProbeTarget {
+    /// Resolve the probe target a provider factory would use for `provider_key`.
+    ///
+    /// Returns `None` for providers that are not probeable or are missing
+    /// their base URL.
+    pub fn for_provider(config: &RociConfig, provider_key: &str, model_id: &str) -> Option<Self> {
+        match ProviderKey::parse(provider_key)? {
+            ProviderKey::Ollama => {
+                let root_url = ollama_root_url(config);
+                Some(Self {
+                    api_base: local_api_base(&root_url),
+                    api_key: config.get_api_key_for(ProviderKey::Ollama),
+                    model_id: model_id.to_string(),
+                    native: NativeModelInfo::Ollama { root_url },
+                })
+            }
+            ProviderKey::LmStudio => {
+                let root_url = lmstudio_root_url(config);
+                Some(Self {
+                    api_base: local_api_base(&root_url),
+                    api_key: config.get_api_key_for(ProviderKey::LmStudio),
+                    model_id: model_id.to_string(),
+                    native: NativeModelInfo::LmStudio { root_url },
+                })
+            }
+            ProviderKey::OpenAiCompatible => Some(Self {
+                api_base: openai_compatible_base_url(config)?,
+                api_key: config
+                    .get_api_key_for(ProviderKey::OpenAiCompatible)
+                    .or_else(|| config.get_api_key_for(ProviderKey::OpenAi)),
+                model_id: model_id.to_string(),
+                native: NativeModelInfo::None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn ollama_root_url(config: &RociConfig) -> String {
+    config
+        .get_base_url_for(ProviderKey::Ollama)
+        .unwrap_or_else(|| "http://localhost:11434".to_string())
+}
+
+pub(crate) fn lmstudio_root_url(config: &RociConfig) -> String {
+    config
+        .get_base_url_for(ProviderKey::LmStudio)
+        .unwrap_or_else(|| "http://localhost:1234".to_string())
+}
+
+pub(crate) fn openai_compatible_base_url(config: &RociConfig) -> Option<String> {
+    config
+        .get_base_url_for(ProviderKey::OpenAiCompatible)
+        .or_else(|| config.get_base_url_for(ProviderKey::OpenAi))
+}
+
+pub(crate) fn local_api_base(root_url: &str) -> String {
+    format!("{}/v1", root_url.trim_end_matches('/'))
+}
+
+/// Return cached capabilities for `(api_base, model_id)`, or `defaults`.
+///
+/// Used by provider factories from the synchronous `create()` path.
+#[cfg_attr(
+    not(any(
+        feature = "ollama",
+        feature = "lmstudio",
+        feature = "openai-compatible"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn cached_or_default(
+    config: &RociConfig,
+    api_base: &str,
+    model_id: &str,
+    defaults: ModelCapabilities,
+) -> ModelCapabilities {
+    if !config.capability_probing_enabled() {
+        return defaults;
+    }
+    CapabilityCache::global()
+        .get(api_base, model_id)
+        .unwrap_or(defaults)
+}
+
+/// Probe `provider_key`/`model_id` through the global cache.
+///
+/// Hosts call this before creating a provider so the following `create()`
+/// picks up discovered capabilities. Returns `None` when probing is
+/// disabled, the provider is not probeable, or the probe failed.
+pub async fn probe_provider_capabilities(
+    config: &RociConfig,
+    provider_key: &str,
+    model_id: &str,
+) -> Option<ModelCapabilities> {
+    let target = ProbeTarget::for_provider(config, provider_key, model_id)?;
+    let defaults = default_capabilities(provider_key, model_id);
+    probe_capabilities(config, &target, defaults, CapabilityCache::global()).await
+}
+
+/// Check that a provider which needs no credential has a server listening.
+///
+/// Sends one `GET {api_base}/models`; any HTTP response counts as
+/// reachable, since some servers behind a proxy reject unauthenticated
+/// model listing. Returns `None` for providers that need credentials or
+/// have no base URL, which are validated by their credentials instead.
+///
+/// # Errors
+///
+/// The inner result is [`RociError::Network`] when the server cannot be
+/// reached, and the offline-mode configuration error for a remote URL.
+pub async fn ping_provider(
+    config: &RociConfig,
+    provider_key: &str,
+) -> Option<Result<(), RociError>> {
+    if config.requires_credentials(provider_key) {
+        return None;
+    }
+    let target = ProbeTarget::for_provider(config, provider_key, "")?;
+    if config.is_offline() && !is_loopback_url(&target.api_base) {
+        return Some(Err(offline_error(provider_key)));
+    }
+    let url = format!("{}/models", target.api_base.trim_end_matches('/'));
+    let request = probe_request(&target, reqwest::Method::GET, url).timeout(PING_TIMEOUT);
+    Some(request.send().await.map(|_| ()).map_err(RociError::from))
+}
+
+fn default_capabilities(provider_key: &str, model_id: &str) -> ModelCapabilities {
+    match ProviderKey::parse(provider_key) {
+        #[cfg(feature = "ollama")]
+        Some(ProviderKey::Ollama) => {
+            use std::str::FromStr;
+            crate::models::ollama::OllamaModel::from_str(model_id)
+                .unwrap_or(crate::models::ollama::OllamaModel::Custom(
+                    model_id.to_string(),
+                ))
+                .capabilities()
+        }
+        #[cfg(feature = "lmstudio")]
+        Some(ProviderKey::LmStudio) => {
+            crate::models::lmstudio::LmStudioModel::Custom(model_id.to_string()).capabilities()
+        }
+        #[cfg(feature = "openai")]
+        Some(ProviderKey::OpenAiCompatible) => {
+            crate::models::openai::OpenAiModel::Custom(model_id.to_string()).capabilities()
+        }
+        _ => {
+            let _ = model_id;
+            ModelCapabilities::default()
+        }
+    }
+}
+
+/// Probe `target`, using and populating `cache`.
+///
+/// Fresh cache entries are returned without touching the network. Returns
+/// `None` when probing is disabled or the endpoint answered neither probe;
+/// callers keep their defaults in that case.
+pub async fn probe_capabilities(
+    config: &RociConfig,
+    target: &ProbeTarget,
+    defaults: ModelCapabilities,
+    cache: &CapabilityCache,
+) -> Option<ModelCapabilities> {
+    if !config.capability_probing_enabled()
+        || (config.is_offline() && !is_loopback_url(&target.api_base))
+    {
+        return None;
+    }
+    if let Some(cached) = cache.get(&target.api_base, &target.model_id) {
+        return Some(cached);
+    }
+    if !cache.begin_probe(&target.api_base, &target.model_id) {
+        return None;
+    }
+    let probed = run_probe(target, defaults).await;
+    cache.end_probe(&target.api_base, &target.model_id);
+
+    let capabilities = probed?;
+    cache.insert(&target.api_base, &target.model_id, capabilities.clone());
+    Some(capabilities)
+}
+
+async fn run_probe(target: &ProbeTarget, defaults: ModelCapabilities) -> Option<ModelCapabilities> {
+    let mut context_length = probe_models_endpoint(target).await;
+    if context_length.is_none() {
+        context_length = probe_native_endpoint(target).await;
+    }
+    let supports_tools = probe_tool_support(target).await;
+
+    if context_length.is_none() && supports_tools.is_none() {
+        tracing::debug!(
+            api_base = %target.api_base,
+            model = %target.model_id,
+            "capability probe found nothing; keeping defaults"
+        );
+        return None;
+    }
+
+    let mut capabilities = defaults;
+    if let Some(context_length) = context_length {
+        capabilities.context_length = context_length;
+    }
+    if let Some(supports_tools) = supports_tools {
+        capabilities.supports_tools = supports_tools;
+    }
+    Some(capabilities)
+}
+
+fn probe_request(
+    target: &ProbeTarget,
+    method: reqwest::Method,
+    url: String,
+) -> reqwest::RequestBuilder {
+    let request = shared_client()
+        .request(method, url)
+        .timeout(PROBE_TIMEOUT)
+        .header(PROBE_HEADER, PROBE_HEADER_VALUE);
+    match target.api_key.as_deref().filter(|key| !key.is_empty()) {
+        Some(key) => request.bearer_auth(key),
+        None => request,
+    }
+}
+
+async fn fetch_json(request: reqwest::RequestBuilder) -> Option<Value> {
+    let response = request.send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json::<Value>().await.ok()
+}
+
+async fn probe_models_endpoint(target: &ProbeTarget) -> Option<usize> {
+    let url = format!("{}/models", target.api_base.trim_end_matches('/'));
+    let body = fetch_json(probe_request(target, reqwest::Method::GET, url)).await?;
+    let entry =
+        body.get("data")?.as_array()?.iter().find(|entry| {
+            entry.get("id").and_then(Value::as_str) == Some(target.model_id.as_str())
+        })?;
+    context_length_from(entry)
+}
+
+async fn probe_native_endpoint(target: &ProbeTarget) -> Option<usize> {
+    match &target.native {
+        NativeModelInfo::None => None,
+        NativeModelInfo::Ollama { root_url } => {
+            let url = format!("{}/api/show", root_url.trim_end_matches('/'));
+            let request = probe_request(target, reqwest::Method::POST, url)
+                .json(&serde_json::json!({ "model": target.model_id }));
+            let body = fetch_json(request).await?;
+            body.get("model_info")?
+                .as_object()?
+                .iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| positive_usize(value))
+        }
+        NativeModelInfo::LmStudio { root_url } => {
+            let url = format!(
+                "{}/api/v0/models/{}",
+                root_url.trim_end_matches('/'),
+                target.model_id
+            );
+            let body = fetch_json(probe_request(target, reqwest::Method::GET, url)).await?;
+            context_length_from(&body)
+        }
+    }
+}
+
+/// Context-length fields used by common OpenAI-compatible servers.
+const CONTEXT_LENGTH_KEYS: &[&str] = &[
+    "context_length",
+    "max_context_length",
+    "context_window",
+    "max_model_len",
+    "loaded_context_length",
+];
+
+fn context_length_from(entry: &Value) -> Option<usize> {
+    CONTEXT_LENGTH_KEYS
+        .iter()
+        .find_map(|key| entry.get(*key).and_then(positive_usize))
+        .or_else(|| {
+            // llama.cpp server reports the training context under `meta`.
+            entry
+                .get("meta")
+                .and_then(|meta| meta.get("n_ctx_train"))
+                .and_then(positive_usize)
+        })
+}
+
+/// Send one cheap tools-enabled request.
+///
+/// `Some(true)` on success, `Some(false)` when the server rejects the request
+/// as invalid (400/422), `None` when the result says nothing about tools
+/// (auth errors, 5xx, timeouts).
+async fn probe_tool_support(target: &ProbeTarget) -> Option<bool> {
+    let url = format!("{}/chat/completions", target.api_base.trim_end_matches('/'));
+    let body = serde_json::json!({
+        "model": target.model_id,
+        "messages": [{
+            "role": "user",
+            "content": "roci capability probe: reply with a single token.",
+        }],
+        "max_tokens": 1,
+        "stream": false,
+        "tools": [{
+            "type": "function",
+            "function": {
+                "name": PROBE_TOOL_NAME,
+                "description": "Capability probe. Do not call.",
+                "parameters": { "type": "object", "properties": {} },
+            },
+        }],
+    });
+    let response = probe_request(target, reqwest::Method::POST, url)
+        .json(&body)
+        .send()
+        .await
+        .ok()?;
+    let status = response.status();
+    if status.is_success() {
+        Some(true)
+    } else if status == reqwest::StatusCode::BAD_REQUEST
+        || status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cache
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    api_base: String,
+    model: String,
+    capabilities: ModelCapabilities,
+    probed_at: DateTime<Utc>,
+}
+
+impl CacheRecord for CacheEntry {
+    type Key = (String, String);
+
+    fn key(&self) -> Self::Key {
+        cache_key(&self.api_base, &self.model)
+    }
+
+    fn stored_at(&self) -> DateTime<Utc> {
+        self.probed_at
+    }
+}
+
+/// Probed capabilities keyed by `(api_base, model)` with a TTL.
+///
+/// In-memory by default; [`CapabilityCache::with_disk_path`] adds best-effort
+/// JSON persistence. Disk errors are logged and otherwise ignored.
+#[derive(Debug)]
+pub struct CapabilityCache {
+    cache: DiskCache<CacheEntry>,
+    in_flight: Mutex<HashSet<(String, String)>>,
+}
+
+impl Default for CapabilityCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_TTL)
+    }
+}
+
+impl CapabilityCache {
+    /// Create an in-memory cache with the given TTL.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: DiskCache::new(ttl, CACHE_FILE_VERSION, "capability cache"),
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Persist entries to `path` (loaded lazily on first lookup).
+    pub fn with_disk_path(self, path: impl Into<PathBuf>) -> Self {
+        self.set_disk_path(Some(path.into()));
+        self
+    }
+
+    /// Change (or clear) the persistence file of an existing cache.
+    ///
+    /// Used to enable disk persistence on [`CapabilityCache::global`].
+    pub fn set_disk_path(&self, path: Option<PathBuf>) {
+        self.cache.set_disk_path(path);
+    }
+
+    /// Default persistence file: `~/.roci/capability-cache.json`.
+    pub fn default_disk_path() -> PathBuf {
+        default_disk_path(CACHE_FILE_NAME)
+    }
+
+    /// Process-wide cache used by the built-in provider factories.
+    pub fn global() -> &'static CapabilityCache {
+        static GLOBAL: OnceLock<CapabilityCache> = OnceLock::new();
+        GLOBAL.get_or_init(CapabilityCache::default)
+    }
+
+    /// Fresh capabilities for `(api_base, model)`, if any.
+    pub fn get(&self, api_base: &str, model: &str) -> Option<ModelCapabilities> {
+        let entries = self.cache.entries();
+        let entry = entries.get(&cache_key(api_base, model))?;
+        self.cache
+            .is_fresh(entry)
+            .then(|| entry.capabilities.clone())
+    }
+
+    /// Store capabilities for `(api_base, model)` and persist if configured.
+    pub fn insert(&self, api_base: &str, model: &str, capabilities: ModelCapabilities) {
+        self.cache.insert(CacheEntry {
+            api_base: normalize_base(api_base),
+            model: model.to_string(),
+            capabilities,
+            probed_at: Utc::now(),
+        });
+    }
+
+    /// Drop the entry for `(api_base, model)` from memory.
+    pub fn invalidate(&self, api_base: &str, model: &str) {
+        self.cache.remove(&cache_key(api_base, model));
+    }
+
+    /// Drop every entry from memory without re-reading the disk file, so
+    /// models are probed again on next use.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    fn begin_probe(&self, api_base: &str, model: &str) -> bool {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .insert(cache_key(api_base, model))
+    }
+
+    fn end_probe(&self, api_base: &str, model: &str) {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .remove(&cache_key(api_base, model));
+    }
+}
+
+fn normalize_base(api_base: &str) -> String {
+    api_base.trim_end_matches('/').to_string()
+}
+
+fn cache_key(api_base: &str, model: &str) -> (String, String) {
+    (normalize_base(api_base), model.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn target(server: &MockServer, model: &str) -> ProbeTarget {
+        ProbeTarget {
+            api_base: format!("{}/v1", server.uri()),
+            api_key: None,
+            model_id: model.to_string(),
+            native: NativeModelInfo::None,
+        }
+    }
+
+    fn probing_config() -> RociConfig {
+        RociConfig::new().with_token_store(None)
+    }
+
+    async fn mount_models(server: &MockServer, body: Value) {
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header(PROBE_HEADER, PROBE_HEADER_VALUE))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn detects_context_length_and_tool_support() {
+        let server = MockServer::start().await;
+        mount_models(
+            &server,
+            serde_json::json!({
+                "data": [
+                    { "id": "other", "context_length": 2048 },
+                    { "id": "qwen-local", "max_model_len": 65536 },
+                ]
+            }),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header(PROBE_HEADER, PROBE_HEADER_VALUE))
+            .and(body_partial_json(serde_json::json!({ "max_tokens": 1 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": "ok" } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache = CapabilityCache::default();
+        let caps = probe_capabilities(
+            &probing_config(),
+            &target(&server, "qwen-local"),
+            ModelCapabilities::default(),
+            &cache,
+        )
+        .await
+        .expect("probe should succeed");
+
+        assert_eq!(caps.context_length, 65536);
+        assert!(caps.supports_tools);
+        assert_eq!(
+            cache.get(&format!("{}/v1/", server.uri()), "qwen-local"),
+            Some(caps)
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_tools_request_marks_tools_unsupported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("tools are not supported"))
+            .mount(&server)
+            .await;
+        let defaults = ModelCapabilities {
+            supports_tools: true,
+            ..ModelCapabilities::default()
+        };
+
+        let caps = probe_capabilities(
+            &probing_config(),
+            &target(&server, "no-tools"),
+            defaults.clone(),
+            &CapabilityCache::default(),
+        )
+        .await
+        .expect("tool probe answered");
+
+        assert!(!caps.supports_tools);
+        assert_eq!(caps.context_length, defaults.context_length);
+    }
+
+    #[tokio::test]
+    async fn ollama_native_endpoint_supplies_context_length() {
+        let server = MockServer::start().await;
+        mount_models(
+            &server,
+            serde_json::json!({ "data": [{ "id": "llama3.3" }] }),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model_info": { "llama.context_length": 131072 }
+            })))
+            .mount(&server)
+            .await;
+        let target = ProbeTarget {
+            native: NativeModelInfo::Ollama {
+                root_url: server.uri(),
+            },
+            ..target(&server, "llama3.3")
+        };
+
+        let caps = probe_capabilities(
+            &probing_config(),
+            &target,
+            ModelCapabilities::default(),
+            &CapabilityCache::default(),
+        )
+        .await
+        .expect("native endpoint answered");
+
+        assert_eq!(caps.context_length, 131072);
+    }
+
+    #[tokio::test]
+    async fn cached_results_skip_the_network() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{ "id": "cached", "context_length": 8192 }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let cache = CapabilityCache::default();
+        let config = probing_config();
+        let target = target(&server, "cached");
+
+        let first = probe_capabilities(&config, &target, ModelCapabilities::default(), &cache)
+            .await
+            .expect("first probe");
+        let second = probe_capabilities(&config, &target, ModelCapabilities::default(), &cache)
+            .await
+            .expect("cached result");
+
+        assert_eq!(first, second);
+        assert_eq!(second.context_length, 8192);
+    }
+
+    #[tokio::test]
+    async fn disabled_probing_sends_no_requests() {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let config = probing_config();
+        config.set_capability_probing(false);
+        let cache = CapabilityCache::default();
+        cache.insert(
+            &format!("{}/v1", server.uri()),
+            "air-gapped",
+            ModelCapabilities {
+                context_length: 1,
+                ..ModelCapabilities::default()
+            },
+        );
+
+        let probed = probe_capabilities(
+            &config,
+            &target(&server, "air-gapped"),
+            ModelCapabilities::default(),
+            &cache,
+        )
+        .await;
+
+        assert!(probed.is_none());
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoint_degrades_to_defaults() {
+        let server = MockServer::start().await;
+        let target = target(&server, "gone");
+        drop(server);
+        let cache = CapabilityCache::default();
+
+        let probed = probe_capabilities(
+            &probing_config(),
+            &target,
+            ModelCapabilities::default(),
+            &cache,
+        )
+        .await;
+
+        assert!(probed.is_none());
+        assert!(cache.get(&target.api_base, "gone").is_none());
+    }
+
+    #[test]
+    fn disk_cache_round_trips_and_honors_ttl() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE_NAME);
+        let caps = ModelCapabilities {
+            context_length: 32_768,
+            supports_tools: true,
+            ..ModelCapabilities::default()
+        };
+        CapabilityCache::default().with_disk_path(&path).insert(
+            "http://localhost:1234/v1",
+            "m",
+            caps.clone(),
+        );
+
+        let reloaded = CapabilityCache::default().with_disk_path(&path);
+        assert_eq!(reloaded.get("http://localhost:1234/v1", "m"), Some(caps));
+
+        let expired = CapabilityCache::new(Duration::ZERO).with_disk_path(&path);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(expired.get("http://localhost:1234/v1", "m").is_none());
+    }
+
+    #[tokio::test]
+    async fn ping_accepts_any_response_from_local_servers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer proxy-token"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let config = probing_config();
+        config.set_base_url("ollama", server.uri());
+        config.set_api_key("ollama", "proxy-token".to_string());
+
+        let result = ping_provider(&config, "ollama").await;
+
+        assert!(matches!(result, Some(Ok(()))));
+    }
+
+    #[tokio::test]
+    async fn ping_reports_unreachable_servers_and_skips_remote_providers() {
+        let config = probing_config();
+        config.set_base_url("lmstudio", "http://127.0.0.1:9".to_string());
+
+        let unreachable = ping_provider(&config, "lmstudio").await;
+
+        assert!(matches!(unreachable, Some(Err(RociError::Network(_)))));
+        assert!(ping_provider(&config, "openai").await.is_none());
+    }
+
+    #[test]
+    fn cached_or_default_respects_disabled_switch() {
+        let config = probing_config();
+        let caps = ModelCapabilities {
+            context_length: 99_999,
+            ..ModelCapabilities::default()
+        };
+        CapabilityCache::global().insert("http://switch.test/v1", "switch-model", caps.clone());
+
+        assert_eq!(
+            cached_or_default(
+                &config,
+                "http://switch.test/v1",
+                "switch-model",
+                ModelCapabilities::default()
+            ),
+            caps
+        );
+        config.set_capability_probing(false);
+        assert_eq!(
+            cached_or_default(
+                &config,
+                "http://switch.test/v1",
+                "switch-model",
+                ModelCapabilities::default()
+            ),
+            ModelCapabilities::default()
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! In-memory TTL cache with best-effort JSON persistence.
+//!
+//! Shared by [`CapabilityCache`](crate::capability_probe::CapabilityCache)
+//! and the remote model catalog cache. The file is read lazily on first use
+//! and rewritten on every insert; disk errors are logged and otherwise
+//! ignored.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// An entry stored in a [`DiskCache`].
+pub(crate) trait CacheRecord: Clone + fmt::Debug + Serialize + DeserializeOwned {
+    type Key: Clone + Eq + Hash + fmt::Debug;
+
+    fn key(&self) -> Self::Key;
+
+    /// When the entry was stored; its age is checked against the TTL.
+    fn stored_at(&self) -> DateTime<Utc>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    entries: Vec<T>,
+}
+
+#[derive(Debug)]
+struct CacheState<T: CacheRecord> {
+    entries: HashMap<T::Key, T>,
+    disk_loaded: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct DiskCache<T: CacheRecord> {
+    ttl: Duration,
+    /// Files written with another version are ignored.
+    version: u32,
+    /// Names the cache in log messages.
+    label: &'static str,
+    /// Write expired entries back to disk instead of dropping them.
+    persist_expired: bool,
+    disk_path: Mutex<Option<PathBuf>>,
+    state: Mutex<CacheState<T>>,
+}
+
+impl<T: CacheRecord> DiskCache<T> {
+    pub fn new(ttl: Duration, version: u32, label: &'static str) -> Self {
+        Self {
+            ttl,
+            version,
+            label,
+            persist_expired: false,
+            disk_path: Mutex::new(None),
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                disk_loaded: false,
+            }),
+        }
+    }
+
+    /// Keep expired entries in the file, for caches that serve stale data.
+    #[cfg_attr(
+        not(any(feature = "openrouter", feature = "together")),
+        allow(dead_code)
+    )]
+    pub fn persisting_expired(mut self) -> Self {
+        self.persist_expired = true;
+        self
+    }
+
+    /// Change (or clear) the persistence file; it is read again on next use.
+    pub fn set_disk_path(&self, path: Option<PathBuf>) {
+        *self.disk_path.lock().unwrap() = path;
+        self.state.lock().unwrap().disk_loaded = false;
+    }
+
+    pub fn is_fresh(&self, entry: &T) -> bool {
+        let age = Utc::now().signed_duration_since(entry.stored_at());
+        age.to_std().map(|age| age <= self.ttl).unwrap_or(true)
+    }
+
+    /// Every entry, fresh or not, after loading the disk file once.
+    pub fn entries(&self) -> impl std::ops::Deref<Target = HashMap<T::Key, T>> + '_ {
+        self.load_from_disk_once();
+        EntriesGuard(self.state.lock().unwrap())
+    }
+
+    /// Store `entry` and persist if configured.
+    pub fn insert(&self, entry: T) {
+        self.load_from_disk_once();
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            state.entries.insert(entry.key(), entry);
+            state
+                .entries
+                .values()
+                .filter(|entry| self.persist_expired || self.is_fresh(entry))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        if let Some(path) = self.disk_path.lock().unwrap().clone() {
+            if let Err(err) = self.write_file(&path, snapshot) {
+                tracing::debug!(path = %path.display(), error = %err, "failed to write {}", self.label);
+            }
+        }
+    }
+
+    /// Drop the entry for `key` from memory.
+    pub fn remove(&self, key: &T::Key) {
+        self.state.lock().unwrap().entries.remove(key);
+    }
+
+    /// Drop every entry from memory without re-reading the disk file.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.disk_loaded = true;
+    }
+
+    fn load_from_disk_once(&self) {
+        let Some(path) = self.disk_path.lock().unwrap().clone() else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.disk_loaded {
+            return;
+        }
+        state.disk_loaded = true;
+        let Some(file) = self.read_file(&path) else {
+            return;
+        };
+        for entry in file.entries {
+            state.entries.entry(entry.key()).or_insert(entry);
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> Option<CacheFile<T>> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<CacheFile<T>>(&contents) {
+            Ok(file) if file.version == self.version => Some(file),
+            Ok(_) => None,
+            Err(err) => {
+                tracing::debug!(path = %path.display(), error = %err, "ignoring unreadable {}", self.label);
+                None
+            }
+        }
+    }
+
+    fn write_file(&self, path: &Path, entries: Vec<T>) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = CacheFile {
+            version: self.version,
+            entries,
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+struct EntriesGuard<'a, T: CacheRecord>(MutexGuard<'a, CacheState<T>>);
+
+impl<T: CacheRecord> std::ops::Deref for EntriesGuard<'_, T> {
+    type Target = HashMap<T::Key, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.entries
+    }
+}
+
+/// `~/.roci/<file_name>`, or `.roci/<file_name>` without a home directory.
+pub(crate) fn default_disk_path(file_name: &str) -> PathBuf {
+    directories::UserDirs::new()
+        .map(|dirs| dirs.home_dir().join(".roci"))
+        .unwrap_or_else(|| PathBuf::from(".roci"))
+        .join(file_name)
+}
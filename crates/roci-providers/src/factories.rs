@@ -4,7 +4,9 @@ use futures::future::BoxFuture;
 use roci_core::config::RociConfig;
 use roci_core::error::RociError;
 use roci_core::models::{ModelCatalog, ModelListOptions, ProviderKey};
-use roci_core::provider::{ModelProvider, ProviderFactory};
+use roci_core::provider::{
+    ModelFamily, ModelProvider, ProviderFactory, ProviderFeatureMetadata, ProviderFeatures,
+};
 
 fn catalog_future<'a>(
     provider_key: &'a str,
@@ -20,6 +22,44 @@ fn catalog_future<'a>(
     })
 }
 
+/// Feature metadata with one model family per static catalog entry.
+fn static_feature_metadata(
+    provider_key: &str,
+    builder: fn(&str) -> ModelCatalog,
+    features: ProviderFeatures,
+) -> ProviderFeatureMetadata {
+    ProviderFeatureMetadata {
+        model_families: builder(provider_key)
+            .into_models()
+            .into_iter()
+            .map(|model| ModelFamily {
+                name: model.model_id,
+                capabilities: model.capabilities,
+            })
+            .collect(),
+        features,
+    }
+}
+
+/// Catalog fetched at runtime, for providers without a static model table.
+#[cfg(any(feature = "openrouter", feature = "together"))]
+fn remote_catalog_future<'a>(
+    config: &'a RociConfig,
+    provider_key: &'a str,
+    options: &'a ModelListOptions,
+) -> BoxFuture<'a, Result<ModelCatalog, RociError>> {
+    Box::pin(async move {
+        if !options.include_dynamic {
+            return Ok(ModelCatalog::default());
+        }
+        Ok(
+            crate::remote_catalog::refresh_provider_catalog(config, provider_key)
+                .await
+                .unwrap_or_default(),
+        )
+    })
+}
+
 /// Resolve an API key from config for the given provider.
 fn require_api_key(
     config: &RociConfig,
@@ -45,6 +85,35 @@ fn optional_api_key(config: &RociConfig, provider: &str) -> String {
     config.get_api_key(provider).unwrap_or_default()
 }
 
+/// Chat format for a local model: the first config rule matching the model,
+/// then the `chat_format` and `role_names` model options on top.
+#[cfg(any(feature = "ollama", feature = "lmstudio"))]
+fn local_chat_format(
+    config: &RociConfig,
+    provider_key: &str,
+    model_id: &str,
+    options: &std::collections::BTreeMap<String, String>,
+) -> Result<roci_core::config::ChatFormat, RociError> {
+    use roci_core::config::ChatFormat;
+
+    roci_core::provider::check_provider_options(
+        provider_key,
+        options,
+        &["chat_format", "role_names"],
+    )?;
+    let mut chat_format = config
+        .chat_format_for(provider_key, model_id)
+        .unwrap_or_default();
+    if let Some(system) = options.get("chat_format") {
+        chat_format.system = system.parse()?;
+    }
+    if let Some(role_names) = options.get("role_names") {
+        chat_format.role_names = ChatFormat::parse_role_names(role_names)?;
+    }
+    chat_format.validate()?;
+    Ok(chat_format)
+}
+
 // ---------------------------------------------------------------------------
 // OpenAI
 // ---------------------------------------------------------------------------
@@ -58,6 +127,35 @@ impl ProviderFactory for OpenAiFactory {
         &["openai"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(
+            config
+                .get_base_url_for(ProviderKey::OpenAi)
+                .unwrap_or_else(|| crate::provider::openai::DEFAULT_BASE_URL.to_string()),
+        )
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        // Responses API requests allow parallel tool calls and send a
+        // prompt cache key; Whisper and TTS live in `roci_core::audio`.
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::openai_catalog,
+            ProviderFeatures {
+                parallel_tool_calls: true,
+                prompt_caching: true,
+                audio: true,
+                image_generation: true,
+                ..ProviderFeatures::default()
+            },
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -93,20 +191,63 @@ impl ProviderFactory for OpenAiFactory {
                 ),
             ))
         } else {
-            Ok(Box::new(crate::provider::openai::OpenAiProvider::new(
-                model,
-                api_key,
-                config.get_base_url_for(ProviderKey::OpenAi),
-                None,
-            )))
+            Ok(Box::new(
+                crate::provider::openai::OpenAiProvider::new(
+                    model,
+                    api_key,
+                    config.get_base_url_for(ProviderKey::OpenAi),
+                    None,
+                )
+                .with_request_compression(config.request_compression_for("openai")),
+            ))
         }
     }
+
+    /// Accepts `reasoning_effort`, the default effort of a Responses API
+    /// model.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        use crate::models::openai::OpenAiModel;
+        use roci_core::types::ReasoningEffort;
+        use std::str::FromStr;
+
+        roci_core::provider::check_provider_options(provider_key, options, &["reasoning_effort"])?;
+        let Some(effort) = options.get("reasoning_effort") else {
+            return self.create(config, provider_key, model_id);
+        };
+        let effort = ReasoningEffort::from_str(effort).map_err(|_| {
+            RociError::Configuration(format!("invalid reasoning_effort '{effort}'"))
+        })?;
+        let model =
+            OpenAiModel::from_str(model_id).unwrap_or(OpenAiModel::Custom(model_id.to_string()));
+        if !model.uses_responses_api() {
+            return Err(RociError::Configuration(format!(
+                "reasoning_effort applies to Responses API models; '{model_id}' is not one"
+            )));
+        }
+        let provider = crate::provider::openai_responses::OpenAiResponsesProvider::new(
+            model,
+            optional_api_key_for(config, ProviderKey::OpenAi),
+            config.get_base_url_for(ProviderKey::OpenAi),
+            None,
+        )
+        .with_default_reasoning_effort(effort)?;
+        Ok(Box::new(provider))
+    }
 }
 
 // ---------------------------------------------------------------------------
 // OpenAI Codex (Codex CLI backend)
 // ---------------------------------------------------------------------------
 
+#[cfg(feature = "openai")]
+const CODEX_BASE_URL: &str = "https://chatgpt.com/backend-api/codex";
+
 #[cfg(feature = "openai")]
 pub struct CodexFactory;
 
@@ -116,6 +257,31 @@ impl ProviderFactory for CodexFactory {
         &["codex"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(
+            config
+                .get_base_url_for(ProviderKey::Codex)
+                .unwrap_or_else(|| CODEX_BASE_URL.to_string()),
+        )
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::codex_catalog,
+            ProviderFeatures {
+                parallel_tool_calls: true,
+                prompt_caching: true,
+                ..ProviderFeatures::default()
+            },
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -137,7 +303,7 @@ impl ProviderFactory for CodexFactory {
         let api_key = optional_api_key_for(config, ProviderKey::Codex);
         let base_url = config
             .get_base_url_for(ProviderKey::Codex)
-            .or_else(|| Some("https://chatgpt.com/backend-api/codex".to_string()));
+            .or_else(|| Some(CODEX_BASE_URL.to_string()));
         let account_id = config.get_account_id_for(ProviderKey::Codex);
         let model =
             OpenAiModel::from_str(model_id).unwrap_or(OpenAiModel::Custom(model_id.to_string()));
@@ -168,6 +334,27 @@ impl ProviderFactory for AnthropicFactory {
         &["anthropic"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(
+            config
+                .get_base_url_for(ProviderKey::Anthropic)
+                .unwrap_or_else(|| crate::provider::anthropic::DEFAULT_BASE_URL.to_string()),
+        )
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::anthropic_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -198,7 +385,8 @@ impl ProviderFactory for AnthropicFactory {
                 model,
                 api_key,
                 config.get_base_url_for(ProviderKey::Anthropic),
-            ),
+            )
+            .with_request_compression(config.request_compression_for("anthropic")),
         ))
     }
 }
@@ -216,6 +404,26 @@ impl ProviderFactory for GoogleFactory {
         &["google"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        match config.google_vertex() {
+            Some(vertex) => Some(crate::provider::google::vertex_base_url(&vertex.location)),
+            None => Some(crate::provider::google::BASE_URL.to_string()),
+        }
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::google_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -238,9 +446,27 @@ impl ProviderFactory for GoogleFactory {
         use crate::models::google::GoogleModel;
         use std::str::FromStr;
 
-        let api_key = require_api_key(config, ProviderKey::Google, "Missing GOOGLE_API_KEY")?;
         let model =
             GoogleModel::from_str(model_id).unwrap_or(GoogleModel::Custom(model_id.to_string()));
+        if let Some(vertex) = config.google_vertex() {
+            let credentials_path =
+                vertex
+                    .credentials_path
+                    .ok_or_else(|| RociError::MissingConfiguration {
+                        key: "GOOGLE_APPLICATION_CREDENTIALS".to_string(),
+                        provider: "google".to_string(),
+                    })?;
+            let tokens =
+                crate::auth::google_service_account::shared_token_source(&credentials_path)?;
+            return Ok(Box::new(crate::provider::google::GoogleProvider::vertex(
+                model,
+                vertex.project,
+                vertex.location,
+                tokens,
+            )));
+        }
+
+        let api_key = require_api_key(config, ProviderKey::Google, "Missing GOOGLE_API_KEY")?;
         Ok(Box::new(crate::provider::google::GoogleProvider::new(
             model, api_key,
         )))
@@ -260,6 +486,23 @@ impl ProviderFactory for GrokFactory {
         &["grok"]
     }
 
+    fn resolved_base_url(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::provider::grok::BASE_URL.to_string())
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::grok_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -300,6 +543,23 @@ impl ProviderFactory for GroqFactory {
         &["groq"]
     }
 
+    fn resolved_base_url(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::provider::groq::BASE_URL.to_string())
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::groq_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -340,6 +600,30 @@ impl ProviderFactory for MistralFactory {
         &["mistral"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        model_id: &str,
+    ) -> Option<String> {
+        use crate::models::mistral::MistralModel;
+        use std::str::FromStr;
+
+        let model =
+            MistralModel::from_str(model_id).unwrap_or(MistralModel::Custom(model_id.to_string()));
+        Some(crate::provider::mistral::MistralEndpoint::base_url(
+            config, &model,
+        ))
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::mistral_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -360,14 +644,13 @@ impl ProviderFactory for MistralFactory {
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
         use crate::models::mistral::MistralModel;
+        use crate::provider::mistral::{MistralEndpoint, MistralProvider};
         use std::str::FromStr;
 
-        let api_key = require_api_key(config, ProviderKey::Mistral, "Missing MISTRAL_API_KEY")?;
         let model =
             MistralModel::from_str(model_id).unwrap_or(MistralModel::Custom(model_id.to_string()));
-        Ok(Box::new(crate::provider::mistral::MistralProvider::new(
-            model, api_key,
-        )))
+        let endpoint = MistralEndpoint::resolve(config, &model)?;
+        Ok(Box::new(MistralProvider::from_endpoint(model, endpoint)))
     }
 }
 
@@ -384,8 +667,21 @@ impl ProviderFactory for OllamaFactory {
         &["ollama"]
     }
 
-    fn requires_credentials(&self, _provider_key: &str) -> bool {
-        false
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::capability_probe::ollama_root_url(config))
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            crate::models::catalog::ollama_catalog,
+            ProviderFeatures::default(),
+        )
     }
 
     fn list_models<'a>(
@@ -404,20 +700,40 @@ impl ProviderFactory for OllamaFactory {
     fn create(
         &self,
         config: &RociConfig,
-        _provider_key: &str,
+        provider_key: &str,
         model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        self.create_with_options(config, provider_key, model_id, &Default::default())
+    }
+
+    /// Accepts `chat_format` and `role_names`, layered over the config's
+    /// chat-format rules for the model.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
         use crate::models::ollama::OllamaModel;
         use std::str::FromStr;
 
-        let base_url = config
-            .get_base_url_for(ProviderKey::Ollama)
-            .unwrap_or_else(|| "http://localhost:11434".to_string());
+        let chat_format = local_chat_format(config, provider_key, model_id, options)?;
+        let base_url = crate::capability_probe::ollama_root_url(config);
         let model =
             OllamaModel::from_str(model_id).unwrap_or(OllamaModel::Custom(model_id.to_string()));
-        Ok(Box::new(crate::provider::ollama::OllamaProvider::new(
-            model, base_url,
-        )))
+        let capabilities = crate::capability_probe::cached_or_default(
+            config,
+            &crate::capability_probe::local_api_base(&base_url),
+            model_id,
+            model.capabilities(),
+        );
+        Ok(Box::new(
+            crate::provider::ollama::OllamaProvider::new(model, base_url)
+                .with_api_key(optional_api_key_for(config, ProviderKey::Ollama))
+                .with_capabilities(capabilities)
+                .with_chat_format(chat_format),
+        ))
     }
 }
 
@@ -434,8 +750,13 @@ impl ProviderFactory for LmStudioFactory {
         &["lmstudio"]
     }
 
-    fn requires_credentials(&self, _provider_key: &str) -> bool {
-        false
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::capability_probe::lmstudio_root_url(config))
     }
 
     fn list_models<'a>(
@@ -454,18 +775,38 @@ impl ProviderFactory for LmStudioFactory {
     fn create(
         &self,
         config: &RociConfig,
-        _provider_key: &str,
+        provider_key: &str,
         model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        self.create_with_options(config, provider_key, model_id, &Default::default())
+    }
+
+    /// Accepts `chat_format` and `role_names`, layered over the config's
+    /// chat-format rules for the model.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
         use crate::models::lmstudio::LmStudioModel;
 
-        let base_url = config
-            .get_base_url_for(ProviderKey::LmStudio)
-            .unwrap_or_else(|| "http://localhost:1234".to_string());
+        let chat_format = local_chat_format(config, provider_key, model_id, options)?;
+        let base_url = crate::capability_probe::lmstudio_root_url(config);
         let model = LmStudioModel::Custom(model_id.to_string());
-        Ok(Box::new(crate::provider::lmstudio::LmStudioProvider::new(
-            model, base_url,
-        )))
+        let capabilities = crate::capability_probe::cached_or_default(
+            config,
+            &crate::capability_probe::local_api_base(&base_url),
+            model_id,
+            model.capabilities(),
+        );
+        Ok(Box::new(
+            crate::provider::lmstudio::LmStudioProvider::new(model, base_url)
+                .with_api_key(optional_api_key_for(config, ProviderKey::LmStudio))
+                .with_capabilities(capabilities)
+                .with_chat_format(chat_format),
+        ))
     }
 }
 
@@ -482,6 +823,15 @@ impl ProviderFactory for OpenAiCompatibleFactory {
         &["openai-compatible"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        crate::capability_probe::openai_compatible_base_url(config)
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -497,22 +847,59 @@ impl ProviderFactory for OpenAiCompatibleFactory {
         _provider_key: &str,
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
-        let api_key = config
-            .get_api_key_for(ProviderKey::OpenAiCompatible)
-            .or_else(|| config.get_api_key_for(ProviderKey::OpenAi))
-            .ok_or_else(|| RociError::Authentication("Missing OPENAI_COMPAT_API_KEY".into()))?;
-        let base_url = config
-            .get_base_url_for(ProviderKey::OpenAiCompatible)
-            .or_else(|| config.get_base_url_for(ProviderKey::OpenAi))
-            .ok_or_else(|| RociError::Configuration("Missing OPENAI_COMPAT_BASE_URL".into()))?;
-        Ok(Box::new(
-            crate::provider::openai_compatible::OpenAiCompatibleProvider::new(
-                model_id.to_string(),
-                api_key,
-                base_url,
-            ),
-        ))
+        create_openai_compatible(config, model_id, None)
+    }
+
+    /// Accepts `base_url`, which replaces `OPENAI_COMPAT_BASE_URL`.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        roci_core::provider::check_provider_options(provider_key, options, &["base_url"])?;
+        create_openai_compatible(config, model_id, options.get("base_url").cloned())
+    }
+}
+
+#[cfg(feature = "openai-compatible")]
+fn create_openai_compatible(
+    config: &RociConfig,
+    model_id: &str,
+    base_url: Option<String>,
+) -> Result<Box<dyn ModelProvider>, RociError> {
+    let requires_credentials = config.requires_credentials(ProviderKey::OpenAiCompatible.as_str());
+    let api_key = match config
+        .get_api_key_for(ProviderKey::OpenAiCompatible)
+        .or_else(|| config.get_api_key_for(ProviderKey::OpenAi))
+    {
+        Some(api_key) => api_key,
+        None if !requires_credentials => String::new(),
+        None => {
+            return Err(RociError::Authentication(
+                "Missing OPENAI_COMPAT_API_KEY".into(),
+            ))
+        }
+    };
+    let base_url = base_url
+        .or_else(|| crate::capability_probe::openai_compatible_base_url(config))
+        .ok_or_else(|| RociError::Configuration("Missing OPENAI_COMPAT_BASE_URL".into()))?;
+    let mut provider = crate::provider::openai_compatible::OpenAiCompatibleProvider::new(
+        model_id.to_string(),
+        api_key,
+        base_url.clone(),
+    );
+    if !requires_credentials {
+        provider = provider.without_required_api_key();
     }
+    let capabilities = crate::capability_probe::cached_or_default(
+        config,
+        &base_url,
+        model_id,
+        provider.capabilities().clone(),
+    );
+    Ok(Box::new(provider.with_capabilities(capabilities)))
 }
 
 // ---------------------------------------------------------------------------
@@ -622,6 +1009,25 @@ impl ProviderFactory for GitHubCopilotFactory {
         &["github-copilot"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        resolve_github_copilot_credentials(config)
+            .ok()
+            .map(|(_, base_url)| base_url)
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> ProviderFeatureMetadata {
+        static_feature_metadata(
+            provider_key,
+            github_copilot_static_catalog,
+            ProviderFeatures::default(),
+        )
+    }
+
     fn list_models<'a>(
         &'a self,
         config: &'a RociConfig,
@@ -700,6 +1106,15 @@ impl ProviderFactory for AnthropicCompatibleFactory {
         &["anthropic-compatible"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        config.get_base_url_for(ProviderKey::Anthropic)
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -726,11 +1141,87 @@ impl ProviderFactory for AnthropicCompatibleFactory {
                 model_id.to_string(),
                 api_key,
                 base_url,
-            ),
+            )
+            .with_compat(config.anthropic_compat()),
         ))
     }
 }
 
+// ---------------------------------------------------------------------------
+// Bedrock
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "bedrock")]
+pub struct BedrockFactory;
+
+#[cfg(feature = "bedrock")]
+impl BedrockFactory {
+    fn create_with_chain(
+        config: &RociConfig,
+        chain: &crate::auth::aws::AwsProfileChain,
+        model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        use crate::provider::bedrock::{validate_region, BedrockProvider};
+
+        let region = chain.region()?;
+        validate_region(&region)?;
+        let credentials = chain.credentials()?;
+        let mut provider = BedrockProvider::new(model_id.to_string(), region, credentials);
+        if let Some(base_url) = config.get_base_url("bedrock") {
+            provider = provider.with_base_url(base_url);
+        }
+        Ok(Box::new(provider))
+    }
+}
+
+#[cfg(feature = "bedrock")]
+impl ProviderFactory for BedrockFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &["bedrock"]
+    }
+
+    /// Credentials come from the AWS chain rather than `RociConfig`.
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        config.get_base_url("bedrock").or_else(|| {
+            let region = crate::auth::aws::AwsProfileChain::from_process_env()
+                .region()
+                .ok()?;
+            Some(crate::provider::bedrock::runtime_base_url(&region))
+        })
+    }
+
+    fn list_models<'a>(
+        &'a self,
+        _config: &'a RociConfig,
+        provider_key: &'a str,
+        options: &'a ModelListOptions,
+    ) -> BoxFuture<'a, Result<ModelCatalog, RociError>> {
+        catalog_future(provider_key, options, crate::models::catalog::empty_catalog)
+    }
+
+    fn create(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Self::create_with_chain(
+            config,
+            &crate::auth::aws::AwsProfileChain::from_process_env(),
+            model_id,
+        )
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Azure
 // ---------------------------------------------------------------------------
@@ -744,6 +1235,15 @@ impl ProviderFactory for AzureFactory {
         &["azure"]
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        config.get_base_url_for(ProviderKey::Azure)
+    }
+
     fn list_models<'a>(
         &'a self,
         _config: &'a RociConfig,
@@ -759,25 +1259,52 @@ impl ProviderFactory for AzureFactory {
         _provider_key: &str,
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
-        let api_key = config.get_api_key_for(ProviderKey::Azure).ok_or_else(|| {
-            RociError::MissingCredential {
-                provider: "azure".to_string(),
-            }
-        })?;
-        let endpoint = config.get_base_url_for(ProviderKey::Azure).ok_or_else(|| {
-            RociError::MissingConfiguration {
-                key: "AZURE_OPENAI_ENDPOINT".to_string(),
-                provider: "azure".to_string(),
-            }
-        })?;
-        let api_version = "2024-06-01".to_string();
-        Ok(Box::new(crate::provider::azure::AzureOpenAiProvider::new(
-            endpoint,
-            model_id.to_string(),
-            api_key,
-            api_version,
-        )))
+        create_azure(config, model_id, AZURE_API_VERSION)
     }
+
+    /// Accepts `api_version`, the `api-version` query parameter.
+    fn create_with_options(
+        &self,
+        config: &RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        roci_core::provider::check_provider_options(provider_key, options, &["api_version"])?;
+        let api_version = options
+            .get("api_version")
+            .map_or(AZURE_API_VERSION, String::as_str);
+        create_azure(config, model_id, api_version)
+    }
+}
+
+#[cfg(feature = "azure")]
+const AZURE_API_VERSION: &str = "2024-06-01";
+
+#[cfg(feature = "azure")]
+fn create_azure(
+    config: &RociConfig,
+    model_id: &str,
+    api_version: &str,
+) -> Result<Box<dyn ModelProvider>, RociError> {
+    let api_key =
+        config
+            .get_api_key_for(ProviderKey::Azure)
+            .ok_or_else(|| RociError::MissingCredential {
+                provider: "azure".to_string(),
+            })?;
+    let endpoint = config.get_base_url_for(ProviderKey::Azure).ok_or_else(|| {
+        RociError::MissingConfiguration {
+            key: "AZURE_OPENAI_ENDPOINT".to_string(),
+            provider: "azure".to_string(),
+        }
+    })?;
+    Ok(Box::new(crate::provider::azure::AzureOpenAiProvider::new(
+        endpoint,
+        model_id.to_string(),
+        api_key,
+        api_version.to_string(),
+    )))
 }
 
 // ---------------------------------------------------------------------------
@@ -793,25 +1320,39 @@ impl ProviderFactory for OpenRouterFactory {
         &["openrouter"]
     }
 
+    fn resolved_base_url(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::provider::openrouter::BASE_URL.to_string())
+    }
+
     fn list_models<'a>(
         &'a self,
-        _config: &'a RociConfig,
+        config: &'a RociConfig,
         provider_key: &'a str,
         options: &'a ModelListOptions,
     ) -> BoxFuture<'a, Result<ModelCatalog, RociError>> {
-        catalog_future(provider_key, options, crate::models::catalog::empty_catalog)
+        remote_catalog_future(config, provider_key, options)
     }
 
     fn create(
         &self,
         config: &RociConfig,
-        _provider_key: &str,
+        provider_key: &str,
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
         let api_key = optional_api_key(config, "openrouter");
-        Ok(Box::new(
-            crate::provider::openrouter::OpenRouterProvider::new(model_id.to_string(), api_key),
-        ))
+        let provider =
+            crate::provider::openrouter::OpenRouterProvider::new(model_id.to_string(), api_key);
+        let capabilities = crate::remote_catalog::cached_capabilities(
+            provider_key,
+            model_id,
+            provider.capabilities().clone(),
+        );
+        Ok(Box::new(provider.with_capabilities(capabilities)))
     }
 }
 
@@ -828,26 +1369,39 @@ impl ProviderFactory for TogetherFactory {
         &["together"]
     }
 
+    fn resolved_base_url(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        _model_id: &str,
+    ) -> Option<String> {
+        Some(crate::provider::together::BASE_URL.to_string())
+    }
+
     fn list_models<'a>(
         &'a self,
-        _config: &'a RociConfig,
+        config: &'a RociConfig,
         provider_key: &'a str,
         options: &'a ModelListOptions,
     ) -> BoxFuture<'a, Result<ModelCatalog, RociError>> {
-        catalog_future(provider_key, options, crate::models::catalog::empty_catalog)
+        remote_catalog_future(config, provider_key, options)
     }
 
     fn create(
         &self,
         config: &RociConfig,
-        _provider_key: &str,
+        provider_key: &str,
         model_id: &str,
     ) -> Result<Box<dyn ModelProvider>, RociError> {
         let api_key = optional_api_key(config, "together");
-        Ok(Box::new(crate::provider::together::TogetherProvider::new(
-            model_id.to_string(),
-            api_key,
-        )))
+        let provider =
+            crate::provider::together::TogetherProvider::new(model_id.to_string(), api_key);
+        let capabilities = crate::remote_catalog::cached_capabilities(
+            provider_key,
+            model_id,
+            provider.capabilities().clone(),
+        );
+        Ok(Box::new(provider.with_capabilities(capabilities)))
     }
 }
 
@@ -889,6 +1443,340 @@ mod tests {
         assert!(provider.is_ok());
     }
 
+    #[cfg(all(feature = "ollama", feature = "lmstudio"))]
+    #[test]
+    fn local_factories_need_no_credentials() {
+        let config = config_without_credentials();
+
+        assert!(!OllamaFactory.requires_credentials("ollama"));
+        assert!(!LmStudioFactory.requires_credentials("lmstudio"));
+        assert!(OllamaFactory.create(&config, "ollama", "llama3.3").is_ok());
+        assert!(LmStudioFactory
+            .create(&config, "lmstudio", "local-model")
+            .is_ok());
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn ollama_factory_forwards_explicit_api_key_as_bearer() {
+        use roci_core::provider::ProviderRequest;
+        use roci_core::types::{GenerationSettings, ModelMessage};
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer proxy-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "content": "ok" }, "finish_reason": "stop" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let config = config_without_credentials();
+        config.set_capability_probing(false);
+        config.set_base_url("ollama", server.uri());
+        config.set_api_key("ollama", "proxy-token".to_string());
+
+        let provider = OllamaFactory.create(&config, "ollama", "llama3.3").unwrap();
+        let response = provider
+            .generate_text(&ProviderRequest {
+                messages: vec![ModelMessage::user("hello")].into(),
+                settings: GenerationSettings::default(),
+                tools: None,
+                response_format: None,
+                api_key_override: None,
+                headers: reqwest::header::HeaderMap::new(),
+                metadata: std::collections::HashMap::new(),
+                payload_callback: None,
+                session_id: None,
+                transport: None,
+                tool_call_ids: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "ok");
+    }
+
+    #[cfg(feature = "lmstudio")]
+    #[tokio::test]
+    async fn lmstudio_chat_format_comes_from_config_rules_and_model_options() {
+        use roci_core::config::{ChatFormat, ChatFormatRule, SystemMessageStrategy};
+        use roci_core::provider::ProviderRequest;
+        use roci_core::types::{GenerationSettings, ModelMessage};
+        use std::sync::{Arc, Mutex};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "content": "ok" }, "finish_reason": "stop" }]
+            })))
+            .mount(&server)
+            .await;
+        let config = config_without_credentials();
+        config.set_capability_probing(false);
+        config.set_base_url("lmstudio", server.uri());
+        config.set_chat_format_rules(
+            "lmstudio",
+            vec![ChatFormatRule::new(
+                "gemma-*",
+                ChatFormat {
+                    system: SystemMessageStrategy::SystemToFirstUser,
+                    ..ChatFormat::default()
+                },
+            )],
+        );
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let request = ProviderRequest {
+            messages: vec![ModelMessage::system("Be terse."), ModelMessage::user("hi")].into(),
+            settings: GenerationSettings::default(),
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: Some({
+                let sent = sent.clone();
+                Arc::new(move |body: serde_json::Value| {
+                    sent.lock().unwrap().push(body["messages"].clone());
+                })
+            }),
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        };
+
+        for model in [
+            "lmstudio:gemma-2-9b",
+            "lmstudio:gemma-2-9b?chat_format=drop_system&role_names=user%3Dhuman",
+            "lmstudio:qwen2.5-7b",
+        ] {
+            let model = model.parse().unwrap();
+            let provider = registry().create_provider_for(&model, &config).unwrap();
+            provider.generate_text(&request).await.unwrap();
+        }
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(
+            sent[0],
+            serde_json::json!([{ "role": "user", "content": "Be terse.\n\nhi" }])
+        );
+        assert_eq!(
+            sent[1],
+            serde_json::json!([{ "role": "human", "content": "hi" }])
+        );
+        assert_eq!(sent[2][0]["role"], "system");
+        let bad = "lmstudio:gemma-2-9b?role_names=tool%3Dfunction"
+            .parse()
+            .unwrap();
+        assert!(registry().create_provider_for(&bad, &config).is_err());
+    }
+
+    fn registry() -> roci_core::provider::ProviderRegistry {
+        let mut registry = roci_core::provider::ProviderRegistry::new();
+        crate::register_default_providers(&mut registry);
+        registry
+    }
+
+    #[cfg(any(feature = "azure", feature = "openai-compatible"))]
+    fn hello_request() -> roci_core::provider::ProviderRequest {
+        roci_core::provider::ProviderRequest {
+            messages: vec![roci_core::types::ModelMessage::user("hello")].into(),
+            settings: roci_core::types::GenerationSettings::default(),
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    #[cfg(feature = "openai")]
+    #[test]
+    fn openai_reasoning_effort_option_sets_the_default_effort() {
+        use roci_core::types::ReasoningEffort;
+
+        let config = config_without_credentials();
+        let model = "openai:o3?reasoning_effort=high".parse().unwrap();
+
+        let provider = registry().create_provider_for(&model, &config).unwrap();
+
+        assert_eq!(
+            provider.capabilities().default_reasoning_effort(),
+            Some(ReasoningEffort::High)
+        );
+        let unknown = "openai:o3?temperature=1".parse().unwrap();
+        let Err(RociError::Configuration(message)) =
+            registry().create_provider_for(&unknown, &config)
+        else {
+            panic!("expected unknown options to be rejected");
+        };
+        assert!(
+            message.ends_with("option 'temperature' (supported: reasoning_effort)"),
+            "{message}"
+        );
+        let chat_model = "openai:gpt-4o?reasoning_effort=high".parse().unwrap();
+        assert!(registry()
+            .create_provider_for(&chat_model, &config)
+            .is_err());
+    }
+
+    #[cfg(feature = "azure")]
+    #[tokio::test]
+    async fn azure_api_version_option_sets_the_query_parameter() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/openai/deployments/gpt4o-deploy/chat/completions"))
+            .and(query_param("api-version", "2024-10-21"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "content": "ok" }, "finish_reason": "stop" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let config = config_without_credentials();
+        config.set_api_key("azure", "test-key".to_string());
+        config.set_base_url("azure", server.uri());
+        let model = "azure:gpt4o-deploy?api_version=2024-10-21".parse().unwrap();
+
+        let provider = registry().create_provider_for(&model, &config).unwrap();
+        let response = provider.generate_text(&hello_request()).await.unwrap();
+
+        assert_eq!(response.text, "ok");
+    }
+
+    #[cfg(feature = "openai-compatible")]
+    #[tokio::test]
+    async fn openai_compatible_base_url_option_replaces_the_configured_one() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "content": "ok" }, "finish_reason": "stop" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let config = config_without_credentials();
+        config.set_capability_probing(false);
+        config.set_api_key("openai-compatible", "test-key".to_string());
+        config.set_base_url(
+            "openai-compatible",
+            "https://llm.example.com/v1".to_string(),
+        );
+        let model = roci_core::models::ModelSelector::parse(&format!(
+            "openai-compatible:qwen?base_url={}/v1",
+            server.uri()
+        ))
+        .unwrap();
+
+        let provider = registry().create_provider_for(&model, &config).unwrap();
+        let response = provider.generate_text(&hello_request()).await.unwrap();
+
+        assert_eq!(response.text, "ok");
+    }
+
+    #[cfg(feature = "openai-compatible")]
+    #[test]
+    fn openai_compatible_factory_needs_no_key_on_loopback() {
+        let config = config_without_credentials();
+        config.set_base_url(
+            "openai-compatible",
+            "https://llm.example.com/v1".to_string(),
+        );
+        assert!(matches!(
+            OpenAiCompatibleFactory.create(&config, "openai-compatible", "m"),
+            Err(RociError::Authentication(_))
+        ));
+
+        config.set_base_url("openai-compatible", "http://localhost:8000/v1".to_string());
+        assert!(OpenAiCompatibleFactory
+            .create(&config, "openai-compatible", "m")
+            .is_ok());
+    }
+
+    #[cfg(feature = "google")]
+    #[test]
+    fn google_factory_uses_vertex_host_when_vertex_is_configured() {
+        use roci_core::config::GoogleVertexConfig;
+
+        let config = config_without_credentials();
+        assert_eq!(
+            GoogleFactory
+                .resolved_base_url(&config, "google", "gemini-2.5-pro")
+                .as_deref(),
+            Some(crate::provider::google::BASE_URL)
+        );
+
+        config.set_google_vertex(Some(
+            GoogleVertexConfig::new("my-project").with_location("europe-west4"),
+        ));
+
+        assert_eq!(
+            GoogleFactory
+                .resolved_base_url(&config, "google", "gemini-2.5-pro")
+                .as_deref(),
+            Some("https://europe-west4-aiplatform.googleapis.com")
+        );
+    }
+
+    #[cfg(feature = "google")]
+    #[test]
+    fn google_vertex_without_credentials_file_is_missing_configuration() {
+        use roci_core::config::GoogleVertexConfig;
+
+        let config = config_without_credentials();
+        config.set_api_key("google", "gemini-key".to_string());
+        config.set_google_vertex(Some(GoogleVertexConfig::new("my-project")));
+
+        let err = GoogleFactory
+            .create(&config, "google", "gemini-2.5-pro")
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err,
+            RociError::MissingConfiguration { ref key, ref provider }
+                if key == "GOOGLE_APPLICATION_CREDENTIALS" && provider == "google"
+        ));
+    }
+
+    #[cfg(feature = "google")]
+    #[test]
+    fn google_vertex_with_unreadable_credentials_is_configuration_error() {
+        use roci_core::config::GoogleVertexConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = config_without_credentials();
+        config.set_google_vertex(Some(
+            GoogleVertexConfig::new("my-project").with_credentials_path(dir.path().join("sa.json")),
+        ));
+
+        let err = GoogleFactory
+            .create(&config, "google", "gemini-2.5-pro")
+            .err()
+            .unwrap();
+
+        assert!(
+            matches!(err, RociError::Configuration(ref message) if message.contains("sa.json"))
+        );
+    }
+
     #[test]
     fn openrouter_api_key_ignores_openai_key() {
         let config = config_without_credentials();
@@ -921,6 +1809,37 @@ mod tests {
         assert_eq!(optional_api_key(&config, "together"), "together-key");
     }
 
+    #[cfg(feature = "together")]
+    #[test]
+    fn together_factory_uses_cached_catalog_capabilities() {
+        use crate::remote_catalog::{RemoteCatalogCache, RemoteCatalogProvider};
+
+        let models = RemoteCatalogProvider::Together
+            .parse_models(&serde_json::json!([{
+                "id": "factory-test/Catalog-VL-Model",
+                "type": "chat",
+                "context_length": 65536,
+                "pricing": { "input": 1.0, "output": 2.0 }
+            }]))
+            .unwrap();
+        RemoteCatalogCache::global().insert("together", models);
+
+        let cached = TogetherFactory
+            .create(
+                &config_without_credentials(),
+                "together",
+                "factory-test/Catalog-VL-Model",
+            )
+            .unwrap();
+        let uncached = TogetherFactory
+            .create(&config_without_credentials(), "together", "not-in-catalog")
+            .unwrap();
+
+        assert_eq!(cached.capabilities().context_length, 65_536);
+        assert!(cached.capabilities().supports_vision);
+        assert_eq!(uncached.capabilities().context_length, 128_000);
+    }
+
     #[cfg(feature = "openai")]
     #[tokio::test]
     async fn factory_registration_lists_static_openai_catalog() {
@@ -940,6 +1859,63 @@ mod tests {
             .any(|model| model.provider_key == "openai" && model.model_id == "gpt-4o"));
     }
 
+    #[cfg(all(feature = "openai", feature = "ollama"))]
+    #[test]
+    fn feature_matrix_reports_static_families_and_provider_features() {
+        let config = config_without_credentials();
+
+        let matrix = registry().feature_matrix(&config);
+
+        let openai = matrix
+            .iter()
+            .find(|report| report.provider_key == "openai")
+            .expect("openai report");
+        assert!(openai.requires_credentials);
+        assert!(!openai.has_credentials);
+        assert!(openai.features.parallel_tool_calls);
+        assert!(openai.features.audio);
+        assert!(!openai.features.embeddings);
+        let gpt_4o = openai
+            .model_families
+            .iter()
+            .find(|family| family.name == "gpt-4o")
+            .expect("gpt-4o family");
+        assert!(gpt_4o.capabilities.supports_tools);
+        assert!(gpt_4o.capabilities.supports_vision);
+
+        let ollama = matrix
+            .iter()
+            .find(|report| report.provider_key == "ollama")
+            .expect("ollama report");
+        assert!(!ollama.requires_credentials);
+        assert!(ollama.is_available());
+        assert_eq!(ollama.features, ProviderFeatures::default());
+    }
+
+    #[cfg(all(feature = "openai", feature = "anthropic"))]
+    #[test]
+    fn offline_mode_blocks_remote_defaults_but_allows_loopback_overrides() {
+        let config = config_without_credentials();
+        config.set_offline(true);
+        config.set_base_url("openai", "http://localhost:8080/v1".to_string());
+        let mut registry = roci_core::provider::ProviderRegistry::new();
+        crate::register_default_providers(&mut registry);
+
+        let err = registry
+            .create_provider("anthropic", "claude-sonnet-4", &config)
+            .err()
+            .expect("anthropic should be blocked offline");
+
+        assert!(matches!(
+            err,
+            RociError::Configuration(message)
+                if message == "offline mode: provider 'anthropic' requires network access"
+        ));
+        assert!(registry
+            .create_provider("openai", "gpt-4o", &config)
+            .is_ok());
+    }
+
     #[cfg(feature = "openai")]
     #[tokio::test]
     async fn static_factory_honors_include_static_false() {
@@ -1116,4 +2092,166 @@ mod tests {
             }));
         }
     }
+
+    #[cfg(feature = "bedrock")]
+    mod bedrock {
+        use super::*;
+        use crate::auth::aws::AwsProfileChain;
+        use std::collections::HashMap;
+
+        const MODEL_ID: &str = "anthropic.claude-sonnet-4-20250514-v1:0";
+
+        fn chain(vars: &[(&str, &str)]) -> AwsProfileChain {
+            let env: HashMap<String, String> = vars
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            AwsProfileChain::new(env, None)
+        }
+
+        fn create(
+            config: &RociConfig,
+            vars: &[(&str, &str)],
+        ) -> Result<Box<dyn ModelProvider>, RociError> {
+            BedrockFactory::create_with_chain(config, &chain(vars), MODEL_ID)
+        }
+
+        #[test]
+        fn missing_region_is_missing_configuration() {
+            let err = create(
+                &config_without_credentials(),
+                &[
+                    ("AWS_ACCESS_KEY_ID", "AKID"),
+                    ("AWS_SECRET_ACCESS_KEY", "secret"),
+                ],
+            )
+            .err()
+            .unwrap();
+
+            assert!(matches!(
+                err,
+                RociError::MissingConfiguration { ref key, ref provider }
+                    if key == "AWS_REGION" && provider == "bedrock"
+            ));
+        }
+
+        #[test]
+        fn malformed_region_is_rejected_before_credentials() {
+            let err = create(
+                &config_without_credentials(),
+                &[("AWS_REGION", "us_east_1")],
+            )
+            .err()
+            .unwrap();
+
+            assert!(matches!(err, RociError::Configuration(_)));
+        }
+
+        #[test]
+        fn missing_credentials_is_authentication_error() {
+            let err = create(
+                &config_without_credentials(),
+                &[("AWS_REGION", "us-east-1")],
+            )
+            .err()
+            .unwrap();
+
+            assert!(matches!(err, RociError::Authentication(_)));
+        }
+
+        #[test]
+        fn creates_provider_with_base_url_override() {
+            let config = config_without_credentials();
+            config.set_base_url("bedrock", "http://127.0.0.1:9".to_string());
+
+            let provider = create(
+                &config,
+                &[
+                    ("AWS_REGION", "eu-west-1"),
+                    ("AWS_ACCESS_KEY_ID", "AKID"),
+                    ("AWS_SECRET_ACCESS_KEY", "secret"),
+                ],
+            )
+            .unwrap();
+
+            assert_eq!(provider.provider_name(), "bedrock");
+            assert_eq!(provider.model_id(), MODEL_ID);
+            assert_eq!(
+                BedrockFactory.resolved_base_url(&config, "bedrock", MODEL_ID),
+                Some("http://127.0.0.1:9".to_string())
+            );
+            assert!(!BedrockFactory.requires_credentials("bedrock"));
+        }
+    }
+
+    #[cfg(feature = "lmstudio")]
+    mod capability_probing {
+        use super::*;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        async fn probed_server(model: &str) -> MockServer {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/v1/models"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "data": [{ "id": model, "max_context_length": 16384 }]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/v1/chat/completions"))
+                .respond_with(ResponseTemplate::new(400))
+                .mount(&server)
+                .await;
+            server
+        }
+
+        #[tokio::test]
+        async fn create_uses_probed_capabilities() {
+            let server = probed_server("probe-enabled-model").await;
+            let config = config_without_credentials();
+            config.set_base_url("lmstudio", server.uri());
+
+            crate::capability_probe::probe_provider_capabilities(
+                &config,
+                "lmstudio",
+                "probe-enabled-model",
+            )
+            .await
+            .expect("probe should succeed");
+            let provider = LmStudioFactory
+                .create(&config, "lmstudio", "probe-enabled-model")
+                .unwrap();
+
+            assert_eq!(provider.capabilities().context_length, 16384);
+            assert!(!provider.capabilities().supports_tools);
+        }
+
+        #[tokio::test]
+        async fn disabled_probing_keeps_static_capabilities() {
+            let server = probed_server("probe-disabled-model").await;
+            let config = config_without_credentials();
+            config.set_base_url("lmstudio", server.uri());
+            config.set_capability_probing(false);
+
+            let probed = crate::capability_probe::probe_provider_capabilities(
+                &config,
+                "lmstudio",
+                "probe-disabled-model",
+            )
+            .await;
+            let provider = LmStudioFactory
+                .create(&config, "lmstudio", "probe-disabled-model")
+                .unwrap();
+
+            assert!(probed.is_none());
+            assert!(server.received_requests().await.unwrap().is_empty());
+            assert_eq!(
+                provider.capabilities(),
+                &crate::models::lmstudio::LmStudioModel::Custom("probe-disabled-model".to_string())
+                    .capabilities()
+            );
+        }
+    }
 }
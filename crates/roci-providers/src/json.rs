@@ -0,0 +1,12 @@
+//! Helpers for reading provider JSON responses.
+
+use serde_json::Value;
+
+/// A positive integer that fits in `usize`; zero, negatives, and
+/// non-integers are `None`.
+pub(crate) fn positive_usize(value: &Value) -> Option<usize> {
+    value
+        .as_u64()
+        .filter(|value| *value > 0)
+        .and_then(|value| usize::try_from(value).ok())
+}
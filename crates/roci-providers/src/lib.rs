@@ -7,10 +7,16 @@
 //! Provider-agnostic abstractions live in `roci-core`.
 
 pub mod auth;
+pub mod capability_probe;
+mod disk_cache;
 pub mod factories;
+mod json;
 pub mod models;
 pub mod overflow;
 pub mod provider;
+#[cfg(any(feature = "openrouter", feature = "together"))]
+pub mod remote_catalog;
+pub mod warm_up;
 
 use std::sync::Arc;
 
@@ -20,9 +26,14 @@ use overflow::OverflowClassifyingFactory;
 ///
 /// Each factory is wrapped with [`OverflowClassifyingFactory`] so every
 /// provider instance gains text-based overflow classification without
-/// editing individual provider implementations.
+/// editing individual provider implementations. The first call also hooks
+/// [`clear_config_caches`] into
+/// [`RociConfig::global_reload`](roci_core::config::RociConfig::global_reload).
 #[allow(unused_variables)]
 pub fn register_default_providers(registry: &mut roci_core::provider::ProviderRegistry) {
+    static RELOAD_HOOK: std::sync::Once = std::sync::Once::new();
+    RELOAD_HOOK.call_once(|| roci_core::config::RociConfig::on_global_reload(clear_config_caches));
+
     #[cfg(feature = "openai")]
     {
         registry.register(OverflowClassifyingFactory::wrap(Arc::new(
@@ -85,6 +96,11 @@ pub fn register_default_providers(registry: &mut roci_core::provider::ProviderRe
         factories::AnthropicCompatibleFactory,
     )));
 
+    #[cfg(feature = "bedrock")]
+    registry.register(OverflowClassifyingFactory::wrap(Arc::new(
+        factories::BedrockFactory,
+    )));
+
     #[cfg(feature = "azure")]
     registry.register(OverflowClassifyingFactory::wrap(Arc::new(
         factories::AzureFactory,
@@ -101,6 +117,19 @@ pub fn register_default_providers(registry: &mut roci_core::provider::ProviderRe
     )));
 }
 
+/// Drop process-wide caches derived from config values: probed
+/// capabilities, fetched model catalogs, and service-account token sources.
+///
+/// Providers hold no other shared state; each is built per run from the
+/// config it is given, and the pooled HTTP client carries no credentials.
+pub fn clear_config_caches() {
+    capability_probe::CapabilityCache::global().clear();
+    #[cfg(any(feature = "openrouter", feature = "together"))]
+    remote_catalog::RemoteCatalogCache::global().clear();
+    #[cfg(feature = "google")]
+    auth::google_service_account::clear_shared_token_sources();
+}
+
 /// Register all built-in auth backends with the given auth service.
 pub fn register_default_auth_backends(service: &mut roci_core::auth::AuthService) {
     service.register_backend(Arc::new(auth::GitHubCopilotBackend));
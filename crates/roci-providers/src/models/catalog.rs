@@ -26,6 +26,7 @@ fn model_info(
             default_for_provider,
         },
         source: ModelCatalogSource::Static,
+        pricing: None,
         metadata: BTreeMap::new(),
     }
 }
@@ -171,6 +172,7 @@ pub fn grok_catalog(provider_key: &str) -> ModelCatalog {
     use crate::models::grok::GrokModel;
 
     let models = [
+        GrokModel::Grok2Vision,
         GrokModel::Grok3,
         GrokModel::Grok3Mini,
         GrokModel::Grok4,
@@ -191,6 +193,10 @@ pub fn groq_catalog(provider_key: &str) -> ModelCatalog {
         GroqModel::Llama3370bVersatile,
         GroqModel::Llama318bInstant,
         GroqModel::Mixtral8x7b,
+        GroqModel::Qwen332b,
+        GroqModel::DeepseekR1DistillLlama70b,
+        GroqModel::GptOss120b,
+        GroqModel::GptOss20b,
     ];
 
     ModelCatalog::from_models(models.into_iter().map(|model| {
@@ -3,11 +3,18 @@
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use roci_core::models::{ModelCapabilities, ModelInputCapabilities};
+use roci_core::models::{ImageInputCapabilities, ModelCapabilities, ModelInputCapabilities};
+
+/// xAI's documented per-image limit.
+pub const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+/// Image formats xAI accepts.
+pub const SUPPORTED_IMAGE_MIME_TYPES: [&str; 2] = ["image/jpeg", "image/png"];
 
 /// Grok models (OpenAI-compatible API).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Display, EnumString)]
 pub enum GrokModel {
+    #[strum(serialize = "grok-2-vision-1212")]
+    Grok2Vision,
     #[strum(serialize = "grok-3")]
     Grok3,
     #[strum(serialize = "grok-3-mini")]
@@ -23,6 +30,7 @@ pub enum GrokModel {
 impl GrokModel {
     pub fn as_str(&self) -> &str {
         match self {
+            Self::Grok2Vision => "grok-2-vision-1212",
             Self::Grok3 => "grok-3",
             Self::Grok3Mini => "grok-3-mini",
             Self::Grok4 => "grok-4",
@@ -31,8 +39,19 @@ impl GrokModel {
         }
     }
 
+    /// Whether the model accepts image inputs. Unknown ids are assumed
+    /// vision-capable when they are Grok 4 or named as vision models.
+    pub fn supports_vision(&self) -> bool {
+        match self {
+            Self::Grok2Vision | Self::Grok4 | Self::Grok41Fast => true,
+            Self::Grok3 | Self::Grok3Mini => false,
+            Self::Custom(id) => id.starts_with("grok-4") || id.contains("vision"),
+        }
+    }
+
     pub fn capabilities(&self) -> ModelCapabilities {
         let (context_length, supports_reasoning) = match self {
+            Self::Grok2Vision => (32_768, false),
             Self::Grok3 => (131_072, false),
             Self::Grok3Mini => (131_072, true),
             Self::Grok4 => (256_000, true),
@@ -40,8 +59,21 @@ impl GrokModel {
             Self::Custom(_) => (131_072, false),
         };
 
+        let supports_vision = self.supports_vision();
+        let input = ModelInputCapabilities {
+            image: supports_vision.then(|| ImageInputCapabilities {
+                max_image_bytes: Some(MAX_IMAGE_BYTES),
+                supported_mime_types: SUPPORTED_IMAGE_MIME_TYPES
+                    .iter()
+                    .map(|mime| mime.to_string())
+                    .collect(),
+                ..ImageInputCapabilities::default()
+            }),
+            ..ModelInputCapabilities::default()
+        };
+
         ModelCapabilities {
-            supports_vision: true,
+            supports_vision,
             supports_tools: true,
             supports_streaming: true,
             supports_json_mode: true,
@@ -51,7 +83,7 @@ impl GrokModel {
             supports_system_messages: true,
             context_length,
             max_output_tokens: Some(16_384),
-            input: ModelInputCapabilities::from_vision_support(true),
+            input,
         }
     }
 }
@@ -71,6 +103,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn capabilities_advertise_vision_only_for_image_models() {
+        for model in [
+            GrokModel::Grok2Vision,
+            GrokModel::Grok4,
+            GrokModel::Grok41Fast,
+            GrokModel::Custom("grok-4-0709".to_string()),
+        ] {
+            let caps = model.capabilities();
+            assert!(caps.supports_vision, "{model} should support vision");
+            let image = caps.input.image.expect("image limits");
+            assert_eq!(image.max_image_bytes, Some(super::MAX_IMAGE_BYTES));
+            assert_eq!(image.supported_mime_types, ["image/jpeg", "image/png"]);
+        }
+        for model in [GrokModel::Grok3, GrokModel::Grok3Mini] {
+            let caps = model.capabilities();
+            assert!(!caps.supports_vision, "{model} should be text-only");
+            assert!(caps.input.image.is_none());
+        }
+    }
+
     #[test]
     fn capabilities_mark_reasoning_models() {
         assert!(!GrokModel::Grok3.capabilities().supports_reasoning);
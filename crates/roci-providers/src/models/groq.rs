@@ -14,6 +14,14 @@ pub enum GroqModel {
     Llama318bInstant,
     #[strum(serialize = "mixtral-8x7b-32768")]
     Mixtral8x7b,
+    #[strum(serialize = "qwen/qwen3-32b")]
+    Qwen332b,
+    #[strum(serialize = "deepseek-r1-distill-llama-70b")]
+    DeepseekR1DistillLlama70b,
+    #[strum(serialize = "openai/gpt-oss-120b")]
+    GptOss120b,
+    #[strum(serialize = "openai/gpt-oss-20b")]
+    GptOss20b,
     #[strum(default)]
     Custom(String),
 }
@@ -24,19 +32,49 @@ impl GroqModel {
             Self::Llama3370bVersatile => "llama-3.3-70b-versatile",
             Self::Llama318bInstant => "llama-3.1-8b-instant",
             Self::Mixtral8x7b => "mixtral-8x7b-32768",
+            Self::Qwen332b => "qwen/qwen3-32b",
+            Self::DeepseekR1DistillLlama70b => "deepseek-r1-distill-llama-70b",
+            Self::GptOss120b => "openai/gpt-oss-120b",
+            Self::GptOss20b => "openai/gpt-oss-20b",
             Self::Custom(s) => s,
         }
     }
 
+    /// Whether the model accepts `reasoning_format`.
+    ///
+    /// Custom ids are matched against the reasoning model families Groq hosts.
+    pub fn supports_reasoning(&self) -> bool {
+        match self {
+            Self::Llama3370bVersatile | Self::Llama318bInstant | Self::Mixtral8x7b => false,
+            Self::Qwen332b
+            | Self::DeepseekR1DistillLlama70b
+            | Self::GptOss120b
+            | Self::GptOss20b => true,
+            Self::Custom(id) => {
+                let id = id.to_ascii_lowercase();
+                ["qwen3", "qwq", "deepseek-r1", "gpt-oss"]
+                    .iter()
+                    .any(|family| id.contains(family))
+            }
+        }
+    }
+
     pub fn capabilities(&self) -> ModelCapabilities {
         let ctx = match self {
             Self::Llama3370bVersatile => 131_072,
             Self::Llama318bInstant => 128_000,
             Self::Mixtral8x7b => 32_768,
+            Self::Qwen332b
+            | Self::DeepseekR1DistillLlama70b
+            | Self::GptOss120b
+            | Self::GptOss20b => 131_072,
             Self::Custom(_) => 32_768,
         };
         let max_output = match self {
             Self::Llama3370bVersatile => 32_768,
+            Self::Qwen332b => 40_960,
+            Self::DeepseekR1DistillLlama70b => 131_072,
+            Self::GptOss120b | Self::GptOss20b => 65_536,
             _ => 8_192,
         };
         ModelCapabilities {
@@ -45,7 +83,7 @@ impl GroqModel {
             supports_streaming: true,
             supports_json_mode: true,
             supports_json_schema: false,
-            supports_reasoning: false,
+            supports_reasoning: self.supports_reasoning(),
             reasoning_effort: Default::default(),
             supports_system_messages: true,
             context_length: ctx,
@@ -75,4 +113,29 @@ mod tests {
         assert_eq!(caps.context_length, 131_072);
         assert_eq!(caps.max_output_tokens, Some(32_768));
     }
+
+    #[test]
+    fn capabilities_mark_reasoning_models() {
+        assert!(
+            !GroqModel::Llama3370bVersatile
+                .capabilities()
+                .supports_reasoning
+        );
+        for model in [
+            GroqModel::Qwen332b,
+            GroqModel::DeepseekR1DistillLlama70b,
+            GroqModel::GptOss120b,
+            GroqModel::GptOss20b,
+        ] {
+            let caps = model.capabilities();
+            assert!(caps.supports_reasoning, "{model}");
+            assert!(caps.supports_tools, "{model}");
+        }
+    }
+
+    #[test]
+    fn custom_ids_of_reasoning_families_support_reasoning() {
+        assert!(GroqModel::Custom("qwen/qwen3-235b".into()).supports_reasoning());
+        assert!(!GroqModel::Custom("gemma2-9b-it".into()).supports_reasoning());
+    }
 }
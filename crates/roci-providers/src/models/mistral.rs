@@ -31,6 +31,12 @@ impl MistralModel {
         }
     }
 
+    /// Whether the model belongs to the Codestral family, which has a
+    /// dedicated endpoint.
+    pub fn is_codestral(&self) -> bool {
+        self.as_str().starts_with("codestral")
+    }
+
     pub fn capabilities(&self) -> ModelCapabilities {
         let vision = matches!(
             self,
@@ -11,8 +11,8 @@
 //!   typed `ErrorCode::ContextLengthExceeded` fast path, plus regex fallback
 //!   for unstructured error messages.
 //!
-//! - **Anthropic** (including anthropic-compatible transports): text-based
-//!   matching today because the transport does not yet preserve
+//! - **Anthropic** (including anthropic-compatible and Bedrock transports):
+//!   text-based matching today because the transport does not yet preserve
 //!   Anthropic-specific structured error details.
 //!
 //! - **Google Gemini**: text-based matching today because the transport does
@@ -127,7 +127,7 @@ pub struct AnthropicOverflowDetector;
 
 impl AnthropicOverflowDetector {
     /// Provider names this detector handles.
-    pub const PROVIDER_NAMES: &[&str] = &["anthropic", "anthropic-compatible"];
+    pub const PROVIDER_NAMES: &[&str] = &["anthropic", "anthropic-compatible", "bedrock"];
 
     /// Check whether this detector applies to the given provider name.
     pub fn handles_provider(provider: &str) -> bool {
@@ -360,6 +360,26 @@ impl roci_core::provider::ModelProvider for OverflowClassifyingProvider {
         self.inner.capabilities()
     }
 
+    fn supports_assistant_prefix(&self) -> bool {
+        self.inner.supports_assistant_prefix()
+    }
+
+    fn supports_response_format_with_tools(&self) -> bool {
+        self.inner.supports_response_format_with_tools()
+    }
+
+    fn tool_limits(&self) -> roci_core::provider::ToolLimits {
+        self.inner.tool_limits()
+    }
+
+    fn supports_warm_up(&self) -> bool {
+        self.inner.supports_warm_up()
+    }
+
+    async fn warm_up(&self) -> Result<roci_core::provider::WarmUpReport, RociError> {
+        self.inner.warm_up().await
+    }
+
     async fn generate_text(
         &self,
         request: &roci_core::provider::ProviderRequest,
@@ -425,6 +445,19 @@ impl roci_core::provider::ProviderFactory for OverflowClassifyingFactory {
         self.inner.requires_credentials(provider_key)
     }
 
+    fn resolved_base_url(
+        &self,
+        config: &roci_core::config::RociConfig,
+        provider_key: &str,
+        model_id: &str,
+    ) -> Option<String> {
+        self.inner.resolved_base_url(config, provider_key, model_id)
+    }
+
+    fn feature_metadata(&self, provider_key: &str) -> roci_core::provider::ProviderFeatureMetadata {
+        self.inner.feature_metadata(provider_key)
+    }
+
     fn list_models<'a>(
         &'a self,
         config: &'a roci_core::config::RociConfig,
@@ -443,6 +476,19 @@ impl roci_core::provider::ProviderFactory for OverflowClassifyingFactory {
         let provider = self.inner.create(config, provider_key, model_id)?;
         Ok(OverflowClassifyingProvider::wrap(provider))
     }
+
+    fn create_with_options(
+        &self,
+        config: &roci_core::config::RociConfig,
+        provider_key: &str,
+        model_id: &str,
+        options: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Box<dyn roci_core::provider::ModelProvider>, RociError> {
+        let provider = self
+            .inner
+            .create_with_options(config, provider_key, model_id, options)?;
+        Ok(OverflowClassifyingProvider::wrap(provider))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -852,6 +898,7 @@ mod tests {
         assert!(AnthropicOverflowDetector::handles_provider(
             "anthropic-compatible"
         ));
+        assert!(AnthropicOverflowDetector::handles_provider("bedrock"));
         assert!(!AnthropicOverflowDetector::handles_provider("openai"));
     }
 
@@ -7,16 +7,43 @@ use serde::Deserialize;
 use tracing::debug;
 
 use crate::models::anthropic::AnthropicModel;
+use roci_core::config::{AnthropicCompatConfig, RequestCompression, SystemPromptPlacement};
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::*;
 
-use roci_core::provider::http::{anthropic_headers, shared_client};
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::provider::anomalies::{with_anomaly_count, ResponseAnomalies};
+use roci_core::provider::http::{
+    anthropic_headers, response_metadata, send_json, shared_client, sse_events, with_payload_sizes,
+    with_response_metadata, ByteCounter, ResponseHeaderRules,
+};
+use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse, ResponseToolCallIds};
 
-const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
 const API_VERSION: &str = "2023-06-01";
 
+/// Stop reasons the Messages API documents.
+const STOP_REASONS: &[&str] = &[
+    "end_turn",
+    "max_tokens",
+    "stop_sequence",
+    "tool_use",
+    "pause_turn",
+    "refusal",
+    "model_context_window_exceeded",
+];
+
+/// Content block types the parsers turn into content; others are dropped.
+const HANDLED_BLOCK_TYPES: &[&str] = &["text", "thinking", "redacted_thinking", "tool_use"];
+
+/// Stream delta types the stream parser handles.
+const HANDLED_DELTA_TYPES: &[&str] = &[
+    "text_delta",
+    "thinking_delta",
+    "signature_delta",
+    "input_json_delta",
+];
+
 /// Beta feature flags for interleaved thinking + fine-grained tool streaming.
 const BETA_FLAGS: &str = "interleaved-thinking-2025-05-14,fine-grained-tool-streaming-2025-05-14";
 
@@ -25,6 +52,9 @@ pub struct AnthropicProvider {
     api_key: String,
     base_url: String,
     capabilities: ModelCapabilities,
+    /// Set for `anthropic-compatible` endpoints; `None` is Anthropic itself.
+    compat: Option<AnthropicCompatConfig>,
+    request_compression: Option<RequestCompression>,
 }
 
 impl AnthropicProvider {
@@ -35,7 +65,33 @@ impl AnthropicProvider {
             model,
             api_key,
             capabilities,
+            compat: None,
+            request_compression: None,
+        }
+    }
+
+    /// Gzip request bodies at least `compression.min_bytes` long.
+    pub fn with_request_compression(mut self, compression: Option<RequestCompression>) -> Self {
+        self.request_compression = compression;
+        self
+    }
+
+    /// Shape requests for an endpoint that implements only part of the
+    /// Messages API, and name it in error messages.
+    #[cfg_attr(not(feature = "anthropic-compatible"), allow(dead_code))]
+    pub(crate) fn with_compat(mut self, compat: AnthropicCompatConfig) -> Self {
+        self.capabilities.supports_tools &= compat.supports_tools;
+        self.capabilities.supports_reasoning &= compat.supports_thinking;
+        if let Some(cap) = compat.max_output_tokens {
+            let cap = cap as usize;
+            self.capabilities.max_output_tokens = Some(
+                self.capabilities
+                    .max_output_tokens
+                    .map_or(cap, |max| max.min(cap)),
+            );
         }
+        self.compat = Some(compat);
+        self
     }
 
     /// Check if thinking mode is enabled in the request settings.
@@ -75,12 +131,48 @@ impl AnthropicProvider {
         Ok(headers)
     }
 
-    fn build_request_body(&self, request: &ProviderRequest, stream: bool) -> serde_json::Value {
-        let thinking = Self::thinking_enabled(request);
+    /// Compatible endpoints word their errors their own way, so name the
+    /// endpoint and keep its body verbatim.
+    fn status_error(&self, url: &str, status: u16, body: &str) -> RociError {
+        let error = anthropic_status_error(status, body);
+        if self.compat.is_none() {
+            return error;
+        }
+        let body = if body.trim().is_empty() {
+            "<empty body>"
+        } else {
+            body
+        };
+        match error {
+            RociError::Api {
+                status,
+                source,
+                details,
+                ..
+            } => RociError::Api {
+                status,
+                message: format!("{url} responded: {body}"),
+                source,
+                details,
+            },
+            RociError::Authentication(_) => {
+                RociError::Authentication(format!("{url} responded: {body}"))
+            }
+            other => other,
+        }
+    }
+
+    pub(crate) fn build_request_body(
+        &self,
+        request: &ProviderRequest,
+        stream: bool,
+    ) -> serde_json::Value {
+        let compat = self.compat.unwrap_or_default();
+        let thinking = compat.supports_thinking && Self::thinking_enabled(request);
         let mut system_parts = Vec::new();
         let mut messages = Vec::new();
 
-        for msg in &request.messages {
+        for msg in request.messages.iter() {
             match msg.role {
                 Role::System => {
                     system_parts.push(msg.text());
@@ -98,30 +190,24 @@ impl AnthropicProvider {
                     // Include thinking blocks when thinking is enabled
                     for part in &msg.content {
                         match part {
-                            ContentPart::Thinking(tc) => {
-                                if thinking {
-                                    content.push(serde_json::json!({
-                                        "type": "thinking",
-                                        "thinking": tc.thinking,
-                                        "signature": tc.signature,
-                                    }));
-                                }
+                            ContentPart::Thinking(tc) if thinking => {
+                                content.push(serde_json::json!({
+                                    "type": "thinking",
+                                    "thinking": tc.thinking,
+                                    "signature": tc.signature,
+                                }));
                             }
-                            ContentPart::RedactedThinking(rc) => {
-                                if thinking {
-                                    content.push(serde_json::json!({
-                                        "type": "redacted_thinking",
-                                        "data": rc.data,
-                                        "signature": rc.signature,
-                                    }));
-                                }
+                            ContentPart::RedactedThinking(rc) if thinking => {
+                                content.push(serde_json::json!({
+                                    "type": "redacted_thinking",
+                                    "data": rc.data,
+                                    "signature": rc.signature,
+                                }));
                             }
-                            ContentPart::Text { text } => {
-                                if !text.is_empty() {
-                                    content.push(serde_json::json!({"type": "text", "text": text}));
-                                }
+                            ContentPart::Text { text } if !text.is_empty() => {
+                                content.push(serde_json::json!({"type": "text", "text": text}));
                             }
-                            ContentPart::ToolCall(tc) => {
+                            ContentPart::ToolCall(tc) if compat.supports_tools => {
                                 content.push(serde_json::json!({
                                     "type": "tool_use",
                                     "id": tc.id,
@@ -129,6 +215,15 @@ impl AnthropicProvider {
                                     "input": tc.arguments,
                                 }));
                             }
+                            ContentPart::ToolCall(tc) => {
+                                content.push(serde_json::json!({
+                                    "type": "text",
+                                    "text": format!(
+                                        "[called tool {} ({}) with {}]",
+                                        tc.name, tc.id, tc.arguments
+                                    ),
+                                }));
+                            }
                             _ => {}
                         }
                     }
@@ -150,23 +245,45 @@ impl AnthropicProvider {
                 }
                 Role::Tool => {
                     for part in &msg.content {
-                        if let ContentPart::ToolResult(tr) = part {
+                        let ContentPart::ToolResult(tr) = part else {
+                            continue;
+                        };
+                        if !compat.supports_tools {
+                            let label = if tr.is_error { "error" } else { "result" };
                             messages.push(serde_json::json!({
                                 "role": "user",
-                                "content": [{
-                                    "type": "tool_result",
-                                    "tool_use_id": tr.tool_call_id,
-                                    "content": tr.result.to_string(),
-                                    "is_error": tr.is_error,
-                                }],
+                                "content": format!(
+                                    "[tool {label} for {}: {}]",
+                                    tr.tool_call_id, tr.result
+                                ),
                             }));
+                            continue;
                         }
+                        messages.push(serde_json::json!({
+                            "role": "user",
+                            "content": [{
+                                "type": "tool_result",
+                                "tool_use_id": tr.tool_call_id,
+                                "content": tr.result.to_string(),
+                                "is_error": tr.is_error,
+                            }],
+                        }));
                     }
                 }
             }
         }
 
-        let max_tokens = if thinking {
+        // The API rejects a prefilled final assistant turn ending in whitespace.
+        if request.settings.assistant_prefix == Some(true) {
+            if let Some(last) = messages
+                .last_mut()
+                .filter(|message| message["role"] == "assistant")
+            {
+                trim_prefill_end(last);
+            }
+        }
+
+        let mut max_tokens = if thinking {
             // When thinking is enabled, ensure sufficient output budget.
             let budget = request
                 .settings
@@ -183,6 +300,20 @@ impl AnthropicProvider {
         } else {
             request.settings.max_tokens.unwrap_or(4096)
         };
+        if let Some(cap) = compat.max_output_tokens {
+            max_tokens = max_tokens.min(cap);
+        }
+
+        if !system_parts.is_empty() && compat.system_prompt == SystemPromptPlacement::FirstMessage {
+            messages.insert(
+                0,
+                serde_json::json!({
+                    "role": "system",
+                    "content": system_parts.join("\n"),
+                }),
+            );
+            system_parts.clear();
+        }
 
         let mut body = serde_json::json!({
             "model": self.model.as_str(),
@@ -214,9 +345,11 @@ impl AnthropicProvider {
 
         // Extended thinking
         if let Some(ref anthropic_opts) = request.settings.anthropic {
-            if let Some(ref thinking_mode) = anthropic_opts.thinking {
+            if let Some(thinking_mode) = anthropic_opts.thinking.as_ref().filter(|_| thinking) {
                 match thinking_mode {
                     ThinkingMode::Enabled { budget_tokens } => {
+                        // The budget must stay below a capped max_tokens.
+                        let budget_tokens = (*budget_tokens).min(max_tokens.saturating_sub(1));
                         obj.insert(
                             "thinking".into(),
                             serde_json::json!({
@@ -236,7 +369,12 @@ impl AnthropicProvider {
         }
 
         // Tool choice
-        if let Some(ref tool_choice) = request.settings.tool_choice {
+        let tool_choice = request
+            .settings
+            .tool_choice
+            .as_ref()
+            .filter(|_| compat.supports_tools && compat.supports_tool_choice);
+        if let Some(tool_choice) = tool_choice {
             match tool_choice {
                 ToolChoice::Auto => {
                     obj.insert("tool_choice".into(), serde_json::json!({"type": "auto"}));
@@ -256,7 +394,7 @@ impl AnthropicProvider {
             }
         }
 
-        if let Some(ref tools) = request.tools {
+        if let Some(tools) = request.tools.as_ref().filter(|_| compat.supports_tools) {
             if !tools.is_empty() {
                 let tool_defs: Vec<serde_json::Value> = tools
                     .iter()
@@ -290,6 +428,10 @@ impl ModelProvider for AnthropicProvider {
         &self.capabilities
     }
 
+    fn supports_assistant_prefix(&self) -> bool {
+        true
+    }
+
     async fn generate_text(
         &self,
         request: &ProviderRequest,
@@ -299,92 +441,34 @@ impl ModelProvider for AnthropicProvider {
 
         debug!(model = self.model.as_str(), "Anthropic generate_text");
 
-        let resp = shared_client()
-            .post(&url)
-            .headers(self.build_headers(request)?)
-            .json(&body)
-            .send()
-            .await?;
+        let (resp, mut payload) = send_json(
+            shared_client()
+                .post(&url)
+                .headers(self.build_headers(request)?),
+            &body,
+            self.request_compression,
+        )
+        .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
             let body_text = resp.text().await.unwrap_or_default();
-            return Err(roci_core::provider::http::status_to_error(
-                status, &body_text,
-            ));
-        }
-
-        let data: AnthropicResponse = resp.json().await?;
-
-        let mut text = String::new();
-        let mut tool_calls = Vec::new();
-        let mut thinking_blocks = Vec::new();
-
-        for block in &data.content {
-            match block.r#type.as_str() {
-                "text" => {
-                    if let Some(ref t) = block.text {
-                        text.push_str(t);
-                    }
-                }
-                "thinking" => {
-                    if let (Some(ref thinking), Some(ref signature)) =
-                        (&block.thinking, &block.signature)
-                    {
-                        thinking_blocks.push(ContentPart::Thinking(ThinkingContent {
-                            thinking: thinking.clone(),
-                            signature: signature.clone(),
-                        }));
-                    }
-                }
-                "redacted_thinking" => {
-                    if let Some(ref signature) = block.signature {
-                        thinking_blocks.push(ContentPart::RedactedThinking(
-                            RedactedThinkingContent {
-                                data: block.data.clone().unwrap_or_default(),
-                                signature: signature.clone(),
-                            },
-                        ));
-                    }
-                }
-                "tool_use" => {
-                    if let (Some(ref id), Some(ref name), Some(ref input)) =
-                        (&block.id, &block.name, &block.input)
-                    {
-                        tool_calls.push(message::AgentToolCall {
-                            id: id.clone(),
-                            name: name.clone(),
-                            arguments: input.clone(),
-                            called_as: None,
-                            recipient: None,
-                        });
-                    }
-                }
-                _ => {}
-            }
+            return Err(self.status_error(&url, status, &body_text));
         }
 
-        let finish_reason = match data.stop_reason.as_deref() {
-            Some("end_turn") => Some(FinishReason::Stop),
-            Some("max_tokens") => Some(FinishReason::Length),
-            Some("tool_use") => Some(FinishReason::ToolCalls),
-            _ => None,
-        };
-
-        Ok(ProviderResponse {
-            text,
-            usage: Usage {
-                input_tokens: data.usage.input_tokens,
-                output_tokens: data.usage.output_tokens,
-                total_tokens: data.usage.input_tokens + data.usage.output_tokens,
-                cache_read_tokens: data.usage.cache_read_input_tokens,
-                cache_creation_tokens: data.usage.cache_creation_input_tokens,
-                ..Default::default()
-            },
-            tool_calls,
-            finish_reason,
-            thinking: thinking_blocks,
-        })
+        let mut response_metadata =
+            response_metadata(resp.headers(), &ResponseHeaderRules::ANTHROPIC);
+        let raw_body = resp.bytes().await?;
+        payload.response_bytes = Some(raw_body.len() as u64);
+        response_metadata.payload = Some(payload);
+        let raw: serde_json::Value = serde_json::from_slice(&raw_body)?;
+        let anomalies = ResponseAnomalies::from_env("anthropic");
+        validate_anthropic_response(&raw, &anomalies);
+        let data: AnthropicResponse = serde_json::from_value(raw)?;
+
+        let mut response = parse_anthropic_response(request, data);
+        response.response_metadata = Some(anomalies.annotate(response_metadata));
+        Ok(response)
     }
 
     async fn stream_text(
@@ -396,192 +480,498 @@ impl ModelProvider for AnthropicProvider {
 
         debug!(model = self.model.as_str(), "Anthropic stream_text");
 
-        let resp = shared_client()
-            .post(&url)
-            .headers(self.build_headers(request)?)
-            .json(&body)
-            .send()
-            .await?;
+        let (resp, payload) = send_json(
+            shared_client()
+                .post(&url)
+                .headers(self.build_headers(request)?),
+            &body,
+            self.request_compression,
+        )
+        .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
             let body_text = resp.text().await.unwrap_or_default();
-            return Err(roci_core::provider::http::status_to_error(
-                status, &body_text,
-            ));
+            return Err(self.status_error(&url, status, &body_text));
         }
 
-        let byte_stream = resp.bytes_stream();
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::ANTHROPIC);
+        let received = ByteCounter::default();
+        let sse = sse_events(received.count(resp.bytes_stream()));
 
+        let anomalies = ResponseAnomalies::from_env("anthropic");
+        let mut events =
+            AnthropicStreamEvents::new(request.begin_tool_call_ids(), anomalies.clone());
         let stream = async_stream::stream! {
-            let mut buffer = String::new();
-            let mut current_block_type: Option<String> = None;
-            let mut current_tool_id: Option<String> = None;
-            let mut current_tool_name: Option<String> = None;
-            let mut current_tool_input = String::new();
-            let mut saw_tool_use = false;
-            futures::pin_mut!(byte_stream);
-
-            while let Some(chunk_result) = byte_stream.next().await {
-                let chunk = match chunk_result {
-                    Ok(c) => c,
+            futures::pin_mut!(sse);
+
+            while let Some(sse_event) = sse.next().await {
+                let sse_event = match sse_event {
+                    Ok(sse_event) => sse_event,
                     Err(e) => {
                         yield Err(RociError::Network(e));
                         break;
                     }
                 };
 
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
+                match serde_json::from_str::<serde_json::Value>(&sse_event.data) {
+                    Ok(event) => {
+                        for item in events.handle(&event) {
+                            let failed = item.is_err();
+                            yield item;
+                            if failed {
+                                return;
+                            }
+                        }
+                    }
+                    Err(_) => events.anomalies.malformed("event", &sse_event.data),
+                }
+            }
+        };
 
-                while let Some(line_end) = buffer.find('\n') {
-                    let line = buffer[..line_end].trim().to_string();
-                    buffer = buffer[line_end + 1..].to_string();
+        Ok(with_anomaly_count(
+            with_payload_sizes(
+                with_response_metadata(Box::pin(stream), response_metadata),
+                payload,
+                received,
+            ),
+            anomalies,
+        ))
+    }
+}
 
-                    if line.is_empty() || line.starts_with(':') {
-                        continue;
-                    }
+/// Convert a non-streaming Messages response body into a [`ProviderResponse`].
+pub(crate) fn parse_anthropic_response(
+    request: &ProviderRequest,
+    data: AnthropicResponse,
+) -> ProviderResponse {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut call_ids = request.begin_tool_call_ids();
+    let mut thinking_blocks = Vec::new();
+
+    for block in &data.content {
+        match block.r#type.as_str() {
+            "text" => {
+                if let Some(ref t) = block.text {
+                    text.push_str(t);
+                }
+            }
+            "thinking" => {
+                if let (Some(ref thinking), Some(ref signature)) =
+                    (&block.thinking, &block.signature)
+                {
+                    thinking_blocks.push(ContentPart::Thinking(ThinkingContent {
+                        thinking: thinking.clone(),
+                        signature: signature.clone(),
+                    }));
+                }
+            }
+            "redacted_thinking" => {
+                if let Some(ref signature) = block.signature {
+                    thinking_blocks.push(ContentPart::RedactedThinking(RedactedThinkingContent {
+                        data: block.data.clone().unwrap_or_default(),
+                        signature: signature.clone(),
+                    }));
+                }
+            }
+            "tool_use" => {
+                if let (Some(ref id), Some(ref name), Some(ref input)) =
+                    (&block.id, &block.name, &block.input)
+                {
+                    tool_calls.push(message::AgentToolCall {
+                        id: call_ids.assign(Some(id.as_str()), name),
+                        name: name.clone(),
+                        arguments: input.clone(),
+                        called_as: None,
+                        recipient: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let finish_reason = data.stop_reason.as_deref().map(parse_stop_reason);
+
+    let usage = data.usage.unwrap_or_default();
+    let input_tokens = usage.input_tokens.unwrap_or_default();
+    let output_tokens = usage.output_tokens.unwrap_or_default();
+    ProviderResponse {
+        text,
+        usage: Usage {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            cache_creation_tokens: usage.cache_creation_input_tokens,
+            ..Default::default()
+        },
+        tool_calls,
+        finish_reason,
+        thinking: thinking_blocks,
+        metadata: Default::default(),
+        refusal: None,
+        images: Vec::new(),
+        response_metadata: None,
+    }
+}
 
-                    if let Some(data) = roci_core::provider::http::parse_sse_data(&line) {
-                        if let Ok(event) = serde_json::from_str::<serde_json::Value>(data) {
-                            let event_type_str = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
-                            match event_type_str {
-                                "content_block_start" => {
-                                    if let Some(block) = event.get("content_block") {
-                                        let btype = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
-                                        current_block_type = Some(btype.to_string());
-                                        if btype == "tool_use" {
-                                            current_tool_id = block.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                            current_tool_name = block.get("name").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                            current_tool_input.clear();
-                                        }
-                                    }
-                                }
-                                "content_block_delta" => {
-                                    if let Some(delta) = event.get("delta") {
-                                        let delta_type = delta.get("type").and_then(|t| t.as_str()).unwrap_or("");
-                                        match delta_type {
-                                            "text_delta" => {
-                                                if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
-                                                    yield Ok(TextStreamDelta {
-                                                        text: text.to_string(),
-                                                        event_type: StreamEventType::TextDelta,
-                                                        tool_call: None,
-                                                        finish_reason: None,
-                                                        usage: None,
-                                                        reasoning: None,
-                                                        reasoning_signature: None,
-                                                        reasoning_type: None,
-                                                    });
-                                                }
-                                            }
-                                            "thinking_delta" => {
-                                                if let Some(thinking) = delta.get("thinking").and_then(|t| t.as_str()) {
-                                                    yield Ok(TextStreamDelta {
-                                                        text: String::new(),
-                                                        event_type: StreamEventType::Reasoning,
-                                                        tool_call: None,
-                                                        finish_reason: None,
-                                                        usage: None,
-                                                        reasoning: Some(thinking.to_string()),
-                                                        reasoning_signature: None,
-                                                        reasoning_type: current_block_type.clone(),
-                                                    });
-                                                }
-                                            }
-                                            "signature_delta" => {
-                                                if let Some(sig) = delta.get("signature").and_then(|t| t.as_str()) {
-                                                    yield Ok(TextStreamDelta {
-                                                        text: String::new(),
-                                                        event_type: StreamEventType::Reasoning,
-                                                        tool_call: None,
-                                                        finish_reason: None,
-                                                        usage: None,
-                                                        reasoning: None,
-                                                        reasoning_signature: Some(sig.to_string()),
-                                                        reasoning_type: current_block_type.clone(),
-                                                    });
-                                                }
-                                            }
-                                            "input_json_delta" => {
-                                                if let Some(json) = delta.get("partial_json").and_then(|t| t.as_str()) {
-                                                    current_tool_input.push_str(json);
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                                "content_block_stop" => {
-                                    if current_block_type.as_deref() == Some("tool_use") {
-                                        if let (Some(id), Some(name)) = (current_tool_id.take(), current_tool_name.take()) {
-                                            let args = serde_json::from_str(&current_tool_input)
-                                                .unwrap_or(serde_json::Value::String(current_tool_input.clone()));
-                                            yield Ok(TextStreamDelta {
-                                                text: String::new(),
-                                                event_type: StreamEventType::ToolCallDelta,
-                                                tool_call: Some(AgentToolCall { id, name, arguments: args, called_as: None, recipient: None }),
-                                                finish_reason: None,
-                                                usage: None,
-                                                reasoning: None,
-                                                reasoning_signature: None,
-                                                reasoning_type: None,
-                                            });
-                                            saw_tool_use = true;
-                                            current_tool_input.clear();
-                                        }
-                                    }
-                                    current_block_type = None;
-                                }
-                                "message_delta" => {
-                                    let stop = event.get("delta")
-                                        .and_then(|d| d.get("stop_reason"))
-                                        .and_then(|s| s.as_str());
-                                    let finish = match stop {
-                                        Some("end_turn") => Some(FinishReason::Stop),
-                                        Some("max_tokens") => Some(FinishReason::Length),
-                                        Some("tool_use") => Some(FinishReason::ToolCalls),
-                                        _ => None,
-                                    };
-                                    if finish.is_some() {
-                                        let usage = event.get("usage").and_then(|u| {
-                                            Some(Usage {
-                                                output_tokens: u.get("output_tokens")?.as_u64()? as u32,
-                                                ..Default::default()
-                                            })
-                                        });
-                                        yield Ok(TextStreamDelta {
-                                            text: String::new(),
-                                            event_type: StreamEventType::Done,
-                                            tool_call: None,
-                                            finish_reason: if saw_tool_use { Some(FinishReason::ToolCalls) } else { finish },
-                                            usage,
-                                            reasoning: None,
-                                            reasoning_signature: None,
-                                            reasoning_type: None,
-                                        });
-                                    }
-                                }
-                                "message_stop" => {
-                                    yield Ok(TextStreamDelta {
-                                        text: String::new(),
-                                        event_type: StreamEventType::Done,
-                                        tool_call: None,
-                                        finish_reason: if saw_tool_use { Some(FinishReason::ToolCalls) } else { Some(FinishReason::Stop) },
-                                        usage: None,
-                                        reasoning: None,
-                                        reasoning_signature: None,
-                                        reasoning_type: None,
-                                    });
-                                }
-                                _ => {}
+/// Map a Messages API `stop_reason`, keeping values with no named variant.
+pub(crate) fn parse_stop_reason(reason: &str) -> FinishReason {
+    match reason {
+        "end_turn" | "stop_sequence" => FinishReason::Stop,
+        "max_tokens" | "model_context_window_exceeded" => FinishReason::Length,
+        "tool_use" => FinishReason::ToolCalls,
+        "refusal" => FinishReason::Refusal,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// Report where a non-streaming Messages response body departs from the
+/// documented shape. Parsing tolerates every one of these.
+pub(crate) fn validate_anthropic_response(raw: &serde_json::Value, anomalies: &ResponseAnomalies) {
+    if !anomalies.is_enabled() {
+        return;
+    }
+    match raw.get("usage") {
+        Some(usage) if usage.is_object() => {
+            for field in ["input_tokens", "output_tokens"] {
+                if usage.get(field).and_then(|v| v.as_u64()).is_none() {
+                    anomalies.missing_field(&format!("usage.{field}"), raw);
+                }
+            }
+        }
+        _ => anomalies.missing_field("usage", raw),
+    }
+    match raw.get("stop_reason").and_then(|v| v.as_str()) {
+        Some(reason) if !STOP_REASONS.contains(&reason) => {
+            anomalies.unknown_finish_reason("stop_reason", reason, raw);
+        }
+        Some(_) => {}
+        None => anomalies.missing_field("stop_reason", raw),
+    }
+    match raw.get("content").and_then(|v| v.as_array()) {
+        Some(blocks) => {
+            for (index, block) in blocks.iter().enumerate() {
+                let block_type = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                if !HANDLED_BLOCK_TYPES.contains(&block_type) {
+                    anomalies.unknown_content_block(
+                        &format!("content.{index}.type"),
+                        block_type,
+                        block,
+                    );
+                }
+            }
+        }
+        None => anomalies.missing_field("content", raw),
+    }
+}
+
+/// Translates decoded Messages stream events into [`TextStreamDelta`]s.
+///
+/// Shared by the SSE transport and transports that wrap the same events in
+/// another framing (Bedrock).
+pub(crate) struct AnthropicStreamEvents {
+    call_ids: ResponseToolCallIds,
+    pub(crate) anomalies: ResponseAnomalies,
+    current_block_type: Option<String>,
+    current_tool_id: Option<String>,
+    current_tool_name: Option<String>,
+    current_tool_input: String,
+    saw_tool_use: bool,
+    /// Mapped `stop_reason` from `message_delta`, repeated on `message_stop`.
+    finish_reason: Option<FinishReason>,
+}
+
+impl AnthropicStreamEvents {
+    pub(crate) fn new(call_ids: ResponseToolCallIds, anomalies: ResponseAnomalies) -> Self {
+        Self {
+            call_ids,
+            anomalies,
+            current_block_type: None,
+            current_tool_id: None,
+            current_tool_name: None,
+            current_tool_input: String::new(),
+            saw_tool_use: false,
+            finish_reason: None,
+        }
+    }
+
+    /// Deltas for one event. An `Err` item ends the stream.
+    pub(crate) fn handle(
+        &mut self,
+        event: &serde_json::Value,
+    ) -> Vec<Result<TextStreamDelta, RociError>> {
+        let event_type_str = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        match event_type_str {
+            "content_block_start" => {
+                if let Some(block) = event.get("content_block") {
+                    let btype = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                    if !HANDLED_BLOCK_TYPES.contains(&btype) {
+                        self.anomalies
+                            .unknown_content_block("content_block.type", btype, event);
+                    }
+                    self.current_block_type = Some(btype.to_string());
+                    if btype == "tool_use" {
+                        self.current_tool_name = block
+                            .get("name")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string());
+                        self.current_tool_id = self.current_tool_name.as_deref().map(|name| {
+                            self.call_ids
+                                .assign(block.get("id").and_then(|v| v.as_str()), name)
+                        });
+                        self.current_tool_input.clear();
+                    }
+                }
+                Vec::new()
+            }
+            "content_block_delta" => {
+                let Some(delta) = event.get("delta") else {
+                    return Vec::new();
+                };
+                let delta_type = delta.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                match delta_type {
+                    "text_delta" => delta
+                        .get("text")
+                        .and_then(|t| t.as_str())
+                        .map(|text| {
+                            Ok(TextStreamDelta {
+                                text: text.to_string(),
+                                event_type: StreamEventType::TextDelta,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: None,
+                                reasoning_signature: None,
+                                reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
+                            })
+                        })
+                        .into_iter()
+                        .collect(),
+                    "thinking_delta" => delta
+                        .get("thinking")
+                        .and_then(|t| t.as_str())
+                        .map(|thinking| {
+                            Ok(TextStreamDelta {
+                                text: String::new(),
+                                event_type: StreamEventType::Reasoning,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: Some(thinking.to_string()),
+                                reasoning_signature: None,
+                                reasoning_type: self.current_block_type.clone(),
+                                image: None,
+                                response_metadata: None,
+                            })
+                        })
+                        .into_iter()
+                        .collect(),
+                    "signature_delta" => delta
+                        .get("signature")
+                        .and_then(|t| t.as_str())
+                        .map(|sig| {
+                            Ok(TextStreamDelta {
+                                text: String::new(),
+                                event_type: StreamEventType::Reasoning,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: None,
+                                reasoning_signature: Some(sig.to_string()),
+                                reasoning_type: self.current_block_type.clone(),
+                                image: None,
+                                response_metadata: None,
+                            })
+                        })
+                        .into_iter()
+                        .collect(),
+                    "input_json_delta" => {
+                        let Some(json) = delta.get("partial_json").and_then(|t| t.as_str()) else {
+                            return Vec::new();
+                        };
+                        self.current_tool_input.push_str(json);
+                        match (
+                            self.current_tool_id.as_deref(),
+                            self.current_tool_name.as_deref(),
+                        ) {
+                            (Some(id), Some(name)) if !json.is_empty() => {
+                                vec![Ok(TextStreamDelta::tool_call_arguments(id, name, json))]
                             }
+                            _ => Vec::new(),
                         }
                     }
+                    _ => {
+                        if !HANDLED_DELTA_TYPES.contains(&delta_type) {
+                            self.anomalies
+                                .unknown_content_block("delta.type", delta_type, event);
+                        }
+                        Vec::new()
+                    }
                 }
             }
-        };
+            "content_block_stop" => {
+                let mut out = Vec::new();
+                if self.current_block_type.as_deref() == Some("tool_use") {
+                    if let (Some(id), Some(name)) =
+                        (self.current_tool_id.take(), self.current_tool_name.take())
+                    {
+                        let args = serde_json::from_str(&self.current_tool_input)
+                            .unwrap_or(serde_json::Value::String(self.current_tool_input.clone()));
+                        out.push(Ok(TextStreamDelta {
+                            text: String::new(),
+                            event_type: StreamEventType::ToolCallDelta,
+                            tool_call: Some(AgentToolCall {
+                                id,
+                                name,
+                                arguments: args,
+                                called_as: None,
+                                recipient: None,
+                            }),
+                            finish_reason: None,
+                            usage: None,
+                            reasoning: None,
+                            reasoning_signature: None,
+                            reasoning_type: None,
+                            image: None,
+                            response_metadata: None,
+                        }));
+                        self.saw_tool_use = true;
+                        self.current_tool_input.clear();
+                    }
+                }
+                self.current_block_type = None;
+                out
+            }
+            "message_delta" => {
+                let stop = event
+                    .get("delta")
+                    .and_then(|d| d.get("stop_reason"))
+                    .and_then(|s| s.as_str());
+                match stop {
+                    Some(reason) if !STOP_REASONS.contains(&reason) => {
+                        self.anomalies
+                            .unknown_finish_reason("delta.stop_reason", reason, event);
+                    }
+                    _ => {}
+                }
+                match event.get("usage") {
+                    Some(usage) if usage.get("output_tokens").is_some_and(|v| v.is_u64()) => {}
+                    Some(_) => self.anomalies.missing_field("usage.output_tokens", event),
+                    None => self.anomalies.missing_field("usage", event),
+                }
+                let Some(finish) = stop.map(parse_stop_reason) else {
+                    return Vec::new();
+                };
+                self.finish_reason = Some(finish.clone());
+                let usage = event.get("usage").and_then(|u| {
+                    Some(Usage {
+                        output_tokens: u.get("output_tokens")?.as_u64()? as u32,
+                        ..Default::default()
+                    })
+                });
+                vec![Ok(TextStreamDelta {
+                    text: String::new(),
+                    event_type: StreamEventType::Done,
+                    tool_call: None,
+                    finish_reason: if self.saw_tool_use {
+                        Some(FinishReason::ToolCalls)
+                    } else {
+                        Some(finish)
+                    },
+                    usage,
+                    reasoning: None,
+                    reasoning_signature: None,
+                    reasoning_type: None,
+                    image: None,
+                    response_metadata: None,
+                })]
+            }
+            // Anthropic reports overloads and other failures mid-stream with
+            // a 200 status; surface them typed so the runner can retry.
+            "error" => vec![Err(anthropic_stream_error(event))],
+            "message_stop" => vec![Ok(TextStreamDelta {
+                text: String::new(),
+                event_type: StreamEventType::Done,
+                tool_call: None,
+                finish_reason: if self.saw_tool_use {
+                    Some(FinishReason::ToolCalls)
+                } else {
+                    Some(self.finish_reason.clone().unwrap_or(FinishReason::Stop))
+                },
+                usage: None,
+                reasoning: None,
+                reasoning_signature: None,
+                reasoning_type: None,
+                image: None,
+                response_metadata: None,
+            })],
+            _ => Vec::new(),
+        }
+    }
+}
 
-        Ok(Box::pin(stream))
+/// Map a non-200 Anthropic response to a typed error.
+///
+/// HTTP 529 and `overloaded_error` bodies become overloaded errors, which the
+/// runner retries with a synthesized backoff (Anthropic sends no retry-after).
+fn trim_prefill_end(message: &mut serde_json::Value) {
+    let content = &mut message["content"];
+    if let Some(text) = content.as_str() {
+        *content = text.trim_end().into();
+    } else if let Some(block) = content
+        .as_array_mut()
+        .and_then(|blocks| blocks.last_mut())
+        .filter(|block| block["type"] == "text")
+    {
+        let trimmed = block["text"]
+            .as_str()
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        block["text"] = trimmed.into();
+    }
+}
+
+pub(crate) fn anthropic_status_error(status: u16, body: &str) -> RociError {
+    let error_type = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("error")
+                .and_then(|error| error.get("type"))
+                .and_then(|kind| kind.as_str())
+                .map(str::to_string)
+        });
+    if status == 529 || error_type.as_deref() == Some("overloaded_error") {
+        return RociError::overloaded(status, body);
+    }
+    roci_core::provider::http::status_to_error(status, body)
+}
+
+/// Map an in-stream `{"type":"error"}` SSE event to a typed error.
+fn anthropic_stream_error(event: &serde_json::Value) -> RociError {
+    let error = event.get("error");
+    let error_type = error
+        .and_then(|error| error.get("type"))
+        .and_then(|kind| kind.as_str())
+        .unwrap_or_default();
+    let message = error
+        .and_then(|error| error.get("message"))
+        .and_then(|message| message.as_str())
+        .unwrap_or("Anthropic stream error")
+        .to_string();
+    match error_type {
+        "overloaded_error" => RociError::overloaded(529, message),
+        "rate_limit_error" => RociError::RateLimited {
+            retry_after_ms: None,
+        },
+        "api_error" => RociError::api(500, message),
+        _ => RociError::Stream(format!("{error_type}: {message}")),
     }
 }
 
@@ -617,10 +1007,13 @@ fn build_anthropic_content(parts: &[ContentPart]) -> serde_json::Value {
 // Internal Anthropic response types
 
 #[derive(Deserialize)]
-struct AnthropicResponse {
+pub(crate) struct AnthropicResponse {
     content: Vec<AnthropicContentBlock>,
     stop_reason: Option<String>,
-    usage: AnthropicUsage,
+    /// Documented as always present; a missing or reshaped object reads as
+    /// zero usage and is reported under strict parsing.
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
 }
 
 #[derive(Deserialize)]
@@ -645,10 +1038,12 @@ struct AnthropicContentBlock {
     signature: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct AnthropicUsage {
-    input_tokens: u32,
-    output_tokens: u32,
+    #[serde(default)]
+    input_tokens: Option<u32>,
+    #[serde(default)]
+    output_tokens: Option<u32>,
     #[serde(default)]
     cache_read_input_tokens: Option<u32>,
     #[serde(default)]
@@ -659,6 +1054,8 @@ struct AnthropicUsage {
 mod tests {
     use super::*;
     use roci_core::provider::ToolDefinition;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn settings() -> GenerationSettings {
         GenerationSettings::default()
@@ -669,7 +1066,7 @@ mod tests {
         headers: reqwest::header::HeaderMap,
     ) -> ProviderRequest {
         ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -679,6 +1076,23 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    #[test]
+    fn stop_reasons_map_known_values_and_keep_the_rest() {
+        let cases = [
+            ("end_turn", FinishReason::Stop),
+            ("stop_sequence", FinishReason::Stop),
+            ("max_tokens", FinishReason::Length),
+            ("model_context_window_exceeded", FinishReason::Length),
+            ("tool_use", FinishReason::ToolCalls),
+            ("refusal", FinishReason::Refusal),
+            ("pause_turn", FinishReason::Other("pause_turn".to_string())),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_stop_reason(raw), expected, "{raw}");
         }
     }
 
@@ -687,7 +1101,7 @@ mod tests {
         let provider =
             AnthropicProvider::new(AnthropicModel::ClaudeSonnet4, "test-key".to_string(), None);
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 anthropic: Some(AnthropicOptions {
                     thinking: Some(ThinkingMode::Enabled {
@@ -705,6 +1119,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         assert_eq!(body["thinking"]["type"], "enabled");
@@ -718,7 +1133,7 @@ mod tests {
         let provider =
             AnthropicProvider::new(AnthropicModel::ClaudeSonnet4, "test-key".to_string(), None);
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 anthropic: Some(AnthropicOptions {
                     thinking: Some(ThinkingMode::Disabled),
@@ -735,6 +1150,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         assert!(body.get("thinking").is_none());
@@ -764,7 +1180,7 @@ mod tests {
             },
         ];
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: GenerationSettings {
                 anthropic: Some(AnthropicOptions {
                     thinking: Some(ThinkingMode::Enabled {
@@ -782,6 +1198,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         let assistant_content = &body["messages"][1]["content"];
@@ -814,7 +1231,7 @@ mod tests {
             },
         ];
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: settings(),
             tools: None,
             response_format: None,
@@ -824,6 +1241,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         let assistant_content = &body["messages"][1]["content"];
@@ -832,6 +1250,20 @@ mod tests {
         assert_eq!(assistant_content[0]["type"], "text");
     }
 
+    #[test]
+    fn request_body_keeps_trailing_assistant_prefill_without_trailing_whitespace() {
+        let provider =
+            AnthropicProvider::new(AnthropicModel::ClaudeSonnet4, "test-key".to_string(), None);
+        let mut request = request_with_headers(None, reqwest::header::HeaderMap::new());
+        std::sync::Arc::make_mut(&mut request.messages)
+            .push(ModelMessage::assistant("{\"answer\": "));
+        request.settings.assistant_prefix = Some(true);
+        let body = provider.build_request_body(&request, false);
+        assert_eq!(body["messages"][1]["role"], "assistant");
+        assert_eq!(body["messages"][1]["content"][0]["text"], "{\"answer\":");
+        assert!(provider.supports_assistant_prefix());
+    }
+
     #[test]
     fn beta_headers_always_sent() {
         let provider =
@@ -916,11 +1348,12 @@ mod tests {
             name: "get_weather".to_string(),
             description: "Get weather".to_string(),
             parameters: serde_json::json!({"type": "object", "properties": {}}),
+            strict: false,
         }];
 
         // auto
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 tool_choice: Some(ToolChoice::Auto),
                 ..Default::default()
@@ -933,13 +1366,14 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         assert_eq!(body["tool_choice"]["type"], "auto");
 
         // required → "any" for Anthropic
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 tool_choice: Some(ToolChoice::Required),
                 ..Default::default()
@@ -952,13 +1386,14 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         assert_eq!(body["tool_choice"]["type"], "any");
 
         // specific function
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 tool_choice: Some(ToolChoice::Function("get_weather".to_string())),
                 ..Default::default()
@@ -971,6 +1406,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         assert_eq!(body["tool_choice"]["type"], "tool");
@@ -997,7 +1433,7 @@ mod tests {
             metadata: None,
         }];
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1007,6 +1443,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
@@ -1041,7 +1478,8 @@ mod tests {
                 name: None,
                 timestamp: None,
                 metadata: None,
-            }],
+            }]
+            .into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1051,6 +1489,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
@@ -1060,4 +1499,325 @@ mod tests {
         assert_eq!(content, marker);
         assert!(!content.contains("/tmp/"));
     }
+
+    const OVERLOADED_BODY: &str =
+        r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+
+    async fn mock_messages_endpoint(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/messages"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn provider_for(server: &MockServer) -> AnthropicProvider {
+        AnthropicProvider::new(
+            AnthropicModel::ClaudeSonnet4,
+            "test-key".to_string(),
+            Some(server.uri()),
+        )
+    }
+
+    #[tokio::test]
+    async fn stream_maps_http_529_to_retryable_overloaded_error() {
+        let server = mock_messages_endpoint(
+            ResponseTemplate::new(529).set_body_raw(OVERLOADED_BODY, "application/json"),
+        )
+        .await;
+
+        let request = request_with_headers(None, reqwest::header::HeaderMap::new());
+        let err = match provider_for(&server).stream_text(&request).await {
+            Ok(_) => panic!("expected overloaded error"),
+            Err(err) => err,
+        };
+
+        assert!(err.is_overloaded());
+        assert!(err.is_retryable());
+        assert_eq!(err.category(), roci_core::error::ErrorCategory::RateLimit);
+    }
+
+    #[tokio::test]
+    async fn stream_maps_in_stream_overloaded_event_after_partial_text() {
+        let sse = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: error\n",
+            "data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"ignored\"}}\n\n",
+        );
+        let server = mock_messages_endpoint(
+            ResponseTemplate::new(200).set_body_raw(sse, "text/event-stream"),
+        )
+        .await;
+
+        let request = request_with_headers(None, reqwest::header::HeaderMap::new());
+        let stream = match provider_for(&server).stream_text(&request).await {
+            Ok(stream) => stream,
+            Err(err) => panic!("stream should open: {err}"),
+        };
+        let items: Vec<_> = stream.collect().await;
+
+        assert_eq!(items.len(), 2, "stream must end at the error event");
+        let first = items[0].as_ref().expect("text delta before error");
+        assert_eq!(first.text, "Hello");
+        let err = items[1].as_ref().expect_err("overloaded error event");
+        assert!(err.is_overloaded());
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn stream_done_carries_anthropic_response_metadata() {
+        let sse = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":1}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        let server = mock_messages_endpoint(
+            ResponseTemplate::new(200)
+                .insert_header("request-id", "req_abc")
+                .insert_header("anthropic-ratelimit-tokens-remaining", "7000")
+                .set_body_raw(sse, "text/event-stream"),
+        )
+        .await;
+
+        let request = request_with_headers(None, reqwest::header::HeaderMap::new());
+        let stream = match provider_for(&server).stream_text(&request).await {
+            Ok(stream) => stream,
+            Err(err) => panic!("stream should open: {err}"),
+        };
+        let items: Vec<_> = stream.collect().await;
+
+        let done = items
+            .iter()
+            .filter_map(|item| item.as_ref().ok())
+            .find(|delta| delta.event_type == StreamEventType::Done)
+            .expect("done delta");
+        let metadata = done.response_metadata.as_ref().expect("response metadata");
+        assert_eq!(metadata.provider_request_id.as_deref(), Some("req_abc"));
+        assert_eq!(
+            metadata
+                .rate_limit
+                .as_ref()
+                .and_then(|limit| limit.tokens_remaining),
+            Some(7000)
+        );
+        assert_eq!(
+            metadata
+                .raw_headers_subset
+                .get("request-id")
+                .map(String::as_str),
+            Some("req_abc")
+        );
+    }
+
+    #[test]
+    fn stream_error_event_maps_non_overload_types() {
+        let rate_limited = anthropic_stream_error(&serde_json::json!({
+            "type": "error",
+            "error": {"type": "rate_limit_error", "message": "slow down"}
+        }));
+        assert!(matches!(rate_limited, RociError::RateLimited { .. }));
+
+        let invalid = anthropic_stream_error(&serde_json::json!({
+            "type": "error",
+            "error": {"type": "invalid_request_error", "message": "bad"}
+        }));
+        assert!(!invalid.is_retryable());
+    }
+
+    /// A Messages response mutated the ways providers have drifted before.
+    fn drifted_response() -> serde_json::Value {
+        serde_json::json!({
+            "content": [
+                {"type": "text", "text": "kept"},
+                {"type": "server_tool_result", "content": []}
+            ],
+            "stop_reason": "paused_for_review",
+            "usage": {"prompt_tokens": 5, "output_tokens": 7}
+        })
+    }
+
+    #[test]
+    fn strict_parsing_reports_drifted_responses() {
+        use roci_core::provider::anomalies::ResponseAnomalyKind;
+        let anomalies = ResponseAnomalies::new("anthropic", true);
+
+        validate_anthropic_response(&drifted_response(), &anomalies);
+
+        let found = anomalies
+            .reports()
+            .into_iter()
+            .map(|anomaly| (anomaly.kind, anomaly.field, anomaly.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    ResponseAnomalyKind::MissingField,
+                    "usage.input_tokens".to_string(),
+                    None
+                ),
+                (
+                    ResponseAnomalyKind::UnknownFinishReason,
+                    "stop_reason".to_string(),
+                    Some("paused_for_review".to_string())
+                ),
+                (
+                    ResponseAnomalyKind::UnknownContentBlock,
+                    "content.1.type".to_string(),
+                    Some("server_tool_result".to_string())
+                ),
+            ]
+        );
+
+        let missing_usage = ResponseAnomalies::new("anthropic", true);
+        let mut raw = drifted_response();
+        raw.as_object_mut().unwrap().remove("usage");
+        validate_anthropic_response(&raw, &missing_usage);
+        assert_eq!(missing_usage.reports()[0].field, "usage");
+    }
+
+    #[test]
+    fn drifted_responses_degrade_gracefully_without_strict_parsing() {
+        let anomalies = ResponseAnomalies::new("anthropic", false);
+        let mut raw = drifted_response();
+        validate_anthropic_response(&raw, &anomalies);
+        raw.as_object_mut().unwrap().remove("usage");
+        let data: AnthropicResponse = serde_json::from_value(raw).expect("tolerant parse");
+
+        let response =
+            parse_anthropic_response(&request_with_headers(None, Default::default()), data);
+
+        assert_eq!(anomalies.count(), 0);
+        assert_eq!(response.text, "kept");
+        assert_eq!(response.usage.total_tokens, 0);
+        assert_eq!(
+            response.finish_reason,
+            Some(FinishReason::Other("paused_for_review".to_string()))
+        );
+    }
+
+    #[test]
+    fn strict_parsing_reports_drifted_stream_events() {
+        use roci_core::provider::anomalies::ResponseAnomalyKind;
+        let request = request_with_headers(None, Default::default());
+        let anomalies = ResponseAnomalies::new("anthropic", true);
+        let mut events =
+            AnthropicStreamEvents::new(request.begin_tool_call_ids(), anomalies.clone());
+
+        for event in [
+            serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "container_upload"}}),
+            serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "citations_delta"}}),
+            serde_json::json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}}),
+        ] {
+            events.handle(&event);
+        }
+
+        let kinds = anomalies
+            .reports()
+            .into_iter()
+            .map(|anomaly| (anomaly.kind, anomaly.field))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    ResponseAnomalyKind::UnknownContentBlock,
+                    "content_block.type".to_string()
+                ),
+                (
+                    ResponseAnomalyKind::UnknownContentBlock,
+                    "delta.type".to_string()
+                ),
+                (ResponseAnomalyKind::MissingField, "usage".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_text_tolerates_a_missing_usage_object() {
+        let server =
+            mock_messages_endpoint(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": [{"type": "text", "text": "hi"}],
+                "stop_reason": "end_turn"
+            })))
+            .await;
+
+        let response = provider_for(&server)
+            .generate_text(&request_with_headers(None, Default::default()))
+            .await
+            .expect("missing usage is tolerated");
+
+        assert_eq!(response.text, "hi");
+        assert_eq!(response.usage.input_tokens, 0);
+        assert_eq!(
+            response
+                .response_metadata
+                .map(|metadata| metadata.parse_anomalies),
+            Some(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_retries_plain_when_gzip_is_blamed_and_reports_sizes() {
+        let sse = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/messages"))
+            .and(wiremock::matchers::header("content-encoding", "gzip"))
+            .respond_with(ResponseTemplate::new(400).set_body_raw(
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"unsupported content-encoding gzip"}}"#,
+                "application/json",
+            ))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(sse, "text/event-stream"))
+            .mount(&server)
+            .await;
+        let provider =
+            provider_for(&server).with_request_compression(Some(RequestCompression::new(1)));
+
+        let request = request_with_headers(None, reqwest::header::HeaderMap::new());
+        let stream = match provider.stream_text(&request).await {
+            Ok(stream) => stream,
+            Err(err) => panic!("stream should open after the plain retry: {err}"),
+        };
+        let items: Vec<_> = stream.collect().await;
+
+        let received = server.received_requests().await.expect("recorded requests");
+        assert_eq!(received.len(), 2);
+        assert!(received[0].headers.contains_key("content-encoding"));
+        assert!(!received[1].headers.contains_key("content-encoding"));
+        let payload = items
+            .iter()
+            .filter_map(|item| item.as_ref().ok())
+            .find_map(|delta| delta.response_metadata.as_ref()?.payload)
+            .expect("payload sizes");
+        assert!(!payload.request_gzipped);
+        assert_eq!(payload.request_sent_bytes, received[1].body.len() as u64);
+        assert_eq!(payload.response_bytes, Some(sse.len() as u64));
+    }
 }
@@ -3,6 +3,7 @@
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 
+use roci_core::config::AnthropicCompatConfig;
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::TextStreamDelta;
@@ -24,6 +25,13 @@ impl AnthropicCompatibleProvider {
             inner: AnthropicProvider::new(model, api_key, Some(base_url)),
         }
     }
+
+    /// Adapt requests to an endpoint that diverges from Anthropic on tools,
+    /// thinking, output limits, or system prompt placement.
+    pub fn with_compat(mut self, compat: AnthropicCompatConfig) -> Self {
+        self.inner = self.inner.with_compat(compat);
+        self
+    }
 }
 
 #[async_trait]
@@ -37,6 +45,9 @@ impl ModelProvider for AnthropicCompatibleProvider {
     fn capabilities(&self) -> &ModelCapabilities {
         self.inner.capabilities()
     }
+    fn supports_assistant_prefix(&self) -> bool {
+        self.inner.supports_assistant_prefix()
+    }
     async fn generate_text(
         &self,
         request: &ProviderRequest,
@@ -50,3 +61,261 @@ impl ModelProvider for AnthropicCompatibleProvider {
         self.inner.stream_text(request).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci_core::config::SystemPromptPlacement;
+    use roci_core::provider::ToolDefinition;
+    use roci_core::types::{
+        AgentToolCall, AgentToolResult, AnthropicOptions, ContentPart, GenerationSettings,
+        ModelMessage, Role, ThinkingContent, ThinkingMode, ToolChoice,
+    };
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn provider(compat: AnthropicCompatConfig) -> AnthropicCompatibleProvider {
+        AnthropicCompatibleProvider::new(
+            "glm-4.6".to_string(),
+            "test-key".to_string(),
+            "https://compat.example/v1".to_string(),
+        )
+        .with_compat(compat)
+    }
+
+    /// System prompt, a tool round trip with thinking, tools, tool choice,
+    /// and a thinking budget: every part a capability flag touches.
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            messages: vec![
+                ModelMessage::system("be brief"),
+                ModelMessage::user("weather?"),
+                ModelMessage {
+                    role: Role::Assistant,
+                    content: vec![
+                        ContentPart::Thinking(ThinkingContent {
+                            thinking: "look it up".to_string(),
+                            signature: "sig".to_string(),
+                        }),
+                        ContentPart::ToolCall(AgentToolCall {
+                            id: "call_1".to_string(),
+                            name: "get_weather".to_string(),
+                            arguments: json!({"city": "Oslo"}),
+                            called_as: None,
+                            recipient: None,
+                        }),
+                    ],
+                    name: None,
+                    timestamp: None,
+                    metadata: None,
+                },
+                ModelMessage {
+                    role: Role::Tool,
+                    content: vec![ContentPart::ToolResult(AgentToolResult {
+                        tool_call_id: "call_1".to_string(),
+                        result: json!("rain"),
+                        is_error: false,
+                    })],
+                    name: None,
+                    timestamp: None,
+                    metadata: None,
+                },
+            ]
+            .into(),
+            settings: GenerationSettings {
+                max_tokens: Some(32_000),
+                temperature: Some(0.3),
+                tool_choice: Some(ToolChoice::Auto),
+                anthropic: Some(AnthropicOptions {
+                    thinking: Some(ThinkingMode::Enabled {
+                        budget_tokens: 10_000,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            tools: Some(vec![ToolDefinition {
+                name: "get_weather".to_string(),
+                description: "Get weather".to_string(),
+                parameters: json!({"type": "object", "properties": {}}),
+                strict: false,
+            }]),
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    fn body(compat: AnthropicCompatConfig) -> serde_json::Value {
+        provider(compat).inner.build_request_body(&request(), false)
+    }
+
+    #[test]
+    fn default_config_keeps_the_anthropic_request_shape() {
+        let plain = AnthropicProvider::new(
+            AnthropicModel::Custom("glm-4.6".to_string()),
+            "test-key".to_string(),
+            None,
+        );
+
+        assert_eq!(
+            body(AnthropicCompatConfig::default()),
+            plain.build_request_body(&request(), false)
+        );
+    }
+
+    #[test]
+    fn without_tools_drops_definitions_and_replays_tool_history_as_text() {
+        let body = body(AnthropicCompatConfig {
+            supports_tools: false,
+            ..Default::default()
+        });
+
+        assert!(body.get("tools").is_none());
+        assert!(body.get("tool_choice").is_none());
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(
+            messages[1]["content"][1],
+            json!({
+                "type": "text",
+                "text": "[called tool get_weather (call_1) with {\"city\":\"Oslo\"}]",
+            })
+        );
+        assert_eq!(
+            messages[2],
+            json!({"role": "user", "content": "[tool result for call_1: \"rain\"]"})
+        );
+        assert!(!body.to_string().contains("tool_use"));
+    }
+
+    #[test]
+    fn without_tool_choice_keeps_tools_but_omits_the_choice() {
+        let body = body(AnthropicCompatConfig {
+            supports_tool_choice: false,
+            ..Default::default()
+        });
+
+        assert_eq!(body["tools"][0]["name"], "get_weather");
+        assert!(body.get("tool_choice").is_none());
+        assert_eq!(body["messages"][1]["content"][1]["type"], "tool_use");
+    }
+
+    #[test]
+    fn without_thinking_omits_thinking_and_allows_temperature() {
+        let body = body(AnthropicCompatConfig {
+            supports_thinking: false,
+            ..Default::default()
+        });
+
+        assert!(body.get("thinking").is_none());
+        assert_eq!(body["temperature"], 0.3);
+        assert_eq!(body["max_tokens"], 32_000);
+        assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+    }
+
+    #[test]
+    fn max_output_tokens_caps_max_tokens_and_thinking_budget() {
+        let body = body(AnthropicCompatConfig {
+            max_output_tokens: Some(8_192),
+            ..Default::default()
+        });
+
+        assert_eq!(body["max_tokens"], 8_192);
+        assert_eq!(body["thinking"]["budget_tokens"], 8_191);
+    }
+
+    #[test]
+    fn first_message_placement_moves_the_system_prompt_into_messages() {
+        let body = body(AnthropicCompatConfig {
+            system_prompt: SystemPromptPlacement::FirstMessage,
+            ..Default::default()
+        });
+
+        assert!(body.get("system").is_none());
+        assert_eq!(
+            body["messages"][0],
+            json!({"role": "system", "content": "be brief"})
+        );
+        assert_eq!(body["messages"][1]["role"], "user");
+    }
+
+    #[test]
+    fn minimal_endpoint_gets_a_plain_chat_request() {
+        let body = body(AnthropicCompatConfig {
+            supports_tools: false,
+            supports_tool_choice: false,
+            supports_thinking: false,
+            max_output_tokens: Some(4_096),
+            system_prompt: SystemPromptPlacement::FirstMessage,
+        });
+
+        for key in ["tools", "tool_choice", "thinking", "system"] {
+            assert!(body.get(key).is_none(), "{key} should be omitted");
+        }
+        assert_eq!(body["max_tokens"], 4_096);
+        assert_eq!(body["temperature"], 0.3);
+        let roles: Vec<_> = body["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|message| message["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user"]);
+    }
+
+    #[test]
+    fn capabilities_reflect_the_overrides() {
+        let provider = provider(AnthropicCompatConfig {
+            supports_tools: false,
+            supports_thinking: false,
+            max_output_tokens: Some(8_192),
+            ..Default::default()
+        });
+
+        let capabilities = provider.capabilities();
+        assert!(!capabilities.supports_tools);
+        assert!(!capabilities.supports_reasoning);
+        assert_eq!(capabilities.max_output_tokens, Some(8_192));
+    }
+
+    #[tokio::test]
+    async fn rejected_request_error_names_the_endpoint_and_keeps_the_body() {
+        let body = r#"{"error":{"code":"1210","message":"tool_choice is not supported"}}"#;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/messages"))
+            .respond_with(ResponseTemplate::new(400).set_body_raw(body, "application/json"))
+            .mount(&server)
+            .await;
+        let provider = AnthropicCompatibleProvider::new(
+            "glm-4.6".to_string(),
+            "test-key".to_string(),
+            server.uri(),
+        )
+        .with_compat(AnthropicCompatConfig::default());
+
+        let err = provider
+            .generate_text(&request())
+            .await
+            .expect_err("endpoint rejects the request");
+
+        match err {
+            RociError::Api {
+                status, message, ..
+            } => {
+                assert_eq!(status, 400);
+                assert_eq!(
+                    message,
+                    format!("{}/messages responded: {body}", server.uri())
+                );
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+    }
+}
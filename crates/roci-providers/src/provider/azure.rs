@@ -115,7 +115,7 @@ mod tests {
             "2024-06-01".to_string(),
         );
         let request = ProviderRequest {
-            messages: vec![],
+            messages: vec![].into(),
             settings: roci_core::types::GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -125,6 +125,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let headers = provider.inner.build_headers(&request).expect("headers");
         assert_eq!(
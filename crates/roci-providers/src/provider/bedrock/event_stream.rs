@@ -0,0 +1,335 @@
+//! Decoder for the AWS event-stream binary framing (`application/vnd.amazon.eventstream`).
+//!
+//! Each message is a 12-byte prelude (total length, headers length, prelude
+//! CRC32), typed headers, a payload, and a CRC32 over everything before it.
+//! All integers are big-endian.
+
+use roci_core::error::RociError;
+
+const PRELUDE_LEN: usize = 12;
+const CRC_LEN: usize = 4;
+const MIN_MESSAGE_LEN: usize = PRELUDE_LEN + CRC_LEN;
+/// Matches the service-side limit; larger lengths mean a corrupt prelude.
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// One decoded event-stream message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Message {
+    /// String-typed headers. Bedrock only sends strings; headers of other
+    /// types are validated and skipped.
+    pub headers: Vec<(String, String)>,
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    /// Value of header `name`, if present.
+    pub(crate) fn header_str(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Incremental decoder: feed raw bytes as they arrive, then drain messages.
+#[derive(Debug, Default)]
+pub(crate) struct EventStreamDecoder {
+    buffer: Vec<u8>,
+}
+
+impl EventStreamDecoder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes received that do not yet form a complete message.
+    pub(crate) fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Decode the next complete message, or `None` until more bytes arrive.
+    pub(crate) fn next_message(&mut self) -> Result<Option<Message>, RociError> {
+        if self.buffer.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+        let total_len = read_u32(&self.buffer[0..4]) as usize;
+        let headers_len = read_u32(&self.buffer[4..8]) as usize;
+        let prelude_crc = read_u32(&self.buffer[8..12]);
+        if crc32(&self.buffer[0..8]) != prelude_crc {
+            return Err(stream_error("prelude checksum mismatch"));
+        }
+        if !(MIN_MESSAGE_LEN..=MAX_MESSAGE_LEN).contains(&total_len)
+            || headers_len > total_len - MIN_MESSAGE_LEN
+        {
+            return Err(stream_error(&format!(
+                "invalid message length {total_len} (headers {headers_len})"
+            )));
+        }
+        if self.buffer.len() < total_len {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = self.buffer.drain(..total_len).collect();
+        let message_crc = read_u32(&frame[total_len - CRC_LEN..]);
+        if crc32(&frame[..total_len - CRC_LEN]) != message_crc {
+            return Err(stream_error("message checksum mismatch"));
+        }
+        let headers_end = PRELUDE_LEN + headers_len;
+        let headers = decode_headers(&frame[PRELUDE_LEN..headers_end])?;
+        let payload = frame[headers_end..total_len - CRC_LEN].to_vec();
+        Ok(Some(Message { headers, payload }))
+    }
+}
+
+fn decode_headers(mut bytes: &[u8]) -> Result<Vec<(String, String)>, RociError> {
+    let mut headers = Vec::new();
+    while !bytes.is_empty() {
+        let name_len = usize::from(take(&mut bytes, 1)?[0]);
+        let name = String::from_utf8(take(&mut bytes, name_len)?.to_vec())
+            .map_err(|_| stream_error("header name is not UTF-8"))?;
+        let value_type = take(&mut bytes, 1)?[0];
+        // Value widths by type: bool true/false, byte, short, int, long,
+        // length-prefixed bytes/string, timestamp, uuid.
+        let value_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            6 | 7 => {
+                let len = take(&mut bytes, 2)?;
+                usize::from(u16::from_be_bytes([len[0], len[1]]))
+            }
+            9 => 16,
+            other => {
+                return Err(stream_error(&format!(
+                    "unknown header type {other} for '{name}'"
+                )))
+            }
+        };
+        let value = take(&mut bytes, value_len)?;
+        if value_type == 7 {
+            let value = String::from_utf8(value.to_vec())
+                .map_err(|_| stream_error("header value is not UTF-8"))?;
+            headers.push((name, value));
+        }
+    }
+    Ok(headers)
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], RociError> {
+    if bytes.len() < len {
+        return Err(stream_error("truncated headers"));
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn stream_error(detail: &str) -> RociError {
+    RociError::Stream(format!("malformed Bedrock event stream: {detail}"))
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320).
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut index = 0;
+        while index < 256 {
+            let mut crc = index as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[index] = crc;
+            index += 1;
+        }
+        table
+    };
+
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A `chunk` event whose payload wraps a base64 `text_delta` event,
+    /// encoded independently of this decoder.
+    pub(crate) const CHUNK_FIXTURE: &str = "000000e20000004bd638d3ce0b3a6576656e742d747970650700056368756e6b0d3a636f6e74656e742d747970650700106170706c69636174696f6e2f6a736f6e0d3a6d6573736167652d747970650700056576656e747b226279746573223a2265794a306558426c496a6f695932397564475675644639696247396a6131396b5a57783059534973496d6c755a475634496a6f774c434a6b5a5778305953493665794a306558426c496a6f69644756346446396b5a57783059534973496e526c654851694f694a4961534a3966513d3d222c2270223a2261626364227d528ecb0b";
+
+    /// A `throttlingException` message.
+    pub(crate) const EXCEPTION_FIXTURE: &str = "000000b2000000613590d5d30f3a657863657074696f6e2d747970650700137468726f74746c696e67457863657074696f6e0d3a636f6e74656e742d747970650700106170706c69636174696f6e2f6a736f6e0d3a6d6573736167652d74797065070009657863657074696f6e7b226d657373616765223a22546f6f206d616e792072657175657374732c20706c656173652077616974206265666f726520747279696e6720616761696e2e227de8ffb26a";
+
+    pub(crate) fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Encode a message with string headers, for building streams in tests.
+    pub(crate) fn encode(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut header_bytes = Vec::new();
+        for (name, value) in headers {
+            header_bytes.push(name.len() as u8);
+            header_bytes.extend_from_slice(name.as_bytes());
+            header_bytes.push(7);
+            header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            header_bytes.extend_from_slice(value.as_bytes());
+        }
+        let total = (PRELUDE_LEN + header_bytes.len() + payload.len() + CRC_LEN) as u32;
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&total.to_be_bytes());
+        frame.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&crc32(&frame).to_be_bytes());
+        frame.extend_from_slice(&header_bytes);
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&crc32(&frame).to_be_bytes());
+        frame
+    }
+
+    #[test]
+    fn crc32_matches_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn decodes_chunk_fixture() {
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&unhex(CHUNK_FIXTURE));
+
+        let message = decoder.next_message().unwrap().expect("complete message");
+        assert_eq!(message.header_str(":event-type"), Some("chunk"));
+        assert_eq!(message.header_str(":message-type"), Some("event"));
+        assert_eq!(
+            message.header_str(":content-type"),
+            Some("application/json")
+        );
+        let payload: serde_json::Value = serde_json::from_slice(&message.payload).unwrap();
+        assert_eq!(payload["p"], "abcd");
+        assert!(payload["bytes"]
+            .as_str()
+            .unwrap()
+            .starts_with("eyJ0eXBlIjoi"));
+        assert_eq!(decoder.next_message().unwrap(), None);
+        assert_eq!(decoder.pending(), 0);
+    }
+
+    #[test]
+    fn decodes_exception_fixture() {
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&unhex(EXCEPTION_FIXTURE));
+
+        let message = decoder.next_message().unwrap().unwrap();
+        assert_eq!(message.header_str(":message-type"), Some("exception"));
+        assert_eq!(
+            message.header_str(":exception-type"),
+            Some("throttlingException")
+        );
+        assert!(String::from_utf8_lossy(&message.payload).contains("Too many requests"));
+    }
+
+    #[test]
+    fn waits_for_split_frames_across_pushes() {
+        let bytes = [unhex(CHUNK_FIXTURE), unhex(EXCEPTION_FIXTURE)].concat();
+        let mut decoder = EventStreamDecoder::new();
+        let mut messages = Vec::new();
+        for piece in bytes.chunks(7) {
+            decoder.push(piece);
+            while let Some(message) = decoder.next_message().unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].header_str(":event-type"), Some("chunk"));
+        assert_eq!(
+            messages[1].header_str(":exception-type"),
+            Some("throttlingException")
+        );
+    }
+
+    #[test]
+    fn encoder_round_trips_through_decoder() {
+        let frame = encode(&[(":event-type", "chunk")], b"{}");
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+
+        assert_eq!(
+            decoder.next_message().unwrap(),
+            Some(Message {
+                headers: vec![(":event-type".to_string(), "chunk".to_string())],
+                payload: b"{}".to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn skips_non_string_header_types() {
+        let mut headers = vec![2, b'o', b'k', 0];
+        headers.extend_from_slice(&[1, b'n', 4]);
+        headers.extend_from_slice(&42i32.to_be_bytes());
+        headers.extend_from_slice(&[1, b't', 8]);
+        headers.extend_from_slice(&1_700_000_000_000i64.to_be_bytes());
+        headers.extend_from_slice(&[1, b'b', 6, 0, 2, 0xde, 0xad]);
+        headers.extend_from_slice(&[1, b'u', 9]);
+        headers.extend_from_slice(&[7; 16]);
+        headers.extend_from_slice(&[1, b's', 7, 0, 2, b'h', b'i']);
+
+        assert_eq!(
+            decode_headers(&headers).unwrap(),
+            vec![("s".to_string(), "hi".to_string())]
+        );
+    }
+
+    #[test]
+    fn corrupted_prelude_is_rejected() {
+        let mut frame = unhex(CHUNK_FIXTURE);
+        frame[3] ^= 0x01;
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+
+        let err = decoder.next_message().unwrap_err();
+        assert!(matches!(err, RociError::Stream(ref message) if message.contains("prelude")));
+    }
+
+    #[test]
+    fn corrupted_payload_is_rejected() {
+        let mut frame = unhex(CHUNK_FIXTURE);
+        let last_payload_byte = frame.len() - CRC_LEN - 1;
+        frame[last_payload_byte] ^= 0x01;
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+
+        let err = decoder.next_message().unwrap_err();
+        assert!(
+            matches!(err, RociError::Stream(ref message) if message.contains("message checksum"))
+        );
+    }
+
+    #[test]
+    fn truncated_headers_are_rejected() {
+        assert!(decode_headers(&[5, b'a']).is_err());
+        assert!(decode_headers(&[1, b'a', 7, 0, 9, b'x']).is_err());
+        assert!(decode_headers(&[1, b'a', 42]).is_err());
+    }
+}
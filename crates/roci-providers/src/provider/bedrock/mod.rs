@@ -0,0 +1,779 @@
+//! AWS Bedrock provider for Anthropic Claude models.
+//!
+//! Requests reuse the Anthropic Messages body (minus `model`/`stream`, plus
+//! `anthropic_version`), are SigV4-signed, and stream back as AWS
+//! event-stream frames whose `chunk` payloads carry base64-encoded Messages
+//! stream events.
+
+pub(crate) mod event_stream;
+pub(crate) mod sigv4;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tracing::debug;
+
+use roci_core::error::RociError;
+use roci_core::models::capabilities::ModelCapabilities;
+use roci_core::provider::anomalies::{with_anomaly_count, ResponseAnomalies};
+use roci_core::provider::http::{
+    response_metadata, shared_client, with_response_metadata, ResponseHeaderRules,
+};
+use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::types::TextStreamDelta;
+
+use super::anthropic::{
+    parse_anthropic_response, validate_anthropic_response, AnthropicProvider, AnthropicResponse,
+    AnthropicStreamEvents,
+};
+use crate::auth::aws::AwsCredentials;
+use crate::models::anthropic::AnthropicModel;
+use event_stream::{EventStreamDecoder, Message};
+use sigv4::{SignableRequest, SigningParams};
+
+const SERVICE: &str = "bedrock";
+const ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+const EVENT_STREAM_CONTENT_TYPE: &str = "application/vnd.amazon.eventstream";
+
+/// Bedrock runtime endpoint for `region`.
+pub fn runtime_base_url(region: &str) -> String {
+    format!("https://bedrock-runtime.{region}.amazonaws.com")
+}
+
+/// Reject region strings that cannot name a Bedrock endpoint (e.g. `us-east`
+/// or a stray URL) before they surface as DNS failures.
+pub fn validate_region(region: &str) -> Result<(), RociError> {
+    let parts: Vec<&str> = region.split('-').collect();
+    let valid = parts.len() >= 3
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        })
+        && parts
+            .last()
+            .is_some_and(|last| last.bytes().all(|b| b.is_ascii_digit()));
+    if valid {
+        Ok(())
+    } else {
+        Err(RociError::Configuration(format!(
+            "invalid AWS region '{region}' for Bedrock; expected a region code such as \
+             us-east-1 (set AWS_REGION)"
+        )))
+    }
+}
+
+/// Anthropic model for a Bedrock model ID, ignoring the cross-region
+/// inference prefix (`us.`), the `anthropic.` vendor prefix, and the
+/// `-v1:0` version suffix.
+fn anthropic_model(model_id: &str) -> AnthropicModel {
+    let name = model_id
+        .split_once("anthropic.")
+        .map(|(_, rest)| rest)
+        .unwrap_or(model_id);
+    let name = match name.rsplit_once("-v") {
+        Some((base, version))
+            if version
+                .split(':')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit())) =>
+        {
+            base
+        }
+        _ => name,
+    };
+    match AnthropicModel::from_str(name) {
+        Ok(AnthropicModel::Custom(_)) | Err(_) => AnthropicModel::Custom(model_id.to_string()),
+        Ok(model) => model,
+    }
+}
+
+pub struct BedrockProvider {
+    /// Builds request bodies and reports capabilities for the underlying model.
+    inner: AnthropicProvider,
+    target: Target,
+    credentials: AwsCredentials,
+    base_url: String,
+}
+
+/// Model and region, kept together for error messages.
+#[derive(Clone)]
+struct Target {
+    model_id: String,
+    region: String,
+}
+
+impl BedrockProvider {
+    pub fn new(model_id: String, region: String, credentials: AwsCredentials) -> Self {
+        Self {
+            inner: AnthropicProvider::new(anthropic_model(&model_id), String::new(), None),
+            base_url: runtime_base_url(&region),
+            target: Target { model_id, region },
+            credentials,
+        }
+    }
+
+    /// Send requests to `base_url` instead of the regional runtime endpoint
+    /// (VPC endpoints, proxies, tests). Signing still uses the region.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn region(&self) -> &str {
+        &self.target.region
+    }
+
+    fn build_request_body(&self, request: &ProviderRequest) -> serde_json::Value {
+        let mut body = self.inner.build_request_body(request, false);
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("model");
+            obj.remove("stream");
+            obj.insert("anthropic_version".into(), ANTHROPIC_VERSION.into());
+        }
+        body
+    }
+
+    /// Build a signed POST to `/model/{id}/{action}`.
+    fn signed_request(
+        &self,
+        request: &ProviderRequest,
+        action: &str,
+        accept: &str,
+        body: Vec<u8>,
+    ) -> Result<reqwest::RequestBuilder, RociError> {
+        let url = format!(
+            "{}/model/{}/{action}",
+            self.base_url,
+            sigv4::uri_encode(&self.target.model_id, true)
+        );
+        let parsed = reqwest::Url::parse(&url).map_err(|err| {
+            RociError::Configuration(format!("invalid Bedrock endpoint {url}: {err}"))
+        })?;
+        let host = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(RociError::Configuration(format!(
+                    "Bedrock endpoint {url} has no host"
+                )))
+            }
+        };
+
+        let signed = sigv4::sign(
+            SignableRequest {
+                method: "POST",
+                path: parsed.path(),
+                query: parsed.query().unwrap_or_default(),
+                headers: vec![
+                    ("host".to_string(), host),
+                    ("content-type".to_string(), "application/json".to_string()),
+                    ("accept".to_string(), accept.to_string()),
+                ],
+                body: &body,
+            },
+            &SigningParams {
+                credentials: &self.credentials,
+                region: &self.target.region,
+                service: SERVICE,
+                time: chrono::Utc::now(),
+            },
+        );
+
+        let mut builder = shared_client()
+            .post(parsed)
+            .header("content-type", "application/json")
+            .header("accept", accept);
+        for (name, value) in signed {
+            builder = builder.header(name, value);
+        }
+        // Caller overrides are sent unsigned, which SigV4 permits.
+        for (name, value) in request.headers.iter() {
+            builder = builder.header(name, value.clone());
+        }
+        Ok(builder.body(body))
+    }
+
+    async fn send(
+        &self,
+        request: &ProviderRequest,
+        action: &str,
+        accept: &str,
+    ) -> Result<reqwest::Response, RociError> {
+        let body = self.build_request_body(request);
+        if let Some(callback) = &request.payload_callback {
+            callback(body.clone());
+        }
+        let body = serde_json::to_vec(&body)?;
+        let resp = self
+            .signed_request(request, action, accept, body)?
+            .send()
+            .await?;
+
+        let status = resp.status().as_u16();
+        if status != 200 {
+            let error_type = resp
+                .headers()
+                .get("x-amzn-errortype")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let body_text = resp.text().await.unwrap_or_default();
+            return Err(self
+                .target
+                .status_error(status, error_type.as_deref(), &body_text));
+        }
+        Ok(resp)
+    }
+}
+
+impl Target {
+    /// Map a non-200 Bedrock response to an actionable typed error.
+    fn status_error(&self, status: u16, error_type: Option<&str>, body: &str) -> RociError {
+        let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+        let message = parsed
+            .as_ref()
+            .and_then(|value| value.get("message").or_else(|| value.get("Message")))
+            .and_then(|message| message.as_str())
+            .unwrap_or(body)
+            .to_string();
+        // `x-amzn-ErrorType` looks like `AccessDeniedException:http://...`.
+        let error_type = error_type
+            .or_else(|| {
+                parsed
+                    .as_ref()
+                    .and_then(|value| value.get("__type"))
+                    .and_then(|kind| kind.as_str())
+            })
+            .map(|kind| kind.split(':').next().unwrap_or(kind).to_string())
+            .unwrap_or_default();
+        self.exception_error(status, &error_type, &message)
+    }
+
+    fn exception_error(&self, status: u16, error_type: &str, message: &str) -> RociError {
+        let (model, region) = (&self.model_id, &self.region);
+        match error_type.to_ascii_lowercase().as_str() {
+            "unrecognizedclientexception"
+            | "invalidsignatureexception"
+            | "expiredtokenexception"
+            | "incompletesignatureexception" => RociError::Authentication(format!(
+                "Bedrock rejected the AWS credentials ({error_type}: {message}); check \
+                 AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY, refresh AWS_SESSION_TOKEN if it \
+                 expired, or select another AWS_PROFILE"
+            )),
+            "accessdeniedexception" => RociError::Authentication(format!(
+                "access to Bedrock model {model} in {region} was denied ({message}); enable \
+                 model access in the Bedrock console for this region and allow \
+                 bedrock:InvokeModel and bedrock:InvokeModelWithResponseStream"
+            )),
+            "resourcenotfoundexception" => {
+                RociError::ModelNotFound(format!("{model} in {region}: {message}"))
+            }
+            "validationexception" if message.to_ascii_lowercase().contains("model identifier") => {
+                RociError::ModelNotFound(format!(
+                    "{model} is not a valid Bedrock model ID in {region}: {message}"
+                ))
+            }
+            "throttlingexception" | "servicequotaexceededexception" => RociError::RateLimited {
+                retry_after_ms: None,
+            },
+            "serviceunavailableexception" | "modelnotreadyexception" => {
+                RociError::overloaded(if status == 200 { 503 } else { status }, message)
+            }
+            "modelstreamerrorexception" | "internalserverexception" => {
+                RociError::api(if status == 200 { 500 } else { status }, message)
+            }
+            "validationexception" => RociError::api(400, message),
+            _ if status == 200 => RociError::Stream(format!("{error_type}: {message}")),
+            _ => roci_core::provider::http::status_to_error(status, message),
+        }
+    }
+
+    /// Deltas for one event-stream message. An `Err` item ends the stream.
+    fn handle_message(
+        &self,
+        message: &Message,
+        events: &mut AnthropicStreamEvents,
+    ) -> Vec<Result<TextStreamDelta, RociError>> {
+        let payload_message = || {
+            serde_json::from_slice::<serde_json::Value>(&message.payload)
+                .ok()
+                .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+                .unwrap_or_else(|| String::from_utf8_lossy(&message.payload).into_owned())
+        };
+        match message.header_str(":message-type") {
+            Some("exception") => {
+                let error_type = message.header_str(":exception-type").unwrap_or_default();
+                vec![Err(self.exception_error(
+                    200,
+                    error_type,
+                    &payload_message(),
+                ))]
+            }
+            Some("error") => {
+                let code = message.header_str(":error-code").unwrap_or("error");
+                let detail = message
+                    .header_str(":error-message")
+                    .map(str::to_string)
+                    .unwrap_or_else(payload_message);
+                vec![Err(RociError::Stream(format!("Bedrock {code}: {detail}")))]
+            }
+            _ if message.header_str(":event-type") == Some("chunk") => {
+                match decode_chunk(&message.payload) {
+                    Ok(event) => events.handle(&event),
+                    Err(err) => vec![Err(err)],
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The Messages stream event inside a `chunk` payload (`{"bytes": base64}`).
+fn decode_chunk(payload: &[u8]) -> Result<serde_json::Value, RociError> {
+    let chunk: serde_json::Value = serde_json::from_slice(payload)?;
+    let encoded = chunk
+        .get("bytes")
+        .and_then(|bytes| bytes.as_str())
+        .ok_or_else(|| RociError::Stream("Bedrock chunk without bytes".to_string()))?;
+    let decoded = STANDARD
+        .decode(encoded)
+        .map_err(|err| RociError::Stream(format!("invalid Bedrock chunk encoding: {err}")))?;
+    Ok(serde_json::from_slice(&decoded)?)
+}
+
+#[async_trait]
+impl ModelProvider for BedrockProvider {
+    fn provider_name(&self) -> &str {
+        "bedrock"
+    }
+
+    fn model_id(&self) -> &str {
+        &self.target.model_id
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_assistant_prefix(&self) -> bool {
+        true
+    }
+
+    async fn generate_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        debug!(model = %self.target.model_id, region = %self.target.region, "Bedrock generate_text");
+
+        let resp = self.send(request, "invoke", "application/json").await?;
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::BEDROCK);
+        let raw: serde_json::Value = resp.json().await?;
+        let anomalies = ResponseAnomalies::from_env("bedrock");
+        validate_anthropic_response(&raw, &anomalies);
+        let data: AnthropicResponse = serde_json::from_value(raw)?;
+        let mut response = parse_anthropic_response(request, data);
+        response.response_metadata = Some(anomalies.annotate(response_metadata));
+        Ok(response)
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        debug!(model = %self.target.model_id, region = %self.target.region, "Bedrock stream_text");
+
+        let resp = self
+            .send(
+                request,
+                "invoke-with-response-stream",
+                EVENT_STREAM_CONTENT_TYPE,
+            )
+            .await?;
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::BEDROCK);
+        let byte_stream = resp.bytes_stream();
+
+        let target = self.target.clone();
+        let anomalies = ResponseAnomalies::from_env("bedrock");
+        let mut events =
+            AnthropicStreamEvents::new(request.begin_tool_call_ids(), anomalies.clone());
+        let stream = async_stream::stream! {
+            let mut decoder = EventStreamDecoder::new();
+            futures::pin_mut!(byte_stream);
+
+            while let Some(chunk_result) = byte_stream.next().await {
+                let chunk = match chunk_result {
+                    Ok(c) => c,
+                    Err(e) => {
+                        yield Err(RociError::Network(e));
+                        return;
+                    }
+                };
+                decoder.push(&chunk);
+
+                loop {
+                    let message = match decoder.next_message() {
+                        Ok(Some(message)) => message,
+                        Ok(None) => break,
+                        Err(err) => {
+                            yield Err(err);
+                            return;
+                        }
+                    };
+                    for item in target.handle_message(&message, &mut events) {
+                        let failed = item.is_err();
+                        yield item;
+                        if failed {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            if decoder.pending() > 0 {
+                yield Err(RociError::Stream(
+                    "Bedrock event stream ended mid-message".to_string(),
+                ));
+            }
+        };
+
+        Ok(with_anomaly_count(
+            with_response_metadata(Box::pin(stream), response_metadata),
+            anomalies,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::event_stream::tests::encode;
+    use super::*;
+    use roci_core::types::{FinishReason, GenerationSettings, ModelMessage, StreamEventType};
+    use wiremock::matchers::{header, method, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MODEL_ID: &str = "anthropic.claude-sonnet-4-20250514-v1:0";
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            messages: vec![ModelMessage::system("be brief"), ModelMessage::user("hi")].into(),
+            settings: GenerationSettings::default(),
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    fn provider_for(server: &MockServer) -> BedrockProvider {
+        BedrockProvider::new(
+            MODEL_ID.to_string(),
+            "us-east-1".to_string(),
+            AwsCredentials::new("AKIDEXAMPLE", "secret").with_session_token("session"),
+        )
+        .with_base_url(server.uri())
+    }
+
+    fn chunk(event: serde_json::Value) -> Vec<u8> {
+        let payload = serde_json::json!({
+            "bytes": STANDARD.encode(event.to_string()),
+        });
+        encode(
+            &[
+                (":event-type", "chunk"),
+                (":content-type", "application/json"),
+                (":message-type", "event"),
+            ],
+            payload.to_string().as_bytes(),
+        )
+    }
+
+    fn exception(kind: &str, message: &str) -> Vec<u8> {
+        encode(
+            &[
+                (":exception-type", kind),
+                (":content-type", "application/json"),
+                (":message-type", "exception"),
+            ],
+            serde_json::json!({ "message": message })
+                .to_string()
+                .as_bytes(),
+        )
+    }
+
+    async fn mount_stream(server: &MockServer, body: Vec<u8>) {
+        Mock::given(method("POST"))
+            .and(path_regex(r"^/model/[^/]+/invoke-with-response-stream$"))
+            .and(header("accept", EVENT_STREAM_CONTENT_TYPE))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, EVENT_STREAM_CONTENT_TYPE))
+            .mount(server)
+            .await;
+    }
+
+    async fn stream_items(provider: &BedrockProvider) -> Vec<Result<TextStreamDelta, RociError>> {
+        match provider.stream_text(&request()).await {
+            Ok(stream) => stream.collect().await,
+            Err(err) => panic!("stream should open: {err}"),
+        }
+    }
+
+    async fn status_error(status: u16, error_type: &str, message: &str) -> RociError {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .insert_header("x-amzn-ErrorType", format!("{error_type}:http://internal.amazon.com/coral/com.amazon.coral.service/"))
+                    .set_body_json(serde_json::json!({ "message": message })),
+            )
+            .mount(&server)
+            .await;
+        match provider_for(&server).stream_text(&request()).await {
+            Ok(_) => panic!("expected {error_type} to fail"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn model_ids_map_to_anthropic_models() {
+        assert_eq!(anthropic_model(MODEL_ID), AnthropicModel::ClaudeSonnet4);
+        assert_eq!(
+            anthropic_model("us.anthropic.claude-sonnet-4-20250514-v1:0"),
+            AnthropicModel::ClaudeSonnet4
+        );
+        assert_eq!(
+            anthropic_model("anthropic.claude-next-v2:0"),
+            AnthropicModel::Custom("anthropic.claude-next-v2:0".to_string())
+        );
+    }
+
+    #[test]
+    fn bedrock_model_selector_keeps_version_suffix() {
+        let model: roci_core::models::LanguageModel =
+            format!("bedrock:{MODEL_ID}").parse().unwrap();
+
+        assert_eq!(model.provider_name(), "bedrock");
+        assert_eq!(model.model_id(), MODEL_ID);
+    }
+
+    #[test]
+    fn region_validation_rejects_malformed_regions() {
+        for region in [
+            "us-east-1",
+            "eu-central-2",
+            "us-gov-west-1",
+            "ap-southeast-3",
+        ] {
+            assert!(validate_region(region).is_ok(), "{region}");
+        }
+        for region in ["", "us-east", "US-EAST-1", "https://bedrock", "us--1"] {
+            let err = validate_region(region).unwrap_err();
+            assert!(
+                matches!(err, RociError::Configuration(ref message) if message.contains("AWS_REGION")),
+                "{region}"
+            );
+        }
+    }
+
+    #[test]
+    fn request_body_uses_bedrock_anthropic_shape() {
+        let provider = BedrockProvider::new(
+            MODEL_ID.to_string(),
+            "us-east-1".to_string(),
+            AwsCredentials::new("AKID", "secret"),
+        );
+
+        let body = provider.build_request_body(&request());
+
+        assert_eq!(body["anthropic_version"], ANTHROPIC_VERSION);
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["messages"][0]["content"], "hi");
+        assert!(body["max_tokens"].as_u64().is_some());
+        assert!(body.get("model").is_none());
+        assert!(body.get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_signs_request_and_decodes_text_and_tool_use() {
+        let server = MockServer::start().await;
+        let body = [
+            chunk(serde_json::json!({"type": "message_start", "message": {"id": "msg_1"}})),
+            chunk(serde_json::json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}})),
+            chunk(serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Checking"}})),
+            chunk(serde_json::json!({"type": "content_block_stop", "index": 0})),
+            chunk(serde_json::json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {}}})),
+            chunk(serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"path\":"}})),
+            chunk(serde_json::json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "\"a.rs\"}"}})),
+            chunk(serde_json::json!({"type": "content_block_stop", "index": 1})),
+            chunk(serde_json::json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 12}})),
+            chunk(serde_json::json!({"type": "message_stop", "amazon-bedrock-invocationMetrics": {"inputTokenCount": 5}})),
+        ]
+        .concat();
+        mount_stream(&server, body).await;
+
+        let items = stream_items(&provider_for(&server)).await;
+        let deltas: Vec<TextStreamDelta> = items.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(deltas[0].event_type, StreamEventType::TextDelta);
+        assert_eq!(deltas[0].text, "Checking");
+        let arguments: Vec<&str> = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::ToolCallArgumentsDelta)
+            .map(|delta| {
+                delta
+                    .tool_call
+                    .as_ref()
+                    .unwrap()
+                    .arguments
+                    .as_str()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(arguments, vec!["{\"path\":", "\"a.rs\"}"]);
+        let call = deltas
+            .iter()
+            .find(|delta| delta.event_type == StreamEventType::ToolCallDelta)
+            .and_then(|delta| delta.tool_call.as_ref())
+            .expect("finalized tool call");
+        assert_eq!(call.id, "toolu_1");
+        assert_eq!(call.name, "read_file");
+        assert_eq!(call.arguments, serde_json::json!({"path": "a.rs"}));
+        let done = deltas
+            .iter()
+            .find(|delta| delta.event_type == StreamEventType::Done)
+            .expect("done");
+        assert_eq!(done.finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(
+            done.usage.as_ref().map(|usage| usage.output_tokens),
+            Some(12)
+        );
+
+        let received = server.received_requests().await.unwrap();
+        let sent = &received[0];
+        assert_eq!(
+            sent.url.path(),
+            "/model/anthropic.claude-sonnet-4-20250514-v1%3A0/invoke-with-response-stream"
+        );
+        let authorization = sent.headers.get("authorization").unwrap().to_str().unwrap();
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/us-east-1/bedrock/aws4_request"));
+        assert!(authorization
+            .contains("SignedHeaders=accept;content-type;host;x-amz-date;x-amz-security-token"));
+        assert_eq!(sent.headers.get("x-amz-security-token").unwrap(), "session");
+        let sent_body: serde_json::Value = serde_json::from_slice(&sent.body).unwrap();
+        assert_eq!(sent_body["anthropic_version"], ANTHROPIC_VERSION);
+    }
+
+    #[tokio::test]
+    async fn stream_exception_after_text_ends_the_stream() {
+        let server = MockServer::start().await;
+        let body = [
+            chunk(serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hel"}})),
+            exception("throttlingException", "Too many requests"),
+            chunk(serde_json::json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "ignored"}})),
+        ]
+        .concat();
+        mount_stream(&server, body).await;
+
+        let items = stream_items(&provider_for(&server)).await;
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap().text, "Hel");
+        assert!(matches!(items[1], Err(RociError::RateLimited { .. })));
+    }
+
+    #[tokio::test]
+    async fn truncated_stream_is_an_error() {
+        let server = MockServer::start().await;
+        let frame = chunk(serde_json::json!({"type": "message_stop"}));
+        mount_stream(&server, frame[..frame.len() - 3].to_vec()).await;
+
+        let items = stream_items(&provider_for(&server)).await;
+
+        assert_eq!(items.len(), 1);
+        assert!(
+            matches!(items[0], Err(RociError::Stream(ref message)) if message.contains("mid-message"))
+        );
+    }
+
+    #[tokio::test]
+    async fn credential_rejections_explain_how_to_fix_them() {
+        let err = status_error(
+            403,
+            "UnrecognizedClientException",
+            "The security token included in the request is invalid.",
+        )
+        .await;
+
+        let RociError::Authentication(message) = err else {
+            panic!("expected authentication error, got {err:?}");
+        };
+        assert!(message.contains("UnrecognizedClientException"));
+        assert!(message.contains("AWS_ACCESS_KEY_ID"));
+    }
+
+    #[tokio::test]
+    async fn access_denied_names_model_and_region() {
+        let err = status_error(
+            403,
+            "AccessDeniedException",
+            "You don't have access to the model with the specified model ID.",
+        )
+        .await;
+
+        assert!(matches!(
+            err,
+            RociError::Authentication(ref message)
+                if message.contains(MODEL_ID) && message.contains("us-east-1")
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_models_are_model_not_found() {
+        let invalid = status_error(
+            400,
+            "ValidationException",
+            "The provided model identifier is invalid.",
+        )
+        .await;
+        assert!(
+            matches!(invalid, RociError::ModelNotFound(ref message) if message.contains("us-east-1"))
+        );
+
+        let missing = status_error(404, "ResourceNotFoundException", "Model not found").await;
+        assert!(matches!(missing, RociError::ModelNotFound(_)));
+
+        let other = status_error(400, "ValidationException", "max_tokens: must be positive").await;
+        assert!(matches!(other, RociError::Api { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn generate_text_parses_invoke_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path_regex(r"^/model/[^/]+/invoke$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": [{"type": "text", "text": "Hello"}],
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 7, "output_tokens": 2},
+            })))
+            .mount(&server)
+            .await;
+
+        let response = provider_for(&server)
+            .generate_text(&request())
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "Hello");
+        assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(response.usage.total_tokens, 9);
+    }
+}
@@ -0,0 +1,353 @@
+//! AWS Signature Version 4 request signing.
+//!
+//! Implements the header-based flow from the SigV4 specification: canonical
+//! request, string to sign, derived signing key, and `Authorization` header.
+
+use chrono::{DateTime, Utc};
+use roci_core::util::hmac::{hex, hmac_sha256};
+use sha2::{Digest, Sha256};
+
+use crate::auth::aws::AwsCredentials;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// A request to sign. `path` must already be percent-encoded as it will be
+/// sent; non-S3 services encode it a second time for the canonical form.
+pub(crate) struct SignableRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: &'a str,
+    /// Headers to sign, including `host`.
+    pub headers: Vec<(String, String)>,
+    pub body: &'a [u8],
+}
+
+/// Who signs, for which service and region, at what time.
+pub(crate) struct SigningParams<'a> {
+    pub credentials: &'a AwsCredentials,
+    pub region: &'a str,
+    pub service: &'a str,
+    pub time: DateTime<Utc>,
+}
+
+impl SigningParams<'_> {
+    fn amz_date(&self) -> String {
+        self.time.format("%Y%m%dT%H%M%SZ").to_string()
+    }
+
+    fn scope(&self) -> String {
+        format!(
+            "{}/{}/{}/aws4_request",
+            self.time.format("%Y%m%d"),
+            self.region,
+            self.service
+        )
+    }
+}
+
+/// Sign `request` and return the headers to add to it: `x-amz-date`,
+/// `x-amz-security-token` when a session token is present, and
+/// `authorization`.
+pub(crate) fn sign(
+    mut request: SignableRequest<'_>,
+    params: &SigningParams<'_>,
+) -> Vec<(String, String)> {
+    let mut added = vec![("x-amz-date".to_string(), params.amz_date())];
+    if let Some(token) = &params.credentials.session_token {
+        added.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    request.headers.extend(added.iter().cloned());
+
+    let (canonical, signed_headers) = canonical_request(&request);
+    let string_to_sign = string_to_sign(&canonical, params);
+    let key = signing_key(
+        &params.credentials.secret_access_key,
+        &params.time.format("%Y%m%d").to_string(),
+        params.region,
+        params.service,
+    );
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+    added.push((
+        "authorization".to_string(),
+        format!(
+            "{ALGORITHM} Credential={}/{}, SignedHeaders={signed_headers}, Signature={signature}",
+            params.credentials.access_key_id,
+            params.scope()
+        ),
+    ));
+    added
+}
+
+/// The canonical request and its signed-header list.
+pub(crate) fn canonical_request(request: &SignableRequest<'_>) -> (String, String) {
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.trim().to_ascii_lowercase(),
+                value.split_whitespace().collect::<Vec<_>>().join(" "),
+            )
+        })
+        .collect();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical = format!(
+        "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+        request.method,
+        canonical_uri(request.path),
+        canonical_query(request.query),
+        hex(&Sha256::digest(request.body)),
+    );
+    (canonical, signed_headers)
+}
+
+pub(crate) fn string_to_sign(canonical_request: &str, params: &SigningParams<'_>) -> String {
+    format!(
+        "{ALGORITHM}\n{}\n{}\n{}",
+        params.amz_date(),
+        params.scope(),
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    )
+}
+
+pub(crate) fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let date_key = hmac_sha256(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let region_key = hmac_sha256(&date_key, region.as_bytes());
+    let service_key = hmac_sha256(&region_key, service.as_bytes());
+    hmac_sha256(&service_key, b"aws4_request")
+}
+
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(|segment| uri_encode(segment, true))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (uri_encode(name, true), uri_encode(value, true))
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+pub(crate) fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Vectors from the AWS SigV4 test suite (`get-vanilla`, `post-vanilla`,
+    // `get-vanilla-query-order-key-case`) and the IAM signing-key example.
+    const SECRET: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+    fn credentials() -> AwsCredentials {
+        AwsCredentials::new("AKIDEXAMPLE", SECRET)
+    }
+
+    fn suite_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+    }
+
+    fn suite_request<'a>(method: &'a str, query: &'a str) -> SignableRequest<'a> {
+        SignableRequest {
+            method,
+            path: "/",
+            query,
+            headers: vec![
+                ("Host".to_string(), "example.amazonaws.com".to_string()),
+                ("X-Amz-Date".to_string(), "20150830T123600Z".to_string()),
+            ],
+            body: b"",
+        }
+    }
+
+    fn suite_signature(method: &str, query: &str) -> String {
+        let credentials = credentials();
+        let params = SigningParams {
+            credentials: &credentials,
+            region: "us-east-1",
+            service: "service",
+            time: suite_time(),
+        };
+        let (canonical, _) = canonical_request(&suite_request(method, query));
+        let key = signing_key(SECRET, "20150830", "us-east-1", "service");
+        hex(&hmac_sha256(
+            &key,
+            string_to_sign(&canonical, &params).as_bytes(),
+        ))
+    }
+
+    #[test]
+    fn signing_key_matches_iam_example() {
+        assert_eq!(
+            hex(&signing_key(SECRET, "20120215", "us-east-1", "iam")),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn get_vanilla_canonical_request() {
+        let (canonical, signed_headers) = canonical_request(&suite_request("GET", ""));
+
+        assert_eq!(
+            canonical,
+            "GET\n/\n\nhost:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             host;x-amz-date\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(signed_headers, "host;x-amz-date");
+        assert_eq!(
+            hex(&Sha256::digest(canonical.as_bytes())),
+            "bb579772317eb040ac9ed261061d46c1f17a8133879d6129b6e1c25292927e63"
+        );
+    }
+
+    #[test]
+    fn get_vanilla_signature() {
+        assert_eq!(
+            suite_signature("GET", ""),
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn post_vanilla_signature() {
+        assert_eq!(
+            suite_signature("POST", ""),
+            "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+        );
+    }
+
+    #[test]
+    fn query_parameters_are_sorted() {
+        assert_eq!(
+            suite_signature("GET", "Param2=value2&Param1=value1"),
+            "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        );
+    }
+
+    #[test]
+    fn sign_builds_authorization_header() {
+        let credentials = credentials();
+        let params = SigningParams {
+            credentials: &credentials,
+            region: "us-east-1",
+            service: "service",
+            time: suite_time(),
+        };
+        let request = SignableRequest {
+            method: "GET",
+            path: "/",
+            query: "",
+            headers: vec![("host".to_string(), "example.amazonaws.com".to_string())],
+            body: b"",
+        };
+
+        let headers = sign(request, &params);
+
+        assert_eq!(
+            headers,
+            vec![
+                ("x-amz-date".to_string(), "20150830T123600Z".to_string()),
+                (
+                    "authorization".to_string(),
+                    "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                     SignedHeaders=host;x-amz-date, \
+                     Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn bedrock_model_path_is_encoded_twice_and_session_token_is_signed() {
+        let credentials = credentials().with_session_token("session-token");
+        let params = SigningParams {
+            credentials: &credentials,
+            region: "us-west-2",
+            service: "bedrock",
+            time: suite_time(),
+        };
+        let request = SignableRequest {
+            method: "POST",
+            path: "/model/anthropic.claude-sonnet-4-20250514-v1%3A0/invoke-with-response-stream",
+            query: "",
+            headers: vec![
+                (
+                    "host".to_string(),
+                    "bedrock-runtime.us-west-2.amazonaws.com".to_string(),
+                ),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            body: br#"{"max_tokens":16}"#,
+        };
+
+        let (canonical, _) = canonical_request(&SignableRequest {
+            headers: request.headers.clone(),
+            ..request
+        });
+        assert!(canonical.starts_with(
+            "POST\n/model/anthropic.claude-sonnet-4-20250514-v1%253A0/invoke-with-response-stream\n"
+        ));
+
+        let headers = sign(request, &params);
+        assert_eq!(
+            headers[1],
+            (
+                "x-amz-security-token".to_string(),
+                "session-token".to_string()
+            )
+        );
+        assert_eq!(
+            headers[2].1,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-west-2/bedrock/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date;x-amz-security-token, \
+             Signature=40a82b60b9a6751af0c1a78bef6eb9c1d692250026f36cfdff26f6b428b7c7e4"
+        );
+    }
+
+    #[test]
+    fn uri_encode_keeps_unreserved_characters() {
+        assert_eq!(uri_encode("a-b_c.d~e", true), "a-b_c.d~e");
+        assert_eq!(uri_encode("v1:0 x/y", true), "v1%3A0%20x%2Fy");
+        assert_eq!(uri_encode("x/y", false), "x/y");
+    }
+}
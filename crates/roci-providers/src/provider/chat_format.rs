@@ -0,0 +1,239 @@
+//! Chat-format overrides applied to serialized chat completions messages.
+//!
+//! Local model servers render the messages array through the model's own
+//! prompt template, and some templates mishandle system messages or expect
+//! different role names. [`apply_chat_format`] reshapes the array just
+//! before the request body is built.
+
+use roci_core::config::{ChatFormat, SystemMessageStrategy};
+use serde_json::Value;
+
+/// Rewrite `messages` (chat completions shape) according to `format`.
+///
+/// `tool` messages are left exactly as they are, so tool results stay linked
+/// to the assistant calls that produced them.
+pub(crate) fn apply_chat_format(format: &ChatFormat, messages: &mut Vec<Value>) {
+    match &format.system {
+        SystemMessageStrategy::Keep => {}
+        SystemMessageStrategy::DropSystem => {
+            messages.retain(|message| role(message) != Some("system"));
+        }
+        SystemMessageStrategy::SystemToFirstUser => {
+            if let Some(system) = take_system_text(messages) {
+                prepend_to_first_user(messages, format!("{system}\n\n"));
+            }
+        }
+        SystemMessageStrategy::PrependSystemTag(template) => {
+            if let Some(system) = take_system_text(messages) {
+                let tagged = if template.contains("{system}") {
+                    template.replace("{system}", &system)
+                } else {
+                    format!("{template}{system}")
+                };
+                prepend_to_first_user(messages, tagged);
+            }
+        }
+    }
+
+    if format.role_names.is_empty() {
+        return;
+    }
+    for message in messages.iter_mut() {
+        let Some(name) = role(message)
+            .filter(|role| *role != "tool")
+            .and_then(|role| format.role_names.get(role))
+        else {
+            continue;
+        };
+        message["role"] = Value::String(name.clone());
+    }
+}
+
+fn role(message: &Value) -> Option<&str> {
+    message.get("role").and_then(Value::as_str)
+}
+
+/// Remove every system message and return their text joined by blank lines.
+fn take_system_text(messages: &mut Vec<Value>) -> Option<String> {
+    let mut texts = Vec::new();
+    messages.retain(|message| {
+        if role(message) != Some("system") {
+            return true;
+        }
+        texts.push(content_text(message));
+        false
+    });
+    (!texts.is_empty()).then(|| texts.join("\n\n"))
+}
+
+fn content_text(message: &Value) -> String {
+    match message.get("content") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Put `prefix` at the start of the first user message, or send it as a
+/// leading user message when the conversation has none.
+fn prepend_to_first_user(messages: &mut Vec<Value>, prefix: String) {
+    let Some(user) = messages
+        .iter_mut()
+        .find(|message| role(message) == Some("user"))
+    else {
+        let text = prefix.trim_end().to_string();
+        messages.insert(0, serde_json::json!({ "role": "user", "content": text }));
+        return;
+    };
+    match user.get_mut("content") {
+        Some(Value::String(text)) => text.insert_str(0, &prefix),
+        Some(Value::Array(parts)) => {
+            parts.insert(0, serde_json::json!({ "type": "text", "text": prefix }));
+        }
+        _ => user["content"] = Value::String(prefix.trim_end().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn conversation() -> Vec<Value> {
+        vec![
+            json!({ "role": "system", "content": "Be terse." }),
+            json!({ "role": "user", "content": "What is 2+2?" }),
+            json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": { "name": "add", "arguments": "{\"a\":2,\"b\":2}" }
+                }]
+            }),
+            json!({ "role": "tool", "tool_call_id": "call_1", "content": "4" }),
+            json!({ "role": "user", "content": "Thanks" }),
+        ]
+    }
+
+    fn format(system: SystemMessageStrategy) -> ChatFormat {
+        ChatFormat {
+            system,
+            ..ChatFormat::default()
+        }
+    }
+
+    fn formatted(format: &ChatFormat) -> Vec<Value> {
+        let mut messages = conversation();
+        apply_chat_format(format, &mut messages);
+        messages
+    }
+
+    #[test]
+    fn default_format_sends_messages_unchanged() {
+        assert_eq!(formatted(&ChatFormat::default()), conversation());
+    }
+
+    #[test]
+    fn system_to_first_user_merges_into_the_first_user_turn_only() {
+        let messages = formatted(&format(SystemMessageStrategy::SystemToFirstUser));
+
+        let mut expected = conversation();
+        expected.remove(0);
+        expected[0]["content"] = json!("Be terse.\n\nWhat is 2+2?");
+        assert_eq!(messages, expected);
+    }
+
+    #[test]
+    fn drop_system_removes_system_messages_and_keeps_tool_results() {
+        let messages = formatted(&format(SystemMessageStrategy::DropSystem));
+
+        assert_eq!(messages, conversation()[1..].to_vec());
+    }
+
+    #[test]
+    fn prepend_system_tag_renders_the_template_into_the_first_user_turn() {
+        let messages = formatted(&format(SystemMessageStrategy::PrependSystemTag(
+            "<<SYS>>\n{system}\n<</SYS>>\n\n".to_string(),
+        )));
+
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0]["content"],
+            "<<SYS>>\nBe terse.\n<</SYS>>\n\nWhat is 2+2?"
+        );
+        assert_eq!(messages[2], conversation()[3]);
+
+        let untagged = formatted(&format(SystemMessageStrategy::PrependSystemTag(
+            "[system] ".to_string(),
+        )));
+        assert_eq!(untagged[0]["content"], "[system] Be terse.What is 2+2?");
+    }
+
+    #[test]
+    fn system_text_goes_into_multipart_user_content_as_a_leading_text_part() {
+        let mut messages = vec![
+            json!({ "role": "system", "content": "Describe images." }),
+            json!({ "role": "user", "content": [
+                { "type": "text", "text": "What is this?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,AAAA" } }
+            ] }),
+        ];
+
+        apply_chat_format(
+            &format(SystemMessageStrategy::SystemToFirstUser),
+            &mut messages,
+        );
+
+        assert_eq!(messages.len(), 1);
+        let parts = messages[0]["content"].as_array().expect("parts");
+        assert_eq!(
+            parts[0],
+            json!({ "type": "text", "text": "Describe images.\n\n" })
+        );
+        assert_eq!(parts.len(), 3);
+    }
+
+    #[test]
+    fn system_without_a_user_turn_becomes_a_user_message() {
+        let mut messages = vec![json!({ "role": "system", "content": "Only rules." })];
+
+        apply_chat_format(
+            &format(SystemMessageStrategy::SystemToFirstUser),
+            &mut messages,
+        );
+
+        assert_eq!(
+            messages,
+            vec![json!({ "role": "user", "content": "Only rules." })]
+        );
+    }
+
+    #[test]
+    fn role_names_remap_chat_roles_but_never_tool_results() {
+        let format = ChatFormat {
+            role_names: [
+                ("assistant".to_string(), "model".to_string()),
+                ("user".to_string(), "human".to_string()),
+                ("tool".to_string(), "function".to_string()),
+            ]
+            .into(),
+            ..ChatFormat::default()
+        };
+
+        let messages = formatted(&format);
+
+        let roles = messages
+            .iter()
+            .map(|message| message["role"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, vec!["system", "human", "model", "tool", "human"]);
+        assert_eq!(messages[2]["tool_calls"], conversation()[2]["tool_calls"]);
+        assert_eq!(messages[3], conversation()[3]);
+    }
+}
@@ -126,6 +126,7 @@ fn copilot_model_info(provider_key: &str, model_id: &str) -> ModelInfo {
         source: ModelCatalogSource::Dynamic {
             endpoint: "/models".to_string(),
         },
+        pricing: None,
         metadata: BTreeMap::new(),
     }
 }
@@ -1,4 +1,6 @@
-//! Google Gemini API provider.
+//! Google Gemini provider, served by the Gemini API or Vertex AI.
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
@@ -6,32 +8,124 @@ use futures::StreamExt;
 use serde::Deserialize;
 use tracing::debug;
 
+use crate::auth::google_service_account::ServiceAccountTokenSource;
 use crate::models::google::GoogleModel;
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::*;
 
-use roci_core::provider::http::shared_client;
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::provider::http::{shared_client, sse_events};
+use roci_core::provider::{
+    ModelProvider, ProviderRequest, ProviderResponse, ResponseToolCallIds, ToolLimits,
+};
 
-const BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+pub(crate) const BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Vertex AI host for `location`; `global` has no regional prefix.
+pub(crate) fn vertex_base_url(location: &str) -> String {
+    if location == "global" {
+        "https://aiplatform.googleapis.com".to_string()
+    } else {
+        format!("https://{location}-aiplatform.googleapis.com")
+    }
+}
+
+/// Vertex AI publisher-model URL for `method` (e.g. `streamGenerateContent`).
+pub(crate) fn vertex_model_url(location: &str, project: &str, model: &str, method: &str) -> String {
+    format!(
+        "{}/v1/projects/{project}/locations/{location}/publishers/google/models/{model}:{method}",
+        vertex_base_url(location)
+    )
+}
+
+enum GoogleEndpoint {
+    GeminiApi {
+        api_key: String,
+    },
+    Vertex {
+        project: String,
+        location: String,
+        tokens: Arc<ServiceAccountTokenSource>,
+    },
+}
 
 pub struct GoogleProvider {
     model: GoogleModel,
-    api_key: String,
+    endpoint: GoogleEndpoint,
     capabilities: ModelCapabilities,
 }
 
 impl GoogleProvider {
     pub fn new(model: GoogleModel, api_key: String) -> Self {
+        Self::with_endpoint(model, GoogleEndpoint::GeminiApi { api_key })
+    }
+
+    /// Serve `model` from Vertex AI in `project`/`location`, authenticating
+    /// with service-account tokens from `tokens`.
+    pub fn vertex(
+        model: GoogleModel,
+        project: impl Into<String>,
+        location: impl Into<String>,
+        tokens: Arc<ServiceAccountTokenSource>,
+    ) -> Self {
+        Self::with_endpoint(
+            model,
+            GoogleEndpoint::Vertex {
+                project: project.into(),
+                location: location.into(),
+                tokens,
+            },
+        )
+    }
+
+    fn with_endpoint(model: GoogleModel, endpoint: GoogleEndpoint) -> Self {
         let capabilities = model.capabilities();
         Self {
             model,
-            api_key,
+            endpoint,
             capabilities,
         }
     }
 
+    /// Authenticated POST for `method`; `stream` requests SSE framing.
+    async fn post(&self, method: &str, stream: bool) -> Result<reqwest::RequestBuilder, RociError> {
+        let sse = if stream { "alt=sse&" } else { "" };
+        match &self.endpoint {
+            GoogleEndpoint::GeminiApi { api_key } => {
+                let url = format!(
+                    "{BASE_URL}/models/{}:{method}?{sse}key={api_key}",
+                    self.api_model_id()
+                );
+                Ok(shared_client().post(url))
+            }
+            GoogleEndpoint::Vertex {
+                project,
+                location,
+                tokens,
+            } => {
+                let mut url = vertex_model_url(location, project, self.api_model_id(), method);
+                if stream {
+                    url.push_str("?alt=sse");
+                }
+                let token = tokens.access_token().await?;
+                Ok(shared_client().post(url).bearer_auth(token))
+            }
+        }
+    }
+
+    fn status_error(&self, status: u16, body: &str) -> RociError {
+        match &self.endpoint {
+            GoogleEndpoint::GeminiApi { .. } => {
+                roci_core::provider::http::status_to_error(status, body)
+            }
+            GoogleEndpoint::Vertex {
+                project,
+                location,
+                tokens,
+            } => vertex_status_error(status, body, project, location, tokens.client_email()),
+        }
+    }
+
     fn api_model_id(&self) -> &str {
         match self.model {
             GoogleModel::Gemini3Flash => "gemini-3-flash-preview",
@@ -64,7 +158,7 @@ impl GoogleProvider {
         let mut contents = Vec::new();
         let mut tool_name_map = std::collections::HashMap::new();
 
-        for msg in &request.messages {
+        for msg in request.messages.iter() {
             for part in &msg.content {
                 if let ContentPart::ToolCall(tc) = part {
                     tool_name_map.insert(tc.id.clone(), tc.name.clone());
@@ -72,7 +166,7 @@ impl GoogleProvider {
             }
         }
 
-        for msg in &request.messages {
+        for msg in request.messages.iter() {
             match msg.role {
                 Role::System => {
                     system_instruction_parts.push(serde_json::json!({ "text": msg.text() }));
@@ -277,89 +371,35 @@ impl ModelProvider for GoogleProvider {
         &self.capabilities
     }
 
+    fn tool_limits(&self) -> ToolLimits {
+        ToolLimits::GOOGLE
+    }
+
     async fn generate_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
         self.validate_settings(&request.settings)?;
         let body = self.build_request_body(request);
-        let url = format!(
-            "{}/models/{}:generateContent?key={}",
-            BASE_URL,
-            self.api_model_id(),
-            self.api_key
-        );
 
         debug!(model = self.model.as_str(), "Google generate_text");
 
-        let resp = shared_client().post(&url).json(&body).send().await?;
+        let resp = self
+            .post("generateContent", false)
+            .await?
+            .json(&body)
+            .send()
+            .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
             let body_text = resp.text().await.unwrap_or_default();
-            return Err(roci_core::provider::http::status_to_error(
-                status, &body_text,
-            ));
+            return Err(self.status_error(status, &body_text));
         }
 
         let data: GeminiResponse = resp.json().await?;
-
-        let candidate = data
-            .candidates
-            .into_iter()
-            .next()
-            .ok_or_else(|| RociError::api(200, "No candidates in Gemini response"))?;
-
-        let mut text = String::new();
-        let mut tool_calls = Vec::new();
-
-        for part in candidate.content.parts {
-            let GeminiPart {
-                text: part_text,
-                function_call,
-                thought_signature,
-            } = part;
-            if let Some(t) = part_text {
-                text.push_str(&t);
-            }
-            if let Some(fc) = function_call {
-                let id = fc.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-                tool_calls.push(AgentToolCall {
-                    id,
-                    name: fc.name,
-                    arguments: fc
-                        .args
-                        .unwrap_or(serde_json::Value::Object(Default::default())),
-                    called_as: None,
-                    recipient: thought_signature,
-                });
-            }
-        }
-
-        let finish_reason = match candidate.finish_reason.as_deref() {
-            Some("STOP") => Some(FinishReason::Stop),
-            Some("MAX_TOKENS") => Some(FinishReason::Length),
-            Some("SAFETY") => Some(FinishReason::ContentFilter),
-            _ => None,
-        };
-
-        let usage = data
-            .usage_metadata
-            .map(|u| Usage {
-                input_tokens: u.prompt_token_count,
-                output_tokens: u.candidates_token_count,
-                total_tokens: u.total_token_count,
-                ..Default::default()
-            })
-            .unwrap_or_default();
-
-        Ok(ProviderResponse {
-            text,
-            usage,
-            tool_calls,
-            finish_reason,
-            thinking: Vec::new(),
-        })
+        let mut call_ids = request.begin_tool_call_ids();
+        parse_gemini_response(data, &mut call_ids)
     }
 
     async fn stream_text(
@@ -368,101 +408,89 @@ impl ModelProvider for GoogleProvider {
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
         self.validate_settings(&request.settings)?;
         let body = self.build_request_body(request);
-        let url = format!(
-            "{}/models/{}:streamGenerateContent?alt=sse&key={}",
-            BASE_URL,
-            self.api_model_id(),
-            self.api_key
-        );
 
         debug!(model = self.model.as_str(), "Google stream_text");
 
-        let resp = shared_client().post(&url).json(&body).send().await?;
+        let resp = self
+            .post("streamGenerateContent", true)
+            .await?
+            .json(&body)
+            .send()
+            .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
             let body_text = resp.text().await.unwrap_or_default();
-            return Err(roci_core::provider::http::status_to_error(
-                status, &body_text,
-            ));
+            return Err(self.status_error(status, &body_text));
         }
 
-        let byte_stream = resp.bytes_stream();
+        let events = sse_events(resp.bytes_stream());
+        let mut call_ids = request.begin_tool_call_ids();
 
         let stream = async_stream::stream! {
-            let mut buffer = String::new();
             let mut saw_tool_call = false;
             let mut finish_reason: Option<FinishReason> = None;
             let mut usage: Option<Usage> = None;
-            futures::pin_mut!(byte_stream);
+            futures::pin_mut!(events);
 
-            while let Some(chunk_result) = byte_stream.next().await {
-                let chunk = match chunk_result {
-                    Ok(c) => c,
+            while let Some(event) = events.next().await {
+                let event = match event {
+                    Ok(event) => event,
                     Err(e) => {
                         yield Err(RociError::Network(e));
                         return;
                     }
                 };
 
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let line = buffer[..line_end].trim().to_string();
-                    buffer = buffer[line_end + 1..].to_string();
-
-                    if let Some(data) = roci_core::provider::http::parse_sse_data(&line) {
-                        if let Ok(resp) = serde_json::from_str::<GeminiResponse>(data) {
-                            let GeminiResponse { candidates, usage_metadata } = resp;
-                            if let Some(candidate) = candidates.into_iter().next() {
-                                for part in candidate.content.parts {
-                                    let GeminiPart { text: part_text, function_call, thought_signature } = part;
-                                    if let Some(call) = function_call {
-                                        saw_tool_call = true;
-                                        let id = call.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-                                        let args = call.args.unwrap_or(serde_json::Value::Object(Default::default()));
-                                        yield Ok(TextStreamDelta {
-                                            text: String::new(),
-                                            event_type: StreamEventType::ToolCallDelta,
-                                            tool_call: Some(AgentToolCall { id, name: call.name, arguments: args, called_as: None, recipient: thought_signature }),
-                                            finish_reason: None,
-                                            usage: None,
-                                            reasoning: None,
-                                            reasoning_signature: None,
-                                            reasoning_type: None,
-                                        });
-                                    }
-                                    if let Some(t) = part_text {
-                                        yield Ok(TextStreamDelta {
-                                            text: t,
-                                            event_type: StreamEventType::TextDelta,
-                                            tool_call: None,
-                                            finish_reason: None,
-                                            usage: None,
-                                            reasoning: None,
-                                            reasoning_signature: None,
-                                            reasoning_type: None,
-                                        });
-                                    }
-                                }
-                                if let Some(reason) = candidate.finish_reason.as_deref() {
-                                    finish_reason = match reason {
-                                        "STOP" => Some(FinishReason::Stop),
-                                        "MAX_TOKENS" => Some(FinishReason::Length),
-                                        "SAFETY" => Some(FinishReason::ContentFilter),
-                                        _ => finish_reason,
-                                    };
-                                }
+                if let Ok(resp) = serde_json::from_str::<GeminiResponse>(&event.data) {
+                    let GeminiResponse { candidates, usage_metadata } = resp;
+                    if let Some(candidate) = candidates.into_iter().next() {
+                        for part in candidate.content.parts {
+                            let GeminiPart { text: part_text, function_call, inline_data, thought_signature } = part;
+                            if let Some(call) = function_call {
+                                saw_tool_call = true;
+                                yield Ok(TextStreamDelta {
+                                    text: String::new(),
+                                    event_type: StreamEventType::ToolCallDelta,
+                                    tool_call: Some(call.into_tool_call(thought_signature, &mut call_ids)),
+                                    finish_reason: None,
+                                    usage: None,
+                                    reasoning: None,
+                                    reasoning_signature: None,
+                                    reasoning_type: None,
+                                    image: None,
+                                    response_metadata: None,
+                                });
+                            }
+                            if let Some(image) = inline_data.and_then(GeminiInlineData::into_image) {
+                                yield Ok(TextStreamDelta::image(image));
                             }
-                            if let Some(meta) = usage_metadata {
-                                usage = Some(Usage {
-                                    input_tokens: meta.prompt_token_count,
-                                    output_tokens: meta.candidates_token_count,
-                                    total_tokens: meta.total_token_count,
-                                    ..Default::default()
+                            if let Some(t) = part_text {
+                                yield Ok(TextStreamDelta {
+                                    text: t,
+                                    event_type: StreamEventType::TextDelta,
+                                    tool_call: None,
+                                    finish_reason: None,
+                                    usage: None,
+                                    reasoning: None,
+                                    reasoning_signature: None,
+                                    reasoning_type: None,
+                                    image: None,
+                                    response_metadata: None,
                                 });
                             }
                         }
+                        if let Some(reason) = candidate.finish_reason.as_deref() {
+                            finish_reason = Some(parse_finish_reason(reason));
+                        }
+                    }
+                    if let Some(meta) = usage_metadata {
+                        usage = Some(Usage {
+                            input_tokens: meta.prompt_token_count,
+                            output_tokens: meta.candidates_token_count,
+                            total_tokens: meta.total_token_count,
+                            ..Default::default()
+                        });
                     }
                 }
             }
@@ -477,6 +505,8 @@ impl ModelProvider for GoogleProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             });
         };
 
@@ -484,6 +514,77 @@ impl ModelProvider for GoogleProvider {
     }
 }
 
+/// Vertex reports bad projects and missing IAM grants as 403/404 with a
+/// `google.rpc.ErrorInfo` reason; surface those as configuration and
+/// permission errors instead of generic API failures.
+fn vertex_status_error(
+    status: u16,
+    body: &str,
+    project: &str,
+    location: &str,
+    client_email: &str,
+) -> RociError {
+    let error = serde_json::from_str::<VertexErrorBody>(body)
+        .map(|body| body.error)
+        .unwrap_or_default();
+    let reasons = error
+        .details
+        .iter()
+        .filter_map(|detail| detail.reason.as_deref())
+        .collect::<Vec<_>>();
+    let message = if error.message.is_empty() {
+        body
+    } else {
+        error.message.as_str()
+    };
+
+    if status == 404 && message.contains("Publisher Model") {
+        return RociError::ModelNotFound(message.to_string());
+    }
+    let bad_project = status == 404
+        || reasons
+            .iter()
+            .any(|reason| matches!(*reason, "CONSUMER_INVALID" | "SERVICE_DISABLED"));
+    if bad_project {
+        return RociError::Configuration(format!(
+            "Vertex AI project '{project}' in location '{location}' is not usable \
+             (check GOOGLE_VERTEX_PROJECT, GOOGLE_VERTEX_LOCATION, and that the Vertex AI API \
+             is enabled): {message}"
+        ));
+    }
+    if status == 403
+        && (error.status == "PERMISSION_DENIED" || reasons.contains(&"IAM_PERMISSION_DENIED"))
+    {
+        return RociError::Authentication(format!(
+            "service account {client_email} lacks permission on Vertex AI project '{project}' \
+             (grant roles/aiplatform.user): {message}"
+        ));
+    }
+    roci_core::provider::http::status_to_error(status, body)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VertexErrorBody {
+    #[serde(default)]
+    error: VertexError,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VertexError {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    details: Vec<VertexErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VertexErrorDetail {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
 fn build_gemini_parts(content: &[ContentPart]) -> Vec<serde_json::Value> {
     content
         .iter()
@@ -500,6 +601,77 @@ fn build_gemini_parts(content: &[ContentPart]) -> Vec<serde_json::Value> {
         .collect()
 }
 
+/// Map a candidate `finishReason`, keeping undocumented values.
+fn parse_finish_reason(reason: &str) -> FinishReason {
+    match reason {
+        "STOP" => FinishReason::Stop,
+        "MAX_TOKENS" => FinishReason::Length,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY" => {
+            FinishReason::ContentFilter
+        }
+        "MALFORMED_FUNCTION_CALL" | "UNEXPECTED_TOOL_CALL" => FinishReason::Error,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// Convert a `generateContent` response into a [`ProviderResponse`].
+fn parse_gemini_response(
+    data: GeminiResponse,
+    call_ids: &mut ResponseToolCallIds,
+) -> Result<ProviderResponse, RociError> {
+    let candidate = data
+        .candidates
+        .into_iter()
+        .next()
+        .ok_or_else(|| RociError::api(200, "No candidates in Gemini response"))?;
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut images = Vec::new();
+
+    for part in candidate.content.parts {
+        let GeminiPart {
+            text: part_text,
+            function_call,
+            inline_data,
+            thought_signature,
+        } = part;
+        if let Some(t) = part_text {
+            text.push_str(&t);
+        }
+        if let Some(fc) = function_call {
+            tool_calls.push(fc.into_tool_call(thought_signature, call_ids));
+        }
+        if let Some(image) = inline_data.and_then(GeminiInlineData::into_image) {
+            images.push(image);
+        }
+    }
+
+    let finish_reason = candidate.finish_reason.as_deref().map(parse_finish_reason);
+
+    let usage = data
+        .usage_metadata
+        .map(|u| Usage {
+            input_tokens: u.prompt_token_count,
+            output_tokens: u.candidates_token_count,
+            total_tokens: u.total_token_count,
+            ..Default::default()
+        })
+        .unwrap_or_default();
+
+    Ok(ProviderResponse {
+        text,
+        usage,
+        tool_calls,
+        finish_reason,
+        thinking: Vec::new(),
+        metadata: Default::default(),
+        refusal: None,
+        images,
+        response_metadata: None,
+    })
+}
+
 // Internal Gemini response types
 
 #[derive(Deserialize)]
@@ -526,9 +698,31 @@ struct GeminiContent {
 struct GeminiPart {
     text: Option<String>,
     function_call: Option<GeminiFunctionCall>,
+    inline_data: Option<GeminiInlineData>,
     thought_signature: Option<String>,
 }
 
+/// Inline media in a response part, such as an image from a Gemini image
+/// model.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+    mime_type: String,
+    data: String,
+}
+
+impl GeminiInlineData {
+    /// Image content for `image/*` parts; other media is ignored.
+    fn into_image(self) -> Option<ImageContent> {
+        self.mime_type
+            .starts_with("image/")
+            .then_some(ImageContent {
+                data: self.data,
+                mime_type: self.mime_type,
+            })
+    }
+}
+
 #[derive(Deserialize)]
 struct GeminiFunctionCall {
     #[serde(default)]
@@ -537,6 +731,25 @@ struct GeminiFunctionCall {
     args: Option<serde_json::Value>,
 }
 
+impl GeminiFunctionCall {
+    /// Gemini often omits call IDs; `call_ids` fills them in.
+    fn into_tool_call(
+        self,
+        thought_signature: Option<String>,
+        call_ids: &mut ResponseToolCallIds,
+    ) -> AgentToolCall {
+        AgentToolCall {
+            id: call_ids.assign(self.id.as_deref(), &self.name),
+            name: self.name,
+            arguments: self
+                .args
+                .unwrap_or(serde_json::Value::Object(Default::default())),
+            called_as: None,
+            recipient: thought_signature,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiUsage {
@@ -573,11 +786,31 @@ mod tests {
             user: None,
             anthropic: None,
             google: None,
+            mistral: None,
+            grok: None,
+            groq: None,
+            assistant_prefix: None,
             tool_choice: None,
             stream_idle_timeout_ms: None,
         }
     }
 
+    #[test]
+    fn finish_reasons_map_known_values_and_keep_the_rest() {
+        let cases = [
+            ("STOP", FinishReason::Stop),
+            ("MAX_TOKENS", FinishReason::Length),
+            ("SAFETY", FinishReason::ContentFilter),
+            ("RECITATION", FinishReason::ContentFilter),
+            ("PROHIBITED_CONTENT", FinishReason::ContentFilter),
+            ("MALFORMED_FUNCTION_CALL", FinishReason::Error),
+            ("LANGUAGE", FinishReason::Other("LANGUAGE".to_string())),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_finish_reason(raw), expected, "{raw}");
+        }
+    }
+
     #[test]
     fn build_request_body_includes_thought_signature_and_call_id() {
         let provider =
@@ -597,7 +830,7 @@ mod tests {
             metadata: None,
         }];
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -607,6 +840,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -638,7 +872,7 @@ mod tests {
             ModelMessage::tool_result("call_1", serde_json::json!({"temp": 18}), false),
         ];
         let request = ProviderRequest {
-            messages,
+            messages: messages.into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -648,6 +882,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -661,6 +896,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inline_image_parts_become_response_images() {
+        let data: GeminiResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": { "parts": [
+                    { "text": "A red square:" },
+                    { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } },
+                    { "inlineData": { "mimeType": "application/pdf", "data": "JVBERi0=" } }
+                ]},
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+        let allocator = roci_core::provider::ToolCallIdAllocator::new("run1").for_iteration(1);
+
+        let response = parse_gemini_response(data, &mut allocator.begin_response()).unwrap();
+
+        assert_eq!(response.text, "A red square:");
+        assert_eq!(
+            response.images,
+            vec![ImageContent {
+                data: "iVBORw0KGgo=".to_string(),
+                mime_type: "image/png".to_string(),
+            }]
+        );
+        assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+    }
+
+    #[test]
+    fn function_calls_without_ids_get_stable_run_unique_ids() {
+        let parts: Vec<GeminiPart> = serde_json::from_value(serde_json::json!([
+            { "functionCall": { "name": "read", "args": { "path": "a" } } },
+            { "functionCall": { "name": "read", "args": { "path": "b" } } }
+        ]))
+        .unwrap();
+        let allocator = roci_core::provider::ToolCallIdAllocator::new("run1").for_iteration(2);
+        let parse = || {
+            let mut call_ids = allocator.begin_response();
+            parts
+                .iter()
+                .map(|part| {
+                    let call = part.function_call.as_ref().unwrap();
+                    GeminiFunctionCall {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        args: call.args.clone(),
+                    }
+                    .into_tool_call(None, &mut call_ids)
+                    .id
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = parse();
+        let retried = parse();
+
+        assert_eq!(first, vec!["call_run1_2_0_read", "call_run1_2_1_read"]);
+        assert_eq!(retried, first);
+    }
+
     #[test]
     fn build_request_body_concatenates_system_messages_in_order() {
         let provider =
@@ -670,7 +965,8 @@ mod tests {
                 ModelMessage::system("base system"),
                 ModelMessage::system("<available_tools>tool metadata</available_tools>"),
                 ModelMessage::user("hello"),
-            ],
+            ]
+            .into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -680,6 +976,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -700,7 +997,7 @@ mod tests {
         let provider =
             GoogleProvider::new(GoogleModel::Gemini3FlashPreview, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("Return JSON")],
+            messages: vec![ModelMessage::user("Return JSON")].into(),
             settings: GenerationSettings {
                 response_format: Some(ResponseFormat::JsonSchema {
                     schema: serde_json::json!({
@@ -727,6 +1024,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -741,7 +1039,7 @@ mod tests {
         let provider =
             GoogleProvider::new(GoogleModel::Gemini3FlashPreview, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: settings(None, None, None, None),
             tools: None,
             response_format: None,
@@ -751,6 +1049,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -767,7 +1066,7 @@ mod tests {
         let provider =
             GoogleProvider::new(GoogleModel::Gemini3FlashPreview, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: settings(Some(1200), Some(0.3), Some(0.5), Some(12)),
             tools: None,
             response_format: None,
@@ -777,6 +1076,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -792,7 +1092,7 @@ mod tests {
     fn build_request_body_includes_thinking_config() {
         let provider = GoogleProvider::new(GoogleModel::Gemini25Pro, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 reasoning_effort: Some(ReasoningEffort::None),
                 google: Some(GoogleOptions {
@@ -813,6 +1113,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         assert!(provider.validate_settings(&request.settings).is_ok());
         let body = provider.build_request_body(&request);
@@ -831,7 +1132,7 @@ mod tests {
         let provider =
             GoogleProvider::new(GoogleModel::Gemini3FlashPreview, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 google: Some(GoogleOptions {
                     thinking_config: Some(GoogleThinkingConfig {
@@ -851,6 +1152,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         assert_eq!(
@@ -863,7 +1165,7 @@ mod tests {
     fn build_request_body_maps_generic_reasoning_effort_to_gemini_thinking_level() {
         let provider = GoogleProvider::new(GoogleModel::Gemini3Flash, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 reasoning_effort: Some(ReasoningEffort::Minimal),
                 ..Default::default()
@@ -876,6 +1178,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -890,7 +1193,7 @@ mod tests {
     fn build_request_body_maps_gemini_2_5_flash_none_to_zero_thinking_budget() {
         let provider = GoogleProvider::new(GoogleModel::Gemini25Flash, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 reasoning_effort: Some(ReasoningEffort::None),
                 ..Default::default()
@@ -903,6 +1206,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -917,7 +1221,7 @@ mod tests {
     async fn generation_and_streaming_reject_unsupported_gemini_2_5_pro_none_effort() {
         let provider = GoogleProvider::new(GoogleModel::Gemini25Pro, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 reasoning_effort: Some(ReasoningEffort::None),
                 ..Default::default()
@@ -930,6 +1234,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let generate_error = provider
@@ -950,7 +1255,7 @@ mod tests {
         let provider =
             GoogleProvider::new(GoogleModel::Gemini3FlashPreview, "test-key".to_string());
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings {
                 google: Some(GoogleOptions {
                     safety_settings: Some(GoogleSafetyLevel::Moderate),
@@ -966,6 +1271,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request);
         let settings = body["safetySettings"].as_array().unwrap();
@@ -992,7 +1298,8 @@ mod tests {
                 name: None,
                 timestamp: None,
                 metadata: None,
-            }],
+            }]
+            .into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1002,6 +1309,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -1033,7 +1341,8 @@ mod tests {
                 name: None,
                 timestamp: None,
                 metadata: None,
-            }],
+            }]
+            .into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1043,6 +1352,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -1053,4 +1363,96 @@ mod tests {
         assert_eq!(text, marker);
         assert!(!text.contains("/tmp/"));
     }
+
+    #[test]
+    fn vertex_model_url_uses_regional_hosts() {
+        for location in ["us-central1", "europe-west4", "asia-northeast1"] {
+            assert_eq!(
+                vertex_model_url(location, "my-project", "gemini-2.5-pro", "streamGenerateContent"),
+                format!(
+                    "https://{location}-aiplatform.googleapis.com/v1/projects/my-project/locations/{location}/publishers/google/models/gemini-2.5-pro:streamGenerateContent"
+                )
+            );
+        }
+        assert_eq!(
+            vertex_model_url("global", "my-project", "gemini-2.5-flash", "generateContent"),
+            "https://aiplatform.googleapis.com/v1/projects/my-project/locations/global/publishers/google/models/gemini-2.5-flash:generateContent"
+        );
+    }
+
+    fn vertex_error(status: u16, body: serde_json::Value) -> RociError {
+        vertex_status_error(
+            status,
+            &body.to_string(),
+            "my-project",
+            "us-central1",
+            "roci@my-project.iam.gserviceaccount.com",
+        )
+    }
+
+    #[test]
+    fn vertex_iam_denial_is_an_authentication_error_naming_the_account() {
+        let err = vertex_error(
+            403,
+            serde_json::json!({ "error": {
+                "code": 403,
+                "message": "Permission 'aiplatform.endpoints.predict' denied",
+                "status": "PERMISSION_DENIED",
+                "details": [{
+                    "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+                    "reason": "IAM_PERMISSION_DENIED"
+                }]
+            }}),
+        );
+
+        let RociError::Authentication(message) = err else {
+            panic!("expected authentication error, got {err:?}");
+        };
+        assert!(message.contains("roci@my-project.iam.gserviceaccount.com"));
+        assert!(message.contains("roles/aiplatform.user"));
+    }
+
+    #[test]
+    fn vertex_unknown_project_is_a_configuration_error() {
+        let consumer_invalid = vertex_error(
+            403,
+            serde_json::json!({ "error": {
+                "code": 403,
+                "message": "Permission denied on resource project my-project.",
+                "status": "PERMISSION_DENIED",
+                "details": [{ "reason": "CONSUMER_INVALID" }]
+            }}),
+        );
+        let not_found = vertex_error(
+            404,
+            serde_json::json!({ "error": {
+                "code": 404,
+                "message": "Project not found",
+                "status": "NOT_FOUND"
+            }}),
+        );
+
+        for err in [consumer_invalid, not_found] {
+            assert!(
+                matches!(err, RociError::Configuration(ref message) if message.contains("'my-project'")),
+                "unexpected error: {err:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn vertex_unknown_model_and_other_statuses_keep_generic_mapping() {
+        let missing_model = vertex_error(
+            404,
+            serde_json::json!({ "error": {
+                "code": 404,
+                "message": "Publisher Model `gemini-9` was not found",
+                "status": "NOT_FOUND"
+            }}),
+        );
+        assert!(matches!(missing_model, RociError::ModelNotFound(_)));
+
+        let rate_limited = vertex_error(429, serde_json::json!({ "error": { "code": 429 } }));
+        assert!(matches!(rate_limited, RociError::RateLimited { .. }));
+    }
 }
@@ -5,7 +5,9 @@ use futures::stream::BoxStream;
 
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
-use roci_core::types::TextStreamDelta;
+use roci_core::types::{
+    ContentPart, GrokSearchMode, GrokSearchParameters, GrokSearchSource, TextStreamDelta,
+};
 
 use super::openai::OpenAiProvider;
 use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
@@ -13,6 +15,12 @@ use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
 use crate::models::grok::GrokModel;
 use crate::models::openai::OpenAiModel;
 
+pub(crate) const BASE_URL: &str = "https://api.x.ai/v1";
+
+/// xAI limits for live search source filters.
+const MAX_SOURCE_WEBSITES: usize = 5;
+const MAX_SOURCE_X_HANDLES: usize = 10;
+
 pub struct GrokProvider {
     inner: OpenAiProvider,
     capabilities: ModelCapabilities,
@@ -20,18 +28,176 @@ pub struct GrokProvider {
 
 impl GrokProvider {
     pub fn new(model: GrokModel, api_key: String) -> Self {
+        Self::with_base_url(model, api_key, BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(model: GrokModel, api_key: String, base_url: String) -> Self {
         let capabilities = model.capabilities();
         let openai_model = OpenAiModel::Custom(model.as_str().to_string());
         Self {
-            inner: OpenAiProvider::new(
-                openai_model,
-                api_key,
-                Some("https://api.x.ai/v1".to_string()),
-                None,
-            ),
+            inner: OpenAiProvider::new(openai_model, api_key, Some(base_url), None)
+                .with_body_hook(apply_grok_options),
             capabilities,
         }
     }
+
+    fn validate_request(&self, request: &ProviderRequest) -> Result<(), RociError> {
+        if let Some(search) = request
+            .settings
+            .grok
+            .as_ref()
+            .and_then(|options| options.search.as_ref())
+        {
+            validate_search(search, request)?;
+        }
+        self.validate_images(request)
+    }
+
+    /// Check image parts against the model's advertised image limits.
+    fn validate_images(&self, request: &ProviderRequest) -> Result<(), RociError> {
+        let images = request
+            .messages
+            .iter()
+            .flat_map(|message| message.content.iter())
+            .filter_map(|part| match part {
+                ContentPart::Image(image) => Some(image),
+                _ => None,
+            });
+        for image in images {
+            let Some(limits) = self.capabilities.input.image.as_ref() else {
+                return Err(RociError::InvalidArgument(format!(
+                    "Grok model {} does not accept image input",
+                    self.model_id()
+                )));
+            };
+            if !limits
+                .supported_mime_types
+                .iter()
+                .any(|mime| mime.eq_ignore_ascii_case(&image.mime_type))
+            {
+                return Err(RociError::InvalidArgument(format!(
+                    "Grok does not accept {} images (supported: {})",
+                    image.mime_type,
+                    limits.supported_mime_types.join(", ")
+                )));
+            }
+            let bytes = decoded_base64_len(&image.data);
+            if let Some(max) = limits.max_image_bytes.filter(|max| bytes > *max) {
+                return Err(RociError::InvalidArgument(format!(
+                    "Grok image is {bytes} bytes, over the {max} byte limit"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn decoded_base64_len(data: &str) -> usize {
+    let padding = data.bytes().rev().take_while(|byte| *byte == b'=').count();
+    (data.len() / 4 * 3).saturating_sub(padding)
+}
+
+/// Reject live search settings the xAI API refuses.
+fn validate_search(
+    search: &GrokSearchParameters,
+    request: &ProviderRequest,
+) -> Result<(), RociError> {
+    let has_tools = request
+        .tools
+        .as_ref()
+        .is_some_and(|tools| !tools.is_empty());
+    if search.mode != GrokSearchMode::Off && has_tools {
+        return Err(RociError::InvalidArgument(
+            "Grok live search cannot be combined with tools; set the search mode to off or send the request without tools".to_string(),
+        ));
+    }
+    if let (Some(from), Some(to)) = (search.from_date, search.to_date) {
+        if from > to {
+            return Err(RociError::InvalidArgument(format!(
+                "Grok live search from_date {from} is after to_date {to}"
+            )));
+        }
+    }
+    for source in &search.sources {
+        match source {
+            GrokSearchSource::Web {
+                allowed_websites,
+                excluded_websites,
+                ..
+            } => {
+                if !allowed_websites.is_empty() && !excluded_websites.is_empty() {
+                    return Err(RociError::InvalidArgument(
+                        "Grok web search source cannot set both allowed_websites and excluded_websites".to_string(),
+                    ));
+                }
+                check_source_limit("allowed_websites", allowed_websites, MAX_SOURCE_WEBSITES)?;
+                check_source_limit("excluded_websites", excluded_websites, MAX_SOURCE_WEBSITES)?;
+            }
+            GrokSearchSource::X {
+                included_x_handles,
+                excluded_x_handles,
+                ..
+            } => {
+                if !included_x_handles.is_empty() && !excluded_x_handles.is_empty() {
+                    return Err(RociError::InvalidArgument(
+                        "Grok X search source cannot set both included_x_handles and excluded_x_handles".to_string(),
+                    ));
+                }
+                check_source_limit(
+                    "included_x_handles",
+                    included_x_handles,
+                    MAX_SOURCE_X_HANDLES,
+                )?;
+                check_source_limit(
+                    "excluded_x_handles",
+                    excluded_x_handles,
+                    MAX_SOURCE_X_HANDLES,
+                )?;
+            }
+            GrokSearchSource::News {
+                excluded_websites, ..
+            } => {
+                check_source_limit("excluded_websites", excluded_websites, MAX_SOURCE_WEBSITES)?;
+            }
+            GrokSearchSource::Rss { links } => {
+                if links.len() != 1 {
+                    return Err(RociError::InvalidArgument(format!(
+                        "Grok RSS search source takes exactly one link, got {}",
+                        links.len()
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_source_limit(field: &str, values: &[String], max: usize) -> Result<(), RociError> {
+    if values.len() > max {
+        return Err(RociError::InvalidArgument(format!(
+            "Grok search source {field} accepts at most {max} entries, got {}",
+            values.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Add Grok-only fields: live search `search_parameters`.
+fn apply_grok_options(
+    request: &ProviderRequest,
+    body: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let Some(search) = request
+        .settings
+        .grok
+        .as_ref()
+        .and_then(|options| options.search.as_ref())
+    else {
+        return;
+    };
+    if let Ok(value) = serde_json::to_value(search) {
+        body.insert("search_parameters".into(), value);
+    }
 }
 
 #[async_trait]
@@ -52,6 +218,7 @@ impl ModelProvider for GrokProvider {
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
+        self.validate_request(request)?;
         self.inner.generate_text(request).await
     }
 
@@ -59,6 +226,7 @@ impl ModelProvider for GrokProvider {
         &self,
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        self.validate_request(request)?;
         self.inner.stream_text(request).await
     }
 }
@@ -66,13 +234,325 @@ impl ModelProvider for GrokProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDate;
+    use roci_core::provider::ToolDefinition;
+    use roci_core::types::{GenerationSettings, GrokOptions, ImageContent, ModelMessage, Role};
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn request(messages: Vec<ModelMessage>, settings: GenerationSettings) -> ProviderRequest {
+        ProviderRequest {
+            messages: messages.into(),
+            settings,
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    fn search_settings(search: GrokSearchParameters) -> GenerationSettings {
+        GenerationSettings {
+            grok: Some(GrokOptions {
+                search: Some(search),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn image_message(mime_type: &str, data: String) -> ModelMessage {
+        ModelMessage {
+            role: Role::User,
+            content: vec![
+                ContentPart::Text {
+                    text: "what is this?".to_string(),
+                },
+                ContentPart::Image(ImageContent {
+                    data,
+                    mime_type: mime_type.to_string(),
+                }),
+            ],
+            name: None,
+            timestamp: None,
+            metadata: None,
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
 
     #[test]
-    fn grok_3_provider_supports_image_input() {
-        let provider = GrokProvider::new(GrokModel::Grok3, String::new());
+    fn grok_4_provider_supports_image_input() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
         let caps = provider.capabilities();
 
         assert!(caps.input.image.is_some());
         assert_eq!(caps.supports_vision, caps.input.image.is_some());
     }
+
+    #[test]
+    fn grok_3_provider_is_text_only() {
+        let provider = GrokProvider::new(GrokModel::Grok3, String::new());
+        let caps = provider.capabilities();
+
+        assert!(!caps.supports_vision);
+        assert!(caps.input.image.is_none());
+    }
+
+    #[test]
+    fn search_parameters_serialize_minimal_mode() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let request = request(
+            vec![ModelMessage::user("news?")],
+            search_settings(GrokSearchParameters::new(GrokSearchMode::Auto)),
+        );
+
+        let body = provider.inner.build_request_body(&request, false);
+
+        assert_eq!(
+            body["search_parameters"],
+            serde_json::json!({ "mode": "auto" })
+        );
+    }
+
+    #[test]
+    fn search_parameters_serialize_dates_citations_and_sources() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let request = request(
+            vec![ModelMessage::user("news?")],
+            search_settings(GrokSearchParameters {
+                return_citations: Some(true),
+                from_date: Some(date("2025-01-01")),
+                to_date: Some(date("2025-06-30")),
+                max_search_results: Some(10),
+                sources: vec![
+                    GrokSearchSource::Web {
+                        country: Some("CH".to_string()),
+                        allowed_websites: Vec::new(),
+                        excluded_websites: vec!["example.com".to_string()],
+                        safe_search: Some(false),
+                    },
+                    GrokSearchSource::X {
+                        included_x_handles: vec!["xai".to_string()],
+                        excluded_x_handles: Vec::new(),
+                        post_favorite_count: Some(100),
+                        post_view_count: None,
+                    },
+                    GrokSearchSource::News {
+                        country: None,
+                        excluded_websites: Vec::new(),
+                        safe_search: None,
+                    },
+                    GrokSearchSource::Rss {
+                        links: vec!["https://status.x.ai/feed.xml".to_string()],
+                    },
+                ],
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            }),
+        );
+
+        let body = provider.inner.build_request_body(&request, true);
+
+        assert_eq!(
+            body["search_parameters"],
+            serde_json::json!({
+                "mode": "on",
+                "return_citations": true,
+                "from_date": "2025-01-01",
+                "to_date": "2025-06-30",
+                "max_search_results": 10,
+                "sources": [
+                    {
+                        "type": "web",
+                        "country": "CH",
+                        "excluded_websites": ["example.com"],
+                        "safe_search": false
+                    },
+                    {
+                        "type": "x",
+                        "included_x_handles": ["xai"],
+                        "post_favorite_count": 100
+                    },
+                    { "type": "news" },
+                    { "type": "rss", "links": ["https://status.x.ai/feed.xml"] }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn search_parameters_are_omitted_by_default() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let request = request(
+            vec![ModelMessage::user("hello")],
+            GenerationSettings::default(),
+        );
+
+        let body = provider.inner.build_request_body(&request, false);
+
+        assert!(body.get("search_parameters").is_none());
+    }
+
+    #[test]
+    fn image_message_serializes_as_data_url() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let request = request(
+            vec![image_message("image/png", "iVBORw0KGgo=".to_string())],
+            GenerationSettings::default(),
+        );
+
+        provider.validate_request(&request).unwrap();
+        let body = provider.inner.build_request_body(&request, false);
+
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(content[1]["type"], "image_url");
+        assert_eq!(
+            content[1]["image_url"]["url"],
+            "data:image/png;base64,iVBORw0KGgo="
+        );
+    }
+
+    #[test]
+    fn images_are_rejected_for_text_only_models() {
+        let provider = GrokProvider::new(GrokModel::Grok3Mini, String::new());
+        let request = request(
+            vec![image_message("image/png", "iVBORw0KGgo=".to_string())],
+            GenerationSettings::default(),
+        );
+
+        let err = provider.validate_request(&request).unwrap_err();
+
+        assert!(err.to_string().contains("does not accept image input"));
+    }
+
+    #[test]
+    fn images_must_be_jpeg_or_png() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let request = request(
+            vec![image_message("image/webp", "UklGRg==".to_string())],
+            GenerationSettings::default(),
+        );
+
+        let err = provider.validate_request(&request).unwrap_err();
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(err.to_string().contains("image/webp"));
+    }
+
+    #[test]
+    fn images_over_the_size_limit_are_rejected() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let oversized = "A".repeat((crate::models::grok::MAX_IMAGE_BYTES / 3 + 1) * 4);
+        let request = request(
+            vec![image_message("image/jpeg", oversized)],
+            GenerationSettings::default(),
+        );
+
+        let err = provider.validate_request(&request).unwrap_err();
+
+        assert!(err.to_string().contains("byte limit"));
+    }
+
+    #[test]
+    fn live_search_with_tools_is_rejected() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let mut request = request(
+            vec![ModelMessage::user("news?")],
+            search_settings(GrokSearchParameters::new(GrokSearchMode::Auto)),
+        );
+        request.tools = Some(vec![ToolDefinition {
+            name: "lookup".to_string(),
+            description: "lookup".to_string(),
+            parameters: serde_json::json!({ "type": "object" }),
+            strict: false,
+        }]);
+
+        let err = provider.validate_request(&request).unwrap_err();
+        assert!(err.to_string().contains("cannot be combined with tools"));
+
+        request.settings = search_settings(GrokSearchParameters::new(GrokSearchMode::Off));
+        provider.validate_request(&request).unwrap();
+    }
+
+    #[test]
+    fn live_search_source_limits_are_enforced() {
+        let provider = GrokProvider::new(GrokModel::Grok4, String::new());
+        let invalid = [
+            GrokSearchParameters {
+                from_date: Some(date("2025-02-01")),
+                to_date: Some(date("2025-01-01")),
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            },
+            GrokSearchParameters {
+                sources: vec![GrokSearchSource::Web {
+                    country: None,
+                    allowed_websites: vec!["a.com".to_string()],
+                    excluded_websites: vec!["b.com".to_string()],
+                    safe_search: None,
+                }],
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            },
+            GrokSearchParameters {
+                sources: vec![GrokSearchSource::News {
+                    country: None,
+                    excluded_websites: (0..6).map(|i| format!("site{i}.com")).collect(),
+                    safe_search: None,
+                }],
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            },
+            GrokSearchParameters {
+                sources: vec![GrokSearchSource::Rss { links: Vec::new() }],
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            },
+        ];
+
+        for search in invalid {
+            let request = request(vec![ModelMessage::user("news?")], search_settings(search));
+            let err = provider.validate_request(&request).unwrap_err();
+            assert!(matches!(err, RociError::InvalidArgument(_)), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn citations_are_returned_in_response_metadata() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "search_parameters": { "mode": "on", "return_citations": true }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "message": { "role": "assistant", "content": "It rained." },
+                    "finish_reason": "stop"
+                }],
+                "citations": ["https://example.com/weather"]
+            })))
+            .mount(&server)
+            .await;
+        let provider =
+            GrokProvider::with_base_url(GrokModel::Grok4, "key".to_string(), server.uri());
+        let request = request(
+            vec![ModelMessage::user("weather?")],
+            search_settings(GrokSearchParameters {
+                return_citations: Some(true),
+                ..GrokSearchParameters::new(GrokSearchMode::On)
+            }),
+        );
+
+        let response = provider.generate_text(&request).await.unwrap();
+
+        assert_eq!(response.text, "It rained.");
+        assert_eq!(
+            response.metadata.get("citations"),
+            Some(&serde_json::json!(["https://example.com/weather"]))
+        );
+    }
 }
@@ -5,14 +5,17 @@ use futures::stream::BoxStream;
 
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
-use roci_core::types::TextStreamDelta;
+use roci_core::types::{GroqReasoningFormat, ResponseFormat, TextStreamDelta};
 
 use super::openai::OpenAiProvider;
+use super::reasoning_tags::ReasoningTagConfig;
 use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
 
 use crate::models::groq::GroqModel;
 use crate::models::openai::OpenAiModel;
 
+pub(crate) const BASE_URL: &str = "https://api.groq.com/openai/v1";
+
 pub struct GroqProvider {
     inner: OpenAiProvider,
     capabilities: ModelCapabilities,
@@ -20,18 +23,85 @@ pub struct GroqProvider {
 
 impl GroqProvider {
     pub fn new(model: GroqModel, api_key: String) -> Self {
+        Self::with_base_url(model, api_key, BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(model: GroqModel, api_key: String, base_url: String) -> Self {
         let capabilities = model.capabilities();
         let openai_model = OpenAiModel::Custom(model.as_str().to_string());
         Self {
-            inner: OpenAiProvider::new(
-                openai_model,
-                api_key,
-                Some("https://api.groq.com/openai/v1".to_string()),
-                None,
-            ),
+            inner: OpenAiProvider::new(openai_model, api_key, Some(base_url), None)
+                .with_body_hook(apply_groq_options),
             capabilities,
         }
     }
+
+    fn validate_request(&self, request: &ProviderRequest) -> Result<(), RociError> {
+        let has_tools = request
+            .tools
+            .as_ref()
+            .is_some_and(|tools| !tools.is_empty());
+        if has_tools && !self.capabilities.supports_tools {
+            return Err(RociError::InvalidArgument(format!(
+                "Groq model {} does not support tools",
+                self.model_id()
+            )));
+        }
+        let Some(format) = reasoning_format(request) else {
+            return Ok(());
+        };
+        if !self.capabilities.supports_reasoning {
+            return Err(RociError::InvalidArgument(format!(
+                "Groq model {} does not support reasoning_format",
+                self.model_id()
+            )));
+        }
+        let json_mode = matches!(
+            request.response_format,
+            Some(ResponseFormat::JsonObject | ResponseFormat::JsonSchema { .. })
+        );
+        if format == GroqReasoningFormat::Raw && (has_tools || json_mode) {
+            return Err(RociError::InvalidArgument(
+                "Groq reasoning_format raw cannot be combined with tools or JSON mode; use parsed or hidden".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reasoning models default to raw output, which inlines `<think>` spans
+    /// in the content; route those to reasoning.
+    fn reasoning_tags(&self, request: &ProviderRequest) -> ReasoningTagConfig {
+        match reasoning_format(request) {
+            None | Some(GroqReasoningFormat::Raw) if self.capabilities.supports_reasoning => {
+                ReasoningTagConfig::default()
+            }
+            _ => ReasoningTagConfig::disabled(),
+        }
+    }
+}
+
+fn reasoning_format(request: &ProviderRequest) -> Option<GroqReasoningFormat> {
+    request
+        .settings
+        .groq
+        .as_ref()
+        .and_then(|options| options.reasoning_format)
+}
+
+/// Add Groq-only fields: `reasoning_format` and `service_tier`.
+fn apply_groq_options(
+    request: &ProviderRequest,
+    body: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let Some(options) = request.settings.groq.as_ref() else {
+        return;
+    };
+    if let Some(format) = options.reasoning_format {
+        body.insert("reasoning_format".into(), format.to_string().into());
+    }
+    if let Some(tier) = options.service_tier {
+        body.insert("service_tier".into(), tier.to_string().into());
+    }
 }
 
 #[async_trait]
@@ -49,19 +119,142 @@ impl ModelProvider for GroqProvider {
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
-        self.inner.generate_text(request).await
+        self.validate_request(request)?;
+        let response = self.inner.generate_text(request).await?;
+        Ok(self.reasoning_tags(request).apply_to_response(response))
     }
     async fn stream_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
-        self.inner.stream_text(request).await
+        self.validate_request(request)?;
+        let stream = self.inner.stream_text(request).await?;
+        Ok(self.reasoning_tags(request).apply_to_stream(stream))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt;
+    use roci_core::provider::ToolDefinition;
+    use roci_core::types::{
+        ContentPart, GenerationSettings, GroqOptions, GroqServiceTier, ModelMessage,
+        StreamEventType,
+    };
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Groq stream for a single tool call: the complete arguments arrive in
+    /// one delta, followed by an empty-arguments duplicate at the next index,
+    /// with usage reported under `x_groq`.
+    const TOOL_CALL_STREAM: &str = concat!(
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"model\":\"llama-3.3-70b-versatile\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":null},\"finish_reason\":null}],\"x_groq\":{\"id\":\"req_1\"}}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"model\":\"llama-3.3-70b-versatile\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"id\":\"call_abc\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\\\"src/lib.rs\\\"}\"},\"index\":0}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"model\":\"llama-3.3-70b-versatile\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"id\":\"call_abc\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"\"},\"index\":1}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"model\":\"llama-3.3-70b-versatile\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}],\"x_groq\":{\"id\":\"req_1\",\"usage\":{\"prompt_tokens\":120,\"completion_tokens\":18,\"total_tokens\":138}}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Groq stream for two tool calls where each complete call is repeated
+    /// verbatim at its own index.
+    const REPEATED_TOOL_CALLS_STREAM: &str = concat!(
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"id\":\"call_a\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\\\"a.rs\\\"}\"},\"index\":0},{\"id\":\"call_b\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\\\"b.rs\\\"}\"},\"index\":1}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"id\":\"call_a\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\\\"a.rs\\\"}\"},\"index\":0},{\"id\":\"call_b\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\\\"b.rs\\\"}\"},\"index\":1}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Groq stream from a reasoning model with `reasoning_format: parsed`.
+    const PARSED_REASONING_STREAM: &str = concat!(
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"reasoning\":\"The user wants \"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"reasoning\":\"a greeting.\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hello!\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}],\"x_groq\":{\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":12,\"total_tokens\":21}}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Groq stream from a reasoning model with `reasoning_format: raw`.
+    const RAW_REASONING_STREAM: &str = concat!(
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"<think>\\nThe user\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\" wants a greeting.\\n</think>\\n\\nHello!\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    fn request(settings: GenerationSettings) -> ProviderRequest {
+        ProviderRequest {
+            messages: vec![ModelMessage::user("hello")].into(),
+            settings,
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
+
+    fn groq_settings(
+        reasoning_format: Option<GroqReasoningFormat>,
+        service_tier: Option<GroqServiceTier>,
+    ) -> GenerationSettings {
+        GenerationSettings {
+            groq: Some(GroqOptions {
+                reasoning_format,
+                service_tier,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "read_file".to_string(),
+            description: "read a file".to_string(),
+            parameters: serde_json::json!({ "type": "object" }),
+            strict: false,
+        }
+    }
+
+    async fn stream_fixture(
+        model: GroqModel,
+        body: &'static str,
+        request: &ProviderRequest,
+    ) -> Vec<TextStreamDelta> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+        let provider = GroqProvider::with_base_url(model, "key".to_string(), server.uri());
+        provider
+            .stream_text(request)
+            .await
+            .expect("stream response")
+            .map(|delta| delta.expect("stream delta"))
+            .collect()
+            .await
+    }
+
+    fn text_of(deltas: &[TextStreamDelta], event_type: StreamEventType) -> String {
+        deltas
+            .iter()
+            .filter(|delta| delta.event_type == event_type)
+            .map(|delta| match event_type {
+                StreamEventType::Reasoning => delta.reasoning.clone().unwrap_or_default(),
+                _ => delta.text.clone(),
+            })
+            .collect()
+    }
 
     #[test]
     fn llama_3_3_70b_provider_is_text_only() {
@@ -71,4 +264,168 @@ mod tests {
         assert!(caps.input.image.is_none());
         assert_eq!(caps.supports_vision, caps.input.image.is_some());
     }
+
+    #[test]
+    fn groq_options_serialize_into_the_request_body() {
+        let provider = GroqProvider::new(GroqModel::Qwen332b, String::new());
+        let request = request(groq_settings(
+            Some(GroqReasoningFormat::Parsed),
+            Some(GroqServiceTier::OnDemand),
+        ));
+
+        let body = provider.inner.build_request_body(&request, true);
+
+        assert_eq!(body["reasoning_format"], "parsed");
+        assert_eq!(body["service_tier"], "on_demand");
+    }
+
+    #[test]
+    fn groq_options_are_omitted_by_default() {
+        let provider = GroqProvider::new(GroqModel::Qwen332b, String::new());
+        let request = request(GenerationSettings::default());
+
+        let body = provider.inner.build_request_body(&request, false);
+
+        assert!(body.get("reasoning_format").is_none());
+        assert!(body.get("service_tier").is_none());
+    }
+
+    #[test]
+    fn reasoning_format_is_rejected_for_non_reasoning_models() {
+        let provider = GroqProvider::new(GroqModel::Llama3370bVersatile, String::new());
+        let request = request(groq_settings(Some(GroqReasoningFormat::Hidden), None));
+
+        let err = provider.validate_request(&request).unwrap_err();
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(err
+            .to_string()
+            .contains("does not support reasoning_format"));
+    }
+
+    #[test]
+    fn raw_reasoning_format_is_rejected_with_tools_or_json_mode() {
+        let provider = GroqProvider::new(GroqModel::Qwen332b, String::new());
+        let mut request = request(groq_settings(Some(GroqReasoningFormat::Raw), None));
+        request.tools = Some(vec![tool()]);
+
+        let err = provider.validate_request(&request).unwrap_err();
+        assert!(err.to_string().contains("raw cannot be combined"));
+
+        request.tools = None;
+        request.response_format = Some(ResponseFormat::JsonObject);
+        assert!(provider.validate_request(&request).is_err());
+
+        request.settings = groq_settings(Some(GroqReasoningFormat::Parsed), None);
+        provider.validate_request(&request).unwrap();
+    }
+
+    #[tokio::test]
+    async fn full_arguments_with_empty_duplicate_yield_one_tool_call() {
+        let mut request = request(GenerationSettings::default());
+        request.tools = Some(vec![tool()]);
+
+        let deltas =
+            stream_fixture(GroqModel::Llama3370bVersatile, TOOL_CALL_STREAM, &request).await;
+
+        let calls = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::ToolCallDelta)
+            .filter_map(|delta| delta.tool_call.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].id, "call_abc");
+        assert_eq!(calls[0].name, "read_file");
+        assert_eq!(
+            calls[0].arguments,
+            serde_json::json!({ "path": "src/lib.rs" })
+        );
+        let usage = deltas
+            .iter()
+            .find_map(|delta| delta.usage.as_ref())
+            .expect("x_groq usage");
+        assert_eq!(usage.input_tokens, 120);
+        assert_eq!(usage.output_tokens, 18);
+    }
+
+    #[tokio::test]
+    async fn repeated_complete_arguments_are_not_concatenated() {
+        let mut request = request(GenerationSettings::default());
+        request.tools = Some(vec![tool()]);
+
+        let deltas = stream_fixture(
+            GroqModel::Llama3370bVersatile,
+            REPEATED_TOOL_CALLS_STREAM,
+            &request,
+        )
+        .await;
+
+        let arguments = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::ToolCallDelta)
+            .filter_map(|delta| delta.tool_call.as_ref())
+            .map(|call| call.arguments.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            arguments,
+            vec![
+                serde_json::json!({ "path": "a.rs" }),
+                serde_json::json!({ "path": "b.rs" }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn parsed_reasoning_streams_to_the_reasoning_channel() {
+        let request = request(groq_settings(Some(GroqReasoningFormat::Parsed), None));
+
+        let deltas = stream_fixture(GroqModel::Qwen332b, PARSED_REASONING_STREAM, &request).await;
+
+        assert_eq!(
+            text_of(&deltas, StreamEventType::Reasoning),
+            "The user wants a greeting."
+        );
+        assert_eq!(text_of(&deltas, StreamEventType::TextDelta), "Hello!");
+    }
+
+    #[tokio::test]
+    async fn raw_reasoning_tags_stream_to_the_reasoning_channel() {
+        let request = request(groq_settings(Some(GroqReasoningFormat::Raw), None));
+
+        let deltas = stream_fixture(GroqModel::Qwen332b, RAW_REASONING_STREAM, &request).await;
+
+        assert!(text_of(&deltas, StreamEventType::Reasoning).contains("wants a greeting."));
+        assert!(!text_of(&deltas, StreamEventType::TextDelta).contains("<think>"));
+    }
+
+    #[tokio::test]
+    async fn parsed_reasoning_is_returned_as_thinking() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": "Hello!",
+                        "reasoning": "The user wants a greeting."
+                    },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 9, "completion_tokens": 12, "total_tokens": 21 }
+            })))
+            .mount(&server)
+            .await;
+        let provider =
+            GroqProvider::with_base_url(GroqModel::Qwen332b, "key".to_string(), server.uri());
+        let request = request(groq_settings(Some(GroqReasoningFormat::Parsed), None));
+
+        let response = provider.generate_text(&request).await.unwrap();
+
+        assert_eq!(response.text, "Hello!");
+        assert!(matches!(
+            response.thinking.as_slice(),
+            [ContentPart::Thinking(thinking)] if thinking.thinking == "The user wants a greeting."
+        ));
+    }
 }
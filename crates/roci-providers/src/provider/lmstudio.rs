@@ -3,12 +3,14 @@
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 
+use roci_core::config::ChatFormat;
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::TextStreamDelta;
 
 use super::openai::OpenAiProvider;
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use super::reasoning_tags::ReasoningTagConfig;
+use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse, WarmUpReport};
 
 use crate::models::lmstudio::LmStudioModel;
 use crate::models::openai::OpenAiModel;
@@ -16,6 +18,9 @@ use crate::models::openai::OpenAiModel;
 pub struct LmStudioProvider {
     inner: OpenAiProvider,
     capabilities: ModelCapabilities,
+    reasoning_tags: ReasoningTagConfig,
+    root_url: String,
+    api_key: Option<String>,
 }
 
 impl LmStudioProvider {
@@ -28,8 +33,39 @@ impl LmStudioProvider {
                 Some(format!("{}/v1", base_url.trim_end_matches('/'))),
             ),
             capabilities,
+            reasoning_tags: ReasoningTagConfig::default(),
+            root_url: base_url,
+            api_key: None,
         }
     }
+
+    /// Send `api_key` as a bearer token, for servers behind an
+    /// authenticating proxy. An empty key sends none.
+    pub fn with_api_key(mut self, api_key: String) -> Self {
+        self.api_key = Some(api_key.clone()).filter(|key| !key.is_empty());
+        self.inner = self.inner.with_api_key(api_key);
+        self
+    }
+
+    /// Override the static capabilities (e.g. with probed values).
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Reshape messages before they are sent, for models whose prompt
+    /// template mishandles system messages or standard role names.
+    pub fn with_chat_format(mut self, chat_format: ChatFormat) -> Self {
+        self.inner = self.inner.with_chat_format(chat_format);
+        self
+    }
+
+    /// Configure extraction of inline reasoning tags such as
+    /// `<think>...</think>` from generated text.
+    pub fn with_reasoning_tags(mut self, reasoning_tags: ReasoningTagConfig) -> Self {
+        self.reasoning_tags = reasoning_tags;
+        self
+    }
 }
 
 #[async_trait]
@@ -43,17 +79,26 @@ impl ModelProvider for LmStudioProvider {
     fn capabilities(&self) -> &ModelCapabilities {
         &self.capabilities
     }
+    fn supports_warm_up(&self) -> bool {
+        true
+    }
+    async fn warm_up(&self) -> Result<WarmUpReport, RociError> {
+        crate::warm_up::warm_up_lmstudio(&self.root_url, self.api_key.as_deref(), self.model_id())
+            .await
+    }
     async fn generate_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
-        self.inner.generate_text(request).await
+        let response = self.inner.generate_text(request).await?;
+        Ok(self.reasoning_tags.apply_to_response(response))
     }
     async fn stream_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
-        self.inner.stream_text(request).await
+        let stream = self.inner.stream_text(request).await?;
+        Ok(self.reasoning_tags.apply_to_stream(stream))
     }
 }
 
@@ -3,16 +3,66 @@
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 
+use roci_core::config::RociConfig;
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
-use roci_core::types::TextStreamDelta;
+use roci_core::models::ProviderKey;
+use roci_core::types::{Role, TextStreamDelta};
 
 use super::openai::OpenAiProvider;
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::provider::{require_api_key, ModelProvider, ProviderRequest, ProviderResponse};
 
 use crate::models::mistral::MistralModel;
 use crate::models::openai::OpenAiModel;
 
+pub const DEFAULT_BASE_URL: &str = "https://api.mistral.ai/v1";
+pub const CODESTRAL_BASE_URL: &str = "https://codestral.mistral.ai/v1";
+/// Config key for the Codestral API key (`CODESTRAL_API_KEY`) and base URL
+/// override (`CODESTRAL_BASE_URL`).
+pub const CODESTRAL_CONFIG_KEY: &str = "codestral";
+
+/// Base URL and API key selected for a Mistral model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MistralEndpoint {
+    pub base_url: String,
+    pub api_key: String,
+}
+
+impl MistralEndpoint {
+    /// Route Codestral models to `codestral.mistral.ai` when a Codestral key
+    /// is configured; everything else uses the regular Mistral API.
+    pub fn resolve(config: &RociConfig, model: &MistralModel) -> Result<Self, RociError> {
+        let codestral_key = config
+            .get_api_key(CODESTRAL_CONFIG_KEY)
+            .filter(|_| model.is_codestral());
+        let api_key = match codestral_key {
+            Some(api_key) => api_key,
+            None => require_api_key(config, ProviderKey::Mistral, "Missing MISTRAL_API_KEY")?,
+        };
+        Ok(Self {
+            base_url: Self::base_url(config, model),
+            api_key,
+        })
+    }
+
+    /// Base URL `resolve` would pick for `model`, without requiring a key.
+    pub fn base_url(config: &RociConfig, model: &MistralModel) -> String {
+        if uses_codestral_endpoint(config, model) {
+            config
+                .get_base_url(CODESTRAL_CONFIG_KEY)
+                .unwrap_or_else(|| CODESTRAL_BASE_URL.to_string())
+        } else {
+            config
+                .get_base_url_for(ProviderKey::Mistral)
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+        }
+    }
+}
+
+fn uses_codestral_endpoint(config: &RociConfig, model: &MistralModel) -> bool {
+    model.is_codestral() && config.get_api_key(CODESTRAL_CONFIG_KEY).is_some()
+}
+
 pub struct MistralProvider {
     inner: OpenAiProvider,
     capabilities: ModelCapabilities,
@@ -20,18 +70,62 @@ pub struct MistralProvider {
 
 impl MistralProvider {
     pub fn new(model: MistralModel, api_key: String) -> Self {
+        Self::with_base_url(model, api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(model: MistralModel, api_key: String, base_url: String) -> Self {
         let capabilities = model.capabilities();
         let openai_model = OpenAiModel::Custom(model.as_str().to_string());
         Self {
-            inner: OpenAiProvider::new(
-                openai_model,
-                api_key,
-                Some("https://api.mistral.ai/v1".to_string()),
-                None,
-            ),
+            inner: OpenAiProvider::new(openai_model, api_key, Some(base_url), None)
+                .with_body_hook(apply_mistral_options),
             capabilities,
         }
     }
+
+    pub fn from_endpoint(model: MistralModel, endpoint: MistralEndpoint) -> Self {
+        Self::with_base_url(model, endpoint.api_key, endpoint.base_url)
+    }
+
+    fn validate_settings(&self, request: &ProviderRequest) -> Result<(), RociError> {
+        if request.settings.assistant_prefix == Some(true)
+            && request.messages.last().map(|message| message.role) != Some(Role::Assistant)
+        {
+            return Err(RociError::InvalidArgument(
+                "assistant_prefix requires the last message to be an assistant message".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Add Mistral-only fields: `safe_prompt` and `prefix` on a trailing
+/// assistant message.
+fn apply_mistral_options(
+    request: &ProviderRequest,
+    body: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    if let Some(safe_prompt) = request
+        .settings
+        .mistral
+        .as_ref()
+        .and_then(|options| options.safe_prompt)
+    {
+        body.insert("safe_prompt".into(), safe_prompt.into());
+    }
+
+    let ends_with_assistant =
+        request.messages.last().map(|message| message.role) == Some(Role::Assistant);
+    if request.settings.assistant_prefix == Some(true) && ends_with_assistant {
+        if let Some(last) = body
+            .get_mut("messages")
+            .and_then(serde_json::Value::as_array_mut)
+            .and_then(|messages| messages.last_mut())
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            last.insert("prefix".into(), true.into());
+        }
+    }
 }
 
 #[async_trait]
@@ -45,16 +139,21 @@ impl ModelProvider for MistralProvider {
     fn capabilities(&self) -> &ModelCapabilities {
         &self.capabilities
     }
+    fn supports_assistant_prefix(&self) -> bool {
+        true
+    }
     async fn generate_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
+        self.validate_settings(request)?;
         self.inner.generate_text(request).await
     }
     async fn stream_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        self.validate_settings(request)?;
         self.inner.stream_text(request).await
     }
 }
@@ -62,6 +161,23 @@ impl ModelProvider for MistralProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use roci_core::types::{GenerationSettings, MistralOptions, ModelMessage};
+
+    fn request(messages: Vec<ModelMessage>, settings: GenerationSettings) -> ProviderRequest {
+        ProviderRequest {
+            messages: messages.into(),
+            settings,
+            tools: None,
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        }
+    }
 
     #[test]
     fn mistral_large_provider_supports_image_input() {
@@ -102,4 +218,136 @@ mod tests {
         assert!(caps.input.image.is_none());
         assert_eq!(caps.supports_vision, caps.input.image.is_some());
     }
+
+    #[test]
+    fn safe_prompt_is_serialized_when_set() {
+        let provider = MistralProvider::new(MistralModel::MistralSmall, String::new());
+        let request = request(
+            vec![ModelMessage::user("hello")],
+            GenerationSettings {
+                mistral: Some(MistralOptions {
+                    safe_prompt: Some(true),
+                }),
+                ..Default::default()
+            },
+        );
+
+        let body = provider.inner.build_request_body(&request, false);
+
+        assert_eq!(body["safe_prompt"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn safe_prompt_is_omitted_by_default() {
+        let provider = MistralProvider::new(MistralModel::MistralSmall, String::new());
+        let request = request(
+            vec![ModelMessage::user("hello")],
+            GenerationSettings::default(),
+        );
+
+        let body = provider.inner.build_request_body(&request, false);
+
+        assert!(body.get("safe_prompt").is_none());
+    }
+
+    #[test]
+    fn assistant_prefix_marks_trailing_assistant_message() {
+        let provider = MistralProvider::new(MistralModel::Codestral, String::new());
+        let request = request(
+            vec![
+                ModelMessage::user("write a haiku"),
+                ModelMessage::assistant("Autumn"),
+            ],
+            GenerationSettings {
+                assistant_prefix: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let body = provider.inner.build_request_body(&request, true);
+
+        assert_eq!(body["messages"][1]["role"], "assistant");
+        assert_eq!(body["messages"][1]["prefix"], serde_json::json!(true));
+        assert!(body["messages"][0].get("prefix").is_none());
+    }
+
+    #[test]
+    fn assistant_prefix_requires_trailing_assistant_message() {
+        let provider = MistralProvider::new(MistralModel::MistralSmall, String::new());
+        let request = request(
+            vec![ModelMessage::user("hello")],
+            GenerationSettings {
+                assistant_prefix: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let err = provider
+            .validate_settings(&request)
+            .expect_err("prefix without assistant message should fail");
+
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+        assert!(provider.supports_assistant_prefix());
+    }
+
+    #[test]
+    fn codestral_routes_to_dedicated_endpoint_when_key_is_configured() {
+        let config = RociConfig::new();
+        config.set_api_key("mistral", "mistral-key".to_string());
+        config.set_api_key(CODESTRAL_CONFIG_KEY, "codestral-key".to_string());
+
+        let endpoint = MistralEndpoint::resolve(&config, &MistralModel::Codestral).unwrap();
+
+        assert_eq!(
+            endpoint,
+            MistralEndpoint {
+                base_url: CODESTRAL_BASE_URL.to_string(),
+                api_key: "codestral-key".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn codestral_falls_back_to_mistral_endpoint_without_codestral_key() {
+        let config = RociConfig::new();
+        config.set_api_key("mistral", "mistral-key".to_string());
+
+        let endpoint =
+            MistralEndpoint::resolve(&config, &MistralModel::Custom("codestral-2501".to_string()))
+                .unwrap();
+
+        assert_eq!(endpoint.base_url, DEFAULT_BASE_URL);
+        assert_eq!(endpoint.api_key, "mistral-key");
+    }
+
+    #[test]
+    fn endpoint_base_urls_honor_config_overrides() {
+        let config = RociConfig::new();
+        config.set_api_key("mistral", "mistral-key".to_string());
+        config.set_api_key(CODESTRAL_CONFIG_KEY, "codestral-key".to_string());
+        config.set_base_url("mistral", "https://mistral.internal/v1".to_string());
+        config.set_base_url(
+            CODESTRAL_CONFIG_KEY,
+            "https://codestral.internal/v1".to_string(),
+        );
+
+        let chat = MistralEndpoint::resolve(&config, &MistralModel::MistralLarge).unwrap();
+        let code = MistralEndpoint::resolve(&config, &MistralModel::Codestral).unwrap();
+
+        assert_eq!(chat.base_url, "https://mistral.internal/v1");
+        assert_eq!(chat.api_key, "mistral-key");
+        assert_eq!(code.base_url, "https://codestral.internal/v1");
+        assert_eq!(code.api_key, "codestral-key");
+    }
+
+    #[test]
+    fn non_codestral_models_require_mistral_key() {
+        let config = RociConfig::new();
+        config.set_api_key(CODESTRAL_CONFIG_KEY, "codestral-key".to_string());
+
+        let err = MistralEndpoint::resolve(&config, &MistralModel::MistralSmall)
+            .expect_err("mistral key should be required");
+
+        assert!(matches!(err, RociError::Authentication(_)));
+    }
 }
@@ -1,11 +1,17 @@
 //! Built-in provider transport implementations.
 
+#[cfg(feature = "openai")]
+pub(crate) mod chat_format;
 #[cfg(feature = "openai")]
 pub mod openai;
 #[cfg(feature = "openai")]
 pub(crate) mod openai_errors;
 #[cfg(feature = "openai")]
 pub mod openai_responses;
+#[cfg(feature = "openai")]
+pub(crate) mod openai_tool_calls;
+#[cfg(feature = "openai")]
+pub mod reasoning_tags;
 
 #[cfg(feature = "anthropic")]
 pub mod anthropic;
@@ -26,6 +32,8 @@ pub mod ollama;
 
 #[cfg(feature = "anthropic-compatible")]
 pub mod anthropic_compatible;
+#[cfg(feature = "bedrock")]
+pub mod bedrock;
 #[cfg(feature = "github-copilot")]
 pub mod github_copilot;
 #[cfg(feature = "openai-compatible-transport")]
@@ -7,19 +7,26 @@ use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde::Deserialize;
 use tracing::debug;
 
+use roci_core::config::{ChatFormat, RequestCompression};
 use roci_core::error::RociError;
 use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::*;
 
+use roci_core::provider::anomalies::{with_anomaly_count, ResponseAnomalies};
 use roci_core::provider::format::tool_result_to_string;
-use roci_core::provider::http::{bearer_headers, shared_client};
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::provider::http::{
+    bearer_headers, response_metadata, send_json, shared_client, sse_events, with_payload_sizes,
+    with_response_metadata, ByteCounter, ResponseHeaderRules,
+};
+use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse, ToolLimits};
 
+use super::chat_format::apply_chat_format;
 use super::openai_errors::status_to_openai_error;
+use super::openai_tool_calls::StreamToolCalls;
 use crate::models::openai::OpenAiModel;
 use roci_core::util::debug::roci_debug_enabled;
 
-const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+pub(crate) const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
 
 #[derive(Clone, Copy)]
 enum AuthMode {
@@ -28,6 +35,10 @@ enum AuthMode {
     ApiKey,
 }
 
+/// Adjusts the serialized chat completions body for OpenAI-compatible
+/// providers that accept extra fields.
+pub(crate) type BodyHook = fn(&ProviderRequest, &mut serde_json::Map<String, serde_json::Value>);
+
 pub struct OpenAiProvider {
     model: OpenAiModel,
     api_key: String,
@@ -38,6 +49,9 @@ pub struct OpenAiProvider {
     auth_mode: AuthMode,
     auth_required: bool,
     capabilities: ModelCapabilities,
+    body_hook: Option<BodyHook>,
+    chat_format: Option<ChatFormat>,
+    request_compression: Option<RequestCompression>,
 }
 
 impl OpenAiProvider {
@@ -149,9 +163,44 @@ impl OpenAiProvider {
             auth_mode,
             auth_required,
             capabilities,
+            body_hook: None,
+            chat_format: None,
+            request_compression: None,
         }
     }
 
+    /// Gzip request bodies at least `compression.min_bytes` long.
+    pub fn with_request_compression(mut self, compression: Option<RequestCompression>) -> Self {
+        self.request_compression = compression;
+        self
+    }
+
+    #[cfg_attr(
+        not(any(feature = "openrouter", feature = "together")),
+        allow(dead_code)
+    )]
+    pub(crate) fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    #[cfg_attr(
+        not(any(feature = "mistral", feature = "grok", feature = "groq")),
+        allow(dead_code)
+    )]
+    pub(crate) fn with_body_hook(mut self, hook: BodyHook) -> Self {
+        self.body_hook = Some(hook);
+        self
+    }
+
+    /// Reshape messages for models with picky prompt templates. A default
+    /// format changes nothing.
+    #[cfg_attr(not(any(feature = "lmstudio", feature = "ollama")), allow(dead_code))]
+    pub(crate) fn with_chat_format(mut self, chat_format: ChatFormat) -> Self {
+        self.chat_format = Some(chat_format).filter(|format| !format.is_default());
+        self
+    }
+
     #[cfg_attr(
         not(any(feature = "lmstudio", feature = "ollama", test)),
         allow(dead_code)
@@ -169,6 +218,21 @@ impl OpenAiProvider {
         )
     }
 
+    /// Replace the default API key; an empty key sends none.
+    #[cfg_attr(not(any(feature = "lmstudio", feature = "ollama")), allow(dead_code))]
+    pub(crate) fn with_api_key(mut self, api_key: String) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Whether a request without any API key fails with
+    /// [`RociError::MissingCredential`] instead of being sent unauthenticated.
+    #[cfg_attr(not(feature = "openai-compatible-transport"), allow(dead_code))]
+    pub(crate) fn with_auth_required(mut self, auth_required: bool) -> Self {
+        self.auth_required = auth_required;
+        self
+    }
+
     fn resolved_api_key<'a>(
         &'a self,
         request: &'a ProviderRequest,
@@ -250,12 +314,19 @@ impl OpenAiProvider {
         }
     }
 
-    fn build_request_body(&self, request: &ProviderRequest, stream: bool) -> serde_json::Value {
-        let messages = request
+    pub(crate) fn build_request_body(
+        &self,
+        request: &ProviderRequest,
+        stream: bool,
+    ) -> serde_json::Value {
+        let mut messages = request
             .messages
             .iter()
             .map(message_to_openai)
             .collect::<Vec<_>>();
+        if let Some(chat_format) = &self.chat_format {
+            apply_chat_format(chat_format, &mut messages);
+        }
 
         let mut body = serde_json::json!({
             "model": self.model.as_str(),
@@ -309,13 +380,18 @@ impl OpenAiProvider {
                 let tool_defs: Vec<serde_json::Value> = tools
                     .iter()
                     .map(|t| {
+                        let strict_parameters = self
+                            .capabilities
+                            .supports_json_schema
+                            .then(|| t.strict_parameters())
+                            .flatten();
                         serde_json::json!({
                             "type": "function",
                             "function": {
                                 "name": t.name,
                                 "description": t.description,
-                                "parameters": t.parameters,
-                                "strict": false,
+                                "parameters": strict_parameters.as_ref().unwrap_or(&t.parameters),
+                                "strict": strict_parameters.is_some(),
                             }
                         })
                     })
@@ -349,6 +425,10 @@ impl OpenAiProvider {
             }
         }
 
+        if let Some(hook) = self.body_hook {
+            hook(request, obj);
+        }
+
         body
     }
 
@@ -373,6 +453,14 @@ impl ModelProvider for OpenAiProvider {
         &self.capabilities
     }
 
+    fn tool_limits(&self) -> ToolLimits {
+        ToolLimits::OPENAI
+    }
+
+    fn supports_response_format_with_tools(&self) -> bool {
+        self.capabilities.supports_json_schema
+    }
+
     async fn generate_text(
         &self,
         request: &ProviderRequest,
@@ -383,12 +471,14 @@ impl ModelProvider for OpenAiProvider {
 
         debug!(model = self.model.as_str(), "OpenAI generate_text");
 
-        let resp = shared_client()
-            .post(&url)
-            .headers(self.build_headers(request)?)
-            .json(&body)
-            .send()
-            .await?;
+        let (resp, mut payload) = send_json(
+            shared_client()
+                .post(&url)
+                .headers(self.build_headers(request)?),
+            &body,
+            self.request_compression,
+        )
+        .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
@@ -396,20 +486,32 @@ impl ModelProvider for OpenAiProvider {
             return Err(status_to_openai_error(status, &body_text));
         }
 
-        let data: OpenAiChatResponse = resp.json().await?;
+        let mut response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::OPENAI);
+        let raw_body = resp.bytes().await?;
+        payload.response_bytes = Some(raw_body.len() as u64);
+        response_metadata.payload = Some(payload);
+        let raw: serde_json::Value = serde_json::from_slice(&raw_body)?;
+        let anomalies = ResponseAnomalies::from_env("openai");
+        validate_chat_response(&raw, &anomalies);
+        let data: OpenAiChatResponse = serde_json::from_value(raw)?;
+        let mut metadata = std::collections::HashMap::new();
+        if let Some(citations) = data.citations {
+            metadata.insert("citations".to_string(), serde_json::json!(citations));
+        }
         let choice = data
             .choices
             .into_iter()
             .next()
             .ok_or_else(|| RociError::api(200, "No choices in OpenAI response"))?;
 
-        let tool_calls = choice
+        let mut call_ids = request.begin_tool_call_ids();
+        let tool_calls: Vec<message::AgentToolCall> = choice
             .message
             .tool_calls
             .unwrap_or_default()
             .into_iter()
             .map(|tc| message::AgentToolCall {
-                id: tc.id,
+                id: call_ids.assign(Some(&tc.id), &tc.function.name),
                 name: tc.function.name,
                 arguments: serde_json::from_str(&tc.function.arguments)
                     .unwrap_or(serde_json::Value::String(tc.function.arguments)),
@@ -418,25 +520,35 @@ impl ModelProvider for OpenAiProvider {
             })
             .collect();
 
-        let finish_reason = choice
-            .finish_reason
-            .as_deref()
-            .and_then(parse_finish_reason);
+        let refusal = choice.message.refusal.filter(|refusal| !refusal.is_empty());
+        let finish_reason = if refusal.is_some() && tool_calls.is_empty() {
+            Some(FinishReason::Refusal)
+        } else {
+            choice.finish_reason.as_deref().map(parse_finish_reason)
+        };
+        let thinking = [choice.message.reasoning_content, choice.message.reasoning]
+            .into_iter()
+            .flatten()
+            .find(|value| !value.is_empty())
+            .map(|thinking| {
+                ContentPart::Thinking(ThinkingContent {
+                    thinking,
+                    signature: String::new(),
+                })
+            })
+            .into_iter()
+            .collect();
 
         Ok(ProviderResponse {
             text: choice.message.content.unwrap_or_default(),
-            usage: data
-                .usage
-                .map(|u| Usage {
-                    input_tokens: u.prompt_tokens,
-                    output_tokens: u.completion_tokens,
-                    total_tokens: u.total_tokens,
-                    ..Default::default()
-                })
-                .unwrap_or_default(),
+            usage: data.usage.map(OpenAiUsage::into_usage).unwrap_or_default(),
             tool_calls,
             finish_reason,
-            thinking: Vec::new(),
+            thinking,
+            metadata,
+            refusal,
+            images: Vec::new(),
+            response_metadata: Some(anomalies.annotate(response_metadata)),
         })
     }
 
@@ -450,12 +562,14 @@ impl ModelProvider for OpenAiProvider {
 
         debug!(model = self.model.as_str(), "OpenAI stream_text");
 
-        let resp = shared_client()
-            .post(&url)
-            .headers(self.build_headers(request)?)
-            .json(&body)
-            .send()
-            .await?;
+        let (resp, payload) = send_json(
+            shared_client()
+                .post(&url)
+                .headers(self.build_headers(request)?),
+            &body,
+            self.request_compression,
+        )
+        .await?;
 
         let status = resp.status().as_u16();
         if status != 200 {
@@ -463,190 +577,228 @@ impl ModelProvider for OpenAiProvider {
             return Err(status_to_openai_error(status, &body_text));
         }
 
-        let byte_stream = resp.bytes_stream();
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::OPENAI);
+        let received = ByteCounter::default();
+        let events = sse_events(received.count(resp.bytes_stream()));
 
-        struct ToolCallBuilder {
-            id: Option<String>,
-            name: Option<String>,
-            arguments: String,
-        }
+        let mut tool_calls = StreamToolCalls::new(request.begin_tool_call_ids());
+        let anomalies = ResponseAnomalies::from_env("openai");
+        let stream_anomalies = anomalies.clone();
         let stream = async_stream::stream! {
-            let mut buffer = String::new();
-            let mut tool_calls: std::collections::HashMap<usize, ToolCallBuilder> = std::collections::HashMap::new();
-            let mut chunk_count: u64 = 0;
-            let mut line_count: u64 = 0;
-            let mut byte_count: u64 = 0;
-            futures::pin_mut!(byte_stream);
-
-            while let Some(chunk_result) = byte_stream.next().await {
-                let chunk = match chunk_result {
-                    Ok(c) => c,
+            let mut event_count: u64 = 0;
+            let mut refused = false;
+            futures::pin_mut!(events);
+
+            while let Some(event) = events.next().await {
+                let event = match event {
+                    Ok(event) => event,
                     Err(e) => {
                         yield Err(RociError::Network(e));
                         break;
                     }
                 };
 
-                chunk_count += 1;
-                byte_count += chunk.len() as u64;
-                if roci_debug_enabled() && chunk_count == 1 {
-                    debug!(chunk_len = chunk.len(), "OpenAI stream first chunk");
+                event_count += 1;
+                if roci_debug_enabled() && event_count == 1 {
+                    debug!(data_len = event.data.len(), "OpenAI stream first event");
                 }
 
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let line = buffer[..line_end].trim().to_string();
-                    buffer = buffer[line_end + 1..].to_string();
-
-                    if line.is_empty() || line.starts_with(':') {
-                        continue;
+                if event.is_done() {
+                    if roci_debug_enabled() {
+                        debug!(event_count, "OpenAI stream done");
                     }
+                    yield Ok(TextStreamDelta {
+                        text: String::new(),
+                        event_type: StreamEventType::Done,
+                        tool_call: None,
+                        finish_reason: None,
+                        usage: None,
+                        reasoning: None,
+                        reasoning_signature: None,
+                        reasoning_type: None,
+                        image: None,
+                        response_metadata: None,
+                    });
+                    continue;
+                }
 
-                    line_count += 1;
-                    if line == "data: [DONE]" {
-                        if roci_debug_enabled() {
-                            debug!(chunk_count, line_count, byte_count, "OpenAI stream done");
+                if let Ok(chunk) = serde_json::from_str::<OpenAiStreamChunk>(&event.data) {
+                    if let Some(choice) = chunk.choices.into_iter().next() {
+                        let OpenAiStreamChoice {
+                            delta,
+                            finish_reason,
+                        } = choice;
+                        let OpenAiStreamDelta {
+                            content,
+                            refusal,
+                            reasoning_content,
+                            reasoning,
+                            reasoning_text,
+                            tool_calls: tool_call_deltas,
+                        } = delta;
+                        let reasoning = [reasoning_content, reasoning, reasoning_text]
+                            .into_iter()
+                            .flatten()
+                            .find(|value| !value.is_empty());
+                        if let Some(reasoning) = reasoning {
+                            yield Ok(TextStreamDelta {
+                                text: String::new(),
+                                event_type: StreamEventType::Reasoning,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: Some(reasoning),
+                                reasoning_signature: None,
+                                reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
+                            });
                         }
-                        yield Ok(TextStreamDelta {
-                            text: String::new(),
-                            event_type: StreamEventType::Done,
-                            tool_call: None,
-                            finish_reason: None,
-                            usage: None,
-                            reasoning: None,
-                            reasoning_signature: None,
-                            reasoning_type: None,
-                        });
-                        continue;
-                    }
-
-                    if let Some(data) = roci_core::provider::http::parse_sse_data(&line) {
-                        if let Ok(chunk) = serde_json::from_str::<OpenAiStreamChunk>(data) {
-                            if let Some(choice) = chunk.choices.into_iter().next() {
-                                let OpenAiStreamChoice {
-                                    delta,
-                                    finish_reason,
-                                } = choice;
-                                let OpenAiStreamDelta {
-                                    content,
-                                    reasoning_content,
-                                    reasoning,
-                                    reasoning_text,
-                                    tool_calls: tool_call_deltas,
-                                } = delta;
-                                let reasoning = [reasoning_content, reasoning, reasoning_text]
-                                    .into_iter()
-                                    .flatten()
-                                    .find(|value| !value.is_empty());
-                                if let Some(reasoning) = reasoning {
-                                    yield Ok(TextStreamDelta {
-                                        text: String::new(),
-                                        event_type: StreamEventType::Reasoning,
-                                        tool_call: None,
-                                        finish_reason: None,
-                                        usage: None,
-                                        reasoning: Some(reasoning),
-                                        reasoning_signature: None,
-                                        reasoning_type: None,
-                                    });
+                        if let Some(deltas) = tool_call_deltas {
+                            for delta in deltas {
+                                let (name, arguments) = delta
+                                    .function
+                                    .map(|func| (func.name, func.arguments))
+                                    .unwrap_or_default();
+                                if let Some(progress) = tool_calls.push(delta.index, delta.id, name, arguments) {
+                                    yield Ok(progress);
                                 }
-                                if let Some(deltas) = tool_call_deltas {
-                                    for delta in deltas {
-                                        let entry = tool_calls.entry(delta.index).or_insert_with(|| ToolCallBuilder {
-                                            id: None,
-                                            name: None,
-                                            arguments: String::new(),
-                                        });
-                                        if let Some(id) = delta.id {
-                                            entry.id = Some(id);
-                                        }
-                                        if let Some(func) = delta.function {
-                                            if let Some(name) = func.name {
-                                                entry.name = Some(name);
-                                            }
-                                            if let Some(args) = func.arguments {
-                                                entry.arguments.push_str(&args);
-                                            }
-                                        }
-                                    }
-                                }
-                                if let Some(text) = content {
+                            }
+                        }
+                        if let Some(refusal) = refusal.filter(|refusal| !refusal.is_empty()) {
+                            refused = true;
+                            yield Ok(TextStreamDelta {
+                                text: refusal,
+                                event_type: StreamEventType::RefusalDelta,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: None,
+                                reasoning_signature: None,
+                                reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
+                            });
+                        }
+                        if let Some(text) = content {
+                            yield Ok(TextStreamDelta {
+                                text,
+                                event_type: StreamEventType::TextDelta,
+                                tool_call: None,
+                                finish_reason: None,
+                                usage: None,
+                                reasoning: None,
+                                reasoning_signature: None,
+                                reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
+                            });
+                        }
+                        if let Some(reason) = finish_reason.as_deref() {
+                            if parse_finish_reason(reason).is_other() && stream_anomalies.is_enabled() {
+                                let raw = serde_json::from_str(&event.data).unwrap_or_default();
+                                stream_anomalies.unknown_finish_reason("choices.0.finish_reason", reason, &raw);
+                            }
+                        }
+                        let finish = finish_reason
+                            .as_deref()
+                            .map(parse_finish_reason)
+                            .map(|reason| match reason {
+                                FinishReason::Stop if refused => FinishReason::Refusal,
+                                reason => reason,
+                            });
+                        if let Some(reason) = finish {
+                            if reason == FinishReason::ToolCalls {
+                                for call in tool_calls.finish() {
                                     yield Ok(TextStreamDelta {
-                                        text,
-                                        event_type: StreamEventType::TextDelta,
-                                        tool_call: None,
+                                        text: String::new(),
+                                        event_type: StreamEventType::ToolCallDelta,
+                                        tool_call: Some(call),
                                         finish_reason: None,
                                         usage: None,
                                         reasoning: None,
                                         reasoning_signature: None,
                                         reasoning_type: None,
-                                    });
-                                }
-                                let finish = finish_reason.as_deref().and_then(parse_finish_reason);
-                                if let Some(reason) = finish {
-                                    if reason == FinishReason::ToolCalls {
-                                        let mut indices = tool_calls.keys().copied().collect::<Vec<_>>();
-                                        indices.sort_unstable();
-                                        for index in indices {
-                                            if let Some(builder) = tool_calls.remove(&index) {
-                                                if let (Some(id), Some(name)) = (builder.id, builder.name) {
-                                                    let args = serde_json::from_str(&builder.arguments)
-                                                        .unwrap_or(serde_json::Value::String(builder.arguments));
-                                                    yield Ok(TextStreamDelta {
-                                                        text: String::new(),
-                                                        event_type: StreamEventType::ToolCallDelta,
-                                                        tool_call: Some(AgentToolCall { id, name, arguments: args, called_as: None, recipient: None }),
-                                                        finish_reason: None,
-                                                        usage: None,
-                                                        reasoning: None,
-                                                        reasoning_signature: None,
-                                                        reasoning_type: None,
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    }
-                                    yield Ok(TextStreamDelta {
-                                        text: String::new(),
-                                        event_type: StreamEventType::Done,
-                                        tool_call: None,
-                                        finish_reason: Some(reason),
-                                        usage: chunk.usage.map(|u| Usage {
-                                            input_tokens: u.prompt_tokens,
-                                            output_tokens: u.completion_tokens,
-                                            total_tokens: u.total_tokens,
-                                            ..Default::default()
-                                        }),
-                                        reasoning: None,
-                                        reasoning_signature: None,
-                                        reasoning_type: None,
+                                        image: None,
+                                        response_metadata: None,
                                     });
                                 }
                             }
-                        } else if roci_debug_enabled() {
-                            debug!(line_len = line.len(), "OpenAI stream parse failed");
+                            yield Ok(TextStreamDelta {
+                                text: String::new(),
+                                event_type: StreamEventType::Done,
+                                tool_call: None,
+                                finish_reason: Some(reason),
+                                usage: chunk.usage.or_else(|| chunk.x_groq.and_then(|extras| extras.usage)).map(OpenAiUsage::into_usage),
+                                reasoning: None,
+                                reasoning_signature: None,
+                                reasoning_type: None,
+                                image: None,
+                                response_metadata: None,
+                            });
                         }
                     }
+                } else {
+                    stream_anomalies.malformed("chunk", &event.data);
+                    if roci_debug_enabled() {
+                        debug!(data_len = event.data.len(), "OpenAI stream parse failed");
+                    }
                 }
             }
 
             if roci_debug_enabled() {
-                debug!(chunk_count, line_count, byte_count, "OpenAI stream ended");
+                debug!(event_count, "OpenAI stream ended");
             }
         };
 
-        Ok(Box::pin(stream))
+        Ok(with_anomaly_count(
+            with_payload_sizes(
+                with_response_metadata(Box::pin(stream), response_metadata),
+                payload,
+                received,
+            ),
+            anomalies,
+        ))
+    }
+}
+
+/// Report where a chat completion body departs from the documented shape.
+/// Parsing tolerates every one of these.
+fn validate_chat_response(raw: &serde_json::Value, anomalies: &ResponseAnomalies) {
+    if !anomalies.is_enabled() {
+        return;
+    }
+    match raw.get("usage") {
+        Some(usage) if usage.is_object() => {
+            for field in ["prompt_tokens", "completion_tokens"] {
+                if usage.get(field).and_then(|v| v.as_u64()).is_none() {
+                    anomalies.missing_field(&format!("usage.{field}"), raw);
+                }
+            }
+        }
+        _ => anomalies.missing_field("usage", raw),
+    }
+    match raw.pointer("/choices/0") {
+        Some(choice) => match choice.get("finish_reason").and_then(|v| v.as_str()) {
+            Some(reason) if parse_finish_reason(reason).is_other() => {
+                anomalies.unknown_finish_reason("choices.0.finish_reason", reason, raw);
+            }
+            Some(_) => {}
+            None => anomalies.missing_field("choices.0.finish_reason", raw),
+        },
+        None => anomalies.missing_field("choices.0", raw),
     }
 }
 
-fn parse_finish_reason(s: &str) -> Option<FinishReason> {
+/// Map a chat completion `finish_reason`, keeping undocumented values.
+fn parse_finish_reason(s: &str) -> FinishReason {
     match s {
-        "stop" => Some(FinishReason::Stop),
-        "length" => Some(FinishReason::Length),
-        "tool_calls" => Some(FinishReason::ToolCalls),
-        "content_filter" => Some(FinishReason::ContentFilter),
-        _ => None,
+        "stop" => FinishReason::Stop,
+        "length" => FinishReason::Length,
+        // `function_call` is the pre-tools spelling of `tool_calls`.
+        "tool_calls" | "function_call" => FinishReason::ToolCalls,
+        "content_filter" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_string()),
     }
 }
 
@@ -732,6 +884,9 @@ fn message_to_openai(msg: &ModelMessage) -> serde_json::Value {
 struct OpenAiChatResponse {
     choices: Vec<OpenAiChoice>,
     usage: Option<OpenAiUsage>,
+    /// Source URLs from compatible providers with search (e.g. Grok live search).
+    #[serde(default)]
+    citations: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
@@ -743,11 +898,22 @@ struct OpenAiChoice {
 #[derive(Deserialize)]
 struct OpenAiMessage {
     content: Option<String>,
+    /// Set instead of `content` when the model declines (structured outputs).
+    #[serde(default)]
+    refusal: Option<String>,
+    /// Reasoning returned separately by compatible servers (DeepSeek-style
+    /// `reasoning_content`, Groq `reasoning_format: parsed`).
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    reasoning: Option<String>,
     tool_calls: Option<Vec<OpenAiToolCall>>,
 }
 
 #[derive(Deserialize)]
 struct OpenAiToolCall {
+    /// Some compatible servers omit it; the request's allocator fills it in.
+    #[serde(default)]
     id: String,
     function: OpenAiFunction,
 }
@@ -758,17 +924,43 @@ struct OpenAiFunction {
     arguments: String,
 }
 
+/// Token counts; absent fields read as zero and are reported under strict
+/// parsing.
 #[derive(Deserialize)]
 struct OpenAiUsage {
-    prompt_tokens: u32,
-    completion_tokens: u32,
-    total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens: Option<u32>,
+    #[serde(default)]
+    completion_tokens: Option<u32>,
+    #[serde(default)]
+    total_tokens: Option<u32>,
+}
+
+impl OpenAiUsage {
+    fn into_usage(self) -> Usage {
+        let input_tokens = self.prompt_tokens.unwrap_or_default();
+        let output_tokens = self.completion_tokens.unwrap_or_default();
+        Usage {
+            input_tokens,
+            output_tokens,
+            total_tokens: self.total_tokens.unwrap_or(input_tokens + output_tokens),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct OpenAiStreamChunk {
     choices: Vec<OpenAiStreamChoice>,
     usage: Option<OpenAiUsage>,
+    /// Groq reports stream usage here instead of in `usage`.
+    #[serde(default)]
+    x_groq: Option<GroqStreamExtras>,
+}
+
+#[derive(Deserialize)]
+struct GroqStreamExtras {
+    usage: Option<OpenAiUsage>,
 }
 
 #[derive(Deserialize)]
@@ -780,6 +972,8 @@ struct OpenAiStreamChoice {
 #[derive(Deserialize)]
 struct OpenAiStreamDelta {
     content: Option<String>,
+    #[serde(default)]
+    refusal: Option<String>,
     reasoning_content: Option<String>,
     reasoning: Option<String>,
     reasoning_text: Option<String>,
@@ -802,7 +996,7 @@ struct OpenAiStreamFunctionDelta {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{body_partial_json, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn settings(
@@ -828,6 +1022,10 @@ mod tests {
             user: None,
             anthropic: None,
             google: None,
+            mistral: None,
+            grok: None,
+            groq: None,
+            assistant_prefix: None,
             tool_choice: None,
             stream_idle_timeout_ms: None,
         }
@@ -838,7 +1036,7 @@ mod tests {
         headers: reqwest::header::HeaderMap,
     ) -> ProviderRequest {
         ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -848,6 +1046,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         }
     }
 
@@ -906,6 +1105,236 @@ mod tests {
         assert_eq!(deltas[6].event_type, StreamEventType::Done);
     }
 
+    #[tokio::test]
+    async fn generate_text_surfaces_structured_output_refusal() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "refusal": "I'm sorry, I can't help with that."
+                    },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 5, "completion_tokens": 7, "total_tokens": 12 }
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Gpt4o,
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        );
+        let response = provider
+            .generate_text(&request_with_headers(None, HeaderMap::new()))
+            .await
+            .expect("response");
+
+        assert_eq!(response.text, "");
+        assert_eq!(
+            response.refusal.as_deref(),
+            Some("I'm sorry, I can't help with that.")
+        );
+        assert_eq!(response.finish_reason, Some(FinishReason::Refusal));
+    }
+
+    #[tokio::test]
+    async fn response_headers_surface_as_response_metadata() {
+        let server = MockServer::start().await;
+        let stream_body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let with_headers = |template: ResponseTemplate| {
+            template
+                .insert_header("x-request-id", "req_123")
+                .insert_header("x-ratelimit-remaining-requests", "42")
+                .insert_header("x-ratelimit-reset-tokens", "1s")
+        };
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "stream": true })))
+            .respond_with(with_headers(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(stream_body),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(with_headers(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({
+                    "choices": [{ "message": { "content": "hi" }, "finish_reason": "stop" }]
+                }),
+            )))
+            .mount(&server)
+            .await;
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Gpt4o,
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        );
+        let request = request_with_headers(None, HeaderMap::new());
+
+        let response = provider.generate_text(&request).await.expect("response");
+        let deltas = provider
+            .stream_text(&request)
+            .await
+            .expect("stream response")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("stream deltas");
+
+        let metadata = response.response_metadata.expect("response metadata");
+        assert_eq!(metadata.provider_request_id.as_deref(), Some("req_123"));
+        let rate_limit = metadata.rate_limit.clone().expect("rate limit");
+        assert_eq!(rate_limit.requests_remaining, Some(42));
+        assert_eq!(rate_limit.tokens_reset_ms, Some(1_000));
+        let with_metadata: Vec<_> = deltas
+            .iter()
+            .filter(|delta| delta.response_metadata.is_some())
+            .collect();
+        assert_eq!(with_metadata.len(), 1);
+        assert_eq!(with_metadata[0].event_type, StreamEventType::Done);
+        let streamed = with_metadata[0].response_metadata.as_deref().unwrap();
+        assert_eq!(
+            streamed.payload.and_then(|payload| payload.response_bytes),
+            Some(stream_body.len() as u64)
+        );
+        // Both calls saw the same headers; only their payload sizes differ.
+        assert_eq!(
+            ResponseMetadata {
+                payload: None,
+                ..streamed.clone()
+            },
+            ResponseMetadata {
+                payload: None,
+                ..metadata
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_emits_refusal_deltas_and_refusal_finish() {
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":null,\"refusal\":\"\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"refusal\":\"I can't \"},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"refusal\":\"help.\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Gpt4o,
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        );
+        let deltas = provider
+            .stream_text(&request_with_headers(None, HeaderMap::new()))
+            .await
+            .expect("stream response")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("stream deltas");
+
+        let refusal: String = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::RefusalDelta)
+            .map(|delta| delta.text.as_str())
+            .collect();
+        assert_eq!(refusal, "I can't help.");
+        assert!(deltas
+            .iter()
+            .all(|delta| delta.event_type != StreamEventType::TextDelta));
+        let finish = deltas.iter().find_map(|delta| delta.finish_reason.clone());
+        assert_eq!(finish, Some(FinishReason::Refusal));
+    }
+
+    #[tokio::test]
+    async fn stream_assigns_missing_and_duplicate_tool_call_ids() {
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"name\":\"read\",\"arguments\":\"{}\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_0\",\"function\":{\"name\":\"read\",\"arguments\":\"{}\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":2,\"id\":\"call_0\",\"function\":{\"name\":\"write\",\"arguments\":\"{}\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Custom("local-model".to_string()),
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        );
+        let allocator = roci_core::provider::ToolCallIdAllocator::new("run1");
+        let mut request = request_with_headers(None, HeaderMap::new());
+        request.tool_call_ids = Some(allocator.for_iteration(1));
+        let ids = provider
+            .stream_text(&request)
+            .await
+            .expect("stream response")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .filter_map(|delta| {
+                let delta = delta.expect("stream delta");
+                (delta.event_type == StreamEventType::ToolCallDelta)
+                    .then(|| delta.tool_call.expect("tool call").id)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec!["call_run1_1_0_read", "call_0", "call_0-2"]);
+        assert_eq!(allocator.original_id("call_0-2").as_deref(), Some("call_0"));
+    }
+
+    #[test]
+    fn finish_reasons_map_known_values_and_keep_the_rest() {
+        let cases = [
+            ("stop", FinishReason::Stop),
+            ("length", FinishReason::Length),
+            ("tool_calls", FinishReason::ToolCalls),
+            ("function_call", FinishReason::ToolCalls),
+            ("content_filter", FinishReason::ContentFilter),
+            ("eos", FinishReason::Other("eos".to_string())),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_finish_reason(raw), expected, "{raw}");
+        }
+    }
+
     #[test]
     fn chat_request_uses_max_completion_tokens_for_gpt5() {
         let provider = OpenAiProvider::new(
@@ -915,7 +1344,7 @@ mod tests {
             None,
         );
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: settings(Some(128), Some(0.4), Some(0.5), Some(0.1), Some(0.2)),
             tools: None,
             response_format: None,
@@ -925,6 +1354,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
@@ -946,7 +1376,7 @@ mod tests {
         let message =
             ModelMessage::tool_result("call_1", serde_json::Value::String("ok".to_string()), false);
         let request = ProviderRequest {
-            messages: vec![message],
+            messages: vec![message].into(),
             settings: settings(None, None, None, None, None),
             tools: None,
             response_format: None,
@@ -956,12 +1386,108 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
         assert_eq!(body["messages"][0]["content"], "ok");
     }
 
+    #[test]
+    fn tool_definitions_are_strict_when_the_schema_allows() {
+        let provider = OpenAiProvider::new(OpenAiModel::Gpt4o, "test-key".to_string(), None, None);
+        let strict_tool = roci_core::provider::ToolDefinition::new(
+            "read_file",
+            "Read a file",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string"},
+                    "limit": {"type": ["integer", "null"]}
+                },
+                "required": ["path"]
+            }),
+        );
+        let loose_parameters = serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}, "limit": {"type": "integer"}},
+            "required": ["path"]
+        });
+        let loose_tool = roci_core::provider::ToolDefinition::new(
+            "list_dir",
+            "List a directory",
+            loose_parameters.clone(),
+        );
+        let request = ProviderRequest {
+            messages: vec![ModelMessage::user("hello")].into(),
+            settings: GenerationSettings::default(),
+            tools: Some(vec![strict_tool, loose_tool]),
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        };
+
+        let body = provider.build_request_body(&request, false);
+
+        assert_eq!(
+            body["tools"][0]["function"],
+            serde_json::json!({
+                "name": "read_file",
+                "description": "Read a file",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "limit": {"type": ["integer", "null"]}
+                    },
+                    "required": ["limit", "path"],
+                    "additionalProperties": false
+                },
+                "strict": true
+            })
+        );
+        assert_eq!(body["tools"][1]["function"]["strict"], false);
+        assert_eq!(body["tools"][1]["function"]["parameters"], loose_parameters);
+    }
+
+    #[test]
+    fn tool_definitions_stay_non_strict_without_json_schema_support() {
+        let provider = OpenAiProvider::new(OpenAiModel::Gpt4o, "test-key".to_string(), None, None)
+            .with_capabilities(ModelCapabilities::default());
+        let parameters = serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        });
+        let request = ProviderRequest {
+            messages: vec![ModelMessage::user("hello")].into(),
+            settings: GenerationSettings::default(),
+            tools: Some(vec![roci_core::provider::ToolDefinition::new(
+                "read_file",
+                "Read a file",
+                parameters.clone(),
+            )]),
+            response_format: None,
+            api_key_override: None,
+            headers: reqwest::header::HeaderMap::new(),
+            metadata: std::collections::HashMap::new(),
+            payload_callback: None,
+            session_id: None,
+            transport: None,
+            tool_call_ids: None,
+        };
+
+        let body = provider.build_request_body(&request, false);
+
+        assert_eq!(body["tools"][0]["function"]["strict"], false);
+        assert_eq!(body["tools"][0]["function"]["parameters"], parameters);
+    }
+
     #[test]
     fn provider_attachment_payload_openai_chat_maps_text_and_image_parts() {
         let provider = OpenAiProvider::new(OpenAiModel::Gpt4o, "test-key".to_string(), None, None);
@@ -980,7 +1506,8 @@ mod tests {
                 name: None,
                 timestamp: None,
                 metadata: None,
-            }],
+            }]
+            .into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -990,6 +1517,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
@@ -1014,7 +1542,7 @@ mod tests {
             "User attached unsupported media: doc.pdf (application/pdf, 7 bytes). Content omitted.";
         let provider = OpenAiProvider::new(OpenAiModel::Gpt4o, "test-key".to_string(), None, None);
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user(marker)],
+            messages: vec![ModelMessage::user(marker)].into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1024,6 +1552,7 @@ mod tests {
             payload_callback: None,
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
 
         let body = provider.build_request_body(&request, false);
@@ -1040,7 +1569,7 @@ mod tests {
         let captured_model = std::sync::Arc::new(std::sync::Mutex::new(None::<String>));
         let captured_model_for_hook = captured_model.clone();
         let request = ProviderRequest {
-            messages: vec![ModelMessage::user("hello")],
+            messages: vec![ModelMessage::user("hello")].into(),
             settings: GenerationSettings::default(),
             tools: None,
             response_format: None,
@@ -1055,6 +1584,7 @@ mod tests {
             })),
             session_id: None,
             transport: None,
+            tool_call_ids: None,
         };
         let body = provider.build_request_body(&request, false);
         provider.emit_payload_callback(&request, &body);
@@ -1212,4 +1742,154 @@ mod tests {
             Some("application/json")
         );
     }
+
+    #[test]
+    fn strict_parsing_reports_drifted_chat_responses() {
+        use roci_core::provider::anomalies::ResponseAnomalyKind;
+        let raw = serde_json::json!({
+            "choices": [{"message": {"content": "kept"}, "finish_reason": "eos"}],
+            "usage": {"input_tokens": 5, "completion_tokens": 7}
+        });
+        let anomalies = ResponseAnomalies::new("openai", true);
+
+        validate_chat_response(&raw, &anomalies);
+
+        let found = anomalies
+            .reports()
+            .into_iter()
+            .map(|anomaly| (anomaly.kind, anomaly.field))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    ResponseAnomalyKind::MissingField,
+                    "usage.prompt_tokens".to_string()
+                ),
+                (
+                    ResponseAnomalyKind::UnknownFinishReason,
+                    "choices.0.finish_reason".to_string()
+                ),
+            ]
+        );
+
+        let disabled = ResponseAnomalies::new("openai", false);
+        validate_chat_response(&raw, &disabled);
+        assert_eq!(disabled.count(), 0);
+        let data: OpenAiChatResponse = serde_json::from_value(raw).expect("tolerant parse");
+        let usage = data.usage.map(OpenAiUsage::into_usage).unwrap_or_default();
+        assert_eq!(
+            (usage.input_tokens, usage.output_tokens, usage.total_tokens),
+            (0, 7, 7)
+        );
+    }
+
+    fn gunzip(body: &[u8]) -> serde_json::Value {
+        let mut json = Vec::new();
+        std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(body), &mut json)
+            .expect("gzip body");
+        serde_json::from_slice(&json).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn large_requests_are_gzipped_and_sizes_recorded() {
+        let server = MockServer::start().await;
+        let reply = serde_json::json!({
+            "choices": [{ "message": { "content": "hi" }, "finish_reason": "stop" }]
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&reply))
+            .mount(&server)
+            .await;
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Gpt4o,
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        )
+        .with_request_compression(Some(RequestCompression::new(16)));
+        let request = request_with_headers(None, HeaderMap::new());
+
+        let response = provider.generate_text(&request).await.expect("response");
+
+        let received = server.received_requests().await.expect("recorded requests");
+        assert_eq!(
+            received[0]
+                .headers
+                .get("content-encoding")
+                .and_then(|value| value.to_str().ok()),
+            Some("gzip")
+        );
+        let body = gunzip(&received[0].body);
+        assert_eq!(body, provider.build_request_body(&request, false));
+        let payload = response
+            .response_metadata
+            .and_then(|metadata| metadata.payload)
+            .expect("payload sizes");
+        assert!(payload.request_gzipped);
+        assert_eq!(payload.request_sent_bytes, received[0].body.len() as u64);
+        assert_eq!(
+            payload.request_bytes,
+            serde_json::to_vec(&body).unwrap().len() as u64
+        );
+        assert_eq!(
+            payload.response_bytes,
+            Some(serde_json::to_vec(&reply).unwrap().len() as u64)
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_gzip_falls_back_to_plain_bodies_for_the_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(wiremock::matchers::header("content-encoding", "gzip"))
+            .respond_with(ResponseTemplate::new(415))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "content": "hi" }, "finish_reason": "stop" }]
+            })))
+            .mount(&server)
+            .await;
+        let provider = OpenAiProvider::new(
+            OpenAiModel::Gpt4o,
+            "test-key".to_string(),
+            Some(server.uri()),
+            None,
+        )
+        .with_request_compression(Some(RequestCompression::new(16)));
+        let request = request_with_headers(None, HeaderMap::new());
+
+        let first = provider
+            .generate_text(&request)
+            .await
+            .expect("first response");
+        let second = provider
+            .generate_text(&request)
+            .await
+            .expect("second response");
+
+        let gzipped: Vec<bool> = server
+            .received_requests()
+            .await
+            .expect("recorded requests")
+            .iter()
+            .map(|request| request.headers.contains_key("content-encoding"))
+            .collect();
+        assert_eq!(gzipped, vec![true, false, false]);
+        for response in [first, second] {
+            assert_eq!(response.text, "hi");
+            let payload = response
+                .response_metadata
+                .and_then(|metadata| metadata.payload)
+                .expect("payload sizes");
+            assert!(!payload.request_gzipped);
+            assert_eq!(payload.request_sent_bytes, payload.request_bytes);
+        }
+    }
 }
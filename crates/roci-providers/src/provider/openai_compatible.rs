@@ -9,6 +9,7 @@ use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::TextStreamDelta;
 
 use super::openai::OpenAiProvider;
+use super::reasoning_tags::ReasoningTagConfig;
 use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
 
 use crate::models::openai::OpenAiModel;
@@ -16,6 +17,8 @@ use crate::models::openai::OpenAiModel;
 /// Generic provider for any OpenAI-compatible API.
 pub struct OpenAiCompatibleProvider {
     inner: OpenAiProvider,
+    capabilities: ModelCapabilities,
+    reasoning_tags: ReasoningTagConfig,
 }
 
 impl OpenAiCompatibleProvider {
@@ -30,6 +33,7 @@ impl OpenAiCompatibleProvider {
         extra_headers: HeaderMap,
     ) -> Self {
         let model = OpenAiModel::Custom(model_id);
+        let capabilities = model.capabilities();
         Self {
             inner: OpenAiProvider::new_with_extra_headers(
                 model,
@@ -38,8 +42,30 @@ impl OpenAiCompatibleProvider {
                 None,
                 extra_headers,
             ),
+            capabilities,
+            reasoning_tags: ReasoningTagConfig::default(),
         }
     }
+
+    /// Allow requests without an API key, for local servers that do not
+    /// check one. A configured key is still sent.
+    pub fn without_required_api_key(mut self) -> Self {
+        self.inner = self.inner.with_auth_required(false);
+        self
+    }
+
+    /// Override the default capabilities (e.g. with probed values).
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Configure extraction of inline reasoning tags such as
+    /// `<think>...</think>` from generated text.
+    pub fn with_reasoning_tags(mut self, reasoning_tags: ReasoningTagConfig) -> Self {
+        self.reasoning_tags = reasoning_tags;
+        self
+    }
 }
 
 #[async_trait]
@@ -51,19 +77,21 @@ impl ModelProvider for OpenAiCompatibleProvider {
         self.inner.model_id()
     }
     fn capabilities(&self) -> &ModelCapabilities {
-        self.inner.capabilities()
+        &self.capabilities
     }
     async fn generate_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<ProviderResponse, RociError> {
-        self.inner.generate_text(request).await
+        let response = self.inner.generate_text(request).await?;
+        Ok(self.reasoning_tags.apply_to_response(response))
     }
     async fn stream_text(
         &self,
         request: &ProviderRequest,
     ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
-        self.inner.stream_text(request).await
+        let stream = self.inner.stream_text(request).await?;
+        Ok(self.reasoning_tags.apply_to_stream(stream))
     }
 }
 
@@ -0,0 +1,182 @@
+//! Stream resumption for Responses API background mode.
+//!
+//! A `background: true` response keeps running server-side when the client
+//! connection drops. The stream is resumed with
+//! `GET /responses/{id}?stream=true&starting_after={seq}`, and the continued
+//! events are spliced into the original event stream.
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use reqwest::header::HeaderMap;
+
+use roci_core::error::RociError;
+use roci_core::provider::http::{shared_client, sse_events, SseEvent};
+use roci_core::types::{StreamEventType, TextStreamDelta};
+
+use super::errors::success_or_openai_error;
+
+/// Where and how often to resume a dropped background stream.
+pub(crate) struct ResumeTarget {
+    pub(crate) responses_url: String,
+    pub(crate) headers: HeaderMap,
+    pub(crate) max_attempts: u32,
+}
+
+impl ResumeTarget {
+    async fn reconnect(
+        &self,
+        response_id: &str,
+        starting_after: Option<u64>,
+    ) -> Result<reqwest::Response, RociError> {
+        let mut query = vec![("stream", "true".to_string())];
+        if let Some(sequence) = starting_after {
+            query.push(("starting_after", sequence.to_string()));
+        }
+        let resp = shared_client()
+            .get(format!("{}/{response_id}", self.responses_url))
+            .headers(self.headers.clone())
+            .query(&query)
+            .send()
+            .await?;
+        success_or_openai_error(resp).await
+    }
+}
+
+/// Response id and position of the last event received.
+#[derive(Debug, Default)]
+struct StreamCursor {
+    response_id: Option<String>,
+    last_sequence: Option<u64>,
+    finished: bool,
+}
+
+impl StreamCursor {
+    /// Record `event`; returns `false` for a replayed event that was already
+    /// delivered before a resume.
+    fn observe(&mut self, event: &SseEvent) -> bool {
+        if event.is_done() {
+            self.finished = true;
+            return true;
+        }
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(&event.data) else {
+            return true;
+        };
+        if let Some(sequence) = payload.get("sequence_number").and_then(|v| v.as_u64()) {
+            if self.last_sequence.is_some_and(|last| sequence <= last) {
+                return false;
+            }
+            self.last_sequence = Some(sequence);
+        }
+        if let Some(id) = payload
+            .get("response")
+            .and_then(|response| response.get("id"))
+            .and_then(|id| id.as_str())
+        {
+            self.response_id = Some(id.to_string());
+        }
+        let event_type = payload
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or(event.event.as_str());
+        if matches!(
+            event_type,
+            "response.completed"
+                | "response.done"
+                | "response.failed"
+                | "response.error"
+                | "response.incomplete"
+        ) {
+            self.finished = true;
+        }
+        true
+    }
+}
+
+/// SSE events from `resp`. With a `resume` target, a stream that errors or
+/// ends before a terminal event is resumed from the last sequence number, up
+/// to `max_attempts` times; after that the error is
+/// [`RociError::BackgroundInterrupted`].
+pub(crate) fn resumable_sse_events(
+    resp: reqwest::Response,
+    resume: Option<ResumeTarget>,
+) -> BoxStream<'static, Result<SseEvent, RociError>> {
+    let stream = async_stream::stream! {
+        let mut sse = sse_events(resp.bytes_stream()).boxed();
+        let mut cursor = StreamCursor::default();
+        let mut attempts = 0u32;
+        loop {
+            let mut cause = match sse.next().await {
+                Some(Ok(event)) => {
+                    if cursor.observe(&event) {
+                        yield Ok(event);
+                    }
+                    continue;
+                }
+                Some(Err(e)) => RociError::Network(e),
+                None if cursor.finished || resume.is_none() => break,
+                None => RociError::Stream(
+                    "stream ended before the response completed".to_string(),
+                ),
+            };
+            let (Some(target), Some(response_id)) = (resume.as_ref(), cursor.response_id.clone())
+            else {
+                yield Err(cause);
+                break;
+            };
+            let resumed = loop {
+                if attempts >= target.max_attempts {
+                    break None;
+                }
+                attempts += 1;
+                tracing::debug!(
+                    response_id = %response_id,
+                    starting_after = ?cursor.last_sequence,
+                    attempt = attempts,
+                    "OpenAI Responses resuming background stream"
+                );
+                match target.reconnect(&response_id, cursor.last_sequence).await {
+                    Ok(resp) => break Some(resp),
+                    Err(e) => cause = e,
+                }
+            };
+            match resumed {
+                Some(resp) => sse = sse_events(resp.bytes_stream()).boxed(),
+                None => {
+                    yield Err(RociError::BackgroundInterrupted {
+                        response_id,
+                        partial_text: String::new(),
+                        message: cause.to_string(),
+                    });
+                    break;
+                }
+            }
+        }
+    };
+    Box::pin(stream)
+}
+
+/// Fill [`RociError::BackgroundInterrupted::partial_text`] with the text
+/// deltas that preceded the interruption.
+pub(crate) fn with_partial_text(
+    deltas: BoxStream<'static, Result<TextStreamDelta, RociError>>,
+) -> BoxStream<'static, Result<TextStreamDelta, RociError>> {
+    let mut partial = String::new();
+    Box::pin(deltas.map(move |item| match item {
+        Ok(delta) => {
+            if delta.event_type == StreamEventType::TextDelta {
+                partial.push_str(&delta.text);
+            }
+            Ok(delta)
+        }
+        Err(RociError::BackgroundInterrupted {
+            response_id,
+            message,
+            ..
+        }) => Err(RociError::BackgroundInterrupted {
+            response_id,
+            partial_text: std::mem::take(&mut partial),
+            message,
+        }),
+        Err(e) => Err(e),
+    }))
+}
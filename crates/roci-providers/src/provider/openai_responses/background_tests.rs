@@ -0,0 +1,177 @@
+use super::*;
+use roci_core::provider::ProviderRequest;
+use wiremock::matchers::{body_partial_json, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CREATED: &str = "data: {\"type\":\"response.created\",\"sequence_number\":0,\"response\":{\"id\":\"resp_bg\",\"status\":\"queued\"}}\n\n";
+const HELLO: &str = "data: {\"type\":\"response.output_text.delta\",\"sequence_number\":1,\"item_id\":\"msg_1\",\"delta\":\"Hello, \"}\n\n";
+const WOR: &str = "data: {\"type\":\"response.output_text.delta\",\"sequence_number\":2,\"item_id\":\"msg_1\",\"delta\":\"wor\"}\n\n";
+const LD: &str = "data: {\"type\":\"response.output_text.delta\",\"sequence_number\":3,\"item_id\":\"msg_1\",\"delta\":\"ld!\"}\n\n";
+const COMPLETED: &str = "data: {\"type\":\"response.completed\",\"sequence_number\":4,\"response\":{\"id\":\"resp_bg\",\"status\":\"completed\",\"output\":[]}}\n\n";
+
+fn sse(events: &[&str]) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_string(events.concat())
+}
+
+fn request(background: Option<bool>, max_background_resumes: Option<u32>) -> ProviderRequest {
+    ProviderRequest {
+        messages: vec![ModelMessage::user("hello")].into(),
+        settings: GenerationSettings {
+            openai_responses: Some(OpenAiResponsesOptions {
+                background,
+                max_background_resumes,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        tools: None,
+        response_format: None,
+        api_key_override: None,
+        headers: reqwest::header::HeaderMap::new(),
+        metadata: std::collections::HashMap::new(),
+        payload_callback: None,
+        session_id: None,
+        transport: None,
+        tool_call_ids: None,
+    }
+}
+
+fn provider(server: &MockServer) -> OpenAiResponsesProvider {
+    OpenAiResponsesProvider::new(
+        OpenAiModel::Gpt41,
+        "test-key".to_string(),
+        Some(server.uri()),
+        None,
+    )
+}
+
+async fn stream_all(
+    provider: &OpenAiResponsesProvider,
+    request: &ProviderRequest,
+) -> Vec<Result<TextStreamDelta, RociError>> {
+    provider
+        .stream_text(request)
+        .await
+        .expect("stream response")
+        .collect()
+        .await
+}
+
+fn text_of(deltas: &[Result<TextStreamDelta, RociError>]) -> String {
+    deltas
+        .iter()
+        .filter_map(|delta| delta.as_ref().ok())
+        .filter(|delta| delta.event_type == StreamEventType::TextDelta)
+        .map(|delta| delta.text.as_str())
+        .collect()
+}
+
+#[test]
+fn background_option_sets_background_and_store() {
+    let provider =
+        OpenAiResponsesProvider::new(OpenAiModel::Gpt41, "test-key".to_string(), None, None);
+
+    let body = provider.build_request_body(&request(Some(true), None), true);
+
+    assert_eq!(body["background"], true);
+    assert_eq!(body["store"], true);
+    assert_eq!(
+        provider.background_resume_attempts(&request(Some(true), None)),
+        Some(3)
+    );
+    assert_eq!(
+        provider.background_resume_attempts(&request(None, Some(5))),
+        None
+    );
+}
+
+#[tokio::test]
+async fn dropped_background_stream_resumes_after_last_sequence_number() {
+    let uninterrupted = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(sse(&[CREATED, HELLO, WOR, LD, COMPLETED]))
+        .mount(&uninterrupted)
+        .await;
+    let expected = text_of(&stream_all(&provider(&uninterrupted), &request(None, None)).await);
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .and(body_partial_json(
+            serde_json::json!({ "background": true, "store": true }),
+        ))
+        .respond_with(sse(&[CREATED, HELLO, WOR]))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/responses/resp_bg"))
+        .and(query_param("stream", "true"))
+        .and(query_param("starting_after", "2"))
+        .respond_with(sse(&[WOR, LD, COMPLETED]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let deltas = stream_all(&provider(&server), &request(Some(true), None)).await;
+
+    assert!(deltas.iter().all(Result::is_ok), "{deltas:?}");
+    assert_eq!(text_of(&deltas), "Hello, world!");
+    assert_eq!(text_of(&deltas), expected);
+    let done = deltas.last().unwrap().as_ref().unwrap();
+    assert_eq!(done.event_type, StreamEventType::Done);
+    assert_eq!(done.finish_reason, Some(FinishReason::Stop));
+}
+
+#[tokio::test]
+async fn exhausted_resumes_surface_partial_text_and_response_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(sse(&[CREATED, HELLO, WOR]))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/responses/resp_bg"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let deltas = stream_all(&provider(&server), &request(Some(true), Some(2))).await;
+
+    match deltas.last().expect("final item") {
+        Err(RociError::BackgroundInterrupted {
+            response_id,
+            partial_text,
+            ..
+        }) => {
+            assert_eq!(response_id, "resp_bg");
+            assert_eq!(partial_text, "Hello, wor");
+        }
+        other => panic!("expected BackgroundInterrupted, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn dropped_stream_without_background_ends_without_resuming() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(sse(&[CREATED, HELLO, WOR]))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let deltas = stream_all(&provider(&server), &request(None, None)).await;
+
+    assert!(deltas.iter().all(Result::is_ok));
+    assert_eq!(text_of(&deltas), "Hello, wor");
+}
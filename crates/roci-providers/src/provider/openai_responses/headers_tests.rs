@@ -10,7 +10,7 @@ fn openai_responses_headers_include_session_affinity() {
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let session_id = "session-1";
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -20,6 +20,7 @@ fn openai_responses_headers_include_session_affinity() {
         payload_callback: None,
         session_id: Some(session_id.to_string()),
         transport: None,
+        tool_call_ids: None,
     };
 
     let headers = provider.build_headers(&request).expect("headers");
@@ -42,7 +43,7 @@ fn openai_responses_headers_omit_session_affinity_when_absent() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -52,6 +53,7 @@ fn openai_responses_headers_omit_session_affinity_when_absent() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let headers = provider.build_headers(&request).expect("headers");
@@ -69,7 +71,7 @@ fn codex_headers_include_session_affinity() {
     );
     let session_id = "codex-session-1";
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -79,6 +81,7 @@ fn codex_headers_include_session_affinity() {
         payload_callback: None,
         session_id: Some(session_id.to_string()),
         transport: None,
+        tool_call_ids: None,
     };
 
     let headers = provider.build_headers(&request).expect("headers");
@@ -105,7 +108,7 @@ fn codex_headers_omit_session_affinity_when_absent() {
         Some("acct-123".to_string()),
     );
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -115,6 +118,7 @@ fn codex_headers_omit_session_affinity_when_absent() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let headers = provider.build_headers(&request).expect("headers");
@@ -132,7 +136,7 @@ fn headers_merge_request_overrides_and_api_key_override() {
         reqwest::header::HeaderValue::from_static("value"),
     );
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -142,6 +146,7 @@ fn headers_merge_request_overrides_and_api_key_override() {
         payload_callback: None,
         session_id: None,
         transport: Some(roci_core::provider::TRANSPORT_PROXY.to_string()),
+        tool_call_ids: None,
     };
 
     let headers = provider
@@ -171,7 +176,7 @@ fn headers_merge_request_overrides_and_api_key_override() {
 fn headers_use_request_api_key_override_when_default_key_missing() {
     let provider = OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, String::new(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -181,6 +186,7 @@ fn headers_use_request_api_key_override_when_default_key_missing() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let headers = provider
@@ -199,7 +205,7 @@ fn headers_use_request_api_key_override_when_default_key_missing() {
 fn headers_error_when_no_default_or_request_api_key() {
     let provider = OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, String::new(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -209,6 +215,7 @@ fn headers_error_when_no_default_or_request_api_key() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let err = provider.build_headers(&request).unwrap_err();
@@ -223,7 +230,7 @@ fn payload_callback_receives_request_payload() {
     let captured_model = std::sync::Arc::new(std::sync::Mutex::new(None::<String>));
     let captured_model_for_hook = captured_model.clone();
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -238,6 +245,7 @@ fn payload_callback_receives_request_payload() {
         })),
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     provider.emit_payload_callback(&request, &body);
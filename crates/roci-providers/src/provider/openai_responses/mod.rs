@@ -1,5 +1,6 @@
 //! OpenAI Responses API provider (for GPT-5, o3, o4-mini, etc.)
 
+mod background;
 mod errors;
 mod headers;
 mod request;
@@ -17,12 +18,15 @@ use roci_core::models::capabilities::ModelCapabilities;
 use roci_core::types::*;
 use roci_core::util::debug::roci_debug_enabled;
 
-use roci_core::provider::http::shared_client;
-use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
+use roci_core::provider::http::{
+    response_metadata, shared_client, with_response_metadata, ResponseHeaderRules,
+};
+use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse, ToolLimits};
 
+use background::{resumable_sse_events, with_partial_text, ResumeTarget};
 use errors::success_or_openai_error;
 use response::ResponsesApiResponse;
-use stream::{extract_response_error, tool_call_delta, StreamToolCallState};
+use stream::{extract_response_error, refusal_delta, tool_call_delta, StreamToolCallState};
 
 const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
 
@@ -58,6 +62,26 @@ impl OpenAiResponsesProvider {
             is_codex,
         }
     }
+
+    /// Use `effort` when a request does not set one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Configuration`] when the model does not support
+    /// `effort`.
+    pub fn with_default_reasoning_effort(
+        mut self,
+        effort: ReasoningEffort,
+    ) -> Result<Self, RociError> {
+        if !self.capabilities.supports_reasoning_effort(effort) {
+            return Err(RociError::Configuration(format!(
+                "model '{}' does not support reasoning effort '{effort}'",
+                self.model.as_str()
+            )));
+        }
+        self.capabilities.reasoning_effort.default = Some(effort);
+        Ok(self)
+    }
 }
 
 #[async_trait]
@@ -74,6 +98,14 @@ impl ModelProvider for OpenAiResponsesProvider {
         &self.capabilities
     }
 
+    fn tool_limits(&self) -> ToolLimits {
+        ToolLimits::OPENAI
+    }
+
+    fn supports_response_format_with_tools(&self) -> bool {
+        self.capabilities.supports_json_schema
+    }
+
     async fn generate_text(
         &self,
         request: &ProviderRequest,
@@ -97,9 +129,12 @@ impl ModelProvider for OpenAiResponsesProvider {
 
         let resp = success_or_openai_error(resp).await?;
 
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::OPENAI);
         let payload: serde_json::Value = resp.json().await?;
         let data: ResponsesApiResponse = serde_json::from_value(payload)?;
-        Self::parse_response(data)
+        let mut response = Self::parse_response(data, &mut request.begin_tool_call_ids())?;
+        response.response_metadata = Some(response_metadata);
+        Ok(response)
     }
 
     async fn stream_text(
@@ -110,347 +145,376 @@ impl ModelProvider for OpenAiResponsesProvider {
         let body = self.build_request_body(request, true);
         self.emit_payload_callback(request, &body);
         let url = self.responses_url(request);
+        let headers = self.build_headers(request)?;
 
         debug!(model = self.model.as_str(), "OpenAI Responses stream_text");
 
         let resp = shared_client()
             .post(&url)
-            .headers(self.build_headers(request)?)
+            .headers(headers.clone())
             .json(&body)
             .send()
             .await?;
 
         let resp = success_or_openai_error(resp).await?;
 
-        let byte_stream = resp.bytes_stream();
+        let response_metadata = response_metadata(resp.headers(), &ResponseHeaderRules::OPENAI);
+        let resume = self
+            .background_resume_attempts(request)
+            .map(|max_attempts| ResumeTarget {
+                responses_url: url,
+                headers,
+                max_attempts,
+            });
+        let sse = resumable_sse_events(resp, resume);
+        let mut call_ids = request.begin_tool_call_ids();
 
         let stream = async_stream::stream! {
-            let mut buffer = String::new();
             let mut tool_call_state = StreamToolCallState::default();
             let mut saw_tool_call = false;
             let mut saw_text_delta = false;
-            let mut pending_data: Vec<String> = Vec::new();
-            let mut saw_done = false;
+            let mut saw_refusal_delta = false;
             let mut debug_event_count = 0usize;
-            futures::pin_mut!(byte_stream);
+            futures::pin_mut!(sse);
 
-            while let Some(chunk_result) = byte_stream.next().await {
-                let chunk = match chunk_result {
-                    Ok(c) => c,
+            while let Some(sse_event) = sse.next().await {
+                let sse_event = match sse_event {
+                    Ok(sse_event) => sse_event,
                     Err(e) => {
-                        yield Err(RociError::Network(e));
+                        yield Err(e);
                         break;
                     }
                 };
-
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let line = buffer[..line_end].to_string();
-                    buffer = buffer[line_end + 1..].to_string();
-
-                    let line = line.trim_end_matches('\r');
-                    if line.is_empty() {
-                        if pending_data.is_empty() {
-                            continue;
-                        }
-                        let data = pending_data.join("\n");
-                        pending_data.clear();
-                        if data == "[DONE]" {
-                            saw_done = true;
-                            break;
-                        }
-                        if roci_debug_enabled() && debug_event_count < 5 {
-                            tracing::debug!(data = %data, "OpenAI Responses SSE raw");
-                            debug_event_count += 1;
-                        }
-                        match serde_json::from_str::<serde_json::Value>(&data) {
-                            Ok(event) => {
-                                let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
-                                match event_type {
-                                    "response.output_item.added" => {
-                                        if let Some(item) = event.get("item") {
-                                            if item.get("type").and_then(|t| t.as_str()) == Some("message")
-                                                && !saw_text_delta
-                                            {
-                                                if let Some(content) =
-                                                    item.get("content").and_then(|v| v.as_array())
+                if sse_event.is_done() {
+                    break;
+                }
+                let data = sse_event.data;
+                if roci_debug_enabled() && debug_event_count < 5 {
+                    tracing::debug!(data = %data, "OpenAI Responses SSE raw");
+                    debug_event_count += 1;
+                }
+                match serde_json::from_str::<serde_json::Value>(&data) {
+                    Ok(event) => {
+                        // Servers that omit `type` from the payload still name
+                        // the event on the `event:` line.
+                        let event_type = event
+                            .get("type")
+                            .and_then(|t| t.as_str())
+                            .unwrap_or(sse_event.event.as_str());
+                        match event_type {
+                            "response.output_item.added" => {
+                                if let Some(item) = event.get("item") {
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("message")
+                                        && !saw_text_delta
+                                    {
+                                        if let Some(content) =
+                                            item.get("content").and_then(|v| v.as_array())
+                                        {
+                                            for part in content {
+                                                if part.get("type").and_then(|t| t.as_str())
+                                                    == Some("output_text")
                                                 {
-                                                    for part in content {
-                                                        if part.get("type").and_then(|t| t.as_str())
-                                                            == Some("output_text")
-                                                        {
-                                                            if let Some(text) =
-                                                                part.get("text").and_then(|t| t.as_str())
-                                                            {
-                                                                if !text.is_empty() {
-                                                                    saw_text_delta = true;
-                                                                    yield Ok(TextStreamDelta {
-                                                                        text: text.to_string(),
-                                                                        event_type: StreamEventType::TextDelta,
-                                                                        tool_call: None,
-                                                                        finish_reason: None,
-                                                                        usage: None,
-                                                                        reasoning: None,
-                                                                        reasoning_signature: None,
-                                                                        reasoning_type: None,
-                                                                    });
-                                                                }
-                                                            }
+                                                    if let Some(text) =
+                                                        part.get("text").and_then(|t| t.as_str())
+                                                    {
+                                                        if !text.is_empty() {
+                                                            saw_text_delta = true;
+                                                            yield Ok(TextStreamDelta {
+                                                                text: text.to_string(),
+                                                                event_type: StreamEventType::TextDelta,
+                                                                tool_call: None,
+                                                                finish_reason: None,
+                                                                usage: None,
+                                                                reasoning: None,
+                                                                reasoning_signature: None,
+                                                                reasoning_type: None,
+                                                                image: None,
+                                                                response_metadata: None,
+                                                            });
                                                         }
                                                     }
                                                 }
                                             }
-                                            if item.get("type").and_then(|t| t.as_str()) == Some("function_call") {
-                                                if let Some(id) = item
-                                                    .get("call_id")
-                                                    .and_then(|v| v.as_str())
-                                                    .or_else(|| item.get("id").and_then(|v| v.as_str()))
-                                                {
-                                                    tool_call_state.observe_call(
-                                                        id,
-                                                        item.get("name").and_then(|v| v.as_str()),
-                                                    );
-                                                }
-                                            }
                                         }
                                     }
-                                    "response.output_item.done" => {
-                                        if let Some(item) = event.get("item") {
-                                            if item.get("type").and_then(|t| t.as_str()) == Some("message")
-                                                && !saw_text_delta
-                                            {
-                                                if let Some(content) =
-                                                    item.get("content").and_then(|v| v.as_array())
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("function_call") {
+                                        if let Some(id) = item
+                                            .get("call_id")
+                                            .and_then(|v| v.as_str())
+                                            .or_else(|| item.get("id").and_then(|v| v.as_str()))
+                                        {
+                                            tool_call_state.observe_call(
+                                                id,
+                                                item.get("name").and_then(|v| v.as_str()),
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            "response.output_item.done" => {
+                                if let Some(item) = event.get("item") {
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("message")
+                                        && !saw_text_delta
+                                    {
+                                        if let Some(content) =
+                                            item.get("content").and_then(|v| v.as_array())
+                                        {
+                                            let mut completed_text = String::new();
+                                            for part in content {
+                                                if part.get("type").and_then(|t| t.as_str())
+                                                    == Some("output_text")
                                                 {
-                                                    let mut completed_text = String::new();
-                                                    for part in content {
-                                                        if part.get("type").and_then(|t| t.as_str())
-                                                            == Some("output_text")
-                                                        {
-                                                            if let Some(text) =
-                                                                part.get("text").and_then(|t| t.as_str())
-                                                            {
-                                                                completed_text.push_str(text);
-                                                            }
-                                                        }
-                                                    }
-                                                    if !completed_text.trim().is_empty() {
-                                                        saw_text_delta = true;
-                                                        yield Ok(TextStreamDelta {
-                                                            text: completed_text,
-                                                            event_type: StreamEventType::TextDelta,
-                                                            tool_call: None,
-                                                            finish_reason: None,
-                                                            usage: None,
-                                                            reasoning: None,
-                                                            reasoning_signature: None,
-                                                            reasoning_type: None,
-                                                        });
+                                                    if let Some(text) =
+                                                        part.get("text").and_then(|t| t.as_str())
+                                                    {
+                                                        completed_text.push_str(text);
                                                     }
                                                 }
                                             }
-                                            if item.get("type").and_then(|t| t.as_str()) == Some("function_call") {
-                                                if let Some(call_id) = item
-                                                    .get("call_id")
-                                                    .and_then(|v| v.as_str())
-                                                    .or_else(|| item.get("id").and_then(|v| v.as_str()))
-                                                {
-                                                    let tool_calls = tool_call_state.finalize_call(
-                                                        call_id,
-                                                        item.get("name").and_then(|v| v.as_str()),
-                                                        item.get("arguments").and_then(|v| v.as_str()),
-                                                    );
-                                                    for tool_call in tool_calls {
-                                                        saw_tool_call = true;
-                                                        yield Ok(tool_call_delta(tool_call));
-                                                    }
-                                                }
+                                            if !completed_text.trim().is_empty() {
+                                                saw_text_delta = true;
+                                                yield Ok(TextStreamDelta {
+                                                    text: completed_text,
+                                                    event_type: StreamEventType::TextDelta,
+                                                    tool_call: None,
+                                                    finish_reason: None,
+                                                    usage: None,
+                                                    reasoning: None,
+                                                    reasoning_signature: None,
+                                                    reasoning_type: None,
+                                                    image: None,
+                                                    response_metadata: None,
+                                                });
                                             }
                                         }
                                     }
-                                    "response.function_call_arguments.delta" => {
-                                        if let Some(call_id) = event.get("call_id")
-                                            .and_then(|v| v.as_str())
-                                            .or_else(|| event.get("item_id").and_then(|v| v.as_str()))
-                                        {
-                                            if let Some(delta) = event.get("delta").and_then(|v| v.as_str()) {
-                                                tool_call_state.append_arguments_delta(call_id, delta);
-                                            }
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("image_generation_call") {
+                                        if let Some(result) = item.get("result").and_then(|v| v.as_str()) {
+                                            let output_format = item.get("output_format").and_then(|v| v.as_str());
+                                            yield Ok(TextStreamDelta::image(response::generated_image(
+                                                result.to_string(),
+                                                output_format,
+                                            )));
                                         }
                                     }
-                                    "response.function_call_arguments.done" => {
-                                        if let Some(call_id) = event.get("call_id")
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("function_call") {
+                                        if let Some(call_id) = item
+                                            .get("call_id")
                                             .and_then(|v| v.as_str())
-                                            .or_else(|| event.get("item_id").and_then(|v| v.as_str()))
+                                            .or_else(|| item.get("id").and_then(|v| v.as_str()))
                                         {
                                             let tool_calls = tool_call_state.finalize_call(
                                                 call_id,
-                                                event.get("name").and_then(|v| v.as_str()),
-                                                event.get("arguments").and_then(|v| v.as_str()),
+                                                item.get("name").and_then(|v| v.as_str()),
+                                                item.get("arguments").and_then(|v| v.as_str()),
                                             );
                                             for tool_call in tool_calls {
                                                 saw_tool_call = true;
-                                                yield Ok(tool_call_delta(tool_call));
+                                                yield Ok(tool_call_delta(tool_call, &mut call_ids));
                                             }
                                         }
                                     }
-                                    "response.output_text.delta" => {
-                                        if let Some(delta) = event.get("delta").and_then(|d| d.as_str()) {
-                                            saw_text_delta = true;
-                                            yield Ok(TextStreamDelta {
-                                                text: delta.to_string(),
-                                                event_type: StreamEventType::TextDelta,
-                                                tool_call: None,
-                                                finish_reason: None,
-                                                usage: None,
-                                                reasoning: None,
-                                                reasoning_signature: None,
-                                                reasoning_type: None,
-                                            });
-                                        }
-                                    }
-                                    "response.output_text.done" => {
-                                        if !saw_text_delta {
-                                            if let Some(text) = event.get("text").and_then(|t| t.as_str()) {
-                                                if !text.is_empty() {
-                                                    yield Ok(TextStreamDelta {
-                                                        text: text.to_string(),
-                                                        event_type: StreamEventType::TextDelta,
-                                                        tool_call: None,
-                                                        finish_reason: None,
-                                                        usage: None,
-                                                        reasoning: None,
-                                                        reasoning_signature: None,
-                                                        reasoning_type: None,
-                                                    });
-                                                }
+                                }
+                            }
+                            "response.function_call_arguments.delta" => {
+                                if let Some(call_id) = event.get("call_id")
+                                    .and_then(|v| v.as_str())
+                                    .or_else(|| event.get("item_id").and_then(|v| v.as_str()))
+                                {
+                                    if let Some(delta) = event.get("delta").and_then(|v| v.as_str()) {
+                                        tool_call_state.append_arguments_delta(call_id, delta);
+                                        if let Some(name) = tool_call_state.call_name(call_id) {
+                                            if !delta.is_empty() {
+                                                yield Ok(TextStreamDelta::tool_call_arguments(call_id, name, delta));
                                             }
                                         }
                                     }
-                                    "response.failed" | "response.error" => {
-                                        let message = extract_response_error(&event)
-                                            .unwrap_or_else(|| "OpenAI Responses error".to_string());
-                                        yield Err(RociError::api(400, message));
-                                        saw_done = true;
-                                        break;
+                                }
+                            }
+                            "response.function_call_arguments.done" => {
+                                if let Some(call_id) = event.get("call_id")
+                                    .and_then(|v| v.as_str())
+                                    .or_else(|| event.get("item_id").and_then(|v| v.as_str()))
+                                {
+                                    let tool_calls = tool_call_state.finalize_call(
+                                        call_id,
+                                        event.get("name").and_then(|v| v.as_str()),
+                                        event.get("arguments").and_then(|v| v.as_str()),
+                                    );
+                                    for tool_call in tool_calls {
+                                        saw_tool_call = true;
+                                        yield Ok(tool_call_delta(tool_call, &mut call_ids));
                                     }
-                                    "response.completed" | "response.done" => {
-                                        if let Some(message) = extract_response_error(&event) {
-                                            yield Err(RociError::api(400, message));
-                                            saw_done = true;
-                                            break;
-                                        }
-                                        if let Some(response) = event.get("response") {
-                                            if let Some(output) = response.get("output").and_then(|v| v.as_array()) {
-                                                if !saw_text_delta {
-                                                    let mut completed_text = String::new();
-                                                    for item in output {
-                                                        if item.get("type").and_then(|t| t.as_str()) == Some("message") {
-                                                            if let Some(content) = item.get("content").and_then(|v| v.as_array()) {
-                                                                for part in content {
-                                                                    if part.get("type").and_then(|t| t.as_str()) == Some("output_text") {
-                                                                        if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
-                                                                            completed_text.push_str(text);
-                                                                        }
-                                                                    }
+                                }
+                            }
+                            "response.output_text.delta" => {
+                                if let Some(delta) = event.get("delta").and_then(|d| d.as_str()) {
+                                    saw_text_delta = true;
+                                    yield Ok(TextStreamDelta {
+                                        text: delta.to_string(),
+                                        event_type: StreamEventType::TextDelta,
+                                        tool_call: None,
+                                        finish_reason: None,
+                                        usage: None,
+                                        reasoning: None,
+                                        reasoning_signature: None,
+                                        reasoning_type: None,
+                                        image: None,
+                                        response_metadata: None,
+                                    });
+                                }
+                            }
+                            "response.output_text.done" if !saw_text_delta => {
+                                if let Some(text) = event.get("text").and_then(|t| t.as_str()) {
+                                    if !text.is_empty() {
+                                        yield Ok(TextStreamDelta {
+                                            text: text.to_string(),
+                                            event_type: StreamEventType::TextDelta,
+                                            tool_call: None,
+                                            finish_reason: None,
+                                            usage: None,
+                                            reasoning: None,
+                                            reasoning_signature: None,
+                                            reasoning_type: None,
+                                            image: None,
+                                            response_metadata: None,
+                                        });
+                                    }
+                                }
+                            }
+                            "response.refusal.delta" => {
+                                if let Some(delta) = event.get("delta").and_then(|d| d.as_str()) {
+                                    saw_refusal_delta = true;
+                                    yield Ok(refusal_delta(delta));
+                                }
+                            }
+                            "response.refusal.done" if !saw_refusal_delta => {
+                                if let Some(refusal) = event.get("refusal").and_then(|r| r.as_str()) {
+                                    if !refusal.is_empty() {
+                                        saw_refusal_delta = true;
+                                        yield Ok(refusal_delta(refusal));
+                                    }
+                                }
+                            }
+                            "response.failed" | "response.error" => {
+                                let message = extract_response_error(&event)
+                                    .unwrap_or_else(|| "OpenAI Responses error".to_string());
+                                yield Err(RociError::api(400, message));
+                                break;
+                            }
+                            "response.completed" | "response.done" => {
+                                if let Some(message) = extract_response_error(&event) {
+                                    yield Err(RociError::api(400, message));
+                                    break;
+                                }
+                                if let Some(response) = event.get("response") {
+                                    if let Some(output) = response.get("output").and_then(|v| v.as_array()) {
+                                        if !saw_text_delta {
+                                            let mut completed_text = String::new();
+                                            for item in output {
+                                                if item.get("type").and_then(|t| t.as_str()) == Some("message") {
+                                                    if let Some(content) = item.get("content").and_then(|v| v.as_array()) {
+                                                        for part in content {
+                                                            if part.get("type").and_then(|t| t.as_str()) == Some("output_text") {
+                                                                if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                                                                    completed_text.push_str(text);
                                                                 }
                                                             }
-                                                        } else if item.get("type").and_then(|t| t.as_str()) == Some("output_text") {
-                                                            if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                                                completed_text.push_str(text);
-                                                            }
                                                         }
                                                     }
-                                                    if !completed_text.trim().is_empty() {
-                                                        saw_text_delta = true;
-                                                        yield Ok(TextStreamDelta {
-                                                            text: completed_text,
-                                                            event_type: StreamEventType::TextDelta,
-                                                            tool_call: None,
-                                                            finish_reason: None,
-                                                            usage: None,
-                                                            reasoning: None,
-                                                            reasoning_signature: None,
-                                                            reasoning_type: None,
-                                                        });
-                                                    } else if roci_debug_enabled() {
-                                                        tracing::debug!("OpenAI Responses completed event had no output text");
+                                                } else if item.get("type").and_then(|t| t.as_str()) == Some("output_text") {
+                                                    if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                                                        completed_text.push_str(text);
                                                     }
                                                 }
-                                                let tool_calls = tool_call_state.finalize_from_response_output(output);
-                                                for tool_call in tool_calls {
-                                                    saw_tool_call = true;
-                                                    yield Ok(tool_call_delta(tool_call));
-                                                }
+                                            }
+                                            if !completed_text.trim().is_empty() {
+                                                saw_text_delta = true;
+                                                yield Ok(TextStreamDelta {
+                                                    text: completed_text,
+                                                    event_type: StreamEventType::TextDelta,
+                                                    tool_call: None,
+                                                    finish_reason: None,
+                                                    usage: None,
+                                                    reasoning: None,
+                                                    reasoning_signature: None,
+                                                    reasoning_type: None,
+                                                    image: None,
+                                                    response_metadata: None,
+                                                });
+                                            } else if roci_debug_enabled() {
+                                                tracing::debug!("OpenAI Responses completed event had no output text");
                                             }
                                         }
-                                        let trailing_tool_calls = tool_call_state.flush_ready(true);
-                                        for tool_call in trailing_tool_calls {
+                                        let tool_calls = tool_call_state.finalize_from_response_output(output);
+                                        for tool_call in tool_calls {
                                             saw_tool_call = true;
-                                            yield Ok(tool_call_delta(tool_call));
+                                            yield Ok(tool_call_delta(tool_call, &mut call_ids));
                                         }
-                                        let finish = event.get("response")
-                                            .and_then(|r| r.get("status"))
-                                            .and_then(|v| v.as_str())
-                                            .and_then(|status| match status {
-                                                "completed" => Some(FinishReason::Stop),
-                                                "incomplete" => Some(FinishReason::Length),
-                                                "failed" => Some(FinishReason::Error),
-                                                _ => None,
-                                            });
-                                        let usage = event.get("response")
-                                            .and_then(|r| r.get("usage"))
-                                            .and_then(|u| {
-                                                Some(Usage {
-                                                    input_tokens: u.get("input_tokens")?.as_u64()? as u32,
-                                                    output_tokens: u.get("output_tokens")?.as_u64()? as u32,
-                                                    total_tokens: u.get("total_tokens")?.as_u64()? as u32,
-                                                    ..Default::default()
-                                                })
-                                            });
-                                        yield Ok(TextStreamDelta {
-                                            text: String::new(),
-                                            event_type: StreamEventType::Done,
-                                            tool_call: None,
-                                            finish_reason: if saw_tool_call {
-                                                Some(FinishReason::ToolCalls)
-                                            } else {
-                                                finish.or(Some(FinishReason::Stop))
-                                            },
-                                            usage,
-                                            reasoning: None,
-                                            reasoning_signature: None,
-                                            reasoning_type: None,
-                                        });
                                     }
-                                    _ => {}
                                 }
-                            }
-                            Err(e) => {
-                                if roci_debug_enabled() {
-                                    tracing::debug!(error = %e, data = %data, "OpenAI Responses SSE parse failed");
+                                let trailing_tool_calls = tool_call_state.flush_ready(true);
+                                for tool_call in trailing_tool_calls {
+                                    saw_tool_call = true;
+                                    yield Ok(tool_call_delta(tool_call, &mut call_ids));
                                 }
+                                let incomplete = event
+                                    .pointer("/response/incomplete_details/reason")
+                                    .and_then(|v| v.as_str());
+                                let finish = event.get("response")
+                                    .and_then(|r| r.get("status"))
+                                    .and_then(|v| v.as_str())
+                                    .map(|status| response::parse_finish_reason(status, incomplete));
+                                let usage = event.get("response")
+                                    .and_then(|r| r.get("usage"))
+                                    .and_then(|u| {
+                                        Some(Usage {
+                                            input_tokens: u.get("input_tokens")?.as_u64()? as u32,
+                                            output_tokens: u.get("output_tokens")?.as_u64()? as u32,
+                                            total_tokens: u.get("total_tokens")?.as_u64()? as u32,
+                                            ..Default::default()
+                                        })
+                                    });
+                                yield Ok(TextStreamDelta {
+                                    text: String::new(),
+                                    event_type: StreamEventType::Done,
+                                    tool_call: None,
+                                    finish_reason: if saw_tool_call {
+                                        Some(FinishReason::ToolCalls)
+                                    } else if saw_refusal_delta {
+                                        Some(FinishReason::Refusal)
+                                    } else {
+                                        finish.or(Some(FinishReason::Stop))
+                                    },
+                                    usage,
+                                    reasoning: None,
+                                    reasoning_signature: None,
+                                    reasoning_type: None,
+                                    image: None,
+                                    response_metadata: None,
+                                });
                             }
+                            _ => {}
+                        }
+                    }
+                    Err(e) => {
+                        if roci_debug_enabled() {
+                            tracing::debug!(error = %e, data = %data, "OpenAI Responses SSE parse failed");
                         }
-                    } else if line.starts_with(':') {
-                        continue;
-                    } else if let Some(rest) = line.strip_prefix("data:") {
-                        let rest = rest.strip_prefix(' ').unwrap_or(rest);
-                        pending_data.push(rest.to_string());
                     }
-                }
-
-                if saw_done {
-                    break;
                 }
             }
         };
 
-        Ok(Box::pin(stream))
+        Ok(with_partial_text(with_response_metadata(
+            Box::pin(stream),
+            response_metadata,
+        )))
     }
 }
 
+#[cfg(test)]
+mod background_tests;
 #[cfg(test)]
 mod headers_tests;
 #[cfg(test)]
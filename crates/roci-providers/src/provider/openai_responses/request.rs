@@ -13,6 +13,7 @@ use roci_core::provider::{ProviderRequest, TRANSPORT_PROXY};
 use super::OpenAiResponsesProvider;
 
 const DEFAULT_CODEX_INSTRUCTIONS: &str = "You are Roci, a helpful assistant.";
+const DEFAULT_BACKGROUND_RESUMES: u32 = 3;
 const RESPONSES_PROXY_BASE_URL_ENV: &str = "ROCI_OPENAI_RESPONSES_PROXY_BASE_URL";
 
 impl OpenAiResponsesProvider {
@@ -58,6 +59,21 @@ impl OpenAiResponsesProvider {
             .and_then(|options| options.previous_response_id.clone())
     }
 
+    /// Resume budget for a dropped stream, or `None` when the request does not
+    /// run in background mode.
+    pub(crate) fn background_resume_attempts(&self, request: &ProviderRequest) -> Option<u32> {
+        request
+            .settings
+            .openai_responses
+            .as_ref()
+            .filter(|options| options.background == Some(true))
+            .map(|options| {
+                options
+                    .max_background_resumes
+                    .unwrap_or(DEFAULT_BACKGROUND_RESUMES)
+            })
+    }
+
     pub(crate) fn merged_metadata(
         &self,
         request: &ProviderRequest,
@@ -245,13 +261,20 @@ impl OpenAiResponsesProvider {
             let tool_defs: Vec<serde_json::Value> = tools
                 .iter()
                 .map(|t| {
-                    let parameters = Self::normalize_tool_parameters(&t.parameters);
+                    let strict_parameters = self
+                        .capabilities
+                        .supports_json_schema
+                        .then(|| t.strict_parameters())
+                        .flatten();
+                    let strict = strict_parameters.is_some();
+                    let parameters = strict_parameters
+                        .unwrap_or_else(|| Self::normalize_tool_parameters(&t.parameters));
                     serde_json::json!({
                         "type": "function",
                         "name": t.name,
                         "description": t.description,
                         "parameters": parameters,
-                        "strict": false,
+                        "strict": strict,
                     })
                 })
                 .collect();
@@ -287,6 +310,10 @@ impl OpenAiResponsesProvider {
             if let Some(store) = options.store {
                 obj.insert("store".into(), store.into());
             }
+            if options.background == Some(true) {
+                obj.insert("background".into(), true.into());
+                obj.insert("store".into(), true.into());
+            }
         }
         if let Some(previous_response_id) = self.resolve_previous_response_id(request) {
             obj.insert("previous_response_id".into(), previous_response_id.into());
@@ -321,6 +348,8 @@ impl OpenAiResponsesProvider {
                             "text": text,
                         }));
                     }
+                    // Generated images are not accepted back as assistant input.
+                    ContentPart::Image(_) if matches!(msg.role, Role::Assistant) => {}
                     ContentPart::Image(img) => {
                         let url = format!("data:{};base64,{}", img.mime_type, img.data);
                         content_parts.push(serde_json::json!({
@@ -5,7 +5,7 @@ use serde::Deserialize;
 use roci_core::error::RociError;
 use roci_core::types::*;
 
-use roci_core::provider::ProviderResponse;
+use roci_core::provider::{ProviderResponse, ResponseToolCallIds};
 
 use super::OpenAiResponsesProvider;
 
@@ -17,10 +17,13 @@ impl OpenAiResponsesProvider {
     /// Convert a Responses API payload into a provider response.
     pub(crate) fn parse_response(
         data: ResponsesApiResponse,
+        call_ids: &mut ResponseToolCallIds,
     ) -> Result<ProviderResponse, RociError> {
         if let Some(outputs) = data.output {
             let mut text = String::new();
+            let mut refusal = String::new();
             let mut tool_calls = Vec::new();
+            let mut images = Vec::new();
 
             for output in outputs {
                 match output.r#type.as_str() {
@@ -33,6 +36,11 @@ impl OpenAiResponsesProvider {
                                             text.push_str(&segment);
                                         }
                                     }
+                                    "refusal" => {
+                                        if let Some(segment) = chunk.refusal {
+                                            refusal.push_str(&segment);
+                                        }
+                                    }
                                     "tool_call" => {
                                         if let Some(tool_call) = chunk.tool_call {
                                             tool_calls.push(Self::convert_tool_call(tool_call));
@@ -55,18 +63,29 @@ impl OpenAiResponsesProvider {
                             tool_calls.push(Self::convert_tool_call(tool_call));
                         }
                     }
+                    "image_generation_call" => {
+                        if let Some(result) = output.result {
+                            images.push(generated_image(result, output.output_format.as_deref()));
+                        }
+                    }
                     _ => {}
                 }
             }
 
+            assign_tool_call_ids(&mut tool_calls, call_ids);
+            let refusal = Some(refusal).filter(|refusal| !refusal.is_empty());
             let finish_reason = if !tool_calls.is_empty() {
                 Some(FinishReason::ToolCalls)
+            } else if refusal.is_some() {
+                Some(FinishReason::Refusal)
             } else {
-                data.status.as_deref().and_then(|s| match s {
-                    "completed" => Some(FinishReason::Stop),
-                    "incomplete" => Some(FinishReason::Length),
-                    _ => None,
-                })
+                let incomplete = data
+                    .incomplete_details
+                    .as_ref()
+                    .and_then(|details| details.reason.as_deref());
+                data.status
+                    .as_deref()
+                    .map(|status| parse_finish_reason(status, incomplete))
             };
 
             return Ok(ProviderResponse {
@@ -75,6 +94,10 @@ impl OpenAiResponsesProvider {
                 tool_calls,
                 finish_reason,
                 thinking: Vec::new(),
+                metadata: Default::default(),
+                refusal,
+                images,
+                response_metadata: None,
             });
         }
 
@@ -83,24 +106,26 @@ impl OpenAiResponsesProvider {
                 .into_iter()
                 .next()
                 .ok_or_else(|| RociError::api(200, "No choices in OpenAI response"))?;
-            let tool_calls = choice
+            let mut tool_calls = choice
                 .message
                 .tool_calls
                 .unwrap_or_default()
                 .into_iter()
                 .map(Self::convert_tool_call)
                 .collect::<Vec<_>>();
-            let finish_reason = choice
-                .finish_reason
-                .as_deref()
-                .and_then(|reason| match reason {
-                    "stop" => Some(FinishReason::Stop),
-                    "length" => Some(FinishReason::Length),
-                    "tool_calls" => Some(FinishReason::ToolCalls),
-                    _ => None,
-                });
+            assign_tool_call_ids(&mut tool_calls, call_ids);
+            let finish_reason = choice.finish_reason.as_deref().map(|reason| match reason {
+                "stop" => FinishReason::Stop,
+                "length" => FinishReason::Length,
+                "tool_calls" | "function_call" => FinishReason::ToolCalls,
+                "content_filter" => FinishReason::ContentFilter,
+                other => FinishReason::Other(other.to_string()),
+            });
+            let refusal = choice.message.refusal.filter(|refusal| !refusal.is_empty());
             let finish_reason = if !tool_calls.is_empty() {
                 Some(FinishReason::ToolCalls)
+            } else if refusal.is_some() {
+                Some(FinishReason::Refusal)
             } else {
                 finish_reason
             };
@@ -111,6 +136,10 @@ impl OpenAiResponsesProvider {
                 tool_calls,
                 finish_reason,
                 thinking: Vec::new(),
+                metadata: Default::default(),
+                refusal,
+                images: Vec::new(),
+                response_metadata: None,
             });
         }
 
@@ -156,18 +185,59 @@ impl OpenAiResponsesProvider {
     }
 }
 
+/// Map a response `status`, refined by `incomplete_details.reason`, keeping
+/// undocumented values.
+pub(crate) fn parse_finish_reason(status: &str, incomplete_reason: Option<&str>) -> FinishReason {
+    match (status, incomplete_reason) {
+        ("completed", _) => FinishReason::Stop,
+        ("incomplete", None | Some("max_output_tokens")) => FinishReason::Length,
+        ("incomplete", Some("content_filter")) => FinishReason::ContentFilter,
+        ("incomplete", Some(reason)) => FinishReason::Other(reason.to_string()),
+        ("failed", _) => FinishReason::Error,
+        (other, _) => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// Replace parsed call IDs with the run-unique IDs from `call_ids`.
+fn assign_tool_call_ids(tool_calls: &mut [AgentToolCall], call_ids: &mut ResponseToolCallIds) {
+    for call in tool_calls {
+        call.id = call_ids.assign(Some(&call.id), &call.name);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // API response serde types
 // ---------------------------------------------------------------------------
 
+/// Image content for the base64 `result` of an `image_generation_call`
+/// output item. `output_format` defaults to PNG, as in the API.
+pub(crate) fn generated_image(result: String, output_format: Option<&str>) -> ImageContent {
+    let format = match output_format.unwrap_or("png") {
+        "jpg" => "jpeg",
+        format => format,
+    };
+    ImageContent {
+        data: result,
+        mime_type: format!("image/{format}"),
+    }
+}
+
 #[derive(Deserialize)]
 pub(crate) struct ResponsesApiResponse {
     pub(crate) output: Option<Vec<ResponsesOutputItem>>,
     pub(crate) choices: Option<Vec<ResponsesChoice>>,
     pub(crate) status: Option<String>,
+    #[serde(default)]
+    pub(crate) incomplete_details: Option<ResponsesIncompleteDetails>,
     pub(crate) usage: Option<ResponsesUsage>,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct ResponsesIncompleteDetails {
+    #[serde(default)]
+    pub(crate) reason: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct ResponsesOutputItem {
     pub(crate) r#type: String,
@@ -180,6 +250,11 @@ pub(crate) struct ResponsesOutputItem {
     pub(crate) arguments: Option<String>,
     #[serde(default)]
     pub(crate) tool_call: Option<ResponsesToolCall>,
+    /// Base64 image on `image_generation_call` items.
+    #[serde(default)]
+    pub(crate) result: Option<String>,
+    #[serde(default)]
+    pub(crate) output_format: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -187,6 +262,9 @@ pub(crate) struct ResponsesOutputContent {
     pub(crate) r#type: String,
     #[serde(default)]
     pub(crate) text: Option<String>,
+    /// Set on `refusal` parts when the model declines.
+    #[serde(default)]
+    pub(crate) refusal: Option<String>,
     #[serde(default)]
     pub(crate) tool_call: Option<ResponsesToolCall>,
 }
@@ -203,6 +281,8 @@ pub(crate) struct ResponsesChoiceMessage {
     #[serde(default)]
     pub(crate) content: Option<String>,
     #[serde(default)]
+    pub(crate) refusal: Option<String>,
+    #[serde(default)]
     pub(crate) tool_calls: Option<Vec<ResponsesToolCall>>,
 }
 
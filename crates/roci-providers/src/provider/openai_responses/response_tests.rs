@@ -4,12 +4,50 @@ use super::response::{
     ResponsesOutputItem, ResponsesToolCall, ResponsesToolCallFunction,
 };
 use super::*;
-use roci_core::provider::ToolDefinition;
+use roci_core::provider::{ResponseToolCallIds, ToolCallIdAllocator, ToolDefinition};
 
 fn settings() -> GenerationSettings {
     GenerationSettings::default()
 }
 
+fn call_ids() -> ResponseToolCallIds {
+    ToolCallIdAllocator::detached().begin_response()
+}
+
+#[test]
+fn finish_reasons_map_status_and_incomplete_reason() {
+    use super::response::parse_finish_reason;
+
+    let cases = [
+        ("completed", None, FinishReason::Stop),
+        ("incomplete", None, FinishReason::Length),
+        (
+            "incomplete",
+            Some("max_output_tokens"),
+            FinishReason::Length,
+        ),
+        (
+            "incomplete",
+            Some("content_filter"),
+            FinishReason::ContentFilter,
+        ),
+        (
+            "incomplete",
+            Some("max_tool_calls"),
+            FinishReason::Other("max_tool_calls".to_string()),
+        ),
+        ("failed", None, FinishReason::Error),
+        (
+            "cancelled",
+            None,
+            FinishReason::Other("cancelled".to_string()),
+        ),
+    ];
+    for (status, reason, expected) in cases {
+        assert_eq!(parse_finish_reason(status, reason), expected, "{status}");
+    }
+}
+
 #[test]
 fn status_error_maps_context_length_to_typed_code() {
     let body = serde_json::json!({
@@ -47,7 +85,7 @@ fn tool_parameters_are_normalized_for_responses_api() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: GenerationSettings::default(),
         tools: Some(vec![ToolDefinition {
             name: "get_date".to_string(),
@@ -58,6 +96,7 @@ fn tool_parameters_are_normalized_for_responses_api() {
                     "format": {"type": "string"}
                 }
             }),
+            strict: false,
         }]),
         response_format: None,
         api_key_override: None,
@@ -66,6 +105,7 @@ fn tool_parameters_are_normalized_for_responses_api() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(
@@ -78,6 +118,45 @@ fn tool_parameters_are_normalized_for_responses_api() {
     );
 }
 
+#[test]
+fn strict_tool_definitions_use_the_strict_schema() {
+    let provider =
+        OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
+    let request = ProviderRequest {
+        messages: vec![ModelMessage::user("hello")].into(),
+        settings: GenerationSettings::default(),
+        tools: Some(vec![ToolDefinition::new(
+            "get_date",
+            "Return a date",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "format": {"type": ["string", "null"]}
+                }
+            }),
+        )]),
+        response_format: None,
+        api_key_override: None,
+        headers: reqwest::header::HeaderMap::new(),
+        metadata: std::collections::HashMap::new(),
+        payload_callback: None,
+        session_id: None,
+        transport: None,
+        tool_call_ids: None,
+    };
+    let body = provider.build_request_body(&request, false);
+    assert_eq!(body["tools"][0]["strict"], true);
+    assert_eq!(
+        body["tools"][0]["parameters"],
+        serde_json::json!({
+            "type": "object",
+            "properties": {"format": {"type": ["string", "null"]}},
+            "required": ["format"],
+            "additionalProperties": false
+        })
+    );
+}
+
 #[test]
 fn response_parses_function_call_output_item() {
     let response = ResponsesApiResponse {
@@ -88,18 +167,51 @@ fn response_parses_function_call_output_item() {
             name: Some("get_date".to_string()),
             arguments: Some(r#"{"date":"today"}"#.to_string()),
             tool_call: None,
+            result: None,
+            output_format: None,
         }]),
         choices: None,
         status: Some("completed".to_string()),
+        incomplete_details: None,
         usage: None,
     };
 
-    let parsed = OpenAiResponsesProvider::parse_response(response).unwrap();
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
     assert_eq!(parsed.tool_calls.len(), 1);
     assert_eq!(parsed.tool_calls[0].name, "get_date");
     assert_eq!(parsed.finish_reason, Some(FinishReason::ToolCalls));
 }
 
+#[test]
+fn response_disambiguates_duplicate_call_ids() {
+    let function_call = |name: &str| ResponsesOutputItem {
+        r#type: "function_call".to_string(),
+        content: None,
+        call_id: Some("call_1".to_string()),
+        name: Some(name.to_string()),
+        arguments: Some("{}".to_string()),
+        tool_call: None,
+        result: None,
+        output_format: None,
+    };
+    let response = ResponsesApiResponse {
+        output: Some(vec![function_call("get_date"), function_call("get_time")]),
+        choices: None,
+        status: Some("completed".to_string()),
+        incomplete_details: None,
+        usage: None,
+    };
+
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
+
+    let ids = parsed
+        .tool_calls
+        .iter()
+        .map(|call| call.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["call_1", "call_1-2"]);
+}
+
 #[test]
 fn response_parses_message_tool_call_content() {
     let tool_call = ResponsesToolCall {
@@ -116,11 +228,13 @@ fn response_parses_message_tool_call_content() {
                 ResponsesOutputContent {
                     r#type: "output_text".to_string(),
                     text: Some("ok".to_string()),
+                    refusal: None,
                     tool_call: None,
                 },
                 ResponsesOutputContent {
                     r#type: "tool_call".to_string(),
                     text: None,
+                    refusal: None,
                     tool_call: Some(tool_call),
                 },
             ]),
@@ -128,18 +242,85 @@ fn response_parses_message_tool_call_content() {
             name: None,
             arguments: None,
             tool_call: None,
+            result: None,
+            output_format: None,
         }]),
         choices: None,
         status: Some("completed".to_string()),
+        incomplete_details: None,
         usage: None,
     };
 
-    let parsed = OpenAiResponsesProvider::parse_response(response).unwrap();
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
     assert_eq!(parsed.text, "ok");
     assert_eq!(parsed.tool_calls.len(), 1);
     assert_eq!(parsed.tool_calls[0].name, "get_date");
 }
 
+#[test]
+fn response_parses_refusal_output_item() {
+    let response: ResponsesApiResponse = serde_json::from_value(serde_json::json!({
+        "status": "completed",
+        "output": [{
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "refusal", "refusal": "I can't help with that." }]
+        }]
+    }))
+    .unwrap();
+
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
+    assert_eq!(parsed.text, "");
+    assert_eq!(parsed.refusal.as_deref(), Some("I can't help with that."));
+    assert_eq!(parsed.finish_reason, Some(FinishReason::Refusal));
+}
+
+#[test]
+fn response_parses_image_generation_call_output_item() {
+    let response: ResponsesApiResponse = serde_json::from_value(serde_json::json!({
+        "status": "completed",
+        "output": [
+            {
+                "type": "image_generation_call",
+                "status": "completed",
+                "output_format": "jpg",
+                "result": "/9j/4AAQ"
+            },
+            {
+                "type": "message",
+                "role": "assistant",
+                "content": [{ "type": "output_text", "text": "Here you go." }]
+            }
+        ]
+    }))
+    .unwrap();
+
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
+    assert_eq!(parsed.text, "Here you go.");
+    assert_eq!(
+        parsed.images,
+        vec![ImageContent {
+            data: "/9j/4AAQ".to_string(),
+            mime_type: "image/jpeg".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn response_parses_choices_refusal() {
+    let response: ResponsesApiResponse = serde_json::from_value(serde_json::json!({
+        "choices": [{
+            "message": { "role": "assistant", "content": null, "refusal": "No." },
+            "finish_reason": "stop"
+        }]
+    }))
+    .unwrap();
+
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
+    assert_eq!(parsed.refusal.as_deref(), Some("No."));
+    assert_eq!(parsed.finish_reason, Some(FinishReason::Refusal));
+}
+
 #[test]
 fn response_parses_choices_fallback() {
     let tool_call = ResponsesToolCall {
@@ -154,15 +335,17 @@ fn response_parses_choices_fallback() {
         choices: Some(vec![ResponsesChoice {
             message: ResponsesChoiceMessage {
                 content: Some("ok".to_string()),
+                refusal: None,
                 tool_calls: Some(vec![tool_call]),
             },
             finish_reason: Some("stop".to_string()),
         }]),
         status: None,
+        incomplete_details: None,
         usage: None,
     };
 
-    let parsed = OpenAiResponsesProvider::parse_response(response).unwrap();
+    let parsed = OpenAiResponsesProvider::parse_response(response, &mut call_ids()).unwrap();
     assert_eq!(parsed.text, "ok");
     assert_eq!(parsed.tool_calls.len(), 1);
     assert_eq!(parsed.tool_calls[0].name, "get_date");
@@ -241,7 +424,8 @@ fn tool_output_uses_plain_string_content() {
             "call_1",
             serde_json::Value::String("ok".to_string()),
             false,
-        )],
+        )]
+        .into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -251,9 +435,75 @@ fn tool_output_uses_plain_string_content() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["input"][0]["type"], "function_call_output");
     assert_eq!(body["input"][0]["output"], "ok");
 }
+
+#[tokio::test]
+async fn stream_emits_refusal_deltas_and_refusal_finish() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    let body = concat!(
+        "data: {\"type\":\"response.refusal.delta\",\"item_id\":\"msg_1\",\"delta\":\"I can't \"}\n\n",
+        "data: {\"type\":\"response.refusal.delta\",\"item_id\":\"msg_1\",\"delta\":\"help.\"}\n\n",
+        "data: {\"type\":\"response.refusal.done\",\"item_id\":\"msg_1\",\"refusal\":\"I can't help.\"}\n\n",
+        "data: {\"type\":\"response.completed\",\"response\":{\"status\":\"completed\",\"output\":[{\"type\":\"message\",\"content\":[{\"type\":\"refusal\",\"refusal\":\"I can't help.\"}]}]}}\n\n",
+    );
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiResponsesProvider::new(
+        OpenAiModel::Gpt41,
+        "test-key".to_string(),
+        Some(server.uri()),
+        None,
+    );
+    let request = ProviderRequest {
+        messages: vec![ModelMessage::user("hello")].into(),
+        settings: settings(),
+        tools: None,
+        response_format: None,
+        api_key_override: None,
+        headers: reqwest::header::HeaderMap::new(),
+        metadata: std::collections::HashMap::new(),
+        payload_callback: None,
+        session_id: None,
+        transport: None,
+        tool_call_ids: None,
+    };
+    let deltas = provider
+        .stream_text(&request)
+        .await
+        .expect("stream response")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("stream deltas");
+
+    let refusal: String = deltas
+        .iter()
+        .filter(|delta| delta.event_type == StreamEventType::RefusalDelta)
+        .map(|delta| delta.text.as_str())
+        .collect();
+    assert_eq!(refusal, "I can't help.");
+    assert!(deltas
+        .iter()
+        .all(|delta| delta.event_type != StreamEventType::TextDelta));
+    let done = deltas.last().expect("done delta");
+    assert_eq!(done.event_type, StreamEventType::Done);
+    assert_eq!(done.finish_reason, Some(FinishReason::Refusal));
+}
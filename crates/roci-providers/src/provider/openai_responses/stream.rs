@@ -1,11 +1,17 @@
 //! Streaming state machine for OpenAI Responses SSE events.
 
+use roci_core::provider::ResponseToolCallIds;
 use roci_core::types::*;
 
 use super::OpenAiResponsesProvider;
 
-/// Build a [`TextStreamDelta`] that carries a completed tool call.
-pub(crate) fn tool_call_delta(tool_call: AgentToolCall) -> TextStreamDelta {
+/// Build a [`TextStreamDelta`] that carries a completed tool call, with its
+/// ID replaced by the run-unique one from `call_ids`.
+pub(crate) fn tool_call_delta(
+    mut tool_call: AgentToolCall,
+    call_ids: &mut ResponseToolCallIds,
+) -> TextStreamDelta {
+    tool_call.id = call_ids.assign(Some(&tool_call.id), &tool_call.name);
     TextStreamDelta {
         text: String::new(),
         event_type: StreamEventType::ToolCallDelta,
@@ -15,6 +21,24 @@ pub(crate) fn tool_call_delta(tool_call: AgentToolCall) -> TextStreamDelta {
         reasoning: None,
         reasoning_signature: None,
         reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+/// Build a [`TextStreamDelta`] that carries refusal text.
+pub(crate) fn refusal_delta(text: &str) -> TextStreamDelta {
+    TextStreamDelta {
+        text: text.to_string(),
+        event_type: StreamEventType::RefusalDelta,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
     }
 }
 
@@ -43,6 +67,11 @@ impl StreamToolCallState {
         }
     }
 
+    /// Name recorded for an in-flight call, if the stream announced it.
+    pub(crate) fn call_name(&self, call_id: &str) -> Option<&str> {
+        self.call_names.get(call_id).map(String::as_str)
+    }
+
     pub(crate) fn append_arguments_delta(&mut self, call_id: &str, delta: &str) {
         self.observe_call(call_id, None);
         self.call_arguments
@@ -17,6 +17,10 @@ fn settings() -> GenerationSettings {
         user: None,
         anthropic: None,
         google: None,
+        mistral: None,
+        grok: None,
+        groq: None,
+        assistant_prefix: None,
         tool_choice: None,
         stream_idle_timeout_ms: None,
     }
@@ -126,7 +130,7 @@ fn gpt52_request_defaults_reasoning_to_none() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt52, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -136,6 +140,7 @@ fn gpt52_request_defaults_reasoning_to_none() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -148,7 +153,7 @@ fn public_gpt54_defaults_reasoning_to_none() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt54, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -158,6 +163,7 @@ fn public_gpt54_defaults_reasoning_to_none() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -192,7 +198,7 @@ fn codex_gpt54_defaults_reasoning_to_medium() {
         None,
     );
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -202,6 +208,7 @@ fn codex_gpt54_defaults_reasoning_to_medium() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -249,7 +256,7 @@ fn request_body_includes_text_verbosity_and_format() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: GenerationSettings {
             text_verbosity: Some(TextVerbosity::Low),
             ..Default::default()
@@ -262,6 +269,7 @@ fn request_body_includes_text_verbosity_and_format() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["text"]["verbosity"], "low");
@@ -276,7 +284,8 @@ fn request_body_maps_system_to_developer_for_reasoning_models() {
         messages: vec![
             ModelMessage::system("Use this system message"),
             ModelMessage::user("hello"),
-        ],
+        ]
+        .into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -286,6 +295,7 @@ fn request_body_maps_system_to_developer_for_reasoning_models() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["input"][0]["role"], "developer");
@@ -311,7 +321,8 @@ fn provider_attachment_payload_openai_responses_maps_text_and_image_parts() {
             name: None,
             timestamp: None,
             metadata: None,
-        }],
+        }]
+        .into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -321,6 +332,7 @@ fn provider_attachment_payload_openai_responses_maps_text_and_image_parts() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -343,7 +355,7 @@ fn provider_attachment_payload_openai_responses_preserves_unsupported_media_mark
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user(marker)],
+        messages: vec![ModelMessage::user(marker)].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -353,6 +365,7 @@ fn provider_attachment_payload_openai_responses_preserves_unsupported_media_mark
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -368,7 +381,7 @@ fn request_body_defaults_reasoning_and_text_for_gpt5() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -378,6 +391,7 @@ fn request_body_defaults_reasoning_and_text_for_gpt5() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["reasoning"]["effort"], "medium");
@@ -395,7 +409,7 @@ fn gpt5_chat_request_omits_reasoning_without_explicit_effort() {
         None,
     );
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -405,6 +419,7 @@ fn gpt5_chat_request_omits_reasoning_without_explicit_effort() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -421,7 +436,7 @@ fn codex_request_uses_the_selected_models_default_reasoning_effort() {
         None,
     );
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -431,6 +446,7 @@ fn codex_request_uses_the_selected_models_default_reasoning_effort() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -444,7 +460,7 @@ fn request_body_defaults_truncation_for_reasoning_models() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::O3, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -454,6 +470,7 @@ fn request_body_defaults_truncation_for_reasoning_models() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["reasoning"]["effort"], "medium");
@@ -478,11 +495,13 @@ fn request_body_includes_openai_responses_options() {
             service_tier: Some(OpenAiServiceTier::Flex),
             truncation: Some(OpenAiTruncation::Auto),
             store: Some(true),
+            background: None,
+            max_background_resumes: None,
         }),
         ..Default::default()
     };
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings,
         tools: None,
         response_format: None,
@@ -492,6 +511,7 @@ fn request_body_includes_openai_responses_options() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
     let body = provider.build_request_body(&request, false);
     assert_eq!(body["user"], "user-1");
@@ -516,7 +536,7 @@ fn request_body_merges_request_metadata_and_includes_prompt_cache_key() {
     request_metadata.insert("trace_id".to_string(), "trace-1".to_string());
     let session_id = "session-abc";
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: GenerationSettings {
             openai_responses: Some(OpenAiResponsesOptions {
                 metadata: Some(options_metadata),
@@ -532,6 +552,7 @@ fn request_body_merges_request_metadata_and_includes_prompt_cache_key() {
         payload_callback: None,
         session_id: Some(session_id.to_string()),
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -547,7 +568,7 @@ fn request_body_omits_prompt_cache_key_when_no_session_id() {
     let provider =
         OpenAiResponsesProvider::new(OpenAiModel::Gpt5Nano, "test-key".to_string(), None, None);
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -557,6 +578,7 @@ fn request_body_omits_prompt_cache_key_when_no_session_id() {
         payload_callback: None,
         session_id: None,
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -574,7 +596,7 @@ fn codex_request_body_includes_prompt_cache_key() {
     );
     let session_id = "codex-session-1";
     let request = ProviderRequest {
-        messages: vec![ModelMessage::user("hello")],
+        messages: vec![ModelMessage::user("hello")].into(),
         settings: settings(),
         tools: None,
         response_format: None,
@@ -584,6 +606,7 @@ fn codex_request_body_includes_prompt_cache_key() {
         payload_callback: None,
         session_id: Some(session_id.to_string()),
         transport: None,
+        tool_call_ids: None,
     };
 
     let body = provider.build_request_body(&request, false);
@@ -0,0 +1,196 @@
+//! Accumulation of streamed chat-completions tool-call deltas.
+//!
+//! OpenAI streams a tool call as one delta carrying the id and name followed
+//! by argument fragments at the same index. Compatible servers diverge: Groq
+//! sends the complete arguments in one delta and may then repeat the call,
+//! either with empty arguments or with the same complete arguments, at the
+//! same or the next index. Naive concatenation turns the repeat into a
+//! second, empty tool call or into doubled, unparseable arguments.
+
+use roci_core::provider::tool_call_ids::ResponseToolCallIds;
+use roci_core::types::{AgentToolCall, TextStreamDelta};
+
+struct ToolCallBuilder {
+    index: usize,
+    provider_id: Option<String>,
+    /// Assigned once the tool name is known.
+    id: Option<String>,
+    name: Option<String>,
+    arguments: String,
+}
+
+impl ToolCallBuilder {
+    /// Whether `fragment` repeats arguments this call already holds in full.
+    fn is_repeat(&self, fragment: &str) -> bool {
+        fragment.is_empty()
+            || (fragment == self.arguments
+                && serde_json::from_str::<serde_json::Value>(fragment).is_ok())
+    }
+}
+
+/// Tool calls assembled from one streamed response, in index order.
+pub(crate) struct StreamToolCalls {
+    call_ids: ResponseToolCallIds,
+    builders: Vec<ToolCallBuilder>,
+}
+
+impl StreamToolCalls {
+    pub(crate) fn new(call_ids: ResponseToolCallIds) -> Self {
+        Self {
+            call_ids,
+            builders: Vec::new(),
+        }
+    }
+
+    /// Fold one tool-call delta in, returning an argument-progress delta for
+    /// new argument text.
+    pub(crate) fn push(
+        &mut self,
+        index: usize,
+        provider_id: Option<String>,
+        name: Option<String>,
+        arguments: Option<String>,
+    ) -> Option<TextStreamDelta> {
+        // A repeat of a call already seen under another index folds into it;
+        // servers that reuse ids for distinct calls send a different name or
+        // new arguments.
+        let repeat_of = provider_id.as_deref().and_then(|id| {
+            self.builders.iter().position(|builder| {
+                builder.index != index
+                    && builder.provider_id.as_deref() == Some(id)
+                    && name
+                        .as_ref()
+                        .is_none_or(|name| builder.name.as_ref() == Some(name))
+                    && arguments
+                        .as_deref()
+                        .is_none_or(|fragment| builder.is_repeat(fragment))
+            })
+        });
+        let position = repeat_of.or_else(|| {
+            self.builders
+                .iter()
+                .position(|builder| builder.index == index)
+        });
+        let builder = match position {
+            Some(position) => &mut self.builders[position],
+            None => {
+                self.builders.push(ToolCallBuilder {
+                    index,
+                    provider_id: None,
+                    id: None,
+                    name: None,
+                    arguments: String::new(),
+                });
+                self.builders.last_mut().expect("builder just pushed")
+            }
+        };
+        let repeated = position.is_some() && builder.id.is_some();
+
+        if let Some(id) = provider_id {
+            builder.provider_id.get_or_insert(id);
+        }
+        if let Some(name) = name.filter(|name| !name.is_empty()) {
+            builder.name.get_or_insert(name);
+        }
+        if let (None, Some(name)) = (builder.id.as_ref(), builder.name.as_deref()) {
+            builder.id = Some(self.call_ids.assign(builder.provider_id.as_deref(), name));
+        }
+        let fragment = arguments?;
+        if repeated && builder.is_repeat(&fragment) {
+            return None;
+        }
+        builder.arguments.push_str(&fragment);
+        match (builder.id.as_deref(), builder.name.as_deref()) {
+            (Some(id), Some(name)) if !fragment.is_empty() => {
+                Some(TextStreamDelta::tool_call_arguments(id, name, fragment))
+            }
+            _ => None,
+        }
+    }
+
+    /// Drain the named calls, ordered by stream index.
+    pub(crate) fn finish(&mut self) -> Vec<AgentToolCall> {
+        let mut builders = std::mem::take(&mut self.builders);
+        builders.sort_by_key(|builder| builder.index);
+        builders
+            .into_iter()
+            .filter_map(|builder| {
+                let (Some(id), Some(name)) = (builder.id, builder.name) else {
+                    return None;
+                };
+                let arguments = if builder.arguments.is_empty() {
+                    serde_json::Value::Object(Default::default())
+                } else {
+                    serde_json::from_str(&builder.arguments)
+                        .unwrap_or(serde_json::Value::String(builder.arguments))
+                };
+                Some(AgentToolCall {
+                    id,
+                    name,
+                    arguments,
+                    called_as: None,
+                    recipient: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci_core::provider::tool_call_ids::ToolCallIdAllocator;
+
+    fn calls() -> StreamToolCalls {
+        StreamToolCalls::new(ToolCallIdAllocator::detached().begin_response())
+    }
+
+    fn some(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn fragments_at_one_index_concatenate() {
+        let mut calls = calls();
+        calls.push(0, some("call_1"), some("read"), some(""));
+        calls.push(0, None, None, some("{\"path\":"));
+        calls.push(0, None, None, some("\"a.rs\"}"));
+
+        let finished = calls.finish();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].arguments, serde_json::json!({ "path": "a.rs" }));
+    }
+
+    #[test]
+    fn repeated_complete_arguments_are_not_appended_twice() {
+        let mut calls = calls();
+        let first = calls.push(0, some("call_1"), some("read"), some("{\"path\":\"a.rs\"}"));
+        let repeat = calls.push(0, some("call_1"), some("read"), some("{\"path\":\"a.rs\"}"));
+
+        assert!(first.is_some());
+        assert!(repeat.is_none());
+        assert_eq!(
+            calls.finish()[0].arguments,
+            serde_json::json!({ "path": "a.rs" })
+        );
+    }
+
+    #[test]
+    fn empty_repeat_under_a_new_index_folds_into_the_original_call() {
+        let mut calls = calls();
+        calls.push(0, some("call_1"), some("read"), some("{\"path\":\"a.rs\"}"));
+        calls.push(1, some("call_1"), some("read"), some(""));
+
+        let finished = calls.finish();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].arguments, serde_json::json!({ "path": "a.rs" }));
+    }
+
+    #[test]
+    fn calls_without_arguments_get_an_empty_object() {
+        let mut calls = calls();
+        calls.push(0, some("call_1"), some("now"), None);
+
+        assert_eq!(calls.finish()[0].arguments, serde_json::json!({}));
+    }
+}
@@ -12,6 +12,8 @@ use roci_core::provider::{ModelProvider, ProviderRequest, ProviderResponse};
 
 use crate::models::openai::OpenAiModel;
 
+pub(crate) const BASE_URL: &str = "https://openrouter.ai/api/v1";
+
 pub struct OpenRouterProvider {
     inner: OpenAiProvider,
 }
@@ -20,14 +22,16 @@ impl OpenRouterProvider {
     pub fn new(model_id: String, api_key: String) -> Self {
         let model = OpenAiModel::Custom(model_id);
         Self {
-            inner: OpenAiProvider::new(
-                model,
-                api_key,
-                Some("https://openrouter.ai/api/v1".to_string()),
-                None,
-            ),
+            inner: OpenAiProvider::new(model, api_key, Some(BASE_URL.to_string()), None),
         }
     }
+
+    /// Replace the default OpenAI-compatible capabilities, e.g. with the
+    /// provider's catalog entry.
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.inner = self.inner.with_capabilities(capabilities);
+        self
+    }
 }
 
 #[async_trait]
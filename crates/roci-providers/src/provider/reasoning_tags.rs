@@ -0,0 +1,577 @@
+//! Reasoning-tag extraction for OpenAI-compatible local model servers.
+//!
+//! Models served through vLLM, llama.cpp, LMStudio, or Ollama often inline
+//! their reasoning in the text stream, e.g. `<think>...</think>`. The
+//! extractor strips tagged spans out of the text and reports them as
+//! reasoning so the runner never persists them in the assistant message.
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+
+use roci_core::error::RociError;
+use roci_core::provider::ProviderResponse;
+use roci_core::types::{ContentPart, StreamEventType, TextStreamDelta, ThinkingContent};
+
+/// An opening and closing tag that delimit inline reasoning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReasoningTag {
+    pub open: String,
+    pub close: String,
+}
+
+impl ReasoningTag {
+    pub fn new(open: impl Into<String>, close: impl Into<String>) -> Self {
+        Self {
+            open: open.into(),
+            close: close.into(),
+        }
+    }
+}
+
+/// Which tags to extract and whether extracted text stays in the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReasoningTagConfig {
+    tags: Vec<ReasoningTag>,
+    keep_in_text: bool,
+}
+
+impl ReasoningTagConfig {
+    pub fn new(tags: Vec<ReasoningTag>) -> Self {
+        Self {
+            tags: tags
+                .into_iter()
+                .filter(|tag| !tag.open.is_empty() && !tag.close.is_empty())
+                .collect(),
+            keep_in_text: false,
+        }
+    }
+
+    /// Pass text through untouched.
+    pub fn disabled() -> Self {
+        Self::new(Vec::new())
+    }
+
+    /// Also extract spans between `open` and `close`.
+    pub fn with_tag(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        let tag = ReasoningTag::new(open, close);
+        if !tag.open.is_empty() && !tag.close.is_empty() {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// Keep tagged spans (tags included) in the text as well as reporting
+    /// them as reasoning.
+    pub fn keep_in_text(mut self, keep: bool) -> Self {
+        self.keep_in_text = keep;
+        self
+    }
+
+    pub fn tags(&self) -> &[ReasoningTag] {
+        &self.tags
+    }
+
+    pub fn keeps_text(&self) -> bool {
+        self.keep_in_text
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
+    /// Move tagged spans in a complete response from `text` into `thinking`.
+    pub fn apply_to_response(&self, mut response: ProviderResponse) -> ProviderResponse {
+        if !self.is_enabled() {
+            return response;
+        }
+        let mut extractor = ReasoningTagExtractor::new(self.clone());
+        let mut segments = extractor.push(&response.text);
+        segments.extend(extractor.finish());
+        let mut text = String::new();
+        let mut reasoning = String::new();
+        for segment in segments {
+            match segment {
+                TagSegment::Text(chunk) => text.push_str(&chunk),
+                TagSegment::Reasoning(chunk) => reasoning.push_str(&chunk),
+            }
+        }
+        if reasoning.is_empty() {
+            return response;
+        }
+        if !self.keep_in_text {
+            response.text = text;
+        }
+        response
+            .thinking
+            .push(ContentPart::Thinking(ThinkingContent {
+                thinking: reasoning,
+                signature: String::new(),
+            }));
+        response
+    }
+
+    /// Route tagged spans in streamed text deltas to reasoning deltas.
+    ///
+    /// Tags may be split across any number of deltas. Text that could still
+    /// become a tag is held back until it is decided, and an unterminated tag
+    /// is flushed as reasoning when the stream finishes.
+    pub fn apply_to_stream(
+        &self,
+        stream: BoxStream<'static, Result<TextStreamDelta, RociError>>,
+    ) -> BoxStream<'static, Result<TextStreamDelta, RociError>> {
+        if !self.is_enabled() {
+            return stream;
+        }
+        let keep_in_text = self.keep_in_text;
+        let mut extractor = ReasoningTagExtractor::new(self.clone());
+        let stream = async_stream::stream! {
+            let mut stream = stream;
+            while let Some(item) = stream.next().await {
+                let delta = match item {
+                    Ok(delta) => delta,
+                    Err(err) => {
+                        yield Err(err);
+                        continue;
+                    }
+                };
+                match delta.event_type {
+                    StreamEventType::TextDelta => {
+                        let segments = extractor.push(&delta.text);
+                        if keep_in_text {
+                            yield Ok(delta);
+                        }
+                        for segment in segments {
+                            if let Some(delta) = segment_delta(segment, keep_in_text) {
+                                yield Ok(delta);
+                            }
+                        }
+                    }
+                    StreamEventType::Done => {
+                        for segment in extractor.finish() {
+                            if let Some(delta) = segment_delta(segment, keep_in_text) {
+                                yield Ok(delta);
+                            }
+                        }
+                        yield Ok(delta);
+                    }
+                    _ => yield Ok(delta),
+                }
+            }
+            for segment in extractor.finish() {
+                if let Some(delta) = segment_delta(segment, keep_in_text) {
+                    yield Ok(delta);
+                }
+            }
+        };
+        Box::pin(stream)
+    }
+}
+
+impl Default for ReasoningTagConfig {
+    /// Extracts `<think>...</think>`.
+    fn default() -> Self {
+        Self::new(vec![ReasoningTag::new("<think>", "</think>")])
+    }
+}
+
+fn segment_delta(segment: TagSegment, keep_in_text: bool) -> Option<TextStreamDelta> {
+    let (text, event_type, reasoning) = match segment {
+        TagSegment::Text(_) if keep_in_text => return None,
+        TagSegment::Text(text) => (text, StreamEventType::TextDelta, None),
+        TagSegment::Reasoning(reasoning) => {
+            (String::new(), StreamEventType::Reasoning, Some(reasoning))
+        }
+    };
+    Some(TextStreamDelta {
+        text,
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TagSegment {
+    Text(String),
+    Reasoning(String),
+}
+
+/// Incremental state machine that splits text into plain and tagged spans.
+struct ReasoningTagExtractor {
+    config: ReasoningTagConfig,
+    buffer: String,
+    /// Index of the tag whose close we are waiting for.
+    active: Option<usize>,
+    /// Drop whitespace between a close tag and the text that follows it.
+    trim_leading: bool,
+}
+
+impl ReasoningTagExtractor {
+    fn new(config: ReasoningTagConfig) -> Self {
+        Self {
+            config,
+            buffer: String::new(),
+            active: None,
+            trim_leading: false,
+        }
+    }
+
+    fn push(&mut self, chunk: &str) -> Vec<TagSegment> {
+        self.buffer.push_str(chunk);
+        let mut segments = Vec::new();
+        loop {
+            match self.active {
+                None => {
+                    if self.trim_leading {
+                        let trimmed = self.buffer.len() - self.buffer.trim_start().len();
+                        self.buffer.drain(..trimmed);
+                        if self.buffer.is_empty() {
+                            break;
+                        }
+                        self.trim_leading = false;
+                    }
+                    let opened = self
+                        .config
+                        .tags
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, tag)| {
+                            self.buffer.find(&tag.open).map(|pos| (pos, index))
+                        })
+                        .min();
+                    if let Some((pos, index)) = opened {
+                        let open_len = self.config.tags[index].open.len();
+                        push_segment(&mut segments, TagSegment::Text(self.take(pos)));
+                        self.buffer.drain(..open_len);
+                        self.active = Some(index);
+                        continue;
+                    }
+                    let held = partial_tag_len(
+                        &self.buffer,
+                        self.config.tags.iter().map(|tag| tag.open.as_str()),
+                    );
+                    let ready = self.buffer.len() - held;
+                    push_segment(&mut segments, TagSegment::Text(self.take(ready)));
+                    break;
+                }
+                Some(index) => {
+                    let close = &self.config.tags[index].close;
+                    if let Some(pos) = self.buffer.find(close.as_str()) {
+                        let close_len = close.len();
+                        push_segment(&mut segments, TagSegment::Reasoning(self.take(pos)));
+                        self.buffer.drain(..close_len);
+                        self.active = None;
+                        self.trim_leading = true;
+                        continue;
+                    }
+                    let held = partial_tag_len(&self.buffer, std::iter::once(close.as_str()));
+                    let ready = self.buffer.len() - held;
+                    push_segment(&mut segments, TagSegment::Reasoning(self.take(ready)));
+                    break;
+                }
+            }
+        }
+        segments
+    }
+
+    /// Flush held-back text. An unterminated tag flushes as reasoning.
+    fn finish(&mut self) -> Vec<TagSegment> {
+        let rest = std::mem::take(&mut self.buffer);
+        let segment = match self.active.take() {
+            Some(_) => TagSegment::Reasoning(rest),
+            None => TagSegment::Text(rest),
+        };
+        self.trim_leading = false;
+        let mut segments = Vec::new();
+        push_segment(&mut segments, segment);
+        segments
+    }
+
+    fn take(&mut self, len: usize) -> String {
+        self.buffer.drain(..len).collect()
+    }
+}
+
+/// Append `segment`, merging it into the previous one of the same kind.
+fn push_segment(segments: &mut Vec<TagSegment>, segment: TagSegment) {
+    match (segments.last_mut(), segment) {
+        (_, TagSegment::Text(text) | TagSegment::Reasoning(text)) if text.is_empty() => {}
+        (Some(TagSegment::Text(last)), TagSegment::Text(text))
+        | (Some(TagSegment::Reasoning(last)), TagSegment::Reasoning(text)) => last.push_str(&text),
+        (_, segment) => segments.push(segment),
+    }
+}
+
+/// Length of the longest suffix of `buffer` that is a proper prefix of one
+/// of `tags`, i.e. text that may still turn into a tag.
+fn partial_tag_len<'a>(buffer: &str, tags: impl Iterator<Item = &'a str>) -> usize {
+    tags.flat_map(|tag| {
+        (1..tag.len())
+            .rev()
+            .find(|len| tag.is_char_boundary(*len) && buffer.ends_with(&tag[..*len]))
+    })
+    .max()
+    .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+    use roci_core::types::FinishReason;
+
+    fn text_delta(text: &str) -> TextStreamDelta {
+        TextStreamDelta {
+            text: text.to_string(),
+            event_type: StreamEventType::TextDelta,
+            tool_call: None,
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        }
+    }
+
+    fn done_delta() -> TextStreamDelta {
+        TextStreamDelta {
+            text: String::new(),
+            event_type: StreamEventType::Done,
+            tool_call: None,
+            finish_reason: Some(FinishReason::Stop),
+            usage: None,
+            reasoning: None,
+            reasoning_signature: None,
+            reasoning_type: None,
+            image: None,
+            response_metadata: None,
+        }
+    }
+
+    /// Feed `chunks` through the extractor and return (text, reasoning).
+    fn extract(config: &ReasoningTagConfig, chunks: &[&str]) -> (String, String) {
+        let mut extractor = ReasoningTagExtractor::new(config.clone());
+        let mut segments = Vec::new();
+        for chunk in chunks {
+            segments.extend(extractor.push(chunk));
+        }
+        segments.extend(extractor.finish());
+        let mut text = String::new();
+        let mut reasoning = String::new();
+        for segment in segments {
+            match segment {
+                TagSegment::Text(chunk) => text.push_str(&chunk),
+                TagSegment::Reasoning(chunk) => reasoning.push_str(&chunk),
+            }
+        }
+        (text, reasoning)
+    }
+
+    /// Every way of splitting `input` into two or three chunks on char
+    /// boundaries.
+    fn splits(input: &str) -> Vec<Vec<&str>> {
+        let bounds = (0..=input.len())
+            .filter(|index| input.is_char_boundary(*index))
+            .collect::<Vec<_>>();
+        let mut splits = Vec::new();
+        for &first in &bounds {
+            for &second in bounds.iter().filter(|second| **second >= first) {
+                splits.push(vec![
+                    &input[..first],
+                    &input[first..second],
+                    &input[second..],
+                ]);
+            }
+        }
+        splits
+    }
+
+    async fn collect(
+        config: &ReasoningTagConfig,
+        deltas: Vec<TextStreamDelta>,
+    ) -> Vec<TextStreamDelta> {
+        let stream = Box::pin(stream::iter(deltas.into_iter().map(Ok)));
+        config
+            .apply_to_stream(stream)
+            .map(|delta| delta.expect("delta"))
+            .collect()
+            .await
+    }
+
+    #[test]
+    fn extracts_tags_split_at_every_delta_boundary() {
+        let config = ReasoningTagConfig::default();
+        let input = "<think>plan: add 2+2 → 4</think>\n\nThe answer is 4.";
+        for chunks in splits(input) {
+            assert_eq!(
+                extract(&config, &chunks),
+                (
+                    "The answer is 4.".to_string(),
+                    "plan: add 2+2 → 4".to_string()
+                ),
+                "chunks: {chunks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extracts_multiple_tag_kinds_split_at_every_delta_boundary() {
+        let config = ReasoningTagConfig::default().with_tag("<reasoning>", "</reasoning>");
+        let input = "a<think>x</think> b <reasoning>y<z</reasoning>c <thin";
+        for chunks in splits(input) {
+            assert_eq!(
+                extract(&config, &chunks),
+                ("ab c <thin".to_string(), "xy<z".to_string()),
+                "chunks: {chunks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn unterminated_tag_flushes_as_reasoning() {
+        let config = ReasoningTagConfig::default();
+        for chunks in splits("Sure. <think>still going</thi") {
+            assert_eq!(
+                extract(&config, &chunks),
+                ("Sure. ".to_string(), "still going</thi".to_string()),
+                "chunks: {chunks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn text_without_tags_passes_through() {
+        let config = ReasoningTagConfig::default();
+        assert_eq!(
+            extract(&config, &["a < b", " and <th", "ink of it"]),
+            ("a < b and <think of it".to_string(), String::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_routes_tagged_text_to_reasoning_events() {
+        let config = ReasoningTagConfig::default();
+        let deltas = collect(
+            &config,
+            vec![
+                text_delta("<th"),
+                text_delta("ink>step one"),
+                text_delta("</th"),
+                text_delta("ink>Done."),
+                done_delta(),
+            ],
+        )
+        .await;
+
+        let text: String = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::TextDelta)
+            .map(|delta| delta.text.as_str())
+            .collect();
+        let reasoning: String = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::Reasoning)
+            .filter_map(|delta| delta.reasoning.as_deref())
+            .collect();
+        assert_eq!(text, "Done.");
+        assert_eq!(reasoning, "step one");
+        assert_eq!(
+            deltas.last().map(|delta| delta.event_type),
+            Some(StreamEventType::Done)
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_flushes_unterminated_tag_before_done() {
+        let config = ReasoningTagConfig::default();
+        let deltas = collect(
+            &config,
+            vec![
+                text_delta("<think>half a tho"),
+                text_delta("ught"),
+                done_delta(),
+            ],
+        )
+        .await;
+
+        assert!(deltas
+            .iter()
+            .all(|delta| delta.event_type != StreamEventType::TextDelta));
+        let reasoning: String = deltas
+            .iter()
+            .filter_map(|delta| delta.reasoning.as_deref())
+            .collect();
+        assert_eq!(reasoning, "half a thought");
+        assert_eq!(
+            deltas.last().map(|delta| delta.event_type),
+            Some(StreamEventType::Done)
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_keeps_raw_text_when_configured() {
+        let config = ReasoningTagConfig::default().keep_in_text(true);
+        let deltas = collect(
+            &config,
+            vec![
+                text_delta("<think>hm</think>"),
+                text_delta("ok"),
+                done_delta(),
+            ],
+        )
+        .await;
+
+        let text: String = deltas
+            .iter()
+            .filter(|delta| delta.event_type == StreamEventType::TextDelta)
+            .map(|delta| delta.text.as_str())
+            .collect();
+        let reasoning: String = deltas
+            .iter()
+            .filter_map(|delta| delta.reasoning.as_deref())
+            .collect();
+        assert_eq!(text, "<think>hm</think>ok");
+        assert_eq!(reasoning, "hm");
+    }
+
+    #[test]
+    fn response_text_moves_tagged_spans_into_thinking() {
+        let response = ProviderResponse {
+            text: "<think>why</think>\nBecause.".to_string(),
+            usage: Default::default(),
+            tool_calls: Vec::new(),
+            finish_reason: Some(FinishReason::Stop),
+            thinking: Vec::new(),
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
+        };
+
+        let response = ReasoningTagConfig::default().apply_to_response(response);
+
+        assert_eq!(response.text, "Because.");
+        assert!(matches!(
+            response.thinking.as_slice(),
+            [ContentPart::Thinking(ThinkingContent { thinking, .. })] if thinking == "why"
+        ));
+    }
+
+    #[tokio::test]
+    async fn disabled_config_passes_stream_through() {
+        let deltas = collect(
+            &ReasoningTagConfig::disabled(),
+            vec![text_delta("<think>x</think>"), done_delta()],
+        )
+        .await;
+        assert_eq!(deltas[0].text, "<think>x</think>");
+    }
+}
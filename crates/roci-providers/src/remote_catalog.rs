@@ -0,0 +1,831 @@
+//! Runtime model catalogs for OpenRouter and Together.
+//!
+//! Both providers route to hundreds of models, so instead of static tables
+//! [`load_catalog`] fetches `GET {api_base}/models` and turns each entry into a
+//! [`ModelInfo`] carrying context length, tool and vision support, and token
+//! pricing. Parsed catalogs are kept per provider in [`RemoteCatalogCache`],
+//! which can optionally persist to `~/.roci/model-catalogs.json`.
+//!
+//! Provider factories read the cache synchronously in `create()`, never the
+//! network. Expired catalogs are kept rather than dropped: when a refresh is
+//! impossible (offline mode, no network, an API error) the last catalog is
+//! served with a staleness warning in each model's `warning` metadata. With
+//! no cached catalog at all, models fall back to default capabilities and
+//! have no pricing.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::models::{
+    ModelCapabilities, ModelCatalog, ModelCatalogSource, ModelInfo, ModelInputCapabilities,
+    ModelPolicy, ModelPricing, PricingTable,
+};
+use roci_core::provider::http::shared_client;
+
+use crate::disk_cache::{default_disk_path, CacheRecord, DiskCache};
+use crate::json::positive_usize;
+use crate::models::openai::OpenAiModel;
+
+/// Default time-to-live for cached catalogs.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// File name of the on-disk cache inside `~/.roci`.
+pub const CACHE_FILE_NAME: &str = "model-catalogs.json";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const CACHE_FILE_VERSION: u32 = 1;
+const MODELS_ENDPOINT: &str = "/models";
+
+/// Provider whose model catalog is fetched at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemoteCatalogProvider {
+    #[cfg(feature = "openrouter")]
+    OpenRouter,
+    #[cfg(feature = "together")]
+    Together,
+}
+
+impl RemoteCatalogProvider {
+    /// Provider for `provider_key`, or `None` when it has no remote catalog.
+    pub fn from_provider_key(provider_key: &str) -> Option<Self> {
+        match provider_key {
+            #[cfg(feature = "openrouter")]
+            "openrouter" => Some(Self::OpenRouter),
+            #[cfg(feature = "together")]
+            "together" => Some(Self::Together),
+            _ => None,
+        }
+    }
+
+    pub const fn provider_key(self) -> &'static str {
+        match self {
+            #[cfg(feature = "openrouter")]
+            Self::OpenRouter => "openrouter",
+            #[cfg(feature = "together")]
+            Self::Together => "together",
+        }
+    }
+
+    fn api_base(self) -> &'static str {
+        match self {
+            #[cfg(feature = "openrouter")]
+            Self::OpenRouter => crate::provider::openrouter::BASE_URL,
+            #[cfg(feature = "together")]
+            Self::Together => crate::provider::together::BASE_URL,
+        }
+    }
+
+    /// Parse a `/models` response body into catalog entries.
+    ///
+    /// # Errors
+    ///
+    /// [`RociError::Provider`] when the body has no model list.
+    pub fn parse_models(self, body: &Value) -> Result<Vec<ModelInfo>, RociError> {
+        let entries = match body {
+            Value::Array(entries) => entries,
+            Value::Object(object) => object
+                .get("data")
+                .and_then(Value::as_array)
+                .ok_or_else(|| self.parse_error("response missing data array"))?,
+            _ => return Err(self.parse_error("response must be an array or object")),
+        };
+        Ok(entries
+            .iter()
+            .filter_map(|entry| match self {
+                #[cfg(feature = "openrouter")]
+                Self::OpenRouter => openrouter_model(entry),
+                #[cfg(feature = "together")]
+                Self::Together => together_model(entry),
+            })
+            .collect())
+    }
+
+    fn parse_error(self, message: &str) -> RociError {
+        RociError::Provider {
+            provider: self.provider_key().to_string(),
+            message: format!("failed to parse model catalog: {message}"),
+        }
+    }
+}
+
+/// Endpoint to fetch a catalog from.
+#[derive(Debug, Clone)]
+pub struct CatalogTarget {
+    pub provider: RemoteCatalogProvider,
+    /// Full URL of the models endpoint.
+    pub models_url: String,
+    /// Bearer token, sent when present. Both catalogs are readable without one.
+    pub api_key: Option<String>,
+}
+
+impl CatalogTarget {
+    /// Resolve the target a provider factory would use for `provider_key`.
+    pub fn for_provider(config: &RociConfig, provider_key: &str) -> Option<Self> {
+        let provider = RemoteCatalogProvider::from_provider_key(provider_key)?;
+        Some(Self {
+            provider,
+            models_url: format!("{}{MODELS_ENDPOINT}", provider.api_base()),
+            api_key: config.get_api_key(provider_key),
+        })
+    }
+}
+
+/// Catalog for `target`, using and populating `cache`.
+///
+/// A fresh cache entry is returned without touching the network. Otherwise
+/// the catalog is fetched; when that is impossible the expired entry is
+/// served with a staleness warning, and an empty catalog when there is none.
+pub async fn load_catalog(
+    config: &RociConfig,
+    target: &CatalogTarget,
+    cache: &RemoteCatalogCache,
+) -> ModelCatalog {
+    let provider_key = target.provider.provider_key();
+    let cached = cache.get(provider_key);
+    if let Some(cached) = cached.as_ref().filter(|cached| !cached.stale) {
+        return ModelCatalog::from_models(cached.models.clone());
+    }
+    if config.is_offline() {
+        return stale_catalog(provider_key, cached, "offline mode");
+    }
+    match fetch_catalog(target).await {
+        Ok(models) => {
+            cache.insert(provider_key, models.clone());
+            ModelCatalog::from_models(models)
+        }
+        Err(error) => stale_catalog(provider_key, cached, &error.to_string()),
+    }
+}
+
+/// Load the catalog for `provider_key` through the global cache.
+///
+/// Hosts call this before creating a provider so the following `create()`
+/// picks up catalog capabilities, and before pricing a run with
+/// [`RemoteCatalogCache::pricing_table`]. Returns `None` for providers
+/// without a remote catalog.
+pub async fn refresh_provider_catalog(
+    config: &RociConfig,
+    provider_key: &str,
+) -> Option<ModelCatalog> {
+    let target = CatalogTarget::for_provider(config, provider_key)?;
+    Some(load_catalog(config, &target, RemoteCatalogCache::global()).await)
+}
+
+/// Capabilities for `model_id` from the global cache, or `defaults`.
+///
+/// Expired catalogs still count. Used by provider factories from the
+/// synchronous `create()` path.
+pub(crate) fn cached_capabilities(
+    provider_key: &str,
+    model_id: &str,
+    defaults: ModelCapabilities,
+) -> ModelCapabilities {
+    RemoteCatalogCache::global()
+        .model(provider_key, model_id)
+        .map(|model| model.capabilities)
+        .unwrap_or(defaults)
+}
+
+fn stale_catalog(provider_key: &str, cached: Option<CachedCatalog>, reason: &str) -> ModelCatalog {
+    let Some(cached) = cached else {
+        tracing::warn!(
+            provider = provider_key,
+            reason,
+            "no cached model catalog; using default capabilities"
+        );
+        return ModelCatalog::default();
+    };
+    let warning = format!(
+        "model catalog is stale (fetched {}): {reason}",
+        cached.fetched_at.to_rfc3339()
+    );
+    tracing::warn!(provider = provider_key, "{warning}");
+    let mut catalog = ModelCatalog::from_models(cached.models);
+    catalog.update_models(|model| {
+        model
+            .metadata
+            .insert("warning".to_string(), Value::String(warning.clone()));
+    });
+    catalog
+}
+
+async fn fetch_catalog(target: &CatalogTarget) -> Result<Vec<ModelInfo>, RociError> {
+    let request = shared_client()
+        .get(&target.models_url)
+        .timeout(FETCH_TIMEOUT);
+    let request = match target.api_key.as_deref().filter(|key| !key.is_empty()) {
+        Some(key) => request.bearer_auth(key),
+        None => request,
+    };
+    let response = request.send().await.map_err(RociError::Network)?;
+    let status = response.status();
+    let body = response.text().await.map_err(RociError::Network)?;
+    if !status.is_success() {
+        return Err(RociError::api(status.as_u16(), body));
+    }
+    let body = serde_json::from_str::<Value>(&body)
+        .map_err(|error| target.provider.parse_error(&error.to_string()))?;
+    target.provider.parse_models(&body)
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+fn catalog_model(
+    provider_key: &str,
+    id: &str,
+    display_name: Option<&str>,
+    capabilities: ModelCapabilities,
+    pricing: Option<ModelPricing>,
+) -> ModelInfo {
+    ModelInfo {
+        provider_key: provider_key.to_string(),
+        model_id: id.to_string(),
+        display_name: Some(display_name.unwrap_or(id).to_string()),
+        capabilities,
+        policy: ModelPolicy {
+            requires_credentials: true,
+            local: false,
+            deprecated: false,
+            default_for_provider: false,
+        },
+        source: ModelCatalogSource::Dynamic {
+            endpoint: MODELS_ENDPOINT.to_string(),
+        },
+        pricing,
+        metadata: Default::default(),
+    }
+}
+
+fn default_capabilities(id: &str) -> ModelCapabilities {
+    OpenAiModel::Custom(id.to_string()).capabilities()
+}
+
+fn with_vision(mut capabilities: ModelCapabilities, supports_vision: bool) -> ModelCapabilities {
+    capabilities.supports_vision = supports_vision;
+    capabilities.input = ModelInputCapabilities {
+        text: capabilities.input.text,
+        ..ModelInputCapabilities::from_vision_support(supports_vision)
+    };
+    capabilities
+}
+
+fn model_id(entry: &Value) -> Option<&str> {
+    entry
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// OpenRouter reports USD per token as strings, with `-1` for routers whose
+/// price depends on the model they pick.
+#[cfg(feature = "openrouter")]
+fn openrouter_price(pricing: &Value, key: &str) -> Option<f64> {
+    let price = match pricing.get(key)? {
+        Value::String(price) => price.parse::<f64>().ok()?,
+        Value::Number(price) => price.as_f64()?,
+        _ => return None,
+    };
+    (price >= 0.0).then_some(price * 1_000_000.0)
+}
+
+#[cfg(feature = "openrouter")]
+fn openrouter_model(entry: &Value) -> Option<ModelInfo> {
+    let id = model_id(entry)?;
+    let has = |list: Option<&Value>, item: &str| {
+        list.and_then(Value::as_array)
+            .is_some_and(|values| values.iter().any(|value| value.as_str() == Some(item)))
+    };
+    let input_modalities = entry
+        .get("architecture")
+        .and_then(|architecture| architecture.get("input_modalities"));
+    let supported_parameters = entry.get("supported_parameters");
+
+    let mut capabilities = with_vision(default_capabilities(id), has(input_modalities, "image"));
+    if supported_parameters.is_some() {
+        capabilities.supports_tools = has(supported_parameters, "tools");
+        capabilities.supports_reasoning = has(supported_parameters, "reasoning");
+    }
+    if let Some(context_length) = entry.get("context_length").and_then(positive_usize) {
+        capabilities.context_length = context_length;
+    }
+    capabilities.max_output_tokens = entry
+        .get("top_provider")
+        .and_then(|provider| provider.get("max_completion_tokens"))
+        .and_then(positive_usize)
+        .or(capabilities.max_output_tokens);
+
+    let pricing = entry.get("pricing").and_then(|pricing| {
+        let input = openrouter_price(pricing, "prompt")?;
+        let output = openrouter_price(pricing, "completion")?;
+        Some(ModelPricing::new(input, output).with_cache(
+            openrouter_price(pricing, "input_cache_read").unwrap_or(0.0),
+            openrouter_price(pricing, "input_cache_write").unwrap_or(0.0),
+        ))
+    });
+
+    Some(catalog_model(
+        "openrouter",
+        id,
+        entry.get("name").and_then(Value::as_str),
+        capabilities,
+        pricing,
+    ))
+}
+
+/// Together model types that accept chat requests.
+#[cfg(feature = "together")]
+const TOGETHER_CHAT_TYPES: &[&str] = &["chat", "language", "code"];
+
+/// Together reports USD per million tokens as numbers. It does not report
+/// tool or vision support, so vision is inferred from the model id and tool
+/// support keeps the OpenAI-compatible default.
+#[cfg(feature = "together")]
+fn together_model(entry: &Value) -> Option<ModelInfo> {
+    let id = model_id(entry)?;
+    let model_type = entry.get("type").and_then(Value::as_str);
+    if model_type.is_some_and(|model_type| !TOGETHER_CHAT_TYPES.contains(&model_type)) {
+        return None;
+    }
+
+    let lower_id = id.to_ascii_lowercase();
+    let supports_vision = lower_id.contains("vision") || lower_id.contains("-vl");
+    let mut capabilities = with_vision(default_capabilities(id), supports_vision);
+    if let Some(context_length) = entry.get("context_length").and_then(positive_usize) {
+        capabilities.context_length = context_length;
+    }
+
+    let pricing = entry.get("pricing").and_then(|pricing| {
+        let price = |key: &str| pricing.get(key).and_then(Value::as_f64);
+        Some(ModelPricing::new(price("input")?, price("output")?))
+    });
+
+    Some(catalog_model(
+        "together",
+        id,
+        entry.get("display_name").and_then(Value::as_str),
+        capabilities,
+        pricing,
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Cache
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    provider_key: String,
+    fetched_at: DateTime<Utc>,
+    models: Vec<ModelInfo>,
+}
+
+impl CacheRecord for CacheEntry {
+    type Key = String;
+
+    fn key(&self) -> Self::Key {
+        self.provider_key.clone()
+    }
+
+    fn stored_at(&self) -> DateTime<Utc> {
+        self.fetched_at
+    }
+}
+
+/// A cached catalog and whether it has outlived the cache TTL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedCatalog {
+    pub models: Vec<ModelInfo>,
+    pub fetched_at: DateTime<Utc>,
+    pub stale: bool,
+}
+
+/// Fetched catalogs keyed by provider with a TTL.
+///
+/// In-memory by default; [`RemoteCatalogCache::with_disk_path`] adds
+/// best-effort JSON persistence. Disk errors are logged and otherwise
+/// ignored. Unlike [`CapabilityCache`](crate::capability_probe::CapabilityCache),
+/// expired entries are kept and reported as stale.
+#[derive(Debug)]
+pub struct RemoteCatalogCache {
+    cache: DiskCache<CacheEntry>,
+}
+
+impl Default for RemoteCatalogCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_TTL)
+    }
+}
+
+impl RemoteCatalogCache {
+    /// Create an in-memory cache with the given TTL.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: DiskCache::new(ttl, CACHE_FILE_VERSION, "model catalog cache")
+                .persisting_expired(),
+        }
+    }
+
+    /// Persist entries to `path` (loaded lazily on first lookup).
+    pub fn with_disk_path(self, path: impl Into<PathBuf>) -> Self {
+        self.set_disk_path(Some(path.into()));
+        self
+    }
+
+    /// Change (or clear) the persistence file of an existing cache.
+    ///
+    /// Used to enable disk persistence on [`RemoteCatalogCache::global`].
+    pub fn set_disk_path(&self, path: Option<PathBuf>) {
+        self.cache.set_disk_path(path);
+    }
+
+    /// Default persistence file: `~/.roci/model-catalogs.json`.
+    pub fn default_disk_path() -> PathBuf {
+        default_disk_path(CACHE_FILE_NAME)
+    }
+
+    /// Process-wide cache used by the built-in provider factories.
+    pub fn global() -> &'static RemoteCatalogCache {
+        static GLOBAL: OnceLock<RemoteCatalogCache> = OnceLock::new();
+        GLOBAL.get_or_init(RemoteCatalogCache::default)
+    }
+
+    /// Cached catalog for `provider_key`, fresh or stale.
+    pub fn get(&self, provider_key: &str) -> Option<CachedCatalog> {
+        let entries = self.cache.entries();
+        let entry = entries.get(provider_key)?;
+        Some(CachedCatalog {
+            models: entry.models.clone(),
+            fetched_at: entry.fetched_at,
+            stale: !self.cache.is_fresh(entry),
+        })
+    }
+
+    /// Cached entry for one model, fresh or stale.
+    pub fn model(&self, provider_key: &str, model_id: &str) -> Option<ModelInfo> {
+        self.cache
+            .entries()
+            .get(provider_key)?
+            .models
+            .iter()
+            .find(|model| model.model_id == model_id)
+            .cloned()
+    }
+
+    /// Store the catalog for `provider_key` and persist if configured.
+    pub fn insert(&self, provider_key: &str, models: Vec<ModelInfo>) {
+        self.cache.insert(CacheEntry {
+            provider_key: provider_key.to_string(),
+            fetched_at: Utc::now(),
+            models,
+        });
+    }
+
+    /// Drop every entry from memory without re-reading the disk file, so
+    /// catalogs are fetched again on next use.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// `base` with the pricing of every cached model added on top.
+    ///
+    /// Catalog prices are exact-id entries, so they take precedence over
+    /// prefix entries in `base`; see [`PricingTable::with_catalog`].
+    pub fn pricing_table(&self, base: PricingTable) -> PricingTable {
+        self.cache.entries().values().fold(base, |table, entry| {
+            table.with_catalog(&ModelCatalog::from_models(entry.models.clone()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roci_core::models::LanguageModel;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[cfg(feature = "openrouter")]
+    fn openrouter_fixture() -> Value {
+        serde_json::json!({
+            "data": [
+                {
+                    "id": "anthropic/claude-sonnet-4",
+                    "name": "Anthropic: Claude Sonnet 4",
+                    "context_length": 200000,
+                    "architecture": {
+                        "input_modalities": ["text", "image"],
+                        "output_modalities": ["text"]
+                    },
+                    "pricing": {
+                        "prompt": "0.000003",
+                        "completion": "0.000015",
+                        "input_cache_read": "0.0000003",
+                        "input_cache_write": "0.00000375"
+                    },
+                    "top_provider": { "max_completion_tokens": 64000 },
+                    "supported_parameters": ["tools", "tool_choice", "reasoning", "max_tokens"]
+                },
+                {
+                    "id": "meta-llama/llama-3-8b-instruct",
+                    "name": "Meta: Llama 3 8B Instruct",
+                    "context_length": 8192,
+                    "architecture": { "input_modalities": ["text"] },
+                    "pricing": { "prompt": "0.00000003", "completion": "0.00000006" },
+                    "supported_parameters": ["max_tokens", "temperature"]
+                },
+                {
+                    "id": "openrouter/auto",
+                    "name": "Auto Router",
+                    "context_length": 2000000,
+                    "pricing": { "prompt": "-1", "completion": "-1" }
+                },
+                { "name": "missing id" }
+            ]
+        })
+    }
+
+    #[cfg(feature = "together")]
+    fn together_fixture() -> Value {
+        serde_json::json!([
+            {
+                "id": "meta-llama/Llama-3.3-70B-Instruct-Turbo",
+                "object": "model",
+                "display_name": "Meta Llama 3.3 70B Instruct Turbo",
+                "type": "chat",
+                "context_length": 131072,
+                "pricing": { "input": 0.88, "output": 0.88, "base": 0, "finetune": 0 }
+            },
+            {
+                "id": "Qwen/Qwen2.5-VL-72B-Instruct",
+                "type": "chat",
+                "context_length": 32768,
+                "pricing": { "input": 1.95, "output": 8.0 }
+            },
+            {
+                "id": "BAAI/bge-large-en-v1.5",
+                "type": "embedding",
+                "context_length": 512,
+                "pricing": { "input": 0.02, "output": 0.0 }
+            }
+        ])
+    }
+
+    fn find<'a>(models: &'a [ModelInfo], id: &str) -> &'a ModelInfo {
+        models
+            .iter()
+            .find(|model| model.model_id == id)
+            .unwrap_or_else(|| panic!("{id} present"))
+    }
+
+    #[cfg(feature = "openrouter")]
+    async fn mount_catalog(server: &MockServer, status: u16, body: Value, expected_calls: u64) {
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .expect(expected_calls)
+            .mount(server)
+            .await;
+    }
+
+    #[cfg(feature = "openrouter")]
+    fn openrouter_target(server: &MockServer) -> CatalogTarget {
+        CatalogTarget {
+            provider: RemoteCatalogProvider::OpenRouter,
+            models_url: format!("{}/models", server.uri()),
+            api_key: None,
+        }
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[test]
+    fn openrouter_catalog_resolves_capabilities_and_pricing() {
+        let models = RemoteCatalogProvider::OpenRouter
+            .parse_models(&openrouter_fixture())
+            .unwrap();
+
+        assert_eq!(models.len(), 3, "entries without an id are skipped");
+        let sonnet = find(&models, "anthropic/claude-sonnet-4");
+        assert_eq!(sonnet.provider_key, "openrouter");
+        assert_eq!(
+            sonnet.display_name.as_deref(),
+            Some("Anthropic: Claude Sonnet 4")
+        );
+        assert_eq!(sonnet.capabilities.context_length, 200_000);
+        assert_eq!(sonnet.capabilities.max_output_tokens, Some(64_000));
+        assert!(sonnet.capabilities.supports_vision);
+        assert!(sonnet.capabilities.input.image.is_some());
+        assert!(sonnet.capabilities.supports_tools);
+        assert!(sonnet.capabilities.supports_reasoning);
+        let pricing = sonnet.pricing.expect("sonnet pricing");
+        assert!((pricing.input_per_million - 3.0).abs() < 1e-9);
+        assert!((pricing.output_per_million - 15.0).abs() < 1e-9);
+        assert!((pricing.cache_read_per_million - 0.3).abs() < 1e-9);
+        assert!((pricing.cache_write_per_million - 3.75).abs() < 1e-9);
+
+        let llama = find(&models, "meta-llama/llama-3-8b-instruct");
+        assert_eq!(llama.capabilities.context_length, 8_192);
+        assert!(!llama.capabilities.supports_vision);
+        assert!(!llama.capabilities.supports_tools);
+
+        let auto = find(&models, "openrouter/auto");
+        assert!(auto.pricing.is_none(), "variable router pricing is unknown");
+        assert!(
+            auto.capabilities.supports_tools,
+            "keeps default tool support"
+        );
+    }
+
+    #[cfg(feature = "together")]
+    #[test]
+    fn together_catalog_keeps_chat_models_with_pricing() {
+        let models = RemoteCatalogProvider::Together
+            .parse_models(&together_fixture())
+            .unwrap();
+
+        assert_eq!(models.len(), 2, "embedding models are skipped");
+        let llama = find(&models, "meta-llama/Llama-3.3-70B-Instruct-Turbo");
+        assert_eq!(llama.provider_key, "together");
+        assert_eq!(llama.capabilities.context_length, 131_072);
+        assert!(!llama.capabilities.supports_vision);
+        assert_eq!(llama.pricing, Some(ModelPricing::new(0.88, 0.88)));
+
+        let qwen = find(&models, "Qwen/Qwen2.5-VL-72B-Instruct");
+        assert!(qwen.capabilities.supports_vision);
+        assert_eq!(qwen.pricing, Some(ModelPricing::new(1.95, 8.0)));
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[test]
+    fn parse_rejects_bodies_without_a_model_list() {
+        let error = RemoteCatalogProvider::OpenRouter
+            .parse_models(&serde_json::json!({ "error": "nope" }))
+            .unwrap_err();
+
+        assert!(matches!(error, RociError::Provider { .. }));
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[tokio::test]
+    async fn fresh_catalog_is_served_from_cache() {
+        let server = MockServer::start().await;
+        mount_catalog(&server, 200, openrouter_fixture(), 1).await;
+        let cache = RemoteCatalogCache::default();
+        let target = openrouter_target(&server);
+        let config = RociConfig::default();
+
+        let first = load_catalog(&config, &target, &cache).await;
+        let second = load_catalog(&config, &target, &cache).await;
+
+        assert_eq!(first.models().len(), 3);
+        assert_eq!(first, second);
+        assert!(!second.models()[0].metadata.contains_key("warning"));
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[tokio::test]
+    async fn expired_catalog_is_refetched() {
+        let server = MockServer::start().await;
+        mount_catalog(&server, 200, openrouter_fixture(), 1).await;
+        let cache = RemoteCatalogCache::new(Duration::ZERO);
+        cache.insert("openrouter", Vec::new());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("openrouter").unwrap().stale);
+
+        let catalog =
+            load_catalog(&RociConfig::default(), &openrouter_target(&server), &cache).await;
+
+        assert_eq!(catalog.models().len(), 3);
+        assert_eq!(cache.get("openrouter").unwrap().models.len(), 3);
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[tokio::test]
+    async fn failed_refresh_serves_stale_catalog_with_warning() {
+        let server = MockServer::start().await;
+        mount_catalog(&server, 503, serde_json::json!({}), 1).await;
+        let cache = RemoteCatalogCache::new(Duration::ZERO);
+        cache.insert(
+            "openrouter",
+            RemoteCatalogProvider::OpenRouter
+                .parse_models(&openrouter_fixture())
+                .unwrap(),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+
+        let catalog =
+            load_catalog(&RociConfig::default(), &openrouter_target(&server), &cache).await;
+
+        assert_eq!(catalog.models().len(), 3);
+        let warning = catalog.models()[0].metadata["warning"].as_str().unwrap();
+        assert!(warning.starts_with("model catalog is stale"), "{warning}");
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[tokio::test]
+    async fn offline_mode_uses_stale_catalog_without_fetching() {
+        let server = MockServer::start().await;
+        mount_catalog(&server, 200, openrouter_fixture(), 0).await;
+        let cache = RemoteCatalogCache::new(Duration::ZERO);
+        cache.insert(
+            "openrouter",
+            RemoteCatalogProvider::OpenRouter
+                .parse_models(&openrouter_fixture())
+                .unwrap(),
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        let config = RociConfig::default();
+        config.set_offline(true);
+
+        let catalog = load_catalog(&config, &openrouter_target(&server), &cache).await;
+
+        assert_eq!(catalog.models().len(), 3);
+        assert!(catalog.models()[0].metadata["warning"]
+            .as_str()
+            .unwrap()
+            .ends_with("offline mode"));
+    }
+
+    #[cfg(feature = "openrouter")]
+    #[tokio::test]
+    async fn cold_cache_without_network_degrades_to_empty_catalog() {
+        let target = CatalogTarget {
+            provider: RemoteCatalogProvider::OpenRouter,
+            models_url: "http://127.0.0.1:9/models".to_string(),
+            api_key: None,
+        };
+        let cache = RemoteCatalogCache::default();
+
+        let catalog = load_catalog(&RociConfig::default(), &target, &cache).await;
+
+        assert!(catalog.models().is_empty());
+        assert!(cache.get("openrouter").is_none());
+    }
+
+    #[cfg(all(feature = "openrouter", feature = "together"))]
+    #[test]
+    fn disk_cache_keeps_stale_entries_and_feeds_pricing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE_NAME);
+        let writer = RemoteCatalogCache::default().with_disk_path(&path);
+        writer.insert(
+            "openrouter",
+            RemoteCatalogProvider::OpenRouter
+                .parse_models(&openrouter_fixture())
+                .unwrap(),
+        );
+        writer.insert(
+            "together",
+            RemoteCatalogProvider::Together
+                .parse_models(&together_fixture())
+                .unwrap(),
+        );
+
+        let reloaded = RemoteCatalogCache::default().with_disk_path(&path);
+        assert!(!reloaded.get("openrouter").unwrap().stale);
+        assert_eq!(
+            reloaded
+                .model("openrouter", "anthropic/claude-sonnet-4")
+                .unwrap()
+                .capabilities
+                .context_length,
+            200_000
+        );
+
+        let expired = RemoteCatalogCache::new(Duration::ZERO).with_disk_path(&path);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(expired.get("together").unwrap().stale);
+
+        let table = expired.pricing_table(PricingTable::default().with_model(
+            "together",
+            "meta-llama",
+            ModelPricing::free(),
+        ));
+        let model = |provider: &str, model_id: &str| LanguageModel::Known {
+            provider_key: provider.to_string(),
+            model_id: model_id.to_string(),
+            provider_options: Default::default(),
+        };
+        assert_eq!(
+            table.lookup(&model(
+                "together",
+                "meta-llama/Llama-3.3-70B-Instruct-Turbo"
+            )),
+            Some(ModelPricing::new(0.88, 0.88))
+        );
+        assert!(table
+            .lookup(&model("openrouter", "anthropic/claude-sonnet-4"))
+            .is_some());
+        assert_eq!(
+            table.lookup(&model("openai", "gpt-4o")),
+            PricingTable::default().lookup(&model("openai", "gpt-4o"))
+        );
+    }
+}
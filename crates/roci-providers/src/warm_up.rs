@@ -0,0 +1,440 @@
+//! Model warm-up for self-hosted providers.
+//!
+//! Loading a large local model can take minutes, which otherwise lands on the
+//! first request of a run. [`warm_up`] loads it ahead of time through the
+//! server's native API:
+//!
+//! - Ollama: `GET {root}/api/ps` to check whether the model is resident, then
+//!   `POST {root}/api/generate` with an empty prompt to load it.
+//! - LM Studio: `GET {root}/api/v0/models/{model}` to check its state, then
+//!   `POST {root}/api/v1/models/load` to load it.
+//!
+//! The built-in Ollama and LM Studio providers implement
+//! [`ModelProvider::warm_up`](roci_core::provider::ModelProvider::warm_up)
+//! with the same requests.
+
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::models::{LanguageModel, ProviderKey};
+use roci_core::provider::http::{shared_client, status_to_error};
+use roci_core::provider::offline::{is_loopback_url, offline_error};
+use roci_core::provider::WarmUpReport;
+
+use crate::capability_probe::{lmstudio_root_url, ollama_root_url};
+use crate::json::positive_usize;
+
+/// Upper bound for a load request; large models can take minutes.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Load `model` on its self-hosted server before it is first used.
+///
+/// # Errors
+///
+/// Returns [`RociError::UnsupportedOperation`] for providers other than
+/// Ollama and LM Studio, the offline-mode configuration error for a remote
+/// server, and the server's error when the load request fails.
+pub async fn warm_up(
+    model: &LanguageModel,
+    config: &RociConfig,
+) -> Result<WarmUpReport, RociError> {
+    let provider_key = model.provider_name();
+    let key = ProviderKey::parse(provider_key);
+    let root_url = match key {
+        Some(ProviderKey::Ollama) => ollama_root_url(config),
+        Some(ProviderKey::LmStudio) => lmstudio_root_url(config),
+        _ => {
+            return Err(RociError::UnsupportedOperation(format!(
+                "{provider_key} does not support model warm-up"
+            )))
+        }
+    };
+    if config.is_offline() && !is_loopback_url(&root_url) {
+        return Err(offline_error(provider_key));
+    }
+    let api_key = key.and_then(|key| config.get_api_key_for(key));
+    match key {
+        Some(ProviderKey::Ollama) => {
+            warm_up_ollama(&root_url, api_key.as_deref(), model.model_id()).await
+        }
+        _ => warm_up_lmstudio(&root_url, api_key.as_deref(), model.model_id()).await,
+    }
+}
+
+/// Warm up `model_id` on the Ollama server at `root_url`.
+pub(crate) async fn warm_up_ollama(
+    root_url: &str,
+    api_key: Option<&str>,
+    model_id: &str,
+) -> Result<WarmUpReport, RociError> {
+    let root_url = root_url.trim_end_matches('/');
+    // Older servers lack `/api/ps`; fall through to loading in that case.
+    if let Ok(Some(entry)) = ollama_running_model(root_url, api_key, model_id).await {
+        return Ok(ollama_report(true, None, Some(&entry)));
+    }
+
+    let started_at = Instant::now();
+    let body = send_json(
+        request(
+            reqwest::Method::POST,
+            format!("{root_url}/api/generate"),
+            api_key,
+        )
+        .timeout(LOAD_TIMEOUT)
+        .json(&serde_json::json!({
+            "model": model_id,
+            "prompt": "",
+            "stream": false,
+        })),
+    )
+    .await?;
+    let load_duration = body
+        .get("load_duration")
+        .and_then(Value::as_u64)
+        .filter(|nanos| *nanos > 0)
+        .map(Duration::from_nanos)
+        .unwrap_or_else(|| started_at.elapsed());
+    let entry = ollama_running_model(root_url, api_key, model_id)
+        .await
+        .ok()
+        .flatten();
+    Ok(ollama_report(false, Some(load_duration), entry.as_ref()))
+}
+
+/// Warm up `model_id` on the LM Studio server at `root_url`.
+pub(crate) async fn warm_up_lmstudio(
+    root_url: &str,
+    api_key: Option<&str>,
+    model_id: &str,
+) -> Result<WarmUpReport, RociError> {
+    let root_url = root_url.trim_end_matches('/');
+    let info = send_json(
+        request(
+            reqwest::Method::GET,
+            format!("{root_url}/api/v0/models/{model_id}"),
+            api_key,
+        )
+        .timeout(STATUS_TIMEOUT),
+    )
+    .await
+    .ok();
+    let max_context_length = info
+        .as_ref()
+        .and_then(|info| info.get("max_context_length"))
+        .and_then(positive_usize);
+    if let Some(info) = info
+        .as_ref()
+        .filter(|info| info.get("state").and_then(Value::as_str) == Some("loaded"))
+    {
+        return Ok(WarmUpReport {
+            already_loaded: true,
+            context_length: info
+                .get("loaded_context_length")
+                .and_then(positive_usize)
+                .or(max_context_length),
+            ..WarmUpReport::default()
+        });
+    }
+
+    let started_at = Instant::now();
+    let body = send_json(
+        request(
+            reqwest::Method::POST,
+            format!("{root_url}/api/v1/models/load"),
+            api_key,
+        )
+        .timeout(LOAD_TIMEOUT)
+        .json(&serde_json::json!({ "model": model_id })),
+    )
+    .await?;
+    let load_duration = body
+        .get("load_time_seconds")
+        .and_then(Value::as_f64)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .unwrap_or_else(|| started_at.elapsed());
+    Ok(WarmUpReport {
+        already_loaded: false,
+        load_duration: Some(load_duration),
+        vram_bytes: None,
+        context_length: body
+            .get("load_config")
+            .and_then(|config| config.get("context_length"))
+            .and_then(positive_usize)
+            .or(max_context_length),
+    })
+}
+
+/// The `/api/ps` entry for `model_id`, matching Ollama's implicit `:latest` tag.
+async fn ollama_running_model(
+    root_url: &str,
+    api_key: Option<&str>,
+    model_id: &str,
+) -> Result<Option<Value>, RociError> {
+    let body = send_json(
+        request(reqwest::Method::GET, format!("{root_url}/api/ps"), api_key)
+            .timeout(STATUS_TIMEOUT),
+    )
+    .await?;
+    let tagged = format!("{model_id}:latest");
+    Ok(body
+        .get("models")
+        .and_then(Value::as_array)
+        .and_then(|models| {
+            models.iter().find(|entry| {
+                ["name", "model"].iter().any(|key| {
+                    entry
+                        .get(*key)
+                        .and_then(Value::as_str)
+                        .is_some_and(|name| name == model_id || name == tagged)
+                })
+            })
+        })
+        .cloned())
+}
+
+fn ollama_report(
+    already_loaded: bool,
+    load_duration: Option<Duration>,
+    entry: Option<&Value>,
+) -> WarmUpReport {
+    WarmUpReport {
+        already_loaded,
+        load_duration,
+        vram_bytes: entry.and_then(|entry| entry.get("size_vram").and_then(Value::as_u64)),
+        context_length: entry
+            .and_then(|entry| entry.get("context_length"))
+            .and_then(positive_usize),
+    }
+}
+
+fn request(method: reqwest::Method, url: String, api_key: Option<&str>) -> reqwest::RequestBuilder {
+    let request = shared_client().request(method, url);
+    match api_key.filter(|key| !key.is_empty()) {
+        Some(key) => request.bearer_auth(key),
+        None => request,
+    }
+}
+
+async fn send_json(request: reqwest::RequestBuilder) -> Result<Value, RociError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(status_to_error(status.as_u16(), &body));
+    }
+    Ok(response.json::<Value>().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config_for(provider_key: &str, server: &MockServer) -> RociConfig {
+        let config = RociConfig::new();
+        config.set_base_url(provider_key, server.uri());
+        config
+    }
+
+    fn model(provider_key: &str, model_id: &str) -> LanguageModel {
+        format!("{provider_key}:{model_id}")
+            .parse()
+            .expect("model should parse")
+    }
+
+    #[tokio::test]
+    async fn ollama_loads_a_cold_model_with_an_empty_prompt() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/ps"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": []
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .and(body_json(serde_json::json!({
+                "model": "llama3.3",
+                "prompt": "",
+                "stream": false,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "llama3.3",
+                "response": "",
+                "done": true,
+                "done_reason": "load",
+                "load_duration": 2_500_000_000u64,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/ps"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [{
+                    "name": "llama3.3:latest",
+                    "model": "llama3.3:latest",
+                    "size_vram": 42_000_000_000u64,
+                    "context_length": 8192,
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = config_for("ollama", &server);
+        let report = warm_up(&model("ollama", "llama3.3"), &config)
+            .await
+            .expect("warm-up should succeed");
+
+        assert_eq!(
+            report,
+            WarmUpReport {
+                already_loaded: false,
+                load_duration: Some(Duration::from_millis(2500)),
+                vram_bytes: Some(42_000_000_000),
+                context_length: Some(8192),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn ollama_reports_a_resident_model_without_loading() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/ps"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [{
+                    "name": "qwen3:32b",
+                    "model": "qwen3:32b",
+                    "size_vram": 20_000_000_000u64,
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let config = config_for("ollama", &server);
+        let report = warm_up(&model("ollama", "qwen3:32b"), &config)
+            .await
+            .expect("warm-up should succeed");
+
+        assert!(report.already_loaded);
+        assert_eq!(report.load_duration, None);
+        assert_eq!(report.vram_bytes, Some(20_000_000_000));
+        assert_eq!(report.context_length, None);
+    }
+
+    #[tokio::test]
+    async fn ollama_load_failure_surfaces_the_server_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/generate"))
+            .respond_with(
+                ResponseTemplate::new(404)
+                    .set_body_string(r#"{"error":"model 'missing' not found"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let config = config_for("ollama", &server);
+        let err = warm_up(&model("ollama", "missing"), &config)
+            .await
+            .expect_err("warm-up should fail");
+
+        assert!(err.to_string().contains("not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn lmstudio_loads_a_cold_model_through_the_native_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v0/models/qwen2.5-7b-instruct"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "qwen2.5-7b-instruct",
+                "state": "not-loaded",
+                "max_context_length": 32768,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/models/load"))
+            .and(body_json(
+                serde_json::json!({ "model": "qwen2.5-7b-instruct" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "type": "llm",
+                "instance_id": "qwen2.5-7b-instruct",
+                "load_time_seconds": 9.5,
+                "status": "loaded",
+                "load_config": { "context_length": 16384 },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = config_for("lmstudio", &server);
+        let report = warm_up(&model("lmstudio", "qwen2.5-7b-instruct"), &config)
+            .await
+            .expect("warm-up should succeed");
+
+        assert_eq!(
+            report,
+            WarmUpReport {
+                already_loaded: false,
+                load_duration: Some(Duration::from_millis(9500)),
+                vram_bytes: None,
+                context_length: Some(16384),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn lmstudio_reports_a_loaded_model_without_loading() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v0/models/qwen2.5-7b-instruct"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "qwen2.5-7b-instruct",
+                "state": "loaded",
+                "max_context_length": 32768,
+                "loaded_context_length": 8192,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/models/load"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let config = config_for("lmstudio", &server);
+        let report = warm_up(&model("lmstudio", "qwen2.5-7b-instruct"), &config)
+            .await
+            .expect("warm-up should succeed");
+
+        assert!(report.already_loaded);
+        assert_eq!(report.context_length, Some(8192));
+    }
+
+    #[tokio::test]
+    async fn hosted_providers_do_not_support_warm_up() {
+        let err = warm_up(&model("openai", "gpt-4o"), &RociConfig::new())
+            .await
+            .expect_err("openai has no warm-up");
+
+        assert!(matches!(err, RociError::UnsupportedOperation(_)));
+    }
+}
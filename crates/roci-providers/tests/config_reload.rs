@@ -0,0 +1,30 @@
+//! Integration test for clearing derived caches on a global config reload.
+//!
+//! Kept in its own test binary because it swaps the process-wide config.
+
+use roci_core::config::RociConfig;
+use roci_core::models::ModelCapabilities;
+use roci_core::provider::ProviderRegistry;
+use roci_providers::capability_probe::CapabilityCache;
+
+#[test]
+fn global_reload_clears_probed_capabilities() {
+    let mut registry = ProviderRegistry::new();
+    roci_providers::register_default_providers(&mut registry);
+    let cache = CapabilityCache::global();
+    cache.insert(
+        "http://127.0.0.1:1234/v1",
+        "local-model",
+        ModelCapabilities::default(),
+    );
+    assert!(cache
+        .get("http://127.0.0.1:1234/v1", "local-model")
+        .is_some());
+
+    RociConfig::global_reload(RociConfig::new());
+
+    assert!(cache
+        .get("http://127.0.0.1:1234/v1", "local-model")
+        .is_none());
+    assert!(RociConfig::global().get_api_key("openai").is_none());
+}
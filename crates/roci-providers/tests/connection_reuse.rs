@@ -0,0 +1,127 @@
+//! Connection reuse across provider instances.
+//!
+//! The runner builds one provider per run and keeps it for every iteration,
+//! and providers send through the process-wide client in
+//! `roci_core::provider::http`, so repeated calls, and calls from separately
+//! built providers, share pooled connections. A raw TCP server counts
+//! accepted connections against served requests.
+
+#![cfg(feature = "openai")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use roci_core::config::RociConfig;
+use roci_core::provider::{ProviderRegistry, ProviderRequest};
+use roci_core::types::{GenerationSettings, ModelMessage};
+
+const CHAT_RESPONSE: &str = r#"{"choices":[{"message":{"content":"hi"},"finish_reason":"stop"}],"usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#;
+
+#[derive(Default)]
+struct Counts {
+    connections: AtomicUsize,
+    requests: AtomicUsize,
+}
+
+/// Serve keep-alive chat completion responses on a local port.
+async fn start_server() -> (String, Arc<Counts>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let url = format!("http://{}/v1", listener.local_addr().expect("addr"));
+    let counts = Arc::new(Counts::default());
+    let server_counts = counts.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            server_counts.connections.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(serve_connection(stream, server_counts.clone()));
+        }
+    });
+    (url, counts)
+}
+
+async fn serve_connection(mut stream: TcpStream, counts: Arc<Counts>) {
+    let mut buffer = Vec::new();
+    loop {
+        let Some(header_end) = read_until_headers_end(&mut stream, &mut buffer).await else {
+            return;
+        };
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_ascii_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        while buffer.len() < header_end + content_length {
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            }
+        }
+        buffer.drain(..header_end + content_length);
+        counts.requests.fetch_add(1, Ordering::SeqCst);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{CHAT_RESPONSE}",
+            CHAT_RESPONSE.len()
+        );
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Offset just past the blank line ending the request headers.
+async fn read_until_headers_end(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<usize> {
+    loop {
+        if let Some(index) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            return Some(index + 4);
+        }
+        let mut chunk = [0u8; 4096];
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+fn request() -> ProviderRequest {
+    ProviderRequest {
+        messages: vec![ModelMessage::user("hello")].into(),
+        settings: GenerationSettings::default(),
+        tools: None,
+        response_format: None,
+        api_key_override: None,
+        headers: reqwest::header::HeaderMap::new(),
+        metadata: std::collections::HashMap::new(),
+        payload_callback: None,
+        session_id: None,
+        transport: None,
+        tool_call_ids: None,
+    }
+}
+
+#[tokio::test]
+async fn providers_share_pooled_connections() {
+    let (url, counts) = start_server().await;
+    let config = RociConfig::new();
+    config.set_api_key("openai", "test-key".to_string());
+    config.set_base_url("openai", url);
+    let mut registry = ProviderRegistry::new();
+    roci_providers::register_default_providers(&mut registry);
+
+    // Two runs, each building its own provider and calling it twice.
+    for _ in 0..2 {
+        let provider = registry
+            .create_provider("openai", "local-model", &config)
+            .expect("create provider");
+        for _ in 0..2 {
+            let response = provider.generate_text(&request()).await.expect("response");
+            assert_eq!(response.text, "hi");
+        }
+    }
+
+    assert_eq!(counts.requests.load(Ordering::SeqCst), 4);
+    assert_eq!(counts.connections.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,179 @@
+//! Device-code logins deferred by one process and finished by another.
+//!
+//! Drives the Codex device-code endpoints on a mock server through
+//! `AuthService::defer_device_code` and `AuthService::resume_device_code`,
+//! with a fresh service per step standing in for separate CLI invocations.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use roci_core::auth::{
+    AuthBackend, AuthError, AuthPollResult, AuthService, AuthStep, DeviceCodeSession,
+    FileTokenStore, PendingLoginStore, Token, TokenStore, TokenStoreConfig,
+};
+use roci_providers::auth::openai_codex::OpenAiCodexAuth;
+
+/// Codex device-code login against a local issuer.
+struct LocalCodexBackend {
+    issuer: String,
+}
+
+impl LocalCodexBackend {
+    fn auth(&self, store: &Arc<dyn TokenStore>) -> OpenAiCodexAuth {
+        OpenAiCodexAuth::new(store.clone()).with_issuer(self.issuer.clone())
+    }
+}
+
+#[async_trait]
+impl AuthBackend for LocalCodexBackend {
+    fn aliases(&self) -> &[&str] {
+        &["codex", "openai-codex"]
+    }
+
+    fn display_name(&self) -> &str {
+        "Codex"
+    }
+
+    fn store_key(&self) -> &str {
+        "openai-codex"
+    }
+
+    async fn start_login(&self, store: &Arc<dyn TokenStore>) -> Result<AuthStep, AuthError> {
+        let session = self.auth(store).start_device_code().await?;
+        Ok(AuthStep::DeviceCode {
+            verification_url: session.verification_url.clone(),
+            user_code: session.user_code.clone(),
+            interval: std::time::Duration::from_secs(session.interval_secs),
+            expires_at: session.expires_at,
+            session,
+        })
+    }
+
+    async fn poll_device_code(
+        &self,
+        store: &Arc<dyn TokenStore>,
+        session: &DeviceCodeSession,
+    ) -> Result<AuthPollResult, AuthError> {
+        self.auth(store).poll_device_code(session).await
+    }
+
+    async fn complete_pkce(
+        &self,
+        _store: &Arc<dyn TokenStore>,
+        _code: &str,
+        _state: &str,
+    ) -> Result<Token, AuthError> {
+        Err(AuthError::Unsupported("device-code only".into()))
+    }
+
+    fn get_status(&self, store: &Arc<dyn TokenStore>) -> Result<Option<Token>, AuthError> {
+        store.load(self.store_key(), "default")
+    }
+
+    fn logout(&self, store: &Arc<dyn TokenStore>) -> Result<(), AuthError> {
+        store.clear(self.store_key(), "default")
+    }
+}
+
+fn service(dir: &TempDir, issuer: &str) -> AuthService {
+    let store = FileTokenStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+    let mut svc = AuthService::new(Arc::new(store));
+    svc.register_backend(Arc::new(LocalCodexBackend {
+        issuer: issuer.to_string(),
+    }));
+    svc
+}
+
+async fn mock_issuer() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/accounts/deviceauth/usercode"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "device_auth_id": "device-1",
+            "user_code": "ABCD-1234",
+            "interval": "1",
+        })))
+        .mount(&server)
+        .await;
+    // The first poll finds the code not yet approved.
+    Mock::given(method("POST"))
+        .and(path("/api/accounts/deviceauth/token"))
+        .respond_with(ResponseTemplate::new(403))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/accounts/deviceauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "authorization_code": "auth-code",
+            "code_verifier": "verifier",
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id_token": "id",
+            "access_token": "codex-access",
+            "refresh_token": "codex-refresh",
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn deferred_device_code_login_completes_in_a_later_service() {
+    let server = mock_issuer().await;
+    let dir = TempDir::new().unwrap();
+    let pending = PendingLoginStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+
+    // `auth login codex --no-poll`
+    let login = service(&dir, &server.uri());
+    let AuthStep::DeviceCode { session, .. } = login.start_login("codex").await.unwrap() else {
+        panic!("expected a device-code step");
+    };
+    assert_eq!(session.user_code, "ABCD-1234");
+    login
+        .defer_device_code(&pending, "codex", &session)
+        .unwrap();
+
+    // A mistyped code is rejected without polling or dropping the session.
+    let later = service(&dir, &server.uri());
+    let mismatch = later
+        .resume_device_code(&pending, "codex", Some("WXYZ-0000"))
+        .await;
+    assert!(matches!(mismatch, Err(AuthError::InvalidResponse(_))));
+
+    // Not yet approved: the session stays pending.
+    let result = later
+        .resume_device_code(&pending, "codex", Some("abcd-1234"))
+        .await
+        .unwrap();
+    assert!(matches!(result, Some(AuthPollResult::Pending)));
+    assert!(pending.load("openai-codex").unwrap().is_some());
+
+    // Approved: the token is stored and the session removed.
+    let result = service(&dir, &server.uri())
+        .resume_device_code(&pending, "openai-codex", None)
+        .await
+        .unwrap();
+    match result {
+        Some(AuthPollResult::Authorized { token }) => {
+            assert_eq!(token.access_token, "codex-access");
+        }
+        other => panic!("expected Authorized, got {other:?}"),
+    }
+    let stored = later.get_status("codex").unwrap().expect("stored token");
+    assert_eq!(stored.access_token, "codex-access");
+    assert!(pending.load("openai-codex").unwrap().is_none());
+    assert!(later
+        .resume_device_code(&pending, "codex", None)
+        .await
+        .unwrap()
+        .is_none());
+}
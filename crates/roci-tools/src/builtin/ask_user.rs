@@ -5,7 +5,8 @@ use std::sync::Arc;
 use roci::error::RociError;
 use roci::tools::arguments::ToolArguments;
 use roci::tools::tool::{
-    AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+    AgentTool, Tool, ToolEffects, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan,
+    ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
 use roci::tools::{
@@ -26,7 +27,8 @@ pub fn ask_user_tool() -> Arc<dyn Tool> {
             execute_ask_user(args, ctx).await
         },
     )
-    .with_static_safety(ToolSafetyPlan::host_input(), ask_user_safety_summary()))
+    .with_static_safety(ToolSafetyPlan::host_input(), ask_user_safety_summary())
+    .with_effects(ToolEffects::ReadOnly))
 }
 
 fn ask_user_safety_summary() -> ToolSafetySummary {
@@ -136,10 +138,11 @@ async fn execute_ask_user(
     ctx: ToolExecutionContext,
 ) -> Result<serde_json::Value, RociError> {
     let prompt = parse_prompt(args.raw())?;
-    let timeout_ms = args
-        .raw()
-        .get("timeout_ms")
-        .and_then(serde_json::Value::as_u64);
+    let timeout_ms = if args.has("timeout_ms") {
+        Some(args.get_u64("timeout_ms")?)
+    } else {
+        None
+    };
 
     let request = UserInputRequest {
         request_id: Uuid::new_v4(),
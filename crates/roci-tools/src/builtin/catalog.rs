@@ -17,6 +17,9 @@ pub fn tool_catalog() -> ToolCatalog {
     catalog
         .insert_first_wins(super::write_file_tool(), ToolOrigin::Builtin)
         .expect("builtin write_file tool catalog entry must be valid");
+    catalog
+        .insert_first_wins(super::diff_files_tool(), ToolOrigin::Builtin)
+        .expect("builtin diff_files tool catalog entry must be valid");
     catalog
         .insert_first_wins(super::list_directory_tool(), ToolOrigin::Builtin)
         .expect("builtin list_directory tool catalog entry must be valid");
@@ -27,4 +30,7 @@ pub fn tool_catalog() -> ToolCatalog {
         .insert_first_wins(super::ask_user_tool(), ToolOrigin::Builtin)
         .expect("builtin ask_user tool catalog entry must be valid");
     catalog
+        .insert_first_wins(super::update_plan_tool(), ToolOrigin::Builtin)
+        .expect("builtin update_plan tool catalog entry must be valid");
+    catalog
 }
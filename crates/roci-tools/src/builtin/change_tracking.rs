@@ -0,0 +1,187 @@
+//! Feeding the run's [`ChangeLog`] from builtin tools.
+//!
+//! `write_file` knows exactly what it changed. `shell` does not, so it
+//! snapshots the relative paths named in the command before and after
+//! running it and records any difference as unverified.
+
+use std::path::{Component, Path, PathBuf};
+
+use roci::tools::ChangeLog;
+
+use super::common::{SHELL_TRACKED_FILE_MAX_BYTES, SHELL_TRACKED_PATHS_MAX};
+
+/// `path` as recorded in the change log: relative to `root` when it lies
+/// under it, with `.` components dropped and `/` separators.
+pub(super) fn change_path(root: Option<&Path>, path: &Path) -> String {
+    let relative = root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path);
+    let parts = relative
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    if relative.has_root() {
+        format!("/{}", parts[1..].join("/"))
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Relative paths that appear as arguments or redirection targets in
+/// `command`.
+///
+/// Purely lexical: flags, absolute paths, parent traversal, and words with
+/// shell expansions are skipped, so paths produced by globs, variables, or
+/// scripts go unnoticed.
+pub(super) fn command_paths(command: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let words = command
+        .split(|ch: char| ch.is_ascii_whitespace() || matches!(ch, ';' | '&' | '|' | '(' | ')'));
+    for word in words {
+        // `>out.txt`, `2>>err.log`, `<in.txt`
+        let word = match word.rfind(['>', '<']) {
+            Some(index) => &word[index + 1..],
+            None => word,
+        };
+        let word = word.trim_matches(['\'', '"']);
+        if word.is_empty()
+            || word.starts_with('-')
+            || word.contains(['$', '`', '*', '?', '[', ']', '{', '}', '~', '=', '\\'])
+        {
+            continue;
+        }
+        let path = Path::new(word);
+        if path.is_absolute()
+            || path
+                .components()
+                .any(|component| matches!(component, Component::ParentDir))
+        {
+            continue;
+        }
+        if !paths.iter().any(|seen| seen == path) {
+            paths.push(path.to_path_buf());
+        }
+        if paths.len() == SHELL_TRACKED_PATHS_MAX {
+            break;
+        }
+    }
+    paths
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum FileState {
+    Missing,
+    Content(Vec<u8>),
+    /// Directory, oversized, or unreadable; not compared.
+    Untracked,
+}
+
+impl FileState {
+    async fn read(path: &Path) -> Self {
+        match tokio::fs::metadata(path).await {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::Missing,
+            Ok(metadata)
+                if metadata.is_file() && metadata.len() <= SHELL_TRACKED_FILE_MAX_BYTES =>
+            {
+                match tokio::fs::read(path).await {
+                    Ok(content) => Self::Content(content),
+                    Err(_) => Self::Untracked,
+                }
+            }
+            _ => Self::Untracked,
+        }
+    }
+
+    fn content(&self) -> Option<&[u8]> {
+        match self {
+            Self::Content(content) => Some(content),
+            Self::Missing | Self::Untracked => None,
+        }
+    }
+}
+
+/// State of the files a shell command names, taken before it runs.
+pub(super) struct ShellSnapshot {
+    files: Vec<(String, PathBuf, FileState)>,
+}
+
+impl ShellSnapshot {
+    /// Snapshot `(change-log path, host path)` pairs.
+    pub(super) async fn capture(paths: Vec<(String, PathBuf)>) -> Self {
+        let mut files = Vec::with_capacity(paths.len());
+        for (path, host_path) in paths {
+            let state = FileState::read(&host_path).await;
+            files.push((path, host_path, state));
+        }
+        Self { files }
+    }
+
+    /// Record every snapshotted file whose state changed since capture.
+    pub(super) async fn record_changes(self, changes: &ChangeLog) {
+        for (path, host_path, before) in self.files {
+            let after = FileState::read(&host_path).await;
+            if before == FileState::Untracked || after == FileState::Untracked || before == after {
+                continue;
+            }
+            changes.record_unverified("shell", &path, before.content(), after.content());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_paths_keeps_relative_arguments_and_redirect_targets() {
+        let paths = command_paths(
+            "rm -f ./old.txt && echo done >out.log; cat \"notes/a b\" 2>>err.log | tee copy.txt",
+        );
+
+        let paths = paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert!(paths.contains(&"./old.txt".to_string()));
+        assert!(paths.contains(&"out.log".to_string()));
+        assert!(paths.contains(&"err.log".to_string()));
+        assert!(paths.contains(&"copy.txt".to_string()));
+        assert!(!paths.iter().any(|path| path.starts_with('-')));
+    }
+
+    #[test]
+    fn command_paths_skips_absolute_traversal_and_expansions() {
+        let paths = command_paths("cp /etc/hosts ../up.txt $HOME/x *.rs ~/y FOO=bar");
+
+        assert!(
+            paths.iter().all(|path| path == Path::new("cp")),
+            "{paths:?}"
+        );
+    }
+
+    #[test]
+    fn command_paths_is_capped() {
+        let command = (0..100)
+            .map(|index| format!("f{index}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        assert_eq!(command_paths(&command).len(), SHELL_TRACKED_PATHS_MAX);
+    }
+
+    #[test]
+    fn change_path_is_root_relative_and_normalized() {
+        let root = Path::new("/work");
+
+        assert_eq!(
+            change_path(Some(root), Path::new("/work/src/./lib.rs")),
+            "src/lib.rs"
+        );
+        assert_eq!(change_path(None, Path::new("./out.log")), "out.log");
+        assert_eq!(
+            change_path(Some(root), Path::new("/elsewhere/a")),
+            "/elsewhere/a"
+        );
+    }
+}
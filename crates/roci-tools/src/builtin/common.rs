@@ -7,12 +7,28 @@ use roci::security::filesystem::{
     FilesystemPolicy, PathAccessRequest, PathBoundary, PathOperation, PathResolutionMode,
     SymlinkPolicy,
 };
+use roci::tools::scratch_relative;
 use roci::tools::tool::ToolExecutionContext;
 
 pub(super) const SHELL_OUTPUT_MAX_BYTES: usize = 32_768;
 pub(super) const READ_FILE_MAX_BYTES: usize = 65_536;
+pub(super) const READ_FILE_DEFAULT_LINE_LIMIT: usize = 2_000;
+pub(super) const READ_FILE_MAX_LINE_BYTES: usize = 2_000;
+pub(super) const READ_FILE_CHUNK_BYTES: usize = 65_536;
+pub(super) const TEXT_SNIFF_BYTES: usize = 8_192;
+pub(super) const READ_FILE_COUNT_LINES_MAX_BYTES: u64 = 16 * 1024 * 1024;
+pub(super) const DIFF_FILES_MAX_INPUT_BYTES: u64 = 4 * 1024 * 1024;
+pub(super) const DIFF_FILES_OUTPUT_MAX_BYTES: usize = 32_768;
 pub(super) const GREP_OUTPUT_MAX_BYTES: usize = 32_768;
+pub(super) const GREP_FILES_PER_INVOCATION: usize = 256;
+pub(super) const GREP_FILE_NOTES_MAX: usize = 50;
 pub(super) const SHELL_TIMEOUT: Duration = Duration::from_secs(30);
+pub(super) const SHELL_TRACKED_PATHS_MAX: usize = 32;
+pub(super) const SHELL_TRACKED_FILE_MAX_BYTES: u64 = 1024 * 1024;
+pub(super) const FETCH_URL_MAX_BYTES: usize = 2 * 1024 * 1024;
+pub(super) const FETCH_URL_OUTPUT_MAX_BYTES: usize = 49_152;
+pub(super) const FETCH_URL_MAX_REDIRECTS: usize = 10;
+pub(super) const FETCH_URL_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub(super) fn truncate_utf8(s: &str, max_bytes: usize) -> String {
     if s.len() <= max_bytes {
@@ -26,6 +42,18 @@ pub(super) fn truncate_utf8(s: &str, max_bytes: usize) -> String {
     s[..cutoff].to_string()
 }
 
+/// Read up to [`TEXT_SNIFF_BYTES`] from the start of a host file.
+pub(super) async fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt as _;
+
+    let file = tokio::fs::File::open(path).await?;
+    let mut head = Vec::with_capacity(TEXT_SNIFF_BYTES);
+    file.take(TEXT_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await?;
+    Ok(head)
+}
+
 pub(super) fn resolve_session_path(
     ctx: &ToolExecutionContext,
     raw_path: &str,
@@ -40,15 +68,35 @@ pub(super) fn resolve_session_path(
     Ok(Some(path))
 }
 
+/// Resolve `raw_path` to a host path for a file tool.
+///
+/// `scratch://` paths and absolute paths inside the run's scratch directory
+/// resolve there, writable regardless of the workspace root. Other paths
+/// resolve inside the workspace root; `None` means no workspace is set.
 pub(super) fn resolve_workspace_path(
     ctx: &ToolExecutionContext,
     raw_path: &str,
     operation: PathOperation,
 ) -> Result<Option<PathBuf>, RociError> {
+    if let Some(relative) = scratch_relative(raw_path) {
+        let scratch = ctx
+            .scratch
+            .as_ref()
+            .ok_or_else(|| RociError::ToolExecution {
+                tool_name: ctx.tool_name.clone().unwrap_or_else(|| "tool".to_string()),
+                message: format!("{raw_path}: scratch paths require an agent run"),
+            })?;
+        return scratch.resolve(relative).map(Some);
+    }
+    let requested = Path::new(raw_path);
+    if let Some(scratch) = ctx.scratch.as_ref() {
+        if requested.is_absolute() && scratch.contains(requested) {
+            return Ok(Some(requested.to_path_buf()));
+        }
+    }
     let Some(root) = ctx.workspace_root.as_ref() else {
         return Ok(None);
     };
-    let requested = Path::new(raw_path);
     if requested.is_absolute() {
         return Err(workspace_path_error(ctx, "absolute paths are not allowed"));
     }
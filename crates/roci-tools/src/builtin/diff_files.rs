@@ -0,0 +1,214 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use roci::error::RociError;
+use roci::security::filesystem::PathOperation;
+use roci::tools::arguments::ToolArguments;
+use roci::tools::changes::content_hash;
+use roci::tools::diff::{unified_diff, DEFAULT_DIFF_CONTEXT};
+use roci::tools::tool::{
+    AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+};
+use roci::tools::types::AgentToolParameters;
+
+use super::common::{
+    resolve_session_path, resolve_workspace_path, DIFF_FILES_MAX_INPUT_BYTES,
+    DIFF_FILES_OUTPUT_MAX_BYTES, TEXT_SNIFF_BYTES,
+};
+use super::encoding::EncodingFallback;
+
+/// Create the `diff_files` tool — compares a file with another file or with
+/// provided content.
+///
+/// `path` is the old side; the new side is either `other_path` or `content`,
+/// never both. Returns a unified diff with `context_lines` of context
+/// (default 3) plus added/removed line counts, capped at 32 KB. Files that
+/// look binary are compared by size and SHA-256 instead. Paths resolve
+/// through the same workspace and session sandbox as `read_file`, and files
+/// over 4 MB are refused.
+pub fn diff_files_tool() -> Arc<dyn Tool> {
+    let encodings = EncodingFallback::default();
+    let tool = AgentTool::new(
+        "diff_files",
+        "Compare a file against another file or against provided content as a unified diff",
+        AgentToolParameters::object()
+            .string("path", "File to diff from (the old side)", true)
+            .string(
+                "other_path",
+                "File to diff against (the new side); omit when passing content",
+                false,
+            )
+            .string(
+                "content",
+                "Proposed content to diff against; omit when passing other_path",
+                false,
+            )
+            .number(
+                "context_lines",
+                "Unchanged lines shown around each change (defaults to 3)",
+                false,
+            )
+            .build(),
+        move |args_val, ctx: ToolExecutionContext| {
+            let encodings = encodings.clone();
+            async move {
+                let path = args_val.get_str("path")?;
+                let context = context_lines(&args_val)?;
+                let old = read_side(&ctx, path).await?;
+                let new = match (
+                    args_val.get_str_opt("other_path"),
+                    args_val.get_str_opt("content"),
+                ) {
+                    (Some(other_path), None) => read_side(&ctx, other_path).await?,
+                    (None, Some(content)) => Side {
+                        label: "proposed".to_string(),
+                        bytes: content.as_bytes().to_vec(),
+                    },
+                    _ => {
+                        return Err(RociError::InvalidArgument(
+                            "pass exactly one of other_path or content".to_string(),
+                        ))
+                    }
+                };
+                Ok(diff_result(&old, &new, context, &encodings))
+            }
+        },
+    );
+    Arc::new(tool.with_safety(diff_files_safety_summary(), diff_files_safety))
+}
+
+/// One side of the comparison: a display label and its raw bytes.
+struct Side {
+    label: String,
+    bytes: Vec<u8>,
+}
+
+async fn read_side(ctx: &ToolExecutionContext, path: &str) -> Result<Side, RociError> {
+    if let Some(workspace_path) = resolve_workspace_path(ctx, path, PathOperation::Read)? {
+        let label = workspace_path.display().to_string();
+        return read_host_side(&workspace_path, label).await;
+    }
+
+    if let (Some(session_fs), Some(logical_path)) =
+        (ctx.session_fs.as_ref(), resolve_session_path(ctx, path)?)
+    {
+        let label = logical_path.to_string();
+        let bytes = session_fs
+            .read(&logical_path)
+            .map_err(|e| diff_files_error(&label, e))?;
+        check_size(&label, bytes.len() as u64)?;
+        return Ok(Side { label, bytes });
+    }
+
+    read_host_side(Path::new(path), path.to_string()).await
+}
+
+async fn read_host_side(path: &Path, label: String) -> Result<Side, RociError> {
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| diff_files_error(&label, e))?
+        .len();
+    check_size(&label, size)?;
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| diff_files_error(&label, e))?;
+    Ok(Side { label, bytes })
+}
+
+fn check_size(label: &str, size: u64) -> Result<(), RociError> {
+    if size > DIFF_FILES_MAX_INPUT_BYTES {
+        return Err(diff_files_error(
+            label,
+            format!("file is {size} bytes; diff_files compares files up to {DIFF_FILES_MAX_INPUT_BYTES} bytes"),
+        ));
+    }
+    Ok(())
+}
+
+fn diff_result(
+    old: &Side,
+    new: &Side,
+    context: usize,
+    encodings: &EncodingFallback,
+) -> serde_json::Value {
+    let (Some(old_text), Some(new_text)) = (
+        decode_text(&old.bytes, encodings),
+        decode_text(&new.bytes, encodings),
+    ) else {
+        let old_hash = content_hash(&old.bytes);
+        let new_hash = content_hash(&new.bytes);
+        return serde_json::json!({
+            "path": old.label,
+            "other": new.label,
+            "binary": true,
+            "identical": old_hash == new_hash,
+            "old_size": old.bytes.len(),
+            "new_size": new.bytes.len(),
+            "old_hash": old_hash,
+            "new_hash": new_hash,
+        });
+    };
+
+    let diff = unified_diff(
+        &old_text,
+        &new_text,
+        &old.label,
+        &new.label,
+        context,
+        DIFF_FILES_OUTPUT_MAX_BYTES,
+    );
+    serde_json::json!({
+        "path": old.label,
+        "other": new.label,
+        "identical": diff.is_empty(),
+        "diff": diff.text,
+        "lines_added": diff.lines_added,
+        "lines_removed": diff.lines_removed,
+        "truncated": diff.truncated,
+    })
+}
+
+/// Decoded text, or `None` when the bytes look binary.
+fn decode_text(bytes: &[u8], encodings: &EncodingFallback) -> Option<String> {
+    if bytes.is_empty() {
+        return Some(String::new());
+    }
+    encodings
+        .detect(&bytes[..bytes.len().min(TEXT_SNIFF_BYTES)])
+        .map(|encoding| encoding.decode(bytes))
+}
+
+fn context_lines(args: &ToolArguments) -> Result<usize, RociError> {
+    let lines = args.get_u64_or("context_lines", DEFAULT_DIFF_CONTEXT as u64)?;
+    Ok(usize::try_from(lines).unwrap_or(usize::MAX))
+}
+
+fn diff_files_error(label: &str, err: impl std::fmt::Display) -> RociError {
+    RociError::ToolExecution {
+        tool_name: "diff_files".into(),
+        message: format!("{label}: {err}"),
+    }
+}
+
+fn diff_files_safety(args: &ToolArguments) -> ToolSafetyPlan {
+    match args.get_str("path") {
+        Ok(path) => {
+            let mut plan = ToolSafetyPlan::file_read(path);
+            if let Some(other_path) = args.get_str_opt("other_path") {
+                plan.filesystem
+                    .extend(ToolSafetyPlan::file_read(other_path).filesystem);
+            }
+            plan
+        }
+        Err(_) => ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read),
+    }
+}
+
+fn diff_files_safety_summary() -> ToolSafetySummary {
+    ToolSafetySummary {
+        read_only_by_default: true,
+        destructive_by_default: false,
+        concurrency_safe_by_default: true,
+        approval_kind: ToolSafetyKind::Read,
+    }
+}
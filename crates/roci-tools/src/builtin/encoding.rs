@@ -0,0 +1,266 @@
+//! Text encoding detection and conversion for the file tools.
+//!
+//! Files are sniffed for a byte-order mark first. Without one, the
+//! [`EncodingFallback`] chain is tried in order and the first encoding that
+//! accepts the sniffed bytes wins. Everything is converted to UTF-8 before it
+//! reaches the model.
+
+use std::borrow::Cow;
+
+use roci::error::RociError;
+
+use super::common::TEXT_SNIFF_BYTES;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Text encodings the file tools can read and write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 with a leading byte-order mark.
+    Utf8Bom,
+    /// UTF-16 little endian; written with a byte-order mark.
+    Utf16Le,
+    /// UTF-16 big endian; written with a byte-order mark.
+    Utf16Be,
+    /// ISO-8859-1; every byte maps to the code point of the same value.
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Label reported in tool results and accepted by `write_file`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf8Bom => "utf-8-bom",
+            Self::Utf16Le => "utf-16le",
+            Self::Utf16Be => "utf-16be",
+            Self::Latin1 => "latin-1",
+        }
+    }
+
+    /// Parse a label such as `utf-8`, `utf-16le`, or `latin-1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::InvalidArgument`] for unknown labels.
+    pub fn parse(label: &str) -> Result<Self, RociError> {
+        match label.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(Self::Utf8),
+            "utf-8-bom" | "utf8-bom" | "utf-8-sig" => Ok(Self::Utf8Bom),
+            "utf-16le" | "utf16le" | "utf-16" | "utf16" => Ok(Self::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Self::Utf16Be),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(Self::Latin1),
+            other => Err(RociError::InvalidArgument(format!(
+                "unsupported encoding '{other}'; expected one of utf-8, utf-8-bom, utf-16le, utf-16be, latin-1"
+            ))),
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Self::Utf8Bom => UTF8_BOM,
+            Self::Utf16Le => UTF16LE_BOM,
+            Self::Utf16Be => UTF16BE_BOM,
+            Self::Utf8 | Self::Latin1 => &[],
+        }
+    }
+
+    fn from_bom(head: &[u8]) -> Option<Self> {
+        if head.starts_with(UTF8_BOM) {
+            Some(Self::Utf8Bom)
+        } else if head.starts_with(UTF16LE_BOM) {
+            Some(Self::Utf16Le)
+        } else if head.starts_with(UTF16BE_BOM) {
+            Some(Self::Utf16Be)
+        } else {
+            None
+        }
+    }
+
+    /// Whether `head` (the first bytes of a file without a BOM) looks like
+    /// text in this encoding. `head` may end mid-character.
+    fn accepts(self, head: &[u8]) -> bool {
+        match self {
+            Self::Utf8 | Self::Utf8Bom => {
+                !head.contains(&0)
+                    && match std::str::from_utf8(head) {
+                        Ok(_) => true,
+                        // A sequence cut at the sniff limit is still valid;
+                        // one cut at the end of a shorter file is not.
+                        Err(err) => err.error_len().is_none() && head.len() >= TEXT_SNIFF_BYTES - 3,
+                    }
+            }
+            Self::Utf16Le => looks_like_utf16(head, u16::from_le_bytes),
+            Self::Utf16Be => looks_like_utf16(head, u16::from_be_bytes),
+            Self::Latin1 => !head.contains(&0),
+        }
+    }
+
+    /// Decode a whole file into UTF-8 text.
+    pub(super) fn decode(self, bytes: &[u8]) -> String {
+        let mut decoder = TextDecoder::new(self);
+        let mut text = decoder.decode(bytes).into_owned();
+        text.extend_from_slice(&decoder.finish());
+        String::from_utf8(text)
+            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+    }
+
+    /// Encode `text` in this encoding, including its byte-order mark.
+    pub(super) fn encode(self, text: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = self.bom().to_vec();
+        match self {
+            Self::Utf8 | Self::Utf8Bom => bytes.extend_from_slice(text.as_bytes()),
+            Self::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Self::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+            Self::Latin1 => {
+                for ch in text.chars() {
+                    let byte = u8::try_from(u32::from(ch))
+                        .map_err(|_| format!("character {ch:?} cannot be encoded as latin-1"))?;
+                    bytes.push(byte);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// BOM-less UTF-16 text is mostly ASCII: one zero byte per code unit and no
+/// NUL code units.
+fn looks_like_utf16(head: &[u8], unit: fn([u8; 2]) -> u16) -> bool {
+    let units = head
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+    if units.is_empty() || units.contains(&0) {
+        return false;
+    }
+    let ascii = units.iter().filter(|unit| **unit < 0x80).count();
+    if ascii * 4 < units.len() * 3 {
+        return false;
+    }
+    let complete = match units.last() {
+        Some(last) if (0xD800..0xDC00).contains(last) => &units[..units.len() - 1],
+        _ => &units[..],
+    };
+    char::decode_utf16(complete.iter().copied()).all(|decoded| decoded.is_ok())
+}
+
+/// Ordered encodings to try for files without a byte-order mark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingFallback {
+    chain: Vec<TextEncoding>,
+}
+
+impl EncodingFallback {
+    /// Try `chain` in order. UTF-16 entries only match BOM-less input that is
+    /// mostly ASCII; `Latin1` accepts any input without NUL bytes, so it
+    /// belongs last.
+    pub fn new(chain: Vec<TextEncoding>) -> Self {
+        Self { chain }
+    }
+
+    /// Encodings tried in order.
+    pub fn chain(&self) -> &[TextEncoding] {
+        &self.chain
+    }
+
+    /// Detect the encoding of a file from its first bytes, or `None` when
+    /// the bytes look binary or match nothing in the chain.
+    ///
+    /// `head` is the first 8 KiB of the file, or the whole
+    /// file when it is shorter.
+    pub fn detect(&self, head: &[u8]) -> Option<TextEncoding> {
+        TextEncoding::from_bom(head).or_else(|| {
+            self.chain
+                .iter()
+                .copied()
+                .find(|encoding| encoding.accepts(head))
+        })
+    }
+}
+
+impl Default for EncodingFallback {
+    /// UTF-8, then BOM-less UTF-16, then Latin-1.
+    fn default() -> Self {
+        Self::new(vec![
+            TextEncoding::Utf8,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+            TextEncoding::Latin1,
+        ])
+    }
+}
+
+/// Incremental decoder from a detected encoding to UTF-8 bytes.
+///
+/// Strips the byte-order mark and carries partial UTF-16 code units across
+/// chunk boundaries. UTF-8 input passes through unchanged.
+pub(super) struct TextDecoder {
+    encoding: TextEncoding,
+    at_start: bool,
+    pending: Vec<u8>,
+}
+
+impl TextDecoder {
+    pub(super) fn new(encoding: TextEncoding) -> Self {
+        Self {
+            encoding,
+            at_start: true,
+            pending: Vec::new(),
+        }
+    }
+
+    pub(super) fn decode<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut chunk = chunk;
+        if self.at_start && !chunk.is_empty() {
+            self.at_start = false;
+            chunk = chunk.strip_prefix(self.encoding.bom()).unwrap_or(chunk);
+        }
+        match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => Cow::Borrowed(chunk),
+            TextEncoding::Latin1 => Cow::Owned(
+                chunk
+                    .iter()
+                    .map(|byte| char::from(*byte))
+                    .collect::<String>()
+                    .into_bytes(),
+            ),
+            TextEncoding::Utf16Le => Cow::Owned(self.decode_utf16(chunk, u16::from_le_bytes)),
+            TextEncoding::Utf16Be => Cow::Owned(self.decode_utf16(chunk, u16::from_be_bytes)),
+        }
+    }
+
+    /// Flush input left over from an incomplete final code unit.
+    pub(super) fn finish(self) -> Vec<u8> {
+        if self.pending.is_empty() {
+            Vec::new()
+        } else {
+            char::REPLACEMENT_CHARACTER.to_string().into_bytes()
+        }
+    }
+
+    fn decode_utf16(&mut self, chunk: &[u8], unit: fn([u8; 2]) -> u16) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        let even = self.pending.len() & !1;
+        let mut units = self.pending[..even]
+            .chunks_exact(2)
+            .map(|pair| unit([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        // Keep a trailing high surrogate until its pair arrives.
+        let keep_from = match units.last() {
+            Some(last) if (0xD800..0xDC00).contains(last) => {
+                units.pop();
+                even - 2
+            }
+            _ => even,
+        };
+        self.pending.drain(..keep_from);
+        char::decode_utf16(units)
+            .map(|decoded| decoded.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect::<String>()
+            .into_bytes()
+    }
+}
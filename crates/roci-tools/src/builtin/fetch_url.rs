@@ -0,0 +1,905 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::Url;
+use roci::error::RociError;
+use roci::provider::http::HttpOptions;
+use roci::resource::FetchUrlSettings;
+use roci::tools::arguments::ToolArguments;
+use roci::tools::tool::{
+    AgentTool, Tool, ToolApprovalRequirement, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan,
+    ToolSafetySummary,
+};
+use roci::tools::types::AgentToolParameters;
+use scraper::{ElementRef, Html, Node, Selector};
+
+use super::common::{
+    truncate_utf8, FETCH_URL_MAX_BYTES, FETCH_URL_MAX_REDIRECTS, FETCH_URL_OUTPUT_MAX_BYTES,
+    FETCH_URL_TIMEOUT,
+};
+
+/// Elements dropped from converted pages: scripts, styling, and site chrome.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "nav", "footer", "form", "button", "select",
+    "iframe", "svg", "canvas",
+];
+
+/// Settings for the `fetch_url` tool.
+///
+/// Host entries match the host itself and its subdomains. An empty
+/// allowlist allows every host not on the denylist; the denylist always wins.
+/// The host policy and `robots.txt` are checked again on every redirect hop.
+/// Proxy, user agent, and timeout come from the shared [`HttpOptions`]; only
+/// the redirect handling and the size cap are specific to this tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchUrlOptions {
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    max_bytes: usize,
+    http: HttpOptions,
+    respect_robots_txt: bool,
+}
+
+impl Default for FetchUrlOptions {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            max_bytes: FETCH_URL_MAX_BYTES,
+            http: HttpOptions::new()
+                .with_user_agent(format!("roci/{}", env!("CARGO_PKG_VERSION")))
+                .with_timeout(FETCH_URL_TIMEOUT),
+            respect_robots_txt: true,
+        }
+    }
+}
+
+impl FetchUrlOptions {
+    /// Default options fenced by the `fetch_url` resource settings.
+    pub fn from_settings(settings: &FetchUrlSettings) -> Self {
+        Self::default()
+            .with_allowed_hosts(settings.allowed_hosts.clone())
+            .with_denied_hosts(settings.denied_hosts.clone())
+    }
+
+    /// Only fetch these hosts (and their subdomains).
+    pub fn with_allowed_hosts(
+        mut self,
+        hosts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.allowed_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Never fetch these hosts (or their subdomains).
+    pub fn with_denied_hosts(mut self, hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.denied_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Refuse responses larger than `max_bytes` (default 2 MiB).
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Use the host's shared HTTP settings. Without a user agent in
+    /// `http`, requests identify as `roci/<version>`.
+    pub fn with_http_options(mut self, http: HttpOptions) -> Self {
+        self.http = match http.user_agent() {
+            Some(_) => http,
+            None => http.with_user_agent(self.user_agent().to_string()),
+        };
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http = self.http.with_user_agent(user_agent);
+        self
+    }
+
+    /// Route requests through `proxy`. Without one, the standard
+    /// `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` environment variables apply.
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.http = self.http.with_proxy(proxy);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.with_timeout(timeout);
+        self
+    }
+
+    /// Whether to refuse paths disallowed by the site's `robots.txt`
+    /// (default `true`). A missing or unreadable `robots.txt` allows all.
+    pub fn with_robots_txt(mut self, respect: bool) -> Self {
+        self.respect_robots_txt = respect;
+        self
+    }
+
+    fn user_agent(&self) -> &str {
+        self.http.user_agent().unwrap_or("roci")
+    }
+
+    /// The shared client settings, with redirects left to [`fetch_url`] so
+    /// every hop is checked.
+    fn client(&self) -> Result<reqwest::Client, RociError> {
+        self.http
+            .client_builder()?
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| RociError::Configuration(format!("failed to build fetch_url client: {e}")))
+    }
+
+    fn check_url(&self, url: &Url) -> Result<(), RociError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(RociError::InvalidArgument(format!(
+                "unsupported URL scheme '{}'; fetch_url only fetches http and https URLs",
+                url.scheme()
+            )));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| RociError::InvalidArgument(format!("URL '{url}' has no host")))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        if self
+            .denied_hosts
+            .iter()
+            .any(|pattern| host_matches(&host, pattern))
+        {
+            return Err(fetch_error(format!(
+                "{url}: host '{host}' is denied by the fetch_url host policy"
+            )));
+        }
+        if !self.allowed_hosts.is_empty()
+            && !self
+                .allowed_hosts
+                .iter()
+                .any(|pattern| host_matches(&host, pattern))
+        {
+            return Err(fetch_error(format!(
+                "{url}: host '{host}' is not in the fetch_url allowlist"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn host_matches(host: &str, pattern: &str) -> bool {
+    let pattern = pattern
+        .trim()
+        .trim_start_matches("*.")
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+    !pattern.is_empty()
+        && (host == pattern
+            || host
+                .strip_suffix(pattern.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+/// Create the `fetch_url` tool — fetches a web page as markdown.
+///
+/// HTML is converted to markdown with scripts, styles, and navigation
+/// removed; an optional CSS `selector` narrows the page to matching
+/// elements. Plain text and JSON bodies are returned as-is and other content
+/// types are refused. Returns the page title, the final URL after redirects,
+/// and the content, capped at 48 KB. Not part of [`super::all_tools`];
+/// embedders opt in and fence it with [`FetchUrlOptions`].
+pub fn fetch_url_tool() -> Arc<dyn Tool> {
+    fetch_url_tool_with_options(FetchUrlOptions::default())
+}
+
+/// Create the `fetch_url` tool with custom host policy and HTTP settings.
+pub fn fetch_url_tool_with_options(options: FetchUrlOptions) -> Arc<dyn Tool> {
+    let tool = AgentTool::new(
+        "fetch_url",
+        "Fetch a web page (returned as markdown), plain text, or JSON document over HTTP(S)",
+        AgentToolParameters::object()
+            .string("url", "The http or https URL to fetch", true)
+            .string(
+                "selector",
+                "CSS selector limiting HTML output to matching elements (e.g. 'main', '#content')",
+                false,
+            )
+            .build(),
+        move |args_val, ctx: ToolExecutionContext| {
+            let mut options = options.clone();
+            options.http = options
+                .http
+                .clone()
+                .with_timeout(ctx.remaining_timeout(options.http.timeout()));
+            async move {
+                let url = args_val.get_str("url")?;
+                let selector = args_val.get_str_opt("selector");
+                fetch_url(url, selector, &options).await
+            }
+        },
+    );
+    Arc::new(tool.with_safety(fetch_url_safety_summary(), fetch_url_safety))
+}
+
+/// How a response body is turned into tool output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyKind {
+    Html,
+    Text,
+    Json,
+}
+
+impl BodyKind {
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "text/html" | "application/xhtml+xml" => Some(Self::Html),
+            "application/json" => Some(Self::Json),
+            mime if mime.ends_with("+json") => Some(Self::Json),
+            "application/xml" => Some(Self::Text),
+            mime if mime.starts_with("text/") || mime.ends_with("+xml") => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// Guess the kind of a body served without a content type.
+    fn sniff(body: &[u8]) -> Option<Self> {
+        if body.contains(&0) || std::str::from_utf8(body).is_err() {
+            return None;
+        }
+        let start = String::from_utf8_lossy(&body[..body.len().min(512)]).to_ascii_lowercase();
+        if start.contains("<html") || start.trim_start().starts_with("<!doctype html") {
+            Some(Self::Html)
+        } else {
+            Some(Self::Text)
+        }
+    }
+}
+
+async fn fetch_url(
+    raw_url: &str,
+    selector: Option<&str>,
+    options: &FetchUrlOptions,
+) -> Result<serde_json::Value, RociError> {
+    let mut url = Url::parse(raw_url)
+        .map_err(|e| RociError::InvalidArgument(format!("invalid URL '{raw_url}': {e}")))?;
+    let client = options.client()?;
+
+    let mut redirects = 0;
+    let mut response = loop {
+        options.check_url(&url)?;
+        if options.respect_robots_txt {
+            check_robots_txt(&client, &url, options.user_agent()).await?;
+        }
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| fetch_error(format!("{url}: {e}")))?;
+        if !response.status().is_redirection() {
+            break response;
+        }
+        let Some(location) = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+        else {
+            break response;
+        };
+        if redirects == FETCH_URL_MAX_REDIRECTS {
+            return Err(fetch_error(format!(
+                "{raw_url}: stopped after {FETCH_URL_MAX_REDIRECTS} redirects"
+            )));
+        }
+        url = url
+            .join(location)
+            .map_err(|e| fetch_error(format!("{url}: invalid redirect '{location}': {e}")))?;
+        redirects += 1;
+    };
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(fetch_error(format!("{url}: HTTP {status}")));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .filter(|mime| !mime.is_empty());
+    let declared_kind = match content_type.as_deref() {
+        Some(mime) => Some(BodyKind::from_mime(mime).ok_or_else(|| refuse_binary(&url, mime))?),
+        None => None,
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > options.max_bytes as u64)
+    {
+        return Err(too_large(&url, options.max_bytes));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| fetch_error(format!("{url}: {e}")))?
+    {
+        if body.len() + chunk.len() > options.max_bytes {
+            return Err(too_large(&url, options.max_bytes));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let kind = match declared_kind {
+        Some(kind) => kind,
+        None => BodyKind::sniff(&body).ok_or_else(|| refuse_binary(&url, "unknown"))?,
+    };
+
+    let text = String::from_utf8_lossy(&body);
+    let (title, content) = match kind {
+        BodyKind::Html => {
+            let page = html_to_markdown(&text, &url, selector)?;
+            (page.title, page.markdown)
+        }
+        BodyKind::Text | BodyKind::Json => (None, text.into_owned()),
+    };
+    let truncated = content.len() > FETCH_URL_OUTPUT_MAX_BYTES;
+    let mut content = truncate_utf8(&content, FETCH_URL_OUTPUT_MAX_BYTES);
+    if truncated {
+        content.push_str("\n... (truncated)");
+    }
+
+    Ok(serde_json::json!({
+        "url": raw_url,
+        "final_url": url.as_str(),
+        "status": status.as_u16(),
+        "content_type": content_type,
+        "title": title,
+        "content": content,
+        "truncated": truncated,
+    }))
+}
+
+/// Refuse `url` when its origin's `robots.txt` disallows it for our agent.
+async fn check_robots_txt(
+    client: &reqwest::Client,
+    url: &Url,
+    user_agent: &str,
+) -> Result<(), RociError> {
+    let Ok(robots_url) = url.join("/robots.txt") else {
+        return Ok(());
+    };
+    let robots = match client.get(robots_url).send().await {
+        Ok(response) if response.status().is_success() => response.text().await.ok(),
+        _ => None,
+    };
+    let Some(robots) = robots else {
+        return Ok(());
+    };
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    let agent = user_agent.split('/').next().unwrap_or(user_agent);
+    if robots_txt_allows(&robots, agent, &target) {
+        Ok(())
+    } else {
+        Err(fetch_error(format!("{url}: disallowed by robots.txt")))
+    }
+}
+
+/// User agents of one `robots.txt` group and its `(allow, path)` rules.
+type RobotsGroup = (Vec<String>, Vec<(bool, String)>);
+
+/// Evaluate `robots.txt` rules for `agent` (a product token such as `roci`).
+///
+/// Uses the groups naming the agent, else the `*` groups. The longest
+/// matching rule wins and `Allow` wins ties.
+pub(super) fn robots_txt_allows(robots: &str, agent: &str, path: &str) -> bool {
+    let agent = agent.to_ascii_lowercase();
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    let mut collecting_agents = false;
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                if !collecting_agents {
+                    groups.push((Vec::new(), Vec::new()));
+                    collecting_agents = true;
+                }
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+            }
+            rule @ ("allow" | "disallow") => {
+                collecting_agents = false;
+                if let Some((_, rules)) = groups.last_mut() {
+                    if !value.is_empty() {
+                        rules.push((rule == "allow", value.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let named = groups
+        .iter()
+        .filter(|(agents, _)| {
+            agents
+                .iter()
+                .any(|name| !name.is_empty() && name != "*" && agent.contains(name.as_str()))
+        })
+        .collect::<Vec<_>>();
+    let selected = if named.is_empty() {
+        groups
+            .iter()
+            .filter(|(agents, _)| agents.iter().any(|name| name == "*"))
+            .collect::<Vec<_>>()
+    } else {
+        named
+    };
+    selected
+        .into_iter()
+        .flat_map(|(_, rules)| rules)
+        .filter(|(_, pattern)| robots_pattern_matches(pattern, path))
+        .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+        .is_none_or(|(allow, _)| *allow)
+}
+
+/// Match a `robots.txt` path pattern supporting `*` and a trailing `$`.
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    for (index, part) in parts.iter().enumerate() {
+        if anchored && index + 1 == parts.len() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+fn fetch_error(message: String) -> RociError {
+    RociError::ToolExecution {
+        tool_name: "fetch_url".into(),
+        message,
+    }
+}
+
+fn refuse_binary(url: &Url, mime: &str) -> RociError {
+    fetch_error(format!(
+        "{url}: refusing content type '{mime}'; fetch_url only returns HTML, plain text, and JSON"
+    ))
+}
+
+fn too_large(url: &Url, max_bytes: usize) -> RociError {
+    fetch_error(format!(
+        "{url}: response exceeds the {max_bytes}-byte fetch_url limit"
+    ))
+}
+
+fn fetch_url_safety(_args: &ToolArguments) -> ToolSafetyPlan {
+    ToolSafetyPlan {
+        read_only: true,
+        destructive: false,
+        concurrency_safe: true,
+        approval: ToolApprovalRequirement {
+            kind: ToolSafetyKind::Read,
+            auto_accept_under_ask: false,
+            action_floor: None,
+            reason: Some("fetches a remote URL".to_string()),
+            allow_session: true,
+        },
+        ..ToolSafetyPlan::default()
+    }
+}
+
+fn fetch_url_safety_summary() -> ToolSafetySummary {
+    ToolSafetySummary {
+        read_only_by_default: true,
+        destructive_by_default: false,
+        concurrency_safe_by_default: true,
+        approval_kind: ToolSafetyKind::Read,
+    }
+}
+
+/// A converted HTML page.
+pub(super) struct HtmlPage {
+    pub(super) title: Option<String>,
+    pub(super) markdown: String,
+}
+
+/// Convert `html` to markdown, resolving links against `base`.
+///
+/// With a `selector`, only matching elements are converted, joined by blank
+/// lines; otherwise the whole `<body>` is.
+pub(super) fn html_to_markdown(
+    html: &str,
+    base: &Url,
+    selector: Option<&str>,
+) -> Result<HtmlPage, RociError> {
+    let document = Html::parse_document(html);
+    let title = first_element(&document, "title")
+        .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty());
+
+    let markdown = match selector {
+        Some(raw) => {
+            let selector = Selector::parse(raw).map_err(|e| {
+                RociError::InvalidArgument(format!("invalid selector '{raw}': {e}"))
+            })?;
+            let fragments = document
+                .select(&selector)
+                .map(|element| {
+                    let mut out = String::new();
+                    render_element(element, base, &mut out);
+                    tidy(&out)
+                })
+                .filter(|fragment| !fragment.is_empty())
+                .collect::<Vec<_>>();
+            if fragments.is_empty() {
+                return Err(fetch_error(format!(
+                    "{base}: selector '{raw}' matched no content"
+                )));
+            }
+            fragments.join("\n\n")
+        }
+        None => {
+            let body = first_element(&document, "body").unwrap_or_else(|| document.root_element());
+            let mut out = String::new();
+            render_children(body, base, &mut out);
+            tidy(&out)
+        }
+    };
+    Ok(HtmlPage { title, markdown })
+}
+
+fn first_element<'a>(document: &'a Html, css: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(css).ok()?;
+    document.select(&selector).next()
+}
+
+fn render_children(element: ElementRef<'_>, base: &Url, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => push_text(out, text),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    render_element(child, base, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_element(element: ElementRef<'_>, base: &Url, out: &mut String) {
+    let name = element.value().name();
+    if SKIPPED_ELEMENTS.contains(&name) {
+        return;
+    }
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let text = inline_text(element, base);
+            let text = text.trim();
+            if !text.is_empty() {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                block_break(out);
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+                out.push_str(text);
+                block_break(out);
+            }
+        }
+        "p" | "div" | "section" | "article" | "main" | "figure" | "figcaption" | "details"
+        | "summary" | "dl" | "dt" | "dd" | "li" => {
+            block_break(out);
+            render_children(element, base, out);
+            block_break(out);
+        }
+        "br" => {
+            trim_trailing_spaces(out);
+            out.push('\n');
+        }
+        "hr" => {
+            block_break(out);
+            out.push_str("---");
+            block_break(out);
+        }
+        "pre" => {
+            let code = element.text().collect::<String>();
+            let code = code.trim_matches('\n');
+            if !code.trim().is_empty() {
+                block_break(out);
+                out.push_str("```");
+                out.push_str(&code_language(element));
+                out.push('\n');
+                out.push_str(code);
+                out.push_str("\n```");
+                block_break(out);
+            }
+        }
+        "code" | "kbd" | "samp" => {
+            let code = collapse_whitespace(&element.text().collect::<String>());
+            let fence = if code.contains('`') { "``" } else { "`" };
+            push_wrapped(out, &code, fence, fence);
+        }
+        "a" => {
+            let text = inline_text(element, base);
+            match element
+                .value()
+                .attr("href")
+                .and_then(|href| resolve_link(base, href))
+            {
+                Some(href) if !text.trim().is_empty() => {
+                    push_wrapped(out, &text, "[", &format!("]({href})"))
+                }
+                _ => push_text(out, &text),
+            }
+        }
+        "img" => {
+            if let Some(src) = element
+                .value()
+                .attr("src")
+                .and_then(|src| resolve_link(base, src))
+            {
+                let alt = collapse_whitespace(element.value().attr("alt").unwrap_or_default());
+                push_text(out, &format!("![{}]({src})", alt.trim()));
+            }
+        }
+        "strong" | "b" => push_wrapped(out, &inline_text(element, base), "**", "**"),
+        "em" | "i" => push_wrapped(out, &inline_text(element, base), "*", "*"),
+        "ul" | "ol" => render_list(element, base, name == "ol", out),
+        "blockquote" => {
+            let mut inner = String::new();
+            render_children(element, base, &mut inner);
+            let inner = tidy(&inner);
+            if !inner.is_empty() {
+                block_break(out);
+                for (index, line) in inner.lines().enumerate() {
+                    if index > 0 {
+                        out.push('\n');
+                    }
+                    out.push('>');
+                    if !line.is_empty() {
+                        out.push(' ');
+                        out.push_str(line);
+                    }
+                }
+                block_break(out);
+            }
+        }
+        "table" => render_table(element, base, out),
+        _ => render_children(element, base, out),
+    }
+}
+
+fn render_list(list: ElementRef<'_>, base: &Url, ordered: bool, out: &mut String) {
+    let start = list
+        .value()
+        .attr("start")
+        .and_then(|start| start.trim().parse::<usize>().ok())
+        .unwrap_or(1);
+    block_break(out);
+    let mut first = true;
+    let items = list
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|item| item.value().name() == "li");
+    for (number, item) in (start..).zip(items) {
+        let mut inner = String::new();
+        render_children(item, base, &mut inner);
+        let inner = tidy(&inner);
+        let marker = if ordered {
+            format!("{number}. ")
+        } else {
+            "- ".to_string()
+        };
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        out.push_str(&marker);
+        let indent = " ".repeat(marker.len());
+        for (index, line) in inner
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+        {
+            if index > 0 {
+                out.push('\n');
+                out.push_str(&indent);
+            }
+            out.push_str(line);
+        }
+    }
+    block_break(out);
+}
+
+fn render_table(table: ElementRef<'_>, base: &Url, out: &mut String) {
+    let rows = table
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|row| row.value().name() == "tr")
+        .map(|row| {
+            row.children()
+                .filter_map(ElementRef::wrap)
+                .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                .map(|cell| inline_text(cell, base).trim().replace('|', "\\|"))
+                .collect::<Vec<_>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>();
+    let Some(columns) = rows.iter().map(Vec::len).max() else {
+        return;
+    };
+    block_break(out);
+    for (index, row) in rows.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        out.push('|');
+        for column in 0..columns {
+            out.push(' ');
+            out.push_str(row.get(column).map(String::as_str).unwrap_or_default());
+            out.push_str(" |");
+        }
+        if index == 0 {
+            out.push_str("\n|");
+            out.push_str(&" --- |".repeat(columns));
+        }
+    }
+    block_break(out);
+}
+
+/// Render an element's children on a single line.
+fn inline_text(element: ElementRef<'_>, base: &Url) -> String {
+    let mut out = String::new();
+    render_children(element, base, &mut out);
+    collapse_whitespace(&out)
+}
+
+/// Language hint from a `language-*` or `lang-*` class on `<pre>` or its
+/// `<code>` child.
+fn code_language(pre: ElementRef<'_>) -> String {
+    std::iter::once(pre)
+        .chain(
+            pre.children()
+                .filter_map(ElementRef::wrap)
+                .filter(|child| child.value().name() == "code"),
+        )
+        .filter_map(|element| element.value().attr("class"))
+        .flat_map(str::split_whitespace)
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn resolve_link(base: &Url, href: &str) -> Option<String> {
+    let href = href.trim();
+    if href.is_empty() || href.to_ascii_lowercase().starts_with("javascript:") {
+        return None;
+    }
+    base.join(href).ok().map(|url| url.to_string())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(ch);
+            in_space = false;
+        }
+    }
+    collapsed
+}
+
+/// Append running text, collapsing whitespace like a browser would.
+fn push_text(out: &mut String, text: &str) {
+    let collapsed = collapse_whitespace(text);
+    let collapsed = match collapsed.strip_prefix(' ') {
+        Some(rest) if out.is_empty() || out.ends_with(char::is_whitespace) => rest,
+        _ => collapsed.as_str(),
+    };
+    out.push_str(collapsed);
+}
+
+/// Append `inner` between `open` and `close`, keeping surrounding spaces
+/// outside the markers.
+fn push_wrapped(out: &mut String, inner: &str, open: &str, close: &str) {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        push_text(out, inner);
+        return;
+    }
+    if inner.starts_with(char::is_whitespace) {
+        push_text(out, " ");
+    }
+    out.push_str(open);
+    out.push_str(trimmed);
+    out.push_str(close);
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let trimmed = out.trim_end_matches([' ', '\t']).len();
+    out.truncate(trimmed);
+}
+
+/// End the current block with a blank line.
+fn block_break(out: &mut String) {
+    trim_trailing_spaces(out);
+    if out.is_empty() {
+        return;
+    }
+    while !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Trim trailing spaces and collapse runs of blank lines outside code
+/// fences.
+fn tidy(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut blank = false;
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if in_fence {
+            out.push('\n');
+            out.push_str(line);
+            in_fence = !line.starts_with("```");
+            continue;
+        }
+        let line = line.trim_end();
+        in_fence = line.starts_with("```");
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(line);
+    }
+    out
+}
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::Arc;
 
 use roci::error::RociError;
@@ -8,16 +10,28 @@ use roci::tools::tool::{
     AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
+use tokio::io::AsyncWriteExt;
 
 use super::common::{
-    resolve_session_path, resolve_workspace_path, truncate_utf8, GREP_OUTPUT_MAX_BYTES,
+    read_head, resolve_session_path, resolve_workspace_path, truncate_utf8,
+    GREP_FILES_PER_INVOCATION, GREP_FILE_NOTES_MAX, GREP_OUTPUT_MAX_BYTES, TEXT_SNIFF_BYTES,
 };
+use super::encoding::{EncodingFallback, TextEncoding};
 
 /// Create the `grep` tool — searches for a pattern in files.
 ///
-/// Runs `grep -rn` with the given pattern. Output is truncated to 32 KB.
-/// When `path` is omitted the search defaults to the current directory.
+/// Runs `grep -n` with the given pattern over every file under `path`.
+/// Files are decoded with the default [`EncodingFallback`] chain: UTF-8 files
+/// are searched in place, other encodings are converted to UTF-8 first and
+/// listed in `decoded_files`, and files that cannot be decoded are listed in
+/// `skipped_files` instead of failing the search. Output is truncated to
+/// 32 KB. When `path` is omitted the search defaults to the current directory.
 pub fn grep_tool() -> Arc<dyn Tool> {
+    grep_tool_with_encodings(EncodingFallback::default())
+}
+
+/// Create the `grep` tool with a custom encoding fallback chain.
+pub fn grep_tool_with_encodings(encodings: EncodingFallback) -> Arc<dyn Tool> {
     let tool = AgentTool::new(
         "grep",
         "Search for a pattern in files using grep",
@@ -29,71 +43,245 @@ pub fn grep_tool() -> Arc<dyn Tool> {
                 false,
             )
             .build(),
-        |args_val, ctx: ToolExecutionContext| async move {
-            let pattern = args_val.get_str("pattern")?;
-            let path = args_val.get_str_opt("path").unwrap_or(".");
+        move |args_val, ctx: ToolExecutionContext| {
+            let encodings = encodings.clone();
+            async move {
+                let pattern = args_val.get_str("pattern")?;
+                let path = args_val.get_str_opt("path").unwrap_or(".");
 
-            if let Some(workspace_path) = resolve_workspace_path(&ctx, path, PathOperation::Search)?
-            {
-                return host_grep(pattern, &workspace_path).await;
-            }
-
-            if let (Some(session_fs), Some(logical_path)) =
-                (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
-            {
-                let mut result = String::new();
-                session_grep(session_fs.as_ref(), &logical_path, pattern, &mut result)?;
+                if let Some(workspace_path) =
+                    resolve_workspace_path(&ctx, path, PathOperation::Search)?
+                {
+                    return host_grep(pattern, &workspace_path, &encodings).await;
+                }
 
-                let truncated = result.len() > GREP_OUTPUT_MAX_BYTES;
-                if truncated {
-                    result = truncate_utf8(&result, GREP_OUTPUT_MAX_BYTES);
-                    result.push_str("\n... (truncated)");
+                if let (Some(session_fs), Some(logical_path)) =
+                    (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
+                {
+                    let mut search = GrepOutput::default();
+                    session_grep(
+                        session_fs.as_ref(),
+                        &logical_path,
+                        pattern,
+                        &encodings,
+                        &mut search,
+                    )?;
+                    let exit_code = if search.output.is_empty() { 1 } else { 0 };
+                    return Ok(search.finish(Some(exit_code)));
                 }
 
-                let exit_code = if result.is_empty() { 1 } else { 0 };
-                return Ok(serde_json::json!({
-                    "exit_code": exit_code,
-                    "output": result,
-                    "truncated": truncated,
-                }));
+                host_grep(pattern, Path::new(path), &encodings).await
             }
-
-            host_grep(pattern, std::path::Path::new(path)).await
         },
     );
     Arc::new(tool.with_safety(grep_safety_summary(), grep_safety))
 }
 
-async fn host_grep(pattern: &str, path: &std::path::Path) -> Result<serde_json::Value, RociError> {
-    let output = tokio::process::Command::new("grep")
-        .args(["-rn", "--", pattern])
-        .arg(path)
-        .output()
-        .await
-        .map_err(|e| RociError::ToolExecution {
-            tool_name: "grep".into(),
-            message: e.to_string(),
-        })?;
+/// Accumulated matches and per-file encoding notes for one search.
+#[derive(Default)]
+struct GrepOutput {
+    output: String,
+    decoded_files: Vec<serde_json::Value>,
+    skipped_files: Vec<serde_json::Value>,
+    skipped_count: usize,
+}
+
+impl GrepOutput {
+    fn full(&self) -> bool {
+        self.output.len() > GREP_OUTPUT_MAX_BYTES
+    }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    fn note_decoded(&mut self, path: impl std::fmt::Display, encoding: TextEncoding) {
+        if self.decoded_files.len() < GREP_FILE_NOTES_MAX {
+            self.decoded_files.push(serde_json::json!({
+                "path": path.to_string(),
+                "encoding": encoding.label(),
+            }));
+        }
+    }
 
-    let mut result = stdout.into_owned();
-    if !stderr.is_empty() {
-        result.push_str(&stderr);
+    fn note_skipped(&mut self, path: impl std::fmt::Display, reason: impl std::fmt::Display) {
+        self.skipped_count += 1;
+        if self.skipped_files.len() < GREP_FILE_NOTES_MAX {
+            self.skipped_files.push(serde_json::json!({
+                "path": path.to_string(),
+                "reason": reason.to_string(),
+            }));
+        }
     }
 
-    let truncated = result.len() > GREP_OUTPUT_MAX_BYTES;
-    if truncated {
-        result = truncate_utf8(&result, GREP_OUTPUT_MAX_BYTES);
-        result.push_str("\n... (truncated)");
+    fn finish(mut self, exit_code: Option<i32>) -> serde_json::Value {
+        let truncated = self.full();
+        if truncated {
+            self.output = truncate_utf8(&self.output, GREP_OUTPUT_MAX_BYTES);
+            self.output.push_str("\n... (truncated)");
+        }
+        serde_json::json!({
+            "exit_code": exit_code,
+            "output": self.output,
+            "truncated": truncated,
+            "decoded_files": self.decoded_files,
+            "skipped_files": self.skipped_files,
+            "skipped_count": self.skipped_count,
+        })
     }
+}
 
-    Ok(serde_json::json!({
-        "exit_code": output.status.code(),
-        "output": result,
-        "truncated": truncated,
-    }))
+/// A host file queued for searching with its detected encoding.
+struct HostFile {
+    path: PathBuf,
+    encoding: TextEncoding,
+}
+
+async fn host_grep(
+    pattern: &str,
+    path: &Path,
+    encodings: &EncodingFallback,
+) -> Result<serde_json::Value, RociError> {
+    let mut search = GrepOutput::default();
+    let mut exit_codes = Vec::new();
+    let files = match collect_host_files(path, encodings, &mut search).await {
+        Ok(files) => files,
+        Err(err) => {
+            search
+                .output
+                .push_str(&format!("grep: {}: {err}\n", path.display()));
+            return Ok(search.finish(Some(2)));
+        }
+    };
+
+    let mut index = 0;
+    while index < files.len() && !search.full() {
+        if files[index].encoding == TextEncoding::Utf8 {
+            let batch = files[index..]
+                .iter()
+                .take(GREP_FILES_PER_INVOCATION)
+                .take_while(|file| file.encoding == TextEncoding::Utf8)
+                .map(|file| file.path.as_path())
+                .collect::<Vec<_>>();
+            index += batch.len();
+            let output = tokio::process::Command::new("grep")
+                .args(["-nH", "--", pattern])
+                .args(&batch)
+                .output()
+                .await
+                .map_err(grep_error)?;
+            exit_codes.push(output.status.code());
+            push_grep_output(&mut search, &output);
+            continue;
+        }
+
+        let file = &files[index];
+        index += 1;
+        let bytes = match tokio::fs::read(&file.path).await {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                search.note_skipped(file.path.display(), err);
+                continue;
+            }
+        };
+        search.note_decoded(file.path.display(), file.encoding);
+        let output = grep_decoded(pattern, &file.path, file.encoding.decode(&bytes)).await?;
+        exit_codes.push(output.status.code());
+        push_grep_output(&mut search, &output);
+    }
+
+    let exit_code = if exit_codes.contains(&Some(0)) {
+        Some(0)
+    } else {
+        exit_codes
+            .into_iter()
+            .find(|code| *code != Some(1))
+            .unwrap_or(Some(1))
+    };
+    Ok(search.finish(exit_code))
+}
+
+/// Walk `root` like `grep -r`: symlinks are followed only for `root` itself.
+async fn collect_host_files(
+    root: &Path,
+    encodings: &EncodingFallback,
+    search: &mut GrepOutput,
+) -> std::io::Result<Vec<HostFile>> {
+    let mut files = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), tokio::fs::metadata(root).await?)];
+    while let Some((path, metadata)) = pending.pop() {
+        if metadata.is_dir() {
+            let mut entries = Vec::new();
+            let mut dir = match tokio::fs::read_dir(&path).await {
+                Ok(dir) => dir,
+                Err(err) => {
+                    search.note_skipped(path.display(), err);
+                    continue;
+                }
+            };
+            while let Some(entry) = dir.next_entry().await? {
+                if let Ok(metadata) = entry.metadata().await {
+                    if !metadata.file_type().is_symlink() {
+                        entries.push((entry.path(), metadata));
+                    }
+                }
+            }
+            // Reverse-sorted onto the stack so files are visited in name order.
+            entries.sort_by(|a, b| b.0.cmp(&a.0));
+            pending.extend(entries);
+            continue;
+        }
+        if !metadata.is_file() {
+            continue;
+        }
+        match read_head(&path).await {
+            Ok(head) => match encodings.detect(&head) {
+                Some(encoding) => files.push(HostFile { path, encoding }),
+                None => search.note_skipped(path.display(), "binary or unsupported encoding"),
+            },
+            Err(err) => search.note_skipped(path.display(), err),
+        }
+    }
+    Ok(files)
+}
+
+/// Search already-decoded text through `grep`, labelled with its path.
+async fn grep_decoded(
+    pattern: &str,
+    path: &Path,
+    text: String,
+) -> Result<std::process::Output, RociError> {
+    let mut child = tokio::process::Command::new("grep")
+        .arg("-nH")
+        .arg(format!("--label={}", path.display()))
+        .args(["--", pattern, "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(grep_error)?;
+    let mut stdin = child.stdin.take().ok_or_else(|| RociError::ToolExecution {
+        tool_name: "grep".into(),
+        message: "grep stdin unavailable".to_string(),
+    })?;
+    let write = async move {
+        // grep may exit before reading everything; a broken pipe is fine.
+        let _ = stdin.write_all(text.as_bytes()).await;
+    };
+    let ((), output) = tokio::join!(write, child.wait_with_output());
+    output.map_err(grep_error)
+}
+
+fn push_grep_output(search: &mut GrepOutput, output: &std::process::Output) {
+    search
+        .output
+        .push_str(&String::from_utf8_lossy(&output.stdout));
+    search
+        .output
+        .push_str(&String::from_utf8_lossy(&output.stderr));
+}
+
+fn grep_error(err: std::io::Error) -> RociError {
+    RociError::ToolExecution {
+        tool_name: "grep".into(),
+        message: err.to_string(),
+    }
 }
 
 fn grep_safety(args: &ToolArguments) -> ToolSafetyPlan {
@@ -113,7 +301,8 @@ fn session_grep(
     session_fs: &(dyn SessionFs + Send + Sync),
     path: &LogicalPath,
     pattern: &str,
-    output: &mut String,
+    encodings: &EncodingFallback,
+    search: &mut GrepOutput,
 ) -> Result<(), RociError> {
     match session_fs
         .metadata(path)
@@ -123,7 +312,7 @@ fn session_grep(
         })?
         .kind
     {
-        SessionFileKind::File => session_grep_file(session_fs, path, pattern, output),
+        SessionFileKind::File => session_grep_file(session_fs, path, pattern, encodings, search),
         SessionFileKind::Directory => {
             for entry in session_fs
                 .list(path)
@@ -134,10 +323,10 @@ fn session_grep(
             {
                 match entry.metadata.kind {
                     SessionFileKind::File => {
-                        session_grep_file(session_fs, &entry.path, pattern, output)?;
+                        session_grep_file(session_fs, &entry.path, pattern, encodings, search)?;
                     }
                     SessionFileKind::Directory => {
-                        session_grep(session_fs, &entry.path, pattern, output)?;
+                        session_grep(session_fs, &entry.path, pattern, encodings, search)?;
                     }
                     SessionFileKind::Symlink => {}
                 }
@@ -152,7 +341,8 @@ fn session_grep_file(
     session_fs: &(dyn SessionFs + Send + Sync),
     path: &LogicalPath,
     pattern: &str,
-    output: &mut String,
+    encodings: &EncodingFallback,
+    search: &mut GrepOutput,
 ) -> Result<(), RociError> {
     use std::fmt::Write as _;
 
@@ -162,13 +352,18 @@ fn session_grep_file(
             tool_name: "grep".into(),
             message: format!("{path}: {e}"),
         })?;
-    let Ok(contents) = String::from_utf8(bytes) else {
+    let Some(encoding) = encodings.detect(&bytes[..bytes.len().min(TEXT_SNIFF_BYTES)]) else {
+        search.note_skipped(path, "binary or unsupported encoding");
         return Ok(());
     };
+    if encoding != TextEncoding::Utf8 {
+        search.note_decoded(path, encoding);
+    }
+    let contents = encoding.decode(&bytes);
 
     for (line_index, line) in contents.lines().enumerate() {
         if line.contains(pattern) {
-            writeln!(output, "{path}:{}:{line}", line_index + 1).map_err(|e| {
+            writeln!(search.output, "{path}:{}:{line}", line_index + 1).map_err(|e| {
                 RociError::ToolExecution {
                     tool_name: "grep".into(),
                     message: e.to_string(),
@@ -0,0 +1,175 @@
+//! Remember and recall tools for long-term memory.
+
+use std::sync::Arc;
+
+use roci::error::RociError;
+use roci::memory::{workspace_namespace, Memory, MemoryMetadata, DEFAULT_NAMESPACE};
+use roci::tools::arguments::ToolArguments;
+use roci::tools::tool::{
+    AgentTool, Tool, ToolEffects, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan,
+    ToolSafetySummary,
+};
+use roci::tools::types::AgentToolParameters;
+
+const DEFAULT_RECALL_LIMIT: usize = 5;
+const MAX_RECALL_LIMIT: usize = 20;
+
+/// Create the `remember` tool — stores a fact in long-term memory.
+///
+/// Memories go to the project namespace derived from the workspace root and
+/// record the run that stored them.
+pub fn remember_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "remember",
+        "Save a fact worth keeping across sessions, such as a user preference or a project command. Storing under an existing key replaces that memory.",
+        remember_parameters(),
+        |args, ctx: ToolExecutionContext| async move { execute_remember(args, ctx) },
+    )
+    .with_static_safety(ToolSafetyPlan::host_input(), remember_safety_summary())
+    .with_effects(ToolEffects::Mutating))
+}
+
+/// Create the `recall` tool — searches long-term memory by keyword.
+///
+/// Results carry provenance: when each memory was stored and by which run.
+pub fn recall_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "recall",
+        "Search facts saved with `remember` in earlier sessions. Matches whole words in keys and values; an empty query lists the newest memories.",
+        recall_parameters(),
+        |args, ctx: ToolExecutionContext| async move { execute_recall(args, ctx) },
+    )
+    .with_static_safety(
+        ToolSafetyPlan::safe_read_only(ToolSafetyKind::Read),
+        recall_safety_summary(),
+    )
+    .with_effects(ToolEffects::ReadOnly))
+}
+
+/// The `remember` and `recall` tools.
+///
+/// They need a memory store on the run (`RunRequest::with_memory`), so they
+/// are not part of [`all_tools`](super::all_tools).
+pub fn memory_tools() -> Vec<Arc<dyn Tool>> {
+    vec![remember_tool(), recall_tool()]
+}
+
+fn remember_safety_summary() -> ToolSafetySummary {
+    ToolSafetySummary {
+        read_only_by_default: false,
+        destructive_by_default: false,
+        concurrency_safe_by_default: false,
+        approval_kind: ToolSafetyKind::Other,
+    }
+}
+
+fn recall_safety_summary() -> ToolSafetySummary {
+    ToolSafetySummary {
+        read_only_by_default: true,
+        destructive_by_default: false,
+        concurrency_safe_by_default: true,
+        approval_kind: ToolSafetyKind::Read,
+    }
+}
+
+fn remember_parameters() -> AgentToolParameters {
+    AgentToolParameters::from_schema(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "key": {
+                "type": "string",
+                "description": "Short topic for the memory, e.g. 'indentation' or 'deploy command'"
+            },
+            "value": {
+                "type": "string",
+                "description": "The fact to remember"
+            }
+        },
+        "required": ["key", "value"]
+    }))
+}
+
+fn recall_parameters() -> AgentToolParameters {
+    AgentToolParameters::from_schema(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "query": {
+                "type": "string",
+                "description": "Keywords to search for"
+            },
+            "limit": {
+                "type": "integer",
+                "description": "Maximum memories to return (default 5, max 20)"
+            }
+        },
+        "required": ["query"]
+    }))
+}
+
+fn memory_store<'a>(
+    ctx: &'a ToolExecutionContext,
+    tool_name: &str,
+) -> Result<&'a Arc<dyn Memory>, RociError> {
+    ctx.memory.as_ref().ok_or_else(|| RociError::ToolExecution {
+        tool_name: tool_name.into(),
+        message: "no memory store configured for this run".into(),
+    })
+}
+
+fn namespace(ctx: &ToolExecutionContext) -> String {
+    ctx.workspace_root
+        .as_deref()
+        .map(workspace_namespace)
+        .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string())
+}
+
+fn execute_remember(
+    args: ToolArguments,
+    ctx: ToolExecutionContext,
+) -> Result<serde_json::Value, RociError> {
+    let memory = memory_store(&ctx, "remember")?;
+    let key = args.get_str("key")?;
+    let value = args.get_str("value")?;
+    let metadata = MemoryMetadata {
+        run_id: ctx.run_id.map(|run_id| run_id.to_string()),
+        ..MemoryMetadata::default()
+    };
+
+    let record = memory.put(&namespace(&ctx), key, value, metadata)?;
+    Ok(serde_json::json!({
+        "remembered": true,
+        "key": record.key,
+        "stored_at": record.stored_at,
+    }))
+}
+
+fn execute_recall(
+    args: ToolArguments,
+    ctx: ToolExecutionContext,
+) -> Result<serde_json::Value, RociError> {
+    let memory = memory_store(&ctx, "recall")?;
+    let query = args.get_str("query")?;
+    let limit = if args.has("limit") {
+        (args.get_u64("limit")? as usize).clamp(1, MAX_RECALL_LIMIT)
+    } else {
+        DEFAULT_RECALL_LIMIT
+    };
+
+    let hits = memory.search(&namespace(&ctx), query, limit)?;
+    let memories = hits
+        .into_iter()
+        .map(|hit| {
+            serde_json::json!({
+                "key": hit.record.key,
+                "value": hit.record.value,
+                "score": hit.score,
+                "stored_at": hit.record.stored_at,
+                "run_id": hit.record.metadata.run_id,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::json!({
+        "count": memories.len(),
+        "memories": memories,
+    }))
+}
@@ -1,26 +1,50 @@
 //! Built-in coding tools for the CLI agent.
 //!
-//! Provides standard tools (`shell`, `read_file`, `write_file`, `list_directory`,
-//! `grep`, `ask_user`) that a coding agent can use to interact with the local filesystem and
-//! execute commands. Each tool is constructed via [`AgentTool::new`] and returned
+//! Provides standard tools (`shell`, `read_file`, `write_file`, `diff_files`,
+//! `list_directory`, `grep`, `ask_user`, `update_plan`) that a coding agent can use to interact with the local
+//! filesystem, execute commands, and track its plan. Each tool is constructed via [`AgentTool::new`] and returned
 //! as `Arc<dyn Tool>`.
 //!
+//! [`fetch_url_tool`] reaches the network, so it is not part of [`all_tools`];
+//! add it explicitly, fenced with [`FetchUrlOptions`]. The [`memory_tools`]
+//! (`remember`, `recall`) need a memory store on the run and are added the
+//! same way.
+//!
+//! [`all_tools_with_overrides`] applies [`ToolOverrides`] — replacement
+//! descriptions, parameter descriptions, and exposed names, loaded from the
+//! `tool_overrides` resource settings — while keeping each tool's executor.
+//!
+//! With the `sandbox` feature, [`shell_tool_with_sandbox`] confines shell
+//! commands with OS primitives according to a [`SandboxPolicy`].
+//!
 //! # Usage
 //!
 //! ```rust,no_run
 //! use roci_tools::builtin::all_tools;
 //!
 //! let tools = all_tools();
-//! assert_eq!(tools.len(), 6);
+//! assert_eq!(tools.len(), 8);
 //! ```
 
 mod ask_user;
 mod catalog;
+mod change_tracking;
 mod common;
+mod diff_files;
+mod encoding;
+mod fetch_url;
 mod grep;
 mod list_directory;
+mod memory;
+mod overrides;
 mod read_file;
+#[cfg(feature = "sandbox")]
+mod sandbox;
+#[cfg(not(feature = "sandbox"))]
+#[path = "sandbox_disabled.rs"]
+mod sandbox;
 mod shell;
+mod update_plan;
 mod write_file;
 
 #[cfg(test)]
@@ -28,17 +52,53 @@ mod tests;
 
 use std::sync::Arc;
 
+use roci::error::RociError;
 use roci::tools::tool::Tool;
+use roci::tools::{ToolCatalog, ToolOrigin};
 
 pub use self::ask_user::ask_user_tool;
 pub use self::catalog::tool_catalog;
-pub use self::grep::grep_tool;
+pub use self::diff_files::diff_files_tool;
+pub use self::encoding::{EncodingFallback, TextEncoding};
+pub use self::fetch_url::{fetch_url_tool, fetch_url_tool_with_options, FetchUrlOptions};
+pub use self::grep::{grep_tool, grep_tool_with_encodings};
 pub use self::list_directory::list_directory_tool;
-pub use self::read_file::read_file_tool;
+pub use self::memory::{memory_tools, recall_tool, remember_tool};
+pub use self::overrides::{ToolOverride, ToolOverrides};
+pub use self::read_file::{read_file_tool, read_file_tool_with_encodings};
+#[cfg(feature = "sandbox")]
+pub use self::sandbox::SandboxPolicy;
 pub use self::shell::shell_tool;
-pub use self::write_file::write_file_tool;
+#[cfg(feature = "sandbox")]
+pub use self::shell::shell_tool_with_sandbox;
+pub use self::update_plan::update_plan_tool;
+pub use self::write_file::{write_file_tool, write_file_tool_with_encodings};
 
 /// Return all built-in coding tools.
 pub fn all_tools() -> Vec<Arc<dyn Tool>> {
     tool_catalog().resolve(&Default::default())
 }
+
+/// Return all built-in coding tools with `overrides` applied.
+///
+/// # Errors
+///
+/// Returns [`RociError::Configuration`] when an override names an unknown
+/// tool or parameter field, or two tools end up with the same name.
+pub fn all_tools_with_overrides(overrides: ToolOverrides) -> Result<Vec<Arc<dyn Tool>>, RociError> {
+    overrides.apply(all_tools())
+}
+
+/// [`tool_catalog`] with `overrides` applied; renamed tools keep their
+/// built-in name as an alias, so visibility policies may use either name.
+///
+/// # Errors
+///
+/// As [`all_tools_with_overrides`].
+pub fn tool_catalog_with_overrides(overrides: ToolOverrides) -> Result<ToolCatalog, RociError> {
+    let mut catalog = ToolCatalog::new();
+    for tool in all_tools_with_overrides(overrides)? {
+        catalog.insert_first_wins(tool, ToolOrigin::Builtin)?;
+    }
+    Ok(catalog)
+}
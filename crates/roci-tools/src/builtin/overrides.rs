@@ -0,0 +1,335 @@
+//! Model-facing overrides of built-in tool descriptions, parameter
+//! descriptions, and names.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use roci::error::RociError;
+use roci::resource::ToolOverrideSettings;
+use roci::tools::arguments::ToolArguments;
+use roci::tools::diff::ChangePreview;
+use roci::tools::tool::{
+    Tool, ToolEffects, ToolErrorClass, ToolExecutionContext, ToolPromptMetadata,
+    ToolResultSizePolicy, ToolSafetyPlan, ToolSafetySummary,
+};
+use roci::tools::types::AgentToolParameters;
+use serde_json::Value;
+
+/// Changes to how one tool is presented to the model. The tool keeps its
+/// executor, safety plan, and effects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolOverride {
+    description: Option<String>,
+    parameters: BTreeMap<String, String>,
+    name: Option<String>,
+}
+
+impl ToolOverride {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the tool description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Replace the description of the parameter at `path`, a dotted field
+    /// path such as `plan.step` (array items are descended automatically).
+    pub fn with_parameter_description(
+        mut self,
+        path: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.parameters.insert(path.into(), description.into());
+        self
+    }
+
+    /// Expose the tool under `name`. The built-in name stays accepted as an
+    /// alias.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// Overrides for built-in tools, keyed by built-in tool name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolOverrides {
+    tools: BTreeMap<String, ToolOverride>,
+}
+
+impl ToolOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides from the `tool_overrides` resource settings.
+    pub fn from_settings(settings: &BTreeMap<String, ToolOverrideSettings>) -> Self {
+        Self {
+            tools: settings
+                .iter()
+                .map(|(tool, settings)| {
+                    let tool_override = ToolOverride {
+                        description: settings.description.clone(),
+                        parameters: settings.parameters.clone(),
+                        name: settings.name.clone(),
+                    };
+                    (tool.clone(), tool_override)
+                })
+                .collect(),
+        }
+    }
+
+    /// Override the tool named `tool`.
+    pub fn with_tool(mut self, tool: impl Into<String>, tool_override: ToolOverride) -> Self {
+        self.tools.insert(tool.into(), tool_override);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Apply the overrides to `tools`, keeping their order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RociError::Configuration`] when an override names a tool
+    /// or parameter field that does not exist, or renames a tool to a name
+    /// another tool already uses.
+    pub fn apply(&self, tools: Vec<Arc<dyn Tool>>) -> Result<Vec<Arc<dyn Tool>>, RociError> {
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name()).collect();
+        if let Some(unknown) = self
+            .tools
+            .keys()
+            .find(|tool| !names.contains(&tool.as_str()))
+        {
+            return Err(RociError::Configuration(format!(
+                "tool_overrides: unknown tool '{unknown}'; available tools: {}",
+                names.join(", ")
+            )));
+        }
+
+        let mut exposed = BTreeSet::new();
+        let mut overridden = Vec::with_capacity(tools.len());
+        for tool in tools {
+            let tool = match self.tools.get(tool.name()) {
+                Some(tool_override) => Arc::new(OverriddenTool::new(tool, tool_override)?),
+                None => tool,
+            };
+            if !exposed.insert(tool.name().to_string()) {
+                return Err(RociError::Configuration(format!(
+                    "tool_overrides: more than one tool is named '{}'",
+                    tool.name()
+                )));
+            }
+            overridden.push(tool);
+        }
+        Ok(overridden)
+    }
+}
+
+/// A tool presented with overridden metadata; execution is delegated.
+struct OverriddenTool {
+    inner: Arc<dyn Tool>,
+    name: String,
+    description: Option<String>,
+    aliases: Vec<String>,
+    parameters: AgentToolParameters,
+}
+
+impl OverriddenTool {
+    fn new(inner: Arc<dyn Tool>, tool_override: &ToolOverride) -> Result<Self, RociError> {
+        let builtin = inner.name().to_string();
+        let mut parameters = inner.parameters().clone();
+        for (path, description) in &tool_override.parameters {
+            let Some(field) = field_mut(&mut parameters.schema, path) else {
+                let mut fields = Vec::new();
+                field_paths(&inner.parameters().schema, "", &mut fields);
+                return Err(RociError::Configuration(format!(
+                    "tool_overrides.{builtin}.parameters: unknown field '{path}'; available fields: {}",
+                    fields.join(", ")
+                )));
+            };
+            field.insert(
+                "description".to_string(),
+                Value::String(description.clone()),
+            );
+        }
+
+        let mut aliases = inner.aliases().to_vec();
+        let name = match &tool_override.name {
+            Some(name) if name.trim().is_empty() => {
+                return Err(RociError::Configuration(format!(
+                    "tool_overrides.{builtin}.name must not be empty"
+                )));
+            }
+            Some(name) => {
+                if name != &builtin {
+                    aliases.push(builtin.clone());
+                }
+                aliases.retain(|alias| alias != name);
+                name.clone()
+            }
+            None => builtin,
+        };
+        Ok(Self {
+            inner,
+            name,
+            description: tool_override.description.clone(),
+            aliases,
+            parameters,
+        })
+    }
+
+    /// Report errors raised under the built-in name with the exposed one.
+    fn rename_error(&self, error: RociError) -> RociError {
+        match error {
+            RociError::ToolExecution { tool_name, message } if tool_name == self.inner.name() => {
+                RociError::ToolExecution {
+                    tool_name: self.name.clone(),
+                    message,
+                }
+            }
+            error => error,
+        }
+    }
+}
+
+/// The schema object of the field at dotted `path`.
+fn field_mut<'a>(
+    schema: &'a mut Value,
+    path: &str,
+) -> Option<&'a mut serde_json::Map<String, Value>> {
+    let mut node = schema;
+    for segment in path.split('.') {
+        while node.get("properties").is_none() && node.get("items").is_some() {
+            node = node.get_mut("items")?;
+        }
+        node = node.get_mut("properties")?.get_mut(segment)?;
+    }
+    node.as_object_mut()
+}
+
+/// Every dotted field path in `schema`, for error messages.
+fn field_paths(schema: &Value, prefix: &str, paths: &mut Vec<String>) {
+    let mut node = schema;
+    while node.get("properties").is_none() && node.get("items").is_some() {
+        node = &node["items"];
+    }
+    let Some(properties) = node.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    for (name, field) in properties {
+        let path = format!("{prefix}{name}");
+        field_paths(field, &format!("{path}."), paths);
+        paths.push(path);
+    }
+}
+
+#[async_trait]
+impl Tool for OverriddenTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        if self.name == self.inner.name() {
+            self.inner.label()
+        } else {
+            &self.name
+        }
+    }
+
+    fn description(&self) -> &str {
+        self.description
+            .as_deref()
+            .unwrap_or_else(|| self.inner.description())
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn prompt(&self) -> &str {
+        self.description
+            .as_deref()
+            .unwrap_or_else(|| self.inner.prompt())
+    }
+
+    fn prompt_metadata(&self) -> ToolPromptMetadata {
+        self.inner.prompt_metadata()
+    }
+
+    fn prompt_guidance(&self) -> Option<String> {
+        self.inner.prompt_guidance()
+    }
+
+    fn priority(&self) -> Option<i32> {
+        self.inner.priority()
+    }
+
+    fn result_policy(&self) -> ToolResultSizePolicy {
+        self.inner.result_policy()
+    }
+
+    fn parameters(&self) -> &AgentToolParameters {
+        &self.parameters
+    }
+
+    fn safety(&self, args: &ToolArguments) -> ToolSafetyPlan {
+        self.inner.safety(args)
+    }
+
+    fn safety_summary(&self) -> ToolSafetySummary {
+        self.inner.safety_summary()
+    }
+
+    fn effects(&self) -> ToolEffects {
+        self.inner.effects()
+    }
+
+    fn classify_error(&self, error: &RociError) -> ToolErrorClass {
+        self.inner.classify_error(error)
+    }
+
+    async fn approval_preview(
+        &self,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+    ) -> Vec<ChangePreview> {
+        self.inner.approval_preview(args, ctx).await
+    }
+
+    fn simulate(&self, args: &ToolArguments) -> Value {
+        self.inner.simulate(args)
+    }
+
+    async fn execute(
+        &self,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+    ) -> Result<Value, RociError> {
+        self.inner
+            .execute(args, ctx)
+            .await
+            .map_err(|error| self.rename_error(error))
+    }
+
+    #[cfg(feature = "agent")]
+    async fn execute_ext(
+        &self,
+        args: &ToolArguments,
+        ctx: &ToolExecutionContext,
+        cancel: tokio_util::sync::CancellationToken,
+        on_update: Option<roci::tools::ToolUpdateCallback>,
+    ) -> Result<Value, RociError> {
+        self.inner
+            .execute_ext(args, ctx, cancel, on_update)
+            .await
+            .map_err(|error| self.rename_error(error))
+    }
+}
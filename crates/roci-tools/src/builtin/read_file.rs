@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use roci::error::RociError;
@@ -7,76 +8,366 @@ use roci::tools::tool::{
     AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
+use tokio::io::AsyncReadExt;
 
 use super::common::{
-    resolve_session_path, resolve_workspace_path, truncate_utf8, READ_FILE_MAX_BYTES,
+    resolve_session_path, resolve_workspace_path, READ_FILE_CHUNK_BYTES,
+    READ_FILE_COUNT_LINES_MAX_BYTES, READ_FILE_DEFAULT_LINE_LIMIT, READ_FILE_MAX_BYTES,
+    READ_FILE_MAX_LINE_BYTES, TEXT_SNIFF_BYTES,
 };
+use super::encoding::{EncodingFallback, TextDecoder, TextEncoding};
 
-/// Create the `read_file` tool — reads a file as UTF-8 text.
+/// Create the `read_file` tool — reads a window of lines from a text file.
 ///
-/// Returns the content, the byte count, and a truncation flag. Content is
-/// capped at 64 KB with a trailing note when truncated.
+/// `offset` (zero-based line) and `limit` (line count, default 2000) page
+/// through large files. Host files are streamed in chunks so memory stays
+/// bounded regardless of file size. Returns the content plus paging metadata:
+/// byte size, whether output was truncated, the `next_offset` to request, and
+/// the total line count when it is cheap to compute. Individual lines are cut
+/// at 2000 bytes with a marker, output is capped at 64 KB, and binary files
+/// are refused with a pointer to a hexdump-style shell command. Files are
+/// decoded with the default [`EncodingFallback`] chain and the detected
+/// encoding is reported as `encoding`.
 pub fn read_file_tool() -> Arc<dyn Tool> {
+    read_file_tool_with_encodings(EncodingFallback::default())
+}
+
+/// Create the `read_file` tool with a custom encoding fallback chain.
+pub fn read_file_tool_with_encodings(encodings: EncodingFallback) -> Arc<dyn Tool> {
     let tool = AgentTool::new(
         "read_file",
-        "Read a file's contents as UTF-8 text",
+        "Read a text file (UTF-8, UTF-16, or Latin-1), paged by line with optional offset/limit",
         AgentToolParameters::object()
-            .string("path", "Path to the file to read", true)
+            .string(
+                "path",
+                "Path to the file to read; `scratch://name` reads from the run's scratch directory",
+                true,
+            )
+            .number(
+                "offset",
+                "Zero-based line to start reading from (defaults to 0)",
+                false,
+            )
+            .number(
+                "limit",
+                "Maximum number of lines to return (defaults to 2000)",
+                false,
+            )
             .build(),
-        |args_val, ctx: ToolExecutionContext| async move {
-            let path = args_val.get_str("path")?;
-
-            let content = if let Some(workspace_path) =
-                resolve_workspace_path(&ctx, path, PathOperation::Read)?
-            {
-                tokio::fs::read_to_string(&workspace_path)
-                    .await
-                    .map_err(|e| RociError::ToolExecution {
-                        tool_name: "read_file".into(),
-                        message: format!("{}: {e}", workspace_path.display()),
-                    })?
-            } else if let (Some(session_fs), Some(path)) =
-                (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
-            {
-                let bytes = session_fs
-                    .read(&path)
-                    .map_err(|e| RociError::ToolExecution {
-                        tool_name: "read_file".into(),
-                        message: format!("{path}: {e}"),
-                    })?;
-                String::from_utf8(bytes).map_err(|e| RociError::ToolExecution {
-                    tool_name: "read_file".into(),
-                    message: format!("{path}: {e}"),
-                })?
-            } else {
-                tokio::fs::read_to_string(path)
-                    .await
-                    .map_err(|e| RociError::ToolExecution {
-                        tool_name: "read_file".into(),
-                        message: format!("{path}: {e}"),
-                    })?
-            };
+        move |args_val, ctx: ToolExecutionContext| {
+            let encodings = encodings.clone();
+            async move {
+                let path = args_val.get_str("path")?;
+                let window = LineWindow::from_args(&args_val)?;
 
-            let total_bytes = content.len();
-            let truncated = total_bytes > READ_FILE_MAX_BYTES;
-            let display = if truncated {
-                let mut s = truncate_utf8(&content, READ_FILE_MAX_BYTES);
-                s.push_str("\n... (truncated)");
-                s
-            } else {
-                content
-            };
+                if let Some(workspace_path) =
+                    resolve_workspace_path(&ctx, path, PathOperation::Read)?
+                {
+                    let label = workspace_path.display().to_string();
+                    return read_host_file(&workspace_path, &label, window, &encodings).await;
+                }
+
+                if let (Some(session_fs), Some(path)) =
+                    (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
+                {
+                    let label = path.to_string();
+                    let bytes = session_fs
+                        .read(&path)
+                        .map_err(|e| read_file_error(&label, e))?;
+                    let encoding = detect_encoding(&label, bytes.len() as u64, &bytes, &encodings)?;
+                    let mut pager = LinePager::new(window, bytes.len() as u64);
+                    pager.feed(encoding.decode(&bytes).as_bytes());
+                    return Ok(pager.finish(encoding));
+                }
 
-            Ok(serde_json::json!({
-                "content": display,
-                "bytes": total_bytes,
-                "truncated": truncated,
-            }))
+                read_host_file(Path::new(path), path, window, &encodings).await
+            }
         },
     );
     Arc::new(tool.with_safety(read_file_safety_summary(), read_file_safety))
 }
 
+/// Requested line window for a single `read_file` call.
+#[derive(Debug, Clone, Copy)]
+struct LineWindow {
+    offset: usize,
+    limit: usize,
+    default_limit: bool,
+}
+
+impl LineWindow {
+    fn from_args(args: &ToolArguments) -> Result<Self, RociError> {
+        let offset = line_arg(args, "offset")?.unwrap_or(0);
+        let requested_limit = line_arg(args, "limit")?;
+        if requested_limit == Some(0) {
+            return Err(RociError::InvalidArgument(
+                "limit must be at least 1".to_string(),
+            ));
+        }
+        Ok(Self {
+            offset,
+            limit: requested_limit.unwrap_or(READ_FILE_DEFAULT_LINE_LIMIT),
+            default_limit: requested_limit.is_none(),
+        })
+    }
+}
+
+fn line_arg(args: &ToolArguments, key: &str) -> Result<Option<usize>, RociError> {
+    if !args.has(key) {
+        return Ok(None);
+    }
+    let value = args.get_u64(key)?;
+    Ok(Some(usize::try_from(value).unwrap_or(usize::MAX)))
+}
+
+async fn read_host_file(
+    path: &Path,
+    label: &str,
+    window: LineWindow,
+    encodings: &EncodingFallback,
+) -> Result<serde_json::Value, RociError> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| read_file_error(label, e))?;
+    let total_bytes = file
+        .metadata()
+        .await
+        .map_err(|e| read_file_error(label, e))?
+        .len();
+
+    let mut pager = LinePager::new(window, total_bytes);
+    let mut buf = vec![0u8; READ_FILE_CHUNK_BYTES];
+    let mut decoder: Option<(TextEncoding, TextDecoder)> = None;
+    let mut exhausted = true;
+    loop {
+        let read = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| read_file_error(label, e))?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buf[..read];
+        if decoder.is_none() {
+            let encoding = detect_encoding(label, total_bytes, chunk, encodings)?;
+            decoder = Some((encoding, TextDecoder::new(encoding)));
+        }
+        let (_, active) = decoder.as_mut().expect("decoder is set on the first chunk");
+        if !pager.feed(&active.decode(chunk)) {
+            exhausted = false;
+            break;
+        }
+        // Large files are scanned chunk by chunk; give other tasks (UI, other
+        // tools, cancellation) a chance to run between chunks.
+        tokio::task::yield_now().await;
+    }
+    let encoding = match decoder {
+        Some((encoding, decoder)) => {
+            if exhausted {
+                pager.feed(&decoder.finish());
+            }
+            encoding
+        }
+        None => TextEncoding::Utf8,
+    };
+    Ok(pager.finish(encoding))
+}
+
+fn read_file_error(label: &str, err: impl std::fmt::Display) -> RociError {
+    RociError::ToolExecution {
+        tool_name: "read_file".into(),
+        message: format!("{label}: {err}"),
+    }
+}
+
+/// Detect the encoding from the leading bytes, refusing files that look
+/// binary or match no encoding in the fallback chain.
+fn detect_encoding(
+    label: &str,
+    total_bytes: u64,
+    head: &[u8],
+    encodings: &EncodingFallback,
+) -> Result<TextEncoding, RociError> {
+    let head = &head[..head.len().min(TEXT_SNIFF_BYTES)];
+    encodings.detect(head).ok_or_else(|| {
+        read_file_error(
+            label,
+            format!(
+                "appears to be a binary file ({total_bytes} bytes); read_file only returns text. \
+                 Inspect it with a hexdump via the shell tool instead, e.g. \
+                 `xxd -l 512 {label}` or `hexdump -C {label} | head -n 32`"
+            ),
+        )
+    })
+}
+
+/// Incremental line pager fed with UTF-8 file chunks.
+///
+/// Keeps at most one capped line plus the page output in memory. Once the
+/// page is full it either stops (`feed` returns `false`) or, for files small
+/// enough to count cheaply, keeps scanning to count the remaining lines.
+struct LinePager {
+    window: LineWindow,
+    total_bytes: u64,
+    count_all_lines: bool,
+    line: usize,
+    newlines: usize,
+    line_open: bool,
+    current: Vec<u8>,
+    current_overflow: usize,
+    output: String,
+    lines_returned: usize,
+    truncated_lines: usize,
+    page_full: bool,
+    has_more: bool,
+}
+
+impl LinePager {
+    fn new(window: LineWindow, total_bytes: u64) -> Self {
+        Self {
+            window,
+            total_bytes,
+            count_all_lines: total_bytes <= READ_FILE_COUNT_LINES_MAX_BYTES,
+            line: 0,
+            newlines: 0,
+            line_open: false,
+            current: Vec::new(),
+            current_overflow: 0,
+            output: String::new(),
+            lines_returned: 0,
+            truncated_lines: 0,
+            page_full: false,
+            has_more: false,
+        }
+    }
+
+    /// Consume a chunk. Returns `false` when no further input is needed.
+    fn feed(&mut self, chunk: &[u8]) -> bool {
+        let mut rest = chunk;
+        while !rest.is_empty() {
+            if self.page_full {
+                self.has_more = true;
+                if !self.count_all_lines {
+                    return false;
+                }
+                self.newlines += rest.iter().filter(|byte| **byte == b'\n').count();
+                self.line_open = rest.last() != Some(&b'\n');
+                return true;
+            }
+
+            let (segment, terminated) = match rest.iter().position(|byte| *byte == b'\n') {
+                Some(idx) => (&rest[..idx], true),
+                None => (rest, false),
+            };
+            rest = &rest[segment.len() + usize::from(terminated)..];
+            self.line_open = true;
+            if self.line >= self.window.offset {
+                self.collect(segment);
+            }
+            if terminated {
+                self.newlines += 1;
+                self.line_open = false;
+                self.end_line(true);
+            }
+        }
+        true
+    }
+
+    fn collect(&mut self, segment: &[u8]) {
+        let room = READ_FILE_MAX_LINE_BYTES.saturating_sub(self.current.len());
+        let keep = segment.len().min(room);
+        self.current.extend_from_slice(&segment[..keep]);
+        self.current_overflow += segment.len() - keep;
+    }
+
+    fn end_line(&mut self, terminated: bool) {
+        if self.line >= self.window.offset {
+            let text = self.take_line(terminated);
+            if !self.output.is_empty() && self.output.len() + text.len() > READ_FILE_MAX_BYTES {
+                self.page_full = true;
+                self.has_more = true;
+                return;
+            }
+            self.output.push_str(&text);
+            self.lines_returned += 1;
+            if self.lines_returned >= self.window.limit {
+                self.page_full = true;
+            }
+        }
+        self.line += 1;
+    }
+
+    fn take_line(&mut self, terminated: bool) -> String {
+        let bytes = std::mem::take(&mut self.current);
+        let overflow = std::mem::take(&mut self.current_overflow);
+        // Cutting a long line may split a multi-byte character; drop the
+        // partial tail instead of emitting a replacement character.
+        let valid = match std::str::from_utf8(&bytes) {
+            Err(err) if overflow > 0 && err.error_len().is_none() => err.valid_up_to(),
+            _ => bytes.len(),
+        };
+        let mut text = String::from_utf8_lossy(&bytes[..valid]).into_owned();
+        let dropped = overflow + (bytes.len() - valid);
+        if dropped > 0 {
+            text.push_str(&format!(" ... [line truncated: {dropped} more bytes]"));
+            self.truncated_lines += 1;
+        }
+        if terminated {
+            text.push('\n');
+        }
+        text
+    }
+
+    fn finish(mut self, encoding: TextEncoding) -> serde_json::Value {
+        if self.line_open && !self.page_full {
+            self.end_line(false);
+        }
+
+        let total_lines = (self.count_all_lines || !self.has_more)
+            .then_some(self.newlines + usize::from(self.line_open));
+        let next_offset = self
+            .has_more
+            .then_some(self.window.offset + self.lines_returned);
+        let truncated = self.has_more || self.truncated_lines > 0;
+
+        let mut content = self.output;
+        if truncated {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str("... (truncated)");
+        }
+
+        let note = next_offset.map(|next| {
+            let mut note = format!(
+                "returned {} lines starting at offset {}; call read_file with offset={next} to continue",
+                self.lines_returned, self.window.offset
+            );
+            if self.window.default_limit {
+                note.push_str(&format!(
+                    " (default limit of {READ_FILE_DEFAULT_LINE_LIMIT} lines applied)"
+                ));
+            }
+            note
+        });
+
+        serde_json::json!({
+            "content": content,
+            "bytes": self.total_bytes,
+            "encoding": encoding.label(),
+            "truncated": truncated,
+            "offset": self.window.offset,
+            "limit": self.window.limit,
+            "default_limit_applied": self.window.default_limit,
+            "lines_returned": self.lines_returned,
+            "total_lines": total_lines,
+            "next_offset": next_offset,
+            "truncated_lines": self.truncated_lines,
+            "note": note,
+        })
+    }
+}
+
 fn read_file_safety(args: &ToolArguments) -> ToolSafetyPlan {
     match args.get_str("path") {
         Ok(path) => ToolSafetyPlan::file_read(path),
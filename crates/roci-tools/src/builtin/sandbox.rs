@@ -0,0 +1,201 @@
+//! OS-level sandbox for the `shell` tool (the `sandbox` feature).
+//!
+//! Reads are left alone; writes are allowed only beneath the command's
+//! working directory, the temp directory, and the policy's writable roots.
+//!
+//! - Linux: a landlock ruleset, applied in the child just before `exec`
+//!   (which also sets `no_new_privs`). Kernels without landlock fail setup
+//!   rather than run unconfined. Network denial covers TCP and needs
+//!   landlock ABI 4 (Linux 6.7); older kernels enforce the filesystem rules
+//!   only.
+//! - macOS: the command runs under `sandbox-exec` with a seatbelt profile.
+//!   Network denial covers all sockets.
+//! - Elsewhere: no-op. The command runs unconfined and the result reports
+//!   sandbox mode `none`.
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use roci::resource::ShellSandboxSettings;
+use tokio::process::Command;
+
+#[cfg(target_os = "linux")]
+pub(super) const MODE: &str = "landlock";
+#[cfg(target_os = "macos")]
+pub(super) const MODE: &str = "seatbelt";
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(super) const MODE: &str = "none";
+
+/// Filesystem and network policy for sandboxed shell commands.
+///
+/// The working directory and the temp directory are always writable.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SandboxPolicy {
+    /// Extra directories commands may write beneath. Relative roots resolve
+    /// against the working directory; a root that does not exist fails
+    /// sandbox setup.
+    pub writable_roots: Vec<PathBuf>,
+    /// Whether commands may use the network.
+    pub allow_network: bool,
+}
+
+impl SandboxPolicy {
+    /// Policy from the `shell_sandbox` resource settings, or `None` when the
+    /// sandbox is not enabled there.
+    pub fn from_settings(settings: &ShellSandboxSettings) -> Option<Self> {
+        settings.enabled.then(|| Self {
+            writable_roots: settings.writable_roots.clone(),
+            allow_network: settings.allow_network,
+        })
+    }
+
+    /// Every writable directory for a command run in `workdir`.
+    fn writable_paths(&self, workdir: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut paths = vec![workdir.to_path_buf(), std::env::temp_dir()];
+        for root in &self.writable_roots {
+            let path = workdir.join(root);
+            std::fs::metadata(&path)
+                .map_err(|err| format!("writable root {}: {err}", path.display()))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// `sh -c command` in `workdir`, confined by `policy`. Errors describe why
+/// the sandbox could not be set up.
+#[cfg(target_os = "linux")]
+pub(super) fn sandboxed_command(
+    policy: &SandboxPolicy,
+    command: &str,
+    workdir: &Path,
+) -> Result<Command, String> {
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, AccessNet, CompatLevel, Compatible, Ruleset,
+        RulesetAttr, RulesetCreated, RulesetCreatedAttr, RulesetError, ABI,
+    };
+
+    const DEVICE_FILES: [&str; 3] = ["/dev/null", "/dev/zero", "/dev/full"];
+    let abi = ABI::V5;
+    let writable = policy.writable_paths(workdir)?;
+
+    let build = || -> Result<RulesetCreated, RulesetError> {
+        let mut ruleset = Ruleset::default()
+            // The base filesystem rights are required, so a kernel without
+            // landlock fails here instead of running the command unconfined.
+            .set_compatibility(CompatLevel::HardRequirement)
+            .handle_access(AccessFs::from_all(ABI::V1))?
+            .set_compatibility(CompatLevel::BestEffort)
+            .handle_access(AccessFs::from_all(abi))?;
+        if !policy.allow_network {
+            // No port rules follow, so every TCP bind and connect is denied.
+            ruleset = ruleset.handle_access(AccessNet::from_all(abi))?;
+        }
+        let devices = DEVICE_FILES
+            .into_iter()
+            .filter(|device| Path::new(device).exists());
+        ruleset
+            .create()?
+            .add_rules(path_beneath_rules(["/"], AccessFs::from_read(abi)))?
+            .add_rules(path_beneath_rules(devices, AccessFs::from_all(abi)))?
+            .add_rules(path_beneath_rules(writable, AccessFs::from_all(abi)))
+    };
+    let mut ruleset = Some(build().map_err(|err| format!("landlock: {err}"))?);
+
+    let mut process = super::shell::shell_process(command);
+    // SAFETY: the hook runs in the forked child before `exec`, where only
+    // async-signal-safe work is allowed. It takes the ruleset the parent
+    // already built (every error with detail is reported from the parent
+    // above) and issues the `prctl` and `landlock_restrict_self` syscalls. A
+    // failure maps to a bare errno, which does not allocate, and it touches
+    // no locks shared with the parent.
+    unsafe {
+        process.pre_exec(move || match ruleset.take() {
+            Some(ruleset) => ruleset
+                .restrict_self()
+                .map(drop)
+                .map_err(|_| std::io::Error::from_raw_os_error(libc::EPERM)),
+            None => Ok(()),
+        });
+    }
+    Ok(process)
+}
+
+/// `sh -c command` in `workdir`, confined by `policy`. Errors describe why
+/// the sandbox could not be set up.
+#[cfg(target_os = "macos")]
+pub(super) fn sandboxed_command(
+    policy: &SandboxPolicy,
+    command: &str,
+    workdir: &Path,
+) -> Result<Command, String> {
+    let mut process = Command::new("/usr/bin/sandbox-exec");
+    let mut writable = Vec::new();
+    for (index, path) in policy.writable_paths(workdir)?.into_iter().enumerate() {
+        // Seatbelt matches resolved paths, e.g. /private/tmp for /tmp.
+        let path = path
+            .canonicalize()
+            .map_err(|err| format!("writable root {}: {err}", path.display()))?;
+        let mut define = std::ffi::OsString::from(format!("WRITABLE_ROOT_{index}="));
+        define.push(path.as_os_str());
+        process.arg("-D").arg(define);
+        writable.push(format!("(subpath (param \"WRITABLE_ROOT_{index}\"))"));
+    }
+    process
+        .arg("-p")
+        .arg(seatbelt_profile(&writable, policy.allow_network))
+        .arg("sh")
+        .arg("-c")
+        .arg(command);
+    Ok(process)
+}
+
+/// `sh -c command`, unconfined: this platform has no sandbox backend.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(super) fn sandboxed_command(
+    _policy: &SandboxPolicy,
+    command: &str,
+    _workdir: &Path,
+) -> Result<Command, String> {
+    Ok(super::shell::shell_process(command))
+}
+
+/// Why the sandbox wrapper refused to run the command, when the process
+/// exited without running it.
+#[cfg(target_os = "macos")]
+pub(super) fn setup_failure(output: &Output) -> Option<String> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .strip_prefix("sandbox-exec: ")
+        .map(|message| message.lines().next().unwrap_or_default().to_string())
+}
+
+/// Why the sandbox wrapper refused to run the command, when the process
+/// exited without running it.
+#[cfg(not(target_os = "macos"))]
+pub(super) fn setup_failure(_output: &Output) -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn seatbelt_profile(writable: &[String], allow_network: bool) -> String {
+    let mut profile = String::from(
+        "(version 1)\n\
+         (allow default)\n\
+         (deny file-write*)\n\
+         (allow file-write*\n    \
+         (literal \"/dev/null\")\n    \
+         (literal \"/dev/zero\")\n    \
+         (literal \"/dev/dtracehelper\")\n    \
+         (regex #\"^/dev/tty\")",
+    );
+    for rule in writable {
+        profile.push_str("\n    ");
+        profile.push_str(rule);
+    }
+    profile.push_str(")\n");
+    if !allow_network {
+        profile.push_str("(deny network*)\n");
+    }
+    profile
+}
@@ -0,0 +1,26 @@
+//! Stand-in for the shell sandbox when the `sandbox` feature is off.
+//!
+//! [`SandboxPolicy`] is uninhabited here, so the shell tool can never be
+//! built with a policy and none of this runs.
+
+use std::path::Path;
+use std::process::Output;
+
+use tokio::process::Command;
+
+pub(super) const MODE: &str = "none";
+
+#[derive(Debug)]
+pub(super) enum SandboxPolicy {}
+
+pub(super) fn sandboxed_command(
+    policy: &SandboxPolicy,
+    _command: &str,
+    _workdir: &Path,
+) -> Result<Command, String> {
+    match *policy {}
+}
+
+pub(super) fn setup_failure(_output: &Output) -> Option<String> {
+    None
+}
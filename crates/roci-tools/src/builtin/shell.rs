@@ -1,106 +1,236 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use roci::error::RociError;
 use roci::security::command::classify_shell_command;
 use roci::tools::arguments::ToolArguments;
+use roci::tools::scratch::SCRATCH_ENV_VAR;
 use roci::tools::tool::{
     AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
 
+use super::change_tracking::{change_path, command_paths, ShellSnapshot};
 use super::common::{
     truncate_utf8, validate_session_shell_command, SHELL_OUTPUT_MAX_BYTES, SHELL_TIMEOUT,
 };
+use super::sandbox::{self, SandboxPolicy};
 
 /// Create the `shell` tool — executes a shell command via `sh -c`.
 ///
 /// Captures stdout and stderr, applies a 30-second timeout, and truncates
 /// output beyond 32 KB to prevent context explosion.
 ///
+/// When the run tracks changes, files named by relative paths in the command
+/// are compared before and after it runs; differences are recorded as
+/// unverified entries.
+///
+/// Inside a run, `$ROCI_RUN_TMP` names the run's scratch directory.
+///
 /// A workspace root only sets the process current directory. It is a trusted-host
 /// convenience, not a filesystem sandbox: commands and child processes retain
 /// normal host filesystem access unless the host supplies an OS sandbox.
 pub fn shell_tool() -> Arc<dyn Tool> {
+    build_shell_tool(None)
+}
+
+/// Create the `shell` tool with commands confined by an OS sandbox.
+///
+/// Writes are limited to the working directory, the temp directory, and the
+/// policy's writable roots; network access follows the policy. Results carry
+/// a `sandbox` field naming the mechanism (`landlock`, `seatbelt`, or `none`
+/// on platforms without one). When the sandbox cannot be set up the command
+/// does not run, and the result has a `sandbox_error` instead of an exit
+/// code.
+#[cfg(feature = "sandbox")]
+pub fn shell_tool_with_sandbox(policy: SandboxPolicy) -> Arc<dyn Tool> {
+    build_shell_tool(Some(Arc::new(policy)))
+}
+
+/// `sh -c command`.
+pub(super) fn shell_process(command: &str) -> tokio::process::Command {
+    let mut process = tokio::process::Command::new("sh");
+    process.arg("-c").arg(command);
+    process
+}
+
+fn build_shell_tool(sandbox: Option<Arc<SandboxPolicy>>) -> Arc<dyn Tool> {
     let tool = AgentTool::new(
         "shell",
         "Execute a shell command and return its output",
         AgentToolParameters::object()
             .string("command", "The shell command to execute", true)
             .build(),
-        |args_val, ctx: ToolExecutionContext| async move {
-            let command = args_val.get_str("command")?;
-
-            let mut process = tokio::process::Command::new("sh");
-            process.arg("-c").arg(command);
-
-            if let Some(workspace_root) = ctx.workspace_root.as_ref() {
-                if let Some(provider) = ctx.sandbox_provider.as_ref() {
-                    provider
-                        .validate_workspace_shell_command(command, workspace_root)
-                        .await?;
-                }
-                process.current_dir(workspace_root);
-            } else if let (Some(session_fs), Some(session_cwd)) =
-                (ctx.session_fs.as_ref(), ctx.session_cwd.as_ref())
-            {
-                if let Some(provider) = ctx.sandbox_provider.as_ref() {
-                    provider
-                        .validate_shell_command(command, session_cwd)
-                        .await?;
-                }
-
-                validate_session_shell_command(command).map_err(|reason| {
-                    RociError::ToolExecution {
-                        tool_name: "shell".into(),
-                        message: format!("session shell command denied: {reason}"),
-                    }
-                })?;
-
-                let cwd = session_fs.files_root().join(session_cwd.to_path_buf());
-                tokio::fs::create_dir_all(&cwd)
-                    .await
-                    .map_err(|e| RociError::ToolExecution {
-                        tool_name: "shell".into(),
-                        message: format!("{}: {e}", cwd.display()),
-                    })?;
-                process.current_dir(cwd);
-            }
+        move |args_val, ctx: ToolExecutionContext| execute_shell(args_val, ctx, sandbox.clone()),
+    );
+    Arc::new(tool.with_safety(shell_safety_summary(), shell_safety))
+}
+
+async fn execute_shell(
+    args_val: ToolArguments,
+    ctx: ToolExecutionContext,
+    sandbox: Option<Arc<SandboxPolicy>>,
+) -> Result<serde_json::Value, RociError> {
+    let command = args_val.get_str("command")?;
+
+    let mut workdir: Option<PathBuf> = None;
+    // (change-log path, host path) for files the command names.
+    let mut tracked_paths = Vec::new();
+
+    if let Some(workspace_root) = ctx.workspace_root.as_ref() {
+        if let Some(provider) = ctx.sandbox_provider.as_ref() {
+            provider
+                .validate_workspace_shell_command(command, workspace_root)
+                .await?;
+        }
+        workdir = Some(workspace_root.clone());
+        if ctx.changes.is_some() {
+            tracked_paths = command_paths(command)
+                .into_iter()
+                .map(|path| {
+                    let host_path = workspace_root.join(&path);
+                    (change_path(Some(workspace_root), &host_path), host_path)
+                })
+                .collect();
+        }
+    } else if let (Some(session_fs), Some(session_cwd)) =
+        (ctx.session_fs.as_ref(), ctx.session_cwd.as_ref())
+    {
+        if let Some(provider) = ctx.sandbox_provider.as_ref() {
+            provider
+                .validate_shell_command(command, session_cwd)
+                .await?;
+        }
+
+        validate_session_shell_command(command).map_err(|reason| RociError::ToolExecution {
+            tool_name: "shell".into(),
+            message: format!("session shell command denied: {reason}"),
+        })?;
+
+        let cwd = session_fs.files_root().join(session_cwd.to_path_buf());
+        tokio::fs::create_dir_all(&cwd)
+            .await
+            .map_err(|e| RociError::ToolExecution {
+                tool_name: "shell".into(),
+                message: format!("{}: {e}", cwd.display()),
+            })?;
+        if ctx.changes.is_some() {
+            tracked_paths = command_paths(command)
+                .into_iter()
+                .filter_map(|path| {
+                    let logical = session_cwd.join(&path).ok()?;
+                    Some((logical.to_string(), cwd.join(&path)))
+                })
+                .collect();
+        }
+        workdir = Some(cwd);
+    } else if ctx.changes.is_some() {
+        tracked_paths = command_paths(command)
+            .into_iter()
+            .map(|path| (change_path(None, &path), path))
+            .collect();
+    }
 
-            let result = tokio::time::timeout(SHELL_TIMEOUT, process.output()).await;
-
-            let output = match result {
-                Ok(Ok(output)) => output,
-                Ok(Err(e)) => {
-                    return Err(RociError::ToolExecution {
-                        tool_name: "shell".into(),
-                        message: e.to_string(),
-                    });
-                }
-                Err(_) => {
-                    return Err(RociError::ToolExecution {
-                        tool_name: "shell".into(),
-                        message: format!("command timed out after {}s", SHELL_TIMEOUT.as_secs()),
-                    });
-                }
-            };
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let mut combined = format!("{stdout}{stderr}");
-            let truncated = combined.len() > SHELL_OUTPUT_MAX_BYTES;
-            if truncated {
-                combined = truncate_utf8(&combined, SHELL_OUTPUT_MAX_BYTES);
-                combined.push_str("\n... (truncated)");
+    let (mut process, sandbox_mode) = match sandbox.as_deref() {
+        Some(policy) => {
+            let confined = match workdir.clone() {
+                Some(dir) => Ok(dir),
+                None => std::env::current_dir().map_err(|e| format!("working directory: {e}")),
             }
+            .and_then(|dir| sandbox::sandboxed_command(policy, command, &dir));
+            match confined {
+                Ok(process) => (process, Some(sandbox::MODE)),
+                Err(message) => return Ok(sandbox_setup_failed(sandbox::MODE, message)),
+            }
+        }
+        None => (shell_process(command), None),
+    };
+    if let Some(dir) = workdir.as_deref() {
+        process.current_dir(dir);
+    }
+    // Only commands that mention the variable create the scratch directory.
+    if let Some(scratch) = ctx.scratch.as_ref() {
+        if command.contains(SCRATCH_ENV_VAR) {
+            process.env(SCRATCH_ENV_VAR, scratch.path()?);
+        }
+    }
+    let snapshot = if ctx.changes.is_some() {
+        Some(ShellSnapshot::capture(tracked_paths).await)
+    } else {
+        None
+    };
 
-            Ok(serde_json::json!({
-                "exit_code": output.status.code(),
-                "output": combined,
-                "truncated": truncated,
-            }))
-        },
-    );
-    Arc::new(tool.with_safety(shell_safety_summary(), shell_safety))
+    let timeout = ctx.remaining_timeout(SHELL_TIMEOUT);
+    let result = tokio::time::timeout(timeout, process.output()).await;
+    // Recorded even when the command failed or timed out: it may
+    // have changed files before stopping.
+    if let (Some(snapshot), Some(changes)) = (snapshot, ctx.changes.as_ref()) {
+        snapshot.record_changes(changes).await;
+    }
+
+    let output = match result {
+        Ok(Ok(output)) => output,
+        // Spawning `sh` itself does not fail in practice; under a
+        // sandbox this is the confinement step refusing.
+        Ok(Err(e)) if sandbox_mode.is_some() => {
+            return Ok(sandbox_setup_failed(sandbox::MODE, e.to_string()));
+        }
+        Ok(Err(e)) => {
+            return Err(RociError::ToolExecution {
+                tool_name: "shell".into(),
+                message: e.to_string(),
+            });
+        }
+        Err(_) if timeout < SHELL_TIMEOUT => {
+            return Err(RociError::ToolExecution {
+                tool_name: "shell".into(),
+                message: "command stopped at the run deadline".to_string(),
+            });
+        }
+        Err(_) => {
+            return Err(RociError::ToolExecution {
+                tool_name: "shell".into(),
+                message: format!("command timed out after {}s", SHELL_TIMEOUT.as_secs()),
+            });
+        }
+    };
+
+    if sandbox_mode.is_some() {
+        if let Some(message) = sandbox::setup_failure(&output) {
+            return Ok(sandbox_setup_failed(sandbox::MODE, message));
+        }
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut combined = format!("{stdout}{stderr}");
+    let truncated = combined.len() > SHELL_OUTPUT_MAX_BYTES;
+    if truncated {
+        combined = truncate_utf8(&combined, SHELL_OUTPUT_MAX_BYTES);
+        combined.push_str("\n... (truncated)");
+    }
+
+    let mut result = serde_json::json!({
+        "exit_code": output.status.code(),
+        "output": combined,
+        "truncated": truncated,
+    });
+    if let Some(mode) = sandbox_mode {
+        result["sandbox"] = mode.into();
+    }
+    Ok(result)
+}
+
+/// Result for a command that never ran because its sandbox could not be set
+/// up, distinct from a command that ran and failed.
+fn sandbox_setup_failed(mode: &str, message: String) -> serde_json::Value {
+    serde_json::json!({
+        "exit_code": null,
+        "output": "",
+        "truncated": false,
+        "sandbox": mode,
+        "sandbox_error": message,
+    })
 }
 
 fn shell_safety(args: &ToolArguments) -> ToolSafetyPlan {
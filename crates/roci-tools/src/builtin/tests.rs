@@ -6,9 +6,12 @@ use roci::error::RociError;
 use roci::prelude::{LocalSessionFs, LogicalPath};
 use roci::security::command::classify_shell_command;
 use roci::tools::arguments::ToolArguments;
+use roci::tools::changes::{content_hash, ChangeLog};
+use roci::tools::diff::ChangePreview;
+use roci::tools::scratch::ScratchDir;
 use roci::tools::tool::{
-    AgentTool, Tool, ToolActionFloor, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan,
-    ToolSafetySummary,
+    AgentTool, Tool, ToolActionFloor, ToolEffects, ToolExecutionContext, ToolSafetyKind,
+    ToolSafetyPlan, ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
 
@@ -31,12 +34,30 @@ fn args(json: serde_json::Value) -> ToolArguments {
     ToolArguments::new(json)
 }
 
+/// The same text as UTF-8, UTF-8 with BOM, UTF-16LE with BOM, and Latin-1.
+fn encoding_fixtures(text: &str) -> Vec<(&'static str, &'static str, Vec<u8>)> {
+    let mut utf8_bom = vec![0xEF, 0xBB, 0xBF];
+    utf8_bom.extend_from_slice(text.as_bytes());
+    let mut utf16le = vec![0xFF, 0xFE];
+    utf16le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    let latin1 = text
+        .chars()
+        .map(|ch| u8::try_from(ch).unwrap_or(b'?'))
+        .collect();
+    vec![
+        ("plain.txt", "utf-8", text.as_bytes().to_vec()),
+        ("bom.txt", "utf-8-bom", utf8_bom),
+        ("wide.txt", "utf-16le", utf16le),
+        ("legacy.txt", "latin-1", latin1),
+    ]
+}
+
 // ── all_tools ──────────────────────────────────────────────────────
 
 #[test]
-fn all_tools_returns_six_tools() {
+fn all_tools_returns_eight_tools() {
     let tools = all_tools();
-    assert_eq!(tools.len(), 6);
+    assert_eq!(tools.len(), 8);
 }
 
 #[test]
@@ -46,8 +67,10 @@ fn all_tools_contains_expected_names() {
     assert!(names.contains(&"shell"));
     assert!(names.contains(&"read_file"));
     assert!(names.contains(&"write_file"));
+    assert!(names.contains(&"diff_files"));
     assert!(names.contains(&"list_directory"));
     assert!(names.contains(&"grep"));
+    assert!(names.contains(&"update_plan"));
 }
 
 #[test]
@@ -55,7 +78,7 @@ fn tool_catalog_marks_all_tools_as_builtin() {
     let catalog = tool_catalog();
     let descriptors = catalog.descriptors();
 
-    assert_eq!(descriptors.len(), 6);
+    assert_eq!(descriptors.len(), 8);
     assert!(descriptors
         .iter()
         .all(|descriptor| descriptor.origin == roci::tools::ToolOrigin::Builtin));
@@ -83,6 +106,7 @@ fn builtins_declare_expected_safety_plans() {
 
     let read_file = read_file_tool();
     assert_eq!(read_file.safety_summary(), read_summary);
+    assert_eq!(read_file.effects(), ToolEffects::ReadOnly);
     assert_eq!(
         read_file.safety(&args(serde_json::json!({"path": "Cargo.toml"}))),
         ToolSafetyPlan::file_read("Cargo.toml")
@@ -94,6 +118,7 @@ fn builtins_declare_expected_safety_plans() {
 
     let list_directory = list_directory_tool();
     assert_eq!(list_directory.safety_summary(), read_summary);
+    assert_eq!(list_directory.effects(), ToolEffects::ReadOnly);
     assert_eq!(
         list_directory.safety(&args(serde_json::json!({"path": "crates"}))),
         ToolSafetyPlan::file_list("crates")
@@ -105,6 +130,7 @@ fn builtins_declare_expected_safety_plans() {
 
     let grep = grep_tool();
     assert_eq!(grep.safety_summary(), read_summary);
+    assert_eq!(grep.effects(), ToolEffects::ReadOnly);
     assert_eq!(
         grep.safety(&args(
             serde_json::json!({"pattern": "AgentTool", "path": "crates"})
@@ -118,6 +144,7 @@ fn builtins_declare_expected_safety_plans() {
 
     let write_file = write_file_tool();
     assert_eq!(write_file.safety_summary(), write_summary);
+    assert_eq!(write_file.effects(), ToolEffects::Mutating);
     assert_eq!(
         write_file.safety(&args(
             serde_json::json!({"path": "out.txt", "content": "ok"})
@@ -131,13 +158,23 @@ fn builtins_declare_expected_safety_plans() {
 
     let ask_user = ask_user_tool();
     assert_eq!(ask_user.safety_summary(), host_input_summary);
+    assert_eq!(ask_user.effects(), ToolEffects::ReadOnly);
     assert_eq!(
         ask_user.safety(&args(serde_json::json!({"kind": "question"}))),
         ToolSafetyPlan::host_input()
     );
 
+    let update_plan = update_plan_tool();
+    assert_eq!(update_plan.safety_summary(), host_input_summary);
+    assert_eq!(update_plan.effects(), ToolEffects::ReadOnly);
+    assert_eq!(
+        update_plan.safety(&args(serde_json::json!({"plan": []}))),
+        ToolSafetyPlan::host_input()
+    );
+
     let shell = shell_tool();
     assert_eq!(shell.safety_summary(), command_summary);
+    assert_eq!(shell.effects(), ToolEffects::Mutating);
     assert_eq!(
         shell.safety(&args(serde_json::json!({"command": "git status"}))),
         ToolSafetyPlan::from_command_insight(classify_shell_command("git status"))
@@ -156,7 +193,7 @@ fn builtins_declare_expected_safety_plans() {
         .reason
         .as_deref()
         .unwrap_or_default()
-        .contains("Missing string argument: command"));
+        .contains("missing required field 'command'"));
 
     let destructive_shell = shell.safety(&args(
         serde_json::json!({"command": "eval 'rm -rf /tmp/roci-tool-safety'"}),
@@ -351,6 +388,126 @@ async fn session_shell_rejects_obvious_escape_commands() {
     }
 }
 
+/// Directory outside both the temp directory and the workspace, which the
+/// sandbox always leaves writable.
+#[cfg(all(feature = "sandbox", any(target_os = "linux", target_os = "macos")))]
+fn outside_sandbox_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix(".sandbox-test-")
+        .tempdir_in(env!("CARGO_MANIFEST_DIR"))
+        .unwrap()
+}
+
+#[cfg(all(feature = "sandbox", any(target_os = "linux", target_os = "macos")))]
+#[tokio::test]
+async fn sandboxed_shell_denies_writes_outside_the_workspace() {
+    let workspace = tempfile::tempdir().unwrap();
+    let outside = outside_sandbox_dir();
+    let target = outside.path().join("escaped.txt");
+    let command = format!("echo escaped > '{}'", target.display());
+
+    let result = shell_tool_with_sandbox(SandboxPolicy::default())
+        .execute(
+            &args(serde_json::json!({"command": command})),
+            &workspace_ctx(workspace.path()),
+        )
+        .await
+        .unwrap();
+
+    assert!(result.get("sandbox_error").is_none(), "{result}");
+    assert_ne!(result["exit_code"], 0, "{result}");
+    assert!(!target.exists());
+
+    let unsandboxed = shell_tool()
+        .execute(
+            &args(serde_json::json!({"command": command})),
+            &workspace_ctx(workspace.path()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(unsandboxed["exit_code"], 0, "{unsandboxed}");
+    assert!(unsandboxed.get("sandbox").is_none());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "escaped\n");
+}
+
+#[cfg(all(feature = "sandbox", any(target_os = "linux", target_os = "macos")))]
+#[tokio::test]
+async fn sandboxed_shell_allows_workspace_and_writable_roots() {
+    let workspace = tempfile::tempdir().unwrap();
+    let extra = outside_sandbox_dir();
+    let policy = SandboxPolicy {
+        writable_roots: vec![extra.path().to_path_buf()],
+        ..SandboxPolicy::default()
+    };
+    let command = format!(
+        "echo inside > inside.txt && echo extra > '{}'",
+        extra.path().join("extra.txt").display()
+    );
+
+    let result = shell_tool_with_sandbox(policy)
+        .execute(
+            &args(serde_json::json!({"command": command})),
+            &workspace_ctx(workspace.path()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["exit_code"], 0, "{result}");
+    assert!(matches!(
+        result["sandbox"].as_str(),
+        Some("landlock" | "seatbelt")
+    ));
+    assert!(workspace.path().join("inside.txt").exists());
+    assert!(extra.path().join("extra.txt").exists());
+}
+
+#[cfg(all(feature = "sandbox", any(target_os = "linux", target_os = "macos")))]
+#[tokio::test]
+async fn sandbox_setup_failure_is_reported_apart_from_command_failure() {
+    let workspace = tempfile::tempdir().unwrap();
+    let missing = workspace.path().join("missing");
+    let policy = SandboxPolicy {
+        writable_roots: vec![missing],
+        ..SandboxPolicy::default()
+    };
+
+    let result = shell_tool_with_sandbox(policy)
+        .execute(
+            &args(serde_json::json!({"command": "touch ran.txt"})),
+            &workspace_ctx(workspace.path()),
+        )
+        .await
+        .unwrap();
+
+    assert!(result["exit_code"].is_null(), "{result}");
+    assert!(result["sandbox_error"]
+        .as_str()
+        .unwrap()
+        .contains("missing"));
+    assert!(!workspace.path().join("ran.txt").exists());
+}
+
+#[cfg(feature = "sandbox")]
+#[test]
+fn sandbox_policy_from_settings_requires_enabled() {
+    let mut settings = roci::resource::ShellSandboxSettings {
+        enabled: false,
+        writable_roots: vec!["cache".into()],
+        allow_network: true,
+    };
+    assert_eq!(SandboxPolicy::from_settings(&settings), None);
+
+    settings.enabled = true;
+    assert_eq!(
+        SandboxPolicy::from_settings(&settings),
+        Some(SandboxPolicy {
+            writable_roots: vec!["cache".into()],
+            allow_network: true,
+        })
+    );
+}
+
 // ── read_file ──────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -445,269 +602,920 @@ async fn read_file_keeps_host_absolute_paths_without_session_context() {
     assert_eq!(result["content"], "host");
 }
 
-// ── write_file ─────────────────────────────────────────────────────
+fn write_numbered_lines(path: &Path, count: usize) {
+    let content: String = (0..count).map(|i| format!("line {i}\n")).collect();
+    std::fs::write(path, content).unwrap();
+}
 
 #[tokio::test]
-async fn write_file_creates_file_with_content() {
+async fn read_file_applies_default_line_limit_and_reports_next_offset() {
     let dir = tempfile::tempdir().unwrap();
-    let file_path = dir.path().join("out.txt");
+    let file_path = dir.path().join("many.log");
+    write_numbered_lines(&file_path, 10_000);
 
-    let tool = write_file_tool();
-    let result = tool
+    let result = read_file_tool()
         .execute(
-            &args(serde_json::json!({
-                "path": file_path.to_str().unwrap(),
-                "content": "hello roci",
-            })),
+            &args(serde_json::json!({"path": file_path.to_str().unwrap()})),
             &default_ctx(),
         )
         .await
         .unwrap();
 
-    assert_eq!(result["success"], true);
-    assert_eq!(result["bytes_written"], 10);
-    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "hello roci");
+    let content = result["content"].as_str().unwrap();
+    assert!(content.starts_with("line 0\nline 1\n"));
+    assert!(content.contains("line 1999\n"));
+    assert!(!content.contains("line 2000\n"));
+    assert_eq!(result["truncated"], true);
+    assert_eq!(result["default_limit_applied"], true);
+    assert_eq!(result["limit"], 2000);
+    assert_eq!(result["lines_returned"], 2000);
+    assert_eq!(result["next_offset"], 2000);
+    assert_eq!(result["total_lines"], 10_000);
+    assert!(result["note"].as_str().unwrap().contains("offset=2000"));
 }
 
 #[tokio::test]
-async fn write_file_creates_parent_directories() {
+async fn read_file_pages_with_offset_and_limit_to_end_of_file() {
     let dir = tempfile::tempdir().unwrap();
-    let file_path = dir.path().join("a").join("b").join("c.txt");
+    let file_path = dir.path().join("many.log");
+    write_numbered_lines(&file_path, 10_000);
+    let tool = read_file_tool();
 
-    let tool = write_file_tool();
-    let result = tool
+    let middle = tool
         .execute(
             &args(serde_json::json!({
                 "path": file_path.to_str().unwrap(),
-                "content": "nested",
+                "offset": 5000,
+                "limit": 3,
             })),
             &default_ctx(),
         )
         .await
         .unwrap();
+    assert_eq!(
+        middle["content"],
+        "line 5000\nline 5001\nline 5002\n... (truncated)"
+    );
+    assert_eq!(middle["next_offset"], 5003);
+    assert_eq!(middle["default_limit_applied"], false);
 
-    assert_eq!(result["success"], true);
-    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "nested");
-}
-
-#[tokio::test]
-async fn write_file_returns_path_in_response() {
-    let dir = tempfile::tempdir().unwrap();
-    let file_path = dir.path().join("file.txt");
-    let path_str = file_path.to_str().unwrap();
-
-    let tool = write_file_tool();
-    let result = tool
+    let tail = tool
         .execute(
-            &args(serde_json::json!({"path": path_str, "content": "data"})),
+            &args(serde_json::json!({
+                "path": file_path.to_str().unwrap(),
+                "offset": 9998,
+                "limit": 50,
+            })),
             &default_ctx(),
         )
         .await
         .unwrap();
-
-    assert_eq!(result["path"].as_str().unwrap(), path_str);
+    assert_eq!(tail["content"], "line 9998\nline 9999\n");
+    assert_eq!(tail["lines_returned"], 2);
+    assert_eq!(tail["truncated"], false);
+    assert!(tail["next_offset"].is_null());
+    assert_eq!(tail["total_lines"], 10_000);
 }
 
 #[tokio::test]
-async fn write_file_uses_session_cwd_when_present() {
+async fn read_file_caps_output_bytes_before_line_limit() {
     let dir = tempfile::tempdir().unwrap();
-    let ctx = session_ctx(dir.path());
+    let file_path = dir.path().join("wide.log");
+    let line = "y".repeat(1000);
+    let content: String = (0..500).map(|_| format!("{line}\n")).collect();
+    std::fs::write(&file_path, content).unwrap();
 
-    let tool = write_file_tool();
-    let result = tool
+    let result = read_file_tool()
         .execute(
-            &args(serde_json::json!({"path": "out.txt", "content": "ok"})),
-            &ctx,
+            &args(serde_json::json!({"path": file_path.to_str().unwrap(), "limit": 500})),
+            &default_ctx(),
         )
         .await
         .unwrap();
 
-    assert_eq!(result["success"], true);
-    let fs = ctx.session_fs.as_ref().unwrap();
-    assert_eq!(
-        fs.read(&LogicalPath::parse("work/out.txt").unwrap())
-            .unwrap(),
-        b"ok"
-    );
+    let returned = result["lines_returned"].as_u64().unwrap();
+    assert!(returned < 500);
+    assert_eq!(result["next_offset"].as_u64().unwrap(), returned);
+    assert!(result["content"].as_str().unwrap().len() <= READ_FILE_MAX_BYTES + 32);
 }
 
 #[tokio::test]
-async fn session_file_tools_reject_absolute_and_parent_paths() {
+async fn read_file_marks_overlong_lines() {
     let dir = tempfile::tempdir().unwrap();
-    let ctx = session_ctx(dir.path());
+    let file_path = dir.path().join("minified.js");
+    std::fs::write(&file_path, format!("{}\nshort\n", "z".repeat(10_000))).unwrap();
 
-    let read_result = read_file_tool()
-        .execute(&args(serde_json::json!({"path": "/etc/passwd"})), &ctx)
-        .await;
-    let write_result = write_file_tool()
+    let result = read_file_tool()
         .execute(
-            &args(serde_json::json!({"path": "../out.txt", "content": "no"})),
-            &ctx,
+            &args(serde_json::json!({"path": file_path.to_str().unwrap()})),
+            &default_ctx(),
         )
-        .await;
+        .await
+        .unwrap();
 
-    assert!(read_result.is_err());
-    assert!(write_result.is_err());
+    let content = result["content"].as_str().unwrap();
+    assert!(content.contains("... [line truncated: 8000 more bytes]\nshort\n"));
+    assert_eq!(result["truncated_lines"], 1);
+    assert_eq!(result["truncated"], true);
+    assert!(result["next_offset"].is_null());
 }
 
-// ── list_directory ─────────────────────────────────────────────────
-
 #[tokio::test]
-async fn list_directory_returns_files_and_dirs() {
+async fn read_file_refuses_binary_files() {
     let dir = tempfile::tempdir().unwrap();
-    std::fs::write(dir.path().join("file.txt"), "content").unwrap();
-    std::fs::create_dir(dir.path().join("subdir")).unwrap();
+    let file_path = dir.path().join("blob.bin");
+    let mut bytes = b"PK\x03\x04".to_vec();
+    bytes.extend(std::iter::repeat_n(0u8, 4096));
+    std::fs::write(&file_path, bytes).unwrap();
 
-    let tool = list_directory_tool();
-    let result = tool
+    let err = read_file_tool()
         .execute(
-            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
+            &args(serde_json::json!({"path": file_path.to_str().unwrap()})),
             &default_ctx(),
         )
         .await
-        .unwrap();
+        .unwrap_err();
 
-    let entries = result["entries"].as_array().unwrap();
-    assert_eq!(entries.len(), 2);
-
-    let names: Vec<&str> = entries
-        .iter()
-        .map(|e| e["name"].as_str().unwrap())
-        .collect();
-    assert!(names.contains(&"file.txt"));
-    assert!(names.contains(&"subdir"));
+    let message = err.to_string();
+    assert!(message.contains("binary file"));
+    assert!(message.contains("xxd"));
+}
 
-    let file_entry = entries.iter().find(|e| e["name"] == "file.txt").unwrap();
-    assert_eq!(file_entry["type"], "file");
-    assert_eq!(file_entry["size"], 7);
+#[tokio::test]
+async fn read_file_decodes_supported_encodings() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, label, bytes) in encoding_fixtures("café\nnaïve\n") {
+        let file_path = dir.path().join(name);
+        std::fs::write(&file_path, bytes).unwrap();
+
+        let result = read_file_tool()
+            .execute(
+                &args(serde_json::json!({"path": file_path.to_str().unwrap()})),
+                &default_ctx(),
+            )
+            .await
+            .unwrap();
 
-    let dir_entry = entries.iter().find(|e| e["name"] == "subdir").unwrap();
-    assert_eq!(dir_entry["type"], "dir");
+        assert_eq!(result["content"], "café\nnaïve\n", "{name}");
+        assert_eq!(result["encoding"], label, "{name}");
+        assert_eq!(result["total_lines"], 2, "{name}");
+    }
 }
 
 #[tokio::test]
-async fn list_directory_returns_sorted_entries() {
+async fn read_file_decodes_utf16_across_chunk_boundaries() {
     let dir = tempfile::tempdir().unwrap();
-    std::fs::write(dir.path().join("zebra.txt"), "").unwrap();
-    std::fs::write(dir.path().join("alpha.txt"), "").unwrap();
-    std::fs::write(dir.path().join("middle.txt"), "").unwrap();
-
-    let tool = list_directory_tool();
-    let result = tool
+    let file_path = dir.path().join("big.txt");
+    // 602 bytes per UTF-16 line, so the 64 KiB read boundary lands near
+    // line 108 and may split a surrogate pair.
+    let line = format!("{}\n", "é😀".repeat(100));
+    let (_, _, bytes) = encoding_fixtures(&line.repeat(200)).remove(2);
+    std::fs::write(&file_path, bytes).unwrap();
+
+    let result = read_file_tool()
         .execute(
-            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
+            &args(serde_json::json!({
+                "path": file_path.to_str().unwrap(),
+                "offset": 100,
+                "limit": 20,
+            })),
             &default_ctx(),
         )
         .await
         .unwrap();
 
-    let names: Vec<&str> = result["entries"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|e| e["name"].as_str().unwrap())
-        .collect();
-    assert_eq!(names, vec!["alpha.txt", "middle.txt", "zebra.txt"]);
+    assert_eq!(result["content"], line.repeat(20) + "... (truncated)");
+    assert_eq!(result["encoding"], "utf-16le");
+    assert_eq!(result["total_lines"], 200);
 }
 
 #[tokio::test]
-async fn list_directory_returns_count() {
+async fn read_file_decodes_session_files_in_latin1() {
     let dir = tempfile::tempdir().unwrap();
-    std::fs::write(dir.path().join("a.txt"), "").unwrap();
-    std::fs::write(dir.path().join("b.txt"), "").unwrap();
+    let ctx = session_ctx(dir.path());
+    let fs = ctx.session_fs.as_ref().unwrap();
+    fs.write(
+        &LogicalPath::parse("work/legacy.txt").unwrap(),
+        b"caf\xe9\n",
+    )
+    .unwrap();
 
-    let tool = list_directory_tool();
-    let result = tool
-        .execute(
-            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
-            &default_ctx(),
-        )
+    let result = read_file_tool()
+        .execute(&args(serde_json::json!({"path": "legacy.txt"})), &ctx)
         .await
         .unwrap();
 
-    assert_eq!(result["count"], 2);
+    assert_eq!(result["content"], "café\n");
+    assert_eq!(result["encoding"], "latin-1");
 }
 
 #[tokio::test]
-async fn list_directory_returns_error_for_nonexistent_path() {
-    let tool = list_directory_tool();
-    let result = tool
-        .execute(
-            &args(serde_json::json!({"path": "/tmp/roci_nonexistent_dir_xyz999"})),
-            &default_ctx(),
-        )
-        .await;
-
-    assert!(result.is_err());
+async fn read_file_rejects_invalid_paging_arguments() {
+    let tool = read_file_tool();
+    for bad in [
+        serde_json::json!({"path": "x", "limit": 0}),
+        serde_json::json!({"path": "x", "offset": -1}),
+        serde_json::json!({"path": "x", "offset": "ten"}),
+    ] {
+        let err = tool.execute(&args(bad), &default_ctx()).await.unwrap_err();
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+    }
 }
 
 #[tokio::test]
-async fn list_directory_uses_session_cwd_when_present() {
+async fn read_file_pages_session_files() {
     let dir = tempfile::tempdir().unwrap();
     let ctx = session_ctx(dir.path());
     let fs = ctx.session_fs.as_ref().unwrap();
-    fs.write(&LogicalPath::parse("work/a.txt").unwrap(), b"a")
-        .unwrap();
-    fs.write(&LogicalPath::parse("work/nested/b.txt").unwrap(), b"b")
-        .unwrap();
+    fs.write(
+        &LogicalPath::parse("work/notes.txt").unwrap(),
+        b"a\nb\nc\nd",
+    )
+    .unwrap();
 
-    let tool = list_directory_tool();
-    let result = tool
-        .execute(&args(serde_json::json!({"path": "."})), &ctx)
+    let result = read_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "notes.txt", "offset": 2, "limit": 5})),
+            &ctx,
+        )
         .await
         .unwrap();
 
-    let entries = result["entries"].as_array().unwrap();
-    assert_eq!(result["path"], "work");
-    assert_eq!(result["count"], 2);
-    assert!(entries
-        .iter()
-        .any(|entry| entry["name"] == "a.txt" && entry["type"] == "file"));
-    assert!(entries
-        .iter()
-        .any(|entry| entry["name"] == "nested" && entry["type"] == "dir"));
+    assert_eq!(result["content"], "c\nd");
+    assert_eq!(result["total_lines"], 4);
+    assert!(result["next_offset"].is_null());
 }
 
-// ── grep ───────────────────────────────────────────────────────────
+// ── write_file ─────────────────────────────────────────────────────
 
 #[tokio::test]
-async fn grep_finds_matching_lines_in_file() {
+async fn write_file_creates_file_with_content() {
     let dir = tempfile::tempdir().unwrap();
-    std::fs::write(
-        dir.path().join("test.txt"),
-        "hello world\nfoo bar\nhello again\n",
-    )
-    .unwrap();
+    let file_path = dir.path().join("out.txt");
 
-    let tool = grep_tool();
+    let tool = write_file_tool();
     let result = tool
         .execute(
             &args(serde_json::json!({
-                "pattern": "hello",
-                "path": dir.path().to_str().unwrap(),
+                "path": file_path.to_str().unwrap(),
+                "content": "hello roci",
             })),
             &default_ctx(),
         )
         .await
         .unwrap();
 
-    let output = result["output"].as_str().unwrap();
-    assert!(output.contains("hello world"));
-    assert!(output.contains("hello again"));
-    assert_eq!(result["exit_code"], 0);
+    assert_eq!(result["success"], true);
+    assert_eq!(result["bytes_written"], 10);
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "hello roci");
 }
 
 #[tokio::test]
-async fn grep_returns_exit_code_one_for_no_matches() {
+async fn write_file_creates_parent_directories() {
     let dir = tempfile::tempdir().unwrap();
-    std::fs::write(dir.path().join("empty_match.txt"), "nothing here\n").unwrap();
+    let file_path = dir.path().join("a").join("b").join("c.txt");
 
-    let tool = grep_tool();
+    let tool = write_file_tool();
     let result = tool
         .execute(
             &args(serde_json::json!({
-                "pattern": "ZZZZNOTFOUND",
-                "path": dir.path().to_str().unwrap(),
+                "path": file_path.to_str().unwrap(),
+                "content": "nested",
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["success"], true);
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "nested");
+}
+
+#[tokio::test]
+async fn write_file_returns_path_in_response() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("file.txt");
+    let path_str = file_path.to_str().unwrap();
+
+    let tool = write_file_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": path_str, "content": "data"})),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["path"].as_str().unwrap(), path_str);
+}
+
+#[tokio::test]
+async fn write_file_preserves_existing_encoding_when_overwriting() {
+    let dir = tempfile::tempdir().unwrap();
+    let expected = encoding_fixtures("résumé\n");
+    for (name, label, bytes) in encoding_fixtures("café\n") {
+        let file_path = dir.path().join(name);
+        std::fs::write(&file_path, bytes).unwrap();
+
+        let result = write_file_tool()
+            .execute(
+                &args(serde_json::json!({
+                    "path": file_path.to_str().unwrap(),
+                    "content": "résumé\n",
+                })),
+                &default_ctx(),
+            )
+            .await
+            .unwrap();
+
+        let (_, _, expected_bytes) = expected
+            .iter()
+            .find(|(fixture, _, _)| *fixture == name)
+            .unwrap();
+        assert_eq!(result["encoding"], label, "{name}");
+        assert_eq!(
+            &std::fs::read(&file_path).unwrap(),
+            expected_bytes,
+            "{name}"
+        );
+
+        let reread = read_file_tool()
+            .execute(
+                &args(serde_json::json!({"path": file_path.to_str().unwrap()})),
+                &default_ctx(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reread["content"], "résumé\n", "{name}");
+        assert_eq!(reread["encoding"], label, "{name}");
+    }
+}
+
+#[tokio::test]
+async fn write_file_honors_explicit_encoding() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("legacy.txt");
+    std::fs::write(&file_path, "plain utf-8\n").unwrap();
+
+    let result = write_file_tool()
+        .execute(
+            &args(serde_json::json!({
+                "path": file_path.to_str().unwrap(),
+                "content": "café",
+                "encoding": "latin-1",
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["encoding"], "latin-1");
+    assert_eq!(result["bytes_written"], 4);
+    assert_eq!(std::fs::read(&file_path).unwrap(), b"caf\xe9");
+}
+
+#[tokio::test]
+async fn write_file_rejects_unencodable_content_and_unknown_encodings() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("out.txt");
+    let tool = write_file_tool();
+
+    for (content, encoding) in [("snow ☃", "latin-1"), ("ok", "ebcdic")] {
+        let err = tool
+            .execute(
+                &args(serde_json::json!({
+                    "path": file_path.to_str().unwrap(),
+                    "content": content,
+                    "encoding": encoding,
+                })),
+                &default_ctx(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RociError::InvalidArgument(_)), "{encoding}");
+    }
+    assert!(!file_path.exists());
+}
+
+#[tokio::test]
+async fn write_file_uses_session_cwd_when_present() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = session_ctx(dir.path());
+
+    let tool = write_file_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": "out.txt", "content": "ok"})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["success"], true);
+    let fs = ctx.session_fs.as_ref().unwrap();
+    assert_eq!(
+        fs.read(&LogicalPath::parse("work/out.txt").unwrap())
+            .unwrap(),
+        b"ok"
+    );
+}
+
+#[tokio::test]
+async fn session_file_tools_reject_absolute_and_parent_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = session_ctx(dir.path());
+
+    let read_result = read_file_tool()
+        .execute(&args(serde_json::json!({"path": "/etc/passwd"})), &ctx)
+        .await;
+    let write_result = write_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "../out.txt", "content": "no"})),
+            &ctx,
+        )
+        .await;
+
+    assert!(read_result.is_err());
+    assert!(write_result.is_err());
+}
+
+fn workspace_ctx(root: &Path) -> ToolExecutionContext {
+    ToolExecutionContext {
+        workspace_root: Some(root.canonicalize().unwrap()),
+        ..ToolExecutionContext::default()
+    }
+}
+
+#[tokio::test]
+async fn write_file_preview_diffs_current_against_proposed_without_writing() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "one\ntwo\n").unwrap();
+    let ctx = workspace_ctx(dir.path());
+
+    let previews = write_file_tool()
+        .approval_preview(
+            &args(serde_json::json!({"path": "notes.txt", "content": "one\n2\n"})),
+            &ctx,
+        )
+        .await;
+    let created = write_file_tool()
+        .approval_preview(
+            &args(serde_json::json!({"path": "sub/new.txt", "content": "hi\n"})),
+            &ctx,
+        )
+        .await;
+
+    assert_eq!(
+        previews,
+        vec![ChangePreview::Text {
+            path: "notes.txt".to_string(),
+            diff: "--- a/notes.txt\n+++ b/notes.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n".to_string(),
+            lines_added: 1,
+            lines_removed: 1,
+            truncated: false,
+        }]
+    );
+    assert!(matches!(
+        &created[..],
+        [ChangePreview::Text { diff, .. }] if diff.starts_with("--- /dev/null\n+++ b/sub/new.txt\n")
+    ));
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+        "one\ntwo\n"
+    );
+    assert!(!dir.path().join("sub").exists());
+}
+
+#[tokio::test]
+async fn write_file_preview_summarizes_binary_targets() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("blob.bin"), [0u8, 159, 146, 150]).unwrap();
+
+    let previews = write_file_tool()
+        .approval_preview(
+            &args(serde_json::json!({"path": "blob.bin", "content": "text"})),
+            &workspace_ctx(dir.path()),
+        )
+        .await;
+
+    assert_eq!(
+        previews,
+        vec![ChangePreview::Binary {
+            path: "blob.bin".to_string(),
+            old_size: Some(4),
+            new_size: Some(4),
+            old_hash: Some(content_hash(&[0, 159, 146, 150])),
+            new_hash: Some(content_hash(b"text")),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn write_file_preview_is_empty_for_paths_the_write_would_reject() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let previews = write_file_tool()
+        .approval_preview(
+            &args(serde_json::json!({"path": "../escape.txt", "content": "x"})),
+            &workspace_ctx(dir.path()),
+        )
+        .await;
+
+    assert!(previews.is_empty());
+}
+
+// ── scratch paths ──────────────────────────────────────────────────
+
+fn scratch_ctx(workspace: &Path, scratch: &Path) -> ToolExecutionContext {
+    ToolExecutionContext {
+        scratch: Some(ScratchDir::new(scratch.to_path_buf())),
+        changes: Some(ChangeLog::new()),
+        ..workspace_ctx(workspace)
+    }
+}
+
+#[tokio::test]
+async fn scratch_paths_resolve_to_the_scratch_dir_outside_the_workspace() {
+    let workspace = tempfile::tempdir().unwrap();
+    let temp = tempfile::tempdir().unwrap();
+    let scratch_root = temp.path().join("run");
+    let ctx = scratch_ctx(workspace.path(), &scratch_root);
+
+    write_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "scratch://notes/a.txt", "content": "draft"})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    let by_scheme = read_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "scratch://notes/a.txt"})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    let absolute = scratch_root.join("notes/a.txt");
+    let by_absolute_path = read_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": absolute.to_str().unwrap()})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read_to_string(&absolute).unwrap(), "draft");
+    assert_eq!(by_scheme["content"], "draft");
+    assert_eq!(by_absolute_path["content"], "draft");
+    assert!(!workspace.path().join("notes").exists());
+    assert!(ctx.changes.as_ref().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn scratch_paths_cannot_escape_or_run_without_a_scratch_dir() {
+    let workspace = tempfile::tempdir().unwrap();
+    let temp = tempfile::tempdir().unwrap();
+    let ctx = scratch_ctx(workspace.path(), &temp.path().join("run"));
+
+    let escape = write_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "scratch://../escape.txt", "content": "x"})),
+            &ctx,
+        )
+        .await;
+    let outside_run = write_file_tool()
+        .execute(
+            &args(serde_json::json!({"path": "scratch://a.txt", "content": "x"})),
+            &workspace_ctx(workspace.path()),
+        )
+        .await;
+
+    assert!(matches!(escape, Err(RociError::InvalidArgument(_))));
+    assert!(!temp.path().join("escape.txt").exists());
+    assert!(
+        matches!(outside_run, Err(RociError::ToolExecution { message, .. }) if message.contains("require an agent run"))
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn shell_exposes_the_scratch_dir_as_roci_run_tmp() {
+    let workspace = tempfile::tempdir().unwrap();
+    let temp = tempfile::tempdir().unwrap();
+    let scratch_root = temp.path().join("run");
+    let ctx = scratch_ctx(workspace.path(), &scratch_root);
+
+    let result = shell_tool()
+        .execute(
+            &args(serde_json::json!({"command": "echo hi > \"$ROCI_RUN_TMP/out.txt\""})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["exit_code"], 0, "{result}");
+    assert_eq!(
+        std::fs::read_to_string(scratch_root.join("out.txt")).unwrap(),
+        "hi\n"
+    );
+}
+
+// ── diff_files ─────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn diff_files_compares_two_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("old.txt"), "a\nb\nc\n").unwrap();
+    std::fs::write(dir.path().join("new.txt"), "a\nB\nc\n").unwrap();
+    let ctx = workspace_ctx(dir.path());
+
+    let result = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({"path": "old.txt", "other_path": "new.txt"})),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    let root = ctx.workspace_root.as_ref().unwrap();
+    let diff = result["diff"].as_str().unwrap();
+    assert_eq!(
+        diff,
+        format!(
+            "--- {}\n+++ {}\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n",
+            root.join("old.txt").display(),
+            root.join("new.txt").display()
+        )
+    );
+    assert_eq!(result["identical"], false);
+    assert_eq!(result["lines_added"], 1);
+    assert_eq!(result["lines_removed"], 1);
+}
+
+#[tokio::test]
+async fn diff_files_against_content_honors_context_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("f.txt"), "1\n2\n3\n4\n5\n").unwrap();
+
+    let result = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({
+                "path": "f.txt",
+                "content": "1\n2\nthree\n4\n5\n",
+                "context_lines": 0,
+            })),
+            &workspace_ctx(dir.path()),
+        )
+        .await
+        .unwrap();
+
+    assert!(result["diff"]
+        .as_str()
+        .unwrap()
+        .ends_with("+++ proposed\n@@ -3 +3 @@\n-3\n+three\n"));
+}
+
+#[tokio::test]
+async fn diff_files_reports_identical_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("f.txt"), "same\n").unwrap();
+
+    let result = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({"path": "f.txt", "content": "same\n"})),
+            &workspace_ctx(dir.path()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["identical"], true);
+    assert_eq!(result["diff"], "");
+}
+
+#[tokio::test]
+async fn diff_files_summarizes_binary_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.bin"), [0u8, 159, 146, 150]).unwrap();
+    std::fs::write(dir.path().join("b.bin"), [0u8, 159, 146, 150, 0]).unwrap();
+
+    let result = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({"path": "a.bin", "other_path": "b.bin"})),
+            &workspace_ctx(dir.path()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["binary"], true);
+    assert_eq!(result["identical"], false);
+    assert_eq!(result["old_size"], 4);
+    assert_eq!(result["new_size"], 5);
+    assert_eq!(result["new_hash"], content_hash(&[0, 159, 146, 150, 0]));
+    assert!(result.get("diff").is_none());
+}
+
+#[tokio::test]
+async fn diff_files_requires_exactly_one_comparison_target() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("f.txt"), "x\n").unwrap();
+    let ctx = workspace_ctx(dir.path());
+
+    for extra in [
+        serde_json::json!({}),
+        serde_json::json!({"other_path": "f.txt", "content": "y"}),
+    ] {
+        let mut call = serde_json::json!({"path": "f.txt"});
+        call.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        let err = diff_files_tool()
+            .execute(&args(call), &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RociError::InvalidArgument(_)));
+    }
+}
+
+#[tokio::test]
+async fn diff_files_stays_inside_the_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("f.txt"), "x\n").unwrap();
+
+    let workspace = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({"path": "f.txt", "other_path": "../outside.txt"})),
+            &workspace_ctx(dir.path()),
+        )
+        .await;
+    let session = diff_files_tool()
+        .execute(
+            &args(serde_json::json!({"path": "/etc/passwd", "content": "x"})),
+            &session_ctx(dir.path()),
+        )
+        .await;
+
+    assert!(workspace.is_err());
+    assert!(session.is_err());
+}
+
+// ── list_directory ─────────────────────────────────────────────────
+
+#[tokio::test]
+async fn list_directory_returns_files_and_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("file.txt"), "content").unwrap();
+    std::fs::create_dir(dir.path().join("subdir")).unwrap();
+
+    let tool = list_directory_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    let entries = result["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let names: Vec<&str> = entries
+        .iter()
+        .map(|e| e["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"file.txt"));
+    assert!(names.contains(&"subdir"));
+
+    let file_entry = entries.iter().find(|e| e["name"] == "file.txt").unwrap();
+    assert_eq!(file_entry["type"], "file");
+    assert_eq!(file_entry["size"], 7);
+
+    let dir_entry = entries.iter().find(|e| e["name"] == "subdir").unwrap();
+    assert_eq!(dir_entry["type"], "dir");
+}
+
+#[tokio::test]
+async fn list_directory_returns_sorted_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("zebra.txt"), "").unwrap();
+    std::fs::write(dir.path().join("alpha.txt"), "").unwrap();
+    std::fs::write(dir.path().join("middle.txt"), "").unwrap();
+
+    let tool = list_directory_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = result["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["alpha.txt", "middle.txt", "zebra.txt"]);
+}
+
+#[tokio::test]
+async fn list_directory_returns_count() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), "").unwrap();
+    std::fs::write(dir.path().join("b.txt"), "").unwrap();
+
+    let tool = list_directory_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": dir.path().to_str().unwrap()})),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["count"], 2);
+}
+
+#[tokio::test]
+async fn list_directory_returns_error_for_nonexistent_path() {
+    let tool = list_directory_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"path": "/tmp/roci_nonexistent_dir_xyz999"})),
+            &default_ctx(),
+        )
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn list_directory_uses_session_cwd_when_present() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = session_ctx(dir.path());
+    let fs = ctx.session_fs.as_ref().unwrap();
+    fs.write(&LogicalPath::parse("work/a.txt").unwrap(), b"a")
+        .unwrap();
+    fs.write(&LogicalPath::parse("work/nested/b.txt").unwrap(), b"b")
+        .unwrap();
+
+    let tool = list_directory_tool();
+    let result = tool
+        .execute(&args(serde_json::json!({"path": "."})), &ctx)
+        .await
+        .unwrap();
+
+    let entries = result["entries"].as_array().unwrap();
+    assert_eq!(result["path"], "work");
+    assert_eq!(result["count"], 2);
+    assert!(entries
+        .iter()
+        .any(|entry| entry["name"] == "a.txt" && entry["type"] == "file"));
+    assert!(entries
+        .iter()
+        .any(|entry| entry["name"] == "nested" && entry["type"] == "dir"));
+}
+
+// ── grep ───────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn grep_finds_matching_lines_in_file() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("test.txt"),
+        "hello world\nfoo bar\nhello again\n",
+    )
+    .unwrap();
+
+    let tool = grep_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({
+                "pattern": "hello",
+                "path": dir.path().to_str().unwrap(),
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    let output = result["output"].as_str().unwrap();
+    assert!(output.contains("hello world"));
+    assert!(output.contains("hello again"));
+    assert_eq!(result["exit_code"], 0);
+}
+
+#[tokio::test]
+async fn grep_returns_exit_code_one_for_no_matches() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("empty_match.txt"), "nothing here\n").unwrap();
+
+    let tool = grep_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({
+                "pattern": "ZZZZNOTFOUND",
+                "path": dir.path().to_str().unwrap(),
             })),
             &default_ctx(),
         )
@@ -762,20 +1570,107 @@ async fn grep_keeps_host_absolute_paths_without_session_context() {
     let file_path = dir.path().join("host.txt");
     std::fs::write(&file_path, "host needle\n").unwrap();
 
-    let tool = grep_tool();
-    let result = tool
-        .execute(
-            &args(serde_json::json!({
-                "pattern": "needle",
-                "path": file_path.to_str().unwrap(),
-            })),
-            &default_ctx(),
-        )
+    let tool = grep_tool();
+    let result = tool
+        .execute(
+            &args(serde_json::json!({
+                "pattern": "needle",
+                "path": file_path.to_str().unwrap(),
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["exit_code"], 0);
+    assert!(result["output"].as_str().unwrap().contains("host needle"));
+}
+
+#[tokio::test]
+async fn grep_finds_hits_in_every_supported_encoding() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, _, bytes) in encoding_fixtures("first line\nnaïve needle\n") {
+        std::fs::write(dir.path().join(name), bytes).unwrap();
+    }
+
+    let result = grep_tool()
+        .execute(
+            &args(serde_json::json!({
+                "pattern": "naïve needle",
+                "path": dir.path().to_str().unwrap(),
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["exit_code"], 0);
+    let output = result["output"].as_str().unwrap();
+    for name in ["plain.txt", "bom.txt", "wide.txt", "legacy.txt"] {
+        assert!(
+            output.contains(&format!("{name}:2:naïve needle")),
+            "{name} missing from {output}"
+        );
+    }
+    let decoded = result["decoded_files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["encoding"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, vec!["utf-8-bom", "latin-1", "utf-16le"]);
+    assert_eq!(result["skipped_count"], 0);
+}
+
+#[tokio::test]
+async fn grep_skips_undecodable_files_with_a_note() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("blob.bin"), b"needle\x00\x00\x00\x01").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "needle\n").unwrap();
+
+    let result = grep_tool()
+        .execute(
+            &args(serde_json::json!({
+                "pattern": "needle",
+                "path": dir.path().to_str().unwrap(),
+            })),
+            &default_ctx(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["exit_code"], 0);
+    assert!(result["output"]
+        .as_str()
+        .unwrap()
+        .contains("notes.txt:1:needle"));
+    assert_eq!(result["skipped_count"], 1);
+    let skipped = &result["skipped_files"][0];
+    assert!(skipped["path"].as_str().unwrap().ends_with("blob.bin"));
+    assert_eq!(skipped["reason"], "binary or unsupported encoding");
+}
+
+#[tokio::test]
+async fn session_grep_decodes_and_skips_files_by_encoding() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = session_ctx(dir.path());
+    let fs = ctx.session_fs.as_ref().unwrap();
+    for (name, _, bytes) in encoding_fixtures("naïve needle\n") {
+        fs.write(&LogicalPath::parse(format!("work/{name}")).unwrap(), &bytes)
+            .unwrap();
+    }
+    fs.write(&LogicalPath::parse("work/blob.bin").unwrap(), b"\x00\x00")
+        .unwrap();
+
+    let result = grep_tool()
+        .execute(&args(serde_json::json!({"pattern": "naïve"})), &ctx)
         .await
         .unwrap();
 
     assert_eq!(result["exit_code"], 0);
-    assert!(result["output"].as_str().unwrap().contains("host needle"));
+    let output = result["output"].as_str().unwrap();
+    assert_eq!(output.matches("naïve needle").count(), 4, "{output}");
+    assert_eq!(result["skipped_count"], 1);
 }
 
 #[cfg(unix)]
@@ -1006,3 +1901,645 @@ async fn ask_user_surfaces_interactive_prompt_unavailable_error() {
     let err = result.expect_err("interactive prompt failure should surface");
     assert!(err.to_string().contains("interactive prompt unavailable"));
 }
+
+// ── update_plan ─────────────────────────────────────────────────────
+
+fn plan_ctx() -> (ToolExecutionContext, roci::tools::PlanStore) {
+    let store = roci::tools::PlanStore::new();
+    let ctx = ToolExecutionContext {
+        plan: Some(store.clone()),
+        ..default_ctx()
+    };
+    (ctx, store)
+}
+
+#[tokio::test]
+async fn update_plan_stores_plan_and_reports_progress() {
+    let tool = update_plan_tool();
+    let (ctx, store) = plan_ctx();
+
+    let result = tool
+        .execute(
+            &args(serde_json::json!({
+                "plan": [
+                    {"step": "read code", "status": "in_progress"},
+                    {"step": "write fix", "status": "pending"}
+                ]
+            })),
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["total_steps"], 2);
+    assert_eq!(result["completed_steps"], 0);
+    assert_eq!(result["in_progress"], "read code");
+    assert_eq!(result["plan"][1]["status"], "pending");
+    assert_eq!(store.revision(), 1);
+    assert_eq!(store.steps().len(), 2);
+}
+
+#[tokio::test]
+async fn update_plan_rejects_invalid_transitions_with_correctable_errors() {
+    let tool = update_plan_tool();
+    let (ctx, store) = plan_ctx();
+
+    let two_in_progress = tool
+        .execute(
+            &args(serde_json::json!({
+                "plan": [
+                    {"step": "a", "status": "in_progress"},
+                    {"step": "b", "status": "in_progress"}
+                ]
+            })),
+            &ctx,
+        )
+        .await
+        .expect_err("two in_progress steps are rejected");
+    assert!(two_in_progress
+        .to_string()
+        .contains("only one step may be in_progress"));
+
+    let never_started = tool
+        .execute(
+            &args(serde_json::json!({
+                "plan": [{"step": "a", "status": "completed"}]
+            })),
+            &ctx,
+        )
+        .await
+        .expect_err("completing an unstarted step is rejected");
+    assert!(never_started.to_string().contains("never started"));
+    assert_eq!(store.revision(), 0);
+}
+
+#[tokio::test]
+async fn update_plan_rejects_malformed_steps() {
+    let tool = update_plan_tool();
+    let (ctx, _store) = plan_ctx();
+
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"plan": [{"step": "a", "status": "done"}]})),
+            &ctx,
+        )
+        .await;
+
+    assert!(matches!(result, Err(RociError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn update_plan_requires_run_plan_store() {
+    let tool = update_plan_tool();
+
+    let result = tool
+        .execute(
+            &args(serde_json::json!({"plan": [{"step": "a", "status": "pending"}]})),
+            &default_ctx(),
+        )
+        .await;
+
+    assert!(matches!(result, Err(RociError::ToolExecution { .. })));
+}
+
+// ── remember / recall ───────────────────────────────────────────────
+
+fn memory_ctx(
+    memory: &Arc<roci::memory::FileMemoryStore>,
+    workspace: &Path,
+    run_id: uuid::Uuid,
+) -> ToolExecutionContext {
+    ToolExecutionContext {
+        memory: Some(memory.clone()),
+        workspace_root: Some(workspace.to_path_buf()),
+        run_id: Some(run_id),
+        ..default_ctx()
+    }
+}
+
+#[tokio::test]
+async fn recall_returns_memories_from_an_earlier_run_with_provenance() {
+    let dir = tempfile::tempdir().unwrap();
+    let workspace = dir.path().join("project");
+    let first_run = uuid::Uuid::new_v4();
+    let memory = Arc::new(roci::memory::FileMemoryStore::in_dir(dir.path()));
+    remember_tool()
+        .execute(
+            &args(serde_json::json!({"key": "deploy", "value": "deploy command is make ship"})),
+            &memory_ctx(&memory, &workspace, first_run),
+        )
+        .await
+        .unwrap();
+
+    let memory = Arc::new(roci::memory::FileMemoryStore::in_dir(dir.path()));
+    let result = recall_tool()
+        .execute(
+            &args(serde_json::json!({"query": "deploy"})),
+            &memory_ctx(&memory, &workspace, uuid::Uuid::new_v4()),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["count"], 1);
+    assert_eq!(
+        result["memories"][0]["value"],
+        "deploy command is make ship"
+    );
+    assert_eq!(result["memories"][0]["run_id"], first_run.to_string());
+    assert!(result["memories"][0]["stored_at"].is_string());
+}
+
+#[tokio::test]
+async fn recall_does_not_leak_memories_across_projects() {
+    let dir = tempfile::tempdir().unwrap();
+    let memory = Arc::new(roci::memory::FileMemoryStore::in_dir(dir.path()));
+    let run_id = uuid::Uuid::new_v4();
+    remember_tool()
+        .execute(
+            &args(serde_json::json!({"key": "indentation", "value": "user prefers tabs"})),
+            &memory_ctx(&memory, &dir.path().join("a"), run_id),
+        )
+        .await
+        .unwrap();
+
+    let result = recall_tool()
+        .execute(
+            &args(serde_json::json!({"query": "tabs"})),
+            &memory_ctx(&memory, &dir.path().join("b"), run_id),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["count"], 0);
+}
+
+#[tokio::test]
+async fn memory_tools_require_a_memory_store() {
+    let err = recall_tool()
+        .execute(&args(serde_json::json!({"query": "tabs"})), &default_ctx())
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("no memory store"));
+}
+// ── fetch_url ───────────────────────────────────────────────────────
+
+const FETCH_URL_PAGE: &str = r#"<!doctype html>
+<html>
+<head><title> Widget   Guide </title><style>body { color: red; }</style></head>
+<body>
+  <nav><a href="/">Home</a> | <a href="/blog">Blog</a></nav>
+  <main id="content">
+    <h1>Installing widgets</h1>
+    <p>Read the <a href="/docs/setup">setup notes</a> <em>before</em> you start.</p>
+    <pre><code class="language-sh">cargo add widget
+cargo build</code></pre>
+    <ul><li>fast</li><li>small</li></ul>
+  </main>
+  <script>alert("tracking");</script>
+</body>
+</html>"#;
+
+async fn fetch(
+    options: FetchUrlOptions,
+    json: serde_json::Value,
+) -> Result<serde_json::Value, RociError> {
+    fetch_url_tool_with_options(options)
+        .execute(&args(json), &default_ctx())
+        .await
+}
+
+#[tokio::test]
+async fn fetch_url_follows_redirects_and_converts_html_to_markdown() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(301).insert_header("location", "/guide"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/guide"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(FETCH_URL_PAGE, "text/html"))
+        .mount(&server)
+        .await;
+
+    let result = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/old", server.uri())}),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(result["final_url"], format!("{}/guide", server.uri()));
+    assert_eq!(result["title"], "Widget Guide");
+    assert_eq!(result["content_type"], "text/html");
+    assert_eq!(
+        result["content"],
+        format!(
+            "# Installing widgets\n\n\
+             Read the [setup notes]({}/docs/setup) *before* you start.\n\n\
+             ```sh\ncargo add widget\ncargo build\n```\n\n\
+             - fast\n- small",
+            server.uri()
+        )
+    );
+}
+
+#[tokio::test]
+async fn fetch_url_selector_extracts_matching_fragment() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/guide"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(FETCH_URL_PAGE, "text/html"))
+        .mount(&server)
+        .await;
+
+    let result = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/guide", server.uri()), "selector": "main ul"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["content"], "- fast\n- small");
+
+    let err = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/guide", server.uri()), "selector": "table"}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("matched no content"), "{err}");
+}
+
+#[tokio::test]
+async fn fetch_url_enforces_size_cap() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/big.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("x".repeat(4096), "text/plain"))
+        .mount(&server)
+        .await;
+
+    let err = fetch(
+        FetchUrlOptions::default().with_max_bytes(1024),
+        serde_json::json!({"url": format!("{}/big.txt", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("exceeds the 1024-byte"), "{err}");
+
+    let result = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/big.txt", server.uri())}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["content"].as_str().unwrap().len(), 4096);
+}
+
+#[tokio::test]
+async fn fetch_url_rejects_denied_hosts_including_redirect_targets() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    let port = server.address().port();
+    Mock::given(method("GET"))
+        .and(path("/hop"))
+        .respond_with(
+            ResponseTemplate::new(302)
+                .insert_header("location", format!("http://localhost:{port}/guide")),
+        )
+        .mount(&server)
+        .await;
+
+    let err = fetch(
+        FetchUrlOptions::default().with_denied_hosts(["127.0.0.1"]),
+        serde_json::json!({"url": format!("{}/hop", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("denied"), "{err}");
+
+    let err = fetch(
+        FetchUrlOptions::default().with_denied_hosts(["localhost"]),
+        serde_json::json!({"url": format!("{}/hop", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("host 'localhost' is denied"),
+        "{err}"
+    );
+
+    let err = fetch(
+        FetchUrlOptions::default().with_allowed_hosts(["docs.rs"]),
+        serde_json::json!({"url": format!("{}/hop", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("allowlist"), "{err}");
+}
+
+#[tokio::test]
+async fn fetch_url_returns_json_and_refuses_binaries() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"ok":true}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/logo.png"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(vec![0x89, b'P', b'N', b'G'], "image/png"),
+        )
+        .mount(&server)
+        .await;
+
+    let result = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/data", server.uri())}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["content"], r#"{"ok":true}"#);
+    assert!(result["title"].is_null());
+
+    let err = fetch(
+        FetchUrlOptions::default(),
+        serde_json::json!({"url": format!("{}/logo.png", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("refusing content type 'image/png'"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn fetch_url_honours_robots_txt() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw("User-agent: *\nDisallow: /private\n", "text/plain"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/private/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("secret", "text/plain"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/private/page", server.uri());
+    let err = fetch(FetchUrlOptions::default(), serde_json::json!({"url": url}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("robots.txt"), "{err}");
+
+    let result = fetch(
+        FetchUrlOptions::default().with_robots_txt(false),
+        serde_json::json!({"url": url}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["content"], "secret");
+}
+
+#[tokio::test]
+async fn fetch_url_sends_with_the_shared_http_options() {
+    use roci::provider::http::HttpOptions;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .and(header("user-agent", "host-app/2.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("hello", "text/plain"))
+        .mount(&server)
+        .await;
+
+    let options = FetchUrlOptions::default()
+        .with_http_options(HttpOptions::new().with_user_agent("host-app/2.0"));
+    let result = fetch(
+        options,
+        serde_json::json!({"url": format!("{}/page", server.uri())}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["content"], "hello");
+
+    let err = fetch(
+        FetchUrlOptions::default().with_http_options(HttpOptions::new().with_proxy("not a url")),
+        serde_json::json!({"url": format!("{}/page", server.uri())}),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("invalid HTTP proxy"), "{err}");
+}
+
+#[test]
+fn robots_txt_prefers_named_groups_and_longest_rules() {
+    use super::fetch_url::robots_txt_allows;
+
+    let robots =
+        "User-agent: *\nDisallow: /\n\nUser-agent: roci\nDisallow: /tmp/\nAllow: /tmp/public$\n";
+    assert!(robots_txt_allows(robots, "roci", "/docs"));
+    assert!(!robots_txt_allows(robots, "roci", "/tmp/cache"));
+    assert!(robots_txt_allows(robots, "roci", "/tmp/public"));
+    assert!(!robots_txt_allows(robots, "other-bot", "/docs"));
+    assert!(robots_txt_allows("", "roci", "/anything"));
+}
+
+#[test]
+fn fetch_url_is_read_only_and_requires_approval() {
+    let tool = fetch_url_tool();
+    let plan = tool.safety(&args(serde_json::json!({"url": "https://example.com"})));
+
+    assert!(plan.read_only);
+    assert!(!plan.approval.auto_accept_under_ask);
+    assert_eq!(plan.approval.kind, ToolSafetyKind::Read);
+    assert!(plan.validate().is_ok());
+}
+
+// ── tool overrides ──────────────────────────────────────────────────
+
+fn definition(tools: &[Arc<dyn Tool>], name: &str) -> roci::provider::ToolDefinition {
+    let tool = tools
+        .iter()
+        .find(|tool| tool.name() == name)
+        .unwrap_or_else(|| panic!("no tool named {name}"));
+    roci::provider::ToolDefinition::new(
+        tool.name(),
+        tool.prompt(),
+        tool.parameters().schema.clone(),
+    )
+}
+
+#[test]
+fn overrides_replace_the_tool_description() {
+    let overrides = ToolOverrides::new().with_tool(
+        "read_file",
+        ToolOverride::new().with_description("Read a file from the repo"),
+    );
+
+    let tools = all_tools_with_overrides(overrides).unwrap();
+
+    assert_eq!(tools.len(), 8);
+    let read_file = definition(&tools, "read_file");
+    assert_eq!(read_file.description, "Read a file from the repo");
+    assert_eq!(
+        read_file.parameters,
+        read_file_tool().parameters().schema,
+        "parameters are untouched"
+    );
+    assert_eq!(
+        definition(&tools, "shell").description,
+        shell_tool().prompt()
+    );
+}
+
+#[test]
+fn overrides_replace_nested_parameter_descriptions() {
+    let overrides = ToolOverrides::new().with_tool(
+        "update_plan",
+        ToolOverride::new()
+            .with_parameter_description("explanation", "Why the plan changed")
+            .with_parameter_description("plan.step", "One short imperative step"),
+    );
+
+    let tools = all_tools_with_overrides(overrides).unwrap();
+
+    let schema = definition(&tools, "update_plan").parameters;
+    assert_eq!(
+        schema["properties"]["explanation"]["description"],
+        "Why the plan changed"
+    );
+    assert_eq!(
+        schema["properties"]["plan"]["items"]["properties"]["step"]["description"],
+        "One short imperative step"
+    );
+    assert_eq!(
+        schema["properties"]["plan"]["description"],
+        "The complete plan, in order"
+    );
+    assert_eq!(schema["required"], serde_json::json!(["plan"]));
+}
+
+#[tokio::test]
+async fn renamed_tool_round_trips_a_stubbed_call() {
+    let overrides =
+        ToolOverrides::new().with_tool("update_plan", ToolOverride::new().with_name("set_plan"));
+    let tools = all_tools_with_overrides(overrides.clone()).unwrap();
+    assert!(tools.iter().all(|tool| tool.name() != "update_plan"));
+    assert_eq!(definition(&tools, "set_plan").name, "set_plan");
+
+    // The model calls the exposed name; dispatch finds the original executor.
+    let (ctx, store) = plan_ctx();
+    let tool = tools.iter().find(|tool| tool.name() == "set_plan").unwrap();
+    assert_eq!(tool.aliases(), ["update_plan"]);
+    let result = tool
+        .execute(
+            &args(serde_json::json!({
+                "plan": [{"step": "rename tools", "status": "in_progress"}]
+            })),
+            &ctx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["in_progress"], "rename tools");
+    assert_eq!(store.revision(), 1);
+
+    // Errors name the tool as the model called it.
+    let error = tool
+        .execute(&args(serde_json::json!({"plan": []})), &default_ctx())
+        .await
+        .expect_err("a run without a plan store is rejected");
+    assert!(
+        matches!(&error, RociError::ToolExecution { tool_name, .. } if tool_name == "set_plan"),
+        "{error:?}"
+    );
+
+    // Visibility policies accept either name.
+    let catalog = tool_catalog_with_overrides(overrides).unwrap();
+    let visible = catalog.resolve(&roci::tools::ToolVisibilityPolicy::allow_only([
+        "update_plan",
+    ]));
+    assert_eq!(visible.len(), 1);
+    assert_eq!(visible[0].name(), "set_plan");
+}
+
+#[test]
+fn overrides_reject_unknown_tools_fields_and_duplicate_names() {
+    let unknown_tool = all_tools_with_overrides(
+        ToolOverrides::new().with_tool("edit_file", ToolOverride::new().with_description("x")),
+    )
+    .err()
+    .expect("unknown tool is rejected");
+    let message = unknown_tool.to_string();
+    assert!(message.contains("unknown tool 'edit_file'"), "{message}");
+    assert!(message.contains("read_file"), "{message}");
+
+    let unknown_field = all_tools_with_overrides(ToolOverrides::new().with_tool(
+        "update_plan",
+        ToolOverride::new().with_parameter_description("plan.title", "x"),
+    ))
+    .err()
+    .expect("unknown field is rejected");
+    let message = unknown_field.to_string();
+    assert!(
+        message.contains("tool_overrides.update_plan.parameters: unknown field 'plan.title'"),
+        "{message}"
+    );
+    assert!(message.contains("plan.step"), "{message}");
+
+    let duplicate = all_tools_with_overrides(
+        ToolOverrides::new().with_tool("grep", ToolOverride::new().with_name("shell")),
+    )
+    .err()
+    .expect("duplicate name is rejected");
+    assert!(duplicate.to_string().contains("'shell'"), "{duplicate}");
+}
+
+#[test]
+fn overrides_load_from_resource_settings() {
+    let settings: std::collections::BTreeMap<String, roci::resource::ToolOverrideSettings> =
+        serde_json::from_value(serde_json::json!({
+            "shell": {
+                "name": "bash",
+                "description": "Run a bash command",
+                "parameters": { "command": "The bash command" }
+            }
+        }))
+        .unwrap();
+
+    let tools = all_tools_with_overrides(ToolOverrides::from_settings(&settings)).unwrap();
+
+    let bash = definition(&tools, "bash");
+    assert_eq!(bash.description, "Run a bash command");
+    assert_eq!(
+        bash.parameters["properties"]["command"]["description"],
+        "The bash command"
+    );
+}
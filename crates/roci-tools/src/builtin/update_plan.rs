@@ -0,0 +1,108 @@
+//! Update plan tool for model-maintained task checklists.
+
+use std::sync::Arc;
+
+use roci::error::RociError;
+use roci::tools::arguments::ToolArguments;
+use roci::tools::tool::{
+    AgentTool, Tool, ToolEffects, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan,
+    ToolSafetySummary,
+};
+use roci::tools::types::AgentToolParameters;
+use roci::tools::{PlanStep, PlanStepStatus};
+
+/// Create the `update_plan` tool — replaces the run's plan with a new list of steps.
+///
+/// The plan is validated against the previous one (a single `in_progress`
+/// step, no completing steps that never started) and the runner emits
+/// `AgentEvent::PlanUpdate` after every accepted update.
+pub fn update_plan_tool() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "update_plan",
+        "Record or update your plan for the current task. Send the full list of steps every time, each with a status of pending, in_progress, or completed. Keep exactly one step in_progress while working, and mark a step in_progress before marking it completed.",
+        update_plan_parameters(),
+        |args, ctx: ToolExecutionContext| async move { execute_update_plan(args, ctx) },
+    )
+    .with_static_safety(ToolSafetyPlan::host_input(), update_plan_safety_summary())
+    .with_effects(ToolEffects::ReadOnly))
+}
+
+fn update_plan_safety_summary() -> ToolSafetySummary {
+    ToolSafetySummary {
+        read_only_by_default: false,
+        destructive_by_default: false,
+        concurrency_safe_by_default: false,
+        approval_kind: ToolSafetyKind::Other,
+    }
+}
+
+fn update_plan_parameters() -> AgentToolParameters {
+    AgentToolParameters::from_schema(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "explanation": {
+                "type": "string",
+                "description": "Optional short note on why the plan changed"
+            },
+            "plan": {
+                "type": "array",
+                "description": "The complete plan, in order",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "step": {
+                            "type": "string",
+                            "description": "Short description of the step"
+                        },
+                        "status": {
+                            "type": "string",
+                            "enum": ["pending", "in_progress", "completed"]
+                        }
+                    },
+                    "required": ["step", "status"]
+                }
+            }
+        },
+        "required": ["plan"]
+    }))
+}
+
+fn execute_update_plan(
+    args: ToolArguments,
+    ctx: ToolExecutionContext,
+) -> Result<serde_json::Value, RociError> {
+    let store = ctx.plan.as_ref().ok_or_else(|| RociError::ToolExecution {
+        tool_name: "update_plan".into(),
+        message: "no plan store configured for this run".into(),
+    })?;
+    let steps = parse_steps(args.raw())?;
+    let steps = store.update(steps)?;
+
+    let completed = count_status(&steps, PlanStepStatus::Completed);
+    let in_progress = steps
+        .iter()
+        .find(|step| step.status == PlanStepStatus::InProgress)
+        .map(|step| step.step.clone());
+    Ok(serde_json::json!({
+        "updated": true,
+        "total_steps": steps.len(),
+        "completed_steps": completed,
+        "in_progress": in_progress,
+        "plan": steps,
+    }))
+}
+
+fn parse_steps(raw: &serde_json::Value) -> Result<Vec<PlanStep>, RociError> {
+    let plan = raw
+        .get("plan")
+        .ok_or_else(|| RociError::InvalidArgument("missing required argument 'plan'".into()))?;
+    serde_json::from_value::<Vec<PlanStep>>(plan.clone()).map_err(|err| {
+        RociError::InvalidArgument(format!(
+            "'plan' must be an array of {{\"step\": string, \"status\": \"pending\" | \"in_progress\" | \"completed\"}} objects: {err}"
+        ))
+    })
+}
+
+fn count_status(steps: &[PlanStep], status: PlanStepStatus) -> usize {
+    steps.iter().filter(|step| step.status == status).count()
+}
@@ -1,103 +1,295 @@
+use std::path::Path;
 use std::sync::Arc;
 
 use roci::error::RociError;
 use roci::security::filesystem::PathOperation;
 use roci::tools::arguments::ToolArguments;
+use roci::tools::artifacts::Artifact;
+use roci::tools::diff::ChangePreview;
 use roci::tools::tool::{
     AgentTool, Tool, ToolExecutionContext, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
 };
 use roci::tools::types::AgentToolParameters;
 
-use super::common::{resolve_session_path, resolve_workspace_path};
+use super::change_tracking::change_path;
+use super::common::{read_head, resolve_session_path, resolve_workspace_path, TEXT_SNIFF_BYTES};
+use super::encoding::{EncodingFallback, TextEncoding};
 
 /// Create the `write_file` tool — writes content to a file.
 ///
-/// Creates parent directories when they do not exist. Returns the written
-/// byte count and the resolved path.
+/// Creates parent directories when they do not exist. Content is written in
+/// the optional `encoding` argument; without it, an overwritten file keeps
+/// the encoding detected from its current bytes and new files are UTF-8.
+/// Returns the written byte count, the encoding, and the resolved path.
+/// Inside a run, each write is recorded in the run's change log and each
+/// written host file is registered as a run artifact. Approval
+/// requests carry a diff of the current file against the proposed content.
+/// `scratch://` paths write to the run's scratch directory whatever the
+/// workspace root; those intermediate files are neither logged as changes
+/// nor registered as artifacts.
 pub fn write_file_tool() -> Arc<dyn Tool> {
+    write_file_tool_with_encodings(EncodingFallback::default())
+}
+
+/// Create the `write_file` tool with a custom encoding fallback chain used
+/// to detect the encoding of files it overwrites.
+pub fn write_file_tool_with_encodings(encodings: EncodingFallback) -> Arc<dyn Tool> {
+    let preview_encodings = encodings.clone();
     let tool = AgentTool::new(
         "write_file",
         "Write content to a file, creating parent directories if needed",
         AgentToolParameters::object()
-            .string("path", "Path to the file to write", true)
+            .string("path", "Path to the file to write; `scratch://name` writes to the run's scratch directory", true)
             .string("content", "Content to write to the file", true)
+            .string(
+                "encoding",
+                "Encoding to write: utf-8, utf-8-bom, utf-16le, utf-16be, or latin-1 (defaults to the existing file's encoding, else utf-8)",
+                false,
+            )
             .build(),
-        |args_val, ctx: ToolExecutionContext| async move {
-            let path = args_val.get_str("path")?;
-            let content = args_val.get_str("content")?;
-
-            if let Some(workspace_path) = resolve_workspace_path(&ctx, path, PathOperation::Write)?
-            {
-                if let Some(parent) = workspace_path.parent() {
-                    tokio::fs::create_dir_all(parent).await.map_err(|e| {
+        move |args_val, ctx: ToolExecutionContext| {
+            let encodings = encodings.clone();
+            async move {
+                let path = args_val.get_str("path")?;
+                let content = args_val.get_str("content")?;
+                let requested = args_val
+                    .get_str_opt("encoding")
+                    .map(TextEncoding::parse)
+                    .transpose()?;
+
+                if let Some(workspace_path) =
+                    resolve_workspace_path(&ctx, path, PathOperation::Write)?
+                {
+                    if let Some(parent) = workspace_path.parent() {
+                        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                            RociError::ToolExecution {
+                                tool_name: "write_file".into(),
+                                message: format!(
+                                    "failed to create directories for {}: {e}",
+                                    workspace_path.display()
+                                ),
+                            }
+                        })?;
+                    }
+                    let encoding = match requested {
+                        Some(encoding) => encoding,
+                        None => existing_host_encoding(&workspace_path, &encodings).await,
+                    };
+                    let bytes = encode_content(path, content, encoding)?;
+                    let before = previous_host_content(&ctx, &workspace_path).await;
+                    tokio::fs::write(&workspace_path, &bytes)
+                        .await
+                        .map_err(|e| RociError::ToolExecution {
+                            tool_name: "write_file".into(),
+                            message: format!("{}: {e}", workspace_path.display()),
+                        })?;
+                    let in_scratch = ctx
+                        .scratch
+                        .as_ref()
+                        .is_some_and(|scratch| scratch.contains(&workspace_path));
+                    if in_scratch {
+                        return Ok(write_result(path, bytes.len(), encoding));
+                    }
+                    if let Some(changes) = ctx.changes.as_ref() {
+                        let changed_path =
+                            change_path(ctx.workspace_root.as_deref(), &workspace_path);
+                        changes.record("write_file", &changed_path, before.as_deref(), Some(&bytes));
+                    }
+                    register_artifact(&ctx, &workspace_path)?;
+                    return Ok(write_result(path, bytes.len(), encoding));
+                }
+
+                if let (Some(session_fs), Some(logical_path)) =
+                    (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
+                {
+                    let encoding = requested.unwrap_or_else(|| {
+                        session_fs
+                            .read(&logical_path)
+                            .ok()
+                            .and_then(|existing| {
+                                encodings.detect(&existing[..existing.len().min(TEXT_SNIFF_BYTES)])
+                            })
+                            .unwrap_or(TextEncoding::Utf8)
+                    });
+                    let bytes = encode_content(path, content, encoding)?;
+                    let before = ctx
+                        .changes
+                        .as_ref()
+                        .and_then(|_| session_fs.read(&logical_path).ok());
+                    session_fs.write(&logical_path, &bytes).map_err(|e| {
                         RociError::ToolExecution {
                             tool_name: "write_file".into(),
-                            message: format!(
-                                "failed to create directories for {}: {e}",
-                                workspace_path.display()
-                            ),
+                            message: format!("{logical_path}: {e}"),
                         }
                     })?;
+                    if let Some(changes) = ctx.changes.as_ref() {
+                        changes.record(
+                            "write_file",
+                            &logical_path.to_string(),
+                            before.as_deref(),
+                            Some(&bytes),
+                        );
+                    }
+
+                    return Ok(write_result(
+                        &logical_path.to_string(),
+                        bytes.len(),
+                        encoding,
+                    ));
                 }
-                let bytes = content.len();
-                tokio::fs::write(&workspace_path, content)
+
+                if let Some(parent) = Path::new(path).parent() {
+                    if !parent.as_os_str().is_empty() {
+                        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                            RociError::ToolExecution {
+                                tool_name: "write_file".into(),
+                                message: format!("failed to create directories for {path}: {e}"),
+                            }
+                        })?;
+                    }
+                }
+
+                let encoding = match requested {
+                    Some(encoding) => encoding,
+                    None => existing_host_encoding(Path::new(path), &encodings).await,
+                };
+                let bytes = encode_content(path, content, encoding)?;
+                let before = previous_host_content(&ctx, Path::new(path)).await;
+                tokio::fs::write(path, &bytes)
                     .await
                     .map_err(|e| RociError::ToolExecution {
                         tool_name: "write_file".into(),
-                        message: format!("{}: {e}", workspace_path.display()),
+                        message: format!("{path}: {e}"),
                     })?;
-                return Ok(serde_json::json!({
-                    "success": true,
-                    "path": path,
-                    "bytes_written": bytes,
-                }));
+                if let Some(changes) = ctx.changes.as_ref() {
+                    let changed_path = change_path(None, Path::new(path));
+                    changes.record("write_file", &changed_path, before.as_deref(), Some(&bytes));
+                }
+                register_artifact(&ctx, Path::new(path))?;
+
+                Ok(write_result(path, bytes.len(), encoding))
             }
+        },
+    );
+    Arc::new(
+        tool.with_safety(write_file_safety_summary(), write_file_safety)
+            .with_approval_preview(move |args, ctx| {
+                let encodings = preview_encodings.clone();
+                async move { write_file_preview(&args, &ctx, &encodings).await }
+            }),
+    )
+}
 
-            if let (Some(session_fs), Some(logical_path)) =
-                (ctx.session_fs.as_ref(), resolve_session_path(&ctx, path)?)
-            {
-                let bytes = content.len();
-                session_fs
-                    .write(&logical_path, content.as_bytes())
-                    .map_err(|e| RociError::ToolExecution {
-                        tool_name: "write_file".into(),
-                        message: format!("{logical_path}: {e}"),
-                    })?;
+/// Current content against the content the call would write.
+///
+/// Resolves the target the same way the write does but only reads; an
+/// argument the write would reject yields no preview. Text files diff as
+/// decoded text, so an encoding the write preserves does not show up as a
+/// change.
+async fn write_file_preview(
+    args: &ToolArguments,
+    ctx: &ToolExecutionContext,
+    encodings: &EncodingFallback,
+) -> Vec<ChangePreview> {
+    let (Ok(path), Ok(content)) = (args.get_str("path"), args.get_str("content")) else {
+        return Vec::new();
+    };
+    let Ok(requested) = args
+        .get_str_opt("encoding")
+        .map(TextEncoding::parse)
+        .transpose()
+    else {
+        return Vec::new();
+    };
 
-                return Ok(serde_json::json!({
-                    "success": true,
-                    "path": logical_path.to_string(),
-                    "bytes_written": bytes,
-                }));
-            }
+    let (label, before) = match resolve_workspace_path(ctx, path, PathOperation::Write) {
+        Err(_) => return Vec::new(),
+        Ok(Some(workspace_path)) => (
+            change_path(ctx.workspace_root.as_deref(), &workspace_path),
+            tokio::fs::read(&workspace_path).await.ok(),
+        ),
+        Ok(None) => match (
+            ctx.session_fs.as_ref(),
+            resolve_session_path(ctx, path).ok().flatten(),
+        ) {
+            (Some(session_fs), Some(logical_path)) => (
+                logical_path.to_string(),
+                session_fs.read(&logical_path).ok(),
+            ),
+            _ => (
+                change_path(None, Path::new(path)),
+                tokio::fs::read(path).await.ok(),
+            ),
+        },
+    };
 
-            if let Some(parent) = std::path::Path::new(path).parent() {
-                if !parent.as_os_str().is_empty() {
-                    tokio::fs::create_dir_all(parent).await.map_err(|e| {
-                        RociError::ToolExecution {
-                            tool_name: "write_file".into(),
-                            message: format!("failed to create directories for {path}: {e}"),
-                        }
-                    })?;
-                }
+    let detected = before
+        .as_deref()
+        .and_then(|bytes| encodings.detect(&bytes[..bytes.len().min(TEXT_SNIFF_BYTES)]));
+    let preview = match (&before, detected) {
+        (Some(bytes), None) => {
+            let encoding = requested.unwrap_or(TextEncoding::Utf8);
+            let Ok(after) = encode_content(path, content, encoding) else {
+                return Vec::new();
+            };
+            ChangePreview::between(label, Some(bytes.as_slice()), Some(&after))
+        }
+        (before, detected) => {
+            let encoding = requested.or(detected).unwrap_or(TextEncoding::Utf8);
+            if encode_content(path, content, encoding).is_err() {
+                return Vec::new();
             }
+            let old_text = before
+                .as_deref()
+                .zip(detected)
+                .map(|(bytes, encoding)| encoding.decode(bytes));
+            ChangePreview::between(
+                label,
+                old_text.as_deref().map(str::as_bytes),
+                Some(content.as_bytes()),
+            )
+        }
+    };
+    vec![preview]
+}
 
-            let bytes = content.len();
-            tokio::fs::write(path, content)
-                .await
-                .map_err(|e| RociError::ToolExecution {
-                    tool_name: "write_file".into(),
-                    message: format!("{path}: {e}"),
-                })?;
-
-            Ok(serde_json::json!({
-                "success": true,
-                "path": path,
-                "bytes_written": bytes,
-            }))
-        },
-    );
-    Arc::new(tool.with_safety(write_file_safety_summary(), write_file_safety))
+/// Register a written host file with the run, when there is one.
+fn register_artifact(ctx: &ToolExecutionContext, path: &Path) -> Result<(), RociError> {
+    if ctx.artifacts.is_some() {
+        ctx.add_artifact(Artifact::file(path))?;
+    }
+    Ok(())
+}
+
+/// Content about to be overwritten, read only when the run tracks changes.
+/// `None` for new files.
+async fn previous_host_content(ctx: &ToolExecutionContext, path: &Path) -> Option<Vec<u8>> {
+    ctx.changes.as_ref()?;
+    tokio::fs::read(path).await.ok()
+}
+
+/// Encoding of the file being overwritten; UTF-8 for new or binary files.
+async fn existing_host_encoding(path: &Path, encodings: &EncodingFallback) -> TextEncoding {
+    read_head(path)
+        .await
+        .ok()
+        .and_then(|head| encodings.detect(&head))
+        .unwrap_or(TextEncoding::Utf8)
+}
+
+fn encode_content(path: &str, content: &str, encoding: TextEncoding) -> Result<Vec<u8>, RociError> {
+    encoding
+        .encode(content)
+        .map_err(|reason| RociError::InvalidArgument(format!("{path}: {reason}")))
+}
+
+fn write_result(path: &str, bytes_written: usize, encoding: TextEncoding) -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "path": path,
+        "bytes_written": bytes_written,
+        "encoding": encoding.label(),
+    })
 }
 
 fn write_file_safety(args: &ToolArguments) -> ToolSafetyPlan {
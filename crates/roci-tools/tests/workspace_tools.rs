@@ -4,7 +4,9 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use roci::error::RociError;
 use roci::prelude::{LocalSessionFs, LogicalPath, SessionFs};
-use roci::tools::{SandboxProvider, ToolArguments, ToolExecutionContext};
+use roci::tools::{
+    ArtifactStore, ChangeLog, FileChangeKind, SandboxProvider, ToolArguments, ToolExecutionContext,
+};
 use roci_tools::builtin::{
     grep_tool, list_directory_tool, read_file_tool, shell_tool, write_file_tool,
 };
@@ -237,3 +239,153 @@ async fn workspace_shell_honors_configured_sandbox_provider() {
 
     assert!(error.to_string().contains("sandbox denied command"));
 }
+
+fn tracked_ctx(root: &Path, changes: &ChangeLog) -> ToolExecutionContext {
+    ToolExecutionContext {
+        changes: Some(changes.clone()),
+        ..workspace_ctx(root)
+    }
+}
+
+#[tokio::test]
+async fn workspace_write_file_records_changes_relative_to_root() {
+    let workspace = tempfile::tempdir().expect("workspace temp dir");
+    std::fs::create_dir(workspace.path().join("src")).expect("create src");
+    std::fs::write(workspace.path().join("src/lib.rs"), "a\nb\n").expect("write lib");
+    let changes = ChangeLog::new();
+    let ctx = tracked_ctx(workspace.path(), &changes);
+    let write = write_file_tool();
+
+    write
+        .execute(
+            &args(serde_json::json!({ "path": "./src/lib.rs", "content": "a\nB\nc\n" })),
+            &ctx,
+        )
+        .await
+        .expect("modify lib");
+    write
+        .execute(
+            &args(serde_json::json!({ "path": "notes/todo.md", "content": "one\n" })),
+            &ctx,
+        )
+        .await
+        .expect("create notes");
+    write
+        .execute(
+            &args(serde_json::json!({ "path": "notes/todo.md", "content": "one\ntwo\n" })),
+            &ctx,
+        )
+        .await
+        .expect("extend notes");
+
+    let recorded = changes.changes();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[0].path, "src/lib.rs");
+    assert_eq!(recorded[0].kind, FileChangeKind::Modified);
+    assert_eq!((recorded[0].lines_added, recorded[0].lines_removed), (2, 1));
+    assert_eq!(recorded[1].path, "notes/todo.md");
+    assert_eq!(recorded[1].kind, FileChangeKind::Added);
+    assert_eq!(recorded[1].old_hash, None);
+    assert_eq!((recorded[1].lines_added, recorded[1].lines_removed), (2, 0));
+    assert!(recorded.iter().all(|change| change.verified));
+}
+
+#[tokio::test]
+async fn workspace_write_file_registers_written_file_as_artifact() {
+    let workspace = tempfile::tempdir().expect("workspace temp dir");
+    let spill = tempfile::tempdir().expect("spill temp dir");
+    let artifacts = ArtifactStore::new(spill.path());
+    let ctx = ToolExecutionContext {
+        tool_call_id: Some("call-1".into()),
+        tool_name: Some("write_file".into()),
+        artifacts: Some(artifacts.clone()),
+        ..workspace_ctx(workspace.path())
+    };
+
+    write_file_tool()
+        .execute(
+            &args(serde_json::json!({ "path": "out/report.md", "content": "# done\n" })),
+            &ctx,
+        )
+        .await
+        .expect("write report");
+
+    let recorded = artifacts.artifacts();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].name, "report.md");
+    assert_eq!(recorded[0].mime, "text/markdown");
+    assert_eq!(recorded[0].size, Some(7));
+    assert_eq!(recorded[0].tool_call_id.as_deref(), Some("call-1"));
+    let path = recorded[0].path().expect("file artifact");
+    assert!(path.ends_with("out/report.md"));
+}
+
+#[tokio::test]
+async fn workspace_shell_records_named_paths_as_unverified() {
+    let workspace = tempfile::tempdir().expect("workspace temp dir");
+    std::fs::write(workspace.path().join("gone.txt"), "bye\n").expect("write gone");
+    std::fs::write(workspace.path().join("kept.txt"), "same\n").expect("write kept");
+    let changes = ChangeLog::new();
+    let ctx = tracked_ctx(workspace.path(), &changes);
+
+    let result = shell_tool()
+        .execute(
+            &args(serde_json::json!({
+                "command": "cat kept.txt && rm gone.txt && printf 'x\\ny\\n' > out.txt"
+            })),
+            &ctx,
+        )
+        .await
+        .expect("run shell");
+    assert_eq!(result["exit_code"], 0);
+
+    let recorded = changes
+        .changes()
+        .into_iter()
+        .map(|change| {
+            (
+                change.path,
+                change.kind,
+                change.lines_added,
+                change.lines_removed,
+                change.verified,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        recorded,
+        vec![
+            ("gone.txt".to_string(), FileChangeKind::Deleted, 0, 1, false),
+            ("out.txt".to_string(), FileChangeKind::Added, 2, 0, false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn shell_changes_to_written_files_stay_verified() {
+    let workspace = tempfile::tempdir().expect("workspace temp dir");
+    let changes = ChangeLog::new();
+    let ctx = tracked_ctx(workspace.path(), &changes);
+
+    write_file_tool()
+        .execute(
+            &args(serde_json::json!({ "path": "log.txt", "content": "one\n" })),
+            &ctx,
+        )
+        .await
+        .expect("write log");
+    shell_tool()
+        .execute(
+            &args(serde_json::json!({ "command": "echo two >> log.txt" })),
+            &ctx,
+        )
+        .await
+        .expect("append log");
+
+    let recorded = changes.changes();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].kind, FileChangeKind::Added);
+    assert!(recorded[0].verified);
+    assert_eq!(recorded[0].tools, vec!["write_file", "shell"]);
+    assert_eq!((recorded[0].lines_added, recorded[0].lines_removed), (2, 0));
+}
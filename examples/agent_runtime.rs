@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use roci::agent::runtime::AgentSnapshot;
 use roci::agent::{AgentConfig, AgentRuntime, AgentState, QueueDrainMode};
-use roci::agent_loop::AgentEvent;
+use roci::agent_loop::{AgentEvent, AgentEventEnvelope, AgentEventSink};
 use roci::config::RociConfig;
 use roci::resource::CompactionSettings;
 use roci::tools::{AgentTool, AgentToolParameters};
@@ -36,8 +36,8 @@ async fn main() {
     ));
 
     // -- 2. Set up an event sink to observe agent lifecycle
-    let event_sink: Arc<dyn Fn(AgentEvent) + Send + Sync> =
-        Arc::new(|event: AgentEvent| match &event {
+    let event_sink: AgentEventSink =
+        Arc::new(|envelope: AgentEventEnvelope| match &envelope.event {
             AgentEvent::AgentStart { run_id } => {
                 println!("[start] run_id: {run_id}");
             }
@@ -69,6 +69,7 @@ async fn main() {
     let config = AgentConfig {
         candidates: vec![model],
         system_prompt: Some("You are a helpful assistant with access to an echo tool.".into()),
+        prompt_sections: Vec::new(),
         tools: vec![echo_tool],
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -95,10 +96,18 @@ async fn main() {
         compaction: CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         session: None,
         workspace_root: None,
         sandbox_provider: None,
@@ -0,0 +1,265 @@
+//! Scripted mock provider and assertion helpers shared by the recipes.
+//!
+//! The mock answers each provider call with the next [`Reply`] of its
+//! script and records every request, so recipes run without network access
+//! or API keys and can check what roci sent.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+
+use roci::config::RociConfig;
+use roci::error::RociError;
+use roci::models::capabilities::ModelCapabilities;
+use roci::provider::{
+    ModelProvider, ProviderFactory, ProviderRegistry, ProviderRequest, ProviderResponse,
+};
+use roci::types::{AgentToolCall, FinishReason, StreamEventType, TextStreamDelta, Usage};
+
+/// Provider key the mock registers under; models are `mock:<anything>`.
+pub const MOCK_PROVIDER: &str = "mock";
+
+/// One scripted provider response.
+#[derive(Debug, Clone)]
+pub enum Reply {
+    /// Assistant text, streamed one word at a time.
+    Text(String),
+    /// Tool calls as `(tool name, arguments)`.
+    ToolCalls(Vec<(String, serde_json::Value)>),
+}
+
+impl Reply {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+}
+
+/// Requests the mock received, in call order.
+pub type Requests = Arc<Mutex<Vec<ProviderRequest>>>;
+
+/// Registry whose only provider is the mock, answering with `script`.
+///
+/// Calls past the end of the script repeat its last reply.
+pub fn mock_registry(script: Vec<Reply>) -> (Arc<ProviderRegistry>, Requests) {
+    let requests = Requests::default();
+    let mut registry = ProviderRegistry::new();
+    registry.register(Arc::new(MockFactory {
+        script: Arc::new(script),
+        requests: requests.clone(),
+    }));
+    (Arc::new(registry), requests)
+}
+
+/// Config that never reads credentials from disk or the environment.
+pub fn offline_config() -> RociConfig {
+    RociConfig::new().with_token_store(None)
+}
+
+struct MockFactory {
+    script: Arc<Vec<Reply>>,
+    requests: Requests,
+}
+
+impl ProviderFactory for MockFactory {
+    fn provider_keys(&self) -> &[&str] {
+        &[MOCK_PROVIDER]
+    }
+
+    fn requires_credentials(&self, _provider_key: &str) -> bool {
+        false
+    }
+
+    fn create(
+        &self,
+        _config: &RociConfig,
+        _provider_key: &str,
+        model_id: &str,
+    ) -> Result<Box<dyn ModelProvider>, RociError> {
+        Ok(Box::new(MockProvider {
+            model_id: model_id.to_string(),
+            script: self.script.clone(),
+            requests: self.requests.clone(),
+        }))
+    }
+}
+
+struct MockProvider {
+    model_id: String,
+    script: Arc<Vec<Reply>>,
+    requests: Requests,
+}
+
+impl MockProvider {
+    /// Record `request` and return the reply scripted for this call.
+    fn next_reply(&self, request: &ProviderRequest) -> (usize, Reply) {
+        let mut requests = self.requests.lock().unwrap();
+        let call_index = requests.len();
+        requests.push(request.clone());
+        let reply = self
+            .script
+            .get(call_index)
+            .or_else(|| self.script.last())
+            .cloned()
+            .unwrap_or_else(|| Reply::text(""));
+        (call_index, reply)
+    }
+}
+
+fn tool_calls(call_index: usize, calls: Vec<(String, serde_json::Value)>) -> Vec<AgentToolCall> {
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(n, (name, arguments))| AgentToolCall {
+            id: format!("call-{call_index}-{n}"),
+            name,
+            arguments,
+            called_as: None,
+            recipient: None,
+        })
+        .collect()
+}
+
+fn delta(text: String, event_type: StreamEventType) -> TextStreamDelta {
+    TextStreamDelta {
+        text,
+        event_type,
+        tool_call: None,
+        finish_reason: None,
+        usage: None,
+        reasoning: None,
+        reasoning_signature: None,
+        reasoning_type: None,
+        image: None,
+        response_metadata: None,
+    }
+}
+
+fn usage() -> Usage {
+    Usage {
+        input_tokens: 10,
+        output_tokens: 5,
+        total_tokens: 15,
+        ..Default::default()
+    }
+}
+
+#[async_trait]
+impl ModelProvider for MockProvider {
+    fn provider_name(&self) -> &str {
+        MOCK_PROVIDER
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn capabilities(&self) -> &ModelCapabilities {
+        static CAPS: OnceLock<ModelCapabilities> = OnceLock::new();
+        CAPS.get_or_init(|| ModelCapabilities {
+            supports_tools: true,
+            supports_json_mode: true,
+            supports_json_schema: true,
+            context_length: 128_000,
+            ..ModelCapabilities::default()
+        })
+    }
+
+    async fn generate_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<ProviderResponse, RociError> {
+        let (call_index, reply) = self.next_reply(request);
+        let (text, tool_calls, finish_reason) = match reply {
+            Reply::Text(text) => (text, Vec::new(), FinishReason::Stop),
+            Reply::ToolCalls(calls) => (
+                String::new(),
+                tool_calls(call_index, calls),
+                FinishReason::ToolCalls,
+            ),
+        };
+        Ok(ProviderResponse {
+            text,
+            usage: usage(),
+            tool_calls,
+            finish_reason: Some(finish_reason),
+            thinking: Vec::new(),
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
+        })
+    }
+
+    async fn stream_text(
+        &self,
+        request: &ProviderRequest,
+    ) -> Result<BoxStream<'static, Result<TextStreamDelta, RociError>>, RociError> {
+        let (call_index, reply) = self.next_reply(request);
+        let (mut deltas, finish_reason) = match reply {
+            Reply::Text(text) => (
+                text.split_inclusive(' ')
+                    .map(|word| delta(word.to_string(), StreamEventType::TextDelta))
+                    .collect::<Vec<_>>(),
+                FinishReason::Stop,
+            ),
+            Reply::ToolCalls(calls) => (
+                tool_calls(call_index, calls)
+                    .into_iter()
+                    .map(|call| TextStreamDelta {
+                        tool_call: Some(call),
+                        ..delta(String::new(), StreamEventType::ToolCallDelta)
+                    })
+                    .collect(),
+                FinishReason::ToolCalls,
+            ),
+        };
+        deltas.push(TextStreamDelta {
+            finish_reason: Some(finish_reason),
+            usage: Some(usage()),
+            ..delta(String::new(), StreamEventType::Done)
+        });
+        Ok(stream::iter(deltas.into_iter().map(Ok)).boxed())
+    }
+}
+
+/// A recipe whose output differs from what it documents.
+#[derive(Debug)]
+pub struct Failure(String);
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<E: std::error::Error> From<E> for Failure {
+    fn from(error: E) -> Self {
+        Self(format!("unexpected error: {error}"))
+    }
+}
+
+/// Result of running one recipe.
+pub type Outcome = Result<(), Failure>;
+
+/// Fail with `what` unless `condition` holds.
+pub fn check(condition: bool, what: &str) -> Outcome {
+    if condition {
+        Ok(())
+    } else {
+        Err(Failure(format!("expected {what}")))
+    }
+}
+
+/// Fail unless `actual == expected`, naming the value as `what`.
+pub fn check_eq<T: PartialEq + fmt::Debug>(actual: T, expected: T, what: &str) -> Outcome {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Failure(format!(
+            "{what}: expected {expected:?}, got {actual:?}"
+        )))
+    }
+}
@@ -0,0 +1,63 @@
+//! Cookbook: runnable recipes for roci's major features, each checked
+//! against a scripted mock provider, so no network access or API keys are
+//! needed.
+//!
+//! Run every recipe: `cargo run --example cookbook --features agent,mcp`
+//! Run some: `cargo run --example cookbook --features agent,mcp -- streaming session`
+//!
+//! Each recipe checks its own output; the process exits nonzero when any
+//! recipe fails. `cargo test --test cookbook --features agent,mcp` runs the
+//! same recipes as tests.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::process::ExitCode;
+
+mod harness;
+mod recipes;
+
+type Recipe = fn() -> Pin<Box<dyn Future<Output = harness::Outcome>>>;
+
+const RECIPES: &[(&str, Recipe)] = &[
+    ("agent_loop", || Box::pin(recipes::agent_loop::run())),
+    ("structured_output", || {
+        Box::pin(recipes::structured_output::run())
+    }),
+    ("streaming", || Box::pin(recipes::streaming::run())),
+    ("mcp_aggregation", || {
+        Box::pin(recipes::mcp_aggregation::run())
+    }),
+    ("session", || Box::pin(recipes::session::run())),
+    ("auth_store", || Box::pin(recipes::auth_store::run())),
+];
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let selected: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(unknown) = selected
+        .iter()
+        .find(|name| !RECIPES.iter().any(|(recipe, _)| recipe == name))
+    {
+        eprintln!("unknown recipe '{unknown}'");
+        return ExitCode::FAILURE;
+    }
+
+    let mut failed = 0;
+    for (name, recipe) in RECIPES {
+        if !selected.is_empty() && !selected.iter().any(|selected| selected == name) {
+            continue;
+        }
+        match recipe().await {
+            Ok(()) => println!("ok      {name}"),
+            Err(failure) => {
+                failed += 1;
+                println!("FAILED  {name}: {failure}");
+            }
+        }
+    }
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
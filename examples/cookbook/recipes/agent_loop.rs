@@ -0,0 +1,119 @@
+//! Agent loop with tools and hooks.
+//!
+//! The model asks to look up an order and delete it in one turn. A
+//! `pre_turn_execution` guardrail drops the delete before any tool runs, and
+//! a `pre_tool_use` hook records each call that survives.
+
+use std::sync::{Arc, Mutex};
+
+use roci::agent_loop::{
+    ApprovalPolicy, LoopRunner, PreToolUseHookResult, PreTurnExecutionHookResult, RunHooks,
+    RunRequest, RunStatus, Runner,
+};
+use roci::tools::{
+    AgentTool, AgentToolParameters, Tool, ToolSafetyKind, ToolSafetyPlan, ToolSafetySummary,
+};
+use roci::types::{ModelMessage, Role};
+use serde_json::json;
+
+use crate::harness::{check, check_eq, mock_registry, offline_config, Outcome, Reply};
+
+fn lookup_order() -> Arc<dyn Tool> {
+    Arc::new(
+        AgentTool::new(
+            "lookup_order",
+            "Look up an order by id",
+            AgentToolParameters::object()
+                .string("id", "Order id", true)
+                .build(),
+            |args, _ctx| async move {
+                let id = args.get_str("id")?;
+                Ok(json!({ "id": id, "status": "shipped" }))
+            },
+        )
+        .with_static_safety(
+            ToolSafetyPlan::safe_read_only(ToolSafetyKind::CustomTool),
+            ToolSafetySummary {
+                read_only_by_default: true,
+                destructive_by_default: false,
+                concurrency_safe_by_default: true,
+                approval_kind: ToolSafetyKind::CustomTool,
+            },
+        ),
+    )
+}
+
+fn delete_order() -> Arc<dyn Tool> {
+    Arc::new(AgentTool::new(
+        "delete_order",
+        "Delete an order by id",
+        AgentToolParameters::object()
+            .string("id", "Order id", true)
+            .build(),
+        |_args, _ctx| async move { Ok(json!({ "deleted": true })) },
+    ))
+}
+
+pub async fn run() -> Outcome {
+    let (registry, requests) = mock_registry(vec![
+        Reply::ToolCalls(vec![
+            ("lookup_order".into(), json!({ "id": "A-1" })),
+            ("delete_order".into(), json!({ "id": "A-1" })),
+        ]),
+        Reply::text("Order A-1 has shipped."),
+    ]);
+    let runner = LoopRunner::with_registry(offline_config(), registry);
+
+    let executed = Arc::new(Mutex::new(Vec::new()));
+    let seen = executed.clone();
+    let hooks = RunHooks {
+        pre_turn_execution: Some(Arc::new(|turn| {
+            let calls = turn
+                .tool_calls
+                .into_iter()
+                .filter(|call| call.name != "delete_order")
+                .collect();
+            Box::pin(async { Ok(PreTurnExecutionHookResult::ModifyCalls(calls)) })
+        })),
+        pre_tool_use: Some(Arc::new(move |call, _context, _cancel| {
+            seen.lock().unwrap().push(call.name);
+            Box::pin(async { Ok(PreToolUseHookResult::Continue) })
+        })),
+        ..RunHooks::default()
+    };
+    let request = RunRequest::new(
+        "mock:cookbook".parse()?,
+        vec![ModelMessage::user("Check order A-1, then delete it.")],
+    )
+    .with_tools(vec![lookup_order(), delete_order()])
+    .with_approval_policy(ApprovalPolicy::always())
+    .with_hooks(hooks);
+
+    let result = runner.start(request).await?.wait().await;
+
+    check_eq(result.status, RunStatus::Completed, "run status")?;
+    check_eq(
+        executed.lock().unwrap().clone(),
+        vec!["lookup_order".to_string()],
+        "executed tools",
+    )?;
+    let answer = result.messages.last().map(ModelMessage::text);
+    check_eq(
+        answer.as_deref(),
+        Some("Order A-1 has shipped."),
+        "final answer",
+    )?;
+    let requests = requests.lock().unwrap();
+    check_eq(requests.len(), 2, "provider calls")?;
+    let tool_results = requests[1]
+        .messages
+        .iter()
+        .filter(|message| message.role == Role::Tool)
+        .map(|message| serde_json::to_string(message).unwrap_or_default())
+        .collect::<Vec<_>>();
+    check_eq(tool_results.len(), 1, "tool results sent back to the model")?;
+    check(
+        tool_results[0].contains("shipped"),
+        "the lookup result in the second request",
+    )
+}
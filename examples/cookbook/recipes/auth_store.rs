@@ -0,0 +1,63 @@
+//! Auth store usage: tokens saved by a login flow are picked up as API keys,
+//! and expired tokens are ignored.
+
+use std::sync::Arc;
+
+use roci::auth::{FileTokenStore, Token, TokenStore, TokenStoreConfig};
+use roci::config::RociConfig;
+
+use crate::harness::{check, check_eq, Outcome};
+
+fn token(access_token: &str, expires_in: chrono::Duration) -> Token {
+    Token {
+        access_token: access_token.to_string(),
+        refresh_token: Some("refresh".to_string()),
+        id_token: None,
+        expires_at: Some(chrono::Utc::now() + expires_in),
+        last_refresh: None,
+        scopes: None,
+        account_id: None,
+    }
+}
+
+pub async fn run() -> Outcome {
+    let dir = tempfile::tempdir()?;
+    let store: Arc<dyn TokenStore> = Arc::new(FileTokenStore::new(TokenStoreConfig::new(
+        dir.path().to_path_buf(),
+    )));
+    let service = roci::default_auth_service(store.clone());
+    let config = RociConfig::new().with_token_store(Some(store.clone()));
+
+    // What a completed Claude login would have saved.
+    store.save(
+        "claude-code",
+        "default",
+        &token("live-token", chrono::Duration::hours(1)),
+    )?;
+    check_eq(
+        config.get_api_key("anthropic"),
+        Some("live-token".to_string()),
+        "API key from the token store",
+    )?;
+    check(
+        service.get_status("claude")?.is_some(),
+        "a logged-in status for claude",
+    )?;
+
+    store.save(
+        "claude-code",
+        "default",
+        &token("stale-token", -chrono::Duration::hours(1)),
+    )?;
+    check_eq(
+        config.get_api_key("anthropic"),
+        None,
+        "API key from an expired token",
+    )?;
+
+    service.logout("claude")?;
+    check(
+        store.load("claude-code", "default")?.is_none(),
+        "logout to clear the stored token",
+    )
+}
@@ -0,0 +1,85 @@
+//! MCP aggregation: one server surface over native tools and several
+//! upstream MCP servers, each exposed as `mcp__<server>__<tool>`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use roci::error::RociError;
+use roci::mcp::McpServerCore;
+use roci::tools::{
+    AgentTool, AgentToolParameters, DynamicTool, DynamicToolProvider, ToolArguments,
+    ToolExecutionContext,
+};
+use serde_json::json;
+
+use crate::harness::{check, check_eq, Outcome};
+
+/// Stand-in for an upstream MCP server with a single `search` tool.
+struct SearchServer {
+    corpus: &'static str,
+}
+
+#[async_trait]
+impl DynamicToolProvider for SearchServer {
+    async fn list_tools(&self) -> Result<Vec<DynamicTool>, RociError> {
+        Ok(vec![DynamicTool::new(
+            "search",
+            format!("Search {}", self.corpus),
+            AgentToolParameters::object()
+                .string("query", "Search terms", true)
+                .build(),
+        )])
+    }
+
+    async fn execute_tool(
+        &self,
+        name: &str,
+        args: &ToolArguments,
+        _ctx: &ToolExecutionContext,
+    ) -> Result<serde_json::Value, RociError> {
+        let query = args.get_str("query")?;
+        Ok(json!({ "tool": name, "corpus": self.corpus, "query": query }))
+    }
+}
+
+pub async fn run() -> Outcome {
+    let server = McpServerCore::new()
+        .with_native_tool(Arc::new(AgentTool::new(
+            "clock",
+            "Current time",
+            AgentToolParameters::empty(),
+            |_args, _ctx| async move { Ok(json!({ "time": "12:00" })) },
+        )))
+        .with_mcp_provider("docs", Arc::new(SearchServer { corpus: "docs" }))
+        .with_mcp_provider("tickets", Arc::new(SearchServer { corpus: "tickets" }));
+
+    let names = server
+        .list_tools()
+        .await?
+        .into_iter()
+        .map(|tool| tool.exposed_name)
+        .collect::<Vec<_>>();
+    check_eq(
+        names,
+        vec![
+            "clock".to_string(),
+            "mcp__docs__search".to_string(),
+            "mcp__tickets__search".to_string(),
+        ],
+        "aggregated tools",
+    )?;
+
+    let routes = server.routes_by_exposed_name().await?;
+    let Some(identity) = routes.get("mcp__tickets__search") else {
+        return check(false, "a route for mcp__tickets__search");
+    };
+    let result = server
+        .call_tool(identity, json!({ "query": "login bug" }))
+        .await?;
+    check(!result.is_error, "the routed call to succeed")?;
+    check_eq(
+        result.structured_content,
+        Some(json!({ "tool": "search", "corpus": "tickets", "query": "login bug" })),
+        "routed call result",
+    )
+}
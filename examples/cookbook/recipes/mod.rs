@@ -0,0 +1,8 @@
+//! One module per recipe; each exposes `run`, which checks its own output.
+
+pub mod agent_loop;
+pub mod auth_store;
+pub mod mcp_aggregation;
+pub mod session;
+pub mod streaming;
+pub mod structured_output;
@@ -0,0 +1,48 @@
+//! Session persistence: a conversation saved to a local session store is
+//! there again when the session is reopened.
+
+use roci::agent::{AgentConfig, AgentRuntime};
+use roci::session::{CreateSessionOptions, LocalSessionStore, SessionId};
+use roci::types::Role;
+
+use crate::harness::{check_eq, mock_registry, offline_config, Outcome, Reply};
+
+pub async fn run() -> Outcome {
+    let dir = tempfile::tempdir()?;
+    let store = LocalSessionStore::new(dir.path());
+    let id = SessionId::parse("cookbook-session")?;
+    let config = || AgentConfig {
+        candidates: vec!["mock:cookbook".parse().expect("valid model")],
+        ..AgentConfig::default()
+    };
+
+    let (registry, _requests) = mock_registry(vec![Reply::text("Noted: blue.")]);
+    let state = store
+        .create(CreateSessionOptions {
+            id: Some(id.clone()),
+            ..CreateSessionOptions::default()
+        })
+        .await?;
+    let agent = AgentRuntime::resume_session(registry, offline_config(), config(), state).await?;
+    agent.prompt("My favourite colour is blue.").await?;
+    drop(agent);
+
+    let state = store.open(id).await?;
+    let saved = state
+        .model_messages
+        .iter()
+        .map(|message| (message.role, message.text()))
+        .collect::<Vec<_>>();
+    check_eq(
+        saved,
+        vec![
+            (Role::User, "My favourite colour is blue.".to_string()),
+            (Role::Assistant, "Noted: blue.".to_string()),
+        ],
+        "persisted messages",
+    )?;
+
+    let (registry, _requests) = mock_registry(vec![Reply::text("Blue.")]);
+    let agent = AgentRuntime::resume_session(registry, offline_config(), config(), state).await?;
+    check_eq(agent.messages().await.len(), 2, "messages after resume")
+}
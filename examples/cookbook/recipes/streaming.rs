@@ -0,0 +1,53 @@
+//! Streaming with transforms: a stop condition ends the stream early and
+//! stream combinators reshape the deltas.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use roci::provider::ModelProvider;
+use roci::stop::StopCondition;
+use roci::types::{GenerationSettings, ModelMessage, StreamEventType};
+
+use crate::harness::{check_eq, mock_registry, offline_config, Outcome, Reply};
+
+/// Stops the stream once the text so far contains `phrase`.
+struct StopOnPhrase {
+    phrase: &'static str,
+}
+
+#[async_trait]
+impl StopCondition for StopOnPhrase {
+    async fn should_stop(&self, text: &str, _delta: Option<&str>) -> bool {
+        text.contains(self.phrase)
+    }
+
+    async fn reset(&self) {}
+}
+
+pub async fn run() -> Outcome {
+    let (registry, _requests) = mock_registry(vec![Reply::text(
+        "first line. second line. END ignored trailing words",
+    )]);
+    let provider: Arc<dyn ModelProvider> =
+        Arc::from(registry.create_provider("mock", "cookbook", &offline_config())?);
+
+    let stream = roci::generation::stream_text(
+        provider,
+        vec![ModelMessage::user("Write two lines.")],
+        GenerationSettings::default(),
+        vec![Box::new(StopOnPhrase { phrase: "END" })],
+    )
+    .await?;
+    let words: Vec<String> = stream
+        .try_filter(|delta| std::future::ready(delta.event_type == StreamEventType::TextDelta))
+        .map_ok(|delta| delta.text.trim().to_uppercase())
+        .try_collect()
+        .await?;
+
+    check_eq(
+        words.join(" "),
+        "FIRST LINE. SECOND LINE. END".to_string(),
+        "transformed stream",
+    )
+}
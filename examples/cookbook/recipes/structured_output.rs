@@ -0,0 +1,59 @@
+//! Structured output: generate a typed object from a JSON schema.
+
+use roci::provider::ProviderRequest;
+use roci::types::{GenerationSettings, ModelMessage};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::harness::{check, check_eq, mock_registry, offline_config, Outcome, Reply};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Invoice {
+    number: String,
+    total_cents: u64,
+    paid: bool,
+}
+
+pub async fn run() -> Outcome {
+    let (registry, requests) = mock_registry(vec![Reply::text(
+        r#"{"number":"INV-7","total_cents":12500,"paid":false}"#,
+    )]);
+    let provider = registry.create_provider("mock", "cookbook", &offline_config())?;
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "number": { "type": "string" },
+            "total_cents": { "type": "integer" },
+            "paid": { "type": "boolean" }
+        },
+        "required": ["number", "total_cents", "paid"]
+    });
+
+    let result = roci::generation::generate_object::<Invoice>(
+        provider.as_ref(),
+        vec![ModelMessage::user(
+            "Extract the invoice from: INV-7, $125.00, unpaid",
+        )],
+        GenerationSettings::default(),
+        schema,
+        "Invoice",
+    )
+    .await?;
+
+    check_eq(
+        result.object,
+        Invoice {
+            number: "INV-7".into(),
+            total_cents: 12_500,
+            paid: false,
+        },
+        "parsed invoice",
+    )?;
+    let requests = requests.lock().unwrap();
+    check(
+        requests
+            .first()
+            .is_some_and(|request: &ProviderRequest| request.response_format.is_some()),
+        "the schema to be sent as the response format",
+    )
+}
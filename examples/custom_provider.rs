@@ -75,6 +75,10 @@ impl ModelProvider for EchoProvider {
             tool_calls: vec![],
             finish_reason: Some(FinishReason::Stop),
             thinking: vec![],
+            metadata: Default::default(),
+            refusal: None,
+            images: Vec::new(),
+            response_metadata: None,
         })
     }
 
@@ -97,6 +101,8 @@ impl ModelProvider for EchoProvider {
                 reasoning: None,
                 reasoning_signature: None,
                 reasoning_type: None,
+                image: None,
+                response_metadata: None,
             }));
         }
 
@@ -114,6 +120,8 @@ impl ModelProvider for EchoProvider {
             reasoning: None,
             reasoning_signature: None,
             reasoning_type: None,
+            image: None,
+            response_metadata: None,
         }));
 
         Ok(stream::iter(deltas).boxed())
@@ -180,9 +188,9 @@ async fn main() -> roci::error::Result<()> {
         let provider = registry.create_provider("echo", "echo-v1", &config)?;
         let response = provider
             .generate_text(&ProviderRequest {
-                messages: vec![roci::types::ModelMessage::user(
+                messages: Arc::new(vec![roci::types::ModelMessage::user(
                     "Hello from the extended registry!",
-                )],
+                )]),
                 settings: Default::default(),
                 tools: None,
                 response_format: None,
@@ -192,6 +200,7 @@ async fn main() -> roci::error::Result<()> {
                 payload_callback: None,
                 session_id: None,
                 transport: None,
+                tool_call_ids: None,
             })
             .await?;
 
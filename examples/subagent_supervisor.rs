@@ -47,6 +47,7 @@ async fn main() {
     let base_config = AgentConfig {
         candidates: vec![model],
         system_prompt: Some("You are a helpful assistant.".into()),
+        prompt_sections: Vec::new(),
         tools: Vec::new(),
         tool_visibility_policy: Default::default(),
         dynamic_tool_providers: Vec::new(),
@@ -73,10 +74,18 @@ async fn main() {
         compaction: CompactionSettings::default(),
         session_before_compact: None,
         session_before_tree: None,
+        pre_turn_execution: None,
         pre_tool_use: None,
         post_tool_use: None,
+        plugins: Vec::new(),
+        response_filter: None,
+        filter_tool_results: false,
         user_input_timeout_ms: None,
         context_budget: None,
+        run_budget: None,
+        dry_run: false,
+        dry_run_results: Default::default(),
+        artifacts_dir: None,
         session: None,
         workspace_root: None,
         sandbox_provider: None,
@@ -0,0 +1,126 @@
+//! Blocking wrappers around the generation API (feature: `blocking`).
+//!
+//! For callers without an async runtime, such as scripts and the C ABI in
+//! [`crate::ffi`]. Providers come from [`default_registry`](crate::default_registry);
+//! calls run on a shared multi-threaded Tokio runtime started on first use.
+//! Calling from inside an async runtime is an error rather than a deadlock.
+
+use std::sync::{Arc, OnceLock};
+
+use futures::StreamExt;
+
+use roci_core::config::RociConfig;
+use roci_core::error::RociError;
+use roci_core::generation;
+use roci_core::models::LanguageModel;
+use roci_core::provider::ModelProvider;
+use roci_core::types::{
+    FinishReason, GenerateTextResult, GenerationSettings, ModelMessage, StreamEventType,
+    TextStreamDelta, Usage,
+};
+
+/// Text and usage collected from a [`stream_text`] call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamedText {
+    pub text: String,
+    pub usage: Option<Usage>,
+    pub finish_reason: Option<FinishReason>,
+    /// Whether `on_delta` stopped the stream before it finished.
+    pub stopped: bool,
+}
+
+/// Generate text for a single user prompt. `model` is `provider:model`.
+///
+/// # Errors
+///
+/// Returns [`RociError::InvalidState`] when called from an async runtime,
+/// a model parse or provider construction error, or the provider's error.
+pub fn generate_text(
+    config: &RociConfig,
+    model: &str,
+    prompt: &str,
+) -> Result<GenerateTextResult, RociError> {
+    let provider = provider_for(config, model)?;
+    block_on(generation::generate_text(
+        provider.as_ref(),
+        vec![ModelMessage::user(prompt)],
+        GenerationSettings::default(),
+        &[],
+    ))
+}
+
+/// Stream text for a single user prompt, calling `on_delta` for each delta.
+///
+/// `on_delta` returns `false` to stop the stream early.
+///
+/// # Errors
+///
+/// As [`generate_text`], plus errors raised mid-stream.
+pub fn stream_text(
+    config: &RociConfig,
+    model: &str,
+    prompt: &str,
+    mut on_delta: impl FnMut(&TextStreamDelta) -> bool,
+) -> Result<StreamedText, RociError> {
+    let provider = provider_for(config, model)?;
+    block_on(async move {
+        let mut stream = generation::stream_text(
+            provider,
+            vec![ModelMessage::user(prompt)],
+            GenerationSettings::default(),
+            Vec::new(),
+        )
+        .await?;
+        let mut streamed = StreamedText::default();
+        while let Some(delta) = stream.next().await {
+            let delta = delta?;
+            match delta.event_type {
+                StreamEventType::TextDelta => streamed.text.push_str(&delta.text),
+                StreamEventType::Done => {
+                    streamed.usage = delta.usage.clone().or(streamed.usage.take());
+                    streamed.finish_reason = delta
+                        .finish_reason
+                        .clone()
+                        .or(streamed.finish_reason.take());
+                }
+                _ => {}
+            }
+            if !on_delta(&delta) {
+                streamed.stopped = true;
+                break;
+            }
+        }
+        Ok(streamed)
+    })
+}
+
+fn provider_for(config: &RociConfig, model: &str) -> Result<Arc<dyn ModelProvider>, RociError> {
+    let model: LanguageModel = model.parse()?;
+    let provider = crate::default_registry().create_provider_for(&model, config)?;
+    Ok(Arc::from(provider))
+}
+
+fn block_on<T>(
+    future: impl std::future::Future<Output = Result<T, RociError>>,
+) -> Result<T, RociError> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(RociError::InvalidState(
+            "roci::blocking called from inside an async runtime; use the async API".into(),
+        ));
+    }
+    runtime()?.block_on(future)
+}
+
+fn runtime() -> Result<&'static tokio::runtime::Runtime, RociError> {
+    static RUNTIME: OnceLock<Result<tokio::runtime::Runtime, String>> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .thread_name("roci-blocking")
+                .build()
+                .map_err(|err| err.to_string())
+        })
+        .as_ref()
+        .map_err(|err| RociError::Configuration(format!("failed to start runtime: {err}")))
+}
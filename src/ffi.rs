@@ -0,0 +1,285 @@
+//! C ABI over [`crate::blocking`] (feature: `ffi`).
+//!
+//! Build the shared library from the `roci-ffi` crate; its checked-in
+//! `include/roci.h` declares these functions.
+//!
+//! Ownership: every `char *` roci returns is allocated by roci and must be
+//! released with [`roci_string_free`]. Strings passed in are borrowed for the
+//! duration of the call. A [`RociConfig`] is released with
+//! [`roci_config_free`].
+//!
+//! Results are JSON envelopes: `{"text", "usage", "finish_reason", "error"}`,
+//! where `error` is `null` on success and `{"code", "message"}` otherwise.
+//! Functions return a [`RociStatus`] code; panics are caught at the boundary
+//! and reported as [`RociStatus::Panic`].
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use roci_core::error::RociError;
+use roci_core::types::{FinishReason, Usage};
+
+use crate::blocking;
+
+/// Status code returned by every fallible roci function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RociStatus {
+    Ok = 0,
+    /// A required pointer was null or a string was not UTF-8.
+    InvalidArgument = 1,
+    /// The model string could not be parsed or has no provider.
+    InvalidModel = 2,
+    /// The provider or network failed; see the envelope's `error`.
+    Generation = 3,
+    /// The stream callback asked to stop.
+    Stopped = 4,
+    /// Rust code panicked; the call had no effect beyond what it reported.
+    Panic = 5,
+}
+
+/// Opaque configuration handle.
+pub struct RociConfig {
+    inner: roci_core::config::RociConfig,
+}
+
+/// Called with one NUL-terminated JSON delta (a `TextStreamDelta`). The
+/// string is only valid during the call. Return 0 to continue, anything else
+/// to stop the stream.
+pub type RociStreamCallback =
+    Option<unsafe extern "C" fn(delta_json: *const c_char, user_data: *mut c_void) -> c_int>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Create a configuration from environment variables and `.env`.
+///
+/// Returns null on failure; see [`roci_last_error_message`].
+#[no_mangle]
+pub extern "C" fn roci_config_new_from_env() -> *mut RociConfig {
+    match catch_unwind(roci_core::config::RociConfig::from_env) {
+        Ok(inner) => {
+            clear_last_error();
+            Box::into_raw(Box::new(RociConfig { inner }))
+        }
+        Err(panic) => {
+            set_last_error(panic_message(&panic));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Release a configuration. Null is ignored.
+///
+/// # Safety
+///
+/// `config` must come from [`roci_config_new_from_env`] and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn roci_config_free(config: *mut RociConfig) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+/// Generate text for `prompt` with `model` (`provider:model`).
+///
+/// When `out_json` is not null it receives the result envelope, on success
+/// and on generation errors alike; free it with [`roci_string_free`].
+///
+/// # Safety
+///
+/// `config` must be a live handle; `model` and `prompt` NUL-terminated
+/// strings; `out_json` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn roci_generate(
+    config: *const RociConfig,
+    model: *const c_char,
+    prompt: *const c_char,
+    out_json: *mut *mut c_char,
+) -> RociStatus {
+    guard(out_json, || {
+        let (config, model, prompt) = inputs(config, model, prompt)?;
+        blocking::generate_text(&config.inner, model, prompt)
+            .map(|result| success(&result.text, Some(&result.usage), result.finish_reason))
+            .map_err(generation_error)
+    })
+}
+
+/// Stream text for `prompt`, calling `callback` with each delta.
+///
+/// `out_json` receives the envelope of the collected text, as for
+/// [`roci_generate`]. A callback that stops the stream yields
+/// [`RociStatus::Stopped`] with the text received so far.
+///
+/// # Safety
+///
+/// As [`roci_generate`]; `callback` must be safe to call with `user_data`
+/// from the calling thread.
+#[no_mangle]
+pub unsafe extern "C" fn roci_stream_generate(
+    config: *const RociConfig,
+    model: *const c_char,
+    prompt: *const c_char,
+    callback: RociStreamCallback,
+    user_data: *mut c_void,
+    out_json: *mut *mut c_char,
+) -> RociStatus {
+    guard(out_json, || {
+        let (config, model, prompt) = inputs(config, model, prompt)?;
+        let Some(callback) = callback else {
+            return Err(failure(RociStatus::InvalidArgument, "callback is null"));
+        };
+        let streamed = blocking::stream_text(&config.inner, model, prompt, |delta| {
+            let json = serde_json::to_string(delta).unwrap_or_default();
+            let json = CString::new(json).unwrap_or_default();
+            // SAFETY: the caller guarantees `callback` accepts `user_data`.
+            unsafe { callback(json.as_ptr(), user_data) == 0 }
+        })
+        .map_err(generation_error)?;
+        let envelope = success(
+            &streamed.text,
+            streamed.usage.as_ref(),
+            streamed.finish_reason,
+        );
+        if streamed.stopped {
+            return Err((RociStatus::Stopped, envelope));
+        }
+        Ok(envelope)
+    })
+}
+
+/// Message of the last error on this thread, or null if the last call
+/// succeeded. Free the result with [`roci_string_free`].
+#[no_mangle]
+pub extern "C" fn roci_last_error_message() -> *mut c_char {
+    LAST_ERROR
+        .with(|last| last.borrow().clone())
+        .map(into_c_string)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Release a string returned by roci. Null is ignored.
+///
+/// # Safety
+///
+/// `value` must come from roci and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn roci_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Outcome of a call body: the envelope, with a status on failure.
+type CallResult = Result<serde_json::Value, (RociStatus, serde_json::Value)>;
+
+/// Run `body`, catching panics, and publish its envelope and error.
+fn guard(out_json: *mut *mut c_char, body: impl FnOnce() -> CallResult) -> RociStatus {
+    let (status, envelope) = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(envelope)) => (RociStatus::Ok, envelope),
+        Ok(Err((status, envelope))) => (status, envelope),
+        Err(panic) => failure(RociStatus::Panic, &panic_message(&panic)),
+    };
+    match envelope["error"]["message"].as_str() {
+        Some(message) if status != RociStatus::Stopped => set_last_error(message.to_string()),
+        _ => clear_last_error(),
+    }
+    if !out_json.is_null() {
+        // SAFETY: the caller guarantees `out_json` is writable when non-null.
+        unsafe { *out_json = into_c_string(envelope.to_string()) };
+    }
+    status
+}
+
+unsafe fn inputs<'a>(
+    config: *const RociConfig,
+    model: *const c_char,
+    prompt: *const c_char,
+) -> Result<(&'a RociConfig, &'a str, &'a str), (RociStatus, serde_json::Value)> {
+    let config = config
+        .as_ref()
+        .ok_or_else(|| failure(RociStatus::InvalidArgument, "config is null"))?;
+    let model = c_str(model, "model")?;
+    if let Err(error) = model.parse::<roci_core::models::LanguageModel>() {
+        return Err(failure(RociStatus::InvalidModel, &error.to_string()));
+    }
+    Ok((config, model, c_str(prompt, "prompt")?))
+}
+
+unsafe fn c_str<'a>(
+    value: *const c_char,
+    name: &str,
+) -> Result<&'a str, (RociStatus, serde_json::Value)> {
+    if value.is_null() {
+        return Err(failure(
+            RociStatus::InvalidArgument,
+            &format!("{name} is null"),
+        ));
+    }
+    CStr::from_ptr(value).to_str().map_err(|_| {
+        failure(
+            RociStatus::InvalidArgument,
+            &format!("{name} is not valid UTF-8"),
+        )
+    })
+}
+
+fn success(
+    text: &str,
+    usage: Option<&Usage>,
+    finish_reason: Option<FinishReason>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "text": text,
+        "usage": usage,
+        "finish_reason": finish_reason,
+        "error": null,
+    })
+}
+
+fn failure(status: RociStatus, message: &str) -> (RociStatus, serde_json::Value) {
+    (
+        status,
+        serde_json::json!({
+            "text": null,
+            "usage": null,
+            "finish_reason": null,
+            "error": { "code": status as i32, "message": message },
+        }),
+    )
+}
+
+fn generation_error(error: RociError) -> (RociStatus, serde_json::Value) {
+    let status = match error {
+        RociError::ModelNotFound(_) => RociStatus::InvalidModel,
+        _ => RociStatus::Generation,
+    };
+    failure(status, &error.to_string())
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    // Interior NULs cannot cross the boundary; replace rather than fail.
+    CString::new(value.replace('\0', "\u{FFFD}"))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    let detail = panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    format!("roci panicked: {detail}")
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
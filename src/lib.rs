@@ -4,12 +4,19 @@
 //! (built-in transports + OAuth flows) with default wiring.
 //!
 //! For explicit control, depend on `roci-core` + `roci-providers` directly.
+//! Either way the behavior is the same: this crate has no implementations of
+//! its own beyond the default wiring and the optional `blocking`/`ffi` layers.
 
 pub use roci_core::*;
 pub use roci_providers;
 
 use std::sync::Arc;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 /// Create a default provider registry with all enabled built-in providers.
 pub fn default_registry() -> roci_core::provider::ProviderRegistry {
     let mut registry = roci_core::provider::ProviderRegistry::new();
@@ -0,0 +1,42 @@
+//! Runs every cookbook recipe (`examples/cookbook`) as a test.
+
+#[path = "../examples/cookbook/harness.rs"]
+mod harness;
+#[path = "../examples/cookbook/recipes/mod.rs"]
+mod recipes;
+
+fn assert_recipe(outcome: harness::Outcome) {
+    if let Err(failure) = outcome {
+        panic!("{failure}");
+    }
+}
+
+#[tokio::test]
+async fn agent_loop() {
+    assert_recipe(recipes::agent_loop::run().await);
+}
+
+#[tokio::test]
+async fn structured_output() {
+    assert_recipe(recipes::structured_output::run().await);
+}
+
+#[tokio::test]
+async fn streaming() {
+    assert_recipe(recipes::streaming::run().await);
+}
+
+#[tokio::test]
+async fn mcp_aggregation() {
+    assert_recipe(recipes::mcp_aggregation::run().await);
+}
+
+#[tokio::test]
+async fn session() {
+    assert_recipe(recipes::session::run().await);
+}
+
+#[tokio::test]
+async fn auth_store() {
+    assert_recipe(recipes::auth_store::run().await);
+}
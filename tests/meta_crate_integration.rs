@@ -49,6 +49,45 @@ fn prelude_types_are_accessible_via_roci() {
     let _: fn() -> roci::prelude::RociConfig = roci::prelude::RociConfig::new;
 }
 
+// ---------------------------------------------------------------------------
+// Single source of truth
+// ---------------------------------------------------------------------------
+
+#[test]
+fn roci_paths_are_the_workspace_crate_types() {
+    // Compile-time check: `roci` re-exports roci-core rather than carrying
+    // its own copies, so the types are interchangeable.
+    let config: roci_core::config::RociConfig = roci::config::RociConfig::new();
+    let _: roci::config::RociConfig = config;
+    let error: roci_core::error::RociError = roci::error::RociError::ModelNotFound("m".into());
+    let _: roci::error::RociError = error;
+    let _: fn() -> roci_core::provider::ProviderRegistry = roci::default_registry;
+}
+
+#[test]
+fn openai_key_does_not_fall_back_to_codex_token_via_roci() {
+    let dir = TempDir::new().unwrap();
+    let store = FileTokenStore::new(TokenStoreConfig::new(dir.path().to_path_buf()));
+    let token = roci::auth::Token {
+        access_token: "oauth-access-token".to_string(),
+        refresh_token: None,
+        id_token: None,
+        expires_at: None,
+        last_refresh: None,
+        scopes: None,
+        account_id: None,
+    };
+    roci::auth::TokenStore::save(&store, "openai-codex", "default", &token).unwrap();
+
+    let config = roci::config::RociConfig::new().with_token_store(Some(Arc::new(store)));
+
+    assert_eq!(config.get_api_key("openai"), None);
+    assert_eq!(
+        config.get_api_key("codex"),
+        Some("oauth-access-token".to_string())
+    );
+}
+
 // ---------------------------------------------------------------------------
 // default_registry()
 // ---------------------------------------------------------------------------
@@ -221,3 +260,10 @@ fn groq_feature_enables_groq_in_registry() {
     let registry = roci::default_registry();
     assert!(registry.has_provider("groq"));
 }
+
+#[cfg(feature = "bedrock")]
+#[test]
+fn bedrock_feature_enables_bedrock_in_registry() {
+    let registry = roci::default_registry();
+    assert!(registry.has_provider("bedrock"));
+}